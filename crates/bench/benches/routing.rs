@@ -0,0 +1,135 @@
+//! Routing decision latency and middleware chain overhead
+//!
+//! Compare against a saved baseline to catch regressions:
+//! `cargo bench -p gate-bench -- --save-baseline main` on the base branch,
+//! then `cargo bench -p gate-bench -- --baseline main` on the change.
+
+use async_trait::async_trait;
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use futures::StreamExt;
+use gate_core::access::SubjectIdentity;
+use gate_core::router::index::SinkIndex;
+use gate_core::router::middleware::{Middleware, Next};
+use gate_core::router::registry::SinkRegistry;
+use gate_core::router::routing::Router;
+use gate_core::router::service::one_shot_stream;
+use gate_core::router::sink::{RequestContext, RouterIdentityContext, Sink};
+use gate_core::router::sinks::mock::MockSink;
+use gate_core::router::types::{
+    Protocol, RequestCapabilities, RequestDescriptor, RequestStream, ResponseStream,
+};
+use gate_core::tests::state::InMemoryBackend;
+use gate_core::tracing::CorrelationId;
+use serde_json::json;
+use std::hint::black_box;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+
+/// Middleware that only forwards, isolating the cost of the chain itself
+struct Passthrough;
+
+#[async_trait]
+impl Middleware for Passthrough {
+    async fn process(
+        &self,
+        _ctx: &mut RequestContext,
+        request: RequestStream,
+        next: Next,
+    ) -> gate_core::Result<ResponseStream> {
+        next(request).await
+    }
+}
+
+async fn router(sinks: usize, middleware: usize) -> Router {
+    let registry = Arc::new(SinkRegistry::new());
+    let index = Arc::new(SinkIndex::new());
+    for i in 0..sinks {
+        let id = format!("self://mock-{i}");
+        let sink = Arc::new(MockSink::success(&id));
+        index
+            .set_snapshot(id.clone(), sink.describe().await, sink.probe().await)
+            .await;
+        registry.register(id, sink).await;
+    }
+    let mut builder = Router::builder()
+        .state_backend(Arc::new(InMemoryBackend::default()))
+        .sink_registry(registry)
+        .sink_index(index);
+    for _ in 0..middleware {
+        builder = builder.middleware(Arc::new(Passthrough));
+    }
+    builder.build()
+}
+
+fn ctx() -> RequestContext {
+    RequestContext {
+        identity: SubjectIdentity::new("bench", "bench", RouterIdentityContext::default()),
+        correlation_id: CorrelationId::new(),
+        headers: Default::default(),
+        query: None,
+        trace_id: None,
+        metadata: Default::default(),
+        response: Default::default(),
+        extensions: Default::default(),
+    }
+}
+
+fn descriptor() -> RequestDescriptor {
+    RequestDescriptor {
+        model: "bench-model".into(),
+        protocol: Protocol::OpenAIChat,
+        capabilities: RequestCapabilities {
+            needs_tools: false,
+            needs_vision: false,
+            needs_streaming: true,
+            max_tokens: Some(64),
+            modalities: vec!["text".into()],
+        },
+        context_length_hint: Some(100),
+    }
+}
+
+fn request() -> RequestStream {
+    one_shot_stream(
+        Protocol::OpenAIChat,
+        json!({
+            "model": "bench-model",
+            "messages": [{"role": "user", "content": "hello"}],
+            "stream": true
+        }),
+    )
+}
+
+fn route_decision(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let mut group = c.benchmark_group("route_decision");
+    for sinks in [1, 10, 100, 1000] {
+        let router = rt.block_on(router(sinks, 0));
+        let (ctx, desc) = (ctx(), descriptor());
+        group.bench_with_input(BenchmarkId::from_parameter(sinks), &sinks, |b, _| {
+            b.to_async(&rt)
+                .iter(|| async { black_box(router.route(&ctx, &desc).await.unwrap()) });
+        });
+    }
+    group.finish();
+}
+
+fn middleware_chain(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let mut group = c.benchmark_group("middleware_chain");
+    for depth in [0, 1, 4, 16] {
+        let router = rt.block_on(router(1, depth));
+        let (ctx, desc) = (ctx(), descriptor());
+        group.bench_with_input(BenchmarkId::from_parameter(depth), &depth, |b, _| {
+            b.to_async(&rt).iter(|| async {
+                let plan = router.route(&ctx, &desc).await.unwrap();
+                let response = router.execute(plan, request()).await.unwrap();
+                black_box(response.collect::<Vec<_>>().await)
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, route_decision, middleware_chain);
+criterion_main!(benches);
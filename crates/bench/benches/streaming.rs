@@ -0,0 +1,56 @@
+//! SSE fan-out throughput with many concurrent streams
+//!
+//! Each stream is converted to an axum SSE response and its body drained, as
+//! the HTTP layer does for every streaming client.
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use gate_core::router::types::{ResponseChunk, ResponseStream, StopReason};
+use gate_http::sinks::response_converter::response_stream_to_axum;
+use http_body_util::BodyExt;
+use serde_json::json;
+use std::hint::black_box;
+use tokio::runtime::Runtime;
+
+const CHUNKS_PER_STREAM: usize = 50;
+
+fn response_stream() -> ResponseStream {
+    let mut chunks: Vec<gate_core::Result<ResponseChunk>> = (0..CHUNKS_PER_STREAM)
+        .map(|i| {
+            Ok(ResponseChunk::Content(json!({
+                "object": "chat.completion.chunk",
+                "choices": [{"index": 0, "delta": {"content": format!("token {i} ")}}]
+            })))
+        })
+        .collect();
+    chunks.push(Ok(ResponseChunk::Stop {
+        reason: StopReason::Complete,
+        error: None,
+        cost: None,
+    }));
+    Box::pin(futures::stream::iter(chunks))
+}
+
+async fn drain_one() -> usize {
+    let response = response_stream_to_axum(response_stream()).await.unwrap();
+    let body = response.into_body().collect().await.unwrap();
+    body.to_bytes().len()
+}
+
+fn sse_fanout(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let mut group = c.benchmark_group("sse_fanout");
+    group.sample_size(10);
+    for streams in [100, 1000, 5000] {
+        group.throughput(Throughput::Elements((streams * CHUNKS_PER_STREAM) as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(streams), &streams, |b, &n| {
+            b.to_async(&rt).iter(|| async move {
+                let tasks = (0..n).map(|_| tokio::spawn(drain_one()));
+                black_box(futures::future::join_all(tasks).await)
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, sse_fanout);
+criterion_main!(benches);
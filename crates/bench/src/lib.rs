@@ -0,0 +1,9 @@
+//! Benchmarks and load generation for Gate
+//!
+//! The criterion suites under `benches/` measure the routing hot path in
+//! process: `route()` decisions, middleware chain overhead and SSE fan-out.
+//! [`load`] drives a running daemon over HTTP and backs `gate bench`.
+
+pub mod load;
+
+pub use load::{LoadConfig, LoadReport, Percentiles};
@@ -0,0 +1,197 @@
+//! Synthetic load against a live gateway
+//!
+//! Sends chat completion requests from a fixed number of concurrent workers
+//! and reports latency percentiles, time to first byte and throughput.
+
+use futures::StreamExt;
+use serde::Serialize;
+use serde_json::json;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// What to send and how hard
+#[derive(Debug, Clone)]
+pub struct LoadConfig {
+    /// Base URL of the gateway, e.g. `http://localhost:31145`
+    pub base_url: String,
+    pub model: String,
+    pub api_key: Option<String>,
+    /// Number of requests in flight at once
+    pub concurrency: usize,
+    /// Total number of requests to send
+    pub requests: usize,
+    pub stream: bool,
+    pub prompt: String,
+}
+
+impl Default for LoadConfig {
+    fn default() -> Self {
+        Self {
+            base_url: "http://localhost:31145".to_string(),
+            model: "mock".to_string(),
+            api_key: None,
+            concurrency: 16,
+            requests: 200,
+            stream: true,
+            prompt: "Say hello".to_string(),
+        }
+    }
+}
+
+/// Latency distribution in milliseconds
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Percentiles {
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+impl Percentiles {
+    fn from_samples(mut samples: Vec<Duration>) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        samples.sort();
+        let at = |p: f64| {
+            let index = ((samples.len() - 1) as f64 * p).round() as usize;
+            samples[index].as_secs_f64() * 1000.0
+        };
+        Self {
+            p50_ms: at(0.50),
+            p95_ms: at(0.95),
+            p99_ms: at(0.99),
+            max_ms: at(1.0),
+        }
+    }
+}
+
+/// Outcome of a load run
+#[derive(Debug, Clone, Serialize)]
+pub struct LoadReport {
+    pub requests: usize,
+    pub failures: usize,
+    pub elapsed_ms: f64,
+    pub requests_per_second: f64,
+    /// Full request latency, including reading the whole body
+    pub latency: Percentiles,
+    /// Time until the first body bytes arrived
+    pub first_byte: Percentiles,
+}
+
+struct Sample {
+    ok: bool,
+    latency: Duration,
+    first_byte: Duration,
+}
+
+/// Run `config` to completion
+pub async fn run(config: &LoadConfig) -> Result<LoadReport, reqwest::Error> {
+    let client = reqwest::Client::builder()
+        .pool_max_idle_per_host(config.concurrency)
+        .build()?;
+    let url = format!(
+        "{}/v1/chat/completions",
+        config.base_url.trim_end_matches('/')
+    );
+    let body = Arc::new(json!({
+        "model": config.model,
+        "messages": [{"role": "user", "content": config.prompt}],
+        "stream": config.stream,
+    }));
+    let issued = Arc::new(AtomicUsize::new(0));
+
+    let started = Instant::now();
+    let workers = (0..config.concurrency.max(1)).map(|_| {
+        let client = client.clone();
+        let url = url.clone();
+        let body = body.clone();
+        let issued = issued.clone();
+        let api_key = config.api_key.clone();
+        let total = config.requests;
+        tokio::spawn(async move {
+            let mut samples = Vec::new();
+            while issued.fetch_add(1, Ordering::Relaxed) < total {
+                samples.push(send(&client, &url, &body, api_key.as_deref()).await);
+            }
+            samples
+        })
+    });
+    let mut samples = Vec::with_capacity(config.requests);
+    for worker in futures::future::join_all(workers).await {
+        samples.extend(worker.unwrap_or_default());
+    }
+    let elapsed = started.elapsed();
+
+    let (ok, failed): (Vec<_>, Vec<_>) = samples.into_iter().partition(|s| s.ok);
+    Ok(LoadReport {
+        requests: ok.len() + failed.len(),
+        failures: failed.len(),
+        elapsed_ms: elapsed.as_secs_f64() * 1000.0,
+        requests_per_second: ok.len() as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+        latency: Percentiles::from_samples(ok.iter().map(|s| s.latency).collect()),
+        first_byte: Percentiles::from_samples(ok.iter().map(|s| s.first_byte).collect()),
+    })
+}
+
+async fn send(
+    client: &reqwest::Client,
+    url: &str,
+    body: &serde_json::Value,
+    api_key: Option<&str>,
+) -> Sample {
+    let started = Instant::now();
+    let mut request = client.post(url).json(body);
+    if let Some(key) = api_key {
+        request = request.bearer_auth(key);
+    }
+    let failed = |started: Instant| Sample {
+        ok: false,
+        latency: started.elapsed(),
+        first_byte: started.elapsed(),
+    };
+
+    let Ok(response) = request.send().await else {
+        return failed(started);
+    };
+    if !response.status().is_success() {
+        return failed(started);
+    }
+    let mut body = response.bytes_stream();
+    let mut first_byte = None;
+    while let Some(chunk) = body.next().await {
+        if chunk.is_err() {
+            return failed(started);
+        }
+        first_byte.get_or_insert_with(|| started.elapsed());
+    }
+    let latency = started.elapsed();
+    Sample {
+        ok: true,
+        latency,
+        first_byte: first_byte.unwrap_or(latency),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentiles_pick_nearest_rank() {
+        let samples = (1..=100).map(Duration::from_millis).collect();
+        let p = Percentiles::from_samples(samples);
+        assert_eq!(p.p50_ms.round(), 51.0);
+        assert_eq!(p.p99_ms.round(), 99.0);
+        assert_eq!(p.max_ms.round(), 100.0);
+    }
+
+    #[test]
+    fn test_percentiles_of_nothing_are_zero() {
+        assert_eq!(
+            Percentiles::from_samples(Vec::new()),
+            Percentiles::default()
+        );
+    }
+}
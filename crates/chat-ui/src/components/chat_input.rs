@@ -317,7 +317,7 @@ pub fn chat_input(props: &ChatInputProps) -> Html {
         <div class={FLEX_COL}>
             // Error message
             if let Some(error) = &*error_message {
-                <div class={combine_styles(&["mx-4 mb-2 p-2 rounded-md bg-red-50 dark:bg-red-900/20", ERROR_TEXT, "text-sm"])}>
+                <div class={combine_styles(&["mx-4 mb-2 p-2 rounded-md bg-red-50 dark:bg-red-900/20", ERROR_TEXT, "text-sm"])} role="alert">
                     {error}
                 </div>
             }
@@ -350,12 +350,15 @@ pub fn chat_input(props: &ChatInputProps) -> Html {
                         multiple={true}
                         onchange={handle_file_select}
                         class="hidden"
+                        tabindex="-1"
+                        aria-hidden="true"
                     />
                 }
 
                 // Attachment button
                 if show_attachments {
                     <button
+                        type="button"
                         onclick={handle_attachment_click}
                         disabled={props.disabled || *is_loading}
                         class={combine_styles(&[
@@ -366,6 +369,7 @@ pub fn chat_input(props: &ChatInputProps) -> Html {
                             "disabled:opacity-50 disabled:cursor-not-allowed"
                         ])}
                         title="Attach files"
+                        aria-label="Attach files"
                     >
                         <svg class="w-5 h-5" fill="none" stroke="currentColor" viewBox="0 0 24 24">
                             <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2"
@@ -379,7 +383,8 @@ pub fn chat_input(props: &ChatInputProps) -> Html {
                     <textarea
                         ref={text_area_ref}
                         class={combine_styles(&["w-full min-h-[40px] max-h-[200px] px-3 py-2 border", INPUT_COLORS, "rounded-lg text-sm leading-relaxed resize-none outline-none overflow-hidden", TRANSITION_COLORS, CARD_BG, PRIMARY_TEXT, "focus:border-gray-400 focus:ring-1 focus:ring-gray-400 dark:focus:border-gray-500 dark:focus:ring-gray-500 disabled:bg-gray-100 disabled:cursor-not-allowed placeholder:text-gray-400 dark:placeholder:text-gray-600"])}
-                        placeholder={placeholder}
+                        placeholder={placeholder.clone()}
+                        aria-label={placeholder}
                         value={(*input_value).clone()}
                         oninput={handle_input}
                         onkeydown={handle_keydown}
@@ -418,11 +423,13 @@ fn attachment_preview(props: &AttachmentPreviewProps) -> Html {
                         alt={attachment.name.clone()}
                         class="w-full h-full object-cover"
                     />
-                    <div class="absolute inset-0 bg-black/50 opacity-0 group-hover:opacity-100 transition-opacity flex items-center justify-center">
+                    <div class="absolute inset-0 bg-black/50 opacity-0 group-hover:opacity-100 group-focus-within:opacity-100 transition-opacity flex items-center justify-center">
                         <button
                             onclick={props.on_remove.clone()}
                             class="p-1 bg-red-500 text-white rounded-full hover:bg-red-600"
+                            type="button"
                             title="Remove attachment"
+                            aria-label={format!("Remove {}", attachment.name)}
                         >
                             <svg class="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24">
                                 <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M6 18L18 6M6 6l12 12" />
@@ -447,7 +454,9 @@ fn attachment_preview(props: &AttachmentPreviewProps) -> Html {
                     <button
                         onclick={props.on_remove.clone()}
                         class="ml-2 p-1 text-gray-500 hover:text-red-500 dark:text-gray-400 dark:hover:text-red-400"
+                        type="button"
                         title="Remove attachment"
+                        aria-label={format!("Remove {}", attachment.name)}
                     >
                         <svg class="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24">
                             <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M6 18L18 6M6 6l12 12" />
@@ -474,6 +483,7 @@ fn send_button(props: &SendButtonProps) -> Html {
 
     html! {
         <button
+            type="button"
             class={combine_styles(&[FLEX_CENTER, "justify-center w-[42px] h-[42px] p-0 rounded-lg bg-blue-500 dark:bg-blue-600 text-white cursor-pointer transition-all duration-200 flex-shrink-0 hover:bg-blue-600 dark:hover:bg-blue-700 disabled:bg-gray-200 disabled:cursor-not-allowed disabled:text-gray-400"])}
             onclick={handle_click}
             disabled={props.disabled}
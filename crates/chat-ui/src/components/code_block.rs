@@ -0,0 +1,76 @@
+use crate::styles::{PRIMARY_BORDER, TERTIARY_TEXT};
+use crate::utils::highlight::highlight;
+use gloo_timers::callback::Timeout;
+use wasm_bindgen_futures::JsFuture;
+use yew::prelude::*;
+
+const COPIED_RESET_MS: u32 = 2000;
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct CodeBlockProps {
+    #[prop_or_default]
+    pub lang: String,
+    pub code: String,
+    /// False while the closing fence has not streamed in yet
+    #[prop_or(true)]
+    pub complete: bool,
+}
+
+#[function_component(CodeBlock)]
+pub fn code_block(props: &CodeBlockProps) -> Html {
+    let copied = use_state(|| false);
+
+    let on_copy = {
+        let code = props.code.clone();
+        let copied = copied.clone();
+        Callback::from(move |_: MouseEvent| {
+            let Some(window) = web_sys::window() else {
+                return;
+            };
+            let promise = window.navigator().clipboard().write_text(&code);
+            let copied = copied.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                if JsFuture::from(promise).await.is_ok() {
+                    copied.set(true);
+                    let copied = copied.clone();
+                    Timeout::new(COPIED_RESET_MS, move || copied.set(false)).forget();
+                } else {
+                    web_sys::console::error_1(&"Failed to copy code to clipboard".into());
+                }
+            });
+        })
+    };
+
+    let label = if props.lang.is_empty() {
+        "text"
+    } else {
+        props.lang.as_str()
+    };
+
+    html! {
+        <div class={classes!("my-2", "rounded", "border", "overflow-hidden", PRIMARY_BORDER)}>
+            <div class={classes!("flex", "justify-between", "items-center", "px-3", "py-1", "text-xs", "bg-gray-100", "dark:bg-gray-900", TERTIARY_TEXT)}>
+                <span class="font-mono">{label}</span>
+                if props.complete {
+                    <button
+                        type="button"
+                        class="hover:text-gray-900 dark:hover:text-gray-100"
+                        onclick={on_copy}
+                        aria-label="Copy code"
+                    >
+                        {if *copied { "Copied" } else { "Copy" }}
+                    </button>
+                } else {
+                    <span class="animate-pulse motion-reduce:animate-none" aria-hidden="true">{"…"}</span>
+                }
+            </div>
+            <pre class="bg-gray-50 dark:bg-gray-800 p-3 font-mono text-xs overflow-x-auto">
+                <code>
+                    {for highlight(&props.lang, &props.code).into_iter().map(|(kind, text)| html! {
+                        <span class={kind.class()}>{text}</span>
+                    })}
+                </code>
+            </pre>
+        </div>
+    }
+}
@@ -5,8 +5,9 @@ use crate::styles::{
 };
 use crate::types::ChatMessage;
 use crate::utils::markdown::render_markdown;
+use crate::utils::reasoning::{is_reasoning_key, metadata_reasoning, split_think_tags};
 use serde_json::Value;
-use web_sys::MouseEvent;
+use web_sys::{HtmlElement, KeyboardEvent, MouseEvent};
 use yew::prelude::*;
 
 #[derive(Properties, Clone, PartialEq)]
@@ -62,6 +63,31 @@ pub fn message(props: &MessageProps) -> Html {
     // Extract finish_reason from metadata
     let finish_reason = message.finish_reason();
 
+    // Reasoning arrives either out of band or as a leading <think> block
+    let is_streaming = message.is_streaming();
+    let think_split = match &message.content {
+        Some(Value::String(text)) => Some(split_think_tags(text)),
+        _ => None,
+    };
+    let reasoning = metadata_reasoning(message)
+        .map(|text| {
+            (
+                text,
+                !is_streaming || message.get_text_content().is_some_and(|t| !t.is_empty()),
+            )
+        })
+        .or_else(|| {
+            think_split
+                .as_ref()
+                .and_then(|split| split.reasoning.map(|text| (text, split.reasoning_complete)))
+        });
+    let content = match &think_split {
+        Some(split) if split.reasoning.is_some() || !split.reasoning_complete => {
+            Some(Value::String(split.answer.to_string()))
+        }
+        _ => message.content.clone(),
+    };
+
     let message_class = match role_class {
         "user" => USER_BUBBLE_COLORS,
         "assistant" => ASSISTANT_BUBBLE_COLORS,
@@ -71,7 +97,13 @@ pub fn message(props: &MessageProps) -> Html {
     };
 
     html! {
-        <div class={classes!("flex", "flex-col", "gap-2", "p-4", "rounded-lg", "shadow", message_class, class.clone())}>
+        <div
+            class={classes!("flex", "flex-col", "gap-2", "p-4", "rounded-lg", "shadow", "focus:outline-none", "focus:ring-2", "focus:ring-blue-500", message_class, class.clone())}
+            role="article"
+            aria-label={role_label.to_string()}
+            data-chat-message="true"
+            tabindex={if *is_last { "0" } else { "-1" }}
+        >
             <div class="flex gap-2 items-center">
                 <span class="font-semibold text-sm text-gray-600 dark:text-gray-400">{role_label}</span>
                 if message.name.is_some() {
@@ -80,7 +112,11 @@ pub fn message(props: &MessageProps) -> Html {
             </div>
 
             <div class="leading-relaxed break-words">
-                {render_message_content(&message.content, message.is_streaming(), *is_last)}
+                if let Some((text, complete)) = reasoning {
+                    {render_reasoning(text, complete)}
+                }
+
+                {render_message_content(&content, is_streaming, *is_last)}
 
                 if let Some(refusal_text) = refusal {
                     <div class="bg-red-50 dark:bg-red-900 text-red-700 dark:text-red-300 px-3 py-2 rounded mt-2">
@@ -91,16 +127,21 @@ pub fn message(props: &MessageProps) -> Html {
 
                 if let Some(tool_calls) = &message.tool_calls {
                     <div class="mt-2">
-                        <button class="bg-transparent border-0 cursor-pointer text-sm text-blue-600 dark:text-blue-400 px-2 py-1 flex items-center gap-1 hover:bg-blue-50 dark:hover:bg-blue-900 rounded" onclick={toggle_tools}>
-                            <span class="text-xs w-3">{if *expanded_tools { "▼" } else { "▶" }}</span>
-                            <span class="text-base">{"🔧"}</span>
+                        <button
+                            type="button"
+                            class="bg-transparent border-0 cursor-pointer text-sm text-blue-600 dark:text-blue-400 px-2 py-1 flex items-center gap-1 hover:bg-blue-50 dark:hover:bg-blue-900 rounded"
+                            onclick={toggle_tools}
+                            aria-expanded={expanded_tools.to_string()}
+                        >
+                            <span class="text-xs w-3" aria-hidden="true">{if *expanded_tools { "▼" } else { "▶" }}</span>
+                            <span class="text-base" aria-hidden="true">{"🔧"}</span>
                             {format!(" {} tool call{}", tool_calls.len(), if tool_calls.len() == 1 { "" } else { "s" })}
                         </button>
 
                         if *expanded_tools {
                             <div class="mt-2 pl-5">
                                 {for tool_calls.iter().map(|tool_call| {
-                                    render_tool_call(tool_call)
+                                    render_tool_call(tool_call, is_streaming)
                                 })}
                             </div>
                         }
@@ -121,8 +162,25 @@ pub fn message(props: &MessageProps) -> Html {
     }
 }
 
+fn render_reasoning(text: &str, complete: bool) -> Html {
+    html! {
+        <details
+            open={!complete}
+            class="mb-2 rounded border border-gray-200 dark:border-gray-600 bg-gray-50 dark:bg-gray-800"
+        >
+            <summary class="cursor-pointer px-3 py-1 text-xs font-semibold text-gray-600 dark:text-gray-400">
+                {if complete { "Reasoning" } else { "Reasoning…" }}
+            </summary>
+            <div class="px-3 pb-2 text-sm text-gray-600 dark:text-gray-400 whitespace-pre-wrap">
+                {text.to_string()}
+            </div>
+        </details>
+    }
+}
+
 fn render_message_content(content: &Option<Value>, is_streaming: bool, is_last: bool) -> Html {
     match content {
+        Some(Value::String(text)) if text.is_empty() && is_streaming => html! {},
         None => html! { <span class="text-gray-400 dark:text-gray-400 italic">{"(empty)"}</span> },
         Some(Value::String(text)) => {
             if is_streaming && is_last {
@@ -137,7 +195,13 @@ fn render_message_content(content: &Option<Value>, is_streaming: bool, is_last:
             html! {
                 <>
                     {for parts.iter().map(|part| {
-                        if let Some(text) = part.get("text").and_then(|t| t.as_str()) {
+                        let part_type = part.get("type").and_then(|t| t.as_str());
+                        if part_type == Some("thinking") {
+                            let text = part.get("thinking").and_then(|t| t.as_str()).unwrap_or_default();
+                            render_reasoning(text, !is_streaming)
+                        } else if part_type == Some("tool_use") {
+                            render_tool_call(part, is_streaming)
+                        } else if let Some(text) = part.get("text").and_then(|t| t.as_str()) {
                             if is_streaming && is_last {
                                 html! {
                                     <StreamingText text={text.to_string()} streaming={true} initial_delay_ms={20} final_delay_ms={4} />
@@ -214,7 +278,11 @@ fn render_message_content(content: &Option<Value>, is_streaming: bool, is_last:
     }
 }
 
-fn render_tool_call(tool_call: &Value) -> Html {
+/// Render an OpenAI `tool_calls` entry or an Anthropic `tool_use` block.
+///
+/// While streaming, arguments are usually incomplete JSON; they are shown
+/// verbatim until they parse so the block grows without jumping around.
+fn render_tool_call(tool_call: &Value, is_streaming: bool) -> Html {
     let id = tool_call
         .get("id")
         .and_then(|v| v.as_str())
@@ -223,28 +291,30 @@ fn render_tool_call(tool_call: &Value) -> Html {
     let function_name = tool_call
         .get("function")
         .and_then(|f| f.get("name"))
+        .or_else(|| tool_call.get("name"))
         .and_then(|n| n.as_str())
         .unwrap_or("unknown");
 
-    let args = tool_call
+    let raw_args = tool_call
         .get("function")
         .and_then(|f| f.get("arguments"))
-        .and_then(|a| a.as_str())
-        .and_then(|args_str| serde_json::from_str::<Value>(args_str).ok())
+        .and_then(|a| a.as_str());
+    let parsed_args = match raw_args {
+        Some(args_str) => serde_json::from_str::<Value>(args_str).ok(),
+        None => tool_call.get("input").cloned(),
+    };
+    let is_partial = parsed_args.is_none() && is_streaming;
+    let args = parsed_args
         .and_then(|v| serde_json::to_string_pretty(&v).ok())
-        .unwrap_or_else(|| {
-            tool_call
-                .get("function")
-                .and_then(|f| f.get("arguments"))
-                .and_then(|a| a.as_str())
-                .unwrap_or("")
-                .to_string()
-        });
+        .unwrap_or_else(|| raw_args.unwrap_or("").to_string());
 
     html! {
         <div class="bg-gray-100 dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded p-3 mb-2">
-            <div class="font-semibold text-gray-700 dark:text-gray-300 mb-2">
+            <div class="font-semibold text-gray-700 dark:text-gray-300 mb-2 flex items-center gap-2">
                 {format!("{} ({})", function_name, id)}
+                if is_partial {
+                    <span class="text-xs font-normal text-gray-500 animate-pulse">{"receiving arguments…"}</span>
+                }
             </div>
             <pre class="bg-gray-50 dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded p-2 font-mono text-xs overflow-x-auto whitespace-pre-wrap">
                 {args}
@@ -258,7 +328,7 @@ fn render_extra_metadata(metadata: &std::collections::HashMap<String, Value>) ->
     let skip_fields = ["finish_reason", "refusal", "is_streaming"];
     let extra_fields: Vec<_> = metadata
         .iter()
-        .filter(|(key, _)| !skip_fields.contains(&key.as_str()))
+        .filter(|(key, _)| !skip_fields.contains(&key.as_str()) && !is_reasoning_key(key))
         .collect();
 
     if extra_fields.is_empty() {
@@ -297,6 +367,8 @@ struct ImageDisplayProps {
 #[function_component(ImageDisplay)]
 fn image_display(props: &ImageDisplayProps) -> Html {
     let expanded = use_state(|| false);
+    let close_ref = use_node_ref();
+    let thumbnail_ref = use_node_ref();
 
     let toggle_expanded = {
         let expanded = expanded.clone();
@@ -305,12 +377,38 @@ fn image_display(props: &ImageDisplayProps) -> Html {
         })
     };
 
+    // Move focus into the overlay when it opens and back to the thumbnail when it closes
+    {
+        let close_ref = close_ref.clone();
+        let thumbnail_ref = thumbnail_ref.clone();
+        use_effect_with(*expanded, move |expanded| {
+            let target = if *expanded { close_ref } else { thumbnail_ref };
+            if let Some(element) = target.cast::<HtmlElement>() {
+                let _ = element.focus();
+            }
+        });
+    }
+
+    let on_overlay_keydown = {
+        let expanded = expanded.clone();
+        Callback::from(move |e: KeyboardEvent| {
+            if e.key() == "Escape" {
+                e.prevent_default();
+                expanded.set(false);
+            }
+        })
+    };
+
     if *expanded {
         // Full screen overlay
         html! {
             <>
                 <div class="fixed inset-0 bg-black/90 z-50 flex items-center justify-center p-4"
-                     onclick={toggle_expanded.clone()}>
+                     role="dialog"
+                     aria-modal="true"
+                     aria-label="Expanded image"
+                     onclick={toggle_expanded.clone()}
+                     onkeydown={on_overlay_keydown}>
                     <img
                         src={props.url.clone()}
                         alt="Expanded image"
@@ -318,9 +416,12 @@ fn image_display(props: &ImageDisplayProps) -> Html {
                         onclick={|e: MouseEvent| e.stop_propagation()}
                     />
                     <button
+                        ref={close_ref}
+                        type="button"
                         class="absolute top-4 right-4 text-white bg-black/50 rounded-full p-2 hover:bg-black/70 transition-colors"
                         onclick={toggle_expanded}
                         title="Close"
+                        aria-label="Close"
                     >
                         <svg class="w-6 h-6" fill="none" stroke="currentColor" viewBox="0 0 24 24">
                             <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M6 18L18 6M6 6l12 12" />
@@ -340,13 +441,20 @@ fn image_display(props: &ImageDisplayProps) -> Html {
         // Thumbnail view
         html! {
             <div class="my-2 max-w-md">
-                <img
-                    src={props.url.clone()}
-                    alt="Message image"
-                    class="max-w-full h-auto rounded-lg shadow-md cursor-zoom-in hover:shadow-lg transition-shadow"
+                <button
+                    ref={thumbnail_ref}
+                    type="button"
+                    class="block rounded-lg focus:outline-none focus:ring-2 focus:ring-blue-500"
                     onclick={toggle_expanded}
                     title="Click to expand"
-                />
+                    aria-label="Expand image"
+                >
+                    <img
+                        src={props.url.clone()}
+                        alt="Message image"
+                        class="max-w-full h-auto rounded-lg shadow-md cursor-zoom-in hover:shadow-lg transition-shadow"
+                    />
+                </button>
             </div>
         }
     }
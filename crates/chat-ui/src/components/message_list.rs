@@ -1,9 +1,13 @@
 use crate::components::Message;
 use crate::styles::{FLEX_COL_GAP_4, STANDARD_PADDING};
 use crate::types::ChatMessage;
-use web_sys::Element;
+use crate::utils::a11y::{Orientation, elements_matching, move_roving_focus};
+use web_sys::{Element, KeyboardEvent};
 use yew::prelude::*;
 
+/// Matches the root element of each rendered message
+const MESSAGE_SELECTOR: &str = "[data-chat-message]";
+
 #[derive(Properties, Clone, PartialEq)]
 pub struct MessageListProps {
     pub messages: Vec<ChatMessage>,
@@ -27,8 +31,27 @@ pub fn message_list(props: &MessageListProps) -> Html {
         }
     });
 
+    // Arrow keys move between messages once one has focus
+    let on_keydown = {
+        let container_ref = container_ref.clone();
+        Callback::from(move |e: KeyboardEvent| {
+            if let Some(container) = container_ref.cast::<Element>() {
+                let items = elements_matching(&container, MESSAGE_SELECTOR);
+                move_roving_focus(&items, &e, Orientation::Vertical);
+            }
+        })
+    };
+
     html! {
-        <div ref={container_ref} class={classes!(STANDARD_PADDING, FLEX_COL_GAP_4, class.clone())}>
+        <div
+            ref={container_ref}
+            class={classes!(STANDARD_PADDING, FLEX_COL_GAP_4, class.clone())}
+            role="log"
+            aria-live="polite"
+            aria-relevant="additions"
+            aria-label="Conversation"
+            onkeydown={on_keydown}
+        >
             {for messages.iter().enumerate().map(|(index, message)| {
                 html! {
                     <Message
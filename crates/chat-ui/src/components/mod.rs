@@ -1,5 +1,6 @@
 mod chat_container;
 mod chat_input;
+mod code_block;
 mod message;
 mod message_list;
 mod streaming_indicator;
@@ -7,6 +8,7 @@ mod streaming_text;
 
 pub use chat_container::ChatContainer;
 pub use chat_input::ChatInput;
+pub use code_block::CodeBlock;
 pub use message::Message;
 pub use message_list::MessageList;
 pub use streaming_indicator::StreamingIndicator;
@@ -1,5 +1,6 @@
+use crate::utils::a11y::prefers_reduced_motion;
 use crate::utils::markdown::render_markdown;
-use gloo_timers::callback::{Interval, Timeout};
+use gloo_timers::callback::Timeout;
 use std::cell::RefCell;
 use std::rc::Rc;
 use yew::prelude::*;
@@ -54,6 +55,18 @@ impl Reducible for StreamingState {
     }
 }
 
+type Step = Rc<RefCell<Option<Box<dyn Fn() -> bool>>>>;
+
+/// Run one animation step, keeping the step alive only while it wants to continue
+fn run_step(step: &Step) {
+    let step_fn = step.borrow_mut().take();
+    if let Some(step_fn) = step_fn
+        && step_fn()
+    {
+        *step.borrow_mut() = Some(step_fn);
+    }
+}
+
 #[function_component(StreamingText)]
 pub fn streaming_text(props: &StreamingTextProps) -> Html {
     let StreamingTextProps {
@@ -69,24 +82,15 @@ pub fn streaming_text(props: &StreamingTextProps) -> Html {
         current_index: 0,
         start_time: js_sys::Date::now(),
     });
-    let interval_handle = use_state(|| None::<Interval>);
-
-    // Reset when text changes
-    use_effect_with(text.clone(), {
-        let state = state.clone();
-        let interval_handle = interval_handle.clone();
-        move |_| {
-            interval_handle.set(None); // Clear interval
-            state.dispatch(StreamingAction::Reset);
-        }
-    });
+    // Bumped whenever a new animation starts so stale timeout chains stop
+    let generation = use_mut_ref(|| 0u64);
 
     // Streaming effect
     use_effect_with(
         (text.clone(), *streaming, *initial_delay_ms, *final_delay_ms),
         {
             let state = state.clone();
-            let interval_handle = interval_handle.clone();
+            let generation = generation.clone();
 
             move |(text, streaming, initial_delay, final_delay)| {
                 // Clone values to avoid lifetime issues
@@ -95,28 +99,32 @@ pub fn streaming_text(props: &StreamingTextProps) -> Html {
                 let initial_delay = *initial_delay;
                 let final_delay = *final_delay;
 
-                // Clear any existing interval
-                interval_handle.set(None);
+                *generation.borrow_mut() += 1;
+                let current_generation = *generation.borrow();
 
-                if !streaming {
-                    // If not streaming, show all text immediately
+                if !streaming || prefers_reduced_motion() {
+                    // If not streaming, or animation is unwanted, show all text immediately
                     state.dispatch(StreamingAction::SetComplete(text));
                     return;
                 }
 
-                // Start with empty text
-                state.dispatch(StreamingAction::Reset);
+                // New chunks usually extend the text; keep what is already
+                // shown instead of restarting the animation from scratch
+                let start_idx = if text.starts_with(state.current_text.as_str()) {
+                    state.current_text.len()
+                } else {
+                    state.dispatch(StreamingAction::Reset);
+                    0
+                };
 
-                // Create interval for streaming animation
-                let current_idx = Rc::new(RefCell::new(0usize));
-                let _start_time = js_sys::Date::now();
+                let current_idx = Rc::new(RefCell::new(start_idx));
 
-                let setup_next_interval = Rc::new(RefCell::new(None::<Box<dyn Fn()>>));
+                let setup_next_interval = Rc::new(RefCell::new(None::<Box<dyn Fn() -> bool>>));
                 let setup_next_interval_clone = setup_next_interval.clone();
 
                 *setup_next_interval.borrow_mut() = Some(Box::new({
                     let state = state.clone();
-                    let interval_handle = interval_handle.clone();
+                    let generation = generation.clone();
                     let text = text.clone();
                     let current_idx = current_idx.clone();
                     let setup_next_interval = setup_next_interval_clone.clone();
@@ -124,10 +132,10 @@ pub fn streaming_text(props: &StreamingTextProps) -> Html {
                     move || {
                         let idx = *current_idx.borrow();
 
-                        if idx >= text.len() {
-                            // Done streaming, clear interval
-                            interval_handle.set(None);
-                            return;
+                        // Stop when done or superseded by a newer chunk; returning
+                        // false drops this closure and breaks the reference cycle
+                        if idx >= text.len() || *generation.borrow() != current_generation {
+                            return false;
                         }
 
                         // Find next character boundary to avoid splitting UTF-8
@@ -153,32 +161,25 @@ pub fn streaming_text(props: &StreamingTextProps) -> Html {
                         *current_idx.borrow_mut() = next_idx;
 
                         // Schedule next update with dynamic delay
-                        if next_idx < text.len() {
-                            let setup_clone = setup_next_interval.clone();
-                            let timeout = Timeout::new(delay, move || {
-                                if let Some(ref setup_fn) = *setup_clone.borrow() {
-                                    setup_fn();
-                                }
-                            });
-                            timeout.forget();
-                        }
+                        let setup_clone = setup_next_interval.clone();
+                        let timeout = Timeout::new(delay, move || run_step(&setup_clone));
+                        timeout.forget();
+                        true
                     }
                 }));
 
-                // Start the first interval
-                if let Some(ref setup_fn) = *setup_next_interval.borrow() {
-                    setup_fn();
-                }
+                // Start the first step
+                run_step(&setup_next_interval);
             }
         },
     );
 
-    // Cleanup interval on unmount
+    // Stop any running animation on unmount
     use_effect_with((), {
-        let interval_handle = interval_handle.clone();
+        let generation = generation.clone();
         move |_| {
             move || {
-                interval_handle.set(None);
+                *generation.borrow_mut() += 1;
             }
         }
     });
@@ -195,7 +196,7 @@ pub fn streaming_text(props: &StreamingTextProps) -> Html {
             {render_markdown(displayed_text)}
 
             if *streaming && state.current_index < text.len() {
-                <span class="inline-block ml-0.5 text-gray-600 dark:text-gray-400 align-baseline animate-pulse">{"▋"}</span>
+                <span class="inline-block ml-0.5 text-gray-600 dark:text-gray-400 align-baseline animate-pulse motion-reduce:animate-none" aria-hidden="true">{"▋"}</span>
             }
         </div>
     }
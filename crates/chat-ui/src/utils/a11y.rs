@@ -0,0 +1,197 @@
+//! Keyboard navigation, focus management and motion preferences
+
+use wasm_bindgen::JsCast;
+use web_sys::{Element, HtmlElement, KeyboardEvent};
+
+/// Elements that can receive keyboard focus
+pub const FOCUSABLE_SELECTOR: &str = "a[href], button:not([disabled]), input:not([disabled]):not([type=\"hidden\"]), select:not([disabled]), textarea:not([disabled]), summary, [tabindex]:not([tabindex=\"-1\"])";
+
+/// Class on `<html>` that switches off animations and transitions
+pub const REDUCE_MOTION_CLASS: &str = "reduce-motion";
+const REDUCE_MOTION_QUERY: &str = "(prefers-reduced-motion: reduce)";
+
+const KEY_TAB: &str = "Tab";
+const KEY_UP: &str = "ArrowUp";
+const KEY_DOWN: &str = "ArrowDown";
+const KEY_LEFT: &str = "ArrowLeft";
+const KEY_RIGHT: &str = "ArrowRight";
+const KEY_HOME: &str = "Home";
+const KEY_END: &str = "End";
+
+/// Layout of a group navigated with arrow keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    Horizontal,
+    Vertical,
+}
+
+/// Index a roving-focus group should move to for `key`, wrapping at the ends.
+///
+/// Returns `None` for keys the group does not handle.
+pub fn roving_index(
+    current: Option<usize>,
+    count: usize,
+    key: &str,
+    orientation: Orientation,
+) -> Option<usize> {
+    if count == 0 {
+        return None;
+    }
+    let (prev, next) = match orientation {
+        Orientation::Horizontal => (KEY_LEFT, KEY_RIGHT),
+        Orientation::Vertical => (KEY_UP, KEY_DOWN),
+    };
+    match key {
+        KEY_HOME => Some(0),
+        KEY_END => Some(count - 1),
+        k if k == next => Some(current.map_or(0, |i| (i + 1) % count)),
+        k if k == prev => Some(current.map_or(count - 1, |i| (i + count - 1) % count)),
+        _ => None,
+    }
+}
+
+/// Index Tab should land on to keep focus inside a trap, or `None` to let
+/// the browser move focus normally.
+pub fn trapped_tab_index(current: Option<usize>, count: usize, backwards: bool) -> Option<usize> {
+    if count == 0 {
+        return None;
+    }
+    match (current, backwards) {
+        (None, false) => Some(0),
+        (None, true) => Some(count - 1),
+        (Some(0), true) => Some(count - 1),
+        (Some(i), false) if i + 1 >= count => Some(0),
+        _ => None,
+    }
+}
+
+/// Visible focusable elements inside `container`, in tab order
+pub fn focusable_elements(container: &Element) -> Vec<HtmlElement> {
+    elements_matching(container, FOCUSABLE_SELECTOR)
+        .into_iter()
+        .filter(|el| el.offset_parent().is_some() || el.get_client_rects().length() > 0)
+        .collect()
+}
+
+/// Elements inside `container` matching `selector`
+pub fn elements_matching(container: &Element, selector: &str) -> Vec<HtmlElement> {
+    let Ok(nodes) = container.query_selector_all(selector) else {
+        return Vec::new();
+    };
+    (0..nodes.length())
+        .filter_map(|i| nodes.item(i))
+        .filter_map(|node| node.dyn_into::<HtmlElement>().ok())
+        .collect()
+}
+
+/// The element that currently has focus
+pub fn active_element() -> Option<Element> {
+    web_sys::window()?.document()?.active_element()
+}
+
+fn position_of(elements: &[HtmlElement], target: Option<&Element>) -> Option<usize> {
+    let target = target?;
+    elements
+        .iter()
+        .position(|el| AsRef::<Element>::as_ref(el) == target)
+}
+
+/// Focus the first focusable element inside `container`
+pub fn focus_first(container: &Element) {
+    if let Some(first) = focusable_elements(container).first() {
+        let _ = first.focus();
+    }
+}
+
+/// Keep Tab and Shift+Tab cycling inside `container`
+pub fn trap_focus(container: &Element, event: &KeyboardEvent) {
+    if event.key() != KEY_TAB {
+        return;
+    }
+    let elements = focusable_elements(container);
+    let current = position_of(&elements, active_element().as_ref());
+    if let Some(index) = trapped_tab_index(current, elements.len(), event.shift_key()) {
+        event.prevent_default();
+        let _ = elements[index].focus();
+    }
+}
+
+/// Move focus between the items of a roving-focus group.
+///
+/// Returns true when the key was handled.
+pub fn move_roving_focus(
+    items: &[HtmlElement],
+    event: &KeyboardEvent,
+    orientation: Orientation,
+) -> bool {
+    let current = position_of(items, active_element().as_ref());
+    match roving_index(current, items.len(), &event.key(), orientation) {
+        Some(index) => {
+            event.prevent_default();
+            let _ = items[index].focus();
+            true
+        }
+        None => false,
+    }
+}
+
+/// Whether animations should be skipped, either by the user's choice in the
+/// app or by their operating system setting
+pub fn prefers_reduced_motion() -> bool {
+    let Some(window) = web_sys::window() else {
+        return false;
+    };
+    let forced = window
+        .document()
+        .and_then(|document| document.document_element())
+        .is_some_and(|root| root.class_list().contains(REDUCE_MOTION_CLASS));
+    forced
+        || window
+            .match_media(REDUCE_MOTION_QUERY)
+            .ok()
+            .flatten()
+            .is_some_and(|query| query.matches())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roving_index_wraps_and_jumps() {
+        let v = Orientation::Vertical;
+        assert_eq!(roving_index(Some(0), 3, KEY_DOWN, v), Some(1));
+        assert_eq!(roving_index(Some(2), 3, KEY_DOWN, v), Some(0));
+        assert_eq!(roving_index(Some(0), 3, KEY_UP, v), Some(2));
+        assert_eq!(roving_index(None, 3, KEY_UP, v), Some(2));
+        assert_eq!(roving_index(Some(1), 3, KEY_HOME, v), Some(0));
+        assert_eq!(roving_index(Some(1), 3, KEY_END, v), Some(2));
+        assert_eq!(roving_index(Some(1), 0, KEY_DOWN, v), None);
+    }
+
+    #[test]
+    fn test_roving_index_ignores_cross_axis_keys() {
+        assert_eq!(
+            roving_index(Some(0), 3, KEY_DOWN, Orientation::Horizontal),
+            None
+        );
+        assert_eq!(
+            roving_index(Some(0), 3, KEY_RIGHT, Orientation::Horizontal),
+            Some(1)
+        );
+        assert_eq!(roving_index(Some(0), 3, "a", Orientation::Vertical), None);
+    }
+
+    #[test]
+    fn test_trapped_tab_index_cycles_at_edges() {
+        assert_eq!(trapped_tab_index(Some(2), 3, false), Some(0));
+        assert_eq!(trapped_tab_index(Some(0), 3, true), Some(2));
+        // Focus escaped the container; pull it back in
+        assert_eq!(trapped_tab_index(None, 3, false), Some(0));
+        assert_eq!(trapped_tab_index(None, 3, true), Some(2));
+        // Interior moves are left to the browser
+        assert_eq!(trapped_tab_index(Some(1), 3, false), None);
+        assert_eq!(trapped_tab_index(Some(1), 3, true), None);
+        assert_eq!(trapped_tab_index(None, 0, false), None);
+    }
+}
@@ -0,0 +1,281 @@
+//! Lightweight syntax highlighting for code blocks
+//!
+//! This is a single-pass tokenizer rather than a real grammar: it recognises
+//! comments, strings, numbers and a keyword list per language, which is
+//! enough to make model output readable without pulling in a highlighter.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Plain,
+    Keyword,
+    String,
+    Number,
+    Comment,
+}
+
+impl TokenKind {
+    pub fn class(self) -> &'static str {
+        match self {
+            TokenKind::Plain => "",
+            TokenKind::Keyword => "text-purple-700 dark:text-purple-300",
+            TokenKind::String => "text-green-700 dark:text-green-300",
+            TokenKind::Number => "text-orange-700 dark:text-orange-300",
+            TokenKind::Comment => "text-gray-500 dark:text-gray-400 italic",
+        }
+    }
+}
+
+struct Syntax {
+    keywords: &'static [&'static str],
+    quotes: &'static [char],
+    line_comment: Option<&'static str>,
+    block_comment: Option<(&'static str, &'static str)>,
+}
+
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "false",
+    "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "type", "unsafe",
+    "use", "where", "while",
+];
+const PYTHON_KEYWORDS: &[&str] = &[
+    "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del", "elif",
+    "else", "except", "False", "finally", "for", "from", "global", "if", "import", "in", "is",
+    "lambda", "None", "nonlocal", "not", "or", "pass", "raise", "return", "True", "try", "while",
+    "with", "yield",
+];
+const JS_KEYWORDS: &[&str] = &[
+    "async",
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "let",
+    "new",
+    "null",
+    "return",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "type",
+    "typeof",
+    "undefined",
+    "var",
+    "void",
+    "while",
+    "yield",
+];
+const GO_KEYWORDS: &[&str] = &[
+    "break",
+    "case",
+    "chan",
+    "const",
+    "continue",
+    "default",
+    "defer",
+    "else",
+    "false",
+    "for",
+    "func",
+    "go",
+    "if",
+    "import",
+    "interface",
+    "map",
+    "nil",
+    "package",
+    "range",
+    "return",
+    "select",
+    "struct",
+    "switch",
+    "true",
+    "type",
+    "var",
+];
+const SHELL_KEYWORDS: &[&str] = &[
+    "case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function", "if", "in",
+    "local", "return", "then", "while",
+];
+const JSON_KEYWORDS: &[&str] = &["true", "false", "null"];
+
+fn syntax_for(lang: &str) -> Option<Syntax> {
+    let c_style = |keywords: &'static [&'static str], quotes: &'static [char]| Syntax {
+        keywords,
+        quotes,
+        line_comment: Some("//"),
+        block_comment: Some(("/*", "*/")),
+    };
+    let hash_comments = |keywords: &'static [&'static str]| Syntax {
+        keywords,
+        quotes: &['"', '\''],
+        line_comment: Some("#"),
+        block_comment: None,
+    };
+    match lang.to_ascii_lowercase().as_str() {
+        // Single quotes are lifetimes and chars in Rust, so only double quotes delimit strings
+        "rust" | "rs" => Some(c_style(RUST_KEYWORDS, &['"'])),
+        "js" | "javascript" | "ts" | "typescript" | "jsx" | "tsx" => {
+            Some(c_style(JS_KEYWORDS, &['"', '\'', '`']))
+        }
+        "go" | "golang" => Some(c_style(GO_KEYWORDS, &['"', '\'', '`'])),
+        "python" | "py" => Some(hash_comments(PYTHON_KEYWORDS)),
+        "sh" | "bash" | "shell" | "zsh" => Some(hash_comments(SHELL_KEYWORDS)),
+        "json" => Some(Syntax {
+            keywords: JSON_KEYWORDS,
+            quotes: &['"'],
+            line_comment: None,
+            block_comment: None,
+        }),
+        _ => None,
+    }
+}
+
+/// Split `code` into highlighted tokens; unknown languages yield one plain token
+pub fn highlight<'a>(lang: &str, code: &'a str) -> Vec<(TokenKind, &'a str)> {
+    let Some(syntax) = syntax_for(lang) else {
+        return vec![(TokenKind::Plain, code)];
+    };
+
+    let mut tokens = Vec::new();
+    let mut plain_start = 0;
+    let mut pos = 0;
+
+    while pos < code.len() {
+        let rest = &code[pos..];
+        let (kind, len) = if let Some(len) = syntax
+            .line_comment
+            .filter(|marker| rest.starts_with(marker))
+            .map(|_| rest.find('\n').unwrap_or(rest.len()))
+        {
+            (TokenKind::Comment, len)
+        } else if let Some(len) = syntax
+            .block_comment
+            .filter(|(open, _)| rest.starts_with(open))
+            .map(|(open, close)| {
+                rest[open.len()..]
+                    .find(close)
+                    .map_or(rest.len(), |end| open.len() + end + close.len())
+            })
+        {
+            (TokenKind::Comment, len)
+        } else if let Some(quote) = rest.chars().next().filter(|c| syntax.quotes.contains(c)) {
+            (TokenKind::String, string_len(rest, quote))
+        } else if rest.starts_with(|c: char| c.is_ascii_digit()) && !ends_with_ident(&code[..pos]) {
+            let len = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '.' || c == '_'))
+                .unwrap_or(rest.len());
+            (TokenKind::Number, len)
+        } else if rest.starts_with(is_ident_char) && !ends_with_ident(&code[..pos]) {
+            let len = rest.find(|c| !is_ident_char(c)).unwrap_or(rest.len());
+            if syntax.keywords.contains(&&rest[..len]) {
+                (TokenKind::Keyword, len)
+            } else {
+                pos += len;
+                continue;
+            }
+        } else {
+            pos += rest.chars().next().map_or(1, char::len_utf8);
+            continue;
+        };
+
+        if plain_start < pos {
+            tokens.push((TokenKind::Plain, &code[plain_start..pos]));
+        }
+        tokens.push((kind, &code[pos..pos + len]));
+        pos += len;
+        plain_start = pos;
+    }
+
+    if plain_start < code.len() {
+        tokens.push((TokenKind::Plain, &code[plain_start..]));
+    }
+    tokens
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn ends_with_ident(text: &str) -> bool {
+    text.chars().next_back().is_some_and(is_ident_char)
+}
+
+/// Length of a quoted string starting at `text`, honouring backslash escapes.
+/// Unterminated strings run to the end of the line so partial output stays stable.
+fn string_len(text: &str, quote: char) -> usize {
+    let mut escaped = false;
+    for (i, c) in text.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '\n' if quote != '`' => return i,
+            c if c == quote => return i + c.len_utf8(),
+            _ => {}
+        }
+    }
+    text.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_language_is_plain() {
+        assert_eq!(
+            highlight("cobol", "MOVE A"),
+            vec![(TokenKind::Plain, "MOVE A")]
+        );
+    }
+
+    #[test]
+    fn test_rust_tokens() {
+        let tokens = highlight("rust", "let x = \"hi\"; // note\n42");
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::Keyword, "let"),
+                (TokenKind::Plain, " x = "),
+                (TokenKind::String, "\"hi\""),
+                (TokenKind::Plain, "; "),
+                (TokenKind::Comment, "// note"),
+                (TokenKind::Plain, "\n"),
+                (TokenKind::Number, "42"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokens_cover_input() {
+        let code = "def f(x):\n    return 'a\\'b' + x1  # tail";
+        let joined: String = highlight("python", code).iter().map(|(_, t)| *t).collect();
+        assert_eq!(joined, code);
+    }
+
+    #[test]
+    fn test_unterminated_string_stops_at_line_end() {
+        let tokens = highlight("js", "const s = \"open\nnext");
+        assert!(tokens.contains(&(TokenKind::String, "\"open")));
+    }
+}
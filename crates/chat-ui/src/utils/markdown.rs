@@ -1,136 +1,389 @@
-use yew::prelude::*;
+//! Markdown rendering for chat messages
+//!
+//! Content is parsed into blocks and inline spans and rendered as Yew nodes,
+//! never as raw HTML, so model output cannot inject markup. The parser is
+//! tolerant of partial input: an unclosed code fence renders as an open code
+//! block and unmatched inline markers stay literal, so text streaming in chunk
+//! by chunk only ever grows in place instead of reflowing.
 
-pub fn render_markdown(content: &str) -> Html {
-    // For now, let's do basic markdown rendering without the pulldown-cmark dependency
-    // to avoid version conflicts. We can enhance this later.
-    let mut html_output = String::new();
-    let mut in_code_block = false;
-    let mut code_content = String::new();
-
-    for line in content.lines() {
-        if line.starts_with("```") {
-            if in_code_block {
-                // End code block
-                html_output.push_str("<pre><code>");
-                html_output.push_str(&html_escape(&code_content));
-                html_output.push_str("</code></pre>");
-                code_content.clear();
-                in_code_block = false;
-            } else {
-                // Start code block
-                in_code_block = true;
-            }
-        } else if in_code_block {
-            if !code_content.is_empty() {
-                code_content.push('\n');
-            }
-            code_content.push_str(line);
-        } else {
-            // Simple replacements for basic markdown
-            let mut processed_line = line.to_string();
-
-            // Headers
-            if let Some(rest) = processed_line.strip_prefix("### ") {
-                html_output.push_str(&format!("<h3>{}</h3>", html_escape(rest)));
-            } else if let Some(rest) = processed_line.strip_prefix("## ") {
-                html_output.push_str(&format!("<h2>{}</h2>", html_escape(rest)));
-            } else if let Some(rest) = processed_line.strip_prefix("# ") {
-                html_output.push_str(&format!("<h1>{}</h1>", html_escape(rest)));
-            } else if processed_line.trim().is_empty() {
-                // Empty line
-                if !html_output.ends_with("</p>") && !html_output.ends_with("</pre>") {
-                    html_output.push_str("<br>");
-                }
-            } else {
-                // Regular paragraph
-                html_output.push_str("<p>");
+use crate::components::CodeBlock;
+use crate::styles::INLINE_CODE;
+use yew::prelude::*;
 
-                // Bold text
-                processed_line = replace_pattern(&processed_line, "**", "<strong>", "</strong>");
+const FENCES: [&str; 2] = ["```", "~~~"];
+const SAFE_LINK_PREFIXES: [&str; 5] = ["http://", "https://", "mailto:", "/", "#"];
 
-                // Italic text
-                processed_line = replace_pattern(&processed_line, "*", "<em>", "</em>");
+#[derive(Debug, Clone, PartialEq)]
+pub enum Block {
+    Heading {
+        level: usize,
+        text: String,
+    },
+    Paragraph(Vec<String>),
+    Code {
+        lang: String,
+        code: String,
+        closed: bool,
+    },
+    List {
+        ordered: bool,
+        items: Vec<String>,
+    },
+    Quote(Vec<String>),
+    Rule,
+}
 
-                // Inline code
-                processed_line = replace_inline_code(&processed_line);
+#[derive(Debug, Clone, PartialEq)]
+pub enum Inline {
+    Text(String),
+    Strong(String),
+    Emphasis(String),
+    Code(String),
+    Link { text: String, href: String },
+}
 
-                html_output.push_str(&processed_line);
-                html_output.push_str("</p>");
-            }
+fn list_item(line: &str) -> Option<(bool, &str)> {
+    let trimmed = line.trim_start();
+    for marker in ["- ", "* ", "+ "] {
+        if let Some(rest) = trimmed.strip_prefix(marker) {
+            return Some((false, rest));
         }
     }
+    let digits = trimmed.find(|c: char| !c.is_ascii_digit())?;
+    if digits == 0 {
+        return None;
+    }
+    trimmed[digits..]
+        .strip_prefix(". ")
+        .or_else(|| trimmed[digits..].strip_prefix(") "))
+        .map(|rest| (true, rest))
+}
 
-    // Handle unclosed code block
-    if in_code_block {
-        html_output.push_str("<pre><code>");
-        html_output.push_str(&html_escape(&code_content));
-        html_output.push_str("</code></pre>");
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
     }
+    line[level..]
+        .strip_prefix(' ')
+        .map(|text| (level, text.trim()))
+}
 
-    Html::from_html_unchecked(AttrValue::from(html_output))
+fn is_rule(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.len() >= 3
+        && ['-', '*', '_']
+            .iter()
+            .any(|&c| trimmed.chars().all(|t| t == c || t == ' ') && trimmed.starts_with(c))
 }
 
-fn replace_pattern(text: &str, delimiter: &str, open_tag: &str, close_tag: &str) -> String {
-    let parts: Vec<&str> = text.split(delimiter).collect();
-    if parts.len() < 3 {
-        return text.to_string();
-    }
+/// Split markdown into blocks
+pub fn parse_blocks(content: &str) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut lines = content.lines().peekable();
 
-    let mut result = String::new();
-    let mut in_delimiter = false;
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim_start();
 
-    for (i, part) in parts.iter().enumerate() {
-        if i == 0 {
-            result.push_str(part);
-        } else if in_delimiter {
-            result.push_str(close_tag);
-            result.push_str(part);
-            in_delimiter = false;
+        if let Some(fence) = FENCES.iter().find(|f| trimmed.starts_with(**f)) {
+            let lang = trimmed[fence.len()..]
+                .split_whitespace()
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            let mut code_lines = Vec::new();
+            let mut closed = false;
+            for code_line in lines.by_ref() {
+                if code_line.trim_start().starts_with(fence) {
+                    closed = true;
+                    break;
+                }
+                code_lines.push(code_line);
+            }
+            blocks.push(Block::Code {
+                lang,
+                code: code_lines.join("\n"),
+                closed,
+            });
+        } else if trimmed.is_empty() {
+            continue;
+        } else if let Some((level, text)) = heading(trimmed) {
+            blocks.push(Block::Heading {
+                level,
+                text: text.to_string(),
+            });
+        } else if is_rule(trimmed) {
+            blocks.push(Block::Rule);
+        } else if let Some((ordered, first)) = list_item(line) {
+            let mut items = vec![first.to_string()];
+            while let Some(next) = lines.peek() {
+                match list_item(next) {
+                    Some((next_ordered, item)) if next_ordered == ordered => {
+                        items.push(item.to_string());
+                    }
+                    None if next.starts_with("  ") && !next.trim().is_empty() => {
+                        if let Some(last) = items.last_mut() {
+                            last.push(' ');
+                            last.push_str(next.trim());
+                        }
+                    }
+                    _ => break,
+                }
+                lines.next();
+            }
+            blocks.push(Block::List { ordered, items });
+        } else if trimmed.starts_with('>') {
+            let mut quote = vec![trimmed[1..].trim_start().to_string()];
+            while let Some(next) = lines.peek().map(|l| l.trim_start()) {
+                let Some(rest) = next.strip_prefix('>') else {
+                    break;
+                };
+                quote.push(rest.trim_start().to_string());
+                lines.next();
+            }
+            blocks.push(Block::Quote(quote));
         } else {
-            result.push_str(open_tag);
-            result.push_str(part);
-            in_delimiter = true;
+            let mut paragraph = vec![line.trim().to_string()];
+            while let Some(next) = lines.peek() {
+                let next_trimmed = next.trim_start();
+                if next_trimmed.is_empty()
+                    || FENCES.iter().any(|f| next_trimmed.starts_with(f))
+                    || heading(next_trimmed).is_some()
+                    || list_item(next).is_some()
+                    || next_trimmed.starts_with('>')
+                {
+                    break;
+                }
+                paragraph.push(next.trim().to_string());
+                lines.next();
+            }
+            blocks.push(Block::Paragraph(paragraph));
         }
     }
 
-    result
+    blocks
 }
 
-fn replace_inline_code(text: &str) -> String {
-    let parts: Vec<&str> = text.split('`').collect();
-    if parts.len() < 3 {
-        return text.to_string();
+/// Find the closing `marker` after `start`, requiring non-empty content
+fn closing(text: &str, start: usize, marker: &str) -> Option<usize> {
+    text[start..]
+        .find(marker)
+        .filter(|&len| len > 0)
+        .map(|len| start + len)
+}
+
+fn parse_link(text: &str) -> Option<(String, String, usize)> {
+    let label_end = text.find("](")?;
+    let href_end = label_end + 2 + text[label_end + 2..].find(')')?;
+    let label = &text[1..label_end];
+    let href = text[label_end + 2..href_end].trim();
+    if label.is_empty() || !SAFE_LINK_PREFIXES.iter().any(|p| href.starts_with(p)) {
+        return None;
     }
+    Some((label.to_string(), href.to_string(), href_end + 1))
+}
 
-    let mut result = String::new();
-    let mut in_code = false;
+/// Split a line of text into inline spans
+pub fn parse_inline(text: &str) -> Vec<Inline> {
+    let mut spans = Vec::new();
+    let mut plain = String::new();
+    let mut pos = 0;
 
-    for (i, part) in parts.iter().enumerate() {
-        if i == 0 {
-            result.push_str(part);
-        } else if in_code {
-            result.push_str("</code>");
-            result.push_str(part);
-            in_code = false;
+    while pos < text.len() {
+        let rest = &text[pos..];
+        let span = if rest.starts_with('`') {
+            closing(rest, 1, "`").map(|end| (Inline::Code(rest[1..end].to_string()), end + 1))
+        } else if rest.starts_with("**") || rest.starts_with("__") {
+            closing(rest, 2, &rest[..2])
+                .map(|end| (Inline::Strong(rest[2..end].to_string()), end + 2))
+        } else if rest.starts_with('*')
+            || (rest.starts_with('_') && !plain.ends_with(char::is_alphanumeric))
+        {
+            closing(rest, 1, &rest[..1])
+                .filter(|&end| !rest[1..end].starts_with(' '))
+                .map(|end| (Inline::Emphasis(rest[1..end].to_string()), end + 1))
+        } else if rest.starts_with('[') {
+            parse_link(rest).map(|(text, href, len)| (Inline::Link { text, href }, len))
         } else {
-            result.push_str("<code>");
-            result.push_str(&html_escape(part));
-            in_code = true;
+            None
+        };
+
+        match span {
+            Some((span, len)) => {
+                if !plain.is_empty() {
+                    spans.push(Inline::Text(std::mem::take(&mut plain)));
+                }
+                spans.push(span);
+                pos += len;
+            }
+            None => {
+                let c = rest.chars().next().unwrap_or_default();
+                plain.push(c);
+                pos += c.len_utf8().max(1);
+            }
         }
     }
 
-    result
+    if !plain.is_empty() {
+        spans.push(Inline::Text(plain));
+    }
+    spans
+}
+
+fn render_inline(text: &str) -> Html {
+    parse_inline(text)
+        .into_iter()
+        .map(|span| match span {
+            Inline::Text(text) => html! { {text} },
+            Inline::Strong(text) => html! { <strong>{text}</strong> },
+            Inline::Emphasis(text) => html! { <em>{text}</em> },
+            Inline::Code(code) => html! { <code class={INLINE_CODE}>{code}</code> },
+            Inline::Link { text, href } => html! {
+                <a {href} target="_blank" rel="noopener noreferrer"
+                   class="text-blue-600 dark:text-blue-400 underline">{text}</a>
+            },
+        })
+        .collect()
 }
 
-fn html_escape(s: &str) -> String {
-    s.chars()
-        .map(|c| match c {
-            '&' => "&amp;".to_string(),
-            '<' => "&lt;".to_string(),
-            '>' => "&gt;".to_string(),
-            '"' => "&quot;".to_string(),
-            '\'' => "&#39;".to_string(),
-            _ => c.to_string(),
+fn render_lines(lines: &[String]) -> Html {
+    lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            html! {
+                <>
+                    if i > 0 { <br /> }
+                    {render_inline(line)}
+                </>
+            }
         })
         .collect()
 }
+
+fn render_block(block: Block) -> Html {
+    match block {
+        Block::Heading { level, text } => {
+            let class = match level {
+                1 => "text-xl font-bold my-2",
+                2 => "text-lg font-bold my-2",
+                _ => "font-semibold my-1",
+            };
+            html! {
+                <@{format!("h{level}")} {class}>{render_inline(&text)}</@>
+            }
+        }
+        Block::Paragraph(lines) => html! { <p class="my-1">{render_lines(&lines)}</p> },
+        Block::Code { lang, code, closed } => html! {
+            <CodeBlock {lang} {code} complete={closed} />
+        },
+        Block::List { ordered, items } => {
+            let items = items
+                .iter()
+                .map(|item| html! { <li>{render_inline(item)}</li> })
+                .collect::<Html>();
+            if ordered {
+                html! { <ol class="list-decimal pl-6 my-1">{items}</ol> }
+            } else {
+                html! { <ul class="list-disc pl-6 my-1">{items}</ul> }
+            }
+        }
+        Block::Quote(lines) => html! {
+            <blockquote class="border-l-4 border-gray-300 dark:border-gray-600 pl-3 my-1 text-gray-600 dark:text-gray-400">
+                {render_lines(&lines)}
+            </blockquote>
+        },
+        Block::Rule => html! { <hr class="my-2 border-gray-200 dark:border-gray-700" /> },
+    }
+}
+
+pub fn render_markdown(content: &str) -> Html {
+    html! {
+        <>
+            {for parse_blocks(content).into_iter().map(render_block)}
+        </>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_blocks() {
+        let blocks =
+            parse_blocks("# Title\n\nSome text\nmore\n\n- a\n- b\n\n```rust\nfn main() {}\n```");
+        assert_eq!(
+            blocks,
+            vec![
+                Block::Heading {
+                    level: 1,
+                    text: "Title".into()
+                },
+                Block::Paragraph(vec!["Some text".into(), "more".into()]),
+                Block::List {
+                    ordered: false,
+                    items: vec!["a".into(), "b".into()]
+                },
+                Block::Code {
+                    lang: "rust".into(),
+                    code: "fn main() {}".into(),
+                    closed: true
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unclosed_fence_is_open_code_block() {
+        let blocks = parse_blocks("Here:\n```py\nprint(1)");
+        assert_eq!(
+            blocks.last(),
+            Some(&Block::Code {
+                lang: "py".into(),
+                code: "print(1)".into(),
+                closed: false
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_inline() {
+        assert_eq!(
+            parse_inline("a **b** *c* `d` [e](https://x.y)"),
+            vec![
+                Inline::Text("a ".into()),
+                Inline::Strong("b".into()),
+                Inline::Text(" ".into()),
+                Inline::Emphasis("c".into()),
+                Inline::Text(" ".into()),
+                Inline::Code("d".into()),
+                Inline::Text(" ".into()),
+                Inline::Link {
+                    text: "e".into(),
+                    href: "https://x.y".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unmatched_markers_stay_literal() {
+        assert_eq!(
+            parse_inline("partial **bold and `code"),
+            vec![Inline::Text("partial **bold and `code".into())]
+        );
+        assert_eq!(
+            parse_inline("snake_case_name"),
+            vec![Inline::Text("snake_case_name".into())]
+        );
+    }
+
+    #[test]
+    fn test_unsafe_links_are_text() {
+        assert_eq!(
+            parse_inline("[x](javascript:alert(1))"),
+            vec![Inline::Text("[x](javascript:alert(1))".into())]
+        );
+    }
+}
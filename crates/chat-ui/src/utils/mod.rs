@@ -1,4 +1,7 @@
+pub mod a11y;
 pub mod cassette_loader;
 pub mod flexible_parser;
+pub mod highlight;
 pub mod markdown;
+pub mod reasoning;
 pub mod simple_cassette_parser;
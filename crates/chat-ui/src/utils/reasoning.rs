@@ -0,0 +1,98 @@
+//! Separate model reasoning from the visible answer
+
+use crate::types::ChatMessage;
+
+const THINK_OPEN: &str = "<think>";
+const THINK_CLOSE: &str = "</think>";
+const REASONING_KEYS: [&str; 2] = ["reasoning_content", "reasoning"];
+
+/// Reasoning text and answer extracted from a message
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReasoningSplit<'a> {
+    pub reasoning: Option<&'a str>,
+    pub answer: &'a str,
+    /// False while a `<think>` block is still open
+    pub reasoning_complete: bool,
+}
+
+/// Split a leading `<think>…</think>` block off the answer text.
+///
+/// An unterminated block is treated as reasoning in progress, so partial
+/// chunks never flash raw tags into the answer.
+pub fn split_think_tags(text: &str) -> ReasoningSplit<'_> {
+    let trimmed = text.trim_start();
+    // Hold back a tag that has only partially arrived
+    if !trimmed.is_empty() && trimmed.len() < THINK_OPEN.len() && THINK_OPEN.starts_with(trimmed) {
+        return ReasoningSplit {
+            reasoning: None,
+            answer: "",
+            reasoning_complete: false,
+        };
+    }
+    let Some(body) = trimmed.strip_prefix(THINK_OPEN) else {
+        return ReasoningSplit {
+            reasoning: None,
+            answer: text,
+            reasoning_complete: true,
+        };
+    };
+    match body.find(THINK_CLOSE) {
+        Some(end) => ReasoningSplit {
+            reasoning: Some(body[..end].trim()),
+            answer: body[end + THINK_CLOSE.len()..].trim_start(),
+            reasoning_complete: true,
+        },
+        None => ReasoningSplit {
+            reasoning: Some(body.trim_start()),
+            answer: "",
+            reasoning_complete: false,
+        },
+    }
+}
+
+/// Reasoning delivered out of band in message metadata
+pub fn metadata_reasoning(message: &ChatMessage) -> Option<&str> {
+    REASONING_KEYS
+        .iter()
+        .find_map(|key| message.metadata.get(*key).and_then(|v| v.as_str()))
+        .filter(|text| !text.is_empty())
+}
+
+/// Whether a metadata key carries reasoning and is rendered separately
+pub fn is_reasoning_key(key: &str) -> bool {
+    REASONING_KEYS.contains(&key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_think_block() {
+        let split = split_think_tags("plain answer");
+        assert_eq!(split.reasoning, None);
+        assert_eq!(split.answer, "plain answer");
+    }
+
+    #[test]
+    fn test_closed_think_block() {
+        let split = split_think_tags("<think> step 1 </think>\n\nThe answer");
+        assert_eq!(split.reasoning, Some("step 1"));
+        assert_eq!(split.answer, "The answer");
+        assert!(split.reasoning_complete);
+    }
+
+    #[test]
+    fn test_open_think_block() {
+        let split = split_think_tags("<think>still going");
+        assert_eq!(split.reasoning, Some("still going"));
+        assert_eq!(split.answer, "");
+        assert!(!split.reasoning_complete);
+    }
+
+    #[test]
+    fn test_partial_open_tag_is_held_back() {
+        assert_eq!(split_think_tags("<thi").answer, "");
+        assert_eq!(split_think_tags("<b>").answer, "<b>");
+    }
+}
@@ -0,0 +1,122 @@
+//! Accessibility checks on rendered chat components
+//!
+//! Run with `wasm-pack test --headless --firefox crates/chat-ui`.
+
+#![cfg(target_arch = "wasm32")]
+
+use gate_chat_ui::ChatMessage;
+use gate_chat_ui::components::{ChatInput, MessageList, StreamingIndicator};
+use std::time::Duration;
+use wasm_bindgen_test::*;
+use web_sys::Element;
+use yew::prelude::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[function_component(Fixture)]
+fn fixture() -> Html {
+    let messages = vec![
+        ChatMessage::system("Be brief."),
+        ChatMessage::user("Hello"),
+        ChatMessage::assistant("Hi there"),
+    ];
+    html! {
+        <>
+            <MessageList {messages} />
+            <StreamingIndicator />
+            <ChatInput on_send={Callback::from(|_: String| ())} allow_files=true />
+        </>
+    }
+}
+
+async fn render_fixture() -> Element {
+    let document = web_sys::window().unwrap().document().unwrap();
+    let root = document.create_element("div").unwrap();
+    document.body().unwrap().append_child(&root).unwrap();
+    yew::Renderer::<Fixture>::with_root(root.clone()).render();
+    // Let the scheduler flush the first render
+    yew::platform::time::sleep(Duration::ZERO).await;
+    root
+}
+
+fn all(root: &Element, selector: &str) -> Vec<Element> {
+    let nodes = root.query_selector_all(selector).unwrap();
+    (0..nodes.length())
+        .filter_map(|i| nodes.item(i))
+        .filter_map(|node| wasm_bindgen::JsCast::dyn_into::<Element>(node).ok())
+        .collect()
+}
+
+fn has_accessible_name(root: &Element, el: &Element) -> bool {
+    let attr = |name: &str| el.get_attribute(name).is_some_and(|v| !v.trim().is_empty());
+    let labelled_by_for = el.get_attribute("id").is_some_and(|id| {
+        root.query_selector(&format!("label[for=\"{id}\"]"))
+            .ok()
+            .flatten()
+            .is_some()
+    });
+    let text = el
+        .text_content()
+        .is_some_and(|text| !text.trim().is_empty());
+    attr("aria-label") || attr("aria-labelledby") || labelled_by_for || text
+}
+
+#[wasm_bindgen_test]
+async fn message_list_is_a_live_log_of_articles() {
+    let root = render_fixture().await;
+
+    let log = root.query_selector("[role=\"log\"]").unwrap().unwrap();
+    assert_eq!(log.get_attribute("aria-live").as_deref(), Some("polite"));
+
+    let messages = all(&root, "[role=\"article\"]");
+    assert_eq!(messages.len(), 3);
+    // Only the newest message is in the tab order; arrows reach the rest
+    let tab_stops: Vec<_> = messages
+        .iter()
+        .map(|m| m.get_attribute("tabindex"))
+        .collect();
+    assert_eq!(
+        tab_stops,
+        vec![Some("-1".into()), Some("-1".into()), Some("0".into())]
+    );
+    assert!(messages.iter().all(|m| has_accessible_name(&root, m)));
+}
+
+#[wasm_bindgen_test]
+async fn controls_have_accessible_names() {
+    let root = render_fixture().await;
+
+    for control in all(
+        &root,
+        "button, textarea, select, input:not([aria-hidden=\"true\"])",
+    ) {
+        assert!(
+            has_accessible_name(&root, &control),
+            "unnamed control: {}",
+            control.outer_html()
+        );
+    }
+    for button in all(&root, "button") {
+        assert_eq!(
+            button.get_attribute("type").as_deref(),
+            Some("button"),
+            "button without explicit type: {}",
+            button.outer_html()
+        );
+    }
+}
+
+#[wasm_bindgen_test]
+async fn streaming_indicator_announces_status() {
+    let root = render_fixture().await;
+
+    let status = root.query_selector("[role=\"status\"]").unwrap().unwrap();
+    assert!(
+        status
+            .text_content()
+            .is_some_and(|text| text.contains("Generating response"))
+    );
+    for dot in all(&status, ".animate-pulse-dot") {
+        assert!(dot.class_list().contains("motion-reduce:animate-none"));
+    }
+}
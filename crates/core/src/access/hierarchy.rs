@@ -0,0 +1,143 @@
+//! Inherited permissions
+//!
+//! Grants on an object cover the objects beneath it, and grants to a group
+//! cover its members, so a handful of grants can stand in for one per
+//! subject and object:
+//!
+//! - a model qualified by its provider (`local/Model/openai/gpt-4o`) is
+//!   beneath the provider (`local/Provider/openai`)
+//! - every object is beneath the `*` of its kind (`local/Model/*`)
+//! - everything in an organization is beneath the organization
+//!   (`org:acme/System/*`), so its projects are too
+//! - everything is beneath the global wildcard (`local/System/*`)
+//!
+//! Groups are subjects named `group:<name>` that hold grants like any other
+//! subject; membership is stored by the state backend.
+
+use super::identity::{ObjectId, ObjectIdentity, ObjectKind, TargetNamespace};
+use super::permissions::Action;
+use crate::{Result, StateBackend};
+
+/// Prefix of the subject id a group's grants are stored under
+pub const GROUP_SUBJECT_PREFIX: &str = "group:";
+
+/// Subject id a group's grants are stored under
+pub fn group_subject(group: &str) -> String {
+    format!("{GROUP_SUBJECT_PREFIX}{group}")
+}
+
+impl ObjectIdentity {
+    /// Objects directly above this one
+    pub fn parents(&self) -> Vec<ObjectIdentity> {
+        let global = Self::wildcard();
+        if *self == global {
+            return Vec::new();
+        }
+
+        let mut parents = Vec::new();
+        if self.kind == ObjectKind::Model
+            && let Some((provider, _)) = self.id.split_once('/')
+        {
+            parents.push(Self {
+                namespace: self.namespace.clone(),
+                kind: ObjectKind::Provider,
+                id: ObjectId::new(provider),
+            });
+        }
+        if &*self.id != "*" {
+            parents.push(Self {
+                namespace: self.namespace.clone(),
+                kind: self.kind.clone(),
+                id: ObjectId::new("*"),
+            });
+        } else if let TargetNamespace::Organization(org) = &self.namespace
+            && self.kind != ObjectKind::System
+        {
+            parents.push(Self::organization(org.clone()));
+        } else {
+            parents.push(global);
+        }
+        parents
+    }
+
+    /// Every object whose grants cover this one, nearest first
+    pub fn ancestors(&self) -> Vec<ObjectIdentity> {
+        let mut ancestors: Vec<ObjectIdentity> = Vec::new();
+        let mut next = 0;
+        let mut pending = self.parents();
+        loop {
+            for parent in pending {
+                if !ancestors.contains(&parent) {
+                    ancestors.push(parent);
+                }
+            }
+            let Some(current) = ancestors.get(next) else {
+                return ancestors;
+            };
+            pending = current.parents();
+            next += 1;
+        }
+    }
+}
+
+/// Whether `subject_id`, or a group it belongs to, holds `action` on
+/// `object` or on an object above it
+pub async fn has_inherited_permission(
+    backend: &dyn StateBackend,
+    subject_id: &str,
+    action: &Action,
+    object: &ObjectIdentity,
+) -> Result<bool> {
+    let mut subjects = vec![subject_id.to_string()];
+    subjects.extend(
+        backend
+            .list_subject_groups(subject_id)
+            .await?
+            .iter()
+            .map(|group| group_subject(group)),
+    );
+
+    let mut objects = vec![object.clone()];
+    objects.extend(object.ancestors());
+
+    backend
+        .has_any_permission(&subjects, action, &objects)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(objects: &[ObjectIdentity]) -> Vec<String> {
+        objects.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_models_inherit_from_provider_and_kind() {
+        let model = ObjectIdentity::provider_model("openai", "gpt-4o");
+        assert_eq!(
+            names(&model.ancestors()),
+            [
+                "local/Provider/openai",
+                "local/Model/*",
+                "local/Provider/*",
+                "local/System/*",
+            ]
+        );
+        assert_eq!(
+            ObjectIdentity::from_string("local/Model/openai/gpt-4o").unwrap(),
+            model
+        );
+        assert!(ObjectIdentity::wildcard().ancestors().is_empty());
+    }
+
+    #[test]
+    fn test_projects_inherit_from_organization() {
+        let project = ObjectIdentity::org_project("acme", "web");
+        assert_eq!(
+            names(&project.ancestors()),
+            ["org:acme/Project/*", "org:acme/System/*", "local/System/*"]
+        );
+    }
+}
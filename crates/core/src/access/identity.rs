@@ -63,6 +63,7 @@ pub enum ObjectKind {
     Billing,
     System,
     Quota,
+    Project,
 }
 
 impl Display for ObjectKind {
@@ -132,6 +133,29 @@ impl ObjectIdentity {
         }
     }
 
+    /// Model served by a provider; grants on the provider cover it
+    pub fn provider_model(provider_id: impl Display, model_id: impl Display) -> Self {
+        Self::local_model(format!("{provider_id}/{model_id}"))
+    }
+
+    /// Project of an organization; grants on the organization cover it
+    pub fn org_project(org: impl Into<String>, project_id: impl Into<String>) -> Self {
+        Self {
+            namespace: TargetNamespace::Organization(org.into()),
+            kind: ObjectKind::Project,
+            id: ObjectId::new(project_id),
+        }
+    }
+
+    /// Everything in an organization
+    pub fn organization(org: impl Into<String>) -> Self {
+        Self {
+            namespace: TargetNamespace::Organization(org.into()),
+            kind: ObjectKind::System,
+            id: ObjectId::new("*"),
+        }
+    }
+
     pub fn local_provider(provider_id: impl Into<String>) -> Self {
         Self {
             namespace: TargetNamespace::Local,
@@ -156,9 +180,10 @@ impl ObjectIdentity {
         }
     }
 
-    /// Parse an object identity from a string format "namespace/kind/id"
+    /// Parse an object identity from a string format "namespace/kind/id".
+    /// The id may itself contain `/`, as in `provider/model`.
     pub fn from_string(s: &str) -> Result<Self, String> {
-        let parts: Vec<&str> = s.split('/').collect();
+        let parts: Vec<&str> = s.splitn(3, '/').collect();
         if parts.len() != 3 {
             return Err(format!(
                 "Invalid object format: expected namespace/kind/id, got {s}"
@@ -186,6 +211,7 @@ impl ObjectIdentity {
             "Billing" => ObjectKind::Billing,
             "System" => ObjectKind::System,
             "Quota" => ObjectKind::Quota,
+            "Project" => ObjectKind::Project,
             _ => return Err(format!("Invalid object kind: {}", parts[1])),
         };
 
@@ -1,7 +1,9 @@
+pub mod hierarchy;
 pub mod identity;
 pub mod permissions;
 pub mod policies;
 
+pub use hierarchy::{GROUP_SUBJECT_PREFIX, group_subject, has_inherited_permission};
 pub use identity::{
     Authentication, AuthenticationError, IdentityContext, ObjectId, ObjectIdentity, ObjectKind,
     SubjectIdentity, TargetNamespace,
@@ -0,0 +1,50 @@
+//! Storage for large artifacts
+//!
+//! Uploaded files, database backups and deduplicated bodies are kept in a
+//! [`BlobStore`], so they need not live on the daemon host: the store may
+//! be a local directory or an S3-compatible bucket. Keys are `/` separated
+//! paths relative to the root of the store.
+
+use crate::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A stored blob
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlobInfo {
+    pub key: String,
+    /// Size in bytes
+    pub size: u64,
+    /// When the blob was last written
+    pub modified: DateTime<Utc>,
+}
+
+/// Whether `key` is a relative path without empty, `.` or `..` segments,
+/// which every store can hold and none can resolve outside its root
+pub fn is_valid_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .split('/')
+            .all(|segment| !matches!(segment, "" | "." | ".."))
+        && !key.contains('\\')
+}
+
+/// Keyed storage of opaque byte blobs
+#[async_trait]
+pub trait BlobStore: Send + Sync {
+    /// Store `data` under `key`, replacing what was there
+    async fn put(&self, key: &str, data: Vec<u8>) -> Result<()>;
+
+    /// Contents of the blob under `key`, if there is one
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Size and last write of the blob under `key`, if there is one
+    async fn head(&self, key: &str) -> Result<Option<BlobInfo>>;
+
+    /// Remove the blob under `key`; removing a missing blob succeeds
+    async fn delete(&self, key: &str) -> Result<()>;
+
+    /// Blobs whose key starts with `prefix`, in no particular order
+    async fn list(&self, prefix: &str) -> Result<Vec<BlobInfo>>;
+}
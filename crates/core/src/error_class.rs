@@ -0,0 +1,309 @@
+//! Normalized classes of provider errors
+//!
+//! Providers describe the same failure in different ways: OpenAI puts a
+//! `code` in its error object, Anthropic an error `type`, Google a gRPC
+//! `status`, and some only a message. Classifying them once lets retries,
+//! circuit breakers, metrics and the client-facing error envelope agree on
+//! what went wrong without knowing which provider said it.
+
+use http::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::fmt;
+use std::time::Duration;
+
+/// Longest provider message kept in an [`UpstreamError`]
+const MAX_MESSAGE_CHARS: usize = 500;
+
+/// What kind of failure a provider reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorClass {
+    /// Too many requests or tokens for the account
+    RateLimited,
+    /// The provider rejected the key it was given
+    InvalidCredentials,
+    /// A safety or moderation filter blocked the prompt or completion
+    ContentFiltered,
+    /// The provider is temporarily over capacity
+    Overloaded,
+    /// The prompt and requested output do not fit the model's context
+    ContextLengthExceeded,
+    /// The provider does not know the model or endpoint
+    NotFound,
+    /// Any other problem with the request itself
+    InvalidRequest,
+    /// The provider did not answer in time
+    Timeout,
+    /// The provider could not be reached or a gateway in front of it failed
+    Unavailable,
+    /// Any other failure on the provider's side
+    Upstream,
+}
+
+impl ErrorClass {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorClass::RateLimited => "rate_limited",
+            ErrorClass::InvalidCredentials => "invalid_credentials",
+            ErrorClass::ContentFiltered => "content_filtered",
+            ErrorClass::Overloaded => "overloaded",
+            ErrorClass::ContextLengthExceeded => "context_length_exceeded",
+            ErrorClass::NotFound => "not_found",
+            ErrorClass::InvalidRequest => "invalid_request",
+            ErrorClass::Timeout => "timeout",
+            ErrorClass::Unavailable => "unavailable",
+            ErrorClass::Upstream => "upstream_error",
+        }
+    }
+
+    /// Whether sending the same request again may succeed
+    pub fn is_retryable(self) -> bool {
+        matches!(
+            self,
+            ErrorClass::RateLimited
+                | ErrorClass::Overloaded
+                | ErrorClass::Timeout
+                | ErrorClass::Unavailable
+        )
+    }
+
+    /// Whether the failure says something about the provider's health rather
+    /// than about the request. A rate limit only says the account is busy.
+    pub fn is_provider_fault(self) -> bool {
+        !matches!(
+            self,
+            ErrorClass::RateLimited
+                | ErrorClass::ContentFiltered
+                | ErrorClass::ContextLengthExceeded
+                | ErrorClass::NotFound
+                | ErrorClass::InvalidRequest
+        )
+    }
+
+    /// Status to answer the client with, given the provider's
+    pub fn status(self, upstream: StatusCode) -> StatusCode {
+        match self {
+            ErrorClass::Overloaded | ErrorClass::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+            ErrorClass::Timeout => StatusCode::GATEWAY_TIMEOUT,
+            ErrorClass::Upstream => StatusCode::BAD_GATEWAY,
+            _ if upstream.is_client_error() => upstream,
+            ErrorClass::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+            ErrorClass::InvalidCredentials => StatusCode::UNAUTHORIZED,
+            ErrorClass::NotFound => StatusCode::NOT_FOUND,
+            _ => StatusCode::BAD_REQUEST,
+        }
+    }
+
+    /// Classify a provider's error response from its status and body
+    pub fn classify(status: StatusCode, body: &str) -> Self {
+        let parsed = serde_json::from_str::<JsonValue>(body).ok();
+        let codes: Vec<String> = parsed
+            .iter()
+            .flat_map(|json| {
+                let error = &json["error"];
+                [
+                    &error["type"],
+                    &error["code"],
+                    &error["status"],
+                    &json["type"],
+                    &json["code"],
+                ]
+            })
+            .filter_map(JsonValue::as_str)
+            .map(str::to_ascii_lowercase)
+            .collect();
+        let has_code = |candidates: &[&str]| codes.iter().any(|c| candidates.contains(&c.as_str()));
+        let text = body.to_ascii_lowercase();
+        let mentions = |phrases: &[&str]| phrases.iter().any(|p| text.contains(p));
+
+        if has_code(&["context_length_exceeded", "string_above_max_length"])
+            || mentions(&[
+                "prompt is too long",
+                "maximum context length",
+                "context window",
+                "context length",
+            ])
+        {
+            ErrorClass::ContextLengthExceeded
+        } else if has_code(&["content_filter", "content_policy_violation", "safety"])
+            || mentions(&[
+                "content management policy",
+                "content filter",
+                "content_filter",
+            ])
+        {
+            ErrorClass::ContentFiltered
+        } else if status.as_u16() == 529 || has_code(&["overloaded_error"]) {
+            ErrorClass::Overloaded
+        } else if status == StatusCode::TOO_MANY_REQUESTS
+            || has_code(&[
+                "rate_limit_error",
+                "rate_limit_exceeded",
+                "resource_exhausted",
+                "insufficient_quota",
+            ])
+        {
+            ErrorClass::RateLimited
+        } else if matches!(status, StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN)
+            || has_code(&[
+                "authentication_error",
+                "permission_error",
+                "invalid_api_key",
+                "unauthenticated",
+                "permission_denied",
+            ])
+        {
+            ErrorClass::InvalidCredentials
+        } else if status == StatusCode::NOT_FOUND
+            || has_code(&["not_found_error", "model_not_found"])
+        {
+            ErrorClass::NotFound
+        } else if matches!(
+            status,
+            StatusCode::REQUEST_TIMEOUT | StatusCode::GATEWAY_TIMEOUT
+        ) {
+            ErrorClass::Timeout
+        } else if matches!(
+            status,
+            StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE
+        ) {
+            ErrorClass::Unavailable
+        } else if status.is_client_error() {
+            ErrorClass::InvalidRequest
+        } else {
+            ErrorClass::Upstream
+        }
+    }
+}
+
+impl fmt::Display for ErrorClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An error response from a provider, classified
+#[derive(Debug, Clone)]
+pub struct UpstreamError {
+    pub provider: String,
+    pub status: StatusCode,
+    pub class: ErrorClass,
+    /// The provider's own message, without the envelope around it
+    pub message: String,
+    /// The response body as the provider sent it
+    pub body: String,
+    /// How long the provider asked callers to wait before retrying
+    pub retry_after: Option<Duration>,
+}
+
+impl UpstreamError {
+    pub fn new(provider: impl Into<String>, status: StatusCode, body: impl Into<String>) -> Self {
+        let body = body.into();
+        let message = serde_json::from_str::<JsonValue>(&body)
+            .ok()
+            .and_then(|json| {
+                [&json["error"]["message"], &json["message"], &json["error"]]
+                    .into_iter()
+                    .find_map(|m| m.as_str().map(String::from))
+            })
+            .unwrap_or_else(|| body.trim().to_string());
+        Self {
+            provider: provider.into(),
+            status,
+            class: ErrorClass::classify(status, &body),
+            message: message.chars().take(MAX_MESSAGE_CHARS).collect(),
+            body,
+            retry_after: None,
+        }
+    }
+
+    pub fn with_retry_after(mut self, retry_after: Option<Duration>) -> Self {
+        self.retry_after = retry_after;
+        self
+    }
+
+    /// Status to answer the client with
+    pub fn client_status(&self) -> StatusCode {
+        self.class.status(self.status)
+    }
+}
+
+impl fmt::Display for UpstreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}: {}", self.provider, self.class, self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classify(status: u16, body: &str) -> ErrorClass {
+        ErrorClass::classify(StatusCode::from_u16(status).unwrap(), body)
+    }
+
+    #[test]
+    fn test_provider_errors_share_classes() {
+        let anthropic = |kind: &str, message: &str| {
+            format!(r#"{{"type":"error","error":{{"type":"{kind}","message":"{message}"}}}}"#)
+        };
+        let openai = |code: &str| format!(r#"{{"error":{{"message":"x","code":"{code}"}}}}"#);
+
+        assert_eq!(
+            classify(529, &anthropic("overloaded_error", "Overloaded")),
+            ErrorClass::Overloaded
+        );
+        assert_eq!(
+            classify(429, &openai("rate_limit_exceeded")),
+            ErrorClass::RateLimited
+        );
+        assert_eq!(
+            classify(400, r#"{"error":{"status":"RESOURCE_EXHAUSTED"}}"#),
+            ErrorClass::RateLimited
+        );
+        assert_eq!(
+            classify(401, &anthropic("authentication_error", "invalid x-api-key")),
+            ErrorClass::InvalidCredentials
+        );
+        assert_eq!(
+            classify(400, &openai("context_length_exceeded")),
+            ErrorClass::ContextLengthExceeded
+        );
+        assert_eq!(
+            classify(
+                400,
+                &anthropic("invalid_request_error", "prompt is too long: 210000 tokens")
+            ),
+            ErrorClass::ContextLengthExceeded
+        );
+        assert_eq!(
+            classify(400, &openai("content_filter")),
+            ErrorClass::ContentFiltered
+        );
+        assert_eq!(classify(400, "bad json"), ErrorClass::InvalidRequest);
+        assert_eq!(
+            classify(502, "<html>Bad Gateway</html>"),
+            ErrorClass::Unavailable
+        );
+        assert_eq!(classify(500, ""), ErrorClass::Upstream);
+    }
+
+    #[test]
+    fn test_upstream_error_extracts_message_and_status() {
+        let error = UpstreamError::new(
+            "anthropic",
+            StatusCode::from_u16(529).unwrap(),
+            r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#,
+        );
+        assert_eq!(error.message, "Overloaded");
+        assert_eq!(error.client_status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(error.class.is_retryable());
+        assert_eq!(error.to_string(), "anthropic overloaded: Overloaded");
+
+        let error = UpstreamError::new("openai", StatusCode::BAD_REQUEST, "plain text");
+        assert_eq!(error.message, "plain text");
+        assert_eq!(error.client_status(), StatusCode::BAD_REQUEST);
+        assert!(!error.class.is_provider_fault());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::error_class::{ErrorClass, UpstreamError};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -23,6 +24,9 @@ pub enum Error {
     #[error("Request rejected: {0}")]
     Rejected(http::StatusCode, String),
 
+    #[error("Upstream error: {0}")]
+    Upstream(UpstreamError),
+
     #[error("Redirect required: {0}")]
     Redirect(String),
 
@@ -51,6 +55,9 @@ pub enum Error {
     #[error("Quota exceeded: {0}")]
     QuotaExceeded(String),
 
+    #[error("Concurrent stream limit reached: {active} of {limit} in use")]
+    ConcurrencyLimit { limit: u32, active: u32 },
+
     #[error("All routes failed")]
     AllRoutesFailed,
 
@@ -63,6 +70,9 @@ pub enum Error {
     #[error("Invalid request: {0}")]
     InvalidRequest(String),
 
+    #[error("Response does not match the requested schema: {0}")]
+    StructuredOutput(String),
+
     #[error("Service unavailable: {0}")]
     ServiceUnavailable(String),
 
@@ -70,6 +80,18 @@ pub enum Error {
     InvalidConfig(String),
 }
 
+impl Error {
+    /// Normalized class of an error a provider caused, if it was one
+    pub fn class(&self) -> Option<ErrorClass> {
+        match self {
+            Error::Upstream(error) => Some(error.class),
+            Error::Rejected(status, message) => Some(ErrorClass::classify(*status, message)),
+            Error::ServiceUnavailable(_) => Some(ErrorClass::Unavailable),
+            _ => None,
+        }
+    }
+}
+
 pub type Result<T> = std::result::Result<T, Error>;
 
 // Cloudflare-specific error conversions
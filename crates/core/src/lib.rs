@@ -1,5 +1,7 @@
 pub mod access;
+pub mod blob;
 pub mod context;
+pub mod error_class;
 pub mod errors;
 pub mod inference;
 pub mod router;
@@ -17,13 +19,20 @@ pub mod tracing;
 #[cfg(any(test, feature = "tests"))]
 pub mod tests;
 
+pub use blob::{BlobInfo, BlobStore};
 pub use context::RequestContext;
+pub use error_class::{ErrorClass, UpstreamError};
 pub use errors::{Error, Result};
 pub use inference::InferenceBackend;
 pub use state::StateBackend;
 
 // Re-export types for convenience
 pub use types::{
-    ApiKey, Error as ProtoError, HookAction, HookResponse, Model, ModelType, Organization,
-    Provider, ProviderType, RequestHookContext, ResponseHookContext, TimeRange, UsageRecord, User,
+    ApiKey, AssistantObject, BlobCollection, Conversation, DataClass, Error as ProtoError,
+    Experiment, ExperimentMetric, ExperimentOutcome, ExperimentStatus, ExperimentVariant,
+    ExperimentVariantResults, Feedback, FeedbackSummary, HookAction, HookResponse, Model,
+    ModelType, Organization, PermissionDecision, PermissionDecisionFilter, PromptMessage,
+    PromptRender, PromptTemplate, PromptVariable, Provider, ProviderType, RequestHookContext,
+    ResponseHookContext, RoutingDecision, StoredResponse, TimeRange, UsageRecord, UsageSubject,
+    UsageTotals, User, UserDataDeletion, UserPreferences,
 };
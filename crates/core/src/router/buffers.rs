@@ -0,0 +1,218 @@
+//! Caps on response bytes held in memory
+//!
+//! Streaming responses pass through a chunk at a time, but non-streaming
+//! responses are read whole and coalesced responses are kept for replay.
+//! Each such response draws on a [`BufferBudget`]: a response larger than
+//! the per-response cap fails with 502, and one that would take the total
+//! across responses over its cap fails with 503. While the budget sheds,
+//! as it does under memory pressure, no new response may start buffering.
+//! Cap hits are counted in `buffer_cap_hits_total`, labelled by scope.
+
+use crate::tracing::metrics::{counter, gauge};
+use crate::{Error, Result};
+use http::StatusCode;
+use serde_json::Value as JsonValue;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+
+/// Budget shared by the router's sinks and services
+static SHARED: OnceLock<BufferBudget> = OnceLock::new();
+
+/// Byte caps on buffered responses; zero means no cap
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BufferLimits {
+    /// Bytes held across all responses
+    pub total_bytes: usize,
+    /// Bytes held for any one response
+    pub response_bytes: usize,
+}
+
+/// Tracks the bytes buffered across responses against [`BufferLimits`]
+#[derive(Debug, Default)]
+pub struct BufferBudget {
+    total_limit: AtomicUsize,
+    response_limit: AtomicUsize,
+    used: AtomicUsize,
+    shedding: AtomicBool,
+    cap_hits: AtomicU64,
+}
+
+impl BufferBudget {
+    pub fn new(limits: BufferLimits) -> Self {
+        let budget = Self::default();
+        budget.set_limits(limits);
+        budget
+    }
+
+    /// The budget shared by the router's sinks and services, uncapped until
+    /// limits are set
+    pub fn shared() -> &'static BufferBudget {
+        SHARED.get_or_init(BufferBudget::default)
+    }
+
+    pub fn set_limits(&self, limits: BufferLimits) {
+        self.total_limit
+            .store(limits.total_bytes, Ordering::Relaxed);
+        self.response_limit
+            .store(limits.response_bytes, Ordering::Relaxed);
+    }
+
+    pub fn limits(&self) -> BufferLimits {
+        BufferLimits {
+            total_bytes: self.total_limit.load(Ordering::Relaxed),
+            response_bytes: self.response_limit.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Bytes buffered now across responses
+    pub fn used(&self) -> usize {
+        self.used.load(Ordering::Relaxed)
+    }
+
+    /// Times a response hit a cap or was refused while shedding
+    pub fn cap_hits(&self) -> u64 {
+        self.cap_hits.load(Ordering::Relaxed)
+    }
+
+    /// Refuse new buffered responses, or accept them again
+    pub fn set_shedding(&self, shedding: bool) {
+        self.shedding.store(shedding, Ordering::Relaxed);
+    }
+
+    pub fn is_shedding(&self) -> bool {
+        self.shedding.load(Ordering::Relaxed)
+    }
+
+    /// Start buffering a response, unless the budget is shedding
+    pub fn buffer(&self) -> Result<ResponseBuffer<'_>> {
+        if self.is_shedding() {
+            self.hit("shed");
+            return Err(Error::Rejected(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "Not buffering responses while memory is short; retry or stream".to_string(),
+            ));
+        }
+        Ok(ResponseBuffer {
+            budget: self,
+            held: 0,
+        })
+    }
+
+    fn hit(&self, scope: &str) {
+        self.cap_hits.fetch_add(1, Ordering::Relaxed);
+        counter(&format!("buffer_cap_hits_total{{scope=\"{scope}\"}}")).increment();
+    }
+
+    fn release(&self, bytes: usize) {
+        let used = self.used.fetch_sub(bytes, Ordering::Relaxed) - bytes;
+        gauge("buffered_response_bytes").set(used as i64);
+    }
+}
+
+/// Bytes one response holds in a [`BufferBudget`], released when dropped
+#[derive(Debug)]
+pub struct ResponseBuffer<'a> {
+    budget: &'a BufferBudget,
+    held: usize,
+}
+
+impl ResponseBuffer<'_> {
+    /// Bytes held for this response
+    pub fn held(&self) -> usize {
+        self.held
+    }
+
+    /// Account for `bytes` more of this response
+    pub fn reserve(&mut self, bytes: usize) -> Result<()> {
+        let limits = self.budget.limits();
+        if limits.response_bytes > 0 && self.held + bytes > limits.response_bytes {
+            self.budget.hit("response");
+            warn!(
+                "Response exceeded the {} byte buffering cap",
+                limits.response_bytes
+            );
+            return Err(Error::Rejected(
+                StatusCode::BAD_GATEWAY,
+                format!(
+                    "Response is larger than the {} byte buffering cap; stream it instead",
+                    limits.response_bytes
+                ),
+            ));
+        }
+        let used = self.budget.used.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        if limits.total_bytes > 0 && used > limits.total_bytes {
+            self.budget.release(bytes);
+            self.budget.hit("total");
+            warn!(
+                "Buffered responses reached the {} byte cap",
+                limits.total_bytes
+            );
+            return Err(Error::Rejected(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "Too many responses are being buffered; retry shortly".to_string(),
+            ));
+        }
+        self.held += bytes;
+        gauge("buffered_response_bytes").set(used as i64);
+        Ok(())
+    }
+}
+
+impl Drop for ResponseBuffer<'_> {
+    fn drop(&mut self) {
+        if self.held > 0 {
+            self.budget.release(self.held);
+        }
+    }
+}
+
+/// Approximate size of `value` serialized, without serializing it
+pub fn json_size(value: &JsonValue) -> usize {
+    match value {
+        JsonValue::Null | JsonValue::Bool(_) => 5,
+        JsonValue::Number(_) => 8,
+        JsonValue::String(s) => s.len() + 2,
+        JsonValue::Array(items) => items.iter().map(|item| json_size(item) + 1).sum::<usize>() + 2,
+        JsonValue::Object(map) => {
+            map.iter()
+                .map(|(key, value)| key.len() + 4 + json_size(value))
+                .sum::<usize>()
+                + 2
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_caps_and_shedding() {
+        let budget = BufferBudget::new(BufferLimits {
+            total_bytes: 100,
+            response_bytes: 60,
+        });
+
+        let mut first = budget.buffer().unwrap();
+        first.reserve(50).unwrap();
+        assert!(matches!(
+            first.reserve(20),
+            Err(Error::Rejected(StatusCode::BAD_GATEWAY, _))
+        ));
+        let mut second = budget.buffer().unwrap();
+        assert!(matches!(
+            second.reserve(60),
+            Err(Error::Rejected(StatusCode::SERVICE_UNAVAILABLE, _))
+        ));
+        second.reserve(40).unwrap();
+        assert_eq!(budget.used(), 90);
+
+        drop(first);
+        assert_eq!(budget.used(), 40);
+        budget.set_shedding(true);
+        assert!(budget.buffer().is_err());
+        assert_eq!(budget.cap_hits(), 3);
+        drop(second);
+        assert_eq!(budget.used(), 0);
+    }
+}
@@ -0,0 +1,227 @@
+//! Fair sharing of sink concurrency
+//!
+//! A provider key allows only so many requests in flight, and one tenant's
+//! burst could otherwise take all of them. The executor admits each request
+//! to a sink through a [`ConcurrencyGate`]: a sink runs at most its limit of
+//! requests at once, and any one identity at most `identity_limit` of those,
+//! so other identities still get through. An identity waits for a share of
+//! its own before it waits for the sink, so it never holds sink capacity
+//! while queued behind itself. Requests over a limit wait up to
+//! `queue_timeout`, counted in `executor_queued{sink="..."}`. With spillover
+//! on, a request whose primary sink is saturated goes to the first fallback
+//! route with room instead of waiting.
+
+use crate::tracing::metrics::{Gauge, gauge};
+use crate::{Error, Result};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// How long a request waits for capacity by default
+const DEFAULT_QUEUE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Limits on requests in flight; unset limits do not apply
+#[derive(Debug, Clone)]
+pub struct ConcurrencyLimits {
+    /// Requests in flight per sink, by sink id
+    pub sink_limits: HashMap<String, usize>,
+    /// Requests in flight for sinks without a limit of their own
+    pub default_sink_limit: Option<usize>,
+    /// Requests one identity may have in flight at any one sink
+    pub identity_limit: Option<usize>,
+    /// Send requests to a fallback route with room rather than wait for a
+    /// saturated primary
+    pub spillover: bool,
+    /// Longest a request waits for capacity before it is refused
+    pub queue_timeout: Duration,
+}
+
+impl Default for ConcurrencyLimits {
+    fn default() -> Self {
+        Self {
+            sink_limits: HashMap::new(),
+            default_sink_limit: None,
+            identity_limit: None,
+            spillover: false,
+            queue_timeout: DEFAULT_QUEUE_TIMEOUT,
+        }
+    }
+}
+
+/// Capacity a request holds at a sink until its response ends
+#[derive(Debug)]
+pub struct ConcurrencyPermit {
+    _identity: Option<OwnedSemaphorePermit>,
+    _sink: Option<OwnedSemaphorePermit>,
+}
+
+/// Decrements a gauge when dropped, so requests that give up are not
+/// counted as queued
+struct Queued(Gauge);
+
+impl Drop for Queued {
+    fn drop(&mut self) {
+        self.0.decrement();
+    }
+}
+
+/// Semaphores for each sink and each identity at a sink
+pub struct ConcurrencyGate {
+    limits: ConcurrencyLimits,
+    sinks: Mutex<HashMap<String, Arc<Semaphore>>>,
+    identities: Mutex<HashMap<(String, String), Arc<Semaphore>>>,
+}
+
+impl ConcurrencyGate {
+    pub fn new(limits: ConcurrencyLimits) -> Self {
+        Self {
+            limits,
+            sinks: Mutex::new(HashMap::new()),
+            identities: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn limits(&self) -> &ConcurrencyLimits {
+        &self.limits
+    }
+
+    fn sink_limit(&self, sink_id: &str) -> Option<usize> {
+        self.limits
+            .sink_limits
+            .get(sink_id)
+            .copied()
+            .or(self.limits.default_sink_limit)
+    }
+
+    fn sink_semaphore(&self, sink_id: &str) -> Option<Arc<Semaphore>> {
+        let limit = self.sink_limit(sink_id)?;
+        let mut sinks = self.sinks.lock().expect("poisoned");
+        Some(
+            sinks
+                .entry(sink_id.to_string())
+                .or_insert_with(|| Arc::new(Semaphore::new(limit)))
+                .clone(),
+        )
+    }
+
+    fn identity_semaphore(&self, identity: &str, sink_id: &str) -> Option<Arc<Semaphore>> {
+        let limit = self.limits.identity_limit?;
+        let mut identities = self.identities.lock().expect("poisoned");
+        let key = (identity.to_string(), sink_id.to_string());
+        if !identities.contains_key(&key) {
+            // Forget identities with nothing in flight or queued
+            identities.retain(|_, semaphore| {
+                Arc::strong_count(semaphore) > 1 || semaphore.available_permits() < limit
+            });
+        }
+        Some(
+            identities
+                .entry(key)
+                .or_insert_with(|| Arc::new(Semaphore::new(limit)))
+                .clone(),
+        )
+    }
+
+    /// Admit a request from `identity` to `sink_id` if there is room now
+    pub fn try_admit(&self, identity: &str, sink_id: &str) -> Option<ConcurrencyPermit> {
+        let identity = match self.identity_semaphore(identity, sink_id) {
+            Some(semaphore) => Some(semaphore.try_acquire_owned().ok()?),
+            None => None,
+        };
+        let sink = match self.sink_semaphore(sink_id) {
+            Some(semaphore) => Some(semaphore.try_acquire_owned().ok()?),
+            None => None,
+        };
+        Some(ConcurrencyPermit {
+            _identity: identity,
+            _sink: sink,
+        })
+    }
+
+    /// Admit a request from `identity` to `sink_id`, waiting for room up to
+    /// the queue timeout
+    pub async fn admit(&self, identity: &str, sink_id: &str) -> Result<ConcurrencyPermit> {
+        if let Some(permit) = self.try_admit(identity, sink_id) {
+            return Ok(permit);
+        }
+        let identity_semaphore = self.identity_semaphore(identity, sink_id);
+        let sink_semaphore = self.sink_semaphore(sink_id);
+
+        let queued = gauge(&format!("executor_queued{{sink=\"{sink_id}\"}}"));
+        queued.increment();
+        let _queued = Queued(queued);
+        let acquire = async {
+            let identity = match &identity_semaphore {
+                Some(semaphore) => Some(semaphore.clone().acquire_owned().await),
+                None => None,
+            };
+            let sink = match &sink_semaphore {
+                Some(semaphore) => Some(semaphore.clone().acquire_owned().await),
+                None => None,
+            };
+            (identity, sink)
+        };
+        match tokio::time::timeout(self.limits.queue_timeout, acquire).await {
+            Ok((identity, sink)) => Ok(ConcurrencyPermit {
+                _identity: identity.and_then(Result::ok),
+                _sink: sink.and_then(Result::ok),
+            }),
+            Err(_) => {
+                // Report the limit the request was still waiting on
+                let (limit, semaphore) = match (&sink_semaphore, self.sink_limit(sink_id)) {
+                    (Some(semaphore), Some(limit)) if semaphore.available_permits() == 0 => {
+                        (limit, semaphore)
+                    }
+                    _ => match (&identity_semaphore, self.limits.identity_limit) {
+                        (Some(semaphore), Some(limit)) => (limit, semaphore),
+                        _ => return Err(Error::Internal("Admission timed out".to_string())),
+                    },
+                };
+                Err(Error::ConcurrencyLimit {
+                    limit: limit as u32,
+                    active: limit.saturating_sub(semaphore.available_permits()) as u32,
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_one_identity_cannot_take_a_whole_sink() {
+        let gate = ConcurrencyGate::new(ConcurrencyLimits {
+            default_sink_limit: Some(3),
+            identity_limit: Some(2),
+            queue_timeout: Duration::from_millis(20),
+            ..Default::default()
+        });
+
+        let a1 = gate.try_admit("alice", "openai").unwrap();
+        let _a2 = gate.try_admit("alice", "openai").unwrap();
+        assert!(gate.try_admit("alice", "openai").is_none());
+        assert!(matches!(
+            gate.admit("alice", "openai").await,
+            Err(Error::ConcurrencyLimit {
+                limit: 2,
+                active: 2
+            })
+        ));
+        // Alice's burst leaves room for Bob, and her share at other sinks
+        let _b1 = gate.try_admit("bob", "openai").unwrap();
+        assert!(gate.try_admit("alice", "anthropic").is_some());
+        assert!(matches!(
+            gate.admit("bob", "openai").await,
+            Err(Error::ConcurrencyLimit {
+                limit: 3,
+                active: 3
+            })
+        ));
+
+        drop(a1);
+        assert!(gate.admit("bob", "openai").await.is_ok());
+    }
+}
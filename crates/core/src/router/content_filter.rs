@@ -0,0 +1,194 @@
+//! What happens when a provider filters a response
+//!
+//! Sinks report a provider's content filter as
+//! [`StopReason::ContentFilter`] and in the response metadata, whatever
+//! protocol the provider speaks. A [`ContentFilterPolicy`] on the router's
+//! pipeline decides what follows: filtered responses can be logged, reported
+//! to a [`ContentFilterNotifier`], and, for non-streaming requests, sent again
+//! to a fallback sink the policy allows. A stream has reached the client by
+//! the time its filter shows, so streams are only logged and reported.
+//! Filtered responses are counted in
+//! `content_filter_total{sink="..."}` and retries in
+//! `content_filter_retries_total{from="...",to="..."}`.
+
+use super::plan::{Route, RoutingPlan};
+use super::sink::{RequestContext, ResponseStream};
+use super::types::{ResponseChunk, StopReason};
+use crate::tracing::metrics::counter;
+use futures::StreamExt;
+use serde::Serialize;
+use std::sync::Arc;
+
+/// A response a provider filtered
+#[derive(Debug, Clone, Serialize)]
+pub struct ContentFilterEvent {
+    pub correlation_id: String,
+    pub identity: String,
+    pub sink_id: String,
+    /// Model that answered, where the provider said
+    pub model: Option<String>,
+    /// Stop reason the provider gave
+    pub reason: String,
+    /// Sink the request was sent to again, if it was
+    pub retried_on: Option<String>,
+}
+
+/// Told of each filtered response
+pub trait ContentFilterNotifier: Send + Sync {
+    fn notify(&self, event: &ContentFilterEvent);
+}
+
+/// What to do with filtered responses
+#[derive(Clone, Default)]
+pub struct ContentFilterPolicy {
+    /// Log each filtered response
+    pub log: bool,
+    /// Fallback sinks a filtered non-streaming response may be sent again
+    /// to; empty never retries
+    pub retry_sinks: Vec<String>,
+    pub notifier: Option<Arc<dyn ContentFilterNotifier>>,
+}
+
+impl ContentFilterPolicy {
+    /// The first of the plan's fallbacks the policy allows retrying on
+    pub fn retry_route<'a>(&self, plan: &'a RoutingPlan, filtered: &str) -> Option<&'a Route> {
+        plan.fallback_routes
+            .iter()
+            .find(|route| route.sink_id != filtered && self.retry_sinks.contains(&route.sink_id))
+    }
+
+    /// Count, log and report a response `sink_id` filtered
+    pub fn report(&self, ctx: &RequestContext, sink_id: &str, retried_on: Option<&str>) {
+        let info = ctx.response.get();
+        let event = ContentFilterEvent {
+            correlation_id: ctx.correlation_id.to_string(),
+            identity: ctx.identity.id.clone(),
+            sink_id: sink_id.to_string(),
+            model: info.model,
+            reason: info
+                .content_filter
+                .unwrap_or_else(|| "content_filter".to_string()),
+            retried_on: retried_on.map(str::to_string),
+        };
+        counter(&format!("content_filter_total{{sink=\"{sink_id}\"}}")).increment();
+        if let Some(to) = retried_on {
+            counter(&format!(
+                "content_filter_retries_total{{from=\"{sink_id}\",to=\"{to}\"}}"
+            ))
+            .increment();
+        }
+        if self.log {
+            match retried_on {
+                Some(to) => warn!(
+                    "Sink {sink_id} filtered response {} ({}); retrying on {to}",
+                    event.correlation_id, event.reason
+                ),
+                None => warn!(
+                    "Sink {sink_id} filtered response {} ({})",
+                    event.correlation_id, event.reason
+                ),
+            }
+        }
+        if let Some(notifier) = &self.notifier {
+            notifier.notify(&event);
+        }
+    }
+
+    /// Report `stream` under the policy if it stops for a content filter
+    pub fn watch(
+        self: Arc<Self>,
+        stream: ResponseStream,
+        ctx: RequestContext,
+        sink_id: String,
+    ) -> ResponseStream {
+        Box::pin(stream.inspect(move |item| {
+            if let Ok(ResponseChunk::Stop {
+                reason: StopReason::ContentFilter,
+                ..
+            }) = item
+            {
+                self.report(&ctx, &sink_id, None);
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::access::SubjectIdentity;
+    use crate::router::sink::RouterIdentityContext;
+    use crate::router::types::RetryConfig;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<ContentFilterEvent>>);
+
+    impl ContentFilterNotifier for Recorder {
+        fn notify(&self, event: &ContentFilterEvent) {
+            self.0.lock().unwrap().push(event.clone());
+        }
+    }
+
+    fn route(sink_id: &str) -> Route {
+        Route {
+            sink_id: sink_id.to_string(),
+            protocol_conversion: None,
+            timeout: Duration::from_secs(1),
+            retry_config: RetryConfig::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retries_only_on_allowed_fallbacks_and_reports_streams() {
+        let recorder = Arc::new(Recorder::default());
+        let policy = Arc::new(ContentFilterPolicy {
+            log: false,
+            retry_sinks: vec!["azure".to_string(), "anthropic".to_string()],
+            notifier: Some(recorder.clone()),
+        });
+        let ctx = RequestContext {
+            identity: SubjectIdentity::new("user-1", "test", RouterIdentityContext::default()),
+            correlation_id: crate::tracing::CorrelationId::new(),
+            headers: Default::default(),
+            query: None,
+            trace_id: None,
+            metadata: Default::default(),
+            response: Default::default(),
+            extensions: Default::default(),
+        };
+        let plan = RoutingPlan::new(
+            ctx.clone(),
+            route("openai"),
+            vec![route("mistral"), route("anthropic"), route("azure")],
+        );
+        assert_eq!(
+            policy.retry_route(&plan, "openai").unwrap().sink_id,
+            "anthropic"
+        );
+        assert!(
+            ContentFilterPolicy::default()
+                .retry_route(&plan, "openai")
+                .is_none()
+        );
+
+        ctx.response
+            .publish(|info| info.content_filter = Some("refusal".to_string()));
+        let chunks = vec![Ok(ResponseChunk::Stop {
+            reason: StopReason::ContentFilter,
+            error: None,
+            cost: None,
+        })];
+        let stream: ResponseStream = Box::pin(futures::stream::iter(chunks));
+        let _: Vec<_> = policy
+            .watch(stream, ctx, "openai".to_string())
+            .collect()
+            .await;
+        let events = recorder.0.lock().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].sink_id, "openai");
+        assert_eq!(events[0].reason, "refusal");
+        assert_eq!(events[0].retried_on, None);
+    }
+}
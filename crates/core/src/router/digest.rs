@@ -0,0 +1,99 @@
+//! Canonical hashes of requests
+//!
+//! Object keys are hashed in sorted order, so two bodies that differ only in
+//! key order hash the same however they were serialized. Duplicate
+//! suppression keys requests by [`request_hash`], and provenance blocks
+//! record [`json_digest`] of the request they answered.
+
+use super::sink::RequestContext;
+use super::types::Protocol;
+use serde_json::Value as JsonValue;
+use sha2::{Digest, Sha256};
+
+/// Hex SHA-256 of `value` in canonical form
+pub fn json_digest(value: &JsonValue) -> String {
+    let mut hasher = Sha256::new();
+    hash_canonical(&mut hasher, value);
+    format!("{:x}", hasher.finalize())
+}
+
+/// Deterministic hash of a request from an identity
+pub fn request_hash(ctx: &RequestContext, protocol: Protocol, request: &JsonValue) -> String {
+    let identity = &ctx.identity;
+    let protocol = protocol.to_string();
+    let mut hasher = Sha256::new();
+    for part in [
+        identity.id.as_str(),
+        identity.context.user_id.as_deref().unwrap_or_default(),
+        identity.context.api_key_hash.as_deref().unwrap_or_default(),
+        protocol.as_str(),
+    ] {
+        hasher.update(part.as_bytes());
+        hasher.update([0]);
+    }
+    hash_canonical(&mut hasher, request);
+    format!("{:x}", hasher.finalize())
+}
+
+fn hash_canonical(hasher: &mut Sha256, value: &JsonValue) {
+    match value {
+        JsonValue::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by_key(|(key, _)| *key);
+            hasher.update(b"{");
+            for (key, value) in entries {
+                hasher.update(JsonValue::String(key.clone()).to_string().as_bytes());
+                hasher.update(b":");
+                hash_canonical(hasher, value);
+                hasher.update(b",");
+            }
+            hasher.update(b"}");
+        }
+        JsonValue::Array(items) => {
+            hasher.update(b"[");
+            for item in items {
+                hash_canonical(hasher, item);
+                hasher.update(b",");
+            }
+            hasher.update(b"]");
+        }
+        other => hasher.update(other.to_string().as_bytes()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::access::SubjectIdentity;
+    use crate::router::sink::RouterIdentityContext;
+    use serde_json::json;
+
+    fn ctx(user: &str) -> RequestContext {
+        RequestContext {
+            identity: SubjectIdentity::new(user, "test", RouterIdentityContext::default()),
+            correlation_id: crate::tracing::CorrelationId::new(),
+            headers: Default::default(),
+            query: None,
+            trace_id: None,
+            metadata: Default::default(),
+            response: Default::default(),
+            extensions: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_hash_ignores_key_order_but_not_identity() {
+        let a = json!({"model": "m", "messages": [{"role": "user", "content": "hi"}]});
+        let b = json!({"messages": [{"content": "hi", "role": "user"}], "model": "m"});
+        let hash = |user, request| request_hash(&ctx(user), Protocol::OpenAIChat, request);
+
+        assert_eq!(hash("alice", &a), hash("alice", &b));
+        assert_ne!(hash("alice", &a), hash("bob", &a));
+        assert_ne!(
+            hash("alice", &a),
+            request_hash(&ctx("alice"), Protocol::Anthropic, &a)
+        );
+        assert_eq!(json_digest(&a), json_digest(&b));
+        assert_ne!(json_digest(&a), hash("alice", &a));
+    }
+}
@@ -1,18 +1,113 @@
+use super::concurrency::{ConcurrencyGate, ConcurrencyPermit};
+use super::content_filter::ContentFilterPolicy;
 use super::plan::{Route, RoutingPlan};
 use super::registry::SinkRegistry;
-use super::sink::{RequestContext, Sink};
-use super::types::{RequestStream, ResponseChunk, StopReason};
-use crate::Result;
+use super::response_info::ResponseMetadata;
+use super::sink::{RequestContext, Sink, in_span, parse_content, with_headers};
+use super::structured;
+use super::timings::Timings;
+use super::types::{RequestStream, ResponseChunk, RetryConfig, StopReason};
+use crate::tracing::metrics::counter;
+use crate::tracing::prelude::Instrument;
+use crate::{Error, ErrorClass, Result};
+use futures::{StreamExt, TryStreamExt};
+use serde_json::Value as JsonValue;
 use std::sync::Arc;
-use std::time::Duration;
+use std::task::Poll;
+use std::time::{Duration, Instant};
+
+/// Counter of sink errors, labelled by class
+fn error_metric(class: ErrorClass) -> String {
+    format!("sink_errors_total{{class=\"{class}\"}}")
+}
+
+/// Publish how long `stream` takes from now until it ends
+fn timed(
+    stream: super::sink::ResponseStream,
+    metadata: ResponseMetadata,
+) -> super::sink::ResponseStream {
+    let started = Instant::now();
+    let end = futures::stream::poll_fn(move |_| {
+        metadata.publish(|info| info.timings.stream = Some(started.elapsed()));
+        Poll::Ready(None)
+    });
+    Box::pin(stream.chain(end))
+}
+
+/// Keep `permit` until `stream` ends or is dropped
+fn holding(
+    stream: super::sink::ResponseStream,
+    permit: Option<ConcurrencyPermit>,
+) -> super::sink::ResponseStream {
+    match permit {
+        Some(permit) => Box::pin(stream.map(move |item| {
+            let _held = &permit;
+            item
+        })),
+        None => stream,
+    }
+}
 
 pub struct PlanExecutor {
     sink_registry: Arc<SinkRegistry>,
+    gate: Option<Arc<ConcurrencyGate>>,
+    content_filter: Option<Arc<ContentFilterPolicy>>,
 }
 
 impl PlanExecutor {
     pub fn new(sink_registry: Arc<SinkRegistry>) -> Self {
-        Self { sink_registry }
+        Self {
+            sink_registry,
+            gate: None,
+            content_filter: None,
+        }
+    }
+
+    /// Handle responses providers filter under `policy`
+    pub fn with_content_filter(mut self, policy: Option<Arc<ContentFilterPolicy>>) -> Self {
+        self.content_filter = policy;
+        self
+    }
+
+    /// Admit requests to sinks through `gate`
+    pub fn with_gate(mut self, gate: Option<Arc<ConcurrencyGate>>) -> Self {
+        self.gate = gate;
+        self
+    }
+
+    /// The route to run the plan on and the capacity it holds there: the
+    /// primary, or with spillover the first fallback with room while the
+    /// primary is saturated
+    async fn admit<'a>(
+        &self,
+        plan: &'a RoutingPlan,
+    ) -> Result<(&'a Route, Option<ConcurrencyPermit>)> {
+        let primary = &plan.primary_route;
+        let Some(gate) = &self.gate else {
+            return Ok((primary, None));
+        };
+        let identity = plan.context.identity.id.as_str();
+        if let Some(permit) = gate.try_admit(identity, &primary.sink_id) {
+            return Ok((primary, Some(permit)));
+        }
+        if gate.limits().spillover {
+            for route in &plan.fallback_routes {
+                if let Some(permit) = gate.try_admit(identity, &route.sink_id) {
+                    counter(&format!(
+                        "executor_spillover_total{{from=\"{}\",to=\"{}\"}}",
+                        primary.sink_id, route.sink_id
+                    ))
+                    .increment();
+                    debug!(
+                        "Sink {} is saturated, spilling over to {}",
+                        primary.sink_id, route.sink_id
+                    );
+                    return Ok((route, Some(permit)));
+                }
+            }
+        }
+        let permit = gate.admit(identity, &primary.sink_id).await?;
+        Ok((primary, Some(permit)))
     }
 
     pub async fn execute(
@@ -20,24 +115,97 @@ impl PlanExecutor {
         plan: RoutingPlan,
         request: RequestStream,
     ) -> Result<super::sink::ResponseStream> {
-        let result = self
-            .execute_route(&plan.context, request, &plan.primary_route)
-            .await;
+        let (route, permit) = self.admit(&plan).await?;
+        let result = match &self.content_filter {
+            Some(policy) => {
+                self.execute_filtered(policy, &plan, request, route, permit)
+                    .await
+            }
+            None => self
+                .execute_route(&plan.context, request, route)
+                .await
+                .map(|stream| (holding(stream, permit), route.sink_id.clone())),
+        };
+        let result = result.map(|(stream, sink_id)| {
+            // Spillover and retries may serve from other than the primary
+            plan.context
+                .response
+                .publish(|info| info.sink_id = Some(sink_id.clone()));
+            match &self.content_filter {
+                Some(policy) => policy.clone().watch(stream, plan.context.clone(), sink_id),
+                None => stream,
+            }
+        });
         match result {
-            Ok(stream) => Ok(stream),
+            Ok(stream) => {
+                let headers = plan.context.response.get().headers();
+                if headers.is_empty() {
+                    return Ok(stream);
+                }
+                Ok(with_headers(stream, headers).await)
+            }
             Err(primary_err) => {
                 #[cfg(feature = "tracing")]
                 {
-                    warn!(
-                        "Primary route failed: {} - {}",
-                        plan.primary_route.sink_id, primary_err
-                    );
+                    warn!("Primary route failed: {} - {}", route.sink_id, primary_err);
                 }
                 Err(primary_err)
             }
         }
     }
 
+    /// Run the plan on `route`, and once more on a fallback the policy
+    /// allows if the whole response came back filtered. Returns the stream,
+    /// holding the capacity of the sink it came from, and that sink.
+    async fn execute_filtered(
+        &self,
+        policy: &ContentFilterPolicy,
+        plan: &RoutingPlan,
+        request: RequestStream,
+        route: &Route,
+        permit: Option<ConcurrencyPermit>,
+    ) -> Result<(super::sink::ResponseStream, String)> {
+        let ctx = &plan.context;
+        if policy.retry_sinks.is_empty() {
+            let stream = self.execute_route(ctx, request, route).await?;
+            return Ok((holding(stream, permit), route.sink_id.clone()));
+        }
+        // Buffer the request so it can be sent again
+        let protocol = request.protocol();
+        let chunks: Vec<JsonValue> = request.try_collect().await?;
+        let replay = |chunks: Vec<JsonValue>| {
+            RequestStream::new(
+                protocol,
+                Box::pin(futures::stream::iter(chunks.into_iter().map(Ok))),
+            )
+        };
+
+        let stream = self
+            .execute_route(ctx, replay(chunks.clone()), route)
+            .await?;
+        // Sinks read whole responses before returning, so a filter on one
+        // is known by now; a stream's shows only once it has been sent
+        let Some(retry) = ctx
+            .response
+            .get()
+            .content_filter
+            .and_then(|_| policy.retry_route(plan, &route.sink_id))
+        else {
+            return Ok((holding(stream, permit), route.sink_id.clone()));
+        };
+        policy.report(ctx, &route.sink_id, Some(&retry.sink_id));
+        drop(stream);
+        drop(permit);
+        // The retry takes capacity at its own sink like any other request
+        let permit = match &self.gate {
+            Some(gate) => Some(gate.admit(&ctx.identity.id, &retry.sink_id).await?),
+            None => None,
+        };
+        ctx.response.publish(|info| info.content_filter = None);
+        let stream = self.execute_route(ctx, replay(chunks), retry).await?;
+        Ok((holding(stream, permit), retry.sink_id.clone()))
+    }
+
     async fn execute_route(
         &self,
         ctx: &RequestContext,
@@ -57,30 +225,122 @@ impl PlanExecutor {
             ));
         }
 
-        self.execute_with_retries(ctx, sink, request, &route.retry_config, route.timeout)
+        // Emulate JSON schema output for sinks that cannot enforce it
+        if !sink
+            .describe()
             .await
+            .capabilities
+            .supports_structured_output
+        {
+            let (request, emulation) = structured::prepare_request(request).await?;
+            let stream = self
+                .execute_with_retries(
+                    ctx,
+                    &route.sink_id,
+                    sink,
+                    request,
+                    &route.retry_config,
+                    route.timeout,
+                )
+                .await?;
+            return Ok(match emulation {
+                Some((format, client_streaming)) => {
+                    structured::enforce_response(parse_content(stream), format, client_streaming)
+                }
+                None => stream,
+            });
+        }
+
+        self.execute_with_retries(
+            ctx,
+            &route.sink_id,
+            sink,
+            request,
+            &route.retry_config,
+            route.timeout,
+        )
+        .await
     }
 
+    /// Send the request to a sink, sending it again after errors whose
+    /// class says a retry may succeed
     async fn execute_with_retries(
         &self,
         ctx: &RequestContext,
+        sink_id: &str,
         sink: Arc<dyn Sink>,
         request: RequestStream,
-        _retry_config: &super::types::RetryConfig,
+        retry_config: &RetryConfig,
         timeout: Duration,
     ) -> Result<super::sink::ResponseStream> {
-        match tokio::time::timeout(timeout, sink.execute(ctx, request)).await {
-            Ok(Ok(stream)) => Ok(stream),
-            Ok(Err(err)) => Err(err),
-            Err(_) => {
-                let chunk = ResponseChunk::Stop {
-                    reason: StopReason::Timeout,
-                    error: Some("Request timed out".to_string()),
-                    cost: None,
-                };
-                let stream = futures::stream::once(async move { Ok(chunk) });
-                Ok(Box::pin(stream))
+        let dispatched = Instant::now();
+        // Buffer the request so it can be sent more than once
+        let protocol = request.protocol();
+        let chunks: Vec<JsonValue> = request.try_collect().await?;
+
+        let mut attempt = 1;
+        loop {
+            ctx.response.publish(|info| {
+                info.sink_id = Some(sink_id.to_string());
+                info.attempts = attempt;
+            });
+            let request = RequestStream::new(
+                protocol,
+                Box::pin(futures::stream::iter(chunks.clone().into_iter().map(Ok))),
+            );
+            let span = info_span!("sink.execute", sink_id, attempt);
+            let sent = Instant::now();
+            let outcome = tokio::time::timeout(timeout, sink.execute(ctx, request))
+                .instrument(span.clone())
+                .await;
+            // Passive health reflects this outcome; push it to the index
+            self.sink_registry
+                .publish_health(sink_id, sink.probe().await);
+            let err = match outcome {
+                Ok(Ok(stream)) => {
+                    ctx.response.publish(|info| {
+                        Timings::add(&mut info.timings.queue, sent - dispatched);
+                        info.timings.upstream_ttfb = Some(sent.elapsed());
+                    });
+                    return Ok(in_span(timed(stream, ctx.response.clone()), span));
+                }
+                Ok(Err(err)) => err,
+                Err(_) => {
+                    counter(&error_metric(ErrorClass::Timeout)).increment();
+                    let chunk = ResponseChunk::Stop {
+                        reason: StopReason::Timeout,
+                        error: Some("Request timed out".to_string()),
+                        cost: None,
+                    };
+                    let stream = futures::stream::once(async move { Ok(chunk) });
+                    return Ok(Box::pin(stream));
+                }
+            };
+
+            let Some(class) = err.class() else {
+                return Err(err);
+            };
+            counter(&error_metric(class)).increment();
+            if !class.is_retryable() || attempt >= retry_config.max_attempts {
+                return Err(err);
+            }
+            let retry_after = match &err {
+                Error::Upstream(upstream) => upstream.retry_after,
+                _ => None,
+            };
+            let delay = retry_after
+                .unwrap_or_default()
+                .max(retry_config.delay(attempt))
+                .min(retry_config.max_delay);
+            #[cfg(feature = "tracing")]
+            {
+                warn!(
+                    "Sink {sink_id} failed with {class}, retrying in {}ms: {err}",
+                    delay.as_millis()
+                );
             }
+            tokio::time::sleep(delay).await;
+            attempt += 1;
         }
     }
 }
@@ -0,0 +1,186 @@
+//! Typed values attached to a request
+//!
+//! [`RequestContext::metadata`](super::sink::RequestContext::metadata) holds
+//! strings under string keys, which suits values that end up in usage
+//! records. Middleware that hand each other richer values use the request's
+//! [`Extensions`] instead, keyed by type, so a value's name and shape are
+//! checked by the compiler. Define a type of your own for values only your
+//! middleware reads; the ones below are set by the router and the HTTP
+//! layer and are stable for anyone to read.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+use std::net::IpAddr;
+
+/// Address of the client that sent the request, when the server knows it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientIp(pub IpAddr);
+
+/// Labels attached to the request, e.g. to group it in reports. Middleware
+/// add to them with [`Extensions::get_or_insert_default`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RequestTags(pub Vec<String>);
+
+/// Tokens the prompt is estimated at, set once rewriters have run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EstimatedTokens(pub u64);
+
+/// Sink the request was routed to, set before middleware run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectedSink(pub String);
+
+/// Limits of the requested model at the [`SelectedSink`], set before
+/// middleware run when the sink reports them
+pub use super::types::ModelLimits;
+
+/// Pricing of the [`SelectedSink`], set before middleware run when the sink
+/// has any
+pub use super::types::CostStructure;
+
+/// Values of any type that is `Clone + Debug + Send + Sync`, at most one of
+/// each type
+#[derive(Clone, Default)]
+pub struct Extensions(HashMap<TypeId, Box<dyn Extension>>);
+
+impl Extensions {
+    /// Attach `value`, returning the one of its type it replaces
+    pub fn insert<T: Clone + fmt::Debug + Send + Sync + 'static>(&mut self, value: T) -> Option<T> {
+        self.0
+            .insert(TypeId::of::<T>(), Box::new(value))
+            .and_then(|previous| previous.into_any().downcast().ok())
+            .map(|previous| *previous)
+    }
+
+    /// The value of type `T`, if one is attached
+    pub fn get<T: 'static>(&self) -> Option<&T> {
+        self.0
+            .get(&TypeId::of::<T>())
+            .and_then(|value| value.as_any().downcast_ref())
+    }
+
+    /// The value of type `T` to change in place, if one is attached
+    pub fn get_mut<T: 'static>(&mut self) -> Option<&mut T> {
+        self.0
+            .get_mut(&TypeId::of::<T>())
+            .and_then(|value| value.as_any_mut().downcast_mut())
+    }
+
+    /// The value of type `T`, attaching its default first if there is none
+    pub fn get_or_insert_default<T: Clone + Default + fmt::Debug + Send + Sync + 'static>(
+        &mut self,
+    ) -> &mut T {
+        self.0
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(T::default()))
+            .as_any_mut()
+            .downcast_mut()
+            .expect("Extensions are keyed by their type")
+    }
+
+    /// Detach the value of type `T`
+    pub fn remove<T: 'static>(&mut self) -> Option<T> {
+        self.0
+            .remove(&TypeId::of::<T>())
+            .and_then(|value| value.into_any().downcast().ok())
+            .map(|value| *value)
+    }
+
+    /// Whether a value of type `T` is attached
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.0.contains_key(&TypeId::of::<T>())
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Debug for Extensions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set()
+            .entries(self.0.values().map(|value| value.as_debug()))
+            .finish()
+    }
+}
+
+/// What [`Extensions`] needs of a value to store it
+trait Extension: Any + Send + Sync {
+    fn clone_box(&self) -> Box<dyn Extension>;
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
+    fn as_debug(&self) -> &dyn fmt::Debug;
+}
+
+impl<T: Clone + fmt::Debug + Send + Sync + 'static> Extension for T {
+    fn clone_box(&self) -> Box<dyn Extension> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+
+    fn as_debug(&self) -> &dyn fmt::Debug {
+        self
+    }
+}
+
+impl Clone for Box<dyn Extension> {
+    fn clone(&self) -> Self {
+        (**self).clone_box()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_values_are_keyed_by_type() {
+        let mut extensions = Extensions::default();
+        assert_eq!(extensions.insert(EstimatedTokens(10)), None);
+        assert_eq!(
+            extensions.insert(EstimatedTokens(12)),
+            Some(EstimatedTokens(10))
+        );
+        extensions
+            .get_or_insert_default::<RequestTags>()
+            .0
+            .push("batch".to_string());
+        extensions
+            .get_or_insert_default::<RequestTags>()
+            .0
+            .push("eval".to_string());
+
+        let copy = extensions.clone();
+        extensions.get_mut::<EstimatedTokens>().unwrap().0 += 1;
+        assert_eq!(copy.get::<EstimatedTokens>(), Some(&EstimatedTokens(12)));
+        assert_eq!(
+            extensions.get::<EstimatedTokens>(),
+            Some(&EstimatedTokens(13))
+        );
+        assert_eq!(
+            copy.get::<RequestTags>().unwrap().0,
+            ["batch".to_string(), "eval".to_string()]
+        );
+        assert!(!copy.contains::<SelectedSink>());
+
+        assert_eq!(extensions.remove::<RequestTags>().unwrap().0.len(), 2);
+        assert_eq!(extensions.len(), 1);
+        assert!(format!("{extensions:?}").contains("EstimatedTokens(13)"));
+    }
+}
@@ -1,32 +1,54 @@
-//! SinkIndex: caller-managed snapshots of sink descriptions and health
+//! SinkIndex: snapshots of sink descriptions and health for routing
+//!
+//! Routing reads the snapshots lock-free. Writers replace the whole map, and
+//! [`SinkIndex::follow`] keeps it in step with a [`SinkRegistry`] by applying
+//! its registration and health events as they are published.
 
+use arc_swap::ArcSwap;
 use tokio::sync::RwLock;
+use tokio::sync::broadcast::error::RecvError;
 
-use super::registry::SinkRegistry;
+use super::registry::{RegistryEvent, SinkRegistry};
 use super::sink::SinkDescription;
 use super::types::SinkHealth;
-use std::collections::HashMap;
+use crate::tracing::metrics::{counter, gauge};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::future::Future;
 use std::sync::Arc;
 
+/// Number of health samples kept per sink
+const HISTORY_LEN: usize = 30;
+
 /// Snapshot of a sink's description and health
 #[derive(Clone, Debug)]
 pub struct SinkSnapshot {
     pub description: SinkDescription,
     pub health: SinkHealth,
     pub updated_at: chrono::DateTime<chrono::Utc>,
+    /// Disabled by an operator; skipped by routing regardless of health
+    pub disabled: bool,
+}
+
+/// Point-in-time health reading, recorded on every snapshot update
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HealthSample {
+    pub at: chrono::DateTime<chrono::Utc>,
+    pub healthy: bool,
+    pub latency_ms: Option<u64>,
 }
 
-/// Caller-managed index of sink snapshots for fast routing
+/// Index of sink snapshots for fast routing
 #[derive(Default, Debug, Clone)]
 pub struct SinkIndex {
-    inner: Arc<RwLock<HashMap<String, SinkSnapshot>>>,
+    inner: Arc<ArcSwap<HashMap<String, SinkSnapshot>>>,
+    disabled: Arc<RwLock<HashSet<String>>>,
+    history: Arc<RwLock<HashMap<String, VecDeque<HealthSample>>>>,
 }
 
 impl SinkIndex {
     pub fn new() -> Self {
-        Self {
-            inner: Arc::new(RwLock::new(HashMap::new())),
-        }
+        Self::default()
     }
 
     /// Set or update a snapshot for a sink ID (URL)
@@ -36,33 +58,123 @@ impl SinkIndex {
         description: SinkDescription,
         health: SinkHealth,
     ) {
-        let mut guard = self.inner.write().await;
-        guard.insert(
-            sink_id,
-            SinkSnapshot {
-                description,
-                health,
-                updated_at: chrono::Utc::now(),
-            },
-        );
+        let updated_at = chrono::Utc::now();
+        {
+            let mut history = self.history.write().await;
+            let samples = history.entry(sink_id.clone()).or_default();
+            if samples.len() == HISTORY_LEN {
+                samples.pop_front();
+            }
+            samples.push_back(HealthSample {
+                at: updated_at,
+                healthy: health.healthy,
+                latency_ms: health.latency_ms,
+            });
+        }
+        let disabled = self.is_disabled(&sink_id).await;
+        let snapshot = SinkSnapshot {
+            description,
+            health,
+            updated_at,
+            disabled,
+        };
+        self.update(|map| {
+            map.insert(sink_id.clone(), snapshot.clone());
+        });
+    }
+
+    /// Record a health probe result for a sink already in the index
+    pub async fn set_health(&self, sink_id: &str, health: SinkHealth) -> bool {
+        let Some(snapshot) = self.inner.load().get(sink_id).cloned() else {
+            return false;
+        };
+        self.set_snapshot(sink_id.to_string(), snapshot.description, health)
+            .await;
+        true
     }
 
     /// Remove a snapshot
     pub async fn remove(&self, sink_id: &str) {
-        let mut guard = self.inner.write().await;
-        guard.remove(sink_id);
+        self.update(|map| {
+            map.remove(sink_id);
+        });
+        self.history.write().await.remove(sink_id);
+    }
+
+    /// Replace the snapshot map with an edited copy
+    fn update(&self, edit: impl Fn(&mut HashMap<String, SinkSnapshot>)) {
+        self.inner.rcu(|map| {
+            let mut map = HashMap::clone(map);
+            edit(&mut map);
+            map
+        });
+        self.export_metrics();
+    }
+
+    /// Lock-free view of every snapshot, as routing sees it
+    pub fn snapshot(&self) -> Arc<HashMap<String, SinkSnapshot>> {
+        self.inner.load_full()
+    }
+
+    /// Age of the oldest snapshot, i.e. how far behind the index may be
+    pub fn staleness(&self) -> Option<chrono::Duration> {
+        let oldest = self.inner.load().values().map(|s| s.updated_at).min()?;
+        Some(chrono::Utc::now() - oldest)
+    }
+
+    /// Publish index size and freshness. Staleness is the scrape time minus
+    /// the oldest update timestamp.
+    fn export_metrics(&self) {
+        let map = self.inner.load();
+        counter("sink_index_updates_total").increment();
+        gauge("sink_index_snapshots").set(map.len() as i64);
+        if let Some(oldest) = map.values().map(|s| s.updated_at).min() {
+            gauge("sink_index_oldest_update_timestamp_seconds").set(oldest.timestamp());
+        }
+    }
+
+    /// Enable or disable routing to a sink. The flag outlives snapshot
+    /// refreshes, so a disabled sink stays disabled until re-enabled.
+    pub async fn set_disabled(&self, sink_id: &str, disabled: bool) {
+        {
+            let mut set = self.disabled.write().await;
+            if disabled {
+                set.insert(sink_id.to_string());
+            } else {
+                set.remove(sink_id);
+            }
+        }
+        self.update(|map| {
+            if let Some(snapshot) = map.get_mut(sink_id) {
+                snapshot.disabled = disabled;
+            }
+        });
+    }
+
+    /// Whether a sink has been disabled by an operator
+    pub async fn is_disabled(&self, sink_id: &str) -> bool {
+        self.disabled.read().await.contains(sink_id)
+    }
+
+    /// Recent health samples for a sink, oldest first
+    pub async fn history(&self, sink_id: &str) -> Vec<HealthSample> {
+        self.history
+            .read()
+            .await
+            .get(sink_id)
+            .map(|samples| samples.iter().cloned().collect())
+            .unwrap_or_default()
     }
 
     /// Get a snapshot by sink ID
     pub async fn get(&self, sink_id: &str) -> Option<SinkSnapshot> {
-        let guard = self.inner.read().await;
-        guard.get(sink_id).cloned()
+        self.inner.load().get(sink_id).cloned()
     }
 
     /// List all snapshots
     pub async fn list(&self) -> Vec<(String, SinkSnapshot)> {
-        let guard = self.inner.read().await;
-        guard.iter().map(|(k, v)| (k.clone(), v.clone())).collect()
+        let map = self.inner.load();
+        map.iter().map(|(k, v)| (k.clone(), v.clone())).collect()
     }
 
     /// Refresh snapshots from the given registry (all sinks)
@@ -88,4 +200,57 @@ impl SinkIndex {
         }
         count
     }
+
+    /// Drop snapshots for sinks no longer registered and refresh the rest
+    pub async fn resync(&self, registry: &SinkRegistry) -> usize {
+        let ids: HashSet<String> = registry.list_ids().await.into_iter().collect();
+        let stale: Vec<String> = self
+            .inner
+            .load()
+            .keys()
+            .filter(|id| !ids.contains(*id))
+            .cloned()
+            .collect();
+        for id in stale {
+            self.remove(&id).await;
+        }
+        self.refresh_from_registry(registry).await
+    }
+
+    /// Keep the index in step with `registry`. Subscribes immediately, so no
+    /// event published after this call is missed; the returned future applies
+    /// events until the registry is dropped and is meant to be spawned.
+    /// If it falls behind the channel, the whole index is resynced.
+    pub fn follow(
+        &self,
+        registry: &Arc<SinkRegistry>,
+    ) -> impl Future<Output = ()> + Send + 'static {
+        let mut events = registry.subscribe();
+        let registry = Arc::downgrade(registry);
+        let index = self.clone();
+        async move {
+            loop {
+                let event = events.recv().await;
+                let Some(registry) = registry.upgrade() else {
+                    break;
+                };
+                match event {
+                    Ok(RegistryEvent::Registered(id)) => {
+                        index.refresh_subset_from_registry(&registry, &[id]).await;
+                    }
+                    Ok(RegistryEvent::Removed(id)) => index.remove(&id).await,
+                    Ok(RegistryEvent::Health { id, health }) => {
+                        if !index.set_health(&id, health).await {
+                            index.refresh_subset_from_registry(&registry, &[id]).await;
+                        }
+                    }
+                    Err(RecvError::Lagged(missed)) => {
+                        warn!("Sink index missed {missed} registry events, resyncing");
+                        index.resync(&registry).await;
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,398 @@
+//! The automatic model
+//!
+//! Requests for `gate/auto` are classified from their prompt before routing:
+//! code goes to the coding model, long or reasoning-heavy prompts and tool
+//! use to the complex model, and everything else to a cheap simple model.
+//! The classifier is a handful of heuristics, cheap enough to run on every
+//! request. Each decision and the reason for it is written to the audit
+//! trail. An identity may be pinned to a tier or a model, which then wins
+//! over the classifier.
+
+use super::RequestRewriter;
+use crate::router::service::estimate_tokens;
+use crate::router::sink::RequestContext;
+use crate::router::types::Protocol;
+use crate::tracing::metrics::counter;
+use crate::{Result, RoutingDecision, StateBackend};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Model name that asks the gateway to pick the model
+pub const AUTO_MODEL: &str = "gate/auto";
+
+/// Context metadata key holding the tier an automatic request was put in
+pub const AUTO_TIER: &str = "auto_tier";
+
+/// Requests estimated above this many tokens count as complex
+const COMPLEX_TOKENS: u64 = 1500;
+
+/// Conversations longer than this many messages count as complex
+const COMPLEX_MESSAGES: usize = 8;
+
+/// Words that suggest a prompt is about code
+const CODING_TERMS: &[&str] = &[
+    "function",
+    "compile",
+    "stack trace",
+    "traceback",
+    "refactor",
+    "debug",
+    "unit test",
+    "regex",
+    "sql",
+    "python",
+    "javascript",
+    "typescript",
+    "def ",
+    "fn ",
+    "#include",
+    "import ",
+    "=>",
+];
+
+/// Words that suggest a prompt needs reasoning
+const REASONING_TERMS: &[&str] = &[
+    "analyze",
+    "analyse",
+    "compare",
+    "prove",
+    "derive",
+    "design",
+    "architecture",
+    "trade-off",
+    "tradeoff",
+    "step by step",
+    "explain why",
+    "strategy",
+    "evaluate",
+];
+
+/// Class of prompt, each served by its own model
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PromptTier {
+    Simple,
+    Complex,
+    Coding,
+}
+
+impl PromptTier {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PromptTier::Simple => "simple",
+            PromptTier::Complex => "complex",
+            PromptTier::Coding => "coding",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "simple" => Some(PromptTier::Simple),
+            "complex" => Some(PromptTier::Complex),
+            "coding" => Some(PromptTier::Coding),
+            _ => None,
+        }
+    }
+}
+
+/// Tier a prompt was put in and why
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Classification {
+    pub tier: PromptTier,
+    pub rationale: String,
+}
+
+/// Put a request in a tier from its text, size and tools
+pub fn classify(request: &JsonValue) -> Classification {
+    let mut texts = Vec::new();
+    for field in ["system", "messages", "input", "instructions"] {
+        if let Some(value) = request.get(field) {
+            collect_text(value, &mut texts);
+        }
+    }
+    let text = texts.join("\n").to_lowercase();
+    let found = |terms: &[&str]| -> Vec<String> {
+        terms
+            .iter()
+            .filter(|term| text.contains(*term))
+            .map(|term| format!("{:?}", term.trim()))
+            .collect()
+    };
+
+    let coding = found(CODING_TERMS);
+    if text.contains("```") {
+        return Classification {
+            tier: PromptTier::Coding,
+            rationale: "prompt contains a code block".to_string(),
+        };
+    }
+    if coding.len() >= 2 {
+        return Classification {
+            tier: PromptTier::Coding,
+            rationale: format!("prompt mentions {}", coding.join(", ")),
+        };
+    }
+
+    let tokens = estimate_tokens(request);
+    let messages = request
+        .get("messages")
+        .and_then(|m| m.as_array())
+        .map_or(0, Vec::len);
+    let has_tools = request
+        .get("tools")
+        .and_then(|t| t.as_array())
+        .is_some_and(|tools| !tools.is_empty());
+    let reasoning = found(REASONING_TERMS);
+    let mut reasons = Vec::new();
+    if tokens > COMPLEX_TOKENS {
+        reasons.push(format!("about {tokens} tokens long"));
+    }
+    if messages > COMPLEX_MESSAGES {
+        reasons.push(format!("{messages} messages into a conversation"));
+    }
+    if has_tools {
+        reasons.push("offers tools".to_string());
+    }
+    if !reasoning.is_empty() {
+        reasons.push(format!("asks to {}", reasoning.join(", ")));
+    }
+    if !reasons.is_empty() {
+        return Classification {
+            tier: PromptTier::Complex,
+            rationale: format!("prompt {}", reasons.join("; ")),
+        };
+    }
+
+    Classification {
+        tier: PromptTier::Simple,
+        rationale: format!("short prompt (about {tokens} tokens) with no code or reasoning cues"),
+    }
+}
+
+/// Text of message content, whether a string or content blocks
+fn collect_text(value: &JsonValue, texts: &mut Vec<String>) {
+    match value {
+        JsonValue::String(text) => texts.push(text.clone()),
+        JsonValue::Array(items) => items.iter().for_each(|item| collect_text(item, texts)),
+        JsonValue::Object(map) => {
+            for key in ["content", "text"] {
+                if let Some(value) = map.get(key) {
+                    collect_text(value, texts);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Models serving each tier, and identities pinned to a tier or model
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AutoRoutes {
+    pub simple: String,
+    pub complex: String,
+    pub coding: String,
+    /// Tier name or model, by identity or user id
+    pub overrides: HashMap<String, String>,
+}
+
+impl AutoRoutes {
+    pub fn model(&self, tier: PromptTier) -> &str {
+        match tier {
+            PromptTier::Simple => &self.simple,
+            PromptTier::Complex => &self.complex,
+            PromptTier::Coding => &self.coding,
+        }
+    }
+
+    /// Model and tier for a request, and whether an override chose them
+    pub fn choose(&self, ctx: &RequestContext, request: &JsonValue) -> RoutingChoice {
+        let classification = classify(request);
+        let pinned = self.overrides.get(&ctx.identity.id).or_else(|| {
+            ctx.identity
+                .context
+                .user_id
+                .as_ref()
+                .and_then(|user| self.overrides.get(user))
+        });
+        match pinned {
+            Some(pinned) => match PromptTier::parse(pinned) {
+                Some(tier) => RoutingChoice {
+                    model: self.model(tier).to_string(),
+                    tier,
+                    rationale: format!("identity is pinned to the {pinned} tier"),
+                    overridden: true,
+                },
+                None => RoutingChoice {
+                    model: pinned.clone(),
+                    tier: classification.tier,
+                    rationale: format!(
+                        "identity is pinned to {pinned}; classifier said {}",
+                        classification.rationale
+                    ),
+                    overridden: true,
+                },
+            },
+            None => RoutingChoice {
+                model: self.model(classification.tier).to_string(),
+                tier: classification.tier,
+                rationale: classification.rationale,
+                overridden: false,
+            },
+        }
+    }
+}
+
+/// Where an automatic request goes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoutingChoice {
+    pub model: String,
+    pub tier: PromptTier,
+    pub rationale: String,
+    pub overridden: bool,
+}
+
+/// Request rewriter that picks the model for `gate/auto` requests
+pub struct AutoRouter {
+    routes: AutoRoutes,
+    state_backend: Arc<dyn StateBackend>,
+}
+
+impl AutoRouter {
+    pub fn new(routes: AutoRoutes, state_backend: Arc<dyn StateBackend>) -> Self {
+        Self {
+            routes,
+            state_backend,
+        }
+    }
+}
+
+#[async_trait]
+impl RequestRewriter for AutoRouter {
+    async fn rewrite(
+        &self,
+        ctx: &mut RequestContext,
+        _protocol: Protocol,
+        request: &mut JsonValue,
+    ) -> Result<()> {
+        if request.get("model").and_then(|m| m.as_str()) != Some(AUTO_MODEL) {
+            return Ok(());
+        }
+
+        let choice = self.routes.choose(ctx, request);
+        debug!(
+            "Routed {} request from {} to {} ({})",
+            AUTO_MODEL, ctx.identity.id, choice.model, choice.rationale
+        );
+        counter(&format!(
+            "auto_route_requests_total{{tier=\"{}\"}}",
+            choice.tier.as_str()
+        ))
+        .increment();
+
+        let decision = RoutingDecision {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id: ctx.identity.id.clone(),
+            request_id: ctx.correlation_id.to_string(),
+            requested_model: AUTO_MODEL.to_string(),
+            model: choice.model.clone(),
+            tier: choice.tier.as_str().to_string(),
+            rationale: choice.rationale,
+            overridden: choice.overridden,
+            created_at: chrono::Utc::now(),
+        };
+        if let Err(e) = self.state_backend.record_routing_decision(&decision).await {
+            warn!("Failed to record routing decision: {e}");
+        }
+
+        request["model"] = JsonValue::String(choice.model);
+        ctx.metadata
+            .insert(AUTO_TIER.to_string(), decision.tier.clone());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::access::SubjectIdentity;
+    use crate::router::sink::RouterIdentityContext;
+    use crate::tests::state::InMemoryBackend;
+    use serde_json::json;
+
+    fn ctx(user: &str) -> RequestContext {
+        RequestContext {
+            identity: SubjectIdentity::new(user, "test", RouterIdentityContext::default()),
+            correlation_id: crate::tracing::CorrelationId::new(),
+            headers: Default::default(),
+            query: None,
+            trace_id: None,
+            metadata: Default::default(),
+            response: Default::default(),
+            extensions: Default::default(),
+        }
+    }
+
+    fn ask(text: &str) -> JsonValue {
+        json!({"model": AUTO_MODEL, "messages": [{"role": "user", "content": text}]})
+    }
+
+    #[tokio::test]
+    async fn test_prompts_are_routed_by_tier_and_recorded() {
+        let backend = Arc::new(InMemoryBackend::default());
+        let router = AutoRouter::new(
+            AutoRoutes {
+                simple: "small".to_string(),
+                complex: "large".to_string(),
+                coding: "coder".to_string(),
+                overrides: HashMap::from([("bob".to_string(), "complex".to_string())]),
+            },
+            backend.clone(),
+        );
+        let router = &router;
+        let route = move |user: &'static str, mut request: JsonValue| async move {
+            let mut ctx = ctx(user);
+            router
+                .rewrite(&mut ctx, Protocol::OpenAIChat, &mut request)
+                .await
+                .unwrap();
+            request["model"].as_str().unwrap().to_string()
+        };
+
+        assert_eq!(
+            route("alice", ask("What is the capital of France?")).await,
+            "small"
+        );
+        assert_eq!(
+            route(
+                "alice",
+                ask("Why does this fail?\n```rust\nlet x: u8 = 256;\n```")
+            )
+            .await,
+            "coder"
+        );
+        assert_eq!(
+            route(
+                "alice",
+                ask("Compare these two designs and explain why one scales")
+            )
+            .await,
+            "large"
+        );
+        // Pinned identities skip the classifier; other models pass through
+        assert_eq!(route("bob", ask("hi")).await, "large");
+        assert_eq!(
+            route("alice", json!({"model": "gpt-4o", "messages": []})).await,
+            "gpt-4o"
+        );
+
+        let decisions = backend.list_routing_decisions(None, 10).await.unwrap();
+        assert_eq!(decisions.len(), 4);
+        assert_eq!(decisions[0].user_id, "bob");
+        assert!(decisions[0].overridden);
+        assert_eq!(decisions[2].tier, "coding");
+        assert_eq!(decisions[2].rationale, "prompt contains a code block");
+    }
+}
@@ -1,20 +1,30 @@
 //! Cost tracking middleware
 
-use super::{Middleware, Next, RequestStream, ResponseStream};
+use super::usage::{CONTEXT_LIMIT, REQUEST_BYTES, RESPONSE_BYTES, ResponseUsage, USAGE_SOURCE};
+use super::{Middleware, Next, RequestStream, ResponseStream, SINK_ID};
+use crate::router::service::estimate_tokens;
 use crate::router::sink::RequestContext;
-use crate::router::types::{ActualCost, ResponseChunk};
+use crate::router::types::{CostStructure, ModelLimits, ResponseChunk};
+use crate::router::workers::offload;
 use crate::state::StateBackend;
-use crate::{Result, UsageRecord};
+use crate::{ErrorClass, Result, UsageRecord};
 use async_trait::async_trait;
 use futures::StreamExt;
 use std::sync::Arc;
 
-/// Cost tracking middleware
-pub struct CostTrackerMiddleware<S: StateBackend + 'static> {
+/// Cost tracking middleware. Records a usage record for every response,
+/// keyed by the request's correlation id, including responses the client
+/// cancelled part way. Counts the provider did not report are estimated and
+/// the record's [`USAGE_SOURCE`] metadata says so, and a cost the provider
+/// did not report is priced from the counts at the sink's pricing. Records
+/// also carry the request and response sizes, and whether the prompt met the
+/// context window; prompts the provider rejected as too long are recorded
+/// too.
+pub struct CostTrackerMiddleware<S: StateBackend + ?Sized + 'static> {
     state_backend: Arc<S>,
 }
 
-impl<S: StateBackend + 'static> CostTrackerMiddleware<S> {
+impl<S: StateBackend + ?Sized + 'static> CostTrackerMiddleware<S> {
     /// Create a new cost tracker middleware
     pub fn new(state_backend: Arc<S>) -> Self {
         Self { state_backend }
@@ -22,80 +32,195 @@ impl<S: StateBackend + 'static> CostTrackerMiddleware<S> {
 }
 
 #[async_trait]
-impl<S: StateBackend + 'static> Middleware for CostTrackerMiddleware<S> {
+impl<S: StateBackend + ?Sized + 'static> Middleware for CostTrackerMiddleware<S> {
     async fn process(
         &self,
         ctx: &mut RequestContext,
-        request: RequestStream,
+        mut request: RequestStream,
         next: Next,
     ) -> Result<ResponseStream> {
+        // Read the model and estimate the input from the first request
+        // chunk, then put it back
+        let protocol = request.protocol();
+        let Some(first) = request.next().await else {
+            return next(request).await;
+        };
+        let first = first?;
+        let model = first
+            .get("model")
+            .and_then(|m| m.as_str())
+            .unwrap_or_default()
+            .to_string();
+        let (first, input_estimate, request_bytes) = offload(move || {
+            let input_estimate = estimate_tokens(&first);
+            let request_bytes = serde_json::to_vec(&first).map_or(0, |v| v.len() as u64);
+            (first, input_estimate, request_bytes)
+        })
+        .await?;
+        let request = RequestStream::new(
+            protocol,
+            Box::pin(futures::stream::once(async move { Ok(first) }).chain(request)),
+        );
+
         // Forward the request
-        let mut response_stream = next(request).await?;
+        let mut response_stream = match next(request).await {
+            Ok(stream) => stream,
+            // Prompts too long for the model are worth knowing about even
+            // though nothing was generated
+            Err(e) if e.class() == Some(ErrorClass::ContextLengthExceeded) => {
+                let mut pending = PendingUsage {
+                    state_backend: self.state_backend.clone(),
+                    provider: ctx.metadata.get(SINK_ID).cloned().unwrap_or_default(),
+                    model,
+                    ctx: ctx.clone(),
+                    input_estimate,
+                    request_bytes,
+                    usage: ResponseUsage {
+                        error: true,
+                        context_exceeded: true,
+                        ..Default::default()
+                    },
+                    recorded: false,
+                };
+                let record = pending.finish(false);
+                if let Err(e) = pending.state_backend.record_usage(&record).await {
+                    warn!("Failed to record usage of rejected request: {e}");
+                }
+                return Err(e);
+            }
+            Err(e) => return Err(e),
+        };
 
         // Create a new stream that intercepts cost information
         let state_backend = self.state_backend.clone();
         let ctx_clone = ctx.clone();
 
         let intercepted_stream = async_stream::stream! {
-            let mut total_cost: Option<ActualCost> = None;
-            let mut model = String::new();
-            let mut provider = String::new();
-
-            while let Some(chunk_result) = response_stream.next().await {
-                match chunk_result {
-                    Ok(chunk) => {
-                        // Intercept metadata for model/provider
-                        // Extract metadata
-                        if let ResponseChunk::Metadata(ref metadata) = chunk {
-                            if let Some(m) = metadata.get("model").and_then(|v| v.as_str()) {
-                                model = m.to_string();
-                            }
-                            if let Some(p) = metadata.get("provider").and_then(|v| v.as_str()) {
-                                provider = p.to_string();
-                            }
-                        }
+            // The requested model and sink id name the route for
+            // quality-aware routing, so they win over what the sink reports
+            let sink_id = ctx_clone.metadata.get(SINK_ID).cloned();
+            let mut pending = PendingUsage {
+                state_backend,
+                provider: sink_id.clone().unwrap_or_default(),
+                model,
+                ctx: ctx_clone,
+                input_estimate,
+                request_bytes,
+                usage: ResponseUsage::default(),
+                recorded: false,
+            };
 
-                        // Intercept final cost on Stop
-                        if let ResponseChunk::Stop { cost: Some(ref c), .. } = chunk {
-                            total_cost = Some(c.clone());
-                        }
-
-                        yield Ok(chunk);
+            while let Some(chunk) = response_stream.next().await {
+                if let Ok(ResponseChunk::Metadata(metadata)) = &chunk {
+                    if pending.model.is_empty()
+                        && let Some(m) = metadata.get("model").and_then(|v| v.as_str())
+                    {
+                        pending.model = m.to_string();
                     }
-                    Err(e) => {
-                        yield Err(e);
+                    if sink_id.is_none()
+                        && let Some(p) = metadata.get("provider").and_then(|v| v.as_str())
+                    {
+                        pending.provider = p.to_string();
                     }
                 }
+                pending.usage.observe(&chunk);
+                yield chunk;
             }
 
-            // Record usage if we have cost information
-            if let Some(cost) = total_cost {
-                let usage = UsageRecord {
-                    id: uuid::Uuid::new_v4().to_string(),
-                    org_id: ctx_clone.identity.context.org_id.clone().unwrap_or_default(),
-                    user_id: ctx_clone.identity.context.user_id.clone().unwrap_or_default(),
-                    api_key_hash: ctx_clone.identity.context.api_key_hash.clone().unwrap_or_default(),
-                    request_id: ctx_clone.trace_id.clone().unwrap_or_default(),
-                    provider_id: provider,
-                    model_id: model,
-                    input_tokens: cost.input_tokens as u64,
-                    output_tokens: cost.output_tokens as u64,
-                    total_tokens: (cost.input_tokens + cost.output_tokens) as u64,
-                    cost: cost.total_cost_usd.to_string().parse().unwrap_or(0.0),
-                    timestamp: chrono::Utc::now(),
-                    metadata: std::collections::HashMap::new(),
-                };
-
-                if let Err(e) = state_backend.record_usage(&usage).await {
-                    #[cfg(feature = "tracing")]
-                    {
-                        error!("Failed to record usage: {}", e);
-                    }
-                    let _ = e; // Suppress unused warning when tracing is disabled
-                }
+            let completed = !pending.usage.error;
+            let record = pending.finish(completed);
+            if let Err(e) = pending.state_backend.record_usage(&record).await {
+                warn!("Failed to record usage: {e}");
             }
         };
 
         Ok(Box::pin(intercepted_stream))
     }
 }
+
+/// Usage of one response, recorded once: when its stream ends, or when the
+/// stream is dropped because the client went away
+struct PendingUsage<S: StateBackend + ?Sized + 'static> {
+    state_backend: Arc<S>,
+    ctx: RequestContext,
+    provider: String,
+    model: String,
+    input_estimate: u64,
+    request_bytes: u64,
+    usage: ResponseUsage,
+    recorded: bool,
+}
+
+impl<S: StateBackend + ?Sized + 'static> PendingUsage<S> {
+    fn finish(&mut self, completed: bool) -> UsageRecord {
+        self.recorded = true;
+        let pricing = self.ctx.extensions.get::<CostStructure>();
+        let source = self
+            .usage
+            .reconstruct(self.input_estimate, completed, pricing);
+        let info = self.ctx.response.get();
+        let mut metadata = self.ctx.metadata.clone();
+        metadata.extend(info.usage_metadata());
+        // Usage belongs to the sink that served, which spillover may have
+        // chosen over the planned one
+        if let Some(sink_id) = info.sink_id {
+            metadata.insert(SINK_ID.to_string(), sink_id.clone());
+            self.provider = sink_id;
+        }
+        let identity = &self.ctx.identity;
+        metadata.insert(USAGE_SOURCE.to_string(), source.to_string());
+        metadata.insert(REQUEST_BYTES.to_string(), self.request_bytes.to_string());
+        metadata.insert(RESPONSE_BYTES.to_string(), self.usage.bytes.to_string());
+        let limits = self.ctx.extensions.get::<ModelLimits>();
+        if let Some(limit) = self
+            .usage
+            .context_limit(limits.and_then(|limits| limits.context_window))
+        {
+            metadata.insert(CONTEXT_LIMIT.to_string(), limit.to_string());
+        }
+        UsageRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            org_id: identity.context.org_id.clone().unwrap_or_default(),
+            user_id: identity
+                .context
+                .user_id
+                .clone()
+                .unwrap_or_else(|| identity.id.clone()),
+            api_key_hash: identity.context.api_key_hash.clone().unwrap_or_default(),
+            request_id: self.ctx.correlation_id.to_string(),
+            provider_id: self.provider.clone(),
+            model_id: self.model.clone(),
+            input_tokens: self.usage.input_tokens,
+            output_tokens: self.usage.output_tokens,
+            total_tokens: self.usage.input_tokens + self.usage.output_tokens,
+            cost: self.usage.cost.unwrap_or_default(),
+            timestamp: chrono::Utc::now(),
+            metadata,
+        }
+    }
+}
+
+impl<S: StateBackend + ?Sized + 'static> Drop for PendingUsage<S> {
+    fn drop(&mut self) {
+        if self.recorded {
+            return;
+        }
+        let record = self.finish(false);
+
+        // Dropping cannot wait, so the record is written on the runtime
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            let state_backend = self.state_backend.clone();
+            runtime.spawn(async move {
+                if let Err(e) = state_backend.record_usage(&record).await {
+                    warn!("Failed to record usage of cancelled request: {e}");
+                }
+            });
+            return;
+        }
+        warn!(
+            "Usage of cancelled request {} was not recorded",
+            record.request_id
+        );
+    }
+}
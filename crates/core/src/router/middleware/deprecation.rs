@@ -0,0 +1,231 @@
+//! Deprecated models and their replacements
+//!
+//! Providers retire dated snapshots and old model families on a schedule,
+//! after which requests for them fail. Requests for a deprecated model are
+//! rewritten to its replacement before routing, logged, and answered with a
+//! `Warning` header so clients learn to move on. Requests keep the model
+//! they asked for in their usage metadata, which is how the deprecation
+//! report finds the clients still asking.
+//!
+//! Like the model limits, the built-in table is updated with releases;
+//! configured rules override it model by model.
+
+use super::{Middleware, Next, RequestRewriter, RequestStream, ResponseStream};
+use crate::Result;
+use crate::router::sink::{RequestContext, with_headers};
+use crate::router::types::Protocol;
+use crate::tracing::metrics::counter;
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+
+/// Context and usage metadata key holding the deprecated model a request asked for
+pub const DEPRECATED_MODEL: &str = "deprecated_model";
+
+/// Response header naming the deprecated model a request asked for
+pub const DEPRECATED_MODEL_HEADER: &str = "x-gate-deprecated-model";
+
+/// Retired models: (model, replacement, retirement date)
+const DEPRECATED_MODELS: &[(&str, &str, &str)] = &[
+    (
+        "claude-instant-1.2",
+        "claude-3-5-haiku-20241022",
+        "2024-11-06",
+    ),
+    ("claude-2.0", "claude-sonnet-4-20250514", "2025-07-21"),
+    ("claude-2.1", "claude-sonnet-4-20250514", "2025-07-21"),
+    (
+        "claude-3-sonnet-20240229",
+        "claude-sonnet-4-20250514",
+        "2025-07-21",
+    ),
+    (
+        "claude-3-5-sonnet-20240620",
+        "claude-sonnet-4-20250514",
+        "2025-10-22",
+    ),
+    (
+        "claude-3-5-sonnet-20241022",
+        "claude-sonnet-4-20250514",
+        "2025-10-22",
+    ),
+    ("gpt-3.5-turbo-0613", "gpt-4o-mini", "2024-09-13"),
+    ("gpt-3.5-turbo-16k-0613", "gpt-4o-mini", "2024-09-13"),
+    ("gpt-4-32k", "gpt-4o", "2025-06-06"),
+    ("gpt-4-vision-preview", "gpt-4o", "2024-12-06"),
+    ("gpt-4.5-preview", "gpt-4.1", "2025-07-14"),
+];
+
+/// A deprecated model and what serves its requests instead
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelDeprecation {
+    pub model: String,
+    /// Model requests are rewritten to; without one they are only warned
+    /// about
+    pub replacement: Option<String>,
+    /// When the provider stops serving the model
+    pub retires_on: Option<NaiveDate>,
+}
+
+impl ModelDeprecation {
+    /// Text of the `Warning` header for a request that asked for this model
+    pub fn warning(&self) -> String {
+        let retires = match self.retires_on {
+            Some(date) if date <= chrono::Utc::now().date_naive() => {
+                format!(" and was retired on {date}")
+            }
+            Some(date) => format!(" and retires on {date}"),
+            None => String::new(),
+        };
+        let served = match &self.replacement {
+            Some(replacement) => format!("; served by {replacement}"),
+            None => String::new(),
+        };
+        format!(
+            "299 gate \"Model {} is deprecated{retires}{served}\"",
+            self.model
+        )
+    }
+}
+
+/// The built-in deprecation table
+pub fn builtin_deprecations() -> Vec<ModelDeprecation> {
+    DEPRECATED_MODELS
+        .iter()
+        .map(|(model, replacement, retires_on)| ModelDeprecation {
+            model: model.to_string(),
+            replacement: Some(replacement.to_string()),
+            retires_on: NaiveDate::parse_from_str(retires_on, "%Y-%m-%d").ok(),
+        })
+        .collect()
+}
+
+/// Built-in rules with `overrides` replacing those for the same model
+pub fn deprecation_rules(overrides: &[ModelDeprecation]) -> Vec<ModelDeprecation> {
+    let mut rules = builtin_deprecations();
+    rules.retain(|rule| !overrides.iter().any(|o| o.model == rule.model));
+    rules.extend(overrides.iter().cloned());
+    rules
+}
+
+/// Middleware and request rewriter that applies deprecation rules. Add it
+/// to the router as both.
+pub struct DeprecationMiddleware {
+    rules: HashMap<String, ModelDeprecation>,
+}
+
+impl DeprecationMiddleware {
+    pub fn new(rules: Vec<ModelDeprecation>) -> Self {
+        Self {
+            rules: rules
+                .into_iter()
+                .map(|rule| (rule.model.clone(), rule))
+                .collect(),
+        }
+    }
+
+    /// The rule for a model, if it is deprecated
+    pub fn rule(&self, model: &str) -> Option<&ModelDeprecation> {
+        self.rules.get(model)
+    }
+}
+
+#[async_trait]
+impl RequestRewriter for DeprecationMiddleware {
+    async fn rewrite(
+        &self,
+        ctx: &mut RequestContext,
+        _protocol: Protocol,
+        request: &mut JsonValue,
+    ) -> Result<()> {
+        let Some(rule) = request
+            .get("model")
+            .and_then(|m| m.as_str())
+            .and_then(|model| self.rule(model))
+        else {
+            return Ok(());
+        };
+
+        warn!(
+            "{} requested deprecated model {}{}",
+            ctx.identity.id,
+            rule.model,
+            rule.replacement
+                .as_ref()
+                .map(|r| format!(", rewritten to {r}"))
+                .unwrap_or_default()
+        );
+        counter(&format!(
+            "deprecated_model_requests_total{{model=\"{}\"}}",
+            rule.model
+        ))
+        .increment();
+        if let Some(replacement) = &rule.replacement {
+            request["model"] = JsonValue::String(replacement.clone());
+        }
+        ctx.metadata
+            .insert(DEPRECATED_MODEL.to_string(), rule.model.clone());
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Middleware for DeprecationMiddleware {
+    async fn process(
+        &self,
+        ctx: &mut RequestContext,
+        request: RequestStream,
+        next: Next,
+    ) -> Result<ResponseStream> {
+        let Some(rule) = ctx
+            .metadata
+            .get(DEPRECATED_MODEL)
+            .and_then(|model| self.rule(model))
+        else {
+            return next(request).await;
+        };
+        let warnings: HashMap<String, String> = [
+            ("warning".to_string(), rule.warning()),
+            (DEPRECATED_MODEL_HEADER.to_string(), rule.model.clone()),
+        ]
+        .into_iter()
+        .collect();
+
+        Ok(with_headers(next(request).await?, warnings).await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_configured_rules_override_builtin() {
+        let overrides = [
+            ModelDeprecation {
+                model: "claude-2.1".to_string(),
+                replacement: None,
+                retires_on: None,
+            },
+            ModelDeprecation {
+                model: "llama-2-70b".to_string(),
+                replacement: Some("llama-3.3-70b".to_string()),
+                retires_on: NaiveDate::from_ymd_opt(2999, 1, 1),
+            },
+        ];
+        let middleware = DeprecationMiddleware::new(deprecation_rules(&overrides));
+
+        assert_eq!(middleware.rule("claude-2.1").unwrap().replacement, None);
+        assert_eq!(
+            middleware.rule("gpt-4-32k").unwrap().replacement.as_deref(),
+            Some("gpt-4o")
+        );
+        assert!(middleware.rule("gpt-4o").is_none());
+        assert_eq!(
+            middleware.rule("llama-2-70b").unwrap().warning(),
+            "299 gate \"Model llama-2-70b is deprecated and retires on 2999-01-01; served by llama-3.3-70b\""
+        );
+    }
+}
@@ -0,0 +1,309 @@
+//! Experiments: A/B tests between models, prompts and parameters
+//!
+//! Requests for a running experiment's model are assigned a variant before
+//! routing, deterministically per user, so each user sees the same variant
+//! for as long as the experiment runs. The variant's model, prompt and
+//! parameters replace the request's own. When the response completes, its
+//! latency, token usage, cost and whether it failed are recorded against the
+//! variant.
+
+use super::usage::ResponseUsage;
+use super::{Middleware, Next, RequestRewriter, RequestStream, ResponseStream};
+use super::{PROMPT_ID, PROMPT_VERSION};
+use crate::router::sink::RequestContext;
+use crate::router::types::Protocol;
+use crate::{
+    Experiment, ExperimentOutcome, ExperimentStatus, ExperimentVariant, ExperimentVariantResults,
+    Result, StateBackend,
+};
+use async_trait::async_trait;
+use futures::StreamExt;
+use serde_json::Value as JsonValue;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Context metadata key naming the experiment a request is part of
+pub const EXPERIMENT_ID: &str = "experiment_id";
+/// Context metadata key naming the variant a request was assigned
+pub const EXPERIMENT_VARIANT: &str = "experiment_variant";
+
+/// FNV-1a, which unlike the std hasher is stable across releases, so
+/// assignments survive upgrades
+fn stable_hash(key: &str) -> u64 {
+    key.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+impl Experiment {
+    /// The variant `user_id` is assigned, in proportion to variant weights
+    pub fn assign(&self, user_id: &str) -> Option<&ExperimentVariant> {
+        let total: u64 = self.variants.iter().map(|v| u64::from(v.weight)).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut point = stable_hash(&format!("{}:{user_id}", self.id)) % total;
+        for variant in &self.variants {
+            let weight = u64::from(variant.weight);
+            if point < weight {
+                return Some(variant);
+            }
+            point -= weight;
+        }
+        None
+    }
+}
+
+/// Aggregate outcomes per variant, in the experiment's variant order
+pub fn experiment_results(
+    experiment: &Experiment,
+    outcomes: &[ExperimentOutcome],
+) -> Vec<ExperimentVariantResults> {
+    experiment
+        .variants
+        .iter()
+        .map(|variant| {
+            let ours: Vec<&ExperimentOutcome> = outcomes
+                .iter()
+                .filter(|o| o.variant == variant.name)
+                .collect();
+            let mut results = ExperimentVariantResults {
+                variant: variant.name.clone(),
+                requests: ours.len() as u64,
+                ..Default::default()
+            };
+            if ours.is_empty() {
+                return results;
+            }
+
+            let mut latencies: Vec<u64> = ours.iter().map(|o| o.latency_ms).collect();
+            latencies.sort_unstable();
+            results.mean_latency_ms = latencies.iter().sum::<u64>() as f64 / latencies.len() as f64;
+            results.p95_latency_ms = latencies[(latencies.len() * 95).div_ceil(100) - 1];
+            results.errors = ours.iter().filter(|o| o.error).count() as u64;
+            results.total_cost = ours.iter().map(|o| o.cost).sum();
+            results.mean_cost = results.total_cost / ours.len() as f64;
+            results.positive_feedback = ours
+                .iter()
+                .filter(|o| o.feedback.is_some_and(|f| f > 0))
+                .count() as u64;
+            results.negative_feedback = ours
+                .iter()
+                .filter(|o| o.feedback.is_some_and(|f| f < 0))
+                .count() as u64;
+            let rated = results.positive_feedback + results.negative_feedback;
+            results.positive_rate =
+                (rated > 0).then(|| results.positive_feedback as f64 / rated as f64);
+            results
+        })
+        .collect()
+}
+
+/// Middleware and request rewriter that runs experiments. Add it to the
+/// router as both.
+pub struct ExperimentMiddleware {
+    state_backend: Arc<dyn StateBackend>,
+}
+
+impl ExperimentMiddleware {
+    pub fn new(state_backend: Arc<dyn StateBackend>) -> Self {
+        Self { state_backend }
+    }
+}
+
+#[async_trait]
+impl RequestRewriter for ExperimentMiddleware {
+    async fn rewrite(
+        &self,
+        ctx: &mut RequestContext,
+        _protocol: Protocol,
+        request: &mut JsonValue,
+    ) -> Result<()> {
+        let Some(model) = request.get("model").and_then(|m| m.as_str()) else {
+            return Ok(());
+        };
+        let experiments = match self.state_backend.list_experiments().await {
+            Ok(experiments) => experiments,
+            Err(e) => {
+                debug!("Experiments unavailable: {e}");
+                return Ok(());
+            }
+        };
+        let Some(experiment) = experiments
+            .iter()
+            .find(|e| e.status == ExperimentStatus::Running && e.model == model)
+        else {
+            return Ok(());
+        };
+        let Some(variant) = experiment.assign(&ctx.identity.id) else {
+            return Ok(());
+        };
+
+        debug!(
+            "Assigned {} to variant {} of experiment {}",
+            ctx.identity.id, variant.name, experiment.id
+        );
+        if let Some(model) = &variant.model {
+            request["model"] = JsonValue::String(model.clone());
+        }
+        if let Some(prompt_id) = &variant.prompt_id {
+            request[PROMPT_ID] = JsonValue::String(prompt_id.clone());
+            request[PROMPT_VERSION] = variant.prompt_version.into();
+        }
+        for (key, value) in &variant.parameters {
+            request[key] = value.clone();
+        }
+        ctx.metadata
+            .insert(EXPERIMENT_ID.to_string(), experiment.id.clone());
+        ctx.metadata
+            .insert(EXPERIMENT_VARIANT.to_string(), variant.name.clone());
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Middleware for ExperimentMiddleware {
+    async fn process(
+        &self,
+        ctx: &mut RequestContext,
+        request: RequestStream,
+        next: Next,
+    ) -> Result<ResponseStream> {
+        let (Some(experiment_id), Some(variant)) = (
+            ctx.metadata.get(EXPERIMENT_ID).cloned(),
+            ctx.metadata.get(EXPERIMENT_VARIANT).cloned(),
+        ) else {
+            return next(request).await;
+        };
+
+        let mut outcome = ExperimentOutcome {
+            id: uuid::Uuid::new_v4().to_string(),
+            experiment_id,
+            variant,
+            user_id: ctx.identity.id.clone(),
+            request_id: ctx.correlation_id.to_string(),
+            latency_ms: 0,
+            input_tokens: 0,
+            output_tokens: 0,
+            cost: 0.0,
+            error: false,
+            feedback: None,
+            created_at: chrono::Utc::now(),
+        };
+        let state_backend = self.state_backend.clone();
+        let started = Instant::now();
+
+        let mut response = match next(request).await {
+            Ok(response) => response,
+            Err(e) => {
+                outcome.latency_ms = started.elapsed().as_millis() as u64;
+                outcome.error = true;
+                if let Err(e) = state_backend.record_experiment_outcome(&outcome).await {
+                    warn!("Failed to record experiment outcome: {e}");
+                }
+                return Err(e);
+            }
+        };
+
+        let recorded = async_stream::stream! {
+            let mut usage = ResponseUsage::default();
+            while let Some(chunk) = response.next().await {
+                usage.observe(&chunk);
+                yield chunk;
+            }
+
+            outcome.input_tokens = usage.input_tokens;
+            outcome.output_tokens = usage.output_tokens;
+            outcome.cost = usage.cost.unwrap_or_default();
+            outcome.error = usage.error;
+            outcome.latency_ms = started.elapsed().as_millis() as u64;
+            if let Err(e) = state_backend.record_experiment_outcome(&outcome).await {
+                warn!("Failed to record experiment outcome: {e}");
+            }
+        };
+        Ok(Box::pin(recorded))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn experiment(weights: &[u32]) -> Experiment {
+        Experiment {
+            id: "exp".to_string(),
+            name: "Experiment".to_string(),
+            description: None,
+            model: "assistant".to_string(),
+            status: ExperimentStatus::Running,
+            variants: weights
+                .iter()
+                .enumerate()
+                .map(|(i, weight)| ExperimentVariant {
+                    name: format!("v{i}"),
+                    weight: *weight,
+                    model: None,
+                    prompt_id: None,
+                    prompt_version: None,
+                    parameters: serde_json::Map::new(),
+                })
+                .collect(),
+            metrics: Vec::new(),
+            created_by: "admin".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_assignment_is_deterministic_and_weighted() {
+        let split = experiment(&[1, 3]);
+        let first = split.assign("alice").unwrap().name.clone();
+        assert_eq!(split.assign("alice").unwrap().name, first);
+
+        let v1 = (0..1000)
+            .filter(|i| split.assign(&format!("user-{i}")).unwrap().name == "v1")
+            .count();
+        assert!((650..850).contains(&v1), "v1 got {v1} of 1000");
+
+        assert!(experiment(&[0, 0]).assign("alice").is_none());
+        assert_eq!(experiment(&[0, 1]).assign("alice").unwrap().name, "v1");
+    }
+
+    #[test]
+    fn test_results_aggregate_per_variant() {
+        let experiment = experiment(&[1, 1]);
+        let outcome = |variant: &str, latency_ms: u64, feedback: Option<i32>| ExperimentOutcome {
+            id: uuid::Uuid::new_v4().to_string(),
+            experiment_id: "exp".to_string(),
+            variant: variant.to_string(),
+            user_id: "user".to_string(),
+            request_id: uuid::Uuid::new_v4().to_string(),
+            latency_ms,
+            input_tokens: 10,
+            output_tokens: 5,
+            cost: 0.5,
+            error: latency_ms > 1000,
+            feedback,
+            created_at: Utc::now(),
+        };
+        let outcomes = vec![
+            outcome("v0", 100, Some(1)),
+            outcome("v0", 300, Some(-1)),
+            outcome("v0", 2000, Some(1)),
+            outcome("v1", 50, None),
+        ];
+
+        let results = experiment_results(&experiment, &outcomes);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].requests, 3);
+        assert_eq!(results[0].errors, 1);
+        assert_eq!(results[0].mean_latency_ms, 800.0);
+        assert_eq!(results[0].p95_latency_ms, 2000);
+        assert_eq!(results[0].total_cost, 1.5);
+        assert_eq!(results[0].positive_rate, Some(2.0 / 3.0));
+        assert_eq!(results[1].requests, 1);
+        assert_eq!(results[1].positive_rate, None);
+    }
+}
@@ -0,0 +1,236 @@
+//! Default output token limits for requests that leave them unset
+//!
+//! Anthropic rejects requests without `max_tokens` and other providers pick
+//! very different defaults. When a request sets no limit, this fills one in
+//! from the virtual model's override, or else from the output limit the
+//! provider reports for the model, capped by what is left of its context
+//! window after the input.
+
+use super::{Middleware, Next, RequestStream, ResponseStream};
+use crate::router::service::estimate_tokens;
+use crate::router::sink::RequestContext;
+use crate::router::types::{ModelLimits, Protocol};
+use crate::{Result, StateBackend};
+use async_trait::async_trait;
+use futures::StreamExt;
+use serde_json::{Value as JsonValue, json};
+use std::sync::Arc;
+
+/// Fallback for Anthropic requests to models without a known output limit
+const ANTHROPIC_FALLBACK_MAX_TOKENS: u32 = 4096;
+
+/// Tokens kept free of the context window for estimation error
+const CONTEXT_SAFETY_MARGIN: u64 = 256;
+
+/// Request field holding the output limit for a protocol and model
+pub(crate) fn limit_field(protocol: Protocol, model: &str) -> &'static str {
+    match protocol {
+        Protocol::OpenAIResponses => "max_output_tokens",
+        // Reasoning models only accept the newer name
+        Protocol::OpenAIChat if ["o1", "o3", "o4"].iter().any(|p| model.starts_with(p)) => {
+            "max_completion_tokens"
+        }
+        _ => "max_tokens",
+    }
+}
+
+fn has_limit(request: &JsonValue) -> bool {
+    ["max_tokens", "max_completion_tokens", "max_output_tokens"]
+        .iter()
+        .any(|field| request.get(*field).is_some_and(|v| !v.is_null()))
+}
+
+/// Default limit from the model's `limits` and the request's input size
+pub fn default_max_tokens(
+    protocol: Protocol,
+    limits: Option<&ModelLimits>,
+    request: &JsonValue,
+) -> Option<u32> {
+    let output = match limits.and_then(|limits| limits.max_output_tokens) {
+        Some(output) => output,
+        None if protocol == Protocol::Anthropic => ANTHROPIC_FALLBACK_MAX_TOKENS,
+        None => return None,
+    };
+    match limits.and_then(|limits| limits.context_window) {
+        Some(context) => {
+            let input = estimate_tokens(request) + CONTEXT_SAFETY_MARGIN;
+            let remaining = u64::from(context).saturating_sub(input).max(1);
+            Some(u64::from(output).min(remaining) as u32)
+        }
+        None => Some(output),
+    }
+}
+
+/// Middleware that fills in `max_tokens` when a request leaves it unset
+#[derive(Default)]
+pub struct MaxTokensMiddleware {
+    state_backend: Option<Arc<dyn StateBackend>>,
+}
+
+impl MaxTokensMiddleware {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Look up per virtual model overrides in `backend`
+    pub fn with_state_backend(mut self, backend: Arc<dyn StateBackend>) -> Self {
+        self.state_backend = Some(backend);
+        self
+    }
+
+    async fn override_for(&self, model: &str) -> Option<u32> {
+        let backend = self.state_backend.as_ref()?;
+        backend
+            .get_virtual_model(model, None)
+            .await
+            .ok()
+            .flatten()
+            .and_then(|virtual_model| virtual_model.default_max_tokens)
+    }
+}
+
+#[async_trait]
+impl Middleware for MaxTokensMiddleware {
+    async fn process(
+        &self,
+        ctx: &mut RequestContext,
+        mut request: RequestStream,
+        next: Next,
+    ) -> Result<ResponseStream> {
+        let protocol = request.protocol();
+        let Some(first) = request.next().await else {
+            return next(request).await;
+        };
+        let mut json = first?;
+
+        // Embeddings generate no output to limit
+        if protocol != Protocol::OpenAIEmbeddings
+            && !has_limit(&json)
+            && let Some(model) = json.get("model").and_then(|m| m.as_str()).map(String::from)
+        {
+            let limit = match self.override_for(&model).await {
+                Some(limit) => Some(limit),
+                None => default_max_tokens(protocol, ctx.extensions.get(), &json),
+            };
+            if let Some(limit) = limit {
+                debug!("Defaulting output limit for {model} to {limit}");
+                json[limit_field(protocol, &model)] = json!(limit);
+            }
+        }
+
+        let rebuilt = RequestStream::new(
+            protocol,
+            Box::pin(futures::stream::once(async move { Ok(json) }).chain(request)),
+        );
+        next(rebuilt).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::access::SubjectIdentity;
+
+    fn limits(context_window: Option<u32>, max_output_tokens: Option<u32>) -> ModelLimits {
+        ModelLimits {
+            context_window,
+            max_output_tokens,
+        }
+    }
+
+    #[test]
+    fn test_default_max_tokens_uses_model_output_limit() {
+        let request = json!({"model": "claude-sonnet-4-20250514", "messages": []});
+        let limits = limits(Some(200_000), Some(64_000));
+        assert_eq!(
+            default_max_tokens(Protocol::Anthropic, Some(&limits), &request),
+            Some(64_000)
+        );
+    }
+
+    #[test]
+    fn test_default_max_tokens_capped_by_remaining_context() {
+        // ~6000 input tokens leave less than the 8192 output limit
+        let request = json!({"messages": [{"role": "user", "content": "x".repeat(24_000)}]});
+        let limits = limits(Some(8_192), Some(8_192));
+        let limit = default_max_tokens(Protocol::OpenAIChat, Some(&limits), &request).unwrap();
+        assert_eq!(limit, 8_192 - 6_001 - 256);
+
+        // The Anthropic fallback is capped the same way
+        let limits = ModelLimits {
+            context_window: Some(8_192),
+            ..Default::default()
+        };
+        let limit = default_max_tokens(Protocol::Anthropic, Some(&limits), &request).unwrap();
+        assert_eq!(
+            limit,
+            ANTHROPIC_FALLBACK_MAX_TOKENS.min(8_192 - 6_001 - 256)
+        );
+    }
+
+    #[test]
+    fn test_unknown_limits_only_defaulted_for_anthropic() {
+        let request = json!({"messages": []});
+        assert_eq!(
+            default_max_tokens(Protocol::Anthropic, None, &request),
+            Some(ANTHROPIC_FALLBACK_MAX_TOKENS)
+        );
+        assert_eq!(
+            default_max_tokens(Protocol::OpenAIChat, None, &request),
+            None
+        );
+        let context_only = limits(Some(128_000), None);
+        assert_eq!(
+            default_max_tokens(Protocol::OpenAIChat, Some(&context_only), &request),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_limits_come_from_the_routed_model() {
+        let mut ctx = RequestContext {
+            identity: SubjectIdentity::new("user", "api_key", Default::default()),
+            correlation_id: crate::tracing::CorrelationId::new(),
+            headers: Default::default(),
+            query: None,
+            trace_id: None,
+            metadata: Default::default(),
+            response: Default::default(),
+            extensions: Default::default(),
+        };
+        ctx.extensions.insert(limits(Some(200_000), Some(32_000)));
+        let request = RequestStream::new(
+            Protocol::Anthropic,
+            Box::pin(futures::stream::once(async {
+                Ok(json!({"model": "claude-opus-4-1", "messages": []}))
+            })),
+        );
+        let (sent_tx, sent_rx) = std::sync::mpsc::channel();
+        let next: Next = Box::new(move |mut request: RequestStream| {
+            Box::pin(async move {
+                sent_tx
+                    .send(request.next().await.unwrap().unwrap())
+                    .unwrap();
+                Ok(Box::pin(futures::stream::empty()) as ResponseStream)
+            })
+        });
+        MaxTokensMiddleware::new()
+            .process(&mut ctx, request, next)
+            .await
+            .unwrap();
+        assert_eq!(sent_rx.recv().unwrap()["max_tokens"], 32_000);
+    }
+
+    #[test]
+    fn test_limit_field_per_protocol() {
+        assert_eq!(
+            limit_field(Protocol::OpenAIResponses, "gpt-4o"),
+            "max_output_tokens"
+        );
+        assert_eq!(
+            limit_field(Protocol::OpenAIChat, "o3-mini"),
+            "max_completion_tokens"
+        );
+        assert_eq!(limit_field(Protocol::Anthropic, "claude-3"), "max_tokens");
+    }
+}
@@ -1,14 +1,46 @@
 //! Middleware system for request/response processing
 
+mod auto_route;
 mod cost_tracker;
+mod deprecation;
+mod experiment;
 mod key_capture;
+mod max_tokens;
 mod monitor;
+mod parameter_profile;
+mod prompt_template;
+mod provenance;
 mod rate_limit;
+mod response_transform;
+mod stream_slots;
+mod usage;
 
+pub use auto_route::{
+    AUTO_MODEL, AUTO_TIER, AutoRouter, AutoRoutes, Classification, PromptTier, RoutingChoice,
+    classify,
+};
 pub use cost_tracker::CostTrackerMiddleware;
+pub use deprecation::{
+    DEPRECATED_MODEL, DEPRECATED_MODEL_HEADER, DeprecationMiddleware, ModelDeprecation,
+    builtin_deprecations, deprecation_rules,
+};
+pub use experiment::{EXPERIMENT_ID, EXPERIMENT_VARIANT, ExperimentMiddleware, experiment_results};
 pub use key_capture::{KeyCaptureMiddleware, KeyCaptureRegistrar};
+pub use max_tokens::{MaxTokensMiddleware, default_max_tokens};
 pub use monitor::MonitoringMiddleware;
-pub use rate_limit::RateLimitMiddleware;
+pub use parameter_profile::{
+    PARAMETER_PROFILE, PARAMETER_PROFILE_HEADER, PROFILE_ADJUSTMENTS, PROFILE_ADJUSTMENTS_HEADER,
+    ParameterProfile, ParameterProfileMiddleware, ProfileLookup,
+};
+pub use prompt_template::{PROMPT_ID, PROMPT_VARIABLES, PROMPT_VERSION, PromptTemplateMiddleware};
+pub use provenance::{PROVENANCE, Provenance, ProvenanceMiddleware, ProvenanceSigner};
+pub use rate_limit::{RateLimitConfig, RateLimitMiddleware};
+pub use response_transform::{ResponseTransformMiddleware, transform_response};
+pub use stream_slots::{StreamLimit, StreamSlotsMiddleware};
+pub use usage::{
+    CONTEXT_EXCEEDED, CONTEXT_LIMIT, CONTEXT_NEAR, REQUEST_BYTES, RESPONSE_BYTES, USAGE_ESTIMATED,
+    USAGE_REPORTED, USAGE_SOURCE,
+};
 
 use crate::Result;
 use async_trait::async_trait;
@@ -16,6 +48,9 @@ use futures::Stream;
 use futures::future::BoxFuture;
 use std::pin::Pin;
 
+/// Context metadata key naming the sink a request was routed to
+pub const SINK_ID: &str = "sink_id";
+
 /// Stream of request data
 pub type RequestStream = super::types::RequestStream;
 
@@ -28,11 +63,33 @@ pub type Next = Box<dyn FnOnce(RequestStream) -> BoxFuture<'static, Result<Respo
 /// Middleware trait for processing requests and responses
 #[async_trait]
 pub trait Middleware: Send + Sync {
-    /// Process the request/response through this middleware
+    /// Process the request/response through this middleware. Sinks may pass
+    /// content through as [`ResponseChunk::Raw`](crate::router::types::ResponseChunk::Raw)
+    /// bytes; middleware that reads content parses it with
+    /// [`parse_content`](crate::router::sink::parse_content).
     async fn process(
         &self,
         ctx: &mut super::sink::RequestContext,
         request: RequestStream,
         next: Next,
     ) -> Result<ResponseStream>;
+
+    /// Name of the span the middleware runs in
+    fn name(&self) -> &'static str {
+        let name = std::any::type_name::<Self>();
+        name.rsplit("::").next().unwrap_or(name)
+    }
+}
+
+/// Hook that rewrites a request before it is routed. Unlike middleware,
+/// which runs once a sink has been chosen, a rewriter may change the model,
+/// and metadata it sets on the context is seen by every middleware.
+#[async_trait]
+pub trait RequestRewriter: Send + Sync {
+    async fn rewrite(
+        &self,
+        ctx: &mut super::sink::RequestContext,
+        protocol: super::types::Protocol,
+        request: &mut serde_json::Value,
+    ) -> Result<()>;
 }
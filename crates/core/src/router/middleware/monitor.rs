@@ -38,7 +38,7 @@ impl Middleware for MonitoringMiddleware {
         let trace_id = ctx
             .trace_id
             .clone()
-            .unwrap_or_else(|| "unknown".to_string());
+            .unwrap_or_else(|| ctx.correlation_id.trace_id());
 
         // Log request start
         #[cfg(feature = "tracing")]
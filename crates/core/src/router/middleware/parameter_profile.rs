@@ -0,0 +1,298 @@
+//! Server-side parameter profiles
+//!
+//! A profile pins what a user or key may ask of a model: the model used
+//! when a request names none, a ceiling on temperature, a cap on output
+//! tokens and the tools that may be offered. Profiles are applied before
+//! routing, overriding or clamping what the client sent, and every change
+//! is reported back in response headers so clients can tell why they got
+//! less than they asked for. A request may fall under several profiles, say
+//! its user's and its key's; each is applied in turn, so the strictest
+//! limit wins. The profiles applied are kept in the usage metadata.
+
+use super::max_tokens::limit_field;
+use super::{Middleware, Next, RequestRewriter, RequestStream, ResponseStream};
+use crate::router::sink::{RequestContext, with_headers};
+use crate::router::types::Protocol;
+use crate::state::StateBackend;
+use crate::{ApiKey, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value as JsonValue, json};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Context and usage metadata key naming the profiles applied to a
+/// request, `,` separated
+pub const PARAMETER_PROFILE: &str = "parameter_profile";
+
+/// Context metadata key listing what the profile changed, `; ` separated
+pub const PROFILE_ADJUSTMENTS: &str = "profile_adjustments";
+
+/// Response header naming the profiles applied to a request
+pub const PARAMETER_PROFILE_HEADER: &str = "x-gate-parameter-profile";
+
+/// Response header listing what the profile changed
+pub const PROFILE_ADJUSTMENTS_HEADER: &str = "x-gate-profile-adjustments";
+
+/// Output limit fields across protocols
+const LIMIT_FIELDS: [&str; 3] = ["max_tokens", "max_completion_tokens", "max_output_tokens"];
+
+/// Limits a profile enforces
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ParameterProfile {
+    pub name: String,
+    /// Model for requests that name none
+    pub default_model: Option<String>,
+    /// Highest temperature a request may ask for
+    pub max_temperature: Option<f64>,
+    /// Most output tokens a request may ask for; also the limit of requests
+    /// that set none
+    pub max_tokens: Option<u32>,
+    /// Tools a request may offer, by name or type; `None` allows all
+    pub allowed_tools: Option<Vec<String>>,
+}
+
+/// Name a tool definition goes by: the function name, else the tool name,
+/// else the type of a built-in tool
+fn tool_name(tool: &JsonValue) -> Option<&str> {
+    tool["function"]["name"]
+        .as_str()
+        .or_else(|| tool["name"].as_str())
+        .or_else(|| tool["type"].as_str())
+}
+
+impl ParameterProfile {
+    /// Apply the profile to a request, returning what was changed
+    pub fn apply(&self, protocol: Protocol, request: &mut JsonValue) -> Vec<String> {
+        let mut adjustments = Vec::new();
+        if protocol == Protocol::OpenAIEmbeddings || !request.is_object() {
+            return adjustments;
+        }
+
+        if let Some(model) = &self.default_model
+            && request["model"].as_str().is_none_or(str::is_empty)
+        {
+            request["model"] = json!(model);
+            adjustments.push(format!("model set to {model}"));
+        }
+
+        if let Some(ceiling) = self.max_temperature
+            && let Some(temperature) = request["temperature"].as_f64()
+            && temperature > ceiling
+        {
+            request["temperature"] = json!(ceiling);
+            adjustments.push(format!("temperature {temperature} lowered to {ceiling}"));
+        }
+
+        if let Some(cap) = self.max_tokens {
+            for field in LIMIT_FIELDS {
+                if let Some(requested) = request[field].as_u64()
+                    && requested > u64::from(cap)
+                {
+                    request[field] = json!(cap);
+                    adjustments.push(format!("{field} {requested} capped at {cap}"));
+                }
+            }
+        }
+
+        if let Some(allowed) = &self.allowed_tools
+            && let Some(tools) = request["tools"].as_array_mut()
+        {
+            let mut removed = Vec::new();
+            tools.retain(|tool| match tool_name(tool) {
+                Some(name) if allowed.iter().any(|a| a == name) => true,
+                name => {
+                    removed.push(name.unwrap_or("unnamed").to_string());
+                    false
+                }
+            });
+            if !removed.is_empty() {
+                adjustments.push(format!("tools removed: {}", removed.join(", ")));
+                let forced = tool_name(&request["tool_choice"])
+                    .is_some_and(|name| removed.iter().any(|r| r == name));
+                let emptied = request["tools"].as_array().is_some_and(Vec::is_empty);
+                if let Some(request) = request.as_object_mut() {
+                    if emptied {
+                        request.remove("tools");
+                    }
+                    if forced || emptied {
+                        request.remove("tool_choice");
+                    }
+                }
+            }
+        }
+        adjustments
+    }
+}
+
+/// Give a request that sets no output limit the lowest cap of `profiles`
+fn default_output_limit(
+    profiles: &[ParameterProfile],
+    protocol: Protocol,
+    request: &mut JsonValue,
+) {
+    let Some(cap) = profiles.iter().filter_map(|p| p.max_tokens).min() else {
+        return;
+    };
+    if protocol == Protocol::OpenAIEmbeddings
+        || !request.is_object()
+        || LIMIT_FIELDS.iter().any(|field| !request[*field].is_null())
+    {
+        return;
+    }
+    let model = request["model"].as_str().unwrap_or_default().to_string();
+    request[limit_field(protocol, &model)] = json!(cap);
+}
+
+/// Profiles of a request, from its context and the API key it used
+pub type ProfileLookup =
+    Arc<dyn Fn(&RequestContext, Option<&ApiKey>) -> Vec<ParameterProfile> + Send + Sync>;
+
+/// Middleware and request rewriter that applies parameter profiles. Add it
+/// to the router as both.
+pub struct ParameterProfileMiddleware<S: StateBackend + ?Sized + 'static> {
+    state_backend: Arc<S>,
+    lookup: ProfileLookup,
+}
+
+impl<S: StateBackend + ?Sized + 'static> ParameterProfileMiddleware<S> {
+    /// Apply the profiles `lookup` finds for each request
+    pub fn new(
+        state_backend: Arc<S>,
+        lookup: impl Fn(&RequestContext, Option<&ApiKey>) -> Vec<ParameterProfile>
+        + Send
+        + Sync
+        + 'static,
+    ) -> Self {
+        Self {
+            state_backend,
+            lookup: Arc::new(lookup),
+        }
+    }
+}
+
+#[async_trait]
+impl<S: StateBackend + ?Sized + 'static> RequestRewriter for ParameterProfileMiddleware<S> {
+    async fn rewrite(
+        &self,
+        ctx: &mut RequestContext,
+        protocol: Protocol,
+        request: &mut JsonValue,
+    ) -> Result<()> {
+        let key = match &ctx.identity.context.api_key_hash {
+            Some(hash) => self.state_backend.get_api_key(hash).await?,
+            None => None,
+        };
+        let profiles = (self.lookup)(ctx, key.as_ref());
+        if profiles.is_empty() {
+            return Ok(());
+        }
+        let adjustments: Vec<String> = profiles
+            .iter()
+            .flat_map(|profile| profile.apply(protocol, request))
+            .collect();
+        default_output_limit(&profiles, protocol, request);
+        let names: Vec<&str> = profiles.iter().map(|p| p.name.as_str()).collect();
+        ctx.metadata
+            .insert(PARAMETER_PROFILE.to_string(), names.join(","));
+        if !adjustments.is_empty() {
+            debug!(
+                "Profiles {} adjusted request from {}: {}",
+                names.join(","),
+                ctx.identity.id,
+                adjustments.join("; ")
+            );
+            ctx.metadata
+                .insert(PROFILE_ADJUSTMENTS.to_string(), adjustments.join("; "));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<S: StateBackend + ?Sized + 'static> Middleware for ParameterProfileMiddleware<S> {
+    async fn process(
+        &self,
+        ctx: &mut RequestContext,
+        request: RequestStream,
+        next: Next,
+    ) -> Result<ResponseStream> {
+        let Some(adjustments) = ctx.metadata.get(PROFILE_ADJUSTMENTS).cloned() else {
+            return next(request).await;
+        };
+        let headers: HashMap<String, String> = [
+            (
+                PARAMETER_PROFILE_HEADER.to_string(),
+                ctx.metadata
+                    .get(PARAMETER_PROFILE)
+                    .cloned()
+                    .unwrap_or_default(),
+            ),
+            (PROFILE_ADJUSTMENTS_HEADER.to_string(), adjustments),
+        ]
+        .into_iter()
+        .collect();
+
+        Ok(with_headers(next(request).await?, headers).await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_overrides_and_clamps() {
+        let profile = ParameterProfile {
+            name: "intern".to_string(),
+            default_model: Some("claude-3-5-haiku-20241022".to_string()),
+            max_temperature: Some(0.5),
+            max_tokens: Some(1000),
+            allowed_tools: Some(vec!["lookup".to_string()]),
+        };
+        let mut request = json!({
+            "messages": [],
+            "temperature": 0.9,
+            "max_tokens": 4000,
+            "tools": [
+                {"type": "function", "function": {"name": "lookup"}},
+                {"type": "function", "function": {"name": "shell"}},
+            ],
+            "tool_choice": {"type": "function", "function": {"name": "shell"}},
+        });
+        let adjustments = profile.apply(Protocol::OpenAIChat, &mut request);
+        assert_eq!(
+            adjustments,
+            [
+                "model set to claude-3-5-haiku-20241022",
+                "temperature 0.9 lowered to 0.5",
+                "max_tokens 4000 capped at 1000",
+                "tools removed: shell",
+            ]
+        );
+        assert_eq!(request["model"], "claude-3-5-haiku-20241022");
+        assert_eq!(request["temperature"], 0.5);
+        assert_eq!(request["max_tokens"], 1000);
+        assert_eq!(request["tools"].as_array().unwrap().len(), 1);
+        assert!(request.get("tool_choice").is_none());
+
+        // Values within the profile are left alone
+        let mut request = json!({
+            "model": "o3-mini",
+            "temperature": 0.2,
+            "tools": [{"type": "function", "function": {"name": "shell"}}],
+        });
+        let adjustments = profile.apply(Protocol::OpenAIChat, &mut request);
+        assert_eq!(adjustments, ["tools removed: shell"]);
+        assert_eq!(request["model"], "o3-mini");
+        assert!(request.get("tools").is_none());
+
+        // An unset limit gets the lowest cap, in the model's field
+        let strict = ParameterProfile {
+            max_tokens: Some(500),
+            ..Default::default()
+        };
+        default_output_limit(&[profile, strict], Protocol::OpenAIChat, &mut request);
+        assert_eq!(request["max_completion_tokens"], 500);
+    }
+}
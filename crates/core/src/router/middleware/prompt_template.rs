@@ -0,0 +1,354 @@
+//! Server-side rendering of prompt templates
+//!
+//! A request naming a template with `prompt_id` has the template's messages,
+//! with `{{variable}}` placeholders filled from the request's `variables`,
+//! put ahead of its own. `prompt_version` pins a version; otherwise the
+//! latest is used. Parameters the template sets apply where the request
+//! leaves them unset, except `model`, since requests are routed before
+//! middleware runs. Each render is recorded with the version used.
+
+use super::{Middleware, Next, RequestStream, ResponseStream};
+use crate::router::sink::RequestContext;
+use crate::router::types::Protocol;
+use crate::{Error, PromptMessage, PromptRender, PromptTemplate, Result, StateBackend};
+use async_trait::async_trait;
+use futures::StreamExt;
+use serde_json::{Map, Value as JsonValue, json};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Request field naming the template
+pub const PROMPT_ID: &str = "prompt_id";
+/// Request field pinning a template version
+pub const PROMPT_VERSION: &str = "prompt_version";
+/// Request field holding variable values
+pub const PROMPT_VARIABLES: &str = "variables";
+
+/// Call `f` with the name of each `{{name}}` placeholder in `text` and the
+/// text before it, returning the text after the last one
+fn scan<'a>(text: &'a str, mut f: impl FnMut(&'a str, &'a str) -> Result<()>) -> Result<&'a str> {
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            break;
+        };
+        f(&rest[..start], after[..end].trim())?;
+        rest = &after[end + 2..];
+    }
+    Ok(rest)
+}
+
+impl PromptTemplate {
+    /// Names of the placeholders the messages use, in order of appearance
+    pub fn placeholders(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for message in &self.messages {
+            let _ = scan(&message.content, |_, name| {
+                if !names.iter().any(|n| n == name) {
+                    names.push(name.to_string());
+                }
+                Ok(())
+            });
+        }
+        names
+    }
+
+    /// The messages with placeholders replaced by `variables`, falling back
+    /// to declared defaults. Non-string values are inserted as JSON.
+    pub fn render(&self, variables: &Map<String, JsonValue>) -> Result<Vec<PromptMessage>> {
+        let mut values: HashMap<&str, String> = self
+            .variables
+            .iter()
+            .filter_map(|v| Some((v.name.as_str(), v.default.clone()?)))
+            .collect();
+        for (name, value) in variables {
+            let value = match value {
+                JsonValue::String(text) => text.clone(),
+                other => other.to_string(),
+            };
+            values.insert(name, value);
+        }
+
+        self.messages
+            .iter()
+            .map(|message| {
+                let mut content = String::with_capacity(message.content.len());
+                let rest = scan(&message.content, |before, name| {
+                    let value = values.get(name).ok_or_else(|| {
+                        Error::InvalidRequest(format!(
+                            "Prompt {} needs a value for variable {name}",
+                            self.name
+                        ))
+                    })?;
+                    content.push_str(before);
+                    content.push_str(value);
+                    Ok(())
+                })?;
+                content.push_str(rest);
+                Ok(PromptMessage {
+                    role: message.role.clone(),
+                    content,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Put rendered messages ahead of the request's own, in its protocol
+fn prepend_messages(
+    protocol: Protocol,
+    request: &mut JsonValue,
+    messages: Vec<PromptMessage>,
+) -> Result<()> {
+    let (system, conversation): (Vec<_>, Vec<_>) =
+        messages.into_iter().partition(|m| m.role == "system");
+    let system = system
+        .into_iter()
+        .map(|m| m.content)
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    let conversation = conversation
+        .into_iter()
+        .map(|m| json!({"role": m.role, "content": m.content}));
+
+    let (system_key, messages_key) = match protocol {
+        Protocol::OpenAIChat => {
+            let mut all: Vec<JsonValue> = Vec::new();
+            if !system.is_empty() {
+                all.push(json!({"role": "system", "content": system}));
+            }
+            all.extend(conversation);
+            prepend_array(&mut request["messages"], all);
+            return Ok(());
+        }
+        Protocol::Anthropic => ("system", "messages"),
+        Protocol::OpenAIResponses => {
+            if let Some(input) = request["input"].as_str() {
+                request["input"] = json!([{"role": "user", "content": input}]);
+            }
+            ("instructions", "input")
+        }
+        other => {
+            return Err(Error::InvalidRequest(format!(
+                "Prompt templates are not supported for {other} requests"
+            )));
+        }
+    };
+
+    if !system.is_empty() {
+        match &mut request[system_key] {
+            JsonValue::String(existing) if !existing.is_empty() => {
+                *existing = format!("{system}\n\n{existing}");
+            }
+            JsonValue::Array(blocks) => blocks.insert(0, json!({"type": "text", "text": system})),
+            other => *other = json!(system),
+        }
+    }
+    prepend_array(&mut request[messages_key], conversation.collect());
+    Ok(())
+}
+
+fn prepend_array(target: &mut JsonValue, mut items: Vec<JsonValue>) {
+    if items.is_empty() {
+        return;
+    }
+    if let Some(existing) = target.as_array() {
+        items.extend(existing.iter().cloned());
+    }
+    *target = JsonValue::Array(items);
+}
+
+/// Middleware that renders prompt templates named by requests
+pub struct PromptTemplateMiddleware {
+    state_backend: Arc<dyn StateBackend>,
+}
+
+impl PromptTemplateMiddleware {
+    pub fn new(state_backend: Arc<dyn StateBackend>) -> Self {
+        Self { state_backend }
+    }
+
+    async fn render_into(
+        &self,
+        ctx: &mut RequestContext,
+        protocol: Protocol,
+        request: &mut JsonValue,
+    ) -> Result<()> {
+        let Some(obj) = request.as_object_mut() else {
+            return Ok(());
+        };
+        let Some(prompt_id) = obj.remove(PROMPT_ID) else {
+            return Ok(());
+        };
+        let name = prompt_id
+            .as_str()
+            .ok_or_else(|| Error::InvalidRequest(format!("{PROMPT_ID} must be a string")))?;
+        let version =
+            match obj.remove(PROMPT_VERSION) {
+                None | Some(JsonValue::Null) => None,
+                Some(v) => Some(v.as_u64().and_then(|v| u32::try_from(v).ok()).ok_or_else(
+                    || Error::InvalidRequest(format!("{PROMPT_VERSION} must be a version number")),
+                )?),
+            };
+        let variables = match obj.remove(PROMPT_VARIABLES) {
+            None | Some(JsonValue::Null) => Map::new(),
+            Some(JsonValue::Object(variables)) => variables,
+            Some(_) => {
+                return Err(Error::InvalidRequest(format!(
+                    "{PROMPT_VARIABLES} must be an object"
+                )));
+            }
+        };
+
+        let template = self
+            .state_backend
+            .get_prompt(name, version)
+            .await?
+            .ok_or_else(|| {
+                Error::InvalidRequest(match version {
+                    Some(version) => format!("Prompt {name} has no version {version}"),
+                    None => format!("Unknown prompt {name}"),
+                })
+            })?;
+        let messages = template.render(&variables)?;
+        prepend_messages(protocol, request, messages)?;
+        for (key, value) in &template.parameters {
+            if key != "model" && request.get(key).is_none_or(JsonValue::is_null) {
+                request[key] = value.clone();
+            }
+        }
+
+        debug!(
+            "Rendered prompt {} version {} for {}",
+            template.name, template.version, ctx.identity.id
+        );
+        ctx.metadata
+            .insert(PROMPT_ID.to_string(), template.name.clone());
+        ctx.metadata
+            .insert(PROMPT_VERSION.to_string(), template.version.to_string());
+        let render = PromptRender {
+            id: uuid::Uuid::new_v4().to_string(),
+            prompt_name: template.name,
+            version: template.version,
+            user_id: ctx.identity.id.clone(),
+            request_id: ctx.correlation_id.to_string(),
+            model: request["model"].as_str().unwrap_or_default().to_string(),
+            created_at: chrono::Utc::now(),
+        };
+        if let Err(e) = self.state_backend.record_prompt_render(&render).await {
+            warn!(
+                "Failed to record render of prompt {}: {e}",
+                render.prompt_name
+            );
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Middleware for PromptTemplateMiddleware {
+    async fn process(
+        &self,
+        ctx: &mut RequestContext,
+        mut request: RequestStream,
+        next: Next,
+    ) -> Result<ResponseStream> {
+        let protocol = request.protocol();
+        let Some(first) = request.next().await else {
+            return next(request).await;
+        };
+        let mut json = first?;
+        self.render_into(ctx, protocol, &mut json).await?;
+
+        let rebuilt = RequestStream::new(
+            protocol,
+            Box::pin(futures::stream::once(async move { Ok(json) }).chain(request)),
+        );
+        next(rebuilt).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PromptVariable;
+
+    fn template() -> PromptTemplate {
+        PromptTemplate {
+            name: "support".to_string(),
+            version: 2,
+            description: None,
+            messages: vec![
+                PromptMessage {
+                    role: "system".to_string(),
+                    content: "You support {{ product }} in {{language}}.".to_string(),
+                },
+                PromptMessage {
+                    role: "user".to_string(),
+                    content: "My plan: {{plan}}".to_string(),
+                },
+            ],
+            variables: vec![PromptVariable {
+                name: "language".to_string(),
+                default: Some("English".to_string()),
+            }],
+            parameters: Map::new(),
+            created_by: "admin".to_string(),
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_render_fills_variables_and_defaults() {
+        let template = template();
+        assert_eq!(template.placeholders(), vec!["product", "language", "plan"]);
+
+        let variables = json!({"product": "Gate", "plan": {"tier": 1}});
+        let messages = template.render(variables.as_object().unwrap()).unwrap();
+        assert_eq!(messages[0].content, "You support Gate in English.");
+        assert_eq!(messages[1].content, r#"My plan: {"tier":1}"#);
+
+        let missing = template.render(json!({"product": "Gate"}).as_object().unwrap());
+        assert!(matches!(missing, Err(Error::InvalidRequest(_))));
+    }
+
+    #[test]
+    fn test_prepend_messages_per_protocol() {
+        let rendered = || {
+            vec![
+                PromptMessage {
+                    role: "system".to_string(),
+                    content: "Be brief".to_string(),
+                },
+                PromptMessage {
+                    role: "user".to_string(),
+                    content: "Context".to_string(),
+                },
+            ]
+        };
+
+        let mut chat = json!({"messages": [{"role": "user", "content": "Hi"}]});
+        prepend_messages(Protocol::OpenAIChat, &mut chat, rendered()).unwrap();
+        let roles: Vec<_> = chat["messages"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m["role"].as_str().unwrap())
+            .collect();
+        assert_eq!(roles, vec!["system", "user", "user"]);
+
+        let mut anthropic = json!({"system": "Own", "messages": []});
+        prepend_messages(Protocol::Anthropic, &mut anthropic, rendered()).unwrap();
+        assert_eq!(anthropic["system"], "Be brief\n\nOwn");
+        assert_eq!(anthropic["messages"][0]["content"], "Context");
+
+        let mut responses = json!({"input": "Question"});
+        prepend_messages(Protocol::OpenAIResponses, &mut responses, rendered()).unwrap();
+        assert_eq!(responses["instructions"], "Be brief");
+        assert_eq!(responses["input"][1]["content"], "Question");
+
+        let mut embeddings = json!({"input": "text"});
+        assert!(prepend_messages(Protocol::OpenAIEmbeddings, &mut embeddings, rendered()).is_err());
+    }
+}
@@ -0,0 +1,299 @@
+//! Provenance blocks naming where a response came from
+//!
+//! Teams that must trace generated content back to the configuration that
+//! produced it can have the gateway sign each response. A [`Provenance`]
+//! block names the gateway, the model and sink that actually answered, when,
+//! and a canonical hash of the request as routed. Non-streaming responses
+//! carry it in a `provenance` field; streams end with a named `provenance`
+//! SSE event, outside the protocol's own chunks, just before they stop. The
+//! signature covers every other field of the block, so anyone
+//! holding the block can check it against the gateway's key.
+
+use super::{Middleware, Next, RequestStream, ResponseStream};
+use crate::Result;
+use crate::router::digest::json_digest;
+use crate::router::response_info::ResponseMetadata;
+use crate::router::sink::{RequestContext, parse_content};
+use crate::router::types::ResponseChunk;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::sync::Arc;
+
+/// Field of a response, and name of the stream event, holding the block
+pub const PROVENANCE: &str = "provenance";
+
+/// Signs provenance blocks with the gateway's key
+pub trait ProvenanceSigner: Send + Sync {
+    /// Id of the key signatures verify against
+    fn key_id(&self) -> &str;
+
+    /// Signature over `message`
+    fn sign(&self, message: &[u8]) -> String;
+}
+
+/// Where a response came from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Provenance {
+    pub gateway_id: String,
+    /// Key the block is signed with
+    pub key_id: String,
+    /// Model that answered, which may be a dated snapshot of the one asked
+    /// for
+    pub model: Option<String>,
+    /// Sink that answered
+    pub sink_id: Option<String>,
+    pub timestamp: DateTime<Utc>,
+    /// `sha256:` and the hex digest of the request as routed
+    pub request_hash: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub signature: String,
+}
+
+impl Provenance {
+    /// Bytes the signature covers: the block serialized without it
+    pub fn signed_bytes(&self) -> Vec<u8> {
+        let unsigned = Self {
+            signature: String::new(),
+            ..self.clone()
+        };
+        serde_json::to_vec(&unsigned).unwrap_or_default()
+    }
+
+    /// Hash of a request body as a block records it
+    pub fn request_hash(request: &JsonValue) -> String {
+        format!("sha256:{}", json_digest(request))
+    }
+}
+
+/// Middleware adding a signed provenance block to each response
+#[derive(Clone)]
+pub struct ProvenanceMiddleware {
+    gateway_id: String,
+    signer: Arc<dyn ProvenanceSigner>,
+    streams: bool,
+}
+
+impl ProvenanceMiddleware {
+    pub fn new(gateway_id: impl Into<String>, signer: Arc<dyn ProvenanceSigner>) -> Self {
+        Self {
+            gateway_id: gateway_id.into(),
+            signer,
+            streams: true,
+        }
+    }
+
+    /// Whether streams end with a provenance event
+    pub fn with_streams(mut self, streams: bool) -> Self {
+        self.streams = streams;
+        self
+    }
+
+    /// Signed block for a response, once it has told who answered
+    fn seal(&self, request_hash: &str, response: &ResponseMetadata) -> Provenance {
+        let info = response.get();
+        let mut provenance = Provenance {
+            gateway_id: self.gateway_id.clone(),
+            key_id: self.signer.key_id().to_string(),
+            model: info.model,
+            sink_id: info.sink_id,
+            timestamp: Utc::now(),
+            request_hash: request_hash.to_string(),
+            signature: String::new(),
+        };
+        provenance.signature = self.signer.sign(&provenance.signed_bytes());
+        provenance
+    }
+}
+
+/// Add a block from `seal` to `stream`: as a field of the final content of a
+/// whole response, or as an event ending a stream. Responses that fail get
+/// none.
+fn with_provenance(
+    stream: ResponseStream,
+    streaming: bool,
+    seal: impl FnOnce() -> Provenance + Send + 'static,
+) -> ResponseStream {
+    // Stream events pass through as they are
+    let mut stream = if streaming {
+        stream
+    } else {
+        parse_content(stream)
+    };
+    let sealed = async_stream::stream! {
+        let mut seal = Some(seal);
+        let mut pending: Option<JsonValue> = None;
+        while let Some(item) = stream.next().await {
+            match item {
+                Ok(ResponseChunk::Content(json)) if !streaming => {
+                    if let Some(previous) = pending.replace(json) {
+                        yield Ok(ResponseChunk::Content(previous));
+                    }
+                }
+                Ok(stop @ ResponseChunk::Stop { error: None, .. }) => {
+                    if let Some(seal) = seal.take() {
+                        yield Ok(sealed(pending.take(), seal()));
+                    }
+                    yield Ok(stop);
+                }
+                end @ (Ok(ResponseChunk::Stop { .. }) | Err(_)) => {
+                    seal = None;
+                    if let Some(last) = pending.take() {
+                        yield Ok(ResponseChunk::Content(last));
+                    }
+                    yield end;
+                }
+                other => yield other,
+            }
+        }
+        if let Some(seal) = seal {
+            yield Ok(sealed(pending, seal()));
+        }
+    };
+    Box::pin(sealed)
+}
+
+/// `last` with the block added, or the stream event carrying it
+fn sealed(last: Option<JsonValue>, provenance: Provenance) -> ResponseChunk {
+    match last {
+        Some(mut json) => {
+            if let Some(obj) = json.as_object_mut() {
+                let block = serde_json::to_value(&provenance).unwrap_or_default();
+                obj.insert(PROVENANCE.to_string(), block);
+            }
+            ResponseChunk::Content(json)
+        }
+        // A named event rather than content, which clients parse as the
+        // protocol's own chunks
+        None => ResponseChunk::Raw {
+            event: Some(PROVENANCE.to_string()),
+            data: serde_json::to_vec(&provenance).unwrap_or_default().into(),
+        },
+    }
+}
+
+#[async_trait]
+impl Middleware for ProvenanceMiddleware {
+    async fn process(
+        &self,
+        ctx: &mut RequestContext,
+        mut request: RequestStream,
+        next: Next,
+    ) -> Result<ResponseStream> {
+        let protocol = request.protocol();
+        let Some(first) = request.next().await else {
+            return next(request).await;
+        };
+        let json = first?;
+        let streaming = json.get("stream").and_then(|s| s.as_bool()) == Some(true);
+        let hash = (self.streams || !streaming).then(|| Provenance::request_hash(&json));
+
+        let rebuilt = RequestStream::new(
+            protocol,
+            Box::pin(futures::stream::once(async move { Ok(json) }).chain(request)),
+        );
+        let response = next(rebuilt).await?;
+        let Some(hash) = hash else {
+            return Ok(response);
+        };
+        let metadata = ctx.response.clone();
+        let middleware = self.clone();
+        Ok(with_provenance(response, streaming, move || {
+            middleware.seal(&hash, &metadata)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::router::types::StopReason;
+    use serde_json::json;
+
+    struct Reversing;
+
+    impl ProvenanceSigner for Reversing {
+        fn key_id(&self) -> &str {
+            "test-key"
+        }
+
+        fn sign(&self, message: &[u8]) -> String {
+            hex::encode(message.iter().rev().copied().collect::<Vec<_>>())
+        }
+    }
+
+    fn stop() -> Result<ResponseChunk> {
+        Ok(ResponseChunk::Stop {
+            reason: StopReason::Complete,
+            error: None,
+            cost: None,
+        })
+    }
+
+    async fn run(chunks: Vec<Result<ResponseChunk>>, streaming: bool) -> Vec<ResponseChunk> {
+        let middleware = ProvenanceMiddleware::new("gate-1", Arc::new(Reversing));
+        let metadata = ResponseMetadata::default();
+        metadata.publish(|info| {
+            info.model = Some("gpt-4o-2024-08-06".to_string());
+            info.sink_id = Some("openai".to_string());
+        });
+        let hash = Provenance::request_hash(&json!({"model": "gpt-4o"}));
+        let stream: ResponseStream = Box::pin(futures::stream::iter(chunks));
+        with_provenance(stream, streaming, move || middleware.seal(&hash, &metadata))
+            .map(|item| item.unwrap())
+            .collect()
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_block_is_added_to_responses_and_ends_streams() {
+        let out = run(
+            vec![Ok(ResponseChunk::Content(json!({"id": 1}))), stop()],
+            false,
+        )
+        .await;
+        let ResponseChunk::Content(body) = &out[0] else {
+            panic!("expected content");
+        };
+        let provenance: Provenance = serde_json::from_value(body[PROVENANCE].clone()).unwrap();
+        assert_eq!(provenance.gateway_id, "gate-1");
+        assert_eq!(provenance.model.as_deref(), Some("gpt-4o-2024-08-06"));
+        assert_eq!(provenance.sink_id.as_deref(), Some("openai"));
+        assert_eq!(
+            provenance.request_hash,
+            Provenance::request_hash(&json!({"model": "gpt-4o"}))
+        );
+        assert_eq!(
+            Reversing.sign(&provenance.signed_bytes()),
+            provenance.signature
+        );
+        assert!(matches!(out[1], ResponseChunk::Stop { .. }));
+
+        let out = run(
+            vec![
+                Ok(ResponseChunk::Content(json!({"id": 1}))),
+                Ok(ResponseChunk::Content(json!({"id": 2}))),
+                stop(),
+            ],
+            true,
+        )
+        .await;
+        assert_eq!(out.len(), 4);
+        let ResponseChunk::Raw { event, data } = &out[2] else {
+            panic!("expected the provenance event");
+        };
+        assert_eq!(event.as_deref(), Some(PROVENANCE));
+        let trailer: Provenance = serde_json::from_slice(data).unwrap();
+        assert_eq!(trailer.key_id, "test-key");
+
+        let failed = Ok(ResponseChunk::Stop {
+            reason: StopReason::Error,
+            error: Some("upstream failed".to_string()),
+            cost: None,
+        });
+        let out = run(vec![failed], true).await;
+        assert_eq!(out.len(), 1);
+    }
+}
@@ -0,0 +1,258 @@
+//! Response post-processing configured per virtual model routing rules
+//!
+//! Sinks differ in the extra fields they return, the model names they report
+//! and the stop reasons they use. A [`ResponseTransform`] on a virtual model's
+//! routing rules evens these out before the response reaches the client.
+
+use super::{Middleware, Next, RequestStream, ResponseStream};
+use crate::router::sink::{RequestContext, parse_content};
+use crate::router::types::{ResponseChunk, ResponseTransform};
+use crate::{Result, StateBackend};
+use async_trait::async_trait;
+use futures::StreamExt;
+use serde_json::{Map, Value as JsonValue};
+use std::sync::Arc;
+
+/// Paths of the model name in responses and stream events across protocols
+const MODEL_POINTERS: &[&str] = &["/model", "/message/model", "/response/model"];
+
+impl ResponseTransform {
+    /// Apply the transform to one content chunk
+    pub fn apply(&self, json: &mut JsonValue, requested_model: Option<&str>) {
+        if let Some(obj) = json.as_object_mut() {
+            for field in &self.strip_fields {
+                obj.remove(field);
+            }
+        }
+
+        if self.normalize_model
+            && let Some(model) = requested_model
+        {
+            for pointer in MODEL_POINTERS {
+                if let Some(value) = json.pointer_mut(pointer)
+                    && value.is_string()
+                {
+                    *value = JsonValue::String(model.to_string());
+                }
+            }
+        }
+
+        if !self.stop_reasons.is_empty() {
+            self.rewrite_stop_reason(json.pointer_mut("/stop_reason"));
+            self.rewrite_stop_reason(json.pointer_mut("/delta/stop_reason"));
+            let choices = json
+                .get_mut("choices")
+                .and_then(|c| c.as_array_mut())
+                .into_iter()
+                .flatten();
+            for choice in choices {
+                self.rewrite_stop_reason(choice.get_mut("finish_reason"));
+            }
+        }
+    }
+
+    fn rewrite_stop_reason(&self, value: Option<&mut JsonValue>) {
+        if let Some(value) = value
+            && let Some(replacement) = value.as_str().and_then(|r| self.stop_reasons.get(r))
+        {
+            *value = JsonValue::String(replacement.clone());
+        }
+    }
+
+    /// Merge the configured metadata into the final content chunk
+    pub fn finish(&self, json: &mut JsonValue) {
+        if self.metadata.is_empty() {
+            return;
+        }
+        let Some(obj) = json.as_object_mut() else {
+            return;
+        };
+        let metadata = obj
+            .entry("metadata")
+            .or_insert_with(|| JsonValue::Object(Map::new()));
+        if let Some(metadata) = metadata.as_object_mut() {
+            for (key, value) in &self.metadata {
+                metadata.insert(key.clone(), value.clone());
+            }
+        }
+    }
+
+    fn strips_header(&self, name: &str) -> bool {
+        self.strip_headers
+            .iter()
+            .any(|header| header.eq_ignore_ascii_case(name))
+    }
+}
+
+/// Apply `transform` to every chunk of a response. Each content chunk is held
+/// back until the next one arrives, so metadata can be merged into the last.
+/// Only responses with a transform pay for parsing raw content.
+pub fn transform_response(
+    stream: ResponseStream,
+    transform: ResponseTransform,
+    requested_model: Option<String>,
+) -> ResponseStream {
+    let mut stream = parse_content(stream);
+    let transformed = async_stream::stream! {
+        let mut pending: Option<JsonValue> = None;
+        while let Some(item) = stream.next().await {
+            match item {
+                Ok(ResponseChunk::Content(mut json)) => {
+                    transform.apply(&mut json, requested_model.as_deref());
+                    if let Some(previous) = pending.replace(json) {
+                        yield Ok(ResponseChunk::Content(previous));
+                    }
+                }
+                Ok(ResponseChunk::Headers(mut headers)) => {
+                    headers.retain(|name, _| !transform.strips_header(name));
+                    yield Ok(ResponseChunk::Headers(headers));
+                }
+                Ok(stop @ ResponseChunk::Stop { .. }) => {
+                    if let Some(mut last) = pending.take() {
+                        transform.finish(&mut last);
+                        yield Ok(ResponseChunk::Content(last));
+                    }
+                    yield Ok(stop);
+                }
+                Err(e) => {
+                    if let Some(last) = pending.take() {
+                        yield Ok(ResponseChunk::Content(last));
+                    }
+                    yield Err(e);
+                }
+                // Usage and metadata may pass the held back chunk
+                other => yield other,
+            }
+        }
+        if let Some(mut last) = pending {
+            transform.finish(&mut last);
+            yield Ok(ResponseChunk::Content(last));
+        }
+    };
+    Box::pin(transformed)
+}
+
+/// Middleware applying the response transform of the requested virtual model
+pub struct ResponseTransformMiddleware {
+    state_backend: Arc<dyn StateBackend>,
+}
+
+impl ResponseTransformMiddleware {
+    pub fn new(state_backend: Arc<dyn StateBackend>) -> Self {
+        Self { state_backend }
+    }
+
+    async fn transform_for(&self, model: &str) -> Option<ResponseTransform> {
+        self.state_backend
+            .get_virtual_model(model, None)
+            .await
+            .ok()
+            .flatten()
+            .and_then(|virtual_model| virtual_model.routing_rules.response_transform)
+    }
+}
+
+#[async_trait]
+impl Middleware for ResponseTransformMiddleware {
+    async fn process(
+        &self,
+        _ctx: &mut RequestContext,
+        mut request: RequestStream,
+        next: Next,
+    ) -> Result<ResponseStream> {
+        let protocol = request.protocol();
+        let Some(first) = request.next().await else {
+            return next(request).await;
+        };
+        let json = first?;
+        let model = json.get("model").and_then(|m| m.as_str()).map(String::from);
+        let transform = match &model {
+            Some(model) => self.transform_for(model).await,
+            None => None,
+        };
+
+        let rebuilt = RequestStream::new(
+            protocol,
+            Box::pin(futures::stream::once(async move { Ok(json) }).chain(request)),
+        );
+        let response = next(rebuilt).await?;
+        Ok(match transform {
+            Some(transform) => transform_response(response, transform, model),
+            None => response,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::router::types::StopReason;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn transform() -> ResponseTransform {
+        ResponseTransform {
+            strip_fields: vec!["system_fingerprint".to_string()],
+            strip_headers: vec!["OpenAI-Organization".to_string()],
+            normalize_model: true,
+            stop_reasons: HashMap::from([("end_turn".to_string(), "stop".to_string())]),
+            metadata: HashMap::from([("served_by".to_string(), json!("gate"))]),
+        }
+    }
+
+    #[test]
+    fn test_apply_strips_normalizes_and_rewrites() {
+        let mut chunk = json!({
+            "model": "gpt-4o-2024-08-06",
+            "system_fingerprint": "fp_1",
+            "choices": [{"finish_reason": "end_turn"}]
+        });
+        transform().apply(&mut chunk, Some("my-model"));
+        assert_eq!(
+            chunk,
+            json!({"model": "my-model", "choices": [{"finish_reason": "stop"}]})
+        );
+
+        let mut event = json!({"type": "message_start", "message": {"model": "claude-x"}});
+        transform().apply(&mut event, Some("my-model"));
+        assert_eq!(event["message"]["model"], "my-model");
+    }
+
+    #[tokio::test]
+    async fn test_metadata_lands_on_final_content_chunk() {
+        let chunks = vec![
+            Ok(ResponseChunk::Headers(HashMap::from([
+                ("openai-organization".to_string(), "org".to_string()),
+                ("content-type".to_string(), "text/event-stream".to_string()),
+            ]))),
+            Ok(ResponseChunk::Content(json!({"id": 1}))),
+            Ok(ResponseChunk::Content(json!({"id": 2}))),
+            Ok(ResponseChunk::Stop {
+                reason: StopReason::Complete,
+                error: None,
+                cost: None,
+            }),
+        ];
+        let stream: ResponseStream = Box::pin(futures::stream::iter(chunks));
+        let out: Vec<_> = transform_response(stream, transform(), None)
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .map(|item| item.unwrap())
+            .collect();
+
+        let ResponseChunk::Headers(headers) = &out[0] else {
+            panic!("expected headers");
+        };
+        assert_eq!(headers.len(), 1);
+        let ResponseChunk::Content(first) = &out[1] else {
+            panic!("expected content");
+        };
+        assert!(first.get("metadata").is_none());
+        let ResponseChunk::Content(last) = &out[2] else {
+            panic!("expected content");
+        };
+        assert_eq!(last["metadata"]["served_by"], "gate");
+        assert!(matches!(out[3], ResponseChunk::Stop { .. }));
+    }
+}
@@ -0,0 +1,322 @@
+//! Concurrent stream limits per API key
+//!
+//! A leaked key can open streams faster than anyone notices, and every open
+//! stream holds provider capacity. Keys may be limited to a number of
+//! streams open at once: each streaming request holds a slot in the state
+//! backend until its response stream ends or is dropped, so the limit holds
+//! across every gateway sharing the store. Requests answered in one piece
+//! hold none. Slots are leased and renewed while their stream is open, so
+//! those held by a gateway that went away free up soon after.
+
+use super::{Middleware, Next, RequestStream, ResponseStream};
+use crate::router::sink::RequestContext;
+use crate::state::StateBackend;
+use crate::tracing::metrics::counter;
+use crate::{ApiKey, Error, Result};
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use futures::StreamExt;
+use futures::future::{Either, select};
+use std::sync::Arc;
+
+/// How long a slot is held without renewal, and so at most when its stream
+/// is lost; open streams renew it at half this
+const DEFAULT_SLOT_LEASE_SECONDS: i64 = 120;
+
+/// Concurrent stream limit of a key; `None` leaves it unlimited
+pub type StreamLimit = Arc<dyn Fn(&ApiKey) -> Option<u32> + Send + Sync>;
+
+/// Middleware that rejects requests from a key holding all of its stream
+/// slots
+pub struct StreamSlotsMiddleware<S: StateBackend + ?Sized + 'static> {
+    state_backend: Arc<S>,
+    limit: StreamLimit,
+    lease: Duration,
+}
+
+impl<S: StateBackend + ?Sized + 'static> StreamSlotsMiddleware<S> {
+    /// Limit keys to the number of streams `limit` reads from them
+    pub fn new(
+        state_backend: Arc<S>,
+        limit: impl Fn(&ApiKey) -> Option<u32> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            state_backend,
+            limit: Arc::new(limit),
+            lease: Duration::seconds(DEFAULT_SLOT_LEASE_SECONDS),
+        }
+    }
+
+    /// Set how long a slot is held without renewal
+    pub fn with_lease(mut self, lease: Duration) -> Self {
+        self.lease = lease;
+        self
+    }
+}
+
+#[async_trait]
+impl<S: StateBackend + ?Sized + 'static> Middleware for StreamSlotsMiddleware<S> {
+    async fn process(
+        &self,
+        ctx: &mut RequestContext,
+        mut request: RequestStream,
+        next: Next,
+    ) -> Result<ResponseStream> {
+        let Some(key_hash) = ctx.identity.context.api_key_hash.clone() else {
+            return next(request).await;
+        };
+        let protocol = request.protocol();
+        let Some(first) = request.next().await else {
+            return next(request).await;
+        };
+        let json = first?;
+        let streaming = json.get("stream").and_then(|s| s.as_bool()) == Some(true);
+        let request = RequestStream::new(
+            protocol,
+            Box::pin(futures::stream::once(async move { Ok(json) }).chain(request)),
+        );
+        if !streaming {
+            return next(request).await;
+        }
+
+        let Some(limit) = self
+            .state_backend
+            .get_api_key(&key_hash)
+            .await?
+            .and_then(|key| (self.limit)(&key))
+        else {
+            return next(request).await;
+        };
+
+        let slot_id = uuid::Uuid::new_v4().to_string();
+        let expires_at = Utc::now() + self.lease;
+        if !self
+            .state_backend
+            .acquire_stream_slot(&key_hash, &slot_id, limit, expires_at)
+            .await?
+        {
+            let active = self.state_backend.count_stream_slots(&key_hash).await?;
+            warn!(
+                "{} holds {active} of {limit} stream slots, rejecting request",
+                ctx.identity.id
+            );
+            counter("stream_slot_rejections_total").increment();
+            return Err(Error::ConcurrencyLimit { limit, active });
+        }
+
+        let slot = HeldSlot {
+            state_backend: self.state_backend.clone(),
+            key_hash,
+            slot_id,
+            released: false,
+        };
+        let mut response = match next(request).await {
+            Ok(response) => response,
+            Err(e) => {
+                slot.release().await;
+                return Err(e);
+            }
+        };
+
+        let lease = self.lease;
+        let renew_every = (lease / 2).to_std().unwrap_or_default();
+        Ok(Box::pin(async_stream::stream! {
+            let mut renewal = Box::pin(tokio::time::sleep(renew_every));
+            loop {
+                match select(response.next(), renewal.as_mut()).await {
+                    Either::Left((Some(chunk), _)) => yield chunk,
+                    Either::Left((None, _)) => break,
+                    Either::Right(_) => {
+                        slot.renew(Utc::now() + lease).await;
+                        renewal
+                            .as_mut()
+                            .reset(tokio::time::Instant::now() + renew_every);
+                    }
+                }
+            }
+            slot.release().await;
+        }))
+    }
+}
+
+/// A slot held for one response, released once: when its stream ends, or
+/// when the stream is dropped because the client went away
+struct HeldSlot<S: StateBackend + ?Sized + 'static> {
+    state_backend: Arc<S>,
+    key_hash: String,
+    slot_id: String,
+    released: bool,
+}
+
+impl<S: StateBackend + ?Sized + 'static> HeldSlot<S> {
+    async fn renew(&self, expires_at: chrono::DateTime<Utc>) {
+        match self
+            .state_backend
+            .renew_stream_slot(&self.key_hash, &self.slot_id, expires_at)
+            .await
+        {
+            Ok(true) => {}
+            Ok(false) => warn!("Stream slot {} lapsed before it was renewed", self.slot_id),
+            Err(e) => warn!("Failed to renew stream slot: {e}"),
+        }
+    }
+
+    async fn release(mut self) {
+        self.released = true;
+        if let Err(e) = self
+            .state_backend
+            .release_stream_slot(&self.key_hash, &self.slot_id)
+            .await
+        {
+            warn!("Failed to release stream slot: {e}");
+        }
+    }
+}
+
+impl<S: StateBackend + ?Sized + 'static> Drop for HeldSlot<S> {
+    fn drop(&mut self) {
+        if self.released {
+            return;
+        }
+
+        // Dropping cannot wait, so the slot is released on the runtime
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            let state_backend = self.state_backend.clone();
+            let key_hash = std::mem::take(&mut self.key_hash);
+            let slot_id = std::mem::take(&mut self.slot_id);
+            runtime.spawn(async move {
+                if let Err(e) = state_backend.release_stream_slot(&key_hash, &slot_id).await {
+                    warn!("Failed to release stream slot of cancelled request: {e}");
+                }
+            });
+            return;
+        }
+        warn!(
+            "Stream slot {} is held until its lease runs out",
+            self.slot_id
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::access::SubjectIdentity;
+    use crate::router::sink::RouterIdentityContext;
+    use crate::router::types::{Protocol, ResponseChunk};
+    use crate::tests::state::InMemoryBackend;
+
+    fn ctx() -> RequestContext {
+        RequestContext {
+            identity: SubjectIdentity::new(
+                "key",
+                "api_key",
+                RouterIdentityContext {
+                    api_key_hash: Some("hash".to_string()),
+                    ..Default::default()
+                },
+            ),
+            correlation_id: crate::tracing::CorrelationId::new(),
+            headers: Default::default(),
+            query: None,
+            trace_id: None,
+            metadata: Default::default(),
+            response: Default::default(),
+            extensions: Default::default(),
+        }
+    }
+
+    /// Send a request, answered after `delay`
+    async fn send(
+        middleware: &StreamSlotsMiddleware<InMemoryBackend>,
+        stream: bool,
+        delay: std::time::Duration,
+    ) -> Result<ResponseStream> {
+        let request = RequestStream::new(
+            Protocol::OpenAIChat,
+            Box::pin(futures::stream::once(async move {
+                Ok(serde_json::json!({ "stream": stream }))
+            })),
+        );
+        let next: Next = Box::new(move |_| {
+            Box::pin(async move {
+                let stop = futures::stream::once(async move {
+                    tokio::time::sleep(delay).await;
+                    Ok(ResponseChunk::Stop {
+                        reason: crate::router::types::StopReason::Complete,
+                        error: None,
+                        cost: None,
+                    })
+                });
+                Ok(Box::pin(stop) as ResponseStream)
+            })
+        });
+        middleware.process(&mut ctx(), request, next).await
+    }
+
+    async fn backend() -> Arc<InMemoryBackend> {
+        let backend = Arc::new(InMemoryBackend::default());
+        backend
+            .create_api_key(
+                &ApiKey {
+                    key_hash: "hash".to_string(),
+                    name: "leaky".to_string(),
+                    org_id: "user-1".to_string(),
+                    config: None,
+                    created_at: Utc::now(),
+                    last_used_at: None,
+                },
+                "raw",
+            )
+            .await
+            .unwrap();
+        backend
+    }
+
+    #[tokio::test]
+    async fn test_streams_beyond_the_limit_are_rejected() {
+        let backend = backend().await;
+        let middleware = StreamSlotsMiddleware::new(backend.clone(), |_| Some(1));
+        let now = std::time::Duration::ZERO;
+
+        let first = send(&middleware, true, now).await.unwrap();
+        // Requests answered in one piece hold no slot
+        send(&middleware, false, now).await.unwrap();
+        assert!(matches!(
+            send(&middleware, true, now).await,
+            Err(Error::ConcurrencyLimit {
+                limit: 1,
+                active: 1
+            })
+        ));
+
+        // Finishing the first stream frees its slot
+        first.collect::<Vec<_>>().await;
+        assert_eq!(backend.count_stream_slots("hash").await.unwrap(), 0);
+        let second = send(&middleware, true, now).await.unwrap();
+
+        // So does dropping it part way
+        drop(second);
+        tokio::task::yield_now().await;
+        assert_eq!(backend.count_stream_slots("hash").await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_open_streams_renew_their_slot() {
+        let backend = backend().await;
+        let middleware = StreamSlotsMiddleware::new(backend.clone(), |_| Some(1))
+            .with_lease(Duration::milliseconds(200));
+
+        let stream = send(&middleware, true, std::time::Duration::from_millis(600))
+            .await
+            .unwrap();
+        let open = tokio::spawn(stream.collect::<Vec<_>>());
+        // Past the first lease, the stream still holds its slot
+        tokio::time::sleep(std::time::Duration::from_millis(400)).await;
+        assert_eq!(backend.count_stream_slots("hash").await.unwrap(), 1);
+
+        open.await.unwrap();
+        assert_eq!(backend.count_stream_slots("hash").await.unwrap(), 0);
+    }
+}
@@ -0,0 +1,305 @@
+//! Token usage and cost seen in a response stream
+//!
+//! Providers report usage at the end of a stream, or not at all, so a
+//! client that cancels a stream would otherwise be billed nothing. The text
+//! streamed so far is counted as it passes, and counts the provider never
+//! reported are reconstructed with the local estimate.
+//!
+//! The bytes streamed are counted too, and a response is marked when its
+//! prompt did not fit the model's context window or came close to it.
+
+use crate::router::service::estimate_text_tokens;
+use crate::router::types::{CostStructure, ResponseChunk};
+use crate::{ErrorClass, Result};
+use http::StatusCode;
+use rust_decimal::Decimal;
+use serde_json::Value as JsonValue;
+
+/// Usage record metadata key saying where its token counts came from
+pub const USAGE_SOURCE: &str = "usage_source";
+/// Counts as reported by the provider
+pub const USAGE_REPORTED: &str = "reported";
+/// Counts estimated locally, in whole or in part
+pub const USAGE_ESTIMATED: &str = "estimated";
+
+/// Usage record metadata key holding the size of the request body in bytes
+pub const REQUEST_BYTES: &str = "request_bytes";
+/// Usage record metadata key holding the bytes of response content streamed
+pub const RESPONSE_BYTES: &str = "response_bytes";
+/// Usage record metadata key set when the prompt met the context window
+pub const CONTEXT_LIMIT: &str = "context_limit";
+/// The provider rejected the prompt as too long for the context window
+pub const CONTEXT_EXCEEDED: &str = "exceeded";
+/// The prompt filled most of the context window
+pub const CONTEXT_NEAR: &str = "near";
+
+/// Share of the context window a prompt must fill to count as near it
+const NEAR_CONTEXT_PERCENT: u64 = 90;
+
+/// Usage accumulated while a response streams past. Counts are the largest
+/// seen, since protocols repeat running totals rather than deltas.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct ResponseUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost: Option<f64>,
+    pub error: bool,
+    /// Characters of generated text streamed so far
+    pub output_chars: usize,
+    /// Bytes of response content streamed so far
+    pub bytes: u64,
+    /// The provider said the prompt did not fit the context window
+    pub context_exceeded: bool,
+}
+
+impl ResponseUsage {
+    pub fn observe(&mut self, chunk: &Result<ResponseChunk>) {
+        match chunk {
+            Ok(ResponseChunk::Usage {
+                prompt_tokens,
+                completion_tokens,
+            }) => self.add_tokens(u64::from(*prompt_tokens), u64::from(*completion_tokens)),
+            Ok(ResponseChunk::Content(json)) => {
+                self.bytes += serde_json::to_vec(json).map_or(0, |v| v.len() as u64);
+                self.observe_json(json);
+            }
+            Ok(ResponseChunk::Raw { data, .. }) => {
+                self.bytes += data.len() as u64;
+                if let Ok(json) = serde_json::from_slice::<JsonValue>(data) {
+                    self.observe_json(&json);
+                }
+            }
+            Ok(ResponseChunk::Stop { error, cost, .. }) => {
+                self.error |= error.is_some();
+                self.context_exceeded |= error.as_deref().is_some_and(|e| {
+                    ErrorClass::classify(StatusCode::BAD_REQUEST, e)
+                        == ErrorClass::ContextLengthExceeded
+                });
+                if let Some(cost) = cost {
+                    self.cost = Some(cost.total_cost_usd.to_string().parse().unwrap_or(0.0));
+                    self.add_tokens(u64::from(cost.input_tokens), u64::from(cost.output_tokens));
+                }
+            }
+            Ok(_) => {}
+            Err(e) => {
+                self.error = true;
+                self.context_exceeded |= e.class() == Some(ErrorClass::ContextLengthExceeded);
+            }
+        }
+    }
+
+    fn observe_json(&mut self, json: &JsonValue) {
+        let (input, output) = content_usage(json);
+        self.add_tokens(input, output);
+        self.output_chars += generated_chars(json);
+    }
+
+    fn add_tokens(&mut self, input: u64, output: u64) {
+        self.input_tokens = self.input_tokens.max(input);
+        self.output_tokens = self.output_tokens.max(output);
+    }
+
+    /// Fill in counts the provider did not report and return the
+    /// [`USAGE_SOURCE`] of the result. `input_estimate` is the local
+    /// estimate for the request. Output counts are only trusted from a
+    /// stream that `completed`: Anthropic reports input tokens when a
+    /// message starts but output tokens only when it ends, with a
+    /// placeholder before then. A cost the provider did not report is
+    /// priced from the counts at `pricing`.
+    pub fn reconstruct(
+        &mut self,
+        input_estimate: u64,
+        completed: bool,
+        pricing: Option<&CostStructure>,
+    ) -> &'static str {
+        let mut source = USAGE_REPORTED;
+        if self.input_tokens == 0 && input_estimate > 0 {
+            self.input_tokens = input_estimate;
+            source = USAGE_ESTIMATED;
+        }
+        let output_estimate = estimate_text_tokens(self.output_chars);
+        let trusted = completed && self.output_tokens > 0;
+        if !trusted && output_estimate > self.output_tokens {
+            self.output_tokens = output_estimate;
+            source = USAGE_ESTIMATED;
+        }
+        if self.cost.is_none()
+            && let Some(pricing) = pricing
+        {
+            let cost = pricing.input_cost_per_token * Decimal::from(self.input_tokens)
+                + pricing.output_cost_per_token * Decimal::from(self.output_tokens);
+            self.cost = Some(cost.to_string().parse().unwrap_or(0.0));
+        }
+        source
+    }
+
+    /// [`CONTEXT_LIMIT`] of the response to a request for a model with
+    /// `context_window`, if its prompt met the context window
+    pub fn context_limit(&self, context_window: Option<u32>) -> Option<&'static str> {
+        if self.context_exceeded {
+            return Some(CONTEXT_EXCEEDED);
+        }
+        let context = context_window?;
+        (self.input_tokens * 100 >= u64::from(context) * NEAR_CONTEXT_PERCENT)
+            .then_some(CONTEXT_NEAR)
+    }
+}
+
+/// Characters of generated text in a response body or stream event: chat
+/// completion deltas and messages, Anthropic content block deltas and
+/// Responses API text and argument deltas
+fn generated_chars(json: &JsonValue) -> usize {
+    let chars = |value: &JsonValue| value.as_str().map_or(0, |s| s.chars().count());
+    if let Some(choices) = json["choices"].as_array() {
+        return choices
+            .iter()
+            .flat_map(|choice| [&choice["delta"], &choice["message"]])
+            .map(|message| {
+                let calls = message["tool_calls"].as_array().map_or(0, |calls| {
+                    calls
+                        .iter()
+                        .map(|call| chars(&call["function"]["arguments"]))
+                        .sum()
+                });
+                chars(&message["content"]) + chars(&message["reasoning_content"]) + calls
+            })
+            .sum();
+    }
+    match json["type"].as_str() {
+        Some("content_block_delta") => {
+            let delta = &json["delta"];
+            chars(&delta["text"]) + chars(&delta["partial_json"]) + chars(&delta["thinking"])
+        }
+        Some(kind) if kind.starts_with("response.") && kind.ends_with(".delta") => {
+            chars(&json["delta"])
+        }
+        _ => 0,
+    }
+}
+
+/// Token counts in a response body, wherever the protocol puts them
+fn content_usage(json: &JsonValue) -> (u64, u64) {
+    let usage = [
+        &json["usage"],
+        &json["message"]["usage"],
+        &json["response"]["usage"],
+    ]
+    .into_iter()
+    .find(|u| u.is_object());
+    let Some(usage) = usage else {
+        return (0, 0);
+    };
+    let count = |keys: [&str; 2]| {
+        keys.iter()
+            .find_map(|k| usage[*k].as_u64())
+            .unwrap_or_default()
+    };
+    (
+        count(["prompt_tokens", "input_tokens"]),
+        count(["completion_tokens", "output_tokens"]),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_usage_across_protocols() {
+        let chat = serde_json::json!({"usage": {"prompt_tokens": 3, "completion_tokens": 4}});
+        assert_eq!(content_usage(&chat), (3, 4));
+        let anthropic = serde_json::json!({"message": {"usage": {"input_tokens": 7}}});
+        assert_eq!(content_usage(&anthropic), (7, 0));
+        assert_eq!(content_usage(&serde_json::json!({"choices": []})), (0, 0));
+    }
+
+    #[test]
+    fn test_observe_keeps_largest_counts() {
+        let mut usage = ResponseUsage::default();
+        usage.observe(&Ok(ResponseChunk::Usage {
+            prompt_tokens: 10,
+            completion_tokens: 2,
+        }));
+        usage.observe(&Ok(ResponseChunk::Content(serde_json::json!({
+            "usage": {"input_tokens": 10, "output_tokens": 8}
+        }))));
+        usage.observe(&Err(crate::Error::Internal("boom".into())));
+        assert_eq!(usage.input_tokens, 10);
+        assert_eq!(usage.output_tokens, 8);
+        assert_eq!(usage.cost, None);
+        assert!(usage.error);
+    }
+
+    #[test]
+    fn test_cancelled_streams_are_reconstructed() {
+        let mut usage = ResponseUsage::default();
+        for event in [
+            serde_json::json!({"type": "message_start", "message": {"usage": {"input_tokens": 12, "output_tokens": 1}}}),
+            serde_json::json!({"type": "content_block_delta", "delta": {"type": "text_delta", "text": "Hello there, "}}),
+            serde_json::json!({"type": "content_block_delta", "delta": {"type": "text_delta", "text": "general Kenobi"}}),
+        ] {
+            usage.observe(&Ok(ResponseChunk::Raw {
+                event: None,
+                data: serde_json::to_vec(&event).unwrap().into(),
+            }));
+        }
+        assert_eq!(usage.output_chars, 27);
+
+        // Cut off before message_delta: input as reported, output estimated,
+        // and both priced at $15 and $75 per million tokens
+        let pricing = CostStructure {
+            input_cost_per_token: Decimal::new(15, 6),
+            output_cost_per_token: Decimal::new(75, 6),
+            cached_input_cost_per_token: None,
+            currency: "USD".into(),
+        };
+        let mut cancelled = usage.clone();
+        assert_eq!(
+            cancelled.reconstruct(50, false, Some(&pricing)),
+            USAGE_ESTIMATED
+        );
+        assert_eq!((cancelled.input_tokens, cancelled.output_tokens), (12, 7));
+        let cost = cancelled.cost.expect("priced");
+        assert!(cost > 0.0);
+        assert!((cost - (12.0 * 15.0 + 7.0 * 75.0) / 1e6).abs() < 1e-12);
+
+        // A completed stream keeps the provider's final count
+        usage.observe(&Ok(ResponseChunk::Content(serde_json::json!({
+            "type": "message_delta", "usage": {"output_tokens": 6}
+        }))));
+        assert_eq!(usage.reconstruct(50, true, None), USAGE_REPORTED);
+        assert_eq!((usage.input_tokens, usage.output_tokens), (12, 6));
+    }
+
+    #[test]
+    fn test_chat_deltas_without_usage_are_estimated() {
+        let mut usage = ResponseUsage::default();
+        usage.observe(&Ok(ResponseChunk::Content(serde_json::json!({
+            "choices": [{"delta": {"content": "abcdefgh"}}]
+        }))));
+        assert_eq!(usage.reconstruct(3, true, None), USAGE_ESTIMATED);
+        assert_eq!((usage.input_tokens, usage.output_tokens), (3, 2));
+    }
+
+    #[test]
+    fn test_bytes_and_context_limits() {
+        let mut usage = ResponseUsage::default();
+        usage.observe(&Ok(ResponseChunk::Raw {
+            event: None,
+            data: bytes::Bytes::from_static(br#"{"usage":{"input_tokens":190000}}"#),
+        }));
+        usage.observe(&Ok(ResponseChunk::Content(serde_json::json!({"a": 1}))));
+        assert_eq!(usage.bytes, 33 + 7);
+        assert_eq!(usage.context_limit(Some(200_000)), Some(CONTEXT_NEAR));
+        assert_eq!(usage.context_limit(Some(1_047_576)), None);
+        assert_eq!(usage.context_limit(None), None);
+
+        let mut rejected = ResponseUsage::default();
+        rejected.observe(&Ok(ResponseChunk::Stop {
+            reason: crate::router::types::StopReason::Error,
+            error: Some("prompt is too long: 210000 tokens > 200000 maximum".to_string()),
+            cost: None,
+        }));
+        assert_eq!(rejected.context_limit(None), Some(CONTEXT_EXCEEDED));
+    }
+}
@@ -3,7 +3,12 @@
 //! This module provides intelligent routing of inference requests across multiple
 //! providers, protocols, and deployment contexts (WASM, local daemon, Cloudflare Workers).
 
+pub mod buffers;
+pub mod concurrency;
+pub mod content_filter;
+pub mod digest;
 pub mod executor;
+pub mod extensions;
 pub mod index;
 pub mod middleware;
 pub mod plan;
@@ -11,25 +16,32 @@ pub mod prelude;
 pub mod protocols;
 pub mod record;
 pub mod registry;
+pub mod response_info;
 pub mod routing;
 pub mod service;
 pub mod signals;
 pub mod sink;
 pub mod sinks;
 pub mod strategy;
+pub mod structured;
+pub mod timings;
 pub mod types;
+pub mod workers;
 
 #[cfg(test)]
 mod tests;
 
 // Re-export main types
-pub use index::{SinkIndex, SinkSnapshot};
+pub use extensions::Extensions;
+pub use index::{HealthSample, SinkIndex, SinkSnapshot};
 pub use plan::{Route, RoutingPlan};
-pub use registry::SinkRegistry;
-pub use routing::Router;
+pub use registry::{RegistryEvent, SinkRegistry};
+pub use response_info::{ResponseInfo, ResponseMetadata};
+pub use routing::{Pipeline, Router};
 pub use sink::RequestContext;
-pub use sink::{ResponseStream, Sink, SinkDescription};
+pub use sink::{ResponseStream, Sink, SinkDescription, in_span, parse_content, with_headers};
+pub use timings::Timings;
 pub use types::{
-    ActualCost, ModelCapabilities, Protocol, ResponseChunk, SinkCapabilities, SinkHealth,
-    StopReason, VirtualModel,
+    ActualCost, CircuitState, ModelCapabilities, Protocol, ResponseChunk, ResponseTransform,
+    SinkCapabilities, SinkHealth, StopReason, VirtualModel,
 };
@@ -2,14 +2,21 @@
 
 use super::Protocol;
 use crate::Result;
+use crate::router::structured::JsonFormat;
+use crate::tracing::prelude::instrument;
 use serde_json::{Value as JsonValue, json};
 
+/// Tool Anthropic is forced to call to answer with structured output; its
+/// input is the reply
+const JSON_RESPONSE_TOOL: &str = "json_response";
+
 /// Check if conversion between protocols is possible
 pub fn can_convert(from: Protocol, to: Protocol) -> bool {
     match (from, to) {
         (Protocol::Anthropic, Protocol::OpenAIChat) => true,
         (Protocol::OpenAIChat, Protocol::Anthropic) => true,
         (Protocol::OpenAICompletions, Protocol::OpenAIChat) => true,
+        (Protocol::OpenAIResponses, Protocol::OpenAIChat) => true,
         (a, b) if a == b => true,
         _ => false,
     }
@@ -29,11 +36,16 @@ pub fn conversion_loss(from: Protocol, to: Protocol) -> Vec<String> {
         (Protocol::OpenAICompletions, Protocol::OpenAIChat) => {
             vec!["completion context becomes single user message".to_string()]
         }
+        (Protocol::OpenAIResponses, Protocol::OpenAIChat) => vec![
+            "built-in tools not supported".to_string(),
+            "reasoning items dropped".to_string(),
+        ],
         _ => vec![],
     }
 }
 
 /// Convert request between protocols
+#[instrument(level = "debug", name = "protocol.convert_request", skip(json))]
 pub fn convert_request(
     from: Protocol,
     to: Protocol,
@@ -43,6 +55,7 @@ pub fn convert_request(
         (Protocol::Anthropic, Protocol::OpenAIChat) => anthropic_to_openai_chat(json),
         (Protocol::OpenAIChat, Protocol::Anthropic) => openai_chat_to_anthropic(json),
         (Protocol::OpenAICompletions, Protocol::OpenAIChat) => completions_to_chat(json),
+        (Protocol::OpenAIResponses, Protocol::OpenAIChat) => responses_to_chat(json),
         (a, b) if a == b => Ok((json.clone(), vec![])),
         _ => Err(crate::Error::UnsupportedConversion(
             format!("{from:?}"),
@@ -60,6 +73,12 @@ pub fn convert_response(
     match (from, to) {
         (Protocol::OpenAIChat, Protocol::Anthropic) => openai_chat_response_to_anthropic(json),
         (Protocol::Anthropic, Protocol::OpenAIChat) => anthropic_response_to_openai_chat(json),
+        (Protocol::OpenAIChat, Protocol::OpenAIResponses) => {
+            openai_chat_response_to_responses(json)
+        }
+        (Protocol::OpenAIChat, Protocol::OpenAICompletions) => {
+            openai_chat_response_to_completions(json)
+        }
         (a, b) if a == b => Ok((json.clone(), vec![])),
         _ => Err(crate::Error::UnsupportedConversion(
             format!("{from:?}"),
@@ -219,17 +238,17 @@ fn openai_chat_to_anthropic(json: &JsonValue) -> Result<(JsonValue, Vec<String>)
     }
 
     // Copy common parameters
-    for field in &[
-        "temperature",
-        "max_tokens",
-        "top_p",
-        "stream",
-        "stop_sequences",
-    ] {
+    for field in &["temperature", "max_tokens", "top_p", "stream"] {
         if let Some(value) = json.get(*field) {
             result[*field] = value.clone();
         }
     }
+    // Anthropic only takes a list of stop sequences
+    match json.get("stop") {
+        Some(JsonValue::String(stop)) => result["stop_sequences"] = json!([stop]),
+        Some(stop @ JsonValue::Array(_)) => result["stop_sequences"] = stop.clone(),
+        _ => {}
+    }
 
     // Handle tools/functions
     if let Some(tools) = json.get("tools") {
@@ -252,6 +271,23 @@ fn openai_chat_to_anthropic(json: &JsonValue) -> Result<(JsonValue, Vec<String>)
         result["tools"] = json!(tools);
     }
 
+    // Structured output is a forced call to a tool taking the schema as input
+    if let Some(format) = JsonFormat::from_request(json) {
+        if json.get("tool_choice").is_some() {
+            warnings.push("tool_choice replaced to force structured output".to_string());
+        }
+        let tool = json!({
+            "name": JSON_RESPONSE_TOOL,
+            "description": "Respond with the reply as this tool's input",
+            "input_schema": format.schema.unwrap_or_else(|| json!({"type": "object"}))
+        });
+        match result["tools"].as_array_mut() {
+            Some(tools) => tools.push(tool),
+            None => result["tools"] = json!([tool]),
+        }
+        result["tool_choice"] = json!({"type": "tool", "name": JSON_RESPONSE_TOOL});
+    }
+
     // Warn about unsupported fields
     if json.get("logprobs").is_some() {
         warnings.push("logprobs not supported in Anthropic format".to_string());
@@ -303,6 +339,10 @@ fn completions_to_chat(json: &JsonValue) -> Result<(JsonValue, Vec<String>)> {
         "stop",
         "presence_penalty",
         "frequency_penalty",
+        "n",
+        "seed",
+        "user",
+        "logit_bias",
     ] {
         if let Some(value) = json.get(*field) {
             result[*field] = value.clone();
@@ -316,6 +356,12 @@ fn completions_to_chat(json: &JsonValue) -> Result<(JsonValue, Vec<String>)> {
     if json.get("echo").is_some() {
         warnings.push("echo not supported in chat format".to_string());
     }
+    if json.get("best_of").is_some() {
+        warnings.push("best_of not supported in chat format".to_string());
+    }
+    if json.get("logprobs").is_some_and(|l| !l.is_null()) {
+        warnings.push("logprobs not supported in chat format".to_string());
+    }
 
     Ok((result, warnings))
 }
@@ -366,6 +412,7 @@ fn openai_chat_response_to_anthropic(json: &JsonValue) -> Result<(JsonValue, Vec
                     Some("stop") => json!("end_turn"),
                     Some("length") => json!("max_tokens"),
                     Some("tool_calls") => json!("tool_use"),
+                    Some("content_filter") => json!("refusal"),
                     _ => finish_reason.clone(),
                 };
             }
@@ -412,14 +459,23 @@ fn anthropic_response_to_openai_chat(json: &JsonValue) -> Result<(JsonValue, Vec
         "role": json.get("role").unwrap_or(&json!("assistant"))
     });
 
+    let mut structured = false;
     if let Some(content) = json.get("content").and_then(|c| c.as_array()) {
         let mut text_parts = Vec::new();
         for block in content {
-            if let Some(block_type) = block.get("type").and_then(|t| t.as_str())
-                && block_type == "text"
-                && let Some(text) = block.get("text").and_then(|t| t.as_str())
-            {
-                text_parts.push(text.to_string());
+            match block.get("type").and_then(|t| t.as_str()) {
+                Some("text") => {
+                    if let Some(text) = block.get("text").and_then(|t| t.as_str()) {
+                        text_parts.push(text.to_string());
+                    }
+                }
+                // The structured reply replaces any text around it
+                Some("tool_use") if block.get("name") == Some(&json!(JSON_RESPONSE_TOOL)) => {
+                    structured = true;
+                    text_parts = vec![block.get("input").unwrap_or(&JsonValue::Null).to_string()];
+                    break;
+                }
+                _ => {}
             }
         }
         message["content"] = json!(text_parts.join("\n"));
@@ -433,12 +489,14 @@ fn anthropic_response_to_openai_chat(json: &JsonValue) -> Result<(JsonValue, Vec
     }
 
     // Convert stop reason to finish reason
-    let finish_reason = if let Some(stop_reason) = json.get("stop_reason").and_then(|s| s.as_str())
-    {
+    let finish_reason = if structured {
+        "stop"
+    } else if let Some(stop_reason) = json.get("stop_reason").and_then(|s| s.as_str()) {
         match stop_reason {
             "end_turn" => "stop",
             "max_tokens" => "length",
             "tool_use" => "tool_calls",
+            "refusal" => "content_filter",
             _ => stop_reason,
         }
     } else {
@@ -463,3 +521,249 @@ fn anthropic_response_to_openai_chat(json: &JsonValue) -> Result<(JsonValue, Vec
 
     Ok((result, warnings))
 }
+
+/// Flatten Responses content (a string or a list of text parts) into text
+fn responses_content_text(content: &JsonValue) -> String {
+    match content {
+        JsonValue::String(text) => text.clone(),
+        JsonValue::Array(parts) => parts
+            .iter()
+            .filter_map(|part| part.get("text").and_then(|t| t.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+/// Convert an OpenAI Responses request to OpenAI chat format
+fn responses_to_chat(json: &JsonValue) -> Result<(JsonValue, Vec<String>)> {
+    let mut warnings = Vec::new();
+    let mut result = json!({});
+    let mut messages = Vec::new();
+
+    if let Some(model) = json.get("model") {
+        result["model"] = model.clone();
+    }
+
+    if let Some(instructions) = json.get("instructions").and_then(|i| i.as_str()) {
+        messages.push(json!({"role": "system", "content": instructions}));
+    }
+
+    match json.get("input") {
+        Some(JsonValue::String(text)) => messages.push(json!({"role": "user", "content": text})),
+        Some(JsonValue::Array(items)) => {
+            for item in items {
+                match item
+                    .get("type")
+                    .and_then(|t| t.as_str())
+                    .unwrap_or("message")
+                {
+                    "message" => {
+                        let role = match item.get("role").and_then(|r| r.as_str()) {
+                            Some("developer") => "system",
+                            Some(role) => role,
+                            None => "user",
+                        };
+                        let content = item
+                            .get("content")
+                            .map(responses_content_text)
+                            .unwrap_or_default();
+                        messages.push(json!({"role": role, "content": content}));
+                    }
+                    "function_call" => messages.push(json!({
+                        "role": "assistant",
+                        "content": null,
+                        "tool_calls": [{
+                            "id": item.get("call_id"),
+                            "type": "function",
+                            "function": {
+                                "name": item.get("name"),
+                                "arguments": item.get("arguments")
+                            }
+                        }]
+                    })),
+                    "function_call_output" => messages.push(json!({
+                        "role": "tool",
+                        "tool_call_id": item.get("call_id"),
+                        "content": item.get("output")
+                    })),
+                    other => warnings.push(format!("{other} input items not supported")),
+                }
+            }
+        }
+        _ => {}
+    }
+    result["messages"] = json!(messages);
+
+    if let Some(max_tokens) = json.get("max_output_tokens") {
+        result["max_tokens"] = max_tokens.clone();
+    }
+    for field in &["temperature", "top_p", "stream", "tool_choice", "user"] {
+        if let Some(value) = json.get(*field) {
+            result[*field] = value.clone();
+        }
+    }
+
+    if let Some(tools) = json.get("tools").and_then(|t| t.as_array()) {
+        let mut converted = Vec::new();
+        for tool in tools {
+            if tool.get("type").and_then(|t| t.as_str()) == Some("function") {
+                converted.push(json!({
+                    "type": "function",
+                    "function": {
+                        "name": tool.get("name"),
+                        "description": tool.get("description"),
+                        "parameters": tool.get("parameters")
+                    }
+                }));
+            } else {
+                warnings.push("Only function tools are supported in chat format".to_string());
+            }
+        }
+        if !converted.is_empty() {
+            result["tools"] = json!(converted);
+        }
+    }
+
+    // Structured output moves from text.format to response_format
+    if let Some(format) = json.get("text").and_then(|t| t.get("format")) {
+        match format.get("type").and_then(|t| t.as_str()) {
+            Some("json_schema") => {
+                result["response_format"] = json!({
+                    "type": "json_schema",
+                    "json_schema": {
+                        "name": format.get("name"),
+                        "schema": format.get("schema"),
+                        "strict": format.get("strict")
+                    }
+                });
+            }
+            Some("json_object") => result["response_format"] = json!({"type": "json_object"}),
+            _ => {}
+        }
+    }
+
+    if json.get("reasoning").is_some() {
+        warnings.push("reasoning not supported in chat format".to_string());
+    }
+
+    Ok((result, warnings))
+}
+
+/// Convert OpenAI chat response to OpenAI Responses format
+fn openai_chat_response_to_responses(json: &JsonValue) -> Result<(JsonValue, Vec<String>)> {
+    let mut warnings = Vec::new();
+    let mut output = Vec::new();
+    let mut status = "completed";
+
+    let choices = json.get("choices").and_then(|c| c.as_array());
+    if let Some(choice) = choices.and_then(|c| c.first()) {
+        let message = choice.get("message");
+        if let Some(text) = message
+            .and_then(|m| m.get("content"))
+            .and_then(|c| c.as_str())
+            .filter(|text| !text.is_empty())
+        {
+            output.push(json!({
+                "type": "message",
+                "id": format!("msg_{}", uuid::Uuid::new_v4().simple()),
+                "role": "assistant",
+                "status": "completed",
+                "content": [{"type": "output_text", "text": text, "annotations": []}]
+            }));
+        }
+        for call in message
+            .and_then(|m| m.get("tool_calls"))
+            .and_then(|t| t.as_array())
+            .into_iter()
+            .flatten()
+        {
+            output.push(json!({
+                "type": "function_call",
+                "id": format!("fc_{}", uuid::Uuid::new_v4().simple()),
+                "call_id": call.get("id"),
+                "name": call.pointer("/function/name"),
+                "arguments": call.pointer("/function/arguments"),
+                "status": "completed"
+            }));
+        }
+        if choice.get("finish_reason").and_then(|f| f.as_str()) == Some("length") {
+            status = "incomplete";
+        }
+    }
+    if choices.is_some_and(|c| c.len() > 1) {
+        warnings.push("Multiple choices not supported in Responses format".to_string());
+    }
+
+    let mut result = json!({
+        "id": format!("resp_{}", uuid::Uuid::new_v4().simple()),
+        "object": "response",
+        "created_at": json
+            .get("created")
+            .cloned()
+            .unwrap_or_else(|| json!(chrono::Utc::now().timestamp())),
+        "model": json.get("model"),
+        "status": status,
+        "output": output
+    });
+    if status == "incomplete" {
+        result["incomplete_details"] = json!({"reason": "max_output_tokens"});
+    }
+
+    if let Some(usage) = json.get("usage") {
+        result["usage"] = json!({
+            "input_tokens": usage.get("prompt_tokens"),
+            "output_tokens": usage.get("completion_tokens"),
+            "total_tokens": usage.get("total_tokens")
+        });
+    }
+
+    Ok((result, warnings))
+}
+
+/// Convert an OpenAI chat response, or a streamed chunk of one, to the legacy
+/// completions format
+fn openai_chat_response_to_completions(json: &JsonValue) -> Result<(JsonValue, Vec<String>)> {
+    let mut warnings = Vec::new();
+    let mut choices = Vec::new();
+
+    for (position, choice) in json
+        .get("choices")
+        .and_then(|c| c.as_array())
+        .into_iter()
+        .flatten()
+        .enumerate()
+    {
+        // Full responses carry a message, stream chunks a delta
+        let message = choice.get("message").or_else(|| choice.get("delta"));
+        let text = message
+            .and_then(|m| m.get("content"))
+            .and_then(|c| c.as_str())
+            .unwrap_or_default();
+        if message.and_then(|m| m.get("tool_calls")).is_some() {
+            warnings.push("tool calls not supported in completions format".to_string());
+        }
+        choices.push(json!({
+            "text": text,
+            "index": choice.get("index").cloned().unwrap_or_else(|| json!(position)),
+            "logprobs": null,
+            "finish_reason": choice.get("finish_reason").cloned().unwrap_or(JsonValue::Null)
+        }));
+    }
+
+    let mut result = json!({
+        "id": json.get("id"),
+        "object": "text_completion",
+        "created": json
+            .get("created")
+            .cloned()
+            .unwrap_or_else(|| json!(chrono::Utc::now().timestamp())),
+        "model": json.get("model"),
+        "choices": choices
+    });
+    if let Some(usage) = json.get("usage").filter(|u| !u.is_null()) {
+        result["usage"] = usage.clone();
+    }
+
+    Ok((result, warnings))
+}
@@ -1,21 +1,43 @@
 use super::sink::Sink;
+use super::types::SinkHealth;
 use std::collections::HashMap;
 use std::sync::Arc;
+use tokio::sync::broadcast;
+
+/// Buffered registry events per subscriber before it starts lagging
+const EVENT_CAPACITY: usize = 256;
+
+/// Change to the registered sinks, pushed to subscribers such as the
+/// [`SinkIndex`](super::index::SinkIndex)
+#[derive(Debug, Clone)]
+pub enum RegistryEvent {
+    Registered(String),
+    Removed(String),
+    /// Result of an active or passive health probe
+    Health {
+        id: String,
+        health: SinkHealth,
+    },
+}
 
 /// Registry for managing sinks
 pub struct SinkRegistry {
     sinks: Arc<tokio::sync::RwLock<HashMap<String, Arc<dyn Sink>>>>,
+    events: broadcast::Sender<RegistryEvent>,
 }
 
 impl SinkRegistry {
     pub fn new() -> Self {
         Self {
             sinks: Arc::new(tokio::sync::RwLock::new(HashMap::new())),
+            events: broadcast::channel(EVENT_CAPACITY).0,
         }
     }
     pub async fn register(&self, id: String, sink: Arc<dyn Sink>) {
         let mut sinks = self.sinks.write().await;
-        sinks.insert(id, sink);
+        sinks.insert(id.clone(), sink);
+        drop(sinks);
+        self.publish(RegistryEvent::Registered(id));
     }
     pub async fn get(&self, id: &str) -> Option<Arc<dyn Sink>> {
         let sinks = self.sinks.read().await;
@@ -23,7 +45,10 @@ impl SinkRegistry {
     }
     pub async fn remove(&self, id: &str) {
         let mut sinks = self.sinks.write().await;
-        sinks.remove(id);
+        if sinks.remove(id).is_some() {
+            drop(sinks);
+            self.publish(RegistryEvent::Removed(id.to_string()));
+        }
     }
     pub async fn list_ids(&self) -> Vec<String> {
         let sinks = self.sinks.read().await;
@@ -33,6 +58,31 @@ impl SinkRegistry {
         let sinks = self.sinks.read().await;
         sinks.values().cloned().collect()
     }
+    pub async fn entries(&self) -> Vec<(String, Arc<dyn Sink>)> {
+        let sinks = self.sinks.read().await;
+        sinks
+            .iter()
+            .map(|(id, sink)| (id.clone(), sink.clone()))
+            .collect()
+    }
+
+    /// Report a health probe result for a sink
+    pub fn publish_health(&self, id: impl Into<String>, health: SinkHealth) {
+        self.publish(RegistryEvent::Health {
+            id: id.into(),
+            health,
+        });
+    }
+
+    /// Receive registry changes from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<RegistryEvent> {
+        self.events.subscribe()
+    }
+
+    fn publish(&self, event: RegistryEvent) {
+        // No subscribers is fine; nothing is watching yet
+        let _ = self.events.send(event);
+    }
 }
 
 impl Default for SinkRegistry {
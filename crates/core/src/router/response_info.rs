@@ -0,0 +1,236 @@
+//! Out-of-band facts about a response
+//!
+//! Middleware sees a response only as chunks, and what is known about it,
+//! such as the provider's request id or the model that actually answered,
+//! sits in provider-specific headers and payloads. Sinks and the executor
+//! publish those facts to the request context's [`ResponseMetadata`] as they
+//! learn them. Every clone of a context shares one channel, so middleware,
+//! usage records and response headers read them back without parsing
+//! chunks.
+
+use super::timings::Timings;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Usage metadata key holding the provider's id for a request
+pub const UPSTREAM_REQUEST_ID: &str = "upstream_request_id";
+
+/// Usage metadata key holding the model that answered a request
+pub const SERVED_MODEL: &str = "served_model";
+
+/// Usage metadata key holding the reason a provider filtered a response
+pub const CONTENT_FILTER: &str = "content_filter";
+
+/// Response header carrying the provider's id for a request
+pub const UPSTREAM_REQUEST_ID_HEADER: &str = "x-gate-upstream-request-id";
+
+/// Response header counting attempts, present when a request was retried
+pub const ATTEMPTS_HEADER: &str = "x-gate-attempts";
+
+/// Provider headers naming the request, in order of preference
+const REQUEST_ID_HEADERS: &[&str] = &["request-id", "x-request-id"];
+
+/// Prefixes of provider rate-limit headers
+const RATE_LIMIT_PREFIXES: &[&str] = &["anthropic-ratelimit-", "x-ratelimit-"];
+
+/// Stop reasons providers give when their content policy withheld or cut
+/// short a response: OpenAI's `content_filter` and Anthropic's `refusal`
+const CONTENT_FILTER_REASONS: &[&str] = &["content_filter", "refusal"];
+
+/// The reason a provider filtered the response `body` is part of, if it did.
+/// Covers OpenAI's finish reason on chat choices and incomplete responses,
+/// and Anthropic's stop reason on messages and `message_delta` events.
+pub fn content_filter_reason(body: &serde_json::Value) -> Option<&str> {
+    let choices = body
+        .get("choices")
+        .and_then(|c| c.as_array())
+        .into_iter()
+        .flatten()
+        .map(|choice| choice.get("finish_reason"));
+    let reasons = [
+        body.get("stop_reason"),
+        body.pointer("/delta/stop_reason"),
+        body.pointer("/incomplete_details/reason"),
+        body.pointer("/response/incomplete_details/reason"),
+    ];
+    choices
+        .chain(reasons)
+        .flatten()
+        .filter_map(|reason| reason.as_str())
+        .find(|reason| CONTENT_FILTER_REASONS.contains(reason))
+}
+
+/// What is known about a response besides its content
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResponseInfo {
+    /// Id the provider assigned to the request
+    pub upstream_request_id: Option<String>,
+    /// Model that answered, which may be a dated snapshot of the requested
+    /// alias
+    pub model: Option<String>,
+    /// Sink that answered
+    pub sink_id: Option<String>,
+    /// Stop reason the provider gave for filtering the response
+    pub content_filter: Option<String>,
+    /// Provider rate-limit headers, by lowercase name
+    pub rate_limits: BTreeMap<String, String>,
+    /// Requests sent to the sink, including the one that answered
+    pub attempts: u32,
+    /// How long each stage of the request took
+    pub timings: Timings,
+}
+
+impl ResponseInfo {
+    /// Take the request id and rate limits from a provider's response
+    /// headers
+    pub fn observe_headers<'a>(&mut self, headers: impl IntoIterator<Item = (&'a str, &'a str)>) {
+        let headers: HashMap<String, &str> = headers
+            .into_iter()
+            .map(|(name, value)| (name.to_ascii_lowercase(), value))
+            .collect();
+        if let Some(id) = REQUEST_ID_HEADERS
+            .iter()
+            .find_map(|name| headers.get(*name))
+        {
+            self.upstream_request_id = Some(id.to_string());
+        }
+        for (name, value) in &headers {
+            if RATE_LIMIT_PREFIXES.iter().any(|p| name.starts_with(p)) {
+                self.rate_limits.insert(name.clone(), value.to_string());
+            }
+        }
+    }
+
+    /// Take the model from a response body or stream event, where providers
+    /// put it at the top level or, for Anthropic's `message_start`, under
+    /// `message`. Returns whether a model was found.
+    pub fn observe_body(&mut self, body: &serde_json::Value) -> bool {
+        let model = body
+            .get("model")
+            .or_else(|| body.get("message").and_then(|m| m.get("model")))
+            .or_else(|| body.get("response").and_then(|r| r.get("model")))
+            .and_then(|m| m.as_str());
+        if let Some(model) = model {
+            self.model = Some(model.to_string());
+        }
+        model.is_some()
+    }
+
+    /// Note whether a response body or stream event says the provider
+    /// filtered the response. Returns whether it did.
+    pub fn observe_stop(&mut self, body: &serde_json::Value) -> bool {
+        let reason = content_filter_reason(body);
+        if let Some(reason) = reason {
+            self.content_filter = Some(reason.to_string());
+        }
+        reason.is_some()
+    }
+
+    /// Headers telling the client what the gateway learned about its request
+    pub fn headers(&self) -> HashMap<String, String> {
+        let mut headers = HashMap::new();
+        if let Some(id) = &self.upstream_request_id {
+            headers.insert(UPSTREAM_REQUEST_ID_HEADER.to_string(), id.clone());
+        }
+        if self.attempts > 1 {
+            headers.insert(ATTEMPTS_HEADER.to_string(), self.attempts.to_string());
+        }
+        headers
+    }
+
+    /// Entries for a usage record's metadata
+    pub fn usage_metadata(&self) -> HashMap<String, String> {
+        let mut metadata = HashMap::new();
+        if let Some(id) = &self.upstream_request_id {
+            metadata.insert(UPSTREAM_REQUEST_ID.to_string(), id.clone());
+        }
+        if let Some(model) = &self.model {
+            metadata.insert(SERVED_MODEL.to_string(), model.clone());
+        }
+        if let Some(reason) = &self.content_filter {
+            metadata.insert(CONTENT_FILTER.to_string(), reason.clone());
+        }
+        metadata.extend(self.timings.usage_metadata());
+        metadata
+    }
+}
+
+/// Channel for [`ResponseInfo`], shared by every clone of a request context
+#[derive(Debug, Clone, Default)]
+pub struct ResponseMetadata(Arc<Mutex<ResponseInfo>>);
+
+impl ResponseMetadata {
+    /// Record facts about the response
+    pub fn publish(&self, update: impl FnOnce(&mut ResponseInfo)) {
+        update(&mut self.0.lock().unwrap_or_else(|e| e.into_inner()));
+    }
+
+    /// Run a protocol conversion, counting its time
+    pub fn time_conversion<T>(&self, convert: impl FnOnce() -> T) -> T {
+        let started = Instant::now();
+        let converted = convert();
+        self.publish(|info| Timings::add(&mut info.timings.conversion, started.elapsed()));
+        converted
+    }
+
+    /// What has been published so far
+    pub fn get(&self) -> ResponseInfo {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_clones_share_published_facts() {
+        let metadata = ResponseMetadata::default();
+        let clone = metadata.clone();
+        clone.publish(|info| {
+            info.observe_headers([
+                ("Request-Id", "req_123"),
+                ("anthropic-ratelimit-requests-remaining", "49"),
+                ("content-type", "text/event-stream"),
+            ]);
+            info.observe_body(
+                &json!({"type": "message_start", "message": {"model": "claude-sonnet-4-20250514"}}),
+            );
+            info.attempts = 2;
+        });
+
+        let info = metadata.get();
+        assert_eq!(info.upstream_request_id.as_deref(), Some("req_123"));
+        assert_eq!(info.model.as_deref(), Some("claude-sonnet-4-20250514"));
+        assert_eq!(
+            info.rate_limits.keys().collect::<Vec<_>>(),
+            ["anthropic-ratelimit-requests-remaining"]
+        );
+        assert_eq!(info.headers()[ATTEMPTS_HEADER], "2");
+        assert_eq!(info.usage_metadata()[UPSTREAM_REQUEST_ID], "req_123");
+    }
+
+    #[test]
+    fn test_content_filter_reasons_across_protocols() {
+        let filtered = [
+            json!({"choices": [{"finish_reason": "stop"}, {"finish_reason": "content_filter"}]}),
+            json!({"type": "message", "stop_reason": "refusal"}),
+            json!({"type": "message_delta", "delta": {"stop_reason": "refusal"}}),
+            json!({"type": "response.incomplete", "response": {"incomplete_details": {"reason": "content_filter"}}}),
+        ];
+        for body in &filtered {
+            assert!(content_filter_reason(body).is_some(), "{body}");
+        }
+        assert_eq!(
+            content_filter_reason(&json!({"choices": [{"finish_reason": "stop"}]})),
+            None
+        );
+
+        let mut info = ResponseInfo::default();
+        assert!(info.observe_stop(&filtered[1]));
+        assert_eq!(info.usage_metadata()[CONTENT_FILTER], "refusal");
+    }
+}
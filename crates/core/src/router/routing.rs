@@ -1,33 +1,68 @@
 //! Router implementation for intelligent request routing
 
+use super::SinkHealth;
+use super::concurrency::{ConcurrencyGate, ConcurrencyLimits};
+use super::content_filter::ContentFilterPolicy;
 use super::executor::PlanExecutor;
+use super::extensions::{EstimatedTokens, SelectedSink};
 use super::index::SinkIndex;
-use super::middleware::Middleware;
+use super::middleware::{Middleware, RequestRewriter};
 use super::plan::{Route, RoutingPlan};
 use super::registry::SinkRegistry;
-use super::sink::{RequestContext, ResponseStream, Sink, SinkDescription};
-use super::strategy::{RoutingStrategy, ScoredRoute, SimpleStrategy, SinkCandidate};
+use super::service::estimate_tokens;
+use super::sink::{RequestContext, ResponseStream, Sink, SinkDescription, in_span};
+use super::strategy::{
+    RoutingStrategy, ScoredRoute, SimpleStrategy, SinkCandidate, TASK_TYPE, task_type,
+};
+use super::timings::Timings;
 use super::types::{Protocol, RequestCapabilities, RequestDescriptor, RequestStream, RetryConfig};
-use super::{SinkHealth, SinkSnapshot};
+use super::workers::offload;
 use crate::Result;
 use crate::router::SinkCapabilities;
 use crate::router::types::ModelList;
 use crate::state::StateBackend;
+use crate::tracing::prelude::{Instrument, instrument};
 use async_trait::async_trait;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
 use futures::future::BoxFuture;
 use std::cmp::Ordering;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+/// Default deadline for describing and probing a sink without an index
+const DEFAULT_PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Default number of sinks probed at once without an index
+const DEFAULT_PROBE_CONCURRENCY: usize = 16;
+
+/// How long a sink's `describe()` result is reused
+const DESCRIBE_CACHE_TTL: chrono::TimeDelta = chrono::TimeDelta::seconds(60);
+
+/// Rewriters and middleware requests pass through, in order. A router's
+/// pipeline is replaced as a whole, so a request sees either the old one or
+/// the new one.
+#[derive(Clone, Default)]
+pub struct Pipeline {
+    pub rewriters: Vec<Arc<dyn RequestRewriter>>,
+    pub middleware: Vec<Arc<dyn Middleware>>,
+    /// What to do with responses providers filter
+    pub content_filter: Option<Arc<ContentFilterPolicy>>,
+}
 
 /// Router - makes routing decisions
 pub struct Router {
     state_backend: Arc<dyn StateBackend>,
     sink_registry: Arc<SinkRegistry>,
     strategy: Box<dyn RoutingStrategy>,
-    middleware: Vec<Arc<dyn Middleware>>,
+    pipeline: std::sync::RwLock<Arc<Pipeline>>,
     sink_index: Option<Arc<SinkIndex>>, // Optional fast-path index
+    probe_timeout: Duration,
+    probe_concurrency: usize,
+    describe_cache: RwLock<HashMap<String, (SinkDescription, DateTime<Utc>)>>,
+    concurrency: Option<Arc<ConcurrencyGate>>,
 }
 
 impl Router {
@@ -37,11 +72,13 @@ impl Router {
     }
 
     /// Route a request to create a plan
+    #[instrument(name = "router.route", skip_all, fields(model = %desc.model))]
     pub async fn route(
         &self,
         ctx: &RequestContext,
         desc: &RequestDescriptor,
     ) -> Result<RoutingPlan> {
+        let started = Instant::now();
         // Resolve aliases for model
         let concrete_models = self.resolve_model(&desc.model).await?;
 
@@ -69,8 +106,59 @@ impl Router {
         // Convert to routes
         let (primary, fallbacks) = self.create_routes(scored_routes)?;
 
-        // Create plan
-        Ok(RoutingPlan::new(ctx.clone(), primary, fallbacks))
+        // Create plan, noting the task type for usage records, and the
+        // model's limits and pricing at the chosen sink for middleware
+        let mut ctx = ctx.clone();
+        ctx.metadata
+            .insert(TASK_TYPE.to_string(), task_type(desc).to_string());
+        if let Some(sink) = self.sink_registry.get(&primary.sink_id).await {
+            if let Some(limits) = sink.model_limits(&desc.model) {
+                ctx.extensions.insert(limits);
+            }
+            let description = self
+                .cached_description(&primary.sink_id, sink.as_ref())
+                .await;
+            if let Some(pricing) = description.cost_structure {
+                ctx.extensions.insert(pricing);
+            }
+        }
+        ctx.response
+            .publish(|info| Timings::add(&mut info.timings.routing, started.elapsed()));
+        Ok(RoutingPlan::new(ctx, primary, fallbacks))
+    }
+
+    /// Apply request rewriters in order, before the request is routed, and
+    /// note the size of the request they leave
+    #[instrument(name = "router.rewrite", skip_all, fields(protocol = ?protocol))]
+    pub async fn rewrite(
+        &self,
+        ctx: &mut RequestContext,
+        protocol: Protocol,
+        request: &mut serde_json::Value,
+    ) -> Result<()> {
+        for rewriter in &self.pipeline().rewriters {
+            rewriter.rewrite(ctx, protocol, request).await?;
+        }
+        let body = std::mem::take(request);
+        let (body, tokens) = offload(move || {
+            let tokens = estimate_tokens(&body);
+            (body, tokens)
+        })
+        .await?;
+        *request = body;
+        ctx.extensions.insert(EstimatedTokens(tokens));
+        Ok(())
+    }
+
+    /// The rewriters and middleware requests pass through now
+    pub fn pipeline(&self) -> Arc<Pipeline> {
+        self.pipeline.read().expect("poisoned").clone()
+    }
+
+    /// Send requests through `pipeline` from now on. Requests already past
+    /// a stage finish with the pipeline they started with.
+    pub fn replace_pipeline(&self, pipeline: Pipeline) {
+        *self.pipeline.write().expect("poisoned") = Arc::new(pipeline);
     }
 
     /// Execute a routing plan
@@ -81,16 +169,36 @@ impl Router {
     ) -> Result<ResponseStream> {
         debug!("Executing routing plan: {:?}", self.sink_index);
 
-        let executor = PlanExecutor::new(self.sink_registry.clone());
+        let pipeline = self.pipeline();
+        let executor = PlanExecutor::new(self.sink_registry.clone())
+            .with_gate(self.concurrency.clone())
+            .with_content_filter(pipeline.content_filter.clone());
 
         // Build middleware pipeline around the executor
-        let middlewares = self.middleware.clone();
-        let ctx_arc = Arc::new(plan.context.clone());
-
-        // Terminal handler
+        let middlewares = pipeline.middleware.clone();
+        let span = info_span!(
+            "router.execute",
+            sink_id = %plan.primary_route.sink_id,
+            correlation_id = %plan.context.correlation_id,
+        );
+        let mut ctx = plan.context.clone();
+        ctx.metadata.insert(
+            super::middleware::SINK_ID.to_string(),
+            plan.primary_route.sink_id.clone(),
+        );
+        ctx.extensions
+            .insert(SelectedSink(plan.primary_route.sink_id.clone()));
+        let ctx_arc = Arc::new(ctx);
+
+        // Terminal handler; the request waited in the middleware until now
+        let started = Instant::now();
         let terminal = move |req: RequestStream| {
             let executor = executor;
             let plan = plan;
+            let queued = started.elapsed();
+            plan.context
+                .response
+                .publish(|info| Timings::add(&mut info.timings.queue, queued));
             Box::pin(async move { executor.execute(plan, req).await })
                 as BoxFuture<'static, Result<ResponseStream>>
         };
@@ -104,14 +212,30 @@ impl Router {
                 let mw = mw.clone();
                 Box::pin(async move {
                     let mut ctx_clone = (*ctx_iter).clone();
-                    mw.process(&mut ctx_clone, req_stream, prev_next).await
+                    let span = debug_span!("middleware", name = mw.name());
+                    let stream = mw
+                        .process(&mut ctx_clone, req_stream, prev_next)
+                        .instrument(span.clone())
+                        .await?;
+                    Ok(in_span(stream, span))
                 })
             });
         }
 
-        // Kick off the chain
-        next(request).await
+        // Kick off the chain; the response streams inside the same span
+        let stream = next(request).instrument(span.clone()).await?;
+        Ok(in_span(stream, span))
+    }
+    /// Registry of sinks this router dispatches to
+    pub fn sink_registry(&self) -> &Arc<SinkRegistry> {
+        &self.sink_registry
+    }
+
+    /// Snapshot index used for fast routing, if attached
+    pub fn sink_index(&self) -> Option<&Arc<SinkIndex>> {
+        self.sink_index.as_ref()
     }
+
     /// Refresh the attached sink index from the current registry. Returns number of refreshed sinks.
     pub async fn refresh_index(&self) -> Result<usize> {
         if let Some(index) = &self.sink_index {
@@ -143,132 +267,97 @@ impl Router {
         let mut candidates = Vec::new();
 
         if let Some(index) = index {
-            // Use snapshots for hot path
-            let snapshots = index.list().await;
-            for (
-                sink_id,
-                SinkSnapshot {
-                    description,
-                    health,
-                    ..
-                },
-            ) in snapshots
-            {
-                let Some(sink) = self.sink_registry.get(&sink_id).await else {
-                    continue;
-                };
-
-                // Check if sink is healthy
-                if !health.healthy {
-                    continue;
-                }
-
-                // Check if sink supports any of the models
-                let supports_model = models.iter().any(|model| description.supports_model(model));
-                if !supports_model {
+            // Use snapshots for hot path; reading them takes no lock
+            let snapshots = index.snapshot();
+            for (sink_id, snapshot) in snapshots.iter() {
+                if snapshot.disabled {
                     continue;
                 }
-
-                // Check protocol support (no conversion in v2)
-                if !description.accepts_protocol(protocol) {
-                    continue;
-                }
-
-                // Check capability support
-                if req_caps.needs_streaming && !description.capabilities.supports_streaming {
-                    continue;
-                }
-
-                if req_caps.needs_tools && !description.capabilities.supports_tools {
+                let Some(sink) = self.sink_registry.get(sink_id).await else {
                     continue;
-                }
-
-                // Modalities (must include all requested)
-                let sink_modalities: HashSet<_> = description
-                    .capabilities
-                    .modalities
-                    .iter()
-                    .cloned()
-                    .collect();
-
-                let req_modalities: HashSet<_> = req_caps.modalities.iter().cloned().collect();
-                if !req_modalities.is_subset(&sink_modalities) {
+                };
+                if !is_eligible(
+                    &snapshot.description,
+                    &snapshot.health,
+                    models,
+                    protocol,
+                    req_caps,
+                    context_hint,
+                ) {
                     continue;
                 }
-
-                // Context length best-effort check
-                if let (Some(max_ctx), Some(input_hint)) =
-                    (description.capabilities.max_context_length, context_hint)
-                {
-                    let want_out = req_caps.max_tokens.unwrap_or(0) as usize;
-                    if input_hint + want_out > max_ctx {
-                        continue;
-                    }
-                }
-
                 candidates.push(SinkCandidate {
-                    sink: sink.clone(),
-                    description,
-                    health,
+                    sink,
+                    description: snapshot.description.clone(),
+                    health: snapshot.health.clone(),
                     needs_conversion: None,
                 });
             }
         } else {
-            // Fallback: query sinks directly (slower)
-            let sinks = self.sink_registry.get_all().await;
-
-            for sink in sinks {
-                let description = sink.describe().await;
-                let health = sink.probe().await;
-
-                if !health.healthy {
-                    continue;
-                }
-
-                let supports_model = models.iter().any(|model| description.supports_model(model));
-                if !supports_model {
-                    continue;
-                }
-
-                if !description.accepts_protocol(protocol) {
-                    continue;
+            // Fallback: query sinks directly, concurrently and with a deadline
+            // so one hung sink cannot hold up the decision
+            let probed: Vec<_> = futures::stream::iter(self.sink_registry.entries().await)
+                .map(|(id, sink)| self.probe_candidate(id, sink))
+                .buffer_unordered(self.probe_concurrency.max(1))
+                .collect()
+                .await;
+
+            for (sink, description, health) in probed.into_iter().flatten() {
+                if is_eligible(
+                    &description,
+                    &health,
+                    models,
+                    protocol,
+                    req_caps,
+                    context_hint,
+                ) {
+                    candidates.push(SinkCandidate {
+                        sink,
+                        description,
+                        health,
+                        needs_conversion: None,
+                    });
                 }
+            }
+        }
 
-                if req_caps.needs_streaming && !description.capabilities.supports_streaming {
-                    continue;
-                }
-                if req_caps.needs_tools && !description.capabilities.supports_tools {
-                    continue;
-                }
-                let sink_modalities: HashSet<_> = description
-                    .capabilities
-                    .modalities
-                    .iter()
-                    .cloned()
-                    .collect();
-                let req_modalities: HashSet<_> = req_caps.modalities.iter().cloned().collect();
-                if !req_modalities.is_subset(&sink_modalities) {
-                    continue;
-                }
-                if let (Some(max_ctx), Some(input_hint)) =
-                    (description.capabilities.max_context_length, context_hint)
-                {
-                    let want_out = req_caps.max_tokens.unwrap_or(0) as usize;
-                    if input_hint + want_out > max_ctx {
-                        continue;
-                    }
-                }
+        Ok(candidates)
+    }
 
-                candidates.push(SinkCandidate {
-                    sink: sink.clone(),
-                    description,
-                    health,
-                    needs_conversion: None,
-                });
+    /// Describe and probe one sink within the probe timeout. Sinks that do
+    /// not answer in time are left out of this decision.
+    async fn probe_candidate(
+        &self,
+        id: String,
+        sink: Arc<dyn Sink>,
+    ) -> Option<(Arc<dyn Sink>, SinkDescription, SinkHealth)> {
+        let probe = async {
+            let description = self.cached_description(&id, sink.as_ref()).await;
+            (description, sink.probe().await)
+        };
+        match tokio::time::timeout(self.probe_timeout, probe).await {
+            Ok((description, health)) => Some((sink, description, health)),
+            Err(_) => {
+                warn!("Probing sink {id} timed out after {:?}", self.probe_timeout);
+                None
             }
         }
+    }
 
-        Ok(candidates)
+    /// `describe()` result for a sink, reused for [`DESCRIBE_CACHE_TTL`]
+    async fn cached_description(&self, id: &str, sink: &dyn Sink) -> SinkDescription {
+        let now = Utc::now();
+        if let Some((description, at)) = self.describe_cache.read().await.get(id)
+            && now - *at < DESCRIBE_CACHE_TTL
+        {
+            return description.clone();
+        }
+        let description = sink.describe().await;
+        self.describe_cache
+            .write()
+            .await
+            .insert(id.to_string(), (description.clone(), now));
+        description
     }
 
     /// Create routes from scored routes
@@ -303,6 +392,60 @@ impl Router {
     }
 }
 
+/// Whether a sink can serve a request given its description and health
+fn is_eligible(
+    description: &SinkDescription,
+    health: &SinkHealth,
+    models: &[String],
+    protocol: Protocol,
+    req_caps: &RequestCapabilities,
+    context_hint: Option<usize>,
+) -> bool {
+    if !health.routable() {
+        return false;
+    }
+
+    // Check if sink supports any of the models
+    if !models.iter().any(|model| description.supports_model(model)) {
+        return false;
+    }
+
+    // Check protocol support (no conversion in v2)
+    if !description.accepts_protocol(protocol) {
+        return false;
+    }
+
+    // Check capability support
+    if req_caps.needs_streaming && !description.capabilities.supports_streaming {
+        return false;
+    }
+    if req_caps.needs_tools && !description.capabilities.supports_tools {
+        return false;
+    }
+
+    // Modalities (must include all requested)
+    let sink_modalities: HashSet<_> = description.capabilities.modalities.iter().collect();
+    if !req_caps
+        .modalities
+        .iter()
+        .all(|modality| sink_modalities.contains(modality))
+    {
+        return false;
+    }
+
+    // Context length best-effort check
+    if let (Some(max_ctx), Some(input_hint)) =
+        (description.capabilities.max_context_length, context_hint)
+    {
+        let want_out = req_caps.max_tokens.unwrap_or(0) as usize;
+        if input_hint + want_out > max_ctx {
+            return false;
+        }
+    }
+
+    true
+}
+
 // Router also implements Sink for composability
 #[async_trait]
 impl Sink for Router {
@@ -348,6 +491,8 @@ impl Sink for Router {
                 supports_streaming,
                 supports_batching: false,
                 supports_tools,
+                // Sinks without native support are handled by the executor
+                supports_structured_output: true,
                 max_context_length: Some(max_context),
                 modalities: vec!["text".to_string()],
             },
@@ -401,7 +546,11 @@ pub struct RouterBuilder {
     sink_registry: Option<Arc<SinkRegistry>>,
     strategy: Option<Box<dyn RoutingStrategy>>,
     middleware: Vec<Arc<dyn Middleware>>,
+    rewriters: Vec<Arc<dyn RequestRewriter>>,
     sink_index: Option<Arc<SinkIndex>>,
+    probe_timeout: Duration,
+    probe_concurrency: usize,
+    concurrency: Option<Arc<ConcurrencyGate>>,
 }
 
 impl RouterBuilder {
@@ -412,7 +561,11 @@ impl RouterBuilder {
             sink_registry: None,
             strategy: None,
             middleware: Vec::new(),
+            rewriters: Vec::new(),
             sink_index: None,
+            probe_timeout: DEFAULT_PROBE_TIMEOUT,
+            probe_concurrency: DEFAULT_PROBE_CONCURRENCY,
+            concurrency: None,
         }
     }
 
@@ -440,12 +593,44 @@ impl RouterBuilder {
         self
     }
 
+    /// Add a rewriter applied before requests are routed
+    pub fn rewriter(mut self, rewriter: Arc<dyn RequestRewriter>) -> Self {
+        self.rewriters.push(rewriter);
+        self
+    }
+
+    /// Add the rewriters and middleware of `pipeline`, after those already
+    /// added
+    pub fn pipeline(mut self, pipeline: Pipeline) -> Self {
+        self.rewriters.extend(pipeline.rewriters);
+        self.middleware.extend(pipeline.middleware);
+        self
+    }
+
     /// Set an optional sink index to use for fast routing
     pub fn sink_index(mut self, index: Arc<SinkIndex>) -> Self {
         self.sink_index = Some(index);
         self
     }
 
+    /// Deadline for describing and probing one sink when routing without an index
+    pub fn probe_timeout(mut self, timeout: Duration) -> Self {
+        self.probe_timeout = timeout;
+        self
+    }
+
+    /// Number of sinks probed at once when routing without an index
+    pub fn probe_concurrency(mut self, concurrency: usize) -> Self {
+        self.probe_concurrency = concurrency;
+        self
+    }
+
+    /// Limit the requests in flight at each sink and for each identity
+    pub fn concurrency_limits(mut self, limits: ConcurrencyLimits) -> Self {
+        self.concurrency = Some(Arc::new(ConcurrencyGate::new(limits)));
+        self
+    }
+
     /// Build the router
     pub fn build(self) -> Router {
         Router {
@@ -456,8 +641,16 @@ impl RouterBuilder {
             strategy: self
                 .strategy
                 .unwrap_or_else(|| Box::new(SimpleStrategy::new())),
-            middleware: self.middleware,
+            pipeline: std::sync::RwLock::new(Arc::new(Pipeline {
+                rewriters: self.rewriters,
+                middleware: self.middleware,
+                content_filter: None,
+            })),
             sink_index: self.sink_index,
+            probe_timeout: self.probe_timeout,
+            probe_concurrency: self.probe_concurrency,
+            describe_cache: RwLock::new(HashMap::new()),
+            concurrency: self.concurrency,
         }
     }
 }
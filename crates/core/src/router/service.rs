@@ -7,6 +7,7 @@ use crate::router::routing::Router;
 use crate::router::sink::RequestContext;
 use crate::router::types::Protocol;
 use crate::router::types::{RequestDescriptor, RequestStream};
+use crate::router::workers::offload;
 use serde_json::Value as JsonValue;
 
 /// Build a RequestDescriptor from a single JSON request body for a known protocol
@@ -28,6 +29,29 @@ pub fn descriptor_from_json_with_protocol(
     })
 }
 
+/// Rough local token estimate for text: characters / 4
+pub fn estimate_text_tokens(chars: usize) -> u64 {
+    chars.div_ceil(4) as u64
+}
+
+/// Rough local token estimate for a request: characters of string content / 4.
+/// Keys, the model name and JSON punctuation are not counted.
+pub fn estimate_tokens(json: &JsonValue) -> u64 {
+    fn text_chars(value: &JsonValue) -> usize {
+        match value {
+            JsonValue::String(s) => s.chars().count(),
+            JsonValue::Array(items) => items.iter().map(text_chars).sum(),
+            JsonValue::Object(map) => map
+                .iter()
+                .filter(|(key, _)| key.as_str() != "model")
+                .map(|(_, value)| text_chars(value))
+                .sum(),
+            _ => 0,
+        }
+    }
+    estimate_text_tokens(text_chars(json))
+}
+
 /// Build a one-off RequestStream from a single JSON payload
 pub fn one_shot_stream(protocol: Protocol, json: JsonValue) -> RequestStream {
     RequestStream::new(
@@ -36,15 +60,48 @@ pub fn one_shot_stream(protocol: Protocol, json: JsonValue) -> RequestStream {
     )
 }
 
-/// Route and execute for a single JSON request with a known protocol
+/// Rewrite, route and execute a single JSON request with a known protocol
 pub async fn route_and_execute_json_with_protocol(
     router: &Router,
     ctx: &RequestContext,
     protocol: Protocol,
-    json: JsonValue,
+    mut json: JsonValue,
 ) -> Result<MwResponseStream> {
+    let mut ctx = ctx.clone();
+    router.rewrite(&mut ctx, protocol, &mut json).await?;
     let desc = descriptor_from_json_with_protocol(&json, protocol)?;
-    let plan = router.route(ctx, &desc).await?;
+    let plan = router.route(&ctx, &desc).await?;
     let stream = one_shot_stream(protocol, json);
     router.execute(plan, stream).await
 }
+
+/// Count input tokens for an Anthropic Messages request.
+///
+/// Asks the routed sinks in plan order and falls back to [`estimate_tokens`]
+/// when none of them can count or routing fails, estimating on the shared
+/// worker pool.
+pub async fn count_tokens(router: &Router, ctx: &RequestContext, json: JsonValue) -> u64 {
+    let plan = match descriptor_from_json_with_protocol(&json, Protocol::Anthropic) {
+        Ok(desc) => router.route(ctx, &desc).await,
+        Err(e) => Err(e),
+    };
+    match plan {
+        Ok(plan) => {
+            let routes = std::iter::once(&plan.primary_route).chain(&plan.fallback_routes);
+            for route in routes {
+                let Some(sink) = router.sink_registry().get(&route.sink_id).await else {
+                    continue;
+                };
+                match sink.count_tokens(ctx, &json).await {
+                    Ok(Some(tokens)) => return tokens,
+                    Ok(None) => {}
+                    Err(e) => debug!("Sink {} failed to count tokens: {e}", route.sink_id),
+                }
+            }
+        }
+        Err(e) => debug!("No route for token counting, using local estimate: {e}"),
+    }
+    offload(move || estimate_tokens(&json))
+        .await
+        .unwrap_or_default()
+}
@@ -1,12 +1,14 @@
 //! Sink trait and core implementations
 
+use super::extensions::Extensions;
+use super::response_info::ResponseMetadata;
 use super::types::{
-    CostStructure, ModelList, Protocol, ResponseChunk, SinkCapabilities, SinkHealth,
+    CostStructure, ModelLimits, ModelList, Protocol, ResponseChunk, SinkCapabilities, SinkHealth,
 };
 use crate::{
     Result,
     access::{IdentityContext, SubjectIdentity},
-    tracing::CorrelationId,
+    tracing::{CorrelationId, prelude::Span},
 };
 use async_trait::async_trait;
 use futures::Stream;
@@ -17,6 +19,40 @@ use std::pin::Pin;
 /// Stream of response chunks
 pub type ResponseStream = Pin<Box<dyn Stream<Item = Result<ResponseChunk>> + Send>>;
 
+/// Parse any raw content in `stream`, for consumers that read or rewrite it
+pub fn parse_content(stream: ResponseStream) -> ResponseStream {
+    use futures::StreamExt;
+    Box::pin(stream.map(|item| item.map(ResponseChunk::parsed)))
+}
+
+/// Poll `stream` inside `span`, so what sinks and middleware log while a
+/// response streams stays in the trace of the request that started it
+pub fn in_span(mut stream: ResponseStream, span: Span) -> ResponseStream {
+    Box::pin(futures::stream::poll_fn(move |cx| {
+        let _entered = span.enter();
+        stream.as_mut().poll_next(cx)
+    }))
+}
+
+/// Add `headers` to the headers chunk a response starts with, inserting one
+/// if it has none
+pub async fn with_headers(
+    mut stream: ResponseStream,
+    headers: std::collections::HashMap<String, String>,
+) -> ResponseStream {
+    use futures::StreamExt;
+    let head = match stream.next().await {
+        Some(Ok(ResponseChunk::Headers(mut existing))) => {
+            existing.extend(headers);
+            vec![Ok(ResponseChunk::Headers(existing))]
+        }
+        other => std::iter::once(Ok(ResponseChunk::Headers(headers)))
+            .chain(other)
+            .collect(),
+    };
+    Box::pin(futures::stream::iter(head).chain(stream))
+}
+
 /// Request context for request execution
 #[derive(Debug, Clone)]
 pub struct RequestContext {
@@ -28,6 +64,12 @@ pub struct RequestContext {
     pub query: Option<String>,
     pub trace_id: Option<String>,
     pub metadata: std::collections::HashMap<String, String>,
+    /// Facts about the response, published by the sink and executor as they
+    /// learn them
+    pub response: ResponseMetadata,
+    /// Typed values middleware hand each other; see
+    /// [`extensions`](super::extensions) for the ones the router sets
+    pub extensions: Extensions,
 }
 
 /// Identity context specific to router
@@ -70,14 +112,39 @@ pub trait Sink: Send + Sync {
     async fn describe(&self) -> SinkDescription;
 
     /// Check sink health
+    ///
+    /// Called on the routing path, so implementations must not do network I/O here.
     async fn probe(&self) -> SinkHealth;
 
+    /// Actively verify the sink is reachable, e.g. on an operator request.
+    /// Defaults to the passive probe.
+    async fn check(&self) -> SinkHealth {
+        self.probe().await
+    }
+
+    /// Token limits the provider reports for `model`, if it reports any
+    ///
+    /// Called on the routing path, so implementations must not do network I/O here.
+    fn model_limits(&self, _model: &str) -> Option<ModelLimits> {
+        None
+    }
+
     /// Execute request
     async fn execute(
         &self,
         ctx: &RequestContext,
         request: super::types::RequestStream,
     ) -> Result<ResponseStream>;
+
+    /// Count the input tokens of an Anthropic Messages request using the provider.
+    /// Returns `None` when the sink has no way to count tokens.
+    async fn count_tokens(
+        &self,
+        _ctx: &RequestContext,
+        _request: &serde_json::Value,
+    ) -> Result<Option<u64>> {
+        Ok(None)
+    }
 }
 
 /// Description of a sink's capabilities
@@ -1,13 +1,16 @@
 //! Mock sink implementations for testing
 
-use crate::Result;
 use crate::router::sink::{RequestContext, Sink, SinkDescription};
 use crate::router::types::RequestStream;
-use crate::router::types::{ModelList, Protocol, ResponseChunk, SinkCapabilities, SinkHealth};
+use crate::router::types::{
+    CostStructure, ModelList, Protocol, ResponseChunk, SinkCapabilities, SinkHealth,
+};
+use crate::{Error, Result, UpstreamError};
 use async_trait::async_trait;
 use futures::StreamExt;
 use serde_json::json;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 pub struct MockSink {
     pub id: String,
@@ -15,6 +18,10 @@ pub struct MockSink {
     pub models: ModelList,
     pub capabilities: SinkCapabilities,
     pub healthy: bool,
+    pub cost_structure: Option<CostStructure>,
+    /// Provider error returned by the next `failures` executions
+    pub failure: Option<(u16, String)>,
+    pub failures: AtomicUsize,
 }
 
 impl MockSink {
@@ -27,13 +34,25 @@ impl MockSink {
                 supports_streaming: true,
                 supports_batching: false,
                 supports_tools: true,
+                supports_structured_output: false,
                 max_context_length: Some(128_000),
                 modalities: vec!["text".into()],
             },
             healthy: true,
+            cost_structure: None,
+            failure: None,
+            failures: AtomicUsize::new(0),
         }
     }
 
+    /// A sink whose first `times` executions fail with a provider error
+    pub fn failing(id: &str, status: u16, body: &str, times: usize) -> Self {
+        let mut s = Self::success(id);
+        s.failure = Some((status, body.to_string()));
+        s.failures = AtomicUsize::new(times);
+        s
+    }
+
     pub fn unhealthy(id: &str) -> Self {
         let mut s = Self::success(id);
         s.healthy = false;
@@ -49,7 +68,7 @@ impl Sink for MockSink {
             accepted_protocols: self.accepted_protocols.clone(),
             models: self.models.clone(),
             capabilities: self.capabilities.clone(),
-            cost_structure: None,
+            cost_structure: self.cost_structure.clone(),
         }
     }
 
@@ -68,6 +87,16 @@ impl Sink for MockSink {
         _ctx: &RequestContext,
         mut request: RequestStream,
     ) -> Result<Pin<Box<dyn futures::Stream<Item = Result<ResponseChunk>> + Send>>> {
+        if let Some((status, body)) = &self.failure
+            && self
+                .failures
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_ok()
+        {
+            let status = http::StatusCode::from_u16(*status).unwrap_or_default();
+            return Err(Error::Upstream(UpstreamError::new(&self.id, status, body)));
+        }
+
         // Consume the request and echo a simple response
         let mut messages = Vec::new();
         while let Some(item) = request.next().await {
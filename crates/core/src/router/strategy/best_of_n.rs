@@ -61,8 +61,8 @@ impl RoutingStrategy for BestOfNStrategy {
         // For best-of-N, we want to select N candidates that will all be executed
         // Score them equally high so they're all selected
 
-        // Filter to healthy candidates only
-        candidates.retain(|c| c.health.healthy);
+        // Filter to candidates the circuit breaker lets through
+        candidates.retain(|c| c.health.routable());
 
         if candidates.is_empty() {
             return Ok(vec![]);
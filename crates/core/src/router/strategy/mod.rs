@@ -4,12 +4,14 @@ mod best_of_n;
 mod cost;
 mod latency;
 mod provider_affinity;
+mod quality;
 mod weighted;
 
 pub use best_of_n::{BestOfNStrategy, SelectionMethod};
 pub use cost::CostOptimizedStrategy;
 pub use latency::LatencyOptimizedStrategy;
 pub use provider_affinity::ProviderAffinityStrategy;
+pub use quality::{QualityStrategy, TASK_TYPE, task_type};
 pub use weighted::WeightedStrategy;
 
 use super::protocols::ProtocolConversion;
@@ -36,7 +36,8 @@ impl ProviderAffinityStrategy {
             Protocol::OpenAIChat
             | Protocol::OpenAIMessages
             | Protocol::OpenAICompletions
-            | Protocol::OpenAIResponses => Some("provider://openai"),
+            | Protocol::OpenAIResponses
+            | Protocol::OpenAIEmbeddings => Some("provider://openai"),
             _ => None,
         }
     }
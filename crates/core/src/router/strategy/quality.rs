@@ -0,0 +1,244 @@
+//! Quality-aware routing strategy
+//!
+//! Scores routes by the feedback users left on earlier responses from the
+//! same model and sink, including feedback on experiment requests. Older
+//! feedback counts for less, halving in weight every `half_life`, and a
+//! route needs `min_samples` rated responses before its feedback counts at
+//! all; until then it scores neutrally. Feedback on requests of the same
+//! task type is used once there is enough of it, else all of the route's
+//! feedback is.
+
+use super::{RoutingStrategy, ScoredRoute, SinkCandidate};
+use crate::router::sink::RequestContext;
+use crate::router::types::{Protocol, RequestDescriptor};
+use crate::{Feedback, Result, StateBackend};
+use async_trait::async_trait;
+use chrono::{DateTime, TimeDelta, Utc};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+/// Context metadata key naming the task type of a request
+pub const TASK_TYPE: &str = "task_type";
+
+/// Score of a route without enough feedback to judge
+const NEUTRAL_SCORE: f64 = 0.5;
+
+/// How long loaded feedback is reused before it is read again
+const REFRESH_INTERVAL: TimeDelta = TimeDelta::seconds(60);
+
+/// Most feedback read at once
+const FEEDBACK_SAMPLE: usize = 10_000;
+
+/// Feedback older than this many half-lives weighs under 1% and is not read
+const WINDOW_HALF_LIVES: u32 = 7;
+
+/// Broad kind of work a request asks for, so feedback is compared like for like
+pub fn task_type(request: &RequestDescriptor) -> &'static str {
+    if request.protocol == Protocol::OpenAIEmbeddings {
+        "embeddings"
+    } else if request.capabilities.needs_tools {
+        "tools"
+    } else if request.capabilities.needs_vision {
+        "vision"
+    } else {
+        "chat"
+    }
+}
+
+/// Share of feedback that is positive, weighting each item by its age and
+/// starting from one positive and one negative pseudo-observation so a
+/// handful of ratings cannot reach 0 or 1. Neutral ratings count half each way.
+fn decayed_quality(feedback: &[&Feedback], now: DateTime<Utc>, half_life: Duration) -> f64 {
+    let half_life = half_life.as_secs_f64().max(1.0);
+    let (positive, total) = feedback.iter().fold((1.0, 2.0), |(positive, total), item| {
+        let age = (now - item.updated_at).num_seconds().max(0) as f64;
+        let weight = 0.5f64.powf(age / half_life);
+        let share = match item.score() {
+            1 => 1.0,
+            -1 => 0.0,
+            _ => 0.5,
+        };
+        (positive + weight * share, total + weight)
+    });
+    positive / total
+}
+
+/// Routing strategy that prefers routes users have rated well
+pub struct QualityStrategy {
+    state_backend: Arc<dyn StateBackend>,
+    half_life: Duration,
+    min_samples: usize,
+    cache: RwLock<Option<(DateTime<Utc>, Arc<Vec<Feedback>>)>>,
+}
+
+impl QualityStrategy {
+    /// Create a quality strategy with a 14 day half-life and 20 sample minimum
+    pub fn new(state_backend: Arc<dyn StateBackend>) -> Self {
+        Self {
+            state_backend,
+            half_life: Duration::from_secs(14 * 24 * 60 * 60),
+            min_samples: 20,
+            cache: RwLock::new(None),
+        }
+    }
+
+    /// Set how long it takes feedback to lose half its weight
+    pub fn with_half_life(mut self, half_life: Duration) -> Self {
+        self.half_life = half_life;
+        self
+    }
+
+    /// Set how many rated responses a route needs before feedback counts
+    pub fn with_min_samples(mut self, min_samples: usize) -> Self {
+        self.min_samples = min_samples;
+        self
+    }
+
+    /// Recent feedback, read from the state backend at most once per
+    /// refresh interval
+    async fn feedback(&self, now: DateTime<Utc>) -> Arc<Vec<Feedback>> {
+        if let Some((loaded_at, feedback)) = self.cache.read().await.as_ref()
+            && now - *loaded_at < REFRESH_INTERVAL
+        {
+            return feedback.clone();
+        }
+
+        let window =
+            TimeDelta::from_std(self.half_life * WINDOW_HALF_LIVES).unwrap_or(TimeDelta::MAX);
+        let since = now.checked_sub_signed(window);
+        let feedback = match self
+            .state_backend
+            .list_feedback(since, FEEDBACK_SAMPLE)
+            .await
+        {
+            Ok(feedback) => Arc::new(feedback),
+            Err(e) => {
+                debug!("Feedback unavailable for routing: {e}");
+                Arc::new(Vec::new())
+            }
+        };
+        *self.cache.write().await = Some((now, feedback.clone()));
+        feedback
+    }
+}
+
+#[async_trait]
+impl RoutingStrategy for QualityStrategy {
+    async fn evaluate(
+        &self,
+        _ctx: &RequestContext,
+        request: &RequestDescriptor,
+        candidates: Vec<SinkCandidate>,
+    ) -> Result<Vec<ScoredRoute>> {
+        let now = Utc::now();
+        let feedback = self.feedback(now).await;
+        let task = task_type(request);
+
+        let mut scored: Vec<ScoredRoute> = candidates
+            .into_iter()
+            .map(|candidate| {
+                let sink_id = candidate.description.id;
+                let route: Vec<&Feedback> = feedback
+                    .iter()
+                    .filter(|f| {
+                        f.provider_id.as_deref() == Some(sink_id.as_str())
+                            && f.model_id.as_deref() == Some(request.model.as_str())
+                    })
+                    .collect();
+                let same_task: Vec<&Feedback> = route
+                    .iter()
+                    .copied()
+                    .filter(|f| f.task_type.as_deref() == Some(task))
+                    .collect();
+                let (samples, scope) = if same_task.len() >= self.min_samples {
+                    (same_task, task)
+                } else {
+                    (route, "all tasks")
+                };
+
+                let (score, rationale) = if samples.len() < self.min_samples {
+                    (
+                        NEUTRAL_SCORE,
+                        format!(
+                            "Quality: {} rated responses, fewer than {} needed",
+                            samples.len(),
+                            self.min_samples
+                        ),
+                    )
+                } else {
+                    let score = decayed_quality(&samples, now, self.half_life);
+                    (
+                        score,
+                        format!(
+                            "Quality: {score:.3} from {} rated responses ({scope})",
+                            samples.len()
+                        ),
+                    )
+                };
+
+                ScoredRoute {
+                    sink_id,
+                    score,
+                    estimated_cost: None,
+                    estimated_latency: candidate
+                        .health
+                        .latency_ms
+                        .map(std::time::Duration::from_millis),
+                    conversion_needed: candidate.needs_conversion,
+                    rationale,
+                }
+            })
+            .collect();
+
+        // Sort by score (highest first)
+        scored.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        Ok(scored)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feedback(thumbs_up: bool, age: TimeDelta, now: DateTime<Utc>) -> Feedback {
+        Feedback {
+            id: uuid::Uuid::new_v4().to_string(),
+            request_id: uuid::Uuid::new_v4().to_string(),
+            user_id: "user".to_string(),
+            thumbs_up: Some(thumbs_up),
+            rating: None,
+            comment: None,
+            model_id: Some("model".to_string()),
+            provider_id: Some("sink".to_string()),
+            task_type: Some("chat".to_string()),
+            created_at: now - age,
+            updated_at: now - age,
+        }
+    }
+
+    #[test]
+    fn test_decayed_quality_weights_recent_feedback() {
+        let now = Utc::now();
+        let day = Duration::from_secs(24 * 60 * 60);
+        let recent_good = [
+            feedback(true, TimeDelta::zero(), now),
+            feedback(false, TimeDelta::days(30), now),
+        ];
+        let recent_bad = [
+            feedback(false, TimeDelta::zero(), now),
+            feedback(true, TimeDelta::days(30), now),
+        ];
+
+        let good = decayed_quality(&recent_good.iter().collect::<Vec<_>>(), now, day);
+        let bad = decayed_quality(&recent_bad.iter().collect::<Vec<_>>(), now, day);
+        assert!(good > 0.6 && good < 1.0);
+        assert!(bad < 0.4 && bad > 0.0);
+        assert_eq!(decayed_quality(&[], now, day), 0.5);
+    }
+}
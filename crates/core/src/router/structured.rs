@@ -0,0 +1,336 @@
+//! Structured output emulation for sinks without native JSON schema support
+//!
+//! Chat requests asking for `response_format: json_schema` (or `json_object`)
+//! are rewritten to carry the schema as a system instruction and sent
+//! unstreamed, so the reply can be repaired and checked before it reaches the
+//! client. Replies that still do not match are errors when the schema is
+//! `strict`; otherwise the repaired value is passed through as is.
+
+use super::sink::ResponseStream;
+use super::types::{Protocol, RequestStream, ResponseChunk};
+use super::workers::offload;
+use crate::{Error, Result};
+use futures::StreamExt;
+use serde_json::{Value as JsonValue, json};
+
+/// Requested JSON output format
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonFormat {
+    /// Schema the reply must match; `None` for plain JSON mode
+    pub schema: Option<JsonValue>,
+    /// Fail instead of passing through replies that do not match
+    pub strict: bool,
+}
+
+impl JsonFormat {
+    /// Format requested by an OpenAI chat request, if any
+    pub fn from_request(request: &JsonValue) -> Option<Self> {
+        let format = request.get("response_format")?;
+        match format.get("type").and_then(|t| t.as_str())? {
+            "json_schema" => {
+                let spec = format.get("json_schema")?;
+                Some(Self {
+                    schema: spec.get("schema").cloned(),
+                    strict: spec
+                        .get("strict")
+                        .and_then(|s| s.as_bool())
+                        .unwrap_or(false),
+                })
+            }
+            "json_object" => Some(Self {
+                schema: None,
+                strict: false,
+            }),
+            _ => None,
+        }
+    }
+
+    fn instructions(&self) -> String {
+        match &self.schema {
+            Some(schema) => format!(
+                "Respond only with a JSON value matching this JSON schema, \
+                 without any other text or code fences:\n{schema}"
+            ),
+            None => "Respond only with a valid JSON value, without any other text or code fences."
+                .to_string(),
+        }
+    }
+
+    /// Replace `response_format` with schema instructions in the system prompt
+    pub fn apply_to_request(&self, request: &mut JsonValue) {
+        let instructions = self.instructions();
+        let Some(obj) = request.as_object_mut() else {
+            return;
+        };
+        obj.remove("response_format");
+        obj.remove("stream_options");
+        obj.insert("stream".to_string(), JsonValue::Bool(false));
+
+        let Some(messages) = obj.get_mut("messages").and_then(|m| m.as_array_mut()) else {
+            return;
+        };
+        match messages.first_mut() {
+            Some(first)
+                if first.get("role").and_then(|r| r.as_str()) == Some("system")
+                    && first.get("content").is_some_and(|c| c.is_string()) =>
+            {
+                let content = first["content"].as_str().unwrap_or_default().to_string();
+                first["content"] = json!(format!("{content}\n\n{instructions}"));
+            }
+            _ => messages.insert(0, json!({"role": "system", "content": instructions})),
+        }
+    }
+
+    /// Repair and check the content of every choice in a chat completion,
+    /// replacing it with the parsed JSON re-serialised
+    pub fn enforce(&self, response: &mut JsonValue) -> Result<()> {
+        let choices = response
+            .get_mut("choices")
+            .and_then(|c| c.as_array_mut())
+            .into_iter()
+            .flatten();
+        for choice in choices {
+            let Some(content) = choice.pointer_mut("/message/content") else {
+                continue;
+            };
+            let text = content.as_str().unwrap_or_default();
+            let Some(value) = repair_json(text) else {
+                if self.strict {
+                    return Err(Error::StructuredOutput("reply is not valid JSON".into()));
+                }
+                continue;
+            };
+            if self.strict
+                && let Some(schema) = &self.schema
+            {
+                let violations = validate(&value, schema);
+                if !violations.is_empty() {
+                    return Err(Error::StructuredOutput(violations.join("; ")));
+                }
+            }
+            *content = JsonValue::String(value.to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Parse JSON from model output, tolerating code fences, surrounding prose
+/// and trailing commas
+pub fn repair_json(text: &str) -> Option<JsonValue> {
+    let text = text.trim();
+    if let Ok(value) = serde_json::from_str(text) {
+        return Some(value);
+    }
+
+    // Keep the outermost object or array, dropping fences and prose around it
+    let start = text.find(['{', '['])?;
+    let close = if text[start..].starts_with('{') {
+        '}'
+    } else {
+        ']'
+    };
+    let end = text.rfind(close)?;
+    let candidate = text.get(start..=end)?;
+    if let Ok(value) = serde_json::from_str(candidate) {
+        return Some(value);
+    }
+
+    serde_json::from_str(&strip_trailing_commas(candidate)).ok()
+}
+
+/// Remove commas directly before a closing bracket, outside of strings
+fn strip_trailing_commas(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if in_string {
+            in_string = escaped || c != '"';
+            escaped = c == '\\' && !escaped;
+            out.push(c);
+            continue;
+        }
+        match c {
+            '"' => {
+                in_string = true;
+                out.push(c);
+            }
+            ',' => {
+                let rest = chars.clone().find(|c| !c.is_whitespace());
+                if !matches!(rest, Some('}' | ']')) {
+                    out.push(c);
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Check `value` against the commonly used subset of JSON Schema: `type`,
+/// `enum`, `const`, `properties`, `required`, `additionalProperties: false`,
+/// `items` and `anyOf`. Returns one message per violation.
+pub fn validate(value: &JsonValue, schema: &JsonValue) -> Vec<String> {
+    let mut violations = Vec::new();
+    validate_at(value, schema, "$", &mut violations);
+    violations
+}
+
+fn type_matches(value: &JsonValue, ty: &str) -> bool {
+    match ty {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn validate_at(value: &JsonValue, schema: &JsonValue, path: &str, out: &mut Vec<String>) {
+    match schema.get("type") {
+        Some(JsonValue::String(ty)) if !type_matches(value, ty) => {
+            out.push(format!("{path}: expected {ty}"));
+            return;
+        }
+        Some(JsonValue::Array(types))
+            if !types
+                .iter()
+                .filter_map(|t| t.as_str())
+                .any(|ty| type_matches(value, ty)) =>
+        {
+            out.push(format!(
+                "{path}: expected one of {}",
+                JsonValue::Array(types.clone())
+            ));
+            return;
+        }
+        _ => {}
+    }
+
+    if let Some(options) = schema.get("enum").and_then(|e| e.as_array())
+        && !options.contains(value)
+    {
+        out.push(format!("{path}: not one of the allowed values"));
+    }
+    if let Some(expected) = schema.get("const")
+        && expected != value
+    {
+        out.push(format!("{path}: expected {expected}"));
+    }
+
+    if let Some(alternatives) = schema.get("anyOf").and_then(|a| a.as_array())
+        && !alternatives
+            .iter()
+            .any(|alt| validate(value, alt).is_empty())
+    {
+        out.push(format!("{path}: matches none of anyOf"));
+    }
+
+    if let Some(obj) = value.as_object() {
+        let properties = schema.get("properties").and_then(|p| p.as_object());
+        for required in schema
+            .get("required")
+            .and_then(|r| r.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|r| r.as_str())
+        {
+            if !obj.contains_key(required) {
+                out.push(format!("{path}: missing required property {required:?}"));
+            }
+        }
+        for (key, item) in obj {
+            match properties.and_then(|p| p.get(key)) {
+                Some(property) => validate_at(item, property, &format!("{path}.{key}"), out),
+                None if schema.get("additionalProperties") == Some(&JsonValue::Bool(false)) => {
+                    out.push(format!("{path}: unexpected property {key:?}"));
+                }
+                None => {}
+            }
+        }
+    }
+
+    if let (Some(items), Some(item_schema)) = (value.as_array(), schema.get("items")) {
+        for (index, item) in items.iter().enumerate() {
+            validate_at(item, item_schema, &format!("{path}[{index}]"), out);
+        }
+    }
+}
+
+/// Turn a complete chat completion into a single stream chunk, for clients
+/// that asked to stream a reply that had to be generated in one piece
+fn completion_as_chunk(mut response: JsonValue) -> JsonValue {
+    response["object"] = json!("chat.completion.chunk");
+    for choice in response
+        .get_mut("choices")
+        .and_then(|c| c.as_array_mut())
+        .into_iter()
+        .flatten()
+    {
+        if let Some(obj) = choice.as_object_mut()
+            && let Some(message) = obj.remove("message")
+        {
+            obj.insert("delta".to_string(), message);
+        }
+    }
+    response
+}
+
+/// Rewrite a request for a sink that cannot enforce JSON output itself.
+/// Returns the request to send and, when emulation applies, the format to
+/// enforce on the reply along with whether the client asked to stream.
+pub(crate) async fn prepare_request(
+    mut request: RequestStream,
+) -> Result<(RequestStream, Option<(JsonFormat, bool)>)> {
+    let protocol = request.protocol();
+    if protocol != Protocol::OpenAIChat {
+        return Ok((request, None));
+    }
+    let Some(first) = request.next().await else {
+        return Err(Error::InvalidRequest("Empty request stream".to_string()));
+    };
+    let mut json = first?;
+
+    let emulation = JsonFormat::from_request(&json).map(|format| {
+        let streaming = json
+            .get("stream")
+            .and_then(|s| s.as_bool())
+            .unwrap_or(false);
+        format.apply_to_request(&mut json);
+        (format, streaming)
+    });
+
+    let rebuilt = RequestStream::new(
+        protocol,
+        Box::pin(futures::stream::once(async move { Ok(json) }).chain(request)),
+    );
+    Ok((rebuilt, emulation))
+}
+
+/// Enforce `format` on every reply chunk from the sink
+pub(crate) fn enforce_response(
+    stream: ResponseStream,
+    format: JsonFormat,
+    client_streaming: bool,
+) -> ResponseStream {
+    Box::pin(stream.then(move |item| {
+        let format = format.clone();
+        async move {
+            match item {
+                Ok(ResponseChunk::Content(mut json)) => {
+                    let json = offload(move || format.enforce(&mut json).map(|()| json)).await??;
+                    Ok(ResponseChunk::Content(if client_streaming {
+                        completion_as_chunk(json)
+                    } else {
+                        json
+                    }))
+                }
+                other => other,
+            }
+        }
+    }))
+}
@@ -155,9 +155,54 @@ fn test_protocol_conversion() {
     assert!(converted.get("messages").is_some());
     assert!(converted.get("system").is_some());
     assert_eq!(converted["system"], "You are helpful");
+
+    let with_stop = json!({"model": "gpt-4", "messages": [], "stop": "END"});
+    let (converted, _) =
+        protocols::convert_request(Protocol::OpenAIChat, Protocol::Anthropic, &with_stop).unwrap();
+    assert_eq!(converted["stop_sequences"], json!(["END"]));
     assert!(warnings.is_empty() || !warnings.is_empty()); // Either case is fine for now
 }
 
+#[test]
+fn test_structured_output_is_a_forced_tool_call_on_anthropic() {
+    use serde_json::json;
+
+    let schema = json!({"type": "object", "properties": {"city": {"type": "string"}}});
+    let request = json!({
+        "model": "claude-sonnet-4",
+        "messages": [{"role": "user", "content": "Where?"}],
+        "response_format": {
+            "type": "json_schema",
+            "json_schema": {"name": "place", "schema": schema, "strict": true}
+        }
+    });
+    let (converted, _) =
+        protocols::convert_request(Protocol::OpenAIChat, Protocol::Anthropic, &request).unwrap();
+    assert_eq!(converted["tools"][0]["input_schema"], schema);
+    assert_eq!(
+        converted["tool_choice"],
+        json!({"type": "tool", "name": converted["tools"][0]["name"]})
+    );
+
+    let reply = json!({
+        "id": "msg_1",
+        "model": "claude-sonnet-4",
+        "role": "assistant",
+        "content": [{
+            "type": "tool_use",
+            "id": "toolu_1",
+            "name": converted["tools"][0]["name"],
+            "input": {"city": "Lisbon"}
+        }],
+        "stop_reason": "tool_use"
+    });
+    let (response, _) =
+        protocols::convert_response(Protocol::Anthropic, Protocol::OpenAIChat, &reply).unwrap();
+    let choice = &response["choices"][0];
+    assert_eq!(choice["message"]["content"], r#"{"city":"Lisbon"}"#);
+    assert_eq!(choice["finish_reason"], "stop");
+}
+
 #[tokio::test]
 async fn test_route_and_execute_with_mock_sink() {
     use crate::access::SubjectIdentity;
@@ -210,6 +255,8 @@ async fn test_route_and_execute_with_mock_sink() {
         query: None,
         trace_id: None,
         metadata: Default::default(),
+        response: Default::default(),
+        extensions: Default::default(),
     };
 
     let desc = RequestDescriptor {
@@ -266,6 +313,14 @@ async fn test_route_and_execute_with_mock_sink() {
             ..
         })
     ));
+
+    // Each stage the request went through published its time
+    let timings = ctx.response.get().timings;
+    assert!(timings.routing.is_some());
+    assert!(timings.queue.is_some());
+    assert!(timings.upstream_ttfb.is_some());
+    assert!(timings.stream.is_some());
+    assert_eq!(timings.auth, None);
 }
 
 #[tokio::test]
@@ -312,6 +367,8 @@ async fn test_service_route_and_execute_json() {
         query: None,
         trace_id: None,
         metadata: Default::default(),
+        response: Default::default(),
+        extensions: Default::default(),
     };
 
     let json_req = json!({
@@ -332,6 +389,224 @@ async fn test_service_route_and_execute_json() {
     assert!(matches!(chunks.last(), Some(ResponseChunk::Stop { .. })));
 }
 
+#[tokio::test]
+async fn test_usage_is_priced_at_the_sinks_pricing() {
+    use crate::access::SubjectIdentity;
+    use crate::router::middleware::CostTrackerMiddleware;
+    use crate::router::service::route_and_execute_json_with_protocol;
+    use crate::router::sink::RouterIdentityContext;
+    use crate::router::sinks::mock::MockSink;
+    use crate::router::types::{CostStructure, Protocol};
+    use crate::tests::state::InMemoryBackend;
+    use futures::StreamExt;
+    use rust_decimal::Decimal;
+    use serde_json::json;
+
+    // $3 and $15 per million tokens
+    let mut sink = MockSink::success("self://mock");
+    sink.cost_structure = Some(CostStructure {
+        input_cost_per_token: Decimal::new(3, 6),
+        output_cost_per_token: Decimal::new(15, 6),
+        cached_input_cost_per_token: None,
+        currency: "USD".into(),
+    });
+    let registry = std::sync::Arc::new(super::registry::SinkRegistry::new());
+    registry
+        .register("self://mock".into(), std::sync::Arc::new(sink))
+        .await;
+    let backend = std::sync::Arc::new(InMemoryBackend::default());
+    let router = routing::Router::builder()
+        .state_backend(backend.clone() as std::sync::Arc<dyn crate::StateBackend>)
+        .sink_registry(registry)
+        .middleware(std::sync::Arc::new(CostTrackerMiddleware::new(
+            backend.clone(),
+        )))
+        .build();
+    let ctx = sink::RequestContext {
+        identity: SubjectIdentity::new(
+            "user-1",
+            "test",
+            RouterIdentityContext {
+                org_id: None,
+                user_id: None,
+                api_key_hash: None,
+            },
+        ),
+        correlation_id: crate::tracing::CorrelationId::new(),
+        headers: Default::default(),
+        query: None,
+        trace_id: None,
+        metadata: Default::default(),
+        response: Default::default(),
+        extensions: Default::default(),
+    };
+
+    let request = json!({"model": "m", "messages": [{"role": "user", "content": "hello there"}]});
+    let mut resp =
+        route_and_execute_json_with_protocol(&router, &ctx, Protocol::OpenAIChat, request)
+            .await
+            .expect("exec");
+    while resp.next().await.is_some() {}
+
+    // The provider reported no cost, so the counts are priced
+    let record = backend
+        .get_usage_by_request(&ctx.correlation_id.to_string())
+        .await
+        .unwrap()
+        .expect("usage recorded");
+    assert!(record.input_tokens > 0);
+    let expected = (record.input_tokens * 3 + record.output_tokens * 15) as f64 / 1e6;
+    assert!((record.cost - expected).abs() < 1e-12);
+    assert!(record.cost > 0.0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_retryable_provider_errors_are_retried() {
+    use crate::access::SubjectIdentity;
+    use crate::router::index::SinkIndex;
+    use crate::router::service::route_and_execute_json_with_protocol;
+    use crate::router::sink::RouterIdentityContext;
+    use crate::router::sinks::mock::MockSink;
+    use crate::router::types::Protocol;
+    use serde_json::json;
+
+    let overloaded =
+        r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#;
+    let invalid = r#"{"error":{"code":"context_length_exceeded","message":"Too long"}}"#;
+    let ctx = sink::RequestContext {
+        identity: SubjectIdentity::new(
+            "user-1",
+            "test",
+            RouterIdentityContext {
+                org_id: None,
+                user_id: None,
+                api_key_hash: None,
+            },
+        ),
+        correlation_id: crate::tracing::CorrelationId::new(),
+        headers: Default::default(),
+        query: None,
+        trace_id: None,
+        metadata: Default::default(),
+        response: Default::default(),
+        extensions: Default::default(),
+    };
+
+    let execute = |sink: MockSink| {
+        let ctx = ctx.clone();
+        async move {
+            let registry = std::sync::Arc::new(super::registry::SinkRegistry::new());
+            registry
+                .register("self://mock".into(), std::sync::Arc::new(sink))
+                .await;
+            let index = std::sync::Arc::new(SinkIndex::new());
+            index.refresh_from_registry(&registry).await;
+            let backend = std::sync::Arc::new(MockStateBackend);
+            let router = routing::Router::builder()
+                .state_backend(backend as std::sync::Arc<dyn crate::StateBackend>)
+                .sink_registry(registry)
+                .sink_index(index)
+                .build();
+            let request = json!({"model": "m", "messages": [{"role": "user", "content": "hi"}]});
+            route_and_execute_json_with_protocol(&router, &ctx, Protocol::OpenAIChat, request)
+                .await
+                .map(|_| ())
+        }
+    };
+
+    // Overloaded twice, then served within the default three attempts
+    let result = execute(MockSink::failing("self://mock", 529, overloaded, 2)).await;
+    assert!(result.is_ok());
+    // Clones of the context share what the executor published
+    let info = ctx.response.get();
+    assert_eq!(info.attempts, 3);
+    assert_eq!(info.sink_id.as_deref(), Some("self://mock"));
+
+    // Errors about the request itself are not retried
+    let result = execute(MockSink::failing("self://mock", 400, invalid, 1)).await;
+    let class = result.err().and_then(|e| e.class());
+    assert_eq!(class, Some(crate::ErrorClass::ContextLengthExceeded));
+}
+
+#[tokio::test]
+async fn test_rewriters_run_before_routing() {
+    use crate::access::SubjectIdentity;
+    use crate::router::middleware::RequestRewriter;
+    use crate::router::service::route_and_execute_json_with_protocol;
+    use crate::router::sink::RouterIdentityContext;
+    use crate::router::sinks::mock::MockSink;
+    use crate::router::types::Protocol;
+    use serde_json::json;
+
+    /// Rejects one model and renames another
+    struct Rename;
+
+    #[async_trait]
+    impl RequestRewriter for Rename {
+        async fn rewrite(
+            &self,
+            _ctx: &mut sink::RequestContext,
+            _protocol: Protocol,
+            request: &mut serde_json::Value,
+        ) -> Result<()> {
+            match request["model"].as_str() {
+                Some("blocked") => Err(crate::Error::InvalidRequest("blocked".into())),
+                Some("alias") => {
+                    request["model"] = json!("m");
+                    Ok(())
+                }
+                _ => Ok(()),
+            }
+        }
+    }
+
+    let registry = std::sync::Arc::new(super::registry::SinkRegistry::new());
+    registry
+        .register(
+            "self://mock".into(),
+            std::sync::Arc::new(MockSink::success("self://mock")),
+        )
+        .await;
+    let router = routing::Router::builder()
+        .state_backend(
+            std::sync::Arc::new(MockStateBackend) as std::sync::Arc<dyn crate::StateBackend>
+        )
+        .sink_registry(registry)
+        .rewriter(std::sync::Arc::new(Rename))
+        .build();
+    let ctx = sink::RequestContext {
+        identity: SubjectIdentity::new(
+            "user-1",
+            "test",
+            RouterIdentityContext {
+                org_id: None,
+                user_id: None,
+                api_key_hash: None,
+            },
+        ),
+        correlation_id: crate::tracing::CorrelationId::new(),
+        headers: Default::default(),
+        query: None,
+        trace_id: None,
+        metadata: Default::default(),
+        response: Default::default(),
+        extensions: Default::default(),
+    };
+
+    let mut request = json!({"model": "alias", "messages": []});
+    let mut rewritten = ctx.clone();
+    router
+        .rewrite(&mut rewritten, Protocol::OpenAIChat, &mut request)
+        .await
+        .unwrap();
+    assert_eq!(request["model"], "m");
+
+    let blocked = json!({"model": "blocked", "messages": []});
+    let result =
+        route_and_execute_json_with_protocol(&router, &ctx, Protocol::OpenAIChat, blocked).await;
+    assert!(matches!(result, Err(crate::Error::InvalidRequest(_))));
+}
+
 #[tokio::test]
 async fn test_sink_index_selects_healthy_candidate() {
     use crate::access::SubjectIdentity;
@@ -391,6 +666,8 @@ async fn test_sink_index_selects_healthy_candidate() {
         query: None,
         trace_id: None,
         metadata: Default::default(),
+        response: Default::default(),
+        extensions: Default::default(),
     };
 
     let desc = RequestDescriptor {
@@ -410,6 +687,88 @@ async fn test_sink_index_selects_healthy_candidate() {
     assert_eq!(plan.primary_route.sink_id, sink_a_id);
 }
 
+#[tokio::test]
+async fn test_quality_strategy_prefers_well_rated_sink() {
+    use crate::access::SubjectIdentity;
+    use crate::router::index::SinkIndex;
+    use crate::router::sink::RouterIdentityContext;
+    use crate::router::sinks::mock::MockSink;
+    use crate::router::strategy::{QualityStrategy, TASK_TYPE};
+    use crate::router::types::{RequestCapabilities, RequestDescriptor};
+    use crate::tests::state::InMemoryBackend;
+
+    let backend = std::sync::Arc::new(InMemoryBackend::default());
+    let registry = std::sync::Arc::new(super::registry::SinkRegistry::new());
+
+    let sink_a_id = "self://a";
+    let sink_b_id = "self://b";
+    for id in [sink_a_id, sink_b_id] {
+        registry
+            .register(id.to_string(), std::sync::Arc::new(MockSink::success(id)))
+            .await;
+    }
+    let index = std::sync::Arc::new(SinkIndex::new());
+    index.refresh_from_registry(&registry).await;
+
+    // Users disliked A's answers and liked B's
+    for (sink_id, thumbs_up) in [(sink_a_id, false), (sink_b_id, true)] {
+        for _ in 0..3 {
+            let feedback = crate::Feedback {
+                id: uuid::Uuid::new_v4().to_string(),
+                request_id: uuid::Uuid::new_v4().to_string(),
+                user_id: "user-1".to_string(),
+                thumbs_up: Some(thumbs_up),
+                rating: None,
+                comment: None,
+                model_id: Some("test".to_string()),
+                provider_id: Some(sink_id.to_string()),
+                task_type: Some("chat".to_string()),
+                created_at: chrono::Utc::now(),
+                updated_at: chrono::Utc::now(),
+            };
+            backend.save_feedback(&feedback).await.unwrap();
+        }
+    }
+
+    let strategy = QualityStrategy::new(backend.clone()).with_min_samples(3);
+    let router = routing::Router::builder()
+        .state_backend(backend as std::sync::Arc<dyn crate::StateBackend>)
+        .sink_registry(registry)
+        .sink_index(index)
+        .strategy(Box::new(strategy))
+        .build();
+
+    let ctx = sink::RequestContext {
+        identity: SubjectIdentity::new("user-1", "test", RouterIdentityContext::default()),
+        correlation_id: crate::tracing::CorrelationId::new(),
+        headers: Default::default(),
+        query: None,
+        trace_id: None,
+        metadata: Default::default(),
+        response: Default::default(),
+        extensions: Default::default(),
+    };
+    let desc = RequestDescriptor {
+        model: "test".into(),
+        protocol: Protocol::OpenAIChat,
+        capabilities: RequestCapabilities {
+            needs_tools: false,
+            needs_vision: false,
+            needs_streaming: false,
+            max_tokens: Some(32),
+            modalities: vec!["text".into()],
+        },
+        context_length_hint: Some(64),
+    };
+
+    let plan = router.route(&ctx, &desc).await.expect("route ok");
+    assert_eq!(plan.primary_route.sink_id, sink_b_id);
+    assert_eq!(
+        plan.context.metadata.get(TASK_TYPE).map(String::as_str),
+        Some("chat")
+    );
+}
+
 #[tokio::test]
 async fn test_sink_index_refresher_lists_all_sinks() {
     use crate::router::index::SinkIndex;
@@ -443,3 +802,563 @@ async fn test_sink_index_refresher_lists_all_sinks() {
     assert!(map.get("self://one").unwrap().health.healthy);
     assert!(!map.get("self://two").unwrap().health.healthy);
 }
+
+#[tokio::test]
+async fn test_sink_index_disabled_flag_survives_refresh() {
+    use crate::router::index::SinkIndex;
+    use crate::router::sinks::mock::MockSink;
+
+    let registry = std::sync::Arc::new(super::registry::SinkRegistry::new());
+    registry
+        .register(
+            "self://one".into(),
+            std::sync::Arc::new(MockSink::success("self://one")),
+        )
+        .await;
+
+    let index = SinkIndex::new();
+    index.refresh_from_registry(&registry).await;
+    index.set_disabled("self://one", true).await;
+    assert!(index.get("self://one").await.unwrap().disabled);
+
+    index.refresh_from_registry(&registry).await;
+    assert!(index.get("self://one").await.unwrap().disabled);
+    assert_eq!(index.history("self://one").await.len(), 2);
+
+    index.set_disabled("self://one", false).await;
+    assert!(!index.get("self://one").await.unwrap().disabled);
+}
+
+#[tokio::test]
+async fn test_sink_index_follows_registry_events() {
+    use crate::router::index::SinkIndex;
+    use crate::router::sinks::mock::MockSink;
+    use std::sync::Arc;
+
+    async fn eventually(check: impl AsyncFn() -> bool) {
+        for _ in 0..100 {
+            if check().await {
+                return;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+        panic!("index did not catch up with the registry");
+    }
+
+    let registry = Arc::new(super::registry::SinkRegistry::new());
+    let index = SinkIndex::new();
+    let follower = tokio::spawn(index.follow(&registry));
+
+    registry
+        .register(
+            "self://one".into(),
+            Arc::new(MockSink::success("self://one")),
+        )
+        .await;
+    eventually(async || index.get("self://one").await.is_some()).await;
+
+    let mut health = index.get("self://one").await.unwrap().health;
+    health.healthy = false;
+    registry.publish_health("self://one", health);
+    eventually(async || !index.get("self://one").await.unwrap().health.healthy).await;
+    assert!(index.staleness().is_some());
+
+    registry.remove("self://one").await;
+    eventually(async || index.snapshot().is_empty()).await;
+
+    // Dropping the registry ends the follower
+    drop(registry);
+    tokio::time::timeout(std::time::Duration::from_secs(1), follower)
+        .await
+        .expect("follower exits")
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_hung_sink_does_not_stall_fallback_routing() {
+    use crate::access::SubjectIdentity;
+    use crate::router::sink::{RequestContext, ResponseStream, RouterIdentityContext, Sink};
+    use crate::router::sinks::mock::MockSink;
+    use crate::router::types::{RequestCapabilities, RequestDescriptor, RequestStream, SinkHealth};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    /// Describes itself but never answers a probe
+    struct HungSink(MockSink);
+
+    #[async_trait]
+    impl Sink for HungSink {
+        async fn describe(&self) -> super::sink::SinkDescription {
+            self.0.describe().await
+        }
+        async fn probe(&self) -> SinkHealth {
+            futures::future::pending().await
+        }
+        async fn execute(
+            &self,
+            ctx: &RequestContext,
+            request: RequestStream,
+        ) -> Result<ResponseStream> {
+            self.0.execute(ctx, request).await
+        }
+    }
+
+    let registry = Arc::new(super::registry::SinkRegistry::new());
+    registry
+        .register(
+            "self://hung".into(),
+            Arc::new(HungSink(MockSink::success("self://hung"))),
+        )
+        .await;
+    registry
+        .register("self://ok".into(), Arc::new(MockSink::success("self://ok")))
+        .await;
+
+    // No index, so routing probes every sink itself
+    let router = routing::Router::builder()
+        .state_backend(Arc::new(MockStateBackend))
+        .sink_registry(registry)
+        .probe_timeout(Duration::from_millis(50))
+        .build();
+    let ctx = RequestContext {
+        identity: SubjectIdentity::new("user-1", "test", RouterIdentityContext::default()),
+        correlation_id: crate::tracing::CorrelationId::new(),
+        headers: Default::default(),
+        query: None,
+        trace_id: None,
+        metadata: Default::default(),
+        response: Default::default(),
+        extensions: Default::default(),
+    };
+    let desc = RequestDescriptor {
+        model: "test".into(),
+        protocol: Protocol::OpenAIChat,
+        capabilities: RequestCapabilities {
+            needs_tools: false,
+            needs_vision: false,
+            needs_streaming: false,
+            max_tokens: Some(32),
+            modalities: vec!["text".into()],
+        },
+        context_length_hint: Some(64),
+    };
+
+    let plan = tokio::time::timeout(Duration::from_secs(1), router.route(&ctx, &desc))
+        .await
+        .expect("routing finishes despite the hung sink")
+        .expect("route ok");
+    assert_eq!(plan.primary_route.sink_id, "self://ok");
+}
+
+#[test]
+fn test_sink_health_circuit_opens_after_repeated_failures() {
+    let mut health = SinkHealth {
+        healthy: true,
+        latency_ms: None,
+        error_rate: 0.0,
+        last_error: None,
+        last_check: chrono::Utc::now(),
+    };
+
+    health.record(Some(10), Some("boom".into()));
+    assert!(health.healthy);
+    assert_eq!(health.circuit_state(), CircuitState::Closed);
+
+    for _ in 0..3 {
+        health.record(Some(10), Some("boom".into()));
+    }
+    assert!(!health.healthy);
+    assert_eq!(health.circuit_state(), CircuitState::Open);
+    assert!(!health.routable());
+
+    // After the cooldown a trial is let through; a failed one reopens
+    health.last_check -= chrono::TimeDelta::seconds(31);
+    assert_eq!(health.circuit_state(), CircuitState::HalfOpen);
+    assert!(health.routable());
+    health.record(Some(10), Some("boom".into()));
+    assert_eq!(health.circuit_state(), CircuitState::Open);
+
+    // and a successful one closes the circuit
+    health.last_check -= chrono::TimeDelta::seconds(31);
+    health.record(Some(5), None);
+    assert!(health.healthy);
+    assert_eq!(health.circuit_state(), CircuitState::Closed);
+    assert_eq!(health.latency_ms, Some(5));
+    assert_eq!(health.last_error.as_deref(), Some("boom"));
+}
+
+#[test]
+fn test_estimate_tokens_counts_text_only() {
+    use serde_json::json;
+
+    let request = json!({
+        "model": "a-very-long-model-name-that-is-ignored",
+        "system": "abcd",
+        "messages": [{"role": "user", "content": [{"type": "text", "text": "abcdefgh"}]}],
+        "max_tokens": 1024
+    });
+
+    // "abcd" + "user" + "text" + "abcdefgh" = 20 chars
+    assert_eq!(service::estimate_tokens(&request), 5);
+    assert_eq!(service::estimate_tokens(&json!({})), 0);
+}
+
+#[test]
+fn test_responses_chat_conversion() {
+    use serde_json::json;
+
+    let responses_request = json!({
+        "model": "gpt-4",
+        "instructions": "Be brief",
+        "input": [
+            {"role": "user", "content": [{"type": "input_text", "text": "Weather?"}]},
+            {"type": "function_call", "call_id": "call_1", "name": "weather", "arguments": "{}"},
+            {"type": "function_call_output", "call_id": "call_1", "output": "Sunny"}
+        ],
+        "max_output_tokens": 100,
+        "tools": [{"type": "function", "name": "weather", "parameters": {}}]
+    });
+    let (chat, _) = protocols::convert_request(
+        Protocol::OpenAIResponses,
+        Protocol::OpenAIChat,
+        &responses_request,
+    )
+    .unwrap();
+    let messages = chat["messages"].as_array().unwrap();
+    assert_eq!(
+        messages[0],
+        json!({"role": "system", "content": "Be brief"})
+    );
+    assert_eq!(messages[1]["content"], "Weather?");
+    assert_eq!(messages[2]["tool_calls"][0]["function"]["name"], "weather");
+    assert_eq!(messages[3]["role"], "tool");
+    assert_eq!(chat["max_tokens"], 100);
+    assert_eq!(chat["tools"][0]["function"]["name"], "weather");
+
+    let chat_response = json!({
+        "id": "chatcmpl-1",
+        "model": "gpt-4",
+        "created": 1,
+        "choices": [{"message": {"role": "assistant", "content": "Sunny today"}, "finish_reason": "stop"}],
+        "usage": {"prompt_tokens": 10, "completion_tokens": 3, "total_tokens": 13}
+    });
+    let (response, _) = protocols::convert_response(
+        Protocol::OpenAIChat,
+        Protocol::OpenAIResponses,
+        &chat_response,
+    )
+    .unwrap();
+    assert_eq!(response["object"], "response");
+    assert_eq!(response["status"], "completed");
+    assert_eq!(response["output"][0]["content"][0]["text"], "Sunny today");
+    assert_eq!(response["usage"]["total_tokens"], 13);
+}
+
+#[test]
+fn test_structured_output_repair_and_validation() {
+    use serde_json::json;
+    use structured::{JsonFormat, repair_json, validate};
+
+    assert_eq!(
+        repair_json("Here you go:\n```json\n{\"a\": [1, 2,],}\n```"),
+        Some(json!({"a": [1, 2]}))
+    );
+    assert_eq!(repair_json("no json here"), None);
+
+    let schema = json!({
+        "type": "object",
+        "properties": {"name": {"type": "string"}, "age": {"type": "integer"}},
+        "required": ["name", "age"],
+        "additionalProperties": false
+    });
+    assert!(validate(&json!({"name": "Ada", "age": 36}), &schema).is_empty());
+    assert_eq!(
+        validate(&json!({"name": 1, "extra": true}), &schema).len(),
+        3 // missing age, wrong name type, unexpected property
+    );
+
+    let request = json!({
+        "model": "m",
+        "messages": [{"role": "user", "content": "Who?"}],
+        "response_format": {"type": "json_schema", "json_schema": {"name": "person", "schema": schema, "strict": true}},
+        "stream": true
+    });
+    let format = JsonFormat::from_request(&request).unwrap();
+    let mut rewritten = request.clone();
+    format.apply_to_request(&mut rewritten);
+    assert!(rewritten.get("response_format").is_none());
+    assert_eq!(rewritten["stream"], false);
+    assert_eq!(rewritten["messages"][0]["role"], "system");
+
+    let mut response = json!({"choices": [{"message": {"content": "```json\n{\"name\": \"Ada\", \"age\": 36}\n```"}}]});
+    format.enforce(&mut response).unwrap();
+    assert_eq!(
+        response["choices"][0]["message"]["content"],
+        json!({"name": "Ada", "age": 36}).to_string()
+    );
+
+    let mut invalid = json!({"choices": [{"message": {"content": "{\"name\": \"Ada\"}"}}]});
+    assert!(matches!(
+        format.enforce(&mut invalid),
+        Err(crate::Error::StructuredOutput(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_parse_content_turns_raw_frames_into_json() {
+    use crate::router::sink::parse_content;
+    use futures::StreamExt;
+    use serde_json::json;
+
+    let chunks = vec![
+        Ok(ResponseChunk::Raw {
+            event: None,
+            data: bytes::Bytes::from_static(br#"{"ok":true}"#),
+        }),
+        Ok(ResponseChunk::Raw {
+            event: None,
+            data: bytes::Bytes::from_static(b"not json"),
+        }),
+    ];
+    let parsed: Vec<_> = parse_content(Box::pin(futures::stream::iter(chunks)))
+        .map(|item| item.unwrap())
+        .collect()
+        .await;
+    assert!(matches!(&parsed[0], ResponseChunk::Content(v) if v == &json!({"ok": true})));
+    assert!(matches!(&parsed[1], ResponseChunk::Content(v) if v == "not json"));
+}
+
+#[tokio::test]
+async fn test_replaced_pipeline_applies_to_new_requests() {
+    use crate::access::SubjectIdentity;
+    use crate::router::middleware::RequestRewriter;
+    use crate::router::sink::RouterIdentityContext;
+    use serde_json::json;
+
+    struct Tag(&'static str);
+
+    #[async_trait]
+    impl RequestRewriter for Tag {
+        async fn rewrite(
+            &self,
+            _ctx: &mut sink::RequestContext,
+            _protocol: Protocol,
+            request: &mut serde_json::Value,
+        ) -> Result<()> {
+            request["tags"]
+                .as_array_mut()
+                .expect("tags")
+                .push(json!(self.0));
+            Ok(())
+        }
+    }
+
+    let router = routing::Router::builder()
+        .state_backend(std::sync::Arc::new(MockStateBackend) as std::sync::Arc<dyn StateBackend>)
+        .rewriter(std::sync::Arc::new(Tag("a")))
+        .build();
+    let mut ctx = sink::RequestContext {
+        identity: SubjectIdentity::new("user-1", "test", RouterIdentityContext::default()),
+        correlation_id: crate::tracing::CorrelationId::new(),
+        headers: Default::default(),
+        query: None,
+        trace_id: None,
+        metadata: Default::default(),
+        response: Default::default(),
+        extensions: Default::default(),
+    };
+
+    let mut request = json!({"tags": []});
+    router
+        .rewrite(&mut ctx, Protocol::OpenAIChat, &mut request)
+        .await
+        .unwrap();
+    assert_eq!(request["tags"], json!(["a"]));
+
+    router.replace_pipeline(Pipeline {
+        rewriters: vec![std::sync::Arc::new(Tag("b")), std::sync::Arc::new(Tag("c"))],
+        ..Default::default()
+    });
+    let mut request = json!({"tags": []});
+    router
+        .rewrite(&mut ctx, Protocol::OpenAIChat, &mut request)
+        .await
+        .unwrap();
+    assert_eq!(request["tags"], json!(["b", "c"]));
+}
+
+#[tokio::test]
+async fn test_filtered_response_is_retried_on_allowed_fallback() {
+    use crate::access::SubjectIdentity;
+    use crate::router::concurrency::{ConcurrencyGate, ConcurrencyLimits};
+    use crate::router::content_filter::ContentFilterPolicy;
+    use crate::router::executor::PlanExecutor;
+    use crate::router::plan::{Route, RoutingPlan};
+    use crate::router::sink::{RequestContext, ResponseStream, RouterIdentityContext, Sink};
+    use crate::router::sinks::mock::MockSink;
+    use crate::router::types::{RequestStream, ResponseChunk, RetryConfig, SinkHealth, StopReason};
+    use futures::StreamExt;
+    use serde_json::json;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    /// Answers every request with a filtered response
+    struct FilteringSink(MockSink);
+
+    #[async_trait]
+    impl Sink for FilteringSink {
+        async fn describe(&self) -> super::sink::SinkDescription {
+            self.0.describe().await
+        }
+        async fn probe(&self) -> SinkHealth {
+            self.0.probe().await
+        }
+        async fn execute(
+            &self,
+            ctx: &RequestContext,
+            _request: RequestStream,
+        ) -> Result<ResponseStream> {
+            ctx.response
+                .publish(|info| info.content_filter = Some("content_filter".to_string()));
+            Ok(Box::pin(futures::stream::iter(vec![
+                Ok(ResponseChunk::Content(json!({"choices": []}))),
+                Ok(ResponseChunk::Stop {
+                    reason: StopReason::ContentFilter,
+                    error: None,
+                    cost: None,
+                }),
+            ])))
+        }
+    }
+
+    let registry = Arc::new(super::registry::SinkRegistry::new());
+    registry
+        .register(
+            "self://filtered".into(),
+            Arc::new(FilteringSink(MockSink::success("self://filtered"))),
+        )
+        .await;
+    registry
+        .register("self://ok".into(), Arc::new(MockSink::success("self://ok")))
+        .await;
+    let gate = Arc::new(ConcurrencyGate::new(ConcurrencyLimits {
+        default_sink_limit: Some(1),
+        queue_timeout: Duration::from_millis(20),
+        ..Default::default()
+    }));
+    let executor = PlanExecutor::new(registry)
+        .with_gate(Some(gate.clone()))
+        .with_content_filter(Some(Arc::new(ContentFilterPolicy {
+            retry_sinks: vec!["self://ok".to_string()],
+            ..Default::default()
+        })));
+
+    let route = |sink_id: &str| Route {
+        sink_id: sink_id.to_string(),
+        protocol_conversion: None,
+        timeout: Duration::from_secs(1),
+        retry_config: RetryConfig::default(),
+    };
+    let ctx = RequestContext {
+        identity: SubjectIdentity::new("user-1", "test", RouterIdentityContext::default()),
+        correlation_id: crate::tracing::CorrelationId::new(),
+        headers: Default::default(),
+        query: None,
+        trace_id: None,
+        metadata: Default::default(),
+        response: Default::default(),
+        extensions: Default::default(),
+    };
+    let plan = RoutingPlan::new(
+        ctx.clone(),
+        route("self://filtered"),
+        vec![route("self://ok")],
+    );
+    let request = RequestStream::new(
+        Protocol::OpenAIChat,
+        Box::pin(futures::stream::iter(vec![Ok(
+            json!({"model": "test", "messages": []}),
+        )])),
+    );
+
+    let stream = executor.execute(plan, request).await.unwrap();
+    // The retry holds the fallback's capacity, not the filtered sink's
+    assert!(gate.try_admit("user-2", "self://ok").is_none());
+    assert!(gate.try_admit("user-2", "self://filtered").is_some());
+
+    let chunks: Vec<_> = stream.map(|item| item.unwrap()).collect().await;
+    assert!(
+        chunks
+            .iter()
+            .any(|chunk| matches!(chunk, ResponseChunk::Content(json) if json["ok"] == true))
+    );
+    assert!(matches!(
+        chunks.last(),
+        Some(ResponseChunk::Stop {
+            reason: StopReason::Complete,
+            ..
+        })
+    ));
+    let info = ctx.response.get();
+    assert_eq!(info.sink_id.as_deref(), Some("self://ok"));
+    assert_eq!(info.content_filter, None);
+    assert!(gate.try_admit("user-2", "self://ok").is_some());
+}
+
+#[tokio::test]
+async fn test_spillover_publishes_the_sink_that_served() {
+    use crate::access::SubjectIdentity;
+    use crate::router::concurrency::{ConcurrencyGate, ConcurrencyLimits};
+    use crate::router::executor::PlanExecutor;
+    use crate::router::plan::{Route, RoutingPlan};
+    use crate::router::sink::{RequestContext, RouterIdentityContext};
+    use crate::router::sinks::mock::MockSink;
+    use crate::router::types::{RequestStream, RetryConfig};
+    use serde_json::json;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let registry = Arc::new(super::registry::SinkRegistry::new());
+    for id in ["self://busy", "self://spare"] {
+        registry
+            .register(id.into(), Arc::new(MockSink::success(id)))
+            .await;
+    }
+    let gate = Arc::new(ConcurrencyGate::new(ConcurrencyLimits {
+        default_sink_limit: Some(1),
+        spillover: true,
+        queue_timeout: Duration::from_millis(20),
+        ..Default::default()
+    }));
+    let _busy = gate.try_admit("user-2", "self://busy").unwrap();
+    let executor = PlanExecutor::new(registry).with_gate(Some(gate));
+
+    let route = |sink_id: &str| Route {
+        sink_id: sink_id.to_string(),
+        protocol_conversion: None,
+        timeout: Duration::from_secs(1),
+        retry_config: RetryConfig::default(),
+    };
+    let ctx = RequestContext {
+        identity: SubjectIdentity::new("user-1", "test", RouterIdentityContext::default()),
+        correlation_id: crate::tracing::CorrelationId::new(),
+        headers: Default::default(),
+        query: None,
+        trace_id: None,
+        metadata: Default::default(),
+        response: Default::default(),
+        extensions: Default::default(),
+    };
+    let plan = RoutingPlan::new(
+        ctx.clone(),
+        route("self://busy"),
+        vec![route("self://spare")],
+    );
+    let request = RequestStream::new(
+        Protocol::OpenAIChat,
+        Box::pin(futures::stream::iter(vec![Ok(json!({"model": "test"}))])),
+    );
+
+    let _stream = executor.execute(plan, request).await.unwrap();
+    assert_eq!(ctx.response.get().sink_id.as_deref(), Some("self://spare"));
+}
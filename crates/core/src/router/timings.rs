@@ -0,0 +1,104 @@
+//! Where the time of a request went
+//!
+//! Each stage of a request publishes how long it took to the request's
+//! [`ResponseMetadata`](super::ResponseMetadata), so a slow request can be
+//! put down to the gateway or to the provider. The timings are kept in the
+//! request's usage record and, when enabled, sent to the client in a
+//! `Server-Timing` header.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Response header carrying the timings known when the response started
+pub const SERVER_TIMING_HEADER: &str = "server-timing";
+
+/// Prefix of the usage metadata keys holding timings, in milliseconds
+pub const TIMING_PREFIX: &str = "timing_";
+
+/// How long each stage of a request took; stages a request did not go
+/// through are `None`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Timings {
+    /// Checking the caller's credentials
+    pub auth: Option<Duration>,
+    /// Choosing the sinks to send the request to
+    pub routing: Option<Duration>,
+    /// From the routing decision until the request that was answered went
+    /// upstream: middleware, stream slots, and earlier attempts and their
+    /// backoff
+    pub queue: Option<Duration>,
+    /// From sending the request upstream until the response started
+    pub upstream_ttfb: Option<Duration>,
+    /// From the start of the response stream until its end
+    pub stream: Option<Duration>,
+    /// Converting requests and responses between protocols
+    pub conversion: Option<Duration>,
+}
+
+impl Timings {
+    /// Add `elapsed` to a stage that may run more than once
+    pub fn add(stage: &mut Option<Duration>, elapsed: Duration) {
+        *stage = Some(stage.unwrap_or_default() + elapsed);
+    }
+
+    fn stages(&self) -> [(&'static str, Option<Duration>); 6] {
+        [
+            ("auth", self.auth),
+            ("route", self.routing),
+            ("queue", self.queue),
+            ("upstream", self.upstream_ttfb),
+            ("stream", self.stream),
+            ("convert", self.conversion),
+        ]
+    }
+
+    /// Entries for a usage record's metadata
+    pub fn usage_metadata(&self) -> HashMap<String, String> {
+        self.stages()
+            .into_iter()
+            .filter_map(|(name, elapsed)| {
+                Some((format!("{TIMING_PREFIX}{name}_ms"), millis(elapsed?)))
+            })
+            .collect()
+    }
+
+    /// Value of a `Server-Timing` header, ending with the `total` time taken
+    /// until the response started
+    pub fn server_timing(&self, total: Duration) -> String {
+        self.stages()
+            .into_iter()
+            .chain([("total", Some(total))])
+            .filter_map(|(name, elapsed)| Some(format!("{name};dur={}", millis(elapsed?))))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+fn millis(elapsed: Duration) -> String {
+    format!("{:.1}", elapsed.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timings_report_the_stages_run() {
+        let mut timings = Timings {
+            auth: Some(Duration::from_micros(1500)),
+            upstream_ttfb: Some(Duration::from_millis(120)),
+            ..Default::default()
+        };
+        Timings::add(&mut timings.conversion, Duration::from_millis(1));
+        Timings::add(&mut timings.conversion, Duration::from_millis(2));
+
+        assert_eq!(
+            timings.server_timing(Duration::from_millis(130)),
+            "auth;dur=1.5, upstream;dur=120.0, convert;dur=3.0, total;dur=130.0"
+        );
+        let metadata = timings.usage_metadata();
+        assert_eq!(metadata.len(), 3);
+        assert_eq!(metadata["timing_upstream_ms"], "120.0");
+    }
+}
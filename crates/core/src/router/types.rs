@@ -1,5 +1,6 @@
 //! Core types for the router module
 
+use bytes::Bytes;
 use futures::Stream;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
@@ -15,6 +16,7 @@ pub enum Protocol {
     OpenAIChat,        // v1/chat/completions
     OpenAICompletions, // v1/completions
     OpenAIResponses,   // v1/responses
+    OpenAIEmbeddings,  // v1/embeddings
     Anthropic,         // v1/messages
     Unknown,           // Escape hatch
 }
@@ -26,6 +28,7 @@ impl std::fmt::Display for Protocol {
             Protocol::OpenAIChat => write!(f, "OpenAI Chat"),
             Protocol::OpenAICompletions => write!(f, "OpenAI Completions"),
             Protocol::OpenAIResponses => write!(f, "OpenAI Responses"),
+            Protocol::OpenAIEmbeddings => write!(f, "OpenAI Embeddings"),
             Protocol::Anthropic => write!(f, "Anthropic"),
             Protocol::Unknown => write!(f, "Unknown"),
         }
@@ -42,6 +45,71 @@ pub struct SinkHealth {
     pub last_check: chrono::DateTime<chrono::Utc>,
 }
 
+/// Circuit-breaker view of a sink's health
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Routing normally
+    Closed,
+    /// Open for long enough that requests are let through again as trials;
+    /// the first to succeed closes the circuit
+    HalfOpen,
+    /// Excluded from routing until the cooldown passes
+    Open,
+}
+
+impl SinkHealth {
+    /// Weight of the newest outcome in the rolling error rate
+    const ERROR_RATE_ALPHA: f32 = 0.2;
+    /// Error rate at which the circuit opens and the sink is marked unhealthy
+    const OPEN_ERROR_RATE: f32 = 0.5;
+    /// How long an open circuit keeps the sink out of routing before trials
+    const OPEN_COOLDOWN: std::time::Duration = std::time::Duration::from_secs(30);
+
+    /// Fold the outcome of a request or check into this health record.
+    ///
+    /// A single failure does not mark the sink unhealthy; it takes a run of
+    /// failures to push the rolling error rate over the open threshold. Once
+    /// open, nothing is recorded until the cooldown lets a trial through: a
+    /// trial that succeeds closes the circuit, and one that fails starts the
+    /// cooldown again from its `last_check`.
+    pub fn record(&mut self, latency_ms: Option<u64>, error: Option<String>) {
+        if error.is_none() && !self.healthy {
+            self.error_rate = 0.0;
+        } else {
+            let failed = if error.is_some() { 1.0 } else { 0.0 };
+            self.error_rate += Self::ERROR_RATE_ALPHA * (failed - self.error_rate);
+        }
+        self.healthy = self.error_rate < Self::OPEN_ERROR_RATE;
+        if latency_ms.is_some() {
+            self.latency_ms = latency_ms;
+        }
+        if error.is_some() {
+            self.last_error = error;
+        }
+        self.last_check = chrono::Utc::now();
+    }
+
+    pub fn circuit_state(&self) -> CircuitState {
+        let open_for = chrono::Utc::now() - self.last_check;
+        if self.healthy {
+            CircuitState::Closed
+        } else if open_for
+            .to_std()
+            .is_ok_and(|open_for| open_for >= Self::OPEN_COOLDOWN)
+        {
+            CircuitState::HalfOpen
+        } else {
+            CircuitState::Open
+        }
+    }
+
+    /// Whether requests may be routed to the sink, as trials if half-open
+    pub fn routable(&self) -> bool {
+        self.circuit_state() != CircuitState::Open
+    }
+}
+
 /// Virtual model definition for user-defined routing
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VirtualModel {
@@ -50,6 +118,29 @@ pub struct VirtualModel {
     pub owner: String,
     pub routing_rules: RoutingRules,
     pub visibility: Visibility,
+    /// Output token limit applied when a request sets none
+    #[serde(default)]
+    pub default_max_tokens: Option<u32>,
+    /// Document collection searched to ground requests for this model
+    #[serde(default)]
+    pub retrieval: Option<RetrievalConfig>,
+}
+
+fn default_top_k() -> usize {
+    4
+}
+
+/// Retrieval augmentation of requests for a virtual model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetrievalConfig {
+    /// Collection of uploaded documents to search
+    pub collection: String,
+    /// Most excerpts added to a request
+    #[serde(default = "default_top_k")]
+    pub top_k: usize,
+    /// Cosine similarity below which excerpts are left out
+    #[serde(default)]
+    pub min_score: f32,
 }
 
 /// Routing rules for virtual models
@@ -59,6 +150,26 @@ pub struct RoutingRules {
     pub targets: Vec<RoutingTarget>,
     pub fallback: Option<Box<RoutingRules>>,
     pub constraints: Option<RoutingConstraints>,
+    /// Post-processing applied to responses served under these rules
+    #[serde(default)]
+    pub response_transform: Option<ResponseTransform>,
+}
+
+/// Post-processing of response chunks, so replies look the same whichever
+/// sink served them
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResponseTransform {
+    /// Top-level response fields to remove, e.g. `system_fingerprint`
+    pub strip_fields: Vec<String>,
+    /// Response headers to remove, matched case-insensitively
+    pub strip_headers: Vec<String>,
+    /// Report the model name the client asked for instead of the upstream one
+    pub normalize_model: bool,
+    /// Replacements for `finish_reason` and `stop_reason` values
+    pub stop_reasons: HashMap<String, String>,
+    /// Entries merged into the `metadata` object of the final content chunk
+    pub metadata: HashMap<String, JsonValue>,
 }
 
 /// Target for routing
@@ -103,10 +214,22 @@ pub struct SinkCapabilities {
     pub supports_streaming: bool,
     pub supports_batching: bool,
     pub supports_tools: bool,
+    /// Honours `response_format: json_schema` natively; otherwise it is emulated
+    #[serde(default)]
+    pub supports_structured_output: bool,
     pub max_context_length: Option<usize>,
     pub modalities: Vec<String>,
 }
 
+/// Token limits of one model, from its provider's model metadata
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelLimits {
+    /// Tokens of prompt the model accepts
+    pub context_window: Option<u32>,
+    /// Tokens the model generates at most in one response
+    pub max_output_tokens: Option<u32>,
+}
+
 /// Actual cost from execution
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ActualCost {
@@ -126,6 +249,13 @@ pub enum ResponseChunk {
     Headers(HashMap<String, String>),
     /// Protocol-agnostic content
     Content(JsonValue),
+    /// Content still in the sink's wire form: one serialized JSON document
+    /// and the SSE event name it arrived under. Forwarded to the client as is
+    /// unless something downstream needs to read it.
+    Raw {
+        event: Option<String>,
+        data: Bytes,
+    },
     Usage {
         prompt_tokens: u32,
         completion_tokens: u32,
@@ -139,6 +269,21 @@ pub enum ResponseChunk {
     },
 }
 
+impl ResponseChunk {
+    /// Parse [`ResponseChunk::Raw`] into [`ResponseChunk::Content`], leaving
+    /// other chunks untouched. Data that is not JSON becomes a string.
+    pub fn parsed(self) -> Self {
+        match self {
+            ResponseChunk::Raw { data, .. } => {
+                ResponseChunk::Content(serde_json::from_slice(&data).unwrap_or_else(|_| {
+                    JsonValue::String(String::from_utf8_lossy(&data).into_owned())
+                }))
+            }
+            other => other,
+        }
+    }
+}
+
 /// Reason for stopping generation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum StopReason {
@@ -146,6 +291,8 @@ pub enum StopReason {
     MaxTokens,
     StopSequence(String),
     ToolUse,
+    /// The provider's content policy withheld or cut short the response
+    ContentFilter,
     Error,
     Cancelled,
     Timeout,
@@ -180,6 +327,14 @@ pub struct RetryConfig {
     pub exponential_base: f64,
 }
 
+impl RetryConfig {
+    /// Backoff before retry number `retry` (from 1)
+    pub fn delay(&self, retry: u32) -> Duration {
+        let factor = self.exponential_base.powi(retry.saturating_sub(1) as i32);
+        self.initial_delay.mul_f64(factor).min(self.max_delay)
+    }
+}
+
 impl Default for RetryConfig {
     fn default() -> Self {
         Self {
@@ -0,0 +1,167 @@
+//! Worker pool for CPU-heavy request processing
+//!
+//! Estimating tokens, serialising large bodies and repairing or checking
+//! structured output can take milliseconds on long prompts. Done on the
+//! async runtime, that work holds up every stream polled by the same thread.
+//! Middleware hands it to a [`WorkerPool`] instead: jobs run on the runtime's
+//! blocking threads, at most `limit` at a time, and the rest wait in a queue
+//! whose depth is exported as `router_workers_queued`. On wasm, which has no
+//! threads to hand work to, jobs run inline.
+
+use crate::tracing::metrics::{Gauge, gauge};
+use crate::{Error, Result};
+use std::sync::{Arc, OnceLock};
+use tokio::sync::Semaphore;
+
+/// Pool used by the router's own middleware
+static SHARED: OnceLock<WorkerPool> = OnceLock::new();
+
+/// Bounded pool running CPU-heavy jobs off the async runtime
+#[derive(Clone)]
+pub struct WorkerPool {
+    limit: usize,
+    permits: Arc<Semaphore>,
+    queued: Gauge,
+    active: Gauge,
+}
+
+/// Decrements a gauge when dropped, so cancelled jobs are not counted
+struct Held(Gauge);
+
+impl Held {
+    fn new(gauge: &Gauge) -> Self {
+        gauge.increment();
+        Self(gauge.clone())
+    }
+}
+
+impl Drop for Held {
+    fn drop(&mut self) {
+        self.0.decrement();
+    }
+}
+
+impl WorkerPool {
+    /// Pool running at most `limit` jobs at once, reporting its queue
+    /// under the `pool` label
+    pub fn new(name: &str, limit: usize) -> Self {
+        let limit = limit.max(1);
+        Self {
+            limit,
+            permits: Arc::new(Semaphore::new(limit)),
+            queued: gauge(&format!("router_workers_queued{{pool=\"{name}\"}}")),
+            active: gauge(&format!("router_workers_active{{pool=\"{name}\"}}")),
+        }
+    }
+
+    /// The pool shared by router middleware, one worker per CPU
+    pub fn shared() -> &'static WorkerPool {
+        SHARED.get_or_init(|| {
+            let cpus = std::thread::available_parallelism().map_or(1, |n| n.get());
+            Self::new("cpu", cpus)
+        })
+    }
+
+    /// Most jobs run at once
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Jobs waiting for a worker
+    pub fn queued(&self) -> i64 {
+        self.queued.get()
+    }
+
+    /// Jobs running now
+    pub fn active(&self) -> i64 {
+        self.active.get()
+    }
+
+    /// Run `job` on a worker once one is free
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn run<F, T>(&self, job: F) -> Result<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let waiting = Held::new(&self.queued);
+        let permit = self
+            .permits
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|_| Error::Internal("Worker pool closed".to_string()))?;
+        drop(waiting);
+
+        // The permit and the active count go with the job, so a caller that
+        // stops waiting does not free a worker that is still busy
+        let active = Held::new(&self.active);
+        tokio::task::spawn_blocking(move || {
+            let _held = (permit, active);
+            job()
+        })
+        .await
+        .map_err(|e| Error::Internal(format!("Worker job failed: {e}")))
+    }
+
+    /// Run `job` inline
+    #[cfg(target_arch = "wasm32")]
+    pub async fn run<F, T>(&self, job: F) -> Result<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let _active = Held::new(&self.active);
+        Ok(job())
+    }
+}
+
+/// Run `job` on the shared pool
+pub async fn offload<F, T>(job: F) -> Result<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    WorkerPool::shared().run(job).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_jobs_beyond_the_limit_queue() {
+        let pool = WorkerPool::new("test_queue", 1);
+        let (release, blocked) = mpsc::channel::<()>();
+
+        let first = tokio::spawn({
+            let pool = pool.clone();
+            async move { pool.run(move || blocked.recv().is_ok()).await }
+        });
+        while pool.active() == 0 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        let second = tokio::spawn({
+            let pool = pool.clone();
+            async move { pool.run(|| 2 + 2).await }
+        });
+        while pool.queued() == 0 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        assert_eq!(pool.active(), 1);
+
+        release.send(()).unwrap();
+        assert!(first.await.unwrap().unwrap());
+        assert_eq!(second.await.unwrap().unwrap(), 4);
+        assert_eq!((pool.queued(), pool.active()), (0, 0));
+    }
+
+    #[tokio::test]
+    async fn test_panicking_job_is_an_error() {
+        let pool = WorkerPool::new("test_panic", 1);
+        assert!(pool.run(|| panic!("job failed")).await.is_err());
+        assert_eq!(pool.run(|| 1).await.unwrap(), 1);
+    }
+}
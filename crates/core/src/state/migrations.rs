@@ -0,0 +1,121 @@
+//! Versioned schema migrations for state backends
+//!
+//! Each backend ships an ordered list of migrations, every one with an up and
+//! a down step. [`SchemaMigrator`] is the common surface the daemon uses to
+//! check which migrations are pending, back up the store before applying
+//! them, and roll back to an earlier version.
+
+use crate::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+/// A migration known to a backend
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MigrationInfo {
+    pub version: i64,
+    pub description: String,
+    /// Whether the migration has a down step
+    pub reversible: bool,
+}
+
+/// How an existing store compares to the migrations a backend knows about
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MigrationStatus {
+    /// Highest applied version, or 0 for an empty store
+    pub current: i64,
+    /// Highest version known to this build
+    pub latest: i64,
+    /// Known migrations not yet applied, in order
+    pub pending: Vec<MigrationInfo>,
+    /// Applied versions this build does not know, e.g. from a newer release
+    pub unknown: Vec<i64>,
+}
+
+impl MigrationStatus {
+    pub fn new(known: &[MigrationInfo], applied: &[i64]) -> Self {
+        let mut pending: Vec<_> = known
+            .iter()
+            .filter(|m| !applied.contains(&m.version))
+            .cloned()
+            .collect();
+        pending.sort_by_key(|m| m.version);
+        let mut unknown: Vec<_> = applied
+            .iter()
+            .copied()
+            .filter(|v| !known.iter().any(|m| m.version == *v))
+            .collect();
+        unknown.sort_unstable();
+        Self {
+            current: applied.iter().copied().max().unwrap_or(0),
+            latest: known.iter().map(|m| m.version).max().unwrap_or(0),
+            pending,
+            unknown,
+        }
+    }
+
+    /// Nothing to apply and nothing from a newer build
+    pub fn is_up_to_date(&self) -> bool {
+        self.pending.is_empty() && self.unknown.is_empty()
+    }
+
+    /// Whether the store has never been migrated
+    pub fn is_fresh(&self) -> bool {
+        self.current == 0
+    }
+}
+
+/// Schema migrations for a state backend
+#[async_trait]
+pub trait SchemaMigrator: Send + Sync {
+    /// Migrations this build knows about, in order
+    fn known(&self) -> Vec<MigrationInfo>;
+
+    /// Versions already applied to the store
+    async fn applied(&self) -> Result<Vec<i64>>;
+
+    /// Apply all pending migrations, returning the versions applied
+    async fn migrate_up(&self) -> Result<Vec<i64>>;
+
+    /// Revert migrations above `target`, newest first, returning the
+    /// versions reverted
+    async fn migrate_down(&self, target: i64) -> Result<Vec<i64>>;
+
+    /// Write a consistent copy of the store to `destination`
+    async fn backup(&self, destination: &str) -> Result<()>;
+
+    async fn status(&self) -> Result<MigrationStatus> {
+        Ok(MigrationStatus::new(&self.known(), &self.applied().await?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn migration(version: i64) -> MigrationInfo {
+        MigrationInfo {
+            version,
+            description: format!("migration {version}"),
+            reversible: true,
+        }
+    }
+
+    #[test]
+    fn test_status_reports_pending_and_unknown_versions() {
+        let known = [migration(1), migration(2), migration(3)];
+
+        let fresh = MigrationStatus::new(&known, &[]);
+        assert!(fresh.is_fresh());
+        assert_eq!(fresh.pending.len(), 3);
+
+        let partial = MigrationStatus::new(&known, &[1, 2]);
+        assert_eq!((partial.current, partial.latest), (2, 3));
+        assert_eq!(partial.pending, vec![migration(3)]);
+        assert!(!partial.is_up_to_date());
+
+        let newer = MigrationStatus::new(&known, &[1, 2, 3, 4]);
+        assert!(newer.pending.is_empty());
+        assert_eq!(newer.unknown, vec![4]);
+        assert!(!newer.is_up_to_date());
+    }
+}
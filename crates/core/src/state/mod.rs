@@ -0,0 +1,545 @@
+use crate::{
+    ApiKey, AssistantObject, BlobCollection, Conversation, DataClass, Experiment,
+    ExperimentOutcome, Feedback, Model, Organization, PermissionDecision, PermissionDecisionFilter,
+    PromptRender, PromptTemplate, Provider, Result, RoutingDecision, StoredResponse, TimeRange,
+    UsageRecord, UsageSubject, UsageTotals, User, UserDataDeletion, UserPreferences,
+    access::{Action, ObjectIdentity},
+};
+use async_trait::async_trait;
+
+pub mod migrations;
+
+pub use migrations::{MigrationInfo, MigrationStatus, SchemaMigrator};
+
+#[async_trait]
+pub trait StateBackend: Send + Sync {
+    // User management
+    async fn get_user(&self, user_id: &str) -> Result<Option<User>>;
+    async fn get_user_by_id(&self, user_id: &str) -> Result<Option<User>>;
+    async fn create_user(&self, user: &User) -> Result<()>;
+    async fn update_user(&self, user: &User) -> Result<()>;
+    async fn delete_user(&self, user_id: &str) -> Result<()>;
+    async fn list_users(&self) -> Result<Vec<User>>;
+
+    // API key management
+    async fn get_api_key(&self, key_hash: &str) -> Result<Option<ApiKey>>;
+    async fn create_api_key(&self, key: &ApiKey, raw_key: &str) -> Result<()>;
+    async fn list_api_keys(&self, org_id: &str) -> Result<Vec<ApiKey>>;
+    async fn delete_api_key(&self, key_hash: &str) -> Result<()>;
+
+    // Usage tracking
+    async fn record_usage(&self, usage: &UsageRecord) -> Result<()>;
+    async fn get_usage(&self, org_id: &str, range: &TimeRange) -> Result<Vec<UsageRecord>>;
+
+    // Provider management
+    async fn get_provider(&self, id: &str) -> Result<Option<Provider>>;
+    async fn list_providers(&self) -> Result<Vec<Provider>>;
+
+    // Model management
+    async fn get_model(&self, id: &str) -> Result<Option<Model>>;
+    async fn list_models(&self) -> Result<Vec<Model>>;
+
+    // Organization management
+    async fn get_organization(&self, id: &str) -> Result<Option<Organization>>;
+    async fn create_organization(&self, org: &Organization) -> Result<()>;
+
+    // Permission management
+    async fn has_permission(
+        &self,
+        subject_id: &str,
+        action: &Action,
+        object: &ObjectIdentity,
+    ) -> Result<bool>;
+
+    async fn grant_permission(
+        &self,
+        subject_id: &str,
+        action: &Action,
+        object: &ObjectIdentity,
+    ) -> Result<()>;
+
+    async fn remove_permission(
+        &self,
+        subject_id: &str,
+        action: &Action,
+        object: &ObjectIdentity,
+    ) -> Result<()>;
+
+    async fn list_user_permissions(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<(String, String, chrono::DateTime<chrono::Utc>)>>;
+
+    /// Whether any of `subject_ids` holds `action` on any of `objects`
+    async fn has_any_permission(
+        &self,
+        subject_ids: &[String],
+        action: &Action,
+        objects: &[ObjectIdentity],
+    ) -> Result<bool> {
+        for subject_id in subject_ids {
+            for object in objects {
+                if self.has_permission(subject_id, action, object).await? {
+                    return Ok(true);
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    /// Add a subject to a group, creating the group if needed
+    async fn add_group_member(&self, _group: &str, _subject_id: &str) -> Result<()> {
+        Err(crate::Error::Internal(
+            "Permission groups not implemented".into(),
+        ))
+    }
+
+    /// Remove a subject from a group, returning whether it was a member
+    async fn remove_group_member(&self, _group: &str, _subject_id: &str) -> Result<bool> {
+        Err(crate::Error::Internal(
+            "Permission groups not implemented".into(),
+        ))
+    }
+
+    /// Groups with at least one member, by name
+    async fn list_groups(&self) -> Result<Vec<String>> {
+        Err(crate::Error::Internal(
+            "Permission groups not implemented".into(),
+        ))
+    }
+
+    async fn list_group_members(&self, _group: &str) -> Result<Vec<String>> {
+        Err(crate::Error::Internal(
+            "Permission groups not implemented".into(),
+        ))
+    }
+
+    /// Groups a subject belongs to; none when groups are not supported
+    async fn list_subject_groups(&self, _subject_id: &str) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    /// Record that an API key was used for authentication
+    async fn touch_api_key(
+        &self,
+        _key_hash: &str,
+        _used_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<()> {
+        Err(crate::Error::Internal(
+            "API key usage tracking not implemented".into(),
+        ))
+    }
+
+    /// Take one of `limit` stream slots for `key` unless all are held,
+    /// returning whether it was taken. The slot is held until it is
+    /// released or `expires_at` passes.
+    async fn acquire_stream_slot(
+        &self,
+        _key: &str,
+        _slot_id: &str,
+        _limit: u32,
+        _expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<bool> {
+        Err(crate::Error::Internal(
+            "Stream slots not implemented".into(),
+        ))
+    }
+
+    /// Extend the lease on a slot of `key` to `expires_at`, returning
+    /// whether it was still held
+    async fn renew_stream_slot(
+        &self,
+        _key: &str,
+        _slot_id: &str,
+        _expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<bool> {
+        Err(crate::Error::Internal(
+            "Stream slots not implemented".into(),
+        ))
+    }
+
+    async fn release_stream_slot(&self, _key: &str, _slot_id: &str) -> Result<()> {
+        Err(crate::Error::Internal(
+            "Stream slots not implemented".into(),
+        ))
+    }
+
+    /// Stream slots currently held for `key`; none when slots are not
+    /// supported
+    async fn count_stream_slots(&self, _key: &str) -> Result<u32> {
+        Ok(0)
+    }
+
+    /// Take the lease `name` for `holder` until `expires_at`, or extend it
+    /// if `holder` has it already, returning whether `holder` holds it.
+    /// Daemons sharing a backend elect the one that runs work only one of
+    /// them should with a lease. A backend no other daemon can share grants
+    /// every lease.
+    async fn acquire_lease(
+        &self,
+        _name: &str,
+        _holder: &str,
+        _expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<bool> {
+        Ok(true)
+    }
+
+    /// Give up the lease `name` if `holder` has it
+    async fn release_lease(&self, _name: &str, _holder: &str) -> Result<()> {
+        Ok(())
+    }
+
+    /// Insert or replace a conversation
+    async fn save_conversation(&self, _conversation: &Conversation) -> Result<()> {
+        Err(crate::Error::Internal(
+            "Conversation storage not implemented".into(),
+        ))
+    }
+
+    async fn get_conversation(&self, _id: &str) -> Result<Option<Conversation>> {
+        Err(crate::Error::Internal(
+            "Conversation storage not implemented".into(),
+        ))
+    }
+
+    /// List a user's conversations, most recently updated first
+    async fn list_conversations(&self, _user_id: &str) -> Result<Vec<Conversation>> {
+        Err(crate::Error::Internal(
+            "Conversation storage not implemented".into(),
+        ))
+    }
+
+    async fn delete_conversation(&self, _id: &str) -> Result<()> {
+        Err(crate::Error::Internal(
+            "Conversation storage not implemented".into(),
+        ))
+    }
+
+    async fn get_user_preferences(&self, _user_id: &str) -> Result<Option<UserPreferences>> {
+        Err(crate::Error::Internal(
+            "Preference storage not implemented".into(),
+        ))
+    }
+
+    /// Insert or replace a user's preferences
+    async fn save_user_preferences(&self, _preferences: &UserPreferences) -> Result<()> {
+        Err(crate::Error::Internal(
+            "Preference storage not implemented".into(),
+        ))
+    }
+
+    async fn get_stored_response(&self, _id: &str) -> Result<Option<StoredResponse>> {
+        Err(crate::Error::Internal(
+            "Response storage not implemented".into(),
+        ))
+    }
+
+    async fn save_stored_response(&self, _response: &StoredResponse) -> Result<()> {
+        Err(crate::Error::Internal(
+            "Response storage not implemented".into(),
+        ))
+    }
+
+    async fn get_assistant_object(&self, _id: &str) -> Result<Option<AssistantObject>> {
+        Err(crate::Error::Internal(
+            "Assistant storage not implemented".into(),
+        ))
+    }
+
+    /// Insert or replace an Assistants API object
+    async fn save_assistant_object(&self, _object: &AssistantObject) -> Result<()> {
+        Err(crate::Error::Internal(
+            "Assistant storage not implemented".into(),
+        ))
+    }
+
+    /// List an owner's objects of one type, oldest first. Messages and runs
+    /// are filtered to `thread_id` when given.
+    async fn list_assistant_objects(
+        &self,
+        _owner_id: &str,
+        _object: &str,
+        _thread_id: Option<&str>,
+    ) -> Result<Vec<AssistantObject>> {
+        Err(crate::Error::Internal(
+            "Assistant storage not implemented".into(),
+        ))
+    }
+
+    async fn delete_assistant_object(&self, _id: &str) -> Result<()> {
+        Err(crate::Error::Internal(
+            "Assistant storage not implemented".into(),
+        ))
+    }
+
+    /// A prompt template's latest version, or the given one
+    async fn get_prompt(
+        &self,
+        _name: &str,
+        _version: Option<u32>,
+    ) -> Result<Option<PromptTemplate>> {
+        Err(crate::Error::Internal(
+            "Prompt storage not implemented".into(),
+        ))
+    }
+
+    /// Store a new version of a prompt template. Versions are never replaced.
+    async fn save_prompt(&self, _prompt: &PromptTemplate) -> Result<()> {
+        Err(crate::Error::Internal(
+            "Prompt storage not implemented".into(),
+        ))
+    }
+
+    /// The latest version of every prompt template, by name
+    async fn list_prompts(&self) -> Result<Vec<PromptTemplate>> {
+        Err(crate::Error::Internal(
+            "Prompt storage not implemented".into(),
+        ))
+    }
+
+    /// Every version of a prompt template, oldest first
+    async fn list_prompt_versions(&self, _name: &str) -> Result<Vec<PromptTemplate>> {
+        Err(crate::Error::Internal(
+            "Prompt storage not implemented".into(),
+        ))
+    }
+
+    /// Remove every version of a prompt template
+    async fn delete_prompt(&self, _name: &str) -> Result<()> {
+        Err(crate::Error::Internal(
+            "Prompt storage not implemented".into(),
+        ))
+    }
+
+    async fn record_prompt_render(&self, _render: &PromptRender) -> Result<()> {
+        Err(crate::Error::Internal(
+            "Prompt storage not implemented".into(),
+        ))
+    }
+
+    /// Most recent renders of a prompt template, newest first
+    async fn list_prompt_renders(&self, _name: &str, _limit: usize) -> Result<Vec<PromptRender>> {
+        Err(crate::Error::Internal(
+            "Prompt storage not implemented".into(),
+        ))
+    }
+
+    async fn get_experiment(&self, _id: &str) -> Result<Option<Experiment>> {
+        Err(crate::Error::Internal(
+            "Experiment storage not implemented".into(),
+        ))
+    }
+
+    /// Create or replace an experiment
+    async fn save_experiment(&self, _experiment: &Experiment) -> Result<()> {
+        Err(crate::Error::Internal(
+            "Experiment storage not implemented".into(),
+        ))
+    }
+
+    /// Every experiment, newest first
+    async fn list_experiments(&self) -> Result<Vec<Experiment>> {
+        Err(crate::Error::Internal(
+            "Experiment storage not implemented".into(),
+        ))
+    }
+
+    /// Remove an experiment and its outcomes
+    async fn delete_experiment(&self, _id: &str) -> Result<()> {
+        Err(crate::Error::Internal(
+            "Experiment storage not implemented".into(),
+        ))
+    }
+
+    async fn record_experiment_outcome(&self, _outcome: &ExperimentOutcome) -> Result<()> {
+        Err(crate::Error::Internal(
+            "Experiment storage not implemented".into(),
+        ))
+    }
+
+    /// Every outcome recorded for an experiment, oldest first
+    async fn list_experiment_outcomes(
+        &self,
+        _experiment_id: &str,
+    ) -> Result<Vec<ExperimentOutcome>> {
+        Err(crate::Error::Internal(
+            "Experiment storage not implemented".into(),
+        ))
+    }
+
+    /// Attach a user rating to the outcome of a request. Returns whether the
+    /// request was part of an experiment.
+    async fn record_experiment_feedback(&self, _request_id: &str, _feedback: i32) -> Result<bool> {
+        Err(crate::Error::Internal(
+            "Experiment storage not implemented".into(),
+        ))
+    }
+
+    /// Usage recorded in a time range across all organizations, newest first
+    async fn list_usage(&self, _range: &TimeRange) -> Result<Vec<UsageRecord>> {
+        Err(crate::Error::Internal(
+            "Usage listing not implemented".into(),
+        ))
+    }
+
+    /// Usage of `subject` recorded since `since`, summed
+    async fn usage_totals(
+        &self,
+        subject: &UsageSubject,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<UsageTotals> {
+        let range = TimeRange {
+            start: since,
+            end: chrono::Utc::now(),
+        };
+        Ok(self
+            .list_usage(&range)
+            .await?
+            .iter()
+            .filter(|record| subject.matches(record))
+            .fold(UsageTotals::default(), UsageTotals::add))
+    }
+
+    /// Usage recorded for a request, by correlation id
+    async fn get_usage_by_request(&self, _request_id: &str) -> Result<Option<UsageRecord>> {
+        Err(crate::Error::Internal(
+            "Usage lookup by request not implemented".into(),
+        ))
+    }
+
+    /// A user's feedback on a request
+    async fn get_feedback(&self, _request_id: &str, _user_id: &str) -> Result<Option<Feedback>> {
+        Err(crate::Error::Internal(
+            "Feedback storage not implemented".into(),
+        ))
+    }
+
+    /// Create or replace feedback
+    async fn save_feedback(&self, _feedback: &Feedback) -> Result<()> {
+        Err(crate::Error::Internal(
+            "Feedback storage not implemented".into(),
+        ))
+    }
+
+    /// Feedback updated since `since`, newest first
+    async fn list_feedback(
+        &self,
+        _since: Option<chrono::DateTime<chrono::Utc>>,
+        _limit: usize,
+    ) -> Result<Vec<Feedback>> {
+        Err(crate::Error::Internal(
+            "Feedback storage not implemented".into(),
+        ))
+    }
+
+    async fn record_permission_decision(&self, _decision: &PermissionDecision) -> Result<()> {
+        Err(crate::Error::Internal(
+            "Permission audit storage not implemented".into(),
+        ))
+    }
+
+    /// Permission decisions matching `filter`, newest first
+    async fn list_permission_decisions(
+        &self,
+        _filter: &PermissionDecisionFilter,
+        _limit: usize,
+    ) -> Result<Vec<PermissionDecision>> {
+        Err(crate::Error::Internal(
+            "Permission audit storage not implemented".into(),
+        ))
+    }
+
+    async fn record_routing_decision(&self, _decision: &RoutingDecision) -> Result<()> {
+        Err(crate::Error::Internal(
+            "Routing audit storage not implemented".into(),
+        ))
+    }
+
+    /// Routing decisions, newest first, of one user if `user_id` is set
+    async fn list_routing_decisions(
+        &self,
+        _user_id: Option<&str>,
+        _limit: usize,
+    ) -> Result<Vec<RoutingDecision>> {
+        Err(crate::Error::Internal(
+            "Routing audit storage not implemented".into(),
+        ))
+    }
+
+    /// Delete data of a class older than `before`, returning how many
+    /// records were removed
+    async fn purge_data(
+        &self,
+        _class: DataClass,
+        _before: chrono::DateTime<chrono::Utc>,
+    ) -> Result<u64> {
+        Err(crate::Error::Internal(
+            "Data purging not implemented".into(),
+        ))
+    }
+
+    /// Delete everything stored about a user's activity: conversations,
+    /// feedback, usage, audit entries, captured bodies, assistant objects
+    /// and preferences. The account itself is kept.
+    async fn delete_user_data(&self, _user_id: &str) -> Result<UserDataDeletion> {
+        Err(crate::Error::Internal(
+            "User data deletion not implemented".into(),
+        ))
+    }
+
+    /// Remove the deduplicated body blobs no record refers to any more.
+    /// Blobs written since `before` are kept, as their records may not be
+    /// saved yet.
+    async fn collect_blobs(
+        &self,
+        _before: chrono::DateTime<chrono::Utc>,
+    ) -> Result<BlobCollection> {
+        Err(crate::Error::Internal(
+            "Blob collection not implemented".into(),
+        ))
+    }
+
+    // Router-specific methods with default implementations
+    async fn resolve_model_alias(&self, _alias: &str) -> Result<Vec<String>> {
+        Err(crate::Error::Internal(
+            "Model alias resolution not implemented".into(),
+        ))
+    }
+
+    async fn get_virtual_model(
+        &self,
+        _name: &str,
+        _version: Option<&str>,
+    ) -> Result<Option<crate::router::types::VirtualModel>> {
+        Err(crate::Error::Internal(
+            "Virtual models not implemented".into(),
+        ))
+    }
+
+    async fn save_virtual_model(&self, _model: &crate::router::types::VirtualModel) -> Result<()> {
+        Err(crate::Error::Internal(
+            "Virtual models not implemented".into(),
+        ))
+    }
+
+    async fn update_sink_health(
+        &self,
+        _sink_id: &str,
+        _health: &crate::router::types::SinkHealth,
+    ) -> Result<()> {
+        Err(crate::Error::Internal(
+            "Sink health tracking not implemented".into(),
+        ))
+    }
+
+    async fn get_sink_health(
+        &self,
+        _sink_id: &str,
+    ) -> Result<Option<crate::router::types::SinkHealth>> {
+        Err(crate::Error::Internal(
+            "Sink health tracking not implemented".into(),
+        ))
+    }
+}
+
+// Router-specific types moved to crate::router::types
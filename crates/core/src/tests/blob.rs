@@ -0,0 +1,70 @@
+//! In-memory blob store for tests
+
+use crate::Result;
+use crate::blob::{BlobInfo, BlobStore};
+use async_trait::async_trait;
+use chrono::Utc;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Blobs held in memory
+#[derive(Default)]
+pub struct InMemoryBlobStore {
+    blobs: Mutex<HashMap<String, (BlobInfo, Vec<u8>)>>,
+}
+
+impl InMemoryBlobStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl BlobStore for InMemoryBlobStore {
+    async fn put(&self, key: &str, data: Vec<u8>) -> Result<()> {
+        let info = BlobInfo {
+            key: key.to_string(),
+            size: data.len() as u64,
+            modified: Utc::now(),
+        };
+        self.blobs
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), (info, data));
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        Ok(self
+            .blobs
+            .lock()
+            .unwrap()
+            .get(key)
+            .map(|(_, data)| data.clone()))
+    }
+
+    async fn head(&self, key: &str) -> Result<Option<BlobInfo>> {
+        Ok(self
+            .blobs
+            .lock()
+            .unwrap()
+            .get(key)
+            .map(|(info, _)| info.clone()))
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        self.blobs.lock().unwrap().remove(key);
+        Ok(())
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<BlobInfo>> {
+        Ok(self
+            .blobs
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|(info, _)| info.key.starts_with(prefix))
+            .map(|(info, _)| info.clone())
+            .collect())
+    }
+}
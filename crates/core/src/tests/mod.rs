@@ -1,2 +1,3 @@
+pub mod blob;
 pub mod context;
 pub mod state;
@@ -5,12 +5,15 @@
 //! can use this to ensure compliance with the expected behavior.
 
 use crate::{
-    ApiKey, Model, ModelType, Organization, Provider, ProviderType, Result, StateBackend,
-    TimeRange, UsageRecord, User,
+    ApiKey, AssistantObject, Conversation, DataClass, Experiment, ExperimentOutcome,
+    ExperimentStatus, ExperimentVariant, Feedback, Model, ModelType, Organization,
+    PermissionDecision, PermissionDecisionFilter, PromptMessage, PromptRender, PromptTemplate,
+    Provider, ProviderType, Result, RoutingDecision, StateBackend, StoredResponse, TimeRange,
+    UsageRecord, UsageSubject, User, UserDataDeletion, UserPreferences,
     access::{Action, ObjectIdentity},
 };
 use async_trait::async_trait;
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
 use std::{collections::HashMap, sync::Arc};
 
 /// Test suite for StateBackend implementations
@@ -32,6 +35,19 @@ impl<B: StateBackend> StateBackendTestSuite<B> {
         self.test_provider_operations().await?;
         self.test_model_operations().await?;
         self.test_organization_operations().await?;
+        self.test_conversation_operations().await?;
+        self.test_preference_operations().await?;
+        self.test_stored_response_operations().await?;
+        self.test_assistant_object_operations().await?;
+        self.test_prompt_operations().await?;
+        self.test_experiment_operations().await?;
+        self.test_feedback_operations().await?;
+        self.test_permission_decisions().await?;
+        self.test_routing_decisions().await?;
+        self.test_permission_groups().await?;
+        self.test_stream_slots().await?;
+        self.test_leases().await?;
+        self.test_data_retention().await?;
         Ok(())
     }
 
@@ -67,12 +83,20 @@ impl<B: StateBackend> StateBackendTestSuite<B> {
         // Test update
         let mut updated_user = retrieved_user;
         updated_user.name = Some("Updated Name".to_string());
+        updated_user
+            .metadata
+            .insert("role".to_string(), "viewer".to_string());
         updated_user.updated_at = Utc::now();
         self.backend.update_user(&updated_user).await?;
 
         // Verify update
         let updated = self.backend.get_user_by_id(&user.id).await?.unwrap();
         assert_eq!(updated.name, Some("Updated Name".to_string()));
+        assert_eq!(updated.metadata.get("role"), Some(&"viewer".to_string()));
+        assert_eq!(
+            updated.metadata.get("email"),
+            Some(&"test@example.com".to_string())
+        );
 
         // Test non-existent user
         let non_existent = self.backend.get_user_by_id("non-existent").await?;
@@ -119,6 +143,12 @@ impl<B: StateBackend> StateBackendTestSuite<B> {
         assert!(!keys.is_empty());
         assert!(keys.iter().any(|k| k.key_hash == key.key_hash));
 
+        // Test last-used tracking
+        let used_at = Utc::now();
+        self.backend.touch_api_key(&key.key_hash, used_at).await?;
+        let touched = self.backend.get_api_key(&key.key_hash).await?.unwrap();
+        assert!(touched.last_used_at.is_some());
+
         // Test delete
         self.backend.delete_api_key(&key.key_hash).await?;
         let deleted = self.backend.get_api_key(&key.key_hash).await?;
@@ -174,6 +204,29 @@ impl<B: StateBackend> StateBackendTestSuite<B> {
         let empty_records = self.backend.get_usage(&org_id, &empty_range).await?;
         assert!(empty_records.is_empty());
 
+        // Listing spans organizations, so other tests' records may be included
+        let listed = self.backend.list_usage(&range).await?;
+        assert_eq!(listed.iter().filter(|u| u.org_id == org_id).count(), 5);
+        for i in 1..listed.len() {
+            assert!(listed[i - 1].timestamp >= listed[i].timestamp);
+        }
+
+        let totals = self
+            .backend
+            .usage_totals(&UsageSubject::User(user_id.clone()), range.start)
+            .await?;
+        assert_eq!(totals.requests, 5);
+        assert_eq!(totals.tokens, 1800);
+        assert!((totals.cost - 0.15).abs() < 1e-9);
+        let recent = self
+            .backend
+            .usage_totals(
+                &UsageSubject::User(user_id.clone()),
+                base_time - Duration::seconds(90),
+            )
+            .await?;
+        assert_eq!(recent.requests, 2);
+
         Ok(())
     }
 
@@ -246,6 +299,700 @@ impl<B: StateBackend> StateBackendTestSuite<B> {
 
         Ok(())
     }
+
+    /// Test conversation storage
+    pub async fn test_conversation_operations(&self) -> Result<()> {
+        let user_id = format!("test-user-{}", uuid::Uuid::new_v4());
+        let now = Utc::now();
+
+        let older = Conversation {
+            id: format!("conv-{}", uuid::Uuid::new_v4()),
+            user_id: user_id.clone(),
+            title: "First chat".to_string(),
+            model: Some("test-model".to_string()),
+            messages: serde_json::json!([{"role": "user", "content": "hello"}]),
+            created_at: now - Duration::minutes(5),
+            updated_at: now - Duration::minutes(5),
+        };
+        let newer = Conversation {
+            id: format!("conv-{}", uuid::Uuid::new_v4()),
+            title: "Second chat".to_string(),
+            created_at: now,
+            updated_at: now,
+            ..older.clone()
+        };
+        self.backend.save_conversation(&older).await?;
+        self.backend.save_conversation(&newer).await?;
+
+        // Listing is per user and newest first
+        let listed = self.backend.list_conversations(&user_id).await?;
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0].id, newer.id);
+        assert!(
+            self.backend
+                .list_conversations("other-user")
+                .await?
+                .is_empty()
+        );
+
+        // Saving again replaces the conversation
+        let renamed = Conversation {
+            title: "Renamed".to_string(),
+            updated_at: now + Duration::minutes(1),
+            ..older.clone()
+        };
+        self.backend.save_conversation(&renamed).await?;
+        let retrieved = self.backend.get_conversation(&older.id).await?.unwrap();
+        assert_eq!(retrieved.title, "Renamed");
+        assert_eq!(retrieved.messages, older.messages);
+        assert_eq!(
+            self.backend.list_conversations(&user_id).await?[0].id,
+            older.id
+        );
+
+        self.backend.delete_conversation(&older.id).await?;
+        assert!(self.backend.get_conversation(&older.id).await?.is_none());
+
+        Ok(())
+    }
+
+    /// Test user preference storage
+    pub async fn test_preference_operations(&self) -> Result<()> {
+        let user_id = format!("test-user-{}", uuid::Uuid::new_v4());
+        assert!(self.backend.get_user_preferences(&user_id).await?.is_none());
+
+        let preferences = UserPreferences {
+            user_id: user_id.clone(),
+            preferences: serde_json::json!({"theme": "dark"}),
+            updated_at: Utc::now(),
+        };
+        self.backend.save_user_preferences(&preferences).await?;
+
+        // Saving again replaces the stored preferences
+        let updated = UserPreferences {
+            preferences: serde_json::json!({"theme": "high_contrast", "accent": "#ff0000"}),
+            ..preferences
+        };
+        self.backend.save_user_preferences(&updated).await?;
+        let retrieved = self.backend.get_user_preferences(&user_id).await?.unwrap();
+        assert_eq!(retrieved.preferences, updated.preferences);
+
+        Ok(())
+    }
+
+    /// Test Responses API state storage
+    pub async fn test_stored_response_operations(&self) -> Result<()> {
+        let id = format!("resp_{}", uuid::Uuid::new_v4().simple());
+        assert!(self.backend.get_stored_response(&id).await?.is_none());
+
+        let stored = StoredResponse {
+            id: id.clone(),
+            owner_id: "test-user".to_string(),
+            input: serde_json::json!([
+                {"role": "user", "content": "Hi"},
+                {"role": "assistant", "content": "Hello"}
+            ]),
+            response: serde_json::json!({"id": id, "object": "response"}),
+            created_at: Utc::now(),
+        };
+        self.backend.save_stored_response(&stored).await?;
+
+        let retrieved = self.backend.get_stored_response(&id).await?.unwrap();
+        assert_eq!(retrieved.owner_id, stored.owner_id);
+        assert_eq!(retrieved.input, stored.input);
+        assert_eq!(retrieved.response, stored.response);
+
+        Ok(())
+    }
+
+    /// Test Assistants API object storage
+    pub async fn test_assistant_object_operations(&self) -> Result<()> {
+        let owner_id = format!("test-user-{}", uuid::Uuid::new_v4());
+        let thread_id = format!("thread_{}", uuid::Uuid::new_v4().simple());
+        let object = |id: &str, kind: &str, thread_id: Option<&str>, offset: i64| AssistantObject {
+            id: id.to_string(),
+            object: kind.to_string(),
+            owner_id: owner_id.clone(),
+            thread_id: thread_id.map(String::from),
+            data: serde_json::json!({"id": id, "object": kind}),
+            state: serde_json::json!({}),
+            created_at: Utc::now() + Duration::seconds(offset),
+        };
+
+        let thread = object(&thread_id, "thread", None, 0);
+        let first = object("msg_first", "thread.message", Some(&thread_id), 1);
+        let second = object("msg_second", "thread.message", Some(&thread_id), 2);
+        let other = object("msg_other", "thread.message", Some("thread_other"), 3);
+        for item in [&thread, &second, &first, &other] {
+            self.backend.save_assistant_object(item).await?;
+        }
+
+        let retrieved = self
+            .backend
+            .get_assistant_object(&thread_id)
+            .await?
+            .unwrap();
+        assert_eq!(retrieved.owner_id, owner_id);
+        assert_eq!(retrieved.data, thread.data);
+
+        let messages = self
+            .backend
+            .list_assistant_objects(&owner_id, "thread.message", Some(&thread_id))
+            .await?;
+        let ids: Vec<_> = messages.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, vec!["msg_first", "msg_second"]);
+
+        let all = self
+            .backend
+            .list_assistant_objects(&owner_id, "thread.message", None)
+            .await?;
+        assert_eq!(all.len(), 3);
+
+        for item in [&thread, &first, &second, &other] {
+            self.backend.delete_assistant_object(&item.id).await?;
+        }
+        assert!(
+            self.backend
+                .get_assistant_object(&thread_id)
+                .await?
+                .is_none()
+        );
+
+        Ok(())
+    }
+
+    /// Test prompt template versions and render records
+    pub async fn test_prompt_operations(&self) -> Result<()> {
+        let name = format!("test-prompt-{}", uuid::Uuid::new_v4().simple());
+        let template = |version: u32, content: &str| PromptTemplate {
+            name: name.clone(),
+            version,
+            description: None,
+            messages: vec![PromptMessage {
+                role: "system".to_string(),
+                content: content.to_string(),
+            }],
+            variables: Vec::new(),
+            parameters: serde_json::Map::new(),
+            created_by: "admin".to_string(),
+            created_at: Utc::now(),
+        };
+
+        self.backend.save_prompt(&template(1, "First")).await?;
+        self.backend.save_prompt(&template(2, "Second")).await?;
+
+        let latest = self.backend.get_prompt(&name, None).await?.unwrap();
+        assert_eq!(latest.version, 2);
+        assert_eq!(latest.messages[0].content, "Second");
+        let first = self.backend.get_prompt(&name, Some(1)).await?.unwrap();
+        assert_eq!(first.messages[0].content, "First");
+        assert!(self.backend.get_prompt(&name, Some(3)).await?.is_none());
+
+        let versions = self.backend.list_prompt_versions(&name).await?;
+        let numbers: Vec<_> = versions.iter().map(|p| p.version).collect();
+        assert_eq!(numbers, vec![1, 2]);
+        let listed = self.backend.list_prompts().await?;
+        let ours: Vec<_> = listed.iter().filter(|p| p.name == name).collect();
+        assert_eq!(ours.len(), 1);
+        assert_eq!(ours[0].version, 2);
+
+        for (i, version) in [1, 2].into_iter().enumerate() {
+            let render = PromptRender {
+                id: uuid::Uuid::new_v4().to_string(),
+                prompt_name: name.clone(),
+                version,
+                user_id: "user".to_string(),
+                request_id: format!("request-{i}"),
+                model: "gpt-4o".to_string(),
+                created_at: Utc::now() + Duration::seconds(i as i64),
+            };
+            self.backend.record_prompt_render(&render).await?;
+        }
+        let renders = self.backend.list_prompt_renders(&name, 1).await?;
+        assert_eq!(renders.len(), 1);
+        assert_eq!(renders[0].version, 2);
+
+        self.backend.delete_prompt(&name).await?;
+        assert!(self.backend.get_prompt(&name, None).await?.is_none());
+
+        Ok(())
+    }
+
+    /// Test experiment CRUD, outcomes and feedback
+    pub async fn test_experiment_operations(&self) -> Result<()> {
+        let id = format!("test-experiment-{}", uuid::Uuid::new_v4().simple());
+        let variant = |name: &str| ExperimentVariant {
+            name: name.to_string(),
+            weight: 1,
+            model: Some(format!("model-{name}")),
+            prompt_id: None,
+            prompt_version: None,
+            parameters: serde_json::Map::new(),
+        };
+        let mut experiment = Experiment {
+            id: id.clone(),
+            name: "Test Experiment".to_string(),
+            description: None,
+            model: "assistant".to_string(),
+            status: ExperimentStatus::Draft,
+            variants: vec![variant("a"), variant("b")],
+            metrics: Vec::new(),
+            created_by: "admin".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        self.backend.save_experiment(&experiment).await?;
+
+        experiment.status = ExperimentStatus::Running;
+        self.backend.save_experiment(&experiment).await?;
+        let stored = self.backend.get_experiment(&id).await?.unwrap();
+        assert_eq!(stored.status, ExperimentStatus::Running);
+        assert_eq!(stored.variants, experiment.variants);
+        let listed = self.backend.list_experiments().await?;
+        assert_eq!(listed.iter().filter(|e| e.id == id).count(), 1);
+
+        let request_id = format!("request-{id}");
+        let outcome = ExperimentOutcome {
+            id: uuid::Uuid::new_v4().to_string(),
+            experiment_id: id.clone(),
+            variant: "a".to_string(),
+            user_id: "user".to_string(),
+            request_id: request_id.clone(),
+            latency_ms: 120,
+            input_tokens: 10,
+            output_tokens: 20,
+            cost: 0.002,
+            error: false,
+            feedback: None,
+            created_at: Utc::now(),
+        };
+        self.backend.record_experiment_outcome(&outcome).await?;
+        assert!(
+            self.backend
+                .record_experiment_feedback(&request_id, 1)
+                .await?
+        );
+        assert!(
+            !self
+                .backend
+                .record_experiment_feedback("unknown-request", 1)
+                .await?
+        );
+        let outcomes = self.backend.list_experiment_outcomes(&id).await?;
+        assert_eq!(outcomes.len(), 1);
+        assert_eq!(outcomes[0].feedback, Some(1));
+        assert_eq!(outcomes[0].latency_ms, 120);
+
+        self.backend.delete_experiment(&id).await?;
+        assert!(self.backend.get_experiment(&id).await?.is_none());
+        assert!(self.backend.list_experiment_outcomes(&id).await?.is_empty());
+
+        Ok(())
+    }
+
+    /// Test feedback storage and usage lookup by request
+    pub async fn test_feedback_operations(&self) -> Result<()> {
+        let request_id = format!("test-request-{}", uuid::Uuid::new_v4().simple());
+        let started = Utc::now() - Duration::seconds(1);
+
+        let usage = UsageRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            org_id: String::new(),
+            user_id: "user".to_string(),
+            api_key_hash: String::new(),
+            request_id: request_id.clone(),
+            provider_id: "anthropic".to_string(),
+            model_id: "claude".to_string(),
+            input_tokens: 10,
+            output_tokens: 20,
+            total_tokens: 30,
+            cost: 0.0,
+            timestamp: Utc::now(),
+            metadata: HashMap::new(),
+        };
+        self.backend.record_usage(&usage).await?;
+        let found = self
+            .backend
+            .get_usage_by_request(&request_id)
+            .await?
+            .unwrap();
+        assert_eq!(found.model_id, "claude");
+        assert!(
+            self.backend
+                .get_usage_by_request("unknown-request")
+                .await?
+                .is_none()
+        );
+
+        let mut feedback = Feedback {
+            id: uuid::Uuid::new_v4().to_string(),
+            request_id: request_id.clone(),
+            user_id: "user".to_string(),
+            thumbs_up: Some(false),
+            rating: None,
+            comment: Some("Wrong answer".to_string()),
+            model_id: Some(found.model_id),
+            provider_id: Some(found.provider_id),
+            task_type: Some("chat".to_string()),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        self.backend.save_feedback(&feedback).await?;
+
+        // Rating again replaces the earlier feedback
+        feedback.thumbs_up = Some(true);
+        feedback.rating = Some(5);
+        feedback.updated_at = Utc::now();
+        self.backend.save_feedback(&feedback).await?;
+        let stored = self
+            .backend
+            .get_feedback(&request_id, "user")
+            .await?
+            .unwrap();
+        assert_eq!(stored.thumbs_up, Some(true));
+        assert_eq!(stored.rating, Some(5));
+        assert_eq!(stored.comment.as_deref(), Some("Wrong answer"));
+        assert_eq!(stored.task_type.as_deref(), Some("chat"));
+        assert!(
+            self.backend
+                .get_feedback(&request_id, "other")
+                .await?
+                .is_none()
+        );
+
+        let listed = self.backend.list_feedback(Some(started), 1000).await?;
+        assert_eq!(
+            listed.iter().filter(|f| f.request_id == request_id).count(),
+            1
+        );
+        let later = self
+            .backend
+            .list_feedback(Some(Utc::now() + Duration::hours(1)), 1000)
+            .await?;
+        assert!(later.is_empty());
+
+        Ok(())
+    }
+
+    /// Test recording and filtering permission decisions
+    pub async fn test_permission_decisions(&self) -> Result<()> {
+        let subject_id = format!("test-subject-{}", uuid::Uuid::new_v4().simple());
+        let decision = |action: &str, allowed: bool| PermissionDecision {
+            id: uuid::Uuid::new_v4().to_string(),
+            subject_id: subject_id.clone(),
+            action: action.to_string(),
+            object: "system/config/*".to_string(),
+            allowed,
+            policy: if allowed { "grant" } else { "no_grant" }.to_string(),
+            reason: (!allowed).then(|| "Not authorized".to_string()),
+            created_at: Utc::now(),
+        };
+        self.backend
+            .record_permission_decision(&decision("Read", true))
+            .await?;
+        self.backend
+            .record_permission_decision(&decision("Write", false))
+            .await?;
+
+        let filter = PermissionDecisionFilter {
+            subject_id: Some(subject_id.clone()),
+            ..Default::default()
+        };
+        let all = self.backend.list_permission_decisions(&filter, 100).await?;
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].action, "Write");
+
+        let denied = self
+            .backend
+            .list_permission_decisions(
+                &PermissionDecisionFilter {
+                    allowed: Some(false),
+                    ..filter.clone()
+                },
+                100,
+            )
+            .await?;
+        assert_eq!(denied.len(), 1);
+        assert_eq!(denied[0].reason.as_deref(), Some("Not authorized"));
+        assert_eq!(
+            self.backend
+                .list_permission_decisions(&filter, 1)
+                .await?
+                .len(),
+            1
+        );
+
+        Ok(())
+    }
+
+    /// Test recording and listing routing decisions
+    pub async fn test_routing_decisions(&self) -> Result<()> {
+        let user_id = format!("test-user-{}", uuid::Uuid::new_v4().simple());
+        let decision = |tier: &str, model: &str| RoutingDecision {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id: user_id.clone(),
+            request_id: uuid::Uuid::new_v4().to_string(),
+            requested_model: "gate/auto".to_string(),
+            model: model.to_string(),
+            tier: tier.to_string(),
+            rationale: "short prompt".to_string(),
+            overridden: false,
+            created_at: Utc::now(),
+        };
+        self.backend
+            .record_routing_decision(&decision("simple", "small-model"))
+            .await?;
+        self.backend
+            .record_routing_decision(&decision("coding", "large-model"))
+            .await?;
+
+        let ours = self
+            .backend
+            .list_routing_decisions(Some(&user_id), 100)
+            .await?;
+        assert_eq!(ours.len(), 2);
+        assert_eq!(ours[0].tier, "coding");
+        assert_eq!(ours[1].model, "small-model");
+        assert_eq!(
+            self.backend
+                .list_routing_decisions(Some(&user_id), 1)
+                .await?
+                .len(),
+            1
+        );
+        assert!(
+            self.backend
+                .list_routing_decisions(Some("no-such-user"), 100)
+                .await?
+                .is_empty()
+        );
+
+        Ok(())
+    }
+
+    /// Test group membership
+    pub async fn test_permission_groups(&self) -> Result<()> {
+        let group = format!("test-group-{}", uuid::Uuid::new_v4().simple());
+        self.backend.add_group_member(&group, "alice").await?;
+        self.backend.add_group_member(&group, "bob").await?;
+        // Adding twice keeps one membership
+        self.backend.add_group_member(&group, "bob").await?;
+
+        let mut members = self.backend.list_group_members(&group).await?;
+        members.sort();
+        assert_eq!(members, ["alice", "bob"]);
+        assert!(
+            self.backend
+                .list_subject_groups("alice")
+                .await?
+                .contains(&group)
+        );
+        assert!(self.backend.list_groups().await?.contains(&group));
+
+        assert!(self.backend.remove_group_member(&group, "alice").await?);
+        assert!(!self.backend.remove_group_member(&group, "alice").await?);
+        assert_eq!(self.backend.list_group_members(&group).await?, ["bob"]);
+        assert!(
+            !self
+                .backend
+                .list_subject_groups("alice")
+                .await?
+                .contains(&group)
+        );
+
+        Ok(())
+    }
+
+    /// Test taking and releasing stream slots
+    pub async fn test_stream_slots(&self) -> Result<()> {
+        let key = format!("test-key-{}", uuid::Uuid::new_v4().simple());
+        let expires_at = Utc::now() + Duration::minutes(5);
+        assert!(
+            self.backend
+                .acquire_stream_slot(&key, "a", 2, expires_at)
+                .await?
+        );
+        assert!(
+            self.backend
+                .acquire_stream_slot(&key, "b", 2, expires_at)
+                .await?
+        );
+        assert!(
+            !self
+                .backend
+                .acquire_stream_slot(&key, "c", 2, expires_at)
+                .await?
+        );
+        assert_eq!(self.backend.count_stream_slots(&key).await?, 2);
+
+        self.backend.release_stream_slot(&key, "a").await?;
+        assert!(
+            self.backend
+                .acquire_stream_slot(&key, "c", 2, expires_at)
+                .await?
+        );
+
+        // Slots past their lease no longer count
+        let expired = Utc::now() - Duration::minutes(1);
+        self.backend.release_stream_slot(&key, "b").await?;
+        assert!(
+            self.backend
+                .acquire_stream_slot(&key, "d", 2, expired)
+                .await?
+        );
+        assert_eq!(self.backend.count_stream_slots(&key).await?, 1);
+        assert!(
+            self.backend
+                .acquire_stream_slot(&key, "e", 2, expires_at)
+                .await?
+        );
+
+        // Renewing keeps a live slot and cannot revive a lapsed one
+        assert!(self.backend.renew_stream_slot(&key, "e", expired).await?);
+        assert_eq!(self.backend.count_stream_slots(&key).await?, 1);
+        assert!(
+            !self
+                .backend
+                .renew_stream_slot(&key, "e", expires_at)
+                .await?
+        );
+
+        Ok(())
+    }
+
+    /// Test that a lease has one holder until it is released or expires
+    pub async fn test_leases(&self) -> Result<()> {
+        let name = format!("test-lease-{}", uuid::Uuid::new_v4().simple());
+        let expires_at = Utc::now() + Duration::minutes(1);
+        assert!(self.backend.acquire_lease(&name, "a", expires_at).await?);
+        assert!(!self.backend.acquire_lease(&name, "b", expires_at).await?);
+        assert!(self.backend.acquire_lease(&name, "a", expires_at).await?);
+
+        self.backend.release_lease(&name, "b").await?;
+        assert!(!self.backend.acquire_lease(&name, "b", expires_at).await?);
+        self.backend.release_lease(&name, "a").await?;
+        assert!(self.backend.acquire_lease(&name, "b", expires_at).await?);
+
+        // An expired lease can be taken over
+        let expired = Utc::now() - Duration::minutes(1);
+        assert!(self.backend.acquire_lease(&name, "b", expired).await?);
+        assert!(self.backend.acquire_lease(&name, "a", expires_at).await?);
+
+        Ok(())
+    }
+
+    /// Test purging old data and deleting a user's data
+    pub async fn test_data_retention(&self) -> Result<()> {
+        let user_id = format!("test-user-{}", uuid::Uuid::new_v4());
+        let now = Utc::now();
+        let old = now - Duration::days(400);
+        let usage = |id: &str, timestamp: DateTime<Utc>| UsageRecord {
+            id: id.to_string(),
+            org_id: String::new(),
+            user_id: user_id.clone(),
+            api_key_hash: String::new(),
+            request_id: id.to_string(),
+            provider_id: "sink".to_string(),
+            model_id: "model".to_string(),
+            input_tokens: 1,
+            output_tokens: 1,
+            total_tokens: 2,
+            cost: 0.0,
+            timestamp,
+            metadata: HashMap::new(),
+        };
+        let old_usage = format!("usage-{}", uuid::Uuid::new_v4());
+        let new_usage = format!("usage-{}", uuid::Uuid::new_v4());
+        self.backend.record_usage(&usage(&old_usage, old)).await?;
+        self.backend.record_usage(&usage(&new_usage, now)).await?;
+
+        let conversation = |timestamp: DateTime<Utc>| Conversation {
+            id: format!("conv-{}", uuid::Uuid::new_v4()),
+            user_id: user_id.clone(),
+            title: "Retention".to_string(),
+            model: None,
+            messages: serde_json::json!([]),
+            created_at: timestamp,
+            updated_at: timestamp,
+        };
+        let old_conversation = conversation(old);
+        let new_conversation = conversation(now);
+        self.backend.save_conversation(&old_conversation).await?;
+        self.backend.save_conversation(&new_conversation).await?;
+
+        let cutoff = now - Duration::days(365);
+        assert!(
+            self.backend
+                .purge_data(DataClass::UsageRecords, cutoff)
+                .await?
+                >= 1
+        );
+        assert!(
+            self.backend
+                .purge_data(DataClass::Conversations, cutoff)
+                .await?
+                >= 1
+        );
+        assert!(
+            self.backend
+                .get_usage_by_request(&old_usage)
+                .await?
+                .is_none()
+        );
+        assert!(
+            self.backend
+                .get_usage_by_request(&new_usage)
+                .await?
+                .is_some()
+        );
+        assert!(
+            self.backend
+                .get_conversation(&old_conversation.id)
+                .await?
+                .is_none()
+        );
+        assert!(
+            self.backend
+                .get_conversation(&new_conversation.id)
+                .await?
+                .is_some()
+        );
+
+        let feedback = Feedback {
+            id: uuid::Uuid::new_v4().to_string(),
+            request_id: new_usage.clone(),
+            user_id: user_id.clone(),
+            thumbs_up: Some(true),
+            rating: None,
+            comment: None,
+            model_id: None,
+            provider_id: None,
+            task_type: None,
+            created_at: now,
+            updated_at: now,
+        };
+        self.backend.save_feedback(&feedback).await?;
+
+        let deleted = self.backend.delete_user_data(&user_id).await?;
+        assert_eq!(deleted.conversations, 1);
+        assert_eq!(deleted.feedback, 1);
+        assert_eq!(deleted.usage_records, 1);
+        assert!(self.backend.list_conversations(&user_id).await?.is_empty());
+        assert!(
+            self.backend
+                .get_feedback(&new_usage, &user_id)
+                .await?
+                .is_none()
+        );
+        assert!(
+            self.backend
+                .get_usage_by_request(&new_usage)
+                .await?
+                .is_none()
+        );
+
+        Ok(())
+    }
 }
 
 /// Helper function to create test data
@@ -326,6 +1073,23 @@ pub struct InMemoryBackend {
     providers: Arc<std::sync::Mutex<HashMap<String, Provider>>>,
     models: Arc<std::sync::Mutex<HashMap<String, Model>>>,
     organizations: Arc<std::sync::Mutex<HashMap<String, Organization>>>,
+    conversations: Arc<std::sync::Mutex<HashMap<String, Conversation>>>,
+    preferences: Arc<std::sync::Mutex<HashMap<String, UserPreferences>>>,
+    responses: Arc<std::sync::Mutex<HashMap<String, StoredResponse>>>,
+    assistant_objects: Arc<std::sync::Mutex<HashMap<String, AssistantObject>>>,
+    prompts: Arc<std::sync::Mutex<Vec<PromptTemplate>>>,
+    prompt_renders: Arc<std::sync::Mutex<Vec<PromptRender>>>,
+    experiments: Arc<std::sync::Mutex<HashMap<String, Experiment>>>,
+    experiment_outcomes: Arc<std::sync::Mutex<Vec<ExperimentOutcome>>>,
+    feedback: Arc<std::sync::Mutex<HashMap<String, Feedback>>>,
+    permission_decisions: Arc<std::sync::Mutex<Vec<PermissionDecision>>>,
+    routing_decisions: Arc<std::sync::Mutex<Vec<RoutingDecision>>>,
+    /// Members of each group
+    groups: Arc<std::sync::Mutex<HashMap<String, std::collections::BTreeSet<String>>>>,
+    /// Expiry of each held stream slot, by key and slot id
+    stream_slots: Arc<std::sync::Mutex<HashMap<String, HashMap<String, DateTime<Utc>>>>>,
+    /// Holder and expiry of each lease, by name
+    leases: Arc<std::sync::Mutex<HashMap<String, (String, DateTime<Utc>)>>>,
 }
 
 #[async_trait]
@@ -398,6 +1162,505 @@ impl StateBackend for InMemoryBackend {
         Ok(())
     }
 
+    async fn touch_api_key(&self, key_hash: &str, used_at: DateTime<Utc>) -> Result<()> {
+        if let Some(key) = self.api_keys.lock().unwrap().get_mut(key_hash) {
+            key.last_used_at = Some(used_at);
+        }
+        Ok(())
+    }
+
+    async fn acquire_stream_slot(
+        &self,
+        key: &str,
+        slot_id: &str,
+        limit: u32,
+        expires_at: DateTime<Utc>,
+    ) -> Result<bool> {
+        let now = Utc::now();
+        let mut slots = self.stream_slots.lock().unwrap();
+        let held = slots.entry(key.to_string()).or_default();
+        held.retain(|_, expires_at| *expires_at > now);
+        if held.len() >= limit as usize {
+            return Ok(false);
+        }
+        held.insert(slot_id.to_string(), expires_at);
+        Ok(true)
+    }
+
+    async fn renew_stream_slot(
+        &self,
+        key: &str,
+        slot_id: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<bool> {
+        let now = Utc::now();
+        let mut slots = self.stream_slots.lock().unwrap();
+        match slots.get_mut(key).and_then(|held| held.get_mut(slot_id)) {
+            Some(held) if *held > now => {
+                *held = expires_at;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn release_stream_slot(&self, key: &str, slot_id: &str) -> Result<()> {
+        if let Some(held) = self.stream_slots.lock().unwrap().get_mut(key) {
+            held.remove(slot_id);
+        }
+        Ok(())
+    }
+
+    async fn count_stream_slots(&self, key: &str) -> Result<u32> {
+        let now = Utc::now();
+        Ok(self
+            .stream_slots
+            .lock()
+            .unwrap()
+            .get(key)
+            .map(|held| {
+                held.values()
+                    .filter(|expires_at| **expires_at > now)
+                    .count() as u32
+            })
+            .unwrap_or_default())
+    }
+
+    async fn acquire_lease(
+        &self,
+        name: &str,
+        holder: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<bool> {
+        let mut leases = self.leases.lock().unwrap();
+        match leases.get(name) {
+            Some((current, until)) if current != holder && *until > Utc::now() => Ok(false),
+            _ => {
+                leases.insert(name.to_string(), (holder.to_string(), expires_at));
+                Ok(true)
+            }
+        }
+    }
+
+    async fn release_lease(&self, name: &str, holder: &str) -> Result<()> {
+        let mut leases = self.leases.lock().unwrap();
+        if leases
+            .get(name)
+            .is_some_and(|(current, _)| current == holder)
+        {
+            leases.remove(name);
+        }
+        Ok(())
+    }
+
+    async fn save_conversation(&self, conversation: &Conversation) -> Result<()> {
+        self.conversations
+            .lock()
+            .unwrap()
+            .insert(conversation.id.clone(), conversation.clone());
+        Ok(())
+    }
+
+    async fn get_conversation(&self, id: &str) -> Result<Option<Conversation>> {
+        Ok(self.conversations.lock().unwrap().get(id).cloned())
+    }
+
+    async fn list_conversations(&self, user_id: &str) -> Result<Vec<Conversation>> {
+        let mut conversations: Vec<Conversation> = self
+            .conversations
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|c| c.user_id == user_id)
+            .cloned()
+            .collect();
+        conversations.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        Ok(conversations)
+    }
+
+    async fn delete_conversation(&self, id: &str) -> Result<()> {
+        self.conversations.lock().unwrap().remove(id);
+        Ok(())
+    }
+
+    async fn get_user_preferences(&self, user_id: &str) -> Result<Option<UserPreferences>> {
+        Ok(self.preferences.lock().unwrap().get(user_id).cloned())
+    }
+
+    async fn save_user_preferences(&self, preferences: &UserPreferences) -> Result<()> {
+        self.preferences
+            .lock()
+            .unwrap()
+            .insert(preferences.user_id.clone(), preferences.clone());
+        Ok(())
+    }
+
+    async fn get_stored_response(&self, id: &str) -> Result<Option<StoredResponse>> {
+        Ok(self.responses.lock().unwrap().get(id).cloned())
+    }
+
+    async fn save_stored_response(&self, response: &StoredResponse) -> Result<()> {
+        self.responses
+            .lock()
+            .unwrap()
+            .insert(response.id.clone(), response.clone());
+        Ok(())
+    }
+
+    async fn get_assistant_object(&self, id: &str) -> Result<Option<AssistantObject>> {
+        Ok(self.assistant_objects.lock().unwrap().get(id).cloned())
+    }
+
+    async fn save_assistant_object(&self, object: &AssistantObject) -> Result<()> {
+        self.assistant_objects
+            .lock()
+            .unwrap()
+            .insert(object.id.clone(), object.clone());
+        Ok(())
+    }
+
+    async fn list_assistant_objects(
+        &self,
+        owner_id: &str,
+        object: &str,
+        thread_id: Option<&str>,
+    ) -> Result<Vec<AssistantObject>> {
+        let mut objects: Vec<AssistantObject> = self
+            .assistant_objects
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|o| {
+                o.owner_id == owner_id
+                    && o.object == object
+                    && thread_id.is_none_or(|t| o.thread_id.as_deref() == Some(t))
+            })
+            .cloned()
+            .collect();
+        objects.sort_by_key(|o| o.created_at);
+        Ok(objects)
+    }
+
+    async fn delete_assistant_object(&self, id: &str) -> Result<()> {
+        self.assistant_objects.lock().unwrap().remove(id);
+        Ok(())
+    }
+
+    async fn get_prompt(&self, name: &str, version: Option<u32>) -> Result<Option<PromptTemplate>> {
+        Ok(self
+            .prompts
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|p| p.name == name && version.is_none_or(|v| p.version == v))
+            .max_by_key(|p| p.version)
+            .cloned())
+    }
+
+    async fn save_prompt(&self, prompt: &PromptTemplate) -> Result<()> {
+        let mut prompts = self.prompts.lock().unwrap();
+        if prompts
+            .iter()
+            .any(|p| p.name == prompt.name && p.version == prompt.version)
+        {
+            return Err(crate::Error::StateError(format!(
+                "Prompt {} version {} already exists",
+                prompt.name, prompt.version
+            )));
+        }
+        prompts.push(prompt.clone());
+        Ok(())
+    }
+
+    async fn list_prompts(&self) -> Result<Vec<PromptTemplate>> {
+        let prompts = self.prompts.lock().unwrap();
+        let mut latest: HashMap<&str, &PromptTemplate> = HashMap::new();
+        for prompt in prompts.iter() {
+            let entry = latest.entry(&prompt.name).or_insert(prompt);
+            if prompt.version > entry.version {
+                *entry = prompt;
+            }
+        }
+        let mut latest: Vec<PromptTemplate> = latest.into_values().cloned().collect();
+        latest.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(latest)
+    }
+
+    async fn list_prompt_versions(&self, name: &str) -> Result<Vec<PromptTemplate>> {
+        let mut versions: Vec<PromptTemplate> = self
+            .prompts
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|p| p.name == name)
+            .cloned()
+            .collect();
+        versions.sort_by_key(|p| p.version);
+        Ok(versions)
+    }
+
+    async fn delete_prompt(&self, name: &str) -> Result<()> {
+        self.prompts.lock().unwrap().retain(|p| p.name != name);
+        Ok(())
+    }
+
+    async fn record_prompt_render(&self, render: &PromptRender) -> Result<()> {
+        self.prompt_renders.lock().unwrap().push(render.clone());
+        Ok(())
+    }
+
+    async fn list_prompt_renders(&self, name: &str, limit: usize) -> Result<Vec<PromptRender>> {
+        let mut renders: Vec<PromptRender> = self
+            .prompt_renders
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|r| r.prompt_name == name)
+            .cloned()
+            .collect();
+        renders.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        renders.truncate(limit);
+        Ok(renders)
+    }
+
+    async fn get_experiment(&self, id: &str) -> Result<Option<Experiment>> {
+        Ok(self.experiments.lock().unwrap().get(id).cloned())
+    }
+
+    async fn save_experiment(&self, experiment: &Experiment) -> Result<()> {
+        self.experiments
+            .lock()
+            .unwrap()
+            .insert(experiment.id.clone(), experiment.clone());
+        Ok(())
+    }
+
+    async fn list_experiments(&self) -> Result<Vec<Experiment>> {
+        let mut experiments: Vec<Experiment> =
+            self.experiments.lock().unwrap().values().cloned().collect();
+        experiments.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok(experiments)
+    }
+
+    async fn delete_experiment(&self, id: &str) -> Result<()> {
+        self.experiments.lock().unwrap().remove(id);
+        self.experiment_outcomes
+            .lock()
+            .unwrap()
+            .retain(|o| o.experiment_id != id);
+        Ok(())
+    }
+
+    async fn record_experiment_outcome(&self, outcome: &ExperimentOutcome) -> Result<()> {
+        self.experiment_outcomes
+            .lock()
+            .unwrap()
+            .push(outcome.clone());
+        Ok(())
+    }
+
+    async fn list_experiment_outcomes(
+        &self,
+        experiment_id: &str,
+    ) -> Result<Vec<ExperimentOutcome>> {
+        Ok(self
+            .experiment_outcomes
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|o| o.experiment_id == experiment_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn record_experiment_feedback(&self, request_id: &str, feedback: i32) -> Result<bool> {
+        let mut found = false;
+        for outcome in self
+            .experiment_outcomes
+            .lock()
+            .unwrap()
+            .iter_mut()
+            .filter(|o| o.request_id == request_id)
+        {
+            outcome.feedback = Some(feedback);
+            found = true;
+        }
+        Ok(found)
+    }
+
+    async fn get_feedback(&self, request_id: &str, user_id: &str) -> Result<Option<Feedback>> {
+        Ok(self
+            .feedback
+            .lock()
+            .unwrap()
+            .values()
+            .find(|f| f.request_id == request_id && f.user_id == user_id)
+            .cloned())
+    }
+
+    async fn save_feedback(&self, feedback: &Feedback) -> Result<()> {
+        self.feedback
+            .lock()
+            .unwrap()
+            .insert(feedback.id.clone(), feedback.clone());
+        Ok(())
+    }
+
+    async fn list_feedback(
+        &self,
+        since: Option<DateTime<Utc>>,
+        limit: usize,
+    ) -> Result<Vec<Feedback>> {
+        let mut feedback: Vec<Feedback> = self
+            .feedback
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|f| since.is_none_or(|since| f.updated_at >= since))
+            .cloned()
+            .collect();
+        feedback.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        feedback.truncate(limit);
+        Ok(feedback)
+    }
+
+    async fn record_permission_decision(&self, decision: &PermissionDecision) -> Result<()> {
+        self.permission_decisions
+            .lock()
+            .unwrap()
+            .push(decision.clone());
+        Ok(())
+    }
+
+    async fn list_permission_decisions(
+        &self,
+        filter: &PermissionDecisionFilter,
+        limit: usize,
+    ) -> Result<Vec<PermissionDecision>> {
+        Ok(self
+            .permission_decisions
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .filter(|d| {
+                filter
+                    .subject_id
+                    .as_ref()
+                    .is_none_or(|s| &d.subject_id == s)
+                    && filter.action.as_ref().is_none_or(|a| &d.action == a)
+                    && filter.allowed.is_none_or(|allowed| d.allowed == allowed)
+                    && filter.since.is_none_or(|since| d.created_at >= since)
+            })
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    async fn record_routing_decision(&self, decision: &RoutingDecision) -> Result<()> {
+        self.routing_decisions
+            .lock()
+            .unwrap()
+            .push(decision.clone());
+        Ok(())
+    }
+
+    async fn list_routing_decisions(
+        &self,
+        user_id: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<RoutingDecision>> {
+        Ok(self
+            .routing_decisions
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .filter(|d| user_id.is_none_or(|u| d.user_id == u))
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    async fn purge_data(&self, class: DataClass, before: DateTime<Utc>) -> Result<u64> {
+        fn retain<T>(items: &mut Vec<T>, keep: impl Fn(&T) -> bool) -> u64 {
+            let len = items.len();
+            items.retain(keep);
+            (len - items.len()) as u64
+        }
+        fn retain_values<T>(items: &mut HashMap<String, T>, keep: impl Fn(&T) -> bool) -> u64 {
+            let len = items.len();
+            items.retain(|_, item| keep(item));
+            (len - items.len()) as u64
+        }
+
+        Ok(match class {
+            DataClass::UsageRecords => retain(&mut self.usage_records.lock().unwrap(), |u| {
+                u.timestamp >= before
+            }),
+            DataClass::AuditLog => {
+                retain(&mut self.prompt_renders.lock().unwrap(), |r| {
+                    r.created_at >= before
+                }) + retain(&mut self.experiment_outcomes.lock().unwrap(), |o| {
+                    o.created_at >= before
+                }) + retain(&mut self.permission_decisions.lock().unwrap(), |d| {
+                    d.created_at >= before
+                }) + retain(&mut self.routing_decisions.lock().unwrap(), |d| {
+                    d.created_at >= before
+                })
+            }
+            DataClass::CapturedBodies => retain_values(&mut self.responses.lock().unwrap(), |r| {
+                r.created_at >= before
+            }),
+            DataClass::Conversations => {
+                retain_values(&mut self.conversations.lock().unwrap(), |c| {
+                    c.updated_at >= before
+                })
+            }
+        })
+    }
+
+    async fn delete_user_data(&self, user_id: &str) -> Result<UserDataDeletion> {
+        fn remove<T>(items: &mut HashMap<String, T>, owned: impl Fn(&T) -> bool) -> u64 {
+            let len = items.len();
+            items.retain(|_, item| !owned(item));
+            (len - items.len()) as u64
+        }
+        fn remove_all<T>(items: &mut Vec<T>, owned: impl Fn(&T) -> bool) -> u64 {
+            let len = items.len();
+            items.retain(|item| !owned(item));
+            (len - items.len()) as u64
+        }
+
+        Ok(UserDataDeletion {
+            conversations: remove(&mut self.conversations.lock().unwrap(), |c| {
+                c.user_id == user_id
+            }),
+            feedback: remove(&mut self.feedback.lock().unwrap(), |f| f.user_id == user_id),
+            usage_records: remove_all(&mut self.usage_records.lock().unwrap(), |u| {
+                u.user_id == user_id
+            }),
+            audit_entries: remove_all(&mut self.prompt_renders.lock().unwrap(), |r| {
+                r.user_id == user_id
+            }) + remove_all(&mut self.experiment_outcomes.lock().unwrap(), |o| {
+                o.user_id == user_id
+            }) + remove_all(&mut self.permission_decisions.lock().unwrap(), |d| {
+                d.subject_id == user_id
+            }) + remove_all(&mut self.routing_decisions.lock().unwrap(), |d| {
+                d.user_id == user_id
+            }),
+            captured_bodies: remove(&mut self.responses.lock().unwrap(), |r| {
+                r.owner_id == user_id
+            }),
+            assistant_objects: remove(&mut self.assistant_objects.lock().unwrap(), |o| {
+                o.owner_id == user_id
+            }),
+            preferences: remove(&mut self.preferences.lock().unwrap(), |p| {
+                p.user_id == user_id
+            }),
+        })
+    }
+
     async fn record_usage(&self, usage: &UsageRecord) -> Result<()> {
         self.usage_records.lock().unwrap().push(usage.clone());
         Ok(())
@@ -421,6 +1684,30 @@ impl StateBackend for InMemoryBackend {
         Ok(records)
     }
 
+    async fn list_usage(&self, range: &TimeRange) -> Result<Vec<UsageRecord>> {
+        let mut records: Vec<UsageRecord> = self
+            .usage_records
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|u| u.timestamp >= range.start && u.timestamp <= range.end)
+            .cloned()
+            .collect();
+        records.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        Ok(records)
+    }
+
+    async fn get_usage_by_request(&self, request_id: &str) -> Result<Option<UsageRecord>> {
+        Ok(self
+            .usage_records
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|u| u.request_id == request_id)
+            .max_by_key(|u| u.timestamp)
+            .cloned())
+    }
+
     async fn get_provider(&self, id: &str) -> Result<Option<Provider>> {
         Ok(self.providers.lock().unwrap().get(id).cloned())
     }
@@ -506,6 +1793,57 @@ impl StateBackend for InMemoryBackend {
         // For tests, return empty list
         Ok(Vec::new())
     }
+
+    async fn add_group_member(&self, group: &str, subject_id: &str) -> Result<()> {
+        self.groups
+            .lock()
+            .unwrap()
+            .entry(group.to_string())
+            .or_default()
+            .insert(subject_id.to_string());
+        Ok(())
+    }
+
+    async fn remove_group_member(&self, group: &str, subject_id: &str) -> Result<bool> {
+        let mut groups = self.groups.lock().unwrap();
+        let Some(members) = groups.get_mut(group) else {
+            return Ok(false);
+        };
+        let removed = members.remove(subject_id);
+        if members.is_empty() {
+            groups.remove(group);
+        }
+        Ok(removed)
+    }
+
+    async fn list_groups(&self) -> Result<Vec<String>> {
+        let mut groups: Vec<_> = self.groups.lock().unwrap().keys().cloned().collect();
+        groups.sort();
+        Ok(groups)
+    }
+
+    async fn list_group_members(&self, group: &str) -> Result<Vec<String>> {
+        Ok(self
+            .groups
+            .lock()
+            .unwrap()
+            .get(group)
+            .map(|members| members.iter().cloned().collect())
+            .unwrap_or_default())
+    }
+
+    async fn list_subject_groups(&self, subject_id: &str) -> Result<Vec<String>> {
+        let mut groups: Vec<_> = self
+            .groups
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, members)| members.contains(subject_id))
+            .map(|(group, _)| group.clone())
+            .collect();
+        groups.sort();
+        Ok(groups)
+    }
 }
 
 #[cfg(test)]
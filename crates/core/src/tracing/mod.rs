@@ -29,7 +29,7 @@ pub mod prelude {
     };
 
     // Re-export common tracing macros and types
-    pub use tracing::{Instrument, debug, error, info, instrument, trace, warn};
+    pub use tracing::{Instrument, Span, debug, error, info, instrument, trace, warn};
 
     // Native-only exports
     #[cfg(not(target_arch = "wasm32"))]
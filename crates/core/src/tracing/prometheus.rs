@@ -1,24 +1,36 @@
 //! Prometheus metrics export
 //!
 //! This module provides functions to export metrics in Prometheus text format.
+//! Counter and gauge names may carry labels, as in
+//! `sink_errors_total{class="timeout"}`; series of one metric are exported
+//! together under a single `TYPE` line.
 
+use std::collections::HashMap;
 use std::fmt::Write;
 
 use crate::tracing::metrics::Metrics;
 
+/// Write the series of each metric under one `TYPE` line
+fn write_series<V: std::fmt::Display>(output: &mut String, kind: &str, values: HashMap<String, V>) {
+    let mut values: Vec<_> = values.into_iter().collect();
+    values.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut family = "";
+    for (name, value) in &values {
+        let base = name.split('{').next().unwrap_or(name);
+        if base != family {
+            let _ = writeln!(output, "# TYPE {base} {kind}");
+            family = base;
+        }
+        let _ = writeln!(output, "{name} {value}");
+    }
+}
+
 /// Export metrics in Prometheus text format
 pub fn export_prometheus(metrics: &Metrics) -> String {
     let mut output = String::new();
 
-    // Export counters
-    for (name, value) in metrics.all_counters() {
-        let _ = writeln!(&mut output, "# TYPE {name} counter\n{name} {value}");
-    }
-
-    // Export gauges
-    for (name, value) in metrics.all_gauges() {
-        let _ = writeln!(&mut output, "# TYPE {name} gauge\n{name} {value}");
-    }
+    write_series(&mut output, "counter", metrics.all_counters());
+    write_series(&mut output, "gauge", metrics.all_gauges());
 
     // Export histograms
     for (name, stats) in metrics.all_histograms() {
@@ -134,6 +146,25 @@ mod tests {
         assert!(output.contains("test_histogram_sum 6"));
     }
 
+    #[test]
+    fn test_labelled_series_share_a_type_line() {
+        let metrics = Metrics::new();
+        metrics
+            .counter("sink_errors_total{class=\"timeout\"}")
+            .add(2);
+        metrics
+            .counter("sink_errors_total{class=\"rate_limited\"}")
+            .add(1);
+
+        let output = export_prometheus(&metrics);
+        assert_eq!(
+            output.matches("# TYPE sink_errors_total counter").count(),
+            1
+        );
+        assert!(output.contains("sink_errors_total{class=\"timeout\"} 2"));
+        assert!(output.contains("sink_errors_total{class=\"rate_limited\"} 1"));
+    }
+
     #[test]
     fn test_format_push_gateway_url() {
         assert_eq!(
@@ -38,6 +38,400 @@ pub struct ApiKey {
     pub last_used_at: Option<DateTime<Utc>>,
 }
 
+/// A saved chat session owned by a user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Conversation {
+    pub id: String,
+    pub user_id: String,
+    pub title: String,
+    pub model: Option<String>,
+    /// Chat messages as a JSON array
+    pub messages: JsonValue,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A user's UI and client preferences
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserPreferences {
+    pub user_id: String,
+    /// Preference values as a JSON object; the schema belongs to the application
+    pub preferences: JsonValue,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A Responses API response kept so later requests can continue from it
+/// via `previous_response_id` when the upstream cannot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredResponse {
+    pub id: String,
+    /// Identity that created the response; only it may continue from it
+    pub owner_id: String,
+    /// Conversation so far as Responses input items, including this response's output
+    pub input: JsonValue,
+    /// The response object returned to the client
+    pub response: JsonValue,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An Assistants API object kept by the emulation layer: an assistant, a
+/// thread, a thread message or a run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssistantObject {
+    pub id: String,
+    /// Object type as the Assistants API names it, e.g. `thread.run`
+    pub object: String,
+    /// Identity that created the object; only it may read or change it
+    pub owner_id: String,
+    /// Thread a message or run belongs to
+    pub thread_id: Option<String>,
+    /// The object as returned to the client
+    pub data: JsonValue,
+    /// Bookkeeping not shown to the client, such as a run's tool call history
+    pub state: JsonValue,
+    pub created_at: DateTime<Utc>,
+}
+
+/// One version of a named prompt template. Saving a template again creates
+/// a new version; earlier ones stay available.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromptTemplate {
+    pub name: String,
+    pub version: u32,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Messages put ahead of the request's own, with `{{variable}}` placeholders
+    pub messages: Vec<PromptMessage>,
+    /// Variables the messages use
+    #[serde(default)]
+    pub variables: Vec<PromptVariable>,
+    /// Request parameters, such as `temperature`, applied when a request
+    /// leaves them unset
+    #[serde(default)]
+    pub parameters: serde_json::Map<String, JsonValue>,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromptMessage {
+    pub role: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromptVariable {
+    pub name: String,
+    /// Value used when a request leaves the variable out; without one the
+    /// variable is required
+    #[serde(default)]
+    pub default: Option<String>,
+}
+
+/// Record of a request rendered from a prompt template
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromptRender {
+    pub id: String,
+    pub prompt_name: String,
+    pub version: u32,
+    pub user_id: String,
+    /// Correlation id of the request
+    pub request_id: String,
+    pub model: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An A/B test between variants of a model, prompt or parameters. Requests
+/// for `model` are split between the variants while the experiment runs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Experiment {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Model name whose requests take part
+    pub model: String,
+    pub status: ExperimentStatus,
+    pub variants: Vec<ExperimentVariant>,
+    /// Metrics the results view compares variants on
+    #[serde(default)]
+    pub metrics: Vec<ExperimentMetric>,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExperimentStatus {
+    Draft,
+    Running,
+    Stopped,
+}
+
+/// One arm of an experiment. Unset fields leave the request as sent.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExperimentVariant {
+    pub name: String,
+    /// Relative share of traffic
+    pub weight: u32,
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Prompt template rendered into requests, see [`PromptTemplate`]
+    #[serde(default)]
+    pub prompt_id: Option<String>,
+    #[serde(default)]
+    pub prompt_version: Option<u32>,
+    /// Request parameters, such as `temperature`, set on every request
+    #[serde(default)]
+    pub parameters: serde_json::Map<String, JsonValue>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExperimentMetric {
+    Latency,
+    Cost,
+    Feedback,
+}
+
+/// How one request in an experiment went
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExperimentOutcome {
+    pub id: String,
+    pub experiment_id: String,
+    pub variant: String,
+    pub user_id: String,
+    /// Correlation id of the request
+    pub request_id: String,
+    pub latency_ms: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost: f64,
+    pub error: bool,
+    /// User rating: positive for thumbs up, negative for thumbs down
+    #[serde(default)]
+    pub feedback: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Aggregated outcomes of one experiment variant
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExperimentVariantResults {
+    pub variant: String,
+    pub requests: u64,
+    pub errors: u64,
+    pub mean_latency_ms: f64,
+    pub p95_latency_ms: u64,
+    pub total_cost: f64,
+    pub mean_cost: f64,
+    pub positive_feedback: u64,
+    pub negative_feedback: u64,
+    /// Share of rated requests rated positively
+    pub positive_rate: Option<f64>,
+}
+
+/// A user's rating of a response. Each user has at most one per request;
+/// rating again replaces it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Feedback {
+    pub id: String,
+    /// Correlation id of the rated request
+    pub request_id: String,
+    pub user_id: String,
+    /// Thumbs up (true) or down (false)
+    #[serde(default)]
+    pub thumbs_up: Option<bool>,
+    /// Rating from 1 to 5
+    #[serde(default)]
+    pub rating: Option<u8>,
+    #[serde(default)]
+    pub comment: Option<String>,
+    /// Model and provider that served the request, from its usage record
+    #[serde(default)]
+    pub model_id: Option<String>,
+    #[serde(default)]
+    pub provider_id: Option<String>,
+    /// Kind of request rated, such as `chat` or `tools`
+    #[serde(default)]
+    pub task_type: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Feedback {
+    /// Whether the feedback is positive (1), negative (-1) or neutral (0).
+    /// Thumbs win over ratings; ratings of 4 and up are positive and 2 and
+    /// below negative.
+    pub fn score(&self) -> i32 {
+        match (self.thumbs_up, self.rating) {
+            (Some(true), _) => 1,
+            (Some(false), _) => -1,
+            (None, Some(rating)) if rating >= 4 => 1,
+            (None, Some(rating)) if rating <= 2 => -1,
+            _ => 0,
+        }
+    }
+}
+
+/// Feedback aggregated for one model and provider
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FeedbackSummary {
+    pub model_id: String,
+    pub provider_id: String,
+    pub count: u64,
+    pub positive: u64,
+    pub negative: u64,
+    /// Mean of the 1 to 5 ratings given
+    pub mean_rating: Option<f64>,
+    /// Share of positive and negative feedback that is positive
+    pub positive_rate: Option<f64>,
+}
+
+impl FeedbackSummary {
+    /// Aggregate feedback per model and provider, most rated first
+    pub fn summarize(feedback: &[Feedback]) -> Vec<FeedbackSummary> {
+        let mut groups: HashMap<(String, String), (FeedbackSummary, u64, u64)> = HashMap::new();
+        for item in feedback {
+            let model_id = item.model_id.clone().unwrap_or_default();
+            let provider_id = item.provider_id.clone().unwrap_or_default();
+            let (summary, rating_sum, ratings) = groups
+                .entry((model_id.clone(), provider_id.clone()))
+                .or_insert_with(|| {
+                    (
+                        FeedbackSummary {
+                            model_id,
+                            provider_id,
+                            ..Default::default()
+                        },
+                        0,
+                        0,
+                    )
+                });
+            summary.count += 1;
+            match item.score() {
+                1 => summary.positive += 1,
+                -1 => summary.negative += 1,
+                _ => {}
+            }
+            if let Some(rating) = item.rating {
+                *rating_sum += u64::from(rating);
+                *ratings += 1;
+            }
+        }
+
+        let mut summaries: Vec<FeedbackSummary> = groups
+            .into_values()
+            .map(|(mut summary, rating_sum, ratings)| {
+                summary.mean_rating = (ratings > 0).then(|| rating_sum as f64 / ratings as f64);
+                let rated = summary.positive + summary.negative;
+                summary.positive_rate = (rated > 0).then(|| summary.positive as f64 / rated as f64);
+                summary
+            })
+            .collect();
+        summaries.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then_with(|| a.model_id.cmp(&b.model_id))
+                .then_with(|| a.provider_id.cmp(&b.provider_id))
+        });
+        summaries
+    }
+}
+
+/// Outcome of one permission check, kept in the audit log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PermissionDecision {
+    pub id: String,
+    pub subject_id: String,
+    /// Action checked, such as `Write`
+    pub action: String,
+    /// Object checked, as `namespace/kind/id`
+    pub object: String,
+    pub allowed: bool,
+    /// What decided the check: `owner`, `grant`, `no_grant` or `error`
+    pub policy: String,
+    /// Why the check was denied, if it was
+    #[serde(default)]
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Which permission decisions to list; unset fields match any decision
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PermissionDecisionFilter {
+    #[serde(default)]
+    pub subject_id: Option<String>,
+    #[serde(default)]
+    pub action: Option<String>,
+    #[serde(default)]
+    pub allowed: Option<bool>,
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+}
+
+/// How a request for the automatic model was routed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoutingDecision {
+    pub id: String,
+    pub user_id: String,
+    /// Correlation id of the request
+    pub request_id: String,
+    /// Model the request asked for
+    pub requested_model: String,
+    /// Model the request was sent to
+    pub model: String,
+    /// Class the prompt was put in, such as `simple` or `coding`
+    pub tier: String,
+    /// Why the prompt was put in that class
+    pub rationale: String,
+    /// Whether a per-identity override chose the model
+    pub overridden: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Stored data with its own retention window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataClass {
+    UsageRecords,
+    /// Audit trail: prompt renders, experiment outcomes, permission
+    /// decisions and routing decisions
+    AuditLog,
+    /// Request and response bodies kept for the Responses API
+    CapturedBodies,
+    Conversations,
+}
+
+impl DataClass {
+    pub const ALL: [DataClass; 4] = [
+        DataClass::UsageRecords,
+        DataClass::AuditLog,
+        DataClass::CapturedBodies,
+        DataClass::Conversations,
+    ];
+}
+
+/// Deduplicated body blobs removed by a collection
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlobCollection {
+    pub blobs: u64,
+    /// Bytes freed on disk
+    pub bytes: u64,
+}
+
+/// What was removed when deleting a user's data
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserDataDeletion {
+    pub conversations: u64,
+    pub feedback: u64,
+    pub usage_records: u64,
+    pub audit_entries: u64,
+    pub captured_bodies: u64,
+    pub assistant_objects: u64,
+    pub preferences: u64,
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum ProviderType {
@@ -112,6 +506,41 @@ pub struct UsageRecord {
     pub metadata: HashMap<String, String>,
 }
 
+/// Whose usage a total covers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UsageSubject {
+    /// Requests made with an API key, by key hash
+    ApiKey(String),
+    /// Requests made by a user, with any credential
+    User(String),
+}
+
+impl UsageSubject {
+    pub fn matches(&self, record: &UsageRecord) -> bool {
+        match self {
+            UsageSubject::ApiKey(key_hash) => &record.api_key_hash == key_hash,
+            UsageSubject::User(user_id) => &record.user_id == user_id,
+        }
+    }
+}
+
+/// Usage summed over a period
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct UsageTotals {
+    pub requests: u64,
+    pub tokens: u64,
+    pub cost: f64,
+}
+
+impl UsageTotals {
+    pub fn add(mut self, record: &UsageRecord) -> Self {
+        self.requests += 1;
+        self.tokens += record.total_tokens;
+        self.cost += record.cost;
+        self
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TimeRange {
     pub start: DateTime<Utc>,
@@ -157,3 +586,53 @@ pub struct ResponseHookContext {
 }
 
 // Legacy chat/messages response types removed in favor of protocol-agnostic streaming APIs in router
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feedback(model: &str, thumbs_up: Option<bool>, rating: Option<u8>) -> Feedback {
+        Feedback {
+            id: uuid::Uuid::new_v4().to_string(),
+            request_id: uuid::Uuid::new_v4().to_string(),
+            user_id: "user".to_string(),
+            thumbs_up,
+            rating,
+            comment: None,
+            model_id: Some(model.to_string()),
+            provider_id: Some("provider".to_string()),
+            task_type: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_feedback_score() {
+        assert_eq!(feedback("m", Some(true), Some(1)).score(), 1);
+        assert_eq!(feedback("m", Some(false), None).score(), -1);
+        assert_eq!(feedback("m", None, Some(4)).score(), 1);
+        assert_eq!(feedback("m", None, Some(3)).score(), 0);
+        assert_eq!(feedback("m", None, Some(2)).score(), -1);
+        assert_eq!(feedback("m", None, None).score(), 0);
+    }
+
+    #[test]
+    fn test_feedback_summary_groups_by_model_and_provider() {
+        let summaries = FeedbackSummary::summarize(&[
+            feedback("a", Some(true), None),
+            feedback("a", None, Some(2)),
+            feedback("a", None, Some(5)),
+            feedback("b", None, Some(3)),
+        ]);
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries[0].model_id, "a");
+        assert_eq!(summaries[0].count, 3);
+        assert_eq!(summaries[0].positive, 2);
+        assert_eq!(summaries[0].negative, 1);
+        assert_eq!(summaries[0].mean_rating, Some(3.5));
+        assert_eq!(summaries[0].positive_rate, Some(2.0 / 3.0));
+        assert_eq!(summaries[1].positive_rate, None);
+        assert_eq!(summaries[1].mean_rating, Some(3.0));
+    }
+}
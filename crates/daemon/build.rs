@@ -0,0 +1,51 @@
+//! Build metadata for the daemon and its web frontend, which share this
+//! script through `package.build`
+
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Files naming the commit checked out: HEAD itself, the branch it points
+/// at, and branches git has packed
+const GIT_REFS: [&str; 3] = [
+    "../../.git/HEAD",
+    "../../.git/refs/heads",
+    "../../.git/packed-refs",
+];
+
+fn main() {
+    // Commit the binary was built from; packagers without a checkout can
+    // set GATE_GIT_HASH themselves
+    println!("cargo:rerun-if-env-changed=GATE_GIT_HASH");
+    // Cargo reruns on every build for a path that does not exist
+    for path in GIT_REFS.into_iter().filter(|path| Path::new(path).exists()) {
+        println!("cargo:rerun-if-changed={path}");
+    }
+    let git_hash = std::env::var("GATE_GIT_HASH").ok().or_else(|| {
+        let output = Command::new("git")
+            .args(["rev-parse", "--short=12", "HEAD"])
+            .output()
+            .ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    });
+    println!(
+        "cargo:rustc-env=GATE_GIT_HASH={}",
+        git_hash.unwrap_or_else(|| "unknown".to_string())
+    );
+
+    // Reproducible builds pin the time with SOURCE_DATE_EPOCH
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    let timestamp = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.parse::<u64>().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default()
+        });
+    println!("cargo:rustc-env=GATE_BUILD_TIMESTAMP={timestamp}");
+}
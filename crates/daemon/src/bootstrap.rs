@@ -1,20 +1,102 @@
 use anyhow::Result;
+use chrono::{DateTime, Utc};
 use gate_sqlx::SqliteWebAuthnBackend;
 use rand::{Rng, distributions::Alphanumeric};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::Path;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 
+/// How long a bootstrap token stays valid unless configured otherwise
+pub const DEFAULT_TOKEN_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Bootstrap attempts allowed per minute unless configured otherwise
+pub const DEFAULT_ATTEMPTS_PER_MINUTE: u32 = 10;
+
+const ATTEMPT_WINDOW: Duration = Duration::from_secs(60);
+
+/// Length of generated bootstrap tokens, and the least a provisioned one
+/// may have
+pub const TOKEN_LENGTH: usize = 32;
+
 /// Manages the bootstrap token for initial user enrollment
 #[derive(Clone)]
 pub struct BootstrapTokenManager {
     inner: Arc<RwLock<BootstrapTokenState>>,
     webauthn_backend: Arc<SqliteWebAuthnBackend>,
+    ttl: Duration,
+    attempts_per_minute: u32,
+    /// Issued instead of a generated token
+    provisioned: Option<String>,
 }
 
 #[derive(Debug)]
 struct BootstrapTokenState {
-    token: Option<String>,
+    token: Option<IssuedToken>,
     is_used: bool,
+    attempts: VecDeque<Instant>,
+}
+
+#[derive(Debug)]
+struct IssuedToken {
+    value: String,
+    expires_at: DateTime<Utc>,
+    /// Whether the token has been shown through [`BootstrapTokenManager::take_display_token`]
+    displayed: bool,
+}
+
+impl IssuedToken {
+    fn is_expired(&self) -> bool {
+        Utc::now() >= self.expires_at
+    }
+}
+
+/// What a phone needs to enroll: scanned from a QR code the GUI renders
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BootstrapPairing {
+    /// Base URL the daemon is reachable at
+    pub url: String,
+    pub token: String,
+    /// SHA-256 of the daemon's TLS certificate, hex encoded, when serving TLS
+    pub cert_fingerprint: Option<String>,
+    pub expires_at: DateTime<Utc>,
+    /// Text to encode in the QR code: the bootstrap page URL, with the
+    /// certificate fingerprint in the fragment so it never reaches a server
+    pub payload: String,
+}
+
+impl BootstrapPairing {
+    pub fn new(
+        url: &str,
+        token: String,
+        cert_fingerprint: Option<String>,
+        expires_at: DateTime<Utc>,
+    ) -> Self {
+        let url = url.trim_end_matches('/').to_string();
+        let mut payload = format!("{url}/bootstrap/{token}");
+        if let Some(fingerprint) = &cert_fingerprint {
+            payload.push_str(&format!("#fp=sha256:{fingerprint}"));
+        }
+        Self {
+            url,
+            token,
+            cert_fingerprint,
+            expires_at,
+            payload,
+        }
+    }
+}
+
+/// SHA-256 fingerprint of the first certificate in a PEM file, hex encoded
+pub async fn cert_fingerprint(cert_path: &Path) -> Option<String> {
+    use rustls::pki_types::{CertificateDer, pem::PemObject};
+    use sha2::{Digest, Sha256};
+
+    let pem = tokio::fs::read(cert_path).await.ok()?;
+    let cert = CertificateDer::pem_slice_iter(&pem).next()?.ok()?;
+    Some(hex::encode(Sha256::digest(cert.as_ref())))
 }
 
 impl BootstrapTokenManager {
@@ -24,12 +106,55 @@ impl BootstrapTokenManager {
             inner: Arc::new(RwLock::new(BootstrapTokenState {
                 token: None,
                 is_used: false,
+                attempts: VecDeque::new(),
             })),
             webauthn_backend,
+            ttl: DEFAULT_TOKEN_TTL,
+            attempts_per_minute: DEFAULT_ATTEMPTS_PER_MINUTE,
+            provisioned: None,
         }
     }
 
-    /// Generates a new bootstrap token if none exists and hasn't been used
+    /// Issue `token` instead of generating one. The operator already knows
+    /// it, so it is never displayed.
+    pub fn with_provisioned_token(mut self, token: Option<String>) -> Self {
+        self.provisioned = token;
+        self
+    }
+
+    /// Set how long issued tokens stay valid
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Set how many bootstrap attempts are allowed per minute
+    pub fn with_attempt_limit(mut self, attempts_per_minute: u32) -> Self {
+        self.attempts_per_minute = attempts_per_minute;
+        self
+    }
+
+    fn issue(&self, state: &mut BootstrapTokenState) -> String {
+        let value = self.provisioned.clone().unwrap_or_else(|| {
+            rand::thread_rng()
+                .sample_iter(&Alphanumeric)
+                .take(TOKEN_LENGTH)
+                .map(char::from)
+                .collect()
+        });
+        let ttl = chrono::Duration::from_std(self.ttl).unwrap_or(chrono::Duration::MAX);
+        state.token = Some(IssuedToken {
+            value: value.clone(),
+            expires_at: Utc::now()
+                .checked_add_signed(ttl)
+                .unwrap_or(DateTime::<Utc>::MAX_UTC),
+            displayed: self.provisioned.is_some(),
+        });
+        value
+    }
+
+    /// Generates a new bootstrap token unless a live one exists and the
+    /// bootstrap hasn't been completed
     pub async fn generate_token(&self) -> Result<String> {
         let mut state = self.inner.write().await;
 
@@ -37,19 +162,22 @@ impl BootstrapTokenManager {
             anyhow::bail!("Bootstrap token has already been used");
         }
 
-        if let Some(ref token) = state.token {
-            return Ok(token.clone());
+        if let Some(token) = state.token.as_ref().filter(|t| !t.is_expired()) {
+            return Ok(token.value.clone());
         }
 
-        // Generate a 32-character random alphanumeric token
-        let token: String = rand::thread_rng()
-            .sample_iter(&Alphanumeric)
-            .take(32)
-            .map(char::from)
-            .collect();
+        Ok(self.issue(&mut state))
+    }
+
+    /// Replaces the current token, live or expired, with a new one
+    pub async fn regenerate_token(&self) -> Result<String> {
+        let mut state = self.inner.write().await;
+
+        if state.is_used {
+            anyhow::bail!("Bootstrap token has already been used");
+        }
 
-        state.token = Some(token.clone());
-        Ok(token)
+        Ok(self.issue(&mut state))
     }
 
     /// Validates a bootstrap token
@@ -61,11 +189,18 @@ impl BootstrapTokenManager {
         }
 
         match &state.token {
-            Some(stored_token) => Ok(stored_token == token),
+            Some(stored) => Ok(stored.value == token && !stored.is_expired()),
             None => Ok(false),
         }
     }
 
+    /// Whether `token` is the most recently issued token, even if expired.
+    /// Proves the caller saw it, so it can be exchanged for a fresh one.
+    pub async fn is_current_token(&self, token: &str) -> bool {
+        let state = self.inner.read().await;
+        !state.is_used && state.token.as_ref().is_some_and(|t| t.value == token)
+    }
+
     /// Marks the bootstrap token as used
     pub async fn mark_as_used(&self) -> Result<()> {
         let mut state = self.inner.write().await;
@@ -85,14 +220,76 @@ impl BootstrapTokenManager {
         state.is_used
     }
 
-    /// Gets the current token if available and not used
+    /// Gets the current token if available, unexpired and not used
     pub async fn get_token(&self) -> Option<String> {
         let state = self.inner.read().await;
-        if !state.is_used {
-            state.token.clone()
-        } else {
-            None
+        if state.is_used {
+            return None;
+        }
+        state
+            .token
+            .as_ref()
+            .filter(|t| !t.is_expired())
+            .map(|t| t.value.clone())
+    }
+
+    /// Gets the current token only the first time it is asked for, so it is
+    /// displayed once; regenerating allows it to be displayed again
+    pub async fn take_display_token(&self) -> Option<String> {
+        let mut state = self.inner.write().await;
+        if state.is_used {
+            return None;
+        }
+        let token = state
+            .token
+            .as_mut()
+            .filter(|t| !t.is_expired() && !t.displayed)?;
+        token.displayed = true;
+        Some(token.value.clone())
+    }
+
+    /// When the current token expires
+    pub async fn expires_at(&self) -> Option<DateTime<Utc>> {
+        let state = self.inner.read().await;
+        state.token.as_ref().map(|t| t.expires_at)
+    }
+
+    /// Pairing details for the current token, if one is live
+    pub async fn pairing(
+        &self,
+        url: &str,
+        cert_fingerprint: Option<String>,
+    ) -> Option<BootstrapPairing> {
+        let state = self.inner.read().await;
+        if state.is_used {
+            return None;
+        }
+        let token = state.token.as_ref().filter(|t| !t.is_expired())?;
+        Some(BootstrapPairing::new(
+            url,
+            token.value.clone(),
+            cert_fingerprint,
+            token.expires_at,
+        ))
+    }
+
+    /// Record a bootstrap attempt, returning false once the per-minute
+    /// limit is exceeded
+    pub async fn check_attempt(&self) -> bool {
+        let mut state = self.inner.write().await;
+        let now = Instant::now();
+        while state
+            .attempts
+            .front()
+            .is_some_and(|t| now.duration_since(*t) >= ATTEMPT_WINDOW)
+        {
+            state.attempts.pop_front();
+        }
+        if state.attempts.len() >= self.attempts_per_minute as usize {
+            return false;
         }
+        state.attempts.push_back(now);
+        true
     }
 
     /// Checks if bootstrap is needed (no credentials exist)
@@ -108,13 +305,17 @@ mod tests {
     use gate_sqlx::{SqliteStateBackend, SqliteWebAuthnBackend};
 
     async fn create_test_manager() -> Arc<BootstrapTokenManager> {
+        Arc::new(create_manager().await)
+    }
+
+    async fn create_manager() -> BootstrapTokenManager {
         let state_backend = Arc::new(
             SqliteStateBackend::new(":memory:")
                 .await
                 .expect("Failed to create backend"),
         );
         let webauthn_backend = Arc::new(SqliteWebAuthnBackend::new(state_backend.pool().clone()));
-        Arc::new(BootstrapTokenManager::new(webauthn_backend))
+        BootstrapTokenManager::new(webauthn_backend)
     }
 
     #[tokio::test]
@@ -242,4 +443,65 @@ mod tests {
         let complete = manager.is_bootstrap_complete().await;
         assert!(complete);
     }
+
+    #[tokio::test]
+    async fn test_expired_token_is_rejected_and_can_be_regenerated() {
+        let manager = create_manager().await.with_ttl(Duration::ZERO);
+        let token = manager.generate_token().await.unwrap();
+
+        assert!(!manager.validate_token(&token).await.unwrap());
+        assert!(manager.get_token().await.is_none());
+        assert!(manager.is_current_token(&token).await);
+
+        let manager = manager.with_ttl(DEFAULT_TOKEN_TTL);
+        let fresh = manager.regenerate_token().await.unwrap();
+        assert_ne!(fresh, token);
+        assert!(!manager.is_current_token(&token).await);
+        assert!(manager.validate_token(&fresh).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_token_is_displayed_once() {
+        let manager = create_test_manager().await;
+        let token = manager.generate_token().await.unwrap();
+
+        assert_eq!(manager.take_display_token().await, Some(token));
+        assert_eq!(manager.take_display_token().await, None);
+
+        let fresh = manager.regenerate_token().await.unwrap();
+        assert_eq!(manager.take_display_token().await, Some(fresh));
+    }
+
+    #[tokio::test]
+    async fn test_provisioned_token_is_issued_but_not_displayed() {
+        let provisioned = "p".repeat(TOKEN_LENGTH);
+        let manager = create_manager()
+            .await
+            .with_provisioned_token(Some(provisioned.clone()));
+        assert_eq!(manager.generate_token().await.unwrap(), provisioned);
+        assert!(manager.validate_token(&provisioned).await.unwrap());
+        assert_eq!(manager.take_display_token().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_attempts_are_rate_limited() {
+        let manager = create_manager().await.with_attempt_limit(2);
+        assert!(manager.check_attempt().await);
+        assert!(manager.check_attempt().await);
+        assert!(!manager.check_attempt().await);
+    }
+
+    #[test]
+    fn test_pairing_payload_carries_fingerprint_in_fragment() {
+        let pairing = BootstrapPairing::new(
+            "https://node.example.com/",
+            "abc".to_string(),
+            Some("00ff".to_string()),
+            Utc::now(),
+        );
+        assert_eq!(
+            pairing.payload,
+            "https://node.example.com/bootstrap/abc#fp=sha256:00ff"
+        );
+    }
 }
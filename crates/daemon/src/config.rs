@@ -1,8 +1,20 @@
 //! Server configuration
 
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
 
 use config::{Config, ConfigError, Environment, File};
+use gate_core::DataClass;
+use gate_core::router::buffers::BufferLimits;
+use gate_core::router::concurrency::ConcurrencyLimits;
+use gate_core::router::middleware::{
+    AutoRoutes, ModelDeprecation, ParameterProfile, deprecation_rules,
+};
+use gate_http::routes::{RoutePrefixes, base_path::base_path_error, prefixes::prefix_error};
+use gate_http::tools::{ToolConfig, ToolKind, ToolsConfig};
+use gate_tlsforward::client::BandwidthLimits;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
@@ -14,6 +26,15 @@ fn default_port() -> u16 {
     31145
 }
 
+/// Returned by inference routes in maintenance mode unless an admin sets
+/// another message
+pub const DEFAULT_MAINTENANCE_MESSAGE: &str =
+    "Gate is down for maintenance. Please try again shortly.";
+
+fn default_maintenance_message() -> String {
+    DEFAULT_MAINTENANCE_MESSAGE.to_string()
+}
+
 fn default_true() -> bool {
     true
 }
@@ -90,7 +111,7 @@ fn default_local_inference() -> Option<LocalInferenceConfig> {
         max_concurrent_inferences: 1,
         default_temperature: 0.7,
         default_max_tokens: 1024,
-        models: vec![],
+        ..Default::default()
     })
 }
 
@@ -102,7 +123,7 @@ fn default_tlsforward_addresses() -> Vec<String> {
 }
 
 /// Server configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Settings {
     /// Server settings
     #[serde(default)]
@@ -119,9 +140,54 @@ pub struct Settings {
     /// Let's Encrypt configuration
     #[serde(default)]
     pub letsencrypt: LetsEncryptConfig,
+    /// Where the TLS certificate comes from when not from Let's Encrypt
+    #[serde(default)]
+    pub certificates: CertificatesConfig,
     /// Local inference configuration
     #[serde(default = "default_local_inference")]
     pub local_inference: Option<LocalInferenceConfig>,
+    /// Tools the gateway runs for agentic chat completions
+    #[serde(default)]
+    pub tools: ToolsConfig,
+    /// Document indexing for retrieval augmented virtual models
+    #[serde(default)]
+    pub documents: DocumentsConfig,
+    /// Routing between sinks that serve the same model
+    #[serde(default)]
+    pub routing: RoutingConfig,
+    /// Spend forecasting and anomaly alerts
+    #[serde(default)]
+    pub spend: SpendConfig,
+    /// How long stored data is kept
+    #[serde(default)]
+    pub retention: RetentionConfig,
+    /// Deduplicated storage of large body strings
+    #[serde(default)]
+    pub blob_store: BlobStoreConfig,
+    /// Recording of permission checks in the audit log
+    #[serde(default)]
+    pub permission_audit: PermissionAuditConfig,
+    /// Signing of outgoing requests and verification of incoming ones
+    #[serde(default)]
+    pub signing: SigningConfig,
+    /// Per-task overrides for the scheduler, keyed by task name
+    #[serde(default)]
+    pub tasks: std::collections::HashMap<String, TaskConfig>,
+    /// Local control socket
+    #[serde(default)]
+    pub control: ControlConfig,
+    /// Running as one of several replicas behind a load balancer
+    #[serde(default)]
+    pub cluster: ClusterConfig,
+    /// Caps on buffered responses and the memory watermark
+    #[serde(default)]
+    pub memory: MemoryConfig,
+    /// CPU and heap profiles captured on demand by admins
+    #[serde(default)]
+    pub profiling: ProfilingConfig,
+    /// Files or directories merged in before this file, relative to it
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
 }
 
 impl Default for Settings {
@@ -131,7 +197,7 @@ impl Default for Settings {
 }
 
 /// Server configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ServerConfig {
     /// Host to bind to
     #[serde(default = "default_host")]
@@ -139,15 +205,59 @@ pub struct ServerConfig {
     /// Port to bind to
     #[serde(default = "default_port")]
     pub port: u16,
-    /// CORS allowed origins
+    /// Origins browsers may call the daemon from: exact origins,
+    /// subdomain wildcards such as `https://*.example.com`, or `*` for any.
+    /// Empty allows same-origin requests only.
     #[serde(default)]
     pub cors_origins: Vec<String>,
+    /// Cross-origin policy details
+    #[serde(default)]
+    pub cors: CorsConfig,
+    /// Security headers sent with every response
+    #[serde(default)]
+    pub security_headers: SecurityHeadersConfig,
     /// Prometheus metrics endpoint port (if enabled)
     #[serde(default)]
     pub metrics_port: Option<u16>,
     /// Allow localhost clients to bypass auth (effective only when host is loopback)
     #[serde(default = "default_true")]
     pub allow_local_bypass: bool,
+    /// Emulate the Anthropic API on `/v1/messages` and `/v1/models` for
+    /// clients such as Claude Code: Anthropic error envelopes, model pages
+    /// and stream events, and gateway keys accepted in `x-api-key`
+    #[serde(default)]
+    pub anthropic_compat: bool,
+    /// Serve the OpenAI Assistants API (`/v1/assistants`, `/v1/threads`),
+    /// emulated on top of the state backend and router
+    #[serde(default)]
+    pub assistants_api: bool,
+    /// Base paths the Anthropic and OpenAI APIs are also served under, so
+    /// clients of either vendor can share one gateway
+    #[serde(default)]
+    pub route_prefixes: RoutePrefixes,
+    /// Message inference routes return while maintenance mode is on
+    #[serde(default = "default_maintenance_message")]
+    pub maintenance_message: String,
+    /// Public path the gateway is served under behind a reverse proxy, such
+    /// as `/gate`. Routes, the web UI and generated links move under it.
+    /// Empty serves at the root.
+    #[serde(default)]
+    pub base_path: String,
+    /// Seconds the model list, status and provider health responses are
+    /// reused for before being built again; they are rebuilt at once when
+    /// providers change. Zero builds every response afresh. ETags are sent
+    /// either way.
+    #[serde(default = "default_metadata_cache_secs")]
+    pub metadata_cache_secs: u64,
+    /// Send a `Server-Timing` header on responses, breaking down the time
+    /// spent on authentication, routing, queueing, the upstream provider and
+    /// protocol conversion. Timings are kept in usage records either way.
+    #[serde(default)]
+    pub server_timing: bool,
+}
+
+fn default_metadata_cache_secs() -> u64 {
+    5
 }
 
 impl Default for ServerConfig {
@@ -156,8 +266,76 @@ impl Default for ServerConfig {
     }
 }
 
+/// Cross-origin policy details
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CorsConfig {
+    /// Let listed origins send cookies to auth and admin routes. Origins
+    /// allowed only by `*` never can.
+    #[serde(default)]
+    pub allow_credentials: bool,
+    /// Origins allowed on admin and configuration routes, in the same forms
+    /// as `cors_origins`. Empty allows same-origin requests only.
+    #[serde(default)]
+    pub admin_origins: Vec<String>,
+    /// Seconds browsers may cache a preflight response
+    #[serde(default = "default_cors_max_age_seconds")]
+    pub max_age_seconds: u64,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        serde_json::from_value(json!({})).expect("Default settings should always be valid")
+    }
+}
+
+fn default_cors_max_age_seconds() -> u64 {
+    600
+}
+
+/// Security headers: HSTS, `X-Content-Type-Options`, framing rules and a
+/// Content-Security-Policy
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SecurityHeadersConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// `Strict-Transport-Security` max-age (seconds); 0 sends none.
+    /// Browsers only honour it over HTTPS.
+    #[serde(default = "default_hsts_max_age_seconds")]
+    pub hsts_max_age_seconds: u64,
+    /// Extend HSTS to subdomains
+    #[serde(default)]
+    pub hsts_include_subdomains: bool,
+    /// Origins allowed to embed the UI in a frame. Empty forbids framing.
+    #[serde(default)]
+    pub frame_ancestors: Vec<String>,
+    /// Content-Security-Policy for the UI and API. Unset uses a strict
+    /// policy that fits the bundled frontend.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_security_policy: Option<String>,
+    /// Content-Security-Policy for the Swagger UI, which needs inline
+    /// scripts and styles
+    #[serde(default = "default_swagger_content_security_policy")]
+    pub swagger_content_security_policy: String,
+}
+
+impl Default for SecurityHeadersConfig {
+    fn default() -> Self {
+        serde_json::from_value(json!({})).expect("Default settings should always be valid")
+    }
+}
+
+fn default_hsts_max_age_seconds() -> u64 {
+    31_536_000
+}
+
+fn default_swagger_content_security_policy() -> String {
+    "default-src 'self'; script-src 'self' 'unsafe-inline'; style-src 'self' 'unsafe-inline'; \
+     img-src 'self' data:; connect-src 'self'; object-src 'none'"
+        .to_string()
+}
+
 /// Authentication configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AuthConfig {
     /// WebAuthn configuration
     #[serde(default)]
@@ -168,9 +346,15 @@ pub struct AuthConfig {
     /// Registration configuration
     #[serde(default)]
     pub registration: RegistrationConfig,
+    /// First-run bootstrap token configuration
+    #[serde(default)]
+    pub bootstrap: BootstrapConfig,
     /// Provider API key passthrough (Anthropic/OpenAI) for inference routes
     #[serde(default)]
     pub provider_passthrough: ProviderPassthroughConfig,
+    /// Lockouts after repeated failed sign-ins
+    #[serde(default)]
+    pub lockout: LockoutConfig,
 }
 
 impl Default for AuthConfig {
@@ -180,7 +364,7 @@ impl Default for AuthConfig {
 }
 
 /// Provider passthrough configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ProviderPassthroughConfig {
     /// Enable passthrough of provider API keys from clients
     #[serde(default = "default_true")]
@@ -195,10 +379,11 @@ pub struct ProviderPassthroughConfig {
 
 fn default_passthrough_paths() -> Vec<String> {
     vec![
-        "/v1/messages".to_string(),         // Anthropic
-        "/v1/chat/completions".to_string(), // OpenAI Chat
-        "/v1/responses".to_string(),        // OpenAI Responses
-        "/v1/completions".to_string(),      // OpenAI Completions (legacy)
+        "/v1/messages".to_string(),              // Anthropic
+        "/v1/messages/count_tokens".to_string(), // Anthropic token counting
+        "/v1/chat/completions".to_string(),      // OpenAI Chat
+        "/v1/responses".to_string(),             // OpenAI Responses
+        "/v1/completions".to_string(),           // OpenAI Completions (legacy)
     ]
 }
 
@@ -209,12 +394,14 @@ impl Default for ProviderPassthroughConfig {
 }
 
 /// Provider type
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum ProviderType {
     Anthropic,
     OpenAI,
     Custom,
+    /// Scripted local provider, for load testing and offline development
+    Mock,
 }
 
 impl std::fmt::Display for ProviderType {
@@ -223,12 +410,13 @@ impl std::fmt::Display for ProviderType {
             ProviderType::Anthropic => write!(f, "Anthropic"),
             ProviderType::OpenAI => write!(f, "OpenAI"),
             ProviderType::Custom => write!(f, "Custom"),
+            ProviderType::Mock => write!(f, "Mock"),
         }
     }
 }
 
 /// Provider configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ProviderConfig {
     /// Name identifier for this provider
     pub name: String,
@@ -245,6 +433,17 @@ pub struct ProviderConfig {
     /// List of supported models (populated on startup)
     #[serde(default, skip_serializing)]
     pub models: Vec<String>,
+    /// Behaviour of a mock provider; ignored for other provider types
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mock: Option<MockProviderConfig>,
+    /// Sign requests with this node's key, for upstreams that are other
+    /// Gates trusting it
+    #[serde(default)]
+    pub sign_requests: bool,
+    /// Node id an upstream Gate must attest to; the provider is not used
+    /// when it attests to another
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node_id: Option<String>,
 }
 
 impl Default for ProviderConfig {
@@ -253,8 +452,41 @@ impl Default for ProviderConfig {
     }
 }
 
+/// Scripted behaviour of a `mock://` provider
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MockProviderConfig {
+    /// Reply text; when unset the last user message is echoed back
+    #[serde(default)]
+    pub response: Option<String>,
+    /// Delay before the response starts
+    #[serde(default)]
+    pub latency_ms: u64,
+    /// Fraction of requests, from 0 to 1, that fail with `error_status`
+    #[serde(default)]
+    pub error_rate: f64,
+    /// HTTP status of injected failures
+    #[serde(default = "default_mock_error_status")]
+    pub error_status: u16,
+    /// Streaming rate; tokens are sent as fast as possible when zero
+    #[serde(default)]
+    pub tokens_per_second: f64,
+    /// Models served; any model is accepted when empty
+    #[serde(default)]
+    pub models: Vec<String>,
+}
+
+impl Default for MockProviderConfig {
+    fn default() -> Self {
+        serde_json::from_value(json!({})).expect("Default settings should always be valid")
+    }
+}
+
+fn default_mock_error_status() -> u16 {
+    500
+}
+
 /// WebAuthn configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct WebAuthnConfig {
     /// Relying Party ID (usually domain name)
     #[serde(default = "default_rp_id")]
@@ -289,13 +521,14 @@ impl Default for WebAuthnConfig {
 }
 
 /// JWT configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct JwtConfig {
     /// JWT issuer
     #[serde(default = "default_jwt_issuer")]
     pub issuer: String,
     /// JWT secret (read from JWT_SECRET env var or generate)
     #[serde(default = "default_jwt_secret")]
+    #[schemars(extend("writeOnly" = true))]
     pub secret: String,
     /// Token expiration in hours
     #[serde(default = "default_jwt_expiration_hours")]
@@ -309,7 +542,7 @@ impl Default for JwtConfig {
 }
 
 /// Registration configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct RegistrationConfig {
     /// Allow open registration after bootstrap
     #[serde(default = "default_false")]
@@ -322,8 +555,83 @@ impl Default for RegistrationConfig {
     }
 }
 
+/// Bootstrap token configuration
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BootstrapConfig {
+    /// How long a bootstrap token stays valid, in seconds
+    #[serde(default = "default_bootstrap_token_ttl")]
+    pub token_ttl_seconds: u64,
+    /// Bootstrap attempts allowed per minute
+    #[serde(default = "default_bootstrap_attempts_per_minute")]
+    pub max_attempts_per_minute: u32,
+    /// Token to enroll the first admin with instead of a generated one,
+    /// e.g. from a secret provisioned with the deployment. It is never
+    /// printed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(extend("writeOnly" = true))]
+    pub token: Option<String>,
+}
+
+impl Default for BootstrapConfig {
+    fn default() -> Self {
+        serde_json::from_value(json!({})).expect("Default settings should always be valid")
+    }
+}
+
+fn default_bootstrap_token_ttl() -> u64 {
+    crate::bootstrap::DEFAULT_TOKEN_TTL.as_secs()
+}
+
+fn default_bootstrap_attempts_per_minute() -> u32 {
+    crate::bootstrap::DEFAULT_ATTEMPTS_PER_MINUTE
+}
+
+/// Lockout of addresses and accounts that keep failing to authenticate
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LockoutConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Failures allowed before the first lockout
+    #[serde(default = "default_lockout_free_attempts")]
+    pub free_attempts: u32,
+    /// First lockout (seconds), doubled for each further failure
+    #[serde(default = "default_lockout_base_seconds")]
+    pub base_lockout_seconds: u64,
+    /// Longest lockout (seconds)
+    #[serde(default = "default_lockout_max_seconds")]
+    pub max_lockout_seconds: u64,
+    /// Failures are forgotten once none has happened for this long (seconds)
+    #[serde(default = "default_lockout_reset_seconds")]
+    pub reset_after_seconds: u64,
+    /// Webhooks notified when an address or account is locked out
+    #[serde(default)]
+    pub webhook_urls: Vec<String>,
+}
+
+impl Default for LockoutConfig {
+    fn default() -> Self {
+        serde_json::from_value(json!({})).expect("Default settings should always be valid")
+    }
+}
+
+fn default_lockout_free_attempts() -> u32 {
+    5
+}
+
+fn default_lockout_base_seconds() -> u64 {
+    30
+}
+
+fn default_lockout_max_seconds() -> u64 {
+    3600
+}
+
+fn default_lockout_reset_seconds() -> u64 {
+    3600
+}
+
 /// TLS forward configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TlsForwardConfig {
     /// Enable TLS forward functionality
     #[serde(default = "default_false")]
@@ -349,6 +657,22 @@ pub struct TlsForwardConfig {
     /// Reconnection backoff in seconds
     #[serde(default = "default_reconnect_backoff")]
     pub reconnect_backoff: u64,
+    /// Cap on the bandwidth of each forwarded connection, in bytes per
+    /// second each way
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_connection_bytes_per_second: Option<u64>,
+    /// Cap on the bandwidth of all forwarded connections together, in bytes
+    /// per second each way
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_total_bytes_per_second: Option<u64>,
+    /// How the node is exposed: through the hellas.ai relay, or a tunnel
+    /// the node keeps open itself
+    #[serde(default)]
+    pub transport: TunnelTransport,
+    /// Logging of requests forwarded by the relay, and bans after repeated
+    /// authentication failures
+    #[serde(default)]
+    pub access_log: AccessLogConfig,
 }
 
 impl Default for TlsForwardConfig {
@@ -357,8 +681,144 @@ impl Default for TlsForwardConfig {
     }
 }
 
+/// Access logging for publicly forwarded requests
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AccessLogConfig {
+    /// Requests kept in memory for the access summary
+    #[serde(default = "default_access_log_entries")]
+    pub max_entries: usize,
+    /// Failed authentications from one address that get it banned; 0 never
+    /// bans
+    #[serde(default = "default_ban_after_failures")]
+    pub ban_after_failures: u32,
+    /// Period failures are counted over (seconds)
+    #[serde(default = "default_ban_window_seconds")]
+    pub ban_window_seconds: u64,
+    /// How long a ban lasts (seconds)
+    #[serde(default = "default_ban_duration_seconds")]
+    pub ban_duration_seconds: u64,
+}
+
+fn default_access_log_entries() -> usize {
+    1000
+}
+
+fn default_ban_after_failures() -> u32 {
+    10
+}
+
+fn default_ban_window_seconds() -> u64 {
+    300
+}
+
+fn default_ban_duration_seconds() -> u64 {
+    900
+}
+
+impl Default for AccessLogConfig {
+    fn default() -> Self {
+        serde_json::from_value(json!({})).expect("Default settings should always be valid")
+    }
+}
+
+impl TlsForwardConfig {
+    /// Delay before reconnect attempt `attempts + 1`, growing linearly with
+    /// the configured backoff
+    pub fn reconnect_delay(&self, attempts: u32) -> std::time::Duration {
+        std::time::Duration::from_secs(self.reconnect_backoff * (attempts as u64 + 1))
+    }
+
+    /// Bandwidth caps on forwarded traffic
+    pub fn bandwidth_limits(&self) -> BandwidthLimits {
+        BandwidthLimits {
+            per_connection: self.max_connection_bytes_per_second,
+            aggregate: self.max_total_bytes_per_second,
+        }
+    }
+}
+
+/// Transport exposing the node publicly
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TunnelTransport {
+    /// TLS forwarding through the relays in `tlsforward_addresses`
+    #[default]
+    Relay,
+    /// Reverse SSH tunnel (`ssh -R`) from a host the node can reach
+    Ssh {
+        host: String,
+        #[serde(default = "default_ssh_port")]
+        port: u16,
+        user: String,
+        /// Private key to authenticate with; the SSH agent and
+        /// `~/.ssh/config` are used when unset
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        key_path: Option<PathBuf>,
+        /// Known hosts file the host key is checked against; the user's
+        /// known hosts are used when unset
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        known_hosts_path: Option<PathBuf>,
+        /// Port opened on the SSH host
+        remote_port: u16,
+        /// Address the remote port binds to; binding beyond localhost needs
+        /// `GatewayPorts` on the SSH server
+        #[serde(default = "default_ssh_remote_bind")]
+        remote_bind: String,
+        /// Name the node is reached at, reported once connected; defaults
+        /// to `host:remote_port`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        domain: Option<String>,
+    },
+    /// Cloudflare Tunnel run through `cloudflared`. With a token the
+    /// tunnel's public hostname must route to the daemon's listen address
+    /// in the Cloudflare dashboard; without one a quick tunnel on
+    /// trycloudflare.com is opened.
+    Cloudflare {
+        /// Tunnel token from the Cloudflare dashboard
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[schemars(extend("writeOnly" = true))]
+        token: Option<String>,
+        /// Public hostname routed to the tunnel; required with a token
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        hostname: Option<String>,
+        #[serde(default = "default_cloudflared_path")]
+        cloudflared_path: PathBuf,
+        /// Address `cloudflared` serves its metrics and readiness endpoint
+        /// on, polled to tell whether the tunnel is connected
+        #[serde(default = "default_cloudflared_metrics_address")]
+        metrics_address: String,
+    },
+    /// Any TCP forwarder (bore, frp, a VPN port forward...) run as a child
+    /// process and restarted when it exits. `{local_host}` and
+    /// `{local_port}` in the arguments are replaced with the daemon's
+    /// listen address.
+    Command {
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+        /// Name the node is reached at, reported while the forwarder runs
+        domain: String,
+    },
+}
+
+fn default_ssh_port() -> u16 {
+    22
+}
+
+fn default_ssh_remote_bind() -> String {
+    "localhost".to_string()
+}
+
+fn default_cloudflared_path() -> PathBuf {
+    PathBuf::from("cloudflared")
+}
+
+fn default_cloudflared_metrics_address() -> String {
+    "127.0.0.1:20241".to_string()
+}
+
 /// Local inference configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct LocalInferenceConfig {
     /// Whether local inference is enabled
     #[serde(default = "default_true")]
@@ -375,6 +835,35 @@ pub struct LocalInferenceConfig {
     /// List of available models for local inference
     #[serde(default)]
     pub models: Vec<String>,
+    /// Models to load at startup, so their first request does not wait
+    /// for the load
+    #[serde(default)]
+    pub prewarm: Vec<String>,
+    /// Memory the loaded models may hold, in MB; the least recently used
+    /// are unloaded beyond it
+    #[serde(default)]
+    pub memory_budget_mb: Option<u64>,
+    /// Free memory to keep on the host, in MB; models are unloaded when it
+    /// runs lower. Only checked where free memory can be read.
+    #[serde(default = "default_min_free_memory_mb")]
+    pub min_free_memory_mb: u64,
+    /// Most models to keep loaded at once
+    #[serde(default)]
+    pub max_loaded_models: Option<usize>,
+    /// Draft models for speculative decoding, by the target model they
+    /// draft for
+    #[serde(default)]
+    pub draft_models: std::collections::HashMap<String, DraftModelConfig>,
+}
+
+/// Draft model paired with a local target model
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DraftModelConfig {
+    /// Draft model id; it must share the target's tokenizer
+    pub model: String,
+    /// Tokens drafted before the target checks them
+    #[serde(default = "default_draft_tokens")]
+    pub draft_tokens: usize,
 }
 
 impl Default for LocalInferenceConfig {
@@ -395,57 +884,2295 @@ fn default_max_tokens() -> u32 {
     2048
 }
 
-/// Let's Encrypt configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct LetsEncryptConfig {
-    /// Enable Let's Encrypt certificate management
-    #[serde(default = "default_false")]
-    pub enabled: bool,
-    /// Email address for ACME account
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub email: Option<String>,
-    /// Use staging environment for testing
-    #[serde(default = "default_false")]
-    pub staging: bool,
-    /// Domains to request certificates for
+fn default_min_free_memory_mb() -> u64 {
+    1024
+}
+
+fn default_draft_tokens() -> usize {
+    4
+}
+
+/// Document indexing configuration
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DocumentsConfig {
+    /// Model documents and queries are embedded with. Changing it leaves
+    /// documents indexed with the previous model out of searches until they
+    /// are uploaded again.
+    #[serde(default = "default_embedding_model")]
+    pub embedding_model: String,
+    /// Longest chunk of document text, in bytes
+    #[serde(default = "default_chunk_size")]
+    pub chunk_size: usize,
+    /// Text repeated between consecutive chunks, in bytes
+    #[serde(default = "default_chunk_overlap")]
+    pub chunk_overlap: usize,
+}
+
+impl Default for DocumentsConfig {
+    fn default() -> Self {
+        serde_json::from_value(json!({})).expect("Default settings should always be valid")
+    }
+}
+
+fn default_embedding_model() -> String {
+    "text-embedding-3-small".to_string()
+}
+
+fn default_chunk_size() -> usize {
+    1200
+}
+
+fn default_chunk_overlap() -> usize {
+    200
+}
+
+/// Routing configuration
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RoutingConfig {
+    /// Weight of user feedback in route scores, next to provider affinity's
+    /// 1.0. Zero routes without feedback.
+    #[serde(default = "default_quality_weight")]
+    pub quality_weight: f64,
+    /// Days for feedback to lose half its weight
+    #[serde(default = "default_feedback_half_life_days")]
+    pub feedback_half_life_days: u64,
+    /// Rated responses a route needs before its feedback counts
+    #[serde(default = "default_feedback_min_samples")]
+    pub feedback_min_samples: usize,
+    /// Deprecated models, overriding the built-in table model by model
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deprecations: Vec<ModelDeprecationConfig>,
+    /// Seconds during which identical non-streaming requests from one
+    /// identity share an upstream execution and its result. Zero turns
+    /// coalescing off; sampled responses are then independent even for
+    /// identical requests.
     #[serde(default)]
-    pub domains: Vec<String>,
-    /// Auto-renew certificates before expiry (days)
-    #[serde(default = "default_auto_renew_days")]
-    pub auto_renew_days: u32,
+    pub coalesce_window_secs: u64,
+    /// Models behind `gate/auto`; without them the automatic model is not
+    /// offered
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto: Option<AutoRouteConfig>,
+    /// Summarize the older turns of conversations that would overflow their
+    /// model's context window; off unless configured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compression: Option<CompressionConfig>,
+    /// Sign each response with a block naming the gateway, model and
+    /// request it came from; off unless configured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<ProvenanceConfig>,
+    /// What to do when a provider filters a response; unset only marks it
+    /// with the `ContentFilter` stop reason
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_filter: Option<ContentFilterConfig>,
+    /// Parameter profiles by name, enforced on the requests of the users
+    /// and keys they are assigned to
+    #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
+    pub profiles: std::collections::HashMap<String, ParameterProfileConfig>,
+    /// Profile name by identity or user id; `*` covers everyone else. Keys
+    /// may name a profile of their own, applied on top.
+    #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
+    pub profile_assignments: std::collections::HashMap<String, String>,
+    /// Middleware requests pass through, in order. Unset runs the built-in
+    /// chain; changes apply to new requests without a restart. Stream slots
+    /// and the cost tracker run first when left out, since key limits and
+    /// spend limits depend on them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub middleware: Option<Vec<MiddlewareConfig>>,
+    /// Limits on requests in flight at each provider and for each identity,
+    /// applied from the next restart; unlimited unless configured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concurrency: Option<ConcurrencyConfig>,
 }
 
-impl Default for LetsEncryptConfig {
+impl Default for RoutingConfig {
     fn default() -> Self {
         serde_json::from_value(json!({})).expect("Default settings should always be valid")
     }
 }
 
-impl Settings {
-    /// Load settings from a specific config file
-    pub fn load_from_file(path: impl Into<PathBuf>) -> Result<Self, ConfigError> {
-        let mut builder = Config::builder();
+fn default_quality_weight() -> f64 {
+    1.0
+}
 
-        // Start with defaults
-        builder = builder.add_source(Config::try_from(&Settings::default())?);
+fn default_feedback_half_life_days() -> u64 {
+    14
+}
 
-        // Add the specific config file
-        builder = builder.add_source(File::from(path.into()).required(true));
+fn default_feedback_min_samples() -> usize {
+    20
+}
 
-        // Add environment variables with GATE_ prefix (can override file settings)
-        builder = builder.add_source(
-            Environment::with_prefix("GATE")
-                .separator("__")
-                .try_parsing(true),
-        );
+/// A deprecated model and what serves its requests instead
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ModelDeprecationConfig {
+    /// Model name as clients request it
+    pub model: String,
+    /// Model requests are rewritten to; without one they are served as
+    /// asked and only warned about
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replacement: Option<String>,
+    /// When the provider stops serving the model
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<String>")]
+    pub retires_on: Option<chrono::NaiveDate>,
+}
+
+/// Limits enforced on requests, overriding or clamping what clients send
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ParameterProfileConfig {
+    /// Model for requests that name none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_model: Option<String>,
+    /// Highest temperature a request may ask for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_temperature: Option<f64>,
+    /// Most output tokens a request may ask for, and the limit of requests
+    /// that set none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+    /// Tools a request may offer, by function name or built-in tool type;
+    /// unset allows all
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_tools: Option<Vec<String>>,
+}
+
+fn default_queue_timeout_secs() -> u64 {
+    30
+}
+
+/// Requests in flight at each provider, shared fairly between identities
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ConcurrencyConfig {
+    /// Requests in flight at a provider, by provider id
+    #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
+    pub per_provider: std::collections::HashMap<String, usize>,
+    /// Requests in flight at providers not listed in `per_provider`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_per_provider: Option<usize>,
+    /// Requests one identity may have in flight at any one provider, so a
+    /// burst from one tenant leaves room for others
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub per_identity: Option<usize>,
+    /// Send requests to a fallback provider with room instead of waiting
+    /// for a saturated one
+    #[serde(default)]
+    pub spillover: bool,
+    /// Seconds a request waits for room before it is refused with 429
+    #[serde(default = "default_queue_timeout_secs")]
+    pub queue_timeout_secs: u64,
+}
 
-        let config = builder.build()?;
-        config.try_deserialize()
+impl ConcurrencyConfig {
+    pub fn limits(&self) -> ConcurrencyLimits {
+        ConcurrencyLimits {
+            sink_limits: self.per_provider.clone(),
+            default_sink_limit: self.default_per_provider,
+            identity_limit: self.per_identity,
+            spillover: self.spillover,
+            queue_timeout: std::time::Duration::from_secs(self.queue_timeout_secs),
+        }
     }
+}
 
-    pub async fn save_to_file(&self, path: impl Into<PathBuf>) -> Result<(), std::io::Error> {
-        let config_str = serde_json::to_string_pretty(self)?;
-        std::fs::write(path.into(), config_str)?;
-        Ok(())
+/// Models serving each class of prompt sent to `gate/auto`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AutoRouteConfig {
+    /// Model for short prompts with no code or reasoning cues
+    pub simple: String,
+    /// Model for long, tool-using or reasoning-heavy prompts
+    pub complex: String,
+    /// Model for prompts about code
+    pub coding: String,
+    /// Tier (`simple`, `complex` or `coding`) or model an identity or user
+    /// id is always routed to, whatever its prompt
+    #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
+    pub overrides: std::collections::HashMap<String, String>,
+}
+
+/// Conversation compression for long chats
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CompressionConfig {
+    /// Model that writes the summaries; a small, cheap one is enough
+    pub model: String,
+    /// Share of the context window a request may fill before its older
+    /// turns are summarized
+    #[serde(default = "default_compression_threshold")]
+    pub threshold: f64,
+    /// Most recent messages always sent verbatim
+    #[serde(default = "default_compression_keep_recent")]
+    pub keep_recent: usize,
+}
+
+/// Provenance blocks added to responses
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ProvenanceConfig {
+    /// Name of this gateway in the blocks; defaults to the node's public key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gateway_id: Option<String>,
+    /// Leave streams without the trailing `provenance` event, for clients
+    /// that fail on events they do not know
+    #[serde(default)]
+    pub skip_streams: bool,
+}
+
+/// Actions taken on responses providers filter under their content policy
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ContentFilterConfig {
+    /// Log each filtered response
+    #[serde(default = "default_true")]
+    pub log: bool,
+    /// Webhooks sent a `content_filtered` event for each filtered response
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub webhook_urls: Vec<String>,
+    /// Providers a filtered non-streaming request may be sent to again,
+    /// when the route has them as fallbacks. Empty never retries; streams
+    /// are never retried, as the client already has their content.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub retry_providers: Vec<String>,
+}
+
+/// A stage of the router's middleware chain
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MiddlewareConfig {
+    /// Provenance blocks as set in `routing.provenance`; skipped while that
+    /// is unset. First, so the block covers the response clients receive.
+    Provenance,
+    /// Concurrent stream limits set on API keys
+    StreamSlots,
+    /// Replacements and warnings for deprecated models
+    Deprecations,
+    /// Variant assignment for running experiments
+    Experiments,
+    /// Usage and cost records
+    CostTracker,
+    /// Capture of provider keys sent by clients
+    KeyCapture,
+    /// Rendering of prompt templates
+    PromptTemplates,
+    /// Inlining of uploaded files
+    FileReferences,
+    /// Passages retrieved from the document store
+    Retrieval,
+    /// Conversation compression as set in `routing.compression`; skipped
+    /// while that is unset
+    Compression,
+    /// Output limits for requests that set none
+    MaxTokens,
+    /// Response transforms of virtual models
+    ResponseTransform,
+    /// Requests and tokens per user per minute
+    RateLimit {
+        requests_per_minute: u32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tokens_per_minute: Option<u32>,
+        /// Log requests over the limit instead of refusing them
+        #[serde(default)]
+        warn_only: bool,
+    },
+    /// Request timing logs
+    Monitoring,
+}
+
+impl MiddlewareConfig {
+    /// Chain run when none is configured
+    pub fn default_chain() -> Vec<Self> {
+        vec![
+            Self::Provenance,
+            Self::StreamSlots,
+            Self::Deprecations,
+            Self::Experiments,
+            Self::CostTracker,
+            Self::KeyCapture,
+            Self::PromptTemplates,
+            Self::FileReferences,
+            Self::Retrieval,
+            Self::Compression,
+            Self::MaxTokens,
+            Self::ResponseTransform,
+        ]
+    }
+
+    /// Name the stage is configured under
+    pub fn kind(&self) -> String {
+        serde_json::to_value(self)
+            .ok()
+            .and_then(|v| v["kind"].as_str().map(str::to_string))
+            .unwrap_or_default()
+    }
+}
+
+fn default_compression_threshold() -> f64 {
+    0.8
+}
+
+fn default_compression_keep_recent() -> usize {
+    6
+}
+
+impl RoutingConfig {
+    /// Middleware chain requests pass through
+    pub fn middleware_chain(&self) -> Vec<MiddlewareConfig> {
+        let Some(chain) = &self.middleware else {
+            return MiddlewareConfig::default_chain();
+        };
+        let mut chain = chain.clone();
+        for required in [MiddlewareConfig::CostTracker, MiddlewareConfig::StreamSlots] {
+            if !chain.contains(&required) {
+                chain.insert(0, required);
+            }
+        }
+        chain
+    }
+
+    /// Models behind `gate/auto`, if configured
+    pub fn auto_routes(&self) -> Option<AutoRoutes> {
+        self.auto.as_ref().map(|auto| AutoRoutes {
+            simple: auto.simple.clone(),
+            complex: auto.complex.clone(),
+            coding: auto.coding.clone(),
+            overrides: auto.overrides.clone(),
+        })
+    }
+
+    /// Profile named `name`, if configured
+    pub fn profile(&self, name: &str) -> Option<ParameterProfile> {
+        self.profiles.get(name).map(|p| ParameterProfile {
+            name: name.to_string(),
+            default_model: p.default_model.clone(),
+            max_temperature: p.max_temperature,
+            max_tokens: p.max_tokens,
+            allowed_tools: p.allowed_tools.clone(),
+        })
+    }
+
+    /// Profiles enforced on a request: the one assigned to the first of
+    /// `subjects` that has one, or to `*`, then the key's own
+    pub fn profiles_for(
+        &self,
+        subjects: &[&str],
+        key_profile: Option<&str>,
+    ) -> Vec<ParameterProfile> {
+        let assigned = subjects
+            .iter()
+            .chain(&["*"])
+            .find_map(|subject| self.profile_assignments.get(*subject));
+        assigned
+            .into_iter()
+            .map(String::as_str)
+            .chain(key_profile)
+            .filter_map(|name| {
+                let profile = self.profile(name);
+                if profile.is_none() {
+                    warn!("Parameter profile '{name}' is not configured");
+                }
+                profile
+            })
+            .collect()
+    }
+
+    /// Deprecation rules: the built-in table with configured overrides
+    pub fn deprecation_rules(&self) -> Vec<ModelDeprecation> {
+        let overrides: Vec<ModelDeprecation> = self
+            .deprecations
+            .iter()
+            .map(|d| ModelDeprecation {
+                model: d.model.clone(),
+                replacement: d.replacement.clone(),
+                retires_on: d.retires_on,
+            })
+            .collect();
+        deprecation_rules(&overrides)
+    }
+}
+
+/// Spend monitoring configuration
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SpendConfig {
+    /// Check spend in the background
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Minutes between checks. Token spikes are judged over this window.
+    #[serde(default = "default_spend_check_interval_minutes")]
+    pub check_interval_minutes: u64,
+    /// How many times its usual hourly rate a user or org must use in one
+    /// window to raise a token spike alert
+    #[serde(default = "default_spike_factor")]
+    pub spike_factor: f64,
+    /// Fewest tokens in one window that can raise a spike or model mix alert
+    #[serde(default = "default_min_alert_tokens")]
+    pub min_alert_tokens: u64,
+    /// Alert when a user or org is forecast to spend more than this many
+    /// USD by the end of the month
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forecast_alert_usd: Option<f64>,
+    /// URLs alerts are posted to as JSON
+    #[serde(default)]
+    pub webhook_urls: Vec<String>,
+    /// Hours before the same alert is raised again for the same user or org
+    #[serde(default = "default_alert_cooldown_hours")]
+    pub alert_cooldown_hours: u64,
+}
+
+impl Default for SpendConfig {
+    fn default() -> Self {
+        serde_json::from_value(json!({})).expect("Default settings should always be valid")
+    }
+}
+
+fn default_spend_check_interval_minutes() -> u64 {
+    60
+}
+
+fn default_spike_factor() -> f64 {
+    10.0
+}
+
+fn default_min_alert_tokens() -> u64 {
+    100_000
+}
+
+fn default_alert_cooldown_hours() -> u64 {
+    24
+}
+
+/// Data retention configuration. Data of a class without a window is kept
+/// until deleted.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RetentionConfig {
+    /// Days usage records are kept
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage_records_days: Option<u64>,
+    /// Days prompt renders, experiment outcomes and permission decisions are
+    /// kept
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audit_log_days: Option<u64>,
+    /// Days request and response bodies stored for the Responses API are kept
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub captured_bodies_days: Option<u64>,
+    /// Days a conversation is kept after its last update
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conversations_days: Option<u64>,
+    /// Hours between purges
+    #[serde(default = "default_purge_interval_hours")]
+    pub purge_interval_hours: u64,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        serde_json::from_value(json!({})).expect("Default settings should always be valid")
+    }
+}
+
+impl RetentionConfig {
+    /// Days data of a class is kept, if it is purged at all
+    pub fn window_days(&self, class: DataClass) -> Option<u64> {
+        match class {
+            DataClass::UsageRecords => self.usage_records_days,
+            DataClass::AuditLog => self.audit_log_days,
+            DataClass::CapturedBodies => self.captured_bodies_days,
+            DataClass::Conversations => self.conversations_days,
+        }
+    }
+}
+
+fn default_purge_interval_hours() -> u64 {
+    24
+}
+
+/// Deduplicated storage of large body strings. Unreferenced blobs are
+/// collected on the retention purge interval.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BlobStoreConfig {
+    /// Where blobs are kept: uploaded files, database backups and
+    /// deduplicated bodies
+    #[serde(default)]
+    pub driver: BlobDriverConfig,
+    /// Keep the long strings of stored conversations and responses once
+    /// each, compressed, rather than in every record. Blobs already stored
+    /// stay readable when this is turned off.
+    #[serde(default = "default_true")]
+    pub dedup: bool,
+    /// Shortest string moved to the blob store, in bytes
+    #[serde(default = "default_dedup_min_bytes")]
+    pub dedup_min_bytes: usize,
+}
+
+impl Default for BlobStoreConfig {
+    fn default() -> Self {
+        serde_json::from_value(json!({})).expect("Default settings should always be valid")
+    }
+}
+
+fn default_dedup_min_bytes() -> usize {
+    4096
+}
+
+/// Storage blobs are kept in
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BlobDriverConfig {
+    /// A local directory, the data directory unless set
+    Local {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        path: Option<String>,
+    },
+    /// An S3-compatible bucket: AWS S3, MinIO, R2 and the like
+    S3 {
+        /// Base URL of the service, e.g. `https://s3.us-east-1.amazonaws.com`
+        endpoint: String,
+        bucket: String,
+        #[serde(default = "default_s3_region")]
+        region: String,
+        /// Prefix of every key, to share a bucket between nodes
+        #[serde(default)]
+        prefix: String,
+        access_key_id: String,
+        #[schemars(extend("writeOnly" = true))]
+        secret_access_key: String,
+        /// Name the bucket in the path instead of the host name
+        #[serde(default)]
+        path_style: bool,
+    },
+}
+
+impl Default for BlobDriverConfig {
+    fn default() -> Self {
+        Self::Local { path: None }
+    }
+}
+
+fn default_s3_region() -> String {
+    "us-east-1".to_string()
+}
+
+/// Permission audit configuration. Decisions are kept with the audit log
+/// and purged on its retention window.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PermissionAuditConfig {
+    /// Record permission checks with their subject, action, object and
+    /// decision
+    #[serde(default)]
+    pub enabled: bool,
+    /// Fraction of allowed checks recorded, from 0 to 1
+    #[serde(default = "default_sample_rate")]
+    #[schemars(range(min = 0.0, max = 1.0))]
+    pub allowed_sample_rate: f64,
+    /// Fraction of denied checks recorded, from 0 to 1
+    #[serde(default = "default_sample_rate")]
+    #[schemars(range(min = 0.0, max = 1.0))]
+    pub denied_sample_rate: f64,
+}
+
+impl Default for PermissionAuditConfig {
+    fn default() -> Self {
+        serde_json::from_value(json!({})).expect("Default settings should always be valid")
+    }
+}
+
+fn default_sample_rate() -> f64 {
+    1.0
+}
+
+/// Request signature configuration. Signatures use the node's identity key
+/// and name it by its hex public key.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SigningConfig {
+    /// Sign webhook deliveries
+    #[serde(default = "default_true")]
+    pub webhooks: bool,
+    /// Hex public keys of Gates whose signed requests are accepted. Signed
+    /// requests from other keys are refused.
+    #[serde(default)]
+    pub trusted_keys: Vec<String>,
+}
+
+impl Default for SigningConfig {
+    fn default() -> Self {
+        serde_json::from_value(json!({})).expect("Default settings should always be valid")
+    }
+}
+
+/// Local control socket configuration
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ControlConfig {
+    /// Serve JSON-RPC on a Unix socket for local tooling
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Socket path; defaults to `control.sock` in the data directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub socket_path: Option<PathBuf>,
+}
+
+impl Default for ControlConfig {
+    fn default() -> Self {
+        serde_json::from_value(json!({})).expect("Default settings should always be valid")
+    }
+}
+
+fn default_lease_seconds() -> u64 {
+    30
+}
+
+/// Running several replicas of the daemon behind a load balancer
+///
+/// Only a SQLite state database is supported, which replicas can share only
+/// on one host; Postgres and Redis stores are not available. Passkey
+/// ceremonies are held by the replica that started them, so
+/// `/auth/webauthn/` must be routed to a single replica. Certificates are
+/// reloaded by the leader, so TLS is best terminated at the load balancer.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ClusterConfig {
+    /// Run as one of several replicas sharing a state database
+    #[serde(default)]
+    pub enabled: bool,
+    /// Name of this replica; defaults to the `HOSTNAME` environment
+    /// variable, which is the pod name under Kubernetes, or a random name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node_id: Option<String>,
+    /// SQLite state database the replicas share, as a `sqlite:` URL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(extend("writeOnly" = true))]
+    pub database_url: Option<String>,
+    /// How long the leader keeps its lease without renewing it, in seconds;
+    /// another replica takes over this long after the leader stops
+    #[serde(default = "default_lease_seconds")]
+    pub lease_seconds: u64,
+}
+
+impl Default for ClusterConfig {
+    fn default() -> Self {
+        serde_json::from_value(json!({})).expect("Default settings should always be valid")
+    }
+}
+
+impl ClusterConfig {
+    /// Name this replica holds leases under
+    pub fn node_id(&self) -> String {
+        self.node_id
+            .clone()
+            .or_else(|| std::env::var("HOSTNAME").ok())
+            .unwrap_or_else(|| format!("gate-{}", uuid::Uuid::new_v4().simple()))
+    }
+}
+
+fn default_max_buffered_mb() -> u64 {
+    512
+}
+
+fn default_max_response_mb() -> u64 {
+    64
+}
+
+fn default_memory_check_seconds() -> u64 {
+    10
+}
+
+/// Limits on the memory responses and background work may take
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MemoryConfig {
+    /// Megabytes of non-streaming and coalesced responses held at once
+    /// across requests; 0 for no cap
+    #[serde(default = "default_max_buffered_mb")]
+    pub max_buffered_mb: u64,
+    /// Megabytes any one non-streaming or coalesced response may hold;
+    /// 0 for no cap
+    #[serde(default = "default_max_response_mb")]
+    pub max_response_mb: u64,
+    /// Resident memory in megabytes above which the daemon sheds work,
+    /// lowest priority first, until it is back under 90% of this
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rss_limit_mb: Option<u64>,
+    /// How often resident memory is checked, in seconds
+    #[serde(default = "default_memory_check_seconds")]
+    pub check_interval_seconds: u64,
+    /// Webhooks told when shedding starts and when responses hit a cap
+    #[serde(default)]
+    pub webhook_urls: Vec<String>,
+}
+
+impl Default for MemoryConfig {
+    fn default() -> Self {
+        serde_json::from_value(json!({})).expect("Default settings should always be valid")
+    }
+}
+
+impl MemoryConfig {
+    /// Caps on buffered responses, in bytes
+    pub fn buffer_limits(&self) -> BufferLimits {
+        let bytes = |mb: u64| usize::try_from(mb.saturating_mul(1 << 20)).unwrap_or(usize::MAX);
+        BufferLimits {
+            total_bytes: bytes(self.max_buffered_mb),
+            response_bytes: bytes(self.max_response_mb),
+        }
+    }
+}
+
+fn default_max_profile_seconds() -> u64 {
+    300
+}
+
+/// Profiles admins can capture from a running daemon
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ProfilingConfig {
+    /// Allow admins to capture profiles
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Longest a profile may sample for, in seconds
+    #[serde(default = "default_max_profile_seconds")]
+    pub max_seconds: u64,
+}
+
+impl Default for ProfilingConfig {
+    fn default() -> Self {
+        serde_json::from_value(json!({})).expect("Default settings should always be valid")
+    }
+}
+
+/// Override for a scheduled task
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TaskConfig {
+    /// Run the task on its schedule. Disabled tasks can still be triggered
+    /// by hand.
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Cron expression (`minute hour day month weekday`, UTC), shortcut such
+    /// as `@daily`, or interval such as `@every 15m`, replacing the task's
+    /// default schedule
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<String>,
+}
+
+/// Let's Encrypt configuration
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LetsEncryptConfig {
+    /// Enable Let's Encrypt certificate management
+    #[serde(default = "default_false")]
+    pub enabled: bool,
+    /// Email address for ACME account
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    /// Use staging environment for testing
+    #[serde(default = "default_false")]
+    pub staging: bool,
+    /// Domains to request certificates for
+    #[serde(default)]
+    pub domains: Vec<String>,
+    /// Auto-renew certificates before expiry (days)
+    #[serde(default = "default_auto_renew_days")]
+    pub auto_renew_days: u32,
+    /// DNS providers answering DNS-01 challenges for custom domains,
+    /// including wildcards. Domains none of them covers are validated
+    /// through the TLS forward relay.
+    #[serde(default)]
+    pub dns: Vec<DnsChallengeConfig>,
+}
+
+/// A DNS provider and the domains it answers challenges for
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DnsChallengeConfig {
+    /// Domains whose challenge records this provider can publish, e.g.
+    /// `example.com` or `*.example.com`
+    pub domains: Vec<String>,
+    #[serde(flatten)]
+    pub provider: DnsProviderConfig,
+    /// Seconds to wait after publishing a record before validation
+    #[serde(default = "default_dns_propagation_seconds")]
+    pub propagation_seconds: u64,
+}
+
+fn default_dns_propagation_seconds() -> u64 {
+    30
+}
+
+/// API a DNS provider is driven through
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "provider", rename_all = "snake_case")]
+pub enum DnsProviderConfig {
+    /// Cloudflare, with an API token allowed to edit the zone's DNS
+    Cloudflare {
+        #[schemars(extend("writeOnly" = true))]
+        api_token: String,
+        zone_id: String,
+    },
+    /// Amazon Route 53
+    Route53 {
+        access_key_id: String,
+        #[schemars(extend("writeOnly" = true))]
+        secret_access_key: String,
+        hosted_zone_id: String,
+        /// Session token of temporary credentials
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[schemars(extend("writeOnly" = true))]
+        session_token: Option<String>,
+    },
+    /// Dynamic DNS update (RFC 2136) to the zone's primary server, signed
+    /// with an HMAC-SHA256 TSIG key
+    Rfc2136 {
+        /// `host:port` of the primary server
+        server: String,
+        zone: String,
+        key_name: String,
+        /// Base64 TSIG secret
+        #[schemars(extend("writeOnly" = true))]
+        key_secret: String,
+    },
+}
+
+impl DnsProviderConfig {
+    /// Secret fields, by name, with their values
+    fn secrets(&self) -> Vec<(&'static str, &str)> {
+        match self {
+            Self::Cloudflare { api_token, .. } => vec![("api_token", api_token.as_str())],
+            Self::Route53 {
+                secret_access_key,
+                session_token,
+                ..
+            } => {
+                let mut secrets = vec![("secret_access_key", secret_access_key.as_str())];
+                if let Some(token) = session_token {
+                    secrets.push(("session_token", token.as_str()));
+                }
+                secrets
+            }
+            Self::Rfc2136 { key_secret, .. } => vec![("key_secret", key_secret.as_str())],
+        }
+    }
+}
+
+impl Default for LetsEncryptConfig {
+    fn default() -> Self {
+        serde_json::from_value(json!({})).expect("Default settings should always be valid")
+    }
+}
+
+/// Source of the TLS certificate
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CertificateSource {
+    /// Obtained through ACME, from Let's Encrypt or the relay
+    #[default]
+    Acme,
+    /// Certificate chain and private key PEM files supplied by the user
+    Files {
+        cert_path: PathBuf,
+        key_path: PathBuf,
+    },
+    /// Directory an external tool such as cert-manager or certbot keeps
+    /// current, holding `tls.crt` and `tls.key` or `fullchain.pem` and
+    /// `privkey.pem`
+    Directory { path: PathBuf },
+}
+
+/// User-supplied certificates
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CertificatesConfig {
+    #[serde(default)]
+    pub source: CertificateSource,
+    /// How often the files are checked for renewal (seconds)
+    #[serde(default = "default_certificate_check_seconds")]
+    pub check_interval_seconds: u64,
+    /// Warn when the certificate expires within this many days
+    #[serde(default = "default_certificate_warning_days")]
+    pub expiry_warning_days: u32,
+    /// Webhooks notified when the certificate nears expiry or fails to load
+    #[serde(default)]
+    pub webhook_urls: Vec<String>,
+}
+
+fn default_certificate_check_seconds() -> u64 {
+    300
+}
+
+fn default_certificate_warning_days() -> u32 {
+    14
+}
+
+impl Default for CertificatesConfig {
+    fn default() -> Self {
+        serde_json::from_value(json!({})).expect("Default settings should always be valid")
+    }
+}
+
+/// File extensions picked up from included directories
+const CONFIG_EXTENSIONS: [&str; 4] = ["json", "toml", "yaml", "yml"];
+
+/// Suffix of environment variables naming a file that holds the value
+const FILE_VAR_SUFFIX: &str = "_FILE";
+
+/// Config files in `dir`, in name order
+fn config_files_in(dir: &Path) -> Result<Vec<PathBuf>, ConfigError> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map_err(|e| ConfigError::Foreign(Box::new(e)))?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| CONFIG_EXTENSIONS.contains(&ext))
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// Where a daemon's settings come from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigSource {
+    /// A config file with its includes, which changes are saved back to
+    File(PathBuf),
+    /// The environment and the files in a mounted directory, see
+    /// [`Settings::load_from_env`]; changes made at runtime are not saved
+    Environment { config_dir: Option<PathBuf> },
+}
+
+impl ConfigSource {
+    pub fn load(&self) -> Result<Settings, ConfigError> {
+        match self {
+            Self::File(path) => Settings::load_from_file(path),
+            Self::Environment { config_dir } => Settings::load_from_env(config_dir.as_deref()),
+        }
+    }
+
+    /// Whether settings changed at runtime are saved to the source
+    pub fn is_writable(&self) -> bool {
+        matches!(self, Self::File(_))
+    }
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::File(path) => write!(f, "{}", path.display()),
+            Self::Environment { config_dir: None } => f.write_str("the environment"),
+            Self::Environment {
+                config_dir: Some(dir),
+            } => write!(f, "the environment and {}", dir.display()),
+        }
+    }
+}
+
+impl Settings {
+    /// Load settings from a specific config file.
+    ///
+    /// The format follows the file extension: JSON, TOML or YAML. Files
+    /// named by `include` are merged first, in the order listed, with the
+    /// files of an included directory taken in name order; the including
+    /// file is merged last and environment variables override them all.
+    /// Providers are merged by name rather than replaced wholesale, so each
+    /// can live in its own file.
+    pub fn load_from_file(path: impl Into<PathBuf>) -> Result<Self, ConfigError> {
+        let path = path.into();
+        let main = Config::builder()
+            .add_source(File::from(path.as_path()).required(true))
+            .build()?;
+        let mut files = Vec::new();
+        for include in Self::includes(&main, &path)? {
+            files.push(
+                Config::builder()
+                    .add_source(File::from(include.as_path()).required(true))
+                    .build()?,
+            );
+        }
+        files.push(main);
+
+        // Start with defaults
+        let mut builder = Config::builder().add_source(Config::try_from(&Settings::default())?);
+
+        // Add the config files in merge order
+        for file in &files {
+            builder = builder.add_source(file.clone());
+        }
+
+        // Add environment variables with GATE_ prefix (can override file settings)
+        builder = builder.add_source(
+            Environment::with_prefix("GATE")
+                .separator("__")
+                .try_parsing(true),
+        );
+
+        let mut settings: Settings = builder.build()?.try_deserialize()?;
+        let providers = merge_providers(&files)?;
+        if !providers.is_empty() {
+            settings.providers = providers;
+        }
+        Ok(settings)
+    }
+
+    /// Files named by the `include` directive of the config file at `path`
+    fn includes(main: &Config, path: &Path) -> Result<Vec<PathBuf>, ConfigError> {
+        let entries: Vec<String> = match main.get("include") {
+            Ok(entries) => entries,
+            Err(ConfigError::NotFound(_)) => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let base = path.parent().unwrap_or(Path::new("."));
+
+        let mut files = Vec::new();
+        for entry in entries {
+            let entry = base.join(entry);
+            if entry.is_dir() {
+                files.extend(config_files_in(&entry)?);
+            } else {
+                files.push(entry);
+            }
+        }
+        Ok(files)
+    }
+
+    /// Load settings from the environment alone, for containers whose
+    /// configuration comes with the deployment.
+    ///
+    /// The config files in `config_dir`, e.g. a mounted ConfigMap, are
+    /// merged in name order and `GATE_` environment variables override
+    /// them. A variable ending in `_FILE` sets its setting to the contents
+    /// of the file it names, so `GATE_AUTH__JWT__SECRET_FILE` reads the JWT
+    /// secret from a mounted secret. Unlike other loads, no JWT secret is
+    /// generated when none is given; [`Settings::deployment_issues`]
+    /// reports it instead.
+    pub fn load_from_env(config_dir: Option<&Path>) -> Result<Self, ConfigError> {
+        Self::load_from_vars(config_dir, std::env::vars().collect())
+    }
+
+    fn load_from_vars(
+        config_dir: Option<&Path>,
+        vars: HashMap<String, String>,
+    ) -> Result<Self, ConfigError> {
+        let mut files = Vec::new();
+        if let Some(dir) = config_dir {
+            for path in config_files_in(dir)? {
+                files.push(
+                    Config::builder()
+                        .add_source(File::from(path.as_path()).required(true))
+                        .build()?,
+                );
+            }
+        }
+
+        let mut defaults = Settings::default();
+        defaults.auth.jwt.secret = vars.get("JWT_SECRET").cloned().unwrap_or_default();
+        let mut builder = Config::builder().add_source(Config::try_from(&defaults)?);
+        for file in &files {
+            builder = builder.add_source(file.clone());
+        }
+        builder = builder.add_source(
+            Environment::with_prefix("GATE")
+                .separator("__")
+                .try_parsing(true)
+                .source(Some(vars.clone())),
+        );
+        for (name, path) in &vars {
+            let Some(key) = name
+                .strip_prefix("GATE_")
+                .and_then(|name| name.strip_suffix(FILE_VAR_SUFFIX))
+            else {
+                continue;
+            };
+            let value = std::fs::read_to_string(path)
+                .map_err(|e| ConfigError::Message(format!("{name}: {path}: {e}")))?;
+            builder = builder.set_override(
+                key.to_lowercase().replace("__", "."),
+                value.trim_end().to_string(),
+            )?;
+        }
+
+        let mut settings: Settings = builder.build()?.try_deserialize()?;
+        let providers = merge_providers(&files)?;
+        if !providers.is_empty() {
+            settings.providers = providers;
+        }
+        Ok(settings)
+    }
+
+    /// Save settings in the format of the file extension: TOML for `.toml`
+    /// and JSON otherwise, which YAML readers accept as well
+    pub async fn save_to_file(&self, path: impl Into<PathBuf>) -> Result<(), std::io::Error> {
+        let path = path.into();
+        let config_str = if path.extension().is_some_and(|ext| ext == "toml") {
+            toml::to_string_pretty(self)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?
+        } else {
+            serde_json::to_string_pretty(self)?
+        };
+        std::fs::write(path, config_str)?;
+        Ok(())
+    }
+}
+
+/// Providers from each file in merge order, a later file replacing an
+/// earlier provider of the same name
+fn merge_providers(files: &[Config]) -> Result<Vec<ProviderConfig>, ConfigError> {
+    let mut providers: Vec<ProviderConfig> = Vec::new();
+    for file in files {
+        let file_providers: Vec<ProviderConfig> = match file.get("providers") {
+            Ok(file_providers) => file_providers,
+            Err(ConfigError::NotFound(_)) => continue,
+            Err(e) => return Err(e),
+        };
+        for provider in file_providers {
+            match providers.iter_mut().find(|p| p.name == provider.name) {
+                Some(existing) => *existing = provider,
+                None => providers.push(provider),
+            }
+        }
+    }
+    Ok(providers)
+}
+
+impl Settings {
+    /// JSON Schema of the settings, with doc comments as descriptions, for
+    /// editors that build their forms from it
+    pub fn json_schema() -> serde_json::Value {
+        let mut schema = serde_json::to_value(schemars::schema_for!(Settings))
+            .expect("Settings schema should always serialize");
+        strip_defaults(&mut schema);
+        schema
+    }
+}
+
+/// Drop defaults that could reveal secrets: those of write-only fields, and
+/// those of whole objects, whose fields carry their own defaults
+fn strip_defaults(schema: &mut serde_json::Value) {
+    match schema {
+        serde_json::Value::Object(map) => {
+            let write_only = map.get("writeOnly") == Some(&serde_json::Value::Bool(true));
+            if write_only || map.get("default").is_some_and(serde_json::Value::is_object) {
+                map.remove("default");
+            }
+            for (key, value) in map.iter_mut() {
+                if key != "default" {
+                    strip_defaults(value);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(strip_defaults),
+        _ => {}
+    }
+}
+
+/// Severity of a configuration validation issue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IssueSeverity {
+    /// The document cannot be saved
+    Error,
+    /// The document can be saved, but not exactly as written
+    Warning,
+}
+
+/// Problem found while validating a raw configuration document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfigIssue {
+    pub severity: IssueSeverity,
+    /// JSON pointer to the offending value, when known
+    pub path: Option<String>,
+    /// 1-based position in the document, when known
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl ConfigIssue {
+    fn at_path(severity: IssueSeverity, path: String, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            path: Some(path),
+            line: None,
+            column: None,
+            message: message.into(),
+        }
+    }
+
+    fn from_parse_error(error: &serde_json::Error) -> Self {
+        Self {
+            severity: IssueSeverity::Error,
+            path: None,
+            line: Some(error.line()),
+            column: Some(error.column()),
+            message: error.to_string(),
+        }
+    }
+}
+
+impl Settings {
+    /// Validate a raw JSON configuration document against the settings schema.
+    ///
+    /// Syntax and type errors stop validation and carry a line and column.
+    /// Fields the schema does not know, and would silently drop on save, are
+    /// reported as warnings alongside semantic errors such as duplicate names.
+    pub fn validate_json(text: &str) -> Vec<ConfigIssue> {
+        let raw: serde_json::Value = match serde_json::from_str(text) {
+            Ok(raw) => raw,
+            Err(e) => return vec![ConfigIssue::from_parse_error(&e)],
+        };
+        let settings: Settings = match serde_json::from_str(text) {
+            Ok(settings) => settings,
+            Err(e) => return vec![ConfigIssue::from_parse_error(&e)],
+        };
+
+        let mut issues = Vec::new();
+        if let Ok(normalized) = serde_json::to_value(&settings) {
+            collect_dropped_fields(&raw, &normalized, String::new(), &mut issues);
+        }
+
+        let mut names = std::collections::HashSet::new();
+        for (i, provider) in settings.providers.iter().enumerate() {
+            if !names.insert(provider.name.as_str()) {
+                issues.push(ConfigIssue::at_path(
+                    IssueSeverity::Error,
+                    format!("/providers/{i}/name"),
+                    format!("Duplicate provider name '{}'", provider.name),
+                ));
+            }
+            if provider.base_url.trim().is_empty() {
+                issues.push(ConfigIssue::at_path(
+                    IssueSeverity::Error,
+                    format!("/providers/{i}/base_url"),
+                    "Provider base_url must not be empty",
+                ));
+            }
+        }
+
+        if let Some(error) = base_path_error(&settings.server.base_path) {
+            issues.push(ConfigIssue::at_path(
+                IssueSeverity::Error,
+                "/server/base_path".to_string(),
+                error,
+            ));
+        }
+
+        let prefixes = &settings.server.route_prefixes;
+        let mut seen = std::collections::HashSet::new();
+        for (family, list) in [
+            ("anthropic", &prefixes.anthropic),
+            ("openai", &prefixes.openai),
+        ] {
+            for (i, prefix) in list.iter().enumerate() {
+                let error = prefix_error(prefix).map(str::to_string).or_else(|| {
+                    (!seen.insert(prefix.as_str()))
+                        .then(|| format!("Prefix '{prefix}' is used more than once"))
+                });
+                if let Some(error) = error {
+                    issues.push(ConfigIssue::at_path(
+                        IssueSeverity::Error,
+                        format!("/server/route_prefixes/{family}/{i}"),
+                        error,
+                    ));
+                }
+            }
+        }
+
+        let cluster = &settings.cluster;
+        if cluster.enabled {
+            match cluster.database_url.as_deref() {
+                None => issues.push(ConfigIssue::at_path(
+                    IssueSeverity::Error,
+                    "/cluster/database_url".to_string(),
+                    "Replicas must share a state database",
+                )),
+                Some(url) if url.starts_with("sqlite:") => issues.push(ConfigIssue::at_path(
+                    IssueSeverity::Warning,
+                    "/cluster/database_url".to_string(),
+                    "A SQLite database can only be shared by replicas on one host",
+                )),
+                Some(_) => issues.push(ConfigIssue::at_path(
+                    IssueSeverity::Error,
+                    "/cluster/database_url".to_string(),
+                    "Only SQLite state databases are supported; Postgres and Redis are not",
+                )),
+            }
+            issues.push(ConfigIssue::at_path(
+                IssueSeverity::Warning,
+                "/cluster".to_string(),
+                "Passkey sign-in is held per replica; route /auth/webauthn/ to one replica",
+            ));
+            if cluster.lease_seconds < 3 {
+                issues.push(ConfigIssue::at_path(
+                    IssueSeverity::Error,
+                    "/cluster/lease_seconds".to_string(),
+                    "The lease must last at least 3 seconds",
+                ));
+            }
+        }
+
+        if let Some(concurrency) = &settings.routing.concurrency {
+            let zero_providers = concurrency
+                .per_provider
+                .iter()
+                .filter(|(_, limit)| **limit == 0)
+                .map(|(provider, _)| format!("/routing/concurrency/per_provider/{provider}"));
+            let zero_defaults = [
+                ("default_per_provider", concurrency.default_per_provider),
+                ("per_identity", concurrency.per_identity),
+            ]
+            .into_iter()
+            .filter(|(_, limit)| *limit == Some(0))
+            .map(|(field, _)| format!("/routing/concurrency/{field}"));
+            for path in zero_providers.chain(zero_defaults) {
+                issues.push(ConfigIssue::at_path(
+                    IssueSeverity::Error,
+                    path,
+                    "A concurrency limit of zero would refuse every request; leave it unset for no limit",
+                ));
+            }
+        }
+
+        let memory = &settings.memory;
+        if memory.max_buffered_mb > 0 && memory.max_response_mb > memory.max_buffered_mb {
+            issues.push(ConfigIssue::at_path(
+                IssueSeverity::Warning,
+                "/memory/max_response_mb".to_string(),
+                "Responses cannot grow past max_buffered_mb, the cap across all responses",
+            ));
+        }
+        if memory.rss_limit_mb == Some(0) {
+            issues.push(ConfigIssue::at_path(
+                IssueSeverity::Error,
+                "/memory/rss_limit_mb".to_string(),
+                "The memory limit must be above zero; leave it unset to disable shedding",
+            ));
+        }
+
+        for class in DataClass::ALL {
+            if settings.retention.window_days(class) == Some(0) {
+                let field = serde_json::to_value(class)
+                    .ok()
+                    .and_then(|v| v.as_str().map(str::to_string))
+                    .unwrap_or_default();
+                issues.push(ConfigIssue::at_path(
+                    IssueSeverity::Error,
+                    format!("/retention/{field}_days"),
+                    "Retention window must be at least one day",
+                ));
+            }
+        }
+
+        let audit = &settings.permission_audit;
+        for (field, rate) in [
+            ("allowed_sample_rate", audit.allowed_sample_rate),
+            ("denied_sample_rate", audit.denied_sample_rate),
+        ] {
+            if !(0.0..=1.0).contains(&rate) {
+                issues.push(ConfigIssue::at_path(
+                    IssueSeverity::Error,
+                    format!("/permission_audit/{field}"),
+                    "Sample rate must be between 0 and 1",
+                ));
+            }
+        }
+
+        if let BlobDriverConfig::S3 {
+            endpoint, bucket, ..
+        } = &settings.blob_store.driver
+        {
+            if let Err(e) = reqwest::Url::parse(endpoint) {
+                issues.push(ConfigIssue::at_path(
+                    IssueSeverity::Error,
+                    "/blob_store/driver/endpoint".to_string(),
+                    format!("Invalid endpoint URL: {e}"),
+                ));
+            }
+            if bucket.is_empty() {
+                issues.push(ConfigIssue::at_path(
+                    IssueSeverity::Error,
+                    "/blob_store/driver/bucket".to_string(),
+                    "Bucket name must not be empty",
+                ));
+            }
+        }
+
+        let routing = &settings.routing;
+        for (subject, name) in &routing.profile_assignments {
+            if !routing.profiles.contains_key(name) {
+                issues.push(ConfigIssue::at_path(
+                    IssueSeverity::Error,
+                    format!("/routing/profile_assignments/{subject}"),
+                    format!("Unknown parameter profile '{name}'"),
+                ));
+            }
+        }
+        for (name, profile) in &routing.profiles {
+            if profile.max_temperature.is_some_and(|t| t < 0.0) {
+                issues.push(ConfigIssue::at_path(
+                    IssueSeverity::Error,
+                    format!("/routing/profiles/{name}/max_temperature"),
+                    "Temperature ceiling must not be negative",
+                ));
+            }
+            if profile.max_tokens == Some(0) {
+                issues.push(ConfigIssue::at_path(
+                    IssueSeverity::Error,
+                    format!("/routing/profiles/{name}/max_tokens"),
+                    "Output token cap must be positive",
+                ));
+            }
+        }
+        let mut kinds = std::collections::HashSet::new();
+        for (i, stage) in routing.middleware.iter().flatten().enumerate() {
+            let kind = stage.kind();
+            if !kinds.insert(kind.clone()) {
+                issues.push(ConfigIssue::at_path(
+                    IssueSeverity::Error,
+                    format!("/routing/middleware/{i}"),
+                    format!("Middleware '{kind}' is listed more than once"),
+                ));
+            }
+            match stage {
+                MiddlewareConfig::Compression if routing.compression.is_none() => {
+                    issues.push(ConfigIssue::at_path(
+                        IssueSeverity::Warning,
+                        format!("/routing/middleware/{i}"),
+                        "Compression is skipped until routing.compression is set",
+                    ));
+                }
+                MiddlewareConfig::Provenance if routing.provenance.is_none() => {
+                    issues.push(ConfigIssue::at_path(
+                        IssueSeverity::Warning,
+                        format!("/routing/middleware/{i}"),
+                        "Provenance is skipped until routing.provenance is set",
+                    ));
+                }
+                MiddlewareConfig::RateLimit {
+                    requests_per_minute,
+                    tokens_per_minute,
+                    ..
+                } => {
+                    for (field, limit) in [
+                        ("requests_per_minute", Some(*requests_per_minute)),
+                        ("tokens_per_minute", *tokens_per_minute),
+                    ] {
+                        if limit == Some(0) {
+                            issues.push(ConfigIssue::at_path(
+                                IssueSeverity::Error,
+                                format!("/routing/middleware/{i}/{field}"),
+                                "Rate limit must be positive",
+                            ));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for (name, task) in &settings.tasks {
+            if let Some(schedule) = &task.schedule
+                && let Err(e) = schedule.parse::<crate::services::scheduler::Schedule>()
+            {
+                issues.push(ConfigIssue::at_path(
+                    IssueSeverity::Error,
+                    format!("/tasks/{name}/schedule"),
+                    format!("Invalid schedule '{schedule}': {e}"),
+                ));
+            }
+        }
+
+        issues
+    }
+
+    /// Problems that keep settings loaded with [`Settings::load_from_env`]
+    /// from running in a container: the checks of
+    /// [`Settings::validate_json`], and settings that only work on a
+    /// workstation
+    pub fn deployment_issues(&self) -> Vec<ConfigIssue> {
+        let mut issues = match serde_json::to_string(self) {
+            Ok(text) => Self::validate_json(&text),
+            Err(e) => vec![ConfigIssue::from_parse_error(&e)],
+        };
+        if self.auth.jwt.secret.is_empty() {
+            issues.push(ConfigIssue::at_path(
+                IssueSeverity::Error,
+                "/auth/jwt/secret".to_string(),
+                "No JWT secret is set, so sessions would not survive a restart; set \
+                 GATE_AUTH__JWT__SECRET_FILE or GATE_AUTH__JWT__SECRET",
+            ));
+        }
+        if matches!(self.server.host.as_str(), "localhost" | "127.0.0.1" | "::1") {
+            issues.push(ConfigIssue::at_path(
+                IssueSeverity::Error,
+                "/server/host".to_string(),
+                format!(
+                    "Listening on {} is unreachable from probes and Services; set \
+                     GATE_SERVER__HOST=0.0.0.0",
+                    self.server.host
+                ),
+            ));
+        }
+        if let Some(token) = &self.auth.bootstrap.token
+            && token.len() < crate::bootstrap::TOKEN_LENGTH
+        {
+            issues.push(ConfigIssue::at_path(
+                IssueSeverity::Error,
+                "/auth/bootstrap/token".to_string(),
+                format!(
+                    "The bootstrap token must be at least {} characters",
+                    crate::bootstrap::TOKEN_LENGTH
+                ),
+            ));
+        }
+        if self.providers.is_empty() && self.local_inference.is_none() {
+            issues.push(ConfigIssue::at_path(
+                IssueSeverity::Warning,
+                "/providers".to_string(),
+                "No providers are configured, so the daemon will not become ready",
+            ));
+        }
+        issues
+    }
+}
+
+/// Name of the JWT signing secret in exports
+const JWT_SECRET_NAME: &str = "auth.jwt.secret";
+
+/// Name of the pre-provisioned bootstrap token among the secrets
+const BOOTSTRAP_TOKEN_SECRET_NAME: &str = "auth.bootstrap.token";
+
+/// Name of the shared state database URL, which may carry a password
+const CLUSTER_DATABASE_SECRET_NAME: &str = "cluster.database_url";
+
+/// Name of the Cloudflare Tunnel token secret
+const TUNNEL_TOKEN_SECRET_NAME: &str = "tlsforward.transport.token";
+
+/// Name of the blob store's S3 secret key among the secrets
+const BLOB_STORE_SECRET_NAME: &str = "blob_store.secret_access_key";
+
+/// Placeholder written in place of a secret in an exported configuration
+pub fn secret_placeholder(name: &str) -> String {
+    format!("${{secret:{name}}}")
+}
+
+/// Bearer token a server-side tool sends, if it has one
+fn tool_api_key(tool: &ToolConfig) -> Option<&str> {
+    match &tool.kind {
+        ToolKind::Http { api_key, .. } | ToolKind::Mcp { api_key, .. } => api_key.as_deref(),
+        ToolKind::WebFetch { .. } => None,
+    }
+}
+
+/// Name of the secret a placeholder stands in for
+fn placeholder_name(value: &str) -> Option<&str> {
+    value.strip_prefix("${secret:")?.strip_suffix('}')
+}
+
+/// A secret held in the configuration
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SecretRef {
+    /// Stable name, e.g. `providers.openai.api_key`
+    pub name: String,
+    /// JSON pointer to the value in the exported document
+    pub path: String,
+}
+
+/// How a secret was resolved during import
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretStatus {
+    /// Supplied alongside the import
+    Provided,
+    /// Kept from this instance's current configuration
+    Retained,
+    /// Left empty; must be re-entered
+    Missing,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SecretReport {
+    pub name: String,
+    pub path: String,
+    pub status: SecretStatus,
+}
+
+/// Configuration with its secrets replaced by placeholders
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigExport {
+    /// Version of gate that produced the export
+    pub gate_version: String,
+    pub config: serde_json::Value,
+    /// Secrets left out of `config`
+    pub secrets: Vec<SecretRef>,
+}
+
+/// A setting that differs between two configurations
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfigChange {
+    /// JSON pointer to the setting
+    pub path: String,
+    /// Value before, absent when the setting was added
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub before: Option<serde_json::Value>,
+    /// Value after, absent when the setting was removed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after: Option<serde_json::Value>,
+}
+
+/// Result of importing an exported configuration
+#[derive(Debug, Clone)]
+pub struct ConfigImport {
+    /// The settings to apply; `None` when any issue is an error
+    pub settings: Option<Settings>,
+    pub issues: Vec<ConfigIssue>,
+    pub secrets: Vec<SecretReport>,
+}
+
+impl ConfigImport {
+    /// Secrets that must be re-entered after applying the import
+    pub fn missing_secrets(&self) -> impl Iterator<Item = &SecretReport> {
+        self.secrets
+            .iter()
+            .filter(|s| s.status == SecretStatus::Missing)
+    }
+}
+
+impl Settings {
+    /// Secrets currently set in this configuration
+    pub fn secrets(&self) -> Vec<SecretRef> {
+        let mut secrets = vec![SecretRef {
+            name: JWT_SECRET_NAME.to_string(),
+            path: "/auth/jwt/secret".to_string(),
+        }];
+        if self.auth.bootstrap.token.is_some() {
+            secrets.push(SecretRef {
+                name: BOOTSTRAP_TOKEN_SECRET_NAME.to_string(),
+                path: "/auth/bootstrap/token".to_string(),
+            });
+        }
+        if self.cluster.database_url.is_some() {
+            secrets.push(SecretRef {
+                name: CLUSTER_DATABASE_SECRET_NAME.to_string(),
+                path: "/cluster/database_url".to_string(),
+            });
+        }
+        for (i, provider) in self.providers.iter().enumerate() {
+            if provider.api_key.is_some() {
+                secrets.push(SecretRef {
+                    name: format!("providers.{}.api_key", provider.name),
+                    path: format!("/providers/{i}/api_key"),
+                });
+            }
+        }
+        for (i, tool) in self.tools.tools.iter().enumerate() {
+            if tool_api_key(tool).is_some() {
+                secrets.push(SecretRef {
+                    name: format!("tools.{}.api_key", tool.name),
+                    path: format!("/tools/tools/{i}/api_key"),
+                });
+            }
+        }
+        for (i, dns) in self.letsencrypt.dns.iter().enumerate() {
+            for (field, _) in dns.provider.secrets() {
+                secrets.push(SecretRef {
+                    name: format!("letsencrypt.dns.{i}.{field}"),
+                    path: format!("/letsencrypt/dns/{i}/{field}"),
+                });
+            }
+        }
+        if let TunnelTransport::Cloudflare { token: Some(_), .. } = &self.tlsforward.transport {
+            secrets.push(SecretRef {
+                name: TUNNEL_TOKEN_SECRET_NAME.to_string(),
+                path: "/tlsforward/transport/token".to_string(),
+            });
+        }
+        if let BlobDriverConfig::S3 { .. } = &self.blob_store.driver {
+            secrets.push(SecretRef {
+                name: BLOB_STORE_SECRET_NAME.to_string(),
+                path: "/blob_store/driver/secret_access_key".to_string(),
+            });
+        }
+        secrets
+    }
+
+    /// Current value of the secret called `name`
+    fn secret_value(&self, name: &str) -> Option<String> {
+        if name == JWT_SECRET_NAME {
+            return Some(self.auth.jwt.secret.clone());
+        }
+        if name == BOOTSTRAP_TOKEN_SECRET_NAME {
+            return self.auth.bootstrap.token.clone();
+        }
+        if name == CLUSTER_DATABASE_SECRET_NAME {
+            return self.cluster.database_url.clone();
+        }
+        if name == TUNNEL_TOKEN_SECRET_NAME {
+            return match &self.tlsforward.transport {
+                TunnelTransport::Cloudflare { token, .. } => token.clone(),
+                _ => None,
+            };
+        }
+        if name == BLOB_STORE_SECRET_NAME {
+            return match &self.blob_store.driver {
+                BlobDriverConfig::S3 {
+                    secret_access_key, ..
+                } => Some(secret_access_key.clone()),
+                BlobDriverConfig::Local { .. } => None,
+            };
+        }
+        if let Some(tool) = name
+            .strip_prefix("tools.")
+            .and_then(|n| n.strip_suffix(".api_key"))
+        {
+            return self
+                .tools
+                .tools
+                .iter()
+                .find(|t| t.name == tool)
+                .and_then(tool_api_key)
+                .map(String::from);
+        }
+        if let Some((index, field)) = name
+            .strip_prefix("letsencrypt.dns.")
+            .and_then(|n| n.split_once('.'))
+        {
+            let dns = self.letsencrypt.dns.get(index.parse::<usize>().ok()?)?;
+            return dns
+                .provider
+                .secrets()
+                .into_iter()
+                .find(|(name, _)| *name == field)
+                .map(|(_, value)| value.to_string());
+        }
+        let provider = name.strip_prefix("providers.")?.strip_suffix(".api_key")?;
+        self.providers
+            .iter()
+            .find(|p| p.name == provider)?
+            .api_key
+            .clone()
+    }
+
+    /// Export the configuration for another instance, replacing every
+    /// secret with a placeholder naming it
+    pub fn export(&self) -> ConfigExport {
+        let mut config = serde_json::to_value(self).expect("Settings should always serialize");
+        let secrets = self.secrets();
+        for secret in &secrets {
+            if let Some(value) = config.pointer_mut(&secret.path) {
+                *value = serde_json::Value::String(secret_placeholder(&secret.name));
+            }
+        }
+        ConfigExport {
+            gate_version: env!("CARGO_PKG_VERSION").to_string(),
+            config,
+            secrets,
+        }
+    }
+
+    /// Settings that differ from this configuration to `other`.
+    ///
+    /// Both sides are compared as exported, so secrets appear only as
+    /// placeholders; a secret whose value changed is reported at its path
+    /// without either value.
+    pub fn diff(&self, other: &Settings) -> Vec<ConfigChange> {
+        let before = self.export();
+        let after = other.export();
+        let mut changes = Vec::new();
+        collect_changes(
+            Some(&before.config),
+            Some(&after.config),
+            String::new(),
+            &mut changes,
+        );
+
+        for secret in before.secrets.iter().chain(&after.secrets) {
+            let old = self.secret_value(&secret.name);
+            let new = other.secret_value(&secret.name);
+            if old != new && !changes.iter().any(|c| c.path == secret.path) {
+                let placeholder = || serde_json::Value::String(secret_placeholder(&secret.name));
+                changes.push(ConfigChange {
+                    path: secret.path.clone(),
+                    before: old.map(|_| placeholder()),
+                    after: new.map(|_| placeholder()),
+                });
+            }
+        }
+        changes
+    }
+
+    /// Import an exported configuration, or a bare configuration document,
+    /// on top of `current`.
+    ///
+    /// Each placeholder is resolved from `provided`, then from the same
+    /// secret in `current`, and is otherwise left empty and reported as
+    /// missing. The result is validated like a hand-written document.
+    pub fn import(
+        document: &serde_json::Value,
+        current: &Settings,
+        provided: &std::collections::HashMap<String, String>,
+    ) -> ConfigImport {
+        let mut config = match document.get("config") {
+            Some(config) if document.get("secrets").is_some() => config.clone(),
+            _ => document.clone(),
+        };
+
+        let mut placeholders = Vec::new();
+        collect_placeholders(&config, String::new(), &mut placeholders);
+        let mut secrets = Vec::new();
+        for (path, name) in placeholders {
+            let (value, status) = if let Some(value) = provided.get(&name) {
+                (Some(value.clone()), SecretStatus::Provided)
+            } else if let Some(value) = current.secret_value(&name) {
+                (Some(value), SecretStatus::Retained)
+            } else {
+                (None, SecretStatus::Missing)
+            };
+            if let Some(slot) = config.pointer_mut(&path) {
+                *slot = value.map_or(serde_json::Value::Null, serde_json::Value::String);
+            }
+            secrets.push(SecretReport { name, path, status });
+        }
+
+        let text = config.to_string();
+        let issues = Settings::validate_json(&text);
+        let settings = if issues.iter().any(|i| i.severity == IssueSeverity::Error) {
+            None
+        } else {
+            serde_json::from_str(&text).ok()
+        };
+        ConfigImport {
+            settings,
+            issues,
+            secrets,
+        }
+    }
+}
+
+/// Find secret placeholders, returning their JSON pointers and names
+fn collect_placeholders(
+    value: &serde_json::Value,
+    path: String,
+    found: &mut Vec<(String, String)>,
+) {
+    use serde_json::Value;
+
+    match value {
+        Value::String(text) => {
+            if let Some(name) = placeholder_name(text) {
+                found.push((path, name.to_string()));
+            }
+        }
+        Value::Object(map) => {
+            for (key, value) in map {
+                let child = format!("{path}/{}", key.replace('~', "~0").replace('/', "~1"));
+                collect_placeholders(value, child, found);
+            }
+        }
+        Value::Array(items) => {
+            for (i, value) in items.iter().enumerate() {
+                collect_placeholders(value, format!("{path}/{i}"), found);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Find values that differ between `before` and `after`, descending into
+/// objects and arrays so each change names the setting that changed
+fn collect_changes(
+    before: Option<&serde_json::Value>,
+    after: Option<&serde_json::Value>,
+    path: String,
+    changes: &mut Vec<ConfigChange>,
+) {
+    use serde_json::Value;
+
+    match (before, after) {
+        (Some(Value::Object(old)), Some(Value::Object(new))) => {
+            let keys = old
+                .keys()
+                .chain(new.keys().filter(|k| !old.contains_key(*k)));
+            for key in keys {
+                let child = format!("{path}/{}", key.replace('~', "~0").replace('/', "~1"));
+                collect_changes(old.get(key), new.get(key), child, changes);
+            }
+        }
+        (Some(Value::Array(old)), Some(Value::Array(new))) => {
+            for i in 0..old.len().max(new.len()) {
+                collect_changes(old.get(i), new.get(i), format!("{path}/{i}"), changes);
+            }
+        }
+        (old, new) if old != new => changes.push(ConfigChange {
+            path,
+            before: old.cloned(),
+            after: new.cloned(),
+        }),
+        _ => {}
+    }
+}
+
+/// Report keys present in `raw` that did not survive a round trip through [`Settings`]
+fn collect_dropped_fields(
+    raw: &serde_json::Value,
+    normalized: &serde_json::Value,
+    path: String,
+    issues: &mut Vec<ConfigIssue>,
+) {
+    use serde_json::Value;
+
+    match (raw, normalized) {
+        (Value::Object(raw), Value::Object(normalized)) => {
+            for (key, value) in raw {
+                let child = format!("{path}/{}", key.replace('~', "~0").replace('/', "~1"));
+                match normalized.get(key) {
+                    Some(next) => collect_dropped_fields(value, next, child, issues),
+                    None if !value.is_null() => issues.push(ConfigIssue::at_path(
+                        IssueSeverity::Warning,
+                        child,
+                        format!("Unknown field '{key}' will be dropped on save"),
+                    )),
+                    None => {}
+                }
+            }
+        }
+        (Value::Array(raw), Value::Array(normalized)) => {
+            for (i, (value, next)) in raw.iter().zip(normalized).enumerate() {
+                collect_dropped_fields(value, next, format!("{path}/{i}"), issues);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_json_reports_syntax_position() {
+        let issues = Settings::validate_json("{\n  \"server\": {,\n}");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, IssueSeverity::Error);
+        assert_eq!(issues[0].line, Some(2));
+    }
+
+    #[test]
+    fn test_validate_json_reports_type_errors() {
+        let issues = Settings::validate_json(r#"{"server": {"port": "not a number"}}"#);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, IssueSeverity::Error);
+        assert!(issues[0].line.is_some());
+    }
+
+    #[test]
+    fn test_validate_json_warns_on_unknown_fields() {
+        let issues = Settings::validate_json(r#"{"server": {"colour": "blue"}, "extra": 1}"#);
+        let paths: Vec<_> = issues.iter().filter_map(|i| i.path.as_deref()).collect();
+        assert!(paths.contains(&"/server/colour"));
+        assert!(paths.contains(&"/extra"));
+        assert!(issues.iter().all(|i| i.severity == IssueSeverity::Warning));
+    }
+
+    #[test]
+    fn test_validate_json_rejects_duplicate_provider_names() {
+        let issues = Settings::validate_json(
+            r#"{"providers": [
+                {"name": "a", "provider": "anthropic", "base_url": "https://x"},
+                {"name": "a", "provider": "openai", "base_url": "https://y"}
+            ]}"#,
+        );
+        assert!(issues.iter().any(|i| i.severity == IssueSeverity::Error
+            && i.path.as_deref() == Some("/providers/1/name")));
+    }
+
+    #[test]
+    fn test_load_merges_includes_before_the_including_file() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("providers.d")).unwrap();
+        std::fs::write(
+            dir.path().join("providers.d/10-openai.yaml"),
+            "providers:\n  - name: openai\n    provider: openai\n    base_url: https://api.openai.com\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("providers.d/20-local.json"),
+            r#"{"providers": [{"name": "local", "provider": "custom", "base_url": "http://a"}]}"#,
+        )
+        .unwrap();
+        let main = dir.path().join("config.toml");
+        std::fs::write(
+            &main,
+            r#"include = ["providers.d"]
+
+[server]
+port = 4000
+
+[[providers]]
+name = "local"
+provider = "custom"
+base_url = "http://b"
+"#,
+        )
+        .unwrap();
+
+        let settings = Settings::load_from_file(&main).unwrap();
+        assert_eq!(settings.server.port, 4000);
+        let providers: Vec<_> = settings
+            .providers
+            .iter()
+            .map(|p| (p.name.as_str(), p.base_url.as_str()))
+            .collect();
+        assert_eq!(
+            providers,
+            [("openai", "https://api.openai.com"), ("local", "http://b")]
+        );
+    }
+
+    #[test]
+    fn test_load_from_env_reads_mounted_files_and_secrets() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_dir = dir.path().join("config");
+        std::fs::create_dir(&config_dir).unwrap();
+        std::fs::write(
+            config_dir.join("providers.yaml"),
+            "providers:\n  - name: openai\n    provider: openai\n    base_url: https://api.openai.com\n",
+        )
+        .unwrap();
+        let secret = dir.path().join("jwt-secret");
+        std::fs::write(&secret, "s3cret\n").unwrap();
+        let vars = HashMap::from([
+            ("GATE_SERVER__PORT".to_string(), "8080".to_string()),
+            (
+                "GATE_AUTH__JWT__SECRET_FILE".to_string(),
+                secret.display().to_string(),
+            ),
+        ]);
+
+        let settings = Settings::load_from_vars(Some(&config_dir), vars).unwrap();
+        assert_eq!(settings.server.port, 8080);
+        assert_eq!(settings.auth.jwt.secret, "s3cret");
+        assert_eq!(settings.providers[0].name, "openai");
+
+        let issues = settings.deployment_issues();
+        let errors: Vec<_> = issues
+            .iter()
+            .filter(|i| i.severity == IssueSeverity::Error)
+            .filter_map(|i| i.path.as_deref())
+            .collect();
+        assert_eq!(errors, ["/server/host"]);
+
+        let settings = Settings::load_from_vars(None, HashMap::new()).unwrap();
+        assert!(settings.auth.jwt.secret.is_empty());
+        assert!(
+            settings
+                .deployment_issues()
+                .iter()
+                .any(|i| i.path.as_deref() == Some("/auth/jwt/secret"))
+        );
+    }
+
+    #[test]
+    fn test_json_schema_describes_settings_without_secrets() {
+        let schema = Settings::json_schema();
+        let text = schema.to_string();
+        assert!(!text.contains(&Settings::default().auth.jwt.secret));
+
+        let port = &schema["$defs"]["ServerConfig"]["properties"]["port"];
+        assert_eq!(port["default"], 31145);
+        assert!(port["description"].is_string());
+        assert!(text.contains(r#""anthropic""#));
+        let secret = &schema["$defs"]["JwtConfig"]["properties"]["secret"];
+        assert_eq!(secret["writeOnly"], true);
+        assert!(secret.get("default").is_none());
+    }
+
+    fn settings_with_provider(api_key: Option<&str>) -> Settings {
+        let mut settings = Settings::default();
+        settings.providers.push(ProviderConfig {
+            name: "openai".to_string(),
+            provider: ProviderType::OpenAI,
+            base_url: "https://api.openai.com".to_string(),
+            api_key: api_key.map(String::from),
+            timeout_seconds: 30,
+            models: Vec::new(),
+            mock: None,
+            sign_requests: false,
+            node_id: None,
+        });
+        settings
+    }
+
+    #[test]
+    fn test_export_replaces_secrets_with_placeholders() {
+        let settings = settings_with_provider(Some("sk-live"));
+        let export = settings.export();
+        let text = export.config.to_string();
+        assert!(!text.contains("sk-live"));
+        assert!(!text.contains(&settings.auth.jwt.secret));
+        assert_eq!(
+            export.config["providers"][0]["api_key"],
+            secret_placeholder("providers.openai.api_key")
+        );
+        assert_eq!(export.secrets.len(), 2);
+    }
+
+    #[test]
+    fn test_diff_names_changed_settings_without_secret_values() {
+        let before = settings_with_provider(Some("sk-old"));
+        let mut after = before.clone();
+        after.providers[0].api_key = Some("sk-new".to_string());
+        after.server.port = 8080;
+
+        let changes = before.diff(&after);
+        let port = changes.iter().find(|c| c.path == "/server/port").unwrap();
+        assert_eq!(port.before, Some(json!(before.server.port)));
+        assert_eq!(port.after, Some(json!(8080)));
+        let key = changes
+            .iter()
+            .find(|c| c.path == "/providers/0/api_key")
+            .unwrap();
+        assert_eq!(
+            key.after,
+            Some(json!(secret_placeholder("providers.openai.api_key")))
+        );
+        assert!(!serde_json::to_string(&changes).unwrap().contains("sk-"));
+        assert_eq!(changes.len(), 2);
+        assert!(before.diff(&before).is_empty());
+    }
+
+    #[test]
+    fn test_tool_api_keys_are_secrets() {
+        let mut settings = Settings::default();
+        settings.tools = serde_json::from_value(json!({"tools": [
+            {"name": "search", "type": "http", "url": "http://localhost", "api_key": "tk-live"},
+            {"name": "fetch", "type": "web_fetch"}
+        ]}))
+        .unwrap();
+        let export = settings.export();
+        assert!(!export.config.to_string().contains("tk-live"));
+        assert_eq!(
+            export.config["tools"]["tools"][0]["api_key"],
+            secret_placeholder("tools.search.api_key")
+        );
+        assert_eq!(
+            settings.secret_value("tools.search.api_key").as_deref(),
+            Some("tk-live")
+        );
+    }
+
+    #[test]
+    fn test_import_reports_secrets_to_re_enter() {
+        let export = settings_with_provider(Some("sk-live")).export();
+        let document = serde_json::to_value(&export).unwrap();
+
+        let fresh = Settings::import(&document, &Settings::default(), &Default::default());
+        let settings = fresh.settings.as_ref().expect("import should be valid");
+        assert_eq!(settings.providers[0].api_key, None);
+        let missing: Vec<_> = fresh.missing_secrets().map(|s| s.name.as_str()).collect();
+        assert_eq!(missing, vec!["providers.openai.api_key"]);
+
+        let current = settings_with_provider(Some("sk-here"));
+        let provided = [("providers.openai.api_key".to_string(), "sk-new".to_string())];
+        let retained = Settings::import(&document, &current, &Default::default());
+        assert_eq!(
+            retained.settings.unwrap().providers[0].api_key.as_deref(),
+            Some("sk-here")
+        );
+        let supplied = Settings::import(&document, &current, &provided.into_iter().collect());
+        assert_eq!(
+            supplied.settings.unwrap().providers[0].api_key.as_deref(),
+            Some("sk-new")
+        );
+    }
+
+    #[test]
+    fn test_parameter_profiles_follow_assignments() {
+        let text = r#"{"routing": {
+            "profiles": {"intern": {"max_tokens": 1000}, "ci": {"allowed_tools": []}},
+            "profile_assignments": {"alice": "intern", "*": "ci", "bob": "missing"}
+        }}"#;
+        let issues = Settings::validate_json(text);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(
+            issues[0].path.as_deref(),
+            Some("/routing/profile_assignments/bob")
+        );
+
+        let settings: Settings = serde_json::from_str(text).unwrap();
+        let names = |subjects: &[&str], key: Option<&str>| -> Vec<String> {
+            settings
+                .routing
+                .profiles_for(subjects, key)
+                .into_iter()
+                .map(|p| p.name)
+                .collect()
+        };
+        assert_eq!(names(&["key-1", "alice"], Some("ci")), ["intern", "ci"]);
+        assert_eq!(names(&["carol"], None), ["ci"]);
+        assert!(names(&["bob"], None).is_empty());
+    }
+
+    #[test]
+    fn test_middleware_chain_is_validated() {
+        let text = r#"{"routing": {"middleware": [
+            {"kind": "cost_tracker"},
+            {"kind": "rate_limit", "requests_per_minute": 0},
+            {"kind": "compression"},
+            {"kind": "cost_tracker"}
+        ]}}"#;
+        let paths: Vec<_> = Settings::validate_json(text)
+            .into_iter()
+            .map(|issue| (issue.severity, issue.path.unwrap_or_default()))
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                (
+                    IssueSeverity::Error,
+                    "/routing/middleware/1/requests_per_minute".to_string()
+                ),
+                (IssueSeverity::Warning, "/routing/middleware/2".to_string()),
+                (IssueSeverity::Error, "/routing/middleware/3".to_string()),
+            ]
+        );
+
+        assert!(
+            !Settings::validate_json(r#"{"routing": {"middleware": [{"kind": "cache"}]}}"#)
+                .is_empty()
+        );
+        assert_eq!(
+            RoutingConfig::default().middleware_chain(),
+            MiddlewareConfig::default_chain()
+        );
+
+        // Stages limits depend on are kept
+        let routing = RoutingConfig {
+            middleware: Some(vec![MiddlewareConfig::MaxTokens]),
+            ..Default::default()
+        };
+        assert_eq!(
+            routing.middleware_chain(),
+            [
+                MiddlewareConfig::StreamSlots,
+                MiddlewareConfig::CostTracker,
+                MiddlewareConfig::MaxTokens
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cluster_accepts_only_a_sqlite_database() {
+        let issues = |url: &str| {
+            Settings::validate_json(&format!(
+                r#"{{"cluster": {{"enabled": true, "database_url": "{url}"}}}}"#
+            ))
+            .into_iter()
+            .map(|issue| (issue.severity, issue.path.unwrap_or_default()))
+            .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            issues("postgres://db/gate"),
+            [
+                (IssueSeverity::Error, "/cluster/database_url".to_string()),
+                (IssueSeverity::Warning, "/cluster".to_string()),
+            ]
+        );
+        assert_eq!(
+            issues("sqlite:///srv/gate/state.db"),
+            [
+                (IssueSeverity::Warning, "/cluster/database_url".to_string()),
+                (IssueSeverity::Warning, "/cluster".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_default_settings_validate_cleanly() {
+        let text = serde_json::to_string(&Settings::default()).unwrap();
+        assert!(Settings::validate_json(&text).is_empty());
     }
 }
@@ -26,6 +26,14 @@ impl DaemonActor {
                     let result = self.inner.update_config(&identity, *config).await;
                     let _ = reply.send(result);
                 }
+                DaemonRequest::RollbackConfig {
+                    identity,
+                    revision,
+                    reply,
+                } => {
+                    let result = self.inner.rollback_config(&identity, revision).await;
+                    let _ = reply.send(result);
+                }
                 DaemonRequest::Restart { identity, reply } => {
                     let result = self.inner.restart(&identity).await;
                     let _ = reply.send(result);
@@ -41,6 +49,9 @@ impl DaemonActor {
                 DaemonRequest::GetSettings { reply } => {
                     let _ = reply.send(self.inner.get_settings().await);
                 }
+                DaemonRequest::SubscribeSettings { reply } => {
+                    let _ = reply.send(self.inner.subscribe_settings());
+                }
                 DaemonRequest::GetBootstrapManager { reply } => {
                     let _ = reply.send(self.inner.get_bootstrap_manager());
                 }
@@ -56,6 +67,54 @@ impl DaemonActor {
                 DaemonRequest::GetStateBackend { reply } => {
                     let _ = reply.send(self.inner.get_state_backend());
                 }
+                DaemonRequest::GetFileStore { reply } => {
+                    let _ = reply.send(self.inner.get_file_store());
+                }
+                DaemonRequest::GetDocumentStore { reply } => {
+                    let _ = reply.send(self.inner.get_document_store());
+                }
+                DaemonRequest::GetExportStore { reply } => {
+                    let _ = reply.send(self.inner.get_export_store());
+                }
+                DaemonRequest::GetEvalStore { reply } => {
+                    let _ = reply.send(self.inner.get_eval_store());
+                }
+                DaemonRequest::GetConfigHistory { reply } => {
+                    let _ = reply.send(self.inner.get_config_history());
+                }
+                DaemonRequest::GetSigner { reply } => {
+                    let _ = reply.send(self.inner.get_signer());
+                }
+                DaemonRequest::GetNodeIdentity { reply } => {
+                    let _ = reply.send(self.inner.get_node_identity());
+                }
+                DaemonRequest::GetTlsManager { reply } => {
+                    let _ = reply.send(self.inner.get_tls_manager());
+                }
+                DaemonRequest::GetTlsForwardService { reply } => {
+                    let _ = reply.send(self.inner.get_tlsforward_service());
+                }
+                DaemonRequest::GetTunnelService { reply } => {
+                    let _ = reply.send(self.inner.get_tunnel_service());
+                }
+                DaemonRequest::GetRenewalLog { reply } => {
+                    let _ = reply.send(self.inner.get_renewal_log());
+                }
+                DaemonRequest::GetTrafficMeter { reply } => {
+                    let _ = reply.send(self.inner.get_traffic_meter());
+                }
+                DaemonRequest::GetJournal { reply } => {
+                    let _ = reply.send(self.inner.get_journal());
+                }
+                DaemonRequest::GetScheduler { reply } => {
+                    let _ = reply.send(self.inner.get_scheduler());
+                }
+                DaemonRequest::GetPairingService { reply } => {
+                    let _ = reply.send(self.inner.get_pairing_service());
+                }
+                DaemonRequest::GetLocalModels { reply } => {
+                    let _ = reply.send(self.inner.get_local_models());
+                }
                 DaemonRequest::GetUserCount { reply } => {
                     let _ = reply.send(self.inner.get_user_count());
                 }
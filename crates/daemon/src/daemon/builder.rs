@@ -1,14 +1,24 @@
 use crate::bootstrap::BootstrapTokenManager;
+use crate::config::TunnelTransport;
 use crate::daemon::{Daemon, actor::DaemonActor, inner::DaemonInner};
 use crate::error::Result;
-use crate::services::{AuthService, WebAuthnService};
+use crate::safe_mode::SafeMode;
+use crate::services::{
+    AuthService, CertificateFiles, ConfigHistory, DocumentStore, EvalStore, ExportStore, FileStore,
+    Journal, MessageSigner, NodeIdentity, TlsManager, TunnelService, WebAuthnService,
+    blob_store::open_blob_store, build_info, config_history, p2p,
+};
 use crate::{Settings, StateDir};
+use gate_core::BlobStore;
+use gate_core::state::SchemaMigrator;
 use gate_http::{
     middleware::WebAuthnConfig,
     services::{JwtConfig, JwtService},
 };
-use gate_sqlx::{SqliteStateBackend, SqliteWebAuthnBackend};
+use gate_sqlx::{ContentStore, SqliteStateBackend, SqliteWebAuthnBackend};
+use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::mpsc;
 
 #[derive(Default)]
@@ -17,6 +27,8 @@ pub struct DaemonBuilder {
     state_dir: Option<StateDir>,
     database_url: Option<String>,
     static_dir: Option<String>,
+    safe_mode: Option<SafeMode>,
+    read_only_config: bool,
 }
 
 impl DaemonBuilder {
@@ -44,6 +56,20 @@ impl DaemonBuilder {
         self
     }
 
+    /// Start in safe mode: no providers, local inference or background jobs,
+    /// and only the auth, config and status API
+    pub fn with_safe_mode(mut self, safe_mode: Option<SafeMode>) -> Self {
+        self.safe_mode = safe_mode;
+        self
+    }
+
+    /// Settings come with the deployment: changes made at runtime apply but
+    /// are not saved
+    pub fn with_read_only_config(mut self, read_only: bool) -> Self {
+        self.read_only_config = read_only;
+        self
+    }
+
     /// Build the JWT service
     fn build_jwt_service(settings: &Settings) -> Arc<JwtService> {
         let jwt_config = JwtConfig {
@@ -77,24 +103,49 @@ impl DaemonBuilder {
             }
         };
 
-        // Get database URL
-        let database_url = self.database_url.unwrap_or_else(|| {
-            format!(
-                "sqlite://{}",
-                state_dir.data_dir().join("gate.db").display()
-            )
-        });
+        // Get database URL; replicas share theirs
+        let database_url = self
+            .database_url
+            .or_else(|| {
+                let cluster = &settings.cluster;
+                cluster
+                    .enabled
+                    .then(|| cluster.database_url.clone())
+                    .flatten()
+            })
+            .unwrap_or_else(|| state_dir.database_url());
 
-        // Create database backend
+        // Create database backend and bring its schema up to date. Uploads,
+        // backups and long body strings, once each, go to the blob store.
+        let blob_config = &settings.blob_store;
+        let blob_store = open_blob_store(&blob_config.driver, state_dir.data_dir())?;
+        let content_store = ContentStore::new(
+            blob_store.clone(),
+            blob_config.dedup.then_some(blob_config.dedup_min_bytes),
+        );
         let state_backend = Arc::new(
-            SqliteStateBackend::new(&database_url)
+            SqliteStateBackend::connect(&database_url)
                 .await
-                .map_err(|e| crate::error::DaemonError::Database(e.to_string()))?,
+                .map_err(|e| crate::error::DaemonError::Database(e.to_string()))?
+                .with_content_store(content_store),
         );
+        migrate_with_backup(
+            state_backend.as_ref(),
+            blob_store.as_ref(),
+            &state_dir.dir_for("staging"),
+        )
+        .await?;
         let webauthn_backend = Arc::new(SqliteWebAuthnBackend::new(state_backend.pool().clone()));
 
         // Check bootstrap and count users
-        let bootstrap_manager = Arc::new(BootstrapTokenManager::new(webauthn_backend.clone()));
+        let bootstrap_manager = Arc::new(
+            BootstrapTokenManager::new(webauthn_backend.clone())
+                .with_ttl(Duration::from_secs(
+                    settings.auth.bootstrap.token_ttl_seconds,
+                ))
+                .with_attempt_limit(settings.auth.bootstrap.max_attempts_per_minute)
+                .with_provisioned_token(settings.auth.bootstrap.token.clone()),
+        );
 
         // Count existing users
         let credentials = webauthn_backend.list_all_credentials().await.map_err(|e| {
@@ -107,11 +158,11 @@ impl DaemonBuilder {
             .await
             .map_err(|e| crate::error::DaemonError::ConfigError(e.to_string()))?
         {
-            let token = bootstrap_manager
+            bootstrap_manager
                 .generate_token()
                 .await
                 .map_err(|e| crate::error::DaemonError::ConfigError(e.to_string()))?;
-            info!("Generated bootstrap token: {}", token);
+            info!("Generated bootstrap token");
         }
 
         // Build services
@@ -160,6 +211,59 @@ impl DaemonBuilder {
         // TODO: Setup TLS forward service if enabled
         let tlsforward_service = None;
 
+        // Tunnels other than the relay are child processes kept running here
+        let tunnel_service = if settings.tlsforward.enabled
+            && settings.tlsforward.transport != TunnelTransport::Relay
+        {
+            let tunnel = TunnelService::start(
+                settings.tlsforward.clone(),
+                &settings.server.host,
+                settings.server.port,
+            )
+            .map_err(|e| crate::error::DaemonError::ConfigError(e.to_string()))?;
+            Some(tunnel)
+        } else {
+            None
+        };
+
+        // Replay the job journal, recovering work interrupted by a crash
+        let journal = Journal::open(state_dir.dir_for("journal")).await?;
+
+        // The configuration started with is the first revision, or a new one
+        // when the file was edited while the daemon was down
+        let config_history = ConfigHistory::new(state_dir.dir_for("config-history"));
+        if let Err(e) = config_history
+            .record(config_history::SYSTEM_AUTHOR, &settings, None)
+            .await
+        {
+            warn!("Failed to record startup config revision: {}", e);
+        }
+
+        // The node's identity key signs its attestation, outgoing webhooks
+        // and upstream Gate requests
+        let node_key = p2p::load_or_create_p2p_secret_key(&state_dir.iroh_secret_key_path())
+            .await
+            .map_err(|e| crate::error::DaemonError::ConfigError(e.to_string()))?;
+        let signer = Arc::new(MessageSigner::new(node_key));
+        info!("Signing outgoing requests as key {}", signer.key_id());
+        let frontend_hash = self.static_dir.as_ref().and_then(|dir| {
+            build_info::frontend_bundle_hash(Path::new(dir))
+                .inspect_err(|e| warn!("Failed to hash frontend bundle in {}: {}", dir, e))
+                .ok()
+        });
+        let node_identity = NodeIdentity::new(signer, state_dir.dir_for("certificates"))
+            .with_frontend_hash(frontend_hash);
+
+        // User-supplied certificates are validated before anything is served
+        let tls_manager = match CertificateFiles::resolve(&settings.certificates.source)? {
+            Some(files) => Some(
+                TlsManager::from_files(&state_dir.data_dir(), &files)
+                    .await
+                    .map_err(|e| crate::error::DaemonError::ConfigError(e.to_string()))?,
+            ),
+            None => None,
+        };
+
         // Create DaemonInner
         let daemon_inner = DaemonInner::new(
             settings,
@@ -169,9 +273,20 @@ impl DaemonBuilder {
             bootstrap_manager,
             webauthn_service,
             tlsforward_service,
+            tls_manager,
+            FileStore::new(blob_store),
+            DocumentStore::new(state_dir.dir_for("documents")),
+            ExportStore::new(state_dir.dir_for("exports")),
+            EvalStore::new(state_dir.dir_for("evals")),
+            config_history,
+            node_identity,
+            journal,
             user_count,
         )
-        .await;
+        .await
+        .with_safe_mode(self.safe_mode)
+        .with_read_only_config(self.read_only_config)
+        .with_tunnel_service(tunnel_service);
 
         // Create channel for actor communication
         let (tx, rx) = mpsc::channel(100);
@@ -182,7 +297,60 @@ impl DaemonBuilder {
             actor.run().await;
         });
 
-        // Return daemon handle with static_dir
-        Ok(Daemon::new(tx, self.static_dir))
+        // Return daemon handle with static_dir and state_dir
+        Ok(Daemon::new(tx, self.static_dir).with_state_dir(state_dir))
     }
 }
+
+/// Apply pending schema migrations, backing up an existing database to
+/// `backups/` in the blob store first. The copy is taken in `staging_dir`.
+/// Refuses to start against a database migrated by a newer release.
+pub async fn migrate_with_backup(
+    backend: &impl SchemaMigrator,
+    backups: &dyn BlobStore,
+    staging_dir: &Path,
+) -> Result<()> {
+    let status = backend
+        .status()
+        .await
+        .map_err(|e| crate::error::DaemonError::Database(e.to_string()))?;
+    if !status.unknown.is_empty() {
+        return Err(crate::error::DaemonError::Database(format!(
+            "Database has migrations {:?} unknown to this release; upgrade gate or restore a backup",
+            status.unknown
+        )));
+    }
+    if status.pending.is_empty() {
+        return Ok(());
+    }
+
+    if !status.is_fresh() {
+        let name = format!(
+            "gate-{}-v{}.db",
+            chrono::Utc::now().format("%Y%m%dT%H%M%SZ"),
+            status.current
+        );
+        tokio::fs::create_dir_all(staging_dir).await?;
+        let staged = staging_dir.join(&name);
+        backend
+            .backup(&staged.to_string_lossy())
+            .await
+            .map_err(|e| crate::error::DaemonError::Database(e.to_string()))?;
+        let key = format!("backups/{name}");
+        let stored = backups.put(&key, tokio::fs::read(&staged).await?).await;
+        tokio::fs::remove_file(&staged).await?;
+        stored?;
+        info!("Backed up database to {key}");
+    }
+
+    let applied = backend
+        .migrate_up()
+        .await
+        .map_err(|e| crate::error::DaemonError::Database(e.to_string()))?;
+    info!(
+        "Applied {} database migration(s), now at version {}",
+        applied.len(),
+        status.latest
+    );
+    Ok(())
+}
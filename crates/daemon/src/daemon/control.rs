@@ -0,0 +1,416 @@
+//! Local control socket
+//!
+//! Exposes the daemon actor over JSON-RPC 2.0 on a Unix socket, one request
+//! per line, so system tooling and the GUI can check status, reload or
+//! change the configuration, manage providers and restart or stop the
+//! daemon without going through the HTTP listener. The socket is bound in a
+//! directory only the daemon's user can enter and moved into place once it
+//! is readable and writable by that user alone, so no one else can connect
+//! in between. Callers act with the system identity.
+//!
+//! ```text
+//! {"jsonrpc":"2.0","id":1,"method":"status"}
+//! {"jsonrpc":"2.0","id":1,"result":{"running":true,...}}
+//! ```
+
+use super::Daemon;
+use crate::Settings;
+use crate::config::{ConfigSource, ProviderConfig};
+use crate::error::{DaemonError, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value as JsonValue, json};
+use std::path::Path;
+use std::sync::Arc;
+#[cfg(unix)]
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::Notify;
+
+/// Methods served over the socket
+pub const METHODS: [&str; 11] = [
+    "status",
+    "config.get",
+    "config.update",
+    "config.reload",
+    "providers.list",
+    "providers.upsert",
+    "providers.remove",
+    "tasks.list",
+    "tasks.run",
+    "restart",
+    "shutdown",
+];
+
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const SERVER_ERROR: i64 = -32000;
+
+#[derive(Debug, Deserialize)]
+struct RpcRequest {
+    #[serde(default)]
+    id: JsonValue,
+    method: String,
+    #[serde(default)]
+    params: JsonValue,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RpcResponse {
+    jsonrpc: String,
+    id: JsonValue,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    result: Option<JsonValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<RpcError>,
+}
+
+impl RpcResponse {
+    fn new(id: JsonValue, outcome: std::result::Result<JsonValue, RpcError>) -> Self {
+        let (result, error) = match outcome {
+            Ok(result) => (Some(result), None),
+            Err(error) => (None, Some(error)),
+        };
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result,
+            error,
+        }
+    }
+}
+
+impl From<DaemonError> for RpcError {
+    fn from(e: DaemonError) -> Self {
+        Self {
+            code: SERVER_ERROR,
+            message: e.to_string(),
+        }
+    }
+}
+
+fn invalid_params(e: impl std::fmt::Display) -> RpcError {
+    RpcError {
+        code: INVALID_PARAMS,
+        message: format!("Invalid params: {e}"),
+    }
+}
+
+/// Provider as listed over the socket, without its API key
+#[derive(Debug, Serialize)]
+struct ProviderSummary<'a> {
+    name: &'a str,
+    provider: &'a crate::config::ProviderType,
+    base_url: &'a str,
+    has_api_key: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct NameParams {
+    name: String,
+}
+
+/// Serves the control socket for one daemon
+pub struct ControlServer {
+    daemon: Daemon,
+    /// Where `config.reload` reads the configuration from
+    config_source: ConfigSource,
+    /// Notified when `shutdown` is called, so the process can stop gracefully
+    shutdown: Arc<Notify>,
+}
+
+impl ControlServer {
+    pub fn new(daemon: Daemon, config_source: ConfigSource, shutdown: Arc<Notify>) -> Self {
+        Self {
+            daemon: daemon.system_identity(),
+            config_source,
+            shutdown,
+        }
+    }
+
+    /// Accept connections on `socket_path` until the daemon stops
+    #[cfg(unix)]
+    pub async fn serve(self, socket_path: &Path) -> Result<()> {
+        let listener = bind_private(socket_path).await?;
+        info!("Control socket listening on {}", socket_path.display());
+
+        let server = Arc::new(self);
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let server = server.clone();
+            tokio::spawn(async move {
+                if let Err(e) = server.handle_connection(stream).await {
+                    debug!("Control connection closed: {e}");
+                }
+            });
+        }
+    }
+
+    #[cfg(not(unix))]
+    pub async fn serve(self, socket_path: &Path) -> Result<()> {
+        Err(DaemonError::ServiceUnavailable(format!(
+            "Control socket {} needs Unix domain sockets",
+            socket_path.display()
+        )))
+    }
+
+    #[cfg(unix)]
+    async fn handle_connection(&self, stream: tokio::net::UnixStream) -> Result<()> {
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let response = self.handle_line(&line).await;
+            let mut bytes = serde_json::to_vec(&response)?;
+            bytes.push(b'\n');
+            writer.write_all(&bytes).await?;
+        }
+        Ok(())
+    }
+
+    async fn handle_line(&self, line: &str) -> RpcResponse {
+        match serde_json::from_str::<RpcRequest>(line) {
+            Ok(request) => {
+                let outcome = self.dispatch(&request.method, request.params).await;
+                RpcResponse::new(request.id, outcome)
+            }
+            Err(e) => RpcResponse::new(
+                JsonValue::Null,
+                Err(RpcError {
+                    code: PARSE_ERROR,
+                    message: format!("Parse error: {e}"),
+                }),
+            ),
+        }
+    }
+
+    async fn dispatch(
+        &self,
+        method: &str,
+        params: JsonValue,
+    ) -> std::result::Result<JsonValue, RpcError> {
+        debug!("Control request {method}");
+        let result = match method {
+            "status" => json!(self.daemon.status().await?),
+            "config.get" => json!(self.daemon.get_settings().await?.export()),
+            "config.update" => {
+                let settings: Settings = serde_json::from_value(params).map_err(invalid_params)?;
+                self.daemon.update_config(settings).await?;
+                json!({ "updated": true })
+            }
+            "config.reload" => {
+                let settings = self
+                    .config_source
+                    .load()
+                    .map_err(|e| DaemonError::ConfigError(e.to_string()))?;
+                self.daemon.update_config(settings).await?;
+                info!("Reloaded configuration from {}", self.config_source);
+                json!({ "reloaded": self.config_source.to_string() })
+            }
+            "providers.list" => {
+                let settings = self.daemon.get_settings().await?;
+                let providers: Vec<_> = settings
+                    .providers
+                    .iter()
+                    .map(|p| ProviderSummary {
+                        name: &p.name,
+                        provider: &p.provider,
+                        base_url: &p.base_url,
+                        has_api_key: p.api_key.is_some(),
+                    })
+                    .collect();
+                json!(providers)
+            }
+            "providers.upsert" => {
+                let provider: ProviderConfig =
+                    serde_json::from_value(params).map_err(invalid_params)?;
+                let mut settings = self.daemon.get_settings().await?;
+                let name = provider.name.clone();
+                match settings.providers.iter_mut().find(|p| p.name == name) {
+                    Some(existing) => *existing = provider,
+                    None => settings.providers.push(provider),
+                }
+                self.daemon.update_config(settings).await?;
+                json!({ "name": name })
+            }
+            "providers.remove" => {
+                let NameParams { name } = serde_json::from_value(params).map_err(invalid_params)?;
+                let mut settings = self.daemon.get_settings().await?;
+                let before = settings.providers.len();
+                settings.providers.retain(|p| p.name != name);
+                let removed = settings.providers.len() < before;
+                if removed {
+                    self.daemon.update_config(settings).await?;
+                }
+                json!({ "removed": removed })
+            }
+            "tasks.list" => json!(self.daemon.get_scheduler().await?.list().await),
+            "tasks.run" => {
+                let NameParams { name } = serde_json::from_value(params).map_err(invalid_params)?;
+                json!(self.daemon.get_scheduler().await?.trigger(&name).await?)
+            }
+            "restart" => {
+                self.daemon.restart().await?;
+                json!({ "restarted": true })
+            }
+            "shutdown" => {
+                self.shutdown.notify_one();
+                json!({ "shutting_down": true })
+            }
+            _ => {
+                return Err(RpcError {
+                    code: METHOD_NOT_FOUND,
+                    message: format!(
+                        "Unknown method {method}; expected one of {}",
+                        METHODS.join(", ")
+                    ),
+                });
+            }
+        };
+        Ok(result)
+    }
+}
+
+/// Call `method` on the control socket at `socket_path`
+#[cfg(unix)]
+pub async fn call(socket_path: &Path, method: &str, params: JsonValue) -> Result<JsonValue> {
+    let stream = tokio::net::UnixStream::connect(socket_path).await?;
+    let (reader, mut writer) = stream.into_split();
+
+    let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+    let mut bytes = serde_json::to_vec(&request)?;
+    bytes.push(b'\n');
+    writer.write_all(&bytes).await?;
+
+    let line = BufReader::new(reader)
+        .lines()
+        .next_line()
+        .await?
+        .ok_or_else(|| DaemonError::ServiceUnavailable("Control socket closed".into()))?;
+    let response: RpcResponse = serde_json::from_str(&line)?;
+    match (response.result, response.error) {
+        (_, Some(error)) => Err(DaemonError::InvalidState(format!(
+            "{} (code {})",
+            error.message, error.code
+        ))),
+        (result, None) => Ok(result.unwrap_or(JsonValue::Null)),
+    }
+}
+
+/// Listen on `socket_path` with a socket only the daemon's user can use.
+/// It is bound in a private directory beside `socket_path`, so it is never
+/// reachable before its permissions are narrowed, then renamed into place.
+#[cfg(unix)]
+async fn bind_private(socket_path: &Path) -> Result<tokio::net::UnixListener> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let parent = socket_path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    tokio::fs::create_dir_all(parent).await?;
+    let name = socket_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let private = parent.join(format!(".{name}.{}", std::process::id()));
+    if tokio::fs::symlink_metadata(&private).await.is_ok() {
+        tokio::fs::remove_dir_all(&private).await?;
+    }
+    tokio::fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&private)
+        .await?;
+
+    let staged = private.join("socket");
+    let bound = async {
+        let listener = tokio::net::UnixListener::bind(&staged)?;
+        tokio::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o600)).await?;
+        // Replaces a socket left behind by a previous run
+        tokio::fs::rename(&staged, socket_path).await?;
+        Ok::<_, std::io::Error>(listener)
+    }
+    .await;
+    if let Err(e) = tokio::fs::remove_dir_all(&private).await {
+        warn!("Failed to remove {}: {e}", private.display());
+    }
+    Ok(bound?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daemon::rpc::DaemonRequest;
+    use tokio::sync::mpsc;
+
+    fn server() -> (ControlServer, mpsc::Receiver<DaemonRequest>) {
+        let (tx, rx) = mpsc::channel(1);
+        let server = ControlServer::new(
+            Daemon::new(tx, None),
+            ConfigSource::File("config.json".into()),
+            Arc::new(Notify::new()),
+        );
+        (server, rx)
+    }
+
+    #[tokio::test]
+    async fn test_malformed_and_unknown_requests_get_errors() {
+        let (server, _rx) = server();
+
+        let response = server.handle_line("not json").await;
+        assert_eq!(response.error.unwrap().code, PARSE_ERROR);
+
+        let response = server
+            .handle_line(r#"{"jsonrpc":"2.0","id":7,"method":"reboot"}"#)
+            .await;
+        assert_eq!(response.id, json!(7));
+        assert_eq!(response.error.unwrap().code, METHOD_NOT_FOUND);
+
+        let response = server
+            .handle_line(r#"{"jsonrpc":"2.0","id":8,"method":"providers.remove","params":{}}"#)
+            .await;
+        assert_eq!(response.error.unwrap().code, INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_notifies_the_process() {
+        let (server, _rx) = server();
+        let shutdown = server.shutdown.clone();
+        let response = server
+            .handle_line(r#"{"jsonrpc":"2.0","id":1,"method":"shutdown"}"#)
+            .await;
+        assert!(response.error.is_none());
+        tokio::time::timeout(std::time::Duration::from_secs(1), shutdown.notified())
+            .await
+            .expect("shutdown should be notified");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_socket_is_private_and_replaces_a_stale_one() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let socket_path = dir.path().join("control.sock");
+        drop(tokio::net::UnixListener::bind(&socket_path).unwrap());
+
+        let listener = bind_private(&socket_path).await.unwrap();
+        let mode = std::fs::metadata(&socket_path)
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+        // Only the socket is left behind
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        let _client = tokio::net::UnixStream::connect(&socket_path).await.unwrap();
+        listener.accept().await.unwrap();
+    }
+}
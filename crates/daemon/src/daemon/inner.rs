@@ -1,19 +1,29 @@
 use crate::bootstrap::BootstrapTokenManager;
 use crate::error::Result;
 use crate::permissions::{LocalIdentity, LocalPermissionManager};
-use crate::services::{AuthService, TlsForwardService, WebAuthnService};
-use crate::types::{DaemonStatus, TlsForwardStatus};
+use crate::safe_mode::SafeMode;
+use crate::services::{
+    AuthService, ConfigHistory, DocumentStore, EvalStore, ExportStore, FileStore, Journal,
+    LocalModels, MessageSigner, NodeIdentity, PairingService, RenewalLog, Scheduler,
+    TlsForwardService, TlsManager, TunnelService, WebAuthnService,
+};
+use crate::sinks::catgrad_sink;
+use crate::types::{DaemonStatus, TlsForwardStatus, TransportKind};
 use crate::{Settings, state_dir::StateDir};
 use gate_core::StateBackend;
 use gate_core::access::{
     Action, ObjectId, ObjectIdentity, ObjectKind, Permissions, TargetNamespace,
 };
+use gate_core::router::buffers::BufferBudget;
 use gate_http::services::JwtService;
+use gate_tlsforward::client::TrafficMeter;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{RwLock, watch};
 
 pub struct DaemonInner {
     settings: Arc<RwLock<Settings>>,
+    /// Settings as last applied, for services that follow changes
+    settings_tx: watch::Sender<Settings>,
     state_backend: Arc<dyn StateBackend>,
     permission_manager: Arc<LocalPermissionManager>,
     auth_service: Arc<AuthService>,
@@ -21,7 +31,27 @@ pub struct DaemonInner {
     bootstrap_manager: Arc<BootstrapTokenManager>,
     webauthn_service: Option<Arc<WebAuthnService>>,
     tlsforward_service: Option<Arc<TlsForwardService>>,
+    /// Set when exposed through an SSH tunnel or other forwarder instead of
+    /// the relay
+    tunnel_service: Option<Arc<TunnelService>>,
+    /// Set when serving user-supplied certificates
+    tls_manager: Option<TlsManager>,
+    renewal_log: RenewalLog,
+    /// Counts and caps traffic forwarded through the relay
+    traffic_meter: TrafficMeter,
+    file_store: FileStore,
+    document_store: DocumentStore,
+    export_store: ExportStore,
+    eval_store: EvalStore,
+    config_history: ConfigHistory,
+    node_identity: NodeIdentity,
+    journal: Journal,
+    scheduler: Scheduler,
+    pairing_service: PairingService,
+    local_models: Option<Arc<LocalModels>>,
     user_count: usize,
+    safe_mode: Option<SafeMode>,
+    read_only_config: bool,
 }
 
 impl DaemonInner {
@@ -34,12 +64,28 @@ impl DaemonInner {
         bootstrap_manager: Arc<BootstrapTokenManager>,
         webauthn_service: Option<Arc<WebAuthnService>>,
         tlsforward_service: Option<Arc<TlsForwardService>>,
+        tls_manager: Option<TlsManager>,
+        file_store: FileStore,
+        document_store: DocumentStore,
+        export_store: ExportStore,
+        eval_store: EvalStore,
+        config_history: ConfigHistory,
+        node_identity: NodeIdentity,
+        journal: Journal,
         user_count: usize,
     ) -> Self {
         let permission_manager = Arc::new(LocalPermissionManager::new(state_backend.clone()));
+        permission_manager.set_audit(settings.permission_audit.clone());
+        let local_models = settings
+            .local_inference
+            .as_ref()
+            .map(|config| Arc::new(LocalModels::new(config, catgrad_sink::load_model)));
+        let traffic_meter = TrafficMeter::new(settings.tlsforward.bandwidth_limits());
+        let (settings_tx, _) = watch::channel(settings.clone());
 
         Self {
             settings: Arc::new(RwLock::new(settings)),
+            settings_tx,
             state_backend,
             permission_manager,
             auth_service,
@@ -47,10 +93,44 @@ impl DaemonInner {
             bootstrap_manager,
             webauthn_service,
             tlsforward_service,
+            tunnel_service: None,
+            tls_manager,
+            renewal_log: RenewalLog::default(),
+            traffic_meter,
+            file_store,
+            document_store,
+            export_store,
+            eval_store,
+            config_history,
+            node_identity,
+            journal,
+            scheduler: Scheduler::new(),
+            pairing_service: PairingService::new(),
+            local_models,
             user_count,
+            safe_mode: None,
+            read_only_config: false,
         }
     }
 
+    /// Run in safe mode for the given reason
+    pub fn with_safe_mode(mut self, safe_mode: Option<SafeMode>) -> Self {
+        self.safe_mode = safe_mode;
+        self
+    }
+
+    /// Apply configuration changes without saving them to the config file
+    pub fn with_read_only_config(mut self, read_only: bool) -> Self {
+        self.read_only_config = read_only;
+        self
+    }
+
+    /// Expose the node through the given tunnel
+    pub fn with_tunnel_service(mut self, tunnel_service: Option<Arc<TunnelService>>) -> Self {
+        self.tunnel_service = tunnel_service;
+        self
+    }
+
     pub async fn status(&self) -> DaemonStatus {
         let settings = self.settings.read().await;
         DaemonStatus {
@@ -58,9 +138,15 @@ impl DaemonInner {
             listen_address: format!("{}:{}", settings.server.host, settings.server.port),
             provider_count: settings.providers.len(),
             user_count: self.user_count,
-            tlsforward_enabled: self.tlsforward_service.is_some(),
+            tlsforward_enabled: self.tlsforward_service.is_some() || self.tunnel_service.is_some(),
             tlsforward_status: self.get_tlsforward_status().await,
             needs_bootstrap: self.user_count == 0,
+            safe_mode: self.safe_mode.clone(),
+            local_models: self.local_models.as_ref().map(|models| models.status()),
+            tlsforward_traffic: settings
+                .tlsforward
+                .enabled
+                .then(|| self.traffic_meter.snapshot()),
         }
     }
 
@@ -68,6 +154,30 @@ impl DaemonInner {
         &mut self,
         identity: &LocalIdentity,
         config: Settings,
+    ) -> Result<()> {
+        self.apply_config(identity, config, None).await
+    }
+
+    /// Re-apply a stored revision of the configuration, returning it, or
+    /// `None` when the revision is no longer kept
+    pub async fn rollback_config(
+        &mut self,
+        identity: &LocalIdentity,
+        revision: u64,
+    ) -> Result<Option<Settings>> {
+        let Some(stored) = self.config_history.get(revision).await? else {
+            return Ok(None);
+        };
+        self.apply_config(identity, stored.settings.clone(), Some(revision))
+            .await?;
+        Ok(Some(stored.settings))
+    }
+
+    async fn apply_config(
+        &mut self,
+        identity: &LocalIdentity,
+        config: Settings,
+        rollback_of: Option<u64>,
     ) -> Result<()> {
         let config_object = ObjectIdentity {
             namespace: TargetNamespace::System,
@@ -79,10 +189,20 @@ impl DaemonInner {
             .check(identity, Action::Write, &config_object)
             .await?;
 
+        // Recorded before applying, so a revision is never lost to a failed save
+        if let Err(e) = self
+            .config_history
+            .record(&identity.id, &config, rollback_of)
+            .await
+        {
+            tracing::warn!("Failed to record config revision: {}", e);
+        }
         *self.settings.write().await = config;
 
         // Persist to default config path
-        if let Ok(state_dir) = StateDir::new().await {
+        if self.read_only_config {
+            tracing::info!("Settings come from the deployment; changes are not saved");
+        } else if let Ok(state_dir) = StateDir::new().await {
             let path = state_dir.config_path();
             if let Err(e) = self.settings.read().await.save_to_file(&path).await {
                 tracing::warn!("Failed to save settings to {}: {}", path.display(), e);
@@ -139,6 +259,70 @@ impl DaemonInner {
         self.state_backend.clone()
     }
 
+    pub fn get_file_store(&self) -> FileStore {
+        self.file_store.clone()
+    }
+
+    pub fn get_document_store(&self) -> DocumentStore {
+        self.document_store.clone()
+    }
+
+    pub fn get_export_store(&self) -> ExportStore {
+        self.export_store.clone()
+    }
+
+    pub fn get_eval_store(&self) -> EvalStore {
+        self.eval_store.clone()
+    }
+
+    pub fn get_config_history(&self) -> ConfigHistory {
+        self.config_history.clone()
+    }
+
+    pub fn get_signer(&self) -> Arc<MessageSigner> {
+        self.node_identity.signer()
+    }
+
+    pub fn get_node_identity(&self) -> NodeIdentity {
+        self.node_identity.clone()
+    }
+
+    pub fn get_tls_manager(&self) -> Option<TlsManager> {
+        self.tls_manager.clone()
+    }
+
+    pub fn get_tlsforward_service(&self) -> Option<Arc<TlsForwardService>> {
+        self.tlsforward_service.clone()
+    }
+
+    pub fn get_tunnel_service(&self) -> Option<Arc<TunnelService>> {
+        self.tunnel_service.clone()
+    }
+
+    pub fn get_renewal_log(&self) -> RenewalLog {
+        self.renewal_log.clone()
+    }
+
+    pub fn get_traffic_meter(&self) -> TrafficMeter {
+        self.traffic_meter.clone()
+    }
+
+    pub fn get_journal(&self) -> Journal {
+        self.journal.clone()
+    }
+
+    pub fn get_scheduler(&self) -> Scheduler {
+        self.scheduler.clone()
+    }
+
+    pub fn get_pairing_service(&self) -> PairingService {
+        self.pairing_service.clone()
+    }
+
+    pub fn get_local_models(&self) -> Option<Arc<LocalModels>> {
+        self.local_models.clone()
+    }
+
     pub fn get_bootstrap_manager(&self) -> Arc<BootstrapTokenManager> {
         self.bootstrap_manager.clone()
     }
@@ -159,6 +343,11 @@ impl DaemonInner {
         self.settings.read().await.clone()
     }
 
+    /// Settings from now on, updated each time a configuration is applied
+    pub fn subscribe_settings(&self) -> watch::Receiver<Settings> {
+        self.settings_tx.subscribe()
+    }
+
     pub async fn get_config(&self, identity: &LocalIdentity) -> Result<Settings> {
         // Check permission to read configuration
         let config_object = ObjectIdentity {
@@ -186,15 +375,23 @@ impl DaemonInner {
                     assigned_domain, ..
                 } => TlsForwardStatus::Connected {
                     domain: assigned_domain,
+                    transport: TransportKind::Relay,
                 },
                 crate::services::TlsForwardState::Error(error) => TlsForwardStatus::Error(error),
             }
         } else {
-            TlsForwardStatus::Disabled
+            self.tunnel_service
+                .as_ref()
+                .map_or(TlsForwardStatus::Disabled, |tunnel| tunnel.status())
         }
     }
 
     async fn reload_services(&mut self) -> Result<()> {
+        let settings = self.settings.read().await.clone();
+        self.permission_manager
+            .set_audit(settings.permission_audit.clone());
+        BufferBudget::shared().set_limits(settings.memory.buffer_limits());
+        self.settings_tx.send_replace(settings);
         // TODO: Implement service reloading logic
         Ok(())
     }
@@ -203,6 +400,9 @@ impl DaemonInner {
         if let Some(service) = &self.tlsforward_service {
             service.shutdown().await;
         }
+        if let Some(tunnel) = &self.tunnel_service {
+            tunnel.shutdown();
+        }
         Ok(())
     }
 
@@ -1,30 +1,34 @@
 pub mod actor;
 pub mod builder;
+pub mod control;
 pub mod inner;
 pub mod rpc;
 pub mod server;
 
 pub use builder::DaemonBuilder;
+use gate_core::router::buffers::BufferBudget;
 use gate_core::router::{SinkIndex, SinkRegistry};
 
 use self::rpc::DaemonRequest;
-use crate::Settings;
-use crate::bootstrap::BootstrapTokenManager;
+use crate::bootstrap::{BootstrapPairing, BootstrapTokenManager, cert_fingerprint};
 use crate::error::{DaemonError, Result};
 use crate::permissions::LocalContext;
 use crate::permissions::LocalIdentity;
 use crate::services::WebAuthnService;
-use crate::types::DaemonStatus;
+use crate::types::{DaemonStatus, TlsForwardStatus, TransportKind};
+use crate::{Settings, StateDir};
 use gate_core::access::SubjectIdentity;
 use std::sync::Arc;
 use tokio::sync::mpsc;
 use tokio::sync::oneshot;
+use tokio::sync::watch;
 
 #[derive(Clone)]
 pub struct Daemon {
     tx: mpsc::Sender<DaemonRequest>,
     identity: Option<LocalIdentity>,
     static_dir: Option<String>,
+    state_dir: Option<StateDir>,
 }
 
 impl Daemon {
@@ -33,6 +37,7 @@ impl Daemon {
             tx,
             identity: None,
             static_dir,
+            state_dir: None,
         }
     }
 
@@ -45,6 +50,19 @@ impl Daemon {
         self
     }
 
+    /// Note the state directory the daemon was built with
+    pub fn with_state_dir(mut self, state_dir: StateDir) -> Self {
+        self.state_dir = Some(state_dir);
+        self
+    }
+
+    /// State directory the daemon keeps its data in
+    pub fn state_dir(&self) -> Result<&StateDir> {
+        self.state_dir
+            .as_ref()
+            .ok_or_else(|| DaemonError::ConfigError("State directory not set".to_string()))
+    }
+
     pub async fn with_http_identity(
         self,
         identity: &gate_http::services::HttpIdentity,
@@ -92,6 +110,25 @@ impl Daemon {
         rx.await?
     }
 
+    /// Re-apply a stored configuration revision, returning the settings now
+    /// in effect, or `None` when the revision is no longer kept
+    pub async fn rollback_config(&self, revision: u64) -> Result<Option<Settings>> {
+        let identity = self
+            .identity
+            .clone()
+            .ok_or_else(|| DaemonError::InvalidState("No identity set".into()))?;
+
+        let (reply, rx) = oneshot::channel();
+        self.tx
+            .send(DaemonRequest::RollbackConfig {
+                identity,
+                revision,
+                reply,
+            })
+            .await?;
+        rx.await?
+    }
+
     pub async fn restart(&self) -> Result<()> {
         let identity = self
             .identity
@@ -124,6 +161,15 @@ impl Daemon {
         Ok(rx.await?)
     }
 
+    /// Settings from now on, updated each time a configuration is applied
+    pub async fn subscribe_settings(&self) -> Result<watch::Receiver<Settings>> {
+        let (reply, rx) = oneshot::channel();
+        self.tx
+            .send(DaemonRequest::SubscribeSettings { reply })
+            .await?;
+        Ok(rx.await?)
+    }
+
     pub async fn get_bootstrap_manager(&self) -> Result<Arc<BootstrapTokenManager>> {
         let (reply, rx) = oneshot::channel();
         self.tx
@@ -154,12 +200,42 @@ impl Daemon {
         let bootstrap_manager = self.get_bootstrap_manager().await?;
         let token = bootstrap_manager.get_token().await;
         let status = self.status().await?;
+        let base_path = self.get_settings().await?.server.base_path;
         Ok(token.map(|t| {
             let port = status.listen_address.split(':').nth(1).unwrap_or("31145");
-            format!("http://localhost:{port}/bootstrap/{t}")
+            format!("http://localhost:{port}{base_path}/bootstrap/{t}")
         }))
     }
 
+    /// Pairing details for enrolling from a phone, while a bootstrap token
+    /// is live. Uses the relay domain and its certificate when connected.
+    pub async fn bootstrap_pairing(&self) -> Result<Option<BootstrapPairing>> {
+        let bootstrap_manager = self.get_bootstrap_manager().await?;
+        let status = self.status().await?;
+        let base_path = self.get_settings().await?.server.base_path;
+        let (url, cert_fingerprint) = match &status.tlsforward_status {
+            TlsForwardStatus::Connected {
+                domain,
+                transport: TransportKind::Relay,
+            } => {
+                let cert_path = StateDir::new()
+                    .await?
+                    .dir_for("certificates")
+                    .join(domain)
+                    .join("fullchain.pem");
+                (
+                    format!("https://{domain}{base_path}"),
+                    cert_fingerprint(&cert_path).await,
+                )
+            }
+            _ => {
+                let port = status.listen_address.split(':').nth(1).unwrap_or("31145");
+                (format!("http://localhost:{port}{base_path}"), None)
+            }
+        };
+        Ok(bootstrap_manager.pairing(&url, cert_fingerprint).await)
+    }
+
     pub async fn server_address(&self) -> Result<String> {
         let status = self.status().await?;
         Ok(status.listen_address)
@@ -187,6 +263,130 @@ impl Daemon {
         Ok(rx.await?)
     }
 
+    pub async fn get_file_store(&self) -> Result<crate::services::FileStore> {
+        let (reply, rx) = oneshot::channel();
+        self.tx.send(DaemonRequest::GetFileStore { reply }).await?;
+        Ok(rx.await?)
+    }
+
+    pub async fn get_document_store(&self) -> Result<crate::services::DocumentStore> {
+        let (reply, rx) = oneshot::channel();
+        self.tx
+            .send(DaemonRequest::GetDocumentStore { reply })
+            .await?;
+        Ok(rx.await?)
+    }
+
+    pub async fn get_export_store(&self) -> Result<crate::services::ExportStore> {
+        let (reply, rx) = oneshot::channel();
+        self.tx
+            .send(DaemonRequest::GetExportStore { reply })
+            .await?;
+        Ok(rx.await?)
+    }
+
+    pub async fn get_eval_store(&self) -> Result<crate::services::EvalStore> {
+        let (reply, rx) = oneshot::channel();
+        self.tx.send(DaemonRequest::GetEvalStore { reply }).await?;
+        Ok(rx.await?)
+    }
+
+    /// Signer of outgoing requests, holding the node's identity key
+    pub async fn get_signer(&self) -> Result<Arc<crate::services::MessageSigner>> {
+        let (reply, rx) = oneshot::channel();
+        self.tx.send(DaemonRequest::GetSigner { reply }).await?;
+        Ok(rx.await?)
+    }
+
+    pub async fn get_node_identity(&self) -> Result<crate::services::NodeIdentity> {
+        let (reply, rx) = oneshot::channel();
+        self.tx
+            .send(DaemonRequest::GetNodeIdentity { reply })
+            .await?;
+        Ok(rx.await?)
+    }
+
+    pub async fn get_config_history(&self) -> Result<crate::services::ConfigHistory> {
+        let (reply, rx) = oneshot::channel();
+        self.tx
+            .send(DaemonRequest::GetConfigHistory { reply })
+            .await?;
+        Ok(rx.await?)
+    }
+
+    /// TLS manager serving user-supplied certificates, if configured
+    pub async fn get_tls_manager(&self) -> Result<Option<crate::services::TlsManager>> {
+        let (reply, rx) = oneshot::channel();
+        self.tx.send(DaemonRequest::GetTlsManager { reply }).await?;
+        Ok(rx.await?)
+    }
+
+    /// Relay connection, when exposed through the relay
+    pub async fn get_tlsforward_service(
+        &self,
+    ) -> Result<Option<Arc<crate::services::TlsForwardService>>> {
+        let (reply, rx) = oneshot::channel();
+        self.tx
+            .send(DaemonRequest::GetTlsForwardService { reply })
+            .await?;
+        Ok(rx.await?)
+    }
+
+    /// Tunnel process, when exposed through a tunnel instead of the relay
+    pub async fn get_tunnel_service(&self) -> Result<Option<Arc<crate::services::TunnelService>>> {
+        let (reply, rx) = oneshot::channel();
+        self.tx
+            .send(DaemonRequest::GetTunnelService { reply })
+            .await?;
+        Ok(rx.await?)
+    }
+
+    /// Last certificate renewal attempts
+    pub async fn get_renewal_log(&self) -> Result<crate::services::RenewalLog> {
+        let (reply, rx) = oneshot::channel();
+        self.tx.send(DaemonRequest::GetRenewalLog { reply }).await?;
+        Ok(rx.await?)
+    }
+
+    /// Counter and bandwidth caps for traffic forwarded through the relay,
+    /// to hand to the TLS forward handler
+    pub async fn get_traffic_meter(&self) -> Result<gate_tlsforward::client::TrafficMeter> {
+        let (reply, rx) = oneshot::channel();
+        self.tx
+            .send(DaemonRequest::GetTrafficMeter { reply })
+            .await?;
+        Ok(rx.await?)
+    }
+
+    pub async fn get_journal(&self) -> Result<crate::services::Journal> {
+        let (reply, rx) = oneshot::channel();
+        self.tx.send(DaemonRequest::GetJournal { reply }).await?;
+        Ok(rx.await?)
+    }
+
+    pub async fn get_scheduler(&self) -> Result<crate::services::Scheduler> {
+        let (reply, rx) = oneshot::channel();
+        self.tx.send(DaemonRequest::GetScheduler { reply }).await?;
+        Ok(rx.await?)
+    }
+
+    pub async fn get_pairing_service(&self) -> Result<crate::services::PairingService> {
+        let (reply, rx) = oneshot::channel();
+        self.tx
+            .send(DaemonRequest::GetPairingService { reply })
+            .await?;
+        Ok(rx.await?)
+    }
+
+    /// Local models kept loaded, if local inference is configured
+    pub async fn get_local_models(&self) -> Result<Option<Arc<crate::services::LocalModels>>> {
+        let (reply, rx) = oneshot::channel();
+        self.tx
+            .send(DaemonRequest::GetLocalModels { reply })
+            .await?;
+        Ok(rx.await?)
+    }
+
     pub async fn get_config(&self) -> Result<Settings> {
         let identity = self
             .identity
@@ -200,11 +400,110 @@ impl Daemon {
         rx.await?
     }
 
+    /// Deliver webhooks and run scheduled tasks in the background
+    async fn start_background_jobs(
+        &self,
+        state_backend: Arc<dyn gate_core::StateBackend>,
+        spend: crate::config::SpendConfig,
+        retention: crate::config::RetentionConfig,
+        task_overrides: std::collections::HashMap<String, crate::config::TaskConfig>,
+        sign_webhooks: bool,
+        certificates: crate::config::CertificatesConfig,
+    ) -> Result<()> {
+        let journal = self.get_journal().await?;
+        let mut webhooks = crate::services::WebhookDispatcher::new(journal.clone());
+        if sign_webhooks {
+            webhooks = webhooks.with_signer(self.get_signer().await?);
+        }
+        tokio::spawn(webhooks.run());
+        let scheduler = self.get_scheduler().await?;
+        let cluster = self.get_settings().await?.cluster;
+        if cluster.enabled {
+            let election = crate::services::cluster::LeaderElection::new(
+                state_backend.clone(),
+                cluster.node_id(),
+                std::time::Duration::from_secs(cluster.lease_seconds),
+            );
+            info!("Running as replica {}", election.node_id());
+            scheduler.set_leader_election(election.clone());
+            tokio::spawn(election.run());
+        }
+        if spend.enabled {
+            let monitor =
+                crate::services::SpendMonitor::new(state_backend.clone(), journal.clone(), spend);
+            scheduler
+                .register(
+                    "spend_check",
+                    "Raise spend forecast and anomaly alerts",
+                    &monitor.default_schedule(),
+                    &task_overrides,
+                    Arc::new(monitor),
+                )
+                .await?;
+        }
+        if let Some(tls) = self.get_tls_manager().await? {
+            let watcher =
+                crate::services::CertificateWatcher::new(certificates, tls.acceptor(), journal)
+                    .with_renewal_log(self.get_renewal_log().await?);
+            // On the leader only, so each renewal and expiry is reported once
+            scheduler
+                .register(
+                    "certificate_watch",
+                    "Reload renewed certificates and warn before they expire",
+                    &watcher.default_schedule(),
+                    &task_overrides,
+                    Arc::new(watcher),
+                )
+                .await?;
+        }
+        let purger = crate::services::RetentionPurger::new(state_backend.clone(), retention);
+        let purge_schedule = purger.default_schedule();
+        if purger.has_windows() {
+            scheduler
+                .register(
+                    "retention_purge",
+                    "Purge data older than its retention window",
+                    &purger.default_schedule(),
+                    &task_overrides,
+                    Arc::new(purger),
+                )
+                .await?;
+        }
+        scheduler
+            .register(
+                "blob_gc",
+                "Remove deduplicated body blobs no record refers to",
+                &purge_schedule,
+                &task_overrides,
+                Arc::new(crate::services::BlobCollector::new(state_backend)),
+            )
+            .await?;
+        scheduler
+            .register_local(
+                "health_probe",
+                "Run the doctor checks and log failures",
+                "@every 15m",
+                &task_overrides,
+                Arc::new(crate::services::HealthProbe::new(self.clone())),
+            )
+            .await?;
+        tokio::spawn(scheduler.run());
+        Ok(())
+    }
+
     /// Serve the daemon - uses ServerBuilder to reduce complexity
     pub async fn serve(self) -> Result<()> {
         // Get settings and create builder
         let settings = self.get_settings().await?;
-        let builder = server::ServerBuilder::new(self.clone(), Arc::new(settings));
+        let spend = settings.spend.clone();
+        let retention = settings.retention.clone();
+        let task_overrides = settings.tasks.clone();
+        let sign_webhooks = settings.signing.webhooks;
+        let certificates = settings.certificates.clone();
+        let memory = settings.memory.clone();
+        let safe_mode = self.status().await?.safe_mode;
+        let builder = server::ServerBuilder::new(self.clone(), Arc::new(settings))
+            .with_safe_mode(safe_mode.is_some());
 
         // Step 1: Bind listener early to fail fast
         let listener = builder.bind_listener().await?;
@@ -221,21 +520,66 @@ impl Daemon {
         let sink_registry = Arc::new(SinkRegistry::new());
         builder.register_sinks(&sink_registry).await?;
 
-        // Step 5: Setup sink index
+        // Step 5: Setup sink index and response cache, kept current by
+        // registry and health events
         let sink_index = Arc::new(SinkIndex::new());
+        let follow = sink_index.follow(&sink_registry);
         sink_index.refresh_from_registry(&sink_registry).await;
+        tokio::spawn(follow);
+        let response_cache = builder.response_cache();
+        if let Some(cache) = &response_cache {
+            tokio::spawn(cache.follow(&sink_registry));
+            app_state = app_state.with_response_cache(cache.clone());
+        }
+        let coalescer = builder.request_coalescer();
+        if let Some(coalescer) = &coalescer {
+            app_state = app_state.with_coalescer(coalescer.clone());
+        }
+
+        // Step 5a: Cap buffered responses and watch resident memory
+        BufferBudget::shared().set_limits(memory.buffer_limits());
+        let guard = crate::services::MemoryGuard::new(memory, self.get_journal().await?)
+            .with_caches(response_cache, coalescer);
+        tokio::spawn(guard.run());
+
+        // Step 5b: Deliver webhooks in the background and schedule periodic
+        // spend checks, purges and health probes, none of which run in safe mode
+        if let Some(safe_mode) = &safe_mode {
+            warn!("Started in safe mode: {}", safe_mode.reason);
+        } else {
+            self.start_background_jobs(
+                state_backend.clone(),
+                spend,
+                retention,
+                task_overrides,
+                sign_webhooks,
+                certificates,
+            )
+            .await?;
+        }
 
         // Step 6: Build core router with strategies and middleware
+        let file_store = self.get_file_store().await?;
+        let document_store = self.get_document_store().await?;
         let router_core = builder
-            .build_router_core(state_backend, sink_registry, sink_index)
+            .build_router_core(
+                state_backend,
+                sink_registry,
+                sink_index,
+                file_store,
+                document_store,
+            )
             .await;
         app_state = app_state.with_router(router_core);
+        if let Some(tools) = builder.tool_registry() {
+            app_state = app_state.with_tools(tools);
+        }
 
         // Step 7: Build complete application with all middleware (still missing state)
         let app_missing_state = builder.build_app(router, app_state.clone()).await;
 
         // Step 8: Supply the actual state and serve the application
-        let app = app_missing_state.with_state(app_state);
+        let app = builder.with_public_paths(app_missing_state.with_state(app_state));
         axum::serve(listener, app).await.map_err(DaemonError::Io)?;
 
         Ok(())
@@ -2,11 +2,15 @@ use crate::Settings;
 use crate::bootstrap::BootstrapTokenManager;
 use crate::error::Result;
 use crate::permissions::{LocalIdentity, LocalPermissionManager};
-use crate::services::{AuthService, WebAuthnService};
+use crate::services::{
+    AuthService, ConfigHistory, DocumentStore, EvalStore, ExportStore, FileStore, Journal,
+    LocalModels, MessageSigner, NodeIdentity, PairingService, RenewalLog, Scheduler,
+    TlsForwardService, TlsManager, TunnelService, WebAuthnService,
+};
 use crate::types::DaemonStatus;
 use gate_core::StateBackend;
 use std::sync::Arc;
-use tokio::sync::oneshot;
+use tokio::sync::{oneshot, watch};
 
 pub enum DaemonRequest {
     GetStatus {
@@ -17,6 +21,11 @@ pub enum DaemonRequest {
         config: Box<Settings>,
         reply: oneshot::Sender<Result<()>>,
     },
+    RollbackConfig {
+        identity: LocalIdentity,
+        revision: u64,
+        reply: oneshot::Sender<Result<Option<Settings>>>,
+    },
     Restart {
         identity: LocalIdentity,
         reply: oneshot::Sender<Result<()>>,
@@ -28,6 +37,9 @@ pub enum DaemonRequest {
     GetSettings {
         reply: oneshot::Sender<Settings>,
     },
+    SubscribeSettings {
+        reply: oneshot::Sender<watch::Receiver<Settings>>,
+    },
     GetBootstrapManager {
         reply: oneshot::Sender<Arc<BootstrapTokenManager>>,
     },
@@ -43,6 +55,54 @@ pub enum DaemonRequest {
     GetStateBackend {
         reply: oneshot::Sender<Arc<dyn StateBackend>>,
     },
+    GetFileStore {
+        reply: oneshot::Sender<FileStore>,
+    },
+    GetDocumentStore {
+        reply: oneshot::Sender<DocumentStore>,
+    },
+    GetExportStore {
+        reply: oneshot::Sender<ExportStore>,
+    },
+    GetEvalStore {
+        reply: oneshot::Sender<EvalStore>,
+    },
+    GetConfigHistory {
+        reply: oneshot::Sender<ConfigHistory>,
+    },
+    GetSigner {
+        reply: oneshot::Sender<Arc<MessageSigner>>,
+    },
+    GetNodeIdentity {
+        reply: oneshot::Sender<NodeIdentity>,
+    },
+    GetTlsManager {
+        reply: oneshot::Sender<Option<TlsManager>>,
+    },
+    GetTlsForwardService {
+        reply: oneshot::Sender<Option<Arc<TlsForwardService>>>,
+    },
+    GetTunnelService {
+        reply: oneshot::Sender<Option<Arc<TunnelService>>>,
+    },
+    GetRenewalLog {
+        reply: oneshot::Sender<RenewalLog>,
+    },
+    GetTrafficMeter {
+        reply: oneshot::Sender<gate_tlsforward::client::TrafficMeter>,
+    },
+    GetJournal {
+        reply: oneshot::Sender<Journal>,
+    },
+    GetScheduler {
+        reply: oneshot::Sender<Scheduler>,
+    },
+    GetPairingService {
+        reply: oneshot::Sender<PairingService>,
+    },
+    GetLocalModels {
+        reply: oneshot::Sender<Option<Arc<LocalModels>>>,
+    },
     GetUserCount {
         reply: oneshot::Sender<usize>,
     },
@@ -5,40 +5,65 @@ use crate::{
     config::{LocalInferenceConfig, ProviderConfig, ProviderType, Settings},
     daemon::{Daemon, Result},
     error::DaemonError,
-    services::{LocalInferenceService, key_capture::DaemonKeyRegistrar},
-    sinks::catgrad_sink::CatgradSink,
+    services::{
+        AccessLog, AuthLockout, CorsPolicy, DocumentStore, FileStore, LocalInferenceService,
+        ServerMode, identity, key_capture::DaemonKeyRegistrar, pipeline::PipelineFactory,
+        security_headers::SecurityHeaders, signing::TrustedKeys,
+    },
+    sinks::{catgrad_sink::CatgradSink, mock_sink::MockSink},
 };
-use axum::http::HeaderName;
+use axum::{handler::HandlerWithoutStateExt, response::Html};
 use gate_core::{
     router::{
         Sink,
         index::SinkIndex,
-        middleware::KeyCaptureMiddleware,
         registry::SinkRegistry,
         routing::Router,
-        strategy::{CompositeStrategy, ProviderAffinityStrategy, SimpleStrategy},
+        strategy::{
+            CompositeStrategy, ProviderAffinityStrategy, QualityStrategy, RoutingStrategy,
+            SimpleStrategy,
+        },
     },
     state::StateBackend,
 };
 use gate_http::{
     AppState,
+    services::{RequestCoalescer, ResponseCache},
     sinks::{
+        HttpSink,
         anthropic::{self, AnthropicConfig},
         openai::{self, OpenAIConfig},
+        signing::RequestSigner,
     },
+    tools::ToolRegistry,
 };
 use std::sync::Arc;
+use std::time::Duration;
 use tower_http::services::{ServeDir, ServeFile};
 use tracing::{info, warn};
 
+/// Time allowed for an upstream Gate to attest to its identity
+const ATTESTATION_TIMEOUT: Duration = Duration::from_secs(5);
+
 pub struct ServerBuilder {
     daemon: Daemon,
     settings: Arc<Settings>,
+    safe_mode: bool,
 }
 
 impl ServerBuilder {
     pub fn new(daemon: Daemon, settings: Arc<Settings>) -> Self {
-        Self { daemon, settings }
+        Self {
+            daemon,
+            settings,
+            safe_mode: false,
+        }
+    }
+
+    /// Serve only the auth, config and status API and register no sinks
+    pub fn with_safe_mode(mut self, safe_mode: bool) -> Self {
+        self.safe_mode = safe_mode;
+        self
     }
 
     /// Build and bind TCP listener
@@ -54,6 +79,34 @@ impl ServerBuilder {
         Ok(listener)
     }
 
+    /// Server-side tools for agentic chat completions, if any are configured
+    pub fn tool_registry(&self) -> Option<Arc<ToolRegistry>> {
+        if self.settings.tools.tools.is_empty() {
+            return None;
+        }
+        info!(
+            "Registered {} server-side tools",
+            self.settings.tools.tools.len()
+        );
+        Some(Arc::new(ToolRegistry::new(self.settings.tools.clone())))
+    }
+
+    /// Coalescer for identical non-streaming requests, if a window is set
+    pub fn request_coalescer(&self) -> Option<Arc<RequestCoalescer>> {
+        match self.settings.routing.coalesce_window_secs {
+            0 => None,
+            secs => Some(Arc::new(RequestCoalescer::new(Duration::from_secs(secs)))),
+        }
+    }
+
+    /// Cache of metadata responses, if a lifetime is set
+    pub fn response_cache(&self) -> Option<Arc<ResponseCache>> {
+        match self.settings.server.metadata_cache_secs {
+            0 => None,
+            secs => Some(Arc::new(ResponseCache::new(Duration::from_secs(secs)))),
+        }
+    }
+
     /// Initialize base router with authentication routes
     ///
     /// Returns a router that is missing `AppState<State>`.
@@ -62,6 +115,42 @@ impl ServerBuilder {
         let router: axum::Router<AppState<State>> = axum::Router::new();
         let router = crate::routes::auth::add_routes(router);
         let router = crate::routes::config::add_routes(router);
+        let router = crate::routes::status::add_routes(router);
+        let router = crate::routes::health::add_routes(router);
+        let router = crate::routes::node::add_routes(router);
+        let router = crate::routes::version::add_routes(router);
+        // Lockouts are enforced in safe mode too, so they can be lifted there
+        let router = crate::routes::lockouts::add_routes(router);
+        if self.safe_mode {
+            return router;
+        }
+        let router = crate::routes::doctor::add_routes(router);
+        let router = crate::routes::credentials::add_routes(router);
+        let router = crate::routes::keys::add_routes(router);
+        let router = crate::routes::audit::add_routes(router);
+        let router = crate::routes::mode::add_routes(router);
+        let router = crate::routes::models::add_routes(router);
+        let router = crate::routes::conversations::add_routes(router);
+        let router = crate::routes::data::add_routes(router);
+        let router = crate::routes::devices::add_routes(router);
+        let router = crate::routes::documents::add_routes(router);
+        let router = crate::routes::evals::add_routes(router);
+        let router = crate::routes::experiments::add_routes(router);
+        let router = crate::routes::export::add_routes(router);
+        let router = crate::routes::feedback::add_routes(router);
+        let router = crate::routes::files::add_routes(router);
+        let router = crate::routes::groups::add_routes(router);
+        let router = crate::routes::journal::add_routes(router);
+        let router = crate::routes::providers::add_routes(router);
+        let router = crate::routes::onboarding::add_routes(router);
+        let router = crate::routes::preferences::add_routes(router);
+        let router = crate::routes::profiling::add_routes(router);
+        let router = crate::routes::prompts::add_routes(router);
+        let router = crate::routes::provenance::add_routes(router);
+        let router = crate::routes::tasks::add_routes(router);
+        let router = crate::routes::tls::add_routes(router);
+        let router = crate::routes::tlsforward::add_routes(router);
+        let router = crate::routes::usage::add_routes(router);
         crate::routes::admin::add_routes(router)
     }
 
@@ -70,23 +159,44 @@ impl ServerBuilder {
         let auth_service = self.daemon.get_auth_service().await?;
         let allow_local_bypass =
             self.settings.server.allow_local_bypass && is_local_host(&self.settings.server.host);
+        // Shared, so the access log's bans are lockouts admins can lift
+        let auth_lockout = AuthLockout::new(self.settings.auth.lockout.clone())
+            .with_journal(self.daemon.get_journal().await?);
 
         Ok(State::new(
             auth_service,
             self.daemon.clone(),
             allow_local_bypass,
             self.settings.auth.provider_passthrough.clone(),
+        )
+        .with_anthropic_compat(self.settings.server.anthropic_compat)
+        .with_server_mode(ServerMode::new(
+            self.settings.server.maintenance_message.clone(),
         ))
+        .with_access_log(
+            AccessLog::new(self.settings.tlsforward.access_log.clone())
+                .with_lockout(auth_lockout.clone()),
+        )
+        .with_auth_lockout(auth_lockout))
     }
 
     /// Register all provider sinks
     pub async fn register_sinks(&self, registry: &Arc<SinkRegistry>) -> Result<()> {
+        if self.safe_mode {
+            warn!("Safe mode: providers and local inference are disabled");
+            return Ok(());
+        }
         let mut has_anthropic = false;
         let mut has_openai = false;
+        let signer: Arc<dyn RequestSigner> = self.daemon.get_signer().await?;
 
         // Register configured provider sinks
         for provider_config in &self.settings.providers {
-            let sink = match self.create_provider_sink(provider_config).await {
+            if !Self::upstream_attests(provider_config).await {
+                continue;
+            }
+            let sink = match Self::create_provider_sink(provider_config, Some(signer.clone())).await
+            {
                 Ok(s) => s,
                 Err(e) => {
                     warn!("Failed to create {} sink: {}", provider_config.provider, e);
@@ -97,7 +207,7 @@ impl ServerBuilder {
             match provider_config.provider {
                 ProviderType::Anthropic => has_anthropic = true,
                 ProviderType::OpenAI => has_openai = true,
-                ProviderType::Custom => {}
+                ProviderType::Custom | ProviderType::Mock => {}
             }
 
             let sink_id = format_provider_sink_id(&provider_config.provider, &provider_config.name);
@@ -121,8 +231,50 @@ impl ServerBuilder {
         Ok(())
     }
 
-    /// Create a provider sink based on configuration
-    async fn create_provider_sink(&self, config: &ProviderConfig) -> Result<Arc<dyn Sink>> {
+    /// Whether a provider pinned to a node id attests to it, over a fresh
+    /// nonce. An upstream that cannot be reached is not used: pinning a node
+    /// id asks for its identity to be proven, and an unreachable one is not.
+    async fn upstream_attests(config: &ProviderConfig) -> bool {
+        let Some(node_id) = &config.node_id else {
+            return true;
+        };
+        let client = reqwest::Client::builder()
+            .timeout(ATTESTATION_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        match identity::fetch_attestation(&client, &config.base_url).await {
+            Ok(attestation) if attestation.document.node_id.eq_ignore_ascii_case(node_id) => {
+                info!(
+                    "Provider {} attested as node {} running {}",
+                    config.name, node_id, attestation.document.version
+                );
+                true
+            }
+            Ok(attestation) => {
+                warn!(
+                    "Not using provider {}: it attested as node {} instead of {}",
+                    config.name, attestation.document.node_id, node_id
+                );
+                false
+            }
+            Err(e) => {
+                warn!("Not using provider {}: {}", config.name, e);
+                false
+            }
+        }
+    }
+
+    /// Create a provider sink based on configuration. Requests are signed
+    /// with `signer` when the provider asks for it.
+    pub(crate) async fn create_provider_sink(
+        config: &ProviderConfig,
+        signer: Option<Arc<dyn RequestSigner>>,
+    ) -> Result<Arc<dyn Sink>> {
+        let signer = signer.filter(|_| config.sign_requests);
+        let sign = |sink: HttpSink| match &signer {
+            Some(signer) => sink.with_signer(signer.clone()),
+            None => sink,
+        };
         match config.provider {
             ProviderType::Anthropic => {
                 let anthropic_config = AnthropicConfig {
@@ -133,7 +285,7 @@ impl ServerBuilder {
                 };
                 anthropic::create_sink(anthropic_config)
                     .await
-                    .map(|sink| Arc::new(sink) as Arc<dyn Sink>)
+                    .map(|sink| Arc::new(sign(sink)) as Arc<dyn Sink>)
                     .map_err(|e| DaemonError::ServiceUnavailable(e.to_string()))
             }
             ProviderType::OpenAI => {
@@ -149,9 +301,13 @@ impl ServerBuilder {
                     sink_id: Some(format!("provider://openai/{}", config.name)),
                 };
                 openai::create_sink(openai_config)
-                    .map(|sink| Arc::new(sink) as Arc<dyn Sink>)
+                    .map(|sink| Arc::new(sign(sink)) as Arc<dyn Sink>)
                     .map_err(|e| DaemonError::ServiceUnavailable(e.to_string()))
             }
+            ProviderType::Mock => Ok(Arc::new(MockSink::new(
+                format!("mock://{}", config.name),
+                config.mock.clone().unwrap_or_default(),
+            )) as Arc<dyn Sink>),
             ProviderType::Custom => Err(DaemonError::ConfigError(
                 "Custom provider type not yet implemented".to_string(),
             )),
@@ -204,13 +360,32 @@ impl ServerBuilder {
         registry: &Arc<SinkRegistry>,
         config: &LocalInferenceConfig,
     ) {
-        match LocalInferenceService::new(config.clone()) {
-            Ok(_) => {
-                let sink = Arc::new(CatgradSink::new("self://catgrad", config.models.clone()));
-                registry.register("self://catgrad".to_string(), sink).await;
-                info!("Registered Catgrad sink for local inference");
+        if let Err(e) = LocalInferenceService::new(config.clone()) {
+            warn!("Failed to initialize LocalInferenceService: {}", e);
+            return;
+        }
+        let loaded = match self.daemon.get_local_models().await {
+            Ok(Some(loaded)) => loaded,
+            Ok(None) => return,
+            Err(e) => {
+                warn!("Failed to get local models: {}", e);
+                return;
             }
-            Err(e) => warn!("Failed to initialize LocalInferenceService: {}", e),
+        };
+        for (target, draft) in &config.draft_models {
+            info!("Drafting for local model {} with {}", target, draft.model);
+        }
+        let sink = Arc::new(
+            CatgradSink::new("self://catgrad", config.models.clone(), loaded.clone())
+                .with_drafts(config.draft_models.clone()),
+        );
+        registry.register("self://catgrad".to_string(), sink).await;
+        info!("Registered Catgrad sink for local inference");
+
+        if !config.prewarm.is_empty() {
+            let models = config.prewarm.clone();
+            info!("Prewarming {} local models", models.len());
+            tokio::spawn(async move { loaded.prewarm(&models).await });
         }
     }
 
@@ -220,25 +395,58 @@ impl ServerBuilder {
         state_backend: Arc<dyn StateBackend>,
         sink_registry: Arc<SinkRegistry>,
         sink_index: Arc<SinkIndex>,
+        file_store: FileStore,
+        document_store: DocumentStore,
     ) -> Arc<Router> {
         let registrar = Arc::new(DaemonKeyRegistrar::new(
             self.daemon.clone(),
             sink_registry.clone(),
-            sink_index.clone(),
         ));
+        let routing = &self.settings.routing;
+        let mut strategies: Vec<(Box<dyn RoutingStrategy>, f64)> = vec![
+            (Box::new(ProviderAffinityStrategy::new()), 1.0),
+            (Box::new(SimpleStrategy::new()), 0.1),
+        ];
+        if routing.quality_weight > 0.0 {
+            strategies.push((
+                Box::new(
+                    QualityStrategy::new(state_backend.clone())
+                        .with_half_life(Duration::from_secs(
+                            routing.feedback_half_life_days * 24 * 60 * 60,
+                        ))
+                        .with_min_samples(routing.feedback_min_samples),
+                ),
+                routing.quality_weight,
+            ));
+        }
 
-        let router = Router::builder()
-            .state_backend(state_backend)
+        let mut router = Router::builder()
+            .state_backend(state_backend.clone())
             .sink_registry(sink_registry)
-            .strategy(Box::new(CompositeStrategy::new(vec![
-                (Box::new(ProviderAffinityStrategy::new()), 1.0),
-                (Box::new(SimpleStrategy::new()), 0.1),
-            ])))
-            .middleware(Arc::new(KeyCaptureMiddleware::new(registrar)))
-            .sink_index(sink_index)
-            .build();
-
-        Arc::new(router)
+            .strategy(Box::new(CompositeStrategy::new(strategies)))
+            .sink_index(sink_index);
+        if let Some(concurrency) = &routing.concurrency {
+            router = router.concurrency_limits(concurrency.limits());
+        }
+        let router = Arc::new(router.build());
+        let mut pipeline =
+            PipelineFactory::new(state_backend, registrar, file_store, document_store);
+        match self.daemon.get_signer().await {
+            Ok(signer) => pipeline = pipeline.with_signer(signer),
+            Err(e) => warn!("Responses will not carry provenance blocks: {e}"),
+        }
+        match self.daemon.get_journal().await {
+            Ok(journal) => pipeline = pipeline.with_journal(journal),
+            Err(e) => warn!("Content filter webhooks will not be sent: {e}"),
+        }
+        router.replace_pipeline(pipeline.build(&self.settings, &router));
+        match self.daemon.subscribe_settings().await {
+            Ok(settings) => {
+                tokio::spawn(pipeline.follow(router.clone(), (*self.settings).clone(), settings));
+            }
+            Err(e) => warn!("Router middleware will not follow configuration changes: {e}"),
+        }
+        router
     }
 
     /// Configure middleware layers
@@ -246,27 +454,16 @@ impl ServerBuilder {
     where
         S: Clone + Send + Sync + 'static,
     {
-        app.layer(
-            tower_http::cors::CorsLayer::new()
-                .allow_origin(tower_http::cors::Any)
-                .allow_methods(tower_http::cors::Any)
-                .allow_headers(vec![
-                    axum::http::header::CONTENT_TYPE,
-                    axum::http::header::AUTHORIZATION,
-                    HeaderName::from_static("x-correlation-id"),
-                    HeaderName::from_static("x-api-key"),
-                    HeaderName::from_static("traceparent"),
-                    HeaderName::from_static("tracestate"),
-                ])
-                .expose_headers(vec![
-                    HeaderName::from_static("x-correlation-id"),
-                    HeaderName::from_static("traceparent"),
-                    HeaderName::from_static("tracestate"),
-                ]),
-        )
-        .layer(axum::middleware::from_fn(
+        app.layer(axum::middleware::from_fn(
             gate_http::middleware::correlation_id_middleware,
         ))
+        .layer(axum::middleware::from_fn(
+            crate::services::lockout::client_ip_middleware,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            self.settings.server.server_timing,
+            gate_http::middleware::timing_middleware,
+        ))
     }
 
     /// Add static file serving if configured
@@ -278,8 +475,27 @@ impl ServerBuilder {
             if std::path::Path::new(static_dir).exists() {
                 info!("Serving static files from: {}", static_dir);
                 let index_path = format!("{static_dir}/index.html");
-                let serve_dir = ServeDir::new(static_dir).fallback(ServeFile::new(index_path));
-                return app.fallback_service(serve_dir);
+                let base_path = &self.settings.server.base_path;
+                if base_path.is_empty() {
+                    let serve_dir = ServeDir::new(static_dir).fallback(ServeFile::new(index_path));
+                    return app.fallback_service(serve_dir);
+                }
+                // The UI resolves its assets, API calls and routes against
+                // <base href>, which has to name the base path
+                match std::fs::read_to_string(&index_path) {
+                    Ok(index) => {
+                        let index = Html(with_base_href(&index, base_path));
+                        let serve_index = move || {
+                            let index = index.clone();
+                            async move { index }
+                        };
+                        let serve_dir = ServeDir::new(static_dir)
+                            .append_index_html_on_directories(false)
+                            .fallback(serve_index.into_service());
+                        return app.fallback_service(serve_dir);
+                    }
+                    Err(e) => warn!("Failed to read {}: {}", index_path, e),
+                }
             } else {
                 warn!("Static directory not found: {}", static_dir);
             }
@@ -287,6 +503,24 @@ impl ServerBuilder {
         app
     }
 
+    /// Serve the finished application under the configured base path, and
+    /// its APIs under the configured prefixes too. Prefixes are mapped
+    /// before routing and are relative to the base path. The cross-origin
+    /// policy wraps it all, seeing paths as requested.
+    pub fn with_public_paths(&self, app: axum::Router) -> axum::Router {
+        let server = &self.settings.server;
+        let prefixes = server.route_prefixes.clone();
+        for prefix in prefixes.anthropic.iter().chain(&prefixes.openai) {
+            info!("Serving API routes under {}{prefix}", server.base_path);
+        }
+        let app = gate_http::routes::with_route_prefixes(app, prefixes);
+        if !server.base_path.is_empty() {
+            info!("Serving under base path {}", server.base_path);
+        }
+        gate_http::routes::with_base_path(app, &server.base_path)
+            .layer(CorsPolicy::new(server).layer())
+    }
+
     /// Build the complete application
     pub async fn build_app(
         &self,
@@ -294,20 +528,94 @@ impl ServerBuilder {
         app_state: AppState<State>,
     ) -> axum::Router<AppState<State>> {
         let app: axum::Router<AppState<State>> = router;
+        let app = if self.settings.server.assistants_api && !self.safe_mode {
+            app.merge(gate_http::routes::assistants::router::<State>())
+        } else {
+            app
+        };
 
         let app = app
             // Merge common HTTP routes (health, inference, models, observability)
             .merge(gate_http::routes::router::<State>())
+            // Refuse what maintenance or read-only mode disallows; added before
+            // auth so it runs after it and only callers who signed in see it
+            .route_layer(axum::middleware::from_fn_with_state(
+                app_state.clone(),
+                crate::services::server_mode::server_mode_middleware,
+            ))
+            // Keep observer tokens to the read-only routes; also after auth
+            .route_layer(axum::middleware::from_fn(
+                crate::services::observer::observer_middleware,
+            ))
             // Apply auth middleware
             .route_layer(axum::middleware::from_fn_with_state(
                 app_state.clone(),
                 gate_http::middleware::auth::auth_middleware::<State>,
             ));
+        // Outside auth, so authentication failures are reshaped too
+        let app = if self.settings.server.anthropic_compat {
+            app.layer(axum::middleware::from_fn(
+                gate_http::middleware::anthropic_compat_middleware,
+            ))
+        } else if !self.settings.server.route_prefixes.anthropic.is_empty() {
+            app.layer(axum::middleware::from_fn(
+                gate_http::middleware::anthropic_prefix_middleware,
+            ))
+        } else {
+            app
+        };
+        // Outside auth too, so a bad signature is refused before the
+        // credentials it came with are looked at
+        let trusted_keys = TrustedKeys::new(self.settings.signing.trusted_keys.clone());
+        let app = app.layer(axum::middleware::from_fn_with_state(
+            Arc::new(trusted_keys),
+            crate::services::signing::signature_middleware,
+        ));
+        // Outside auth, so locked out clients never reach it and rejected
+        // credentials are counted
+        let app = app.layer(axum::middleware::from_fn_with_state(
+            app_state.clone(),
+            crate::services::lockout::lockout_middleware,
+        ));
+        // Outermost of the application's own layers, so every status is
+        // logged, including the lockout middleware's refusals of banned
+        // clients
+        let app = app.layer(axum::middleware::from_fn_with_state(
+            app_state.clone(),
+            crate::services::access_log::access_log_middleware,
+        ));
 
         let app = self.configure_middleware(app);
         let app = self.add_static_serving(app);
+        // After static serving, so the frontend's files are covered too
+        let app = app.layer(axum::middleware::from_fn_with_state(
+            Arc::new(self.security_headers()),
+            crate::services::security_headers::security_headers_middleware,
+        ));
         gate_http::middleware::with_request_tracing(app)
     }
+
+    /// Security headers, with the default policy fitted to the frontend's
+    /// `index.html`
+    fn security_headers(&self) -> SecurityHeaders {
+        let index = self
+            .daemon
+            .static_dir
+            .as_ref()
+            .and_then(|dir| std::fs::read_to_string(format!("{dir}/index.html")).ok());
+        SecurityHeaders::new(&self.settings.server.security_headers, index.as_deref())
+    }
+}
+
+/// `index.html` with its `<base href>` naming `base_path`
+fn with_base_href(index: &str, base_path: &str) -> String {
+    const ROOT_BASE: &str = "<base href=\"/\" />";
+    let base = format!("<base href=\"{base_path}/\" />");
+    if index.contains(ROOT_BASE) {
+        index.replacen(ROOT_BASE, &base, 1)
+    } else {
+        index.replacen("<head>", &format!("<head>\n    {base}"), 1)
+    }
 }
 
 /// Helper function to check if host is localhost
@@ -316,10 +624,11 @@ fn is_local_host(host: &str) -> bool {
 }
 
 /// Format provider sink ID
-fn format_provider_sink_id(provider: &ProviderType, name: &str) -> String {
+pub(crate) fn format_provider_sink_id(provider: &ProviderType, name: &str) -> String {
     match provider {
         ProviderType::Anthropic => format!("provider://anthropic/{name}"),
         ProviderType::OpenAI => format!("provider://openai/{name}"),
         ProviderType::Custom => format!("provider://{name}"),
+        ProviderType::Mock => format!("mock://{name}"),
     }
 }
@@ -26,6 +26,9 @@ pub enum DaemonError {
     #[error("Database error: {0}")]
     Database(String),
 
+    #[error(transparent)]
+    Core(#[from] gate_core::Error),
+
     #[error("TLS forward error: {0}")]
     TlsForward(String),
 
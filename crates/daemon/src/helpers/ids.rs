@@ -0,0 +1,30 @@
+//! Ids of records kept on disk
+//!
+//! Stores name records by a kind prefix and a random hex suffix. Ids are
+//! only generated here, so stores reject anything else before it can name a
+//! path outside them.
+
+/// New id for a record of the kind `prefix` names
+pub fn new_id(prefix: &str) -> String {
+    format!("{prefix}{}", uuid::Uuid::new_v4().simple())
+}
+
+/// Whether `id` could have come from [`new_id`] with `prefix`
+pub fn is_valid_id(prefix: &str, id: &str) -> bool {
+    id.strip_prefix(prefix)
+        .is_some_and(|rest| !rest.is_empty() && rest.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_generated_ids_are_valid() {
+        let id = new_id("file-");
+        assert!(is_valid_id("file-", &id));
+        assert!(!is_valid_id("doc-", &id));
+        assert!(!is_valid_id("file-", "file-"));
+        assert!(!is_valid_id("file-", "file-../../etc/passwd"));
+    }
+}
@@ -2,4 +2,5 @@
 
 pub mod admin;
 pub mod errors;
+pub mod ids;
 pub mod services;
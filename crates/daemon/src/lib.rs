@@ -9,6 +9,7 @@ pub mod error;
 pub mod helpers;
 pub mod permissions;
 pub mod routes;
+pub mod safe_mode;
 pub mod services;
 pub mod sinks;
 pub mod state;
@@ -22,4 +23,4 @@ pub use daemon::Daemon;
 pub use error::{DaemonError, Result};
 pub use state::State;
 pub use state_dir::StateDir;
-pub use types::{DaemonStatus, TlsForwardStatus};
+pub use types::{DaemonStatus, DoctorReport, TlsForwardStatus, TransportKind};
@@ -2,12 +2,22 @@
 extern crate tracing;
 
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use gate_bench::LoadConfig;
+use gate_core::state::SchemaMigrator;
 use gate_core::tracing::{
     config::{InstrumentationConfig, OtlpConfig},
     init::init_tracing,
 };
-use gate_daemon::{Daemon, Settings, StateDir};
+use gate_daemon::config::{ConfigSource, IssueSeverity};
+use gate_daemon::daemon::DaemonBuilder;
+use gate_daemon::daemon::control::ControlServer;
+use gate_daemon::safe_mode::{STABLE_AFTER, SafeMode, StartupTracker, crash_loop};
+use gate_daemon::{Daemon, Settings, StateDir, services::DoctorService, types::CheckStatus};
+use gate_sqlx::SqliteStateBackend;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Notify;
 
 /// Gate daemon - High-performance AI gateway
 #[derive(Parser, Debug)]
@@ -16,6 +26,108 @@ struct Cli {
     /// Configuration file path
     #[arg(short = 'c', long = "config")]
     config: Option<String>,
+
+    /// Report pending database migrations and exit, failing if any are
+    /// pending; nothing is applied
+    #[arg(long)]
+    check_migrations: bool,
+
+    /// Start in safe mode: no providers, local inference or background jobs,
+    /// only the auth, config and status API
+    #[arg(long)]
+    safe_mode: bool,
+
+    /// Run as a Kubernetes workload: settings come only from `GATE_`
+    /// environment variables, `*_FILE` secret mounts and `--config-dir`,
+    /// and are never written back; invalid settings stop the daemon instead
+    /// of starting it in safe mode
+    #[arg(long, env = "GATE_KUBERNETES")]
+    kubernetes: bool,
+
+    /// Directory of config files, e.g. a mounted ConfigMap, merged in name
+    /// order
+    #[arg(long, env = "GATE_CONFIG_DIR", requires = "kubernetes")]
+    config_dir: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Run first-run diagnostics and connectivity checks, then exit
+    Doctor {
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Generate load against a running daemon and report latency
+    Bench {
+        /// Base URL of the daemon; defaults to the configured listen address
+        #[arg(long)]
+        url: Option<String>,
+        /// Model to request
+        #[arg(long, default_value = "mock")]
+        model: String,
+        /// API key sent as a bearer token
+        #[arg(long, env = "GATE_API_KEY")]
+        api_key: Option<String>,
+        /// Requests in flight at once
+        #[arg(long, default_value_t = 16)]
+        concurrency: usize,
+        /// Total requests to send
+        #[arg(long, default_value_t = 200)]
+        requests: usize,
+        /// Request non-streaming completions instead of SSE
+        #[arg(long)]
+        no_stream: bool,
+        /// Fail if p99 latency exceeds this many milliseconds
+        #[arg(long)]
+        max_p99_ms: Option<f64>,
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Export or import the configuration for another instance
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommand,
+    },
+    /// Call a method on a running daemon's control socket and print the
+    /// result
+    Ctl {
+        /// Method, e.g. status, config.reload, providers.list or shutdown
+        method: String,
+        /// Params as JSON
+        params: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ConfigCommand {
+    /// Print the configuration with secrets replaced by placeholders
+    Export {
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Import an exported configuration into this instance's config file
+    Import {
+        /// Exported configuration to import
+        file: PathBuf,
+        /// Value for a secret placeholder, as NAME=VALUE
+        #[arg(long = "secret", value_parser = parse_secret)]
+        secrets: Vec<(String, String)>,
+        /// Validate and report without writing the config file
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+fn parse_secret(arg: &str) -> std::result::Result<(String, String), String> {
+    arg.split_once('=')
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected NAME=VALUE, got '{arg}'"))
 }
 
 #[tokio::main]
@@ -46,16 +158,29 @@ async fn main() -> Result<()> {
     };
     init_tracing(&instrumentation_config)?;
 
-    // Build daemon
-    let mut builder = Daemon::builder();
     let state_dir = StateDir::new().await?;
     let default_config_path = state_dir.config_path();
+    let target_config_path = cli
+        .config
+        .clone()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| default_config_path.clone());
+
+    let config_source = if cli.kubernetes {
+        ConfigSource::Environment {
+            config_dir: cli.config_dir.clone(),
+        }
+    } else {
+        ConfigSource::File(target_config_path.clone())
+    };
 
     // Load configuration if specified
-    if let Some(config_path) = cli.config {
+    let loaded = if cli.kubernetes {
+        debug!("Loading configuration from {}", config_source);
+        config_source.load()
+    } else if let Some(config_path) = cli.config.as_deref() {
         debug!("Loading configuration from: {}", config_path);
-        let settings = Settings::load_from_file(&config_path)?;
-        builder = builder.with_settings(settings);
+        Settings::load_from_file(config_path)
     } else {
         debug!(
             "No configuration path specified, using default at {}",
@@ -66,47 +191,160 @@ async fn main() -> Result<()> {
                 "Loading configuration from default path: {}",
                 default_config_path.display()
             );
-            builder = builder.with_settings(Settings::load_from_file(&default_config_path)?);
+            Settings::load_from_file(&default_config_path)
+        } else if cli.command.is_some() {
+            Ok(Settings::default())
         } else {
             warn!("No configuration found, creating one using default settings");
             let settings = Settings::default();
             settings.save_to_file(&default_config_path).await?;
-            builder = builder.with_settings(settings);
+            Ok(settings)
+        }
+    };
+
+    // A daemon whose configuration cannot be loaded starts in safe mode with
+    // default settings, keeping a copy of the broken file
+    let mut safe_mode = cli
+        .safe_mode
+        .then(|| SafeMode::new("Started with --safe-mode"));
+    let settings = match loaded {
+        Ok(settings) => settings,
+        Err(e) if cli.command.is_none() && !cli.check_migrations && !cli.kubernetes => {
+            error!(
+                "Failed to load configuration from {}: {}",
+                target_config_path.display(),
+                e
+            );
+            let backup = target_config_path.with_extension("json.broken");
+            if let Err(e) = std::fs::copy(&target_config_path, &backup) {
+                warn!("Failed to back up configuration: {}", e);
+            }
+            safe_mode = Some(SafeMode::new(format!(
+                "Configuration could not be loaded ({e}); a copy was kept at {}",
+                backup.display()
+            )));
+            Settings::default()
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    // Settings a workload has no later chance to fix stop it, each reported
+    // as one JSON line
+    if cli.kubernetes && cli.command.is_none() && !cli.check_migrations {
+        let issues = settings.deployment_issues();
+        for issue in &issues {
+            eprintln!("{}", serde_json::to_string(issue)?);
+        }
+        let errors = issues
+            .iter()
+            .filter(|i| i.severity == IssueSeverity::Error)
+            .count();
+        if errors > 0 {
+            anyhow::bail!("{errors} configuration errors in {config_source}");
         }
     }
 
-    // Pass state_dir to builder
-    builder = builder.with_state_dir(state_dir);
+    let control_socket = settings
+        .control
+        .socket_path
+        .clone()
+        .unwrap_or_else(|| state_dir.control_socket_path());
 
-    // Set static directory if specified
-    if let Ok(static_dir) = std::env::var("GATE_SERVER__STATIC_DIR") {
-        info!("Using static directory from environment: {}", static_dir);
-        builder = builder.with_static_dir(static_dir);
-    } else {
-        builder = builder.with_static_dir("crates/frontend-daemon/dist".to_string());
+    match cli.command {
+        Some(Command::Doctor { json }) => return run_doctor(settings, &state_dir, json).await,
+        Some(Command::Bench {
+            url,
+            model,
+            api_key,
+            concurrency,
+            requests,
+            no_stream,
+            max_p99_ms,
+            json,
+        }) => {
+            let config = LoadConfig {
+                base_url: url.unwrap_or_else(|| {
+                    format!("http://{}:{}", settings.server.host, settings.server.port)
+                }),
+                model,
+                api_key,
+                concurrency,
+                requests,
+                stream: !no_stream,
+                ..Default::default()
+            };
+            return run_bench(config, max_p99_ms, json).await;
+        }
+        Some(Command::Config { action }) => {
+            return run_config(action, settings, &target_config_path).await;
+        }
+        Some(Command::Ctl { method, params }) => {
+            return run_ctl(&control_socket, &method, params.as_deref()).await;
+        }
+        None => {}
+    }
+    if cli.check_migrations {
+        return check_migrations(&state_dir).await;
+    }
+    let control_enabled = settings.control.enabled;
+
+    // Repeated starts that never became stable are a crash loop; under
+    // Kubernetes restarts are left to the kubelet
+    let startup = StartupTracker::new(&state_dir);
+    if !cli.kubernetes {
+        let previous_starts = startup.record_start().await?;
+        if safe_mode.is_none() {
+            safe_mode = crash_loop(previous_starts);
+        }
     }
 
-    // Build the daemon
-    let daemon = builder.build().await?;
+    // Build the daemon, falling back to safe mode with an in-memory database
+    // when the state cannot be opened
+    let daemon = match daemon_builder(settings.clone(), state_dir, safe_mode.clone())
+        .with_read_only_config(!config_source.is_writable())
+        .build()
+        .await
+    {
+        Ok(daemon) => daemon,
+        Err(e) if safe_mode.is_none() && !cli.kubernetes => {
+            error!("Failed to start the daemon: {}", e);
+            let reason = SafeMode::new(format!(
+                "The daemon failed to start ({e}); running on a temporary database"
+            ));
+            daemon_builder(settings, StateDir::new().await?, Some(reason))
+                .with_database_url("sqlite::memory:".to_string())
+                .build()
+                .await?
+        }
+        Err(e) => return Err(e.into()),
+    };
+    if let Some(safe_mode) = daemon.status().await?.safe_mode {
+        println!("\n  Started in safe mode: {}", safe_mode.reason);
+        println!("  Fix the configuration from the UI, then restart the daemon.");
+    }
 
     // Print startup information
     let bootstrap_manager = daemon.get_bootstrap_manager().await?;
-    if let Some(token) = bootstrap_manager.get_token().await {
+    if let Some(token) = bootstrap_manager.take_display_token().await {
         println!("\n===========================================");
         println!("First-time setup required!");
         println!("Please visit the following URL to create your admin account:");
         println!(
-            "\n  http://localhost:{}/bootstrap/{}",
+            "\n  http://localhost:{}{}/bootstrap/{}",
             daemon
                 .server_address()
                 .await?
                 .split(':')
                 .nth(1)
                 .unwrap_or("31145"),
+            daemon.get_settings().await?.server.base_path,
             token
         );
+        if let Some(expires_at) = bootstrap_manager.expires_at().await {
+            println!("\n  This link is shown once and expires at {expires_at}");
+        }
         println!("\n===========================================\n");
-        info!("Bootstrap URL printed for token: {}", token);
+        info!("Bootstrap URL printed");
     } else {
         println!("\n===========================================");
         println!("Gate daemon is running");
@@ -121,20 +359,253 @@ async fn main() -> Result<()> {
         println!("===========================================\n");
     }
 
+    // Serve the control socket apart from the HTTP listener, so the daemon
+    // stays controllable when the listener fails
+    let shutdown = Arc::new(Notify::new());
+    if control_enabled {
+        let control = ControlServer::new(daemon.clone(), config_source, shutdown.clone());
+        tokio::spawn(async move {
+            if let Err(e) = control.serve(&control_socket).await {
+                warn!("Control socket unavailable: {}", e);
+            }
+        });
+    }
+
     // Spawn server task
     let daemon_clone = daemon.clone();
-    let _server_handle = tokio::spawn(async move {
+    let server_handle = tokio::spawn(async move {
         if let Err(e) = daemon_clone.serve().await {
             tracing::error!("Server error: {}", e);
         }
     });
 
-    // Wait for Ctrl+C
-    tokio::signal::ctrl_c().await?;
-    info!("Received shutdown signal");
+    // A start that keeps serving for a while no longer counts towards a crash
+    // loop
+    tokio::spawn(async move {
+        tokio::time::sleep(STABLE_AFTER).await;
+        if !server_handle.is_finished()
+            && let Err(e) = startup.mark_stable().await
+        {
+            warn!("Failed to record stable start: {}", e);
+        }
+    });
+
+    // Wait for Ctrl+C, SIGTERM or a shutdown request on the control socket
+    tokio::select! {
+        signal = tokio::signal::ctrl_c() => {
+            signal?;
+            info!("Received shutdown signal");
+        }
+        signal = terminated() => {
+            signal?;
+            info!("Received SIGTERM");
+        }
+        _ = shutdown.notified() => info!("Shutdown requested over the control socket"),
+    }
 
     // Graceful shutdown
     daemon.system_identity().shutdown().await?;
+    if let Err(e) = StartupTracker::new(&StateDir::new().await?)
+        .mark_stable()
+        .await
+    {
+        warn!("Failed to record clean shutdown: {}", e);
+    }
+
+    Ok(())
+}
+
+/// Resolves when the process is asked to terminate, as Kubernetes does
+/// before killing a pod
+#[cfg(unix)]
+async fn terminated() -> std::io::Result<()> {
+    use tokio::signal::unix::{SignalKind, signal};
+    signal(SignalKind::terminate())?.recv().await;
+    Ok(())
+}
 
+#[cfg(not(unix))]
+async fn terminated() -> std::io::Result<()> {
+    std::future::pending().await
+}
+
+/// Builder for the daemon with the given settings
+fn daemon_builder(
+    settings: Settings,
+    state_dir: StateDir,
+    safe_mode: Option<SafeMode>,
+) -> DaemonBuilder {
+    let builder = Daemon::builder()
+        .with_settings(settings)
+        .with_state_dir(state_dir)
+        .with_safe_mode(safe_mode);
+
+    // Set static directory if specified
+    if let Ok(static_dir) = std::env::var("GATE_SERVER__STATIC_DIR") {
+        info!("Using static directory from environment: {}", static_dir);
+        builder.with_static_dir(static_dir)
+    } else {
+        builder.with_static_dir("crates/frontend-daemon/dist".to_string())
+    }
+}
+
+/// Run the doctor checks and print the report, failing if any check failed
+async fn run_doctor(settings: Settings, state_dir: &StateDir, json: bool) -> Result<()> {
+    let report = DoctorService::new(settings, state_dir).run().await;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        for check in &report.checks {
+            let label = match check.status {
+                CheckStatus::Pass => "PASS",
+                CheckStatus::Warn => "WARN",
+                CheckStatus::Fail => "FAIL",
+                CheckStatus::Skipped => "SKIP",
+            };
+            println!("[{label}] {}: {}", check.name, check.message);
+            if let Some(remediation) = &check.remediation {
+                println!("       -> {remediation}");
+            }
+        }
+    }
+
+    if !report.healthy {
+        anyhow::bail!("One or more doctor checks failed");
+    }
+    Ok(())
+}
+
+/// Call a control socket method and print its result as JSON
+#[cfg(unix)]
+async fn run_ctl(socket: &Path, method: &str, params: Option<&str>) -> Result<()> {
+    let params = match params {
+        Some(params) => serde_json::from_str(params)?,
+        None => serde_json::Value::Null,
+    };
+    let result = gate_daemon::daemon::control::call(socket, method, params)
+        .await
+        .map_err(|e| anyhow::anyhow!("Control call to {} failed: {e}", socket.display()))?;
+    println!("{}", serde_json::to_string_pretty(&result)?);
+    Ok(())
+}
+
+#[cfg(not(unix))]
+async fn run_ctl(_socket: &Path, _method: &str, _params: Option<&str>) -> Result<()> {
+    anyhow::bail!("The control socket needs Unix domain sockets")
+}
+
+/// Export the configuration, or import one into the config file at `path`
+async fn run_config(action: ConfigCommand, settings: Settings, path: &Path) -> Result<()> {
+    match action {
+        ConfigCommand::Export { output } => {
+            let text = serde_json::to_string_pretty(&settings.export())?;
+            match output {
+                Some(output) => {
+                    std::fs::write(&output, text)?;
+                    println!("Exported configuration to {}", output.display());
+                }
+                None => println!("{text}"),
+            }
+        }
+        ConfigCommand::Import {
+            file,
+            secrets,
+            dry_run,
+        } => {
+            let document: serde_json::Value =
+                serde_json::from_str(&std::fs::read_to_string(&file)?)?;
+            let import = Settings::import(&document, &settings, &secrets.into_iter().collect());
+
+            for issue in &import.issues {
+                let location = issue.path.as_deref().unwrap_or("");
+                println!("[{:?}] {location}: {}", issue.severity, issue.message);
+            }
+            for secret in &import.secrets {
+                println!("  {:?}: {}", secret.status, secret.name);
+            }
+            let Some(imported) = import.settings.as_ref() else {
+                anyhow::bail!("Configuration in {} is invalid", file.display());
+            };
+            if dry_run {
+                println!("Dry run: {} not written", path.display());
+            } else {
+                imported.save_to_file(path).await?;
+                println!("Imported configuration into {}", path.display());
+            }
+
+            let missing: Vec<_> = import.missing_secrets().map(|s| s.name.as_str()).collect();
+            if !missing.is_empty() {
+                println!("Re-enter these secrets before use: {}", missing.join(", "));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Print the migration status of the state database, failing if it is not
+/// up to date
+async fn check_migrations(state_dir: &StateDir) -> Result<()> {
+    let backend = SqliteStateBackend::connect(&state_dir.database_url()).await?;
+    let status = backend.status().await?;
+
+    println!(
+        "Database schema at version {} of {}",
+        status.current, status.latest
+    );
+    for migration in &status.pending {
+        println!("  pending: {} {}", migration.version, migration.description);
+    }
+    for version in &status.unknown {
+        println!("  unknown: {version} (applied by a newer release)");
+    }
+
+    if !status.unknown.is_empty() {
+        anyhow::bail!("Database was migrated by a newer release");
+    }
+    if !status.pending.is_empty() {
+        anyhow::bail!("{} migration(s) pending", status.pending.len());
+    }
+    Ok(())
+}
+
+/// Drive load at a live daemon and print the report, failing on errors or a
+/// p99 above `max_p99_ms`
+async fn run_bench(config: LoadConfig, max_p99_ms: Option<f64>, json: bool) -> Result<()> {
+    let report = gate_bench::load::run(&config).await?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!(
+            "{} requests to {} ({} failed) in {:.0} ms, {:.1} req/s",
+            report.requests,
+            config.base_url,
+            report.failures,
+            report.elapsed_ms,
+            report.requests_per_second
+        );
+        for (label, p) in [
+            ("latency", &report.latency),
+            ("first byte", &report.first_byte),
+        ] {
+            println!(
+                "{label:>10}: p50 {:.1} ms, p95 {:.1} ms, p99 {:.1} ms, max {:.1} ms",
+                p.p50_ms, p.p95_ms, p.p99_ms, p.max_ms
+            );
+        }
+    }
+
+    if report.failures > 0 {
+        anyhow::bail!("{} of {} requests failed", report.failures, report.requests);
+    }
+    if let Some(max) = max_p99_ms
+        && report.latency.p99_ms > max
+    {
+        anyhow::bail!(
+            "p99 latency {:.1} ms exceeds the {max:.1} ms limit",
+            report.latency.p99_ms
+        );
+    }
     Ok(())
 }
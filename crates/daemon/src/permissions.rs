@@ -1,13 +1,14 @@
+use crate::config::PermissionAuditConfig;
 use async_trait::async_trait;
-use gate_core::StateBackend;
 use gate_core::access::{
     Action, IdentityContext, ObjectIdentity, PermissionDenied, PermissionManager, PermissionResult,
-    Permissions, SubjectIdentity,
+    Permissions, SubjectIdentity, has_inherited_permission,
 };
+use gate_core::{PermissionDecision, StateBackend};
 use gate_http::services::identity::HttpIdentity;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 
 /// Local context for self-hosted deployments
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -66,11 +67,63 @@ pub type LocalIdentity = SubjectIdentity<LocalContext>;
 
 pub struct LocalPermissionManager {
     backend: Arc<dyn StateBackend>,
+    audit: RwLock<PermissionAuditConfig>,
 }
 
 impl LocalPermissionManager {
     pub fn new(backend: Arc<dyn StateBackend>) -> Self {
-        Self { backend }
+        Self {
+            backend,
+            audit: RwLock::new(PermissionAuditConfig::default()),
+        }
+    }
+
+    /// Change which permission checks are recorded in the audit log
+    pub fn set_audit(&self, config: PermissionAuditConfig) {
+        *self.audit.write().unwrap() = config;
+    }
+
+    /// Record a decision if auditing is on and it is sampled. The write
+    /// happens in the background so checks never wait on the audit log.
+    fn record(
+        &self,
+        subject: &SubjectIdentity<LocalContext>,
+        action: &Action,
+        object: &ObjectIdentity,
+        policy: &str,
+        result: &PermissionResult,
+    ) {
+        let rate = {
+            let audit = self.audit.read().unwrap();
+            if !audit.enabled {
+                return;
+            }
+            if result.is_ok() {
+                audit.allowed_sample_rate
+            } else {
+                audit.denied_sample_rate
+            }
+        };
+        if rand::random::<f64>() >= rate {
+            return;
+        }
+
+        let decision = PermissionDecision {
+            id: uuid::Uuid::new_v4().to_string(),
+            subject_id: subject.id.clone(),
+            action: format!("{action:?}"),
+            object: object.to_string(),
+            allowed: result.is_ok(),
+            policy: policy.to_string(),
+            reason: result.as_ref().err().map(ToString::to_string),
+            created_at: chrono::Utc::now(),
+        };
+        let backend = self.backend.clone();
+        tokio::spawn(async move {
+            if let Err(e) = backend.record_permission_decision(&decision).await {
+                warn!("Failed to record permission decision: {e}");
+            }
+        });
     }
 
     /// Initialize the first user as owner with all permissions
@@ -95,20 +148,25 @@ impl Permissions<LocalContext> for LocalPermissionManager {
         action: Action,
         object: &ObjectIdentity,
     ) -> PermissionResult {
-        if subject.context.is_owner {
-            return Ok(());
-        }
+        let (policy, result) = if subject.context.is_owner {
+            ("owner", Ok(()))
+        } else {
+            // Check for a grant to the subject or its groups, on the object
+            // or one above it
+            match has_inherited_permission(self.backend.as_ref(), &subject.id, &action, object)
+                .await
+            {
+                Ok(true) => ("grant", Ok(())),
+                Ok(false) => ("no_grant", Err(PermissionDenied::NotAuthorized)),
+                Err(e) => (
+                    "error",
+                    Err(PermissionDenied::Custom(format!("Database error: {e}"))),
+                ),
+            }
+        };
 
-        // Check database for specific permission
-        match self
-            .backend
-            .has_permission(&subject.id, &action, object)
-            .await
-        {
-            Ok(true) => Ok(()),
-            Ok(false) => Err(PermissionDenied::NotAuthorized),
-            Err(e) => Err(PermissionDenied::Custom(format!("Database error: {e}"))),
-        }
+        self.record(subject, &action, object, policy, &result);
+        result
     }
 }
 
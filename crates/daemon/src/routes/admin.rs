@@ -1,11 +1,13 @@
 //! Admin user management routes - refactored version
 
 use crate::helpers::{admin::AdminPermissionHelper, errors::ErrorMapExt};
+use crate::services::limits::UserQuota;
+use crate::services::observer::{OBSERVER_SCOPE, observer_permissions};
 use axum::{
     Router,
     extract::{Path, State},
     response::Json,
-    routing::{get, patch},
+    routing::{get, patch, put},
 };
 use gate_core::access::{
     Action, ObjectId, ObjectIdentity, ObjectKind, PermissionManager, TargetNamespace,
@@ -13,6 +15,63 @@ use gate_core::access::{
 use gate_core::types::User;
 use gate_http::{AppState, error::HttpError, services::HttpIdentity};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+pub(crate) const ROLE_METADATA_KEY: &str = "role";
+
+/// Built-in roles, each granting a fixed bundle of permissions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UserRole {
+    /// Reads status, usage and health only; sessions issued after the role
+    /// is assigned carry the observer scope
+    Observer,
+    Viewer,
+    Member,
+    Admin,
+}
+
+impl UserRole {
+    fn as_str(self) -> &'static str {
+        match self {
+            UserRole::Observer => OBSERVER_SCOPE,
+            UserRole::Viewer => "viewer",
+            UserRole::Member => "member",
+            UserRole::Admin => "admin",
+        }
+    }
+
+    fn from_metadata(metadata: &HashMap<String, String>) -> Option<Self> {
+        match metadata.get(ROLE_METADATA_KEY)?.as_str() {
+            OBSERVER_SCOPE => Some(UserRole::Observer),
+            "viewer" => Some(UserRole::Viewer),
+            "member" => Some(UserRole::Member),
+            "admin" => Some(UserRole::Admin),
+            _ => None,
+        }
+    }
+
+    /// Permissions granted by this role
+    fn permissions(self) -> Vec<(Action, ObjectIdentity)> {
+        let all_models = ObjectIdentity {
+            namespace: TargetNamespace::Local,
+            kind: ObjectKind::Model,
+            id: ObjectId::new("*"),
+        };
+        match self {
+            UserRole::Observer => observer_permissions(),
+            UserRole::Viewer => vec![(Action::Read, all_models)],
+            UserRole::Member => vec![
+                (Action::Read, all_models.clone()),
+                (Action::Execute, all_models),
+            ],
+            UserRole::Admin => vec![
+                (Action::GrantPermission, ObjectIdentity::wildcard()),
+                (Action::Manage, ObjectIdentity::wildcard()),
+            ],
+        }
+    }
+}
 
 // Re-use existing type definitions
 #[derive(Debug, Serialize, Deserialize)]
@@ -31,12 +90,14 @@ pub struct UserInfo {
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
     pub disabled_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub role: Option<UserRole>,
 }
 
 impl From<User> for UserInfo {
     fn from(user: User) -> Self {
         let enabled = user.is_enabled();
         UserInfo {
+            role: UserRole::from_metadata(&user.metadata),
             id: user.id,
             name: user.name,
             enabled,
@@ -91,6 +152,23 @@ pub struct GrantPermissionRequest {
     pub object: String,
 }
 
+#[derive(Debug, Serialize)]
+pub struct PermissionChangeResponse {
+    pub action: String,
+    pub object: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateUserRoleRequest {
+    /// `None` clears the role, revoking its permissions
+    pub role: Option<UserRole>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UpdateUserRoleResponse {
+    pub user: UserInfo,
+}
+
 /// List all users (admin only)
 #[instrument(name = "list_users", skip(app_state), fields(page = %query.page, page_size = %query.page_size))]
 pub async fn list_users(
@@ -411,7 +489,7 @@ pub async fn revoke_user_permission(
     identity: HttpIdentity,
     State(app_state): State<AppState<crate::State>>,
     Path(user_id): Path<String>,
-    axum::extract::Query(params): axum::extract::Query<std::collections::HashMap<String, String>>,
+    axum::extract::Query(params): axum::extract::Query<HashMap<String, String>>,
 ) -> Result<axum::http::StatusCode, HttpError> {
     let action = params
         .get("action")
@@ -477,9 +555,154 @@ pub async fn revoke_user_permission(
     Ok(axum::http::StatusCode::NO_CONTENT)
 }
 
-// Helper function to parse action string
-fn parse_action(s: &str) -> Option<Action> {
-    match s {
+/// Assign a built-in role, replacing the permissions of any previous role
+#[instrument(name = "update_user_role", skip(app_state), fields(target_user_id = %user_id, role = ?request.role))]
+pub async fn update_user_role(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(user_id): Path<String>,
+    Json(request): Json<UpdateUserRoleRequest>,
+) -> Result<Json<UpdateUserRoleResponse>, HttpError> {
+    let helper = AdminPermissionHelper::new(&app_state.data.daemon, identity.clone()).await?;
+
+    helper
+        .require_admin(
+            Action::GrantPermission,
+            &ObjectIdentity {
+                namespace: TargetNamespace::System,
+                kind: ObjectKind::User,
+                id: ObjectId::new(user_id.clone()),
+            },
+        )
+        .await?;
+
+    let mut user = helper
+        .state_backend
+        .get_user(&user_id)
+        .await
+        .map_internal_error()?
+        .ok_or_else(|| HttpError::NotFound(format!("User {user_id} not found")))?;
+
+    let subject = gate_core::access::SubjectIdentity::new(
+        user.id.clone(),
+        "user",
+        crate::permissions::LocalContext {
+            is_owner: false,
+            node_id: "local".to_string(),
+        },
+    );
+
+    if let Some(previous) = UserRole::from_metadata(&user.metadata) {
+        for (action, object) in previous.permissions() {
+            helper
+                .permission_manager
+                .revoke(&helper.local_identity, &subject, action, &object)
+                .await
+                .map_internal_error_with_context("Failed to revoke role permission")?;
+        }
+    }
+
+    match request.role {
+        Some(role) => {
+            for (action, object) in role.permissions() {
+                helper
+                    .permission_manager
+                    .grant(&helper.local_identity, &subject, action, &object)
+                    .await
+                    .map_internal_error_with_context("Failed to grant role permission")?;
+            }
+            user.metadata
+                .insert(ROLE_METADATA_KEY.to_string(), role.as_str().to_string());
+        }
+        None => {
+            user.metadata.remove(ROLE_METADATA_KEY);
+        }
+    }
+    user.updated_at = chrono::Utc::now();
+
+    helper
+        .state_backend
+        .update_user(&user)
+        .await
+        .map_internal_error_with_context("Failed to update user role")?;
+
+    info!(
+        "Admin {} set role of user {} to {:?}",
+        identity.id, user_id, request.role
+    );
+
+    Ok(Json(UpdateUserRoleResponse {
+        user: UserInfo::from(user),
+    }))
+}
+
+/// Get a user's quota
+#[instrument(name = "get_user_quota", skip(app_state), fields(target_user_id = %user_id))]
+pub async fn get_user_quota(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(user_id): Path<String>,
+) -> Result<Json<UserQuota>, HttpError> {
+    let helper = AdminPermissionHelper::new(&app_state.data.daemon, identity.clone()).await?;
+
+    helper
+        .require_admin(Action::ViewQuota, &ObjectIdentity::quota(user_id.clone()))
+        .await?;
+
+    let user = helper
+        .state_backend
+        .get_user(&user_id)
+        .await
+        .map_internal_error()?
+        .ok_or_else(|| HttpError::NotFound(format!("User {user_id} not found")))?;
+
+    Ok(Json(UserQuota::from_metadata(&user.metadata)))
+}
+
+/// Replace a user's quota
+#[instrument(name = "update_user_quota", skip(app_state), fields(target_user_id = %user_id))]
+pub async fn update_user_quota(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(user_id): Path<String>,
+    Json(quota): Json<UserQuota>,
+) -> Result<Json<UserQuota>, HttpError> {
+    if quota.monthly_spend_limit.is_some_and(|v| v < 0.0) {
+        return Err(HttpError::BadRequest(
+            "Monthly spend limit cannot be negative".to_string(),
+        ));
+    }
+
+    let helper = AdminPermissionHelper::new(&app_state.data.daemon, identity.clone()).await?;
+
+    helper
+        .require_admin(Action::UpdateQuota, &ObjectIdentity::quota(user_id.clone()))
+        .await?;
+
+    let mut user = helper
+        .state_backend
+        .get_user(&user_id)
+        .await
+        .map_internal_error()?
+        .ok_or_else(|| HttpError::NotFound(format!("User {user_id} not found")))?;
+
+    quota.apply_to(&mut user.metadata);
+    user.updated_at = chrono::Utc::now();
+
+    helper
+        .state_backend
+        .update_user(&user)
+        .await
+        .map_internal_error_with_context("Failed to update user quota")?;
+
+    info!("Admin {} updated quota for user {}", identity.id, user_id);
+    Ok(Json(quota))
+}
+
+// Helper function to parse action string, accepting the JSON-quoted form
+// permissions are stored in
+pub(crate) fn parse_action(s: &str) -> Option<Action> {
+    match s.trim_matches('"') {
         "read" | "Read" => Some(Action::Read),
         "write" | "Write" => Some(Action::Write),
         "delete" | "Delete" => Some(Action::Delete),
@@ -495,8 +718,9 @@ fn parse_action(s: &str) -> Option<Action> {
 }
 
 // Helper function to parse object identity string
-fn parse_object_identity(s: &str) -> Option<ObjectIdentity> {
-    let parts: Vec<&str> = s.split('/').collect();
+pub(crate) fn parse_object_identity(s: &str) -> Option<ObjectIdentity> {
+    // Ids may contain `/`, as models qualified by provider do
+    let parts: Vec<&str> = s.splitn(3, '/').collect();
     if parts.len() != 3 {
         return None;
     }
@@ -504,10 +728,10 @@ fn parse_object_identity(s: &str) -> Option<ObjectIdentity> {
     let namespace = match parts[0] {
         "system" => TargetNamespace::System,
         "local" => TargetNamespace::Local,
-        _ => return None,
+        ns => TargetNamespace::Organization(ns.strip_prefix("org:")?.to_string()),
     };
 
-    let kind = match parts[1] {
+    let kind = match parts[1].to_ascii_lowercase().as_str() {
         "model" => ObjectKind::Model,
         "provider" => ObjectKind::Provider,
         "user" => ObjectKind::User,
@@ -516,6 +740,7 @@ fn parse_object_identity(s: &str) -> Option<ObjectIdentity> {
         "billing" => ObjectKind::Billing,
         "system" => ObjectKind::System,
         "quota" => ObjectKind::Quota,
+        "project" => ObjectKind::Project,
         _ => return None,
     };
 
@@ -546,4 +771,9 @@ pub fn add_routes(
                 .post(grant_user_permission)
                 .delete(revoke_user_permission),
         )
+        .route("/api/admin/users/{user_id}/role", put(update_user_role))
+        .route(
+            "/api/admin/users/{user_id}/quota",
+            get(get_user_quota).put(update_user_quota),
+        )
 }
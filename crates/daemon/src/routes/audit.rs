@@ -0,0 +1,266 @@
+//! Admin routes for the permission audit log
+//!
+//! Lists recorded permission decisions, to answer why a request was denied,
+//! and compares a subject's grants with the checks they actually passed, to
+//! find grants an account never uses. Decisions are only recorded while
+//! `permission_audit.enabled` is set, and only a sample of them when the
+//! sample rates are below one. Requests for `gate/auto` record the model
+//! they were routed to and why, listed separately.
+
+use crate::helpers::{admin::AdminPermissionHelper, errors::ErrorMapExt};
+use axum::{
+    Router,
+    extract::{Path, Query, State},
+    response::Json,
+    routing::get,
+};
+use chrono::{DateTime, Utc};
+use gate_core::access::{Action, ObjectId, ObjectIdentity, ObjectKind, TargetNamespace};
+use gate_core::{PermissionDecision, PermissionDecisionFilter, RoutingDecision, StateBackend};
+use gate_http::{AppState, error::HttpError, services::HttpIdentity};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Decisions listed when the query sets no limit
+const DEFAULT_LIMIT: usize = 100;
+
+/// Most decisions listed, and considered for a subject's grant usage
+const MAX_LIMIT: usize = 10_000;
+
+#[derive(Debug, Deserialize)]
+pub struct DecisionQuery {
+    pub subject: Option<String>,
+    pub action: Option<String>,
+    pub allowed: Option<bool>,
+    pub since: Option<DateTime<Utc>>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DecisionListResponse {
+    pub decisions: Vec<PermissionDecision>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RoutingQuery {
+    pub user: Option<String>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RoutingListResponse {
+    pub decisions: Vec<RoutingDecision>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SubjectQuery {
+    pub since: Option<DateTime<Utc>>,
+}
+
+/// A stored grant and how often it let a check through
+#[derive(Debug, Serialize)]
+pub struct GrantUsage {
+    pub action: String,
+    pub object: String,
+    pub granted_at: DateTime<Utc>,
+    pub checks: u64,
+    pub last_used: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SubjectAuditResponse {
+    pub subject_id: String,
+    pub since: Option<DateTime<Utc>>,
+    pub allowed: u64,
+    pub denied: u64,
+    pub grants: Vec<GrantUsage>,
+    /// Grants no recorded check used; candidates for revocation
+    pub unused_grants: usize,
+}
+
+/// Check admin access to the audit log and fetch the state backend
+async fn admin_backend(
+    app_state: &AppState<crate::State>,
+    identity: &HttpIdentity,
+) -> Result<Arc<dyn StateBackend>, HttpError> {
+    let daemon = &app_state.data.daemon;
+    AdminPermissionHelper::new(daemon, identity.clone())
+        .await?
+        .require_admin(
+            Action::ViewPermissions,
+            &ObjectIdentity {
+                namespace: TargetNamespace::System,
+                kind: ObjectKind::System,
+                id: ObjectId::new("audit"),
+            },
+        )
+        .await?;
+    daemon.get_state_backend().await.map_internal_error()
+}
+
+/// List permission decisions, newest first (admin only)
+#[instrument(name = "list_permission_decisions", skip(app_state))]
+pub async fn list_decisions(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Query(query): Query<DecisionQuery>,
+) -> Result<Json<DecisionListResponse>, HttpError> {
+    let backend = admin_backend(&app_state, &identity).await?;
+    let filter = PermissionDecisionFilter {
+        subject_id: query.subject,
+        action: query.action,
+        allowed: query.allowed,
+        since: query.since,
+    };
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+    let decisions = backend
+        .list_permission_decisions(&filter, limit)
+        .await
+        .map_internal_error_with_context("Failed to list permission decisions")?;
+    Ok(Json(DecisionListResponse { decisions }))
+}
+
+/// List automatic routing decisions, newest first (admin only)
+#[instrument(name = "list_routing_decisions", skip(app_state))]
+pub async fn list_routing(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Query(query): Query<RoutingQuery>,
+) -> Result<Json<RoutingListResponse>, HttpError> {
+    let backend = admin_backend(&app_state, &identity).await?;
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+    let decisions = backend
+        .list_routing_decisions(query.user.as_deref(), limit)
+        .await
+        .map_internal_error_with_context("Failed to list routing decisions")?;
+    Ok(Json(RoutingListResponse { decisions }))
+}
+
+/// Compare a subject's grants with its recorded decisions (admin only)
+#[instrument(name = "get_subject_audit", skip(app_state))]
+pub async fn get_subject(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(subject_id): Path<String>,
+    Query(query): Query<SubjectQuery>,
+) -> Result<Json<SubjectAuditResponse>, HttpError> {
+    let backend = admin_backend(&app_state, &identity).await?;
+    let filter = PermissionDecisionFilter {
+        subject_id: Some(subject_id.clone()),
+        since: query.since,
+        ..Default::default()
+    };
+    let decisions = backend
+        .list_permission_decisions(&filter, MAX_LIMIT)
+        .await
+        .map_internal_error_with_context("Failed to list permission decisions")?;
+    let grants = backend
+        .list_user_permissions(&subject_id)
+        .await
+        .map_internal_error_with_context("Failed to list permissions")?;
+
+    Ok(Json(summarize(subject_id, query.since, grants, &decisions)))
+}
+
+fn summarize(
+    subject_id: String,
+    since: Option<DateTime<Utc>>,
+    grants: Vec<(String, String, DateTime<Utc>)>,
+    decisions: &[PermissionDecision],
+) -> SubjectAuditResponse {
+    let grants: Vec<GrantUsage> = grants
+        .into_iter()
+        .map(|(action, object, granted_at)| {
+            // Grants store the action as JSON, decisions by name
+            let action = serde_json::from_str::<String>(&action).unwrap_or(action);
+            let used: Vec<_> = decisions
+                .iter()
+                .filter(|d| d.policy == "grant" && d.action == action && covers(&object, d))
+                .collect();
+            GrantUsage {
+                checks: used.len() as u64,
+                last_used: used.iter().map(|d| d.created_at).max(),
+                action,
+                object,
+                granted_at,
+            }
+        })
+        .collect();
+    let allowed = decisions.iter().filter(|d| d.allowed).count() as u64;
+
+    SubjectAuditResponse {
+        subject_id,
+        since,
+        allowed,
+        denied: decisions.len() as u64 - allowed,
+        unused_grants: grants.iter().filter(|g| g.checks == 0).count(),
+        grants,
+    }
+}
+
+/// Whether a grant on `object` covers the object a decision was about
+fn covers(object: &str, decision: &PermissionDecision) -> bool {
+    decision.object == object
+        || ObjectIdentity::from_string(&decision.object).is_ok_and(|checked| {
+            checked
+                .ancestors()
+                .iter()
+                .any(|ancestor| ancestor.to_string() == object)
+        })
+}
+
+/// Add permission audit routes
+pub fn add_routes(
+    router: Router<gate_http::AppState<crate::State>>,
+) -> Router<gate_http::AppState<crate::State>> {
+    router
+        .route("/api/admin/audit/permissions", get(list_decisions))
+        .route("/api/admin/audit/routing", get(list_routing))
+        .route(
+            "/api/admin/audit/permissions/subjects/{id}",
+            get(get_subject),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decision(action: &str, allowed: bool) -> PermissionDecision {
+        PermissionDecision {
+            id: uuid::Uuid::new_v4().to_string(),
+            subject_id: "user".to_string(),
+            action: action.to_string(),
+            object: "system/Config/*".to_string(),
+            allowed,
+            policy: if allowed { "grant" } else { "no_grant" }.to_string(),
+            reason: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_grants_without_allowed_checks_are_unused() {
+        let grants = vec![
+            (
+                "\"Read\"".to_string(),
+                "system/Config/*".to_string(),
+                Utc::now(),
+            ),
+            (
+                "\"Write\"".to_string(),
+                "system/Config/*".to_string(),
+                Utc::now(),
+            ),
+        ];
+        let mut inherited = decision("Read", true);
+        inherited.object = "system/Config/theme".to_string();
+        let decisions = [decision("Read", true), inherited, decision("Delete", false)];
+
+        let summary = summarize("user".to_string(), None, grants, &decisions);
+        assert_eq!((summary.allowed, summary.denied), (2, 1));
+        assert_eq!(summary.grants[0].checks, 2);
+        assert_eq!(summary.grants[1].action, "Write");
+        assert_eq!(summary.unused_grants, 1);
+    }
+}
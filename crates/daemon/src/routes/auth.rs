@@ -1,6 +1,10 @@
 //! Custom authentication routes with registration control
 
-use crate::types::BootstrapStatusResponse;
+use crate::routes::preferences::{Preferences, load_preferences};
+use crate::services::lockout::LockoutSubject;
+use crate::types::{
+    BootstrapRegenerateRequest, BootstrapRegenerateResponse, BootstrapStatusResponse,
+};
 use axum::{
     Router,
     routing::{get, post},
@@ -34,6 +38,11 @@ pub async fn get_bootstrap_status(
     })?;
 
     let is_complete = bootstrap_manager.is_bootstrap_complete().await;
+    let token_expires_at = if needs_bootstrap {
+        bootstrap_manager.expires_at().await
+    } else {
+        None
+    };
 
     Ok(Json(BootstrapStatusResponse {
         needs_bootstrap,
@@ -43,24 +52,66 @@ pub async fn get_bootstrap_status(
         } else {
             "System is bootstrapped".to_string()
         },
+        token_expires_at,
     }))
 }
 
+/// Exchange the current bootstrap token, even an expired one, for a new one.
+/// Holding the current token proves it was displayed to the caller.
+#[instrument(name = "regenerate_bootstrap_token", skip(state, request))]
+pub async fn regenerate_bootstrap_token(
+    State(state): State<gate_http::AppState<crate::State>>,
+    Json(request): Json<BootstrapRegenerateRequest>,
+) -> Result<Json<BootstrapRegenerateResponse>, HttpError> {
+    let bootstrap_manager = state
+        .data
+        .daemon
+        .get_bootstrap_manager()
+        .await
+        .map_err(|e| HttpError::InternalServerError(e.to_string()))?;
+
+    if !bootstrap_manager.check_attempt().await {
+        warn!("Bootstrap token regeneration rate limited");
+        return Err(HttpError::RateLimitExceeded);
+    }
+    if !bootstrap_manager.is_current_token(&request.token).await {
+        warn!("Invalid bootstrap token provided for regeneration");
+        return Err(HttpError::AuthorizationFailed(
+            "Invalid bootstrap token".to_string(),
+        ));
+    }
+
+    let token = bootstrap_manager
+        .regenerate_token()
+        .await
+        .map_err(|e| HttpError::BadRequest(e.to_string()))?;
+    let expires_at = bootstrap_manager.expires_at().await.ok_or_else(|| {
+        HttpError::InternalServerError("Regenerated token has no expiry".to_string())
+    })?;
+    // The caller receives the new token here; don't show it anywhere else
+    bootstrap_manager.take_display_token().await;
+    info!("Bootstrap token regenerated");
+
+    Ok(Json(BootstrapRegenerateResponse { token, expires_at }))
+}
+
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct CurrentUser {
     pub id: String,
     pub name: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub preferences: Preferences,
 }
 
-impl From<User> for CurrentUser {
-    fn from(user: User) -> Self {
+impl CurrentUser {
+    fn new(user: User, preferences: Preferences) -> Self {
         Self {
             id: user.id,
             name: user.name,
             created_at: user.created_at,
             updated_at: user.updated_at,
+            preferences,
         }
     }
 }
@@ -109,7 +160,12 @@ pub async fn register_with_bootstrap(
         HttpError::AuthorizationFailed("Bootstrap token is required for this endpoint".to_string())
     })?;
 
-    info!("Bootstrap registration attempt with token: {}", token);
+    info!("Bootstrap registration attempt");
+
+    if !bootstrap_manager.check_attempt().await {
+        warn!("Bootstrap registration rate limited");
+        return Err(HttpError::RateLimitExceeded);
+    }
 
     // Check if bootstrap is still needed
     let needs_bootstrap = bootstrap_manager.needs_bootstrap().await.unwrap_or(true);
@@ -296,9 +352,27 @@ pub async fn auth_complete(
 
     let auth_service = &state.data.auth_service;
 
-    let (credential_id, counter) = webauthn_service
+    // Failures count against the credential tried as well as the address
+    let lockout = &state.data.auth_lockout;
+    let account = request
+        .credential
+        .get("id")
+        .and_then(|id| id.as_str())
+        .map(|id| LockoutSubject::Account(id.to_string()));
+    let subjects: Vec<_> = account.into_iter().collect();
+    if lockout.locked_until(&subjects, Utc::now()).is_some() {
+        warn!("Sign-in refused for locked out credential");
+        return Err(HttpError::RateLimitExceeded);
+    }
+
+    let result = webauthn_service
         .complete_authentication(request.session_id, request.credential)
-        .await?;
+        .await;
+    if let Err(HttpError::AuthenticationFailed(_)) = &result {
+        lockout.record_failure(&subjects, Utc::now()).await;
+    }
+    let (credential_id, counter) = result?;
+    lockout.record_success(&credential_id);
 
     let response = auth_service
         .complete_authentication(credential_id.clone(), counter)
@@ -331,7 +405,15 @@ async fn get_current_user(
         .map_err(|e| HttpError::InternalServerError(format!("Failed to get user: {e}")))?
         .ok_or_else(|| HttpError::AuthorizationFailed("User not found".to_string()))?;
 
-    Ok(Json(user_data.into()))
+    // Preferences are a convenience here; don't fail the lookup over them
+    let preferences = load_preferences(state_backend.as_ref(), &identity.id)
+        .await
+        .unwrap_or_else(|e| {
+            warn!("Failed to load preferences for {}: {e}", identity.id);
+            Preferences::default()
+        });
+
+    Ok(Json(CurrentUser::new(user_data, preferences)))
 }
 
 /// Add custom auth routes
@@ -340,6 +422,10 @@ pub fn add_routes(
 ) -> Router<gate_http::AppState<crate::State>> {
     router
         .route("/auth/bootstrap/status", get(get_bootstrap_status))
+        .route(
+            "/auth/bootstrap/regenerate",
+            post(regenerate_bootstrap_token),
+        )
         .route(
             "/auth/webauthn/register/bootstrap",
             post(register_with_bootstrap),
@@ -1,12 +1,86 @@
 //! Configuration management routes
 
 use crate::Settings;
-use axum::{Router, extract, response, routing::get};
+use crate::config::{ConfigChange, ConfigExport, ConfigIssue, IssueSeverity, SecretReport};
+use crate::helpers::errors::not_found;
+use crate::services::config_history::ConfigRevisionInfo;
+use axum::{
+    Router, extract, response,
+    routing::{get, post},
+};
 use gate_http::{
     error::HttpError,
     services::HttpIdentity,
     types::{ConfigResponse, ConfigUpdateRequest},
 };
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Raw configuration document to validate
+#[derive(Debug, Deserialize)]
+pub struct ConfigValidateRequest {
+    pub text: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConfigValidateResponse {
+    /// False when any issue is an error
+    pub valid: bool,
+    pub issues: Vec<ConfigIssue>,
+}
+
+/// Exported configuration to import, with values for its secrets
+#[derive(Debug, Deserialize)]
+pub struct ConfigImportRequest {
+    /// Output of the export endpoint, or a bare configuration document
+    pub config: serde_json::Value,
+    /// Secret values by name, for placeholders in `config`
+    #[serde(default)]
+    pub secrets: HashMap<String, String>,
+    /// Validate and report without applying
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConfigImportResponse {
+    /// False when any issue is an error
+    pub valid: bool,
+    pub applied: bool,
+    pub issues: Vec<ConfigIssue>,
+    /// How each secret placeholder was resolved; `missing` ones must be
+    /// re-entered
+    pub secrets: Vec<SecretReport>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConfigHistoryResponse {
+    /// Kept revisions, newest first
+    pub revisions: Vec<ConfigRevisionInfo>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConfigRevisionResponse {
+    #[serde(flatten)]
+    pub info: ConfigRevisionInfo,
+    /// The configuration as applied, with secrets replaced by placeholders
+    pub config: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConfigDiffQuery {
+    /// Revision to compare against; defaults to the one before
+    pub against: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConfigDiffResponse {
+    /// Revision compared against, absent for the oldest kept revision, which
+    /// has nothing to compare to
+    pub from: Option<u64>,
+    pub to: u64,
+    pub changes: Vec<ConfigChange>,
+}
 
 /// Get the full configuration
 pub async fn get_config(
@@ -55,9 +129,252 @@ pub async fn update_config(
     Ok(response::Json(ConfigResponse { config }))
 }
 
+/// Validate a raw configuration document without applying it
+pub async fn validate_config(
+    identity: HttpIdentity,
+    extract::State(state): extract::State<gate_http::AppState<crate::State>>,
+    extract::Json(request): extract::Json<ConfigValidateRequest>,
+) -> Result<response::Json<ConfigValidateResponse>, HttpError> {
+    // Validation reveals the schema, so require the same access as reading config
+    state
+        .data
+        .daemon
+        .clone()
+        .with_http_identity(&identity)
+        .await
+        .map_err(|e| HttpError::InternalServerError(e.to_string()))?
+        .get_config()
+        .await
+        .map_err(|e| HttpError::InternalServerError(e.to_string()))?;
+
+    let issues = Settings::validate_json(&request.text);
+    let valid = !issues
+        .iter()
+        .any(|issue| issue.severity == IssueSeverity::Error);
+    Ok(response::Json(ConfigValidateResponse { valid, issues }))
+}
+
+/// JSON Schema of the configuration, for building editor forms
+pub async fn get_schema(
+    identity: HttpIdentity,
+    extract::State(state): extract::State<gate_http::AppState<crate::State>>,
+) -> Result<response::Json<serde_json::Value>, HttpError> {
+    // The schema describes the configuration, so require access to read it
+    state
+        .data
+        .daemon
+        .clone()
+        .with_http_identity(&identity)
+        .await
+        .map_err(|e| HttpError::InternalServerError(e.to_string()))?
+        .get_config()
+        .await
+        .map_err(|e| HttpError::InternalServerError(e.to_string()))?;
+    Ok(response::Json(Settings::json_schema()))
+}
+
+/// Export the configuration with secrets replaced by placeholders
+pub async fn export_config(
+    identity: HttpIdentity,
+    extract::State(state): extract::State<gate_http::AppState<crate::State>>,
+) -> Result<response::Json<ConfigExport>, HttpError> {
+    let settings = state
+        .data
+        .daemon
+        .clone()
+        .with_http_identity(&identity)
+        .await
+        .map_err(|e| HttpError::InternalServerError(e.to_string()))?
+        .get_config()
+        .await
+        .map_err(|e| HttpError::InternalServerError(e.to_string()))?;
+    Ok(response::Json(settings.export()))
+}
+
+/// Import an exported configuration, keeping this instance's secrets where
+/// the import does not supply them
+pub async fn import_config(
+    identity: HttpIdentity,
+    extract::State(state): extract::State<gate_http::AppState<crate::State>>,
+    extract::Json(request): extract::Json<ConfigImportRequest>,
+) -> Result<response::Json<ConfigImportResponse>, HttpError> {
+    let daemon = state
+        .data
+        .daemon
+        .clone()
+        .with_http_identity(&identity)
+        .await
+        .map_err(|e| HttpError::InternalServerError(e.to_string()))?;
+    let current = daemon
+        .get_config()
+        .await
+        .map_err(|e| HttpError::InternalServerError(e.to_string()))?;
+
+    let import = Settings::import(&request.config, &current, &request.secrets);
+    let applied = match import.settings {
+        Some(settings) if !request.dry_run => {
+            daemon
+                .update_config(settings)
+                .await
+                .map_err(|e| HttpError::InternalServerError(e.to_string()))?;
+            true
+        }
+        _ => false,
+    };
+    let valid = !import
+        .issues
+        .iter()
+        .any(|issue| issue.severity == IssueSeverity::Error);
+    Ok(response::Json(ConfigImportResponse {
+        valid,
+        applied,
+        issues: import.issues,
+        secrets: import.secrets,
+    }))
+}
+
+/// Check access to read the configuration and fetch its history
+async fn readable_history(
+    state: &gate_http::AppState<crate::State>,
+    identity: &HttpIdentity,
+) -> Result<crate::services::ConfigHistory, HttpError> {
+    let daemon = state
+        .data
+        .daemon
+        .clone()
+        .with_http_identity(identity)
+        .await
+        .map_err(|e| HttpError::InternalServerError(e.to_string()))?;
+    daemon
+        .get_config()
+        .await
+        .map_err(|e| HttpError::InternalServerError(e.to_string()))?;
+    daemon
+        .get_config_history()
+        .await
+        .map_err(|e| HttpError::InternalServerError(e.to_string()))
+}
+
+/// List applied configuration revisions, newest first
+pub async fn list_history(
+    identity: HttpIdentity,
+    extract::State(state): extract::State<gate_http::AppState<crate::State>>,
+) -> Result<response::Json<ConfigHistoryResponse>, HttpError> {
+    let history = readable_history(&state, &identity).await?;
+    let revisions = history
+        .list()
+        .await
+        .map_err(|e| HttpError::InternalServerError(e.to_string()))?;
+    Ok(response::Json(ConfigHistoryResponse { revisions }))
+}
+
+/// Get one configuration revision, secrets replaced by placeholders
+pub async fn get_revision(
+    identity: HttpIdentity,
+    extract::State(state): extract::State<gate_http::AppState<crate::State>>,
+    extract::Path(revision): extract::Path<u64>,
+) -> Result<response::Json<ConfigRevisionResponse>, HttpError> {
+    let history = readable_history(&state, &identity).await?;
+    let stored = history
+        .get(revision)
+        .await
+        .map_err(|e| HttpError::InternalServerError(e.to_string()))?
+        .ok_or_else(|| not_found("Config revision", &revision.to_string()))?;
+    Ok(response::Json(ConfigRevisionResponse {
+        info: stored.info,
+        config: stored.settings.export().config,
+    }))
+}
+
+/// Settings a revision changed, compared to the revision before it or to
+/// `against`
+pub async fn diff_revision(
+    identity: HttpIdentity,
+    extract::State(state): extract::State<gate_http::AppState<crate::State>>,
+    extract::Path(revision): extract::Path<u64>,
+    extract::Query(query): extract::Query<ConfigDiffQuery>,
+) -> Result<response::Json<ConfigDiffResponse>, HttpError> {
+    let history = readable_history(&state, &identity).await?;
+    let load = |revision: u64| {
+        let history = history.clone();
+        async move {
+            history
+                .get(revision)
+                .await
+                .map_err(|e| HttpError::InternalServerError(e.to_string()))?
+                .ok_or_else(|| not_found("Config revision", &revision.to_string()))
+        }
+    };
+    let to = load(revision).await?;
+
+    let from = match query.against {
+        Some(against) => Some(load(against).await?),
+        // The nearest older revision still kept
+        None => {
+            let older = history
+                .list()
+                .await
+                .map_err(|e| HttpError::InternalServerError(e.to_string()))?
+                .into_iter()
+                .find(|info| info.revision < revision);
+            match older {
+                Some(info) => Some(load(info.revision).await?),
+                None => None,
+            }
+        }
+    };
+    let changes = from
+        .as_ref()
+        .map(|from| from.settings.diff(&to.settings))
+        .unwrap_or_default();
+    Ok(response::Json(ConfigDiffResponse {
+        from: from.map(|from| from.info.revision),
+        to: revision,
+        changes,
+    }))
+}
+
+/// Re-apply a configuration revision, recording it as a new revision
+pub async fn rollback_config(
+    identity: HttpIdentity,
+    extract::State(state): extract::State<gate_http::AppState<crate::State>>,
+    extract::Path(revision): extract::Path<u64>,
+) -> Result<response::Json<ConfigResponse>, HttpError> {
+    let settings = state
+        .data
+        .daemon
+        .clone()
+        .with_http_identity(&identity)
+        .await
+        .map_err(|e| HttpError::InternalServerError(e.to_string()))?
+        .rollback_config(revision)
+        .await
+        .map_err(|e| HttpError::InternalServerError(e.to_string()))?
+        .ok_or_else(|| not_found("Config revision", &revision.to_string()))?;
+    info!(
+        "User {} rolled the configuration back to revision {}",
+        identity.id, revision
+    );
+    let config = serde_json::to_value(settings)
+        .map_err(|e| HttpError::InternalServerError(e.to_string()))?;
+    Ok(response::Json(ConfigResponse { config }))
+}
+
 /// Add config routes to a router
 pub fn add_routes(
     router: Router<gate_http::AppState<crate::State>>,
 ) -> Router<gate_http::AppState<crate::State>> {
-    router.route("/api/config", get(get_config).put(update_config))
+    router
+        .route("/api/config", get(get_config).put(update_config))
+        .route("/api/config/validate", post(validate_config))
+        .route("/api/config/schema", get(get_schema))
+        .route("/api/config/export", get(export_config))
+        .route("/api/config/import", post(import_config))
+        .route("/api/config/history", get(list_history))
+        .route("/api/config/history/{revision}", get(get_revision))
+        .route("/api/config/history/{revision}/diff", get(diff_revision))
+        .route(
+            "/api/config/history/{revision}/rollback",
+            post(rollback_config),
+        )
 }
@@ -0,0 +1,298 @@
+//! Saved chat conversations for the authenticated user
+
+use crate::helpers::errors::ErrorMapExt;
+use axum::{
+    Router,
+    extract::{Path, State},
+    response::Json,
+    routing::get,
+};
+use chrono::{DateTime, Utc};
+use gate_core::{Conversation, StateBackend};
+use gate_http::{AppState, error::HttpError, services::HttpIdentity};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::sync::Arc;
+
+const DEFAULT_TITLE: &str = "New conversation";
+const MAX_DERIVED_TITLE_CHARS: usize = 60;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConversationSummary {
+    pub id: String,
+    pub title: String,
+    pub model: Option<String>,
+    pub message_count: usize,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<&Conversation> for ConversationSummary {
+    fn from(conversation: &Conversation) -> Self {
+        ConversationSummary {
+            id: conversation.id.clone(),
+            title: conversation.title.clone(),
+            model: conversation.model.clone(),
+            message_count: conversation.messages.as_array().map_or(0, Vec::len),
+            created_at: conversation.created_at,
+            updated_at: conversation.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConversationListResponse {
+    pub conversations: Vec<ConversationSummary>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConversationInfo {
+    pub id: String,
+    pub title: String,
+    pub model: Option<String>,
+    pub messages: JsonValue,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<Conversation> for ConversationInfo {
+    fn from(conversation: Conversation) -> Self {
+        ConversationInfo {
+            id: conversation.id,
+            title: conversation.title,
+            model: conversation.model,
+            messages: conversation.messages,
+            created_at: conversation.created_at,
+            updated_at: conversation.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateConversationRequest {
+    pub title: Option<String>,
+    pub model: Option<String>,
+    #[serde(default = "empty_messages")]
+    pub messages: JsonValue,
+}
+
+/// Partial update; omitted fields are left unchanged
+#[derive(Debug, Deserialize)]
+pub struct UpdateConversationRequest {
+    pub title: Option<String>,
+    pub model: Option<String>,
+    pub messages: Option<JsonValue>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeleteConversationResponse {
+    pub id: String,
+}
+
+fn empty_messages() -> JsonValue {
+    JsonValue::Array(Vec::new())
+}
+
+fn validate_messages(messages: &JsonValue) -> Result<(), HttpError> {
+    if messages.is_array() {
+        Ok(())
+    } else {
+        Err(HttpError::BadRequest(
+            "messages must be a JSON array".to_string(),
+        ))
+    }
+}
+
+/// Title from the first user message, falling back to a generic title
+fn derive_title(messages: &JsonValue) -> String {
+    messages
+        .as_array()
+        .and_then(|messages| {
+            messages
+                .iter()
+                .find(|m| m.get("role").and_then(|r| r.as_str()) == Some("user"))
+        })
+        .and_then(|m| m.get("content").and_then(|c| c.as_str()))
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .map(|text| {
+            let title: String = text.chars().take(MAX_DERIVED_TITLE_CHARS).collect();
+            if text.chars().count() > MAX_DERIVED_TITLE_CHARS {
+                format!("{title}…")
+            } else {
+                title
+            }
+        })
+        .unwrap_or_else(|| DEFAULT_TITLE.to_string())
+}
+
+async fn state_backend(
+    app_state: &AppState<crate::State>,
+) -> Result<Arc<dyn StateBackend>, HttpError> {
+    app_state
+        .data
+        .daemon
+        .get_state_backend()
+        .await
+        .map_internal_error()
+}
+
+/// Load a conversation, treating other users' conversations as missing
+async fn owned_conversation(
+    backend: &dyn StateBackend,
+    identity: &HttpIdentity,
+    id: &str,
+) -> Result<Conversation, HttpError> {
+    backend
+        .get_conversation(id)
+        .await
+        .map_internal_error()?
+        .filter(|c| c.user_id == identity.id)
+        .ok_or_else(|| HttpError::NotFound(format!("Conversation {id} not found")))
+}
+
+/// List the caller's conversations, most recent first
+#[instrument(name = "list_conversations", skip(app_state))]
+pub async fn list_conversations(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+) -> Result<Json<ConversationListResponse>, HttpError> {
+    let conversations = state_backend(&app_state)
+        .await?
+        .list_conversations(&identity.id)
+        .await
+        .map_internal_error_with_context("Failed to list conversations")?;
+
+    Ok(Json(ConversationListResponse {
+        conversations: conversations
+            .iter()
+            .map(ConversationSummary::from)
+            .collect(),
+    }))
+}
+
+/// Save a new conversation
+#[instrument(name = "create_conversation", skip(app_state, request))]
+pub async fn create_conversation(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Json(request): Json<CreateConversationRequest>,
+) -> Result<Json<ConversationInfo>, HttpError> {
+    validate_messages(&request.messages)?;
+
+    let now = Utc::now();
+    let conversation = Conversation {
+        id: uuid::Uuid::new_v4().to_string(),
+        user_id: identity.id.clone(),
+        title: request
+            .title
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .unwrap_or_else(|| derive_title(&request.messages)),
+        model: request.model,
+        messages: request.messages,
+        created_at: now,
+        updated_at: now,
+    };
+
+    state_backend(&app_state)
+        .await?
+        .save_conversation(&conversation)
+        .await
+        .map_internal_error_with_context("Failed to save conversation")?;
+
+    debug!(
+        "User {} created conversation {}",
+        identity.id, conversation.id
+    );
+    Ok(Json(ConversationInfo::from(conversation)))
+}
+
+/// Get one of the caller's conversations with its messages
+#[instrument(name = "get_conversation", skip(app_state))]
+pub async fn get_conversation(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(conversation_id): Path<String>,
+) -> Result<Json<ConversationInfo>, HttpError> {
+    let backend = state_backend(&app_state).await?;
+    let conversation = owned_conversation(backend.as_ref(), &identity, &conversation_id).await?;
+    Ok(Json(ConversationInfo::from(conversation)))
+}
+
+/// Rename a conversation or replace its messages
+#[instrument(name = "update_conversation", skip(app_state, request))]
+pub async fn update_conversation(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(conversation_id): Path<String>,
+    Json(request): Json<UpdateConversationRequest>,
+) -> Result<Json<ConversationInfo>, HttpError> {
+    let backend = state_backend(&app_state).await?;
+    let mut conversation =
+        owned_conversation(backend.as_ref(), &identity, &conversation_id).await?;
+
+    if let Some(title) = request.title {
+        let title = title.trim();
+        if title.is_empty() {
+            return Err(HttpError::BadRequest("Title cannot be empty".to_string()));
+        }
+        conversation.title = title.to_string();
+    }
+    if let Some(messages) = request.messages {
+        validate_messages(&messages)?;
+        conversation.messages = messages;
+    }
+    if request.model.is_some() {
+        conversation.model = request.model;
+    }
+    conversation.updated_at = Utc::now();
+
+    backend
+        .save_conversation(&conversation)
+        .await
+        .map_internal_error_with_context("Failed to save conversation")?;
+
+    Ok(Json(ConversationInfo::from(conversation)))
+}
+
+/// Delete one of the caller's conversations
+#[instrument(name = "delete_conversation", skip(app_state))]
+pub async fn delete_conversation(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(conversation_id): Path<String>,
+) -> Result<Json<DeleteConversationResponse>, HttpError> {
+    let backend = state_backend(&app_state).await?;
+    owned_conversation(backend.as_ref(), &identity, &conversation_id).await?;
+
+    backend
+        .delete_conversation(&conversation_id)
+        .await
+        .map_internal_error_with_context("Failed to delete conversation")?;
+
+    debug!(
+        "User {} deleted conversation {}",
+        identity.id, conversation_id
+    );
+    Ok(Json(DeleteConversationResponse {
+        id: conversation_id,
+    }))
+}
+
+/// Add conversation routes
+pub fn add_routes(
+    router: Router<gate_http::AppState<crate::State>>,
+) -> Router<gate_http::AppState<crate::State>> {
+    router
+        .route(
+            "/api/conversations",
+            get(list_conversations).post(create_conversation),
+        )
+        .route(
+            "/api/conversations/{conversation_id}",
+            get(get_conversation)
+                .patch(update_conversation)
+                .delete(delete_conversation),
+        )
+}
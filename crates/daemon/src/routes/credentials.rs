@@ -0,0 +1,100 @@
+//! Provider credential health routes
+
+use crate::helpers::{admin::AdminPermissionHelper, errors::ErrorMapExt};
+use crate::routes::providers::config_name;
+use crate::services::CredentialChecker;
+use crate::services::credentials::CredentialStatus;
+use axum::{Router, extract::State, http::HeaderMap, response::Response, routing::get};
+use gate_core::access::{Action, ObjectId, ObjectIdentity, ObjectKind, TargetNamespace};
+use gate_http::{
+    AppState,
+    error::HttpError,
+    services::{HttpIdentity, cached_json},
+};
+use serde::Serialize;
+
+/// Cache key of the credential checks, which call every provider
+const CREDENTIALS_KEY: &str = "credentials";
+
+#[derive(Debug, Serialize)]
+pub struct CredentialStatusResponse {
+    pub credentials: Vec<CredentialStatus>,
+}
+
+/// Check every stored provider credential against its provider (admin only)
+#[instrument(name = "credentials_status", skip(app_state, headers))]
+pub async fn credentials_status(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    headers: HeaderMap,
+) -> Result<Response, HttpError> {
+    let daemon = &app_state.data.daemon;
+    AdminPermissionHelper::new(daemon, identity.clone())
+        .await?
+        .require_admin(
+            Action::Read,
+            &ObjectIdentity {
+                namespace: TargetNamespace::System,
+                kind: ObjectKind::System,
+                id: ObjectId::new("credentials"),
+            },
+        )
+        .await?;
+
+    cached_json(
+        app_state.response_cache.as_deref(),
+        CREDENTIALS_KEY,
+        &headers,
+        || check_credentials(&app_state, &identity),
+    )
+    .await
+}
+
+async fn check_credentials(
+    app_state: &AppState<crate::State>,
+    identity: &HttpIdentity,
+) -> Result<CredentialStatusResponse, HttpError> {
+    let settings = app_state
+        .data
+        .daemon
+        .get_settings()
+        .await
+        .map_internal_error()?;
+    let sink_ids = match &app_state.router {
+        Some(router) => router.sink_registry().list_ids().await,
+        None => Vec::new(),
+    };
+
+    let checker = CredentialChecker::new();
+    let checks = settings
+        .providers
+        .iter()
+        .map(|provider| checker.check(provider));
+    let mut credentials: Vec<CredentialStatus> = futures::future::join_all(checks)
+        .await
+        .into_iter()
+        .flatten()
+        .collect();
+    for credential in &mut credentials {
+        credential.sinks = sink_ids
+            .iter()
+            .filter(|id| config_name(id) == Some(credential.provider.as_str()))
+            .cloned()
+            .collect();
+        credential.sinks.sort();
+    }
+
+    info!(
+        "Admin {} checked {} provider credentials",
+        identity.id,
+        credentials.len()
+    );
+    Ok(CredentialStatusResponse { credentials })
+}
+
+/// Add credential routes
+pub fn add_routes(
+    router: Router<gate_http::AppState<crate::State>>,
+) -> Router<gate_http::AppState<crate::State>> {
+    router.route("/api/admin/credentials/status", get(credentials_status))
+}
@@ -0,0 +1,39 @@
+//! Personal data routes
+//!
+//! Lets an authenticated user delete everything the gateway stored about
+//! their activity. The account and its credentials are kept, so the user can
+//! still sign in afterwards.
+
+use crate::helpers::errors::ErrorMapExt;
+use axum::{Router, extract::State, response::Json, routing::delete};
+use gate_core::UserDataDeletion;
+use gate_http::{AppState, error::HttpError, services::HttpIdentity};
+
+/// Delete the caller's conversations, feedback, usage, audit entries,
+/// captured bodies, assistant objects and preferences
+#[instrument(name = "delete_my_data", skip(app_state))]
+pub async fn delete_my_data(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+) -> Result<Json<UserDataDeletion>, HttpError> {
+    let backend = app_state
+        .data
+        .daemon
+        .get_state_backend()
+        .await
+        .map_internal_error()?;
+    let deleted = backend
+        .delete_user_data(&identity.id)
+        .await
+        .map_internal_error_with_context("Failed to delete user data")?;
+
+    info!("Deleted stored data of user {}: {:?}", identity.id, deleted);
+    Ok(Json(deleted))
+}
+
+/// Add personal data routes
+pub fn add_routes(
+    router: Router<gate_http::AppState<crate::State>>,
+) -> Router<gate_http::AppState<crate::State>> {
+    router.route("/api/me/data", delete(delete_my_data))
+}
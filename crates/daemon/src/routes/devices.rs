@@ -0,0 +1,276 @@
+//! Device pairing and per-user device management
+//!
+//! An admin creates a pairing code for a user; the new device redeems it at
+//! `/auth/pair` for a registration challenge and finishes at
+//! `/auth/pair/complete`, which adds its passkey to that user. Codes that fail
+//! to redeem count as failed sign-ins from the client's address, so guessing
+//! locks it out. Users list and revoke their own devices under
+//! `/api/devices`.
+
+use crate::helpers::{
+    admin::AdminPermissionHelper,
+    errors::{ErrorMapExt, authorization_failed, bad_request, not_found},
+};
+use crate::services::lockout::LockoutSubject;
+use crate::services::pairing::{PairingCode, PendingDevice};
+use axum::{
+    Extension, Router,
+    extract::{Path, State},
+    response::Json,
+    routing::{delete, get, post},
+};
+use chrono::{DateTime, Utc};
+use gate_core::access::{Action, ObjectId, ObjectIdentity, ObjectKind, TargetNamespace};
+use gate_core::router::extensions::ClientIp;
+use gate_http::{
+    AppState,
+    error::HttpError,
+    services::HttpIdentity,
+    types::{RegisterCompleteResponse, RegisterStartResponse},
+};
+use gate_sqlx::StoredCredential;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize)]
+pub struct CreatePairingRequest {
+    /// User the device is added to; defaults to the caller
+    pub user_id: Option<String>,
+    pub device_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PairStartRequest {
+    pub code: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PairCompleteRequest {
+    pub session_id: String,
+    pub credential: serde_json::Value,
+    /// Overrides the name given when the code was created
+    pub device_name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeviceInfo {
+    pub credential_id: String,
+    pub device_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+impl From<StoredCredential> for DeviceInfo {
+    fn from(credential: StoredCredential) -> Self {
+        Self {
+            credential_id: credential.credential_id,
+            device_name: credential.device_name,
+            created_at: credential.created_at,
+            last_used_at: credential.last_used_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeviceListResponse {
+    pub devices: Vec<DeviceInfo>,
+    /// Outstanding pairing codes for the caller
+    pub pending: Vec<PairingCode>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RevokeDeviceResponse {
+    pub credential_id: String,
+    pub revoked: bool,
+}
+
+/// Create a pairing code for adding a device to a user (admin only)
+#[instrument(name = "create_pairing_code", skip(app_state))]
+pub async fn create_pairing_code(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Json(request): Json<CreatePairingRequest>,
+) -> Result<Json<PairingCode>, HttpError> {
+    let daemon = &app_state.data.daemon;
+    let user_id = request.user_id.unwrap_or_else(|| identity.id.clone());
+    AdminPermissionHelper::new(daemon, identity.clone())
+        .await?
+        .require_admin(
+            Action::Manage,
+            &ObjectIdentity {
+                namespace: TargetNamespace::System,
+                kind: ObjectKind::User,
+                id: ObjectId::new(user_id.clone()),
+            },
+        )
+        .await?;
+
+    let state_backend = daemon.get_state_backend().await.map_internal_error()?;
+    if state_backend
+        .get_user_by_id(&user_id)
+        .await
+        .map_internal_error()?
+        .is_none()
+    {
+        return Err(not_found("User", &user_id));
+    }
+
+    let pairing = daemon.get_pairing_service().await.map_internal_error()?;
+    let code = pairing.create_code(&user_id, request.device_name).await;
+    info!(
+        "Admin {} created a pairing code for user {} expiring at {}",
+        identity.id, user_id, code.expires_at
+    );
+    Ok(Json(code))
+}
+
+/// List the caller's devices and outstanding pairing codes
+#[instrument(name = "list_devices", skip(app_state))]
+pub async fn list_devices(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+) -> Result<Json<DeviceListResponse>, HttpError> {
+    let devices = app_state
+        .data
+        .auth_service
+        .list_devices(&identity.id)
+        .await?
+        .into_iter()
+        .map(DeviceInfo::from)
+        .collect();
+    let pairing = app_state
+        .data
+        .daemon
+        .get_pairing_service()
+        .await
+        .map_internal_error()?;
+    let pending = pairing.list_codes(&identity.id).await;
+    Ok(Json(DeviceListResponse { devices, pending }))
+}
+
+/// Revoke one of the caller's devices
+#[instrument(name = "revoke_device", skip(app_state))]
+pub async fn revoke_device(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(credential_id): Path<String>,
+) -> Result<Json<RevokeDeviceResponse>, HttpError> {
+    app_state
+        .data
+        .auth_service
+        .revoke_device(&identity.id, &credential_id)
+        .await?;
+    info!("User {} revoked device {}", identity.id, credential_id);
+    Ok(Json(RevokeDeviceResponse {
+        credential_id,
+        revoked: true,
+    }))
+}
+
+/// Exchange a pairing code for a registration challenge
+#[instrument(name = "pair_start", skip(app_state, client_ip, request))]
+pub async fn pair_start(
+    State(app_state): State<AppState<crate::State>>,
+    client_ip: Option<Extension<ClientIp>>,
+    Json(request): Json<PairStartRequest>,
+) -> Result<Json<RegisterStartResponse>, HttpError> {
+    let daemon = &app_state.data.daemon;
+    let pairing = daemon.get_pairing_service().await.map_internal_error()?;
+    let webauthn_service = daemon
+        .get_webauthn_service()
+        .await
+        .map_internal_error()?
+        .ok_or_else(|| bad_request("WebAuthn service not enabled"))?;
+    let Some(code) = pairing.redeem(&request.code).await else {
+        // Locked out addresses are refused before reaching here
+        let subjects: Vec<_> = client_ip
+            .map(|Extension(ClientIp(ip))| LockoutSubject::Address(ip))
+            .into_iter()
+            .collect();
+        app_state
+            .data
+            .auth_lockout
+            .record_failure(&subjects, Utc::now())
+            .await;
+        return Err(authorization_failed("Invalid or expired pairing code"));
+    };
+    let user = daemon
+        .get_state_backend()
+        .await
+        .map_internal_error()?
+        .get_user_by_id(&code.user_id)
+        .await
+        .map_internal_error()?
+        .ok_or_else(|| not_found("User", &code.user_id))?;
+
+    let user_name = user.name.unwrap_or_else(|| user.id.clone());
+    let (challenge, session_id) = webauthn_service.start_registration(user_name).await?;
+    pairing
+        .begin_registration(
+            session_id.clone(),
+            PendingDevice {
+                user_id: code.user_id,
+                device_name: code.device_name,
+            },
+        )
+        .await;
+
+    Ok(Json(RegisterStartResponse {
+        challenge,
+        session_id,
+    }))
+}
+
+/// Finish registering a paired device, adding its passkey to the user
+#[instrument(
+    name = "pair_complete",
+    skip(app_state, request),
+    fields(session_id = %request.session_id)
+)]
+pub async fn pair_complete(
+    State(app_state): State<AppState<crate::State>>,
+    Json(request): Json<PairCompleteRequest>,
+) -> Result<Json<RegisterCompleteResponse>, HttpError> {
+    let daemon = &app_state.data.daemon;
+    let pairing = daemon.get_pairing_service().await.map_internal_error()?;
+    let device = pairing
+        .finish_registration(&request.session_id)
+        .await
+        .ok_or_else(|| bad_request("Unknown pairing session"))?;
+
+    let webauthn_service = daemon
+        .get_webauthn_service()
+        .await
+        .map_internal_error()?
+        .ok_or_else(|| bad_request("WebAuthn service not enabled"))?;
+    let (passkey, credential_id, _) = webauthn_service
+        .complete_registration(request.session_id, request.credential)
+        .await?;
+
+    let response = app_state
+        .data
+        .auth_service
+        .add_device(
+            &device.user_id,
+            credential_id,
+            request.device_name.or(device.device_name),
+            passkey,
+        )
+        .await?;
+    info!(
+        "Paired new device {} for user {}",
+        response.credential_id, response.user_id
+    );
+    Ok(Json(response))
+}
+
+/// Add device routes
+pub fn add_routes(
+    router: Router<gate_http::AppState<crate::State>>,
+) -> Router<gate_http::AppState<crate::State>> {
+    router
+        .route("/api/devices", get(list_devices))
+        .route("/api/devices/pairing", post(create_pairing_code))
+        .route("/api/devices/{credential_id}", delete(revoke_device))
+        .route("/auth/pair", post(pair_start))
+        .route("/auth/pair/complete", post(pair_complete))
+}
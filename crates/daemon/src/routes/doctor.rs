@@ -0,0 +1,53 @@
+//! Diagnostics routes
+
+use crate::helpers::{admin::AdminPermissionHelper, errors::ErrorMapExt};
+use crate::services::DoctorService;
+use crate::types::DoctorReport;
+use axum::{Router, extract::State, response::Json, routing::get};
+use gate_core::access::{Action, ObjectId, ObjectIdentity, ObjectKind, TargetNamespace};
+use gate_http::{AppState, error::HttpError, services::HttpIdentity};
+
+/// Run the doctor checks against the running daemon (admin only)
+#[instrument(name = "run_doctor", skip(app_state))]
+pub async fn run_doctor(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+) -> Result<Json<DoctorReport>, HttpError> {
+    let daemon = &app_state.data.daemon;
+    let helper = AdminPermissionHelper::new(daemon, identity.clone()).await?;
+
+    helper
+        .require_admin(
+            Action::Read,
+            &ObjectIdentity {
+                namespace: TargetNamespace::System,
+                kind: ObjectKind::System,
+                id: ObjectId::new("daemon"),
+            },
+        )
+        .await?;
+
+    let settings = daemon.get_settings().await.map_internal_error()?;
+    let status = daemon.status().await.map_internal_error()?;
+    let state_dir = daemon.state_dir().map_internal_error()?;
+
+    let report = DoctorService::new(settings, state_dir)
+        .with_status(status)
+        .run()
+        .await;
+    info!(
+        "Admin {} ran doctor: {} checks, healthy={}",
+        identity.id,
+        report.checks.len(),
+        report.healthy
+    );
+
+    Ok(Json(report))
+}
+
+/// Add doctor routes
+pub fn add_routes(
+    router: Router<gate_http::AppState<crate::State>>,
+) -> Router<gate_http::AppState<crate::State>> {
+    router.route("/api/admin/doctor", get(run_doctor))
+}
@@ -0,0 +1,315 @@
+//! Admin routes for the retrieval document store
+//!
+//! Documents added here are chunked, embedded and made searchable by virtual
+//! models whose retrieval config names their collection; see
+//! [`crate::services::retrieval`].
+
+use crate::helpers::{
+    admin::AdminPermissionHelper,
+    errors::{ErrorMapExt, bad_request, not_found},
+};
+use crate::services::retrieval::{
+    Chunk, Document, DocumentInfo, DocumentStore, SearchHit, chunk_text, embed,
+};
+use axum::{
+    Extension, Router,
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    response::Json,
+    routing::{get, post},
+};
+use chrono::Utc;
+use gate_core::access::{Action, ObjectId, ObjectIdentity, ObjectKind, TargetNamespace};
+use gate_core::router::{routing::Router as CoreRouter, sink::RequestContext};
+use gate_core::tracing::CorrelationId;
+use gate_http::{AppState, auth::extract_identity, error::HttpError, services::HttpIdentity};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+fn default_top_k() -> usize {
+    4
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DocumentListQuery {
+    pub collection: Option<String>,
+}
+
+/// A document to index, given as text or as an uploaded text file
+#[derive(Debug, Deserialize)]
+pub struct CreateDocumentRequest {
+    pub collection: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub file_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SearchDocumentsRequest {
+    pub collection: String,
+    pub query: String,
+    #[serde(default = "default_top_k")]
+    pub top_k: usize,
+    #[serde(default)]
+    pub min_score: f32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DocumentListResponse {
+    pub documents: Vec<DocumentInfo>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchDocumentsResponse {
+    pub hits: Vec<SearchHit>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeleteDocumentResponse {
+    pub id: String,
+    pub deleted: bool,
+}
+
+/// Check admin access to documents and fetch the store
+async fn admin_documents(
+    app_state: &AppState<crate::State>,
+    identity: &HttpIdentity,
+    action: Action,
+) -> Result<DocumentStore, HttpError> {
+    let daemon = &app_state.data.daemon;
+    AdminPermissionHelper::new(daemon, identity.clone())
+        .await?
+        .require_admin(
+            action,
+            &ObjectIdentity {
+                namespace: TargetNamespace::System,
+                kind: ObjectKind::System,
+                id: ObjectId::new("documents"),
+            },
+        )
+        .await?;
+    daemon.get_document_store().await.map_internal_error()
+}
+
+/// Router and request context used to embed text on the caller's behalf
+fn embedding_context(
+    app_state: &AppState<crate::State>,
+    headers: &HeaderMap,
+    correlation_id: CorrelationId,
+) -> Result<(Arc<CoreRouter>, RequestContext), HttpError> {
+    let router = app_state
+        .router
+        .clone()
+        .ok_or_else(|| HttpError::InternalServerError("Router not configured".to_string()))?;
+    let ctx = RequestContext {
+        identity: extract_identity(headers),
+        correlation_id,
+        headers: headers.clone(),
+        query: None,
+        trace_id: None,
+        metadata: Default::default(),
+        response: Default::default(),
+        extensions: Default::default(),
+    };
+    Ok((router, ctx))
+}
+
+/// Text of an uploaded file the caller owns
+async fn file_text(
+    app_state: &AppState<crate::State>,
+    identity: &HttpIdentity,
+    file_id: &str,
+) -> Result<(String, String), HttpError> {
+    let files = app_state
+        .data
+        .daemon
+        .get_file_store()
+        .await
+        .map_internal_error()?;
+    let record = files
+        .get(file_id)
+        .await
+        .map_internal_error()?
+        .filter(|record| record.owner_id == identity.id)
+        .ok_or_else(|| not_found("File", file_id))?;
+    let data = files
+        .read(file_id)
+        .await
+        .map_internal_error_with_context("Failed to read file")?;
+    let text =
+        String::from_utf8(data).map_err(|_| bad_request("Only text files can be indexed"))?;
+    Ok((record.filename, text))
+}
+
+/// List indexed documents, optionally in one collection (admin only)
+#[instrument(name = "list_documents", skip(app_state))]
+pub async fn list_documents(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Query(query): Query<DocumentListQuery>,
+) -> Result<Json<DocumentListResponse>, HttpError> {
+    let store = admin_documents(&app_state, &identity, Action::Read).await?;
+    let documents = store
+        .list(query.collection.as_deref())
+        .await
+        .map_internal_error()?;
+    Ok(Json(DocumentListResponse { documents }))
+}
+
+/// Chunk, embed and index a document (admin only)
+#[instrument(name = "create_document", skip(app_state, headers, request))]
+pub async fn create_document(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Extension(correlation_id): Extension<CorrelationId>,
+    headers: HeaderMap,
+    Json(request): Json<CreateDocumentRequest>,
+) -> Result<Json<DocumentInfo>, HttpError> {
+    let store = admin_documents(&app_state, &identity, Action::Write).await?;
+    let collection = request.collection.trim().to_string();
+    if collection.is_empty() {
+        return Err(bad_request("collection is required"));
+    }
+    let (title, text) = match (request.text, &request.file_id) {
+        (Some(text), None) => (
+            request.title.unwrap_or_else(|| "Untitled".to_string()),
+            text,
+        ),
+        (None, Some(file_id)) => {
+            let (filename, text) = file_text(&app_state, &identity, file_id).await?;
+            (request.title.unwrap_or(filename), text)
+        }
+        _ => return Err(bad_request("Provide exactly one of text or file_id")),
+    };
+
+    let settings = app_state
+        .data
+        .daemon
+        .get_settings()
+        .await
+        .map_internal_error()?
+        .documents;
+    let chunks = chunk_text(&text, settings.chunk_size, settings.chunk_overlap);
+    if chunks.is_empty() {
+        return Err(bad_request("Document has no text"));
+    }
+
+    let (router, ctx) = embedding_context(&app_state, &headers, correlation_id)?;
+    let embeddings = embed(&router, &ctx, &settings.embedding_model, &chunks)
+        .await
+        .map_internal_error_with_context("Failed to embed document")?;
+    let document = Document {
+        id: Document::new_id(),
+        collection,
+        title,
+        owner_id: identity.id.clone(),
+        file_id: request.file_id,
+        embedding_model: settings.embedding_model,
+        chunks: chunks
+            .into_iter()
+            .zip(embeddings)
+            .map(|(text, embedding)| Chunk { text, embedding })
+            .collect(),
+        created_at: Utc::now(),
+    };
+    let info = DocumentInfo::from(&document);
+    store
+        .save(document)
+        .await
+        .map_internal_error_with_context("Failed to store document")?;
+
+    info!(
+        "Admin {} indexed document {} ({} chunks) in {}",
+        identity.id, info.id, info.chunks, info.collection
+    );
+    Ok(Json(info))
+}
+
+/// Get an indexed document (admin only)
+#[instrument(name = "get_document", skip(app_state))]
+pub async fn get_document(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(id): Path<String>,
+) -> Result<Json<DocumentInfo>, HttpError> {
+    let store = admin_documents(&app_state, &identity, Action::Read).await?;
+    let document = store
+        .get(&id)
+        .await
+        .map_internal_error()?
+        .ok_or_else(|| not_found("Document", &id))?;
+    Ok(Json(DocumentInfo::from(&document)))
+}
+
+/// Remove a document from the index (admin only)
+#[instrument(name = "delete_document", skip(app_state))]
+pub async fn delete_document(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(id): Path<String>,
+) -> Result<Json<DeleteDocumentResponse>, HttpError> {
+    let store = admin_documents(&app_state, &identity, Action::Delete).await?;
+    if !store.delete(&id).await.map_internal_error()? {
+        return Err(not_found("Document", &id));
+    }
+    info!("Admin {} deleted document {}", identity.id, id);
+    Ok(Json(DeleteDocumentResponse { id, deleted: true }))
+}
+
+/// Search a collection the way retrieval does, for checking what a virtual
+/// model would be given (admin only)
+#[instrument(name = "search_documents", skip(app_state, headers, request))]
+pub async fn search_documents(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Extension(correlation_id): Extension<CorrelationId>,
+    headers: HeaderMap,
+    Json(request): Json<SearchDocumentsRequest>,
+) -> Result<Json<SearchDocumentsResponse>, HttpError> {
+    let store = admin_documents(&app_state, &identity, Action::Read).await?;
+    let embedding_model = app_state
+        .data
+        .daemon
+        .get_settings()
+        .await
+        .map_internal_error()?
+        .documents
+        .embedding_model;
+
+    let (router, ctx) = embedding_context(&app_state, &headers, correlation_id)?;
+    let query = embed(&router, &ctx, &embedding_model, &[request.query])
+        .await
+        .map_internal_error_with_context("Failed to embed query")?
+        .pop()
+        .ok_or_else(|| HttpError::InternalServerError("No query embedding".to_string()))?;
+    let hits = store
+        .search(
+            &request.collection,
+            &embedding_model,
+            &query,
+            request.top_k,
+            request.min_score,
+        )
+        .await
+        .map_internal_error()?;
+    Ok(Json(SearchDocumentsResponse { hits }))
+}
+
+/// Add document routes
+pub fn add_routes(
+    router: Router<gate_http::AppState<crate::State>>,
+) -> Router<gate_http::AppState<crate::State>> {
+    router
+        .route(
+            "/api/admin/documents",
+            get(list_documents).post(create_document),
+        )
+        .route("/api/admin/documents/search", post(search_documents))
+        .route(
+            "/api/admin/documents/{id}",
+            get(get_document).delete(delete_document),
+        )
+}
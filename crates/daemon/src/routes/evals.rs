@@ -0,0 +1,284 @@
+//! Admin routes for offline evaluations
+//!
+//! Datasets are uploaded as JSONL and run against models in the background;
+//! see [`crate::services::evals`]. A run's progress can be followed as
+//! server-sent events until it finishes.
+
+use crate::helpers::{
+    admin::AdminPermissionHelper,
+    errors::{ErrorMapExt, bad_request, not_found},
+};
+use crate::services::EvalStore;
+use crate::services::evals::{
+    EvalDataset, EvalDatasetInfo, EvalProgress, EvalRun, MAX_CONCURRENCY, parse_dataset,
+};
+use axum::{
+    Extension, Router,
+    extract::{Path, State},
+    http::HeaderMap,
+    response::{
+        Json,
+        sse::{Event, KeepAlive, Sse},
+    },
+    routing::{delete, get},
+};
+use chrono::Utc;
+use futures::{Stream, StreamExt};
+use gate_core::access::{Action, ObjectId, ObjectIdentity, ObjectKind, TargetNamespace};
+use gate_core::router::sink::RequestContext;
+use gate_core::tracing::CorrelationId;
+use gate_http::{AppState, auth::extract_identity, error::HttpError, services::HttpIdentity};
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use tokio::sync::broadcast::error::RecvError;
+
+fn default_concurrency() -> usize {
+    4
+}
+
+/// A dataset to store, one JSON item per line
+#[derive(Debug, Deserialize)]
+pub struct CreateDatasetRequest {
+    pub name: String,
+    pub jsonl: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StartRunRequest {
+    pub dataset_id: String,
+    pub models: Vec<String>,
+    #[serde(default = "default_concurrency")]
+    pub concurrency: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DatasetListResponse {
+    pub datasets: Vec<EvalDatasetInfo>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RunListResponse {
+    pub runs: Vec<EvalRun>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeleteDatasetResponse {
+    pub id: String,
+    pub deleted: bool,
+}
+
+/// Check admin access to evals and fetch the store
+async fn admin_evals(
+    app_state: &AppState<crate::State>,
+    identity: &HttpIdentity,
+    action: Action,
+) -> Result<EvalStore, HttpError> {
+    let daemon = &app_state.data.daemon;
+    AdminPermissionHelper::new(daemon, identity.clone())
+        .await?
+        .require_admin(
+            action,
+            &ObjectIdentity {
+                namespace: TargetNamespace::System,
+                kind: ObjectKind::System,
+                id: ObjectId::new("evals"),
+            },
+        )
+        .await?;
+    daemon.get_eval_store().await.map_internal_error()
+}
+
+/// List datasets, newest first (admin only)
+#[instrument(name = "list_eval_datasets", skip(app_state))]
+pub async fn list_datasets(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+) -> Result<Json<DatasetListResponse>, HttpError> {
+    let store = admin_evals(&app_state, &identity, Action::Read).await?;
+    let datasets = store.list_datasets().await.map_internal_error()?;
+    Ok(Json(DatasetListResponse { datasets }))
+}
+
+/// Store a JSONL dataset (admin only)
+#[instrument(name = "create_eval_dataset", skip(app_state, request))]
+pub async fn create_dataset(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Json(request): Json<CreateDatasetRequest>,
+) -> Result<Json<EvalDatasetInfo>, HttpError> {
+    let store = admin_evals(&app_state, &identity, Action::Write).await?;
+    let name = request.name.trim().to_string();
+    if name.is_empty() {
+        return Err(bad_request("name is required"));
+    }
+    let items = parse_dataset(&request.jsonl).map_err(|e| bad_request(e.to_string()))?;
+    let dataset = EvalDataset {
+        id: EvalDataset::new_id(),
+        name,
+        owner_id: identity.id.clone(),
+        items,
+        created_at: Utc::now(),
+    };
+    store
+        .save_dataset(&dataset)
+        .await
+        .map_internal_error_with_context("Failed to store dataset")?;
+
+    let info = EvalDatasetInfo::from(&dataset);
+    info!(
+        "Admin {} uploaded eval dataset {} ({} items)",
+        identity.id, info.id, info.items
+    );
+    Ok(Json(info))
+}
+
+/// Remove a dataset; runs of it are kept (admin only)
+#[instrument(name = "delete_eval_dataset", skip(app_state))]
+pub async fn delete_dataset(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(id): Path<String>,
+) -> Result<Json<DeleteDatasetResponse>, HttpError> {
+    let store = admin_evals(&app_state, &identity, Action::Delete).await?;
+    if !store.delete_dataset(&id).await.map_internal_error()? {
+        return Err(not_found("Dataset", &id));
+    }
+    info!("Admin {} deleted eval dataset {}", identity.id, id);
+    Ok(Json(DeleteDatasetResponse { id, deleted: true }))
+}
+
+/// List runs without their per-item results, newest first (admin only)
+#[instrument(name = "list_eval_runs", skip(app_state))]
+pub async fn list_runs(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+) -> Result<Json<RunListResponse>, HttpError> {
+    let store = admin_evals(&app_state, &identity, Action::Read).await?;
+    let runs = store.list_runs().await.map_internal_error()?;
+    Ok(Json(RunListResponse { runs }))
+}
+
+/// Start running a dataset against models, as the caller (admin only)
+#[instrument(name = "start_eval_run", skip(app_state, headers))]
+pub async fn start_run(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Extension(correlation_id): Extension<CorrelationId>,
+    headers: HeaderMap,
+    Json(request): Json<StartRunRequest>,
+) -> Result<Json<EvalRun>, HttpError> {
+    let store = admin_evals(&app_state, &identity, Action::Write).await?;
+    if request.models.is_empty() {
+        return Err(bad_request("models is required"));
+    }
+    if request.concurrency == 0 || request.concurrency > MAX_CONCURRENCY {
+        return Err(bad_request(format!(
+            "concurrency must be between 1 and {MAX_CONCURRENCY}"
+        )));
+    }
+    let dataset = store
+        .get_dataset(&request.dataset_id)
+        .await
+        .map_internal_error()?
+        .ok_or_else(|| not_found("Dataset", &request.dataset_id))?;
+
+    let router = app_state
+        .router
+        .clone()
+        .ok_or_else(|| HttpError::InternalServerError("Router not configured".to_string()))?;
+    let ctx = RequestContext {
+        identity: extract_identity(&headers),
+        correlation_id,
+        headers: headers.clone(),
+        query: None,
+        trace_id: None,
+        metadata: Default::default(),
+        response: Default::default(),
+        extensions: Default::default(),
+    };
+    let run = store
+        .start(router, ctx, dataset, request.models, request.concurrency)
+        .await
+        .map_internal_error_with_context("Failed to start eval run")?;
+
+    info!(
+        "Admin {} started eval run {} of {} against {:?}",
+        identity.id, run.id, run.dataset_name, run.models
+    );
+    Ok(Json(run))
+}
+
+/// A run with its per-item results (admin only)
+#[instrument(name = "get_eval_run", skip(app_state))]
+pub async fn get_run(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(id): Path<String>,
+) -> Result<Json<EvalRun>, HttpError> {
+    let store = admin_evals(&app_state, &identity, Action::Read).await?;
+    let run = store
+        .get_run(&id)
+        .await
+        .map_internal_error()?
+        .ok_or_else(|| not_found("Eval run", &id))?;
+    Ok(Json(run))
+}
+
+/// Progress of a run as server-sent `progress` events, starting with where
+/// it stands and ending once it finishes (admin only)
+#[instrument(name = "stream_eval_run", skip(app_state))]
+pub async fn stream_run(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(id): Path<String>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, HttpError> {
+    let store = admin_evals(&app_state, &identity, Action::Read).await?;
+    // Subscribe first, so no result lands between the snapshot and the feed
+    let receiver = store.subscribe(&id);
+    let run = store
+        .get_run(&id)
+        .await
+        .map_internal_error()?
+        .ok_or_else(|| not_found("Eval run", &id))?;
+
+    let event = |progress: &EvalProgress| {
+        Event::default()
+            .event("progress")
+            .json_data(progress)
+            .unwrap_or_else(|_| Event::default().event("progress"))
+    };
+    let snapshot = futures::stream::once({
+        let event = event(&EvalProgress::new(&run, None));
+        async move { event }
+    });
+    let updates = futures::stream::unfold(receiver, |receiver| async move {
+        let mut receiver = receiver?;
+        loop {
+            match receiver.recv().await {
+                Ok(progress) => return Some((progress, Some(receiver))),
+                // Later events carry the totals, so skipped ones are not missed
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+    let stream = snapshot
+        .chain(updates.map(move |progress| event(&progress)))
+        .map(Ok);
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+/// Add eval routes
+pub fn add_routes(
+    router: Router<gate_http::AppState<crate::State>>,
+) -> Router<gate_http::AppState<crate::State>> {
+    router
+        .route(
+            "/api/admin/evals/datasets",
+            get(list_datasets).post(create_dataset),
+        )
+        .route("/api/admin/evals/datasets/{id}", delete(delete_dataset))
+        .route("/api/admin/evals/runs", get(list_runs).post(start_run))
+        .route("/api/admin/evals/runs/{id}", get(get_run))
+        .route("/api/admin/evals/runs/{id}/events", get(stream_run))
+}
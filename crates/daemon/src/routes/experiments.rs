@@ -0,0 +1,299 @@
+//! Admin routes for experiments
+//!
+//! An experiment splits requests for one model between variants and records
+//! how each request went; see [`gate_core::router::middleware::ExperimentMiddleware`].
+
+use crate::helpers::{
+    admin::AdminPermissionHelper,
+    errors::{ErrorMapExt, bad_request, not_found},
+};
+use axum::{
+    Router,
+    extract::{Path, State},
+    response::Json,
+    routing::get,
+};
+use chrono::Utc;
+use gate_core::access::{Action, ObjectId, ObjectIdentity, ObjectKind, TargetNamespace};
+use gate_core::router::middleware::experiment_results;
+use gate_core::{
+    Experiment, ExperimentMetric, ExperimentStatus, ExperimentVariant, ExperimentVariantResults,
+    StateBackend,
+};
+use gate_http::{AppState, error::HttpError, services::HttpIdentity};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+fn default_status() -> ExperimentStatus {
+    ExperimentStatus::Draft
+}
+
+fn default_metrics() -> Vec<ExperimentMetric> {
+    vec![
+        ExperimentMetric::Latency,
+        ExperimentMetric::Cost,
+        ExperimentMetric::Feedback,
+    ]
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExperimentRequest {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub model: String,
+    #[serde(default = "default_status")]
+    pub status: ExperimentStatus,
+    pub variants: Vec<ExperimentVariant>,
+    #[serde(default = "default_metrics")]
+    pub metrics: Vec<ExperimentMetric>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExperimentListResponse {
+    pub experiments: Vec<Experiment>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExperimentResultsResponse {
+    pub experiment: Experiment,
+    pub variants: Vec<ExperimentVariantResults>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeleteExperimentResponse {
+    pub id: String,
+    pub deleted: bool,
+}
+
+/// Check admin access to experiments and fetch the state backend
+async fn admin_experiments(
+    app_state: &AppState<crate::State>,
+    identity: &HttpIdentity,
+    action: Action,
+) -> Result<Arc<dyn StateBackend>, HttpError> {
+    let daemon = &app_state.data.daemon;
+    AdminPermissionHelper::new(daemon, identity.clone())
+        .await?
+        .require_admin(
+            action,
+            &ObjectIdentity {
+                namespace: TargetNamespace::System,
+                kind: ObjectKind::System,
+                id: ObjectId::new("experiments"),
+            },
+        )
+        .await?;
+    daemon.get_state_backend().await.map_internal_error()
+}
+
+/// Check an experiment is well formed and would not compete with another
+/// running experiment for the same model
+async fn validate(
+    backend: &dyn StateBackend,
+    id: Option<&str>,
+    request: &ExperimentRequest,
+) -> Result<(), HttpError> {
+    if request.name.trim().is_empty() || request.model.trim().is_empty() {
+        return Err(bad_request("name and model are required"));
+    }
+    if request.variants.is_empty() {
+        return Err(bad_request("An experiment needs at least one variant"));
+    }
+    let mut names = HashSet::new();
+    if let Some(variant) = request
+        .variants
+        .iter()
+        .find(|v| v.name.is_empty() || !names.insert(v.name.as_str()))
+    {
+        return Err(bad_request(format!(
+            "Variant names must be unique and non-empty: {:?}",
+            variant.name
+        )));
+    }
+    if request.variants.iter().all(|v| v.weight == 0) {
+        return Err(bad_request("At least one variant needs a positive weight"));
+    }
+
+    if request.status == ExperimentStatus::Running {
+        let experiments = backend.list_experiments().await.map_internal_error()?;
+        if let Some(other) = experiments.iter().find(|e| {
+            e.status == ExperimentStatus::Running
+                && e.model == request.model
+                && Some(e.id.as_str()) != id
+        }) {
+            return Err(bad_request(format!(
+                "Experiment {} is already running on {}",
+                other.id, request.model
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// List experiments, newest first (admin only)
+#[instrument(name = "list_experiments", skip(app_state))]
+pub async fn list_experiments(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+) -> Result<Json<ExperimentListResponse>, HttpError> {
+    let backend = admin_experiments(&app_state, &identity, Action::Read).await?;
+    let experiments = backend.list_experiments().await.map_internal_error()?;
+    Ok(Json(ExperimentListResponse { experiments }))
+}
+
+/// Create an experiment (admin only)
+#[instrument(name = "create_experiment", skip(app_state, request))]
+pub async fn create_experiment(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Json(request): Json<ExperimentRequest>,
+) -> Result<Json<Experiment>, HttpError> {
+    let backend = admin_experiments(&app_state, &identity, Action::Write).await?;
+    validate(backend.as_ref(), None, &request).await?;
+
+    let now = Utc::now();
+    let experiment = Experiment {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: request.name,
+        description: request.description,
+        model: request.model,
+        status: request.status,
+        variants: request.variants,
+        metrics: request.metrics,
+        created_by: identity.id.clone(),
+        created_at: now,
+        updated_at: now,
+    };
+    backend
+        .save_experiment(&experiment)
+        .await
+        .map_internal_error_with_context("Failed to save experiment")?;
+
+    info!(
+        "Admin {} created experiment {} on {}",
+        identity.id, experiment.id, experiment.model
+    );
+    Ok(Json(experiment))
+}
+
+/// Get an experiment (admin only)
+#[instrument(name = "get_experiment", skip(app_state))]
+pub async fn get_experiment(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(id): Path<String>,
+) -> Result<Json<Experiment>, HttpError> {
+    let backend = admin_experiments(&app_state, &identity, Action::Read).await?;
+    let experiment = backend
+        .get_experiment(&id)
+        .await
+        .map_internal_error()?
+        .ok_or_else(|| not_found("Experiment", &id))?;
+    Ok(Json(experiment))
+}
+
+/// Replace an experiment's definition, such as to start or stop it (admin only)
+#[instrument(name = "update_experiment", skip(app_state, request))]
+pub async fn update_experiment(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(id): Path<String>,
+    Json(request): Json<ExperimentRequest>,
+) -> Result<Json<Experiment>, HttpError> {
+    let backend = admin_experiments(&app_state, &identity, Action::Write).await?;
+    let existing = backend
+        .get_experiment(&id)
+        .await
+        .map_internal_error()?
+        .ok_or_else(|| not_found("Experiment", &id))?;
+    validate(backend.as_ref(), Some(&id), &request).await?;
+
+    let experiment = Experiment {
+        name: request.name,
+        description: request.description,
+        model: request.model,
+        status: request.status,
+        variants: request.variants,
+        metrics: request.metrics,
+        updated_at: Utc::now(),
+        ..existing
+    };
+    backend
+        .save_experiment(&experiment)
+        .await
+        .map_internal_error_with_context("Failed to save experiment")?;
+
+    info!(
+        "Admin {} updated experiment {} ({:?})",
+        identity.id, experiment.id, experiment.status
+    );
+    Ok(Json(experiment))
+}
+
+/// Delete an experiment and its recorded outcomes (admin only)
+#[instrument(name = "delete_experiment", skip(app_state))]
+pub async fn delete_experiment(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(id): Path<String>,
+) -> Result<Json<DeleteExperimentResponse>, HttpError> {
+    let backend = admin_experiments(&app_state, &identity, Action::Delete).await?;
+    if backend
+        .get_experiment(&id)
+        .await
+        .map_internal_error()?
+        .is_none()
+    {
+        return Err(not_found("Experiment", &id));
+    }
+    backend.delete_experiment(&id).await.map_internal_error()?;
+    info!("Admin {} deleted experiment {}", identity.id, id);
+    Ok(Json(DeleteExperimentResponse { id, deleted: true }))
+}
+
+/// Outcomes aggregated per variant (admin only)
+#[instrument(name = "experiment_results", skip(app_state))]
+pub async fn get_experiment_results(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(id): Path<String>,
+) -> Result<Json<ExperimentResultsResponse>, HttpError> {
+    let backend = admin_experiments(&app_state, &identity, Action::Read).await?;
+    let experiment = backend
+        .get_experiment(&id)
+        .await
+        .map_internal_error()?
+        .ok_or_else(|| not_found("Experiment", &id))?;
+    let outcomes = backend
+        .list_experiment_outcomes(&id)
+        .await
+        .map_internal_error()?;
+    let variants = experiment_results(&experiment, &outcomes);
+    Ok(Json(ExperimentResultsResponse {
+        experiment,
+        variants,
+    }))
+}
+
+/// Add experiment routes
+pub fn add_routes(
+    router: Router<gate_http::AppState<crate::State>>,
+) -> Router<gate_http::AppState<crate::State>> {
+    router
+        .route(
+            "/api/admin/experiments",
+            get(list_experiments).post(create_experiment),
+        )
+        .route(
+            "/api/admin/experiments/{id}",
+            get(get_experiment)
+                .put(update_experiment)
+                .delete(delete_experiment),
+        )
+        .route(
+            "/api/admin/experiments/{id}/results",
+            get(get_experiment_results),
+        )
+}
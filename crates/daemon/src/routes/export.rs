@@ -0,0 +1,217 @@
+//! Personal data export routes
+//!
+//! Users export their own data under `/api/auth/me/export`; admins export any
+//! user's data when offboarding them. Exports are generated in the
+//! background: starting one returns the job, which is polled for progress
+//! until the archive can be downloaded.
+
+use crate::helpers::errors::{ErrorMapExt, bad_request, not_found};
+use crate::routes::guard::require_system_admin;
+use crate::services::ExportStore;
+use crate::services::export::{ExportJob, ExportStatus};
+use axum::{
+    Router,
+    extract::{Path, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Json, Response},
+    routing::{get, post},
+};
+use gate_core::access::Action;
+use gate_http::{AppState, error::HttpError, services::HttpIdentity};
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct ExportListResponse {
+    pub exports: Vec<ExportJob>,
+}
+
+async fn export_store(app_state: &AppState<crate::State>) -> Result<ExportStore, HttpError> {
+    app_state
+        .data
+        .daemon
+        .get_export_store()
+        .await
+        .map_internal_error()
+}
+
+/// Start exporting a user's data
+async fn start_export(
+    app_state: &AppState<crate::State>,
+    user_id: &str,
+    requested_by: &str,
+) -> Result<ExportJob, HttpError> {
+    let backend = app_state
+        .data
+        .daemon
+        .get_state_backend()
+        .await
+        .map_internal_error()?;
+    backend
+        .get_user_by_id(user_id)
+        .await
+        .map_internal_error()?
+        .ok_or_else(|| not_found("User", user_id))?;
+
+    export_store(app_state)
+        .await?
+        .start(
+            backend,
+            app_state.data.auth_service.clone(),
+            user_id,
+            requested_by,
+        )
+        .await
+        .map_internal_error_with_context("Failed to start export")
+}
+
+/// An export job, if it exports `user_id`'s data (any user's when `None`)
+async fn find_export(
+    store: &ExportStore,
+    id: &str,
+    user_id: Option<&str>,
+) -> Result<ExportJob, HttpError> {
+    store
+        .get(id)
+        .await
+        .map_internal_error()?
+        .filter(|job| user_id.is_none_or(|user_id| job.user_id == user_id))
+        .ok_or_else(|| not_found("Export", id))
+}
+
+/// The archive of a completed export, as a download
+async fn download(store: &ExportStore, job: &ExportJob) -> Result<Response, HttpError> {
+    if job.status != ExportStatus::Complete {
+        return Err(bad_request(format!(
+            "Export {} is not complete ({}% done)",
+            job.id, job.progress
+        )));
+    }
+    let data = store
+        .read_archive(&job.id)
+        .await
+        .map_internal_error_with_context("Failed to read export")?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/json".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}.json\"", job.id),
+            ),
+        ],
+        data,
+    )
+        .into_response())
+}
+
+/// Start exporting the caller's data
+#[instrument(name = "export_my_data", skip(app_state))]
+pub async fn export_my_data(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+) -> Result<(StatusCode, Json<ExportJob>), HttpError> {
+    let job = start_export(&app_state, &identity.id, &identity.id).await?;
+    info!("User {} started data export {}", identity.id, job.id);
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+/// The caller's exports, newest first
+#[instrument(name = "list_my_exports", skip(app_state))]
+pub async fn list_my_exports(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+) -> Result<Json<ExportListResponse>, HttpError> {
+    let exports = export_store(&app_state)
+        .await?
+        .list(&identity.id)
+        .await
+        .map_internal_error()?;
+    Ok(Json(ExportListResponse { exports }))
+}
+
+/// Progress of one of the caller's exports
+#[instrument(name = "get_my_export", skip(app_state))]
+pub async fn get_my_export(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(export_id): Path<String>,
+) -> Result<Json<ExportJob>, HttpError> {
+    let store = export_store(&app_state).await?;
+    Ok(Json(
+        find_export(&store, &export_id, Some(&identity.id)).await?,
+    ))
+}
+
+/// Download one of the caller's completed exports
+#[instrument(name = "download_my_export", skip(app_state))]
+pub async fn download_my_export(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(export_id): Path<String>,
+) -> Result<Response, HttpError> {
+    let store = export_store(&app_state).await?;
+    let job = find_export(&store, &export_id, Some(&identity.id)).await?;
+    download(&store, &job).await
+}
+
+/// Start exporting a user's data, e.g. when offboarding them (admin only)
+#[instrument(name = "export_user_data", skip(app_state))]
+pub async fn export_user_data(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(user_id): Path<String>,
+) -> Result<(StatusCode, Json<ExportJob>), HttpError> {
+    require_system_admin(&app_state, &identity, Action::Write, "exports").await?;
+    let job = start_export(&app_state, &user_id, &identity.id).await?;
+    info!(
+        "Admin {} started data export {} for user {}",
+        identity.id, job.id, user_id
+    );
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+/// Progress of any export (admin only)
+#[instrument(name = "get_export", skip(app_state))]
+pub async fn get_export(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(export_id): Path<String>,
+) -> Result<Json<ExportJob>, HttpError> {
+    require_system_admin(&app_state, &identity, Action::Read, "exports").await?;
+    let store = export_store(&app_state).await?;
+    Ok(Json(find_export(&store, &export_id, None).await?))
+}
+
+/// Download any completed export (admin only)
+#[instrument(name = "download_export", skip(app_state))]
+pub async fn download_export(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(export_id): Path<String>,
+) -> Result<Response, HttpError> {
+    require_system_admin(&app_state, &identity, Action::Read, "exports").await?;
+    let store = export_store(&app_state).await?;
+    let job = find_export(&store, &export_id, None).await?;
+    download(&store, &job).await
+}
+
+/// Add data export routes
+pub fn add_routes(
+    router: Router<gate_http::AppState<crate::State>>,
+) -> Router<gate_http::AppState<crate::State>> {
+    router
+        .route(
+            "/api/auth/me/export",
+            post(export_my_data).get(list_my_exports),
+        )
+        .route("/api/auth/me/export/{export_id}", get(get_my_export))
+        .route(
+            "/api/auth/me/export/{export_id}/download",
+            get(download_my_export),
+        )
+        .route("/api/admin/users/{user_id}/export", post(export_user_data))
+        .route("/api/admin/exports/{export_id}", get(get_export))
+        .route(
+            "/api/admin/exports/{export_id}/download",
+            get(download_export),
+        )
+}
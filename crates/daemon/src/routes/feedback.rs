@@ -0,0 +1,232 @@
+//! Feedback routes
+//!
+//! Clients rate a response by the correlation id returned in its
+//! `x-correlation-id` header. Feedback is joined to the request's usage
+//! record for the model and provider that served it, and passed on to the
+//! experiment the request was part of, if any.
+
+use crate::helpers::{
+    admin::AdminPermissionHelper,
+    errors::{ErrorMapExt, bad_request},
+};
+use axum::{
+    Router,
+    extract::{Query, State},
+    response::Json,
+    routing::{get, post},
+};
+use chrono::{DateTime, Utc};
+use gate_core::access::{Action, ObjectId, ObjectIdentity, ObjectKind, TargetNamespace};
+use gate_core::router::strategy::TASK_TYPE;
+use gate_core::{Feedback, FeedbackSummary, StateBackend};
+use gate_http::{AppState, error::HttpError, services::HttpIdentity};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+const MAX_COMMENT_LENGTH: usize = 4000;
+const MAX_LIST_LIMIT: usize = 1000;
+/// Feedback read when summarizing
+const SUMMARY_SAMPLE: usize = 10_000;
+
+fn default_limit() -> usize {
+    100
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FeedbackRequest {
+    /// Correlation id of the rated request
+    pub request_id: String,
+    #[serde(default)]
+    pub thumbs_up: Option<bool>,
+    /// Rating from 1 to 5
+    #[serde(default)]
+    pub rating: Option<u8>,
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FeedbackQuery {
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SummaryQuery {
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FeedbackListResponse {
+    pub feedback: Vec<Feedback>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FeedbackSummaryResponse {
+    pub summaries: Vec<FeedbackSummary>,
+}
+
+/// Check admin access to feedback and fetch the state backend
+async fn admin_feedback(
+    app_state: &AppState<crate::State>,
+    identity: &HttpIdentity,
+) -> Result<Arc<dyn StateBackend>, HttpError> {
+    let daemon = &app_state.data.daemon;
+    AdminPermissionHelper::new(daemon, identity.clone())
+        .await?
+        .require_admin(
+            Action::Read,
+            &ObjectIdentity {
+                namespace: TargetNamespace::System,
+                kind: ObjectKind::System,
+                id: ObjectId::new("feedback"),
+            },
+        )
+        .await?;
+    daemon.get_state_backend().await.map_internal_error()
+}
+
+fn validate(request: &FeedbackRequest) -> Result<(), HttpError> {
+    if request.request_id.trim().is_empty() {
+        return Err(bad_request("request_id is required"));
+    }
+    if request.thumbs_up.is_none() && request.rating.is_none() && request.comment.is_none() {
+        return Err(bad_request(
+            "Feedback needs at least one of thumbs_up, rating or comment",
+        ));
+    }
+    if let Some(rating) = request.rating
+        && !(1..=5).contains(&rating)
+    {
+        return Err(bad_request(format!(
+            "rating must be between 1 and 5, got {rating}"
+        )));
+    }
+    if request
+        .comment
+        .as_ref()
+        .is_some_and(|c| c.chars().count() > MAX_COMMENT_LENGTH)
+    {
+        return Err(bad_request(format!(
+            "comment must be at most {MAX_COMMENT_LENGTH} characters"
+        )));
+    }
+    Ok(())
+}
+
+/// Rate a response. Rating the same request again replaces the caller's
+/// earlier feedback.
+#[instrument(name = "submit_feedback", skip(app_state, request))]
+pub async fn submit_feedback(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Json(request): Json<FeedbackRequest>,
+) -> Result<Json<Feedback>, HttpError> {
+    validate(&request)?;
+    let backend = app_state
+        .data
+        .daemon
+        .get_state_backend()
+        .await
+        .map_internal_error()?;
+
+    let existing = backend
+        .get_feedback(&request.request_id, &identity.id)
+        .await
+        .map_internal_error()?;
+    // Usage is recorded once a response completes; feedback on a request
+    // without one is kept, just not attributed to a model
+    let usage = backend
+        .get_usage_by_request(&request.request_id)
+        .await
+        .map_internal_error()?;
+
+    let now = Utc::now();
+    let feedback = Feedback {
+        id: existing
+            .as_ref()
+            .map(|f| f.id.clone())
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+        request_id: request.request_id,
+        user_id: identity.id.clone(),
+        thumbs_up: request.thumbs_up,
+        rating: request.rating,
+        comment: request.comment.filter(|c| !c.trim().is_empty()),
+        model_id: usage
+            .as_ref()
+            .map(|u| u.model_id.clone())
+            .filter(|m| !m.is_empty()),
+        provider_id: usage
+            .as_ref()
+            .map(|u| u.provider_id.clone())
+            .filter(|p| !p.is_empty()),
+        task_type: usage
+            .as_ref()
+            .and_then(|u| u.metadata.get(TASK_TYPE).cloned()),
+        created_at: existing.as_ref().map_or(now, |f| f.created_at),
+        updated_at: now,
+    };
+    backend
+        .save_feedback(&feedback)
+        .await
+        .map_internal_error_with_context("Failed to save feedback")?;
+
+    let in_experiment = backend
+        .record_experiment_feedback(&feedback.request_id, feedback.score())
+        .await
+        .map_internal_error()?;
+
+    info!(
+        "User {} left feedback on request {} (score {}, experiment: {})",
+        identity.id,
+        feedback.request_id,
+        feedback.score(),
+        in_experiment
+    );
+    Ok(Json(feedback))
+}
+
+/// Recent feedback, newest first (admin only)
+#[instrument(name = "list_feedback", skip(app_state))]
+pub async fn list_feedback(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Query(query): Query<FeedbackQuery>,
+) -> Result<Json<FeedbackListResponse>, HttpError> {
+    let backend = admin_feedback(&app_state, &identity).await?;
+    let feedback = backend
+        .list_feedback(query.since, query.limit.min(MAX_LIST_LIMIT))
+        .await
+        .map_internal_error()?;
+    Ok(Json(FeedbackListResponse { feedback }))
+}
+
+/// Feedback aggregated per model and provider (admin only)
+#[instrument(name = "feedback_summary", skip(app_state))]
+pub async fn feedback_summary(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Query(query): Query<SummaryQuery>,
+) -> Result<Json<FeedbackSummaryResponse>, HttpError> {
+    let backend = admin_feedback(&app_state, &identity).await?;
+    let feedback = backend
+        .list_feedback(query.since, SUMMARY_SAMPLE)
+        .await
+        .map_internal_error()?;
+    Ok(Json(FeedbackSummaryResponse {
+        summaries: FeedbackSummary::summarize(&feedback),
+    }))
+}
+
+/// Add feedback routes
+pub fn add_routes(
+    router: Router<gate_http::AppState<crate::State>>,
+) -> Router<gate_http::AppState<crate::State>> {
+    router
+        .route("/api/feedback", post(submit_feedback))
+        .route("/api/admin/feedback", get(list_feedback))
+        .route("/api/admin/feedback/summary", get(feedback_summary))
+}
@@ -0,0 +1,222 @@
+//! OpenAI-compatible file uploads
+//!
+//! Files uploaded here can be referenced by id from inference requests; see
+//! [`crate::services::files`] for how references are resolved.
+
+use crate::helpers::errors::{ErrorMapExt, bad_request, not_found};
+use crate::services::files::{FileRecord, FileStore};
+use axum::{
+    Router,
+    extract::{DefaultBodyLimit, Multipart, Path, State},
+    http::header,
+    response::{IntoResponse, Json, Response},
+    routing::get,
+};
+use gate_http::{AppState, error::HttpError, services::HttpIdentity};
+use serde::Serialize;
+
+/// Largest accepted upload
+const MAX_UPLOAD_BYTES: usize = 32 * 1024 * 1024;
+const DEFAULT_PURPOSE: &str = "user_data";
+const DEFAULT_CONTENT_TYPE: &str = "application/octet-stream";
+
+/// File object as returned by the OpenAI files API
+#[derive(Debug, Serialize)]
+pub struct FileObject {
+    pub id: String,
+    pub object: &'static str,
+    pub bytes: u64,
+    pub created_at: i64,
+    pub filename: String,
+    pub purpose: String,
+}
+
+impl From<FileRecord> for FileObject {
+    fn from(record: FileRecord) -> Self {
+        FileObject {
+            id: record.id,
+            object: "file",
+            bytes: record.bytes,
+            created_at: record.created_at.timestamp(),
+            filename: record.filename,
+            purpose: record.purpose,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileListResponse {
+    pub object: &'static str,
+    pub data: Vec<FileObject>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeleteFileResponse {
+    pub id: String,
+    pub object: &'static str,
+    pub deleted: bool,
+}
+
+async fn file_store(app_state: &AppState<crate::State>) -> Result<FileStore, HttpError> {
+    app_state
+        .data
+        .daemon
+        .get_file_store()
+        .await
+        .map_internal_error()
+}
+
+/// Load an upload's metadata, treating other users' files as missing
+async fn owned_file(
+    store: &FileStore,
+    identity: &HttpIdentity,
+    id: &str,
+) -> Result<FileRecord, HttpError> {
+    store
+        .get(id)
+        .await
+        .map_internal_error()?
+        .filter(|record| record.owner_id == identity.id)
+        .ok_or_else(|| not_found("File", id))
+}
+
+/// Upload a file as multipart form data with `file` and `purpose` fields
+#[instrument(name = "upload_file", skip(app_state, multipart))]
+pub async fn upload_file(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    mut multipart: Multipart,
+) -> Result<Json<FileObject>, HttpError> {
+    let mut purpose = None;
+    let mut upload = None;
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| bad_request(format!("Invalid multipart body: {e}")))?
+    {
+        match field.name() {
+            Some("purpose") => {
+                purpose = Some(
+                    field
+                        .text()
+                        .await
+                        .map_err(|e| bad_request(format!("Invalid purpose: {e}")))?,
+                );
+            }
+            Some("file") => {
+                let filename = field.file_name().unwrap_or("upload").to_string();
+                let content_type = field
+                    .content_type()
+                    .unwrap_or(DEFAULT_CONTENT_TYPE)
+                    .to_string();
+                let data = field
+                    .bytes()
+                    .await
+                    .map_err(|e| bad_request(format!("Failed to read file: {e}")))?;
+                upload = Some((filename, content_type, data));
+            }
+            _ => {}
+        }
+    }
+
+    let (filename, content_type, data) = upload.ok_or_else(|| bad_request("Missing file field"))?;
+    let purpose = purpose
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+        .unwrap_or_else(|| DEFAULT_PURPOSE.to_string());
+
+    let record = file_store(&app_state)
+        .await?
+        .save(&identity.id, &filename, &purpose, &content_type, &data)
+        .await
+        .map_internal_error_with_context("Failed to store file")?;
+
+    debug!(
+        "User {} uploaded file {} ({} bytes)",
+        identity.id, record.id, record.bytes
+    );
+    Ok(Json(FileObject::from(record)))
+}
+
+/// List the caller's files, most recent first
+#[instrument(name = "list_files", skip(app_state))]
+pub async fn list_files(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+) -> Result<Json<FileListResponse>, HttpError> {
+    let records = file_store(&app_state)
+        .await?
+        .list(&identity.id)
+        .await
+        .map_internal_error_with_context("Failed to list files")?;
+
+    Ok(Json(FileListResponse {
+        object: "list",
+        data: records.into_iter().map(FileObject::from).collect(),
+    }))
+}
+
+/// Get one of the caller's files
+#[instrument(name = "get_file", skip(app_state))]
+pub async fn get_file(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(file_id): Path<String>,
+) -> Result<Json<FileObject>, HttpError> {
+    let store = file_store(&app_state).await?;
+    let record = owned_file(&store, &identity, &file_id).await?;
+    Ok(Json(FileObject::from(record)))
+}
+
+/// Download the contents of one of the caller's files
+#[instrument(name = "get_file_content", skip(app_state))]
+pub async fn get_file_content(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(file_id): Path<String>,
+) -> Result<Response, HttpError> {
+    let store = file_store(&app_state).await?;
+    let record = owned_file(&store, &identity, &file_id).await?;
+    let data = store
+        .read(&file_id)
+        .await
+        .map_internal_error_with_context("Failed to read file")?;
+    Ok(([(header::CONTENT_TYPE, record.content_type)], data).into_response())
+}
+
+/// Delete one of the caller's files
+#[instrument(name = "delete_file", skip(app_state))]
+pub async fn delete_file(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(file_id): Path<String>,
+) -> Result<Json<DeleteFileResponse>, HttpError> {
+    let store = file_store(&app_state).await?;
+    owned_file(&store, &identity, &file_id).await?;
+    store
+        .delete(&file_id)
+        .await
+        .map_internal_error_with_context("Failed to delete file")?;
+
+    debug!("User {} deleted file {}", identity.id, file_id);
+    Ok(Json(DeleteFileResponse {
+        id: file_id,
+        object: "file",
+        deleted: true,
+    }))
+}
+
+/// Add file routes
+pub fn add_routes(
+    router: Router<gate_http::AppState<crate::State>>,
+) -> Router<gate_http::AppState<crate::State>> {
+    router
+        .route(
+            "/v1/files",
+            get(list_files)
+                .post(upload_file)
+                .layer(DefaultBodyLimit::max(MAX_UPLOAD_BYTES)),
+        )
+        .route("/v1/files/{file_id}", get(get_file).delete(delete_file))
+        .route("/v1/files/{file_id}/content", get(get_file_content))
+}
@@ -0,0 +1,295 @@
+//! Admin routes for permission groups
+//!
+//! Members of a group inherit every grant made to it; see
+//! [`gate_core::access::hierarchy`]. Groups are listed while they have
+//! members.
+
+use crate::helpers::{
+    admin::AdminPermissionHelper,
+    errors::{ErrorMapExt, bad_request, not_found},
+};
+use crate::permissions::LocalContext;
+use crate::routes::admin::{
+    GrantPermissionRequest, PermissionChangeResponse, UserPermission, parse_action,
+    parse_object_identity,
+};
+use axum::{
+    Router,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+    routing::{get, post, put},
+};
+use gate_core::access::{
+    Action, ObjectId, ObjectIdentity, ObjectKind, PermissionManager, SubjectIdentity,
+    TargetNamespace, group_subject,
+};
+use gate_http::{AppState, error::HttpError, services::HttpIdentity};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize)]
+pub struct GroupSummary {
+    pub name: String,
+    pub members: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GroupListResponse {
+    pub groups: Vec<GroupSummary>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GroupResponse {
+    pub name: String,
+    pub members: Vec<String>,
+    /// Grants every member inherits
+    pub permissions: Vec<UserPermission>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RevokeGroupPermissionQuery {
+    pub action: String,
+    pub object: String,
+}
+
+/// Check admin access to groups
+async fn admin_helper(
+    app_state: &AppState<crate::State>,
+    identity: &HttpIdentity,
+    action: Action,
+) -> Result<AdminPermissionHelper, HttpError> {
+    let helper = AdminPermissionHelper::new(&app_state.data.daemon, identity.clone()).await?;
+    helper
+        .require_admin(
+            action,
+            &ObjectIdentity {
+                namespace: TargetNamespace::System,
+                kind: ObjectKind::Users,
+                id: ObjectId::new("groups"),
+            },
+        )
+        .await?;
+    Ok(helper)
+}
+
+fn validate_group_name(group: &str) -> Result<(), HttpError> {
+    if group.is_empty()
+        || !group
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    {
+        return Err(bad_request(
+            "Group names may only contain letters, digits, '-', '_' and '.'",
+        ));
+    }
+    Ok(())
+}
+
+/// Identity grants to a group are stored under
+fn group_identity(group: &str) -> SubjectIdentity<LocalContext> {
+    SubjectIdentity::new(
+        group_subject(group),
+        "group",
+        LocalContext {
+            is_owner: false,
+            node_id: "local".to_string(),
+        },
+    )
+}
+
+/// List groups and their members (admin only)
+#[instrument(name = "list_groups", skip(app_state))]
+pub async fn list_groups(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+) -> Result<Json<GroupListResponse>, HttpError> {
+    let helper = admin_helper(&app_state, &identity, Action::ViewPermissions).await?;
+    let backend = &helper.state_backend;
+    let mut groups = Vec::new();
+    for name in backend.list_groups().await.map_internal_error()? {
+        let members = backend
+            .list_group_members(&name)
+            .await
+            .map_internal_error()?;
+        groups.push(GroupSummary { name, members });
+    }
+    Ok(Json(GroupListResponse { groups }))
+}
+
+/// Get a group's members and grants (admin only)
+#[instrument(name = "get_group", skip(app_state))]
+pub async fn get_group(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(group): Path<String>,
+) -> Result<Json<GroupResponse>, HttpError> {
+    let helper = admin_helper(&app_state, &identity, Action::ViewPermissions).await?;
+    let backend = &helper.state_backend;
+    let members = backend
+        .list_group_members(&group)
+        .await
+        .map_internal_error()?;
+    let permissions: Vec<_> = backend
+        .list_user_permissions(&group_subject(&group))
+        .await
+        .map_internal_error()?
+        .into_iter()
+        .map(|(action, object, granted_at)| UserPermission {
+            action,
+            object,
+            granted_at,
+        })
+        .collect();
+    if members.is_empty() && permissions.is_empty() {
+        return Err(not_found("Group", &group));
+    }
+
+    Ok(Json(GroupResponse {
+        name: group,
+        members,
+        permissions,
+    }))
+}
+
+/// Add a user to a group, creating it if needed (admin only)
+#[instrument(name = "add_group_member", skip(app_state))]
+pub async fn add_member(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path((group, user_id)): Path<(String, String)>,
+) -> Result<StatusCode, HttpError> {
+    validate_group_name(&group)?;
+    let helper = admin_helper(&app_state, &identity, Action::Manage).await?;
+    helper
+        .state_backend
+        .get_user(&user_id)
+        .await
+        .map_internal_error()?
+        .ok_or_else(|| not_found("User", &user_id))?;
+    helper
+        .state_backend
+        .add_group_member(&group, &user_id)
+        .await
+        .map_internal_error_with_context("Failed to add group member")?;
+
+    info!(
+        "Admin {} added user {} to group {}",
+        identity.id, user_id, group
+    );
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Remove a user from a group (admin only)
+#[instrument(name = "remove_group_member", skip(app_state))]
+pub async fn remove_member(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path((group, user_id)): Path<(String, String)>,
+) -> Result<StatusCode, HttpError> {
+    let helper = admin_helper(&app_state, &identity, Action::Manage).await?;
+    let removed = helper
+        .state_backend
+        .remove_group_member(&group, &user_id)
+        .await
+        .map_internal_error_with_context("Failed to remove group member")?;
+    if !removed {
+        return Err(not_found("Group member", &user_id));
+    }
+
+    info!(
+        "Admin {} removed user {} from group {}",
+        identity.id, user_id, group
+    );
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Grant a permission every member of a group inherits (admin only)
+#[instrument(name = "grant_group_permission", skip(app_state), fields(action = %request.action, object = %request.object))]
+pub async fn grant_permission(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(group): Path<String>,
+    Json(request): Json<GrantPermissionRequest>,
+) -> Result<(StatusCode, Json<PermissionChangeResponse>), HttpError> {
+    validate_group_name(&group)?;
+    let helper = admin_helper(&app_state, &identity, Action::GrantPermission).await?;
+    let action = parse_action(&request.action)
+        .ok_or_else(|| bad_request(format!("Invalid action: {}", request.action)))?;
+    let object = parse_object_identity(&request.object)
+        .ok_or_else(|| bad_request(format!("Invalid object format: {}", request.object)))?;
+
+    helper
+        .permission_manager
+        .grant(
+            &helper.local_identity,
+            &group_identity(&group),
+            action,
+            &object,
+        )
+        .await
+        .map_internal_error_with_context("Failed to grant permission")?;
+
+    info!(
+        "Admin {} granted {} permission on {} to group {}",
+        identity.id, request.action, request.object, group
+    );
+    Ok((
+        StatusCode::CREATED,
+        Json(PermissionChangeResponse {
+            action: request.action,
+            object: request.object,
+        }),
+    ))
+}
+
+/// Revoke a permission from a group (admin only)
+#[instrument(name = "revoke_group_permission", skip(app_state))]
+pub async fn revoke_permission(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(group): Path<String>,
+    Query(query): Query<RevokeGroupPermissionQuery>,
+) -> Result<Json<PermissionChangeResponse>, HttpError> {
+    let helper = admin_helper(&app_state, &identity, Action::RevokePermission).await?;
+    let action = parse_action(&query.action)
+        .ok_or_else(|| bad_request(format!("Invalid action: {}", query.action)))?;
+    let object = parse_object_identity(&query.object)
+        .ok_or_else(|| bad_request(format!("Invalid object format: {}", query.object)))?;
+
+    helper
+        .permission_manager
+        .revoke(
+            &helper.local_identity,
+            &group_identity(&group),
+            action,
+            &object,
+        )
+        .await
+        .map_internal_error_with_context("Failed to revoke permission")?;
+
+    info!(
+        "Admin {} revoked {} permission on {} from group {}",
+        identity.id, query.action, query.object, group
+    );
+    Ok(Json(PermissionChangeResponse {
+        action: query.action,
+        object: query.object,
+    }))
+}
+
+/// Add permission group routes
+pub fn add_routes(
+    router: Router<gate_http::AppState<crate::State>>,
+) -> Router<gate_http::AppState<crate::State>> {
+    router
+        .route("/api/admin/groups", get(list_groups))
+        .route("/api/admin/groups/{group}", get(get_group))
+        .route(
+            "/api/admin/groups/{group}/members/{user_id}",
+            put(add_member).delete(remove_member),
+        )
+        .route(
+            "/api/admin/groups/{group}/permissions",
+            post(grant_permission).delete(revoke_permission),
+        )
+}
@@ -0,0 +1,26 @@
+//! Permission check shared by admin-only routes
+
+use crate::helpers::admin::AdminPermissionHelper;
+use gate_core::access::{Action, ObjectId, ObjectIdentity, ObjectKind, TargetNamespace};
+use gate_http::{AppState, error::HttpError, services::HttpIdentity};
+
+/// Require `identity` to be allowed `action` on the system object named
+/// `object`, after what the route manages
+pub async fn require_system_admin(
+    app_state: &AppState<crate::State>,
+    identity: &HttpIdentity,
+    action: Action,
+    object: &str,
+) -> Result<(), HttpError> {
+    AdminPermissionHelper::new(&app_state.data.daemon, identity.clone())
+        .await?
+        .require_admin(
+            action,
+            &ObjectIdentity {
+                namespace: TargetNamespace::System,
+                kind: ObjectKind::System,
+                id: ObjectId::new(object),
+            },
+        )
+        .await
+}
@@ -0,0 +1,79 @@
+//! Liveness and readiness probe routes
+//!
+//! Served without authentication so probes need no credentials. Anyone gets
+//! the overall status; callers allowed to read the daemon's status also get
+//! each check.
+
+use crate::helpers::admin::AdminPermissionHelper;
+use crate::services::readiness::{LIVENESS_PATH, READINESS_PATH, readiness};
+use crate::types::ProbeReport;
+use axum::{
+    Router,
+    extract::State,
+    http::{StatusCode, request::Parts},
+    response::Json,
+    routing::get,
+};
+use gate_core::access::{Action, ObjectId, ObjectIdentity, ObjectKind, TargetNamespace};
+use gate_http::{AppState, middleware::AuthProvider};
+
+/// Whether the request carries credentials allowed to read the daemon's
+/// status
+async fn may_read_detail(app_state: &AppState<crate::State>, parts: &Parts) -> bool {
+    let Ok(identity) = app_state.authenticate(parts).await else {
+        return false;
+    };
+    let Ok(helper) = AdminPermissionHelper::new(&app_state.data.daemon, identity).await else {
+        return false;
+    };
+    helper
+        .require_admin(
+            Action::Read,
+            &ObjectIdentity {
+                namespace: TargetNamespace::System,
+                kind: ObjectKind::System,
+                id: ObjectId::new("daemon"),
+            },
+        )
+        .await
+        .is_ok()
+}
+
+/// Liveness probe: answers while the daemon is running
+pub async fn healthz() -> Json<ProbeReport> {
+    Json(ProbeReport::alive())
+}
+
+/// Readiness probe: 503 while the daemon cannot serve requests
+#[instrument(name = "readyz", skip_all)]
+pub async fn readyz(
+    State(app_state): State<AppState<crate::State>>,
+    parts: Parts,
+) -> (StatusCode, Json<ProbeReport>) {
+    let report = readiness(
+        &app_state.data.daemon,
+        app_state.state_backend.as_ref(),
+        app_state.router.as_deref(),
+    )
+    .await;
+    let status = if report.is_ready() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    let report = if may_read_detail(&app_state, &parts).await {
+        report
+    } else {
+        report.summary()
+    };
+    (status, Json(report))
+}
+
+/// Add probe routes
+pub fn add_routes(
+    router: Router<gate_http::AppState<crate::State>>,
+) -> Router<gate_http::AppState<crate::State>> {
+    router
+        .route(LIVENESS_PATH, get(healthz))
+        .route(READINESS_PATH, get(readyz))
+}
@@ -0,0 +1,138 @@
+//! Admin routes for the job journal
+//!
+//! Lists queued, running and dead-lettered jobs and lets an admin retry or
+//! discard dead-lettered ones; see [`crate::services::journal`].
+
+use crate::helpers::{
+    admin::AdminPermissionHelper,
+    errors::{ErrorMapExt, bad_request, not_found},
+};
+use crate::services::journal::{JobKind, JobStatus, Journal, JournalEntry};
+use axum::{
+    Router,
+    extract::{Path, Query, State},
+    response::Json,
+    routing::{get, post},
+};
+use gate_core::access::{Action, ObjectId, ObjectIdentity, ObjectKind, TargetNamespace};
+use gate_http::{AppState, error::HttpError, services::HttpIdentity};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize)]
+pub struct JournalQuery {
+    pub kind: Option<JobKind>,
+    pub status: Option<JobStatus>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JournalListResponse {
+    pub entries: Vec<JournalEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiscardJournalEntryResponse {
+    pub id: String,
+    pub discarded: bool,
+}
+
+/// Check admin access to the journal and fetch it
+async fn admin_journal(
+    app_state: &AppState<crate::State>,
+    identity: &HttpIdentity,
+    action: Action,
+) -> Result<Journal, HttpError> {
+    let daemon = &app_state.data.daemon;
+    AdminPermissionHelper::new(daemon, identity.clone())
+        .await?
+        .require_admin(
+            action,
+            &ObjectIdentity {
+                namespace: TargetNamespace::System,
+                kind: ObjectKind::System,
+                id: ObjectId::new("journal"),
+            },
+        )
+        .await?;
+    daemon.get_journal().await.map_internal_error()
+}
+
+/// List journal entries, optionally filtered by kind and status (admin only)
+#[instrument(name = "list_journal_entries", skip(app_state))]
+pub async fn list_entries(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Query(query): Query<JournalQuery>,
+) -> Result<Json<JournalListResponse>, HttpError> {
+    let journal = admin_journal(&app_state, &identity, Action::Read).await?;
+    let entries = journal.list(query.kind, query.status).await;
+    Ok(Json(JournalListResponse { entries }))
+}
+
+/// Get one journal entry (admin only)
+#[instrument(name = "get_journal_entry", skip(app_state))]
+pub async fn get_entry(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(id): Path<String>,
+) -> Result<Json<JournalEntry>, HttpError> {
+    let journal = admin_journal(&app_state, &identity, Action::Read).await?;
+    let entry = journal
+        .get(&id)
+        .await
+        .ok_or_else(|| not_found("Journal entry", &id))?;
+    Ok(Json(entry))
+}
+
+/// Queue a dead-lettered entry again (admin only)
+#[instrument(name = "retry_journal_entry", skip(app_state))]
+pub async fn retry_entry(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(id): Path<String>,
+) -> Result<Json<JournalEntry>, HttpError> {
+    let journal = admin_journal(&app_state, &identity, Action::Manage).await?;
+    let Some(current) = journal.get(&id).await else {
+        return Err(not_found("Journal entry", &id));
+    };
+    if current.status != JobStatus::DeadLetter {
+        return Err(bad_request("Only dead-lettered entries can be retried"));
+    }
+    let entry = journal
+        .retry(&id)
+        .await
+        .map_internal_error()?
+        .ok_or_else(|| bad_request("Only dead-lettered entries can be retried"))?;
+    info!("Admin {} retried journal entry {}", identity.id, id);
+    Ok(Json(entry))
+}
+
+/// Drop an entry without running it (admin only)
+#[instrument(name = "discard_journal_entry", skip(app_state))]
+pub async fn discard_entry(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(id): Path<String>,
+) -> Result<Json<DiscardJournalEntryResponse>, HttpError> {
+    let journal = admin_journal(&app_state, &identity, Action::Manage).await?;
+    if !journal.discard(&id).await.map_internal_error()? {
+        return Err(not_found("Journal entry", &id));
+    }
+    info!("Admin {} discarded journal entry {}", identity.id, id);
+    Ok(Json(DiscardJournalEntryResponse {
+        id,
+        discarded: true,
+    }))
+}
+
+/// Add journal routes
+pub fn add_routes(
+    router: Router<gate_http::AppState<crate::State>>,
+) -> Router<gate_http::AppState<crate::State>> {
+    router
+        .route("/api/admin/journal", get(list_entries))
+        .route(
+            "/api/admin/journal/{id}",
+            get(get_entry).delete(discard_entry),
+        )
+        .route("/api/admin/journal/{id}/retry", post(retry_entry))
+}
@@ -0,0 +1,283 @@
+//! API key management routes for the authenticated user
+
+use crate::helpers::{admin::AdminPermissionHelper, errors::ErrorMapExt};
+use crate::services::observer::observer_permissions;
+use crate::services::{ApiKeyService, api_keys::ApiKeyScope};
+use axum::{
+    Router,
+    extract::{Path, State},
+    response::Json,
+    routing::{delete, get},
+};
+use chrono::{DateTime, Utc};
+use gate_core::ApiKey;
+use gate_core::access::IdentityContext;
+use gate_http::{AppState, error::HttpError, services::HttpIdentity};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiKeyInfo {
+    pub id: String,
+    pub name: String,
+    pub key_prefix: String,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub allowed_models: Vec<String>,
+    pub spend_cap: Option<f64>,
+    pub max_concurrent_streams: Option<u32>,
+    /// Streams the key has open right now
+    pub active_streams: u32,
+    pub observer: bool,
+    pub parameter_profile: Option<String>,
+    pub expired: bool,
+}
+
+impl From<ApiKey> for ApiKeyInfo {
+    fn from(key: ApiKey) -> Self {
+        let scope = ApiKeyScope::from_key(&key);
+        ApiKeyInfo {
+            expired: scope.is_expired_at(Utc::now()),
+            id: key.key_hash,
+            name: key.name,
+            key_prefix: scope.key_prefix,
+            created_at: key.created_at,
+            last_used_at: key.last_used_at,
+            expires_at: scope.expires_at,
+            allowed_models: scope.allowed_models,
+            spend_cap: scope.spend_cap,
+            max_concurrent_streams: scope.max_concurrent_streams,
+            active_streams: 0,
+            observer: scope.observer,
+            parameter_profile: scope.parameter_profile,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiKeyListResponse {
+    pub keys: Vec<ApiKeyInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateApiKeyRequest {
+    pub name: String,
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub allowed_models: Vec<String>,
+    pub spend_cap: Option<f64>,
+    /// Streaming requests the key may have open at once
+    pub max_concurrent_streams: Option<u32>,
+    /// Create a read-only observer key for dashboards and monitoring
+    #[serde(default)]
+    pub observer: bool,
+    /// Parameter profile enforced on the key's requests
+    #[serde(default)]
+    pub parameter_profile: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreateApiKeyResponse {
+    /// The raw key; it is not retrievable after this response
+    pub key: String,
+    pub info: ApiKeyInfo,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RevokeApiKeyResponse {
+    pub id: String,
+}
+
+async fn api_key_service(app_state: &AppState<crate::State>) -> Result<ApiKeyService, HttpError> {
+    let state_backend = app_state
+        .data
+        .daemon
+        .get_state_backend()
+        .await
+        .map_internal_error()?;
+    Ok(ApiKeyService::new(state_backend))
+}
+
+/// Refuse callers signed in with an API key. A key could otherwise mint
+/// keys owned by itself, which escape its scope and outlive its revocation.
+fn require_user(identity: &HttpIdentity) -> Result<(), HttpError> {
+    if identity.context.get("auth_method") == Some("api_key") {
+        return Err(HttpError::AuthorizationFailed(
+            "API keys cannot manage API keys".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// List the caller's API keys
+#[instrument(name = "list_api_keys", skip(app_state))]
+pub async fn list_keys(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+) -> Result<Json<ApiKeyListResponse>, HttpError> {
+    require_user(&identity)?;
+    let service = api_key_service(&app_state).await?;
+    let keys = service
+        .list(&identity.id)
+        .await
+        .map_internal_error_with_context("Failed to list API keys")?;
+
+    let mut infos = Vec::with_capacity(keys.len());
+    for key in keys {
+        let mut info = ApiKeyInfo::from(key);
+        info.active_streams = service
+            .active_streams(&info.id)
+            .await
+            .map_internal_error_with_context("Failed to count open streams")?;
+        infos.push(info);
+    }
+
+    Ok(Json(ApiKeyListResponse { keys: infos }))
+}
+
+/// Create a new API key for the caller
+#[instrument(name = "create_api_key", skip(app_state, request))]
+pub async fn create_key(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Json(request): Json<CreateApiKeyRequest>,
+) -> Result<Json<CreateApiKeyResponse>, HttpError> {
+    require_user(&identity)?;
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err(HttpError::BadRequest("Key name is required".to_string()));
+    }
+    if request.expires_at.is_some_and(|e| e <= Utc::now()) {
+        return Err(HttpError::BadRequest(
+            "Expiry must be in the future".to_string(),
+        ));
+    }
+    if request.spend_cap.is_some_and(|cap| cap <= 0.0) {
+        return Err(HttpError::BadRequest(
+            "Spend cap must be positive".to_string(),
+        ));
+    }
+    if request.max_concurrent_streams == Some(0) {
+        return Err(HttpError::BadRequest(
+            "Stream limit must be positive".to_string(),
+        ));
+    }
+
+    if let Some(profile) = &request.parameter_profile {
+        let settings = app_state
+            .data
+            .daemon
+            .get_settings()
+            .await
+            .map_internal_error()?;
+        if !settings.routing.profiles.contains_key(profile) {
+            return Err(HttpError::BadRequest(format!(
+                "Unknown parameter profile '{profile}'"
+            )));
+        }
+    }
+
+    if request.observer {
+        // The key is granted what observers may read, so its owner must
+        // already be able to read it
+        let helper = AdminPermissionHelper::new(&app_state.data.daemon, identity.clone()).await?;
+        for (action, object) in observer_permissions() {
+            helper.check_permission(action, &object).await?;
+        }
+    }
+
+    let scope = ApiKeyScope {
+        expires_at: request.expires_at,
+        allowed_models: request.allowed_models,
+        spend_cap: request.spend_cap,
+        max_concurrent_streams: request.max_concurrent_streams,
+        observer: request.observer,
+        parameter_profile: request.parameter_profile,
+        ..Default::default()
+    };
+
+    let (key, record) = api_key_service(&app_state)
+        .await?
+        .create(&identity.id, name.to_string(), scope)
+        .await
+        .map_internal_error_with_context("Failed to create API key")?;
+
+    info!("User {} created API key '{}'", identity.id, record.name);
+    Ok(Json(CreateApiKeyResponse {
+        key,
+        info: ApiKeyInfo::from(record),
+    }))
+}
+
+/// Revoke one of the caller's API keys
+#[instrument(name = "revoke_api_key", skip(app_state))]
+pub async fn revoke_key(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(key_id): Path<String>,
+) -> Result<Json<RevokeApiKeyResponse>, HttpError> {
+    require_user(&identity)?;
+    let revoked = api_key_service(&app_state)
+        .await?
+        .revoke(&identity.id, &key_id)
+        .await
+        .map_internal_error_with_context("Failed to revoke API key")?;
+
+    if !revoked {
+        return Err(HttpError::NotFound(format!("API key {key_id} not found")));
+    }
+
+    info!("User {} revoked API key {}", identity.id, key_id);
+    Ok(Json(RevokeApiKeyResponse { id: key_id }))
+}
+
+/// Add API key routes
+pub fn add_routes(
+    router: Router<gate_http::AppState<crate::State>>,
+) -> Router<gate_http::AppState<crate::State>> {
+    router
+        .route("/api/keys", get(list_keys).post(create_key))
+        .route("/api/keys/{key_id}", delete(revoke_key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::{Method, Request, StatusCode};
+    use gate_http::services::HttpContext;
+    use gate_sqlx::SqliteStateBackend;
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_api_keys_cannot_manage_keys() {
+        let state = crate::state::tests::make_minimal_state(false).await;
+        let backend = Arc::new(SqliteStateBackend::new(":memory:").await.unwrap());
+        let app = add_routes(Router::new()).with_state(AppState::new(backend, state));
+        let identity = HttpIdentity::new(
+            "apikey:0123456789abcdef".to_string(),
+            "api-key".to_string(),
+            HttpContext::new()
+                .with_attribute("auth_method", "api_key")
+                .with_attribute("user_id", "owner"),
+        );
+
+        let requests = [
+            (Method::POST, "/api/keys", r#"{"name": "child"}"#),
+            (Method::GET, "/api/keys", ""),
+            (Method::DELETE, "/api/keys/0123456789abcdef", ""),
+        ];
+        for (method, uri, body) in requests {
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .extension(identity.clone())
+                .body(Body::from(body))
+                .unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::FORBIDDEN, "{uri}");
+        }
+    }
+}
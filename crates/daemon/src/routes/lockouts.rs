@@ -0,0 +1,54 @@
+//! Sign-in lockout routes
+//!
+//! Admins see the addresses and accounts with failed sign-ins on record,
+//! along with addresses the relay's access log banned, and can lift a
+//! lockout or ban, for instance when a user mistyped their way into one.
+
+use crate::routes::guard::require_system_admin;
+use crate::services::lockout::{Lockout, LockoutSubject};
+use axum::{
+    Router,
+    extract::State,
+    response::Json,
+    routing::{get, post},
+};
+use chrono::Utc;
+use gate_core::access::Action;
+use gate_http::{AppState, error::HttpError, services::HttpIdentity};
+
+/// Addresses and accounts with failed sign-ins, locked out ones first
+/// (admin only)
+#[instrument(name = "list_lockouts", skip(app_state))]
+pub async fn list_lockouts(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+) -> Result<Json<Vec<Lockout>>, HttpError> {
+    require_system_admin(&app_state, &identity, Action::Read, "lockouts").await?;
+    Ok(Json(app_state.data.auth_lockout.lockouts(Utc::now())))
+}
+
+/// Lift the lockout on an address or account (admin only)
+#[instrument(name = "unblock_lockout", skip(app_state))]
+pub async fn unblock(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Json(subject): Json<LockoutSubject>,
+) -> Result<Json<Vec<Lockout>>, HttpError> {
+    require_system_admin(&app_state, &identity, Action::Write, "lockouts").await?;
+    let lockout = &app_state.data.auth_lockout;
+    if !lockout.unblock(&subject, &identity.id).await {
+        return Err(HttpError::NotFound(format!(
+            "No failed sign-ins for {subject}"
+        )));
+    }
+    Ok(Json(lockout.lockouts(Utc::now())))
+}
+
+/// Add lockout routes
+pub fn add_routes(
+    router: Router<gate_http::AppState<crate::State>>,
+) -> Router<gate_http::AppState<crate::State>> {
+    router
+        .route("/api/admin/lockouts", get(list_lockouts))
+        .route("/api/admin/lockouts/unblock", post(unblock))
+}
@@ -1,3 +1,36 @@
 pub mod admin;
+pub mod audit;
 pub mod auth;
 pub mod config;
+pub mod conversations;
+pub mod credentials;
+pub mod data;
+pub mod devices;
+pub mod doctor;
+pub mod documents;
+pub mod evals;
+pub mod experiments;
+pub mod export;
+pub mod feedback;
+pub mod files;
+pub mod groups;
+pub mod guard;
+pub mod health;
+pub mod journal;
+pub mod keys;
+pub mod lockouts;
+pub mod mode;
+pub mod models;
+pub mod node;
+pub mod onboarding;
+pub mod preferences;
+pub mod profiling;
+pub mod prompts;
+pub mod provenance;
+pub mod providers;
+pub mod status;
+pub mod tasks;
+pub mod tls;
+pub mod tlsforward;
+pub mod usage;
+pub mod version;
@@ -0,0 +1,91 @@
+//! Maintenance and read-only mode routes
+//!
+//! Admins turn maintenance mode on to take inference offline during an
+//! upgrade, and read-only mode to stop admin and config changes while it
+//! runs. This endpoint stays writable in read-only mode so it can be turned
+//! off again.
+
+use crate::routes::guard::require_system_admin;
+use crate::services::server_mode::{MODE_PATH, ServerModeStatus};
+use axum::{Router, extract::State, response::Json, routing::get};
+use chrono::Utc;
+use gate_core::access::Action;
+use gate_http::{AppState, error::HttpError, services::HttpIdentity};
+use serde::{Deserialize, Serialize};
+
+/// Modes in effect and the message inference clients get in maintenance
+#[derive(Debug, Serialize)]
+pub struct ServerModeResponse {
+    #[serde(flatten)]
+    pub status: ServerModeStatus,
+    pub maintenance_message: String,
+}
+
+/// Modes to change; omitted fields keep their current value
+#[derive(Debug, Deserialize)]
+pub struct UpdateServerModeRequest {
+    #[serde(default)]
+    pub maintenance: Option<bool>,
+    #[serde(default)]
+    pub read_only: Option<bool>,
+    /// Maintenance message; an empty string restores the configured one
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+fn response(app_state: &AppState<crate::State>) -> ServerModeResponse {
+    let mode = &app_state.data.server_mode;
+    ServerModeResponse {
+        status: mode.status(),
+        maintenance_message: mode.maintenance_message(),
+    }
+}
+
+/// Current maintenance and read-only modes (admin only)
+#[instrument(name = "get_server_mode", skip(app_state))]
+pub async fn get_server_mode(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+) -> Result<Json<ServerModeResponse>, HttpError> {
+    require_system_admin(&app_state, &identity, Action::Read, "mode").await?;
+    Ok(Json(response(&app_state)))
+}
+
+/// Turn maintenance or read-only mode on or off (admin only)
+#[instrument(name = "update_server_mode", skip(app_state))]
+pub async fn update_server_mode(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Json(request): Json<UpdateServerModeRequest>,
+) -> Result<Json<ServerModeResponse>, HttpError> {
+    require_system_admin(&app_state, &identity, Action::Manage, "mode").await?;
+
+    let mode = &app_state.data.server_mode;
+    let mut status = mode.status();
+    if let Some(maintenance) = request.maintenance {
+        status.maintenance = maintenance;
+    }
+    if let Some(read_only) = request.read_only {
+        status.read_only = read_only;
+    }
+    if let Some(message) = request.message {
+        let message = message.trim();
+        status.message = (!message.is_empty()).then(|| message.to_string());
+    }
+    status.updated_by = Some(identity.id.clone());
+    status.updated_at = Some(Utc::now());
+    mode.set(status.clone());
+
+    info!(
+        "Admin {} set maintenance={} read_only={}",
+        identity.id, status.maintenance, status.read_only
+    );
+    Ok(Json(response(&app_state)))
+}
+
+/// Add server mode routes
+pub fn add_routes(
+    router: Router<gate_http::AppState<crate::State>>,
+) -> Router<gate_http::AppState<crate::State>> {
+    router.route(MODE_PATH, get(get_server_mode).put(update_server_mode))
+}
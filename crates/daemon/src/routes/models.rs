@@ -0,0 +1,111 @@
+//! Local model routes
+//!
+//! Admins see which local models are loaded and the memory they hold, load
+//! a model before its first request and unload one to free memory. Model
+//! ids contain slashes, so they are passed in the body rather than the path.
+
+use crate::helpers::{admin::AdminPermissionHelper, errors::ErrorMapExt};
+use crate::services::{LocalModels, ModelCacheStatus, models::LoadedModel};
+use axum::{
+    Router,
+    extract::State,
+    response::Json,
+    routing::{get, post},
+};
+use gate_core::access::{Action, ObjectId, ObjectIdentity, ObjectKind, TargetNamespace};
+use gate_http::{AppState, error::HttpError, services::HttpIdentity};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Model to load or unload
+#[derive(Debug, Deserialize)]
+pub struct ModelRequest {
+    pub model: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UnloadModelResponse {
+    pub model: String,
+    /// Whether the model was loaded
+    pub unloaded: bool,
+}
+
+/// Check the caller is an admin and get the local models
+async fn local_models(
+    app_state: &AppState<crate::State>,
+    identity: &HttpIdentity,
+    action: Action,
+) -> Result<Arc<LocalModels>, HttpError> {
+    let daemon = &app_state.data.daemon;
+    AdminPermissionHelper::new(daemon, identity.clone())
+        .await?
+        .require_admin(
+            action,
+            &ObjectIdentity {
+                namespace: TargetNamespace::System,
+                kind: ObjectKind::System,
+                id: ObjectId::new("local_models"),
+            },
+        )
+        .await?;
+    daemon
+        .get_local_models()
+        .await
+        .map_internal_error()?
+        .ok_or_else(|| HttpError::NotFound("Local inference is not configured".to_string()))
+}
+
+/// Loaded local models and memory use (admin only)
+#[instrument(name = "get_local_models", skip(app_state))]
+pub async fn get_local_models(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+) -> Result<Json<ModelCacheStatus>, HttpError> {
+    let models = local_models(&app_state, &identity, Action::Read).await?;
+    Ok(Json(models.status()))
+}
+
+/// Load a local model, unloading others if it does not fit (admin only)
+#[instrument(name = "load_local_model", skip(app_state))]
+pub async fn load_local_model(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Json(request): Json<ModelRequest>,
+) -> Result<Json<LoadedModel>, HttpError> {
+    let models = local_models(&app_state, &identity, Action::Manage).await?;
+    let loaded = models
+        .load(&request.model)
+        .await
+        .map_err(|e| HttpError::ServiceUnavailable(e.to_string()))?;
+    info!("Admin {} loaded local model {}", identity.id, request.model);
+    Ok(Json(loaded))
+}
+
+/// Unload a local model to free its memory (admin only)
+#[instrument(name = "unload_local_model", skip(app_state))]
+pub async fn unload_local_model(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Json(request): Json<ModelRequest>,
+) -> Result<Json<UnloadModelResponse>, HttpError> {
+    let models = local_models(&app_state, &identity, Action::Manage).await?;
+    let unloaded = models.unload(&request.model);
+    info!(
+        "Admin {} unloaded local model {} (was loaded: {unloaded})",
+        identity.id, request.model
+    );
+    Ok(Json(UnloadModelResponse {
+        model: request.model,
+        unloaded,
+    }))
+}
+
+/// Add local model routes
+pub fn add_routes(
+    router: Router<gate_http::AppState<crate::State>>,
+) -> Router<gate_http::AppState<crate::State>> {
+    router
+        .route("/api/admin/models", get(get_local_models))
+        .route("/api/admin/models/load", post(load_local_model))
+        .route("/api/admin/models/unload", post(unload_local_model))
+}
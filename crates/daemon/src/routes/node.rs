@@ -0,0 +1,55 @@
+//! Node identity routes
+//!
+//! Serves the node's signed attestation without authentication, so the UI
+//! and peering Gates can check who they are talking to before trusting it.
+//! A `nonce` in the query is signed with the document.
+
+use crate::helpers::errors::ErrorMapExt;
+use crate::services::identity::{ATTESTATION_PATH, Attestation, MAX_NONCE_LENGTH};
+use axum::{
+    Router,
+    extract::{Query, State},
+    response::Json,
+    routing::get,
+};
+use gate_http::{AppState, error::HttpError};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct IdentityQuery {
+    /// Verifier's nonce, to be signed with the document
+    #[serde(default)]
+    pub nonce: Option<String>,
+}
+
+/// The node's attestation, signed with its identity key
+#[instrument(name = "get_node_identity", skip(app_state))]
+pub async fn get_identity(
+    State(app_state): State<AppState<crate::State>>,
+    Query(query): Query<IdentityQuery>,
+) -> Result<Json<Attestation>, HttpError> {
+    if query
+        .nonce
+        .as_ref()
+        .is_some_and(|nonce| nonce.len() > MAX_NONCE_LENGTH)
+    {
+        return Err(HttpError::BadRequest(format!(
+            "Nonce is longer than {MAX_NONCE_LENGTH} bytes"
+        )));
+    }
+    let daemon = &app_state.data.daemon;
+    let identity = daemon.get_node_identity().await.map_internal_error()?;
+    let settings = daemon.get_settings().await.map_internal_error()?;
+    let attestation = identity
+        .attest(&settings, query.nonce)
+        .await
+        .map_internal_error()?;
+    Ok(Json(attestation))
+}
+
+/// Add node identity routes
+pub fn add_routes(
+    router: Router<gate_http::AppState<crate::State>>,
+) -> Router<gate_http::AppState<crate::State>> {
+    router.route(ATTESTATION_PATH, get(get_identity))
+}
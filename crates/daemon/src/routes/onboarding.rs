@@ -0,0 +1,178 @@
+//! Onboarding routes for importing provider keys from existing tools
+
+use crate::config::ProviderType;
+use crate::daemon::server::ServerBuilder;
+use crate::helpers::{admin::AdminPermissionHelper, errors::ErrorMapExt};
+use crate::services::credential_import::{
+    CredentialImportService, CredentialSource, DetectedCredential, provider_config_for,
+};
+use axum::{
+    Router,
+    extract::State,
+    response::Json,
+    routing::{get, post},
+};
+use gate_core::access::{Action, ObjectId, ObjectIdentity, ObjectKind, TargetNamespace};
+use gate_http::{AppState, error::HttpError, services::HttpIdentity};
+use serde::{Deserialize, Serialize};
+
+/// Fallback sinks that a configured key of the same provider replaces
+const ANTHROPIC_FALLBACK_SINK: &str = "provider://anthropic/fallback";
+const OPENAI_FALLBACK_SINK: &str = "provider://openai/fallback";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DetectedCredentialsResponse {
+    pub sources: Vec<DetectedCredential>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportCredentialsRequest {
+    pub sources: Vec<CredentialSource>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportedProvider {
+    pub source: CredentialSource,
+    pub provider_name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SkippedSource {
+    pub source: CredentialSource,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportCredentialsResponse {
+    pub imported: Vec<ImportedProvider>,
+    pub skipped: Vec<SkippedSource>,
+}
+
+fn config_object() -> ObjectIdentity {
+    ObjectIdentity {
+        namespace: TargetNamespace::System,
+        kind: ObjectKind::Config,
+        id: ObjectId::new("*"),
+    }
+}
+
+/// List credential sources present on this machine (admin only)
+#[instrument(name = "detect_credentials", skip(app_state))]
+pub async fn detect_credentials(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+) -> Result<Json<DetectedCredentialsResponse>, HttpError> {
+    AdminPermissionHelper::new(&app_state.data.daemon, identity.clone())
+        .await?
+        .require_admin(Action::Write, &config_object())
+        .await?;
+
+    let sources = CredentialImportService::new().detect();
+    Ok(Json(DetectedCredentialsResponse { sources }))
+}
+
+/// Import keys from the sources the user selected into the provider config (admin only)
+#[instrument(name = "import_credentials", skip(app_state, request))]
+pub async fn import_credentials(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Json(request): Json<ImportCredentialsRequest>,
+) -> Result<Json<ImportCredentialsResponse>, HttpError> {
+    AdminPermissionHelper::new(&app_state.data.daemon, identity.clone())
+        .await?
+        .require_admin(Action::Write, &config_object())
+        .await?;
+
+    let daemon = app_state
+        .data
+        .daemon
+        .clone()
+        .with_http_identity(&identity)
+        .await
+        .map_internal_error()?;
+    let mut settings = daemon.get_config().await.map_internal_error()?;
+
+    let service = CredentialImportService::new();
+    let mut imported = Vec::new();
+    let mut skipped = Vec::new();
+    let mut new_providers = Vec::new();
+    for source in request.sources {
+        let key = match service.read(source) {
+            Ok(key) => key,
+            Err(e) => {
+                skipped.push(SkippedSource {
+                    source,
+                    reason: e.to_string(),
+                });
+                continue;
+            }
+        };
+        match provider_config_for(&settings, source, key) {
+            Some(provider) => {
+                imported.push(ImportedProvider {
+                    source,
+                    provider_name: provider.name.clone(),
+                });
+                settings.providers.push(provider.clone());
+                new_providers.push(provider);
+            }
+            None => skipped.push(SkippedSource {
+                source,
+                reason: "Key is already configured".to_string(),
+            }),
+        }
+    }
+
+    if new_providers.is_empty() {
+        return Ok(Json(ImportCredentialsResponse { imported, skipped }));
+    }
+
+    daemon
+        .update_config(settings)
+        .await
+        .map_internal_error_with_context("Failed to save imported providers")?;
+
+    // Route to the new providers right away instead of waiting for a restart
+    if let Some(router) = &app_state.router {
+        let registry = router.sink_registry();
+        for provider in &new_providers {
+            let sink = match ServerBuilder::create_provider_sink(provider, None).await {
+                Ok(sink) => sink,
+                Err(e) => {
+                    warn!("Failed to create sink for imported {}: {e}", provider.name);
+                    continue;
+                }
+            };
+            let (kind, fallback) = match provider.provider {
+                ProviderType::Anthropic => ("anthropic", ANTHROPIC_FALLBACK_SINK),
+                ProviderType::OpenAI | ProviderType::Custom | ProviderType::Mock => {
+                    ("openai", OPENAI_FALLBACK_SINK)
+                }
+            };
+            // The sink index follows the registry, so routing sees both changes
+            registry.remove(fallback).await;
+            registry
+                .register(format!("provider://{kind}/{}", provider.name), sink)
+                .await;
+        }
+    }
+
+    info!(
+        "Admin {} imported {} provider key(s) during onboarding",
+        identity.id,
+        imported.len()
+    );
+    Ok(Json(ImportCredentialsResponse { imported, skipped }))
+}
+
+/// Add onboarding routes
+pub fn add_routes(
+    router: Router<gate_http::AppState<crate::State>>,
+) -> Router<gate_http::AppState<crate::State>> {
+    router
+        .route("/api/onboarding/credentials", get(detect_credentials))
+        .route(
+            "/api/onboarding/credentials/import",
+            post(import_credentials),
+        )
+}
@@ -0,0 +1,195 @@
+//! Preferences for the authenticated user
+
+use crate::helpers::errors::ErrorMapExt;
+use axum::{Router, extract::State, response::Json, routing::get};
+use chrono::Utc;
+use gate_core::{StateBackend, UserPreferences};
+use gate_http::{AppState, error::HttpError, services::HttpIdentity};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value as JsonValue};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemePreference {
+    Light,
+    Dark,
+    HighContrast,
+}
+
+/// Preferences stored per user; unset fields fall back to client defaults
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Preferences {
+    /// Model preselected in the chat playground
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub theme: Option<ThemePreference>,
+    /// Accent colour as `#rrggbb`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accent_color: Option<String>,
+    /// Stream chat responses token by token
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub streaming: Option<bool>,
+    /// ISO 4217 code used when displaying costs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+}
+
+impl Preferences {
+    fn validate(&self) -> Result<(), HttpError> {
+        if let Some(accent) = &self.accent_color
+            && !is_hex_color(accent)
+        {
+            return Err(HttpError::BadRequest(format!(
+                "accent_color must be a #rrggbb colour, got {accent:?}"
+            )));
+        }
+        if let Some(currency) = &self.currency
+            && !is_currency_code(currency)
+        {
+            return Err(HttpError::BadRequest(format!(
+                "currency must be a three-letter ISO 4217 code, got {currency:?}"
+            )));
+        }
+        if self
+            .default_model
+            .as_deref()
+            .is_some_and(|model| model.trim().is_empty())
+        {
+            return Err(HttpError::BadRequest(
+                "default_model cannot be empty".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn is_hex_color(value: &str) -> bool {
+    value
+        .strip_prefix('#')
+        .is_some_and(|hex| hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+fn is_currency_code(value: &str) -> bool {
+    value.len() == 3 && value.chars().all(|c| c.is_ascii_uppercase())
+}
+
+/// Overwrite top-level keys from `patch`; `null` removes a key
+fn merge(stored: &mut Map<String, JsonValue>, patch: Map<String, JsonValue>) {
+    for (key, value) in patch {
+        if value.is_null() {
+            stored.remove(&key);
+        } else {
+            stored.insert(key, value);
+        }
+    }
+}
+
+async fn state_backend(
+    app_state: &AppState<crate::State>,
+) -> Result<Arc<dyn StateBackend>, HttpError> {
+    app_state
+        .data
+        .daemon
+        .get_state_backend()
+        .await
+        .map_internal_error()
+}
+
+/// Stored preference document for a user, empty if none were saved
+async fn stored_preferences(
+    backend: &dyn StateBackend,
+    user_id: &str,
+) -> Result<Map<String, JsonValue>, HttpError> {
+    let stored = backend
+        .get_user_preferences(user_id)
+        .await
+        .map_internal_error_with_context("Failed to load preferences")?;
+    Ok(match stored.map(|p| p.preferences) {
+        Some(JsonValue::Object(map)) => map,
+        _ => Map::new(),
+    })
+}
+
+/// A user's preferences; malformed stored values are dropped rather than failing
+pub(crate) async fn load_preferences(
+    backend: &dyn StateBackend,
+    user_id: &str,
+) -> Result<Preferences, HttpError> {
+    let stored = stored_preferences(backend, user_id).await?;
+    Ok(serde_json::from_value(JsonValue::Object(stored)).unwrap_or_default())
+}
+
+/// Get the caller's preferences
+#[instrument(name = "get_preferences", skip(app_state))]
+pub async fn get_preferences(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+) -> Result<Json<Preferences>, HttpError> {
+    let backend = state_backend(&app_state).await?;
+    Ok(Json(
+        load_preferences(backend.as_ref(), &identity.id).await?,
+    ))
+}
+
+/// Validate and store a complete preference document
+async fn save_preferences(
+    backend: &dyn StateBackend,
+    user_id: &str,
+    document: Map<String, JsonValue>,
+) -> Result<Preferences, HttpError> {
+    let preferences: Preferences = serde_json::from_value(JsonValue::Object(document))
+        .map_err(|e| HttpError::BadRequest(format!("Invalid preferences: {e}")))?;
+    preferences.validate()?;
+
+    backend
+        .save_user_preferences(&UserPreferences {
+            user_id: user_id.to_string(),
+            preferences: serde_json::to_value(&preferences).map_internal_error()?,
+            updated_at: Utc::now(),
+        })
+        .await
+        .map_internal_error_with_context("Failed to save preferences")?;
+
+    debug!("User {user_id} updated preferences");
+    Ok(preferences)
+}
+
+/// Replace all of the caller's preferences
+#[instrument(name = "replace_preferences", skip(app_state, document))]
+pub async fn replace_preferences(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Json(document): Json<Map<String, JsonValue>>,
+) -> Result<Json<Preferences>, HttpError> {
+    let backend = state_backend(&app_state).await?;
+    let preferences = save_preferences(backend.as_ref(), &identity.id, document).await?;
+    Ok(Json(preferences))
+}
+
+/// Update some of the caller's preferences; omitted fields are left unchanged
+#[instrument(name = "update_preferences", skip(app_state, patch))]
+pub async fn update_preferences(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Json(patch): Json<Map<String, JsonValue>>,
+) -> Result<Json<Preferences>, HttpError> {
+    let backend = state_backend(&app_state).await?;
+    let mut stored = stored_preferences(backend.as_ref(), &identity.id).await?;
+    merge(&mut stored, patch);
+    let preferences = save_preferences(backend.as_ref(), &identity.id, stored).await?;
+    Ok(Json(preferences))
+}
+
+/// Add preference routes
+pub fn add_routes(
+    router: Router<gate_http::AppState<crate::State>>,
+) -> Router<gate_http::AppState<crate::State>> {
+    router.route(
+        "/api/preferences",
+        get(get_preferences)
+            .put(replace_preferences)
+            .patch(update_preferences),
+    )
+}
@@ -0,0 +1,185 @@
+//! Profiling routes
+//!
+//! Admins start a CPU or heap profile for a number of seconds, poll the job
+//! and download the profile once it is complete. Profiles are kept under the
+//! state directory, so they survive a restart of a daemon being diagnosed.
+
+use crate::helpers::{
+    admin::AdminPermissionHelper,
+    errors::{ErrorMapExt, bad_request, not_found},
+};
+use crate::services::ProfileStore;
+use crate::services::profiling::{ProfileJob, ProfileKind, ProfileStatus};
+use crate::state_dir::StateDir;
+use axum::{
+    Router,
+    extract::{Path, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Json, Response},
+    routing::get,
+};
+use gate_core::access::{Action, ObjectId, ObjectIdentity, ObjectKind, TargetNamespace};
+use gate_http::{AppState, error::HttpError, services::HttpIdentity};
+use serde::{Deserialize, Serialize};
+
+fn default_seconds() -> u64 {
+    30
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProfileRequest {
+    pub kind: ProfileKind,
+    /// How long to sample for
+    #[serde(default = "default_seconds")]
+    pub seconds: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProfileListResponse {
+    pub profiles: Vec<ProfileJob>,
+}
+
+/// The profile store, once the caller is allowed `action` on profiles
+async fn profile_store(
+    app_state: &AppState<crate::State>,
+    identity: &HttpIdentity,
+    action: Action,
+) -> Result<ProfileStore, HttpError> {
+    AdminPermissionHelper::new(&app_state.data.daemon, identity.clone())
+        .await?
+        .require_admin(
+            action,
+            &ObjectIdentity {
+                namespace: TargetNamespace::System,
+                kind: ObjectKind::System,
+                id: ObjectId::new("profiles"),
+            },
+        )
+        .await?;
+    let dir = StateDir::new()
+        .await
+        .map_internal_error()?
+        .dir_for("profiles");
+    Ok(ProfileStore::new(dir))
+}
+
+async fn find_profile(store: &ProfileStore, id: &str) -> Result<ProfileJob, HttpError> {
+    store
+        .get(id)
+        .await
+        .map_internal_error()?
+        .ok_or_else(|| not_found("Profile", id))
+}
+
+/// Start capturing a profile (admin only)
+#[instrument(name = "start_profile", skip(app_state))]
+pub async fn start_profile(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Json(request): Json<ProfileRequest>,
+) -> Result<(StatusCode, Json<ProfileJob>), HttpError> {
+    let store = profile_store(&app_state, &identity, Action::Execute).await?;
+    let config = app_state
+        .data
+        .daemon
+        .get_settings()
+        .await
+        .map_internal_error()?
+        .profiling;
+    if !config.enabled {
+        return Err(HttpError::AuthorizationFailed(
+            "Profiling is disabled in the settings".to_string(),
+        ));
+    }
+    if request.seconds == 0 || request.seconds > config.max_seconds {
+        return Err(bad_request(format!(
+            "Profiles sample for 1 to {} seconds",
+            config.max_seconds
+        )));
+    }
+    if request.kind == ProfileKind::Cpu && ProfileStore::cpu_busy() {
+        return Err(HttpError::Conflict(
+            "A CPU profile is already being captured".to_string(),
+        ));
+    }
+    let job = store
+        .start(request.kind, request.seconds, &identity.id)
+        .await
+        .map_internal_error_with_context("Failed to start profile")?;
+
+    info!(
+        "Admin {} started {:?} profile {} for {}s",
+        identity.id, job.kind, job.id, job.seconds
+    );
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+/// Captured and running profiles, newest first (admin only)
+#[instrument(name = "list_profiles", skip(app_state))]
+pub async fn list_profiles(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+) -> Result<Json<ProfileListResponse>, HttpError> {
+    let store = profile_store(&app_state, &identity, Action::Read).await?;
+    let profiles = store.list().await.map_internal_error()?;
+    Ok(Json(ProfileListResponse { profiles }))
+}
+
+/// One profile job (admin only)
+#[instrument(name = "get_profile", skip(app_state))]
+pub async fn get_profile(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(profile_id): Path<String>,
+) -> Result<Json<ProfileJob>, HttpError> {
+    let store = profile_store(&app_state, &identity, Action::Read).await?;
+    Ok(Json(find_profile(&store, &profile_id).await?))
+}
+
+/// Download a completed profile (admin only)
+#[instrument(name = "download_profile", skip(app_state))]
+pub async fn download_profile(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(profile_id): Path<String>,
+) -> Result<Response, HttpError> {
+    let store = profile_store(&app_state, &identity, Action::Read).await?;
+    let job = find_profile(&store, &profile_id).await?;
+    if job.status != ProfileStatus::Complete {
+        return Err(bad_request(format!(
+            "Profile {} is {:?}, not complete",
+            job.id, job.status
+        )));
+    }
+    let data = store
+        .read_profile(&job)
+        .await
+        .map_internal_error_with_context("Failed to read profile")?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, job.kind.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", job.file_name()),
+            ),
+        ],
+        data,
+    )
+        .into_response())
+}
+
+/// Add profiling routes
+pub fn add_routes(
+    router: Router<gate_http::AppState<crate::State>>,
+) -> Router<gate_http::AppState<crate::State>> {
+    router
+        .route(
+            "/api/admin/profiles",
+            get(list_profiles).post(start_profile),
+        )
+        .route("/api/admin/profiles/{profile_id}", get(get_profile))
+        .route(
+            "/api/admin/profiles/{profile_id}/download",
+            get(download_profile),
+        )
+}
@@ -0,0 +1,294 @@
+//! Prompt library routes
+//!
+//! Templates are versioned: saving a prompt never replaces an existing
+//! version, so requests pinned to a `prompt_version` keep rendering the same
+//! messages. Any signed-in user can read prompts; changing them is admin only.
+
+use crate::helpers::{
+    admin::AdminPermissionHelper,
+    errors::{ErrorMapExt, bad_request, not_found},
+};
+use axum::{
+    Router,
+    extract::{Path, Query, State},
+    response::Json,
+    routing::get,
+};
+use chrono::Utc;
+use gate_core::access::{Action, ObjectId, ObjectIdentity, ObjectKind, TargetNamespace};
+use gate_core::{PromptMessage, PromptRender, PromptTemplate, PromptVariable, StateBackend};
+use gate_http::{AppState, error::HttpError, services::HttpIdentity};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value as JsonValue};
+use std::sync::Arc;
+
+fn default_render_limit() -> usize {
+    100
+}
+
+/// Contents of a new prompt version
+#[derive(Debug, Deserialize)]
+pub struct PromptVersionRequest {
+    #[serde(default)]
+    pub description: Option<String>,
+    pub messages: Vec<PromptMessage>,
+    #[serde(default)]
+    pub variables: Vec<PromptVariable>,
+    #[serde(default)]
+    pub parameters: Map<String, JsonValue>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreatePromptRequest {
+    pub name: String,
+    #[serde(flatten)]
+    pub version: PromptVersionRequest,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PromptQuery {
+    pub version: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RenderQuery {
+    #[serde(default = "default_render_limit")]
+    pub limit: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PromptListResponse {
+    pub prompts: Vec<PromptTemplate>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PromptRenderListResponse {
+    pub renders: Vec<PromptRender>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeletePromptResponse {
+    pub name: String,
+    pub deleted: bool,
+}
+
+async fn state_backend(
+    app_state: &AppState<crate::State>,
+) -> Result<Arc<dyn StateBackend>, HttpError> {
+    app_state
+        .data
+        .daemon
+        .get_state_backend()
+        .await
+        .map_internal_error()
+}
+
+/// Check admin access to the prompt library
+async fn require_prompt_admin(
+    app_state: &AppState<crate::State>,
+    identity: &HttpIdentity,
+    action: Action,
+) -> Result<(), HttpError> {
+    AdminPermissionHelper::new(&app_state.data.daemon, identity.clone())
+        .await?
+        .require_admin(
+            action,
+            &ObjectIdentity {
+                namespace: TargetNamespace::System,
+                kind: ObjectKind::System,
+                id: ObjectId::new("prompts"),
+            },
+        )
+        .await
+}
+
+/// Validate and store the next version of `name`
+async fn save_version(
+    backend: &dyn StateBackend,
+    identity: &HttpIdentity,
+    name: &str,
+    request: PromptVersionRequest,
+) -> Result<PromptTemplate, HttpError> {
+    if name.trim().is_empty() || name.contains('/') {
+        return Err(bad_request(
+            "Prompt name must be non-empty and contain no '/'",
+        ));
+    }
+    if request.messages.is_empty() {
+        return Err(bad_request("Prompt needs at least one message"));
+    }
+    let version = backend
+        .list_prompt_versions(name)
+        .await
+        .map_internal_error()?
+        .last()
+        .map_or(1, |latest| latest.version + 1);
+
+    let mut prompt = PromptTemplate {
+        name: name.to_string(),
+        version,
+        description: request.description,
+        messages: request.messages,
+        variables: request.variables,
+        parameters: request.parameters,
+        created_by: identity.id.clone(),
+        created_at: Utc::now(),
+    };
+    // Placeholders nobody declared become required variables
+    for placeholder in prompt.placeholders() {
+        if !prompt.variables.iter().any(|v| v.name == placeholder) {
+            prompt.variables.push(PromptVariable {
+                name: placeholder,
+                default: None,
+            });
+        }
+    }
+
+    backend
+        .save_prompt(&prompt)
+        .await
+        .map_internal_error_with_context("Failed to save prompt")?;
+    info!(
+        "User {} saved prompt {} version {}",
+        identity.id, prompt.name, prompt.version
+    );
+    Ok(prompt)
+}
+
+/// List the latest version of every prompt
+#[instrument(name = "list_prompts", skip(app_state))]
+pub async fn list_prompts(
+    _identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+) -> Result<Json<PromptListResponse>, HttpError> {
+    let prompts = state_backend(&app_state)
+        .await?
+        .list_prompts()
+        .await
+        .map_internal_error()?;
+    Ok(Json(PromptListResponse { prompts }))
+}
+
+/// Create a prompt, or a new version of an existing one (admin only)
+#[instrument(name = "create_prompt", skip(app_state, request))]
+pub async fn create_prompt(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Json(request): Json<CreatePromptRequest>,
+) -> Result<Json<PromptTemplate>, HttpError> {
+    require_prompt_admin(&app_state, &identity, Action::Write).await?;
+    let backend = state_backend(&app_state).await?;
+    let prompt = save_version(
+        backend.as_ref(),
+        &identity,
+        request.name.trim(),
+        request.version,
+    )
+    .await?;
+    Ok(Json(prompt))
+}
+
+/// Get the latest version of a prompt, or the one given by `?version=`
+#[instrument(name = "get_prompt", skip(app_state))]
+pub async fn get_prompt(
+    _identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(name): Path<String>,
+    Query(query): Query<PromptQuery>,
+) -> Result<Json<PromptTemplate>, HttpError> {
+    let prompt = state_backend(&app_state)
+        .await?
+        .get_prompt(&name, query.version)
+        .await
+        .map_internal_error()?
+        .ok_or_else(|| not_found("Prompt", &name))?;
+    Ok(Json(prompt))
+}
+
+/// Save a new version of a prompt (admin only)
+#[instrument(name = "update_prompt", skip(app_state, request))]
+pub async fn update_prompt(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(name): Path<String>,
+    Json(request): Json<PromptVersionRequest>,
+) -> Result<Json<PromptTemplate>, HttpError> {
+    require_prompt_admin(&app_state, &identity, Action::Write).await?;
+    let backend = state_backend(&app_state).await?;
+    let prompt = save_version(backend.as_ref(), &identity, &name, request).await?;
+    Ok(Json(prompt))
+}
+
+/// Delete every version of a prompt (admin only)
+#[instrument(name = "delete_prompt", skip(app_state))]
+pub async fn delete_prompt(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(name): Path<String>,
+) -> Result<Json<DeletePromptResponse>, HttpError> {
+    require_prompt_admin(&app_state, &identity, Action::Delete).await?;
+    let backend = state_backend(&app_state).await?;
+    if backend
+        .get_prompt(&name, None)
+        .await
+        .map_internal_error()?
+        .is_none()
+    {
+        return Err(not_found("Prompt", &name));
+    }
+    backend.delete_prompt(&name).await.map_internal_error()?;
+    info!("Admin {} deleted prompt {}", identity.id, name);
+    Ok(Json(DeletePromptResponse {
+        name,
+        deleted: true,
+    }))
+}
+
+/// List every version of a prompt, oldest first
+#[instrument(name = "list_prompt_versions", skip(app_state))]
+pub async fn list_prompt_versions(
+    _identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(name): Path<String>,
+) -> Result<Json<PromptListResponse>, HttpError> {
+    let prompts = state_backend(&app_state)
+        .await?
+        .list_prompt_versions(&name)
+        .await
+        .map_internal_error()?;
+    if prompts.is_empty() {
+        return Err(not_found("Prompt", &name));
+    }
+    Ok(Json(PromptListResponse { prompts }))
+}
+
+/// Recent requests rendered from a prompt and the version each used (admin only)
+#[instrument(name = "list_prompt_renders", skip(app_state))]
+pub async fn list_prompt_renders(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(name): Path<String>,
+    Query(query): Query<RenderQuery>,
+) -> Result<Json<PromptRenderListResponse>, HttpError> {
+    require_prompt_admin(&app_state, &identity, Action::Read).await?;
+    let renders = state_backend(&app_state)
+        .await?
+        .list_prompt_renders(&name, query.limit)
+        .await
+        .map_internal_error()?;
+    Ok(Json(PromptRenderListResponse { renders }))
+}
+
+/// Add prompt library routes
+pub fn add_routes(
+    router: Router<gate_http::AppState<crate::State>>,
+) -> Router<gate_http::AppState<crate::State>> {
+    router
+        .route("/api/prompts", get(list_prompts).post(create_prompt))
+        .route(
+            "/api/prompts/{name}",
+            get(get_prompt).put(update_prompt).delete(delete_prompt),
+        )
+        .route("/api/prompts/{name}/versions", get(list_prompt_versions))
+        .route("/api/prompts/{name}/renders", get(list_prompt_renders))
+}
@@ -0,0 +1,75 @@
+//! Provenance verification routes
+//!
+//! Anyone holding a response's provenance block can ask this gateway whether
+//! it verifies, without authentication, much as the node's attestation is
+//! served. The block is posted under `provenance`, as a response carries it;
+//! a stream's `provenance` event holds the block itself. With the original request included, the
+//! gateway also checks it is the one the block was issued for.
+
+use crate::helpers::errors::ErrorMapExt;
+use crate::services::signing::verify_signature;
+use axum::{Router, extract::State, response::Json, routing::post};
+use gate_core::router::middleware::Provenance;
+use gate_http::{AppState, error::HttpError};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+#[derive(Debug, Deserialize)]
+pub struct VerifyRequest {
+    pub provenance: Provenance,
+    /// Request body the response answered, as routed: after parameter
+    /// profiles and the automatic model have rewritten it
+    #[serde(default)]
+    pub request: Option<JsonValue>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VerifyResponse {
+    /// Whether the signature verifies against the block's key
+    pub valid: bool,
+    /// Whether the block was signed with this gateway's key
+    pub issued_here: bool,
+    /// Whether the request matches the block's hash, when one was sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_matches: Option<bool>,
+    /// Why the signature does not verify
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub provenance: Provenance,
+}
+
+/// Check a provenance block's signature and, optionally, its request
+#[instrument(name = "verify_provenance", skip(app_state, request))]
+pub async fn verify_provenance(
+    State(app_state): State<AppState<crate::State>>,
+    Json(request): Json<VerifyRequest>,
+) -> Result<Json<VerifyResponse>, HttpError> {
+    let signer = app_state
+        .data
+        .daemon
+        .get_signer()
+        .await
+        .map_internal_error()?;
+    let provenance = request.provenance;
+    let verified = verify_signature(
+        &provenance.key_id,
+        &provenance.signed_bytes(),
+        &provenance.signature,
+    );
+    Ok(Json(VerifyResponse {
+        valid: verified.is_ok(),
+        issued_here: provenance.key_id.eq_ignore_ascii_case(signer.key_id()),
+        request_matches: request
+            .request
+            .map(|body| Provenance::request_hash(&body) == provenance.request_hash),
+        error: verified.err().map(|e| e.to_string()),
+        provenance,
+    }))
+}
+
+/// Add provenance routes
+pub fn add_routes(
+    router: Router<gate_http::AppState<crate::State>>,
+) -> Router<gate_http::AppState<crate::State>> {
+    router.route("/api/provenance/verify", post(verify_provenance))
+}
@@ -0,0 +1,282 @@
+//! Provider (sink) health and control routes
+
+use crate::helpers::admin::AdminPermissionHelper;
+use crate::helpers::errors::{ErrorMapExt, not_found};
+use axum::{
+    Router,
+    extract::State,
+    http::HeaderMap,
+    response::{Json, Response},
+    routing::{get, post, put},
+};
+use chrono::{DateTime, Utc};
+use gate_core::access::{Action, ObjectIdentity};
+use gate_core::router::{CircuitState, HealthSample, SinkHealth, SinkIndex};
+use gate_http::services::response_cache::HEALTH_KEY_PREFIX;
+use gate_http::{
+    AppState,
+    error::HttpError,
+    services::{HttpIdentity, cached_json},
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Cache key of the provider list, dropped on every health event
+const PROVIDERS_KEY: &str = "health:providers";
+
+const PROVIDER_SCHEME: &str = "provider://";
+const SELF_SCHEME: &str = "self://";
+/// Sinks that take their credentials from client requests rather than config
+const CAPTURE_SINK_IDS: [&str; 3] = [
+    "provider://anthropic/fallback",
+    "provider://openai/fallback",
+    "provider://openai/codex",
+];
+
+/// How a provider gets its upstream credentials
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyStatus {
+    /// An API key is set in the provider config
+    Configured,
+    /// The provider is configured without an API key
+    Missing,
+    /// Keys are forwarded from client requests until one is captured
+    AwaitingCapture,
+    /// Local sinks need no key
+    NotRequired,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProviderStatus {
+    pub id: String,
+    /// Name of the matching entry in the provider config, if any
+    pub config_name: Option<String>,
+    pub healthy: bool,
+    pub latency_ms: Option<u64>,
+    pub error_rate: f32,
+    pub last_error: Option<String>,
+    pub last_check: DateTime<Utc>,
+    pub circuit: CircuitState,
+    pub key_status: KeyStatus,
+    pub disabled: bool,
+    pub history: Vec<HealthSample>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProviderListResponse {
+    pub providers: Vec<ProviderStatus>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProbeProviderRequest {
+    pub id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetProviderEnabledRequest {
+    pub id: String,
+    pub enabled: bool,
+}
+
+/// Config entry name for a `provider://{type}/{name}` sink id
+pub(crate) fn config_name(sink_id: &str) -> Option<&str> {
+    sink_id
+        .strip_prefix(PROVIDER_SCHEME)?
+        .split_once('/')
+        .map(|(_, name)| name)
+        .filter(|_| !CAPTURE_SINK_IDS.contains(&sink_id))
+}
+
+fn key_status(sink_id: &str, settings: &crate::Settings) -> KeyStatus {
+    if sink_id.starts_with(SELF_SCHEME) {
+        return KeyStatus::NotRequired;
+    }
+    if CAPTURE_SINK_IDS.contains(&sink_id) {
+        return KeyStatus::AwaitingCapture;
+    }
+    let has_key = config_name(sink_id)
+        .and_then(|name| settings.providers.iter().find(|p| p.name == name))
+        .is_some_and(|p| p.api_key.as_deref().is_some_and(|key| !key.is_empty()));
+    if has_key {
+        KeyStatus::Configured
+    } else {
+        KeyStatus::Missing
+    }
+}
+
+async fn provider_status(
+    index: &SinkIndex,
+    settings: &crate::Settings,
+    id: String,
+    health: SinkHealth,
+) -> ProviderStatus {
+    ProviderStatus {
+        config_name: config_name(&id).map(str::to_string),
+        circuit: health.circuit_state(),
+        key_status: key_status(&id, settings),
+        disabled: index.is_disabled(&id).await,
+        history: index.history(&id).await,
+        healthy: health.healthy,
+        latency_ms: health.latency_ms,
+        error_rate: health.error_rate,
+        last_error: health.last_error,
+        last_check: health.last_check,
+        id,
+    }
+}
+
+async fn require_provider_admin(
+    app_state: &AppState<crate::State>,
+    identity: &HttpIdentity,
+    action: Action,
+    provider_id: &str,
+) -> Result<(), HttpError> {
+    AdminPermissionHelper::new(&app_state.data.daemon, identity.clone())
+        .await?
+        .require_admin(action, &ObjectIdentity::local_provider(provider_id))
+        .await
+}
+
+fn router_index(
+    app_state: &AppState<crate::State>,
+) -> Result<(Arc<gate_core::router::Router>, Arc<SinkIndex>), HttpError> {
+    let router = app_state
+        .router
+        .clone()
+        .ok_or_else(|| HttpError::ServiceUnavailable("Router not initialized".into()))?;
+    let index = router
+        .sink_index()
+        .cloned()
+        .ok_or_else(|| HttpError::ServiceUnavailable("Sink index not configured".into()))?;
+    Ok((router, index))
+}
+
+/// List registered providers with their current health (admin only)
+#[instrument(name = "list_providers", skip(app_state, headers))]
+pub async fn list_providers(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    headers: HeaderMap,
+) -> Result<Response, HttpError> {
+    require_provider_admin(&app_state, &identity, Action::Read, "*").await?;
+
+    cached_json(
+        app_state.response_cache.as_deref(),
+        PROVIDERS_KEY,
+        &headers,
+        || provider_list(&app_state),
+    )
+    .await
+}
+
+async fn provider_list(
+    app_state: &AppState<crate::State>,
+) -> Result<ProviderListResponse, HttpError> {
+    let (router, index) = router_index(app_state)?;
+    let settings = app_state
+        .data
+        .daemon
+        .get_settings()
+        .await
+        .map_internal_error()?;
+
+    let mut providers = Vec::new();
+    let mut ids = router.sink_registry().list_ids().await;
+    ids.sort();
+    for id in ids {
+        let Some(snapshot) = index.get(&id).await else {
+            continue;
+        };
+        providers.push(provider_status(&index, &settings, id, snapshot.health).await);
+    }
+
+    Ok(ProviderListResponse { providers })
+}
+
+/// Actively check a provider and return its updated status (admin only)
+#[instrument(name = "probe_provider", skip(app_state))]
+pub async fn probe_provider(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Json(request): Json<ProbeProviderRequest>,
+) -> Result<Json<ProviderStatus>, HttpError> {
+    require_provider_admin(&app_state, &identity, Action::Execute, &request.id).await?;
+
+    let (router, index) = router_index(&app_state)?;
+    let sink = router
+        .sink_registry()
+        .get(&request.id)
+        .await
+        .ok_or_else(|| not_found("Provider", &request.id))?;
+    let settings = app_state
+        .data
+        .daemon
+        .get_settings()
+        .await
+        .map_internal_error()?;
+
+    let health = sink.check().await;
+    router
+        .sink_registry()
+        .publish_health(request.id.clone(), health.clone());
+    info!(
+        "Admin {} probed provider {}: healthy={}",
+        identity.id, request.id, health.healthy
+    );
+
+    Ok(Json(
+        provider_status(&index, &settings, request.id, health).await,
+    ))
+}
+
+/// Enable or disable routing to a provider (admin only)
+#[instrument(name = "set_provider_enabled", skip(app_state))]
+pub async fn set_provider_enabled(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Json(request): Json<SetProviderEnabledRequest>,
+) -> Result<Json<ProviderStatus>, HttpError> {
+    require_provider_admin(&app_state, &identity, Action::Manage, &request.id).await?;
+
+    let (_, index) = router_index(&app_state)?;
+    let snapshot = index
+        .get(&request.id)
+        .await
+        .ok_or_else(|| not_found("Provider", &request.id))?;
+    let settings = app_state
+        .data
+        .daemon
+        .get_settings()
+        .await
+        .map_internal_error()?;
+
+    index.set_disabled(&request.id, !request.enabled).await;
+    if let Some(cache) = &app_state.response_cache {
+        cache.invalidate_prefix(HEALTH_KEY_PREFIX);
+    }
+    info!(
+        "Admin {} {} provider {}",
+        identity.id,
+        if request.enabled {
+            "enabled"
+        } else {
+            "disabled"
+        },
+        request.id
+    );
+
+    Ok(Json(
+        provider_status(&index, &settings, request.id, snapshot.health).await,
+    ))
+}
+
+/// Add provider routes
+pub fn add_routes(
+    router: Router<gate_http::AppState<crate::State>>,
+) -> Router<gate_http::AppState<crate::State>> {
+    router
+        .route("/api/admin/providers", get(list_providers))
+        .route("/api/admin/providers/probe", post(probe_provider))
+        .route("/api/admin/providers/enabled", put(set_provider_enabled))
+}
@@ -0,0 +1,52 @@
+//! Daemon status routes
+//!
+//! Lets the UI tell signed-in users when the daemon started in safe mode
+//! and why, so the configuration can be fixed before restarting.
+
+use crate::helpers::errors::ErrorMapExt;
+use crate::safe_mode::SafeMode;
+use axum::{Router, extract::State, http::HeaderMap, response::Response, routing::get};
+use gate_http::{
+    AppState,
+    error::HttpError,
+    services::{HttpIdentity, cached_json},
+};
+use serde::Serialize;
+
+/// Cache key of the status response
+const STATUS_KEY: &str = "status";
+
+#[derive(Debug, Serialize)]
+pub struct StatusResponse {
+    /// Set when the daemon started in safe mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub safe_mode: Option<SafeMode>,
+}
+
+/// Whether the daemon is running normally
+#[instrument(name = "get_status", skip(app_state, headers))]
+pub async fn get_status(
+    _identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    headers: HeaderMap,
+) -> Result<Response, HttpError> {
+    cached_json(
+        app_state.response_cache.as_deref(),
+        STATUS_KEY,
+        &headers,
+        || async {
+            let status = app_state.data.daemon.status().await.map_internal_error()?;
+            Ok(StatusResponse {
+                safe_mode: status.safe_mode,
+            })
+        },
+    )
+    .await
+}
+
+/// Add daemon status routes
+pub fn add_routes(
+    router: Router<gate_http::AppState<crate::State>>,
+) -> Router<gate_http::AppState<crate::State>> {
+    router.route("/api/status", get(get_status))
+}
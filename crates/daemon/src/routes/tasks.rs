@@ -0,0 +1,113 @@
+//! Scheduled task routes
+//!
+//! Lists the daemon's scheduled tasks with their last and next runs, and
+//! lets admins run a task immediately.
+
+use crate::helpers::{
+    admin::AdminPermissionHelper,
+    errors::{ErrorMapExt, not_found},
+};
+use crate::services::Scheduler;
+use crate::services::scheduler::TaskStatus;
+use axum::{
+    Router,
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    routing::{get, post},
+};
+use gate_core::access::{Action, ObjectId, ObjectIdentity, ObjectKind, TargetNamespace};
+use gate_http::{AppState, error::HttpError, services::HttpIdentity};
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct TaskListResponse {
+    pub tasks: Vec<TaskStatus>,
+}
+
+async fn scheduler(
+    app_state: &AppState<crate::State>,
+    identity: &HttpIdentity,
+    action: Action,
+) -> Result<Scheduler, HttpError> {
+    AdminPermissionHelper::new(&app_state.data.daemon, identity.clone())
+        .await?
+        .require_admin(
+            action,
+            &ObjectIdentity {
+                namespace: TargetNamespace::System,
+                kind: ObjectKind::System,
+                id: ObjectId::new("tasks"),
+            },
+        )
+        .await?;
+    app_state
+        .data
+        .daemon
+        .get_scheduler()
+        .await
+        .map_internal_error()
+}
+
+/// Scheduled tasks with their last and next runs (admin only)
+#[instrument(name = "list_tasks", skip(app_state))]
+pub async fn list_tasks(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+) -> Result<Json<TaskListResponse>, HttpError> {
+    let scheduler = scheduler(&app_state, &identity, Action::Read).await?;
+    Ok(Json(TaskListResponse {
+        tasks: scheduler.list().await,
+    }))
+}
+
+/// One scheduled task (admin only)
+#[instrument(name = "get_task", skip(app_state))]
+pub async fn get_task(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(name): Path<String>,
+) -> Result<Json<TaskStatus>, HttpError> {
+    let scheduler = scheduler(&app_state, &identity, Action::Read).await?;
+    let task = scheduler
+        .get(&name)
+        .await
+        .ok_or_else(|| not_found("Task", &name))?;
+    Ok(Json(task))
+}
+
+/// Run a task now, outside its schedule (admin only)
+#[instrument(name = "run_task", skip(app_state))]
+pub async fn run_task(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Path(name): Path<String>,
+) -> Result<(StatusCode, Json<TaskStatus>), HttpError> {
+    let scheduler = scheduler(&app_state, &identity, Action::Execute).await?;
+    let task = scheduler
+        .get(&name)
+        .await
+        .ok_or_else(|| not_found("Task", &name))?;
+    if task.running {
+        return Err(HttpError::Conflict(format!(
+            "Task {name} is already running"
+        )));
+    }
+    let task = scheduler
+        .trigger(&name)
+        .await
+        .map_internal_error_with_context("Failed to run task")?;
+
+    info!("Admin {} triggered task {}", identity.id, name);
+    Ok((StatusCode::ACCEPTED, Json(task)))
+}
+
+/// Add scheduled task routes
+pub fn add_routes(
+    router: Router<gate_http::AppState<crate::State>>,
+) -> Router<gate_http::AppState<crate::State>> {
+    router
+        .route("/api/admin/tasks", get(list_tasks))
+        .route("/api/admin/tasks/{name}", get(get_task))
+        .route("/api/admin/tasks/{name}/run", post(run_task))
+}
@@ -0,0 +1,132 @@
+//! TLS certificate status and renewal routes
+
+use crate::helpers::errors::{ErrorMapExt, bad_request};
+use crate::routes::guard::require_system_admin;
+use crate::services::certificates::CertificateFiles;
+use crate::services::tls::request_certificate;
+use crate::services::tls_status::{
+    CertificateStatus, MANUAL_CERTIFICATE, certificate_status, source_name,
+};
+use crate::state_dir::StateDir;
+use axum::{
+    Router,
+    extract::State,
+    response::Json,
+    routing::{get, post},
+};
+use chrono::Utc;
+use gate_core::access::Action;
+use gate_http::{AppState, error::HttpError, services::HttpIdentity};
+use gate_tlsforward::CertificateManager;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize)]
+pub struct TlsStatusResponse {
+    /// `acme`, `files` or `directory`
+    pub source: String,
+    pub certificates: Vec<CertificateStatus>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RenewRequest {
+    /// Domain to renew; ignored for user-supplied certificates
+    #[serde(default)]
+    pub domain: Option<String>,
+}
+
+async fn status_response(
+    app_state: &AppState<crate::State>,
+) -> Result<TlsStatusResponse, HttpError> {
+    let daemon = &app_state.data.daemon;
+    let settings = daemon.get_settings().await.map_internal_error()?;
+    let renewals = daemon.get_renewal_log().await.map_internal_error()?;
+    let cert_dir = StateDir::new()
+        .await
+        .map_internal_error()?
+        .dir_for("certificates");
+    Ok(TlsStatusResponse {
+        source: source_name(&settings.certificates.source).to_string(),
+        certificates: certificate_status(&settings, &cert_dir, &renewals, Utc::now()).await,
+    })
+}
+
+/// Certificate served for each domain, with its last renewal (admin only)
+#[instrument(name = "tls_status", skip(app_state))]
+pub async fn tls_status(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+) -> Result<Json<TlsStatusResponse>, HttpError> {
+    require_system_admin(&app_state, &identity, Action::Read, "tls").await?;
+    Ok(Json(status_response(&app_state).await?))
+}
+
+/// Renew a certificate now: request it again through ACME, or reload
+/// user-supplied files (admin only)
+#[instrument(name = "tls_renew", skip(app_state))]
+pub async fn renew_certificate(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Json(request): Json<RenewRequest>,
+) -> Result<Json<TlsStatusResponse>, HttpError> {
+    require_system_admin(&app_state, &identity, Action::Execute, "tls").await?;
+    let daemon = &app_state.data.daemon;
+    let settings = daemon.get_settings().await.map_internal_error()?;
+    let renewals = daemon.get_renewal_log().await.map_internal_error()?;
+
+    let files = CertificateFiles::resolve(&settings.certificates.source)
+        .map_err(|e| bad_request(e.to_string()))?;
+    let (name, result) = if let Some(files) = files {
+        let tls = daemon
+            .get_tls_manager()
+            .await
+            .map_internal_error()?
+            .ok_or_else(|| {
+                HttpError::ServiceUnavailable(
+                    "Restart the daemon to serve user-supplied certificates".to_string(),
+                )
+            })?;
+        let result = tls.reload_files(&files).await.map(|_| ());
+        (MANUAL_CERTIFICATE.to_string(), result)
+    } else {
+        let domain = request
+            .domain
+            .ok_or_else(|| bad_request("Name the domain to renew"))?;
+        if !settings.letsencrypt.domains.contains(&domain) {
+            return Err(bad_request(format!(
+                "{domain} is not in letsencrypt.domains"
+            )));
+        }
+        let email = settings
+            .letsencrypt
+            .email
+            .clone()
+            .ok_or_else(|| bad_request("Set letsencrypt.email to request certificates"))?;
+        let data_dir = StateDir::new().await.map_internal_error()?.data_dir();
+        let cert_mgr = CertificateManager::new(data_dir);
+        let result =
+            request_certificate(&cert_mgr, &domain, &email, &settings.letsencrypt.dns).await;
+        (domain, result)
+    };
+
+    renewals.record(&name, &result).await;
+    if let Err(e) = result {
+        warn!(
+            "Admin {} failed to renew certificate {}: {:#}",
+            identity.id, name, e
+        );
+        return Err(HttpError::ServiceUnavailable(format!(
+            "Renewing {name} failed: {e:#}"
+        )));
+    }
+    info!("Admin {} renewed certificate {}", identity.id, name);
+    Ok(Json(status_response(&app_state).await?))
+}
+
+/// Add TLS routes
+pub fn add_routes(
+    router: Router<gate_http::AppState<crate::State>>,
+) -> Router<gate_http::AppState<crate::State>> {
+    router
+        .route("/api/admin/tls", get(tls_status))
+        .route("/api/admin/tls/renew", post(renew_certificate))
+}
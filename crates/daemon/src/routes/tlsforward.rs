@@ -0,0 +1,95 @@
+//! Relay link diagnostics and access log routes
+
+use crate::helpers::errors::ErrorMapExt;
+use crate::routes::guard::require_system_admin;
+use crate::services::access_log::AccessSummary;
+use crate::services::relay_diagnostics::{LinkEvent, RelayDiagnostics, ServerDiagnostics};
+use crate::types::TlsForwardStatus;
+use axum::{
+    Router,
+    extract::{Query, State},
+    response::Json,
+    routing::get,
+};
+use chrono::Utc;
+use gate_core::access::Action;
+use gate_http::{AppState, error::HttpError, services::HttpIdentity};
+use gate_tlsforward::client::{ForwardedConnection, TrafficSnapshot};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize)]
+pub struct TlsForwardDiagnostics {
+    pub enabled: bool,
+    pub status: TlsForwardStatus,
+    /// Round trip time and last error per TLS forward server
+    pub servers: Vec<ServerDiagnostics>,
+    /// Connect and disconnect history, newest first
+    pub events: Vec<LinkEvent>,
+    /// Forwarded connections that are open
+    pub connections: Vec<ForwardedConnection>,
+    pub traffic: TrafficSnapshot,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DiagnosticsQuery {
+    /// Ping every TLS forward server before answering
+    #[serde(default)]
+    pub probe: bool,
+}
+
+/// Relay link diagnostics (admin only)
+#[instrument(name = "tlsforward_diagnostics", skip(app_state))]
+pub async fn diagnostics(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Query(query): Query<DiagnosticsQuery>,
+) -> Result<Json<TlsForwardDiagnostics>, HttpError> {
+    require_system_admin(&app_state, &identity, Action::Read, "tlsforward").await?;
+    let daemon = &app_state.data.daemon;
+
+    let link = match daemon.get_tlsforward_service().await.map_internal_error()? {
+        Some(service) => {
+            if query.probe {
+                service.probe_servers().await;
+            }
+            service.diagnostics()
+        }
+        None => daemon
+            .get_tunnel_service()
+            .await
+            .map_internal_error()?
+            .map(|tunnel| tunnel.diagnostics())
+            .unwrap_or_else(RelayDiagnostics::default),
+    };
+    let status = daemon.status().await.map_internal_error()?;
+    let meter = daemon.get_traffic_meter().await.map_internal_error()?;
+
+    Ok(Json(TlsForwardDiagnostics {
+        enabled: status.tlsforward_enabled,
+        status: status.tlsforward_status,
+        servers: link.servers(),
+        events: link.events(),
+        connections: meter.connections(),
+        traffic: meter.snapshot(),
+    }))
+}
+
+/// Who has been calling through the relay: top addresses, failed
+/// authentications and bans in effect (admin only)
+#[instrument(name = "tlsforward_access", skip(app_state))]
+pub async fn access_summary(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+) -> Result<Json<AccessSummary>, HttpError> {
+    require_system_admin(&app_state, &identity, Action::Read, "tlsforward").await?;
+    Ok(Json(app_state.data.access_log.summary(Utc::now())))
+}
+
+/// Add relay diagnostics and access log routes
+pub fn add_routes(
+    router: Router<gate_http::AppState<crate::State>>,
+) -> Router<gate_http::AppState<crate::State>> {
+    router
+        .route("/api/admin/tlsforward/diagnostics", get(diagnostics))
+        .route("/api/admin/tlsforward/access", get(access_summary))
+}
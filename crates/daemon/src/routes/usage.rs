@@ -0,0 +1,341 @@
+//! Usage and spend routes (admin only)
+//!
+//! Lists recorded usage across users and organizations, reports the
+//! month-end spend forecasts and anomalies the background spend monitor
+//! alerts on, finds the clients still requesting deprecated models, and
+//! reports prompt sizes and the clients running into context limits.
+//! Alert links point at the usage listing.
+
+use crate::helpers::{admin::AdminPermissionHelper, errors::ErrorMapExt};
+use crate::services::prompt_sizes::{PromptSizeReport, prompt_size_report};
+use crate::services::spend::{
+    SpendAlert, SpendForecast, detect_anomalies, forecast_spend, history_start,
+};
+use axum::{
+    Router,
+    extract::{Query, State},
+    response::Json,
+    routing::get,
+};
+use chrono::{DateTime, Duration, Utc};
+use gate_core::access::{Action, ObjectId, ObjectIdentity, ObjectKind, TargetNamespace};
+use gate_core::router::middleware::{
+    DEPRECATED_MODEL, ModelDeprecation, USAGE_ESTIMATED, USAGE_SOURCE,
+};
+use gate_core::{StateBackend, TimeRange, UsageRecord};
+use gate_http::{AppState, error::HttpError, services::HttpIdentity};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+const MAX_LIST_LIMIT: usize = 1000;
+
+fn default_limit() -> usize {
+    100
+}
+
+fn default_report_limit() -> usize {
+    20
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UsageQuery {
+    #[serde(default)]
+    pub user_id: Option<String>,
+    #[serde(default)]
+    pub org_id: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Defaults to a day before `until`
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+    /// Defaults to now
+    #[serde(default)]
+    pub until: Option<DateTime<Utc>>,
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UsageListResponse {
+    pub records: Vec<UsageRecord>,
+    pub total_tokens: u64,
+    pub total_cost: f64,
+    /// Matching records whose token counts were estimated, because the
+    /// stream was cancelled or the provider did not report usage
+    pub estimated_records: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeprecationQuery {
+    /// Defaults to 30 days ago
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+}
+
+/// A client that requested a deprecated model
+#[derive(Debug, Serialize)]
+pub struct DeprecatedModelClient {
+    pub model: String,
+    pub user_id: String,
+    pub org_id: String,
+    pub api_key_hash: String,
+    pub requests: u64,
+    pub last_seen: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeprecationReport {
+    pub since: DateTime<Utc>,
+    /// Deprecation rules in effect
+    pub rules: Vec<ModelDeprecation>,
+    /// Clients still requesting deprecated models, most requests first
+    pub clients: Vec<DeprecatedModelClient>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PromptSizeQuery {
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Defaults to 7 days ago
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+    /// Entries listed in each part of the report
+    #[serde(default = "default_report_limit")]
+    pub limit: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SpendResponse {
+    pub forecasts: Vec<SpendForecast>,
+    pub anomalies: Vec<SpendAlert>,
+}
+
+/// Check admin access to usage and fetch the state backend
+async fn admin_usage(
+    app_state: &AppState<crate::State>,
+    identity: &HttpIdentity,
+) -> Result<Arc<dyn StateBackend>, HttpError> {
+    let daemon = &app_state.data.daemon;
+    AdminPermissionHelper::new(daemon, identity.clone())
+        .await?
+        .require_admin(
+            Action::Read,
+            &ObjectIdentity {
+                namespace: TargetNamespace::System,
+                kind: ObjectKind::System,
+                id: ObjectId::new("usage"),
+            },
+        )
+        .await?;
+    daemon.get_state_backend().await.map_internal_error()
+}
+
+/// Usage records, newest first. Totals cover every matching record, not
+/// just the listed ones.
+#[instrument(name = "list_usage", skip(app_state))]
+pub async fn list_usage(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Query(query): Query<UsageQuery>,
+) -> Result<Json<UsageListResponse>, HttpError> {
+    let backend = admin_usage(&app_state, &identity).await?;
+    let end = query.until.unwrap_or_else(Utc::now);
+    let range = TimeRange {
+        start: query.since.unwrap_or(end - Duration::days(1)),
+        end,
+    };
+    let records: Vec<UsageRecord> = backend
+        .list_usage(&range)
+        .await
+        .map_internal_error()?
+        .into_iter()
+        .filter(|r| query.user_id.as_ref().is_none_or(|id| &r.user_id == id))
+        .filter(|r| query.org_id.as_ref().is_none_or(|id| &r.org_id == id))
+        .filter(|r| query.model.as_ref().is_none_or(|m| &r.model_id == m))
+        .collect();
+
+    let total_tokens = records.iter().map(|r| r.total_tokens).sum();
+    let total_cost = records.iter().map(|r| r.cost).sum();
+    let estimated_records = records
+        .iter()
+        .filter(|r| r.metadata.get(USAGE_SOURCE).map(String::as_str) == Some(USAGE_ESTIMATED))
+        .count();
+    Ok(Json(UsageListResponse {
+        records: records
+            .into_iter()
+            .take(query.limit.min(MAX_LIST_LIMIT))
+            .collect(),
+        total_tokens,
+        total_cost,
+        estimated_records,
+    }))
+}
+
+/// Month-end spend forecasts and current anomalies
+#[instrument(name = "spend_report", skip(app_state))]
+pub async fn spend_report(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+) -> Result<Json<SpendResponse>, HttpError> {
+    let backend = admin_usage(&app_state, &identity).await?;
+    let config = app_state
+        .data
+        .daemon
+        .get_settings()
+        .await
+        .map_internal_error()?
+        .spend;
+    let now = Utc::now();
+    let range = TimeRange {
+        start: history_start(now, &config),
+        end: now,
+    };
+    let records = backend.list_usage(&range).await.map_internal_error()?;
+    Ok(Json(SpendResponse {
+        forecasts: forecast_spend(&records, now),
+        anomalies: detect_anomalies(&records, now, &config),
+    }))
+}
+
+/// Clients that requested deprecated models since `since`
+#[instrument(name = "deprecation_report", skip(app_state))]
+pub async fn deprecation_report(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Query(query): Query<DeprecationQuery>,
+) -> Result<Json<DeprecationReport>, HttpError> {
+    let backend = admin_usage(&app_state, &identity).await?;
+    let rules = app_state
+        .data
+        .daemon
+        .get_settings()
+        .await
+        .map_internal_error()?
+        .routing
+        .deprecation_rules();
+    let now = Utc::now();
+    let since = query.since.unwrap_or(now - Duration::days(30));
+    let range = TimeRange {
+        start: since,
+        end: now,
+    };
+    let records = backend.list_usage(&range).await.map_internal_error()?;
+    Ok(Json(DeprecationReport {
+        since,
+        rules,
+        clients: deprecated_model_clients(&records),
+    }))
+}
+
+/// Prompt sizes per model and user, the largest prompts and the clients
+/// hitting context limits since `since`
+#[instrument(name = "prompt_size_report", skip(app_state))]
+pub async fn prompt_sizes(
+    identity: HttpIdentity,
+    State(app_state): State<AppState<crate::State>>,
+    Query(query): Query<PromptSizeQuery>,
+) -> Result<Json<PromptSizeReport>, HttpError> {
+    let backend = admin_usage(&app_state, &identity).await?;
+    let end = Utc::now();
+    let range = TimeRange {
+        start: query.since.unwrap_or(end - Duration::days(7)),
+        end,
+    };
+    let records: Vec<UsageRecord> = backend
+        .list_usage(&range)
+        .await
+        .map_internal_error()?
+        .into_iter()
+        .filter(|r| query.model.as_ref().is_none_or(|m| &r.model_id == m))
+        .collect();
+    Ok(Json(prompt_size_report(
+        &records,
+        query.limit.min(MAX_LIST_LIMIT),
+    )))
+}
+
+fn deprecated_model_clients(records: &[UsageRecord]) -> Vec<DeprecatedModelClient> {
+    let mut clients: BTreeMap<(&str, &str, &str), DeprecatedModelClient> = BTreeMap::new();
+    for record in records {
+        let Some(model) = record.metadata.get(DEPRECATED_MODEL) else {
+            continue;
+        };
+        let client = clients
+            .entry((
+                model.as_str(),
+                record.user_id.as_str(),
+                record.api_key_hash.as_str(),
+            ))
+            .or_insert_with(|| DeprecatedModelClient {
+                model: model.clone(),
+                user_id: record.user_id.clone(),
+                org_id: record.org_id.clone(),
+                api_key_hash: record.api_key_hash.clone(),
+                requests: 0,
+                last_seen: record.timestamp,
+            });
+        client.requests += 1;
+        client.last_seen = client.last_seen.max(record.timestamp);
+    }
+    let mut clients: Vec<_> = clients.into_values().collect();
+    clients.sort_by(|a, b| b.requests.cmp(&a.requests));
+    clients
+}
+
+/// Add usage routes
+pub fn add_routes(
+    router: Router<gate_http::AppState<crate::State>>,
+) -> Router<gate_http::AppState<crate::State>> {
+    router
+        .route("/api/admin/usage", get(list_usage))
+        .route("/api/admin/spend", get(spend_report))
+        .route("/api/admin/usage/deprecated", get(deprecation_report))
+        .route("/api/admin/usage/prompts", get(prompt_sizes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn record(user: &str, deprecated: Option<&str>) -> UsageRecord {
+        UsageRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            org_id: "org".to_string(),
+            user_id: user.to_string(),
+            api_key_hash: String::new(),
+            request_id: uuid::Uuid::new_v4().to_string(),
+            provider_id: "anthropic".to_string(),
+            model_id: "claude-sonnet-4-20250514".to_string(),
+            input_tokens: 10,
+            output_tokens: 5,
+            total_tokens: 15,
+            cost: 0.0,
+            timestamp: Utc::now(),
+            metadata: deprecated
+                .map(|model| (DEPRECATED_MODEL.to_string(), model.to_string()))
+                .into_iter()
+                .collect::<HashMap<_, _>>(),
+        }
+    }
+
+    #[test]
+    fn test_clients_are_grouped_by_deprecated_model() {
+        let records = [
+            record("alice", Some("claude-2.1")),
+            record("bob", Some("claude-2.1")),
+            record("bob", Some("claude-2.1")),
+            record("bob", Some("gpt-4-32k")),
+            record("carol", None),
+        ];
+        let clients = deprecated_model_clients(&records);
+        assert_eq!(clients.len(), 3);
+        assert_eq!(
+            (clients[0].user_id.as_str(), clients[0].requests),
+            ("bob", 2)
+        );
+        assert_eq!(clients[0].model, "claude-2.1");
+        assert!(clients.iter().all(|c| c.user_id != "carol"));
+    }
+}
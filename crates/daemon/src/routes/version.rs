@@ -0,0 +1,33 @@
+//! Version routes
+//!
+//! Serves what the daemon was built from and the hash of the frontend it
+//! serves, without authentication, so the UI can notice it is out of date
+//! before signing in.
+
+use crate::helpers::errors::ErrorMapExt;
+use crate::services::build_info::{VERSION_PATH, VersionInfo};
+use axum::{Router, extract::State, response::Json, routing::get};
+use gate_http::{AppState, error::HttpError};
+
+/// Daemon version, git hash, build date and frontend bundle hash
+#[instrument(name = "get_version", skip(app_state))]
+pub async fn get_version(
+    State(app_state): State<AppState<crate::State>>,
+) -> Result<Json<VersionInfo>, HttpError> {
+    let identity = app_state
+        .data
+        .daemon
+        .get_node_identity()
+        .await
+        .map_internal_error()?;
+    Ok(Json(VersionInfo::current(
+        identity.frontend_hash().map(String::from),
+    )))
+}
+
+/// Add version routes
+pub fn add_routes(
+    router: Router<gate_http::AppState<crate::State>>,
+) -> Router<gate_http::AppState<crate::State>> {
+    router.route(VERSION_PATH, get(get_version))
+}
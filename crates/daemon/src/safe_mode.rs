@@ -0,0 +1,127 @@
+//! Crash-loop detection and safe mode
+//!
+//! Each start is recorded in a marker file that is cleared once the daemon
+//! has run for a while or shuts down cleanly. Several recorded starts in a
+//! short window mean the daemon keeps crashing, and the next start comes up
+//! in safe mode: providers, local inference and background jobs stay off
+//! and only the auth, config and status API is served, so the configuration
+//! can be fixed from the UI. Safe mode is also entered when the
+//! configuration cannot be loaded or the daemon cannot be built.
+
+use crate::StateDir;
+use crate::error::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Unclean starts in a row that count as a crash loop
+pub const CRASH_LOOP_STARTS: u32 = 3;
+
+/// Starts further apart than this are not part of the same crash loop
+const CRASH_LOOP_WINDOW_MINUTES: i64 = 10;
+
+/// How long the daemon has to run before its start counts as clean
+pub const STABLE_AFTER: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Why the daemon started in safe mode
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SafeMode {
+    pub reason: String,
+    pub since: DateTime<Utc>,
+}
+
+impl SafeMode {
+    pub fn new(reason: impl Into<String>) -> Self {
+        Self {
+            reason: reason.into(),
+            since: Utc::now(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StartupRecord {
+    unclean_starts: u32,
+    last_start: Option<DateTime<Utc>>,
+}
+
+/// Tracks starts that have not yet proven stable
+pub struct StartupTracker {
+    path: PathBuf,
+}
+
+impl StartupTracker {
+    pub fn new(state_dir: &StateDir) -> Self {
+        Self::at(state_dir.data_dir().join("startup.json"))
+    }
+
+    fn at(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Record a start, returning how many earlier starts in the crash loop
+    /// window never became stable
+    pub async fn record_start(&self) -> Result<u32> {
+        self.record_start_at(Utc::now()).await
+    }
+
+    async fn record_start_at(&self, now: DateTime<Utc>) -> Result<u32> {
+        let mut record = match tokio::fs::read(&self.path).await {
+            Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_default(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => StartupRecord::default(),
+            Err(e) => return Err(e.into()),
+        };
+        let in_window = record
+            .last_start
+            .is_some_and(|last| now - last < Duration::minutes(CRASH_LOOP_WINDOW_MINUTES));
+        let previous = if in_window { record.unclean_starts } else { 0 };
+
+        record.unclean_starts = previous + 1;
+        record.last_start = Some(now);
+        tokio::fs::write(&self.path, serde_json::to_vec(&record)?).await?;
+        Ok(previous)
+    }
+
+    /// Forget recorded starts once the daemon is known to be stable
+    pub async fn mark_stable(&self) -> Result<()> {
+        match tokio::fs::remove_file(&self.path).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Safe mode for a start after `previous` unclean starts, if it is a crash
+/// loop
+pub fn crash_loop(previous: u32) -> Option<SafeMode> {
+    (previous >= CRASH_LOOP_STARTS).then(|| {
+        SafeMode::new(format!(
+            "The daemon failed to keep running after {previous} recent starts"
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_repeated_unclean_starts_are_a_crash_loop() {
+        let dir = tempfile::tempdir().unwrap();
+        let tracker = StartupTracker::at(dir.path().join("startup.json"));
+        let now = Utc::now();
+
+        for expected in 0..CRASH_LOOP_STARTS {
+            let previous = tracker.record_start_at(now).await.unwrap();
+            assert_eq!(previous, expected);
+            assert!(crash_loop(previous).is_none());
+        }
+        assert!(crash_loop(tracker.record_start_at(now).await.unwrap()).is_some());
+
+        // Starts outside the window, or after a stable run, start over
+        let later = now + Duration::minutes(CRASH_LOOP_WINDOW_MINUTES + 1);
+        assert_eq!(tracker.record_start_at(later).await.unwrap(), 0);
+        tracker.mark_stable().await.unwrap();
+        assert_eq!(tracker.record_start_at(later).await.unwrap(), 0);
+    }
+}
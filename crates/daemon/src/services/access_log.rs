@@ -0,0 +1,319 @@
+//! Access log for publicly forwarded traffic
+//!
+//! Requests that reach the node through the relay carry the client's
+//! address. Each is logged with its path, status and user agent and kept in
+//! memory for a summary of who is calling. An address that fails to
+//! authenticate too often is banned for a while: the ban is an
+//! [`AuthLockout`] on the address, so its requests are refused before they
+//! reach authentication and an admin lifts it as they would any lockout.
+
+use crate::config::AccessLogConfig;
+use crate::services::lockout::{AuthLockout, LockoutSubject};
+use axum::{
+    extract::{Request, State},
+    http::{StatusCode, header},
+    middleware::Next,
+    response::Response,
+};
+use chrono::{DateTime, Duration, Utc};
+use gate_http::{AppState, server::ForwardedClient};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
+use std::sync::{Arc, RwLock};
+
+/// Addresses listed in the summary
+const TOP_ADDRESSES: usize = 10;
+
+/// Failed requests listed in the summary
+const RECENT_FAILURES: usize = 20;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccessEntry {
+    pub at: DateTime<Utc>,
+    pub ip: IpAddr,
+    pub method: String,
+    pub path: String,
+    pub status: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+}
+
+impl AccessEntry {
+    fn auth_failed(&self) -> bool {
+        self.status == StatusCode::UNAUTHORIZED.as_u16()
+    }
+}
+
+/// Requests from one address among those kept
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressSummary {
+    pub ip: IpAddr,
+    pub requests: u64,
+    pub auth_failures: u64,
+    pub last_seen: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ban {
+    pub ip: IpAddr,
+    pub until: DateTime<Utc>,
+    /// Failed authentications that led to the ban
+    pub failures: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccessSummary {
+    /// Requests kept, oldest first dropped
+    pub requests: u64,
+    pub auth_failures: u64,
+    /// Addresses with the most requests
+    pub top_addresses: Vec<AddressSummary>,
+    /// Failed authentications, newest first
+    pub recent_failures: Vec<AccessEntry>,
+    /// Addresses locked out, whether banned here or for failed sign-ins
+    pub bans: Vec<Ban>,
+}
+
+#[derive(Default)]
+struct AccessState {
+    entries: VecDeque<AccessEntry>,
+    /// Recent authentication failures per address
+    failures: HashMap<IpAddr, VecDeque<DateTime<Utc>>>,
+}
+
+/// Shared access log; clones share state
+#[derive(Clone)]
+pub struct AccessLog {
+    config: AccessLogConfig,
+    state: Arc<RwLock<AccessState>>,
+    lockout: AuthLockout,
+}
+
+impl Default for AccessLog {
+    fn default() -> Self {
+        Self::new(AccessLogConfig::default())
+    }
+}
+
+impl AccessLog {
+    pub fn new(config: AccessLogConfig) -> Self {
+        Self {
+            config,
+            state: Arc::new(RwLock::new(AccessState::default())),
+            lockout: AuthLockout::default(),
+        }
+    }
+
+    /// Ban addresses by locking them out in `lockout`
+    pub fn with_lockout(mut self, lockout: AuthLockout) -> Self {
+        self.lockout = lockout;
+        self
+    }
+
+    /// Log a forwarded request, banning its address if it failed to
+    /// authenticate too often
+    pub async fn record(&self, entry: AccessEntry) {
+        info!(
+            ip = %entry.ip,
+            method = %entry.method,
+            path = %entry.path,
+            status = entry.status,
+            user_agent = entry.user_agent.as_deref().unwrap_or("-"),
+            "Forwarded request"
+        );
+
+        let ban = self.count_failure(&entry);
+        {
+            let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+            if self.config.max_entries > 0 {
+                if state.entries.len() == self.config.max_entries {
+                    state.entries.pop_front();
+                }
+                state.entries.push_back(entry.clone());
+            }
+        }
+        if let Some(ban) = ban {
+            self.lockout
+                .lock_out(
+                    LockoutSubject::Address(ban.ip),
+                    ban.failures,
+                    ban.until,
+                    entry.at,
+                )
+                .await;
+        }
+    }
+
+    /// Count `entry` if it failed to authenticate, returning the ban it earns
+    /// its address
+    fn count_failure(&self, entry: &AccessEntry) -> Option<Ban> {
+        if !entry.auth_failed() || self.config.ban_after_failures == 0 {
+            return None;
+        }
+        let window_start = entry.at - Duration::seconds(self.config.ban_window_seconds as i64);
+        let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+        if !state.failures.contains_key(&entry.ip) {
+            // Forget addresses whose failures have all left the window
+            state
+                .failures
+                .retain(|_, failures| failures.back().is_some_and(|at| *at > window_start));
+        }
+        let failures = state.failures.entry(entry.ip).or_default();
+        failures.retain(|at| *at > window_start);
+        failures.push_back(entry.at);
+        let count = failures.len() as u32;
+        if count < self.config.ban_after_failures {
+            return None;
+        }
+        state.failures.remove(&entry.ip);
+        Some(Ban {
+            ip: entry.ip,
+            until: entry.at + Duration::seconds(self.config.ban_duration_seconds as i64),
+            failures: count,
+        })
+    }
+
+    /// Who has been calling, from the requests kept
+    pub fn summary(&self, now: DateTime<Utc>) -> AccessSummary {
+        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+        let mut addresses: HashMap<IpAddr, AddressSummary> = HashMap::new();
+        for entry in &state.entries {
+            let address = addresses.entry(entry.ip).or_insert(AddressSummary {
+                ip: entry.ip,
+                requests: 0,
+                auth_failures: 0,
+                last_seen: entry.at,
+            });
+            address.requests += 1;
+            address.auth_failures += entry.auth_failed() as u64;
+            address.last_seen = address.last_seen.max(entry.at);
+        }
+        let mut top_addresses: Vec<_> = addresses.into_values().collect();
+        top_addresses.sort_by(|a, b| b.requests.cmp(&a.requests).then(a.ip.cmp(&b.ip)));
+        top_addresses.truncate(TOP_ADDRESSES);
+
+        let failed = || state.entries.iter().filter(|e| e.auth_failed());
+        let mut bans: Vec<_> = self
+            .lockout
+            .lockouts(now)
+            .into_iter()
+            .filter_map(|lockout| match (lockout.subject, lockout.locked_until) {
+                (LockoutSubject::Address(ip), Some(until)) if until > now => Some(Ban {
+                    ip,
+                    until,
+                    failures: lockout.failures,
+                }),
+                _ => None,
+            })
+            .collect();
+        bans.sort_by_key(|ban| ban.until);
+
+        AccessSummary {
+            requests: state.entries.len() as u64,
+            auth_failures: failed().count() as u64,
+            top_addresses,
+            recent_failures: failed().rev().take(RECENT_FAILURES).cloned().collect(),
+            bans,
+        }
+    }
+}
+
+/// Log requests forwarded by the relay. Banned addresses are refused by the
+/// lockout middleware inside this one, so their refusals are logged too.
+pub async fn access_log_middleware(
+    State(app_state): State<AppState<crate::State>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(ForwardedClient(client)) = request.extensions().get::<ForwardedClient>().copied()
+    else {
+        return next.run(request).await;
+    };
+    let access_log = &app_state.data.access_log;
+    let mut entry = AccessEntry {
+        at: Utc::now(),
+        ip: client.ip(),
+        method: request.method().to_string(),
+        path: request.uri().path().to_string(),
+        status: 0,
+        user_agent: request
+            .headers()
+            .get(header::USER_AGENT)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from),
+    };
+
+    let response = next.run(request).await;
+    entry.status = response.status().as_u16();
+    access_log.record(entry).await;
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LockoutConfig;
+
+    fn entry(ip: &str, status: u16, at: DateTime<Utc>) -> AccessEntry {
+        AccessEntry {
+            at,
+            ip: ip.parse().unwrap(),
+            method: "POST".to_string(),
+            path: "/v1/messages".to_string(),
+            status,
+            user_agent: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bans_after_repeated_auth_failures() {
+        // Sign-in lockouts are off; bans apply regardless
+        let lockout = AuthLockout::new(LockoutConfig {
+            enabled: false,
+            ..Default::default()
+        });
+        let log = AccessLog::new(AccessLogConfig {
+            max_entries: 10,
+            ban_after_failures: 3,
+            ban_window_seconds: 60,
+            ban_duration_seconds: 600,
+        })
+        .with_lockout(lockout.clone());
+        let start = Utc::now();
+        let attacker: IpAddr = "203.0.113.7".parse().unwrap();
+        let subjects = [LockoutSubject::Address(attacker)];
+
+        // Failures spread past the window do not add up
+        log.record(entry("203.0.113.7", 401, start)).await;
+        log.record(entry("203.0.113.7", 401, start + Duration::seconds(90)))
+            .await;
+        log.record(entry("198.51.100.1", 200, start + Duration::seconds(90)))
+            .await;
+        log.record(entry("203.0.113.7", 401, start + Duration::seconds(100)))
+            .await;
+        assert_eq!(
+            lockout.locked_until(&subjects, start + Duration::seconds(100)),
+            None
+        );
+
+        let third = start + Duration::seconds(110);
+        log.record(entry("203.0.113.7", 401, third)).await;
+        let until = third + Duration::seconds(600);
+        assert_eq!(lockout.locked_until(&subjects, third), Some(until));
+        assert_eq!(lockout.locked_until(&subjects, until), None);
+
+        let summary = log.summary(third);
+        assert_eq!(summary.requests, 5);
+        assert_eq!(summary.auth_failures, 4);
+        assert_eq!(summary.top_addresses[0].ip, attacker);
+        assert_eq!(summary.top_addresses[0].requests, 4);
+        assert_eq!(summary.recent_failures[0].at, third);
+        assert_eq!(summary.bans[0].until, until);
+
+        // Lifting the lockout lifts the ban
+        assert!(lockout.unblock(&subjects[0], "admin").await);
+        assert_eq!(lockout.locked_until(&subjects, third), None);
+        assert!(log.summary(third).bans.is_empty());
+    }
+}
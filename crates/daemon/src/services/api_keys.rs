@@ -0,0 +1,259 @@
+//! Gateway API key management
+//!
+//! Keys are owned by a user (stored as the key's `org_id`), shown in full only
+//! once at creation, and persisted as a SHA-256 hash alongside their scope.
+//! Observer keys are granted the observer permissions under the key's own
+//! subject, and lose them when revoked.
+
+use crate::error::Result;
+use crate::services::observer::observer_permissions;
+use chrono::{DateTime, Utc};
+use gate_core::{ApiKey, StateBackend};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+/// Prefix identifying gateway-issued API keys
+pub const API_KEY_PREFIX: &str = "gk_";
+
+const API_KEY_RANDOM_LENGTH: usize = 40;
+const API_KEY_DISPLAY_LENGTH: usize = 10;
+
+/// Restrictions attached to an API key, stored in the key's config
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ApiKeyScope {
+    /// Leading characters of the raw key, for display only
+    #[serde(default)]
+    pub key_prefix: String,
+    /// When the key stops being accepted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Models the key may use; empty means all models
+    #[serde(default)]
+    pub allowed_models: Vec<String>,
+    /// Maximum spend in USD; `None` means unlimited
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spend_cap: Option<f64>,
+    /// Streaming requests the key may have open at once; `None` means
+    /// unlimited
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent_streams: Option<u32>,
+    /// Read-only access to status, usage and health; see
+    /// [`crate::services::observer`]
+    #[serde(default)]
+    pub observer: bool,
+    /// Parameter profile enforced on the key's requests, on top of its
+    /// owner's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parameter_profile: Option<String>,
+}
+
+impl ApiKeyScope {
+    /// Read the scope from a stored key, falling back to an unrestricted scope
+    pub fn from_key(key: &ApiKey) -> Self {
+        key.config
+            .clone()
+            .and_then(|config| serde_json::from_value(config).ok())
+            .unwrap_or_default()
+    }
+
+    /// Whether the key has expired at the given instant
+    pub fn is_expired_at(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+}
+
+/// Hash a raw API key the same way it is stored
+pub fn hash_api_key(raw_key: &str) -> String {
+    format!("{:x}", Sha256::digest(raw_key.as_bytes()))
+}
+
+/// Subject a key authenticates as, distinct from its owner. Grants and
+/// limits attach to it, so it carries the whole hash.
+pub fn key_subject(key_hash: &str) -> String {
+    format!("apikey:{key_hash}")
+}
+
+/// Service for creating, listing, revoking and validating API keys
+#[derive(Clone)]
+pub struct ApiKeyService {
+    state_backend: Arc<dyn StateBackend>,
+}
+
+impl ApiKeyService {
+    pub fn new(state_backend: Arc<dyn StateBackend>) -> Self {
+        Self { state_backend }
+    }
+
+    /// Create a key for `owner_id`, returning the raw key and the stored record
+    pub async fn create(
+        &self,
+        owner_id: &str,
+        name: String,
+        mut scope: ApiKeyScope,
+    ) -> Result<(String, ApiKey)> {
+        let raw_key = generate_raw_key();
+        scope.key_prefix = raw_key.chars().take(API_KEY_DISPLAY_LENGTH).collect();
+
+        let key = ApiKey {
+            key_hash: hash_api_key(&raw_key),
+            name,
+            org_id: owner_id.to_string(),
+            config: Some(serde_json::to_value(&scope)?),
+            created_at: Utc::now(),
+            last_used_at: None,
+        };
+        self.state_backend.create_api_key(&key, &raw_key).await?;
+        if scope.observer {
+            let subject = key_subject(&key.key_hash);
+            for (action, object) in observer_permissions() {
+                self.state_backend
+                    .grant_permission(&subject, &action, &object)
+                    .await?;
+            }
+        }
+
+        Ok((raw_key, key))
+    }
+
+    /// List keys owned by `owner_id`
+    pub async fn list(&self, owner_id: &str) -> Result<Vec<ApiKey>> {
+        Ok(self.state_backend.list_api_keys(owner_id).await?)
+    }
+
+    /// Revoke a key owned by `owner_id`, returning whether it existed
+    pub async fn revoke(&self, owner_id: &str, key_hash: &str) -> Result<bool> {
+        match self.state_backend.get_api_key(key_hash).await? {
+            Some(key) if key.org_id == owner_id => {
+                self.state_backend.delete_api_key(key_hash).await?;
+                if ApiKeyScope::from_key(&key).observer {
+                    let subject = key_subject(key_hash);
+                    for (action, object) in observer_permissions() {
+                        self.state_backend
+                            .remove_permission(&subject, &action, &object)
+                            .await?;
+                    }
+                }
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Streams the key has open right now, across every gateway sharing
+    /// the state backend
+    pub async fn active_streams(&self, key_hash: &str) -> Result<u32> {
+        Ok(self.state_backend.count_stream_slots(key_hash).await?)
+    }
+
+    /// Validate a raw key, recording its use; returns `None` if unknown or expired
+    pub async fn authenticate(&self, raw_key: &str) -> Result<Option<ApiKey>> {
+        let key_hash = hash_api_key(raw_key);
+        let Some(key) = self.state_backend.get_api_key(&key_hash).await? else {
+            return Ok(None);
+        };
+
+        let now = Utc::now();
+        if ApiKeyScope::from_key(&key).is_expired_at(now) {
+            return Ok(None);
+        }
+
+        if let Err(e) = self.state_backend.touch_api_key(&key_hash, now).await {
+            warn!("Failed to record API key usage: {}", e);
+        }
+
+        Ok(Some(key))
+    }
+}
+
+fn generate_raw_key() -> String {
+    use rand::Rng;
+    let mut rng = rand::thread_rng();
+    let random: String = (0..API_KEY_RANDOM_LENGTH)
+        .map(|_| rng.sample(rand::distributions::Alphanumeric) as char)
+        .collect();
+    format!("{API_KEY_PREFIX}{random}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gate_sqlx::SqliteStateBackend;
+
+    async fn make_service() -> ApiKeyService {
+        let backend = SqliteStateBackend::new(":memory:").await.unwrap();
+        ApiKeyService::new(Arc::new(backend))
+    }
+
+    #[tokio::test]
+    async fn test_create_authenticate_revoke() {
+        let service = make_service().await;
+        let (raw_key, key) = service
+            .create("user-1", "laptop".to_string(), ApiKeyScope::default())
+            .await
+            .unwrap();
+
+        assert!(raw_key.starts_with(API_KEY_PREFIX));
+        assert_eq!(key.key_hash, hash_api_key(&raw_key));
+        assert!(raw_key.starts_with(&ApiKeyScope::from_key(&key).key_prefix));
+
+        let authenticated = service.authenticate(&raw_key).await.unwrap().unwrap();
+        assert_eq!(authenticated.org_id, "user-1");
+        let listed = service.list("user-1").await.unwrap();
+        assert!(listed[0].last_used_at.is_some());
+
+        assert!(!service.revoke("user-2", &key.key_hash).await.unwrap());
+        assert!(service.revoke("user-1", &key.key_hash).await.unwrap());
+        assert!(service.authenticate(&raw_key).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_expired_key_rejected() {
+        let service = make_service().await;
+        let scope = ApiKeyScope {
+            expires_at: Some(Utc::now() - chrono::Duration::hours(1)),
+            ..Default::default()
+        };
+        let (raw_key, _) = service
+            .create("user-1", "old".to_string(), scope)
+            .await
+            .unwrap();
+
+        assert!(service.authenticate(&raw_key).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_observer_key_permissions_follow_the_key() {
+        let service = make_service().await;
+        let scope = ApiKeyScope {
+            observer: true,
+            ..Default::default()
+        };
+        let (_, key) = service
+            .create("user-1", "wallboard".to_string(), scope)
+            .await
+            .unwrap();
+
+        let subject = key_subject(&key.key_hash);
+        let (action, object) = &observer_permissions()[0];
+        let granted = || {
+            service
+                .state_backend
+                .has_permission(&subject, action, object)
+        };
+        assert!(granted().await.unwrap());
+
+        // Keys whose hashes share a prefix do not share grants
+        let lookalike = format!("{}{}", &key.key_hash[..8], "0".repeat(56));
+        assert!(
+            !service
+                .state_backend
+                .has_permission(&key_subject(&lookalike), action, object)
+                .await
+                .unwrap()
+        );
+
+        assert!(service.revoke("user-1", &key.key_hash).await.unwrap());
+        assert!(!granted().await.unwrap());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::services::observer::{SCOPE_ATTRIBUTE, token_scope};
 use chrono::Utc;
 use gate_core::{StateBackend, User};
 use gate_http::error::HttpError;
@@ -39,13 +40,91 @@ impl AuthService {
             .await
             .map_err(|e| HttpError::InternalServerError(format!("Failed to create user: {e}")))?;
 
-        let passkey_data = serde_json::to_vec(&passkey).map_err(|e| {
+        self.store_credential(&user.id, &credential_id, device_name, &passkey)
+            .await?;
+
+        let token = self.issue_token(&user)?;
+
+        Ok(RegisterCompleteResponse {
+            user_id: user.id.clone(),
+            name: user.name.unwrap_or_else(|| "User".to_string()),
+            credential_id,
+            token,
+        })
+    }
+
+    /// Attach a passkey registered through a pairing code to an existing
+    /// user, without creating a new account
+    pub async fn add_device(
+        &self,
+        user_id: &str,
+        credential_id: String,
+        device_name: Option<String>,
+        passkey: Passkey,
+    ) -> Result<RegisterCompleteResponse, HttpError> {
+        let user = self
+            .state_backend
+            .get_user_by_id(user_id)
+            .await
+            .map_err(|e| HttpError::InternalServerError(format!("Failed to get user: {e}")))?
+            .ok_or_else(|| HttpError::NotFound("User not found".to_string()))?;
+
+        self.store_credential(&user.id, &credential_id, device_name, &passkey)
+            .await?;
+
+        let token = self.issue_token(&user)?;
+
+        Ok(RegisterCompleteResponse {
+            user_id: user.id,
+            name: user.name.unwrap_or_else(|| "User".to_string()),
+            credential_id,
+            token,
+        })
+    }
+
+    /// Credentials registered for a user, one per device
+    pub async fn list_devices(&self, user_id: &str) -> Result<Vec<StoredCredential>, HttpError> {
+        self.webauthn_backend
+            .list_user_credentials(user_id)
+            .await
+            .map_err(|e| HttpError::InternalServerError(format!("Failed to list credentials: {e}")))
+    }
+
+    /// Remove one of a user's credentials. The last one cannot be removed,
+    /// since the user could no longer sign in.
+    pub async fn revoke_device(&self, user_id: &str, credential_id: &str) -> Result<(), HttpError> {
+        let devices = self.list_devices(user_id).await?;
+        if !devices.iter().any(|d| d.credential_id == credential_id) {
+            return Err(HttpError::NotFound("Device not found".to_string()));
+        }
+        if devices.len() == 1 {
+            return Err(HttpError::BadRequest(
+                "Cannot revoke the last device for a user".to_string(),
+            ));
+        }
+
+        self.webauthn_backend
+            .delete_credential(credential_id)
+            .await
+            .map_err(|e| {
+                HttpError::InternalServerError(format!("Failed to delete credential: {e}"))
+            })
+    }
+
+    async fn store_credential(
+        &self,
+        user_id: &str,
+        credential_id: &str,
+        device_name: Option<String>,
+        passkey: &Passkey,
+    ) -> Result<(), HttpError> {
+        let passkey_data = serde_json::to_vec(passkey).map_err(|e| {
             HttpError::InternalServerError(format!("Failed to serialize passkey: {e}"))
         })?;
 
         let stored_credential = StoredCredential {
-            credential_id: credential_id.clone(),
-            user_id: user.id.clone(),
+            credential_id: credential_id.to_string(),
+            user_id: user_id.to_string(),
             public_key: passkey_data,
             aaguid: None,
             counter: 0,
@@ -57,20 +136,7 @@ impl AuthService {
         self.webauthn_backend
             .store_webauthn_credential(&stored_credential)
             .await
-            .map_err(|e| {
-                HttpError::InternalServerError(format!("Failed to store credential: {e}"))
-            })?;
-
-        let token = self
-            .jwt_service
-            .generate_token(&user.id, user.name.as_deref())?;
-
-        Ok(RegisterCompleteResponse {
-            user_id: user.id.clone(),
-            name: user.name.unwrap_or_else(|| "User".to_string()),
-            credential_id,
-            token,
-        })
+            .map_err(|e| HttpError::InternalServerError(format!("Failed to store credential: {e}")))
     }
 
     pub async fn complete_authentication(
@@ -78,9 +144,19 @@ impl AuthService {
         credential_id: String,
         counter: u32,
     ) -> Result<AuthCompleteResponse, HttpError> {
+        // Paired devices belong to the user they were added to; the first
+        // credential of a user shares its id
+        let user_id = self
+            .webauthn_backend
+            .get_webauthn_credential(&credential_id)
+            .await
+            .map_err(|e| HttpError::InternalServerError(format!("Failed to get credential: {e}")))?
+            .map(|c| c.user_id)
+            .unwrap_or_else(|| credential_id.clone());
+
         let user = self
             .state_backend
-            .get_user_by_id(&credential_id)
+            .get_user_by_id(&user_id)
             .await
             .map_err(|e| HttpError::InternalServerError(format!("Failed to get user: {e}")))?
             .ok_or_else(|| HttpError::NotFound("User not found".to_string()))?;
@@ -92,9 +168,7 @@ impl AuthService {
                 HttpError::InternalServerError(format!("Failed to update credential: {e}"))
             })?;
 
-        let token = self
-            .jwt_service
-            .generate_token(&user.id, user.name.as_deref())?;
+        let token = self.issue_token(&user)?;
 
         Ok(AuthCompleteResponse {
             user_id: user.id,
@@ -103,20 +177,25 @@ impl AuthService {
         })
     }
 
+    /// Session token for a user, scoped by their role
+    fn issue_token(&self, user: &User) -> Result<String, HttpError> {
+        self.jwt_service
+            .generate_scoped_token(&user.id, user.name.as_deref(), token_scope(user))
+    }
+
     pub fn validate_token(&self, token: &str) -> Result<HttpIdentity, HttpError> {
         let claims = self.jwt_service.validate_token(token)?;
 
-        let identity = HttpIdentity::new(
-            claims.sub.clone(),
-            "jwt".to_string(),
-            HttpContext::new()
-                .with_attribute("auth_method", "webauthn")
-                .with_attribute("issued_at", claims.iat.to_string())
-                .with_attribute("expires_at", claims.exp.to_string())
-                .with_attribute("name", claims.name.as_deref().unwrap_or("")),
-        );
-
-        Ok(identity)
+        let mut context = HttpContext::new()
+            .with_attribute("auth_method", "webauthn")
+            .with_attribute("issued_at", claims.iat.to_string())
+            .with_attribute("expires_at", claims.exp.to_string())
+            .with_attribute("name", claims.name.as_deref().unwrap_or(""));
+        if let Some(scope) = claims.scope {
+            context = context.with_attribute(SCOPE_ATTRIBUTE, scope);
+        }
+
+        Ok(HttpIdentity::new(claims.sub, "jwt".to_string(), context))
     }
 
     pub fn authenticate_from_header(&self, auth_header: &str) -> Result<HttpIdentity, HttpError> {
@@ -0,0 +1,471 @@
+//! Blob store drivers
+//!
+//! Uploads, database backups and deduplicated bodies go through one
+//! [`BlobStore`]: a directory, by default the state directory so existing
+//! files stay where they are, or an S3-compatible bucket reached over its
+//! REST API with Signature Version 4.
+
+use crate::config::BlobDriverConfig;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use gate_core::blob::is_valid_key;
+use gate_core::{BlobInfo, BlobStore, Error, Result};
+use hmac::{Hmac, Mac};
+use regex::Regex;
+use reqwest::{Method, StatusCode, Url};
+use sha2::{Digest, Sha256};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock};
+
+/// Suffix of files being written, which are not blobs yet
+const PARTIAL_SUFFIX: &str = ".partial";
+
+const SERVICE: &str = "s3";
+
+/// Objects in a ListObjectsV2 response: key, last modified, size
+static LIST_ENTRY: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?s)<Contents>.*?<Key>(.*?)</Key>.*?<LastModified>(.*?)</LastModified>.*?<Size>(\d+)</Size>.*?</Contents>",
+    )
+    .expect("valid regex")
+});
+
+static CONTINUATION_TOKEN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"<NextContinuationToken>(.*?)</NextContinuationToken>").expect("valid regex")
+});
+
+/// Blob store for `config`; a local store without a path lives in
+/// `default_root`
+pub fn open_blob_store(
+    config: &BlobDriverConfig,
+    default_root: PathBuf,
+) -> crate::error::Result<Arc<dyn BlobStore>> {
+    Ok(match config {
+        BlobDriverConfig::Local { path } => Arc::new(LocalBlobStore::new(
+            path.as_ref().map_or(default_root, PathBuf::from),
+        )),
+        BlobDriverConfig::S3 {
+            endpoint,
+            bucket,
+            region,
+            prefix,
+            access_key_id,
+            secret_access_key,
+            path_style,
+        } => Arc::new(S3BlobStore {
+            client: reqwest::Client::new(),
+            endpoint: Url::parse(endpoint).map_err(|e| {
+                crate::error::DaemonError::ConfigError(format!(
+                    "Invalid blob store endpoint {endpoint}: {e}"
+                ))
+            })?,
+            bucket: bucket.clone(),
+            region: region.clone(),
+            prefix: prefix.clone(),
+            access_key_id: access_key_id.clone(),
+            secret_access_key: secret_access_key.clone(),
+            path_style: *path_style,
+        }),
+    })
+}
+
+fn invalid_key(key: &str) -> Error {
+    Error::InvalidRequest(format!("Invalid blob key {key:?}"))
+}
+
+/// Blobs as files under a directory, keys being their relative paths
+pub struct LocalBlobStore {
+    root: PathBuf,
+}
+
+impl LocalBlobStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf> {
+        if !is_valid_key(key) {
+            return Err(invalid_key(key));
+        }
+        Ok(self.root.join(key))
+    }
+
+    fn info(key: String, metadata: &std::fs::Metadata) -> io::Result<BlobInfo> {
+        Ok(BlobInfo {
+            key,
+            size: metadata.len(),
+            modified: DateTime::<Utc>::from(metadata.modified()?),
+        })
+    }
+}
+
+#[async_trait]
+impl BlobStore for LocalBlobStore {
+    async fn put(&self, key: &str, data: Vec<u8>) -> Result<()> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let mut partial = path.clone().into_os_string();
+        partial.push(PARTIAL_SUFFIX);
+        tokio::fs::write(&partial, data).await?;
+        tokio::fs::rename(&partial, &path).await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        match tokio::fs::read(self.path(key)?).await {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn head(&self, key: &str) -> Result<Option<BlobInfo>> {
+        match tokio::fs::metadata(self.path(key)?).await {
+            Ok(metadata) if metadata.is_file() => Ok(Some(Self::info(key.to_string(), &metadata)?)),
+            Ok(_) => Ok(None),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        match tokio::fs::remove_file(self.path(key)?).await {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<BlobInfo>> {
+        // Walk only the directory the prefix names
+        let start = prefix.rsplit_once('/').map_or("", |(dir, _)| dir);
+        let mut blobs = Vec::new();
+        let mut dirs = vec![self.root.join(start)];
+        while let Some(dir) = dirs.pop() {
+            let mut entries = match tokio::fs::read_dir(&dir).await {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            while let Some(entry) = entries.next_entry().await? {
+                let metadata = entry.metadata().await?;
+                if metadata.is_dir() {
+                    dirs.push(entry.path());
+                    continue;
+                }
+                let Some(key) = relative_key(&self.root, &entry.path()) else {
+                    continue;
+                };
+                if key.starts_with(prefix) && !key.ends_with(PARTIAL_SUFFIX) {
+                    blobs.push(Self::info(key, &metadata)?);
+                }
+            }
+        }
+        Ok(blobs)
+    }
+}
+
+/// Key of the file at `path` under `root`
+fn relative_key(root: &Path, path: &Path) -> Option<String> {
+    let segments: Option<Vec<&str>> = path
+        .strip_prefix(root)
+        .ok()?
+        .components()
+        .map(|c| c.as_os_str().to_str())
+        .collect();
+    Some(segments?.join("/"))
+}
+
+fn hmac(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Key requests made on `date` (`YYYYMMDD`) are signed with
+fn signing_key(secret: &str, date: &str, region: &str) -> Vec<u8> {
+    let key = hmac(format!("AWS4{secret}").as_bytes(), date);
+    let key = hmac(&key, region);
+    let key = hmac(&key, SERVICE);
+    hmac(&key, "aws4_request")
+}
+
+/// Percent-encode everything but unreserved characters, and `/` unless
+/// `encode_slash`, as Signature Version 4 expects
+fn uri_encode(value: &str, encode_slash: bool) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char);
+            }
+            b'/' if !encode_slash => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+fn unescape_xml(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Blobs as objects in an S3-compatible bucket, under a key prefix
+pub struct S3BlobStore {
+    client: reqwest::Client,
+    endpoint: Url,
+    bucket: String,
+    region: String,
+    prefix: String,
+    access_key_id: String,
+    secret_access_key: String,
+    /// Address the bucket in the path rather than the host name, as most
+    /// self-hosted stores require
+    path_style: bool,
+}
+
+impl S3BlobStore {
+    /// Host and canonical path of the object `key`, or of the bucket
+    fn location(&self, key: Option<&str>) -> (String, String) {
+        let mut host = self.endpoint.host_str().unwrap_or_default().to_string();
+        if let Some(port) = self.endpoint.port() {
+            host = format!("{host}:{port}");
+        }
+        let object = key
+            .map(|key| uri_encode(&format!("{}{key}", self.prefix), false))
+            .unwrap_or_default();
+        if self.path_style {
+            (host, format!("/{}/{object}", self.bucket))
+        } else {
+            (format!("{}.{host}", self.bucket), format!("/{object}"))
+        }
+    }
+
+    /// Send a signed request for `key`, or for the bucket
+    async fn send(
+        &self,
+        method: Method,
+        key: Option<&str>,
+        query: &[(&str, &str)],
+        body: Vec<u8>,
+    ) -> Result<reqwest::Response> {
+        if let Some(key) = key
+            && !is_valid_key(key)
+        {
+            return Err(invalid_key(key));
+        }
+        let (host, path) = self.location(key);
+        let mut query: Vec<(String, String)> = query
+            .iter()
+            .map(|(name, value)| (uri_encode(name, true), uri_encode(value, true)))
+            .collect();
+        query.sort();
+        let query = query
+            .iter()
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect::<Vec<_>>()
+            .join("&");
+
+        let now = Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let payload_hash = hex::encode(Sha256::digest(&body));
+        let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+        let canonical_request = format!(
+            "{method}\n{path}\n{query}\nhost:{host}\nx-amz-content-sha256:{payload_hash}\nx-amz-date:{amz_date}\n\n{signed_headers}\n{payload_hash}"
+        );
+        let scope = format!("{date}/{}/{SERVICE}/aws4_request", self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+        let key = signing_key(&self.secret_access_key, &date, &self.region);
+        let signature = hex::encode(hmac(&key, &string_to_sign));
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+            self.access_key_id
+        );
+
+        let mut url = format!("{}://{host}{path}", self.endpoint.scheme());
+        if !query.is_empty() {
+            url = format!("{url}?{query}");
+        }
+        self.client
+            .request(method, url)
+            .header("x-amz-content-sha256", payload_hash)
+            .header("x-amz-date", amz_date)
+            .header("authorization", authorization)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| Error::StateError(format!("Blob store request failed: {e}")))
+    }
+
+    /// `response` if it succeeded, else an error naming what failed
+    async fn check(response: reqwest::Response, what: &str) -> Result<reqwest::Response> {
+        if response.status().is_success() {
+            return Ok(response);
+        }
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        Err(Error::StateError(format!(
+            "Blob store refused to {what}: {status} {body}"
+        )))
+    }
+}
+
+#[async_trait]
+impl BlobStore for S3BlobStore {
+    async fn put(&self, key: &str, data: Vec<u8>) -> Result<()> {
+        let response = self.send(Method::PUT, Some(key), &[], data).await?;
+        Self::check(response, &format!("store {key}")).await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let response = self.send(Method::GET, Some(key), &[], Vec::new()).await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let response = Self::check(response, &format!("read {key}")).await?;
+        let data = response
+            .bytes()
+            .await
+            .map_err(|e| Error::StateError(format!("Failed to read blob {key}: {e}")))?;
+        Ok(Some(data.to_vec()))
+    }
+
+    async fn head(&self, key: &str) -> Result<Option<BlobInfo>> {
+        let response = self.send(Method::HEAD, Some(key), &[], Vec::new()).await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let response = Self::check(response, &format!("describe {key}")).await?;
+        let header = |name: &str| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        Ok(Some(BlobInfo {
+            key: key.to_string(),
+            size: header("content-length")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or_default(),
+            modified: header("last-modified")
+                .and_then(|v| DateTime::parse_from_rfc2822(&v).ok())
+                .map_or_else(Utc::now, |t| t.with_timezone(&Utc)),
+        }))
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let response = self
+            .send(Method::DELETE, Some(key), &[], Vec::new())
+            .await?;
+        if response.status() != StatusCode::NOT_FOUND {
+            Self::check(response, &format!("delete {key}")).await?;
+        }
+        Ok(())
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<BlobInfo>> {
+        let full_prefix = format!("{}{prefix}", self.prefix);
+        let mut blobs = Vec::new();
+        let mut token: Option<String> = None;
+        loop {
+            let mut query = vec![("list-type", "2"), ("prefix", full_prefix.as_str())];
+            if let Some(token) = &token {
+                query.push(("continuation-token", token));
+            }
+            let response = self.send(Method::GET, None, &query, Vec::new()).await?;
+            let body = Self::check(response, "list blobs")
+                .await?
+                .text()
+                .await
+                .map_err(|e| Error::StateError(format!("Failed to list blobs: {e}")))?;
+            for entry in LIST_ENTRY.captures_iter(&body) {
+                let key = unescape_xml(&entry[1]);
+                let Some(key) = key.strip_prefix(&self.prefix) else {
+                    continue;
+                };
+                blobs.push(BlobInfo {
+                    key: key.to_string(),
+                    size: entry[3].parse().unwrap_or_default(),
+                    modified: entry[2].parse().unwrap_or_else(|_| Utc::now()),
+                });
+            }
+            token = CONTINUATION_TOKEN
+                .captures(&body)
+                .map(|captures| unescape_xml(&captures[1]));
+            if token.is_none() {
+                return Ok(blobs);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_local_store_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = LocalBlobStore::new(dir.path());
+
+        store.put("files/a/one", b"1".to_vec()).await.unwrap();
+        store.put("files/two", b"22".to_vec()).await.unwrap();
+        store.put("backups/three", b"333".to_vec()).await.unwrap();
+        assert_eq!(store.get("files/two").await.unwrap(), Some(b"22".to_vec()));
+        assert_eq!(store.head("files/a/one").await.unwrap().unwrap().size, 1);
+        assert_eq!(store.get("files/none").await.unwrap(), None);
+
+        let mut keys: Vec<_> = store
+            .list("files/")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|b| b.key)
+            .collect();
+        keys.sort();
+        assert_eq!(keys, ["files/a/one", "files/two"]);
+
+        store.delete("files/two").await.unwrap();
+        store.delete("files/two").await.unwrap();
+        assert_eq!(store.head("files/two").await.unwrap(), None);
+
+        assert!(store.get("../outside").await.is_err());
+        assert!(store.put("/etc/passwd", Vec::new()).await.is_err());
+    }
+
+    #[test]
+    fn test_s3_keys_are_encoded_per_segment() {
+        assert_eq!(uri_encode("blobs/a b/ü~", false), "blobs/a%20b/%C3%BC~");
+        assert_eq!(uri_encode("a/b", true), "a%2Fb");
+        let store = S3BlobStore {
+            client: reqwest::Client::new(),
+            endpoint: Url::parse("http://localhost:9000").unwrap(),
+            bucket: "gate".to_string(),
+            region: "us-east-1".to_string(),
+            prefix: "node-1/".to_string(),
+            access_key_id: String::new(),
+            secret_access_key: String::new(),
+            path_style: true,
+        };
+        assert_eq!(
+            store.location(Some("files/x")),
+            (
+                "localhost:9000".to_string(),
+                "/gate/node-1/files/x".to_string()
+            )
+        );
+    }
+}
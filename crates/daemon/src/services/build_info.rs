@@ -0,0 +1,108 @@
+//! What the running daemon was built from, and the frontend it serves
+//!
+//! The git hash and build time are stamped in by the build script. The
+//! frontend bundle is hashed at startup, file by file, so a UI left behind
+//! by a partial upgrade can be told apart from the one the daemon shipped
+//! with.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Path the version is served at
+pub const VERSION_PATH: &str = "/api/version";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionInfo {
+    pub version: String,
+    /// Commit the daemon was built from, or `unknown`
+    pub git_hash: String,
+    pub build_date: DateTime<Utc>,
+    /// `sha256:` hash of the frontend bundle being served
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frontend_hash: Option<String>,
+}
+
+impl VersionInfo {
+    /// The running daemon, serving the bundle hashed as `frontend_hash`
+    pub fn current(frontend_hash: Option<String>) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            git_hash: env!("GATE_GIT_HASH").to_string(),
+            build_date: build_date(),
+            frontend_hash,
+        }
+    }
+}
+
+/// When the daemon was built
+pub fn build_date() -> DateTime<Utc> {
+    env!("GATE_BUILD_TIMESTAMP")
+        .parse()
+        .ok()
+        .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+        .unwrap_or_default()
+}
+
+/// `sha256:` hash of the bundle under `dir`, covering each file's path
+/// relative to `dir` and its contents
+pub fn frontend_bundle_hash(dir: &Path) -> io::Result<String> {
+    let mut files = Vec::new();
+    collect_files(dir, &mut files)?;
+    files.sort();
+
+    let mut hasher = Sha256::new();
+    for file in files {
+        let relative = file.strip_prefix(dir).unwrap_or(&file);
+        let contents = std::fs::read(&file)?;
+        hasher.update(relative.to_string_lossy().as_bytes());
+        hasher.update([0]);
+        hasher.update((contents.len() as u64).to_be_bytes());
+        hasher.update(&contents);
+    }
+    Ok(format!("sha256:{}", hex::encode(hasher.finalize())))
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundle_hash_follows_contents_and_names() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("assets")).unwrap();
+        std::fs::write(dir.path().join("index.html"), "<html></html>").unwrap();
+        std::fs::write(dir.path().join("assets/app.wasm"), b"\0asm").unwrap();
+
+        let hash = frontend_bundle_hash(dir.path()).unwrap();
+        assert!(hash.starts_with("sha256:"));
+        assert_eq!(frontend_bundle_hash(dir.path()).unwrap(), hash);
+
+        std::fs::write(dir.path().join("assets/app.wasm"), b"\0asm\x01").unwrap();
+        let changed = frontend_bundle_hash(dir.path()).unwrap();
+        assert_ne!(changed, hash);
+
+        std::fs::rename(
+            dir.path().join("assets/app.wasm"),
+            dir.path().join("assets/other.wasm"),
+        )
+        .unwrap();
+        assert_ne!(frontend_bundle_hash(dir.path()).unwrap(), changed);
+
+        assert!(VersionInfo::current(None).build_date > DateTime::<Utc>::default());
+    }
+}
@@ -0,0 +1,387 @@
+//! User-supplied TLS certificates
+//!
+//! Instead of being obtained from Let's Encrypt, the certificate can come
+//! from PEM files the user supplies, or from a directory an external tool
+//! such as cert-manager or certbot keeps current. The files are validated
+//! before they are served and checked again on a schedule: a renewed
+//! certificate is hot-loaded into the TLS acceptor, one that fails to load
+//! is reported and the current one kept, and a certificate nearing expiry
+//! is warned about in the log and on the configured webhooks.
+
+use crate::config::{CertificateSource, CertificatesConfig};
+use crate::error::{DaemonError, Result};
+use crate::services::journal::Journal;
+use crate::services::scheduler::ScheduledTask;
+use crate::services::tls_status::{MANUAL_CERTIFICATE, RenewalLog};
+use crate::services::webhooks::enqueue_webhook;
+use crate::tls_reload::ReloadableTlsAcceptor;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rustls::ServerConfig;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, pem::PemObject};
+use serde::Serialize;
+use serde_json::{Value as JsonValue, json};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+use tokio::sync::Mutex;
+use tokio_rustls::TlsAcceptor;
+use x509_parser::prelude::{FromDer, GeneralName, X509Certificate};
+
+/// Certificate and key file names looked for in a watched directory, as
+/// cert-manager and certbot write them
+const DIRECTORY_LAYOUTS: [(&str, &str); 3] = [
+    ("tls.crt", "tls.key"),
+    ("fullchain.pem", "privkey.pem"),
+    ("fullchain.pem", "key.pem"),
+];
+
+/// Shortest interval the files are checked at
+const MIN_CHECK_SECONDS: u64 = 10;
+
+/// Certificate chain and private key PEM files
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CertificateFiles {
+    pub cert: PathBuf,
+    pub key: PathBuf,
+}
+
+impl CertificateFiles {
+    /// Files `source` currently names, or `None` for certificates obtained
+    /// through ACME
+    pub fn resolve(source: &CertificateSource) -> Result<Option<Self>> {
+        match source {
+            CertificateSource::Acme => Ok(None),
+            CertificateSource::Files {
+                cert_path,
+                key_path,
+            } => Ok(Some(Self {
+                cert: cert_path.clone(),
+                key: key_path.clone(),
+            })),
+            CertificateSource::Directory { path } => Self::in_directory(path).map(Some),
+        }
+    }
+
+    fn in_directory(dir: &Path) -> Result<Self> {
+        DIRECTORY_LAYOUTS
+            .iter()
+            .map(|(cert, key)| Self {
+                cert: dir.join(cert),
+                key: dir.join(key),
+            })
+            .find(|files| files.cert.is_file() && files.key.is_file())
+            .ok_or_else(|| {
+                DaemonError::ConfigError(format!(
+                    "No certificate in {}; expected tls.crt and tls.key, or fullchain.pem and privkey.pem",
+                    dir.display()
+                ))
+            })
+    }
+
+    /// Modification times of both files, which change on renewal
+    async fn modified(&self) -> Option<(SystemTime, SystemTime)> {
+        let cert = tokio::fs::metadata(&self.cert)
+            .await
+            .ok()?
+            .modified()
+            .ok()?;
+        let key = tokio::fs::metadata(&self.key).await.ok()?.modified().ok()?;
+        Some((cert, key))
+    }
+}
+
+/// What a certificate covers and for how long
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CertificateInfo {
+    pub subject: String,
+    pub issuer: String,
+    /// DNS names in the subject alternative names
+    pub domains: Vec<String>,
+    pub not_before: DateTime<Utc>,
+    pub not_after: DateTime<Utc>,
+}
+
+impl CertificateInfo {
+    /// Whole days until the certificate expires, negative once it has
+    pub fn days_left(&self, now: DateTime<Utc>) -> i64 {
+        (self.not_after - now).num_days()
+    }
+}
+
+/// A validated certificate, ready to serve
+pub struct LoadedCertificate {
+    pub acceptor: TlsAcceptor,
+    pub info: CertificateInfo,
+}
+
+fn invalid(path: &Path, reason: impl std::fmt::Display) -> DaemonError {
+    DaemonError::ConfigError(format!(
+        "Invalid certificate file {}: {reason}",
+        path.display()
+    ))
+}
+
+/// Read and validate a certificate and its key: the chain must parse, the
+/// leaf must be current and the key must belong to it
+pub async fn load_certificate(files: &CertificateFiles) -> Result<LoadedCertificate> {
+    let cert_pem = tokio::fs::read(&files.cert)
+        .await
+        .map_err(|e| invalid(&files.cert, e))?;
+    let key_pem = tokio::fs::read(&files.key)
+        .await
+        .map_err(|e| invalid(&files.key, e))?;
+
+    let chain = CertificateDer::pem_slice_iter(&cert_pem)
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|e| invalid(&files.cert, e))?;
+    let leaf = chain
+        .first()
+        .ok_or_else(|| invalid(&files.cert, "no certificate found"))?;
+    let info = certificate_info(leaf).map_err(|e| invalid(&files.cert, e))?;
+    let now = Utc::now();
+    if info.not_after <= now {
+        return Err(invalid(
+            &files.cert,
+            format!("expired at {}", info.not_after),
+        ));
+    }
+    if info.not_before > now {
+        return Err(invalid(
+            &files.cert,
+            format!("not valid before {}", info.not_before),
+        ));
+    }
+
+    let key = PrivateKeyDer::from_pem_slice(&key_pem).map_err(|e| invalid(&files.key, e))?;
+    let config = ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(chain, key)
+        .map_err(|e| invalid(&files.key, format!("does not match the certificate: {e}")))?;
+
+    Ok(LoadedCertificate {
+        acceptor: TlsAcceptor::from(Arc::new(config)),
+        info,
+    })
+}
+
+/// Read what the leaf of the PEM chain at `path` covers, without checking
+/// it further
+pub async fn read_certificate(path: &Path) -> Result<CertificateInfo> {
+    let pem = tokio::fs::read(path).await.map_err(|e| invalid(path, e))?;
+    let leaf = CertificateDer::pem_slice_iter(&pem)
+        .next()
+        .ok_or_else(|| invalid(path, "no certificate found"))?
+        .map_err(|e| invalid(path, e))?;
+    certificate_info(&leaf).map_err(|e| invalid(path, e))
+}
+
+fn certificate_info(der: &CertificateDer<'_>) -> std::result::Result<CertificateInfo, String> {
+    let (_, cert) = X509Certificate::from_der(der.as_ref()).map_err(|e| e.to_string())?;
+    let domains = cert
+        .subject_alternative_name()
+        .ok()
+        .flatten()
+        .map(|san| {
+            san.value
+                .general_names
+                .iter()
+                .filter_map(|name| match name {
+                    GeneralName::DNSName(domain) => Some(domain.to_string()),
+                    _ => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let validity = cert.validity();
+    Ok(CertificateInfo {
+        subject: cert.subject().to_string(),
+        issuer: cert.issuer().to_string(),
+        domains,
+        not_before: DateTime::from_timestamp(validity.not_before.timestamp(), 0)
+            .unwrap_or_default(),
+        not_after: DateTime::from_timestamp(validity.not_after.timestamp(), 0).unwrap_or_default(),
+    })
+}
+
+#[derive(Default)]
+struct WatchState {
+    files: Option<CertificateFiles>,
+    modified: Option<(SystemTime, SystemTime)>,
+    current: Option<CertificateInfo>,
+    /// Expiry already warned about, so each certificate is warned of once
+    warned_for: Option<DateTime<Utc>>,
+}
+
+/// Reloads user-supplied certificates when they change and warns before
+/// they expire
+pub struct CertificateWatcher {
+    config: CertificatesConfig,
+    acceptor: Arc<ReloadableTlsAcceptor>,
+    journal: Journal,
+    renewals: RenewalLog,
+    state: Mutex<WatchState>,
+}
+
+impl CertificateWatcher {
+    pub fn new(
+        config: CertificatesConfig,
+        acceptor: Arc<ReloadableTlsAcceptor>,
+        journal: Journal,
+    ) -> Self {
+        Self {
+            config,
+            acceptor,
+            journal,
+            renewals: RenewalLog::default(),
+            state: Mutex::new(WatchState::default()),
+        }
+    }
+
+    /// Record reloads in `renewals`, where the certificate status reports
+    /// them
+    pub fn with_renewal_log(mut self, renewals: RenewalLog) -> Self {
+        self.renewals = renewals;
+        self
+    }
+
+    /// Scheduler interval matching the configured check interval
+    pub fn default_schedule(&self) -> String {
+        format!(
+            "@every {}s",
+            self.config.check_interval_seconds.max(MIN_CHECK_SECONDS)
+        )
+    }
+
+    /// Certificate currently served, once the files have been checked
+    pub async fn current(&self) -> Option<CertificateInfo> {
+        self.state.lock().await.current.clone()
+    }
+
+    async fn notify(&self, event: JsonValue) {
+        for url in &self.config.webhook_urls {
+            if let Err(e) = enqueue_webhook(&self.journal, url, event.clone()).await {
+                warn!("Failed to queue certificate event for {url}: {e}");
+            }
+        }
+    }
+
+    /// Reload the files if they changed, then warn if the certificate
+    /// served expires soon
+    async fn check(&self, now: DateTime<Utc>) -> Result<()> {
+        let files = match CertificateFiles::resolve(&self.config.source) {
+            Ok(Some(files)) => files,
+            Ok(None) => return Ok(()),
+            Err(e) => {
+                warn!("Keeping the current TLS certificate: {e}");
+                return Ok(());
+            }
+        };
+        let modified = files.modified().await;
+
+        let mut state = self.state.lock().await;
+        if state.files.as_ref() != Some(&files) || state.modified != modified {
+            let result = load_certificate(&files).await;
+            self.renewals.record(MANUAL_CERTIFICATE, &result).await;
+            match result {
+                Ok(loaded) => {
+                    self.acceptor.reload(loaded.acceptor).await;
+                    info!(
+                        "Loaded TLS certificate for {:?}, valid until {}",
+                        loaded.info.domains, loaded.info.not_after
+                    );
+                    state.current = Some(loaded.info);
+                }
+                Err(e) => {
+                    warn!("Keeping the current TLS certificate: {e}");
+                    self.notify(json!({ "type": "certificate_invalid", "error": e.to_string() }))
+                        .await;
+                }
+            }
+            state.files = Some(files);
+            state.modified = modified;
+        }
+
+        let Some(info) = state.current.clone() else {
+            return Ok(());
+        };
+        let days_left = info.days_left(now);
+        if days_left <= self.config.expiry_warning_days as i64
+            && state.warned_for != Some(info.not_after)
+        {
+            warn!(
+                "TLS certificate for {:?} expires in {} day(s), at {}",
+                info.domains, days_left, info.not_after
+            );
+            self.notify(json!({
+                "type": "certificate_expiring",
+                "certificate": info,
+                "days_left": days_left,
+            }))
+            .await;
+            state.warned_for = Some(info.not_after);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ScheduledTask for CertificateWatcher {
+    async fn run(&self) -> Result<()> {
+        self.check(Utc::now()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rcgen::{CertifiedKey, generate_simple_self_signed};
+
+    fn write_pair(dir: &Path, cert: &str, key: &str, domain: &str) {
+        let CertifiedKey {
+            cert: c,
+            signing_key,
+        } = generate_simple_self_signed(vec![domain.to_string()]).unwrap();
+        std::fs::write(dir.join(cert), c.pem()).unwrap();
+        std::fs::write(dir.join(key), signing_key.serialize_pem()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_directory_certificates_validate_and_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = CertificateSource::Directory {
+            path: dir.path().to_path_buf(),
+        };
+        assert!(CertificateFiles::resolve(&source).is_err());
+
+        write_pair(dir.path(), "tls.crt", "tls.key", "gate.example.com");
+        let files = CertificateFiles::resolve(&source).unwrap().unwrap();
+        assert_eq!(files.cert, dir.path().join("tls.crt"));
+        let loaded = load_certificate(&files).await.unwrap();
+        assert_eq!(loaded.info.domains, vec!["gate.example.com"]);
+
+        // A key from another certificate is refused
+        write_pair(dir.path(), "other.crt", "tls.key", "other.example.com");
+        assert!(load_certificate(&files).await.is_err());
+
+        let journal = Journal::open(dir.path().join("journal")).await.unwrap();
+        let config = CertificatesConfig {
+            source,
+            ..Default::default()
+        };
+        let watcher = CertificateWatcher::new(
+            config,
+            Arc::new(ReloadableTlsAcceptor::new(loaded.acceptor)),
+            journal,
+        );
+        // The mismatched pair keeps nothing loaded until it is fixed
+        watcher.check(Utc::now()).await.unwrap();
+        assert!(watcher.current().await.is_none());
+
+        write_pair(dir.path(), "tls.crt", "tls.key", "renewed.example.com");
+        watcher.check(Utc::now()).await.unwrap();
+        let current = watcher.current().await.unwrap();
+        assert_eq!(current.domains, vec!["renewed.example.com"]);
+        assert!(current.days_left(Utc::now()) > 14);
+    }
+}
@@ -0,0 +1,111 @@
+//! Running several replicas behind a load balancer
+//!
+//! Replicas share the state database and keep sessions in JWTs, so any of
+//! them can serve any request, streaming ones included, without sticky
+//! routing. Scheduled work only one of them should do, such as purges and
+//! spend rollups, runs on the replica holding the leader lease in the shared
+//! database. The leader renews its lease while it runs; when it stops,
+//! another replica takes over once the lease lapses.
+//!
+//! What replicas share is limited to a SQLite database on one host. There
+//! are no Postgres or Redis stores, and passkey ceremonies stay in the memory
+//! of the replica that began them; configuration validation says so.
+
+use chrono::{DateTime, Utc};
+use gate_core::StateBackend;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// Name of the lease held by the leader
+const LEADER_LEASE: &str = "leader";
+
+/// Whether this replica leads, kept current by renewing the leader lease
+#[derive(Clone)]
+pub struct LeaderElection {
+    backend: Arc<dyn StateBackend>,
+    node_id: String,
+    lease: Duration,
+    leader: Arc<AtomicBool>,
+}
+
+impl LeaderElection {
+    pub fn new(backend: Arc<dyn StateBackend>, node_id: String, lease: Duration) -> Self {
+        Self {
+            backend,
+            node_id,
+            lease,
+            leader: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn node_id(&self) -> &str {
+        &self.node_id
+    }
+
+    /// Whether this replica held the lease when it last renewed it
+    pub fn is_leader(&self) -> bool {
+        self.leader.load(Ordering::Relaxed)
+    }
+
+    /// Take or renew the lease, returning whether this replica leads. A
+    /// replica that cannot reach the database stops leading.
+    pub async fn renew(&self) -> bool {
+        let lease = chrono::Duration::from_std(self.lease).unwrap_or(chrono::Duration::MAX);
+        let expires_at = Utc::now()
+            .checked_add_signed(lease)
+            .unwrap_or(DateTime::<Utc>::MAX_UTC);
+        let leader = match self
+            .backend
+            .acquire_lease(LEADER_LEASE, &self.node_id, expires_at)
+            .await
+        {
+            Ok(leader) => leader,
+            Err(e) => {
+                warn!("Failed to renew the leader lease: {}", e);
+                false
+            }
+        };
+        if self.leader.swap(leader, Ordering::Relaxed) != leader {
+            if leader {
+                info!("Replica {} is now the leader", self.node_id);
+            } else {
+                info!("Replica {} is no longer the leader", self.node_id);
+            }
+        }
+        leader
+    }
+
+    /// Renew the lease three times per term until the daemon stops
+    pub async fn run(self) {
+        loop {
+            self.renew().await;
+            tokio::time::sleep(self.lease / 3).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gate_sqlx::SqliteStateBackend;
+
+    #[tokio::test]
+    async fn test_one_replica_leads_until_its_lease_lapses() {
+        let backend: Arc<dyn StateBackend> =
+            Arc::new(SqliteStateBackend::new(":memory:").await.unwrap());
+        let lease = Duration::from_millis(200);
+        let a = LeaderElection::new(backend.clone(), "a".to_string(), lease);
+        let b = LeaderElection::new(backend, "b".to_string(), lease);
+
+        assert!(a.renew().await);
+        assert!(!b.renew().await);
+        assert!(a.renew().await);
+        assert!(a.is_leader() && !b.is_leader());
+
+        tokio::time::sleep(lease + Duration::from_millis(50)).await;
+        assert!(b.renew().await);
+        assert!(!a.renew().await);
+        assert!(!a.is_leader());
+    }
+}
@@ -0,0 +1,340 @@
+//! Conversation compression middleware
+//!
+//! Long chats eventually outgrow the model they are sent to. When a request
+//! would fill more than the configured share of its model's context window,
+//! the older turns are summarized by a cheap model, routed like any other
+//! request, and replaced by the summary. System prompts and the most recent
+//! turns are kept verbatim. The response carries a header saying how many
+//! messages were summarized, so clients know the model saw a summary.
+//!
+//! Models whose provider reports no context window are never compressed,
+//! and a failed summary leaves the request as it was.
+
+use crate::error::{DaemonError, Result};
+use async_trait::async_trait;
+use futures::StreamExt;
+use gate_core::router::middleware::{Middleware, Next, RequestStream, ResponseStream};
+use gate_core::router::routing::Router;
+use gate_core::router::service::{estimate_tokens, route_and_execute_json_with_protocol};
+use gate_core::router::sink::{RequestContext, with_headers};
+use gate_core::router::types::{ModelLimits, Protocol, ResponseChunk, StopReason};
+use gate_core::tracing::metrics::counter;
+use serde_json::{Value as JsonValue, json};
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, Weak};
+
+/// Response header giving the number of messages replaced by a summary
+pub const COMPRESSED_HEADER: &str = "x-gate-compressed-messages";
+
+/// Context metadata key set on summary requests, so they are not compressed
+const SUMMARY_REQUEST: &str = "compression_summary";
+
+const SUMMARY_PROMPT: &str = "Summarize the conversation below so it can replace the \
+original messages. Keep facts, names, numbers, decisions, code and open questions; drop \
+pleasantries. Write in the third person and reply with the summary only.";
+
+/// When and how conversations are compressed
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompressionSettings {
+    /// Model that writes summaries
+    pub model: String,
+    /// Share of the context window a request may fill before compression
+    pub threshold: f64,
+    /// Most recent messages always kept verbatim
+    pub keep_recent: usize,
+}
+
+/// Request field holding the conversation for a protocol
+fn messages_key(protocol: Protocol) -> Option<&'static str> {
+    match protocol {
+        Protocol::OpenAIChat | Protocol::Anthropic => Some("messages"),
+        Protocol::OpenAIResponses => Some("input"),
+        _ => None,
+    }
+}
+
+fn is_system(message: &JsonValue) -> bool {
+    matches!(message["role"].as_str(), Some("system" | "developer"))
+}
+
+/// Index of the first message to keep verbatim, if any come before it.
+/// Leading system messages are never summarized, and the kept turns start
+/// at a user message so tool results stay with the calls they answer.
+fn split_point(messages: &[JsonValue], keep_recent: usize) -> Option<usize> {
+    let start = messages.iter().take_while(|m| is_system(m)).count();
+    let mut split = messages.len().saturating_sub(keep_recent.max(1)).max(start);
+    while split > start && messages.get(split).is_some_and(|m| m["role"] != "user") {
+        split -= 1;
+    }
+    (split > start).then_some(split)
+}
+
+/// Text of a message's content, with non-text parts named
+fn message_text(message: &JsonValue) -> String {
+    match &message["content"] {
+        JsonValue::String(text) => text.clone(),
+        JsonValue::Array(parts) => parts
+            .iter()
+            .map(|part| match part["text"].as_str() {
+                Some(text) => text.to_string(),
+                None => format!("[{}]", part["type"].as_str().unwrap_or("content")),
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ if message.get("tool_calls").is_some() => "[tool call]".to_string(),
+        _ => String::new(),
+    }
+}
+
+/// Older turns as a transcript for the summarizer
+fn transcript(messages: &[JsonValue]) -> String {
+    messages
+        .iter()
+        .map(|message| {
+            let role = message["role"]
+                .as_str()
+                .or_else(|| message["type"].as_str())
+                .unwrap_or("message");
+            format!("{role}: {}", message_text(message))
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Replace the messages before `split` (after any leading system messages)
+/// with `summary`
+fn substitute(protocol: Protocol, request: &mut JsonValue, split: usize, summary: &str) {
+    let Some(key) = messages_key(protocol) else {
+        return;
+    };
+    let Some(messages) = request[key].as_array_mut() else {
+        return;
+    };
+    let start = messages.iter().take_while(|m| is_system(m)).count();
+    let summary = format!("Summary of the earlier conversation:\n{summary}");
+    match protocol {
+        // Anthropic keeps its system prompt outside the messages
+        Protocol::Anthropic => {
+            messages.drain(..split);
+            match &mut request["system"] {
+                JsonValue::String(system) if !system.is_empty() => {
+                    system.push_str("\n\n");
+                    system.push_str(&summary);
+                }
+                JsonValue::Array(blocks) => blocks.push(json!({"type": "text", "text": summary})),
+                other => *other = json!(summary),
+            }
+        }
+        _ => {
+            messages.splice(
+                start..split,
+                [json!({"role": "system", "content": summary})],
+            );
+        }
+    }
+}
+
+/// Text of a chat completion, whether returned whole or streamed
+pub(crate) async fn completion_text(mut stream: ResponseStream, model: &str) -> Result<String> {
+    let mut text = String::new();
+    while let Some(chunk) = stream.next().await {
+        match chunk?.parsed() {
+            ResponseChunk::Content(json) => {
+                let choice = &json["choices"][0];
+                let content = choice["message"]["content"]
+                    .as_str()
+                    .or_else(|| choice["delta"]["content"].as_str());
+                text.push_str(content.unwrap_or_default());
+            }
+            ResponseChunk::Stop {
+                reason: StopReason::Error,
+                error,
+                ..
+            } => {
+                return Err(DaemonError::ServiceUnavailable(format!(
+                    "Completion from {model} failed: {}",
+                    error.unwrap_or_default()
+                )));
+            }
+            _ => {}
+        }
+    }
+    if text.trim().is_empty() {
+        return Err(DaemonError::ServiceUnavailable(format!(
+            "Empty completion from {model}"
+        )));
+    }
+    Ok(text)
+}
+
+/// Middleware summarizing older turns of conversations that would overflow
+/// the context window of their model
+pub struct CompressionMiddleware {
+    settings: CompressionSettings,
+    /// Router used to summarize, bound once it has been built
+    router: OnceLock<Weak<Router>>,
+}
+
+impl CompressionMiddleware {
+    pub fn new(settings: CompressionSettings) -> Self {
+        Self {
+            settings,
+            router: OnceLock::new(),
+        }
+    }
+
+    /// Summarize through `router`, which this middleware is part of
+    pub fn bind(&self, router: &Arc<Router>) {
+        let _ = self.router.set(Arc::downgrade(router));
+    }
+
+    /// Index to split the conversation at, if the request needs compressing
+    /// for a model with `limits`
+    fn needs_compression(
+        &self,
+        limits: Option<&ModelLimits>,
+        protocol: Protocol,
+        request: &JsonValue,
+    ) -> Option<usize> {
+        let context = limits?.context_window?;
+        let budget = (f64::from(context) * self.settings.threshold) as u64;
+        if estimate_tokens(request) <= budget {
+            return None;
+        }
+        let messages = request.get(messages_key(protocol)?)?.as_array()?;
+        split_point(messages, self.settings.keep_recent)
+    }
+
+    async fn summarize(&self, ctx: &RequestContext, messages: &[JsonValue]) -> Result<String> {
+        let router =
+            self.router.get().and_then(Weak::upgrade).ok_or_else(|| {
+                DaemonError::ServiceUnavailable("Router is not bound".to_string())
+            })?;
+        let mut ctx = ctx.clone();
+        ctx.metadata
+            .insert(SUMMARY_REQUEST.to_string(), "true".to_string());
+        let request = json!({
+            "model": self.settings.model,
+            "stream": false,
+            "messages": [
+                {"role": "system", "content": SUMMARY_PROMPT},
+                {"role": "user", "content": transcript(messages)}
+            ]
+        });
+        let stream =
+            route_and_execute_json_with_protocol(&router, &ctx, Protocol::OpenAIChat, request)
+                .await?;
+        completion_text(stream, &self.settings.model).await
+    }
+}
+
+#[async_trait]
+impl Middleware for CompressionMiddleware {
+    async fn process(
+        &self,
+        ctx: &mut RequestContext,
+        mut request: RequestStream,
+        next: Next,
+    ) -> gate_core::Result<ResponseStream> {
+        let protocol = request.protocol();
+        if messages_key(protocol).is_none() || ctx.metadata.contains_key(SUMMARY_REQUEST) {
+            return next(request).await;
+        }
+        let Some(first) = request.next().await else {
+            return next(request).await;
+        };
+        let mut json = first?;
+
+        let mut compressed = 0;
+        if let Some(split) = self.needs_compression(ctx.extensions.get(), protocol, &json) {
+            let key = messages_key(protocol).unwrap_or("messages");
+            let messages = json[key].as_array().cloned().unwrap_or_default();
+            let start = messages.iter().take_while(|m| is_system(m)).count();
+            // Send the request as it is rather than fail it
+            match self.summarize(ctx, &messages[start..split]).await {
+                Ok(summary) => {
+                    substitute(protocol, &mut json, split, &summary);
+                    compressed = split - start;
+                    debug!(
+                        "Summarized {compressed} messages with {}",
+                        self.settings.model
+                    );
+                    counter("conversation_compressions_total").increment();
+                    counter("conversation_compressed_messages_total").add(compressed as u64);
+                }
+                Err(e) => warn!("Conversation compression failed: {e}"),
+            }
+        }
+
+        let rebuilt = RequestStream::new(
+            protocol,
+            Box::pin(futures::stream::once(async move { Ok(json) }).chain(request)),
+        );
+        let response = next(rebuilt).await?;
+        if compressed == 0 {
+            return Ok(response);
+        }
+        let headers = HashMap::from([(COMPRESSED_HEADER.to_string(), compressed.to_string())]);
+        Ok(with_headers(response, headers).await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(role: &str, content: &str) -> JsonValue {
+        json!({"role": role, "content": content})
+    }
+
+    #[test]
+    fn test_split_keeps_system_prompt_and_starts_at_a_user_turn() {
+        let messages = vec![
+            message("system", "be brief"),
+            message("user", "one"),
+            message("assistant", "two"),
+            message("user", "three"),
+            message("assistant", "four"),
+            message("tool", "five"),
+            message("assistant", "six"),
+        ];
+        // Keeping three would start mid-turn, so four are kept
+        assert_eq!(split_point(&messages, 3), Some(3));
+        assert_eq!(split_point(&messages, 6), None);
+        // The latest message is always kept
+        assert_eq!(split_point(&messages[..2], 0), None);
+
+        let mut request = json!({"model": "gpt-4o", "messages": messages});
+        substitute(Protocol::OpenAIChat, &mut request, 3, "they counted");
+        let kept = request["messages"].as_array().unwrap();
+        assert_eq!(kept.len(), 6);
+        assert_eq!(kept[0]["content"], "be brief");
+        assert_eq!(
+            kept[1]["content"],
+            "Summary of the earlier conversation:\nthey counted"
+        );
+        assert_eq!(kept[2]["content"], "three");
+    }
+
+    #[test]
+    fn test_anthropic_summary_goes_in_the_system_prompt() {
+        let mut request = json!({
+            "system": "be brief",
+            "messages": [
+                message("user", "one"),
+                message("assistant", "two"),
+                message("user", "three")
+            ]
+        });
+        assert_eq!(
+            transcript(request["messages"].as_array().unwrap()),
+            "user: one\n\nassistant: two\n\nuser: three"
+        );
+        substitute(Protocol::Anthropic, &mut request, 2, "they counted");
+        assert_eq!(request["messages"].as_array().unwrap().len(), 1);
+        assert_eq!(
+            request["system"],
+            "be brief\n\nSummary of the earlier conversation:\nthey counted"
+        );
+    }
+}
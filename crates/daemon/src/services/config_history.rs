@@ -0,0 +1,182 @@
+//! Revisions of the configuration
+//!
+//! Every configuration the daemon applies is kept as a numbered revision
+//! along with who applied it, so changes can be reviewed and a bad one
+//! undone by re-applying an earlier revision. Revisions are JSON files under
+//! the state directory; only the newest [`MAX_REVISIONS`] are kept.
+
+use crate::Settings;
+use crate::error::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Revisions kept before the oldest are pruned
+pub const MAX_REVISIONS: usize = 200;
+
+/// Author of the configuration loaded at startup
+pub const SYSTEM_AUTHOR: &str = "system";
+
+/// When and by whom a revision was applied
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfigRevisionInfo {
+    pub revision: u64,
+    pub author: String,
+    pub created_at: DateTime<Utc>,
+    /// Revision this one restored, when it was a rollback
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rollback_of: Option<u64>,
+}
+
+/// A configuration as it was applied
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigRevision {
+    #[serde(flatten)]
+    pub info: ConfigRevisionInfo,
+    pub settings: Settings,
+}
+
+/// Applied configurations stored on disk
+#[derive(Debug, Clone)]
+pub struct ConfigHistory {
+    dir: PathBuf,
+}
+
+impl ConfigHistory {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, revision: u64) -> PathBuf {
+        self.dir.join(format!("{revision:08}.json"))
+    }
+
+    /// Stored revision numbers, oldest first
+    async fn revisions(&self) -> Result<Vec<u64>> {
+        let mut entries = match tokio::fs::read_dir(&self.dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut revisions = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            let name = entry.file_name();
+            if let Some(revision) = name
+                .to_str()
+                .and_then(|n| n.strip_suffix(".json"))
+                .and_then(|n| n.parse().ok())
+            {
+                revisions.push(revision);
+            }
+        }
+        revisions.sort_unstable();
+        Ok(revisions)
+    }
+
+    /// A revision, if it is still kept
+    pub async fn get(&self, revision: u64) -> Result<Option<ConfigRevision>> {
+        match tokio::fs::read(self.path(revision)).await {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// The revision applied most recently
+    pub async fn latest(&self) -> Result<Option<ConfigRevision>> {
+        match self.revisions().await?.last() {
+            Some(&revision) => self.get(revision).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Kept revisions without their settings, newest first
+    pub async fn list(&self) -> Result<Vec<ConfigRevisionInfo>> {
+        let mut infos = Vec::new();
+        for revision in self.revisions().await?.into_iter().rev() {
+            if let Some(stored) = self.get(revision).await? {
+                infos.push(stored.info);
+            }
+        }
+        Ok(infos)
+    }
+
+    /// Record `settings` as applied by `author`. Nothing is recorded when
+    /// they match the latest revision.
+    pub async fn record(
+        &self,
+        author: &str,
+        settings: &Settings,
+        rollback_of: Option<u64>,
+    ) -> Result<Option<ConfigRevisionInfo>> {
+        let revisions = self.revisions().await?;
+        if let Some(&last) = revisions.last()
+            && let Some(latest) = self.get(last).await?
+            && serde_json::to_value(&latest.settings)? == serde_json::to_value(settings)?
+        {
+            return Ok(None);
+        }
+
+        let info = ConfigRevisionInfo {
+            revision: revisions.last().map_or(1, |last| last + 1),
+            author: author.to_string(),
+            created_at: Utc::now(),
+            rollback_of,
+        };
+        let stored = ConfigRevision {
+            info: info.clone(),
+            settings: settings.clone(),
+        };
+        tokio::fs::create_dir_all(&self.dir).await?;
+        tokio::fs::write(
+            self.path(info.revision),
+            serde_json::to_vec_pretty(&stored)?,
+        )
+        .await?;
+
+        let excess = (revisions.len() + 1).saturating_sub(MAX_REVISIONS);
+        for &revision in &revisions[..excess] {
+            if let Err(e) = tokio::fs::remove_file(self.path(revision)).await {
+                tracing::warn!("Failed to prune config revision {}: {}", revision, e);
+            }
+        }
+        Ok(Some(info))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_record_skips_unchanged_settings_and_lists_newest_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = ConfigHistory::new(dir.path());
+        let mut settings = Settings::default();
+
+        let first = history
+            .record(SYSTEM_AUTHOR, &settings, None)
+            .await
+            .unwrap();
+        assert_eq!(first.unwrap().revision, 1);
+        assert!(
+            history
+                .record("alice", &settings, None)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        settings.server.port = 8080;
+        let second = history.record("alice", &settings, None).await.unwrap();
+        assert_eq!(second.unwrap().revision, 2);
+
+        let listed = history.list().await.unwrap();
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0].author, "alice");
+        assert_eq!(listed[1].author, SYSTEM_AUTHOR);
+        let latest = history.latest().await.unwrap().unwrap();
+        assert_eq!(latest.settings.server.port, 8080);
+        assert!(history.get(3).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,68 @@
+//! Content filter policy from settings
+//!
+//! Responses a provider filtered are reported to the webhooks in
+//! `routing.content_filter`, as `content_filtered` events naming the request,
+//! the sink and the provider's reason. Retry providers are named as in
+//! `providers` and matched against the sinks they are registered as.
+
+use crate::config::{ContentFilterConfig, ProviderConfig};
+use crate::daemon::server::format_provider_sink_id;
+use crate::services::journal::Journal;
+use crate::services::webhooks::enqueue_webhook;
+use gate_core::router::content_filter::{
+    ContentFilterEvent, ContentFilterNotifier, ContentFilterPolicy,
+};
+use serde_json::json;
+use std::sync::Arc;
+
+/// Queues a webhook event for each filtered response
+pub struct WebhookFilterNotifier {
+    journal: Journal,
+    urls: Vec<String>,
+}
+
+impl ContentFilterNotifier for WebhookFilterNotifier {
+    fn notify(&self, event: &ContentFilterEvent) {
+        let journal = self.journal.clone();
+        let urls = self.urls.clone();
+        let event = json!({ "type": "content_filtered", "response": event });
+        tokio::spawn(async move {
+            for url in &urls {
+                if let Err(e) = enqueue_webhook(&journal, url, event.clone()).await {
+                    warn!("Failed to queue content filter event for {url}: {e}");
+                }
+            }
+        });
+    }
+}
+
+/// Policy `config` describes for the configured `providers`, reporting to
+/// its webhooks through `journal`
+pub fn content_filter_policy(
+    config: &ContentFilterConfig,
+    providers: &[ProviderConfig],
+    journal: Option<&Journal>,
+) -> ContentFilterPolicy {
+    let notifier: Option<Arc<dyn ContentFilterNotifier>> = match journal {
+        Some(journal) if !config.webhook_urls.is_empty() => Some(Arc::new(WebhookFilterNotifier {
+            journal: journal.clone(),
+            urls: config.webhook_urls.clone(),
+        })),
+        _ => None,
+    };
+    ContentFilterPolicy {
+        log: config.log,
+        retry_sinks: config
+            .retry_providers
+            .iter()
+            .filter_map(|name| {
+                let provider = providers.iter().find(|provider| &provider.name == name);
+                if provider.is_none() {
+                    warn!("Content filter retry provider {name} is not configured");
+                }
+                provider.map(|provider| format_provider_sink_id(&provider.provider, name))
+            })
+            .collect(),
+        notifier,
+    }
+}
@@ -0,0 +1,256 @@
+//! Cross-origin request policy
+//!
+//! Browsers on other origins may call the daemon only from origins listed in
+//! `server.cors_origins`, or `server.cors.admin_origins` for admin routes.
+//! Entries are exact origins (`https://app.example.com`), subdomain
+//! wildcards (`https://*.example.com`) or `*` for any origin. Credentials
+//! (cookies) are only allowed on auth and admin routes when enabled, and
+//! never for an origin that matched `*`; inference and other API routes
+//! authenticate with bearer tokens and never receive them. The policy wraps
+//! the whole application, so routes are classed by their path below
+//! `server.base_path`.
+
+use crate::config::ServerConfig;
+use axum::http::{HeaderName, HeaderValue, Method, header, request::Parts};
+use std::sync::Arc;
+use std::time::Duration;
+use tower_http::cors::{AllowCredentials, AllowOrigin, CorsLayer};
+
+/// Route classes with their own policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteClass {
+    /// Sign-in, registration and pairing
+    Auth,
+    /// Configuration and administration
+    Admin,
+    /// Inference and everything else
+    Api,
+}
+
+impl RouteClass {
+    /// Class of the route at `path`, as requested under `base_path`
+    pub fn of(path: &str, base_path: &str) -> Self {
+        let path = path
+            .strip_prefix(base_path)
+            .filter(|rest| rest.starts_with('/'))
+            .unwrap_or(path);
+        if path.starts_with("/auth/") || path.starts_with("/api/auth/") {
+            RouteClass::Auth
+        } else if crate::services::server_mode::ADMIN_PREFIXES
+            .iter()
+            .any(|prefix| path.starts_with(prefix))
+        {
+            RouteClass::Admin
+        } else {
+            RouteClass::Api
+        }
+    }
+}
+
+/// How an origin matched an allow-list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OriginMatch {
+    /// Listed, exactly or by a subdomain wildcard
+    Listed,
+    /// Allowed only by `*`
+    Any,
+}
+
+/// Origins allowed by one allow-list
+#[derive(Debug, Clone, Default)]
+struct OriginList {
+    any: bool,
+    exact: Vec<String>,
+    /// `(scheme, domain)` of `scheme://*.domain` entries
+    wildcards: Vec<(String, String)>,
+}
+
+impl OriginList {
+    fn new(entries: &[String]) -> Self {
+        let mut list = Self::default();
+        for entry in entries {
+            let entry = entry.trim().trim_end_matches('/').to_ascii_lowercase();
+            if entry == "*" {
+                list.any = true;
+            } else if let Some((scheme, domain)) = entry
+                .split_once("://")
+                .and_then(|(scheme, host)| Some((scheme, host.strip_prefix("*.")?)))
+            {
+                list.wildcards
+                    .push((scheme.to_string(), domain.to_string()));
+            } else if !entry.is_empty() {
+                list.exact.push(entry);
+            }
+        }
+        list
+    }
+
+    fn matches(&self, origin: &str) -> Option<OriginMatch> {
+        let origin = origin.to_ascii_lowercase();
+        if self.exact.contains(&origin) {
+            return Some(OriginMatch::Listed);
+        }
+        if let Some((scheme, host)) = origin.split_once("://") {
+            let listed = self.wildcards.iter().any(|(s, domain)| {
+                s == scheme
+                    && host
+                        .strip_suffix(domain.as_str())
+                        .is_some_and(|sub| sub.len() > 1 && sub.ends_with('.'))
+            });
+            if listed {
+                return Some(OriginMatch::Listed);
+            }
+        }
+        self.any.then_some(OriginMatch::Any)
+    }
+}
+
+/// Cross-origin policy for every route class
+#[derive(Debug, Clone)]
+pub struct CorsPolicy {
+    origins: OriginList,
+    admin_origins: OriginList,
+    allow_credentials: bool,
+    max_age: Duration,
+    base_path: String,
+}
+
+impl CorsPolicy {
+    pub fn new(config: &ServerConfig) -> Self {
+        Self {
+            origins: OriginList::new(&config.cors_origins),
+            admin_origins: OriginList::new(&config.cors.admin_origins),
+            allow_credentials: config.cors.allow_credentials,
+            max_age: Duration::from_secs(config.cors.max_age_seconds),
+            base_path: config.base_path.clone(),
+        }
+    }
+
+    fn origin_match(&self, origin: &str, class: RouteClass) -> Option<OriginMatch> {
+        match class {
+            RouteClass::Admin => self.admin_origins.matches(origin),
+            RouteClass::Auth | RouteClass::Api => self.origins.matches(origin),
+        }
+    }
+
+    /// Whether `origin` may call `path`
+    pub fn allows_origin(&self, origin: &str, path: &str) -> bool {
+        self.origin_match(origin, RouteClass::of(path, &self.base_path))
+            .is_some()
+    }
+
+    /// Whether `origin` may send credentials to `path`
+    pub fn allows_credentials(&self, origin: &str, path: &str) -> bool {
+        let class = RouteClass::of(path, &self.base_path);
+        self.allow_credentials
+            && class != RouteClass::Api
+            && self.origin_match(origin, class) == Some(OriginMatch::Listed)
+    }
+
+    /// Layer enforcing the policy and answering preflight requests
+    pub fn layer(self) -> CorsLayer {
+        let max_age = self.max_age;
+        let policy = Arc::new(self);
+        let origin_policy = policy.clone();
+        CorsLayer::new()
+            .allow_origin(AllowOrigin::predicate(
+                move |origin: &HeaderValue, parts: &Parts| {
+                    origin
+                        .to_str()
+                        .is_ok_and(|origin| origin_policy.allows_origin(origin, parts.uri.path()))
+                },
+            ))
+            .allow_credentials(AllowCredentials::predicate(
+                move |origin: &HeaderValue, parts: &Parts| {
+                    origin
+                        .to_str()
+                        .is_ok_and(|origin| policy.allows_credentials(origin, parts.uri.path()))
+                },
+            ))
+            .allow_methods([
+                Method::GET,
+                Method::POST,
+                Method::PUT,
+                Method::PATCH,
+                Method::DELETE,
+                Method::OPTIONS,
+            ])
+            .allow_headers([
+                header::CONTENT_TYPE,
+                header::AUTHORIZATION,
+                HeaderName::from_static("x-correlation-id"),
+                HeaderName::from_static("x-api-key"),
+                HeaderName::from_static("traceparent"),
+                HeaderName::from_static("tracestate"),
+            ])
+            .expose_headers([
+                HeaderName::from_static("x-correlation-id"),
+                HeaderName::from_static("traceparent"),
+                HeaderName::from_static("tracestate"),
+            ])
+            .max_age(max_age)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(origins: &[&str], admin_origins: &[&str]) -> CorsPolicy {
+        let mut config = ServerConfig::default();
+        config.cors_origins = origins.iter().map(|o| o.to_string()).collect();
+        config.cors.admin_origins = admin_origins.iter().map(|o| o.to_string()).collect();
+        config.cors.allow_credentials = true;
+        CorsPolicy::new(&config)
+    }
+
+    #[test]
+    fn test_origins_match_exactly_or_by_subdomain_wildcard() {
+        let policy = policy(&["https://app.example.com/", "https://*.hellas.ai"], &[]);
+        assert!(policy.allows_origin("https://app.example.com", "/v1/models"));
+        assert!(policy.allows_origin("https://a.b.hellas.ai", "/v1/models"));
+        assert!(!policy.allows_origin("https://hellas.ai", "/v1/models"));
+        assert!(!policy.allows_origin("https://evilhellas.ai", "/v1/models"));
+        assert!(!policy.allows_origin("http://x.hellas.ai", "/v1/models"));
+        assert!(!policy.allows_origin("https://other.example.com", "/v1/models"));
+    }
+
+    #[test]
+    fn test_route_classes_have_their_own_policy() {
+        let policy = policy(
+            &["*", "https://app.example.com"],
+            &["https://admin.example.com"],
+        );
+
+        // Admin routes only accept their own list
+        assert!(!policy.allows_origin("https://app.example.com", "/api/config"));
+        assert!(policy.allows_credentials("https://admin.example.com", "/api/admin/mode"));
+
+        // Credentials go to listed origins on auth routes, never to `*` or
+        // API routes
+        assert!(policy.allows_credentials(
+            "https://app.example.com",
+            "/auth/webauthn/authenticate/start"
+        ));
+        assert!(policy.allows_origin("https://anywhere.test", "/auth/bootstrap/status"));
+        assert!(!policy.allows_credentials("https://anywhere.test", "/auth/bootstrap/status"));
+        assert!(!policy.allows_credentials("https://app.example.com", "/v1/chat/completions"));
+    }
+
+    #[test]
+    fn test_routes_are_classed_below_the_base_path() {
+        assert_eq!(
+            RouteClass::of("/gate/api/config", "/gate"),
+            RouteClass::Admin
+        );
+        assert_eq!(
+            RouteClass::of("/gate/auth/bootstrap", "/gate"),
+            RouteClass::Auth
+        );
+        assert_eq!(
+            RouteClass::of("/gateway/api/config", "/gate"),
+            RouteClass::Api
+        );
+        assert_eq!(RouteClass::of("/api/config", ""), RouteClass::Admin);
+    }
+}
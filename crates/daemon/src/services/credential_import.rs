@@ -0,0 +1,312 @@
+//! Import provider keys from other tools installed on this machine
+//!
+//! Detection only checks whether a source exists; key material is read
+//! solely when the user asks to import a source.
+
+use crate::config::{ProviderConfig, ProviderType, Settings};
+use crate::error::DaemonError;
+use directories::BaseDirs;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+const ANTHROPIC_KEY_ENV: &str = "ANTHROPIC_API_KEY";
+const OPENAI_KEY_ENV: &str = "OPENAI_API_KEY";
+const ANTHROPIC_BASE_URL: &str = "https://api.anthropic.com";
+const OPENAI_BASE_URL: &str = "https://api.openai.com";
+const DEFAULT_TIMEOUT_SECONDS: u64 = 600;
+
+/// Place a provider key can be imported from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CredentialSource {
+    /// `~/.config/anthropic/api_key`
+    AnthropicConfig,
+    /// `ANTHROPIC_API_KEY` in the daemon's environment
+    AnthropicEnv,
+    /// `OPENAI_API_KEY` in the daemon's environment
+    OpenAIEnv,
+    /// Claude Code OAuth credentials or API key
+    ClaudeCode,
+    /// Codex CLI `auth.json`
+    Codex,
+}
+
+impl CredentialSource {
+    pub const ALL: [CredentialSource; 5] = [
+        CredentialSource::AnthropicConfig,
+        CredentialSource::AnthropicEnv,
+        CredentialSource::OpenAIEnv,
+        CredentialSource::ClaudeCode,
+        CredentialSource::Codex,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CredentialSource::AnthropicConfig => "Anthropic config directory",
+            CredentialSource::AnthropicEnv => "ANTHROPIC_API_KEY environment variable",
+            CredentialSource::OpenAIEnv => "OPENAI_API_KEY environment variable",
+            CredentialSource::ClaudeCode => "Claude Code",
+            CredentialSource::Codex => "Codex CLI",
+        }
+    }
+
+    pub fn provider(&self) -> ProviderType {
+        match self {
+            CredentialSource::AnthropicConfig
+            | CredentialSource::AnthropicEnv
+            | CredentialSource::ClaudeCode => ProviderType::Anthropic,
+            CredentialSource::OpenAIEnv | CredentialSource::Codex => ProviderType::OpenAI,
+        }
+    }
+
+    /// Base name for the provider entry created from this source
+    fn provider_name(&self) -> &'static str {
+        match self.provider() {
+            ProviderType::Anthropic => "anthropic",
+            ProviderType::OpenAI | ProviderType::Custom | ProviderType::Mock => "openai",
+        }
+    }
+
+    fn env_var(&self) -> Option<&'static str> {
+        match self {
+            CredentialSource::AnthropicEnv => Some(ANTHROPIC_KEY_ENV),
+            CredentialSource::OpenAIEnv => Some(OPENAI_KEY_ENV),
+            _ => None,
+        }
+    }
+
+    /// Candidate files relative to the home directory, in preference order
+    fn files(&self) -> &'static [&'static str] {
+        match self {
+            CredentialSource::AnthropicConfig => &[".config/anthropic/api_key"],
+            CredentialSource::ClaudeCode => &[".claude/.credentials.json", ".claude.json"],
+            CredentialSource::Codex => &[".codex/auth.json"],
+            CredentialSource::AnthropicEnv | CredentialSource::OpenAIEnv => &[],
+        }
+    }
+}
+
+/// A source found on this machine; never includes the key itself
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectedCredential {
+    pub source: CredentialSource,
+    pub label: String,
+    pub provider: ProviderType,
+    /// Where the key would be read from
+    pub location: String,
+}
+
+pub struct CredentialImportService {
+    home: Option<PathBuf>,
+}
+
+impl CredentialImportService {
+    pub fn new() -> Self {
+        Self {
+            home: BaseDirs::new().map(|dirs| dirs.home_dir().to_path_buf()),
+        }
+    }
+
+    /// Look for sources under a specific home directory
+    pub fn with_home(home: impl Into<PathBuf>) -> Self {
+        Self {
+            home: Some(home.into()),
+        }
+    }
+
+    /// List sources that exist, without reading any key material
+    pub fn detect(&self) -> Vec<DetectedCredential> {
+        CredentialSource::ALL
+            .iter()
+            .filter_map(|source| {
+                let location = self.location(*source)?;
+                Some(DetectedCredential {
+                    source: *source,
+                    label: source.label().to_string(),
+                    provider: source.provider(),
+                    location,
+                })
+            })
+            .collect()
+    }
+
+    /// Read the key from a source the user consented to import
+    pub fn read(&self, source: CredentialSource) -> Result<String, DaemonError> {
+        if let Some(var) = source.env_var() {
+            return std::env::var(var)
+                .ok()
+                .filter(|key| !key.trim().is_empty())
+                .map(|key| key.trim().to_string())
+                .ok_or_else(|| DaemonError::ConfigError(format!("{var} is not set")));
+        }
+
+        for path in self.existing_files(source) {
+            let content = std::fs::read_to_string(&path)?;
+            if let Some(key) = extract_key(source, &content) {
+                return Ok(key);
+            }
+        }
+        Err(DaemonError::ConfigError(format!(
+            "No key found for {}",
+            source.label()
+        )))
+    }
+
+    fn location(&self, source: CredentialSource) -> Option<String> {
+        if let Some(var) = source.env_var() {
+            return std::env::var(var)
+                .is_ok_and(|key| !key.trim().is_empty())
+                .then(|| format!("${var}"));
+        }
+        self.existing_files(source)
+            .next()
+            .map(|path| path.display().to_string())
+    }
+
+    fn existing_files(&self, source: CredentialSource) -> impl Iterator<Item = PathBuf> + '_ {
+        source
+            .files()
+            .iter()
+            .filter_map(|file| self.home.as_deref().map(|home| home.join(file)))
+            .filter(|path| Path::is_file(path))
+    }
+}
+
+impl Default for CredentialImportService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Pull a key out of a source file's contents
+fn extract_key(source: CredentialSource, content: &str) -> Option<String> {
+    let trimmed = content.trim();
+    let json = serde_json::from_str::<serde_json::Value>(trimmed).ok();
+    let pointers: &[&str] = match source {
+        CredentialSource::AnthropicConfig => &["/api_key"],
+        CredentialSource::ClaudeCode => &["/claudeAiOauth/accessToken", "/primaryApiKey"],
+        CredentialSource::Codex => &["/OPENAI_API_KEY"],
+        CredentialSource::AnthropicEnv | CredentialSource::OpenAIEnv => &[],
+    };
+
+    let from_json = json.as_ref().and_then(|json| {
+        pointers
+            .iter()
+            .find_map(|pointer| json.pointer(pointer).and_then(|v| v.as_str()))
+    });
+    match (from_json, &json, source) {
+        (Some(key), _, _) => Some(key.trim().to_string()),
+        // The Anthropic config file may hold the bare key
+        (None, None, CredentialSource::AnthropicConfig) => Some(trimmed.to_string()),
+        _ => None,
+    }
+    .filter(|key| !key.is_empty())
+}
+
+/// Provider entry for an imported key, or `None` if the key is already configured
+pub fn provider_config_for(
+    settings: &Settings,
+    source: CredentialSource,
+    key: String,
+) -> Option<ProviderConfig> {
+    if settings
+        .providers
+        .iter()
+        .any(|p| p.api_key.as_deref() == Some(key.as_str()))
+    {
+        return None;
+    }
+
+    let base_name = source.provider_name();
+    let mut name = base_name.to_string();
+    let mut i = 1u32;
+    while settings.providers.iter().any(|p| p.name == name) {
+        name = format!("{base_name}-{i}");
+        i += 1;
+    }
+
+    let provider = source.provider();
+    let base_url = match provider {
+        ProviderType::Anthropic => ANTHROPIC_BASE_URL,
+        ProviderType::OpenAI | ProviderType::Custom | ProviderType::Mock => OPENAI_BASE_URL,
+    };
+    Some(ProviderConfig {
+        name,
+        provider,
+        base_url: base_url.to_string(),
+        api_key: Some(key),
+        timeout_seconds: DEFAULT_TIMEOUT_SECONDS,
+        models: vec![],
+        mock: None,
+        sign_requests: false,
+        node_id: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(home: &Path, file: &str, content: &str) {
+        let path = home.join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_detect_and_read_file_sources() {
+        let home = tempfile::tempdir().unwrap();
+        write(home.path(), ".config/anthropic/api_key", "sk-ant-plain\n");
+        write(
+            home.path(),
+            ".claude/.credentials.json",
+            r#"{"claudeAiOauth": {"accessToken": "sk-ant-oat01-abc"}}"#,
+        );
+        write(
+            home.path(),
+            ".codex/auth.json",
+            r#"{"OPENAI_API_KEY": null}"#,
+        );
+
+        let service = CredentialImportService::with_home(home.path());
+        let detected: Vec<_> = service.detect().into_iter().map(|d| d.source).collect();
+        assert!(detected.contains(&CredentialSource::AnthropicConfig));
+        assert!(detected.contains(&CredentialSource::ClaudeCode));
+        assert!(detected.contains(&CredentialSource::Codex));
+
+        assert_eq!(
+            service.read(CredentialSource::AnthropicConfig).unwrap(),
+            "sk-ant-plain"
+        );
+        assert_eq!(
+            service.read(CredentialSource::ClaudeCode).unwrap(),
+            "sk-ant-oat01-abc"
+        );
+        // Codex signed in with ChatGPT rather than an API key
+        assert!(service.read(CredentialSource::Codex).is_err());
+    }
+
+    #[test]
+    fn test_provider_config_skips_known_keys_and_dedups_names() {
+        let mut settings = Settings::default();
+        settings.providers.push(ProviderConfig {
+            name: "anthropic".into(),
+            provider: ProviderType::Anthropic,
+            base_url: ANTHROPIC_BASE_URL.into(),
+            api_key: Some("existing".into()),
+            timeout_seconds: DEFAULT_TIMEOUT_SECONDS,
+            models: vec![],
+            mock: None,
+            sign_requests: false,
+            node_id: None,
+        });
+
+        assert!(
+            provider_config_for(&settings, CredentialSource::AnthropicEnv, "existing".into())
+                .is_none()
+        );
+        let config =
+            provider_config_for(&settings, CredentialSource::AnthropicEnv, "new".into()).unwrap();
+        assert_eq!(config.name, "anthropic-1");
+    }
+}
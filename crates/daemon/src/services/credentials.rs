@@ -0,0 +1,298 @@
+//! Health of stored provider credentials
+//!
+//! Each provider key or OAuth token in the configuration is checked with a
+//! cheap authenticated call to the provider's model list. Alongside whether
+//! the key was accepted, the report carries the rate limits the provider
+//! disclosed in its response headers and, for tokens that carry one, when
+//! the token expires, so keys can be rotated before they stop working.
+
+use crate::config::{ProviderConfig, ProviderType};
+use crate::services::doctor::{
+    ANTHROPIC_API_KEY_HEADER, ANTHROPIC_VERSION_HEADER, ANTHROPIC_VERSION_VALUE, models_url,
+};
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use reqwest::header::{AUTHORIZATION, HeaderMap};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Prefix of Anthropic OAuth access tokens, which are sent as bearer tokens
+/// with the OAuth beta enabled
+const ANTHROPIC_OAUTH_PREFIX: &str = "sk-ant-oat";
+const ANTHROPIC_BETA_HEADER: &str = "anthropic-beta";
+const ANTHROPIC_BETA_OAUTH: &str = "oauth-2025-04-20";
+
+/// Rate-limit header prefixes, by provider: OpenAI sends
+/// `x-ratelimit-{limit,remaining,reset}-{resource}`, Anthropic
+/// `anthropic-ratelimit-{resource}-{limit,remaining,reset}`
+const OPENAI_RATELIMIT_PREFIX: &str = "x-ratelimit-";
+const ANTHROPIC_RATELIMIT_PREFIX: &str = "anthropic-ratelimit-";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CredentialKind {
+    ApiKey,
+    OauthToken,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CredentialValidity {
+    /// The provider accepted the credential
+    Valid,
+    /// The provider refused the credential
+    Rejected,
+    /// The provider could not be reached or answered unexpectedly
+    Unknown,
+    /// The provider cannot be checked, e.g. custom or mock providers
+    Unchecked,
+}
+
+/// A limit the provider reported for the credential
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimit {
+    /// What is limited, e.g. `requests` or `tokens`
+    pub resource: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remaining: Option<u64>,
+    /// When the limit resets, as the provider wrote it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reset: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CredentialStatus {
+    /// Name of the secret, e.g. `providers.openai.api_key`
+    pub name: String,
+    pub provider: String,
+    pub provider_type: ProviderType,
+    pub kind: CredentialKind,
+    /// Last characters of the credential, to tell keys apart
+    pub hint: String,
+    pub validity: CredentialValidity,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub limits: Vec<RateLimit>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Sinks that send requests with this credential
+    pub sinks: Vec<String>,
+    pub checked_at: DateTime<Utc>,
+}
+
+/// Checks provider credentials against their providers
+pub struct CredentialChecker {
+    client: reqwest::Client,
+}
+
+impl Default for CredentialChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CredentialChecker {
+    pub fn new() -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(CHECK_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        Self { client }
+    }
+
+    /// Check the credential of `provider`, or `None` when it has none
+    pub async fn check(&self, provider: &ProviderConfig) -> Option<CredentialStatus> {
+        let key = provider.api_key.as_deref().filter(|key| !key.is_empty())?;
+        let mut status = CredentialStatus {
+            name: format!("providers.{}.api_key", provider.name),
+            provider: provider.name.clone(),
+            provider_type: provider.provider.clone(),
+            kind: credential_kind(key),
+            hint: hint(key),
+            validity: CredentialValidity::Unchecked,
+            message: None,
+            limits: Vec::new(),
+            expires_at: token_expiry(key),
+            sinks: Vec::new(),
+            checked_at: Utc::now(),
+        };
+
+        let request = match provider.provider {
+            ProviderType::Anthropic => {
+                let request = self
+                    .client
+                    .get(models_url(&provider.base_url))
+                    .header(ANTHROPIC_VERSION_HEADER, ANTHROPIC_VERSION_VALUE);
+                if status.kind == CredentialKind::OauthToken {
+                    request
+                        .header(AUTHORIZATION, format!("Bearer {key}"))
+                        .header(ANTHROPIC_BETA_HEADER, ANTHROPIC_BETA_OAUTH)
+                } else {
+                    request.header(ANTHROPIC_API_KEY_HEADER, key)
+                }
+            }
+            ProviderType::OpenAI => self
+                .client
+                .get(models_url(&provider.base_url))
+                .header(AUTHORIZATION, format!("Bearer {key}")),
+            ProviderType::Custom | ProviderType::Mock => {
+                status.message = Some(format!("{} providers cannot be checked", provider.provider));
+                return Some(status);
+            }
+        };
+
+        match request.send().await {
+            Ok(response) => {
+                let code = response.status();
+                status.limits = rate_limits(response.headers());
+                (status.validity, status.message) = match code {
+                    s if s.is_success() => (CredentialValidity::Valid, None),
+                    StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => (
+                        CredentialValidity::Rejected,
+                        Some(format!("Rejected by the provider ({code})")),
+                    ),
+                    StatusCode::TOO_MANY_REQUESTS => (
+                        CredentialValidity::Valid,
+                        Some("Accepted, but rate limited".to_string()),
+                    ),
+                    _ => (
+                        CredentialValidity::Unknown,
+                        Some(format!("Unexpected status {code}")),
+                    ),
+                };
+            }
+            Err(e) => {
+                status.validity = CredentialValidity::Unknown;
+                status.message = Some(format!("{} is unreachable: {e}", provider.base_url));
+            }
+        }
+        if let Some(expires_at) = status.expires_at
+            && expires_at <= status.checked_at
+            && status.validity != CredentialValidity::Valid
+        {
+            status.message = Some(format!("Token expired at {expires_at}"));
+        }
+        Some(status)
+    }
+}
+
+fn credential_kind(key: &str) -> CredentialKind {
+    if key.starts_with(ANTHROPIC_OAUTH_PREFIX) || jwt_claims(key).is_some() {
+        CredentialKind::OauthToken
+    } else {
+        CredentialKind::ApiKey
+    }
+}
+
+/// The last four characters, enough to match a key without revealing it
+fn hint(key: &str) -> String {
+    let tail: String = key
+        .chars()
+        .rev()
+        .take(4)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect();
+    format!("…{tail}")
+}
+
+/// Claims of a token shaped like a JWT
+fn jwt_claims(token: &str) -> Option<serde_json::Value> {
+    let mut parts = token.split('.');
+    let (_, payload, _) = (parts.next()?, parts.next()?, parts.next()?);
+    if parts.next().is_some() {
+        return None;
+    }
+    let bytes = URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok()?;
+    serde_json::from_slice::<serde_json::Value>(&bytes)
+        .ok()
+        .filter(serde_json::Value::is_object)
+}
+
+/// Expiry of a JWT-shaped token, from its `exp` claim
+fn token_expiry(token: &str) -> Option<DateTime<Utc>> {
+    let exp = jwt_claims(token)?.get("exp")?.as_i64()?;
+    DateTime::from_timestamp(exp, 0)
+}
+
+/// Rate limits disclosed in response headers
+fn rate_limits(headers: &HeaderMap) -> Vec<RateLimit> {
+    let mut limits: BTreeMap<String, RateLimit> = BTreeMap::new();
+    for (name, value) in headers {
+        let Ok(value) = value.to_str() else {
+            continue;
+        };
+        let name = name.as_str();
+        let parsed = if let Some(rest) = name.strip_prefix(OPENAI_RATELIMIT_PREFIX) {
+            rest.split_once('-')
+        } else if let Some(rest) = name.strip_prefix(ANTHROPIC_RATELIMIT_PREFIX) {
+            rest.rsplit_once('-')
+                .map(|(resource, field)| (field, resource))
+        } else {
+            None
+        };
+        let Some((field, resource)) = parsed else {
+            continue;
+        };
+        let limit = limits
+            .entry(resource.to_string())
+            .or_insert_with(|| RateLimit {
+                resource: resource.to_string(),
+                ..Default::default()
+            });
+        match field {
+            "limit" => limit.limit = value.parse().ok(),
+            "remaining" => limit.remaining = value.parse().ok(),
+            "reset" => limit.reset = Some(value.to_string()),
+            _ => {}
+        }
+    }
+    limits.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_rate_limits_from_either_provider_and_token_expiry() {
+        let mut headers = HeaderMap::new();
+        for (name, value) in [
+            ("x-ratelimit-limit-requests", "500"),
+            ("x-ratelimit-remaining-requests", "499"),
+            ("x-ratelimit-reset-requests", "120ms"),
+            ("anthropic-ratelimit-input-tokens-limit", "40000"),
+            ("anthropic-ratelimit-input-tokens-remaining", "39000"),
+            ("content-type", "application/json"),
+        ] {
+            headers.insert(name, HeaderValue::from_static(value));
+        }
+        let limits = rate_limits(&headers);
+        assert_eq!(limits.len(), 2);
+        assert_eq!(limits[0].resource, "input-tokens");
+        assert_eq!(limits[0].remaining, Some(39000));
+        assert_eq!(limits[1].resource, "requests");
+        assert_eq!(limits[1].limit, Some(500));
+        assert_eq!(limits[1].reset.as_deref(), Some("120ms"));
+
+        let payload = URL_SAFE_NO_PAD.encode(br#"{"exp":1767225600}"#);
+        let token = format!("eyJhbGciOiJub25lIn0.{payload}.sig");
+        assert_eq!(credential_kind(&token), CredentialKind::OauthToken);
+        assert_eq!(
+            token_expiry(&token),
+            DateTime::from_timestamp(1_767_225_600, 0)
+        );
+        assert_eq!(credential_kind("sk-proj-abcd"), CredentialKind::ApiKey);
+        assert_eq!(token_expiry("sk-proj-abcd"), None);
+        assert_eq!(hint("sk-proj-abcd"), "…abcd");
+    }
+}
@@ -0,0 +1,798 @@
+//! First-run diagnostics ("doctor") for the daemon
+//!
+//! Runs a set of independent checks against the current settings and
+//! environment and reports structured, actionable results. The same checks
+//! run periodically as the `health_probe` scheduled task.
+
+use crate::Daemon;
+use crate::config::{ProviderConfig, ProviderType, Settings, TunnelTransport};
+use crate::error::{DaemonError, Result};
+use crate::services::certificates::{CertificateFiles, load_certificate};
+use crate::services::scheduler::ScheduledTask;
+use crate::state_dir::StateDir;
+use crate::types::{
+    CheckStatus, DaemonStatus, DoctorCheck, DoctorReport, TlsForwardStatus, TransportKind,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use gate_tlsforward::client::certificate_dir_name;
+use reqwest::StatusCode;
+use reqwest::header::{AUTHORIZATION, DATE};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+const CLOCK_SKEW_WARN_SECS: i64 = 30;
+const CLOCK_SKEW_FAIL_SECS: i64 = 300;
+const CERT_LIFETIME_DAYS: u64 = 90;
+const PROBE_FILE_NAME: &str = ".gate-doctor-probe";
+
+pub(crate) const ANTHROPIC_API_KEY_HEADER: &str = "x-api-key";
+pub(crate) const ANTHROPIC_VERSION_HEADER: &str = "anthropic-version";
+pub(crate) const ANTHROPIC_VERSION_VALUE: &str = "2023-06-01";
+
+const CHECK_PORT: &str = "port";
+const CHECK_PROVIDER_PREFIX: &str = "provider";
+const CHECK_RELAY: &str = "relay";
+const CHECK_CERTIFICATES: &str = "certificates";
+const CHECK_CLOCK_SKEW: &str = "clock_skew";
+const CHECK_STATE_DIR: &str = "state_dir";
+
+/// Runs connectivity and environment diagnostics
+pub struct DoctorService {
+    settings: Settings,
+    status: Option<DaemonStatus>,
+    config_dir: PathBuf,
+    data_dir: PathBuf,
+    client: reqwest::Client,
+}
+
+impl DoctorService {
+    /// Create a doctor for the given settings and the state directory the
+    /// daemon uses
+    pub fn new(settings: Settings, state_dir: &StateDir) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(PROBE_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            settings,
+            status: None,
+            config_dir: state_dir.config_dir(),
+            data_dir: state_dir.data_dir(),
+            client,
+        }
+    }
+
+    /// Use the live status of a running daemon instead of probing from scratch
+    pub fn with_status(mut self, status: DaemonStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Run all checks and collect the results
+    pub async fn run(&self) -> DoctorReport {
+        let mut checks = vec![self.check_port().await];
+
+        let mut server_dates = Vec::new();
+        for provider in &self.settings.providers {
+            let (check, date) = self.check_provider(provider).await;
+            checks.push(check);
+            server_dates.extend(date);
+        }
+
+        checks.push(self.check_relay().await);
+        checks.push(self.check_clock_skew(&server_dates));
+
+        checks.push(self.check_certificates().await);
+        checks.push(check_dir_writable(&self.config_dir, "config").await);
+        checks.push(check_dir_writable(&self.data_dir, "data").await);
+
+        DoctorReport {
+            healthy: checks.iter().all(|c| c.status != CheckStatus::Fail),
+            checks,
+            generated_at: Utc::now(),
+        }
+    }
+
+    async fn check_port(&self) -> DoctorCheck {
+        let addr = format!(
+            "{}:{}",
+            self.settings.server.host, self.settings.server.port
+        );
+        let name = "Listen port".to_string();
+
+        if self.status.as_ref().is_some_and(|s| s.running) {
+            return DoctorCheck {
+                id: CHECK_PORT.to_string(),
+                name,
+                status: CheckStatus::Pass,
+                message: format!("Daemon is listening on {addr}"),
+                remediation: None,
+            };
+        }
+
+        match tokio::net::TcpListener::bind(&addr).await {
+            Ok(_) => DoctorCheck {
+                id: CHECK_PORT.to_string(),
+                name,
+                status: CheckStatus::Pass,
+                message: format!("{addr} is available"),
+                remediation: None,
+            },
+            Err(e) => DoctorCheck {
+                id: CHECK_PORT.to_string(),
+                name,
+                status: CheckStatus::Fail,
+                message: format!("Cannot bind {addr}: {e}"),
+                remediation: Some(format!(
+                    "Stop the process using port {} or change server.port in the configuration",
+                    self.settings.server.port
+                )),
+            },
+        }
+    }
+
+    async fn check_provider(
+        &self,
+        provider: &ProviderConfig,
+    ) -> (DoctorCheck, Option<DateTime<Utc>>) {
+        let id = format!("{CHECK_PROVIDER_PREFIX}:{}", provider.name);
+        let name = format!("Provider {} ({})", provider.name, provider.provider);
+
+        let request = match provider.provider {
+            ProviderType::Anthropic => {
+                let request = self
+                    .client
+                    .get(models_url(&provider.base_url))
+                    .header(ANTHROPIC_VERSION_HEADER, ANTHROPIC_VERSION_VALUE);
+                match &provider.api_key {
+                    Some(key) => request.header(ANTHROPIC_API_KEY_HEADER, key),
+                    None => request,
+                }
+            }
+            ProviderType::OpenAI => {
+                let request = self.client.get(models_url(&provider.base_url));
+                match &provider.api_key {
+                    Some(key) => request.header(AUTHORIZATION, format!("Bearer {key}")),
+                    None => request,
+                }
+            }
+            ProviderType::Custom => {
+                let check = DoctorCheck {
+                    id,
+                    name,
+                    status: CheckStatus::Skipped,
+                    message: "Custom providers cannot be probed".to_string(),
+                    remediation: None,
+                };
+                return (check, None);
+            }
+            ProviderType::Mock => {
+                let check = DoctorCheck {
+                    id,
+                    name,
+                    status: CheckStatus::Pass,
+                    message: "Mock provider is served locally".to_string(),
+                    remediation: None,
+                };
+                return (check, None);
+            }
+        };
+
+        let response = match request.send().await {
+            Ok(response) => response,
+            Err(e) => {
+                let check = DoctorCheck {
+                    id,
+                    name,
+                    status: CheckStatus::Fail,
+                    message: format!("{} is unreachable: {e}", provider.base_url),
+                    remediation: Some(
+                        "Check network connectivity, proxy settings and the provider base_url"
+                            .to_string(),
+                    ),
+                };
+                return (check, None);
+            }
+        };
+
+        let server_date = response
+            .headers()
+            .get(DATE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| DateTime::parse_from_rfc2822(v).ok())
+            .map(|d| d.with_timezone(&Utc));
+
+        let status = response.status();
+        let (status, message, remediation) = match (status, provider.api_key.is_some()) {
+            (s, true) if s.is_success() => (
+                CheckStatus::Pass,
+                "Reachable and API key accepted".to_string(),
+                None,
+            ),
+            (StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN, true) => (
+                CheckStatus::Fail,
+                format!("API key rejected ({status})"),
+                Some(format!(
+                    "Replace the API key for provider '{}' with a valid one",
+                    provider.name
+                )),
+            ),
+            (_, false) => (
+                CheckStatus::Warn,
+                "Reachable, but no API key is configured".to_string(),
+                Some("Add an API key or rely on client-supplied keys".to_string()),
+            ),
+            (s, true) => (
+                CheckStatus::Warn,
+                format!("Reachable, but returned unexpected status {s}"),
+                Some("Verify the provider base_url points at the API root".to_string()),
+            ),
+        };
+
+        let check = DoctorCheck {
+            id,
+            name,
+            status,
+            message,
+            remediation,
+        };
+        (check, server_date)
+    }
+
+    async fn check_relay(&self) -> DoctorCheck {
+        let id = CHECK_RELAY.to_string();
+        let name = "TLS forward relay".to_string();
+
+        if !self.settings.tlsforward.enabled {
+            return DoctorCheck {
+                id,
+                name,
+                status: CheckStatus::Skipped,
+                message: "TLS forwarding is disabled".to_string(),
+                remediation: None,
+            };
+        }
+
+        if let Some(status) = self.status.as_ref().map(|s| &s.tlsforward_status) {
+            let (status, message, remediation) = match status {
+                TlsForwardStatus::Connected {
+                    domain,
+                    transport: TransportKind::Relay,
+                } => (
+                    CheckStatus::Pass,
+                    format!("Connected, serving {domain}"),
+                    None,
+                ),
+                TlsForwardStatus::Connected { domain, .. } => (
+                    CheckStatus::Pass,
+                    format!("Tunnel up, serving {domain}"),
+                    None,
+                ),
+                TlsForwardStatus::Error(e)
+                    if self.settings.tlsforward.transport != TunnelTransport::Relay =>
+                {
+                    (
+                        CheckStatus::Fail,
+                        format!("Tunnel failed: {e}"),
+                        Some(
+                            "Check that the tunnel command runs by hand with the configured \
+                             host and key"
+                                .to_string(),
+                        ),
+                    )
+                }
+                TlsForwardStatus::Connecting => (
+                    CheckStatus::Warn,
+                    "Still connecting to the relay".to_string(),
+                    Some("Wait a few seconds and run the doctor again".to_string()),
+                ),
+                TlsForwardStatus::Error(e) => (
+                    CheckStatus::Fail,
+                    format!("Relay connection failed: {e}"),
+                    Some("Check that UDP traffic to the relay address is allowed".to_string()),
+                ),
+                TlsForwardStatus::Disconnected | TlsForwardStatus::Disabled => (
+                    CheckStatus::Warn,
+                    "Relay is enabled but not connected".to_string(),
+                    Some("Restart the daemon to re-establish the relay connection".to_string()),
+                ),
+            };
+            return DoctorCheck {
+                id,
+                name,
+                status,
+                message,
+                remediation,
+            };
+        }
+
+        for address in &self.settings.tlsforward.tlsforward_addresses {
+            let Some((_, socket_addr)) = address.split_once('@') else {
+                continue;
+            };
+            if let Ok(mut addrs) = tokio::net::lookup_host(socket_addr).await
+                && addrs.next().is_some()
+            {
+                return DoctorCheck {
+                    id,
+                    name,
+                    status: CheckStatus::Pass,
+                    message: format!("Relay address {socket_addr} resolves"),
+                    remediation: None,
+                };
+            }
+        }
+
+        DoctorCheck {
+            id,
+            name,
+            status: CheckStatus::Fail,
+            message: "No configured relay address could be resolved".to_string(),
+            remediation: Some(
+                "Check DNS and tlsforward.tlsforward_addresses (format: node_id@host:port)"
+                    .to_string(),
+            ),
+        }
+    }
+
+    fn check_clock_skew(&self, server_dates: &[DateTime<Utc>]) -> DoctorCheck {
+        let id = CHECK_CLOCK_SKEW.to_string();
+        let name = "Clock skew".to_string();
+
+        let Some(skew) = server_dates
+            .iter()
+            .map(|d| (Utc::now() - *d).num_seconds().abs())
+            .min()
+        else {
+            return DoctorCheck {
+                id,
+                name,
+                status: CheckStatus::Skipped,
+                message: "No reachable provider to compare time against".to_string(),
+                remediation: None,
+            };
+        };
+
+        let (status, remediation) = if skew >= CLOCK_SKEW_FAIL_SECS {
+            (
+                CheckStatus::Fail,
+                Some("Enable NTP time synchronization on this machine".to_string()),
+            )
+        } else if skew >= CLOCK_SKEW_WARN_SECS {
+            (
+                CheckStatus::Warn,
+                Some("Enable NTP time synchronization on this machine".to_string()),
+            )
+        } else {
+            (CheckStatus::Pass, None)
+        };
+
+        DoctorCheck {
+            id,
+            name,
+            status,
+            message: format!("Local clock differs from upstream by {skew}s"),
+            remediation,
+        }
+    }
+
+    async fn check_certificates(&self) -> DoctorCheck {
+        let id = CHECK_CERTIFICATES.to_string();
+        let name = "TLS certificates".to_string();
+        let letsencrypt = &self.settings.letsencrypt;
+
+        let files = match CertificateFiles::resolve(&self.settings.certificates.source) {
+            Ok(files) => files,
+            Err(e) => {
+                return DoctorCheck {
+                    id,
+                    name,
+                    status: CheckStatus::Fail,
+                    message: e.to_string(),
+                    remediation: Some("Check the path in certificates.source".to_string()),
+                };
+            }
+        };
+        if let Some(files) = files {
+            let warning_days = self.settings.certificates.expiry_warning_days as i64;
+            let (status, message, remediation) = match load_certificate(&files).await {
+                Ok(loaded) => {
+                    let days_left = loaded.info.days_left(Utc::now());
+                    let message = format!(
+                        "Certificate for {} expires in {days_left} day(s)",
+                        loaded.info.domains.join(", ")
+                    );
+                    if days_left <= warning_days {
+                        (
+                            CheckStatus::Warn,
+                            message,
+                            Some("Renew the certificate; it is reloaded once replaced".to_string()),
+                        )
+                    } else {
+                        (CheckStatus::Pass, message, None)
+                    }
+                }
+                Err(e) => (
+                    CheckStatus::Fail,
+                    e.to_string(),
+                    Some("Supply a current certificate and its matching key".to_string()),
+                ),
+            };
+            return DoctorCheck {
+                id,
+                name,
+                status,
+                message,
+                remediation,
+            };
+        }
+
+        if !letsencrypt.enabled {
+            return DoctorCheck {
+                id,
+                name,
+                status: CheckStatus::Skipped,
+                message: "Let's Encrypt is disabled".to_string(),
+                remediation: None,
+            };
+        }
+
+        if letsencrypt.domains.is_empty() {
+            return DoctorCheck {
+                id,
+                name,
+                status: CheckStatus::Warn,
+                message: "Let's Encrypt is enabled but no domains are configured".to_string(),
+                remediation: Some("Add at least one domain to letsencrypt.domains".to_string()),
+            };
+        }
+
+        // The relay only answers challenges for names it serves, so a
+        // wildcard needs a DNS provider of its own
+        let unanswerable: Vec<&str> = letsencrypt
+            .domains
+            .iter()
+            .filter(|domain| domain.starts_with("*."))
+            .filter(|domain| !letsencrypt.dns.iter().any(|c| c.domains.contains(domain)))
+            .map(String::as_str)
+            .collect();
+        if !unanswerable.is_empty() {
+            return DoctorCheck {
+                id,
+                name,
+                status: CheckStatus::Warn,
+                message: format!(
+                    "No DNS provider configured for wildcard domains: {}",
+                    unanswerable.join(", ")
+                ),
+                remediation: Some("Add a letsencrypt.dns entry listing these domains".to_string()),
+            };
+        }
+
+        let renew_after = Duration::from_secs(
+            CERT_LIFETIME_DAYS.saturating_sub(letsencrypt.auto_renew_days as u64) * 24 * 60 * 60,
+        );
+        let mut missing = Vec::new();
+        let mut stale = Vec::new();
+
+        for domain in &letsencrypt.domains {
+            let cert_path = self
+                .data_dir
+                .join("certificates")
+                .join(certificate_dir_name(domain))
+                .join("fullchain.pem");
+            match tokio::fs::metadata(&cert_path).await {
+                Ok(metadata) => {
+                    let age = metadata
+                        .modified()
+                        .ok()
+                        .and_then(|m| m.elapsed().ok())
+                        .unwrap_or_default();
+                    if age > renew_after {
+                        stale.push(domain.as_str());
+                    }
+                }
+                Err(_) => missing.push(domain.as_str()),
+            }
+        }
+
+        if !missing.is_empty() {
+            DoctorCheck {
+                id,
+                name,
+                status: CheckStatus::Warn,
+                message: format!("No certificate issued yet for: {}", missing.join(", ")),
+                remediation: Some(
+                    "Ensure the relay is connected, or a letsencrypt.dns provider covers \
+                     these domains, so DNS challenges can complete"
+                        .to_string(),
+                ),
+            }
+        } else if !stale.is_empty() {
+            DoctorCheck {
+                id,
+                name,
+                status: CheckStatus::Warn,
+                message: format!("Certificates due for renewal: {}", stale.join(", ")),
+                remediation: Some("Restart the daemon to trigger certificate renewal".to_string()),
+            }
+        } else {
+            DoctorCheck {
+                id,
+                name,
+                status: CheckStatus::Pass,
+                message: format!("{} certificate(s) present", letsencrypt.domains.len()),
+                remediation: None,
+            }
+        }
+    }
+}
+
+/// Build `{base_url}/v1/models`, the endpoint used to probe providers
+pub(crate) fn models_url(base_url: &str) -> String {
+    format!("{}/v1/models", base_url.trim_end_matches('/'))
+}
+
+async fn check_dir_writable(dir: &Path, label: &str) -> DoctorCheck {
+    let id = format!("{CHECK_STATE_DIR}:{label}");
+    let name = format!("State directory ({label})");
+    let probe = dir.join(PROBE_FILE_NAME);
+
+    if let Err(e) = tokio::fs::write(&probe, b"ok").await {
+        return DoctorCheck {
+            id,
+            name,
+            status: CheckStatus::Fail,
+            message: format!("{} is not writable: {e}", dir.display()),
+            remediation: Some(format!(
+                "Fix ownership or permissions of {} for the user running gate",
+                dir.display()
+            )),
+        };
+    }
+    let _ = tokio::fs::remove_file(&probe).await;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        if let Ok(metadata) = tokio::fs::metadata(dir).await
+            && metadata.permissions().mode() & 0o002 != 0
+        {
+            return DoctorCheck {
+                id,
+                name,
+                status: CheckStatus::Warn,
+                message: format!("{} is world-writable", dir.display()),
+                remediation: Some(format!("Run `chmod o-w {}`", dir.display())),
+            };
+        }
+    }
+
+    DoctorCheck {
+        id,
+        name,
+        status: CheckStatus::Pass,
+        message: format!("{} is writable", dir.display()),
+        remediation: None,
+    }
+}
+
+/// Scheduled task running the doctor checks against the running daemon,
+/// logging checks that fail
+pub struct HealthProbe {
+    daemon: Daemon,
+}
+
+impl HealthProbe {
+    pub fn new(daemon: Daemon) -> Self {
+        Self { daemon }
+    }
+}
+
+#[async_trait]
+impl ScheduledTask for HealthProbe {
+    async fn run(&self) -> Result<()> {
+        let settings = self.daemon.get_settings().await?;
+        let status = self.daemon.status().await?;
+        let report = DoctorService::new(settings, self.daemon.state_dir()?)
+            .with_status(status)
+            .run()
+            .await;
+
+        let failed: Vec<&str> = report
+            .checks
+            .iter()
+            .filter(|c| c.status == CheckStatus::Fail)
+            .inspect(|c| warn!("Health probe: {} failed: {}", c.name, c.message))
+            .map(|c| c.id.as_str())
+            .collect();
+        if failed.is_empty() {
+            Ok(())
+        } else {
+            Err(DaemonError::ServiceUnavailable(format!(
+                "Failing checks: {}",
+                failed.join(", ")
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doctor(settings: Settings, state: &Path) -> DoctorService {
+        DoctorService {
+            settings,
+            status: None,
+            config_dir: state.join("config"),
+            data_dir: state.join("data"),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    fn status(tlsforward_status: TlsForwardStatus) -> DaemonStatus {
+        DaemonStatus {
+            running: true,
+            listen_address: "127.0.0.1:31145".to_string(),
+            provider_count: 0,
+            user_count: 1,
+            tlsforward_enabled: true,
+            tlsforward_status,
+            needs_bootstrap: false,
+            safe_mode: None,
+            local_models: None,
+            tlsforward_traffic: None,
+        }
+    }
+
+    fn find<'a>(report: &'a DoctorReport, id: &str) -> &'a DoctorCheck {
+        report.checks.iter().find(|c| c.id == id).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_state_checks_use_the_configured_directories() {
+        let state = tempfile::tempdir().unwrap();
+        std::fs::create_dir(state.path().join("config")).unwrap();
+        std::fs::create_dir(state.path().join("data")).unwrap();
+        let mut settings = Settings::default();
+        settings.server.host = "127.0.0.1".to_string();
+        settings.server.port = 0;
+
+        let report = doctor(settings, state.path()).run().await;
+        assert!(report.healthy, "{:?}", report.checks);
+        let data = find(&report, "state_dir:data");
+        assert_eq!(data.status, CheckStatus::Pass);
+        assert!(
+            data.message
+                .contains(&state.path().join("data").display().to_string())
+        );
+        assert_eq!(find(&report, CHECK_PORT).status, CheckStatus::Pass);
+        assert_eq!(find(&report, CHECK_RELAY).status, CheckStatus::Skipped);
+        assert_eq!(find(&report, CHECK_CLOCK_SKEW).status, CheckStatus::Skipped);
+    }
+
+    #[tokio::test]
+    async fn test_missing_or_open_directories_are_reported() {
+        let state = tempfile::tempdir().unwrap();
+        let check = check_dir_writable(&state.path().join("missing"), "data").await;
+        assert_eq!(check.status, CheckStatus::Fail);
+        assert!(check.remediation.is_some());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let open = std::fs::Permissions::from_mode(0o777);
+            std::fs::set_permissions(state.path(), open).unwrap();
+            let check = check_dir_writable(state.path(), "data").await;
+            assert_eq!(check.status, CheckStatus::Warn);
+        }
+    }
+
+    #[test]
+    fn test_clock_skew_thresholds() {
+        let state = tempfile::tempdir().unwrap();
+        let doctor = doctor(Settings::default(), state.path());
+        let skewed =
+            |secs| doctor.check_clock_skew(&[Utc::now() - chrono::Duration::seconds(secs)]);
+
+        assert_eq!(doctor.check_clock_skew(&[]).status, CheckStatus::Skipped);
+        assert_eq!(skewed(2).status, CheckStatus::Pass);
+        assert_eq!(skewed(CLOCK_SKEW_WARN_SECS + 5).status, CheckStatus::Warn);
+        assert_eq!(skewed(CLOCK_SKEW_FAIL_SECS + 5).status, CheckStatus::Fail);
+    }
+
+    #[tokio::test]
+    async fn test_relay_check_reads_the_live_status() {
+        let state = tempfile::tempdir().unwrap();
+        let mut settings = Settings::default();
+        assert_eq!(
+            doctor(settings.clone(), state.path())
+                .check_relay()
+                .await
+                .status,
+            CheckStatus::Skipped
+        );
+
+        settings.tlsforward.enabled = true;
+        let connected = TlsForwardStatus::Connected {
+            domain: "node.example.com".to_string(),
+            transport: TransportKind::Relay,
+        };
+        let check = doctor(settings.clone(), state.path())
+            .with_status(status(connected))
+            .check_relay()
+            .await;
+        assert_eq!(check.status, CheckStatus::Pass);
+        assert!(check.message.contains("node.example.com"));
+
+        let failed = TlsForwardStatus::Error("handshake timed out".to_string());
+        let check = doctor(settings, state.path())
+            .with_status(status(failed))
+            .check_relay()
+            .await;
+        assert_eq!(check.status, CheckStatus::Fail);
+        assert!(check.message.contains("handshake timed out"));
+    }
+
+    #[tokio::test]
+    async fn test_issued_certificates_are_found_in_the_data_dir() {
+        let state = tempfile::tempdir().unwrap();
+        let mut settings = Settings::default();
+        assert_eq!(
+            doctor(settings.clone(), state.path())
+                .check_certificates()
+                .await
+                .status,
+            CheckStatus::Skipped
+        );
+
+        settings.letsencrypt.enabled = true;
+        settings.letsencrypt.domains = vec!["gate.example.com".to_string()];
+        let check = doctor(settings.clone(), state.path())
+            .check_certificates()
+            .await;
+        assert_eq!(check.status, CheckStatus::Warn);
+        assert!(check.message.contains("gate.example.com"));
+
+        let cert_dir = state
+            .path()
+            .join("data/certificates")
+            .join(certificate_dir_name("gate.example.com"));
+        std::fs::create_dir_all(&cert_dir).unwrap();
+        std::fs::write(cert_dir.join("fullchain.pem"), "").unwrap();
+        let check = doctor(settings.clone(), state.path())
+            .check_certificates()
+            .await;
+        assert_eq!(check.status, CheckStatus::Pass);
+
+        // Wildcards need a DNS provider to answer their challenges
+        settings.letsencrypt.domains = vec!["*.example.com".to_string()];
+        let check = doctor(settings, state.path()).check_certificates().await;
+        assert_eq!(check.status, CheckStatus::Warn);
+        assert!(check.message.contains("*.example.com"));
+    }
+
+    #[tokio::test]
+    async fn test_local_providers_are_not_probed() {
+        let state = tempfile::tempdir().unwrap();
+        let doctor = doctor(Settings::default(), state.path());
+        let provider = |provider| ProviderConfig {
+            name: "local".to_string(),
+            provider,
+            base_url: "http://unreachable.invalid".to_string(),
+            ..Default::default()
+        };
+
+        let (check, date) = doctor.check_provider(&provider(ProviderType::Mock)).await;
+        assert_eq!((check.status, date), (CheckStatus::Pass, None));
+        let (check, date) = doctor.check_provider(&provider(ProviderType::Custom)).await;
+        assert_eq!((check.status, date), (CheckStatus::Skipped, None));
+        assert_eq!(check.id, "provider:local");
+    }
+}
@@ -0,0 +1,667 @@
+//! Offline evaluations against routed models
+//!
+//! A dataset is uploaded as JSONL, one prompt per line with the graders its
+//! answer must pass. A run sends every prompt to each selected model through
+//! the router, as the admin who started it and a bounded number at a time,
+//! so virtual models, prompt templates and budgets apply as they would to
+//! live traffic. Each answer is graded and kept with the run, and scores are
+//! aggregated per model as results arrive.
+//!
+//! Datasets and runs are JSON files under the state directory. A running
+//! eval also broadcasts each result, for live progress views.
+
+use crate::error::{DaemonError, Result};
+use crate::helpers::ids::{is_valid_id, new_id};
+use crate::services::compression::completion_text;
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use gate_core::router::routing::Router;
+use gate_core::router::service::route_and_execute_json_with_protocol;
+use gate_core::router::sink::RequestContext;
+use gate_core::router::types::Protocol;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value as JsonValue, json};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::sync::broadcast;
+
+const DATASET_ID_PREFIX: &str = "evalset-";
+const RUN_ID_PREFIX: &str = "evalrun-";
+
+/// Most requests a run has in flight at once
+pub const MAX_CONCURRENCY: usize = 16;
+
+/// Results between saves of a running eval
+const SAVE_EVERY: usize = 10;
+
+/// Progress events buffered for slow subscribers
+const PROGRESS_BUFFER: usize = 256;
+
+/// Check an answer must pass
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Grader {
+    /// The answer is the value, ignoring surrounding whitespace
+    Exact { value: String },
+    /// The answer contains the value
+    Contains {
+        value: String,
+        #[serde(default)]
+        ignore_case: bool,
+    },
+    /// The answer matches a regular expression
+    Regex { pattern: String },
+    /// The answer is a JSON document
+    Json,
+}
+
+impl Grader {
+    pub fn passes(&self, answer: &str) -> bool {
+        match self {
+            Grader::Exact { value } => answer.trim() == value.trim(),
+            Grader::Contains {
+                value,
+                ignore_case: true,
+            } => answer.to_lowercase().contains(&value.to_lowercase()),
+            Grader::Contains { value, .. } => answer.contains(value.as_str()),
+            Grader::Regex { pattern } => {
+                regex::Regex::new(pattern).is_ok_and(|re| re.is_match(answer))
+            }
+            Grader::Json => serde_json::from_str::<JsonValue>(answer.trim()).is_ok(),
+        }
+    }
+}
+
+/// One prompt of a dataset and how its answer is graded
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvalItem {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Sent as a single user message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    /// Chat messages sent instead of `prompt`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub messages: Option<Vec<JsonValue>>,
+    /// Pattern the answer must match; shorthand for a regex grader
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub graders: Vec<Grader>,
+}
+
+impl EvalItem {
+    pub fn graders(&self) -> Vec<Grader> {
+        let expected = self.expected.iter().map(|pattern| Grader::Regex {
+            pattern: pattern.clone(),
+        });
+        expected.chain(self.graders.iter().cloned()).collect()
+    }
+
+    fn messages(&self) -> Vec<JsonValue> {
+        match (&self.messages, &self.prompt) {
+            (Some(messages), _) => messages.clone(),
+            (None, Some(prompt)) => vec![json!({"role": "user", "content": prompt})],
+            (None, None) => Vec::new(),
+        }
+    }
+}
+
+/// Parse a JSONL dataset, rejecting it whole if any line is invalid
+pub fn parse_dataset(jsonl: &str) -> Result<Vec<EvalItem>> {
+    let mut items = Vec::new();
+    for (i, line) in jsonl.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let invalid =
+            |reason: String| DaemonError::InvalidState(format!("Line {}: {reason}", i + 1));
+        let item: EvalItem = serde_json::from_str(line).map_err(|e| invalid(e.to_string()))?;
+        if item.prompt.is_none() && item.messages.is_none() {
+            return Err(invalid("needs a prompt or messages".to_string()));
+        }
+        let graders = item.graders();
+        if graders.is_empty() {
+            return Err(invalid("needs expected or graders".to_string()));
+        }
+        for grader in &graders {
+            if let Grader::Regex { pattern } = grader {
+                regex::Regex::new(pattern).map_err(|e| invalid(e.to_string()))?;
+            }
+        }
+        items.push(item);
+    }
+    if items.is_empty() {
+        return Err(DaemonError::InvalidState(
+            "Dataset has no items".to_string(),
+        ));
+    }
+    Ok(items)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvalDataset {
+    pub id: String,
+    pub name: String,
+    pub owner_id: String,
+    pub items: Vec<EvalItem>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl EvalDataset {
+    pub fn new_id() -> String {
+        new_id(DATASET_ID_PREFIX)
+    }
+}
+
+/// A dataset without its items, as listed
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EvalDatasetInfo {
+    pub id: String,
+    pub name: String,
+    pub owner_id: String,
+    pub items: usize,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<&EvalDataset> for EvalDatasetInfo {
+    fn from(dataset: &EvalDataset) -> Self {
+        Self {
+            id: dataset.id.clone(),
+            name: dataset.name.clone(),
+            owner_id: dataset.owner_id.clone(),
+            items: dataset.items.len(),
+            created_at: dataset.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvalStatus {
+    Running,
+    Complete,
+    Failed,
+}
+
+/// A model's answer to one item and its grade
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvalResult {
+    /// Index of the item in its dataset
+    pub item: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub item_id: Option<String>,
+    pub model: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub answer: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Whether every grader passed
+    pub passed: bool,
+    /// Share of graders passed
+    pub score: f64,
+    pub latency_ms: u64,
+}
+
+/// Results of one model aggregated over a run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelScore {
+    pub model: String,
+    pub items: usize,
+    pub passed: usize,
+    pub errors: usize,
+    pub pass_rate: f64,
+    pub mean_score: f64,
+    pub mean_latency_ms: f64,
+}
+
+/// Scores of each model in `models` over the results so far
+pub fn scores(models: &[String], results: &[EvalResult]) -> Vec<ModelScore> {
+    models
+        .iter()
+        .map(|model| {
+            let results: Vec<_> = results.iter().filter(|r| &r.model == model).collect();
+            let items = results.len();
+            let mean = |total: f64| {
+                if items == 0 {
+                    0.0
+                } else {
+                    total / items as f64
+                }
+            };
+            let passed = results.iter().filter(|r| r.passed).count();
+            ModelScore {
+                model: model.clone(),
+                items,
+                passed,
+                errors: results.iter().filter(|r| r.error.is_some()).count(),
+                pass_rate: mean(passed as f64),
+                mean_score: mean(results.iter().map(|r| r.score).sum()),
+                mean_latency_ms: mean(results.iter().map(|r| r.latency_ms as f64).sum()),
+            }
+        })
+        .collect()
+}
+
+/// A dataset run against some models, with its results so far
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvalRun {
+    pub id: String,
+    pub dataset_id: String,
+    pub dataset_name: String,
+    pub models: Vec<String>,
+    pub concurrency: usize,
+    pub started_by: String,
+    pub status: EvalStatus,
+    /// Items times models
+    pub total: usize,
+    pub completed: usize,
+    pub scores: Vec<ModelScore>,
+    /// Per-item results; left out when runs are listed
+    #[serde(default)]
+    pub results: Vec<EvalResult>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Progress of a running eval, sent after each result
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EvalProgress {
+    pub run_id: String,
+    pub status: EvalStatus,
+    pub completed: usize,
+    pub total: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<EvalResult>,
+    pub scores: Vec<ModelScore>,
+}
+
+impl EvalProgress {
+    pub fn new(run: &EvalRun, result: Option<EvalResult>) -> Self {
+        Self {
+            run_id: run.id.clone(),
+            status: run.status,
+            completed: run.completed,
+            total: run.total,
+            result,
+            scores: run.scores.clone(),
+        }
+    }
+}
+
+/// Ask `model` to answer an item and grade the answer
+async fn evaluate(
+    router: &Router,
+    ctx: &RequestContext,
+    index: usize,
+    item: &EvalItem,
+    model: &str,
+) -> EvalResult {
+    let started = Instant::now();
+    let request = json!({"model": model, "messages": item.messages(), "stream": false});
+    let answer = match route_and_execute_json_with_protocol(
+        router,
+        ctx,
+        Protocol::OpenAIChat,
+        request,
+    )
+    .await
+    {
+        Ok(stream) => completion_text(stream, model).await,
+        Err(e) => Err(e.into()),
+    };
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    let mut result = EvalResult {
+        item: index,
+        item_id: item.id.clone(),
+        model: model.to_string(),
+        answer: None,
+        error: None,
+        passed: false,
+        score: 0.0,
+        latency_ms,
+    };
+    match answer {
+        Ok(answer) => {
+            let graders = item.graders();
+            let passed = graders.iter().filter(|g| g.passes(&answer)).count();
+            result.passed = passed == graders.len();
+            result.score = passed as f64 / graders.len().max(1) as f64;
+            result.answer = Some(answer);
+        }
+        Err(e) => result.error = Some(e.to_string()),
+    }
+    result
+}
+
+/// Eval datasets and runs stored on disk
+#[derive(Debug, Clone)]
+pub struct EvalStore {
+    dir: PathBuf,
+    /// Progress senders of runs in this process, by run id
+    progress: Arc<Mutex<HashMap<String, broadcast::Sender<EvalProgress>>>>,
+}
+
+impl EvalStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            progress: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    fn path(&self, kind: &str, id: &str) -> PathBuf {
+        self.dir.join(kind).join(format!("{id}.json"))
+    }
+
+    async fn put<T: Serialize>(&self, kind: &str, id: &str, value: &T) -> Result<()> {
+        tokio::fs::create_dir_all(self.dir.join(kind)).await?;
+        tokio::fs::write(self.path(kind, id), serde_json::to_vec(value)?).await?;
+        Ok(())
+    }
+
+    async fn get<T: for<'de> Deserialize<'de>>(
+        &self,
+        kind: &str,
+        prefix: &str,
+        id: &str,
+    ) -> Result<Option<T>> {
+        if !is_valid_id(prefix, id) {
+            return Ok(None);
+        }
+        match tokio::fs::read(self.path(kind, id)).await {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn list<T: for<'de> Deserialize<'de>>(&self, kind: &str, prefix: &str) -> Result<Vec<T>> {
+        let mut entries = match tokio::fs::read_dir(self.dir.join(kind)).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut values = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            let name = entry.file_name();
+            let Some(id) = name.to_str().and_then(|n| n.strip_suffix(".json")) else {
+                continue;
+            };
+            match self.get(kind, prefix, id).await {
+                Ok(Some(value)) => values.push(value),
+                Ok(None) => {}
+                Err(e) => warn!("Skipping unreadable eval file {id}: {e}"),
+            }
+        }
+        Ok(values)
+    }
+
+    pub async fn save_dataset(&self, dataset: &EvalDataset) -> Result<()> {
+        if !is_valid_id(DATASET_ID_PREFIX, &dataset.id) {
+            return Err(DaemonError::InvalidState(format!(
+                "Invalid dataset id {}",
+                dataset.id
+            )));
+        }
+        self.put("datasets", &dataset.id, dataset).await
+    }
+
+    pub async fn get_dataset(&self, id: &str) -> Result<Option<EvalDataset>> {
+        self.get("datasets", DATASET_ID_PREFIX, id).await
+    }
+
+    /// Datasets, newest first
+    pub async fn list_datasets(&self) -> Result<Vec<EvalDatasetInfo>> {
+        let datasets: Vec<EvalDataset> = self.list("datasets", DATASET_ID_PREFIX).await?;
+        let mut datasets: Vec<_> = datasets.iter().map(EvalDatasetInfo::from).collect();
+        datasets.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok(datasets)
+    }
+
+    /// Remove a dataset; its runs are kept
+    pub async fn delete_dataset(&self, id: &str) -> Result<bool> {
+        if !is_valid_id(DATASET_ID_PREFIX, id) {
+            return Ok(false);
+        }
+        match tokio::fs::remove_file(self.path("datasets", id)).await {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Runs left running by an earlier process never finish; show them as
+    /// failed
+    fn settle(&self, run: &mut EvalRun) {
+        let progress = self.progress.lock().unwrap_or_else(|e| e.into_inner());
+        if run.status == EvalStatus::Running && !progress.contains_key(&run.id) {
+            run.status = EvalStatus::Failed;
+            run.error = Some("Interrupted before it finished".to_string());
+        }
+    }
+
+    pub async fn get_run(&self, id: &str) -> Result<Option<EvalRun>> {
+        let mut run: Option<EvalRun> = self.get("runs", RUN_ID_PREFIX, id).await?;
+        if let Some(run) = &mut run {
+            self.settle(run);
+        }
+        Ok(run)
+    }
+
+    /// Runs without their per-item results, newest first
+    pub async fn list_runs(&self) -> Result<Vec<EvalRun>> {
+        let mut runs: Vec<EvalRun> = self.list("runs", RUN_ID_PREFIX).await?;
+        for run in &mut runs {
+            run.results.clear();
+            self.settle(run);
+        }
+        runs.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok(runs)
+    }
+
+    /// Progress of a run still going in this process
+    pub fn subscribe(&self, id: &str) -> Option<broadcast::Receiver<EvalProgress>> {
+        let progress = self.progress.lock().unwrap_or_else(|e| e.into_inner());
+        progress.get(id).map(broadcast::Sender::subscribe)
+    }
+
+    /// Start running `dataset` against `models` in the background, as the
+    /// identity in `ctx`
+    pub async fn start(
+        &self,
+        router: Arc<Router>,
+        ctx: RequestContext,
+        dataset: EvalDataset,
+        models: Vec<String>,
+        concurrency: usize,
+    ) -> Result<EvalRun> {
+        let now = Utc::now();
+        let run = EvalRun {
+            id: new_id(RUN_ID_PREFIX),
+            dataset_id: dataset.id.clone(),
+            dataset_name: dataset.name.clone(),
+            total: dataset.items.len() * models.len(),
+            scores: scores(&models, &[]),
+            models,
+            concurrency: concurrency.clamp(1, MAX_CONCURRENCY),
+            started_by: ctx.identity.id.clone(),
+            status: EvalStatus::Running,
+            completed: 0,
+            results: Vec::new(),
+            error: None,
+            created_at: now,
+            updated_at: now,
+        };
+        let (sender, _) = broadcast::channel(PROGRESS_BUFFER);
+        self.progress
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(run.id.clone(), sender.clone());
+        if let Err(e) = self.put("runs", &run.id, &run).await {
+            self.progress
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .remove(&run.id);
+            return Err(e);
+        }
+
+        let store = self.clone();
+        let mut running = run.clone();
+        tokio::spawn(async move {
+            if let Err(e) = store
+                .run(&router, &ctx, &dataset, &mut running, &sender)
+                .await
+            {
+                warn!("Eval run {} failed: {e}", running.id);
+                running.status = EvalStatus::Failed;
+                running.error = Some(e.to_string());
+                running.updated_at = Utc::now();
+                if let Err(e) = store.put("runs", &running.id, &running).await {
+                    warn!("Failed to record failure of eval run {}: {e}", running.id);
+                }
+                let _ = sender.send(EvalProgress::new(&running, None));
+            }
+            store
+                .progress
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .remove(&running.id);
+        });
+        Ok(run)
+    }
+
+    /// Evaluate every item against every model, saving every few results
+    async fn run(
+        &self,
+        router: &Router,
+        ctx: &RequestContext,
+        dataset: &EvalDataset,
+        run: &mut EvalRun,
+        sender: &broadcast::Sender<EvalProgress>,
+    ) -> Result<()> {
+        let models = run.models.clone();
+        let jobs = models.iter().flat_map(|model| {
+            dataset
+                .items
+                .iter()
+                .enumerate()
+                .map(move |(index, item)| (index, item, model))
+        });
+        let mut results = futures::stream::iter(jobs)
+            .map(|(index, item, model)| evaluate(router, ctx, index, item, model))
+            .buffer_unordered(run.concurrency);
+
+        while let Some(result) = results.next().await {
+            run.results.push(result.clone());
+            run.completed += 1;
+            run.scores = scores(&run.models, &run.results);
+            run.updated_at = Utc::now();
+            if run.completed % SAVE_EVERY == 0 {
+                self.put("runs", &run.id, run).await?;
+            }
+            let _ = sender.send(EvalProgress::new(run, Some(result)));
+        }
+
+        run.results.sort_by_key(|r| {
+            let model = models.iter().position(|m| *m == r.model);
+            (r.item, model)
+        });
+        run.status = EvalStatus::Complete;
+        run.updated_at = Utc::now();
+        self.put("runs", &run.id, run).await?;
+        info!(
+            "Eval run {} finished {} results against {} models",
+            run.id,
+            run.completed,
+            models.len()
+        );
+        let _ = sender.send(EvalProgress::new(run, None));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(model: &str, passed: bool, error: bool) -> EvalResult {
+        EvalResult {
+            item: 0,
+            item_id: None,
+            model: model.to_string(),
+            answer: (!error).then(|| "answer".to_string()),
+            error: error.then(|| "timeout".to_string()),
+            passed,
+            score: if passed { 1.0 } else { 0.0 },
+            latency_ms: 100,
+        }
+    }
+
+    #[test]
+    fn test_parse_and_grade_dataset() {
+        let items = parse_dataset(concat!(
+            r#"{"prompt": "2+2?", "expected": "\\b4\\b"}"#,
+            "\n\n",
+            r#"{"messages": [{"role": "user", "content": "Reply in JSON"}], "graders": [{"type": "json"}, {"type": "contains", "value": "OK", "ignore_case": true}]}"#
+        ))
+        .unwrap();
+        assert_eq!(items.len(), 2);
+        assert!(items[0].graders()[0].passes("It is 4."));
+        assert!(!items[0].graders()[0].passes("It is 42."));
+        assert!(
+            items[1]
+                .graders()
+                .iter()
+                .all(|g| g.passes(r#"{"status": "ok"}"#))
+        );
+
+        let error = parse_dataset("{\"prompt\": \"hi\"}").unwrap_err();
+        assert!(error.to_string().contains("Line 1"));
+        assert!(parse_dataset(r#"{"prompt": "hi", "expected": "("}"#).is_err());
+        assert!(parse_dataset("").is_err());
+    }
+
+    #[test]
+    fn test_scores_per_model() {
+        let models = vec!["a".to_string(), "b".to_string()];
+        let results = vec![
+            result("a", true, false),
+            result("a", false, false),
+            result("b", false, true),
+        ];
+        let scores = scores(&models, &results);
+        assert_eq!((scores[0].items, scores[0].passed), (2, 1));
+        assert_eq!(scores[0].pass_rate, 0.5);
+        assert_eq!(scores[1].errors, 1);
+        assert_eq!(scores[1].mean_latency_ms, 100.0);
+    }
+
+    #[tokio::test]
+    async fn test_store_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = EvalStore::new(dir.path());
+        let dataset = EvalDataset {
+            id: EvalDataset::new_id(),
+            name: "arithmetic".to_string(),
+            owner_id: "admin".to_string(),
+            items: parse_dataset(r#"{"prompt": "2+2?", "expected": "4"}"#).unwrap(),
+            created_at: Utc::now(),
+        };
+        store.save_dataset(&dataset).await.unwrap();
+
+        assert_eq!(
+            store.get_dataset(&dataset.id).await.unwrap(),
+            Some(dataset.clone())
+        );
+        assert_eq!(store.list_datasets().await.unwrap()[0].items, 1);
+        assert_eq!(store.get_dataset("../secrets").await.unwrap(), None);
+        assert!(store.delete_dataset(&dataset.id).await.unwrap());
+        assert!(store.list_datasets().await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,337 @@
+//! Personal data exports
+//!
+//! An export gathers a user's profile, credential metadata, conversations,
+//! usage and feedback into one JSON archive. Exports run in the background;
+//! the job is kept under the state directory next to its archive, and its
+//! progress is updated after each section so clients can poll it.
+
+use crate::error::{DaemonError, Result};
+use crate::helpers::ids::{is_valid_id, new_id};
+use crate::services::AuthService;
+use chrono::{DateTime, Utc};
+use gate_core::{StateBackend, TimeRange};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value as JsonValue, json};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const EXPORT_ID_PREFIX: &str = "export-";
+
+/// Version of the archive layout
+const ARCHIVE_VERSION: u32 = 1;
+
+/// Sections gathered into an archive, in order
+const SECTIONS: [&str; 5] = [
+    "profile",
+    "credentials",
+    "conversations",
+    "usage",
+    "feedback",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportStatus {
+    Pending,
+    Running,
+    Complete,
+    Failed,
+}
+
+/// A requested export and how far along it is
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportJob {
+    pub id: String,
+    /// User whose data is exported
+    pub user_id: String,
+    /// Who asked for the export: the user, or an admin offboarding them
+    pub requested_by: String,
+    pub status: ExportStatus,
+    /// Percent complete
+    pub progress: u8,
+    /// Section being gathered
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Archive size once complete
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<u64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Export jobs and their archives stored on disk
+#[derive(Debug, Clone)]
+pub struct ExportStore {
+    dir: PathBuf,
+}
+
+impl ExportStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn job_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{id}.json"))
+    }
+
+    fn archive_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{id}.archive.json"))
+    }
+
+    async fn put(&self, job: &ExportJob) -> Result<()> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        tokio::fs::write(self.job_path(&job.id), serde_json::to_vec_pretty(job)?).await?;
+        Ok(())
+    }
+
+    /// An export job, if it exists
+    pub async fn get(&self, id: &str) -> Result<Option<ExportJob>> {
+        if !is_valid_id(EXPORT_ID_PREFIX, id) {
+            return Ok(None);
+        }
+        match tokio::fs::read(self.job_path(id)).await {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Exports of `user_id`'s data, newest first
+    pub async fn list(&self, user_id: &str) -> Result<Vec<ExportJob>> {
+        let mut entries = match tokio::fs::read_dir(&self.dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut jobs = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            let name = entry.file_name();
+            let Some(id) = name.to_str().and_then(|n| n.strip_suffix(".json")) else {
+                continue;
+            };
+            if let Some(job) = self.get(id).await?
+                && job.user_id == user_id
+            {
+                jobs.push(job);
+            }
+        }
+        jobs.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok(jobs)
+    }
+
+    /// Contents of a completed export
+    pub async fn read_archive(&self, id: &str) -> Result<Vec<u8>> {
+        if !is_valid_id(EXPORT_ID_PREFIX, id) {
+            return Err(DaemonError::InvalidState(format!("Invalid export id {id}")));
+        }
+        Ok(tokio::fs::read(self.archive_path(id)).await?)
+    }
+
+    /// Queue an export of `user_id`'s data and start gathering it in the
+    /// background
+    pub async fn start(
+        &self,
+        state_backend: Arc<dyn StateBackend>,
+        auth_service: Arc<AuthService>,
+        user_id: &str,
+        requested_by: &str,
+    ) -> Result<ExportJob> {
+        let now = Utc::now();
+        let job = ExportJob {
+            id: new_id(EXPORT_ID_PREFIX),
+            user_id: user_id.to_string(),
+            requested_by: requested_by.to_string(),
+            status: ExportStatus::Pending,
+            progress: 0,
+            step: None,
+            error: None,
+            bytes: None,
+            created_at: now,
+            updated_at: now,
+        };
+        self.put(&job).await?;
+
+        let store = self.clone();
+        let mut running = job.clone();
+        tokio::spawn(async move {
+            if let Err(e) = store
+                .run(&mut running, state_backend.as_ref(), &auth_service)
+                .await
+            {
+                warn!("Export {} failed: {e}", running.id);
+                running.status = ExportStatus::Failed;
+                running.error = Some(e.to_string());
+                running.updated_at = Utc::now();
+                if let Err(e) = store.put(&running).await {
+                    warn!("Failed to record failure of export {}: {e}", running.id);
+                }
+            }
+        });
+        Ok(job)
+    }
+
+    /// Gather each section, recording progress after each one, then write
+    /// the archive
+    async fn run(
+        &self,
+        job: &mut ExportJob,
+        state_backend: &dyn StateBackend,
+        auth_service: &AuthService,
+    ) -> Result<()> {
+        job.status = ExportStatus::Running;
+        let mut archive = serde_json::Map::new();
+        for (i, section) in SECTIONS.iter().enumerate() {
+            job.step = Some(section.to_string());
+            job.progress = (i * 100 / (SECTIONS.len() + 1)) as u8;
+            job.updated_at = Utc::now();
+            self.put(job).await?;
+
+            let data = gather(section, &job.user_id, state_backend, auth_service).await?;
+            archive.insert(section.to_string(), data);
+        }
+
+        job.step = Some("archive".to_string());
+        job.progress = (SECTIONS.len() * 100 / (SECTIONS.len() + 1)) as u8;
+        self.put(job).await?;
+        let archive = json!({
+            "version": ARCHIVE_VERSION,
+            "user_id": job.user_id,
+            "generated_at": Utc::now(),
+            "data": archive,
+        });
+        let bytes = serde_json::to_vec_pretty(&archive)?;
+        tokio::fs::write(self.archive_path(&job.id), &bytes).await?;
+
+        job.status = ExportStatus::Complete;
+        job.progress = 100;
+        job.step = None;
+        job.bytes = Some(bytes.len() as u64);
+        job.updated_at = Utc::now();
+        self.put(job).await
+    }
+}
+
+/// One section of a user's archive
+async fn gather(
+    section: &str,
+    user_id: &str,
+    state_backend: &dyn StateBackend,
+    auth_service: &AuthService,
+) -> Result<JsonValue> {
+    let value = match section {
+        "profile" => json!({
+            "user": state_backend.get_user(user_id).await?,
+            "preferences": state_backend
+                .get_user_preferences(user_id)
+                .await?
+                .map(|p| p.preferences),
+        }),
+        // Metadata only: key hashes and public keys stay out of the archive
+        "credentials" => {
+            let api_keys: Vec<JsonValue> = state_backend
+                .list_api_keys(user_id)
+                .await?
+                .into_iter()
+                .map(|key| {
+                    json!({
+                        "name": key.name,
+                        "config": key.config,
+                        "created_at": key.created_at,
+                        "last_used_at": key.last_used_at,
+                    })
+                })
+                .collect();
+            let devices: Vec<JsonValue> = auth_service
+                .list_devices(user_id)
+                .await
+                .map_err(|e| DaemonError::ServiceUnavailable(e.to_string()))?
+                .into_iter()
+                .map(|device| {
+                    json!({
+                        "credential_id": device.credential_id,
+                        "device_name": device.device_name,
+                        "created_at": device.created_at,
+                        "last_used_at": device.last_used_at,
+                    })
+                })
+                .collect();
+            json!({ "api_keys": api_keys, "devices": devices })
+        }
+        "conversations" => json!(state_backend.list_conversations(user_id).await?),
+        "usage" => {
+            let range = TimeRange {
+                start: DateTime::<Utc>::UNIX_EPOCH,
+                end: Utc::now(),
+            };
+            let usage: Vec<_> = state_backend
+                .list_usage(&range)
+                .await?
+                .into_iter()
+                .filter(|u| u.user_id == user_id)
+                .collect();
+            json!(usage)
+        }
+        "feedback" => {
+            let feedback: Vec<_> = state_backend
+                .list_feedback(None, usize::MAX)
+                .await?
+                .into_iter()
+                .filter(|f| f.user_id == user_id)
+                .collect();
+            json!(feedback)
+        }
+        _ => JsonValue::Null,
+    };
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(user_id: &str) -> ExportJob {
+        let now = Utc::now();
+        ExportJob {
+            id: new_id(EXPORT_ID_PREFIX),
+            user_id: user_id.to_string(),
+            requested_by: user_id.to_string(),
+            status: ExportStatus::Pending,
+            progress: 0,
+            step: None,
+            error: None,
+            bytes: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_jobs_are_listed_per_user() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ExportStore::new(dir.path());
+        let mut alice = job("alice");
+        store.put(&alice).await.unwrap();
+        store.put(&job("bob")).await.unwrap();
+
+        alice.status = ExportStatus::Complete;
+        alice.progress = 100;
+        store.put(&alice).await.unwrap();
+        tokio::fs::write(store.archive_path(&alice.id), b"{}")
+            .await
+            .unwrap();
+
+        assert_eq!(store.get(&alice.id).await.unwrap(), Some(alice.clone()));
+        assert_eq!(store.list("alice").await.unwrap(), vec![alice.clone()]);
+        assert_eq!(store.read_archive(&alice.id).await.unwrap(), b"{}");
+    }
+
+    #[tokio::test]
+    async fn test_invalid_ids_never_touch_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ExportStore::new(dir.path());
+        assert_eq!(store.get("../config").await.unwrap(), None);
+        assert!(store.read_archive("export-../../etc/passwd").await.is_err());
+    }
+}
@@ -0,0 +1,385 @@
+//! Uploaded files and resolution of file references in requests
+//!
+//! Uploads are kept in the blob store under `files/`, each next to a JSON
+//! metadata sidecar. Requests that refer to an upload by id have the reference
+//! replaced with the file's content before routing: inline file data for
+//! OpenAI protocols, which accept files, and document or image blocks for
+//! Anthropic. Text files are inlined as text so any model can read them.
+
+use crate::error::{DaemonError, Result};
+use crate::helpers::ids::{is_valid_id, new_id};
+use async_trait::async_trait;
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use gate_core::BlobStore;
+use gate_core::router::middleware::{Middleware, Next, RequestStream, ResponseStream};
+use gate_core::router::sink::RequestContext;
+use gate_core::router::types::Protocol;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value as JsonValue, json};
+use std::sync::Arc;
+
+const FILE_ID_PREFIX: &str = "file-";
+
+/// Prefix of the keys uploads are stored under
+const KEY_PREFIX: &str = "files/";
+
+/// Metadata kept for an uploaded file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileRecord {
+    pub id: String,
+    pub owner_id: String,
+    pub filename: String,
+    pub purpose: String,
+    pub content_type: String,
+    pub bytes: u64,
+    pub created_at: DateTime<Utc>,
+}
+
+impl FileRecord {
+    fn is_text(&self) -> bool {
+        self.content_type.starts_with("text/")
+            || matches!(
+                self.content_type.as_str(),
+                "application/json" | "application/xml" | "application/x-yaml"
+            )
+    }
+
+    fn data_url(&self, data: &[u8]) -> String {
+        format!(
+            "data:{};base64,{}",
+            self.content_type,
+            STANDARD.encode(data)
+        )
+    }
+}
+
+/// Uploaded files kept in the blob store
+#[derive(Clone)]
+pub struct FileStore {
+    store: Arc<dyn BlobStore>,
+}
+
+impl FileStore {
+    pub fn new(store: Arc<dyn BlobStore>) -> Self {
+        Self { store }
+    }
+
+    fn content_key(id: &str) -> String {
+        format!("{KEY_PREFIX}{id}")
+    }
+
+    fn metadata_key(id: &str) -> String {
+        format!("{KEY_PREFIX}{id}.json")
+    }
+
+    /// Store a new upload
+    pub async fn save(
+        &self,
+        owner_id: &str,
+        filename: &str,
+        purpose: &str,
+        content_type: &str,
+        data: &[u8],
+    ) -> Result<FileRecord> {
+        let record = FileRecord {
+            id: new_id(FILE_ID_PREFIX),
+            owner_id: owner_id.to_string(),
+            filename: filename.to_string(),
+            purpose: purpose.to_string(),
+            content_type: content_type.to_string(),
+            bytes: data.len() as u64,
+            created_at: Utc::now(),
+        };
+        self.store
+            .put(&Self::content_key(&record.id), data.to_vec())
+            .await?;
+        self.store
+            .put(
+                &Self::metadata_key(&record.id),
+                serde_json::to_vec_pretty(&record)?,
+            )
+            .await?;
+        Ok(record)
+    }
+
+    /// Metadata for an upload, if it exists
+    pub async fn get(&self, id: &str) -> Result<Option<FileRecord>> {
+        if !is_valid_id(FILE_ID_PREFIX, id) {
+            return Ok(None);
+        }
+        match self.store.get(&Self::metadata_key(id)).await? {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Uploads belonging to `owner_id`, newest first
+    pub async fn list(&self, owner_id: &str) -> Result<Vec<FileRecord>> {
+        let mut records = Vec::new();
+        for blob in self.store.list(KEY_PREFIX).await? {
+            let Some(id) = blob
+                .key
+                .strip_prefix(KEY_PREFIX)
+                .and_then(|n| n.strip_suffix(".json"))
+            else {
+                continue;
+            };
+            if let Some(record) = self.get(id).await?
+                && record.owner_id == owner_id
+            {
+                records.push(record);
+            }
+        }
+        records.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok(records)
+    }
+
+    /// Contents of an upload
+    pub async fn read(&self, id: &str) -> Result<Vec<u8>> {
+        if !is_valid_id(FILE_ID_PREFIX, id) {
+            return Err(DaemonError::InvalidState(format!("Invalid file id {id}")));
+        }
+        self.store
+            .get(&Self::content_key(id))
+            .await?
+            .ok_or_else(|| DaemonError::InvalidState(format!("File {id} has no contents")))
+    }
+
+    /// Remove an upload and its metadata
+    pub async fn delete(&self, id: &str) -> Result<()> {
+        if !is_valid_id(FILE_ID_PREFIX, id) {
+            return Err(DaemonError::InvalidState(format!("Invalid file id {id}")));
+        }
+        self.store.delete(&Self::metadata_key(id)).await?;
+        self.store.delete(&Self::content_key(id)).await?;
+        Ok(())
+    }
+}
+
+/// Id of an uploaded file a content part refers to, if any
+fn file_reference(part: &JsonValue) -> Option<&str> {
+    match part.get("type")?.as_str()? {
+        // OpenAI chat
+        "file" => part.pointer("/file/file_id")?.as_str(),
+        // OpenAI Responses
+        "input_file" => part.get("file_id")?.as_str(),
+        // Anthropic
+        "document" | "image" if part.pointer("/source/type")?.as_str()? == "file" => {
+            part.pointer("/source/file_id")?.as_str()
+        }
+        _ => None,
+    }
+}
+
+/// Content part carrying an uploaded file inline, in the request's protocol
+fn inline_part(protocol: Protocol, record: &FileRecord, data: &[u8]) -> Option<JsonValue> {
+    let text = record
+        .is_text()
+        .then(|| std::str::from_utf8(data).ok())
+        .flatten();
+    let image = record.content_type.starts_with("image/");
+    let part = match protocol {
+        Protocol::OpenAIChat => match text {
+            Some(text) => json!({"type": "text", "text": format!("{}:\n{text}", record.filename)}),
+            None if image => json!({
+                "type": "image_url",
+                "image_url": {"url": record.data_url(data)}
+            }),
+            None => json!({
+                "type": "file",
+                "file": {"filename": record.filename, "file_data": record.data_url(data)}
+            }),
+        },
+        Protocol::OpenAIResponses => match text {
+            Some(text) => json!({
+                "type": "input_text",
+                "text": format!("{}:\n{text}", record.filename)
+            }),
+            None if image => json!({"type": "input_image", "image_url": record.data_url(data)}),
+            None => json!({
+                "type": "input_file",
+                "filename": record.filename,
+                "file_data": record.data_url(data)
+            }),
+        },
+        Protocol::Anthropic => match text {
+            Some(text) => json!({
+                "type": "document",
+                "title": record.filename,
+                "source": {"type": "text", "media_type": "text/plain", "data": text}
+            }),
+            None => json!({
+                "type": if image { "image" } else { "document" },
+                "source": {
+                    "type": "base64",
+                    "media_type": record.content_type,
+                    "data": STANDARD.encode(data)
+                }
+            }),
+        },
+        _ => return None,
+    };
+    Some(part)
+}
+
+/// Middleware that replaces references to uploaded files with their content
+pub struct FileReferenceMiddleware {
+    store: FileStore,
+}
+
+impl FileReferenceMiddleware {
+    pub fn new(store: FileStore) -> Self {
+        Self { store }
+    }
+
+    /// Inline every reference to a file `owner_id` uploaded. Other ids are
+    /// left alone since they may name files held by the provider.
+    async fn resolve(&self, protocol: Protocol, owner_id: &str, request: &mut JsonValue) {
+        let messages_key = match protocol {
+            Protocol::OpenAIResponses => "input",
+            _ => "messages",
+        };
+        let parts = request
+            .get_mut(messages_key)
+            .and_then(|m| m.as_array_mut())
+            .into_iter()
+            .flatten()
+            .filter_map(|message| message.get_mut("content")?.as_array_mut())
+            .flatten();
+        for part in parts {
+            let Some(id) = file_reference(part).map(String::from) else {
+                continue;
+            };
+            let record = match self.store.get(&id).await {
+                Ok(Some(record)) if record.owner_id == owner_id => record,
+                Ok(_) => continue,
+                Err(e) => {
+                    warn!("Failed to load file {id}: {e}");
+                    continue;
+                }
+            };
+            match self.store.read(&id).await {
+                Ok(data) => {
+                    if let Some(inline) = inline_part(protocol, &record, &data) {
+                        debug!("Inlined file {id} ({} bytes)", record.bytes);
+                        *part = inline;
+                    }
+                }
+                Err(e) => warn!("Failed to read file {id}: {e}"),
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Middleware for FileReferenceMiddleware {
+    async fn process(
+        &self,
+        ctx: &mut RequestContext,
+        mut request: RequestStream,
+        next: Next,
+    ) -> gate_core::Result<ResponseStream> {
+        let protocol = request.protocol();
+        let Some(first) = request.next().await else {
+            return next(request).await;
+        };
+        let mut json = first?;
+        self.resolve(protocol, &ctx.identity.id, &mut json).await;
+
+        let rebuilt = RequestStream::new(
+            protocol,
+            Box::pin(futures::stream::once(async move { Ok(json) }).chain(request)),
+        );
+        next(rebuilt).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::blob_store::LocalBlobStore;
+
+    #[tokio::test]
+    async fn test_file_store_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileStore::new(Arc::new(LocalBlobStore::new(dir.path())));
+
+        let record = store
+            .save("alice", "notes.txt", "user_data", "text/plain", b"hello")
+            .await
+            .unwrap();
+        assert_eq!(store.get(&record.id).await.unwrap(), Some(record.clone()));
+        assert_eq!(store.read(&record.id).await.unwrap(), b"hello");
+        assert_eq!(store.list("alice").await.unwrap(), vec![record.clone()]);
+        assert!(store.list("bob").await.unwrap().is_empty());
+
+        store.delete(&record.id).await.unwrap();
+        assert_eq!(store.get(&record.id).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_ids_never_touch_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileStore::new(Arc::new(LocalBlobStore::new(dir.path())));
+        assert_eq!(store.get("../config").await.unwrap(), None);
+        assert!(store.read("file-../../etc/passwd").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_inlines_owned_files_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileStore::new(Arc::new(LocalBlobStore::new(dir.path())));
+        let text = store
+            .save("alice", "a.txt", "user_data", "text/plain", b"contents")
+            .await
+            .unwrap();
+        let pdf = store
+            .save("alice", "a.pdf", "user_data", "application/pdf", b"%PDF")
+            .await
+            .unwrap();
+        let middleware = FileReferenceMiddleware::new(store);
+
+        let mut request = json!({
+            "messages": [{"role": "user", "content": [
+                {"type": "file", "file": {"file_id": text.id}},
+                {"type": "file", "file": {"file_id": pdf.id}},
+                {"type": "file", "file": {"file_id": "file-abc"}}
+            ]}]
+        });
+        middleware
+            .resolve(Protocol::OpenAIChat, "alice", &mut request)
+            .await;
+        let parts = &request["messages"][0]["content"];
+        assert_eq!(
+            parts[0],
+            json!({"type": "text", "text": "a.txt:\ncontents"})
+        );
+        assert_eq!(
+            parts[1]["file"]["file_data"],
+            "data:application/pdf;base64,JVBERg=="
+        );
+        assert_eq!(parts[2]["file"]["file_id"], "file-abc");
+
+        let mut request = json!({
+            "messages": [{"role": "user", "content": [
+                {"type": "document", "source": {"type": "file", "file_id": pdf.id}}
+            ]}]
+        });
+        middleware
+            .resolve(Protocol::Anthropic, "bob", &mut request)
+            .await;
+        assert_eq!(
+            request["messages"][0]["content"][0]["source"]["type"],
+            "file"
+        );
+        middleware
+            .resolve(Protocol::Anthropic, "alice", &mut request)
+            .await;
+        assert_eq!(
+            request["messages"][0]["content"][0]["source"],
+            json!({"type": "base64", "media_type": "application/pdf", "data": "JVBERg=="})
+        );
+    }
+}
@@ -0,0 +1,296 @@
+//! Node identity and attestation
+//!
+//! A node is identified by its Ed25519 key, the one its iroh endpoint and
+//! request signatures use. Its attestation describes the running node - the
+//! node id, version, a hash of the configuration with its secrets left out,
+//! the fingerprints of its TLS certificates and the hash of the frontend
+//! bundle it serves - and is signed with that key. The GUI and peering Gates
+//! fetch it when pairing and pin the node id, so a changed key or an altered
+//! document is noticed. A verifier sends a fresh nonce for the node to sign
+//! and refuses documents issued more than [`MAX_ATTESTATION_AGE`] away from
+//! its own clock, so a recorded attestation cannot be replayed.
+//!
+//! An attestation proves who answered the request for it, and nothing about
+//! the TLS session it came over or later requests; those rely on the pinned
+//! node id and request signatures.
+//!
+//! The key lives in the configuration directory and is bound to the machine
+//! only as far as that directory is.
+
+use crate::Settings;
+use crate::error::Result;
+use crate::services::signing::{MessageSigner, SignatureError, verify_signature};
+use chrono::{DateTime, TimeDelta, Utc};
+use rustls::pki_types::{CertificateDer, pem::PemObject};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use thiserror::Error;
+
+/// Path attestations are served at, relative to a Gate's base URL
+pub const ATTESTATION_PATH: &str = "/api/node/identity";
+
+/// How far from the verifier's clock an attestation may have been issued
+pub const MAX_ATTESTATION_AGE: TimeDelta = TimeDelta::minutes(5);
+
+/// Longest nonce a node signs
+pub const MAX_NONCE_LENGTH: usize = 128;
+
+/// Certificate a domain is served with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CertFingerprint {
+    pub domain: String,
+    /// Hex SHA-256 of the leaf certificate's DER encoding
+    pub sha256: String,
+}
+
+/// What a node attests to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AttestationDocument {
+    /// Hex public key of the node
+    pub node_id: String,
+    pub version: String,
+    /// `sha256:` hash of the running configuration as exported, with
+    /// secrets replaced by placeholders
+    pub config_hash: String,
+    pub certificates: Vec<CertFingerprint>,
+    /// `sha256:` hash of the frontend bundle served, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frontend_hash: Option<String>,
+    pub issued_at: DateTime<Utc>,
+    /// Nonce the verifier asked the node to sign
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+}
+
+/// An attestation document signed by the node it describes. The signature
+/// covers the document as compact JSON with its keys sorted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attestation {
+    pub document: AttestationDocument,
+    /// Base64 Ed25519 signature
+    pub signature: String,
+}
+
+#[derive(Debug, Error)]
+pub enum AttestationError {
+    #[error("Could not fetch attestation: {0}")]
+    Unreachable(String),
+    #[error("Malformed attestation: {0}")]
+    Malformed(String),
+    #[error("Attestation does not verify: {0}")]
+    Signature(#[from] SignatureError),
+    #[error("Attestation is not fresh: {0}")]
+    Stale(String),
+}
+
+/// Bytes an attestation's signature covers
+fn signed_bytes(document: &AttestationDocument) -> serde_json::Result<Vec<u8>> {
+    // Objects in a `Value` are sorted by key
+    serde_json::to_vec(&serde_json::to_value(document)?)
+}
+
+impl Attestation {
+    /// Check the signature against the node id the document names
+    pub fn verify(&self) -> std::result::Result<(), AttestationError> {
+        let message =
+            signed_bytes(&self.document).map_err(|e| AttestationError::Malformed(e.to_string()))?;
+        verify_signature(&self.document.node_id, &message, &self.signature)?;
+        Ok(())
+    }
+
+    /// Check the signature, and that the document signs `nonce` and was
+    /// issued within [`MAX_ATTESTATION_AGE`] of `now`
+    pub fn verify_fresh(
+        &self,
+        nonce: &str,
+        now: DateTime<Utc>,
+    ) -> std::result::Result<(), AttestationError> {
+        self.verify()?;
+        if self.document.nonce.as_deref() != Some(nonce) {
+            return Err(AttestationError::Stale(
+                "it does not sign the nonce sent".to_string(),
+            ));
+        }
+        if (now - self.document.issued_at).abs() > MAX_ATTESTATION_AGE {
+            return Err(AttestationError::Stale(format!(
+                "issued at {}",
+                self.document.issued_at
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// The node's key and what it attests to
+#[derive(Clone)]
+pub struct NodeIdentity {
+    signer: Arc<MessageSigner>,
+    cert_dir: PathBuf,
+    frontend_hash: Option<String>,
+}
+
+impl NodeIdentity {
+    /// An identity holding `signer`'s key, reporting the certificates kept
+    /// as `<domain>/fullchain.pem` under `cert_dir`
+    pub fn new(signer: Arc<MessageSigner>, cert_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            signer,
+            cert_dir: cert_dir.into(),
+            frontend_hash: None,
+        }
+    }
+
+    /// Attest to serving the frontend bundle hashed as `hash`
+    pub fn with_frontend_hash(mut self, hash: Option<String>) -> Self {
+        self.frontend_hash = hash;
+        self
+    }
+
+    /// `sha256:` hash of the frontend bundle served, if any
+    pub fn frontend_hash(&self) -> Option<&str> {
+        self.frontend_hash.as_deref()
+    }
+
+    pub fn signer(&self) -> Arc<MessageSigner> {
+        self.signer.clone()
+    }
+
+    /// Sign an attestation of the node running `settings`, over the
+    /// verifier's `nonce` when it sent one
+    pub async fn attest(&self, settings: &Settings, nonce: Option<String>) -> Result<Attestation> {
+        let config = serde_json::to_vec(&settings.export().config)?;
+        let document = AttestationDocument {
+            node_id: self.signer.key_id().to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            config_hash: format!("sha256:{}", hex::encode(Sha256::digest(&config))),
+            certificates: certificate_fingerprints(&self.cert_dir).await,
+            frontend_hash: self.frontend_hash.clone(),
+            issued_at: Utc::now(),
+            nonce,
+        };
+        let signature = self.signer.sign_bytes(&signed_bytes(&document)?);
+        Ok(Attestation {
+            document,
+            signature,
+        })
+    }
+}
+
+/// Fingerprints of the leaf certificates under `dir`, by domain
+async fn certificate_fingerprints(dir: &Path) -> Vec<CertFingerprint> {
+    let Ok(mut entries) = tokio::fs::read_dir(dir).await else {
+        return Vec::new();
+    };
+    let mut fingerprints = Vec::new();
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let Ok(pem) = tokio::fs::read(entry.path().join("fullchain.pem")).await else {
+            continue;
+        };
+        let Some(Ok(leaf)) = CertificateDer::pem_slice_iter(&pem).next() else {
+            warn!("No certificate in {}", entry.path().display());
+            continue;
+        };
+        fingerprints.push(CertFingerprint {
+            domain: entry.file_name().to_string_lossy().into_owned(),
+            sha256: hex::encode(Sha256::digest(leaf.as_ref())),
+        });
+    }
+    fingerprints.sort_by(|a, b| a.domain.cmp(&b.domain));
+    fingerprints
+}
+
+/// Fetch the attestation of the Gate at `base_url`, over a fresh nonce, and
+/// check it
+pub async fn fetch_attestation(
+    client: &reqwest::Client,
+    base_url: &str,
+) -> std::result::Result<Attestation, AttestationError> {
+    let url = format!("{}{ATTESTATION_PATH}", base_url.trim_end_matches('/'));
+    let nonce = hex::encode(rand::random::<[u8; 16]>());
+    let response = client
+        .get(&url)
+        .query(&[("nonce", &nonce)])
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| AttestationError::Unreachable(e.to_string()))?;
+    let attestation: Attestation = response
+        .json()
+        .await
+        .map_err(|e| AttestationError::Malformed(e.to_string()))?;
+    attestation.verify_fresh(&nonce, Utc::now())?;
+    Ok(attestation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use iroh::SecretKey;
+
+    #[tokio::test]
+    async fn test_attestation_verifies_until_altered() {
+        let dir = tempfile::tempdir().unwrap();
+        let signer = Arc::new(MessageSigner::new(SecretKey::from_bytes(&[3u8; 32])));
+        let identity = NodeIdentity::new(signer.clone(), dir.path());
+
+        let attestation = identity
+            .attest(&Settings::default(), Some("n-1".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(attestation.document.node_id, signer.key_id());
+        assert!(attestation.document.config_hash.starts_with("sha256:"));
+        assert!(attestation.document.certificates.is_empty());
+        attestation.verify().unwrap();
+
+        // Secrets are left out of the configuration hashed
+        let mut rotated = Settings::default();
+        rotated.auth.jwt.secret = "another-secret".to_string();
+        let hash = |a: Attestation| a.document.config_hash;
+        assert_eq!(
+            hash(identity.attest(&rotated, None).await.unwrap()),
+            hash(identity.attest(&Settings::default(), None).await.unwrap())
+        );
+
+        // The frontend hash is covered too
+        let identity = identity.with_frontend_hash(Some("sha256:00".to_string()));
+        let mut bundled = identity.attest(&Settings::default(), None).await.unwrap();
+        bundled.verify().unwrap();
+        bundled.document.frontend_hash = Some("sha256:01".to_string());
+        assert!(bundled.verify().is_err());
+
+        // Round trips through JSON, as peers receive it
+        let received: Attestation =
+            serde_json::from_str(&serde_json::to_string(&attestation).unwrap()).unwrap();
+        received.verify().unwrap();
+
+        // Only fresh, for the nonce it signs
+        let issued = received.document.issued_at;
+        received.verify_fresh("n-1", issued).unwrap();
+        assert!(matches!(
+            received.verify_fresh("n-2", issued),
+            Err(AttestationError::Stale(_))
+        ));
+        assert!(matches!(
+            received.verify_fresh("n-1", issued + TimeDelta::minutes(6)),
+            Err(AttestationError::Stale(_))
+        ));
+        assert!(matches!(
+            bundled.verify_fresh("n-1", Utc::now()),
+            Err(AttestationError::Signature(_))
+        ));
+
+        let mut altered = received.clone();
+        altered.document.version = "0.0.0".to_string();
+        assert!(matches!(
+            altered.verify(),
+            Err(AttestationError::Signature(SignatureError::Invalid))
+        ));
+
+        let mut impersonated = received;
+        let other = MessageSigner::new(SecretKey::from_bytes(&[4u8; 32]));
+        impersonated.document.node_id = other.key_id().to_string();
+        assert!(impersonated.verify().is_err());
+    }
+}
@@ -0,0 +1,375 @@
+//! Write-ahead journal for queued work
+//!
+//! Async inference jobs and webhook deliveries are journaled before they are
+//! acknowledged, so work accepted before a crash is not lost. Every change is
+//! appended to a log under the state directory and synced to disk. Opening
+//! the journal replays the log, compacts it, and recovers entries that were
+//! running when the daemon stopped: they are queued again while attempts
+//! remain and dead-lettered otherwise. Dead-lettered entries stay until an
+//! admin retries or discards them.
+
+use crate::error::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::fs::{File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+const JOURNAL_FILE: &str = "journal.log";
+const INTERRUPTED_ERROR: &str = "Interrupted by daemon restart";
+
+/// Kind of work held in the journal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    Inference,
+    Webhook,
+}
+
+/// Where an entry is in its lifecycle. Completed entries are removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Pending,
+    Running,
+    DeadLetter,
+}
+
+/// One unit of queued work
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub id: String,
+    pub kind: JobKind,
+    pub payload: JsonValue,
+    pub status: JobStatus,
+    pub attempts: u32,
+    pub max_attempts: u32,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Line in the journal file
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum Record {
+    Put { entry: JournalEntry },
+    Remove { id: String },
+}
+
+/// Entries found running when the journal was opened
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Recovery {
+    pub requeued: usize,
+    pub dead_lettered: usize,
+}
+
+#[derive(Debug)]
+struct JournalState {
+    file: File,
+    entries: BTreeMap<String, JournalEntry>,
+}
+
+impl JournalState {
+    /// Append a record and sync it before the change is visible
+    async fn append(&mut self, record: &Record) -> Result<()> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        self.file.write_all(&line).await?;
+        self.file.sync_data().await?;
+        Ok(())
+    }
+
+    async fn put(&mut self, entry: JournalEntry) -> Result<JournalEntry> {
+        self.append(&Record::Put {
+            entry: entry.clone(),
+        })
+        .await?;
+        self.entries.insert(entry.id.clone(), entry.clone());
+        Ok(entry)
+    }
+
+    async fn remove(&mut self, id: &str) -> Result<bool> {
+        if !self.entries.contains_key(id) {
+            return Ok(false);
+        }
+        self.append(&Record::Remove { id: id.to_string() }).await?;
+        self.entries.remove(id);
+        Ok(true)
+    }
+}
+
+/// Durable queue of async jobs and webhook deliveries
+#[derive(Debug, Clone)]
+pub struct Journal {
+    state: Arc<Mutex<JournalState>>,
+    recovery: Recovery,
+}
+
+impl Journal {
+    /// Open the journal in `dir`, recovering work interrupted by a crash
+    pub async fn open(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref();
+        tokio::fs::create_dir_all(dir).await?;
+        let path = dir.join(JOURNAL_FILE);
+
+        let mut entries = replay(&path).await?;
+        let mut recovery = Recovery::default();
+        let now = Utc::now();
+        for entry in entries.values_mut() {
+            if entry.status != JobStatus::Running {
+                continue;
+            }
+            entry.last_error = Some(INTERRUPTED_ERROR.to_string());
+            entry.updated_at = now;
+            if entry.attempts < entry.max_attempts {
+                entry.status = JobStatus::Pending;
+                recovery.requeued += 1;
+            } else {
+                entry.status = JobStatus::DeadLetter;
+                recovery.dead_lettered += 1;
+            }
+        }
+        compact(&path, &entries).await?;
+        if recovery != Recovery::default() {
+            warn!(
+                "Recovered interrupted jobs from journal: {} requeued, {} dead-lettered",
+                recovery.requeued, recovery.dead_lettered
+            );
+        }
+
+        let file = OpenOptions::new().append(true).open(&path).await?;
+        Ok(Self {
+            state: Arc::new(Mutex::new(JournalState { file, entries })),
+            recovery,
+        })
+    }
+
+    /// What was recovered when the journal was opened
+    pub fn recovery(&self) -> Recovery {
+        self.recovery
+    }
+
+    /// Durably queue new work
+    pub async fn enqueue(
+        &self,
+        kind: JobKind,
+        payload: JsonValue,
+        max_attempts: u32,
+    ) -> Result<JournalEntry> {
+        let now = Utc::now();
+        let entry = JournalEntry {
+            id: format!("job_{}", uuid::Uuid::new_v4().simple()),
+            kind,
+            payload,
+            status: JobStatus::Pending,
+            attempts: 0,
+            max_attempts: max_attempts.max(1),
+            last_error: None,
+            created_at: now,
+            updated_at: now,
+        };
+        self.state.lock().await.put(entry).await
+    }
+
+    /// Take the oldest pending entry of `kind` and mark it running
+    pub async fn claim(&self, kind: JobKind) -> Result<Option<JournalEntry>> {
+        let mut state = self.state.lock().await;
+        let Some(mut entry) = state
+            .entries
+            .values()
+            .filter(|e| e.kind == kind && e.status == JobStatus::Pending)
+            .min_by_key(|e| e.created_at)
+            .cloned()
+        else {
+            return Ok(None);
+        };
+        entry.status = JobStatus::Running;
+        entry.attempts += 1;
+        entry.updated_at = Utc::now();
+        state.put(entry).await.map(Some)
+    }
+
+    /// Finish an entry, removing it from the journal
+    pub async fn complete(&self, id: &str) -> Result<bool> {
+        self.state.lock().await.remove(id).await
+    }
+
+    /// Record a failed attempt. The entry is queued again while attempts
+    /// remain, and dead-lettered after the last one.
+    pub async fn fail(&self, id: &str, error: &str) -> Result<Option<JournalEntry>> {
+        let mut state = self.state.lock().await;
+        let Some(mut entry) = state.entries.get(id).cloned() else {
+            return Ok(None);
+        };
+        entry.status = if entry.attempts < entry.max_attempts {
+            JobStatus::Pending
+        } else {
+            JobStatus::DeadLetter
+        };
+        entry.last_error = Some(error.to_string());
+        entry.updated_at = Utc::now();
+        state.put(entry).await.map(Some)
+    }
+
+    /// Queue a dead-lettered entry again with a fresh set of attempts.
+    /// Returns `None` if there is no dead-lettered entry with this id.
+    pub async fn retry(&self, id: &str) -> Result<Option<JournalEntry>> {
+        let mut state = self.state.lock().await;
+        let Some(mut entry) = state
+            .entries
+            .get(id)
+            .filter(|e| e.status == JobStatus::DeadLetter)
+            .cloned()
+        else {
+            return Ok(None);
+        };
+        entry.status = JobStatus::Pending;
+        entry.attempts = 0;
+        entry.updated_at = Utc::now();
+        state.put(entry).await.map(Some)
+    }
+
+    /// Drop an entry without running it
+    pub async fn discard(&self, id: &str) -> Result<bool> {
+        self.state.lock().await.remove(id).await
+    }
+
+    pub async fn get(&self, id: &str) -> Option<JournalEntry> {
+        self.state.lock().await.entries.get(id).cloned()
+    }
+
+    /// Entries matching the filters, oldest first
+    pub async fn list(
+        &self,
+        kind: Option<JobKind>,
+        status: Option<JobStatus>,
+    ) -> Vec<JournalEntry> {
+        let state = self.state.lock().await;
+        let mut entries: Vec<_> = state
+            .entries
+            .values()
+            .filter(|e| kind.is_none_or(|k| e.kind == k))
+            .filter(|e| status.is_none_or(|s| e.status == s))
+            .cloned()
+            .collect();
+        entries.sort_by_key(|e| e.created_at);
+        entries
+    }
+}
+
+/// Rebuild entries from the log. A torn final line from a crash mid-write
+/// is skipped.
+async fn replay(path: &Path) -> Result<BTreeMap<String, JournalEntry>> {
+    let contents = match tokio::fs::read_to_string(path).await {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut entries = BTreeMap::new();
+    for (number, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<Record>(line) {
+            Ok(Record::Put { entry }) => {
+                entries.insert(entry.id.clone(), entry);
+            }
+            Ok(Record::Remove { id }) => {
+                entries.remove(&id);
+            }
+            Err(e) => warn!("Skipping unreadable journal line {}: {e}", number + 1),
+        }
+    }
+    Ok(entries)
+}
+
+/// Rewrite the log with one record per live entry
+async fn compact(path: &Path, entries: &BTreeMap<String, JournalEntry>) -> Result<()> {
+    let tmp: PathBuf = path.with_extension("log.tmp");
+    let mut contents = Vec::new();
+    for entry in entries.values() {
+        serde_json::to_writer(
+            &mut contents,
+            &Record::Put {
+                entry: entry.clone(),
+            },
+        )?;
+        contents.push(b'\n');
+    }
+    let mut file = File::create(&tmp).await?;
+    file.write_all(&contents).await?;
+    file.sync_all().await?;
+    tokio::fs::rename(&tmp, path).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_running_entries_are_recovered_on_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let journal = Journal::open(dir.path()).await.unwrap();
+        let once = journal
+            .enqueue(JobKind::Webhook, json!({"url": "https://example.com"}), 1)
+            .await
+            .unwrap();
+        let twice = journal
+            .enqueue(JobKind::Inference, json!({"model": "m"}), 2)
+            .await
+            .unwrap();
+        journal.claim(JobKind::Webhook).await.unwrap();
+        journal.claim(JobKind::Inference).await.unwrap();
+        drop(journal);
+
+        // Both were running when the "crash" happened
+        let journal = Journal::open(dir.path()).await.unwrap();
+        assert_eq!(
+            journal.recovery(),
+            Recovery {
+                requeued: 1,
+                dead_lettered: 1
+            }
+        );
+        let once = journal.get(&once.id).await.unwrap();
+        assert_eq!(once.status, JobStatus::DeadLetter);
+        assert_eq!(once.last_error.as_deref(), Some(INTERRUPTED_ERROR));
+        assert_eq!(
+            journal.get(&twice.id).await.unwrap().status,
+            JobStatus::Pending
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fail_dead_letters_after_last_attempt_and_retry_requeues() {
+        let dir = tempfile::tempdir().unwrap();
+        let journal = Journal::open(dir.path()).await.unwrap();
+        let entry = journal
+            .enqueue(JobKind::Webhook, json!({}), 2)
+            .await
+            .unwrap();
+
+        journal.claim(JobKind::Webhook).await.unwrap();
+        let entry = journal.fail(&entry.id, "503").await.unwrap().unwrap();
+        assert_eq!(entry.status, JobStatus::Pending);
+        journal.claim(JobKind::Webhook).await.unwrap();
+        let entry = journal.fail(&entry.id, "503").await.unwrap().unwrap();
+        assert_eq!(entry.status, JobStatus::DeadLetter);
+
+        let entry = journal.retry(&entry.id).await.unwrap().unwrap();
+        assert_eq!((entry.status, entry.attempts), (JobStatus::Pending, 0));
+        assert!(journal.retry(&entry.id).await.unwrap().is_none());
+
+        assert!(journal.complete(&entry.id).await.unwrap());
+        drop(journal);
+        let journal = Journal::open(dir.path()).await.unwrap();
+        assert!(journal.list(None, None).await.is_empty());
+    }
+}
@@ -1,7 +1,6 @@
 use crate::Settings;
 use crate::daemon::Daemon;
 use gate_core::Result;
-use gate_core::router::index::SinkIndex;
 use gate_core::router::middleware::KeyCaptureRegistrar;
 use gate_core::router::registry::SinkRegistry;
 use std::collections::HashSet;
@@ -11,20 +10,14 @@ use tokio::sync::Mutex;
 pub struct DaemonKeyRegistrar {
     daemon: Daemon,
     sink_registry: Arc<SinkRegistry>,
-    sink_index: Arc<SinkIndex>,
     created: Mutex<HashSet<String>>, // prevent duplicate work in-process
 }
 
 impl DaemonKeyRegistrar {
-    pub fn new(
-        daemon: Daemon,
-        sink_registry: Arc<SinkRegistry>,
-        sink_index: Arc<SinkIndex>,
-    ) -> Self {
+    pub fn new(daemon: Daemon, sink_registry: Arc<SinkRegistry>) -> Self {
         Self {
             daemon,
             sink_registry,
-            sink_index,
             created: Mutex::new(HashSet::new()),
         }
     }
@@ -75,6 +68,9 @@ impl KeyCaptureRegistrar for DaemonKeyRegistrar {
             api_key: Some(key.to_string()),
             timeout_seconds: 600,
             models: vec![],
+            mock: None,
+            sign_requests: false,
+            node_id: None,
         };
         new_settings.providers.push(provider_cfg);
 
@@ -104,16 +100,11 @@ impl KeyCaptureRegistrar for DaemonKeyRegistrar {
         self.sink_registry
             .remove("provider://anthropic/fallback")
             .await;
+        // The sink index picks up both changes from the registry
         self.sink_registry
             .register(format!("provider://anthropic/{name}"), sink)
             .await;
 
-        // Refresh index
-        let _ = self
-            .sink_index
-            .refresh_from_registry(&self.sink_registry)
-            .await;
-
         Ok(())
     }
 }
@@ -0,0 +1,447 @@
+//! Limits on who may send a request, checked before it is routed
+//!
+//! A request made with an API key must name a model the key's
+//! [`ApiKeyScope`] allows, and is refused once the key has spent its
+//! `spend_cap` since it was created. The user a request is made for is held
+//! to their [`UserQuota`]: spend this calendar month and tokens this day,
+//! both UTC, from recorded usage, and requests in the current minute, counted
+//! here as they arrive. The check runs after every other rewriter, so it sees
+//! the model the request is actually routed to, not an alias a profile or the
+//! automatic model resolved.
+
+use crate::services::api_keys::ApiKeyScope;
+use async_trait::async_trait;
+use chrono::{Datelike, NaiveTime, Utc};
+use gate_core::router::middleware::RequestRewriter;
+use gate_core::router::sink::RequestContext;
+use gate_core::router::types::Protocol;
+use gate_core::{Error, Result, StateBackend, UsageSubject};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+const QUOTA_MONTHLY_SPEND_KEY: &str = "quota.monthly_spend_limit";
+const QUOTA_REQUESTS_PER_MINUTE_KEY: &str = "quota.requests_per_minute";
+const QUOTA_TOKENS_PER_DAY_KEY: &str = "quota.tokens_per_day";
+
+/// Window `requests_per_minute` counts over
+const MINUTE: Duration = Duration::from_secs(60);
+
+/// Per-user usage limits stored in user metadata; `None` means unlimited
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UserQuota {
+    pub monthly_spend_limit: Option<f64>,
+    pub requests_per_minute: Option<u32>,
+    pub tokens_per_day: Option<u64>,
+}
+
+impl UserQuota {
+    pub(crate) fn from_metadata(metadata: &HashMap<String, String>) -> Self {
+        UserQuota {
+            monthly_spend_limit: metadata
+                .get(QUOTA_MONTHLY_SPEND_KEY)
+                .and_then(|v| v.parse().ok()),
+            requests_per_minute: metadata
+                .get(QUOTA_REQUESTS_PER_MINUTE_KEY)
+                .and_then(|v| v.parse().ok()),
+            tokens_per_day: metadata
+                .get(QUOTA_TOKENS_PER_DAY_KEY)
+                .and_then(|v| v.parse().ok()),
+        }
+    }
+
+    pub(crate) fn apply_to(&self, metadata: &mut HashMap<String, String>) {
+        fn set(metadata: &mut HashMap<String, String>, key: &str, value: Option<String>) {
+            match value {
+                Some(value) => metadata.insert(key.to_string(), value),
+                None => metadata.remove(key),
+            };
+        }
+        set(
+            metadata,
+            QUOTA_MONTHLY_SPEND_KEY,
+            self.monthly_spend_limit.map(|v| v.to_string()),
+        );
+        set(
+            metadata,
+            QUOTA_REQUESTS_PER_MINUTE_KEY,
+            self.requests_per_minute.map(|v| v.to_string()),
+        );
+        set(
+            metadata,
+            QUOTA_TOKENS_PER_DAY_KEY,
+            self.tokens_per_day.map(|v| v.to_string()),
+        );
+    }
+}
+
+/// Requests a user has made in the window that started at `start`
+struct Window {
+    start: Instant,
+    requests: u32,
+}
+
+/// Rewriter refusing requests over their key's or user's limits
+pub struct UsageLimits {
+    state_backend: Arc<dyn StateBackend>,
+    windows: Mutex<HashMap<String, Window>>,
+}
+
+impl UsageLimits {
+    pub fn new(state_backend: Arc<dyn StateBackend>) -> Self {
+        Self {
+            state_backend,
+            windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Refuse `model` if the key hashed as `key_hash` may not use it or has
+    /// spent its cap
+    async fn check_key(&self, key_hash: &str, model: &str) -> Result<()> {
+        let Some(key) = self.state_backend.get_api_key(key_hash).await? else {
+            return Ok(());
+        };
+        let scope = ApiKeyScope::from_key(&key);
+        if !scope.allowed_models.is_empty() && !scope.allowed_models.iter().any(|m| m == model) {
+            warn!("API key {} may not use model {model}", scope.key_prefix);
+            return Err(Error::Unauthorized);
+        }
+        if let Some(cap) = scope.spend_cap {
+            let spent = self
+                .state_backend
+                .usage_totals(&UsageSubject::ApiKey(key.key_hash.clone()), key.created_at)
+                .await?
+                .cost;
+            if spent >= cap {
+                return Err(Error::QuotaExceeded(format!(
+                    "API key has spent ${spent:.2} of its ${cap:.2} cap"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Refuse a request for `user_id` once it is over its quota
+    async fn check_user(&self, user_id: &str) -> Result<()> {
+        let Some(user) = self.state_backend.get_user(user_id).await? else {
+            return Ok(());
+        };
+        let quota = UserQuota::from_metadata(&user.metadata);
+        let subject = UsageSubject::User(user_id.to_string());
+        let now = Utc::now();
+        if let Some(limit) = quota.monthly_spend_limit {
+            let month = now
+                .date_naive()
+                .with_day(1)
+                .unwrap_or(now.date_naive())
+                .and_time(NaiveTime::MIN)
+                .and_utc();
+            let spent = self.state_backend.usage_totals(&subject, month).await?.cost;
+            if spent >= limit {
+                return Err(Error::QuotaExceeded(format!(
+                    "Monthly spend limit of ${limit:.2} reached"
+                )));
+            }
+        }
+        if let Some(limit) = quota.tokens_per_day {
+            let day = now.date_naive().and_time(NaiveTime::MIN).and_utc();
+            let tokens = self.state_backend.usage_totals(&subject, day).await?.tokens;
+            if tokens >= limit {
+                return Err(Error::QuotaExceeded(format!(
+                    "Daily limit of {limit} tokens reached"
+                )));
+            }
+        }
+        if let Some(limit) = quota.requests_per_minute {
+            self.count_request(user_id, limit)?;
+        }
+        Ok(())
+    }
+
+    /// Count a request for `user_id` in its current minute, refusing it if
+    /// the minute already holds `limit`
+    fn count_request(&self, user_id: &str, limit: u32) -> Result<()> {
+        let mut windows = self.windows.lock().unwrap();
+        windows.retain(|_, window| window.start.elapsed() < MINUTE);
+        let window = windows.entry(user_id.to_string()).or_insert(Window {
+            start: Instant::now(),
+            requests: 0,
+        });
+        if window.requests >= limit {
+            return Err(Error::QuotaExceeded(format!(
+                "Rate limit of {limit} requests per minute reached"
+            )));
+        }
+        window.requests += 1;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl RequestRewriter for UsageLimits {
+    async fn rewrite(
+        &self,
+        ctx: &mut RequestContext,
+        _protocol: Protocol,
+        request: &mut JsonValue,
+    ) -> Result<()> {
+        let model = request
+            .get("model")
+            .and_then(|m| m.as_str())
+            .unwrap_or_default();
+        let identity = &ctx.identity;
+        if let Some(key_hash) = &identity.context.api_key_hash {
+            self.check_key(key_hash, model).await?;
+        }
+        let user_id = identity.context.user_id.as_deref().unwrap_or(&identity.id);
+        self.check_user(user_id).await
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::services::ApiKeyService;
+    use futures::StreamExt;
+    use gate_core::access::SubjectIdentity;
+    use gate_core::router::middleware::{CostTrackerMiddleware, Middleware, Next, ResponseStream};
+    use gate_core::router::sink::RouterIdentityContext;
+    use gate_core::router::types::{CostStructure, RequestStream, ResponseChunk, StopReason};
+    use gate_core::{UsageRecord, User};
+    use gate_sqlx::SqliteStateBackend;
+    use rust_decimal::Decimal;
+    use serde_json::json;
+
+    pub(crate) fn context(user_id: &str, key_hash: Option<&str>) -> RequestContext {
+        let identity = RouterIdentityContext {
+            user_id: Some(user_id.to_string()),
+            api_key_hash: key_hash.map(str::to_string),
+            ..Default::default()
+        };
+        RequestContext {
+            identity: SubjectIdentity::new(user_id, "test", identity),
+            correlation_id: gate_core::tracing::CorrelationId::new(),
+            headers: Default::default(),
+            query: None,
+            trace_id: None,
+            metadata: Default::default(),
+            response: Default::default(),
+            extensions: Default::default(),
+        }
+    }
+
+    async fn check(limits: &UsageLimits, ctx: &mut RequestContext, model: &str) -> Result<()> {
+        let mut request = json!({ "model": model });
+        limits
+            .rewrite(ctx, Protocol::OpenAIChat, &mut request)
+            .await
+    }
+
+    /// Serve a response from `model` reporting `input` and `output` tokens
+    /// through the cost tracker, at $10 and $30 per million tokens, and
+    /// return the usage it recorded
+    pub(crate) async fn serve(
+        backend: &Arc<dyn StateBackend>,
+        ctx: &RequestContext,
+        model: &str,
+        input: u32,
+        output: u32,
+    ) -> UsageRecord {
+        let mut ctx = ctx.clone();
+        ctx.correlation_id = gate_core::tracing::CorrelationId::new();
+        ctx.extensions.insert(CostStructure {
+            input_cost_per_token: Decimal::new(10, 6),
+            output_cost_per_token: Decimal::new(30, 6),
+            cached_input_cost_per_token: None,
+            currency: "USD".to_string(),
+        });
+        let first = json!({"model": model, "messages": []});
+        let request = RequestStream::new(
+            Protocol::OpenAIChat,
+            Box::pin(futures::stream::once(async { Ok(first) })),
+        );
+        let next: Next = Box::new(move |_| {
+            Box::pin(async move {
+                let chunks = vec![
+                    Ok(ResponseChunk::Usage {
+                        prompt_tokens: input,
+                        completion_tokens: output,
+                    }),
+                    Ok(ResponseChunk::Stop {
+                        reason: StopReason::Complete,
+                        error: None,
+                        cost: None,
+                    }),
+                ];
+                Ok(Box::pin(futures::stream::iter(chunks)) as ResponseStream)
+            })
+        });
+        let mut response = CostTrackerMiddleware::new(backend.clone())
+            .process(&mut ctx, request, next)
+            .await
+            .unwrap();
+        while response.next().await.is_some() {}
+        backend
+            .get_usage_by_request(&ctx.correlation_id.to_string())
+            .await
+            .unwrap()
+            .expect("usage recorded")
+    }
+
+    pub(crate) async fn backend() -> Arc<dyn StateBackend> {
+        Arc::new(SqliteStateBackend::new(":memory:").await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_key_models_and_spend_cap_are_enforced() {
+        let backend = backend().await;
+        let scope = ApiKeyScope {
+            allowed_models: vec!["gpt-4o-mini".to_string()],
+            spend_cap: Some(1.0),
+            ..Default::default()
+        };
+        let (_, key) = ApiKeyService::new(backend.clone())
+            .create("user-1", "ci".to_string(), scope)
+            .await
+            .unwrap();
+        let limits = UsageLimits::new(backend.clone());
+        let mut ctx = context("user-1", Some(&key.key_hash));
+
+        check(&limits, &mut ctx, "gpt-4o-mini").await.unwrap();
+        assert!(matches!(
+            check(&limits, &mut ctx, "gpt-4o").await,
+            Err(Error::Unauthorized)
+        ));
+
+        // $0.50 of input and $0.90 of output
+        serve(&backend, &ctx, "gpt-4o-mini", 50_000, 30_000).await;
+        assert!(matches!(
+            check(&limits, &mut ctx, "gpt-4o-mini").await,
+            Err(Error::QuotaExceeded(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_user_quota_is_enforced() {
+        let backend = backend().await;
+        let mut user = User {
+            id: "user-1".to_string(),
+            name: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            disabled_at: None,
+            metadata: HashMap::new(),
+        };
+        UserQuota {
+            requests_per_minute: Some(2),
+            ..Default::default()
+        }
+        .apply_to(&mut user.metadata);
+        backend.create_user(&user).await.unwrap();
+        let limits = UsageLimits::new(backend.clone());
+        let mut ctx = context("user-1", None);
+
+        check(&limits, &mut ctx, "gpt-4o").await.unwrap();
+        check(&limits, &mut ctx, "gpt-4o").await.unwrap();
+        assert!(matches!(
+            check(&limits, &mut ctx, "gpt-4o").await,
+            Err(Error::QuotaExceeded(_))
+        ));
+
+        UserQuota {
+            monthly_spend_limit: Some(5.0),
+            tokens_per_day: Some(10_000),
+            ..Default::default()
+        }
+        .apply_to(&mut user.metadata);
+        backend.update_user(&user).await.unwrap();
+        // $0.06 of input and $0.18 of output
+        serve(&backend, &context("user-1", None), "gpt-4o", 6_000, 6_000).await;
+        let err = check(&limits, &mut ctx, "gpt-4o").await.unwrap_err();
+        assert!(err.to_string().contains("10000 tokens"), "{err}");
+
+        UserQuota {
+            monthly_spend_limit: Some(0.2),
+            ..Default::default()
+        }
+        .apply_to(&mut user.metadata);
+        backend.update_user(&user).await.unwrap();
+        let err = check(&limits, &mut ctx, "gpt-4o").await.unwrap_err();
+        assert!(err.to_string().contains("Monthly spend"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_monthly_spend_limit_stops_routed_requests() {
+        use gate_core::router::service::route_and_execute_json_with_protocol;
+        use gate_core::router::sinks::mock::MockSink;
+        use gate_core::router::{Pipeline, Router, SinkRegistry};
+
+        let backend = backend().await;
+        let mut user = User {
+            id: "user-1".to_string(),
+            name: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            disabled_at: None,
+            metadata: HashMap::new(),
+        };
+        UserQuota {
+            monthly_spend_limit: Some(1.0),
+            ..Default::default()
+        }
+        .apply_to(&mut user.metadata);
+        backend.create_user(&user).await.unwrap();
+
+        // A cent a token, so each request costs well under the limit but a
+        // handful exceed it
+        let mut sink = MockSink::success("self://mock");
+        sink.cost_structure = Some(CostStructure {
+            input_cost_per_token: Decimal::new(1, 2),
+            output_cost_per_token: Decimal::new(1, 2),
+            cached_input_cost_per_token: None,
+            currency: "USD".to_string(),
+        });
+        let registry = Arc::new(SinkRegistry::new());
+        registry
+            .register("self://mock".into(), Arc::new(sink))
+            .await;
+        let router = Router::builder()
+            .state_backend(backend.clone())
+            .sink_registry(registry)
+            .pipeline(Pipeline {
+                rewriters: vec![Arc::new(UsageLimits::new(backend.clone()))],
+                middleware: vec![Arc::new(CostTrackerMiddleware::new(backend.clone()))],
+                content_filter: None,
+            })
+            .build();
+
+        let ctx = context("user-1", None);
+        let mut served = 0;
+        let err = loop {
+            let request = json!({"model": "m", "messages": [{"role": "user", "content": "hi"}]});
+            match route_and_execute_json_with_protocol(&router, &ctx, Protocol::OpenAIChat, request)
+                .await
+            {
+                Ok(mut response) => {
+                    while response.next().await.is_some() {}
+                    served += 1;
+                    assert!(served < 100, "spend limit never tripped");
+                }
+                Err(e) => break e,
+            }
+        };
+        assert!(served > 1);
+        assert!(matches!(err, Error::QuotaExceeded(_)), "{err}");
+        let spent = backend
+            .usage_totals(
+                &UsageSubject::User("user-1".to_string()),
+                Utc::now() - chrono::Duration::days(31),
+            )
+            .await
+            .unwrap()
+            .cost;
+        assert!(spent >= 1.0);
+    }
+}
@@ -0,0 +1,395 @@
+//! Lockouts after repeated failed sign-ins
+//!
+//! Failed WebAuthn assertions, JWTs and API keys are counted per client
+//! address, and WebAuthn failures per account too. Past the free attempts
+//! each further failure locks the address or account out, for a period that
+//! doubles every time up to a cap. Failures are forgotten after a quiet
+//! period or, for an account, when it signs in. Loopback addresses are never
+//! locked out, so the owner can always sign in locally. The relay's
+//! [`AccessLog`](crate::services::AccessLog) bans addresses through
+//! [`AuthLockout::lock_out`] too, so there is one list of blocked clients.
+//! Lockouts are held in memory, pruned as subjects are added, announced to
+//! the configured webhooks, and can be lifted by an admin.
+
+use crate::config::LockoutConfig;
+use crate::services::journal::Journal;
+use crate::services::webhooks::enqueue_webhook;
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{Extensions, HeaderMap, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Json, Response},
+};
+use chrono::{DateTime, Duration, Utc};
+use gate_core::router::extensions::ClientIp;
+use gate_http::{
+    AppState, error::ErrorResponse, server::ForwardedClient, services::jwt::EXPIRED_TOKEN_MESSAGE,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, RwLock};
+
+/// Path WebAuthn assertions are checked on
+pub const WEBAUTHN_AUTH_PATH: &str = "/auth/webauthn/authenticate/complete";
+
+/// What failed to authenticate
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "id", rename_all = "snake_case")]
+pub enum LockoutSubject {
+    Address(IpAddr),
+    /// WebAuthn credential the client tried to sign in with
+    Account(String),
+}
+
+impl fmt::Display for LockoutSubject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LockoutSubject::Address(ip) => write!(f, "address {ip}"),
+            LockoutSubject::Account(id) => write!(f, "account {id}"),
+        }
+    }
+}
+
+/// Failures recorded against a subject
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lockout {
+    pub subject: LockoutSubject,
+    pub failures: u32,
+    pub last_failure: DateTime<Utc>,
+    /// End of the current lockout, if the subject is locked out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locked_until: Option<DateTime<Utc>>,
+}
+
+/// Shared lockout state; clones share it
+#[derive(Clone)]
+pub struct AuthLockout {
+    config: LockoutConfig,
+    journal: Option<Journal>,
+    subjects: Arc<RwLock<HashMap<LockoutSubject, Lockout>>>,
+}
+
+impl Default for AuthLockout {
+    fn default() -> Self {
+        Self::new(LockoutConfig::default())
+    }
+}
+
+impl AuthLockout {
+    pub fn new(config: LockoutConfig) -> Self {
+        Self {
+            config,
+            journal: None,
+            subjects: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Queue lockout events for the configured webhooks on `journal`
+    pub fn with_journal(mut self, journal: Journal) -> Self {
+        self.journal = Some(journal);
+        self
+    }
+
+    fn tracked(&self, subject: &LockoutSubject) -> bool {
+        self.config.enabled
+            && !matches!(subject, LockoutSubject::Address(ip) if ip.to_canonical().is_loopback())
+    }
+
+    /// Lockout period after `failures` failures, if they earn one
+    fn lockout_period(&self, failures: u32) -> Option<Duration> {
+        let over = failures.checked_sub(self.config.free_attempts)?;
+        if over == 0 {
+            return None;
+        }
+        let seconds = self
+            .config
+            .base_lockout_seconds
+            .saturating_mul(1u64.checked_shl(over - 1).unwrap_or(u64::MAX))
+            .min(self.config.max_lockout_seconds);
+        Some(Duration::seconds(seconds as i64))
+    }
+
+    /// Drop subjects neither locked out nor with recent failures
+    fn prune(&self, state: &mut HashMap<LockoutSubject, Lockout>, now: DateTime<Utc>) {
+        let reset_after = Duration::seconds(self.config.reset_after_seconds as i64);
+        state.retain(|_, lockout| {
+            lockout.locked_until.is_some_and(|until| until > now)
+                || now - lockout.last_failure <= reset_after
+        });
+    }
+
+    /// Latest end of a lockout on any of `subjects` at `now`
+    pub fn locked_until(
+        &self,
+        subjects: &[LockoutSubject],
+        now: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        let state = self.subjects.read().unwrap_or_else(|e| e.into_inner());
+        subjects
+            .iter()
+            .filter_map(|subject| state.get(subject)?.locked_until)
+            .filter(|until| *until > now)
+            .max()
+    }
+
+    /// Count a failed sign-in against `subjects`, locking out those past
+    /// their free attempts
+    pub async fn record_failure(&self, subjects: &[LockoutSubject], now: DateTime<Utc>) {
+        let reset_after = Duration::seconds(self.config.reset_after_seconds as i64);
+        let mut locked = Vec::new();
+        {
+            let mut state = self.subjects.write().unwrap_or_else(|e| e.into_inner());
+            for subject in subjects.iter().filter(|s| self.tracked(s)) {
+                if !state.contains_key(subject) {
+                    self.prune(&mut state, now);
+                }
+                let entry = state.entry(subject.clone()).or_insert(Lockout {
+                    subject: subject.clone(),
+                    failures: 0,
+                    last_failure: now,
+                    locked_until: None,
+                });
+                if now - entry.last_failure > reset_after {
+                    entry.failures = 0;
+                }
+                entry.failures += 1;
+                entry.last_failure = now;
+                if let Some(period) = self.lockout_period(entry.failures) {
+                    entry.locked_until = Some(now + period);
+                    locked.push(entry.clone());
+                }
+            }
+        }
+
+        for lockout in locked {
+            warn!(
+                "Locked out {} until {} after {} failed sign-ins",
+                lockout.subject,
+                lockout.locked_until.unwrap_or(now),
+                lockout.failures
+            );
+            self.notify(json!({ "type": "auth_lockout", "lockout": lockout }))
+                .await;
+        }
+    }
+
+    /// Lock `subject` out until `until` after `failures` failures counted
+    /// elsewhere, whether or not sign-in lockouts are enabled
+    pub async fn lock_out(
+        &self,
+        subject: LockoutSubject,
+        failures: u32,
+        until: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) {
+        if matches!(subject, LockoutSubject::Address(ip) if ip.to_canonical().is_loopback()) {
+            return;
+        }
+        let lockout = {
+            let mut state = self.subjects.write().unwrap_or_else(|e| e.into_inner());
+            if !state.contains_key(&subject) {
+                self.prune(&mut state, now);
+            }
+            let entry = state.entry(subject.clone()).or_insert(Lockout {
+                subject,
+                failures: 0,
+                last_failure: now,
+                locked_until: None,
+            });
+            entry.failures = entry.failures.max(failures);
+            entry.last_failure = now;
+            entry.locked_until = entry.locked_until.max(Some(until));
+            entry.clone()
+        };
+        warn!(
+            "Locked out {} until {} after {} failed requests",
+            lockout.subject, until, failures
+        );
+        self.notify(json!({ "type": "auth_lockout", "lockout": lockout }))
+            .await;
+    }
+
+    /// Forget the failures of an account that signed in
+    pub fn record_success(&self, account: &str) {
+        self.subjects
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&LockoutSubject::Account(account.to_string()));
+    }
+
+    /// Subjects with failures on record, locked out ones first
+    pub fn lockouts(&self, now: DateTime<Utc>) -> Vec<Lockout> {
+        let mut state = self.subjects.write().unwrap_or_else(|e| e.into_inner());
+        self.prune(&mut state, now);
+        let mut lockouts: Vec<_> = state.values().cloned().collect();
+        lockouts.sort_by_key(|lockout| {
+            (
+                std::cmp::Reverse(lockout.locked_until.filter(|until| *until > now)),
+                std::cmp::Reverse(lockout.last_failure),
+            )
+        });
+        lockouts
+    }
+
+    /// Lift the lockout on `subject` and forget its failures
+    pub async fn unblock(&self, subject: &LockoutSubject, by: &str) -> bool {
+        let removed = self
+            .subjects
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(subject)
+            .is_some();
+        if removed {
+            info!("{} lifted the lockout on {}", by, subject);
+            self.notify(json!({ "type": "auth_unblocked", "subject": subject, "by": by }))
+                .await;
+        }
+        removed
+    }
+
+    async fn notify(&self, event: serde_json::Value) {
+        let Some(journal) = &self.journal else {
+            return;
+        };
+        for url in &self.config.webhook_urls {
+            if let Err(e) = enqueue_webhook(journal, url, event.clone()).await {
+                warn!("Failed to queue lockout event for {url}: {e}");
+            }
+        }
+    }
+}
+
+/// Address a request came from: the client a relay forwarded it for, or the
+/// peer that connected
+pub fn client_address(extensions: &Extensions) -> Option<IpAddr> {
+    extensions
+        .get::<ForwardedClient>()
+        .map(|client| client.0.ip())
+        .or_else(|| {
+            extensions
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|info| info.0.ip())
+        })
+}
+
+/// Hand the address a request came from to the router's middleware, as
+/// its [`ClientIp`]
+pub async fn client_ip_middleware(mut request: Request, next: Next) -> Response {
+    if let Some(ip) = client_address(request.extensions()) {
+        request.extensions_mut().insert(ClientIp(ip));
+    }
+    next.run(request).await
+}
+
+/// Whether a rejected JWT or API key is a failed sign-in: credentials were
+/// presented, and were not merely a session that expired
+pub fn is_failed_sign_in(headers: &HeaderMap, reason: &str) -> bool {
+    (headers.contains_key(header::AUTHORIZATION) || headers.contains_key("x-api-key"))
+        && reason != EXPIRED_TOKEN_MESSAGE
+}
+
+/// Response to a locked out client
+pub fn locked_out_response(until: DateTime<Utc>, now: DateTime<Utc>) -> Response {
+    let retry_after = (until - now).num_seconds().max(1);
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(header::RETRY_AFTER, retry_after.to_string())],
+        Json(ErrorResponse {
+            error: "locked_out".to_string(),
+            message: format!("Too many failed sign-ins; try again in {retry_after} seconds"),
+            details: Some(json!({ "locked_until": until })),
+        }),
+    )
+        .into_response()
+}
+
+/// Refuse locked out addresses, and count rejected WebAuthn assertions
+/// against the address they came from. Rejected JWTs and API keys are
+/// counted where they are checked.
+pub async fn lockout_middleware(
+    State(app_state): State<AppState<crate::State>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(ip) = client_address(request.extensions()) else {
+        return next.run(request).await;
+    };
+    let lockout = &app_state.data.auth_lockout;
+    let subjects = [LockoutSubject::Address(ip)];
+    let now = Utc::now();
+    if let Some(until) = lockout.locked_until(&subjects, now) {
+        return locked_out_response(until, now);
+    }
+
+    let counted = request.uri().path() == WEBAUTHN_AUTH_PATH;
+    let response = next.run(request).await;
+    if counted && response.status() == StatusCode::UNAUTHORIZED {
+        lockout.record_failure(&subjects, Utc::now()).await;
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_lockout_backs_off_and_resets() {
+        let lockout = AuthLockout::new(LockoutConfig {
+            enabled: true,
+            free_attempts: 2,
+            base_lockout_seconds: 10,
+            max_lockout_seconds: 30,
+            reset_after_seconds: 600,
+            webhook_urls: Vec::new(),
+        });
+        let address = LockoutSubject::Address("203.0.113.7".parse().unwrap());
+        let account = LockoutSubject::Account("credential-1".to_string());
+        let subjects = [address.clone(), account.clone()];
+        let now = Utc::now();
+
+        lockout.record_failure(&subjects, now).await;
+        lockout.record_failure(&subjects, now).await;
+        assert_eq!(lockout.locked_until(&subjects, now), None);
+
+        // Each further failure doubles the lockout, up to the cap
+        for (failure, seconds) in [(3, 10), (4, 20), (5, 30), (6, 30)] {
+            lockout.record_failure(&subjects, now).await;
+            assert_eq!(
+                lockout.locked_until(&[account.clone()], now),
+                Some(now + Duration::seconds(seconds)),
+                "after failure {failure}"
+            );
+        }
+        let later = now + Duration::seconds(31);
+        assert_eq!(lockout.locked_until(&subjects, later), None);
+
+        // Signing in clears the account but not the address
+        lockout.record_success("credential-1");
+        lockout.record_failure(&subjects, later).await;
+        assert_eq!(lockout.locked_until(&[account.clone()], later), None);
+        assert!(lockout.locked_until(&[address.clone()], later).is_some());
+
+        assert!(lockout.unblock(&address, "admin").await);
+        assert_eq!(lockout.locked_until(&subjects, later), None);
+
+        // Subjects whose failures lapsed are dropped as others are added
+        let much_later = later + Duration::seconds(601);
+        let other = LockoutSubject::Address("198.51.100.1".parse().unwrap());
+        lockout.record_failure(&[other.clone()], much_later).await;
+        assert_eq!(
+            lockout.subjects.read().unwrap().keys().collect::<Vec<_>>(),
+            [&other]
+        );
+
+        // Loopback is never locked out
+        let local = [LockoutSubject::Address("127.0.0.1".parse().unwrap())];
+        for _ in 0..5 {
+            lockout.record_failure(&local, now).await;
+        }
+        assert_eq!(lockout.locked_until(&local, now), None);
+    }
+}
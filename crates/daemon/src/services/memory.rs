@@ -0,0 +1,173 @@
+//! Memory watermark and buffer cap alerts
+//!
+//! The guard checks the daemon's resident memory on an interval. Above the
+//! configured limit it sheds work, lowest priority first: cached metadata
+//! and coalesced responses are dropped, scheduled maintenance is put off
+//! and new requests that would buffer a whole response are refused with
+//! 503. Streaming requests carry on. Shedding stops once memory is back
+//! under 90% of the limit, so the daemon does not flap around it.
+//!
+//! Responses hitting a buffer cap since the last check are logged and
+//! posted to the configured webhooks along with shedding changes.
+
+use crate::config::MemoryConfig;
+use crate::services::journal::Journal;
+use crate::services::webhooks::enqueue_webhook;
+use gate_core::router::buffers::BufferBudget;
+use gate_core::tracing::metrics::gauge;
+use gate_http::services::{RequestCoalescer, ResponseCache};
+use serde_json::{Value as JsonValue, json};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Share of the limit memory must fall below before shedding stops
+const RESUME_RATIO: f64 = 0.9;
+
+/// A memory figure of this process in bytes, such as `VmRSS`, where the
+/// platform reports it
+pub fn process_memory(field: &str) -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| {
+        line.strip_prefix(field)
+            .is_some_and(|rest| rest.starts_with(':'))
+    })?;
+    let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib * 1024)
+}
+
+/// Resident memory of this process in bytes, where the platform reports it
+pub fn resident_bytes() -> Option<u64> {
+    process_memory("VmRSS")
+}
+
+/// Whether to shed with `rss` bytes resident and a limit of `limit` bytes,
+/// given whether the daemon sheds already
+fn should_shed(shedding: bool, rss: u64, limit: u64) -> bool {
+    if shedding {
+        rss as f64 >= limit as f64 * RESUME_RATIO
+    } else {
+        rss >= limit
+    }
+}
+
+/// Watches resident memory and buffer caps
+pub struct MemoryGuard {
+    config: MemoryConfig,
+    budget: &'static BufferBudget,
+    journal: Journal,
+    response_cache: Option<Arc<ResponseCache>>,
+    coalescer: Option<Arc<RequestCoalescer>>,
+}
+
+impl MemoryGuard {
+    pub fn new(config: MemoryConfig, journal: Journal) -> Self {
+        Self {
+            config,
+            budget: BufferBudget::shared(),
+            journal,
+            response_cache: None,
+            coalescer: None,
+        }
+    }
+
+    /// Drop these caches' entries while shedding
+    pub fn with_caches(
+        mut self,
+        response_cache: Option<Arc<ResponseCache>>,
+        coalescer: Option<Arc<RequestCoalescer>>,
+    ) -> Self {
+        self.response_cache = response_cache;
+        self.coalescer = coalescer;
+        self
+    }
+
+    fn shed_caches(&self) {
+        if let Some(cache) = &self.response_cache {
+            cache.clear();
+        }
+        if let Some(coalescer) = &self.coalescer {
+            coalescer.clear_completed();
+        }
+    }
+
+    async fn notify(&self, event: JsonValue) {
+        for url in &self.config.webhook_urls {
+            if let Err(e) = enqueue_webhook(&self.journal, url, event.clone()).await {
+                warn!("Failed to queue memory event for {url}: {e}");
+            }
+        }
+    }
+
+    /// Shed or stop shedding for the memory in use now
+    async fn check_memory(&self) {
+        let Some(limit_mb) = self.config.rss_limit_mb else {
+            return;
+        };
+        let Some(rss) = resident_bytes() else {
+            return;
+        };
+        gauge("process_resident_bytes").set(rss as i64);
+        let limit = limit_mb.saturating_mul(1 << 20);
+        let shedding = self.budget.is_shedding();
+        let shed = should_shed(shedding, rss, limit);
+        if shed {
+            self.shed_caches();
+        }
+        if shed == shedding {
+            return;
+        }
+        self.budget.set_shedding(shed);
+        let rss_mb = rss >> 20;
+        if shed {
+            warn!("Resident memory is {rss_mb} MB, over the {limit_mb} MB limit; shedding work");
+            self.notify(
+                json!({ "type": "memory_pressure", "rss_mb": rss_mb, "limit_mb": limit_mb }),
+            )
+            .await;
+        } else {
+            info!("Resident memory is down to {rss_mb} MB; no longer shedding work");
+            self.notify(
+                json!({ "type": "memory_recovered", "rss_mb": rss_mb, "limit_mb": limit_mb }),
+            )
+            .await;
+        }
+    }
+
+    /// Check on the configured interval until the daemon stops
+    pub async fn run(self) {
+        if self.config.rss_limit_mb.is_some() && resident_bytes().is_none() {
+            warn!(
+                "Resident memory is not reported on this platform; memory.rss_limit_mb has no effect"
+            );
+        }
+        let interval = Duration::from_secs(self.config.check_interval_seconds.max(1));
+        let mut reported = self.budget.cap_hits();
+        loop {
+            tokio::time::sleep(interval).await;
+            self.check_memory().await;
+
+            let hits = self.budget.cap_hits();
+            if hits > reported {
+                let count = hits - reported;
+                warn!("{count} responses hit a buffer cap in the last {interval:?}");
+                self.notify(json!({ "type": "buffer_cap_hits", "count": count }))
+                    .await;
+                reported = hits;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shedding_stops_below_the_resume_ratio() {
+        let limit = 1000;
+        assert!(!should_shed(false, 999, limit));
+        assert!(should_shed(false, 1000, limit));
+        assert!(should_shed(true, 950, limit));
+        assert!(!should_shed(true, 899, limit));
+    }
+}
@@ -1,13 +1,84 @@
+pub mod access_log;
+pub mod api_keys;
 pub mod auth;
+pub mod blob_store;
+pub mod build_info;
+pub mod certificates;
+pub mod cluster;
+pub mod compression;
+pub mod config_history;
+pub mod content_filter;
+pub mod cors;
+pub mod credential_import;
+pub mod credentials;
+pub mod doctor;
+pub mod evals;
+pub mod export;
+pub mod files;
+pub mod identity;
 pub mod inference;
+pub mod journal;
 pub mod key_capture;
+pub mod limits;
+pub mod lockout;
+pub mod memory;
+pub mod models;
 pub mod monitoring;
+pub mod observer;
 pub mod p2p;
+pub mod pairing;
+pub mod pipeline;
+pub mod profiling;
+pub mod prompt_sizes;
+pub mod readiness;
+pub mod relay_diagnostics;
+pub mod retention;
+pub mod retrieval;
+pub mod scheduler;
+pub mod security_headers;
+pub mod server_mode;
+pub mod signing;
+pub mod speculative;
+pub mod spend;
 pub mod tls;
+pub mod tls_status;
 pub mod tlsforward;
+pub mod tunnel;
 pub mod webauthn;
+pub mod webhooks;
 
+pub use access_log::AccessLog;
+pub use api_keys::ApiKeyService;
 pub use auth::AuthService;
+pub use certificates::{CertificateFiles, CertificateWatcher};
+pub use compression::CompressionMiddleware;
+pub use config_history::ConfigHistory;
+pub use cors::CorsPolicy;
+pub use credential_import::CredentialImportService;
+pub use credentials::CredentialChecker;
+pub use doctor::{DoctorService, HealthProbe};
+pub use evals::EvalStore;
+pub use export::ExportStore;
+pub use files::{FileReferenceMiddleware, FileStore};
+pub use identity::NodeIdentity;
 pub use inference::{LocalInferenceService, LocalInferenceServiceBuilder};
+pub use journal::Journal;
+pub use limits::UsageLimits;
+pub use lockout::AuthLockout;
+pub use memory::MemoryGuard;
+pub use models::{LocalModels, ModelCacheStatus};
+pub use pairing::PairingService;
+pub use profiling::ProfileStore;
+pub use relay_diagnostics::RelayDiagnostics;
+pub use retention::{BlobCollector, RetentionPurger};
+pub use retrieval::{DocumentStore, RetrievalMiddleware};
+pub use scheduler::Scheduler;
+pub use server_mode::ServerMode;
+pub use signing::MessageSigner;
+pub use spend::SpendMonitor;
+pub use tls::TlsManager;
+pub use tls_status::RenewalLog;
 pub use tlsforward::{TlsForwardService, TlsForwardState};
+pub use tunnel::TunnelService;
 pub use webauthn::WebAuthnService;
+pub use webhooks::WebhookDispatcher;
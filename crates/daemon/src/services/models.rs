@@ -0,0 +1,358 @@
+//! Local models kept loaded between requests
+//!
+//! Loading a model's weights takes long enough that the first request for
+//! it stalls. Models listed under `prewarm` are loaded when the daemon
+//! starts, and every model loaded stays loaded for the requests after it.
+//! When the loaded models outgrow the memory budget or the model limit, or
+//! free memory runs below the configured floor, the least recently used are
+//! unloaded. A model's size is the memory its load took, so it is only known
+//! where free memory can be read.
+
+use crate::config::LocalInferenceConfig;
+use crate::services::speculative::DraftStats;
+use chrono::{DateTime, Utc};
+use gate_core::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+/// Models served by the catgrad sink
+pub type LocalModels = ModelCache<crate::sinks::catgrad_sink::CatgradModel>;
+
+/// Loads a model by id; runs on a blocking thread
+pub type ModelLoader<M> = Arc<dyn Fn(&str) -> Result<M> + Send + Sync>;
+
+/// Reads the free memory in MB, if it can be read
+pub type MemoryProbe = Arc<dyn Fn() -> Option<u64> + Send + Sync>;
+
+/// A model held in memory
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoadedModel {
+    pub model: String,
+    pub loaded_at: DateTime<Utc>,
+    pub last_used_at: DateTime<Utc>,
+    /// Memory the load took, where it could be measured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_mb: Option<u64>,
+    /// Requests served since it was loaded
+    pub uses: u64,
+    /// Whether it was loaded at startup
+    pub prewarmed: bool,
+}
+
+/// Loaded models and the memory they hold
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModelCacheStatus {
+    /// Loaded models, most recently used first
+    pub loaded: Vec<LoadedModel>,
+    /// Models being loaded
+    pub loading: Vec<String>,
+    /// Memory held by the loaded models, as far as it is known
+    pub used_mb: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget_mb: Option<u64>,
+    /// Free memory on the host, where it can be read
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub available_mb: Option<u64>,
+    /// Models unloaded to make room since startup
+    pub evictions: u64,
+    /// Draft acceptance of each target and draft pairing used since startup
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub drafts: Vec<DraftStats>,
+}
+
+struct Entry<M> {
+    model: Arc<Mutex<M>>,
+    info: LoadedModel,
+}
+
+struct Models<M> {
+    entries: HashMap<String, Entry<M>>,
+    loading: HashSet<String>,
+    evictions: u64,
+    drafts: HashMap<String, DraftStats>,
+}
+
+/// Loaded models, unloaded least recently used first under memory pressure
+pub struct ModelCache<M> {
+    loader: ModelLoader<M>,
+    memory: MemoryProbe,
+    budget_mb: Option<u64>,
+    min_free_mb: u64,
+    max_loaded: Option<usize>,
+    models: Mutex<Models<M>>,
+    /// Loads run one at a time, so each is measured on its own
+    load_lock: tokio::sync::Mutex<()>,
+}
+
+impl<M: Send + 'static> ModelCache<M> {
+    pub fn new(
+        config: &LocalInferenceConfig,
+        loader: impl Fn(&str) -> Result<M> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            loader: Arc::new(loader),
+            memory: Arc::new(available_memory_mb),
+            budget_mb: config.memory_budget_mb,
+            min_free_mb: config.min_free_memory_mb,
+            max_loaded: config.max_loaded_models,
+            models: Mutex::new(Models {
+                entries: HashMap::new(),
+                loading: HashSet::new(),
+                evictions: 0,
+                drafts: HashMap::new(),
+            }),
+            load_lock: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Read free memory with `probe` instead of from the host
+    pub fn with_memory_probe(
+        mut self,
+        probe: impl Fn() -> Option<u64> + Send + Sync + 'static,
+    ) -> Self {
+        self.memory = Arc::new(probe);
+        self
+    }
+
+    /// The model for a request, loading it if it is not loaded
+    pub async fn get(&self, model: &str) -> Result<Arc<Mutex<M>>> {
+        if let Some(loaded) = self.touch(model) {
+            return Ok(loaded);
+        }
+        self.load_model(model, false)
+            .await
+            .map(|(loaded, _)| loaded)
+    }
+
+    /// Load a model ahead of requests for it
+    pub async fn load(&self, model: &str) -> Result<LoadedModel> {
+        if let Some(entry) = self.lock().entries.get(model) {
+            return Ok(entry.info.clone());
+        }
+        self.load_model(model, false).await.map(|(_, info)| info)
+    }
+
+    /// Unload a model; requests using it finish first. Returns whether it
+    /// was loaded.
+    pub fn unload(&self, model: &str) -> bool {
+        let removed = self.lock().entries.remove(model).is_some();
+        if removed {
+            info!("Unloaded local model {model}");
+        }
+        removed
+    }
+
+    /// Load models at startup, logging those that fail
+    pub async fn prewarm(&self, models: &[String]) {
+        for model in models {
+            let started = std::time::Instant::now();
+            match self.load_model(model, true).await {
+                Ok(_) => info!(
+                    "Prewarmed local model {model} in {:.1}s",
+                    started.elapsed().as_secs_f64()
+                ),
+                Err(e) => warn!("Failed to prewarm local model {model}: {e}"),
+            }
+        }
+    }
+
+    pub fn status(&self) -> ModelCacheStatus {
+        let models = self.lock();
+        let mut loaded: Vec<_> = models.entries.values().map(|e| e.info.clone()).collect();
+        loaded.sort_by(|a, b| b.last_used_at.cmp(&a.last_used_at));
+        let mut loading: Vec<_> = models.loading.iter().cloned().collect();
+        loading.sort();
+        let mut drafts: Vec<_> = models.drafts.values().cloned().collect();
+        drafts.sort_by(|a, b| a.target.cmp(&b.target));
+        ModelCacheStatus {
+            used_mb: loaded.iter().filter_map(|m| m.memory_mb).sum(),
+            loaded,
+            loading,
+            budget_mb: self.budget_mb,
+            available_mb: (self.memory)(),
+            evictions: models.evictions,
+            drafts,
+        }
+    }
+
+    /// Count draft tokens `target` checked and accepted from `draft`
+    pub fn record_draft(&self, target: &str, draft: &str, proposed: u64, accepted: u64) {
+        self.lock()
+            .drafts
+            .entry(target.to_string())
+            .or_insert_with(|| DraftStats::new(target, draft))
+            .record(proposed, accepted);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Models<M>> {
+        self.models.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// A loaded model, marked as used
+    fn touch(&self, model: &str) -> Option<Arc<Mutex<M>>> {
+        let mut models = self.lock();
+        let entry = models.entries.get_mut(model)?;
+        entry.info.last_used_at = Utc::now();
+        entry.info.uses += 1;
+        Some(entry.model.clone())
+    }
+
+    async fn load_model(
+        &self,
+        model: &str,
+        prewarmed: bool,
+    ) -> Result<(Arc<Mutex<M>>, LoadedModel)> {
+        let _load = self.load_lock.lock().await;
+        // Another request may have loaded it while this one waited
+        if let Some(entry) = self.lock().entries.get(model) {
+            return Ok((entry.model.clone(), entry.info.clone()));
+        }
+
+        self.lock().loading.insert(model.to_string());
+        self.relieve_pressure(None);
+        let before = (self.memory)();
+        let loader = self.loader.clone();
+        let name = model.to_string();
+        let loaded = tokio::task::spawn_blocking(move || loader(&name)).await;
+        self.lock().loading.remove(model);
+        let loaded = loaded.map_err(|e| Error::Internal(format!("Model load panicked: {e}")))??;
+        let memory_mb = before
+            .zip((self.memory)())
+            .map(|(before, after)| before.saturating_sub(after));
+
+        let now = Utc::now();
+        let info = LoadedModel {
+            model: model.to_string(),
+            loaded_at: now,
+            last_used_at: now,
+            memory_mb,
+            uses: u64::from(!prewarmed),
+            prewarmed,
+        };
+        let loaded = Arc::new(Mutex::new(loaded));
+        self.lock().entries.insert(
+            model.to_string(),
+            Entry {
+                model: loaded.clone(),
+                info: info.clone(),
+            },
+        );
+        self.relieve_pressure(Some(model));
+        Ok((loaded, info))
+    }
+
+    /// Unload least recently used models until the loaded ones fit. Before
+    /// a load (`keep` is `None`) this also makes room for one more model;
+    /// after it, the model just loaded is kept.
+    fn relieve_pressure(&self, keep: Option<&str>) {
+        let slots = usize::from(keep.is_none());
+        let available = (self.memory)();
+        let mut models = self.lock();
+        // Memory is freed when the last request using a model finishes, so
+        // what unloading frees is counted rather than read back
+        let mut freed = 0;
+        loop {
+            let used: u64 = models
+                .entries
+                .values()
+                .filter_map(|e| e.info.memory_mb)
+                .sum();
+            let over_count = self
+                .max_loaded
+                .is_some_and(|max| models.entries.len() + slots > max);
+            let over_budget = self.budget_mb.is_some_and(|budget| used > budget);
+            let low_memory = available.is_some_and(|a| a + freed < self.min_free_mb);
+            if !(over_count || over_budget || low_memory) {
+                return;
+            }
+
+            let Some(victim) = models
+                .entries
+                .values()
+                .filter(|e| Some(e.info.model.as_str()) != keep)
+                .min_by_key(|e| e.info.last_used_at)
+                .map(|e| e.info.model.clone())
+            else {
+                if low_memory {
+                    warn!(
+                        "Free memory is below {} MB with no local model left to unload",
+                        self.min_free_mb
+                    );
+                }
+                return;
+            };
+            let entry = models.entries.remove(&victim).expect("victim is loaded");
+            freed += entry.info.memory_mb.unwrap_or_default();
+            models.evictions += 1;
+            info!(
+                "Unloaded local model {victim} to relieve memory pressure (over_count={over_count}, over_budget={over_budget}, low_memory={low_memory})"
+            );
+        }
+    }
+}
+
+/// Free memory on the host in MB
+#[cfg(target_os = "linux")]
+pub fn available_memory_mb() -> Option<u64> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+    let line = meminfo.lines().find(|l| l.starts_with("MemAvailable:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb / 1024)
+}
+
+/// Free memory on the host in MB
+#[cfg(not(target_os = "linux"))]
+pub fn available_memory_mb() -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    #[tokio::test]
+    async fn test_least_recently_used_models_are_unloaded_over_budget() {
+        // Every load takes 400 MB of the host's free memory
+        let free = Arc::new(AtomicU64::new(4000));
+        let loader_free = free.clone();
+        let config = LocalInferenceConfig {
+            memory_budget_mb: Some(1000),
+            min_free_memory_mb: 500,
+            ..Default::default()
+        };
+        let cache = ModelCache::new(&config, move |model: &str| {
+            let _ = loader_free.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |f| {
+                Some(f.saturating_sub(400))
+            });
+            Ok(model.to_string())
+        })
+        .with_memory_probe({
+            let free = free.clone();
+            move || Some(free.load(Ordering::SeqCst))
+        });
+
+        cache.prewarm(&["a".to_string(), "b".to_string()]).await;
+        assert_eq!(cache.status().used_mb, 800);
+        assert!(cache.status().loaded.iter().all(|m| m.prewarmed));
+
+        // Using a makes b the least recently used, so b goes to fit c
+        cache.get("a").await.unwrap();
+        cache.get("c").await.unwrap();
+        let status = cache.status();
+        let loaded: Vec<_> = status.loaded.iter().map(|m| m.model.as_str()).collect();
+        assert_eq!(loaded, ["c", "a"]);
+        assert_eq!(status.evictions, 1);
+
+        // Low free memory unloads models until the floor is met
+        free.store(300, Ordering::SeqCst);
+        cache.get("d").await.unwrap();
+        let status = cache.status();
+        assert_eq!(status.loaded.len(), 1);
+        assert_eq!(status.loaded[0].model, "d");
+
+        assert!(cache.unload("d"));
+        assert!(!cache.unload("d"));
+    }
+}
@@ -0,0 +1,107 @@
+//! Read-only observer accounts
+//!
+//! Observers are for wallboard dashboards and monitoring integrations. They
+//! may read status, health, usage analytics and provider health, and nothing
+//! else: no prompts or conversations, no keys or configuration, and no
+//! changes of any kind. Users get the observer role from an admin and API
+//! keys are created with the observer scope; either way the token carries
+//! the scope, and requests outside the observer routes are refused before
+//! they reach a handler.
+
+use axum::{
+    extract::Request,
+    http::Method,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use gate_core::User;
+use gate_core::access::{
+    Action, IdentityContext, ObjectId, ObjectIdentity, ObjectKind, TargetNamespace,
+};
+use gate_http::{error::HttpError, services::HttpIdentity};
+
+/// Token scope carried by observer sessions and API keys
+pub const OBSERVER_SCOPE: &str = "observer";
+
+/// Identity attribute holding the token scope
+pub const SCOPE_ATTRIBUTE: &str = "scope";
+
+/// Routes observers may read
+const OBSERVER_PATHS: [&str; 10] = [
+    "/health",
+    crate::services::readiness::LIVENESS_PATH,
+    crate::services::readiness::READINESS_PATH,
+    "/metrics",
+    "/api/status",
+    "/api/admin/usage",
+    "/api/admin/spend",
+    "/api/admin/doctor",
+    "/api/admin/providers",
+    crate::services::server_mode::MODE_PATH,
+];
+
+fn system(id: &str) -> ObjectIdentity {
+    ObjectIdentity {
+        namespace: TargetNamespace::System,
+        kind: ObjectKind::System,
+        id: ObjectId::new(id),
+    }
+}
+
+/// Permissions the observer routes check, granted to observers
+pub fn observer_permissions() -> Vec<(Action, ObjectIdentity)> {
+    vec![
+        (Action::Read, system("usage")),
+        (Action::Read, system("daemon")),
+        (Action::Read, system("mode")),
+        (Action::Read, ObjectIdentity::local_provider("*")),
+    ]
+}
+
+/// Scope of the tokens issued to a user: observer for users with the
+/// observer role, unrestricted otherwise
+pub fn token_scope(user: &User) -> Option<&'static str> {
+    let role = user.metadata.get(crate::routes::admin::ROLE_METADATA_KEY);
+    (role.map(String::as_str) == Some(OBSERVER_SCOPE)).then_some(OBSERVER_SCOPE)
+}
+
+/// Whether the identity carries the observer scope
+pub fn is_observer(identity: &HttpIdentity) -> bool {
+    identity.context.get(SCOPE_ATTRIBUTE) == Some(OBSERVER_SCOPE)
+}
+
+/// Whether an observer may make this request
+pub fn observer_allows(method: &Method, path: &str) -> bool {
+    matches!(*method, Method::GET | Method::HEAD) && OBSERVER_PATHS.contains(&path)
+}
+
+/// Refuse observer requests outside the observer routes
+pub async fn observer_middleware(request: Request, next: Next) -> Response {
+    let observer = request
+        .extensions()
+        .get::<HttpIdentity>()
+        .is_some_and(is_observer);
+    if observer && !observer_allows(request.method(), request.uri().path()) {
+        return HttpError::AuthorizationFailed(
+            "Observer accounts can only read status, usage and health".to_string(),
+        )
+        .into_response();
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_observers_only_read_observer_routes() {
+        assert!(observer_allows(&Method::GET, "/api/admin/usage"));
+        assert!(observer_allows(&Method::HEAD, "/health"));
+        assert!(!observer_allows(&Method::PUT, "/api/admin/mode"));
+        assert!(!observer_allows(&Method::GET, "/api/prompts"));
+        assert!(!observer_allows(&Method::GET, "/api/keys"));
+        assert!(!observer_allows(&Method::GET, "/api/admin/usage/../users"));
+        assert!(!observer_allows(&Method::POST, "/v1/chat/completions"));
+    }
+}
@@ -1,6 +1,7 @@
 use anyhow::Result;
 use gate_http::server::HttpServer;
 use gate_p2p::{Endpoint, NodeAddr, Router, SecretKey, discovery::static_provider::StaticProvider};
+use gate_tlsforward::client::TrafficMeter;
 use gate_tlsforward::{TLS_FORWARD_ALPN, TlsForwardHandler};
 use std::path::Path;
 use std::sync::Arc;
@@ -57,6 +58,7 @@ impl P2PManager {
         http_server: Arc<HttpServer>,
         max_connections: usize,
         connection_timeout_secs: u64,
+        traffic: TrafficMeter,
     ) -> Result<()> {
         // Create TLS forward handler
         let tls_handler = TlsForwardHandler::new(
@@ -64,7 +66,8 @@ impl P2PManager {
             http_server,
             max_connections,
             connection_timeout_secs,
-        );
+        )
+        .with_traffic_meter(traffic);
 
         // Create router and register the TLS forward handler
         let router = Router::builder(self.endpoint.as_ref().clone())
@@ -116,7 +119,7 @@ impl P2PManager {
 }
 
 /// Load or create P2P secret key
-async fn load_or_create_p2p_secret_key(path: &Path) -> Result<SecretKey> {
+pub(crate) async fn load_or_create_p2p_secret_key(path: &Path) -> Result<SecretKey> {
     use tokio::fs;
 
     if path.exists() {
@@ -0,0 +1,218 @@
+//! Pairing codes for adding devices to an existing account
+//!
+//! An admin creates a short-lived, single-use code for a user. The new device
+//! redeems it at `/auth/pair` for a WebAuthn registration challenge, and the
+//! passkey it registers is attached to that user as a named device rather
+//! than creating a new account. Registrations not finished within the code
+//! TTL are dropped along with expired codes. Failed redemptions count
+//! against the client's address in the sign-in lockout.
+
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+/// How long a pairing code can be redeemed, and a registration started from
+/// it finished
+pub const PAIRING_CODE_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Unambiguous characters: no 0/O or 1/I
+const CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+/// An outstanding pairing code
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PairingCode {
+    /// Code to type on the new device, formatted `XXXX-XXXX`
+    pub code: String,
+    /// User the device will be added to
+    pub user_id: String,
+    /// Name the device will be listed under
+    pub device_name: Option<String>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Registration started from a redeemed code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingDevice {
+    pub user_id: String,
+    pub device_name: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct PairingState {
+    codes: HashMap<String, PairingCode>,
+    /// WebAuthn registration session id to the device being registered, and
+    /// when the registration lapses
+    registrations: HashMap<String, (PendingDevice, DateTime<Utc>)>,
+}
+
+impl PairingState {
+    /// Drop lapsed codes and registrations
+    fn prune(&mut self, now: DateTime<Utc>) {
+        self.codes.retain(|_, c| c.expires_at > now);
+        self.registrations
+            .retain(|_, (_, expires_at)| *expires_at > now);
+    }
+}
+
+fn ttl() -> chrono::Duration {
+    chrono::Duration::from_std(PAIRING_CODE_TTL).expect("TTL fits in chrono")
+}
+
+/// Issues and redeems device pairing codes
+#[derive(Debug, Clone, Default)]
+pub struct PairingService {
+    state: Arc<Mutex<PairingState>>,
+}
+
+/// Strip separators and case so `abcd efgh` matches `ABCD-EFGH`
+fn normalize(code: &str) -> String {
+    code.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+impl PairingService {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a code that adds a device to `user_id`
+    pub async fn create_code(&self, user_id: &str, device_name: Option<String>) -> PairingCode {
+        let mut rng = rand::thread_rng();
+        let raw: String = (0..8)
+            .map(|_| CODE_ALPHABET[rng.gen_range(0..CODE_ALPHABET.len())] as char)
+            .collect();
+        let now = Utc::now();
+        let code = PairingCode {
+            code: format!("{}-{}", &raw[..4], &raw[4..]),
+            user_id: user_id.to_string(),
+            device_name,
+            expires_at: now + ttl(),
+        };
+
+        let mut state = self.state.lock().await;
+        state.prune(now);
+        state.codes.insert(raw, code.clone());
+        code
+    }
+
+    /// Outstanding codes created for `user_id`
+    pub async fn list_codes(&self, user_id: &str) -> Vec<PairingCode> {
+        let state = self.state.lock().await;
+        let now = Utc::now();
+        state
+            .codes
+            .values()
+            .filter(|c| c.user_id == user_id && c.expires_at > now)
+            .cloned()
+            .collect()
+    }
+
+    /// Consume a code. Returns `None` for unknown or expired codes.
+    pub async fn redeem(&self, code: &str) -> Option<PairingCode> {
+        let mut state = self.state.lock().await;
+        state
+            .codes
+            .remove(&normalize(code))
+            .filter(|c| c.expires_at > Utc::now())
+    }
+
+    /// Remember which device a registration session belongs to
+    pub async fn begin_registration(&self, session_id: String, device: PendingDevice) {
+        let now = Utc::now();
+        let mut state = self.state.lock().await;
+        state.prune(now);
+        state
+            .registrations
+            .insert(session_id, (device, now + ttl()));
+    }
+
+    /// Take the device a registration session was started for. Returns
+    /// `None` for unknown or lapsed sessions.
+    pub async fn finish_registration(&self, session_id: &str) -> Option<PendingDevice> {
+        self.state
+            .lock()
+            .await
+            .registrations
+            .remove(session_id)
+            .filter(|(_, expires_at)| *expires_at > Utc::now())
+            .map(|(device, _)| device)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_code_is_single_use_and_tolerates_formatting() {
+        let service = PairingService::new();
+        let code = service
+            .create_code("user-1", Some("Phone".to_string()))
+            .await;
+        assert_eq!(code.code.len(), 9);
+
+        let typed = code.code.to_lowercase().replace('-', " ");
+        let redeemed = service.redeem(&typed).await.unwrap();
+        assert_eq!(redeemed.user_id, "user-1");
+        assert!(service.redeem(&code.code).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_registration_session_maps_to_device() {
+        let service = PairingService::new();
+        let device = PendingDevice {
+            user_id: "user-1".to_string(),
+            device_name: None,
+        };
+        service
+            .begin_registration("session".to_string(), device.clone())
+            .await;
+        assert_eq!(
+            service.finish_registration("session").await,
+            Some(device.clone())
+        );
+        assert_eq!(service.finish_registration("session").await, None);
+
+        // Lapsed registrations are refused, and dropped as others begin
+        service
+            .begin_registration("stale".to_string(), device.clone())
+            .await;
+        service
+            .state
+            .lock()
+            .await
+            .registrations
+            .get_mut("stale")
+            .unwrap()
+            .1 = Utc::now();
+        service
+            .begin_registration("fresh".to_string(), device.clone())
+            .await;
+        assert!(
+            !service
+                .state
+                .lock()
+                .await
+                .registrations
+                .contains_key("stale")
+        );
+        service
+            .begin_registration("stale".to_string(), device)
+            .await;
+        service
+            .state
+            .lock()
+            .await
+            .registrations
+            .get_mut("stale")
+            .unwrap()
+            .1 = Utc::now();
+        assert_eq!(service.finish_registration("stale").await, None);
+    }
+}
@@ -0,0 +1,264 @@
+//! The router's middleware pipeline, assembled from settings
+//!
+//! Parameter profiles and the automatic model always run first, when
+//! configured. The stages after them follow `routing.middleware`, or the
+//! built-in chain when that is unset. API key and user limits are checked
+//! last, on the model the request is routed to, by one [`UsageLimits`] kept
+//! across rebuilds so its request windows survive a reload. The content filter policy
+//! follows `routing.content_filter`. The pipeline is rebuilt whenever the
+//! configuration is applied and swapped into the running router, so
+//! requests already under way finish with the one they started with.
+
+use crate::config::{MiddlewareConfig, Settings};
+use crate::services::api_keys::ApiKeyScope;
+use crate::services::compression::CompressionSettings;
+use crate::services::content_filter::content_filter_policy;
+use crate::services::journal::Journal;
+use crate::services::key_capture::DaemonKeyRegistrar;
+use crate::services::{
+    CompressionMiddleware, DocumentStore, FileReferenceMiddleware, FileStore, MessageSigner,
+    RetrievalMiddleware, UsageLimits,
+};
+use gate_core::router::middleware::{
+    AutoRouter, CostTrackerMiddleware, DeprecationMiddleware, ExperimentMiddleware,
+    KeyCaptureMiddleware, MaxTokensMiddleware, MonitoringMiddleware, ParameterProfileMiddleware,
+    PromptTemplateMiddleware, ProvenanceMiddleware, RateLimitConfig, RateLimitMiddleware,
+    ResponseTransformMiddleware, StreamSlotsMiddleware,
+};
+use gate_core::router::types::QuotaBehavior;
+use gate_core::router::{Pipeline, Router};
+use gate_core::state::StateBackend;
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// What the pipeline's stages are built from, besides settings
+pub struct PipelineFactory {
+    state_backend: Arc<dyn StateBackend>,
+    registrar: Arc<DaemonKeyRegistrar>,
+    file_store: FileStore,
+    document_store: DocumentStore,
+    signer: Option<Arc<MessageSigner>>,
+    journal: Option<Journal>,
+    limits: Arc<UsageLimits>,
+}
+
+impl PipelineFactory {
+    pub fn new(
+        state_backend: Arc<dyn StateBackend>,
+        registrar: Arc<DaemonKeyRegistrar>,
+        file_store: FileStore,
+        document_store: DocumentStore,
+    ) -> Self {
+        Self {
+            limits: Arc::new(UsageLimits::new(state_backend.clone())),
+            state_backend,
+            registrar,
+            file_store,
+            document_store,
+            signer: None,
+            journal: None,
+        }
+    }
+
+    /// Queue webhook events through `journal`; without one they are skipped
+    pub fn with_journal(mut self, journal: Journal) -> Self {
+        self.journal = Some(journal);
+        self
+    }
+
+    /// Sign provenance blocks with `signer`; without one they are skipped
+    pub fn with_signer(mut self, signer: Arc<MessageSigner>) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    /// Pipeline `settings` describe, for `router`. Stages that call back
+    /// into the router are bound to it.
+    pub fn build(&self, settings: &Settings, router: &Arc<Router>) -> Pipeline {
+        let state_backend = &self.state_backend;
+        let routing = &settings.routing;
+        let mut pipeline = Pipeline::default();
+
+        if !routing.profiles.is_empty() {
+            let routing = routing.clone();
+            let profiles = Arc::new(ParameterProfileMiddleware::new(
+                state_backend.clone(),
+                move |ctx, key| {
+                    let mut subjects = vec![ctx.identity.id.as_str()];
+                    subjects.extend(ctx.identity.context.user_id.as_deref());
+                    let key_profile = key.and_then(|k| ApiKeyScope::from_key(k).parameter_profile);
+                    routing.profiles_for(&subjects, key_profile.as_deref())
+                },
+            ));
+            // Profiles come first, so a default model of gate/auto is resolved
+            pipeline.rewriters.push(profiles.clone());
+            pipeline.middleware.push(profiles);
+        }
+        // The automatic model resolves next, so its choice can still be
+        // deprecated or experimented on
+        if let Some(routes) = routing.auto_routes() {
+            pipeline
+                .rewriters
+                .push(Arc::new(AutoRouter::new(routes, state_backend.clone())));
+        }
+
+        for stage in routing.middleware_chain() {
+            match stage {
+                MiddlewareConfig::Provenance => {
+                    let (Some(config), Some(signer)) = (&routing.provenance, &self.signer) else {
+                        continue;
+                    };
+                    let gateway_id = config
+                        .gateway_id
+                        .clone()
+                        .unwrap_or_else(|| signer.key_id().to_string());
+                    pipeline.middleware.push(Arc::new(
+                        ProvenanceMiddleware::new(gateway_id, signer.clone())
+                            .with_streams(!config.skip_streams),
+                    ));
+                }
+                MiddlewareConfig::StreamSlots => {
+                    pipeline
+                        .middleware
+                        .push(Arc::new(StreamSlotsMiddleware::new(
+                            state_backend.clone(),
+                            |key| ApiKeyScope::from_key(key).max_concurrent_streams,
+                        )));
+                }
+                MiddlewareConfig::Deprecations => {
+                    let deprecations =
+                        Arc::new(DeprecationMiddleware::new(routing.deprecation_rules()));
+                    pipeline.rewriters.push(deprecations.clone());
+                    pipeline.middleware.push(deprecations);
+                }
+                MiddlewareConfig::Experiments => {
+                    let experiments = Arc::new(ExperimentMiddleware::new(state_backend.clone()));
+                    pipeline.rewriters.push(experiments.clone());
+                    pipeline.middleware.push(experiments);
+                }
+                MiddlewareConfig::CostTracker => {
+                    pipeline
+                        .middleware
+                        .push(Arc::new(CostTrackerMiddleware::new(state_backend.clone())));
+                }
+                MiddlewareConfig::KeyCapture => {
+                    pipeline
+                        .middleware
+                        .push(Arc::new(KeyCaptureMiddleware::new(self.registrar.clone())));
+                }
+                MiddlewareConfig::PromptTemplates => {
+                    pipeline
+                        .middleware
+                        .push(Arc::new(PromptTemplateMiddleware::new(
+                            state_backend.clone(),
+                        )));
+                }
+                MiddlewareConfig::FileReferences => {
+                    pipeline
+                        .middleware
+                        .push(Arc::new(FileReferenceMiddleware::new(
+                            self.file_store.clone(),
+                        )));
+                }
+                MiddlewareConfig::Retrieval => {
+                    let retrieval = Arc::new(RetrievalMiddleware::new(
+                        self.document_store.clone(),
+                        state_backend.clone(),
+                        settings.documents.embedding_model.clone(),
+                    ));
+                    // Queries are embedded through the router the middleware runs in
+                    retrieval.bind(router);
+                    pipeline.middleware.push(retrieval);
+                }
+                MiddlewareConfig::Compression => {
+                    let Some(config) = &routing.compression else {
+                        continue;
+                    };
+                    let compression = Arc::new(CompressionMiddleware::new(CompressionSettings {
+                        model: config.model.clone(),
+                        threshold: config.threshold,
+                        keep_recent: config.keep_recent,
+                    }));
+                    compression.bind(router);
+                    pipeline.middleware.push(compression);
+                }
+                MiddlewareConfig::MaxTokens => {
+                    pipeline.middleware.push(Arc::new(
+                        MaxTokensMiddleware::new().with_state_backend(state_backend.clone()),
+                    ));
+                }
+                MiddlewareConfig::ResponseTransform => {
+                    pipeline
+                        .middleware
+                        .push(Arc::new(ResponseTransformMiddleware::new(
+                            state_backend.clone(),
+                        )));
+                }
+                MiddlewareConfig::RateLimit {
+                    requests_per_minute,
+                    tokens_per_minute,
+                    warn_only,
+                } => {
+                    pipeline
+                        .middleware
+                        .push(Arc::new(RateLimitMiddleware::new(RateLimitConfig {
+                            requests_per_minute,
+                            tokens_per_minute,
+                            behavior: if warn_only {
+                                QuotaBehavior::WarnOnly
+                            } else {
+                                QuotaBehavior::Reject
+                            },
+                        })));
+                }
+                MiddlewareConfig::Monitoring => {
+                    pipeline
+                        .middleware
+                        .push(Arc::new(MonitoringMiddleware::default()));
+                }
+            }
+        }
+        pipeline.rewriters.push(self.limits.clone());
+        pipeline.content_filter = routing.content_filter.as_ref().map(|config| {
+            Arc::new(content_filter_policy(
+                config,
+                &settings.providers,
+                self.journal.as_ref(),
+            ))
+        });
+        pipeline
+    }
+
+    /// Rebuild the pipeline of `router`, built from `applied`, each time
+    /// the routing or document settings change. Runs until the settings
+    /// channel closes and is meant to be spawned.
+    pub async fn follow(
+        self,
+        router: Arc<Router>,
+        mut applied: Settings,
+        mut settings: watch::Receiver<Settings>,
+    ) {
+        let relevant =
+            |s: &Settings| serde_json::to_value((&s.routing, &s.documents.embedding_model)).ok();
+        loop {
+            let current = settings.borrow_and_update().clone();
+            if relevant(&current) != relevant(&applied) {
+                router.replace_pipeline(self.build(&current, &router));
+                info!(
+                    "Rebuilt router middleware: {}",
+                    current
+                        .routing
+                        .middleware_chain()
+                        .iter()
+                        .map(MiddlewareConfig::kind)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+                applied = current;
+            }
+            if settings.changed().await.is_err() {
+                break;
+            }
+        }
+    }
+}
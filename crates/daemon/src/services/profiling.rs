@@ -0,0 +1,372 @@
+//! Performance profiles captured on demand
+//!
+//! Admins capture a CPU profile, sampled while the daemon keeps serving and
+//! rendered as a flamegraph, or a heap profile, which samples the memory
+//! the kernel reports for the process every second along with the async
+//! runtime's task counts. Either runs in the background for the requested
+//! time; the job and what it captured are kept under the state directory
+//! until newer profiles push them out, and can be downloaded once complete.
+
+use crate::error::{DaemonError, Result};
+use crate::helpers::ids::{is_valid_id, new_id};
+use crate::services::memory::process_memory;
+use chrono::{DateTime, Utc};
+use gate_core::router::buffers::BufferBudget;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+const PROFILE_ID_PREFIX: &str = "profile-";
+
+/// Profiles kept on disk; older ones are removed as new ones start
+const KEEP: usize = 20;
+
+/// CPU samples taken per second
+#[cfg(unix)]
+const CPU_SAMPLE_HZ: i32 = 99;
+
+/// Time between heap samples
+const HEAP_SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Set while a CPU profile runs; the sampler is process-wide
+static CPU_PROFILING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileKind {
+    /// CPU samples rendered as an SVG flamegraph
+    Cpu,
+    /// Process memory and runtime task counts sampled over time, as JSON
+    Heap,
+}
+
+impl ProfileKind {
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Cpu => "svg",
+            Self::Heap => "json",
+        }
+    }
+
+    /// Media type of the captured profile
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Cpu => "image/svg+xml",
+            Self::Heap => "application/json",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileStatus {
+    Running,
+    Complete,
+    Failed,
+}
+
+/// A requested profile and its outcome
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProfileJob {
+    pub id: String,
+    pub kind: ProfileKind,
+    /// Admin who asked for the profile
+    pub requested_by: String,
+    /// How long the profile samples for
+    pub seconds: u64,
+    pub status: ProfileStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Size of the captured profile once complete
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<u64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ProfileJob {
+    /// File name offered when the profile is downloaded
+    pub fn file_name(&self) -> String {
+        format!("{}.{}", self.id, self.kind.extension())
+    }
+}
+
+/// One heap sample
+#[derive(Debug, Clone, Serialize)]
+pub struct HeapSample {
+    pub at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resident_bytes: Option<u64>,
+    /// Highest resident memory since the process started
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peak_resident_bytes: Option<u64>,
+    /// Heap and other private data mapped by the process
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub swap_bytes: Option<u64>,
+    /// Response bytes held against the buffer budget
+    pub buffered_response_bytes: usize,
+    /// Tasks alive on the async runtime
+    pub alive_tasks: usize,
+    /// Tasks waiting in the runtime's global queue
+    pub queued_tasks: usize,
+    pub runtime_workers: usize,
+}
+
+impl HeapSample {
+    fn take(runtime: &tokio::runtime::Handle) -> Self {
+        let metrics = runtime.metrics();
+        Self {
+            at: Utc::now(),
+            resident_bytes: process_memory("VmRSS"),
+            peak_resident_bytes: process_memory("VmHWM"),
+            data_bytes: process_memory("VmData"),
+            swap_bytes: process_memory("VmSwap"),
+            buffered_response_bytes: BufferBudget::shared().used(),
+            alive_tasks: metrics.num_alive_tasks(),
+            queued_tasks: metrics.global_queue_depth(),
+            runtime_workers: metrics.num_workers(),
+        }
+    }
+}
+
+/// Profile jobs and what they captured, stored on disk
+#[derive(Debug, Clone)]
+pub struct ProfileStore {
+    dir: PathBuf,
+}
+
+impl ProfileStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Whether a CPU profile is being captured
+    pub fn cpu_busy() -> bool {
+        CPU_PROFILING.load(Ordering::Relaxed)
+    }
+
+    fn job_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{id}.job.json"))
+    }
+
+    fn profile_path(&self, job: &ProfileJob) -> PathBuf {
+        self.dir.join(job.file_name())
+    }
+
+    async fn put(&self, job: &ProfileJob) -> Result<()> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        tokio::fs::write(self.job_path(&job.id), serde_json::to_vec_pretty(job)?).await?;
+        Ok(())
+    }
+
+    /// A profile job, if it exists
+    pub async fn get(&self, id: &str) -> Result<Option<ProfileJob>> {
+        if !is_valid_id(PROFILE_ID_PREFIX, id) {
+            return Ok(None);
+        }
+        match tokio::fs::read(self.job_path(id)).await {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Profile jobs, newest first
+    pub async fn list(&self) -> Result<Vec<ProfileJob>> {
+        let mut entries = match tokio::fs::read_dir(&self.dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut jobs = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            let name = entry.file_name();
+            let Some(id) = name.to_str().and_then(|n| n.strip_suffix(".job.json")) else {
+                continue;
+            };
+            if let Some(job) = self.get(id).await? {
+                jobs.push(job);
+            }
+        }
+        jobs.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok(jobs)
+    }
+
+    /// The profile a completed job captured
+    pub async fn read_profile(&self, job: &ProfileJob) -> Result<Vec<u8>> {
+        if !is_valid_id(PROFILE_ID_PREFIX, &job.id) {
+            return Err(DaemonError::InvalidState(format!(
+                "Invalid profile id {}",
+                job.id
+            )));
+        }
+        Ok(tokio::fs::read(self.profile_path(job)).await?)
+    }
+
+    /// Remove all but the newest profiles, keeping room for one more
+    async fn prune(&self) -> Result<()> {
+        for job in self.list().await?.iter().skip(KEEP - 1) {
+            if job.status == ProfileStatus::Running {
+                continue;
+            }
+            let _ = tokio::fs::remove_file(self.profile_path(job)).await;
+            tokio::fs::remove_file(self.job_path(&job.id)).await?;
+        }
+        Ok(())
+    }
+
+    /// Start capturing a profile in the background
+    pub async fn start(
+        &self,
+        kind: ProfileKind,
+        seconds: u64,
+        requested_by: &str,
+    ) -> Result<ProfileJob> {
+        if kind == ProfileKind::Cpu && CPU_PROFILING.swap(true, Ordering::AcqRel) {
+            return Err(DaemonError::InvalidState(
+                "A CPU profile is already being captured".to_string(),
+            ));
+        }
+        if let Err(e) = self.prune().await {
+            warn!("Failed to remove old profiles: {e}");
+        }
+        let now = Utc::now();
+        let job = ProfileJob {
+            id: new_id(PROFILE_ID_PREFIX),
+            kind,
+            requested_by: requested_by.to_string(),
+            seconds,
+            status: ProfileStatus::Running,
+            error: None,
+            bytes: None,
+            created_at: now,
+            updated_at: now,
+        };
+        if let Err(e) = self.put(&job).await {
+            if kind == ProfileKind::Cpu {
+                CPU_PROFILING.store(false, Ordering::Release);
+            }
+            return Err(e);
+        }
+
+        let store = self.clone();
+        let mut running = job.clone();
+        tokio::spawn(async move {
+            let duration = Duration::from_secs(running.seconds);
+            let captured = match running.kind {
+                ProfileKind::Cpu => {
+                    let captured = capture_cpu(duration).await;
+                    CPU_PROFILING.store(false, Ordering::Release);
+                    captured
+                }
+                ProfileKind::Heap => capture_heap(duration).await,
+            };
+            let written = match captured {
+                Ok(data) => tokio::fs::write(store.profile_path(&running), &data)
+                    .await
+                    .map(|()| data.len() as u64)
+                    .map_err(DaemonError::from),
+                Err(e) => Err(e),
+            };
+            match written {
+                Ok(bytes) => {
+                    running.status = ProfileStatus::Complete;
+                    running.bytes = Some(bytes);
+                }
+                Err(e) => {
+                    warn!("Profile {} failed: {e}", running.id);
+                    running.status = ProfileStatus::Failed;
+                    running.error = Some(e.to_string());
+                }
+            }
+            running.updated_at = Utc::now();
+            if let Err(e) = store.put(&running).await {
+                warn!("Failed to record profile {}: {e}", running.id);
+            }
+        });
+        Ok(job)
+    }
+}
+
+/// Sample the CPU for `duration` on a blocking thread and render the
+/// samples as a flamegraph
+#[cfg(unix)]
+async fn capture_cpu(duration: Duration) -> Result<Vec<u8>> {
+    let failed = |e: pprof::Error| DaemonError::ServiceUnavailable(format!("CPU profiler: {e}"));
+    tokio::task::spawn_blocking(move || {
+        let guard = pprof::ProfilerGuardBuilder::default()
+            .frequency(CPU_SAMPLE_HZ)
+            .blocklist(&["libc", "libgcc", "pthread", "vdso"])
+            .build()
+            .map_err(failed)?;
+        std::thread::sleep(duration);
+        let report = guard.report().build().map_err(failed)?;
+        let mut svg = Vec::new();
+        report.flamegraph(&mut svg).map_err(failed)?;
+        Ok(svg)
+    })
+    .await
+    .map_err(|e| DaemonError::ServiceUnavailable(format!("CPU profiler: {e}")))?
+}
+
+#[cfg(not(unix))]
+async fn capture_cpu(_duration: Duration) -> Result<Vec<u8>> {
+    Err(DaemonError::ServiceUnavailable(
+        "CPU profiles can only be captured on Unix platforms".to_string(),
+    ))
+}
+
+/// Sample memory and runtime tasks every second for `duration`
+async fn capture_heap(duration: Duration) -> Result<Vec<u8>> {
+    let runtime = tokio::runtime::Handle::current();
+    let started = tokio::time::Instant::now();
+    let mut samples = vec![HeapSample::take(&runtime)];
+    while started.elapsed() < duration {
+        tokio::time::sleep(HEAP_SAMPLE_INTERVAL.min(duration)).await;
+        samples.push(HeapSample::take(&runtime));
+    }
+    let peak = samples.iter().filter_map(|s| s.resident_bytes).max();
+    let growth = match (samples.first(), samples.last()) {
+        (Some(first), Some(last)) => last
+            .resident_bytes
+            .zip(first.resident_bytes)
+            .map(|(last, first)| last as i64 - first as i64),
+        _ => None,
+    };
+    Ok(serde_json::to_vec_pretty(&serde_json::json!({
+        "interval_seconds": HEAP_SAMPLE_INTERVAL.as_secs(),
+        "peak_resident_bytes": peak,
+        "resident_growth_bytes": growth,
+        "samples": samples,
+    }))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_heap_profile_completes_and_can_be_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ProfileStore::new(dir.path());
+        let job = store.start(ProfileKind::Heap, 0, "admin").await.unwrap();
+        assert_eq!(job.status, ProfileStatus::Running);
+        assert!(store.get("../etc/passwd").await.unwrap().is_none());
+
+        let job = loop {
+            let job = store.get(&job.id).await.unwrap().unwrap();
+            if job.status != ProfileStatus::Running {
+                break job;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        };
+        assert_eq!(job.status, ProfileStatus::Complete);
+        let profile: serde_json::Value =
+            serde_json::from_slice(&store.read_profile(&job).await.unwrap()).unwrap();
+        assert_eq!(profile["samples"].as_array().unwrap().len(), 1);
+        assert_eq!(store.list().await.unwrap(), vec![job]);
+    }
+}
@@ -0,0 +1,290 @@
+//! Prompt size analytics
+//!
+//! Usage records carry the bytes sent and received and whether the prompt
+//! met the model's context window. From them this reports how prompt sizes
+//! are distributed per model and per user, the largest prompts, and the
+//! clients that keep running into context limits - the places where
+//! trimming prompts saves the most.
+
+use chrono::{DateTime, Utc};
+use gate_core::UsageRecord;
+use gate_core::router::middleware::{
+    CONTEXT_EXCEEDED, CONTEXT_LIMIT, CONTEXT_NEAR, REQUEST_BYTES, RESPONSE_BYTES,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Prompt token percentiles of a group of requests
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenDistribution {
+    pub p50: u64,
+    pub p90: u64,
+    pub p99: u64,
+    pub max: u64,
+    pub mean: f64,
+}
+
+impl TokenDistribution {
+    fn of(mut tokens: Vec<u64>) -> Self {
+        tokens.sort_unstable();
+        let percentile = |p: usize| {
+            tokens
+                .get((tokens.len() * p / 100).min(tokens.len().saturating_sub(1)))
+                .copied()
+                .unwrap_or_default()
+        };
+        let mean = if tokens.is_empty() {
+            0.0
+        } else {
+            tokens.iter().sum::<u64>() as f64 / tokens.len() as f64
+        };
+        Self {
+            p50: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+            max: tokens.last().copied().unwrap_or_default(),
+            mean,
+        }
+    }
+}
+
+/// Sizes of the requests of one model or user
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SizeSummary {
+    /// Model or user id
+    pub key: String,
+    pub requests: u64,
+    pub prompt_tokens: TokenDistribution,
+    pub request_bytes: u64,
+    pub response_bytes: u64,
+}
+
+/// One of the largest prompts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LargePrompt {
+    pub request_id: String,
+    pub user_id: String,
+    pub api_key_hash: String,
+    pub model: String,
+    pub input_tokens: u64,
+    pub request_bytes: u64,
+    pub cost: f64,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// A client whose prompts met the context window
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContextLimitClient {
+    pub user_id: String,
+    pub api_key_hash: String,
+    pub model: String,
+    pub requests: u64,
+    /// Prompts the provider rejected as too long
+    pub exceeded: u64,
+    /// Prompts that filled most of the context window
+    pub near: u64,
+    pub last_seen: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromptSizeReport {
+    pub requests: u64,
+    pub request_bytes: u64,
+    pub response_bytes: u64,
+    /// Per model, most prompt tokens first
+    pub models: Vec<SizeSummary>,
+    /// Per user, most prompt tokens first
+    pub users: Vec<SizeSummary>,
+    /// Largest prompts, largest first
+    pub largest_prompts: Vec<LargePrompt>,
+    /// Clients that met the context window, most often first
+    pub context_limit_clients: Vec<ContextLimitClient>,
+}
+
+fn metadata_bytes(record: &UsageRecord, key: &str) -> u64 {
+    record
+        .metadata
+        .get(key)
+        .and_then(|v| v.parse().ok())
+        .unwrap_or_default()
+}
+
+fn summaries<'a>(
+    records: &[&'a UsageRecord],
+    key: impl Fn(&'a UsageRecord) -> &'a str,
+    limit: usize,
+) -> Vec<SizeSummary> {
+    let mut groups: BTreeMap<&str, Vec<&UsageRecord>> = BTreeMap::new();
+    for &record in records {
+        groups.entry(key(record)).or_default().push(record);
+    }
+    let mut summaries: Vec<_> = groups
+        .into_iter()
+        .map(|(key, records)| SizeSummary {
+            key: key.to_string(),
+            requests: records.len() as u64,
+            prompt_tokens: TokenDistribution::of(records.iter().map(|r| r.input_tokens).collect()),
+            request_bytes: records
+                .iter()
+                .map(|r| metadata_bytes(r, REQUEST_BYTES))
+                .sum(),
+            response_bytes: records
+                .iter()
+                .map(|r| metadata_bytes(r, RESPONSE_BYTES))
+                .sum(),
+        })
+        .collect();
+    summaries.sort_by(|a, b| {
+        let total = |s: &SizeSummary| s.prompt_tokens.mean * s.requests as f64;
+        total(b).total_cmp(&total(a))
+    });
+    summaries.truncate(limit);
+    summaries
+}
+
+/// Prompt size report over `records`, listing at most `limit` entries of
+/// each kind
+pub fn prompt_size_report(records: &[UsageRecord], limit: usize) -> PromptSizeReport {
+    let all: Vec<&UsageRecord> = records.iter().collect();
+
+    let mut largest: Vec<&UsageRecord> = all.clone();
+    largest.sort_by(|a, b| b.input_tokens.cmp(&a.input_tokens));
+    let largest_prompts = largest
+        .into_iter()
+        .take(limit)
+        .map(|r| LargePrompt {
+            request_id: r.request_id.clone(),
+            user_id: r.user_id.clone(),
+            api_key_hash: r.api_key_hash.clone(),
+            model: r.model_id.clone(),
+            input_tokens: r.input_tokens,
+            request_bytes: metadata_bytes(r, REQUEST_BYTES),
+            cost: r.cost,
+            timestamp: r.timestamp,
+        })
+        .collect();
+
+    let mut clients: BTreeMap<(&str, &str, &str), ContextLimitClient> = BTreeMap::new();
+    for record in records {
+        let client = clients
+            .entry((
+                record.user_id.as_str(),
+                record.api_key_hash.as_str(),
+                record.model_id.as_str(),
+            ))
+            .or_insert_with(|| ContextLimitClient {
+                user_id: record.user_id.clone(),
+                api_key_hash: record.api_key_hash.clone(),
+                model: record.model_id.clone(),
+                requests: 0,
+                exceeded: 0,
+                near: 0,
+                last_seen: record.timestamp,
+            });
+        client.requests += 1;
+        client.last_seen = client.last_seen.max(record.timestamp);
+        match record.metadata.get(CONTEXT_LIMIT).map(String::as_str) {
+            Some(CONTEXT_EXCEEDED) => client.exceeded += 1,
+            Some(CONTEXT_NEAR) => client.near += 1,
+            _ => {}
+        }
+    }
+    let mut context_limit_clients: Vec<_> = clients
+        .into_values()
+        .filter(|c| c.exceeded + c.near > 0)
+        .collect();
+    context_limit_clients.sort_by(|a, b| {
+        (b.exceeded, b.near)
+            .cmp(&(a.exceeded, a.near))
+            .then(b.last_seen.cmp(&a.last_seen))
+    });
+    context_limit_clients.truncate(limit);
+
+    PromptSizeReport {
+        requests: records.len() as u64,
+        request_bytes: records
+            .iter()
+            .map(|r| metadata_bytes(r, REQUEST_BYTES))
+            .sum(),
+        response_bytes: records
+            .iter()
+            .map(|r| metadata_bytes(r, RESPONSE_BYTES))
+            .sum(),
+        models: summaries(&all, |r| r.model_id.as_str(), limit),
+        users: summaries(&all, |r| r.user_id.as_str(), limit),
+        largest_prompts,
+        context_limit_clients,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn record(user: &str, model: &str, tokens: u64, limit: Option<&str>) -> UsageRecord {
+        let mut metadata = HashMap::from([
+            (REQUEST_BYTES.to_string(), (tokens * 4).to_string()),
+            (RESPONSE_BYTES.to_string(), "100".to_string()),
+        ]);
+        if let Some(limit) = limit {
+            metadata.insert(CONTEXT_LIMIT.to_string(), limit.to_string());
+        }
+        UsageRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            org_id: "org".to_string(),
+            user_id: user.to_string(),
+            api_key_hash: String::new(),
+            request_id: format!("{user}-{tokens}"),
+            provider_id: "anthropic".to_string(),
+            model_id: model.to_string(),
+            input_tokens: tokens,
+            output_tokens: 5,
+            total_tokens: tokens + 5,
+            cost: 0.0,
+            timestamp: Utc::now(),
+            metadata,
+        }
+    }
+
+    #[test]
+    fn test_report_ranks_large_prompts_and_context_limits() {
+        let records: Vec<_> = (1..=9)
+            .map(|i| record("alice", "small", i * 10, None))
+            .chain([
+                record("bob", "large", 190_000, Some(CONTEXT_NEAR)),
+                record("bob", "large", 210_000, Some(CONTEXT_EXCEEDED)),
+                record("carol", "large", 195_000, Some(CONTEXT_NEAR)),
+            ])
+            .collect();
+        let report = prompt_size_report(&records, 2);
+
+        assert_eq!(report.requests, 12);
+        assert_eq!(report.response_bytes, 1200);
+        assert_eq!(report.models.len(), 2);
+        assert_eq!(report.models[0].key, "large");
+        let small = &report.models[1].prompt_tokens;
+        assert_eq!((small.p50, small.p90, small.max), (50, 90, 90));
+        assert_eq!(report.users[0].key, "bob");
+        assert_eq!(report.users[0].request_bytes, 1_600_000);
+
+        let largest: Vec<_> = report
+            .largest_prompts
+            .iter()
+            .map(|p| p.input_tokens)
+            .collect();
+        assert_eq!(largest, [210_000, 195_000]);
+
+        let clients = &report.context_limit_clients;
+        assert_eq!(clients.len(), 2);
+        assert_eq!(
+            (
+                clients[0].user_id.as_str(),
+                clients[0].exceeded,
+                clients[0].near
+            ),
+            ("bob", 1, 1)
+        );
+        assert_eq!(clients[1].user_id, "carol");
+    }
+}
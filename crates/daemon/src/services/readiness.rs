@@ -0,0 +1,259 @@
+//! Liveness and readiness probes
+//!
+//! `/healthz` answers as long as the daemon's HTTP server does, so a
+//! supervisor only restarts a daemon that has stopped responding. `/readyz`
+//! checks what serving requests depends on: the state backend answers, at
+//! least one connector is healthy, the certificates served are valid and,
+//! when enabled, the relay is connected. It answers 503 while any check
+//! fails, so Kubernetes and uptime monitors can hold traffic back from a
+//! daemon that is up but not usable.
+
+use crate::Daemon;
+use crate::error::DaemonError;
+use crate::services::tls_status::{CertificateHealth, CertificateStatus, certificate_status};
+use crate::state_dir::StateDir;
+use crate::types::{CheckStatus, ProbeCheck, ProbeReport, TlsForwardStatus};
+use chrono::Utc;
+use gate_core::StateBackend;
+use gate_core::router::{Router, SinkHealth};
+use std::future::Future;
+use std::time::Instant;
+
+/// Path of the liveness probe
+pub const LIVENESS_PATH: &str = "/healthz";
+
+/// Path of the readiness probe
+pub const READINESS_PATH: &str = "/readyz";
+
+const CHECK_STATE_BACKEND: &str = "state_backend";
+const CHECK_CONNECTORS: &str = "connectors";
+const CHECK_CERTIFICATES: &str = "certificates";
+const CHECK_RELAY: &str = "relay";
+
+type Outcome = (CheckStatus, String);
+
+impl ProbeReport {
+    /// Report whose status is the worst of `checks`
+    pub fn from_checks(checks: Vec<ProbeCheck>) -> Self {
+        let status = if checks.iter().any(|c| c.status == CheckStatus::Fail) {
+            CheckStatus::Fail
+        } else if checks.iter().any(|c| c.status == CheckStatus::Warn) {
+            CheckStatus::Warn
+        } else {
+            CheckStatus::Pass
+        };
+        Self { status, checks }
+    }
+
+    /// Report of a daemon that is running
+    pub fn alive() -> Self {
+        Self::from_checks(Vec::new())
+    }
+
+    /// Whether no check failed
+    pub fn is_ready(&self) -> bool {
+        self.status != CheckStatus::Fail
+    }
+
+    /// The report without its checks, for callers not allowed to see them
+    pub fn summary(self) -> Self {
+        Self {
+            checks: Vec::new(),
+            ..self
+        }
+    }
+}
+
+/// Run the readiness checks concurrently
+pub async fn readiness(
+    daemon: &Daemon,
+    state_backend: &dyn StateBackend,
+    router: Option<&Router>,
+) -> ProbeReport {
+    let (state_backend, connectors, certificates, relay) = tokio::join!(
+        timed(CHECK_STATE_BACKEND, check_state_backend(state_backend)),
+        timed(CHECK_CONNECTORS, check_connectors(router)),
+        timed(CHECK_CERTIFICATES, check_certificates(daemon)),
+        timed(CHECK_RELAY, check_relay(daemon)),
+    );
+    ProbeReport::from_checks(vec![state_backend, connectors, certificates, relay])
+}
+
+async fn timed(id: &str, check: impl Future<Output = Outcome>) -> ProbeCheck {
+    let started = Instant::now();
+    let (status, message) = check.await;
+    ProbeCheck {
+        id: id.to_string(),
+        status,
+        message,
+        latency_ms: started.elapsed().as_secs_f64() * 1000.0,
+    }
+}
+
+async fn check_state_backend(state_backend: &dyn StateBackend) -> Outcome {
+    match state_backend.list_providers().await {
+        Ok(_) => (CheckStatus::Pass, "Reachable".to_string()),
+        Err(e) => (CheckStatus::Fail, format!("Unreachable: {e}")),
+    }
+}
+
+async fn check_connectors(router: Option<&Router>) -> Outcome {
+    let Some(router) = router else {
+        return (CheckStatus::Fail, "Routing is not running".to_string());
+    };
+    let mut health = Vec::new();
+    for (id, sink) in router.sink_registry().entries().await {
+        health.push((id, sink.probe().await));
+    }
+    connectors_outcome(&health)
+}
+
+async fn check_certificates(daemon: &Daemon) -> Outcome {
+    let statuses = async {
+        let settings = daemon.get_settings().await?;
+        let renewals = daemon.get_renewal_log().await?;
+        let cert_dir = StateDir::new().await?.dir_for("certificates");
+        Ok::<_, DaemonError>(certificate_status(&settings, &cert_dir, &renewals, Utc::now()).await)
+    };
+    match statuses.await {
+        Ok(statuses) => certificates_outcome(&statuses),
+        Err(e) => (CheckStatus::Fail, format!("Could not be read: {e}")),
+    }
+}
+
+async fn check_relay(daemon: &Daemon) -> Outcome {
+    match daemon.status().await {
+        Ok(status) => relay_outcome(&status.tlsforward_status),
+        Err(e) => (CheckStatus::Fail, format!("Status unavailable: {e}")),
+    }
+}
+
+fn connectors_outcome(health: &[(String, SinkHealth)]) -> Outcome {
+    let healthy = health.iter().filter(|(_, h)| h.healthy).count();
+    if health.is_empty() {
+        (
+            CheckStatus::Fail,
+            "No connectors are configured".to_string(),
+        )
+    } else if healthy == 0 {
+        (
+            CheckStatus::Fail,
+            format!("None of {} connectors is healthy", health.len()),
+        )
+    } else if healthy < health.len() {
+        let unhealthy: Vec<&str> = health
+            .iter()
+            .filter(|(_, h)| !h.healthy)
+            .map(|(id, _)| id.as_str())
+            .collect();
+        (
+            CheckStatus::Warn,
+            format!("Unhealthy: {}", unhealthy.join(", ")),
+        )
+    } else {
+        (CheckStatus::Pass, format!("{healthy} healthy"))
+    }
+}
+
+fn certificates_outcome(certificates: &[CertificateStatus]) -> Outcome {
+    if certificates.is_empty() {
+        return (
+            CheckStatus::Skipped,
+            "No certificates are served".to_string(),
+        );
+    }
+    let failing: Vec<String> = certificates
+        .iter()
+        .filter(|c| {
+            matches!(
+                c.health,
+                CertificateHealth::Expired
+                    | CertificateHealth::Missing
+                    | CertificateHealth::Invalid
+            )
+        })
+        .map(|c| format!("{} ({:?})", c.name, c.health).to_lowercase())
+        .collect();
+    if !failing.is_empty() {
+        return (
+            CheckStatus::Fail,
+            format!("Not usable: {}", failing.join(", ")),
+        );
+    }
+    let expiring: Vec<&str> = certificates
+        .iter()
+        .filter(|c| c.health == CertificateHealth::Expiring)
+        .map(|c| c.name.as_str())
+        .collect();
+    if expiring.is_empty() {
+        (CheckStatus::Pass, format!("{} valid", certificates.len()))
+    } else {
+        (
+            CheckStatus::Warn,
+            format!("Expiring soon: {}", expiring.join(", ")),
+        )
+    }
+}
+
+fn relay_outcome(status: &TlsForwardStatus) -> Outcome {
+    match status {
+        TlsForwardStatus::Disabled => (CheckStatus::Skipped, "Disabled".to_string()),
+        TlsForwardStatus::Connected { domain, .. } => {
+            (CheckStatus::Pass, format!("Connected, serving {domain}"))
+        }
+        TlsForwardStatus::Connecting => (CheckStatus::Warn, "Connecting".to_string()),
+        TlsForwardStatus::Disconnected => (CheckStatus::Fail, "Disconnected".to_string()),
+        TlsForwardStatus::Error(e) => (CheckStatus::Fail, format!("Failed: {e}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sink_health(healthy: bool) -> SinkHealth {
+        SinkHealth {
+            healthy,
+            latency_ms: None,
+            error_rate: 0.0,
+            last_error: None,
+            last_check: Utc::now(),
+        }
+    }
+
+    fn check(outcome: Outcome) -> ProbeCheck {
+        ProbeCheck {
+            id: "test".to_string(),
+            status: outcome.0,
+            message: outcome.1,
+            latency_ms: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_readiness_needs_one_healthy_connector() {
+        let one_down = [
+            ("openai".to_string(), sink_health(true)),
+            ("anthropic".to_string(), sink_health(false)),
+        ];
+        assert_eq!(connectors_outcome(&one_down).0, CheckStatus::Warn);
+        assert_eq!(connectors_outcome(&one_down[1..]).0, CheckStatus::Fail);
+        assert_eq!(connectors_outcome(&[]).0, CheckStatus::Fail);
+
+        let report = ProbeReport::from_checks(vec![
+            check(connectors_outcome(&one_down)),
+            check(certificates_outcome(&[])),
+            check(relay_outcome(&TlsForwardStatus::Disabled)),
+        ]);
+        assert!(report.is_ready());
+        assert_eq!(report.status, CheckStatus::Warn);
+
+        let report = ProbeReport::from_checks(vec![
+            check(connectors_outcome(&one_down)),
+            check(relay_outcome(&TlsForwardStatus::Disconnected)),
+        ]);
+        assert!(!report.is_ready());
+        let summary = serde_json::to_value(report.summary()).unwrap();
+        assert_eq!(summary, serde_json::json!({ "status": "fail" }));
+    }
+}
@@ -0,0 +1,173 @@
+//! Relay link diagnostics
+//!
+//! Records the round trip time to each TLS forward server and the link's
+//! connect and disconnect history, so an unreachable public URL can be
+//! traced to the relay, the link, or the node.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// Link events kept, oldest dropped first
+const MAX_EVENTS: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkEventKind {
+    Connected,
+    Disconnected,
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkEvent {
+    pub at: DateTime<Utc>,
+    pub kind: LinkEventKind,
+    /// Server or tunnel program the event concerns
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// What is known of one TLS forward server
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ServerDiagnostics {
+    pub server: String,
+    pub connected: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rtt_ms: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checked_at: Option<DateTime<Utc>>,
+    /// Why the last connection or ping failed, cleared when one succeeds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Default)]
+struct LinkState {
+    servers: BTreeMap<String, ServerDiagnostics>,
+    events: VecDeque<LinkEvent>,
+}
+
+/// Shared record of the relay link; clones share state
+#[derive(Clone, Default)]
+pub struct RelayDiagnostics {
+    state: Arc<RwLock<LinkState>>,
+}
+
+impl RelayDiagnostics {
+    fn server<'a>(state: &'a mut LinkState, server: &str) -> &'a mut ServerDiagnostics {
+        state
+            .servers
+            .entry(server.to_string())
+            .or_insert_with(|| ServerDiagnostics {
+                server: server.to_string(),
+                ..Default::default()
+            })
+    }
+
+    /// A ping to `server` answered after `rtt`
+    pub fn record_rtt(&self, server: &str, rtt: Duration) {
+        let mut state = self.state.write().unwrap();
+        let entry = Self::server(&mut state, server);
+        entry.rtt_ms = Some(rtt.as_secs_f64() * 1000.0);
+        entry.checked_at = Some(Utc::now());
+        entry.error = None;
+    }
+
+    /// Connecting to or pinging `server` failed
+    pub fn record_failure(&self, server: &str, error: impl ToString) {
+        let mut state = self.state.write().unwrap();
+        let entry = Self::server(&mut state, server);
+        entry.checked_at = Some(Utc::now());
+        entry.error = Some(error.to_string());
+    }
+
+    /// The link changed state
+    pub fn record_event(&self, kind: LinkEventKind, server: Option<&str>, detail: Option<String>) {
+        let mut state = self.state.write().unwrap();
+        if let Some(server) = server {
+            let connected = kind == LinkEventKind::Connected;
+            if connected {
+                for entry in state.servers.values_mut() {
+                    entry.connected = false;
+                }
+            }
+            Self::server(&mut state, server).connected = connected;
+        }
+        if state.events.len() == MAX_EVENTS {
+            state.events.pop_front();
+        }
+        state.events.push_back(LinkEvent {
+            at: Utc::now(),
+            kind,
+            server: server.map(String::from),
+            detail,
+        });
+    }
+
+    pub fn servers(&self) -> Vec<ServerDiagnostics> {
+        self.state
+            .read()
+            .unwrap()
+            .servers
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    /// Link events, newest first
+    pub fn events(&self) -> Vec<LinkEvent> {
+        self.state
+            .read()
+            .unwrap()
+            .events
+            .iter()
+            .rev()
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracks_servers_and_history() {
+        let diagnostics = RelayDiagnostics::default();
+        diagnostics.record_failure("relay-a", "timed out");
+        diagnostics.record_event(
+            LinkEventKind::Failed,
+            Some("relay-a"),
+            Some("timed out".to_string()),
+        );
+        diagnostics.record_rtt("relay-b", Duration::from_millis(42));
+        diagnostics.record_event(
+            LinkEventKind::Connected,
+            Some("relay-b"),
+            Some("abc.hellas.ai".to_string()),
+        );
+
+        let servers = diagnostics.servers();
+        assert_eq!(servers.len(), 2);
+        assert!(!servers[0].connected);
+        assert_eq!(servers[0].error.as_deref(), Some("timed out"));
+        assert!(servers[1].connected);
+        assert_eq!(servers[1].rtt_ms, Some(42.0));
+
+        let events = diagnostics.events();
+        assert_eq!(events[0].kind, LinkEventKind::Connected);
+        assert_eq!(events[1].kind, LinkEventKind::Failed);
+
+        for _ in 0..MAX_EVENTS {
+            diagnostics.record_event(LinkEventKind::Disconnected, None, None);
+        }
+        let events = diagnostics.events();
+        assert_eq!(events.len(), MAX_EVENTS);
+        assert!(events.iter().all(|e| e.kind == LinkEventKind::Disconnected));
+    }
+}
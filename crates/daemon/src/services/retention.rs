@@ -0,0 +1,108 @@
+//! Data retention
+//!
+//! Purges each class of stored data once it is older than the class's
+//! retention window. Classes without a window are kept until deleted.
+//! Deduplicated body blobs are collected once no record refers to them.
+
+use crate::config::RetentionConfig;
+use crate::error::Result;
+use crate::services::scheduler::ScheduledTask;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use gate_core::{BlobCollection, DataClass, StateBackend};
+use std::sync::Arc;
+
+/// Hours a blob is spared after it was last written, as the record
+/// referring to it may still be being saved
+const BLOB_GRACE_HOURS: i64 = 1;
+
+/// Scheduled task purging data past its retention window
+pub struct RetentionPurger {
+    state_backend: Arc<dyn StateBackend>,
+    config: RetentionConfig,
+}
+
+impl RetentionPurger {
+    pub fn new(state_backend: Arc<dyn StateBackend>, config: RetentionConfig) -> Self {
+        Self {
+            state_backend,
+            config,
+        }
+    }
+
+    /// Whether any class of data has a retention window
+    pub fn has_windows(&self) -> bool {
+        DataClass::ALL
+            .into_iter()
+            .any(|class| self.config.window_days(class).is_some())
+    }
+
+    /// Scheduler interval matching the configured purge interval
+    pub fn default_schedule(&self) -> String {
+        format!("@every {}h", self.config.purge_interval_hours.max(1))
+    }
+
+    /// Purge each class with a window, returning how many records were
+    /// removed per class. A failing class does not stop the others.
+    pub async fn purge(&self, now: DateTime<Utc>) -> Vec<(DataClass, u64)> {
+        let mut purged = Vec::new();
+        for class in DataClass::ALL {
+            let Some(days) = self.config.window_days(class) else {
+                continue;
+            };
+            let before = now - Duration::days(days.max(1) as i64);
+            match self.state_backend.purge_data(class, before).await {
+                Ok(count) => {
+                    if count > 0 {
+                        info!("Purged {count} {class:?} records older than {before}");
+                    }
+                    purged.push((class, count));
+                }
+                Err(e) => warn!("Failed to purge {class:?}: {e}"),
+            }
+        }
+        purged
+    }
+}
+
+#[async_trait]
+impl ScheduledTask for RetentionPurger {
+    async fn run(&self) -> Result<()> {
+        self.purge(Utc::now()).await;
+        Ok(())
+    }
+}
+
+/// Scheduled task removing deduplicated body blobs no record refers to
+pub struct BlobCollector {
+    state_backend: Arc<dyn StateBackend>,
+}
+
+impl BlobCollector {
+    pub fn new(state_backend: Arc<dyn StateBackend>) -> Self {
+        Self { state_backend }
+    }
+
+    /// Remove the unreferenced blobs last written before the grace period
+    pub async fn collect(&self, now: DateTime<Utc>) -> Result<BlobCollection> {
+        let collection = self
+            .state_backend
+            .collect_blobs(now - Duration::hours(BLOB_GRACE_HOURS))
+            .await?;
+        if collection.blobs > 0 {
+            info!(
+                "Collected {} unreferenced blobs, freeing {} bytes",
+                collection.blobs, collection.bytes
+            );
+        }
+        Ok(collection)
+    }
+}
+
+#[async_trait]
+impl ScheduledTask for BlobCollector {
+    async fn run(&self) -> Result<()> {
+        self.collect(Utc::now()).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,687 @@
+//! Document store and retrieval middleware
+//!
+//! Documents are split into overlapping chunks and embedded through the
+//! router like any other request, with the embeddings model named in the
+//! `documents` settings. Each document is kept under the state directory as a
+//! JSON file holding its chunks and their vectors; the files are loaded into
+//! an in-memory index on first use and searched by cosine similarity.
+//!
+//! Requests for a virtual model with a retrieval config have their latest
+//! user message embedded and the closest excerpts from the model's collection
+//! added to the system prompt. The excerpts used are returned as `citations`
+//! in the `metadata` of the final response chunk.
+
+use crate::error::{DaemonError, Result};
+use crate::helpers::ids::{is_valid_id, new_id};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use gate_core::StateBackend;
+use gate_core::router::middleware::{
+    Middleware, Next, RequestStream, ResponseStream, transform_response,
+};
+use gate_core::router::routing::Router;
+use gate_core::router::service::route_and_execute_json_with_protocol;
+use gate_core::router::sink::RequestContext;
+use gate_core::router::types::{
+    Protocol, ResponseChunk, ResponseTransform, RetrievalConfig, StopReason,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value as JsonValue, json};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock, Weak};
+use tokio::sync::{OnceCell, RwLock};
+
+const DOCUMENT_ID_PREFIX: &str = "doc-";
+/// Inputs sent in one embeddings request
+const EMBEDDING_BATCH: usize = 64;
+
+/// A chunk of document text and its embedding
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Chunk {
+    pub text: String,
+    pub embedding: Vec<f32>,
+}
+
+/// An indexed document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Document {
+    pub id: String,
+    pub collection: String,
+    pub title: String,
+    pub owner_id: String,
+    /// Upload the text was taken from, if any
+    #[serde(default)]
+    pub file_id: Option<String>,
+    /// Model the chunks were embedded with. Only queries embedded by the same
+    /// model are compared with them.
+    pub embedding_model: String,
+    pub chunks: Vec<Chunk>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Document {
+    pub fn new_id() -> String {
+        new_id(DOCUMENT_ID_PREFIX)
+    }
+}
+
+/// A document without its chunks, as listed
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DocumentInfo {
+    pub id: String,
+    pub collection: String,
+    pub title: String,
+    pub owner_id: String,
+    pub file_id: Option<String>,
+    pub embedding_model: String,
+    pub chunks: usize,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<&Document> for DocumentInfo {
+    fn from(document: &Document) -> Self {
+        DocumentInfo {
+            id: document.id.clone(),
+            collection: document.collection.clone(),
+            title: document.title.clone(),
+            owner_id: document.owner_id.clone(),
+            file_id: document.file_id.clone(),
+            embedding_model: document.embedding_model.clone(),
+            chunks: document.chunks.len(),
+            created_at: document.created_at,
+        }
+    }
+}
+
+/// An excerpt found by a search
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SearchHit {
+    pub document_id: String,
+    pub title: String,
+    /// Position of the chunk in its document
+    pub chunk: usize,
+    pub text: String,
+    pub score: f32,
+}
+
+/// Split `text` into chunks of at most `size` bytes, breaking between words.
+/// Each chunk repeats up to `overlap` bytes from the end of the one before,
+/// so passages cut at a boundary still appear whole in one of them. A single
+/// word longer than `size` becomes a chunk of its own.
+pub fn chunk_text(text: &str, size: usize, overlap: usize) -> Vec<String> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < words.len() {
+        let mut end = start;
+        let mut len = 0;
+        while end < words.len() && (end == start || len + 1 + words[end].len() <= size) {
+            len += words[end].len() + usize::from(end > start);
+            end += 1;
+        }
+        chunks.push(words[start..end].join(" "));
+        if end == words.len() {
+            break;
+        }
+
+        // Step back over the words that fit in the overlap, always advancing
+        let mut next = end;
+        let mut back = 0;
+        while next > start + 1 && back + words[next - 1].len() <= overlap {
+            next -= 1;
+            back += words[next].len() + 1;
+        }
+        start = next;
+    }
+    chunks
+}
+
+/// Cosine similarity of two vectors; 0 when either is empty or zero, or
+/// their dimensions differ
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let (dot, norm_a, norm_b) = a
+        .iter()
+        .zip(b)
+        .fold((0.0, 0.0, 0.0), |(dot, x, y), (a, b)| {
+            (dot + a * b, x + a * a, y + b * b)
+        });
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a.sqrt() * norm_b.sqrt())
+    }
+}
+
+/// Embed `inputs` with `model`, routed like any other embeddings request
+pub async fn embed(
+    router: &Router,
+    ctx: &RequestContext,
+    model: &str,
+    inputs: &[String],
+) -> Result<Vec<Vec<f32>>> {
+    let mut embeddings = Vec::with_capacity(inputs.len());
+    for batch in inputs.chunks(EMBEDDING_BATCH) {
+        let request = json!({"model": model, "input": batch});
+        let mut stream =
+            route_and_execute_json_with_protocol(router, ctx, Protocol::OpenAIEmbeddings, request)
+                .await?;
+        let mut response = None;
+        while let Some(chunk) = stream.next().await {
+            match chunk?.parsed() {
+                ResponseChunk::Content(json) => response = Some(json),
+                ResponseChunk::Stop {
+                    reason: StopReason::Error,
+                    error,
+                    ..
+                } => {
+                    return Err(DaemonError::ServiceUnavailable(format!(
+                        "Embedding with {model} failed: {}",
+                        error.unwrap_or_default()
+                    )));
+                }
+                _ => {}
+            }
+        }
+        let response = response.ok_or_else(|| {
+            DaemonError::ServiceUnavailable(format!("Empty embeddings response from {model}"))
+        })?;
+        embeddings.extend(parse_embeddings(&response, batch.len())?);
+    }
+    Ok(embeddings)
+}
+
+/// Vectors of an OpenAI embeddings response, in input order
+fn parse_embeddings(response: &JsonValue, expected: usize) -> Result<Vec<Vec<f32>>> {
+    let mut data: Vec<(u64, Vec<f32>)> = response["data"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|item| {
+            let vector = item["embedding"]
+                .as_array()?
+                .iter()
+                .map(|v| v.as_f64().map(|v| v as f32))
+                .collect::<Option<Vec<f32>>>()?;
+            Some((item["index"].as_u64().unwrap_or(0), vector))
+        })
+        .collect();
+    if data.len() != expected {
+        return Err(DaemonError::InvalidState(format!(
+            "Expected {expected} embeddings, got {}",
+            data.len()
+        )));
+    }
+    data.sort_by_key(|(index, _)| *index);
+    Ok(data.into_iter().map(|(_, vector)| vector).collect())
+}
+
+/// Indexed documents stored on disk
+#[derive(Debug, Clone)]
+pub struct DocumentStore {
+    dir: PathBuf,
+    /// Every document, loaded on first use
+    index: Arc<OnceCell<RwLock<HashMap<String, Document>>>>,
+}
+
+impl DocumentStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            index: Arc::new(OnceCell::new()),
+        }
+    }
+
+    fn path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{id}.json"))
+    }
+
+    async fn load(&self) -> Result<HashMap<String, Document>> {
+        let mut documents = HashMap::new();
+        let mut entries = match tokio::fs::read_dir(&self.dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(documents),
+            Err(e) => return Err(e.into()),
+        };
+        while let Some(entry) = entries.next_entry().await? {
+            let name = entry.file_name();
+            let Some(id) = name.to_str().and_then(|n| n.strip_suffix(".json")) else {
+                continue;
+            };
+            if !is_valid_id(DOCUMENT_ID_PREFIX, id) {
+                continue;
+            }
+            let bytes = tokio::fs::read(entry.path()).await?;
+            match serde_json::from_slice::<Document>(&bytes) {
+                Ok(document) => {
+                    documents.insert(document.id.clone(), document);
+                }
+                Err(e) => warn!("Skipping unreadable document {id}: {e}"),
+            }
+        }
+        debug!("Loaded {} documents", documents.len());
+        Ok(documents)
+    }
+
+    async fn index(&self) -> Result<&RwLock<HashMap<String, Document>>> {
+        self.index
+            .get_or_try_init(|| async { self.load().await.map(RwLock::new) })
+            .await
+    }
+
+    /// Store a document, replacing any with the same id
+    pub async fn save(&self, document: Document) -> Result<()> {
+        if !is_valid_id(DOCUMENT_ID_PREFIX, &document.id) {
+            return Err(DaemonError::InvalidState(format!(
+                "Invalid document id {}",
+                document.id
+            )));
+        }
+        let index = self.index().await?;
+        tokio::fs::create_dir_all(&self.dir).await?;
+        tokio::fs::write(self.path(&document.id), serde_json::to_vec(&document)?).await?;
+        index.write().await.insert(document.id.clone(), document);
+        Ok(())
+    }
+
+    pub async fn get(&self, id: &str) -> Result<Option<Document>> {
+        Ok(self.index().await?.read().await.get(id).cloned())
+    }
+
+    /// Documents in `collection`, or in every collection, newest first
+    pub async fn list(&self, collection: Option<&str>) -> Result<Vec<DocumentInfo>> {
+        let index = self.index().await?.read().await;
+        let mut documents: Vec<DocumentInfo> = index
+            .values()
+            .filter(|d| collection.is_none_or(|c| d.collection == c))
+            .map(DocumentInfo::from)
+            .collect();
+        documents.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok(documents)
+    }
+
+    /// Remove a document, returning whether it existed
+    pub async fn delete(&self, id: &str) -> Result<bool> {
+        let mut index = self.index().await?.write().await;
+        if index.remove(id).is_none() {
+            return Ok(false);
+        }
+        match tokio::fs::remove_file(self.path(id)).await {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(true),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// The `top_k` chunks in `collection` closest to `query`, scoring at
+    /// least `min_score`, best first
+    pub async fn search(
+        &self,
+        collection: &str,
+        embedding_model: &str,
+        query: &[f32],
+        top_k: usize,
+        min_score: f32,
+    ) -> Result<Vec<SearchHit>> {
+        let index = self.index().await?.read().await;
+        let mut scored: Vec<(f32, &Document, usize)> = index
+            .values()
+            .filter(|d| d.collection == collection && d.embedding_model == embedding_model)
+            .flat_map(|d| {
+                d.chunks
+                    .iter()
+                    .enumerate()
+                    .map(move |(i, chunk)| (cosine_similarity(query, &chunk.embedding), d, i))
+            })
+            .filter(|(score, _, _)| *score >= min_score)
+            .collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+        Ok(scored
+            .into_iter()
+            .take(top_k)
+            .map(|(score, document, chunk)| SearchHit {
+                document_id: document.id.clone(),
+                title: document.title.clone(),
+                chunk,
+                text: document.chunks[chunk].text.clone(),
+                score,
+            })
+            .collect())
+    }
+}
+
+/// Text of the last user message in a request
+fn latest_user_text(protocol: Protocol, request: &JsonValue) -> Option<String> {
+    let messages_key = match protocol {
+        Protocol::OpenAIResponses => {
+            if let Some(input) = request["input"].as_str() {
+                return Some(input.to_string());
+            }
+            "input"
+        }
+        _ => "messages",
+    };
+    let message = request
+        .get(messages_key)?
+        .as_array()?
+        .iter()
+        .rev()
+        .find(|m| m["role"] == "user")?;
+    let text = match &message["content"] {
+        JsonValue::String(text) => text.clone(),
+        JsonValue::Array(parts) => parts
+            .iter()
+            .filter(|p| matches!(p["type"].as_str(), Some("text" | "input_text")))
+            .filter_map(|p| p["text"].as_str())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => return None,
+    };
+    (!text.trim().is_empty()).then_some(text)
+}
+
+/// System prompt text presenting the excerpts
+fn context_prompt(hits: &[SearchHit]) -> String {
+    let mut prompt = String::from(
+        "Answer using the numbered excerpts below where they are relevant, \
+         citing them as [1], [2] and so on.\n",
+    );
+    for (i, hit) in hits.iter().enumerate() {
+        prompt.push_str(&format!("\n[{}] {}\n{}\n", i + 1, hit.title, hit.text));
+    }
+    prompt
+}
+
+/// Add `context` to the system prompt of a request
+fn augment(protocol: Protocol, request: &mut JsonValue, context: &str) {
+    if !request.is_object() {
+        return;
+    }
+    let system_key = match protocol {
+        Protocol::Anthropic => "system",
+        Protocol::OpenAIResponses => "instructions",
+        _ => {
+            if let Some(messages) = request["messages"].as_array_mut() {
+                messages.insert(0, json!({"role": "system", "content": context}));
+            }
+            return;
+        }
+    };
+    match &mut request[system_key] {
+        JsonValue::String(system) if !system.is_empty() => {
+            system.push_str("\n\n");
+            system.push_str(context);
+        }
+        JsonValue::Array(blocks) => blocks.push(json!({"type": "text", "text": context})),
+        other => *other = json!(context),
+    }
+}
+
+/// Middleware grounding requests for virtual models with a retrieval config
+/// in excerpts from their document collection
+pub struct RetrievalMiddleware {
+    store: DocumentStore,
+    state_backend: Arc<dyn StateBackend>,
+    embedding_model: String,
+    /// Router used to embed queries, bound once it has been built
+    router: OnceLock<Weak<Router>>,
+}
+
+impl RetrievalMiddleware {
+    pub fn new(
+        store: DocumentStore,
+        state_backend: Arc<dyn StateBackend>,
+        embedding_model: impl Into<String>,
+    ) -> Self {
+        Self {
+            store,
+            state_backend,
+            embedding_model: embedding_model.into(),
+            router: OnceLock::new(),
+        }
+    }
+
+    /// Embed queries through `router`, which this middleware is part of
+    pub fn bind(&self, router: &Arc<Router>) {
+        let _ = self.router.set(Arc::downgrade(router));
+    }
+
+    async fn config_for(&self, model: &str) -> Option<RetrievalConfig> {
+        self.state_backend
+            .get_virtual_model(model, None)
+            .await
+            .ok()
+            .flatten()
+            .and_then(|virtual_model| virtual_model.retrieval)
+    }
+
+    async fn retrieve(
+        &self,
+        ctx: &RequestContext,
+        config: &RetrievalConfig,
+        query: &str,
+    ) -> Result<Vec<SearchHit>> {
+        let router =
+            self.router.get().and_then(Weak::upgrade).ok_or_else(|| {
+                DaemonError::ServiceUnavailable("Router is not bound".to_string())
+            })?;
+        let query = embed(&router, ctx, &self.embedding_model, &[query.to_string()])
+            .await?
+            .pop()
+            .ok_or_else(|| DaemonError::InvalidState("No query embedding returned".to_string()))?;
+        self.store
+            .search(
+                &config.collection,
+                &self.embedding_model,
+                &query,
+                config.top_k,
+                config.min_score,
+            )
+            .await
+    }
+}
+
+#[async_trait]
+impl Middleware for RetrievalMiddleware {
+    async fn process(
+        &self,
+        ctx: &mut RequestContext,
+        mut request: RequestStream,
+        next: Next,
+    ) -> gate_core::Result<ResponseStream> {
+        let protocol = request.protocol();
+        if !matches!(
+            protocol,
+            Protocol::OpenAIChat | Protocol::OpenAIResponses | Protocol::Anthropic
+        ) {
+            return next(request).await;
+        }
+        let Some(first) = request.next().await else {
+            return next(request).await;
+        };
+        let mut json = first?;
+
+        let config = match json.get("model").and_then(|m| m.as_str()) {
+            Some(model) => self.config_for(model).await,
+            None => None,
+        };
+        let mut citations = Vec::new();
+        if let Some(config) = config
+            && let Some(query) = latest_user_text(protocol, &json)
+        {
+            // Answer without excerpts rather than fail the request
+            match self.retrieve(ctx, &config, &query).await {
+                Ok(hits) if !hits.is_empty() => {
+                    debug!(
+                        "Adding {} excerpts from {} to the request",
+                        hits.len(),
+                        config.collection
+                    );
+                    augment(protocol, &mut json, &context_prompt(&hits));
+                    citations = hits
+                        .iter()
+                        .enumerate()
+                        .map(|(i, hit)| {
+                            json!({
+                                "index": i + 1,
+                                "document_id": hit.document_id,
+                                "title": hit.title,
+                                "chunk": hit.chunk,
+                                "score": hit.score,
+                                "text": hit.text
+                            })
+                        })
+                        .collect();
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Retrieval from {} failed: {e}", config.collection),
+            }
+        }
+
+        let rebuilt = RequestStream::new(
+            protocol,
+            Box::pin(futures::stream::once(async move { Ok(json) }).chain(request)),
+        );
+        let response = next(rebuilt).await?;
+        if citations.is_empty() {
+            return Ok(response);
+        }
+        let transform = ResponseTransform {
+            metadata: HashMap::from([("citations".to_string(), json!(citations))]),
+            ..Default::default()
+        };
+        Ok(transform_response(response, transform, None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(collection: &str, chunks: &[(&str, [f32; 2])]) -> Document {
+        Document {
+            id: Document::new_id(),
+            collection: collection.to_string(),
+            title: "Handbook".to_string(),
+            owner_id: "admin".to_string(),
+            file_id: None,
+            embedding_model: "embed".to_string(),
+            chunks: chunks
+                .iter()
+                .map(|(text, embedding)| Chunk {
+                    text: text.to_string(),
+                    embedding: embedding.to_vec(),
+                })
+                .collect(),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_chunk_text_overlaps_between_words() {
+        let chunks = chunk_text("one two three four five six", 13, 5);
+        assert_eq!(chunks, vec!["one two three", "three four", "four five six"]);
+        assert!(chunk_text("  ", 10, 2).is_empty());
+        assert_eq!(chunk_text("unbreakable", 4, 2), vec!["unbreakable"]);
+    }
+
+    #[test]
+    fn test_cosine_similarity() {
+        assert!((cosine_similarity(&[1.0, 0.0], &[2.0, 0.0]) - 1.0).abs() < 1e-6);
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[0.0, 1.0]), 0.0);
+        assert_eq!(cosine_similarity(&[1.0], &[1.0, 0.0]), 0.0);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 0.0]), 0.0);
+    }
+
+    #[test]
+    fn test_parse_embeddings_in_input_order() {
+        let response = json!({"data": [
+            {"index": 1, "embedding": [0.0, 1.0]},
+            {"index": 0, "embedding": [1.0, 0.0]}
+        ]});
+        let vectors = parse_embeddings(&response, 2).unwrap();
+        assert_eq!(vectors, vec![vec![1.0, 0.0], vec![0.0, 1.0]]);
+        assert!(parse_embeddings(&response, 3).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_store_round_trip_and_search() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = DocumentStore::new(dir.path());
+        let handbook = document(
+            "docs",
+            &[("vacation policy", [1.0, 0.0]), ("expenses", [0.0, 1.0])],
+        );
+        let other = document("other", &[("vacation elsewhere", [1.0, 0.0])]);
+        store.save(handbook.clone()).await.unwrap();
+        store.save(other).await.unwrap();
+
+        let hits = store
+            .search("docs", "embed", &[0.9, 0.1], 1, 0.0)
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].text, "vacation policy");
+        assert_eq!(hits[0].document_id, handbook.id);
+        let none = store
+            .search("docs", "other-model", &[0.9, 0.1], 4, 0.0)
+            .await
+            .unwrap();
+        assert!(none.is_empty());
+
+        // A fresh store reads the index back from disk
+        let reopened = DocumentStore::new(dir.path());
+        assert_eq!(
+            reopened.get(&handbook.id).await.unwrap(),
+            Some(handbook.clone())
+        );
+        assert_eq!(reopened.list(Some("docs")).await.unwrap().len(), 1);
+        assert_eq!(reopened.list(None).await.unwrap().len(), 2);
+
+        assert!(reopened.delete(&handbook.id).await.unwrap());
+        assert!(!reopened.delete(&handbook.id).await.unwrap());
+        assert!(
+            DocumentStore::new(dir.path())
+                .get(&handbook.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_augment_per_protocol() {
+        let mut chat = json!({"messages": [{"role": "user", "content": "Hi"}]});
+        augment(Protocol::OpenAIChat, &mut chat, "context");
+        assert_eq!(
+            chat["messages"][0],
+            json!({"role": "system", "content": "context"})
+        );
+        assert_eq!(
+            latest_user_text(Protocol::OpenAIChat, &chat).as_deref(),
+            Some("Hi")
+        );
+
+        let mut anthropic = json!({"system": "Be brief", "messages": [
+            {"role": "user", "content": [{"type": "text", "text": "Hello"}]}
+        ]});
+        augment(Protocol::Anthropic, &mut anthropic, "context");
+        assert_eq!(anthropic["system"], "Be brief\n\ncontext");
+        assert_eq!(
+            latest_user_text(Protocol::Anthropic, &anthropic).as_deref(),
+            Some("Hello")
+        );
+
+        let mut responses = json!({"input": "Question"});
+        augment(Protocol::OpenAIResponses, &mut responses, "context");
+        assert_eq!(responses["instructions"], "context");
+        assert_eq!(
+            latest_user_text(Protocol::OpenAIResponses, &responses).as_deref(),
+            Some("Question")
+        );
+    }
+}
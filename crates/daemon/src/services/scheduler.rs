@@ -0,0 +1,553 @@
+//! Scheduled tasks
+//!
+//! Periodic work such as purges, spend checks and health probes registers
+//! here instead of running its own timer loop. Each task has a schedule,
+//! either a five-field cron expression (`minute hour day month weekday`,
+//! evaluated in UTC), a shortcut such as `@daily`, or an interval such as
+//! `@every 15m`. Schedules and enablement can be overridden per task under
+//! `tasks` in the settings, and any task can be triggered by hand.
+//!
+//! When replicas elect a leader, tasks run on the leader only, except those
+//! registered with [`Scheduler::register_local`], which look after the
+//! replica they run on, such as health probes. Those are also the only tasks run while the daemon
+//! sheds work for lack of memory; the rest wait for their next slot.
+
+use crate::config::TaskConfig;
+use crate::error::{DaemonError, Result};
+use crate::services::cluster::LeaderElection;
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Timelike, Utc};
+use gate_core::router::buffers::BufferBudget;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock};
+use tokio::sync::Mutex;
+
+/// Longest the scheduler sleeps before looking for due tasks again
+const MAX_SLEEP: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Work run on a schedule
+#[async_trait]
+pub trait ScheduledTask: Send + Sync {
+    async fn run(&self) -> Result<()>;
+}
+
+/// A set of values a cron field matches, as a bitmask
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CronField {
+    bits: u64,
+    /// Written as `*`, which matters for how days and weekdays combine
+    any: bool,
+}
+
+impl CronField {
+    fn parse(field: &str, min: u32, max: u32) -> std::result::Result<Self, String> {
+        let mut bits = 0u64;
+        for item in field.split(',') {
+            let (range, step) = match item.split_once('/') {
+                Some((range, step)) => (
+                    range,
+                    step.parse::<u32>()
+                        .ok()
+                        .filter(|s| *s > 0)
+                        .ok_or_else(|| format!("invalid step in '{item}'"))?,
+                ),
+                None => (item, 1),
+            };
+            let (start, end) = if range == "*" {
+                (min, max)
+            } else if let Some((start, end)) = range.split_once('-') {
+                (parse_value(start, item)?, parse_value(end, item)?)
+            } else {
+                let value = parse_value(range, item)?;
+                // `5/15` means every 15 starting at 5
+                (value, if step > 1 { max } else { value })
+            };
+            if start < min || end > max || start > end {
+                return Err(format!("'{item}' is outside {min}-{max}"));
+            }
+            for value in (start..=end).step_by(step as usize) {
+                bits |= 1 << value;
+            }
+        }
+        Ok(Self {
+            bits,
+            any: field.starts_with('*'),
+        })
+    }
+
+    fn matches(&self, value: u32) -> bool {
+        self.bits & (1 << value) != 0
+    }
+}
+
+fn parse_value(value: &str, item: &str) -> std::result::Result<u32, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value in '{item}'"))
+}
+
+/// A parsed five-field cron expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSpec {
+    minutes: CronField,
+    hours: CronField,
+    days: CronField,
+    months: CronField,
+    weekdays: CronField,
+}
+
+impl CronSpec {
+    fn parse(expr: &str) -> std::result::Result<Self, String> {
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        let [minutes, hours, days, months, weekdays] = fields[..] else {
+            return Err(format!(
+                "expected 5 fields (minute hour day month weekday), got {}",
+                fields.len()
+            ));
+        };
+        let mut weekdays = CronField::parse(weekdays, 0, 7)?;
+        // Both 0 and 7 are Sunday
+        if weekdays.matches(7) {
+            weekdays.bits |= 1;
+        }
+        Ok(Self {
+            minutes: CronField::parse(minutes, 0, 59)?,
+            hours: CronField::parse(hours, 0, 23)?,
+            days: CronField::parse(days, 1, 31)?,
+            months: CronField::parse(months, 1, 12)?,
+            weekdays,
+        })
+    }
+
+    /// Days match when either the day of month or the weekday does, unless
+    /// one of them is `*`, as in classic cron
+    fn matches_day(&self, date: NaiveDate) -> bool {
+        let day = self.days.matches(date.day());
+        let weekday = self.weekdays.matches(date.weekday().num_days_from_sunday());
+        match (self.days.any, self.weekdays.any) {
+            (true, true) => true,
+            (true, false) => weekday,
+            (false, true) => day,
+            (false, false) => day || weekday,
+        }
+    }
+
+    fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut t = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        // Skipping whole months, days and hours keeps this bounded even for
+        // expressions that never match, such as 30 February
+        for _ in 0..100_000 {
+            let date = t.date_naive();
+            if !self.months.matches(date.month()) {
+                let (year, month) = if date.month() == 12 {
+                    (date.year() + 1, 1)
+                } else {
+                    (date.year(), date.month() + 1)
+                };
+                t = NaiveDate::from_ymd_opt(year, month, 1)?
+                    .and_hms_opt(0, 0, 0)?
+                    .and_utc();
+            } else if !self.matches_day(date) {
+                t = date.succ_opt()?.and_hms_opt(0, 0, 0)?.and_utc();
+            } else if !self.hours.matches(t.hour()) {
+                t = t.with_minute(0)? + Duration::hours(1);
+            } else if !self.minutes.matches(t.minute()) {
+                t += Duration::minutes(1);
+            } else {
+                return Some(t);
+            }
+        }
+        None
+    }
+}
+
+/// When a task runs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Schedule {
+    Cron(Box<CronSpec>),
+    Every(Duration),
+}
+
+impl Schedule {
+    /// First time the task is due strictly after `after`
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Schedule::Cron(spec) => spec.next_after(after),
+            Schedule::Every(period) => Some(after + *period),
+        }
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = String;
+
+    fn from_str(expr: &str) -> std::result::Result<Self, String> {
+        let expr = expr.trim();
+        let cron = match expr {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            _ => match expr.strip_prefix("@every ") {
+                Some(interval) => return parse_interval(interval.trim()).map(Schedule::Every),
+                None => expr,
+            },
+        };
+        CronSpec::parse(cron).map(|spec| Schedule::Cron(Box::new(spec)))
+    }
+}
+
+/// An interval such as `90s`, `15m`, `6h` or `1d`
+fn parse_interval(interval: &str) -> std::result::Result<Duration, String> {
+    let split = interval
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(interval.len());
+    let (count, unit) = interval.split_at(split);
+    let count: i64 = count
+        .parse()
+        .ok()
+        .filter(|c| *c > 0)
+        .ok_or_else(|| format!("invalid interval '{interval}'"))?;
+    match unit {
+        "s" => Ok(Duration::seconds(count)),
+        "m" => Ok(Duration::minutes(count)),
+        "h" => Ok(Duration::hours(count)),
+        "d" => Ok(Duration::days(count)),
+        _ => Err(format!(
+            "invalid interval unit in '{interval}' (use s, m, h or d)"
+        )),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskTrigger {
+    Scheduled,
+    Manual,
+}
+
+/// One run of a task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskRun {
+    pub trigger: TaskTrigger,
+    pub started_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A registered task and when it last and next runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskStatus {
+    pub name: String,
+    pub description: String,
+    pub schedule: String,
+    pub enabled: bool,
+    pub running: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_run: Option<TaskRun>,
+    /// Not set while the task is disabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_run: Option<DateTime<Utc>>,
+}
+
+struct Entry {
+    task: Arc<dyn ScheduledTask>,
+    schedule: Schedule,
+    status: TaskStatus,
+    /// Runs on every replica rather than on the leader only
+    local: bool,
+}
+
+/// Runs registered tasks when they are due
+#[derive(Clone, Default)]
+pub struct Scheduler {
+    entries: Arc<Mutex<BTreeMap<String, Entry>>>,
+    election: Arc<OnceLock<LeaderElection>>,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run tasks not registered as local only while `election` says this
+    /// replica leads
+    pub fn set_leader_election(&self, election: LeaderElection) {
+        if self.election.set(election).is_err() {
+            warn!("The scheduler already follows a leader election");
+        }
+    }
+
+    /// Register a task under `name`, applying any override from the
+    /// settings to its default schedule
+    pub async fn register(
+        &self,
+        name: &str,
+        description: &str,
+        default_schedule: &str,
+        overrides: &HashMap<String, TaskConfig>,
+        task: Arc<dyn ScheduledTask>,
+    ) -> Result<()> {
+        self.insert(name, description, default_schedule, overrides, task, false)
+            .await
+    }
+
+    /// Register a task that runs on every replica, such as one probing the
+    /// replica's own health
+    pub async fn register_local(
+        &self,
+        name: &str,
+        description: &str,
+        default_schedule: &str,
+        overrides: &HashMap<String, TaskConfig>,
+        task: Arc<dyn ScheduledTask>,
+    ) -> Result<()> {
+        self.insert(name, description, default_schedule, overrides, task, true)
+            .await
+    }
+
+    async fn insert(
+        &self,
+        name: &str,
+        description: &str,
+        default_schedule: &str,
+        overrides: &HashMap<String, TaskConfig>,
+        task: Arc<dyn ScheduledTask>,
+        local: bool,
+    ) -> Result<()> {
+        let config = overrides.get(name);
+        let expr = config
+            .and_then(|c| c.schedule.clone())
+            .unwrap_or_else(|| default_schedule.to_string());
+        let schedule: Schedule = expr.parse().map_err(|e| {
+            DaemonError::ConfigError(format!("Invalid schedule '{expr}' for task {name}: {e}"))
+        })?;
+        let enabled = config.is_none_or(|c| c.enabled);
+        let next_run = if enabled {
+            schedule.next_after(Utc::now())
+        } else {
+            None
+        };
+
+        let status = TaskStatus {
+            name: name.to_string(),
+            description: description.to_string(),
+            schedule: expr,
+            enabled,
+            running: false,
+            last_run: None,
+            next_run,
+        };
+        self.entries.lock().await.insert(
+            name.to_string(),
+            Entry {
+                task,
+                schedule,
+                status,
+                local,
+            },
+        );
+        Ok(())
+    }
+
+    /// Every registered task, by name
+    pub async fn list(&self) -> Vec<TaskStatus> {
+        self.entries
+            .lock()
+            .await
+            .values()
+            .map(|e| e.status.clone())
+            .collect()
+    }
+
+    pub async fn get(&self, name: &str) -> Option<TaskStatus> {
+        self.entries
+            .lock()
+            .await
+            .get(name)
+            .map(|e| e.status.clone())
+    }
+
+    /// Run a task now, whether or not it is enabled. Its schedule is left
+    /// as it was.
+    pub async fn trigger(&self, name: &str) -> Result<TaskStatus> {
+        let task = {
+            let mut entries = self.entries.lock().await;
+            let entry = entries
+                .get_mut(name)
+                .ok_or_else(|| DaemonError::InvalidState(format!("Unknown task {name}")))?;
+            if entry.status.running {
+                return Err(DaemonError::InvalidState(format!(
+                    "Task {name} is already running"
+                )));
+            }
+            entry.status.running = true;
+            entry.task.clone()
+        };
+        tokio::spawn(
+            self.clone()
+                .execute(name.to_string(), task, TaskTrigger::Manual),
+        );
+        self.get(name)
+            .await
+            .ok_or_else(|| DaemonError::InvalidState(format!("Unknown task {name}")))
+    }
+
+    /// Start tasks as they fall due until the daemon stops
+    pub async fn run(self) {
+        loop {
+            let now = Utc::now();
+            let leader = self.election.get().is_none_or(LeaderElection::is_leader);
+            let shedding = BufferBudget::shared().is_shedding();
+            let mut due = Vec::new();
+            let mut wake = now + Duration::seconds(MAX_SLEEP.as_secs() as i64);
+            {
+                let mut entries = self.entries.lock().await;
+                for (name, entry) in entries.iter_mut() {
+                    let Some(next_run) = entry.status.next_run else {
+                        continue;
+                    };
+                    if next_run <= now {
+                        entry.status.next_run = entry.schedule.next_after(now);
+                        // A run still in progress is skipped rather than
+                        // overlapped, and the leader runs shared work
+                        if !entry.local && !leader {
+                            debug!("Leaving task {name} to the leader");
+                        } else if !entry.local && shedding {
+                            info!("Putting off task {name} while memory is short");
+                        } else if !entry.status.running {
+                            entry.status.running = true;
+                            due.push((name.clone(), entry.task.clone()));
+                        }
+                    }
+                    if let Some(next_run) = entry.status.next_run {
+                        wake = wake.min(next_run);
+                    }
+                }
+            }
+            for (name, task) in due {
+                tokio::spawn(self.clone().execute(name, task, TaskTrigger::Scheduled));
+            }
+            let sleep = (wake - Utc::now()).to_std().unwrap_or_default();
+            tokio::time::sleep(sleep.min(MAX_SLEEP)).await;
+        }
+    }
+
+    async fn execute(self, name: String, task: Arc<dyn ScheduledTask>, trigger: TaskTrigger) {
+        let mut run = TaskRun {
+            trigger,
+            started_at: Utc::now(),
+            finished_at: None,
+            error: None,
+        };
+        if let Some(entry) = self.entries.lock().await.get_mut(&name) {
+            entry.status.last_run = Some(run.clone());
+        }
+
+        debug!("Running task {name} ({trigger:?})");
+        if let Err(e) = task.run().await {
+            warn!("Task {name} failed: {e}");
+            run.error = Some(e.to_string());
+        }
+        run.finished_at = Some(Utc::now());
+
+        if let Some(entry) = self.entries.lock().await.get_mut(&name) {
+            entry.status.running = false;
+            entry.status.last_run = Some(run);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn at(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, mo, d, h, mi, 0).unwrap()
+    }
+
+    fn next(expr: &str, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        expr.parse::<Schedule>().unwrap().next_after(after)
+    }
+
+    #[test]
+    fn test_cron_schedules() {
+        let t = at(2025, 1, 31, 10, 7);
+        assert_eq!(next("*/15 * * * *", t), Some(at(2025, 1, 31, 10, 15)));
+        assert_eq!(next("0 3 * * *", t), Some(at(2025, 2, 1, 3, 0)));
+        assert_eq!(next("@daily", t), Some(at(2025, 2, 1, 0, 0)));
+        // 2 February 2025 is a Sunday
+        assert_eq!(next("30 9 * * 7", t), Some(at(2025, 2, 2, 9, 30)));
+        assert_eq!(next("0 0 29 2 *", t), Some(at(2028, 2, 29, 0, 0)));
+        assert_eq!(next("0 0 30 2 *", t), None);
+        assert_eq!(next("@every 90m", t), Some(at(2025, 1, 31, 11, 37)));
+    }
+
+    #[test]
+    fn test_invalid_schedules_are_rejected() {
+        for expr in [
+            "* * * *",
+            "60 * * * *",
+            "*/0 * * * *",
+            "@every 5x",
+            "@every 0m",
+        ] {
+            assert!(expr.parse::<Schedule>().is_err(), "{expr} should not parse");
+        }
+    }
+
+    struct Counter(AtomicUsize);
+
+    #[async_trait]
+    impl ScheduledTask for Counter {
+        async fn run(&self) -> Result<()> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_disabled_tasks_only_run_when_triggered() {
+        let scheduler = Scheduler::new();
+        let counter = Arc::new(Counter(AtomicUsize::new(0)));
+        let overrides = HashMap::from([(
+            "count".to_string(),
+            TaskConfig {
+                enabled: false,
+                schedule: Some("@every 1s".to_string()),
+            },
+        )]);
+        scheduler
+            .register(
+                "count",
+                "Counts runs",
+                "@hourly",
+                &overrides,
+                counter.clone(),
+            )
+            .await
+            .unwrap();
+
+        let status = scheduler.get("count").await.unwrap();
+        assert_eq!(status.schedule, "@every 1s");
+        assert!(!status.enabled && status.next_run.is_none());
+
+        scheduler.trigger("count").await.unwrap();
+        for _ in 0..100 {
+            if !scheduler.get("count").await.unwrap().running {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        let status = scheduler.get("count").await.unwrap();
+        assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+        assert_eq!(status.last_run.unwrap().trigger, TaskTrigger::Manual);
+        assert!(scheduler.trigger("missing").await.is_err());
+    }
+}
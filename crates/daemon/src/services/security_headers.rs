@@ -0,0 +1,224 @@
+//! Security headers for every response
+//!
+//! Responses carry `Strict-Transport-Security`, `X-Content-Type-Options`,
+//! framing rules and a Content-Security-Policy. Unless one is configured the
+//! policy is a strict one built for the bundled frontend: scripts only from
+//! the node, plus the inline bootstrap scripts of `index.html` by hash and
+//! WebAssembly compilation. The Swagger UI needs inline scripts and gets a
+//! policy of its own. Headers a handler already set are left alone.
+
+use crate::config::SecurityHeadersConfig;
+use axum::{
+    extract::{Request, State},
+    http::{HeaderName, HeaderValue, header},
+    middleware::Next,
+    response::Response,
+};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use regex::Regex;
+use sha2::{Digest, Sha256};
+use std::sync::{Arc, LazyLock};
+
+/// Paths served with the Swagger UI policy
+const SWAGGER_PATHS: [&str; 2] = ["/swagger-ui", "/api-docs"];
+
+/// Inline `<script>` elements: attributes, then body
+static INLINE_SCRIPT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<script([^>]*)>(.*?)</script>").expect("valid regex"));
+
+/// Headers to add, worked out once from the configuration
+pub struct SecurityHeaders {
+    enabled: bool,
+    hsts: Option<HeaderValue>,
+    frame_options: Option<HeaderValue>,
+    content_security_policy: Option<HeaderValue>,
+    swagger_content_security_policy: Option<HeaderValue>,
+}
+
+impl SecurityHeaders {
+    /// Headers for `config`; `index_html` is the frontend page whose inline
+    /// scripts the default policy allows
+    pub fn new(config: &SecurityHeadersConfig, index_html: Option<&str>) -> Self {
+        if !config.enabled {
+            return Self {
+                enabled: false,
+                hsts: None,
+                frame_options: None,
+                content_security_policy: None,
+                swagger_content_security_policy: None,
+            };
+        }
+
+        let hsts = (config.hsts_max_age_seconds > 0).then(|| {
+            let mut value = format!("max-age={}", config.hsts_max_age_seconds);
+            if config.hsts_include_subdomains {
+                value.push_str("; includeSubDomains");
+            }
+            value
+        });
+        let frame_ancestors = if config.frame_ancestors.is_empty() {
+            "'none'".to_string()
+        } else {
+            config.frame_ancestors.join(" ")
+        };
+        // X-Frame-Options cannot name origins; browsers that know
+        // frame-ancestors ignore it anyway
+        let frame_options = config
+            .frame_ancestors
+            .is_empty()
+            .then(|| "DENY".to_string());
+        let content_security_policy = config
+            .content_security_policy
+            .clone()
+            .unwrap_or_else(|| default_policy(index_html, &frame_ancestors));
+        let swagger_content_security_policy = format!(
+            "{}; frame-ancestors {frame_ancestors}",
+            config.swagger_content_security_policy.trim_end_matches(';')
+        );
+
+        Self {
+            enabled: true,
+            hsts: hsts.and_then(|v| header_value("HSTS", v)),
+            frame_options: frame_options.and_then(|v| header_value("X-Frame-Options", v)),
+            content_security_policy: header_value(
+                "Content-Security-Policy",
+                content_security_policy,
+            ),
+            swagger_content_security_policy: header_value(
+                "Swagger Content-Security-Policy",
+                swagger_content_security_policy,
+            ),
+        }
+    }
+
+    fn apply(&self, path: &str, response: &mut Response) {
+        if !self.enabled {
+            return;
+        }
+        let headers = response.headers_mut();
+        let mut set = |name: HeaderName, value: Option<&HeaderValue>| {
+            if let Some(value) = value
+                && !headers.contains_key(&name)
+            {
+                headers.insert(name, value.clone());
+            }
+        };
+        let policy = if SWAGGER_PATHS.iter().any(|p| path.starts_with(p)) {
+            &self.swagger_content_security_policy
+        } else {
+            &self.content_security_policy
+        };
+
+        set(header::STRICT_TRANSPORT_SECURITY, self.hsts.as_ref());
+        set(
+            header::X_CONTENT_TYPE_OPTIONS,
+            Some(&HeaderValue::from_static("nosniff")),
+        );
+        set(header::X_FRAME_OPTIONS, self.frame_options.as_ref());
+        set(header::CONTENT_SECURITY_POLICY, policy.as_ref());
+    }
+}
+
+fn header_value(name: &str, value: String) -> Option<HeaderValue> {
+    match HeaderValue::from_str(&value) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            warn!("Not sending invalid {name} header {value:?}: {e}");
+            None
+        }
+    }
+}
+
+/// Policy for the bundled frontend, allowing the inline scripts of
+/// `index_html` by hash
+fn default_policy(index_html: Option<&str>, frame_ancestors: &str) -> String {
+    let mut script_src = "'self' 'wasm-unsafe-eval'".to_string();
+    for hash in index_html.map(inline_script_hashes).unwrap_or_default() {
+        script_src.push_str(&format!(" '{hash}'"));
+    }
+    // Components set style attributes, which only 'unsafe-inline' allows
+    format!(
+        "default-src 'self'; script-src {script_src}; style-src 'self' 'unsafe-inline'; \
+         img-src 'self' data:; connect-src 'self'; object-src 'none'; base-uri 'self'; \
+         form-action 'self'; frame-ancestors {frame_ancestors}"
+    )
+}
+
+/// CSP hashes of the inline scripts in an HTML page
+fn inline_script_hashes(html: &str) -> Vec<String> {
+    INLINE_SCRIPT
+        .captures_iter(html)
+        .filter(|captures| !captures[1].contains("src="))
+        .map(|captures| {
+            let digest = Sha256::digest(captures[2].as_bytes());
+            format!("sha256-{}", STANDARD.encode(digest))
+        })
+        .collect()
+}
+
+/// Add the security headers to every response
+pub async fn security_headers_middleware(
+    State(headers): State<Arc<SecurityHeaders>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let path = request.uri().path().to_string();
+    let mut response = next.run(request).await;
+    headers.apply(&path, &mut response);
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_policy_allows_inline_bootstrap() {
+        let index = r#"<html><head>
+            <script type="module">import init from '/app.js'; init();</script>
+            <script src="/other.js"></script>
+        </head></html>"#;
+        let headers = SecurityHeaders::new(&SecurityHeadersConfig::default(), Some(index));
+
+        let mut response = Response::new(axum::body::Body::empty());
+        headers.apply("/", &mut response);
+        let policy = response.headers()[header::CONTENT_SECURITY_POLICY]
+            .to_str()
+            .unwrap();
+        let hash = STANDARD.encode(Sha256::digest(b"import init from '/app.js'; init();"));
+        assert!(policy.contains(&format!(
+            "script-src 'self' 'wasm-unsafe-eval' 'sha256-{hash}';"
+        )));
+        assert!(policy.ends_with("frame-ancestors 'none'"));
+        assert_eq!(response.headers()[header::X_FRAME_OPTIONS], "DENY");
+        assert_eq!(
+            response.headers()[header::X_CONTENT_TYPE_OPTIONS],
+            "nosniff"
+        );
+        assert_eq!(
+            response.headers()[header::STRICT_TRANSPORT_SECURITY],
+            "max-age=31536000"
+        );
+
+        // The Swagger UI gets its relaxed policy; handler headers are kept
+        let mut response = Response::new(axum::body::Body::empty());
+        response.headers_mut().insert(
+            header::X_FRAME_OPTIONS,
+            HeaderValue::from_static("SAMEORIGIN"),
+        );
+        headers.apply("/swagger-ui/index.html", &mut response);
+        let policy = response.headers()[header::CONTENT_SECURITY_POLICY]
+            .to_str()
+            .unwrap();
+        assert!(policy.contains("script-src 'self' 'unsafe-inline'"));
+        assert_eq!(response.headers()[header::X_FRAME_OPTIONS], "SAMEORIGIN");
+
+        let disabled = SecurityHeadersConfig {
+            enabled: false,
+            ..Default::default()
+        };
+        let mut response = Response::new(axum::body::Body::empty());
+        SecurityHeaders::new(&disabled, Some(index)).apply("/", &mut response);
+        assert!(response.headers().is_empty());
+    }
+}
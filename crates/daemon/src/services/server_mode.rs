@@ -0,0 +1,171 @@
+//! Maintenance and read-only modes
+//!
+//! Admins switch these on around upgrades. In maintenance mode inference
+//! routes answer 503 with a maintenance message while admin, auth and the
+//! UI stay up. In read-only mode requests that would change admin state or
+//! configuration are refused, so nothing changes under a running upgrade.
+//! Modes are held in memory and reset when the daemon restarts.
+
+use axum::{
+    extract::{Request, State},
+    http::{Method, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Json, Response},
+};
+use chrono::{DateTime, Utc};
+use gate_http::{AppState, error::ErrorResponse};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
+
+/// Seconds clients are told to wait before retrying
+const RETRY_AFTER_SECS: &str = "120";
+
+/// Path of the endpoint that switches modes, which read-only mode must
+/// leave reachable
+pub const MODE_PATH: &str = "/api/admin/mode";
+
+/// Routes whose writes change admin state or configuration
+pub(crate) const ADMIN_PREFIXES: [&str; 4] = [
+    "/api/admin/",
+    "/api/config",
+    "/api/onboarding/",
+    "/api/prompts",
+];
+
+/// Modes in effect
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerModeStatus {
+    /// Inference routes return 503
+    pub maintenance: bool,
+    /// Mutating admin and config endpoints are disabled
+    pub read_only: bool,
+    /// Message returned during maintenance, in place of the configured one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_by: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Shared handle to the modes in effect
+#[derive(Debug, Clone)]
+pub struct ServerMode {
+    status: Arc<RwLock<ServerModeStatus>>,
+    default_message: Arc<str>,
+}
+
+impl ServerMode {
+    pub fn new(default_message: impl Into<String>) -> Self {
+        Self {
+            status: Arc::new(RwLock::new(ServerModeStatus::default())),
+            default_message: default_message.into().into(),
+        }
+    }
+
+    pub fn status(&self) -> ServerModeStatus {
+        self.status
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub fn set(&self, status: ServerModeStatus) {
+        *self.status.write().unwrap_or_else(|e| e.into_inner()) = status;
+    }
+
+    /// Message returned to inference clients during maintenance
+    pub fn maintenance_message(&self) -> String {
+        self.status()
+            .message
+            .unwrap_or_else(|| self.default_message.to_string())
+    }
+
+    /// Why a request is refused under the current modes, if it is
+    fn refusal(&self, method: &Method, path: &str) -> Option<(&'static str, String)> {
+        let status = self.status();
+        if status.maintenance && path.starts_with("/v1/") {
+            return Some(("maintenance", self.maintenance_message()));
+        }
+        let mutating = !matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS);
+        let admin = ADMIN_PREFIXES.iter().any(|prefix| path.starts_with(prefix));
+        if status.read_only && mutating && admin && path != MODE_PATH {
+            return Some((
+                "read_only",
+                "Gate is read-only; admin and configuration changes are disabled".to_string(),
+            ));
+        }
+        None
+    }
+}
+
+/// Refuse requests the current modes do not allow
+pub async fn server_mode_middleware(
+    State(app_state): State<AppState<crate::State>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let refusal = app_state
+        .data
+        .server_mode
+        .refusal(request.method(), request.uri().path());
+    match refusal {
+        Some((error, message)) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            [(header::RETRY_AFTER, RETRY_AFTER_SECS)],
+            Json(ErrorResponse {
+                error: error.to_string(),
+                message,
+                details: None,
+            }),
+        )
+            .into_response(),
+        None => next.run(request).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_modes_refuse_only_their_routes() {
+        let mode = ServerMode::new("Back soon");
+        assert!(
+            mode.refusal(&Method::POST, "/v1/chat/completions")
+                .is_none()
+        );
+
+        mode.set(ServerModeStatus {
+            maintenance: true,
+            ..Default::default()
+        });
+        let (error, message) = mode.refusal(&Method::POST, "/v1/chat/completions").unwrap();
+        assert_eq!((error, message.as_str()), ("maintenance", "Back soon"));
+        assert!(
+            mode.refusal(&Method::POST, "/api/admin/providers")
+                .is_none()
+        );
+        assert!(
+            mode.refusal(&Method::POST, "/auth/webauthn/authenticate/start")
+                .is_none()
+        );
+
+        mode.set(ServerModeStatus {
+            read_only: true,
+            ..Default::default()
+        });
+        assert!(
+            mode.refusal(&Method::POST, "/v1/chat/completions")
+                .is_none()
+        );
+        assert!(mode.refusal(&Method::PUT, "/api/config").is_some());
+        assert!(mode.refusal(&Method::POST, "/api/prompts").is_some());
+        assert!(
+            mode.refusal(&Method::DELETE, "/api/admin/users/u")
+                .is_some()
+        );
+        assert!(mode.refusal(&Method::GET, "/api/admin/users").is_none());
+        assert!(mode.refusal(&Method::PUT, MODE_PATH).is_none());
+    }
+}
@@ -0,0 +1,478 @@
+//! HTTP message signatures
+//!
+//! Webhook deliveries and requests to upstream Gates are signed with this
+//! node's Ed25519 identity key, following RFC 9421, so receivers can check
+//! that a call came from a particular Gate without sharing a secret with it.
+//! Each signature covers the method, authority, path, query and a digest of
+//! the body (RFC 9530), and names the signing key by its hex public key.
+//!
+//! The receiving side is [`signature_middleware`]: a request that carries a
+//! signature must verify against a trusted key before it goes any further.
+//! The signature is checked on top of the usual authentication, not instead
+//! of it, and unsigned requests are left to that authentication alone.
+
+use axum::{
+    body::{Body, to_bytes},
+    extract::{OriginalUri, Request, State},
+    http::{HeaderMap, HeaderName, HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use chrono::Utc;
+use gate_core::router::middleware::ProvenanceSigner;
+use gate_http::sinks::signing::RequestSigner;
+use iroh::{PublicKey, SecretKey, Signature};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::sync::Arc;
+use thiserror::Error;
+
+/// Label of Gate's signature among any others on a message
+const LABEL: &str = "gate";
+
+const ALGORITHM: &str = "ed25519";
+
+pub const SIGNATURE_INPUT: HeaderName = HeaderName::from_static("signature-input");
+pub const SIGNATURE: HeaderName = HeaderName::from_static("signature");
+pub const CONTENT_DIGEST: HeaderName = HeaderName::from_static("content-digest");
+
+/// Components every signature covers, in order
+const COVERED: [&str; 5] = ["@method", "@authority", "@path", "@query", "content-digest"];
+
+/// How far a signature's creation time may be from the receiver's clock
+const MAX_SKEW_SECS: i64 = 300;
+
+/// Largest body buffered to check a signed request's digest
+const MAX_SIGNED_BODY_BYTES: usize = 4 * 1024 * 1024;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SignatureError {
+    #[error("Missing {0} header")]
+    Missing(&'static str),
+    #[error("Malformed signature: {0}")]
+    Malformed(&'static str),
+    #[error("Signature was created too far from now")]
+    Expired,
+    #[error("Body does not match its content digest")]
+    DigestMismatch,
+    #[error("Key {0} is not trusted")]
+    Untrusted(String),
+    #[error("Signature does not verify")]
+    Invalid,
+}
+
+/// The key a request was verified as signed with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifiedSigner {
+    /// Hex public key the request was signed with
+    pub key_id: String,
+}
+
+/// `sha-256=:<base64>:` digest of a body
+pub fn content_digest(body: &[u8]) -> String {
+    format!("sha-256=:{}:", STANDARD.encode(Sha256::digest(body)))
+}
+
+fn signature_params(created: i64, key_id: &str) -> String {
+    let covered: Vec<String> = COVERED.iter().map(|c| format!("\"{c}\"")).collect();
+    format!(
+        "({});created={created};keyid=\"{key_id}\";alg=\"{ALGORITHM}\"",
+        covered.join(" ")
+    )
+}
+
+/// The `@query` component of RFC 9421 section 2.2.7: `?` and the query,
+/// which is just `?` for a request without one
+fn query_component(query: Option<&str>) -> String {
+    format!("?{}", query.unwrap_or_default())
+}
+
+/// The signature base of RFC 9421 section 2.5
+fn signature_base(
+    method: &str,
+    authority: &str,
+    path: &str,
+    query: Option<&str>,
+    digest: &str,
+    params: &str,
+) -> String {
+    let query = query_component(query);
+    let values = [method, authority, path, &query, digest];
+    let mut base = String::new();
+    for (component, value) in COVERED.iter().zip(values) {
+        base.push_str(&format!("\"{component}\": {value}\n"));
+    }
+    base.push_str(&format!("\"@signature-params\": {params}"));
+    base
+}
+
+/// Signs requests with this node's identity key
+pub struct MessageSigner {
+    key: SecretKey,
+    key_id: String,
+}
+
+impl MessageSigner {
+    pub fn new(key: SecretKey) -> Self {
+        let key_id = hex::encode(key.public().as_bytes());
+        Self { key, key_id }
+    }
+
+    /// Hex public key receivers verify against
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    /// Base64 Ed25519 signature over `message`
+    pub fn sign_bytes(&self, message: &[u8]) -> String {
+        STANDARD.encode(self.key.sign(message).to_bytes())
+    }
+
+    /// `Content-Digest`, `Signature-Input` and `Signature` headers for a
+    /// request created at `created` (Unix seconds)
+    pub fn headers(
+        &self,
+        method: &str,
+        authority: &str,
+        path: &str,
+        query: Option<&str>,
+        body: &[u8],
+        created: i64,
+    ) -> [(HeaderName, String); 3] {
+        let digest = content_digest(body);
+        let params = signature_params(created, &self.key_id);
+        let base = signature_base(method, authority, path, query, &digest, &params);
+        let signature = self.sign_bytes(base.as_bytes());
+        [
+            (CONTENT_DIGEST, digest),
+            (SIGNATURE_INPUT, format!("{LABEL}={params}")),
+            (SIGNATURE, format!("{LABEL}=:{signature}:")),
+        ]
+    }
+
+    fn insert(&self, request: &mut reqwest::Request, authority: &str, body: &[u8]) {
+        let created = Utc::now().timestamp();
+        let method = request.method().as_str().to_string();
+        let url = request.url();
+        let headers = self.headers(&method, authority, url.path(), url.query(), body, created);
+        for (name, value) in headers {
+            if let Ok(value) = HeaderValue::from_str(&value) {
+                request.headers_mut().insert(name, value);
+            }
+        }
+    }
+}
+
+impl RequestSigner for MessageSigner {
+    fn sign(&self, request: &mut reqwest::Request) {
+        let url = request.url();
+        let authority = match url.port() {
+            Some(port) => format!("{}:{port}", url.host_str().unwrap_or_default()),
+            None => url.host_str().unwrap_or_default().to_string(),
+        };
+        let body = match request.body() {
+            None => Vec::new(),
+            Some(body) => match body.as_bytes() {
+                Some(bytes) => bytes.to_vec(),
+                // Streamed bodies cannot be digested up front and go unsigned
+                None => {
+                    warn!("Not signing streamed request to {}", authority);
+                    return;
+                }
+            },
+        };
+        self.insert(request, &authority, &body);
+    }
+}
+
+impl ProvenanceSigner for MessageSigner {
+    fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    fn sign(&self, message: &[u8]) -> String {
+        self.sign_bytes(message)
+    }
+}
+
+/// Value of `label` in a dictionary header such as `gate=...`
+fn member<'a>(header: &'a str, label: &str) -> Option<&'a str> {
+    header.split(',').find_map(|member| {
+        let (name, value) = member.trim().split_once('=')?;
+        (name == label).then_some(value)
+    })
+}
+
+/// A signature's input, checked as far as it can be without the body
+#[derive(Debug)]
+pub struct SignatureInput<'a> {
+    params: String,
+    key_id: &'a str,
+    digest: &'a str,
+    signature: &'a str,
+}
+
+impl<'a> SignatureInput<'a> {
+    /// Read Gate's signature from `headers`, checking its parameters and that
+    /// it was created close to `now`
+    pub fn parse(headers: &'a HeaderMap, now: i64) -> Result<Self, SignatureError> {
+        let text = |name: &HeaderName, label: &'static str| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .ok_or(SignatureError::Missing(label))
+        };
+        let input = member(text(&SIGNATURE_INPUT, "Signature-Input")?, LABEL)
+            .ok_or(SignatureError::Missing("gate signature"))?;
+        let signature = member(text(&SIGNATURE, "Signature")?, LABEL)
+            .ok_or(SignatureError::Missing("gate signature"))?;
+        let digest = text(&CONTENT_DIGEST, "Content-Digest")?;
+
+        // Only the parameters Gate itself writes are accepted
+        let (_, rest) = input
+            .split_once(')')
+            .ok_or(SignatureError::Malformed("signature input"))?;
+        let param = |name: &str| {
+            rest.split(';')
+                .find_map(|p| p.strip_prefix(name)?.strip_prefix('='))
+                .map(|v| v.trim_matches('"'))
+        };
+        let created: i64 = param("created")
+            .and_then(|v| v.parse().ok())
+            .ok_or(SignatureError::Malformed("created"))?;
+        let key_id = param("keyid").ok_or(SignatureError::Malformed("keyid"))?;
+        if param("alg") != Some(ALGORITHM) {
+            return Err(SignatureError::Malformed("alg"));
+        }
+        let params = signature_params(created, key_id);
+        if input != params {
+            return Err(SignatureError::Malformed("covered components"));
+        }
+        if (now - created).abs() > MAX_SKEW_SECS {
+            return Err(SignatureError::Expired);
+        }
+        let signature = signature
+            .strip_prefix(':')
+            .and_then(|s| s.strip_suffix(':'))
+            .ok_or(SignatureError::Malformed("signature"))?;
+        Ok(Self {
+            params,
+            key_id,
+            digest,
+            signature,
+        })
+    }
+
+    /// Hex public key the signature names
+    pub fn key_id(&self) -> &str {
+        self.key_id
+    }
+
+    /// Check the body against the digest and the signature over the request
+    pub fn verify(
+        &self,
+        method: &str,
+        authority: &str,
+        path: &str,
+        query: Option<&str>,
+        body: &[u8],
+    ) -> Result<VerifiedSigner, SignatureError> {
+        if self.digest != content_digest(body) {
+            return Err(SignatureError::DigestMismatch);
+        }
+        let base = signature_base(method, authority, path, query, self.digest, &self.params);
+        verify_signature(self.key_id, base.as_bytes(), self.signature)?;
+        Ok(VerifiedSigner {
+            key_id: self.key_id.to_string(),
+        })
+    }
+}
+
+/// Verify the signature on a request, returning the key that signed it
+pub fn verify(
+    method: &str,
+    authority: &str,
+    path: &str,
+    query: Option<&str>,
+    headers: &HeaderMap,
+    body: &[u8],
+    now: i64,
+) -> Result<VerifiedSigner, SignatureError> {
+    SignatureInput::parse(headers, now)?.verify(method, authority, path, query, body)
+}
+
+/// Check a base64 Ed25519 `signature` over `message` by the hex key `key_id`
+pub fn verify_signature(
+    key_id: &str,
+    message: &[u8],
+    signature: &str,
+) -> Result<(), SignatureError> {
+    let key_bytes: [u8; 32] = hex::decode(key_id)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(SignatureError::Malformed("keyid"))?;
+    let key = PublicKey::from_bytes(&key_bytes).map_err(|_| SignatureError::Malformed("keyid"))?;
+    let signature_bytes: [u8; 64] = STANDARD
+        .decode(signature)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(SignatureError::Malformed("signature"))?;
+    key.verify(message, &Signature::from_bytes(&signature_bytes))
+        .map_err(|_| SignatureError::Invalid)
+}
+
+/// Keys whose signatures are accepted
+#[derive(Debug, Clone, Default)]
+pub struct TrustedKeys {
+    keys: HashSet<String>,
+}
+
+impl TrustedKeys {
+    pub fn new(keys: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            keys: keys.into_iter().map(|k| k.to_ascii_lowercase()).collect(),
+        }
+    }
+}
+
+/// Verify signed requests, refusing those with a bad or untrusted signature.
+/// The signature input, key and creation time are checked before the body is
+/// read, so only trusted signers have a body buffered.
+pub async fn signature_middleware(
+    State(trusted): State<Arc<TrustedKeys>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !request.headers().contains_key(&SIGNATURE) {
+        return next.run(request).await;
+    }
+    let reject = |e: SignatureError| {
+        debug!("Rejected signed request: {}", e);
+        (StatusCode::UNAUTHORIZED, e.to_string()).into_response()
+    };
+
+    let (parts, body) = request.into_parts();
+    let input = match SignatureInput::parse(&parts.headers, Utc::now().timestamp()) {
+        Ok(input) if trusted.keys.contains(input.key_id()) => input,
+        Ok(input) => return reject(SignatureError::Untrusted(input.key_id().to_string())),
+        Err(e) => return reject(e),
+    };
+    let body = match to_bytes(body, MAX_SIGNED_BODY_BYTES).await {
+        Ok(body) => body,
+        Err(_) => return StatusCode::PAYLOAD_TOO_LARGE.into_response(),
+    };
+    // Signatures cover the path the sender used, before any prefix is stripped
+    let uri = parts
+        .extensions
+        .get::<OriginalUri>()
+        .map(|original| original.0.clone())
+        .unwrap_or_else(|| parts.uri.clone());
+    let authority = parts
+        .headers
+        .get(header::HOST)
+        .and_then(|v| v.to_str().ok())
+        .or_else(|| uri.authority().map(|a| a.as_str()))
+        .unwrap_or_default();
+
+    match input.verify(
+        parts.method.as_str(),
+        authority,
+        uri.path(),
+        uri.query(),
+        &body,
+    ) {
+        Ok(signer) => {
+            debug!("Verified request signature from {}", signer.key_id);
+            next.run(Request::from_parts(parts, Body::from(body))).await
+        }
+        Err(e) => reject(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signed(signer: &MessageSigner, body: &[u8], created: i64) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in signer.headers(
+            "POST",
+            "hooks.example.com",
+            "/gate",
+            Some("x=1"),
+            body,
+            created,
+        ) {
+            headers.insert(name, HeaderValue::from_str(&value).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn test_signatures_verify_and_reject_tampering() {
+        let signer = MessageSigner::new(SecretKey::from_bytes(&[7u8; 32]));
+        let body = br#"{"event":"spend.alert"}"#;
+        let headers = signed(&signer, body, 1_000);
+
+        let verified = verify(
+            "POST",
+            "hooks.example.com",
+            "/gate",
+            Some("x=1"),
+            &headers,
+            body,
+            1_010,
+        )
+        .unwrap();
+        assert_eq!(verified.key_id, signer.key_id());
+
+        let verify_with = |method, path, query, body: &[u8], now| {
+            verify(
+                method,
+                "hooks.example.com",
+                path,
+                query,
+                &headers,
+                body,
+                now,
+            )
+        };
+        assert_eq!(
+            verify_with("POST", "/gate", Some("x=1"), b"{}", 1_010),
+            Err(SignatureError::DigestMismatch)
+        );
+        assert_eq!(
+            verify_with("PUT", "/gate", Some("x=1"), body, 1_010),
+            Err(SignatureError::Invalid)
+        );
+        assert_eq!(
+            verify_with("POST", "/other", Some("x=1"), body, 1_010),
+            Err(SignatureError::Invalid)
+        );
+        assert_eq!(
+            verify_with("POST", "/gate", Some("x=2"), body, 1_010),
+            Err(SignatureError::Invalid)
+        );
+        assert_eq!(
+            verify_with("POST", "/gate", None, body, 1_010),
+            Err(SignatureError::Invalid)
+        );
+        assert_eq!(
+            verify_with("POST", "/gate", Some("x=1"), body, 2_000),
+            Err(SignatureError::Expired)
+        );
+        assert_eq!(
+            verify(
+                "POST",
+                "hooks.example.com",
+                "/",
+                None,
+                &HeaderMap::new(),
+                body,
+                0
+            ),
+            Err(SignatureError::Missing("Signature-Input"))
+        );
+    }
+}
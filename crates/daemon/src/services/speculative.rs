@@ -0,0 +1,125 @@
+//! Speculative decoding with a draft model
+//!
+//! A small draft model guesses the next few tokens and the target model
+//! checks the guess, keeping the longest prefix it agrees with and its own
+//! next token after it. Decoding is greedy, so the output is exactly what
+//! the target alone would produce; tokens per second go up by as much of
+//! the draft as is accepted. Acceptance is counted per pairing, so a draft
+//! that rarely agrees with its target shows up in the status.
+
+use gate_core::tracing::metrics::counter;
+use serde::{Deserialize, Serialize};
+
+/// Tokens proposed and accepted for one target and draft pairing
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DraftStats {
+    pub target: String,
+    pub draft: String,
+    /// Draft tokens the target checked
+    pub proposed: u64,
+    /// Draft tokens the target agreed with
+    pub accepted: u64,
+    /// Share of proposed tokens accepted
+    pub acceptance_rate: f64,
+}
+
+impl DraftStats {
+    pub fn new(target: impl Into<String>, draft: impl Into<String>) -> Self {
+        Self {
+            target: target.into(),
+            draft: draft.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn record(&mut self, proposed: u64, accepted: u64) {
+        self.proposed += proposed;
+        self.accepted += accepted;
+        if self.proposed > 0 {
+            self.acceptance_rate = self.accepted as f64 / self.proposed as f64;
+        }
+        counter("local_draft_tokens_proposed_total").add(proposed);
+        counter("local_draft_tokens_accepted_total").add(accepted);
+    }
+}
+
+/// Tokens generated and how much of the draft was accepted
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decoded<T> {
+    pub tokens: Vec<T>,
+    pub proposed: u64,
+    pub accepted: u64,
+}
+
+/// Generate up to `max_tokens` after `context`, drafting `draft_tokens` at
+/// a time. Each model is a function returning up to `n` tokens it greedily
+/// continues a context with; fewer mean it reached the end of its output.
+pub fn speculative_decode<T: Clone + PartialEq>(
+    mut target: impl FnMut(&[T], usize) -> Vec<T>,
+    mut draft: impl FnMut(&[T], usize) -> Vec<T>,
+    mut context: Vec<T>,
+    max_tokens: usize,
+    draft_tokens: usize,
+) -> Decoded<T> {
+    let mut decoded = Decoded {
+        tokens: Vec::new(),
+        proposed: 0,
+        accepted: 0,
+    };
+    while decoded.tokens.len() < max_tokens {
+        let remaining = max_tokens - decoded.tokens.len();
+        let proposal = draft(&context, draft_tokens.clamp(1, remaining));
+        let checked = target(&context, proposal.len() + 1);
+        let accepted = proposal
+            .iter()
+            .zip(&checked)
+            .take_while(|(proposed, checked)| proposed == checked)
+            .count();
+        decoded.proposed += proposal.len() as u64;
+        decoded.accepted += accepted as u64;
+
+        // The accepted tokens, then the target's own token in place of the
+        // first one it disagreed with
+        let kept = &checked[..checked.len().min(accepted + 1)];
+        context.extend_from_slice(kept);
+        decoded.tokens.extend_from_slice(kept);
+        if kept.len() == checked.len() && checked.len() <= proposal.len() {
+            break;
+        }
+    }
+    decoded.tokens.truncate(max_tokens);
+    decoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A model that continues any context with the rest of `text`
+    fn model(text: &'static str) -> impl FnMut(&[char], usize) -> Vec<char> {
+        move |context, n| text.chars().skip(context.len()).take(n).collect()
+    }
+
+    #[test]
+    fn test_output_matches_the_target_alone() {
+        let target = "the cat sat on the mat";
+        let decoded = speculative_decode(
+            model(target),
+            model("the cat sat in the hat"),
+            vec![],
+            100,
+            4,
+        );
+        assert_eq!(decoded.tokens.iter().collect::<String>(), target);
+        assert!(decoded.accepted > 0 && decoded.accepted < decoded.proposed);
+
+        // A draft that always agrees is always accepted
+        let decoded = speculative_decode(model(target), model(target), vec![], 8, 4);
+        assert_eq!(decoded.tokens.iter().collect::<String>(), "the cat ");
+        assert_eq!(decoded.accepted, decoded.proposed);
+
+        let mut stats = DraftStats::new("big", "small");
+        stats.record(decoded.proposed, decoded.accepted);
+        assert_eq!(stats.acceptance_rate, 1.0);
+    }
+}
@@ -0,0 +1,479 @@
+//! Spend forecasting and anomaly alerts
+//!
+//! Usage is grouped per user and per organization. Month-to-date spend plus
+//! the last week's daily run rate over the rest of the month forecasts
+//! month-end spend. A user or org is flagged when it uses many times its
+//! usual hourly tokens in one check window, or when a model it barely used
+//! before takes most of its recent traffic. [`SpendMonitor`] runs these
+//! checks on a schedule, logs each alert and posts it to the configured
+//! webhooks with a link to the offending traffic.
+
+use crate::config::SpendConfig;
+use crate::error::Result;
+use crate::services::journal::Journal;
+use crate::services::scheduler::ScheduledTask;
+use crate::services::webhooks::enqueue_webhook;
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, SecondsFormat, Utc};
+use gate_core::{StateBackend, TimeRange, UsageRecord};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Days of usage the run rate is taken over
+const RUN_RATE_DAYS: i64 = 7;
+
+/// Days of usage before the check window that spikes are compared against
+const BASELINE_DAYS: i64 = 7;
+
+/// Share of recent tokens a model needs to count as taking over
+const MODEL_MIX_RECENT_SHARE: f64 = 0.5;
+
+/// Share of baseline tokens under which a model counts as barely used
+const MODEL_MIX_BASELINE_SHARE: f64 = 0.05;
+
+/// Who spend is attributed to
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SpendSubject {
+    User { id: String },
+    Org { id: String },
+}
+
+impl SpendSubject {
+    /// Users and orgs a usage record counts towards
+    fn of(record: &UsageRecord) -> impl Iterator<Item = SpendSubject> {
+        let user = (!record.user_id.is_empty()).then(|| SpendSubject::User {
+            id: record.user_id.clone(),
+        });
+        let org = (!record.org_id.is_empty()).then(|| SpendSubject::Org {
+            id: record.org_id.clone(),
+        });
+        user.into_iter().chain(org)
+    }
+
+    /// Admin usage listing filtered to this subject since `since`
+    fn traffic_link(&self, since: DateTime<Utc>) -> String {
+        let (key, id) = match self {
+            SpendSubject::User { id } => ("user_id", id),
+            SpendSubject::Org { id } => ("org_id", id),
+        };
+        let since = since.to_rfc3339_opts(SecondsFormat::Secs, true);
+        match reqwest::Url::parse_with_params(
+            "http://localhost/api/admin/usage",
+            [(key, id.as_str()), ("since", since.as_str())],
+        ) {
+            Ok(url) => format!("{}?{}", url.path(), url.query().unwrap_or_default()),
+            Err(_) => "/api/admin/usage".to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for SpendSubject {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpendSubject::User { id } => write!(f, "user {id}"),
+            SpendSubject::Org { id } => write!(f, "org {id}"),
+        }
+    }
+}
+
+/// Month-end spend forecast for a user or org
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpendForecast {
+    pub subject: SpendSubject,
+    pub month_to_date_usd: f64,
+    pub month_to_date_tokens: u64,
+    /// Average daily spend over the last week
+    pub daily_run_rate_usd: f64,
+    pub forecast_usd: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpendAlertKind {
+    ForecastOverThreshold,
+    TokenSpike,
+    ModelMixShift,
+}
+
+/// Unusual spend by a user or org
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpendAlert {
+    pub id: String,
+    pub kind: SpendAlertKind,
+    pub subject: SpendSubject,
+    pub message: String,
+    /// Admin API path listing the traffic behind the alert
+    pub traffic: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl SpendAlert {
+    fn new(
+        kind: SpendAlertKind,
+        subject: SpendSubject,
+        message: String,
+        since: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            kind,
+            traffic: subject.traffic_link(since),
+            subject,
+            message,
+            created_at: now,
+        }
+    }
+}
+
+fn month_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    NaiveDate::from_ymd_opt(now.year(), now.month(), 1)
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map_or(now, |d| d.and_utc())
+}
+
+fn next_month_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    let (year, month) = if now.month() == 12 {
+        (now.year() + 1, 1)
+    } else {
+        (now.year(), now.month() + 1)
+    };
+    NaiveDate::from_ymd_opt(year, month, 1)
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map_or(now, |d| d.and_utc())
+}
+
+fn check_window(config: &SpendConfig) -> Duration {
+    Duration::minutes(config.check_interval_minutes.max(1) as i64)
+}
+
+/// Earliest usage the forecasts and anomaly checks read
+pub fn history_start(now: DateTime<Utc>, config: &SpendConfig) -> DateTime<Utc> {
+    let baseline = now - check_window(config) - Duration::days(BASELINE_DAYS);
+    month_start(now)
+        .min(now - Duration::days(RUN_RATE_DAYS))
+        .min(baseline)
+}
+
+/// Month-end spend forecasts for every user and org with usage this month,
+/// highest first
+pub fn forecast_spend(records: &[UsageRecord], now: DateTime<Utc>) -> Vec<SpendForecast> {
+    let month_start = month_start(now);
+    let run_rate_start = now - Duration::days(RUN_RATE_DAYS);
+    let remaining_days = (next_month_start(now) - now).num_seconds().max(0) as f64 / 86_400.0;
+
+    // (month-to-date cost, month-to-date tokens, run rate window cost)
+    let mut totals: BTreeMap<SpendSubject, (f64, u64, f64)> = BTreeMap::new();
+    for record in records.iter().filter(|r| r.timestamp <= now) {
+        for subject in SpendSubject::of(record) {
+            let total = totals.entry(subject).or_default();
+            if record.timestamp >= month_start {
+                total.0 += record.cost;
+                total.1 += record.total_tokens;
+            }
+            if record.timestamp > run_rate_start {
+                total.2 += record.cost;
+            }
+        }
+    }
+
+    let mut forecasts: Vec<SpendForecast> = totals
+        .into_iter()
+        .filter(|(_, (_, tokens, _))| *tokens > 0)
+        .map(|(subject, (cost, tokens, recent))| {
+            let daily_run_rate_usd = recent / RUN_RATE_DAYS as f64;
+            SpendForecast {
+                subject,
+                month_to_date_usd: cost,
+                month_to_date_tokens: tokens,
+                daily_run_rate_usd,
+                forecast_usd: cost + daily_run_rate_usd * remaining_days,
+            }
+        })
+        .collect();
+    forecasts.sort_by(|a, b| b.forecast_usd.total_cmp(&a.forecast_usd));
+    forecasts
+}
+
+/// Token spikes and model mix shifts in the last check window, judged
+/// against the week before it. Users and orgs without earlier usage have
+/// nothing to compare against and are not flagged.
+pub fn detect_anomalies(
+    records: &[UsageRecord],
+    now: DateTime<Utc>,
+    config: &SpendConfig,
+) -> Vec<SpendAlert> {
+    let window = check_window(config);
+    let window_start = now - window;
+    let baseline_start = window_start - Duration::days(BASELINE_DAYS);
+
+    #[derive(Default)]
+    struct Usage {
+        recent: u64,
+        baseline: u64,
+        recent_by_model: BTreeMap<String, u64>,
+        baseline_by_model: HashMap<String, u64>,
+    }
+
+    let mut usage: BTreeMap<SpendSubject, Usage> = BTreeMap::new();
+    for record in records {
+        let recent = record.timestamp > window_start && record.timestamp <= now;
+        let baseline = record.timestamp > baseline_start && record.timestamp <= window_start;
+        if !recent && !baseline {
+            continue;
+        }
+        for subject in SpendSubject::of(record) {
+            let entry = usage.entry(subject).or_default();
+            if recent {
+                entry.recent += record.total_tokens;
+                *entry
+                    .recent_by_model
+                    .entry(record.model_id.clone())
+                    .or_default() += record.total_tokens;
+            } else {
+                entry.baseline += record.total_tokens;
+                *entry
+                    .baseline_by_model
+                    .entry(record.model_id.clone())
+                    .or_default() += record.total_tokens;
+            }
+        }
+    }
+
+    let windows_per_baseline =
+        Duration::days(BASELINE_DAYS).num_seconds() as f64 / window.num_seconds() as f64;
+    let mut alerts = Vec::new();
+    for (subject, usage) in usage {
+        if usage.baseline == 0 || usage.recent < config.min_alert_tokens {
+            continue;
+        }
+
+        let expected = usage.baseline as f64 / windows_per_baseline;
+        if usage.recent as f64 >= config.spike_factor * expected {
+            alerts.push(SpendAlert::new(
+                SpendAlertKind::TokenSpike,
+                subject.clone(),
+                format!(
+                    "{subject} used {} tokens in the last {} minutes, {:.1}x its usual {:.0}",
+                    usage.recent,
+                    window.num_minutes(),
+                    usage.recent as f64 / expected,
+                    expected
+                ),
+                window_start,
+                now,
+            ));
+        }
+
+        for (model, tokens) in &usage.recent_by_model {
+            let recent_share = *tokens as f64 / usage.recent as f64;
+            let baseline_share = usage.baseline_by_model.get(model).copied().unwrap_or(0) as f64
+                / usage.baseline as f64;
+            if recent_share >= MODEL_MIX_RECENT_SHARE && baseline_share < MODEL_MIX_BASELINE_SHARE {
+                alerts.push(SpendAlert::new(
+                    SpendAlertKind::ModelMixShift,
+                    subject.clone(),
+                    format!(
+                        "{subject} sent {:.0}% of its recent tokens to {model}, up from {:.1}%",
+                        recent_share * 100.0,
+                        baseline_share * 100.0
+                    ),
+                    window_start,
+                    now,
+                ));
+            }
+        }
+    }
+    alerts
+}
+
+/// Anomalies plus forecasts over the configured threshold
+pub fn spend_alerts(
+    records: &[UsageRecord],
+    now: DateTime<Utc>,
+    config: &SpendConfig,
+) -> Vec<SpendAlert> {
+    let mut alerts = detect_anomalies(records, now, config);
+    if let Some(threshold) = config.forecast_alert_usd {
+        alerts.extend(
+            forecast_spend(records, now)
+                .into_iter()
+                .filter(|f| f.forecast_usd > threshold)
+                .map(|f| {
+                    SpendAlert::new(
+                        SpendAlertKind::ForecastOverThreshold,
+                        f.subject.clone(),
+                        format!(
+                            "{} is forecast to spend ${:.2} this month, over the ${threshold:.2} threshold (${:.2} so far, ${:.2}/day)",
+                            f.subject, f.forecast_usd, f.month_to_date_usd, f.daily_run_rate_usd
+                        ),
+                        month_start(now),
+                        now,
+                    )
+                }),
+        );
+    }
+    alerts
+}
+
+/// Scheduled task raising spend alerts
+pub struct SpendMonitor {
+    state_backend: Arc<dyn StateBackend>,
+    journal: Journal,
+    config: SpendConfig,
+    /// When each alert was last raised, for the cooldown
+    raised: Mutex<HashMap<(SpendAlertKind, SpendSubject), DateTime<Utc>>>,
+}
+
+impl SpendMonitor {
+    pub fn new(
+        state_backend: Arc<dyn StateBackend>,
+        journal: Journal,
+        config: SpendConfig,
+    ) -> Self {
+        Self {
+            state_backend,
+            journal,
+            config,
+            raised: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Scheduler interval matching the configured check interval
+    pub fn default_schedule(&self) -> String {
+        format!("@every {}m", self.config.check_interval_minutes.max(1))
+    }
+
+    /// Raise alerts for current spend, skipping ones still cooling down
+    async fn check(&self, now: DateTime<Utc>) -> Result<()> {
+        let range = TimeRange {
+            start: history_start(now, &self.config),
+            end: now,
+        };
+        let records = self.state_backend.list_usage(&range).await?;
+
+        let cooldown = Duration::hours(self.config.alert_cooldown_hours as i64);
+        let mut raised = self.raised.lock().await;
+        raised.retain(|_, at| now - *at < cooldown);
+        for alert in spend_alerts(&records, now, &self.config) {
+            let key = (alert.kind, alert.subject.clone());
+            if raised.contains_key(&key) {
+                continue;
+            }
+            raised.insert(key, now);
+
+            warn!("Spend alert: {} ({})", alert.message, alert.traffic);
+            for url in &self.config.webhook_urls {
+                let event = json!({ "type": "spend_alert", "alert": alert });
+                if let Err(e) = enqueue_webhook(&self.journal, url, event).await {
+                    warn!("Failed to queue spend alert for {url}: {e}");
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ScheduledTask for SpendMonitor {
+    async fn run(&self) -> Result<()> {
+        self.check(Utc::now()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::limits::tests::{backend, context, serve};
+    use chrono::TimeZone;
+
+    /// Usage of `tokens` input tokens recorded by the cost tracker, at $10
+    /// per million, dated `at`
+    async fn record(
+        backend: &Arc<dyn StateBackend>,
+        user: &str,
+        model: &str,
+        tokens: u32,
+        at: DateTime<Utc>,
+    ) -> UsageRecord {
+        let mut record = serve(backend, &context(user, None), model, tokens, 0).await;
+        record.timestamp = at;
+        record
+    }
+
+    #[tokio::test]
+    async fn test_forecast_extends_run_rate_to_month_end() {
+        // Ten days into a 30 day month, spending $2 a day
+        let backend = backend().await;
+        let now = Utc.with_ymd_and_hms(2026, 6, 11, 0, 0, 0).unwrap();
+        let mut records = Vec::new();
+        for day in 1..=10 {
+            let at = now - Duration::days(day) + Duration::hours(1);
+            records.push(record(&backend, "alice", "m", 200_000, at).await);
+        }
+
+        let forecasts = forecast_spend(&records, now);
+        assert_eq!(forecasts.len(), 1);
+        let forecast = &forecasts[0];
+        assert!((forecast.month_to_date_usd - 20.0).abs() < 1e-9);
+        assert!((forecast.daily_run_rate_usd - 2.0).abs() < 1e-9);
+        assert!((forecast.forecast_usd - 60.0).abs() < 1e-9);
+        assert_eq!(forecast.month_to_date_tokens, 2_000_000);
+    }
+
+    #[tokio::test]
+    async fn test_detects_spike_and_model_shift() {
+        let backend = backend().await;
+        let now = Utc.with_ymd_and_hms(2026, 6, 20, 12, 0, 0).unwrap();
+        let config = SpendConfig::default();
+        // A steady 1000 tokens an hour for a week, then 200k in the last hour
+        let mut records = Vec::new();
+        for hour in 2..=BASELINE_DAYS * 24 {
+            let at = now - Duration::hours(hour);
+            records.push(record(&backend, "alice", "small", 1000, at).await);
+        }
+        let at = now - Duration::minutes(5);
+        records.push(record(&backend, "alice", "large", 200_000, at).await);
+        // Steady usage is not flagged
+        for hour in 0..=BASELINE_DAYS * 24 {
+            let at = now - Duration::hours(hour);
+            records.push(record(&backend, "bob", "small", 1000, at).await);
+        }
+
+        let alerts = detect_anomalies(&records, now, &config);
+        let kinds: Vec<_> = alerts.iter().map(|a| a.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![SpendAlertKind::TokenSpike, SpendAlertKind::ModelMixShift]
+        );
+        assert!(alerts.iter().all(|a| a.subject
+            == SpendSubject::User {
+                id: "alice".to_string()
+            }));
+        assert!(
+            alerts[0]
+                .traffic
+                .starts_with("/api/admin/usage?user_id=alice&since=")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_forecast_alerts_need_a_threshold() {
+        // $50 of usage
+        let backend = backend().await;
+        let now = Utc.with_ymd_and_hms(2026, 6, 11, 0, 0, 0).unwrap();
+        let records =
+            vec![record(&backend, "alice", "m", 5_000_000, now - Duration::hours(1)).await];
+        let mut config = SpendConfig::default();
+        assert!(spend_alerts(&records, now, &config).is_empty());
+
+        config.forecast_alert_usd = Some(100.0);
+        let alerts = spend_alerts(&records, now, &config);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].kind, SpendAlertKind::ForecastOverThreshold);
+    }
+}
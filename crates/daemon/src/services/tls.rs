@@ -1,11 +1,80 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use gate_tlsforward::client::dns::{
+    CloudflareDns, DnsProvider, Rfc2136Dns, Route53Credentials, Route53Dns, TsigKey,
+};
 use gate_tlsforward::{CertificateManager, TlsForwardClient};
 use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Mutex;
 
+use crate::config::{DnsChallengeConfig, DnsProviderConfig};
+use crate::services::certificates::{CertificateFiles, CertificateInfo, load_certificate};
 use crate::{services::TlsForwardService, tls_reload::ReloadableTlsAcceptor};
 
+/// Build the DNS provider a challenge configuration describes
+pub fn dns_provider(config: &DnsChallengeConfig) -> Result<Arc<dyn DnsProvider>> {
+    let delay = Duration::from_secs(config.propagation_seconds);
+    let provider: Arc<dyn DnsProvider> = match &config.provider {
+        DnsProviderConfig::Cloudflare { api_token, zone_id } => Arc::new(CloudflareDns::new(
+            api_token.clone(),
+            zone_id.clone(),
+            delay,
+        )),
+        DnsProviderConfig::Route53 {
+            access_key_id,
+            secret_access_key,
+            hosted_zone_id,
+            session_token,
+        } => Arc::new(Route53Dns::new(
+            Route53Credentials {
+                access_key_id: access_key_id.clone(),
+                secret_access_key: secret_access_key.clone(),
+                session_token: session_token.clone(),
+            },
+            hosted_zone_id,
+            delay,
+        )),
+        DnsProviderConfig::Rfc2136 {
+            server,
+            zone,
+            key_name,
+            key_secret,
+        } => Arc::new(Rfc2136Dns::new(
+            server.clone(),
+            zone.clone(),
+            TsigKey {
+                name: key_name.clone(),
+                secret: STANDARD
+                    .decode(key_secret.trim())
+                    .context("TSIG secret is not valid base64")?,
+            },
+            delay,
+        )),
+    };
+    Ok(provider)
+}
+
+/// Request a certificate for `domain`, publishing the challenge through the
+/// DNS provider configured for it or else through the TLS forward relay
+pub async fn request_certificate(
+    cert_mgr: &CertificateManager,
+    domain: &str,
+    email: &str,
+    dns: &[DnsChallengeConfig],
+) -> Result<()> {
+    match dns.iter().find(|c| c.domains.iter().any(|d| d == domain)) {
+        Some(config) => {
+            let provider = dns_provider(config)?;
+            cert_mgr
+                .request_certificate_with(domain, email, provider.as_ref())
+                .await
+        }
+        None => cert_mgr.request_certificate(domain, email).await,
+    }
+}
+
 /// Manages TLS certificates and acceptors
 #[derive(Clone)]
 pub struct TlsManager {
@@ -41,6 +110,32 @@ impl TlsManager {
         })
     }
 
+    /// Create a TLS manager serving user-supplied certificate files, which
+    /// are validated first
+    pub async fn from_files(data_dir: &Path, files: &CertificateFiles) -> Result<Self> {
+        let loaded = load_certificate(files).await?;
+        info!(
+            "Serving TLS certificate from {} for {:?}, valid until {}",
+            files.cert.display(),
+            loaded.info.domains,
+            loaded.info.not_after
+        );
+        Ok(Self {
+            certificate_manager: Arc::new(Mutex::new(CertificateManager::new(
+                data_dir.to_path_buf(),
+            ))),
+            reloadable_acceptor: Arc::new(ReloadableTlsAcceptor::new(loaded.acceptor)),
+        })
+    }
+
+    /// Reload user-supplied certificate files now, keeping the current
+    /// certificate if they fail to validate
+    pub async fn reload_files(&self, files: &CertificateFiles) -> Result<CertificateInfo> {
+        let loaded = load_certificate(files).await?;
+        self.reloadable_acceptor.reload(loaded.acceptor).await;
+        Ok(loaded.info)
+    }
+
     /// Get the reloadable TLS acceptor
     pub fn acceptor(&self) -> Arc<ReloadableTlsAcceptor> {
         self.reloadable_acceptor.clone()
@@ -60,8 +155,15 @@ impl TlsManager {
         debug!("Certificate manager configured with TLS forward client");
     }
 
-    /// Request Let's Encrypt certificates
-    pub async fn request_certificates(&self, domains: Vec<String>, email: &str) -> Result<()> {
+    /// Request Let's Encrypt certificates. Challenges for a domain listed
+    /// in `dns` are published through that provider, others through the
+    /// TLS forward relay.
+    pub async fn request_certificates(
+        &self,
+        domains: Vec<String>,
+        email: &str,
+        dns: &[DnsChallengeConfig],
+    ) -> Result<()> {
         if domains.is_empty() {
             return Ok(());
         }
@@ -73,7 +175,7 @@ impl TlsManager {
             let cert_mgr = self.certificate_manager.lock().await;
             if !cert_mgr.has_certificate(domain).await {
                 info!("Requesting new certificate for https://{}", domain);
-                match cert_mgr.request_certificate(domain, email).await {
+                match request_certificate(&cert_mgr, domain, email, dns).await {
                     Ok(()) => {
                         info!("Successfully obtained certificate for {}", domain);
                         // Reload TLS acceptor with new certificates
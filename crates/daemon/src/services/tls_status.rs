@@ -0,0 +1,212 @@
+//! Certificate status and renewal
+//!
+//! Reports the certificate served for each domain - issuer, names and
+//! validity - with the outcome of its last renewal attempt, so expiry shows
+//! up on the network page rather than as browser errors. Renewal can be
+//! forced: certificates obtained through ACME are requested again, and
+//! user-supplied ones are reloaded from their files.
+
+use crate::Settings;
+use crate::config::CertificateSource;
+use crate::services::certificates::{CertificateFiles, read_certificate};
+use chrono::{DateTime, Utc};
+use gate_tlsforward::client::certificate_dir_name;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Name user-supplied certificates are reported and logged under
+pub const MANUAL_CERTIFICATE: &str = "manual";
+
+/// Outcome of an attempt to renew or reload a certificate
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RenewalAttempt {
+    pub at: DateTime<Utc>,
+    pub succeeded: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Last renewal attempt per certificate, kept in memory
+#[derive(Clone, Default)]
+pub struct RenewalLog {
+    attempts: Arc<RwLock<HashMap<String, RenewalAttempt>>>,
+}
+
+impl RenewalLog {
+    pub async fn record<T, E: Display>(&self, name: &str, result: &std::result::Result<T, E>) {
+        let attempt = RenewalAttempt {
+            at: Utc::now(),
+            succeeded: result.is_ok(),
+            error: result.as_ref().err().map(ToString::to_string),
+        };
+        self.attempts
+            .write()
+            .await
+            .insert(name.to_string(), attempt);
+    }
+
+    pub async fn last(&self, name: &str) -> Option<RenewalAttempt> {
+        self.attempts.read().await.get(name).cloned()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CertificateHealth {
+    Valid,
+    /// Within the configured warning period of its expiry
+    Expiring,
+    Expired,
+    /// Not issued yet
+    Missing,
+    /// Present but unreadable
+    Invalid,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CertificateStatus {
+    /// Domain the certificate was requested for, or `manual` for a
+    /// user-supplied certificate
+    pub name: String,
+    pub health: CertificateHealth,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issuer: Option<String>,
+    /// DNS names the certificate covers
+    #[serde(default)]
+    pub sans: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub not_before: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub not_after: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub days_left: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_renewal: Option<RenewalAttempt>,
+}
+
+/// Where the certificates come from, as reported
+pub fn source_name(source: &CertificateSource) -> &'static str {
+    match source {
+        CertificateSource::Acme => "acme",
+        CertificateSource::Files { .. } => "files",
+        CertificateSource::Directory { .. } => "directory",
+    }
+}
+
+/// Status of every certificate `settings` serves, reading ACME certificates
+/// from `cert_dir`
+pub async fn certificate_status(
+    settings: &Settings,
+    cert_dir: &Path,
+    renewals: &RenewalLog,
+    now: DateTime<Utc>,
+) -> Vec<CertificateStatus> {
+    let targets: Vec<(String, std::result::Result<PathBuf, String>)> =
+        match CertificateFiles::resolve(&settings.certificates.source) {
+            Ok(Some(files)) => vec![(MANUAL_CERTIFICATE.to_string(), Ok(files.cert))],
+            Ok(None) => settings
+                .letsencrypt
+                .domains
+                .iter()
+                .map(|domain| {
+                    let path = cert_dir
+                        .join(certificate_dir_name(domain))
+                        .join("fullchain.pem");
+                    (domain.clone(), Ok(path))
+                })
+                .collect(),
+            Err(e) => vec![(MANUAL_CERTIFICATE.to_string(), Err(e.to_string()))],
+        };
+
+    let warning_days = settings.certificates.expiry_warning_days as i64;
+    let mut statuses = Vec::with_capacity(targets.len());
+    for (name, path) in targets {
+        let mut status = CertificateStatus {
+            last_renewal: renewals.last(&name).await,
+            name,
+            health: CertificateHealth::Missing,
+            issuer: None,
+            sans: Vec::new(),
+            not_before: None,
+            not_after: None,
+            days_left: None,
+            error: None,
+        };
+        match path {
+            Err(e) => {
+                status.health = CertificateHealth::Invalid;
+                status.error = Some(e);
+            }
+            Ok(path) if !path.exists() => {}
+            Ok(path) => match read_certificate(&path).await {
+                Ok(info) => {
+                    let days_left = info.days_left(now);
+                    status.health = if info.not_after <= now {
+                        CertificateHealth::Expired
+                    } else if days_left <= warning_days {
+                        CertificateHealth::Expiring
+                    } else {
+                        CertificateHealth::Valid
+                    };
+                    status.issuer = Some(info.issuer);
+                    status.sans = info.domains;
+                    status.not_before = Some(info.not_before);
+                    status.not_after = Some(info.not_after);
+                    status.days_left = Some(days_left);
+                }
+                Err(e) => {
+                    status.health = CertificateHealth::Invalid;
+                    status.error = Some(e.to_string());
+                }
+            },
+        }
+        statuses.push(status);
+    }
+    statuses
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rcgen::generate_simple_self_signed;
+
+    #[tokio::test]
+    async fn test_status_reports_issued_and_missing_domains() {
+        let dir = tempfile::tempdir().unwrap();
+        let issued = dir.path().join("gate.example.com");
+        std::fs::create_dir_all(&issued).unwrap();
+        let key = generate_simple_self_signed(vec!["gate.example.com".to_string()]).unwrap();
+        std::fs::write(issued.join("fullchain.pem"), key.cert.pem()).unwrap();
+
+        let mut settings = Settings::default();
+        settings.letsencrypt.domains =
+            vec!["gate.example.com".to_string(), "*.example.com".to_string()];
+        let renewals = RenewalLog::default();
+        renewals
+            .record::<(), _>("*.example.com", &Err("rate limited"))
+            .await;
+
+        let statuses = certificate_status(&settings, dir.path(), &renewals, Utc::now()).await;
+        assert_eq!(statuses.len(), 2);
+        assert_eq!(statuses[0].health, CertificateHealth::Valid);
+        assert_eq!(statuses[0].sans, vec!["gate.example.com"]);
+        assert!(statuses[0].days_left.unwrap() > 30);
+        assert!(statuses[0].last_renewal.is_none());
+
+        assert_eq!(statuses[1].health, CertificateHealth::Missing);
+        let attempt = statuses[1].last_renewal.as_ref().unwrap();
+        assert!(!attempt.succeeded);
+        assert_eq!(attempt.error.as_deref(), Some("rate limited"));
+
+        // Past the warning period's start, the same certificate is expiring
+        let later = statuses[0].not_after.unwrap() - chrono::Duration::days(3);
+        let statuses = certificate_status(&settings, dir.path(), &renewals, later).await;
+        assert_eq!(statuses[0].health, CertificateHealth::Expiring);
+    }
+}
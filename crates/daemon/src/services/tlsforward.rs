@@ -1,6 +1,7 @@
 //! TLS forward service for managing P2P TLS forwarding connections
 
 use crate::config::TlsForwardConfig;
+use crate::services::relay_diagnostics::{LinkEventKind, RelayDiagnostics};
 use crate::tracing::Instrument;
 use anyhow::{Context, Result};
 use gate_p2p::Endpoint;
@@ -68,6 +69,7 @@ impl TlsForwardServiceBuilder {
             state_tx,
             state_rx,
             shutdown_tx,
+            diagnostics: RelayDiagnostics::default(),
         });
 
         // Start connection loop
@@ -88,6 +90,16 @@ pub struct TlsForwardService {
     state_tx: watch::Sender<TlsForwardState>,
     state_rx: watch::Receiver<TlsForwardState>,
     shutdown_tx: watch::Sender<bool>,
+    diagnostics: RelayDiagnostics,
+}
+
+/// Node ID of a TLS forward server address, `node_id` or
+/// `node_id@host:port`; addresses are found by discovery
+fn parse_node_id(address: &str) -> Result<NodeId> {
+    let node_id = address.split_once('@').map_or(address, |(id, _)| id);
+    node_id
+        .parse::<NodeId>()
+        .map_err(|e| anyhow::anyhow!("Invalid node ID '{}': {}", node_id, e))
 }
 
 impl TlsForwardService {
@@ -111,6 +123,40 @@ impl TlsForwardService {
         &self.endpoint
     }
 
+    /// Round trip times and connection history of the relay link
+    pub fn diagnostics(&self) -> RelayDiagnostics {
+        self.diagnostics.clone()
+    }
+
+    /// Ping every configured TLS forward server, recording the round trip
+    /// times in the diagnostics
+    pub async fn probe_servers(&self) {
+        let probes = self
+            .config
+            .tlsforward_addresses
+            .iter()
+            .map(|address| async move {
+                let node_id = match parse_node_id(address) {
+                    Ok(node_id) => node_id,
+                    Err(e) => {
+                        self.diagnostics.record_failure(address, e);
+                        return;
+                    }
+                };
+                let client = TlsForwardClient::new(self.endpoint.clone(), node_id);
+                match tokio::time::timeout(Duration::from_secs(10), client.ping()).await {
+                    Ok(Ok(rtt)) => self.diagnostics.record_rtt(&node_id.to_string(), rtt),
+                    Ok(Err(e)) => self
+                        .diagnostics
+                        .record_failure(&node_id.to_string(), format!("{e:#}")),
+                    Err(_) => self
+                        .diagnostics
+                        .record_failure(&node_id.to_string(), "Ping timed out"),
+                }
+            });
+        futures::future::join_all(probes).await;
+    }
+
     /// Get the connected relay node ID
     pub async fn tlsforward_node_id(&self) -> Option<iroh::NodeId> {
         let connection = self.active_connection.read().await;
@@ -140,9 +186,7 @@ impl TlsForwardService {
                     }
 
                     // Wait before reconnecting
-                    let backoff = Duration::from_secs(
-                        self.config.reconnect_backoff * (reconnect_attempts as u64 + 1)
-                    );
+                    let backoff = self.config.reconnect_delay(reconnect_attempts);
                     info!("Waiting {}s before reconnecting (attempt {})", backoff.as_secs(), reconnect_attempts + 1);
                     time::sleep(backoff).await;
                 }
@@ -163,20 +207,7 @@ impl TlsForwardService {
         for tlsforward_addr_str in &self.config.tlsforward_addresses {
             debug!("Trying TLS forward address: {}", tlsforward_addr_str);
 
-            // Parse node ID from address string
-            // We now only care about the node ID part, as addresses are handled by discovery
-            let node_id =
-                if let Some((node_id_str, _addr_str)) = tlsforward_addr_str.split_once('@') {
-                    // Format: node_id@address:port - extract just the node ID
-                    node_id_str
-                        .parse::<iroh::NodeId>()
-                        .map_err(|e| anyhow::anyhow!("Invalid node ID '{}': {}", node_id_str, e))?
-                } else {
-                    // Just a node ID
-                    tlsforward_addr_str.parse::<iroh::NodeId>().map_err(|e| {
-                        anyhow::anyhow!("Invalid node ID '{}': {}", tlsforward_addr_str, e)
-                    })?
-                };
+            let node_id = parse_node_id(tlsforward_addr_str)?;
 
             debug!("Connecting to TLS forward server with node ID: {}", node_id);
 
@@ -190,6 +221,11 @@ impl TlsForwardService {
                         tlsforward_node: node_id,
                         assigned_domain: assigned_domain.clone(),
                     })?;
+                    self.diagnostics.record_event(
+                        LinkEventKind::Connected,
+                        Some(&node_id.to_string()),
+                        Some(assigned_domain.clone()),
+                    );
 
                     *reconnect_attempts = 0;
 
@@ -200,6 +236,13 @@ impl TlsForwardService {
                 }
                 Err(e) => {
                     error!("Failed to connect to TLS forward server {}: {}", node_id, e);
+                    let server = node_id.to_string();
+                    self.diagnostics.record_failure(&server, format!("{e:#}"));
+                    self.diagnostics.record_event(
+                        LinkEventKind::Failed,
+                        Some(&server),
+                        Some(format!("{e:#}")),
+                    );
                     continue;
                 }
             }
@@ -268,6 +311,15 @@ impl TlsForwardService {
                 _ = interval.tick() => {
                     if let Err(e) = self.send_heartbeat().await {
                         error!("Heartbeat failed: {}", e);
+                        let server = self.tlsforward_node_id().await.map(|id| id.to_string());
+                        if let Some(server) = &server {
+                            self.diagnostics.record_failure(server, format!("{e:#}"));
+                        }
+                        self.diagnostics.record_event(
+                            LinkEventKind::Disconnected,
+                            server.as_deref(),
+                            Some(format!("{e:#}")),
+                        );
                         self.state_tx.send(TlsForwardState::Disconnected).ok();
                         break;
                     }
@@ -284,10 +336,12 @@ impl TlsForwardService {
             .context("No active TLS forward connection")?;
 
         // Send ping with timeout
-        tokio::time::timeout(Duration::from_secs(10), active.tls_forward_client.ping())
+        let rtt = tokio::time::timeout(Duration::from_secs(10), active.tls_forward_client.ping())
             .await
             .map_err(|_| anyhow::anyhow!("Heartbeat timeout"))?
             .context("Heartbeat ping failed")?;
+        self.diagnostics
+            .record_rtt(&active.node_id.to_string(), rtt);
 
         debug!("Heartbeat sent successfully");
         Ok(())
@@ -298,10 +352,15 @@ impl TlsForwardService {
         info!("Disconnecting from TLS forward server");
 
         // Unregister from TLS forward server
-        if let Some(active) = self.active_connection.write().await.take()
-            && let Err(e) = active.tls_forward_client.unregister().await
-        {
-            warn!("Failed to unregister from TLS forward server: {}", e);
+        if let Some(active) = self.active_connection.write().await.take() {
+            if let Err(e) = active.tls_forward_client.unregister().await {
+                warn!("Failed to unregister from TLS forward server: {}", e);
+            }
+            self.diagnostics.record_event(
+                LinkEventKind::Disconnected,
+                Some(&active.node_id.to_string()),
+                None,
+            );
         }
 
         // Update state
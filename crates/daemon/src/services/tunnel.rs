@@ -0,0 +1,604 @@
+//! Tunnels exposing the node without the relay
+//!
+//! Keeps a reverse SSH tunnel, `cloudflared`, or any other TCP forwarder
+//! running as a child process that forwards to the daemon's listen address.
+//! The process is restarted with the same backoff and attempt limits as the
+//! relay connection, and its state is reported as the TLS forward status.
+
+use crate::config::{TlsForwardConfig, TunnelTransport};
+use crate::services::relay_diagnostics::{LinkEventKind, RelayDiagnostics};
+use crate::types::{TlsForwardStatus, TransportKind};
+use anyhow::{Context, Result};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::Command;
+use tokio::sync::watch;
+use tokio::time;
+
+/// How long a process without a readiness endpoint must stay up before the
+/// tunnel counts as connected. `ssh` exits within this when the forward is
+/// refused.
+const CONNECT_GRACE: Duration = Duration::from_secs(5);
+
+/// How often the readiness endpoint is polled while connecting
+const READY_POLL: Duration = Duration::from_secs(1);
+
+/// Hostname reported for a quick tunnel whose URL was not seen
+const QUICK_TUNNEL_DOMAIN: &str = "trycloudflare.com";
+
+/// Process keeping a tunnel open
+pub struct TunnelCommand {
+    pub program: String,
+    pub args: Vec<String>,
+    /// Passed through the environment so secrets stay out of the process
+    /// list and logs
+    pub env: Vec<(String, String)>,
+    /// Answers 2xx while the tunnel is up; without one the tunnel counts as
+    /// up once the process has stayed running for a few seconds
+    pub ready_url: Option<String>,
+}
+
+/// `host`, bracketed when it is an IPv6 address
+fn url_host(host: &str) -> String {
+    if host.contains(':') {
+        format!("[{host}]")
+    } else {
+        host.to_string()
+    }
+}
+
+/// Process keeping `transport` open to `local_host:local_port`, or `None`
+/// for the relay
+pub fn tunnel_command(
+    transport: &TunnelTransport,
+    heartbeat_interval: u64,
+    local_host: &str,
+    local_port: u16,
+) -> Option<TunnelCommand> {
+    match transport {
+        TunnelTransport::Relay => None,
+        TunnelTransport::Ssh {
+            host,
+            port,
+            user,
+            key_path,
+            known_hosts_path,
+            remote_port,
+            remote_bind,
+            ..
+        } => {
+            let mut args: Vec<String> = vec![
+                "-N".into(),
+                "-T".into(),
+                "-o".into(),
+                "ExitOnForwardFailure=yes".into(),
+                "-o".into(),
+                "BatchMode=yes".into(),
+                "-o".into(),
+                format!("ServerAliveInterval={heartbeat_interval}"),
+                "-o".into(),
+                "ServerAliveCountMax=3".into(),
+            ];
+            if let Some(key) = key_path {
+                args.extend([
+                    "-i".into(),
+                    key.display().to_string(),
+                    "-o".into(),
+                    "IdentitiesOnly=yes".into(),
+                ]);
+            }
+            if let Some(known_hosts) = known_hosts_path {
+                args.extend([
+                    "-o".into(),
+                    format!("UserKnownHostsFile={}", known_hosts.display()),
+                    "-o".into(),
+                    "StrictHostKeyChecking=yes".into(),
+                ]);
+            }
+            args.extend([
+                "-p".into(),
+                port.to_string(),
+                "-R".into(),
+                format!(
+                    "{remote_bind}:{remote_port}:{}:{local_port}",
+                    url_host(local_host)
+                ),
+                format!("{user}@{host}"),
+            ]);
+            Some(TunnelCommand {
+                program: "ssh".to_string(),
+                args,
+                env: Vec::new(),
+                ready_url: None,
+            })
+        }
+        TunnelTransport::Cloudflare {
+            token,
+            cloudflared_path,
+            metrics_address,
+            ..
+        } => {
+            let mut args: Vec<String> = vec![
+                "tunnel".into(),
+                "--no-autoupdate".into(),
+                "--metrics".into(),
+                metrics_address.clone(),
+            ];
+            let mut env = Vec::new();
+            match token {
+                Some(token) => {
+                    args.push("run".into());
+                    env.push(("TUNNEL_TOKEN".to_string(), token.clone()));
+                }
+                None => args.extend([
+                    "--url".into(),
+                    format!("http://{}:{local_port}", url_host(local_host)),
+                ]),
+            }
+            Some(TunnelCommand {
+                program: cloudflared_path.display().to_string(),
+                args,
+                env,
+                ready_url: Some(format!("http://{metrics_address}/ready")),
+            })
+        }
+        TunnelTransport::Command { command, args, .. } => {
+            let args = args
+                .iter()
+                .map(|arg| {
+                    arg.replace("{local_host}", local_host)
+                        .replace("{local_port}", &local_port.to_string())
+                })
+                .collect();
+            Some(TunnelCommand {
+                program: command.clone(),
+                args,
+                env: Vec::new(),
+                ready_url: None,
+            })
+        }
+    }
+}
+
+/// Transport kind reported for `transport`
+pub fn transport_kind(transport: &TunnelTransport) -> TransportKind {
+    match transport {
+        TunnelTransport::Relay => TransportKind::Relay,
+        TunnelTransport::Ssh { .. } => TransportKind::Ssh,
+        TunnelTransport::Cloudflare { .. } => TransportKind::Cloudflare,
+        TunnelTransport::Command { .. } => TransportKind::Command,
+    }
+}
+
+/// Configured name the node is reached at through `transport`
+fn public_domain(transport: &TunnelTransport) -> Option<String> {
+    match transport {
+        TunnelTransport::Relay => None,
+        TunnelTransport::Ssh {
+            host,
+            remote_port,
+            domain,
+            ..
+        } => Some(
+            domain
+                .clone()
+                .unwrap_or_else(|| format!("{host}:{remote_port}")),
+        ),
+        TunnelTransport::Cloudflare { hostname, .. } => hostname.clone(),
+        TunnelTransport::Command { domain, .. } => Some(domain.clone()),
+    }
+}
+
+/// Hostname of a quick tunnel, from the banner `cloudflared` prints
+fn quick_tunnel_hostname(line: &str) -> Option<String> {
+    line.split(|c: char| c.is_whitespace() || c == '|')
+        .filter_map(|word| word.strip_prefix("https://"))
+        .find(|host| host.ends_with(".trycloudflare.com"))
+        .map(String::from)
+}
+
+/// Keeps a tunnel process running for the configured transport
+pub struct TunnelService {
+    config: TlsForwardConfig,
+    command: TunnelCommand,
+    http: reqwest::Client,
+    state_tx: watch::Sender<TlsForwardStatus>,
+    shutdown_tx: watch::Sender<bool>,
+    diagnostics: RelayDiagnostics,
+}
+
+impl TunnelService {
+    /// Start keeping the tunnel open to the daemon listening on
+    /// `listen_host:listen_port`
+    pub fn start(
+        config: TlsForwardConfig,
+        listen_host: &str,
+        listen_port: u16,
+    ) -> Result<Arc<Self>> {
+        if let TunnelTransport::Cloudflare {
+            token: Some(_),
+            hostname: None,
+            ..
+        } = &config.transport
+        {
+            anyhow::bail!(
+                "Set tlsforward.transport.hostname to the public hostname routed to the Cloudflare tunnel"
+            );
+        }
+
+        // A wildcard bind is reached through loopback
+        let local_host = match listen_host {
+            "0.0.0.0" => "127.0.0.1",
+            "::" => "::1",
+            host => host,
+        };
+        let command = tunnel_command(
+            &config.transport,
+            config.heartbeat_interval,
+            local_host,
+            listen_port,
+        )
+        .context("The relay transport is not run as a tunnel")?;
+
+        let (state_tx, _) = watch::channel(TlsForwardStatus::Disconnected);
+        let (shutdown_tx, _) = watch::channel(false);
+        let service = Arc::new(Self {
+            config,
+            command,
+            http: reqwest::Client::new(),
+            state_tx,
+            shutdown_tx,
+            diagnostics: RelayDiagnostics::default(),
+        });
+
+        let service_clone = service.clone();
+        tokio::spawn(async move {
+            service_clone.run_loop().await;
+        });
+
+        Ok(service)
+    }
+
+    /// Current tunnel state
+    pub fn status(&self) -> TlsForwardStatus {
+        self.state_tx.borrow().clone()
+    }
+
+    /// Subscribe to state changes
+    pub fn subscribe(&self) -> watch::Receiver<TlsForwardStatus> {
+        self.state_tx.subscribe()
+    }
+
+    /// Connection history of the tunnel
+    pub fn diagnostics(&self) -> RelayDiagnostics {
+        self.diagnostics.clone()
+    }
+
+    /// Stop the tunnel process and stop restarting it
+    pub fn shutdown(&self) {
+        info!("Shutting down {} tunnel", self.command.program);
+        self.shutdown_tx.send_replace(true);
+        self.transition(TlsForwardStatus::Disconnected);
+    }
+
+    async fn run_loop(&self) {
+        let mut shutdown_rx = self.shutdown_tx.subscribe();
+        let mut attempts = 0;
+
+        loop {
+            tokio::select! {
+                _ = shutdown_rx.changed() => {
+                    if *shutdown_rx.borrow() {
+                        break;
+                    }
+                }
+                result = self.run_once(&mut attempts) => {
+                    if let Err(e) = result {
+                        error!("Tunnel failed: {:#}", e);
+                    }
+
+                    if !self.config.auto_reconnect || attempts >= self.config.max_reconnect_attempts {
+                        break;
+                    }
+
+                    let backoff = self.config.reconnect_delay(attempts);
+                    info!("Waiting {}s before restarting the tunnel (attempt {})", backoff.as_secs(), attempts + 1);
+                    time::sleep(backoff).await;
+                }
+            }
+        }
+    }
+
+    /// Whether the readiness endpoint answers successfully
+    async fn is_ready(&self, url: &str) -> bool {
+        match self
+            .http
+            .get(url)
+            .timeout(Duration::from_secs(5))
+            .send()
+            .await
+        {
+            Ok(response) => response.status().is_success(),
+            Err(_) => false,
+        }
+    }
+
+    /// Resolve once the tunnel is up
+    async fn until_up(&self) {
+        let Some(url) = &self.command.ready_url else {
+            time::sleep(CONNECT_GRACE).await;
+            return;
+        };
+        while !self.is_ready(url).await {
+            time::sleep(READY_POLL).await;
+        }
+    }
+
+    fn connected(&self, discovered: &Mutex<Option<String>>) -> TlsForwardStatus {
+        let domain = public_domain(&self.config.transport)
+            .or_else(|| discovered.lock().unwrap().clone())
+            .unwrap_or_else(|| QUICK_TUNNEL_DOMAIN.to_string());
+        TlsForwardStatus::Connected {
+            domain,
+            transport: transport_kind(&self.config.transport),
+        }
+    }
+
+    /// Move to `status`, recording the change in the link history
+    fn transition(&self, status: TlsForwardStatus) {
+        let was_connected = matches!(*self.state_tx.borrow(), TlsForwardStatus::Connected { .. });
+        let event = match &status {
+            TlsForwardStatus::Connected { domain, .. } => {
+                Some((LinkEventKind::Connected, Some(domain.clone())))
+            }
+            TlsForwardStatus::Error(e) if was_connected => {
+                Some((LinkEventKind::Disconnected, Some(e.clone())))
+            }
+            TlsForwardStatus::Error(e) => Some((LinkEventKind::Failed, Some(e.clone()))),
+            TlsForwardStatus::Disconnected if was_connected => {
+                Some((LinkEventKind::Disconnected, None))
+            }
+            _ => None,
+        };
+        if let Some((kind, detail)) = event {
+            self.diagnostics
+                .record_event(kind, Some(&self.command.program), detail);
+        }
+        self.state_tx.send_replace(status);
+    }
+
+    /// Poll the readiness endpoint, reporting the tunnel down while the
+    /// process runs without a connection
+    async fn check_health(&self, url: &str, discovered: &Mutex<Option<String>>) {
+        let ready = self.is_ready(url).await;
+        let was_connected = matches!(*self.state_tx.borrow(), TlsForwardStatus::Connected { .. });
+        if ready && !was_connected {
+            info!("{} reconnected", self.command.program);
+            self.transition(self.connected(discovered));
+        } else if !ready && was_connected {
+            warn!("{} reports no connections", self.command.program);
+            self.transition(TlsForwardStatus::Error(format!(
+                "{} is running but not connected",
+                self.command.program
+            )));
+        }
+    }
+
+    /// Run the tunnel process until it exits
+    async fn run_once(&self, attempts: &mut u32) -> Result<()> {
+        let program = &self.command.program;
+        self.transition(TlsForwardStatus::Connecting);
+        debug!(
+            "Starting tunnel: {} {}",
+            program,
+            self.command.args.join(" ")
+        );
+
+        // Dropped with this future on shutdown, which kills the process
+        let spawned = Command::new(program)
+            .args(&self.command.args)
+            .envs(self.command.env.iter().map(|(k, v)| (k, v)))
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn();
+        let mut child = match spawned {
+            Ok(child) => child,
+            Err(e) => {
+                *attempts += 1;
+                let error = format!("Failed to start {program}: {e}");
+                self.transition(TlsForwardStatus::Error(error.clone()));
+                anyhow::bail!(error);
+            }
+        };
+
+        // Keep the last line the process printed to explain why it exited,
+        // and the hostname a quick tunnel was given
+        let last_line = Arc::new(Mutex::new(None::<String>));
+        let discovered = Arc::new(Mutex::new(None::<String>));
+        if let Some(stderr) = child.stderr.take() {
+            let last_line = last_line.clone();
+            let discovered = discovered.clone();
+            tokio::spawn(async move {
+                let mut lines = BufReader::new(stderr).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    debug!("tunnel: {}", line);
+                    if let Some(hostname) = quick_tunnel_hostname(&line) {
+                        *discovered.lock().unwrap() = Some(hostname);
+                    }
+                    *last_line.lock().unwrap() = Some(line);
+                }
+            });
+        }
+
+        let exited = tokio::select! {
+            status = child.wait() => Some(status?),
+            _ = self.until_up() => None,
+        };
+        let status = match exited {
+            Some(status) => status,
+            None => {
+                let connected = self.connected(&discovered);
+                if let TlsForwardStatus::Connected { domain, .. } = &connected {
+                    info!("Tunnel up through {}, serving {}", program, domain);
+                }
+                self.transition(connected);
+                *attempts = 0;
+
+                let mut health =
+                    time::interval(Duration::from_secs(self.config.heartbeat_interval.max(1)));
+                health.tick().await;
+                loop {
+                    tokio::select! {
+                        status = child.wait() => break status?,
+                        _ = health.tick() => {
+                            if let Some(url) = &self.command.ready_url {
+                                self.check_health(url, &discovered).await;
+                            }
+                        }
+                    }
+                }
+            }
+        };
+
+        *attempts += 1;
+        let reason = last_line
+            .lock()
+            .unwrap()
+            .take()
+            .unwrap_or_else(|| status.to_string());
+        let error = format!("{program} exited: {reason}");
+        self.transition(TlsForwardStatus::Error(error.clone()));
+        if *attempts >= self.config.max_reconnect_attempts {
+            error!("Maximum reconnection attempts reached");
+        }
+        Err(anyhow::anyhow!(error))
+    }
+}
+
+impl Drop for TunnelService {
+    fn drop(&mut self) {
+        self.shutdown_tx.send(true).ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_tunnel_commands() {
+        let ssh = TunnelTransport::Ssh {
+            host: "tunnel.example.com".to_string(),
+            port: 2222,
+            user: "gate".to_string(),
+            key_path: Some(PathBuf::from("/keys/id_ed25519")),
+            known_hosts_path: None,
+            remote_port: 8443,
+            remote_bind: "0.0.0.0".to_string(),
+            domain: None,
+        };
+        let command = tunnel_command(&ssh, 30, "127.0.0.1", 31145).unwrap();
+        let args = &command.args;
+        assert_eq!(command.program, "ssh");
+        assert!(args.contains(&"ExitOnForwardFailure=yes".to_string()));
+        assert!(args.contains(&"ServerAliveInterval=30".to_string()));
+        assert!(args.windows(2).any(|w| w == ["-i", "/keys/id_ed25519"]));
+        assert!(args.windows(2).any(|w| w == ["-p", "2222"]));
+        assert!(
+            args.windows(2)
+                .any(|w| w == ["-R", "0.0.0.0:8443:127.0.0.1:31145"])
+        );
+        assert_eq!(args.last().unwrap(), "gate@tunnel.example.com");
+        assert!(command.ready_url.is_none());
+        assert_eq!(
+            public_domain(&ssh).as_deref(),
+            Some("tunnel.example.com:8443")
+        );
+        assert_eq!(transport_kind(&ssh), TransportKind::Ssh);
+
+        let bore = TunnelTransport::Command {
+            command: "bore".to_string(),
+            args: vec![
+                "local".to_string(),
+                "{local_port}".to_string(),
+                "--local-host".to_string(),
+                "{local_host}".to_string(),
+                "--to".to_string(),
+                "bore.pub".to_string(),
+            ],
+            domain: "bore.pub:4242".to_string(),
+        };
+        let command = tunnel_command(&bore, 30, "::1", 31145).unwrap();
+        assert_eq!(command.program, "bore");
+        assert_eq!(
+            command.args,
+            ["local", "31145", "--local-host", "::1", "--to", "bore.pub"]
+        );
+
+        assert!(tunnel_command(&TunnelTransport::Relay, 30, "127.0.0.1", 31145).is_none());
+    }
+
+    #[test]
+    fn test_cloudflare_tunnel() {
+        let named = TunnelTransport::Cloudflare {
+            token: Some("eyJhIjoi".to_string()),
+            hostname: Some("gate.example.com".to_string()),
+            cloudflared_path: PathBuf::from("cloudflared"),
+            metrics_address: "127.0.0.1:20241".to_string(),
+        };
+        let command = tunnel_command(&named, 30, "127.0.0.1", 31145).unwrap();
+        assert_eq!(
+            command.args,
+            [
+                "tunnel",
+                "--no-autoupdate",
+                "--metrics",
+                "127.0.0.1:20241",
+                "run"
+            ]
+        );
+        // The token never appears on the command line
+        assert_eq!(
+            command.env,
+            [("TUNNEL_TOKEN".to_string(), "eyJhIjoi".to_string())]
+        );
+        assert_eq!(
+            command.ready_url.as_deref(),
+            Some("http://127.0.0.1:20241/ready")
+        );
+        assert_eq!(public_domain(&named).as_deref(), Some("gate.example.com"));
+
+        let quick = TunnelTransport::Cloudflare {
+            token: None,
+            hostname: None,
+            cloudflared_path: PathBuf::from("/usr/local/bin/cloudflared"),
+            metrics_address: "127.0.0.1:20241".to_string(),
+        };
+        let command = tunnel_command(&quick, 30, "::1", 31145).unwrap();
+        assert_eq!(command.program, "/usr/local/bin/cloudflared");
+        assert!(
+            command
+                .args
+                .windows(2)
+                .any(|w| w == ["--url", "http://[::1]:31145"])
+        );
+        assert!(command.env.is_empty());
+
+        assert_eq!(
+            quick_tunnel_hostname(
+                "2026-10-17T10:00:00Z INF |  https://calm-river-1234.trycloudflare.com  |"
+            )
+            .as_deref(),
+            Some("calm-river-1234.trycloudflare.com")
+        );
+        assert!(
+            quick_tunnel_hostname("INF Requesting new quick Tunnel on trycloudflare.com...")
+                .is_none()
+        );
+    }
+}
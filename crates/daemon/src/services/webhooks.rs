@@ -0,0 +1,124 @@
+//! Webhook delivery
+//!
+//! Events are posted to webhooks from [`JobKind::Webhook`] journal entries
+//! whose payload names the `url` and the JSON `event` to post, so events
+//! raised before a crash are still delivered after it. Failed deliveries are
+//! retried until the entry runs out of attempts and is dead-lettered.
+
+use crate::error::Result;
+use crate::services::journal::{JobKind, Journal, JournalEntry};
+use crate::services::signing::MessageSigner;
+use gate_http::sinks::signing::RequestSigner;
+use serde_json::{Value as JsonValue, json};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Delivery attempts before an event is dead-lettered
+const MAX_ATTEMPTS: u32 = 5;
+
+/// Time allowed for a webhook to answer
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Wait between polls of an empty queue, and after a failed delivery
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Queue an event for delivery to a webhook
+pub async fn enqueue_webhook(
+    journal: &Journal,
+    url: &str,
+    event: JsonValue,
+) -> Result<JournalEntry> {
+    journal
+        .enqueue(
+            JobKind::Webhook,
+            json!({ "url": url, "event": event }),
+            MAX_ATTEMPTS,
+        )
+        .await
+}
+
+/// Posts queued webhook events
+pub struct WebhookDispatcher {
+    journal: Journal,
+    client: reqwest::Client,
+    signer: Option<Arc<MessageSigner>>,
+}
+
+impl WebhookDispatcher {
+    pub fn new(journal: Journal) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        Self {
+            journal,
+            client,
+            signer: None,
+        }
+    }
+
+    /// Sign deliveries so receivers can tell they came from this node
+    pub fn with_signer(mut self, signer: Arc<MessageSigner>) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    /// Deliver queued events until the daemon stops
+    pub async fn run(self) {
+        loop {
+            match self.journal.claim(JobKind::Webhook).await {
+                Ok(Some(entry)) => {
+                    if !self.deliver(&entry).await {
+                        tokio::time::sleep(POLL_INTERVAL).await;
+                    }
+                }
+                Ok(None) => tokio::time::sleep(POLL_INTERVAL).await,
+                Err(e) => {
+                    warn!("Failed to claim webhook delivery: {e}");
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                }
+            }
+        }
+    }
+
+    /// Post one event, returning whether it was delivered
+    async fn deliver(&self, entry: &JournalEntry) -> bool {
+        let outcome = match entry.payload["url"].as_str() {
+            Some(url) => self.post(url, &entry.payload["event"]).await,
+            None => Err("Webhook delivery has no url".to_string()),
+        };
+
+        let recorded = match &outcome {
+            Ok(()) => self.journal.complete(&entry.id).await.map(|_| ()),
+            Err(error) => {
+                debug!(
+                    "Webhook delivery {} failed (attempt {} of {}): {error}",
+                    entry.id, entry.attempts, entry.max_attempts
+                );
+                self.journal.fail(&entry.id, error).await.map(|_| ())
+            }
+        };
+        if let Err(e) = recorded {
+            warn!("Failed to record webhook delivery {}: {e}", entry.id);
+        }
+        outcome.is_ok()
+    }
+
+    async fn post(&self, url: &str, event: &JsonValue) -> std::result::Result<(), String> {
+        let mut request = self
+            .client
+            .post(url)
+            .json(event)
+            .build()
+            .map_err(|e| e.to_string())?;
+        if let Some(signer) = &self.signer {
+            signer.sign(&mut request);
+        }
+        self.client
+            .execute(request)
+            .await
+            .and_then(|response| response.error_for_status())
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
@@ -1,3 +1,6 @@
+use crate::config::DraftModelConfig;
+use crate::services::models::LocalModels;
+use crate::services::speculative::speculative_decode;
 use async_trait::async_trait;
 use catgrad_llm::serve::Loader;
 use futures::StreamExt;
@@ -7,25 +10,56 @@ use gate_core::router::prelude::{
     SinkDescription, SinkHealth, StopReason,
 };
 use serde_json::json;
+use std::collections::HashMap;
 use std::pin::Pin;
+use std::sync::Arc;
 
 use catgrad_llm::{
     run::{ModelLoader, ModelRunner, ModelTokenizer},
     serve::{ChatTokenizer, LM, Message, Tokenizer},
 };
 
+/// A model's runner and tokenizer, loaded together
+pub type CatgradModel = (ModelRunner, ModelTokenizer);
+
+/// Load a model's weights and tokenizer
+pub fn load_model(model: &str) -> gate_core::Result<CatgradModel> {
+    use gate_core::Error;
+    info!("Loading model: {}", model);
+    let loader = ModelLoader::new(model, true)
+        .map_err(|e| Error::Internal(format!("Failed to create model loader: {e}")))?;
+    let runner = loader
+        .load_runner()
+        .map_err(|e| Error::Internal(format!("Failed to load model runner: {e}")))?;
+    let tokenizer = loader
+        .load_tokenizer()
+        .map_err(|e| Error::Internal(format!("Failed to load tokenizer: {e}")))?;
+    info!("Model {} loaded successfully", model);
+    Ok((runner, tokenizer))
+}
+
 pub struct CatgradSink {
     id: String,
     models: Vec<String>,
+    loaded: Arc<LocalModels>,
+    drafts: HashMap<String, DraftModelConfig>,
 }
 
 impl CatgradSink {
-    pub fn new(id: impl Into<String>, models: Vec<String>) -> Self {
+    pub fn new(id: impl Into<String>, models: Vec<String>, loaded: Arc<LocalModels>) -> Self {
         Self {
             id: id.into(),
             models,
+            loaded,
+            drafts: HashMap::new(),
         }
     }
+
+    /// Decode speculatively with the draft models paired to targets
+    pub fn with_drafts(mut self, drafts: HashMap<String, DraftModelConfig>) -> Self {
+        self.drafts = drafts;
+        self
+    }
 }
 
 #[async_trait]
@@ -44,6 +78,7 @@ impl Sink for CatgradSink {
                 supports_streaming: true, // we emit a streaming response interface
                 supports_batching: false,
                 supports_tools: false,
+                supports_structured_output: false,
                 max_context_length: Some(8192),
                 modalities: vec!["text".into()],
             },
@@ -128,19 +163,61 @@ impl Sink for CatgradSink {
             .and_then(|v| v.as_u64())
             .unwrap_or(256) as usize;
 
+        // A draft model that fails to load leaves the target decoding alone
+        let loaded = self.loaded.get(&model).await?;
+        let draft = match self.drafts.get(&model).filter(|d| d.model != model) {
+            Some(config) => match self.loaded.get(&config.model).await {
+                Ok(draft) => Some((config.draft_tokens, draft)),
+                Err(e) => {
+                    warn!("Failed to load draft model {}: {}", config.model, e);
+                    None
+                }
+            },
+            None => None,
+        };
+
         // Spawn blocking generation to compute deltas of decoded text and usage
-        let model_clone = model.clone();
-        let (deltas, prompt_tokens, completion_tokens): (Vec<String>, u32, u32) =
-            tokio::task::spawn_blocking(move || -> gate_core::Result<(Vec<String>, u32, u32)> {
-                let loader = ModelLoader::new(&model_clone, true).map_err(internalize)?;
-                let mut runner: ModelRunner = loader.load_runner().map_err(internalize)?;
-                let tokenizer: ModelTokenizer = loader.load_tokenizer().map_err(internalize)?;
+        type Generated = (Vec<String>, u32, u32, Option<(u64, u64)>);
+        let (deltas, prompt_tokens, completion_tokens, drafted): Generated =
+            tokio::task::spawn_blocking(move || -> gate_core::Result<Generated> {
+                let mut loaded = loaded.lock().unwrap_or_else(|e| e.into_inner());
+                let (runner, tokenizer) = &mut *loaded;
 
                 let context = tokenizer
                     .encode_messages(catgrad_messages)
                     .map_err(internalize)?;
                 let prompt_len = context.len() as u32;
 
+                // A draft busy with another request is skipped rather than
+                // waited on, which also keeps paired models from deadlocking
+                if let Some((draft_tokens, draft)) = &draft
+                    && let Ok(mut draft) = draft.try_lock()
+                {
+                    let (draft_runner, _) = &mut *draft;
+                    let decoded = speculative_decode(
+                        |context: &[_], n| runner.complete(context.to_vec()).take(n).collect(),
+                        |context: &[_], n| {
+                            draft_runner.complete(context.to_vec()).take(n).collect()
+                        },
+                        context,
+                        max_tokens,
+                        *draft_tokens,
+                    );
+                    let deltas = decoded
+                        .tokens
+                        .iter()
+                        .filter_map(|token| tokenizer.decode(vec![token.clone()]).ok())
+                        .filter(|piece| !piece.is_empty())
+                        .collect();
+                    let count = decoded.tokens.len() as u32;
+                    return Ok((
+                        deltas,
+                        prompt_len,
+                        count,
+                        Some((decoded.proposed, decoded.accepted)),
+                    ));
+                }
+
                 let mut deltas = Vec::new();
                 let mut count = 0usize;
                 for token in runner.complete(context) {
@@ -155,10 +232,14 @@ impl Sink for CatgradSink {
                         break;
                     }
                 }
-                Ok((deltas, prompt_len, count as u32))
+                Ok((deltas, prompt_len, count as u32, None))
             })
             .await
             .map_err(internalize)??;
+        if let (Some(config), Some((proposed, accepted))) = (self.drafts.get(&model), drafted) {
+            self.loaded
+                .record_draft(&model, &config.model, proposed, accepted);
+        }
 
         // Build a stream over headers, metadata, incremental deltas, usage, then Stop
         let mut items: Vec<gate_core::Result<ResponseChunk>> = Vec::with_capacity(deltas.len() + 4);
@@ -0,0 +1,328 @@
+//! Scriptable `mock://` provider
+//!
+//! Serves OpenAI chat and Anthropic messages requests locally with a canned
+//! or echoed reply, optional latency, injected failures and a fixed token
+//! rate. Useful for load testing the gateway and developing clients offline.
+
+use crate::config::MockProviderConfig;
+use async_trait::async_trait;
+use axum::http::StatusCode;
+use futures::StreamExt;
+use gate_core::router::prelude::{
+    ModelList, Protocol, RequestContext, RequestStream, ResponseChunk, ResponseStream, Sink,
+    SinkCapabilities, SinkDescription, SinkHealth, StopReason,
+};
+use gate_core::router::service::estimate_tokens;
+use gate_core::{Error, Result, UpstreamError};
+use serde_json::{Value as JsonValue, json};
+use std::time::Duration;
+
+pub struct MockSink {
+    id: String,
+    config: MockProviderConfig,
+}
+
+impl MockSink {
+    pub fn new(id: impl Into<String>, config: MockProviderConfig) -> Self {
+        Self {
+            id: id.into(),
+            config,
+        }
+    }
+
+    /// Delay between streamed tokens
+    fn token_interval(&self) -> Duration {
+        if self.config.tokens_per_second > 0.0 {
+            Duration::from_secs_f64(1.0 / self.config.tokens_per_second)
+        } else {
+            Duration::ZERO
+        }
+    }
+
+    fn reply_text(&self, request: &JsonValue) -> String {
+        self.config
+            .response
+            .clone()
+            .unwrap_or_else(|| last_user_text(request))
+    }
+}
+
+/// Text of the last user message, for echoing
+fn last_user_text(request: &JsonValue) -> String {
+    let Some(message) = request
+        .get("messages")
+        .and_then(|m| m.as_array())
+        .and_then(|messages| {
+            messages
+                .iter()
+                .rev()
+                .find(|m| m.get("role").and_then(|r| r.as_str()) == Some("user"))
+        })
+    else {
+        return String::new();
+    };
+    match message.get("content") {
+        Some(JsonValue::String(text)) => text.clone(),
+        Some(JsonValue::Array(blocks)) => blocks
+            .iter()
+            .filter_map(|b| b.get("text").and_then(|t| t.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+/// Split text into word-sized tokens that concatenate back to the original
+fn tokens(text: &str) -> Vec<String> {
+    text.split_inclusive(' ').map(String::from).collect()
+}
+
+fn chat_chunks(id: &str, model: &str, tokens: &[String]) -> Vec<JsonValue> {
+    let chunk = |delta: JsonValue, finish_reason: Option<&str>| {
+        json!({
+            "id": id,
+            "object": "chat.completion.chunk",
+            "created": chrono::Utc::now().timestamp(),
+            "model": model,
+            "choices": [{"index": 0, "delta": delta, "finish_reason": finish_reason}]
+        })
+    };
+    let mut chunks = vec![chunk(json!({"role": "assistant", "content": ""}), None)];
+    chunks.extend(tokens.iter().map(|t| chunk(json!({"content": t}), None)));
+    chunks.push(chunk(json!({}), Some("stop")));
+    chunks
+}
+
+fn chat_completion(id: &str, model: &str, text: &str, usage: (u32, u32)) -> JsonValue {
+    json!({
+        "id": id,
+        "object": "chat.completion",
+        "created": chrono::Utc::now().timestamp(),
+        "model": model,
+        "choices": [{
+            "index": 0,
+            "message": {"role": "assistant", "content": text},
+            "finish_reason": "stop"
+        }],
+        "usage": {
+            "prompt_tokens": usage.0,
+            "completion_tokens": usage.1,
+            "total_tokens": usage.0 + usage.1
+        }
+    })
+}
+
+fn anthropic_events(id: &str, model: &str, tokens: &[String], usage: (u32, u32)) -> Vec<JsonValue> {
+    let mut events = vec![
+        json!({
+            "type": "message_start",
+            "message": {
+                "id": id,
+                "type": "message",
+                "role": "assistant",
+                "model": model,
+                "content": [],
+                "stop_reason": null,
+                "usage": {"input_tokens": usage.0, "output_tokens": 0}
+            }
+        }),
+        json!({
+            "type": "content_block_start",
+            "index": 0,
+            "content_block": {"type": "text", "text": ""}
+        }),
+    ];
+    events.extend(tokens.iter().map(|t| {
+        json!({
+            "type": "content_block_delta",
+            "index": 0,
+            "delta": {"type": "text_delta", "text": t}
+        })
+    }));
+    events.extend([
+        json!({"type": "content_block_stop", "index": 0}),
+        json!({
+            "type": "message_delta",
+            "delta": {"stop_reason": "end_turn", "stop_sequence": null},
+            "usage": {"output_tokens": usage.1}
+        }),
+        json!({"type": "message_stop"}),
+    ]);
+    events
+}
+
+fn anthropic_message(id: &str, model: &str, text: &str, usage: (u32, u32)) -> JsonValue {
+    json!({
+        "id": id,
+        "type": "message",
+        "role": "assistant",
+        "model": model,
+        "content": [{"type": "text", "text": text}],
+        "stop_reason": "end_turn",
+        "stop_sequence": null,
+        "usage": {"input_tokens": usage.0, "output_tokens": usage.1}
+    })
+}
+
+#[async_trait]
+impl Sink for MockSink {
+    async fn describe(&self) -> SinkDescription {
+        SinkDescription {
+            id: self.id.clone(),
+            accepted_protocols: vec![Protocol::OpenAIChat, Protocol::Anthropic],
+            models: if self.config.models.is_empty() {
+                ModelList::Dynamic
+            } else {
+                ModelList::Static(self.config.models.clone())
+            },
+            capabilities: SinkCapabilities {
+                supports_streaming: true,
+                supports_batching: false,
+                supports_tools: false,
+                supports_structured_output: false,
+                max_context_length: None,
+                modalities: vec!["text".into()],
+            },
+            cost_structure: None,
+        }
+    }
+
+    async fn probe(&self) -> SinkHealth {
+        SinkHealth {
+            healthy: true,
+            latency_ms: Some(self.config.latency_ms),
+            error_rate: self.config.error_rate as f32,
+            last_error: None,
+            last_check: chrono::Utc::now(),
+        }
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &RequestContext,
+        mut request: RequestStream,
+    ) -> Result<ResponseStream> {
+        let protocol = request.protocol();
+        let first = request
+            .next()
+            .await
+            .ok_or_else(|| Error::InvalidRequest("Empty request stream".to_string()))??;
+
+        if self.config.latency_ms > 0 {
+            tokio::time::sleep(Duration::from_millis(self.config.latency_ms)).await;
+        }
+        if rand::random::<f64>() < self.config.error_rate {
+            let status = StatusCode::from_u16(self.config.error_status)
+                .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+            return Err(Error::Upstream(UpstreamError::new(
+                "mock",
+                status,
+                "injected failure",
+            )));
+        }
+
+        let model = first
+            .get("model")
+            .and_then(|m| m.as_str())
+            .unwrap_or("mock")
+            .to_string();
+        let streaming = first
+            .get("stream")
+            .and_then(|s| s.as_bool())
+            .unwrap_or(false);
+        let text = self.reply_text(&first);
+        let tokens = tokens(&text);
+        let usage = (estimate_tokens(&first) as u32, tokens.len() as u32);
+        let id = uuid::Uuid::new_v4().simple().to_string();
+
+        let (content, interval) = match (protocol, streaming) {
+            (Protocol::OpenAIChat, true) => (
+                chat_chunks(&format!("chatcmpl-{id}"), &model, &tokens),
+                self.token_interval(),
+            ),
+            (Protocol::OpenAIChat, false) => (
+                vec![chat_completion(
+                    &format!("chatcmpl-{id}"),
+                    &model,
+                    &text,
+                    usage,
+                )],
+                Duration::ZERO,
+            ),
+            (Protocol::Anthropic, true) => (
+                anthropic_events(&format!("msg_{id}"), &model, &tokens, usage),
+                self.token_interval(),
+            ),
+            (Protocol::Anthropic, false) => (
+                vec![anthropic_message(
+                    &format!("msg_{id}"),
+                    &model,
+                    &text,
+                    usage,
+                )],
+                Duration::ZERO,
+            ),
+            (other, _) => {
+                return Err(Error::InvalidRequest(format!(
+                    "Mock provider does not serve {other:?} requests"
+                )));
+            }
+        };
+
+        // A whole reply takes as long as streaming it would have
+        if !streaming {
+            tokio::time::sleep(self.token_interval() * tokens.len() as u32).await;
+        }
+
+        let mut items: Vec<Result<ResponseChunk>> = Vec::with_capacity(content.len() + 3);
+        items.push(Ok(ResponseChunk::Headers(Default::default())));
+        items.extend(content.into_iter().map(|c| Ok(ResponseChunk::Content(c))));
+        items.push(Ok(ResponseChunk::Usage {
+            prompt_tokens: usage.0,
+            completion_tokens: usage.1,
+        }));
+        items.push(Ok(ResponseChunk::Stop {
+            reason: StopReason::Complete,
+            error: None,
+            cost: None,
+        }));
+
+        let stream = futures::stream::iter(items).then(move |item| async move {
+            if !interval.is_zero() && matches!(item, Ok(ResponseChunk::Content(_))) {
+                tokio::time::sleep(interval).await;
+            }
+            item
+        });
+        Ok(Box::pin(stream))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens_concatenate_to_text() {
+        let text = "Hello there, world";
+        assert_eq!(tokens(text).concat(), text);
+        assert_eq!(tokens(text).len(), 3);
+    }
+
+    #[test]
+    fn test_echoes_last_user_message() {
+        let request = json!({"messages": [
+            {"role": "user", "content": "first"},
+            {"role": "assistant", "content": "reply"},
+            {"role": "user", "content": [{"type": "text", "text": "second"}]}
+        ]});
+        assert_eq!(last_user_text(&request), "second");
+    }
+
+    #[test]
+    fn test_chat_chunks_end_with_stop() {
+        let chunks = chat_chunks("id", "m", &tokens("a b"));
+        assert_eq!(chunks.len(), 4);
+        assert_eq!(chunks[2]["choices"][0]["delta"]["content"], "b");
+        assert_eq!(chunks[3]["choices"][0]["finish_reason"], "stop");
+    }
+}
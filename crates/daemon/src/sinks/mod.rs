@@ -1 +1,2 @@
 pub mod catgrad_sink;
+pub mod mock_sink;
@@ -5,14 +5,21 @@
 
 use crate::Daemon;
 use crate::config::ProviderPassthroughConfig;
-use crate::services::AuthService;
+use crate::services::AccessLog;
+use crate::services::ServerMode;
+use crate::services::api_keys::{API_KEY_PREFIX, ApiKeyScope, ApiKeyService, key_subject};
+use crate::services::lockout::{LockoutSubject, client_address, is_failed_sign_in};
+use crate::services::observer::{OBSERVER_SCOPE, SCOPE_ATTRIBUTE};
+use crate::services::{AuthLockout, AuthService};
 use async_trait::async_trait;
 use axum::extract::connect_info::ConnectInfo;
 use axum::http::HeaderName;
 use axum::http::request::Parts;
+use chrono::Utc;
 use gate_core::router::signals::{anthropic_key_from, openai_bearer_from};
 use gate_http::error::HttpError;
 use gate_http::middleware::AuthProvider;
+use gate_http::routes::ApiSurface;
 use gate_http::services::{HttpContext, HttpIdentity};
 use std::net::SocketAddr;
 use std::sync::Arc;
@@ -28,6 +35,15 @@ pub struct State {
     pub allow_local_bypass: bool,
     /// Provider passthrough configuration
     pub provider_passthrough: ProviderPassthroughConfig,
+    /// Accept Anthropic client credentials the way the Anthropic API does
+    pub anthropic_compat: bool,
+    /// Maintenance and read-only modes in effect
+    pub server_mode: ServerMode,
+    /// Requests forwarded by the relay
+    pub access_log: AccessLog,
+    /// Addresses and accounts locked out after failed sign-ins, and
+    /// addresses the access log banned
+    pub auth_lockout: AuthLockout,
 }
 
 impl State {
@@ -42,14 +58,74 @@ impl State {
             daemon,
             allow_local_bypass,
             provider_passthrough,
+            anthropic_compat: false,
+            server_mode: ServerMode::new(crate::config::DEFAULT_MAINTENANCE_MESSAGE),
+            access_log: AccessLog::default(),
+            auth_lockout: AuthLockout::default(),
         }
     }
+
+    /// Enable Anthropic compatibility for authentication
+    pub fn with_anthropic_compat(mut self, enabled: bool) -> Self {
+        self.anthropic_compat = enabled;
+        self
+    }
+
+    /// Use the given maintenance and read-only mode handle
+    pub fn with_server_mode(mut self, server_mode: ServerMode) -> Self {
+        self.server_mode = server_mode;
+        self
+    }
+
+    /// Log forwarded requests to the given access log
+    pub fn with_access_log(mut self, access_log: AccessLog) -> Self {
+        self.access_log = access_log;
+        self
+    }
+
+    /// Track failed sign-ins with the given lockout state
+    pub fn with_auth_lockout(mut self, auth_lockout: AuthLockout) -> Self {
+        self.auth_lockout = auth_lockout;
+        self
+    }
+
+    /// Authenticate a gateway-issued API key
+    async fn authenticate_api_key(&self, raw_key: &str) -> Result<HttpIdentity, HttpError> {
+        let state_backend = self
+            .daemon
+            .get_state_backend()
+            .await
+            .map_err(|e| HttpError::InternalServerError(e.to_string()))?;
+
+        let key = ApiKeyService::new(state_backend)
+            .authenticate(raw_key)
+            .await
+            .map_err(|e| HttpError::InternalServerError(e.to_string()))?
+            .ok_or_else(|| {
+                HttpError::AuthenticationFailed("Invalid or expired API key".to_string())
+            })?;
+
+        let mut context = HttpContext::new()
+            .with_attribute("auth_method", "api_key")
+            .with_attribute("api_key_hash", key.key_hash.clone())
+            .with_attribute("user_id", key.org_id.clone())
+            .with_attribute("node_id", "local");
+        if ApiKeyScope::from_key(&key).observer {
+            context = context.with_attribute(SCOPE_ATTRIBUTE, OBSERVER_SCOPE);
+        }
+
+        // API keys get their own subject so they never inherit the owner's admin rights
+        Ok(HttpIdentity::new(
+            key_subject(&key.key_hash),
+            "api-key".to_string(),
+            context,
+        ))
+    }
 }
 
-// Implement AuthProvider directly for State
-#[async_trait]
-impl AuthProvider for State {
-    async fn authenticate(&self, parts: &Parts) -> Result<HttpIdentity, HttpError> {
+impl State {
+    /// Identity the request's credentials prove
+    async fn authenticate_credentials(&self, parts: &Parts) -> Result<HttpIdentity, HttpError> {
         // Helper: detect Anthropic API key from headers
         fn detect_anthropic_key(parts: &Parts) -> Option<String> {
             anthropic_key_from(&parts.headers).map(|s| s.to_string())
@@ -71,13 +147,35 @@ impl AuthProvider for State {
             None
         }
 
+        // Gateway-issued API keys take precedence over provider passthrough
+        if let Some(raw_key) =
+            openai_bearer_from(&parts.headers).filter(|token| token.starts_with(API_KEY_PREFIX))
+        {
+            return self.authenticate_api_key(raw_key).await;
+        }
+        // Anthropic clients send their key as x-api-key, in compatibility
+        // mode or on an Anthropic prefix
+        let anthropic_surface = self.anthropic_compat
+            || parts.extensions.get::<ApiSurface>() == Some(&ApiSurface::Anthropic);
+        if anthropic_surface
+            && let Some(raw_key) = parts
+                .headers
+                .get(HeaderName::from_static("x-api-key"))
+                .and_then(|v| v.to_str().ok())
+                .filter(|key| key.starts_with(API_KEY_PREFIX))
+        {
+            return self.authenticate_api_key(raw_key).await;
+        }
+
         let path = parts.uri.path();
-        // Provider-token auth bypass for Anthropic/OpenAI, gated by config
+        // Provider-token auth bypass for Anthropic/OpenAI, gated by config.
+        // Anthropic clients also list models with their own credentials.
         let passthrough_allowed_path = self
             .provider_passthrough
             .allowed_paths
             .iter()
-            .any(|p| p == path);
+            .any(|p| p == path)
+            || (anthropic_surface && path == "/v1/models");
         let is_loopback = parts
             .extensions
             .get::<ConnectInfo<SocketAddr>>()
@@ -147,11 +245,34 @@ impl AuthProvider for State {
             "Missing authorization header".to_string(),
         ))
     }
+}
+
+// Implement AuthProvider directly for State
+#[async_trait]
+impl AuthProvider for State {
+    async fn authenticate(&self, parts: &Parts) -> Result<HttpIdentity, HttpError> {
+        let result = self.authenticate_credentials(parts).await;
+        // Rejected credentials count towards a lockout of their address
+        if let Err(HttpError::AuthenticationFailed(reason)) = &result
+            && is_failed_sign_in(&parts.headers, reason)
+            && let Some(ip) = client_address(&parts.extensions)
+        {
+            self.auth_lockout
+                .record_failure(&[LockoutSubject::Address(ip)], Utc::now())
+                .await;
+        }
+        result
+    }
 
     fn should_skip_auth(&self, path: &str) -> bool {
         path.starts_with("/auth/webauthn/")
             || path.starts_with("/auth/bootstrap/")
+            || path.starts_with("/auth/pair")
             || path == "/health"
+            || path == crate::services::readiness::LIVENESS_PATH
+            || path == crate::services::readiness::READINESS_PATH
+            || path == crate::services::identity::ATTESTATION_PATH
+            || path == crate::services::build_info::VERSION_PATH
             || path.starts_with("/swagger-ui")
             || path == "/"
             || path.ends_with(".js")
@@ -162,7 +283,7 @@ impl AuthProvider for State {
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
     use crate::daemon::rpc::DaemonRequest;
     use axum::http::Request;
@@ -172,7 +293,7 @@ mod tests {
     use gate_sqlx::SqliteStateBackend;
     use tokio::sync::mpsc;
 
-    async fn make_minimal_state(allow_local_bypass: bool) -> State {
+    pub(crate) async fn make_minimal_state(allow_local_bypass: bool) -> State {
         // Build a lightweight AuthService stack (will not be exercised in these tests)
         let jwt_service = Arc::new(JwtService::new(JwtSvcConfig::new(
             "test-secret".to_string(),
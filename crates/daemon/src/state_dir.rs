@@ -4,6 +4,7 @@ use directories::ProjectDirs;
 use std::path::PathBuf;
 
 /// Manages platform-specific application directories
+#[derive(Clone)]
 pub struct StateDir(ProjectDirs);
 
 impl StateDir {
@@ -49,11 +50,21 @@ impl StateDir {
         self.data_dir().join(component)
     }
 
+    /// Get the URL of the state database
+    pub fn database_url(&self) -> String {
+        format!("sqlite://{}", self.data_dir().join("gate.db").display())
+    }
+
     /// Get the config path
     pub fn config_path(&self) -> PathBuf {
         self.config_dir().join("config.json")
     }
 
+    /// Get the default path of the local control socket
+    pub fn control_socket_path(&self) -> PathBuf {
+        self.data_dir().join("control.sock")
+    }
+
     /// Get the path for the Iroh secret key
     pub fn iroh_secret_key_path(&self) -> PathBuf {
         self.config_dir().join("iroh_secret.key")
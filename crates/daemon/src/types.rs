@@ -9,6 +9,17 @@ pub struct DaemonStatus {
     pub tlsforward_enabled: bool,
     pub tlsforward_status: TlsForwardStatus,
     pub needs_bootstrap: bool,
+    /// Set when the daemon started in safe mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub safe_mode: Option<crate::safe_mode::SafeMode>,
+    /// Local models loaded and the memory they hold, when local inference
+    /// is configured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub local_models: Option<crate::services::ModelCacheStatus>,
+    /// Bytes forwarded through the relay, per hostname, when TLS
+    /// forwarding is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tlsforward_traffic: Option<gate_tlsforward::client::TrafficSnapshot>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,10 +27,25 @@ pub enum TlsForwardStatus {
     Disabled,
     Disconnected,
     Connecting,
-    Connected { domain: String },
+    Connected {
+        domain: String,
+        #[serde(default)]
+        transport: TransportKind,
+    },
     Error(String),
 }
 
+/// Transport a connected node is exposed through
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransportKind {
+    #[default]
+    Relay,
+    Ssh,
+    Cloudflare,
+    Command,
+}
+
 /// Response for bootstrap status endpoint
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BootstrapStatusResponse {
@@ -29,6 +55,22 @@ pub struct BootstrapStatusResponse {
     pub is_complete: bool,
     /// Human-readable status message
     pub message: String,
+    /// When the current bootstrap token expires
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_expires_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Request to exchange the current, possibly expired, bootstrap token for a new one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BootstrapRegenerateRequest {
+    pub token: String,
+}
+
+/// Newly issued bootstrap token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BootstrapRegenerateResponse {
+    pub token: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
 }
 
 /// Response for daemon runtime configuration
@@ -39,3 +81,63 @@ pub struct DaemonRuntimeConfigResponse {
     /// Whether TLS forward is enabled
     pub tlsforward_enabled: bool,
 }
+
+/// Outcome of a single doctor check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+    Skipped,
+}
+
+/// Result of a single doctor check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DoctorCheck {
+    /// Stable identifier for the check (e.g. `port`, `provider:openai`)
+    pub id: String,
+    /// Human-readable check name
+    pub name: String,
+    /// Outcome of the check
+    pub status: CheckStatus,
+    /// What was observed
+    pub message: String,
+    /// Suggested fix when the check did not pass
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remediation: Option<String>,
+}
+
+/// Aggregated result of a doctor run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DoctorReport {
+    /// Whether no check failed
+    pub healthy: bool,
+    /// Individual check results in execution order
+    pub checks: Vec<DoctorCheck>,
+    /// When the checks were run
+    pub generated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Result of a single readiness check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProbeCheck {
+    /// Stable identifier for the check (e.g. `state_backend`)
+    pub id: String,
+    pub status: CheckStatus,
+    /// What was observed
+    pub message: String,
+    /// How long the check took
+    pub latency_ms: f64,
+}
+
+/// Answer to a liveness or readiness probe
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProbeReport {
+    /// `fail` if any check failed, `warn` if any warned, `pass` otherwise
+    pub status: CheckStatus,
+    /// Individual checks, only shown to callers allowed to read the
+    /// daemon's status
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub checks: Vec<ProbeCheck>,
+}
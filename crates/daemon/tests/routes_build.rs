@@ -1,7 +1,12 @@
 use axum::Router;
 use gate_daemon::{
     State,
-    routes::{admin, auth, config},
+    routes::{
+        admin, audit, auth, config, conversations, credentials, data, devices, doctor, documents,
+        evals, experiments, export, feedback, files, groups, journal, keys, lockouts, mode, models,
+        node, onboarding, preferences, prompts, providers, status, tasks, tls, tlsforward, usage,
+        version,
+    },
 };
 
 // Ensure admin routes construct without panicking (e.g., invalid path syntax)
@@ -21,3 +26,164 @@ fn auth_routes_builds() {
 fn config_routes_builds() {
     let _ = config::add_routes(Router::<gate_http::AppState<State>>::new());
 }
+
+// Ensure device routes construct without panicking
+#[test]
+fn devices_routes_builds() {
+    let _ = devices::add_routes(Router::<gate_http::AppState<State>>::new());
+}
+
+// Ensure credential routes construct without panicking
+#[test]
+fn credentials_routes_builds() {
+    let _ = credentials::add_routes(Router::<gate_http::AppState<State>>::new());
+}
+
+// Ensure doctor routes construct without panicking
+#[test]
+fn doctor_routes_builds() {
+    let _ = doctor::add_routes(Router::<gate_http::AppState<State>>::new());
+}
+
+// Ensure document routes construct without panicking
+#[test]
+fn documents_routes_builds() {
+    let _ = documents::add_routes(Router::<gate_http::AppState<State>>::new());
+}
+
+// Ensure eval routes construct without panicking
+#[test]
+fn evals_routes_builds() {
+    let _ = evals::add_routes(Router::<gate_http::AppState<State>>::new());
+}
+
+#[test]
+fn experiments_routes_builds() {
+    let _ = experiments::add_routes(Router::<gate_http::AppState<State>>::new());
+}
+
+#[test]
+fn feedback_routes_builds() {
+    let _ = feedback::add_routes(Router::<gate_http::AppState<State>>::new());
+}
+
+// Ensure file routes construct without panicking
+#[test]
+fn files_routes_builds() {
+    let _ = files::add_routes(Router::<gate_http::AppState<State>>::new());
+}
+
+// Ensure journal routes construct without panicking
+#[test]
+fn journal_routes_builds() {
+    let _ = journal::add_routes(Router::<gate_http::AppState<State>>::new());
+}
+
+// Ensure API key routes construct without panicking
+#[test]
+fn keys_routes_builds() {
+    let _ = keys::add_routes(Router::<gate_http::AppState<State>>::new());
+}
+
+// Ensure sign-in lockout routes construct without panicking
+#[test]
+fn lockouts_routes_builds() {
+    let _ = lockouts::add_routes(Router::<gate_http::AppState<State>>::new());
+}
+
+// Ensure conversation routes construct without panicking
+#[test]
+fn conversations_routes_builds() {
+    let _ = conversations::add_routes(Router::<gate_http::AppState<State>>::new());
+}
+
+// Ensure provider routes construct without panicking
+#[test]
+fn providers_routes_builds() {
+    let _ = providers::add_routes(Router::<gate_http::AppState<State>>::new());
+}
+
+// Ensure onboarding routes construct without panicking
+#[test]
+fn onboarding_routes_builds() {
+    let _ = onboarding::add_routes(Router::<gate_http::AppState<State>>::new());
+}
+
+// Ensure preference routes construct without panicking
+#[test]
+fn preferences_routes_builds() {
+    let _ = preferences::add_routes(Router::<gate_http::AppState<State>>::new());
+}
+
+#[test]
+fn prompts_routes_builds() {
+    let _ = prompts::add_routes(Router::<gate_http::AppState<State>>::new());
+}
+
+#[test]
+fn usage_routes_builds() {
+    let _ = usage::add_routes(Router::<gate_http::AppState<State>>::new());
+}
+
+#[test]
+fn data_routes_builds() {
+    let _ = data::add_routes(Router::<gate_http::AppState<State>>::new());
+}
+
+#[test]
+fn export_routes_builds() {
+    let _ = export::add_routes(Router::<gate_http::AppState<State>>::new());
+}
+
+#[test]
+fn mode_routes_builds() {
+    let _ = mode::add_routes(Router::<gate_http::AppState<State>>::new());
+}
+
+#[test]
+fn models_routes_builds() {
+    let _ = models::add_routes(Router::<gate_http::AppState<State>>::new());
+}
+
+#[test]
+fn tasks_routes_builds() {
+    let _ = tasks::add_routes(Router::<gate_http::AppState<State>>::new());
+}
+
+#[test]
+fn tls_routes_builds() {
+    let _ = tls::add_routes(Router::<gate_http::AppState<State>>::new());
+}
+
+#[test]
+fn tlsforward_routes_builds() {
+    let _ = tlsforward::add_routes(Router::<gate_http::AppState<State>>::new());
+}
+
+#[test]
+fn status_routes_builds() {
+    let _ = status::add_routes(Router::<gate_http::AppState<State>>::new());
+}
+
+#[test]
+fn node_routes_builds() {
+    let _ = node::add_routes(Router::<gate_http::AppState<State>>::new());
+}
+
+// Ensure permission audit routes construct without panicking
+#[test]
+fn audit_routes_builds() {
+    let _ = audit::add_routes(Router::<gate_http::AppState<State>>::new());
+}
+
+// Ensure permission group routes construct without panicking
+#[test]
+fn groups_routes_builds() {
+    let _ = groups::add_routes(Router::<gate_http::AppState<State>>::new());
+}
+
+// Ensure version routes construct without panicking
+#[test]
+fn version_routes_builds() {
+    let _ = version::add_routes(Router::<gate_http::AppState<State>>::new());
+}
@@ -11,12 +11,23 @@ use web_sys::window;
 static PUBLIC_CLIENT: Lazy<Mutex<Option<PublicGateClient>>> = Lazy::new(|| Mutex::new(None));
 static AUTH_CLIENT: Lazy<Mutex<Option<WrappedAuthClient>>> = Lazy::new(|| Mutex::new(None));
 
+/// Path the UI is served under, from the page's `<base href>`, without a
+/// trailing slash. Empty when served at the root.
+pub fn base_path() -> String {
+    window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.query_selector("base").ok().flatten())
+        .and_then(|base| base.get_attribute("href"))
+        .map(|href| href.trim_end_matches('/').to_string())
+        .unwrap_or_default()
+}
+
 /// Get the base URL for API calls
 fn get_base_url() -> String {
-    // Try to get from window location
+    // Try to get from window location, under the page's base path
     if let Some(window) = window() {
         if let Ok(location) = window.location().origin() {
-            return location;
+            return format!("{location}{}", base_path());
         }
     }
 
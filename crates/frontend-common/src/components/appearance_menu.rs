@@ -0,0 +1,137 @@
+//! Appearance menu: theme and accent colour
+
+use crate::i18n::use_i18n;
+use crate::theme::{
+    accent::{ACCENT_PRESETS, DEFAULT_ACCENT},
+    use_accent, use_theme, Theme, ThemeAction,
+};
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+const PANEL_ID: &str = "appearance-menu";
+
+#[function_component(AppearanceMenu)]
+pub fn appearance_menu() -> Html {
+    let i18n = use_i18n();
+    let theme_ctx = use_theme();
+    let (accent, set_accent) = use_accent();
+    let open = use_state(|| false);
+
+    let on_toggle = {
+        let open = open.clone();
+        Callback::from(move |_: MouseEvent| open.set(!*open))
+    };
+    let on_keydown = {
+        let open = open.clone();
+        Callback::from(move |e: KeyboardEvent| {
+            if e.key() == "Escape" {
+                open.set(false);
+            }
+        })
+    };
+    let on_custom_accent = {
+        let set_accent = set_accent.clone();
+        Callback::from(move |e: Event| {
+            if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                set_accent.emit(Some(input.value()));
+            }
+        })
+    };
+
+    let label = i18n.t("theme.appearance");
+    let current_accent = accent.clone().unwrap_or_else(|| DEFAULT_ACCENT.to_string());
+
+    html! {
+        <div class="relative" onkeydown={on_keydown}>
+            <button
+                onclick={on_toggle}
+                type="button"
+                class="relative inline-flex items-center justify-center w-10 h-10 p-2 rounded-lg bg-gray-200 dark:bg-gray-700 hover:bg-gray-300 dark:hover:bg-gray-600 transition-colors"
+                aria-expanded={open.to_string()}
+                aria-controls={PANEL_ID}
+                aria-label={label.clone()}
+                title={label.clone()}
+            >
+                <svg class="w-6 h-6 text-gray-600 dark:text-gray-300" fill="none" stroke="currentColor" viewBox="0 0 24 24" aria-hidden="true">
+                    <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M7 21a4 4 0 01-4-4V5a2 2 0 012-2h4a2 2 0 012 2v12a4 4 0 01-4 4zm0 0h12a2 2 0 002-2v-4a2 2 0 00-2-2h-2.343M11 7.343l1.657-1.657a2 2 0 012.828 0l2.829 2.829a2 2 0 010 2.828l-8.486 8.485M7 17h.01"/>
+                </svg>
+            </button>
+            if *open {
+                <div
+                    id={PANEL_ID}
+                    role="group"
+                    aria-label={label}
+                    class="absolute right-0 z-20 mt-2 w-64 p-4 space-y-4 rounded-lg shadow-lg bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700"
+                >
+                    <fieldset>
+                        <legend class="text-sm font-medium text-gray-900 dark:text-gray-100 mb-2">
+                            {i18n.t("theme.theme")}
+                        </legend>
+                        <div class="space-y-1">
+                            {for Theme::ALL.iter().map(|theme| {
+                                let theme = *theme;
+                                let onchange = {
+                                    let theme_ctx = theme_ctx.clone();
+                                    Callback::from(move |_: Event| theme_ctx.dispatch(ThemeAction::Set(theme)))
+                                };
+                                html! {
+                                    <label class="flex items-center gap-2 text-sm text-gray-700 dark:text-gray-300">
+                                        <input
+                                            type="radio"
+                                            name="theme"
+                                            checked={theme_ctx.theme == theme}
+                                            {onchange}
+                                        />
+                                        {i18n.t(theme.label_key())}
+                                    </label>
+                                }
+                            })}
+                        </div>
+                    </fieldset>
+
+                    <fieldset>
+                        <legend class="text-sm font-medium text-gray-900 dark:text-gray-100 mb-2">
+                            {i18n.t("theme.accent")}
+                        </legend>
+                        <div class="flex flex-wrap items-center gap-2">
+                            {for ACCENT_PRESETS.iter().map(|(key, color)| {
+                                let selected = current_accent.eq_ignore_ascii_case(color);
+                                let onclick = set_accent.reform(move |_: MouseEvent| Some(color.to_string()));
+                                html! {
+                                    <button
+                                        {onclick}
+                                        type="button"
+                                        class={classes!(
+                                            "w-6", "h-6", "rounded-full", "border-2",
+                                            if selected { "border-gray-900 dark:border-white" } else { "border-transparent" }
+                                        )}
+                                        style={format!("background-color: {color}")}
+                                        aria-pressed={selected.to_string()}
+                                        aria-label={i18n.t(key)}
+                                        title={i18n.t(key)}
+                                    />
+                                }
+                            })}
+                            <input
+                                type="color"
+                                class="w-8 h-8 p-0 border-0 bg-transparent cursor-pointer"
+                                value={current_accent}
+                                onchange={on_custom_accent}
+                                aria-label={i18n.t("theme.accent.custom")}
+                            />
+                        </div>
+                        if accent.is_some() {
+                            <button
+                                type="button"
+                                class="mt-2 text-xs text-gray-600 dark:text-gray-400 hover:underline"
+                                onclick={set_accent.reform(|_: MouseEvent| None)}
+                            >
+                                {i18n.t("theme.accent.reset")}
+                            </button>
+                        }
+                    </fieldset>
+                </div>
+            }
+        </div>
+    }
+}
@@ -1,5 +1,6 @@
 //! Bootstrap prompt component for initial admin setup
 
+use crate::hooks::use_modal_focus;
 use crate::services::BootstrapStatus;
 use yew::prelude::*;
 
@@ -51,14 +52,24 @@ pub fn bootstrap_prompt(props: &BootstrapPromptProps) -> Html {
         })
     };
 
+    // Setup must finish before the app is usable, so Escape does nothing
+    let dialog_ref = use_node_ref();
+    let on_keydown = use_modal_focus(dialog_ref.clone(), props.show, Callback::noop());
+
     if !props.show {
         return html! {};
     }
 
     html! {
-        <div class="fixed inset-0 bg-black/50 backdrop-blur-sm flex items-center justify-center z-50">
-            <div class="bg-white dark:bg-gray-800 rounded-lg p-6 max-w-md w-full mx-4 shadow-xl">
-                <h2 class="text-2xl font-bold mb-4 text-gray-800 dark:text-white">
+        <div class="fixed inset-0 bg-black/50 backdrop-blur-sm flex items-center justify-center z-50" onkeydown={on_keydown}>
+            <div
+                ref={dialog_ref}
+                class="bg-white dark:bg-gray-800 rounded-lg p-6 max-w-md w-full mx-4 shadow-xl"
+                role="dialog"
+                aria-modal="true"
+                aria-labelledby="bootstrap-title"
+            >
+                <h2 id="bootstrap-title" class="text-2xl font-bold mb-4 text-gray-800 dark:text-white">
                     {"Initial Admin Setup"}
                 </h2>
 
@@ -107,10 +118,11 @@ pub fn bootstrap_prompt(props: &BootstrapPromptProps) -> Html {
 
                 <form onsubmit={on_submit}>
                     <div class="mb-4">
-                        <label class="block text-sm font-medium text-gray-700 dark:text-gray-300 mb-2">
+                        <label for="bootstrap-token" class="block text-sm font-medium text-gray-700 dark:text-gray-300 mb-2">
                             {"Bootstrap Token"}
                         </label>
                         <input
+                            id="bootstrap-token"
                             type="text"
                             class="w-full px-4 py-2 border border-gray-300 dark:border-gray-600 rounded-lg bg-white dark:bg-gray-700 text-gray-900 dark:text-white focus:ring-2 focus:ring-blue-500 focus:border-transparent"
                             placeholder="Enter bootstrap token"
@@ -123,7 +135,7 @@ pub fn bootstrap_prompt(props: &BootstrapPromptProps) -> Html {
 
                     {if let Some(error_msg) = props.token_error.as_ref().or((*error).as_ref()) {
                         html! {
-                            <div class="mb-4 p-3 bg-red-50 dark:bg-red-900/20 rounded-lg">
+                            <div class="mb-4 p-3 bg-red-50 dark:bg-red-900/20 rounded-lg" role="alert">
                                 <p class="text-sm text-red-800 dark:text-red-200">
                                     {error_msg}
                                 </p>
@@ -0,0 +1,35 @@
+//! Language picker component
+
+use crate::i18n::{use_i18n, Locale};
+use web_sys::HtmlSelectElement;
+use yew::prelude::*;
+
+#[function_component(LanguageSelect)]
+pub fn language_select() -> Html {
+    let i18n = use_i18n();
+
+    let onchange = {
+        let i18n = i18n.clone();
+        Callback::from(move |e: Event| {
+            if let Some(select) = e.target_dyn_into::<HtmlSelectElement>()
+                && let Some(locale) = Locale::from_tag(&select.value())
+            {
+                i18n.set_locale(locale);
+            }
+        })
+    };
+
+    html! {
+        <select
+            {onchange}
+            class="h-10 px-2 text-sm rounded-lg bg-gray-200 dark:bg-gray-700 text-gray-700 dark:text-gray-200 hover:bg-gray-300 dark:hover:bg-gray-600 transition-colors"
+            aria-label={i18n.t("common.language")}
+        >
+            {for Locale::ALL.iter().map(|locale| html! {
+                <option value={locale.code()} selected={*locale == i18n.locale}>
+                    {locale.native_name()}
+                </option>
+            })}
+        </select>
+    }
+}
@@ -0,0 +1,277 @@
+//! Saved conversation list for the playground sidebar
+
+use crate::i18n::use_i18n;
+use crate::services::{Conversation, ConversationService, ConversationSummary};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{Blob, BlobPropertyBag, HtmlAnchorElement, HtmlInputElement, Url};
+use yew::prelude::*;
+
+const MARKDOWN_MIME: &str = "text/markdown";
+
+#[derive(Properties, PartialEq)]
+pub struct ConversationListProps {
+    /// Conversation currently loaded in the playground
+    pub active: Option<String>,
+    /// Bumped by the parent whenever the list should be refetched
+    pub refresh: u32,
+    pub on_select: Callback<Conversation>,
+    pub on_new: Callback<()>,
+    pub on_deleted: Callback<String>,
+    pub on_error: Callback<String>,
+}
+
+/// Turn a title into a safe file name
+fn file_name(title: &str) -> String {
+    let slug: String = title
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let slug = slug.trim_matches('-');
+    if slug.is_empty() {
+        "conversation.md".to_string()
+    } else {
+        format!("{slug}.md")
+    }
+}
+
+/// Offer the conversation to the browser as a markdown download
+fn download_markdown(conversation: &Conversation) -> Result<(), JsValue> {
+    let parts = js_sys::Array::of1(&JsValue::from_str(&conversation.to_markdown()));
+    let options = BlobPropertyBag::new();
+    options.set_type(MARKDOWN_MIME);
+    let blob = Blob::new_with_str_sequence_and_options(&parts, &options)?;
+    let url = Url::create_object_url_with_blob(&blob)?;
+
+    let document = web_sys::window()
+        .and_then(|w| w.document())
+        .ok_or_else(|| JsValue::from_str("document unavailable"))?;
+    let anchor: HtmlAnchorElement = document.create_element("a")?.dyn_into()?;
+    anchor.set_href(&url);
+    anchor.set_download(&file_name(&conversation.title));
+    anchor.click();
+
+    Url::revoke_object_url(&url)
+}
+
+#[function_component(ConversationList)]
+pub fn conversation_list(props: &ConversationListProps) -> Html {
+    let i18n = use_i18n();
+    let conversations = use_state(Vec::<ConversationSummary>::new);
+    let renaming = use_state(|| None::<(String, String)>);
+
+    {
+        let conversations = conversations.clone();
+        let on_error = props.on_error.clone();
+        use_effect_with(props.refresh, move |_| {
+            spawn_local(async move {
+                match ConversationService::list().await {
+                    Ok(list) => conversations.set(list),
+                    Err(e) => on_error.emit(format!("Failed to load conversations: {e}")),
+                }
+            });
+        });
+    }
+
+    let on_open = {
+        let on_select = props.on_select.clone();
+        let on_error = props.on_error.clone();
+        move |id: String| {
+            let on_select = on_select.clone();
+            let on_error = on_error.clone();
+            Callback::from(move |_: MouseEvent| {
+                let id = id.clone();
+                let on_select = on_select.clone();
+                let on_error = on_error.clone();
+                spawn_local(async move {
+                    match ConversationService::get(&id).await {
+                        Ok(conversation) => on_select.emit(conversation),
+                        Err(e) => on_error.emit(format!("Failed to open conversation: {e}")),
+                    }
+                });
+            })
+        }
+    };
+
+    let on_export = {
+        let on_error = props.on_error.clone();
+        move |id: String| {
+            let on_error = on_error.clone();
+            Callback::from(move |_: MouseEvent| {
+                let id = id.clone();
+                let on_error = on_error.clone();
+                spawn_local(async move {
+                    let result = ConversationService::get(&id)
+                        .await
+                        .map_err(|e| e.to_string())
+                        .and_then(|c| download_markdown(&c).map_err(|e| format!("{e:?}")));
+                    if let Err(e) = result {
+                        on_error.emit(format!("Failed to export conversation: {e}"));
+                    }
+                });
+            })
+        }
+    };
+
+    let on_delete = {
+        let conversations = conversations.clone();
+        let on_deleted = props.on_deleted.clone();
+        let on_error = props.on_error.clone();
+        move |id: String| {
+            let conversations = conversations.clone();
+            let on_deleted = on_deleted.clone();
+            let on_error = on_error.clone();
+            Callback::from(move |_: MouseEvent| {
+                let confirmed = web_sys::window()
+                    .and_then(|w| w.confirm_with_message("Delete this conversation?").ok())
+                    .unwrap_or(false);
+                if !confirmed {
+                    return;
+                }
+                let id = id.clone();
+                let conversations = conversations.clone();
+                let on_deleted = on_deleted.clone();
+                let on_error = on_error.clone();
+                spawn_local(async move {
+                    match ConversationService::delete(&id).await {
+                        Ok(()) => {
+                            conversations.set(
+                                conversations
+                                    .iter()
+                                    .filter(|c| c.id != id)
+                                    .cloned()
+                                    .collect(),
+                            );
+                            on_deleted.emit(id);
+                        }
+                        Err(e) => on_error.emit(format!("Failed to delete conversation: {e}")),
+                    }
+                });
+            })
+        }
+    };
+
+    let on_rename_submit = {
+        let conversations = conversations.clone();
+        let renaming = renaming.clone();
+        let on_error = props.on_error.clone();
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            let Some((id, title)) = (*renaming).clone() else {
+                return;
+            };
+            renaming.set(None);
+            if title.trim().is_empty() {
+                return;
+            }
+            let conversations = conversations.clone();
+            let on_error = on_error.clone();
+            spawn_local(async move {
+                match ConversationService::rename(&id, title.trim()).await {
+                    Ok(updated) => conversations.set(
+                        conversations
+                            .iter()
+                            .cloned()
+                            .map(|mut c| {
+                                if c.id == updated.id {
+                                    c.title = updated.title.clone();
+                                }
+                                c
+                            })
+                            .collect(),
+                    ),
+                    Err(e) => on_error.emit(format!("Failed to rename conversation: {e}")),
+                }
+            });
+        })
+    };
+
+    let button_class =
+        "text-xs text-gray-500 dark:text-gray-400 hover:text-gray-800 dark:hover:text-gray-200";
+
+    html! {
+        <div class="mb-4">
+            <div class="flex justify-between items-center mb-2">
+                <span class="text-sm font-medium text-gray-700 dark:text-gray-300">{i18n.t("chat.conversations")}</span>
+                <button
+                    onclick={props.on_new.reform(|_| ())}
+                    class="text-sm text-blue-600 dark:text-blue-400 hover:underline"
+                >
+                    {i18n.t("chat.new_conversation")}
+                </button>
+            </div>
+            if conversations.is_empty() {
+                <p class="text-xs text-gray-500 dark:text-gray-400">{i18n.t("chat.no_conversations")}</p>
+            } else {
+                <ul class="space-y-1 max-h-60 overflow-y-auto">
+                    {conversations.iter().map(|c| {
+                        let is_active = props.active.as_ref() == Some(&c.id);
+                        let is_renaming = renaming.as_ref().is_some_and(|(id, _)| id == &c.id);
+                        let item_class = if is_active {
+                            "rounded px-2 py-1 bg-blue-50 dark:bg-blue-900"
+                        } else {
+                            "rounded px-2 py-1 hover:bg-gray-50 dark:hover:bg-gray-700"
+                        };
+                        html! {
+                            <li key={c.id.clone()} class={item_class}>
+                                if is_renaming {
+                                    <form onsubmit={on_rename_submit.clone()}>
+                                        <input
+                                            type="text"
+                                            aria-label={i18n.t("chat.conversation_title")}
+                                            class="w-full p-1 border border-gray-300 dark:border-gray-600 dark:bg-gray-700 dark:text-gray-200 rounded text-sm"
+                                            value={renaming.as_ref().map(|(_, t)| t.clone()).unwrap_or_default()}
+                                            oninput={{
+                                                let renaming = renaming.clone();
+                                                let id = c.id.clone();
+                                                Callback::from(move |e: InputEvent| {
+                                                    let input: HtmlInputElement = e.target_unchecked_into();
+                                                    renaming.set(Some((id.clone(), input.value())));
+                                                })
+                                            }}
+                                        />
+                                    </form>
+                                } else {
+                                    <button
+                                        onclick={on_open(c.id.clone())}
+                                        class="w-full text-left text-sm text-gray-800 dark:text-gray-200 truncate"
+                                        title={c.title.clone()}
+                                    >
+                                        {&c.title}
+                                    </button>
+                                }
+                                <div class="flex gap-2 mt-1">
+                                    <span class="text-xs text-gray-400 flex-1">
+                                        {i18n.t_with("chat.message_count", &[("count", &c.message_count.to_string())])}
+                                    </span>
+                                    <button
+                                        class={button_class}
+                                        onclick={{
+                                            let renaming = renaming.clone();
+                                            let entry = (c.id.clone(), c.title.clone());
+                                            Callback::from(move |_| renaming.set(Some(entry.clone())))
+                                        }}
+                                    >
+                                        {i18n.t("chat.rename")}
+                                    </button>
+                                    <button class={button_class} onclick={on_export(c.id.clone())}>
+                                        {i18n.t("chat.export")}
+                                    </button>
+                                    <button class={button_class} onclick={on_delete(c.id.clone())}>
+                                        {i18n.t("chat.delete")}
+                                    </button>
+                                </div>
+                            </li>
+                        }
+                    }).collect::<Html>()}
+                </ul>
+            }
+        </div>
+    }
+}
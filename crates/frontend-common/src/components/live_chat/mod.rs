@@ -0,0 +1,761 @@
+mod conversations;
+mod state;
+
+use crate::i18n::use_i18n;
+use crate::services::{
+    ChatMessage, Conversation, ConversationService, GenerationParams, InferenceService, Model,
+    PreferencesService, PreferencesUpdate, Role, StreamEvent,
+};
+use conversations::ConversationList;
+use gate_chat_ui::{
+    components::ChatInput,
+    types::{ChatResponse, Provider as UIProvider},
+    ChatContainer,
+};
+use state::{PaneState, PlaygroundAction, PlaygroundState, Pricing, DEFAULT_CURRENCY, PANE_COUNT};
+use std::collections::HashMap;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement};
+use yew::prelude::*;
+
+const DEFAULT_TEMPERATURE: f32 = 0.7;
+const DEFAULT_MAX_TOKENS: &str = "1000";
+const SETTINGS_PANEL_ID: &str = "playground-settings";
+const SETTINGS_TITLE_ID: &str = "playground-settings-title";
+const MODEL_INPUT_ID: &str = "playground-model";
+/// Currencies offered for cost display
+const CURRENCIES: [&str; 6] = ["USD", "EUR", "GBP", "JPY", "CAD", "AUD"];
+
+/// Convert a pane's finished messages into API messages
+fn history(pane: &PaneState) -> Vec<ChatMessage> {
+    pane.messages
+        .iter()
+        .filter(|msg| !msg.is_streaming())
+        .map(|msg| ChatMessage {
+            role: match msg.role.as_str() {
+                "system" => Role::System,
+                "assistant" => Role::Assistant,
+                _ => Role::User,
+            },
+            content: msg.get_text_content().unwrap_or_default(),
+        })
+        .collect()
+}
+
+fn parse_price(value: &str) -> f64 {
+    value.trim().parse::<f64>().unwrap_or(0.0).max(0.0)
+}
+
+fn input_value(e: InputEvent) -> String {
+    e.target_unchecked_into::<HtmlInputElement>().value()
+}
+
+/// Save a preference change in the background, reporting failures in the sidebar
+fn save_preference(update: PreferencesUpdate, error: UseStateHandle<Option<String>>) {
+    spawn_local(async move {
+        if let Err(e) = PreferencesService::update(&update).await {
+            error.set(Some(format!("Failed to save preference: {e}")));
+        }
+    });
+}
+
+#[function_component(LiveChat)]
+pub fn live_chat() -> Html {
+    let i18n = use_i18n();
+    let playground = use_reducer(PlaygroundState::default);
+    let error = use_state(|| None::<String>);
+    let selected_model = use_state(|| None::<String>);
+    let compare_model = use_state(|| None::<String>);
+    let compare_mode = use_state(|| false);
+    let available_models = use_state(Vec::<Model>::new);
+    let models_loading = use_state(|| true); // Start as true to indicate initial load
+    let show_settings = use_state(|| true);
+    let manual_model_input = use_state(String::new);
+    let use_manual_model = use_state(|| false);
+    let temperature = use_state(|| DEFAULT_TEMPERATURE);
+    let max_tokens = use_state(|| DEFAULT_MAX_TOKENS.to_string());
+    let system_prompt = use_state(String::new);
+    let input_price = use_state(String::new);
+    let output_price = use_state(String::new);
+    let conversation_id = use_state(|| None::<String>);
+    let conversations_version = use_state(|| 0u32);
+    let pending_save = use_state(|| false);
+    let streaming = use_state(|| true);
+    let currency = use_state(|| DEFAULT_CURRENCY.to_string());
+    let default_model = use_state(|| None::<String>);
+
+    let primary_model = if *use_manual_model {
+        Some((*manual_model_input).trim().to_string()).filter(|m| !m.is_empty())
+    } else {
+        (*selected_model).clone()
+    };
+    let pane_models: [Option<String>; PANE_COUNT] = [primary_model, (*compare_model).clone()];
+    let active_panes = if *compare_mode { PANE_COUNT } else { 1 };
+    let is_loading = playground.panes.iter().any(|pane| pane.loading);
+
+    // Fetch preferences and models on component mount
+    {
+        let available_models = available_models.clone();
+        let models_loading = models_loading.clone();
+        let error = error.clone();
+        let selected_model = selected_model.clone();
+        let streaming = streaming.clone();
+        let currency = currency.clone();
+        let default_model = default_model.clone();
+
+        use_effect_with((), move |_| {
+            spawn_local(async move {
+                models_loading.set(true);
+                // Preferences are optional; fall back to the built-in defaults
+                let preferences = PreferencesService::get().await.unwrap_or_default();
+                streaming.set(preferences.streaming.unwrap_or(true));
+                if let Some(code) = preferences.currency {
+                    currency.set(code);
+                }
+                default_model.set(preferences.default_model.clone());
+
+                match InferenceService::get_models().await {
+                    Ok(models) => {
+                        web_sys::console::log_1(&format!("Fetched {} models", models.len()).into());
+
+                        // Prefer the user's default model, then Qwen, if available
+                        let preferred = preferences
+                            .default_model
+                            .filter(|id| models.iter().any(|m| &m.id == id));
+                        if selected_model.is_none() && preferred.is_some() {
+                            selected_model.set(preferred);
+                        } else if selected_model.is_none() {
+                            if let Some(qwen_model) = models.iter().find(|m| m.id.contains("Qwen"))
+                            {
+                                selected_model.set(Some(qwen_model.id.clone()));
+                                web_sys::console::log_1(
+                                    &format!("Set default model to: {}", qwen_model.id).into(),
+                                );
+                            }
+                        }
+
+                        available_models.set(models);
+                    }
+                    Err(e) => {
+                        // Auth errors are handled automatically by the client wrapper
+                        web_sys::console::error_1(&format!("Failed to fetch models: {e}").into());
+                        error.set(Some(format!("Failed to fetch models: {e}")));
+                    }
+                }
+                models_loading.set(false);
+            });
+        });
+    }
+
+    // Save the first pane once a reply has finished
+    {
+        let conversation_id = conversation_id.clone();
+        let conversations_version = conversations_version.clone();
+        let pending_save = pending_save.clone();
+        let error = error.clone();
+        let messages = playground.panes[0].messages.clone();
+        let model = pane_models[0].clone();
+        use_effect_with(playground.panes[0].loading, move |loading| {
+            if !*loading && *pending_save && !messages.is_empty() {
+                pending_save.set(false);
+                spawn_local(async move {
+                    let saved = match &*conversation_id {
+                        Some(id) => {
+                            ConversationService::update_messages(id, model.as_deref(), &messages)
+                                .await
+                        }
+                        None => ConversationService::create(model.as_deref(), &messages).await,
+                    };
+                    match saved {
+                        Ok(conversation) => {
+                            conversation_id.set(Some(conversation.id));
+                            conversations_version.set(*conversations_version + 1);
+                        }
+                        Err(e) => error.set(Some(format!("Failed to save conversation: {e}"))),
+                    }
+                });
+            }
+        });
+    }
+
+    let on_send_message = {
+        let playground = playground.clone();
+        let error = error.clone();
+        let pane_models = pane_models.clone();
+        let temperature = *temperature;
+        let max_tokens = (*max_tokens).clone();
+        let system_prompt = (*system_prompt).clone();
+        let input_price = (*input_price).clone();
+        let output_price = (*output_price).clone();
+        let pending_save = pending_save.clone();
+        let buffered = !*streaming;
+        let currency = (*currency).clone();
+
+        Callback::from(move |user_message: String| {
+            if user_message.trim().is_empty() || is_loading {
+                return;
+            }
+
+            let targets: Vec<(usize, String)> = match pane_models[..active_panes]
+                .iter()
+                .enumerate()
+                .map(|(pane, model)| model.clone().map(|model| (pane, model)))
+                .collect::<Option<Vec<_>>>()
+            {
+                Some(targets) => targets,
+                None => {
+                    error.set(Some(if active_panes > 1 {
+                        "Please select a model for both panes".to_string()
+                    } else {
+                        "Please select a model or enter one manually".to_string()
+                    }));
+                    return;
+                }
+            };
+            error.set(None);
+            pending_save.set(true);
+
+            let params = GenerationParams {
+                temperature: Some(temperature),
+                max_tokens: max_tokens.trim().parse().ok(),
+                system_prompt: Some(system_prompt.trim().to_string()).filter(|s| !s.is_empty()),
+                buffered,
+            };
+            let pricing = Some(Pricing {
+                input_per_million: parse_price(&input_price),
+                output_per_million: parse_price(&output_price),
+                currency: currency.clone(),
+            })
+            .filter(|p| p.input_per_million > 0.0 || p.output_per_million > 0.0);
+
+            for (pane, model) in targets {
+                let mut messages = history(&playground.panes[pane]);
+                messages.push(ChatMessage {
+                    role: Role::User,
+                    content: user_message.clone(),
+                });
+                playground.dispatch(PlaygroundAction::Send {
+                    pane,
+                    prompt: user_message.clone(),
+                });
+
+                let dispatcher = playground.dispatcher();
+                let params = params.clone();
+                let pricing = pricing.clone();
+                spawn_local(async move {
+                    let provider = InferenceService::detect_provider(&model);
+                    let on_event = {
+                        let dispatcher = dispatcher.clone();
+                        move |event| match event {
+                            StreamEvent::Delta(text) => {
+                                dispatcher.dispatch(PlaygroundAction::Delta { pane, text })
+                            }
+                            StreamEvent::Reasoning(text) => {
+                                dispatcher.dispatch(PlaygroundAction::Reasoning { pane, text })
+                            }
+                            StreamEvent::ToolCall {
+                                index,
+                                id,
+                                name,
+                                arguments,
+                            } => dispatcher.dispatch(PlaygroundAction::ToolCall {
+                                pane,
+                                index,
+                                id,
+                                name,
+                                arguments,
+                            }),
+                            StreamEvent::Usage {
+                                input_tokens,
+                                output_tokens,
+                            } => dispatcher.dispatch(PlaygroundAction::Usage {
+                                pane,
+                                input_tokens,
+                                output_tokens,
+                            }),
+                        }
+                    };
+
+                    match InferenceService::stream_chat(provider, model, messages, params, on_event)
+                        .await
+                    {
+                        Ok(()) => dispatcher.dispatch(PlaygroundAction::Finish { pane, pricing }),
+                        Err(e) => {
+                            // Auth errors are handled automatically by the client wrapper
+                            web_sys::console::error_1(&format!("API Error: {e}").into());
+                            dispatcher.dispatch(PlaygroundAction::Fail {
+                                pane,
+                                error: format!("API Error: {e}"),
+                            });
+                        }
+                    }
+                });
+            }
+        })
+    };
+
+    let model_select = |id: &'static str,
+                        value: &Option<String>,
+                        on_change: Callback<Option<String>>| {
+        let onchange = Callback::from(move |e: Event| {
+            if let Some(select) = e.target_dyn_into::<HtmlSelectElement>() {
+                let value = select.value();
+                on_change.emit(Some(value).filter(|v| !v.is_empty()));
+            }
+        });
+        html! {
+            <select
+                {onchange}
+                id={id}
+                class="w-full p-2 border border-gray-300 dark:border-gray-600 dark:bg-gray-700 dark:text-gray-200 rounded text-sm"
+                value={value.clone().unwrap_or_default()}
+            >
+                <option value="">{i18n.t("chat.select_model")}</option>
+                {available_models.iter().map(|model| {
+                    html! {
+                        <option value={model.id.clone()} selected={Some(&model.id) == value.as_ref()}>
+                            {format!("{} ({})", model.id, model.owned_by)}
+                        </option>
+                    }
+                }).collect::<Html>()}
+            </select>
+        }
+    };
+
+    let on_model_change = {
+        let selected_model = selected_model.clone();
+        Callback::from(move |model: Option<String>| selected_model.set(model))
+    };
+
+    let on_compare_model_change = {
+        let compare_model = compare_model.clone();
+        Callback::from(move |model: Option<String>| compare_model.set(model))
+    };
+
+    let toggle_settings = {
+        let show_settings = show_settings.clone();
+        Callback::from(move |_| {
+            show_settings.set(!*show_settings);
+        })
+    };
+
+    let toggle_compare = {
+        let compare_mode = compare_mode.clone();
+        Callback::from(move |_| compare_mode.set(!*compare_mode))
+    };
+
+    let clear_chat = {
+        let playground = playground.clone();
+        let error = error.clone();
+        let conversation_id = conversation_id.clone();
+        Callback::from(move |()| {
+            playground.dispatch(PlaygroundAction::Clear);
+            conversation_id.set(None);
+            error.set(None);
+        })
+    };
+
+    let on_select_conversation = {
+        let playground = playground.clone();
+        let conversation_id = conversation_id.clone();
+        let selected_model = selected_model.clone();
+        let compare_mode = compare_mode.clone();
+        let error = error.clone();
+        Callback::from(move |conversation: Conversation| {
+            if conversation.model.is_some() {
+                selected_model.set(conversation.model.clone());
+            }
+            compare_mode.set(false);
+            playground.dispatch(PlaygroundAction::Load {
+                messages: conversation.messages,
+            });
+            conversation_id.set(Some(conversation.id));
+            error.set(None);
+        })
+    };
+
+    let on_conversation_deleted = {
+        let playground = playground.clone();
+        let conversation_id = conversation_id.clone();
+        Callback::from(move |id: String| {
+            if conversation_id.as_deref() == Some(id.as_str()) {
+                playground.dispatch(PlaygroundAction::Clear);
+                conversation_id.set(None);
+            }
+        })
+    };
+
+    let on_conversation_error = {
+        let error = error.clone();
+        Callback::from(move |message: String| error.set(Some(message)))
+    };
+
+    let on_manual_model_change = {
+        let manual_model_input = manual_model_input.clone();
+        Callback::from(move |e: InputEvent| manual_model_input.set(input_value(e)))
+    };
+
+    let on_toggle_manual_model = {
+        let use_manual_model = use_manual_model.clone();
+        Callback::from(move |_| {
+            use_manual_model.set(!*use_manual_model);
+        })
+    };
+
+    let on_temperature_change = {
+        let temperature = temperature.clone();
+        Callback::from(move |e: InputEvent| {
+            if let Ok(value) = input_value(e).parse() {
+                temperature.set(value);
+            }
+        })
+    };
+
+    let on_max_tokens_change = {
+        let max_tokens = max_tokens.clone();
+        Callback::from(move |e: InputEvent| max_tokens.set(input_value(e)))
+    };
+
+    let on_system_prompt_change = {
+        let system_prompt = system_prompt.clone();
+        Callback::from(move |e: InputEvent| {
+            system_prompt.set(e.target_unchecked_into::<HtmlTextAreaElement>().value());
+        })
+    };
+
+    let on_input_price_change = {
+        let input_price = input_price.clone();
+        Callback::from(move |e: InputEvent| input_price.set(input_value(e)))
+    };
+
+    let on_output_price_change = {
+        let output_price = output_price.clone();
+        Callback::from(move |e: InputEvent| output_price.set(input_value(e)))
+    };
+
+    let on_streaming_change = {
+        let streaming = streaming.clone();
+        let error = error.clone();
+        Callback::from(move |e: Event| {
+            let enabled = e.target_unchecked_into::<HtmlInputElement>().checked();
+            streaming.set(enabled);
+            save_preference(
+                PreferencesUpdate {
+                    streaming: Some(enabled),
+                    ..Default::default()
+                },
+                error.clone(),
+            );
+        })
+    };
+
+    let on_currency_change = {
+        let currency = currency.clone();
+        let error = error.clone();
+        Callback::from(move |e: Event| {
+            if let Some(select) = e.target_dyn_into::<HtmlSelectElement>() {
+                let code = select.value();
+                currency.set(code.clone());
+                save_preference(
+                    PreferencesUpdate {
+                        currency: Some(code),
+                        ..Default::default()
+                    },
+                    error.clone(),
+                );
+            }
+        })
+    };
+
+    let on_set_default_model = {
+        let default_model = default_model.clone();
+        let error = error.clone();
+        let model = pane_models[0].clone();
+        Callback::from(move |_: MouseEvent| {
+            default_model.set(model.clone());
+            save_preference(
+                PreferencesUpdate {
+                    default_model: Some(model.clone()),
+                    ..Default::default()
+                },
+                error.clone(),
+            );
+        })
+    };
+    let is_default_model = pane_models[0].is_some() && pane_models[0] == *default_model;
+
+    let render_pane = |index: usize| {
+        let pane = &playground.panes[index];
+        let model = pane_models[index]
+            .clone()
+            .unwrap_or_else(|| i18n.t("chat.no_model_selected"));
+        let chat_response = ChatResponse {
+            id: format!("live-chat-{index}"),
+            provider: UIProvider::OpenAI,
+            model: model.clone(),
+            messages: pane.messages.clone(),
+            usage: None,
+            metadata: HashMap::new(),
+        };
+        html! {
+            <div class="flex-1 min-w-0 flex flex-col border-r last:border-r-0 border-gray-200 dark:border-gray-700">
+                if active_panes > 1 {
+                    <div class="px-3 py-2 text-xs font-semibold text-gray-600 dark:text-gray-400 bg-gray-50 dark:bg-gray-900 border-b border-gray-200 dark:border-gray-700">
+                        {model}
+                    </div>
+                }
+                if let Some(err) = &pane.error {
+                    <div class="bg-red-50 dark:bg-red-900 text-red-700 dark:text-red-300 p-2 text-sm">
+                        {err}
+                    </div>
+                }
+                <ChatContainer
+                    chat_response={chat_response}
+                    show_metadata={false}
+                    show_input={false}
+                />
+            </div>
+        }
+    };
+
+    let label_class = "block text-sm font-medium text-gray-700 dark:text-gray-300 mb-2";
+    let field_class = "w-full p-2 border border-gray-300 dark:border-gray-600 dark:bg-gray-700 dark:text-gray-200 rounded text-sm";
+
+    html! {
+        <div class="flex h-[calc(100vh-100px)] gap-4 p-4 bg-gray-100 dark:bg-gray-900">
+            if *show_settings {
+                <aside
+                    id={SETTINGS_PANEL_ID}
+                    class="w-[300px] bg-white dark:bg-gray-800 rounded-lg p-5 shadow-md overflow-y-auto"
+                    aria-labelledby={SETTINGS_TITLE_ID}
+                >
+                    <div class="flex justify-between items-center mb-4">
+                        <h2 id={SETTINGS_TITLE_ID} class="text-xl font-bold text-gray-800 dark:text-gray-200">{i18n.t("chat.settings_title")}</h2>
+                        <button
+                            type="button"
+                            aria-expanded="true"
+                            aria-controls={SETTINGS_PANEL_ID}
+                            onclick={toggle_settings.clone()}
+                            class="text-gray-600 dark:text-gray-400 hover:text-gray-800 dark:hover:text-gray-200 transition-colors"
+                            title={i18n.t("chat.hide_sidebar_title")}
+                        >
+                            {i18n.t("chat.hide_sidebar")}
+                        </button>
+                    </div>
+
+                    <ConversationList
+                        active={(*conversation_id).clone()}
+                        refresh={*conversations_version}
+                        on_select={on_select_conversation}
+                        on_new={clear_chat.clone()}
+                        on_deleted={on_conversation_deleted}
+                        on_error={on_conversation_error}
+                    />
+
+                    <div class="mb-4">
+                        <label class={label_class} for={MODEL_INPUT_ID}>
+                            {i18n.t("chat.model")}
+                        </label>
+
+                        // Toggle button for manual model input
+                        <div class="mb-2">
+                            <button
+                                type="button"
+                                onclick={on_toggle_manual_model}
+                                class="text-sm text-blue-600 dark:text-blue-400 hover:underline"
+                            >
+                                {if *use_manual_model { i18n.t("chat.use_model_list") } else { i18n.t("chat.enter_model_manually") }}
+                            </button>
+                        </div>
+
+                        if *use_manual_model {
+                            // Manual model input
+                            <input
+                                id={MODEL_INPUT_ID}
+                                type="text"
+                                placeholder={i18n.t("chat.model_placeholder")}
+                                value={(*manual_model_input).clone()}
+                                oninput={on_manual_model_change}
+                                class={field_class}
+                            />
+                        } else {
+                            // Model selector
+                            if *models_loading {
+                                <div class="w-full p-2 border border-gray-300 dark:border-gray-600 dark:bg-gray-700 dark:text-gray-400 rounded text-sm">
+                                    {i18n.t("chat.loading_models")}
+                                </div>
+                            } else if available_models.is_empty() {
+                                <div class="w-full p-2 border border-gray-300 dark:border-gray-600 dark:bg-gray-700 dark:text-gray-400 rounded text-sm">
+                                    {i18n.t("chat.no_models")}
+                                </div>
+                            } else {
+                                {model_select(MODEL_INPUT_ID, &selected_model, on_model_change)}
+                            }
+                            if !available_models.is_empty() {
+                                <p class="text-xs text-gray-500 dark:text-gray-400 mt-1">
+                                    {i18n.t_with("chat.models_available", &[("count", &available_models.len().to_string())])}
+                                </p>
+                            }
+                        }
+                        if pane_models[0].is_some() {
+                            <button
+                                type="button"
+                                onclick={on_set_default_model}
+                                disabled={is_default_model}
+                                class="mt-1 text-xs text-blue-600 dark:text-blue-400 hover:underline disabled:text-gray-500 disabled:no-underline"
+                            >
+                                {if is_default_model { i18n.t("chat.is_default_model") } else { i18n.t("chat.set_default_model") }}
+                            </button>
+                        }
+                    </div>
+
+                    <div class="mb-4">
+                        <label class="flex items-center gap-2 text-sm text-gray-700 dark:text-gray-300">
+                            <input type="checkbox" checked={*streaming} onchange={on_streaming_change} />
+                            {i18n.t("chat.stream_responses")}
+                        </label>
+                    </div>
+
+                    <div class="mb-4">
+                        <label class="flex items-center gap-2 text-sm text-gray-700 dark:text-gray-300">
+                            <input type="checkbox" checked={*compare_mode} onchange={toggle_compare} />
+                            {i18n.t("chat.compare")}
+                        </label>
+                        if *compare_mode {
+                            <div class="mt-2">
+                                {model_select("playground-compare-model", &compare_model, on_compare_model_change)}
+                            </div>
+                        }
+                    </div>
+
+                    <div class="mb-4">
+                        <label class={label_class} for="playground-temperature">
+                            {i18n.t_with("chat.temperature", &[("value", &format!("{:.1}", *temperature))])}
+                        </label>
+                        <input
+                            id="playground-temperature"
+                            type="range"
+                            min="0"
+                            max="2"
+                            step="0.1"
+                            value={temperature.to_string()}
+                            oninput={on_temperature_change}
+                            class="w-full"
+                        />
+                    </div>
+
+                    <div class="mb-4">
+                        <label class={label_class} for="playground-max-tokens">{i18n.t("chat.max_tokens")}</label>
+                        <input
+                            id="playground-max-tokens"
+                            type="number"
+                            min="1"
+                            value={(*max_tokens).clone()}
+                            oninput={on_max_tokens_change}
+                            class={field_class}
+                        />
+                    </div>
+
+                    <div class="mb-4">
+                        <label class={label_class} for="playground-system-prompt">{i18n.t("chat.system_prompt")}</label>
+                        <textarea
+                            id="playground-system-prompt"
+                            rows="4"
+                            placeholder={i18n.t("chat.system_prompt_placeholder")}
+                            value={(*system_prompt).clone()}
+                            oninput={on_system_prompt_change}
+                            class={field_class}
+                        />
+                    </div>
+
+                    <div class="mb-4">
+                        <label class={label_class} for="playground-currency">{i18n.t("chat.currency")}</label>
+                        <select
+                            id="playground-currency"
+                            onchange={on_currency_change}
+                            class={field_class}
+                        >
+                            {for CURRENCIES.iter().map(|code| html! {
+                                <option value={*code} selected={*code == currency.as_str()}>{*code}</option>
+                            })}
+                        </select>
+                    </div>
+
+                    <div class="mb-4">
+                        <label class={label_class}>{i18n.t_with("chat.price", &[("currency", currency.as_str())])}</label>
+                        <div class="grid grid-cols-2 gap-2">
+                            <input
+                                type="number"
+                                min="0"
+                                step="any"
+                                placeholder={i18n.t("chat.price_input")}
+                                aria-label={i18n.t("chat.price_input")}
+                                value={(*input_price).clone()}
+                                oninput={on_input_price_change}
+                                class={field_class}
+                            />
+                            <input
+                                type="number"
+                                min="0"
+                                step="any"
+                                placeholder={i18n.t("chat.price_output")}
+                                aria-label={i18n.t("chat.price_output")}
+                                value={(*output_price).clone()}
+                                oninput={on_output_price_change}
+                                class={field_class}
+                            />
+                        </div>
+                        <p class="text-xs text-gray-500 dark:text-gray-400 mt-1">
+                            {i18n.t("chat.price_hint")}
+                        </p>
+                    </div>
+
+                    <button
+                        type="button"
+                        onclick={clear_chat.reform(|_: MouseEvent| ())}
+                        class="w-full bg-gray-200 hover:bg-gray-300 dark:bg-gray-700 dark:hover:bg-gray-600 text-gray-700 dark:text-gray-300 px-4 py-2 rounded text-sm transition-colors mb-4"
+                    >
+                        {i18n.t("chat.clear")}
+                    </button>
+
+                    if let Some(err) = &*error {
+                        <div class="bg-red-50 dark:bg-red-900 text-red-700 dark:text-red-300 p-3 rounded text-sm mb-4" role="alert">
+                            {err}
+                        </div>
+                    }
+                </aside>
+            }
+
+            <div class="flex-1 bg-white dark:bg-gray-800 rounded-lg shadow-md overflow-hidden flex flex-col">
+                <div class="p-3 bg-gray-50 dark:bg-gray-900 border-b border-gray-200 dark:border-gray-700 flex justify-between items-center">
+                    <div class="flex items-center gap-2">
+                        if !*show_settings {
+                            <button
+                                type="button"
+                                aria-expanded="false"
+                                aria-controls={SETTINGS_PANEL_ID}
+                                onclick={toggle_settings}
+                                class="text-gray-600 dark:text-gray-400 hover:text-gray-800 dark:hover:text-gray-200 transition-colors"
+                            >
+                                {i18n.t("chat.show_sidebar")}
+                            </button>
+                        }
+                    </div>
+                    <span class="text-sm text-gray-500 dark:text-gray-400" role="status">
+                        if is_loading {
+                            {i18n.t("common.loading")}
+                        }
+                    </span>
+                </div>
+
+                <div class="flex-1 flex min-h-0">
+                    {for (0..active_panes).map(render_pane)}
+                </div>
+
+                <div class="flex-shrink-0">
+                    <ChatInput on_send={on_send_message} disabled={is_loading} />
+                </div>
+            </div>
+        </div>
+    }
+}
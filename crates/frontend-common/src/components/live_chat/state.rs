@@ -0,0 +1,234 @@
+//! Playground state shared by the chat panes
+
+use gate_chat_ui::types::ChatMessage as UIChatMessage;
+use serde_json::{json, Value};
+use std::rc::Rc;
+use yew::prelude::*;
+
+/// Number of panes available in comparison mode
+pub const PANE_COUNT: usize = 2;
+
+const STREAMING_KEY: &str = "is_streaming";
+const TOKENS_KEY: &str = "tokens";
+const COST_KEY: &str = "cost";
+const REASONING_KEY: &str = "reasoning_content";
+
+/// Currency costs are shown in unless the user picked another
+pub const DEFAULT_CURRENCY: &str = "USD";
+
+/// Price per million tokens, used to estimate the cost of each response
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Pricing {
+    pub input_per_million: f64,
+    pub output_per_million: f64,
+    /// ISO 4217 code the prices are given in
+    pub currency: String,
+}
+
+impl Pricing {
+    pub fn cost(&self, input_tokens: u32, output_tokens: u32) -> f64 {
+        (f64::from(input_tokens) * self.input_per_million
+            + f64::from(output_tokens) * self.output_per_million)
+            / 1_000_000.0
+    }
+
+    /// Cost formatted with the currency's symbol, or its code when it has no common symbol
+    pub fn format_cost(&self, input_tokens: u32, output_tokens: u32) -> String {
+        let cost = self.cost(input_tokens, output_tokens);
+        match self.currency.as_str() {
+            "USD" => format!("${cost:.6}"),
+            "EUR" => format!("€{cost:.6}"),
+            "GBP" => format!("£{cost:.6}"),
+            "JPY" => format!("¥{cost:.6}"),
+            code => format!("{cost:.6} {code}"),
+        }
+    }
+}
+
+/// One conversation against a single model
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PaneState {
+    pub messages: Vec<UIChatMessage>,
+    pub loading: bool,
+    pub error: Option<String>,
+    input_tokens: Option<u32>,
+    output_tokens: Option<u32>,
+}
+
+impl PaneState {
+    fn streaming_message(&mut self) -> Option<&mut UIChatMessage> {
+        self.messages
+            .last_mut()
+            .filter(|m| m.role == "assistant" && m.is_streaming())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlaygroundState {
+    pub panes: [PaneState; PANE_COUNT],
+}
+
+pub enum PlaygroundAction {
+    /// Append the user prompt and an empty streaming assistant reply
+    Send {
+        pane: usize,
+        prompt: String,
+    },
+    Delta {
+        pane: usize,
+        text: String,
+    },
+    Reasoning {
+        pane: usize,
+        text: String,
+    },
+    /// Merge a streamed tool call fragment into the reply's `tool_calls`
+    ToolCall {
+        pane: usize,
+        index: usize,
+        id: Option<String>,
+        name: Option<String>,
+        arguments: String,
+    },
+    Usage {
+        pane: usize,
+        input_tokens: Option<u32>,
+        output_tokens: Option<u32>,
+    },
+    /// Finalize the streaming reply, annotating it with tokens and cost
+    Finish {
+        pane: usize,
+        pricing: Option<Pricing>,
+    },
+    Fail {
+        pane: usize,
+        error: String,
+    },
+    Clear,
+    /// Replace the first pane with a saved conversation
+    Load {
+        messages: Vec<UIChatMessage>,
+    },
+}
+
+impl Reducible for PlaygroundState {
+    type Action = PlaygroundAction;
+
+    fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+        let mut state = (*self).clone();
+        match action {
+            PlaygroundAction::Send { pane, prompt } => {
+                let pane = &mut state.panes[pane];
+                let mut reply = UIChatMessage::assistant("");
+                reply
+                    .metadata
+                    .insert(STREAMING_KEY.to_string(), Value::Bool(true));
+                pane.messages.push(UIChatMessage::user(prompt));
+                pane.messages.push(reply);
+                pane.loading = true;
+                pane.error = None;
+                pane.input_tokens = None;
+                pane.output_tokens = None;
+            }
+            PlaygroundAction::Delta { pane, text } => {
+                if let Some(message) = state.panes[pane].streaming_message() {
+                    let mut content = message.get_text_content().unwrap_or_default();
+                    content.push_str(&text);
+                    message.content = Some(Value::String(content));
+                }
+            }
+            PlaygroundAction::Reasoning { pane, text } => {
+                if let Some(message) = state.panes[pane].streaming_message() {
+                    let mut reasoning = message
+                        .metadata
+                        .get(REASONING_KEY)
+                        .and_then(|v| v.as_str())
+                        .unwrap_or_default()
+                        .to_string();
+                    reasoning.push_str(&text);
+                    message
+                        .metadata
+                        .insert(REASONING_KEY.to_string(), Value::String(reasoning));
+                }
+            }
+            PlaygroundAction::ToolCall {
+                pane,
+                index,
+                id,
+                name,
+                arguments,
+            } => {
+                if let Some(message) = state.panes[pane].streaming_message() {
+                    let calls = message.tool_calls.get_or_insert_with(Vec::new);
+                    if calls.len() <= index {
+                        calls.resize_with(index + 1, || {
+                            json!({"id": "", "type": "function", "function": {"name": "", "arguments": ""}})
+                        });
+                    }
+                    let call = &mut calls[index];
+                    if let Some(id) = id {
+                        call["id"] = Value::String(id);
+                    }
+                    if let Some(name) = name {
+                        call["function"]["name"] = Value::String(name);
+                    }
+                    let mut merged = call["function"]["arguments"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string();
+                    merged.push_str(&arguments);
+                    call["function"]["arguments"] = Value::String(merged);
+                }
+            }
+            PlaygroundAction::Usage {
+                pane,
+                input_tokens,
+                output_tokens,
+            } => {
+                let pane = &mut state.panes[pane];
+                pane.input_tokens = input_tokens.or(pane.input_tokens);
+                pane.output_tokens = output_tokens.or(pane.output_tokens);
+            }
+            PlaygroundAction::Finish { pane, pricing } => {
+                let pane = &mut state.panes[pane];
+                let usage = pane.input_tokens.zip(pane.output_tokens);
+                if let Some(message) = pane.streaming_message() {
+                    message.metadata.remove(STREAMING_KEY);
+                    if let Some((input, output)) = usage {
+                        message.metadata.insert(
+                            TOKENS_KEY.to_string(),
+                            Value::String(format!("{input} in / {output} out")),
+                        );
+                        if let Some(pricing) = pricing {
+                            message.metadata.insert(
+                                COST_KEY.to_string(),
+                                Value::String(pricing.format_cost(input, output)),
+                            );
+                        }
+                    }
+                }
+                pane.loading = false;
+            }
+            PlaygroundAction::Fail { pane, error } => {
+                let pane = &mut state.panes[pane];
+                // Drop the empty reply but keep any partial output
+                if pane
+                    .streaming_message()
+                    .is_some_and(|m| m.get_text_content().unwrap_or_default().is_empty())
+                {
+                    pane.messages.pop();
+                } else if let Some(message) = pane.streaming_message() {
+                    message.metadata.remove(STREAMING_KEY);
+                }
+                pane.loading = false;
+                pane.error = Some(error);
+            }
+            PlaygroundAction::Clear => state = PlaygroundState::default(),
+            PlaygroundAction::Load { messages } => {
+                state = PlaygroundState::default();
+                state.panes[0].messages = messages;
+            }
+        }
+        Rc::new(state)
+    }
+}
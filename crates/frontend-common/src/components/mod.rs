@@ -1,11 +1,17 @@
+mod appearance_menu;
 mod bootstrap_prompt;
+mod language_select;
 mod live_chat;
+mod motion_toggle;
 mod reauth_modal;
 mod spinner;
 mod theme_toggle;
 
+pub use appearance_menu::AppearanceMenu;
 pub use bootstrap_prompt::BootstrapPrompt;
+pub use language_select::LanguageSelect;
 pub use live_chat::LiveChat;
+pub use motion_toggle::MotionToggle;
 pub use reauth_modal::ReauthModal;
 pub use spinner::LoadingSpinner as Spinner;
 pub use theme_toggle::ThemeToggle;
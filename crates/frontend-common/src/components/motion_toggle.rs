@@ -0,0 +1,33 @@
+//! Reduced-motion toggle component
+
+use crate::i18n::use_i18n;
+use crate::theme::use_reduced_motion;
+use yew::prelude::*;
+
+#[function_component(MotionToggle)]
+pub fn motion_toggle() -> Html {
+    let i18n = use_i18n();
+    let (reduced_motion, set_reduced_motion) = use_reduced_motion();
+
+    let onclick = Callback::from(move |_| set_reduced_motion.emit(!reduced_motion));
+    let label = i18n.t("common.reduce_motion");
+
+    html! {
+        <button
+            {onclick}
+            type="button"
+            class="relative inline-flex items-center justify-center w-10 h-10 p-2 rounded-lg bg-gray-200 dark:bg-gray-700 hover:bg-gray-300 dark:hover:bg-gray-600 transition-colors"
+            aria-pressed={reduced_motion.to_string()}
+            aria-label={label.clone()}
+            title={label}
+        >
+            <svg class="w-6 h-6 text-gray-600 dark:text-gray-300" fill="none" stroke="currentColor" viewBox="0 0 24 24" aria-hidden="true">
+                if reduced_motion {
+                    <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M10 9v6m4-6v6m7-3a9 9 0 11-18 0 9 9 0 0118 0z"/>
+                } else {
+                    <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M14.752 11.168l-3.197-2.132A1 1 0 0010 9.87v4.263a1 1 0 001.555.832l3.197-2.132a1 1 0 000-1.664zM21 12a9 9 0 11-18 0 9 9 0 0118 0z"/>
+                }
+            </svg>
+        </button>
+    }
+}
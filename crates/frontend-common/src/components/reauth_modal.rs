@@ -1,7 +1,7 @@
 //! Re-authentication modal component
 
 use crate::auth::{use_auth, AuthAction};
-use crate::hooks::{use_webauthn, WebAuthnState};
+use crate::hooks::{use_modal_focus, use_webauthn, WebAuthnState};
 use yew::prelude::*;
 
 /// Re-authentication modal that appears when session expires
@@ -55,36 +55,47 @@ pub fn reauth_modal() -> Html {
         });
     }
 
+    // The session cannot continue without re-authenticating, so Escape does nothing
+    let dialog_ref = use_node_ref();
+    let on_keydown = use_modal_focus(dialog_ref.clone(), auth.show_reauth_modal, Callback::noop());
+
     // Only render modal content if show_reauth_modal is true
     if !auth.show_reauth_modal {
         return html! {};
     }
 
     html! {
-        <div class="fixed inset-0 bg-black bg-opacity-50 flex items-center justify-center z-50">
-            <div class="bg-white dark:bg-gray-800 rounded-lg p-6 max-w-md w-full mx-4 shadow-xl">
+        <div class="fixed inset-0 bg-black bg-opacity-50 flex items-center justify-center z-50" onkeydown={on_keydown}>
+            <div
+                ref={dialog_ref}
+                class="bg-white dark:bg-gray-800 rounded-lg p-6 max-w-md w-full mx-4 shadow-xl"
+                role="alertdialog"
+                aria-modal="true"
+                aria-labelledby="reauth-title"
+                aria-describedby="reauth-description"
+            >
                 <div class="flex items-center mb-4">
-                    <svg class="w-8 h-8 text-yellow-500 mr-3" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                    <svg class="w-8 h-8 text-yellow-500 mr-3" fill="none" stroke="currentColor" viewBox="0 0 24 24" aria-hidden="true">
                         <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2"
                             d="M12 9v2m0 4h.01m-6.938 4h13.856c1.54 0 2.502-1.667 1.732-3L13.732 4c-.77-1.333-2.694-1.333-3.464 0L3.34 16c-.77 1.333.192 3 1.732 3z" />
                     </svg>
-                    <h2 class="text-xl font-bold text-gray-900 dark:text-white">
+                    <h2 id="reauth-title" class="text-xl font-bold text-gray-900 dark:text-white">
                         {"Session Expired"}
                     </h2>
                 </div>
 
-                <p class="text-gray-600 dark:text-gray-300 mb-6">
+                <p id="reauth-description" class="text-gray-600 dark:text-gray-300 mb-6">
                     {"Your session has expired. Please re-authenticate to continue using the application."}
                 </p>
 
                 if let Some(error) = &auth.error {
-                    <div class="mb-4 p-3 bg-red-50 dark:bg-red-900/30 text-red-700 dark:text-red-300 rounded text-sm">
+                    <div class="mb-4 p-3 bg-red-50 dark:bg-red-900/30 text-red-700 dark:text-red-300 rounded text-sm" role="alert">
                         {error}
                     </div>
                 }
 
                 if let WebAuthnState::Error(error) = webauthn.state() {
-                    <div class="mb-4 p-3 bg-red-50 dark:bg-red-900/30 text-red-700 dark:text-red-300 rounded text-sm">
+                    <div class="mb-4 p-3 bg-red-50 dark:bg-red-900/30 text-red-700 dark:text-red-300 rounded text-sm" role="alert">
                         {error}
                     </div>
                 }
@@ -97,7 +108,7 @@ pub fn reauth_modal() -> Html {
                            flex items-center justify-center"
                 >
                     if *is_authenticating {
-                        <svg class="animate-spin h-5 w-5 mr-2" xmlns="http://www.w3.org/2000/svg" fill="none" viewBox="0 0 24 24">
+                        <svg class="animate-spin motion-reduce:animate-none h-5 w-5 mr-2" aria-hidden="true" xmlns="http://www.w3.org/2000/svg" fill="none" viewBox="0 0 24 24">
                             <circle class="opacity-25" cx="12" cy="12" r="10" stroke="currentColor" stroke-width="4"></circle>
                             <path class="opacity-75" fill="currentColor" d="M4 12a8 8 0 018-8V0C5.373 0 0 5.373 0 12h4zm2 5.291A7.962 7.962 0 014 12H0c0 3.042 1.135 5.824 3 7.938l3-2.647z"></path>
                         </svg>
@@ -1,6 +1,6 @@
 //! Theme toggle component
 
-use crate::theme::{use_current_theme, use_theme_toggle, Theme};
+use crate::theme::{use_current_theme, use_theme_toggle};
 use yew::prelude::*;
 
 #[function_component(ThemeToggle)]
@@ -19,9 +19,10 @@ pub fn theme_toggle() -> Html {
         <button
             {onclick}
             class="relative inline-flex items-center justify-center w-10 h-10 p-2 rounded-lg bg-gray-200 dark:bg-gray-700 hover:bg-gray-300 dark:hover:bg-gray-600 transition-colors"
+            type="button"
             aria-label="Toggle theme"
         >
-            if current_theme == Theme::Light {
+            if !current_theme.is_dark() {
                 // Sun icon for light mode
                 <svg class="w-6 h-6 text-yellow-500" fill="currentColor" viewBox="0 0 20 20">
                     <path fill-rule="evenodd" d="M10 2a1 1 0 011 1v1a1 1 0 11-2 0V3a1 1 0 011-1zm4 8a4 4 0 11-8 0 4 4 0 018 0zm-.464 4.95l.707.707a1 1 0 001.414-1.414l-.707-.707a1 1 0 00-1.414 1.414zm2.12-10.607a1 1 0 010 1.414l-.706.707a1 1 0 11-1.414-1.414l.707-.707a1 1 0 011.414 0zM17 11a1 1 0 100-2h-1a1 1 0 100 2h1zm-7 4a1 1 0 011 1v1a1 1 0 11-2 0v-1a1 1 0 011-1zM5.05 6.464A1 1 0 106.465 5.05l-.708-.707a1 1 0 00-1.414 1.414l.707.707zm1.414 8.486l-.707.707a1 1 0 01-1.414-1.414l.707-.707a1 1 0 011.414 1.414zM4 11a1 1 0 100-2H3a1 1 0 000 2h1z" clip-rule="evenodd"/>
@@ -1,6 +1,8 @@
 //! Custom hooks for the application
 
 pub mod use_auth_callback;
+pub mod use_modal_focus;
 pub mod use_webauthn;
 
+pub use use_modal_focus::use_modal_focus;
 pub use use_webauthn::{use_webauthn, WebAuthnState};
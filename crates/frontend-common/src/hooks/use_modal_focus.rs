@@ -0,0 +1,48 @@
+//! Focus handling for modal dialogs
+
+use gate_chat_ui::utils::a11y::{active_element, focus_first, trap_focus};
+use wasm_bindgen::JsCast;
+use web_sys::{Element, HtmlElement};
+use yew::prelude::*;
+
+const KEY_ESCAPE: &str = "Escape";
+
+/// Focus management for a modal dialog rendered into `container`.
+///
+/// Focus moves into the dialog when `open` becomes true and returns to the
+/// previously focused element when it closes or unmounts. Attach the returned
+/// handler as the dialog's `onkeydown`: Escape calls `on_close` and Tab stays
+/// inside.
+#[hook]
+pub fn use_modal_focus(
+    container: NodeRef,
+    open: bool,
+    on_close: Callback<()>,
+) -> Callback<KeyboardEvent> {
+    {
+        let container = container.clone();
+        use_effect_with(open, move |open| {
+            let previous = open
+                .then(active_element)
+                .flatten()
+                .and_then(|el| el.dyn_into::<HtmlElement>().ok());
+            if *open && let Some(element) = container.cast::<Element>() {
+                focus_first(&element);
+            }
+            move || {
+                if let Some(previous) = previous {
+                    let _ = previous.focus();
+                }
+            }
+        });
+    }
+
+    Callback::from(move |e: KeyboardEvent| {
+        if e.key() == KEY_ESCAPE {
+            e.prevent_default();
+            on_close.emit(());
+        } else if let Some(element) = container.cast::<Element>() {
+            trap_focus(&element, &e);
+        }
+    })
+}
@@ -0,0 +1,83 @@
+//! Message catalogs bundled with the frontends
+//!
+//! Each catalog is a flat JSON object in `locales/` mapping message keys to
+//! text. Placeholders are written as `{name}`. Missing keys fall back to
+//! English, then to the key itself.
+
+use super::context::Locale;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+
+const EN_CATALOG: &str = include_str!("../../locales/en.json");
+const ES_CATALOG: &str = include_str!("../../locales/es.json");
+
+type Catalog = HashMap<String, String>;
+
+static CATALOGS: Lazy<HashMap<Locale, Catalog>> = Lazy::new(|| {
+    Locale::ALL
+        .into_iter()
+        .map(|locale| (locale, parse(locale)))
+        .collect()
+});
+
+fn source(locale: Locale) -> &'static str {
+    match locale {
+        Locale::En => EN_CATALOG,
+        Locale::Es => ES_CATALOG,
+    }
+}
+
+fn parse(locale: Locale) -> Catalog {
+    serde_json::from_str(source(locale)).unwrap_or_else(|e| {
+        tracing::warn!("Invalid {} message catalog: {e}", locale.code());
+        Catalog::new()
+    })
+}
+
+fn lookup(locale: Locale, key: &str) -> Option<&'static str> {
+    CATALOGS
+        .get(&locale)
+        .and_then(|catalog| catalog.get(key))
+        .map(String::as_str)
+}
+
+/// Translate a message key into the given locale
+pub fn translate(locale: Locale, key: &str) -> String {
+    lookup(locale, key)
+        .or_else(|| lookup(Locale::En, key))
+        .unwrap_or(key)
+        .to_string()
+}
+
+/// Translate a message key and fill its `{name}` placeholders
+pub fn translate_with(locale: Locale, key: &str, args: &[(&str, &str)]) -> String {
+    args.iter()
+        .fold(translate(locale, key), |text, (name, value)| {
+            text.replace(&format!("{{{name}}}"), value)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn test_catalogs_have_the_same_keys() {
+        let english: BTreeSet<_> = parse(Locale::En).into_keys().collect();
+        assert!(!english.is_empty());
+        for locale in Locale::ALL {
+            let keys: BTreeSet<_> = parse(locale).into_keys().collect();
+            assert_eq!(keys, english, "{} catalog keys differ", locale.code());
+        }
+    }
+
+    #[test]
+    fn test_translate_falls_back_and_fills_placeholders() {
+        assert_eq!(translate(Locale::Es, "no.such.key"), "no.such.key");
+        assert_eq!(
+            translate_with(Locale::En, "chat.models_available", &[("count", "3")]),
+            "3 models available"
+        );
+    }
+}
@@ -0,0 +1,73 @@
+//! Locale and translation context
+
+use super::catalog;
+use serde::{Deserialize, Serialize};
+use yew::prelude::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+pub enum Locale {
+    #[default]
+    En,
+    Es,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::En, Locale::Es];
+
+    /// BCP 47 language code
+    pub fn code(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Es => "es",
+        }
+    }
+
+    /// Name of the language in that language, for the language picker
+    pub fn native_name(&self) -> &'static str {
+        match self {
+            Locale::En => "English",
+            Locale::Es => "Español",
+        }
+    }
+
+    /// Match a language tag such as `es-MX` on its primary subtag
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let primary = tag.split(['-', '_']).next()?.to_ascii_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|locale| locale.code() == primary)
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct I18nContext {
+    pub locale: Locale,
+    set_locale: Callback<Locale>,
+}
+
+impl I18nContext {
+    pub fn new(locale: Locale, set_locale: Callback<Locale>) -> Self {
+        Self { locale, set_locale }
+    }
+
+    /// Switch the active locale
+    pub fn set_locale(&self, locale: Locale) {
+        self.set_locale.emit(locale);
+    }
+
+    /// Translate a message key
+    pub fn t(&self, key: &str) -> String {
+        catalog::translate(self.locale, key)
+    }
+
+    /// Translate a message key, filling `{name}` placeholders
+    pub fn t_with(&self, key: &str, args: &[(&str, &str)]) -> String {
+        catalog::translate_with(self.locale, key, args)
+    }
+}
+
+impl Default for I18nContext {
+    fn default() -> Self {
+        Self::new(Locale::default(), Callback::noop())
+    }
+}
@@ -0,0 +1,19 @@
+//! Internationalization: message catalogs, locale detection and switching
+
+mod catalog;
+mod context;
+mod provider;
+
+pub use catalog::{translate, translate_with};
+pub use context::{I18nContext, Locale};
+pub use provider::I18nProvider;
+
+use yew::prelude::*;
+
+/// Hook to access translations
+///
+/// Components rendered outside an [`I18nProvider`] get English.
+#[hook]
+pub fn use_i18n() -> I18nContext {
+    use_context::<I18nContext>().unwrap_or_default()
+}
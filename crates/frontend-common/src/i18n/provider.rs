@@ -0,0 +1,66 @@
+//! I18n provider component
+
+use super::context::{I18nContext, Locale};
+use yew::prelude::*;
+
+const LOCALE_STORAGE_KEY: &str = "locale";
+
+fn saved_locale() -> Option<Locale> {
+    let storage = web_sys::window()?.local_storage().ok()??;
+    let code = storage.get_item(LOCALE_STORAGE_KEY).ok()??;
+    Locale::from_tag(&code)
+}
+
+fn save_locale(locale: Locale) {
+    if let Some(window) = web_sys::window()
+        && let Ok(Some(storage)) = window.local_storage()
+    {
+        let _ = storage.set_item(LOCALE_STORAGE_KEY, locale.code());
+    }
+}
+
+/// Preferred language reported by the browser
+fn browser_locale() -> Option<Locale> {
+    let language = web_sys::window()?.navigator().language()?;
+    Locale::from_tag(&language)
+}
+
+/// Keep `<html lang>` in sync so assistive technology reads the right language
+fn update_document_locale(locale: Locale) {
+    if let Some(window) = web_sys::window()
+        && let Some(document) = window.document()
+        && let Some(element) = document.document_element()
+    {
+        let _ = element.set_attribute("lang", locale.code());
+    }
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct I18nProviderProps {
+    pub children: Children,
+}
+
+#[function_component(I18nProvider)]
+pub fn i18n_provider(props: &I18nProviderProps) -> Html {
+    // A saved choice wins over the browser's language
+    let locale = use_state(|| saved_locale().or_else(browser_locale).unwrap_or_default());
+
+    use_effect_with(*locale, |locale| {
+        update_document_locale(*locale);
+        || ()
+    });
+
+    let set_locale = {
+        let locale = locale.clone();
+        Callback::from(move |next: Locale| {
+            save_locale(next);
+            locale.set(next);
+        })
+    };
+
+    html! {
+        <ContextProvider<I18nContext> context={I18nContext::new(*locale, set_locale)}>
+            { props.children.clone() }
+        </ContextProvider<I18nContext>>
+    }
+}
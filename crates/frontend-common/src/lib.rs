@@ -6,11 +6,15 @@ pub mod client_wrapper;
 pub mod components;
 pub mod config;
 pub mod hooks;
+pub mod i18n;
 pub mod services;
 pub mod theme;
 
 pub use auth::context::AuthContext;
 pub use client::{create_authenticated_client, create_public_client};
-pub use components::{LiveChat, Spinner, ThemeToggle};
+pub use components::{
+    AppearanceMenu, LanguageSelect, LiveChat, MotionToggle, Spinner, ThemeToggle,
+};
 pub use config::AuthConfig;
+pub use i18n::{I18nContext, I18nProvider, Locale};
 pub use theme::{Theme, ThemeContext, ThemeProvider};
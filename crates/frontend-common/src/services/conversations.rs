@@ -0,0 +1,179 @@
+//! Saved conversation service for the chat playground
+
+use crate::client::create_authenticated_client;
+use gate_chat_ui::types::ChatMessage as UIChatMessage;
+use gate_http::client::error::ClientError;
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+
+const CONVERSATIONS_PATH: &str = "/api/conversations";
+
+/// Conversation entry as shown in the conversation list
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ConversationSummary {
+    pub id: String,
+    pub title: String,
+    pub model: Option<String>,
+    pub message_count: usize,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ConversationListResponse {
+    conversations: Vec<ConversationSummary>,
+}
+
+/// Full conversation including its messages
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Conversation {
+    pub id: String,
+    pub title: String,
+    pub model: Option<String>,
+    pub messages: Vec<UIChatMessage>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl Conversation {
+    /// Render the conversation as a markdown document
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!("# {}\n\n", self.title);
+        if let Some(model) = &self.model {
+            markdown.push_str(&format!("_Model: {model}_\n\n"));
+        }
+        for message in &self.messages {
+            let content = message.get_text_content().unwrap_or_default();
+            markdown.push_str(&format!(
+                "## {}\n\n{}\n\n",
+                capitalize(&message.role),
+                content.trim()
+            ));
+        }
+        markdown
+    }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct SaveConversationRequest<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    model: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    messages: Option<&'a [UIChatMessage]>,
+}
+
+pub struct ConversationService;
+
+impl ConversationService {
+    /// List the current user's conversations, most recent first
+    pub async fn list() -> Result<Vec<ConversationSummary>, ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        let response: ConversationListResponse = client
+            .execute(client.request(Method::GET, CONVERSATIONS_PATH)?)
+            .await?;
+
+        Ok(response.conversations)
+    }
+
+    /// Fetch a conversation with its messages
+    pub async fn get(id: &str) -> Result<Conversation, ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        client
+            .execute(client.request(Method::GET, &format!("{CONVERSATIONS_PATH}/{id}"))?)
+            .await
+    }
+
+    /// Save a new conversation; the server derives a title when none is given
+    pub async fn create(
+        model: Option<&str>,
+        messages: &[UIChatMessage],
+    ) -> Result<Conversation, ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        let body = SaveConversationRequest {
+            title: None,
+            model,
+            messages: Some(messages),
+        };
+        client
+            .execute(
+                client
+                    .request(Method::POST, CONVERSATIONS_PATH)?
+                    .json(&body),
+            )
+            .await
+    }
+
+    /// Replace the messages of an existing conversation
+    pub async fn update_messages(
+        id: &str,
+        model: Option<&str>,
+        messages: &[UIChatMessage],
+    ) -> Result<Conversation, ClientError> {
+        Self::update(
+            id,
+            &SaveConversationRequest {
+                title: None,
+                model,
+                messages: Some(messages),
+            },
+        )
+        .await
+    }
+
+    /// Rename a conversation
+    pub async fn rename(id: &str, title: &str) -> Result<Conversation, ClientError> {
+        Self::update(
+            id,
+            &SaveConversationRequest {
+                title: Some(title),
+                model: None,
+                messages: None,
+            },
+        )
+        .await
+    }
+
+    /// Delete a conversation
+    pub async fn delete(id: &str) -> Result<(), ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        let _: serde_json::Value = client
+            .execute(client.request(Method::DELETE, &format!("{CONVERSATIONS_PATH}/{id}"))?)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn update(
+        id: &str,
+        body: &SaveConversationRequest<'_>,
+    ) -> Result<Conversation, ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        client
+            .execute(
+                client
+                    .request(Method::PATCH, &format!("{CONVERSATIONS_PATH}/{id}"))?
+                    .json(body),
+            )
+            .await
+    }
+}
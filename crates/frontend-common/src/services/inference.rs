@@ -1,9 +1,15 @@
 //! Inference service for communicating with LLM endpoints
 
 use crate::client::create_authenticated_client;
+use futures::StreamExt;
 use gate_http::client::error::ClientError;
 use serde::{Deserialize, Serialize};
-use serde_json::Value as JsonValue;
+use serde_json::{json, Value as JsonValue};
+
+/// Anthropic requires max_tokens; used when the caller doesn't set one
+const DEFAULT_ANTHROPIC_MAX_TOKENS: u32 = 1024;
+const SSE_DATA_PREFIX: &str = "data:";
+const SSE_DONE: &str = "[DONE]";
 
 /// Message role
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -82,6 +88,232 @@ pub struct ModelsResponse {
     pub data: Vec<Model>,
 }
 
+/// Sampling parameters and system prompt applied to a chat request
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GenerationParams {
+    pub temperature: Option<f32>,
+    pub max_tokens: Option<u32>,
+    pub system_prompt: Option<String>,
+    /// Request the complete response at once instead of streaming it
+    pub buffered: bool,
+}
+
+/// Incremental event from a streamed chat response
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamEvent {
+    /// Newly generated text
+    Delta(String),
+    /// Newly generated reasoning ("thinking") text
+    Reasoning(String),
+    /// Fragment of a tool call; `arguments` is appended to earlier fragments
+    /// with the same index
+    ToolCall {
+        index: usize,
+        id: Option<String>,
+        name: Option<String>,
+        arguments: String,
+    },
+    /// Token counts; either side may arrive in a separate event
+    Usage {
+        input_tokens: Option<u32>,
+        output_tokens: Option<u32>,
+    },
+}
+
+/// Splits a server-sent event byte stream into `data:` payloads, keeping
+/// partial lines buffered across chunks
+#[derive(Debug, Default)]
+struct SseBuffer {
+    pending: Vec<u8>,
+}
+
+impl SseBuffer {
+    fn feed(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.pending.extend_from_slice(chunk);
+        let mut payloads = Vec::new();
+        while let Some(pos) = self.pending.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            if let Some(data) = line.trim_end().strip_prefix(SSE_DATA_PREFIX) {
+                payloads.push(data.trim_start().to_string());
+            }
+        }
+        payloads
+    }
+}
+
+/// Extract stream events from an OpenAI or Anthropic streaming chunk
+fn stream_events(chunk: &JsonValue) -> Vec<StreamEvent> {
+    let mut events = Vec::new();
+    let as_u32 = |v: Option<&JsonValue>| v.and_then(|v| v.as_u64()).map(|v| v as u32);
+
+    let as_string = |v: Option<&JsonValue>| v.and_then(|v| v.as_str()).map(str::to_string);
+
+    // OpenAI chat completion chunks
+    if let Some(delta) = chunk.pointer("/choices/0/delta") {
+        if let Some(text) = as_string(delta.get("reasoning_content").or(delta.get("reasoning"))) {
+            events.push(StreamEvent::Reasoning(text));
+        }
+        if let Some(text) = as_string(delta.get("content")) {
+            events.push(StreamEvent::Delta(text));
+        }
+        for (position, call) in delta
+            .get("tool_calls")
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+            .enumerate()
+        {
+            events.push(StreamEvent::ToolCall {
+                index: call
+                    .get("index")
+                    .and_then(|v| v.as_u64())
+                    .map_or(position, |i| i as usize),
+                id: as_string(call.get("id")),
+                name: as_string(call.pointer("/function/name")),
+                arguments: as_string(call.pointer("/function/arguments")).unwrap_or_default(),
+            });
+        }
+    }
+
+    // Anthropic message events
+    let block_index = chunk
+        .get("index")
+        .and_then(|v| v.as_u64())
+        .unwrap_or_default() as usize;
+    match chunk.get("type").and_then(|t| t.as_str()) {
+        Some("content_block_start") => {
+            if let Some(block) = chunk
+                .get("content_block")
+                .filter(|b| b.get("type").and_then(|t| t.as_str()) == Some("tool_use"))
+            {
+                events.push(StreamEvent::ToolCall {
+                    index: block_index,
+                    id: as_string(block.get("id")),
+                    name: as_string(block.get("name")),
+                    arguments: String::new(),
+                });
+            }
+        }
+        Some("content_block_delta") => {
+            let delta = chunk.get("delta");
+            match delta.and_then(|d| d.get("type")).and_then(|t| t.as_str()) {
+                Some("thinking_delta") => {
+                    if let Some(text) = as_string(delta.and_then(|d| d.get("thinking"))) {
+                        events.push(StreamEvent::Reasoning(text));
+                    }
+                }
+                Some("input_json_delta") => events.push(StreamEvent::ToolCall {
+                    index: block_index,
+                    id: None,
+                    name: None,
+                    arguments: as_string(delta.and_then(|d| d.get("partial_json")))
+                        .unwrap_or_default(),
+                }),
+                _ => {
+                    if let Some(text) = as_string(delta.and_then(|d| d.get("text"))) {
+                        events.push(StreamEvent::Delta(text));
+                    }
+                }
+            }
+        }
+        Some("message_start") => events.push(StreamEvent::Usage {
+            input_tokens: as_u32(chunk.pointer("/message/usage/input_tokens")),
+            output_tokens: None,
+        }),
+        Some("message_delta") => events.push(StreamEvent::Usage {
+            input_tokens: None,
+            output_tokens: as_u32(chunk.pointer("/usage/output_tokens")),
+        }),
+        _ => {
+            if let Some(usage) = chunk.get("usage").filter(|u| u.is_object()) {
+                events.push(StreamEvent::Usage {
+                    input_tokens: as_u32(usage.get("prompt_tokens")),
+                    output_tokens: as_u32(usage.get("completion_tokens")),
+                });
+            }
+        }
+    }
+
+    events
+}
+
+/// Extract events from a complete (non-streamed) OpenAI or Anthropic response
+fn response_events(response: &JsonValue) -> Vec<StreamEvent> {
+    let mut events = Vec::new();
+    let as_u32 = |v: Option<&JsonValue>| v.and_then(|v| v.as_u64()).map(|v| v as u32);
+    let as_string = |v: Option<&JsonValue>| v.and_then(|v| v.as_str()).map(str::to_string);
+
+    // OpenAI chat completion
+    if let Some(message) = response.pointer("/choices/0/message") {
+        if let Some(text) = as_string(
+            message
+                .get("reasoning_content")
+                .or(message.get("reasoning")),
+        ) {
+            events.push(StreamEvent::Reasoning(text));
+        }
+        if let Some(text) = as_string(message.get("content")) {
+            events.push(StreamEvent::Delta(text));
+        }
+        for (index, call) in message
+            .get("tool_calls")
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+            .enumerate()
+        {
+            events.push(StreamEvent::ToolCall {
+                index,
+                id: as_string(call.get("id")),
+                name: as_string(call.pointer("/function/name")),
+                arguments: as_string(call.pointer("/function/arguments")).unwrap_or_default(),
+            });
+        }
+        events.push(StreamEvent::Usage {
+            input_tokens: as_u32(response.pointer("/usage/prompt_tokens")),
+            output_tokens: as_u32(response.pointer("/usage/completion_tokens")),
+        });
+        return events;
+    }
+
+    // Anthropic message
+    for (index, block) in response
+        .get("content")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .enumerate()
+    {
+        match block.get("type").and_then(|t| t.as_str()) {
+            Some("thinking") => {
+                if let Some(text) = as_string(block.get("thinking")) {
+                    events.push(StreamEvent::Reasoning(text));
+                }
+            }
+            Some("tool_use") => events.push(StreamEvent::ToolCall {
+                index,
+                id: as_string(block.get("id")),
+                name: as_string(block.get("name")),
+                arguments: block
+                    .get("input")
+                    .map(JsonValue::to_string)
+                    .unwrap_or_default(),
+            }),
+            _ => {
+                if let Some(text) = as_string(block.get("text")) {
+                    events.push(StreamEvent::Delta(text));
+                }
+            }
+        }
+    }
+    events.push(StreamEvent::Usage {
+        input_tokens: as_u32(response.pointer("/usage/input_tokens")),
+        output_tokens: as_u32(response.pointer("/usage/output_tokens")),
+    });
+    events
+}
+
 /// Inference service for making LLM API calls
 pub struct InferenceService;
 
@@ -173,6 +405,103 @@ impl InferenceService {
         }
     }
 
+    /// Stream a chat response, invoking `on_event` for each text delta and usage report
+    ///
+    /// With `params.buffered` the complete response is requested and replayed as events.
+    pub async fn stream_chat(
+        provider: Provider,
+        model: String,
+        messages: Vec<ChatMessage>,
+        params: GenerationParams,
+        mut on_event: impl FnMut(StreamEvent),
+    ) -> Result<(), ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        let (path, mut body) = match provider {
+            Provider::OpenAI => {
+                let mut api_messages = Vec::new();
+                if let Some(system) = &params.system_prompt {
+                    api_messages.push(json!({ "role": Role::System, "content": system }));
+                }
+                api_messages.extend(
+                    messages
+                        .iter()
+                        .map(|m| json!({ "role": m.role, "content": m.content })),
+                );
+                (
+                    "/v1/chat/completions",
+                    json!({
+                        "model": model,
+                        "messages": api_messages,
+                        "temperature": params.temperature,
+                        "max_tokens": params.max_tokens,
+                        "stream": !params.buffered,
+                        "stream_options": (!params.buffered).then(|| json!({ "include_usage": true })),
+                    }),
+                )
+            }
+            Provider::Anthropic => {
+                let api_messages: Vec<JsonValue> = messages
+                    .iter()
+                    .filter(|m| !matches!(m.role, Role::System))
+                    .map(|m| json!({ "role": m.role, "content": m.content }))
+                    .collect();
+                (
+                    "/v1/messages",
+                    json!({
+                        "model": model,
+                        "messages": api_messages,
+                        "system": params.system_prompt,
+                        "temperature": params.temperature,
+                        "max_tokens": params.max_tokens.unwrap_or(DEFAULT_ANTHROPIC_MAX_TOKENS),
+                        "stream": !params.buffered,
+                    }),
+                )
+            }
+        };
+        if let Some(fields) = body.as_object_mut() {
+            fields.retain(|_, v| !v.is_null());
+        }
+
+        let response = client
+            .request(reqwest::Method::POST, path)?
+            .json(&body)
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.text().await.unwrap_or_else(|_| status.to_string());
+            let error = ClientError::from_status(status, message);
+            if error.is_auth_expired() {
+                crate::auth::error_handler::trigger_auth_error();
+            }
+            return Err(error);
+        }
+
+        if params.buffered {
+            let value: JsonValue = response.json().await?;
+            response_events(&value).into_iter().for_each(&mut on_event);
+            return Ok(());
+        }
+
+        let mut buffer = SseBuffer::default();
+        let mut chunks = response.bytes_stream();
+        while let Some(chunk) = chunks.next().await {
+            for payload in buffer.feed(&chunk?) {
+                if payload == SSE_DONE {
+                    continue;
+                }
+                match serde_json::from_str::<JsonValue>(&payload) {
+                    Ok(value) => stream_events(&value).into_iter().for_each(&mut on_event),
+                    Err(e) => tracing::debug!("Skipping unparseable stream chunk: {e}"),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     /// Parse response based on provider format
     pub fn parse_response(provider: Provider, response: &JsonValue) -> Option<String> {
         match provider {
@@ -1,11 +1,15 @@
 pub mod api_wrapper;
 pub mod auth;
 pub mod bootstrap;
+pub mod conversations;
 pub mod inference;
+pub mod preferences;
 pub mod webauthn_browser;
 
 pub use api_wrapper::{handle_api_error, with_auth_error_handling};
 pub use auth::AuthApiService;
 pub use bootstrap::{BootstrapService, BootstrapStatus};
-pub use inference::{ChatMessage, InferenceService, Model, Role};
+pub use conversations::{Conversation, ConversationService, ConversationSummary};
+pub use inference::{ChatMessage, GenerationParams, InferenceService, Model, Role, StreamEvent};
+pub use preferences::{Preferences, PreferencesService, PreferencesUpdate};
 pub use webauthn_browser::WebAuthnBrowserService;
@@ -0,0 +1,68 @@
+//! Per-user preferences stored by the daemon
+
+use crate::client::create_authenticated_client;
+use crate::theme::Theme;
+use gate_http::client::error::ClientError;
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+
+const PREFERENCES_PATH: &str = "/api/preferences";
+
+/// The current user's preferences; unset fields use the client default
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Preferences {
+    #[serde(default)]
+    pub default_model: Option<String>,
+    #[serde(default)]
+    pub theme: Option<Theme>,
+    #[serde(default)]
+    pub accent_color: Option<String>,
+    #[serde(default)]
+    pub streaming: Option<bool>,
+    /// ISO 4217 code used when displaying costs
+    #[serde(default)]
+    pub currency: Option<String>,
+}
+
+/// Partial update; `None` leaves a field unchanged, `Some(None)` clears it
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PreferencesUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_model: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub theme: Option<Theme>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accent_color: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub streaming: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+}
+
+pub struct PreferencesService;
+
+impl PreferencesService {
+    /// Fetch the current user's preferences
+    pub async fn get() -> Result<Preferences, ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        client
+            .execute(client.request(Method::GET, PREFERENCES_PATH)?)
+            .await
+    }
+
+    /// Update some of the current user's preferences
+    pub async fn update(update: &PreferencesUpdate) -> Result<Preferences, ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        client
+            .execute(
+                client
+                    .request(Method::PATCH, PREFERENCES_PATH)?
+                    .json(update),
+            )
+            .await
+    }
+}
@@ -0,0 +1,70 @@
+//! User-defined accent colours
+
+/// CSS variable the accent colour ramp is derived from
+pub const ACCENT_VAR: &str = "--color-accent";
+/// CSS variable for text drawn on top of the accent colour
+pub const ACCENT_CONTRAST_VAR: &str = "--color-accent-contrast";
+
+/// Default accent, matching the stylesheet's `--color-accent`
+pub const DEFAULT_ACCENT: &str = "#2563eb";
+
+/// Accent colours offered in the appearance menu, as (message key, colour)
+pub const ACCENT_PRESETS: [(&str, &str); 6] = [
+    ("theme.accent.blue", DEFAULT_ACCENT),
+    ("theme.accent.purple", "#7c3aed"),
+    ("theme.accent.green", "#16a34a"),
+    ("theme.accent.orange", "#ea580c"),
+    ("theme.accent.pink", "#db2777"),
+    ("theme.accent.slate", "#475569"),
+];
+
+/// Parse a `#rrggbb` colour into its channels
+pub fn parse_hex_color(value: &str) -> Option<(u8, u8, u8)> {
+    let hex = value.strip_prefix('#')?;
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+/// Black or white, whichever reads better on the given accent colour
+pub fn contrast_color(accent: &str) -> Option<&'static str> {
+    let (r, g, b) = parse_hex_color(accent)?;
+    // WCAG relative luminance
+    let linear = |c: u8| {
+        let c = f64::from(c) / 255.0;
+        if c <= 0.03928 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    let luminance = 0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b);
+    // Contrast against white is (1.05) / (L + 0.05), against black (L + 0.05) / 0.05
+    Some(if luminance > 0.179 {
+        "#000000"
+    } else {
+        "#ffffff"
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_only_six_digit_hex() {
+        assert_eq!(parse_hex_color("#2563eb"), Some((0x25, 0x63, 0xeb)));
+        assert_eq!(parse_hex_color("#FFF"), None);
+        assert_eq!(parse_hex_color("2563eb"), None);
+        assert_eq!(parse_hex_color("#zzzzzz"), None);
+    }
+
+    #[test]
+    fn picks_readable_text_color() {
+        assert_eq!(contrast_color("#2563eb"), Some("#ffffff"));
+        assert_eq!(contrast_color("#facc15"), Some("#000000"));
+        assert_eq!(contrast_color("not a colour"), None);
+    }
+}
@@ -1,34 +1,93 @@
 //! Theme context definition
 
+use super::accent::{contrast_color, parse_hex_color, ACCENT_CONTRAST_VAR, ACCENT_VAR};
+use gate_chat_ui::utils::a11y::REDUCE_MOTION_CLASS;
 use serde::{Deserialize, Serialize};
 use std::rc::Rc;
 use wasm_bindgen::JsCast;
 use yew::prelude::*;
 
+/// Class on `<html>` that switches the design tokens to the high-contrast set
+const HIGH_CONTRAST_CLASS: &str = "high-contrast";
+const DARK_CLASS: &str = "dark";
+
+// Older builds stored the variant names, so accept those too
 #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
 pub enum Theme {
     #[default]
+    #[serde(alias = "Light")]
     Light,
+    #[serde(alias = "Dark")]
     Dark,
+    /// Dark theme with maximum contrast and a visible focus ring
+    HighContrast,
 }
 
 impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::Light, Theme::Dark, Theme::HighContrast];
+
     pub fn toggle(&self) -> Self {
         match self {
             Theme::Light => Theme::Dark,
-            Theme::Dark => Theme::Light,
+            Theme::Dark | Theme::HighContrast => Theme::Light,
+        }
+    }
+
+    /// Whether `dark:` styles apply
+    pub fn is_dark(&self) -> bool {
+        matches!(self, Theme::Dark | Theme::HighContrast)
+    }
+
+    /// Message catalog key for the theme's name
+    pub fn label_key(&self) -> &'static str {
+        match self {
+            Theme::Light => "theme.light",
+            Theme::Dark => "theme.dark",
+            Theme::HighContrast => "theme.high_contrast",
         }
     }
 }
 
+/// localStorage key for the selected theme
+pub(crate) const THEME_KEY: &str = "theme";
+/// localStorage key for the custom accent colour
+pub(crate) const ACCENT_KEY: &str = "accent";
+/// localStorage key for the reduced-motion setting
+pub(crate) const REDUCED_MOTION_KEY: &str = "reduced_motion";
+
 #[derive(Clone, Debug, PartialEq, Default)]
 pub struct ThemeContext {
     pub theme: Theme,
+    /// Custom accent colour as `#rrggbb`; `None` keeps the built-in blue
+    pub accent: Option<String>,
+    /// Animations and transitions are switched off
+    pub reduced_motion: bool,
 }
 
 pub enum ThemeAction {
     Set(Theme),
     Toggle,
+    SetAccent(Option<String>),
+    SetReducedMotion(bool),
+}
+
+fn store(key: &str, value: Option<&str>) {
+    if let Some(storage) =
+        web_sys::window().and_then(|window| window.local_storage().ok().flatten())
+    {
+        let _ = match value {
+            Some(value) => storage.set_item(key, value),
+            None => storage.remove_item(key),
+        };
+    }
+}
+
+fn store_theme(theme: Theme) {
+    store(
+        THEME_KEY,
+        Some(&serde_json::to_string(&theme).unwrap_or_default()),
+    );
 }
 
 impl Reducible for ThemeContext {
@@ -37,57 +96,95 @@ impl Reducible for ThemeContext {
     fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
         match action {
             ThemeAction::Set(theme) => {
-                // Save to localStorage
-                if let Some(window) = web_sys::window() {
-                    if let Ok(Some(storage)) = window.local_storage() {
-                        let _ = storage
-                            .set_item("theme", &serde_json::to_string(&theme).unwrap_or_default());
-                    }
-                }
-
-                // Update document class
+                store_theme(theme);
                 update_document_theme(theme);
 
-                Rc::new(Self { theme })
+                Rc::new(Self {
+                    theme,
+                    ..(*self).clone()
+                })
             }
             ThemeAction::Toggle => {
-                let new_theme = self.theme.toggle();
-
-                // Save to localStorage
-                if let Some(window) = web_sys::window() {
-                    if let Ok(Some(storage)) = window.local_storage() {
-                        let _ = storage.set_item(
-                            "theme",
-                            &serde_json::to_string(&new_theme).unwrap_or_default(),
-                        );
-                    }
-                }
-
-                // Update document class
-                update_document_theme(new_theme);
-
-                Rc::new(Self { theme: new_theme })
+                let theme = self.theme.toggle();
+                store_theme(theme);
+                update_document_theme(theme);
+
+                Rc::new(Self {
+                    theme,
+                    ..(*self).clone()
+                })
+            }
+            ThemeAction::SetAccent(accent) => {
+                // Ignore anything that is not a `#rrggbb` colour
+                let accent = accent.filter(|accent| parse_hex_color(accent).is_some());
+                store(ACCENT_KEY, accent.as_deref());
+                update_document_accent(accent.as_deref());
+
+                Rc::new(Self {
+                    accent,
+                    ..(*self).clone()
+                })
+            }
+            ThemeAction::SetReducedMotion(reduced_motion) => {
+                store(REDUCED_MOTION_KEY, Some(&reduced_motion.to_string()));
+                update_document_motion(reduced_motion);
+
+                Rc::new(Self {
+                    reduced_motion,
+                    ..(*self).clone()
+                })
             }
         }
     }
 }
 
+fn document_element() -> Option<web_sys::Element> {
+    web_sys::window()?.document()?.document_element()
+}
+
 pub fn update_document_theme(theme: Theme) {
-    if let Some(window) = web_sys::window() {
-        if let Some(document) = window.document() {
-            if let Some(element) = document.document_element() {
-                if let Ok(html_element) = element.dyn_into::<web_sys::HtmlElement>() {
-                    let class_list = html_element.class_list();
-                    match theme {
-                        Theme::Dark => {
-                            let _ = class_list.add_1("dark");
-                        }
-                        Theme::Light => {
-                            let _ = class_list.remove_1("dark");
-                        }
-                    }
-                }
-            }
+    if let Some(element) = document_element() {
+        let class_list = element.class_list();
+        let _ = if theme.is_dark() {
+            class_list.add_1(DARK_CLASS)
+        } else {
+            class_list.remove_1(DARK_CLASS)
+        };
+        let _ = if theme == Theme::HighContrast {
+            class_list.add_1(HIGH_CONTRAST_CLASS)
+        } else {
+            class_list.remove_1(HIGH_CONTRAST_CLASS)
+        };
+    }
+}
+
+/// Point the accent tokens at a custom colour, or back at the stylesheet default
+pub fn update_document_accent(accent: Option<&str>) {
+    let Some(element) = document_element().and_then(|e| e.dyn_into::<web_sys::HtmlElement>().ok())
+    else {
+        return;
+    };
+    let style = element.style();
+    match accent.and_then(|accent| Some((accent, contrast_color(accent)?))) {
+        Some((accent, contrast)) => {
+            let _ = style.set_property(ACCENT_VAR, accent);
+            let _ = style.set_property(ACCENT_CONTRAST_VAR, contrast);
+        }
+        None => {
+            let _ = style.remove_property(ACCENT_VAR);
+            let _ = style.remove_property(ACCENT_CONTRAST_VAR);
         }
     }
 }
+
+/// Toggle the class that disables animations across the app
+pub fn update_document_motion(reduced_motion: bool) {
+    if let Some(element) = document_element() {
+        let class_list = element.class_list();
+        let _ = if reduced_motion {
+            class_list.add_1(REDUCE_MOTION_CLASS)
+        } else {
+            class_list.remove_1(REDUCE_MOTION_CLASS)
+        };
+    }
+}
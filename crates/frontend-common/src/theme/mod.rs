@@ -1,11 +1,15 @@
 //! Theme management module
 
+pub mod accent;
 mod context;
 mod provider;
 
 pub use context::{Theme, ThemeAction, ThemeContext};
 pub use provider::ThemeProvider;
 
+use crate::services::preferences::{PreferencesService, PreferencesUpdate};
+use std::cell::RefCell;
+use std::rc::Rc;
 use yew::prelude::*;
 
 /// Hook to access theme context
@@ -30,3 +34,87 @@ pub fn use_theme_toggle() -> Callback<()> {
         theme_ctx.dispatch(ThemeAction::Toggle);
     })
 }
+
+/// Hook to get whether reduced motion is switched on, and a setter
+#[hook]
+pub fn use_reduced_motion() -> (bool, Callback<bool>) {
+    let theme_ctx = use_theme();
+    let reduced_motion = theme_ctx.reduced_motion;
+    let set = Callback::from(move |value| {
+        theme_ctx.dispatch(ThemeAction::SetReducedMotion(value));
+    });
+    (reduced_motion, set)
+}
+
+/// Hook to get the custom accent colour, and a setter (`None` restores the default)
+#[hook]
+pub fn use_accent() -> (Option<String>, Callback<Option<String>>) {
+    let theme_ctx = use_theme();
+    let accent = theme_ctx.accent.clone();
+    let set = Callback::from(move |value| {
+        theme_ctx.dispatch(ThemeAction::SetAccent(value));
+    });
+    (accent, set)
+}
+
+/// Keep the theme and accent in sync with the signed-in user's saved preferences
+///
+/// Once `authenticated` is true the server copy is applied, and later changes are
+/// saved back. Until then settings only live in localStorage.
+#[hook]
+pub fn use_theme_sync(authenticated: bool) {
+    let theme_ctx = use_theme();
+    // Last theme and accent known to match the server; `None` until loaded
+    let synced: Rc<RefCell<Option<(Theme, Option<String>)>>> = use_mut_ref(|| None);
+
+    {
+        let theme_ctx = theme_ctx.clone();
+        let synced = synced.clone();
+        use_effect_with(authenticated, move |authenticated| {
+            if *authenticated {
+                wasm_bindgen_futures::spawn_local(async move {
+                    let preferences = match PreferencesService::get().await {
+                        Ok(preferences) => preferences,
+                        Err(e) => {
+                            tracing::warn!("Failed to load preferences: {e}");
+                            return;
+                        }
+                    };
+                    let theme = preferences.theme.unwrap_or(theme_ctx.theme);
+                    let accent = preferences
+                        .accent_color
+                        .or_else(|| theme_ctx.accent.clone());
+                    *synced.borrow_mut() = Some((theme, accent.clone()));
+                    theme_ctx.dispatch(ThemeAction::Set(theme));
+                    theme_ctx.dispatch(ThemeAction::SetAccent(accent));
+                });
+            } else {
+                *synced.borrow_mut() = None;
+            }
+            || ()
+        });
+    }
+
+    use_effect_with(
+        (theme_ctx.theme, theme_ctx.accent.clone()),
+        move |(theme, accent)| {
+            let update = match synced.borrow().as_ref() {
+                Some((synced_theme, synced_accent)) => PreferencesUpdate {
+                    theme: (synced_theme != theme).then_some(*theme),
+                    accent_color: (synced_accent != accent).then(|| accent.clone()),
+                    ..Default::default()
+                },
+                None => PreferencesUpdate::default(),
+            };
+            if update.theme.is_some() || update.accent_color.is_some() {
+                *synced.borrow_mut() = Some((*theme, accent.clone()));
+                wasm_bindgen_futures::spawn_local(async move {
+                    if let Err(e) = PreferencesService::update(&update).await {
+                        tracing::warn!("Failed to save preferences: {e}");
+                    }
+                });
+            }
+            || ()
+        },
+    );
+}
@@ -1,7 +1,10 @@
 //! Theme provider component
 
-use super::context::{Theme, ThemeAction, ThemeContext};
-use wasm_bindgen::JsCast;
+use super::accent::parse_hex_color;
+use super::context::{
+    update_document_accent, update_document_motion, update_document_theme, Theme, ThemeAction,
+    ThemeContext, ACCENT_KEY, REDUCED_MOTION_KEY, THEME_KEY,
+};
 use yew::prelude::*;
 
 #[derive(Properties, Clone, PartialEq)]
@@ -9,66 +12,67 @@ pub struct ThemeProviderProps {
     pub children: Children,
 }
 
+fn stored(key: &str) -> Option<String> {
+    web_sys::window()?
+        .local_storage()
+        .ok()
+        .flatten()?
+        .get_item(key)
+        .ok()
+        .flatten()
+}
+
 #[function_component(ThemeProvider)]
 pub fn theme_provider(props: &ThemeProviderProps) -> Html {
     let theme = use_reducer(|| {
-        // Try to load theme from localStorage
-        let saved_theme = if let Some(window) = web_sys::window() {
-            if let Ok(Some(storage)) = window.local_storage() {
-                if let Ok(Some(theme_str)) = storage.get_item("theme") {
-                    serde_json::from_str(&theme_str).ok()
-                } else {
-                    None
-                }
-            } else {
-                None
-            }
-        } else {
-            None
-        };
+        // Saved settings win; the server copy is applied once the user signs in
+        let theme: Theme = stored(THEME_KEY)
+            .and_then(|theme| serde_json::from_str(&theme).ok())
+            .unwrap_or_default();
+        update_document_theme(theme);
 
-        // Use saved theme or default
-        let theme = saved_theme.unwrap_or_default();
+        let accent = stored(ACCENT_KEY).filter(|accent| parse_hex_color(accent).is_some());
+        update_document_accent(accent.as_deref());
 
-        // Apply theme to document
-        if let Some(window) = web_sys::window()
-            && let Some(document) = window.document()
-            && let Some(element) = document.document_element()
-            && let Ok(html_element) = element.dyn_into::<web_sys::HtmlElement>()
-        {
-            let class_list = html_element.class_list();
-            match theme {
-                Theme::Dark => {
-                    let _ = class_list.add_1("dark");
-                }
-                Theme::Light => {
-                    let _ = class_list.remove_1("dark");
-                }
-            }
-        }
+        // An explicit choice is saved; otherwise follow the system setting
+        let reduced_motion = stored(REDUCED_MOTION_KEY)
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(false);
+        update_document_motion(reduced_motion);
 
-        ThemeContext { theme }
+        ThemeContext {
+            theme,
+            accent,
+            reduced_motion,
+        }
     });
 
     // Set up system theme preference detection
     {
         let theme = theme.clone();
         use_effect_with((), move |_| {
-            // Only set system preference if no saved theme
-            if let Some(window) = web_sys::window() {
-                if let Ok(Some(storage)) = window.local_storage() {
-                    if storage.get_item("theme").unwrap_or(None).is_none() {
-                        // Check system preference using JavaScript evaluation
-                        if let Ok(matches) = js_sys::eval(
-                    "window.matchMedia && window.matchMedia('(prefers-color-scheme: dark)').matches",
-                ) {
-                    if let Some(dark_mode) = matches.as_bool() {
-                        if dark_mode {
-                            theme.dispatch(ThemeAction::Set(Theme::Dark));
-                        }
-                    }
-                }
-                    }
+            // Only follow the system preference if no theme was saved
+            if stored(THEME_KEY).is_none() {
+                let prefers_dark = web_sys::window()
+                    .and_then(|window| {
+                        window
+                            .match_media("(prefers-color-scheme: dark)")
+                            .ok()
+                            .flatten()
+                    })
+                    .is_some_and(|query| query.matches());
+                let prefers_contrast = web_sys::window()
+                    .and_then(|window| {
+                        window
+                            .match_media("(prefers-contrast: more)")
+                            .ok()
+                            .flatten()
+                    })
+                    .is_some_and(|query| query.matches());
+                if prefers_contrast {
+                    theme.dispatch(ThemeAction::Set(Theme::HighContrast));
+                } else if prefers_dark {
+                    theme.dispatch(ThemeAction::Set(Theme::Dark));
                 }
             }
             || ()
@@ -0,0 +1,255 @@
+//! API key management container component
+
+use super::create_form::CreateApiKeyForm;
+use super::list::ApiKeyList;
+use crate::services::api_keys::{ApiKeyInfo, ApiKeyService, CreateApiKeyRequest};
+use gloo::timers::callback::Timeout;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+#[function_component(ApiKeysContainer)]
+pub fn api_keys_container() -> Html {
+    let key_service = use_memo((), |_| ApiKeyService::new());
+
+    let keys = use_state(Vec::<ApiKeyInfo>::new);
+    let is_loading = use_state(|| true);
+    let show_form = use_state(|| false);
+    let is_submitting = use_state(|| false);
+    let created_key = use_state(|| Option::<String>::None);
+    let error = use_state(|| Option::<String>::None);
+    let success = use_state(|| Option::<String>::None);
+
+    let reload_keys = {
+        let keys = keys.clone();
+        let is_loading = is_loading.clone();
+        let error = error.clone();
+        let key_service = key_service.clone();
+
+        Callback::from(move |_: ()| {
+            let keys = keys.clone();
+            let is_loading = is_loading.clone();
+            let error = error.clone();
+            let key_service = key_service.clone();
+
+            wasm_bindgen_futures::spawn_local(async move {
+                match key_service.list_keys().await {
+                    Ok(list) => {
+                        keys.set(list);
+                        error.set(None);
+                    }
+                    Err(e) => {
+                        error.set(Some(format!("Failed to load API keys: {e}")));
+                    }
+                }
+                is_loading.set(false);
+            });
+        })
+    };
+
+    // Load keys on mount
+    {
+        let reload_keys = reload_keys.clone();
+        use_effect_with((), move |_| {
+            reload_keys.emit(());
+        });
+    }
+
+    // Clear success message after timeout
+    {
+        let success = success.clone();
+        use_effect_with(success.clone(), move |msg| {
+            if msg.is_some() {
+                let success = success.clone();
+                Timeout::new(3000, move || {
+                    success.set(None);
+                })
+                .forget();
+            }
+        });
+    }
+
+    let on_create = {
+        let key_service = key_service.clone();
+        let is_submitting = is_submitting.clone();
+        let show_form = show_form.clone();
+        let created_key = created_key.clone();
+        let error = error.clone();
+        let reload = reload_keys.clone();
+
+        Callback::from(move |request: CreateApiKeyRequest| {
+            let key_service = key_service.clone();
+            let is_submitting = is_submitting.clone();
+            let show_form = show_form.clone();
+            let created_key = created_key.clone();
+            let error = error.clone();
+            let reload = reload.clone();
+
+            is_submitting.set(true);
+            wasm_bindgen_futures::spawn_local(async move {
+                match key_service.create_key(&request).await {
+                    Ok(response) => {
+                        created_key.set(Some(response.key));
+                        show_form.set(false);
+                        error.set(None);
+                        reload.emit(());
+                    }
+                    Err(e) => {
+                        error.set(Some(format!("Failed to create API key: {e}")));
+                    }
+                }
+                is_submitting.set(false);
+            });
+        })
+    };
+
+    let on_revoke = {
+        let key_service = key_service.clone();
+        let error = error.clone();
+        let success = success.clone();
+        let reload = reload_keys.clone();
+
+        Callback::from(move |key: ApiKeyInfo| {
+            if web_sys::window()
+                .and_then(|w| {
+                    w.confirm_with_message(&format!(
+                        "Revoke API key '{}'? Clients using it will stop working.",
+                        key.name
+                    ))
+                    .ok()
+                })
+                .unwrap_or(false)
+            {
+                let key_service = key_service.clone();
+                let error = error.clone();
+                let success = success.clone();
+                let reload = reload.clone();
+
+                wasm_bindgen_futures::spawn_local(async move {
+                    match key_service.revoke_key(&key.id).await {
+                        Ok(_) => {
+                            success.set(Some(format!("API key '{}' revoked", key.name)));
+                            reload.emit(());
+                        }
+                        Err(e) => {
+                            error.set(Some(format!("Failed to revoke API key: {e}")));
+                        }
+                    }
+                });
+            }
+        })
+    };
+
+    let on_show_form = {
+        let show_form = show_form.clone();
+        Callback::from(move |_| show_form.set(true))
+    };
+
+    let on_cancel = {
+        let show_form = show_form.clone();
+        Callback::from(move |_| show_form.set(false))
+    };
+
+    let on_dismiss_created = {
+        let created_key = created_key.clone();
+        Callback::from(move |_| created_key.set(None))
+    };
+
+    let on_select_key = Callback::from(|e: FocusEvent| {
+        let input: HtmlInputElement = e.target_unchecked_into();
+        input.select();
+    });
+
+    html! {
+        <div class="p-6 max-w-7xl mx-auto">
+            <div class="mb-6 flex items-start justify-between">
+                <div>
+                    <h1 class="text-2xl font-bold text-gray-900 dark:text-gray-100">
+                        {"API Keys"}
+                    </h1>
+                    <p class="mt-1 text-sm text-gray-600 dark:text-gray-400">
+                        {"Create scoped keys for calling the gateway from other tools"}
+                    </p>
+                </div>
+                {if !*show_form {
+                    html! {
+                        <button
+                            onclick={on_show_form}
+                            class="px-4 py-2 text-sm font-medium text-white bg-blue-600 hover:bg-blue-700 rounded-md"
+                        >
+                            {"New key"}
+                        </button>
+                    }
+                } else {
+                    html! {}
+                }}
+            </div>
+
+            {if let Some(err) = (*error).as_ref() {
+                html! {
+                    <div class="mb-4 p-4 bg-red-50 dark:bg-red-900/20 border border-red-200 dark:border-red-800 rounded-md">
+                        <p class="text-red-700 dark:text-red-300">{err}</p>
+                    </div>
+                }
+            } else {
+                html! {}
+            }}
+
+            {if let Some(msg) = (*success).as_ref() {
+                html! {
+                    <div class="mb-4 p-4 bg-green-50 dark:bg-green-900/20 border border-green-200 dark:border-green-800 rounded-md">
+                        <p class="text-green-700 dark:text-green-300">{msg}</p>
+                    </div>
+                }
+            } else {
+                html! {}
+            }}
+
+            {if let Some(key) = (*created_key).as_ref() {
+                html! {
+                    <div class="mb-4 p-4 bg-yellow-50 dark:bg-yellow-900/20 border border-yellow-200 dark:border-yellow-800 rounded-md space-y-2">
+                        <p class="text-sm font-medium text-yellow-800 dark:text-yellow-300">
+                            {"Copy your new API key now. It will not be shown again."}
+                        </p>
+                        <input
+                            type="text"
+                            readonly=true
+                            class="block w-full px-3 py-2 font-mono text-sm border border-yellow-300 dark:border-yellow-700 rounded-md bg-white dark:bg-gray-800 text-gray-900 dark:text-gray-100"
+                            value={key.clone()}
+                            onfocus={on_select_key}
+                        />
+                        <div class="flex justify-end">
+                            <button
+                                onclick={on_dismiss_created}
+                                class="px-3 py-1 text-sm font-medium text-yellow-800 dark:text-yellow-300 hover:underline"
+                            >
+                                {"I have saved this key"}
+                            </button>
+                        </div>
+                    </div>
+                }
+            } else {
+                html! {}
+            }}
+
+            {if *show_form {
+                html! {
+                    <div class="mb-6">
+                        <CreateApiKeyForm
+                            on_submit={on_create}
+                            on_cancel={on_cancel}
+                            is_submitting={*is_submitting}
+                        />
+                    </div>
+                }
+            } else {
+                html! {}
+            }}
+
+            <ApiKeyList
+                keys={(*keys).clone()}
+                is_loading={*is_loading}
+                on_revoke={on_revoke}
+            />
+        </div>
+    }
+}
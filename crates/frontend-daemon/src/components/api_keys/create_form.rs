@@ -0,0 +1,208 @@
+//! Form for creating a scoped API key
+
+use crate::services::api_keys::CreateApiKeyRequest;
+use chrono::{NaiveDate, TimeZone, Utc};
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+const INPUT_CLASS: &str = "block w-full px-3 py-2 border border-gray-300 dark:border-gray-600 rounded-md bg-white dark:bg-gray-800 text-gray-900 dark:text-gray-100 placeholder-gray-500 focus:outline-none focus:ring-1 focus:ring-blue-500 focus:border-blue-500 sm:text-sm";
+const LABEL_CLASS: &str = "block text-sm font-medium text-gray-700 dark:text-gray-300 mb-1";
+
+#[derive(Properties, PartialEq)]
+pub struct CreateApiKeyFormProps {
+    pub on_submit: Callback<CreateApiKeyRequest>,
+    pub on_cancel: Callback<()>,
+    pub is_submitting: bool,
+}
+
+/// Parse a `YYYY-MM-DD` date input into the end of that day in UTC
+fn parse_expiry(value: &str) -> Option<chrono::DateTime<Utc>> {
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?;
+    Utc.from_local_datetime(&date.and_hms_opt(23, 59, 59)?)
+        .single()
+}
+
+/// Split a comma-separated model list, dropping empty entries
+fn parse_models(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|m| !m.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+#[function_component(CreateApiKeyForm)]
+pub fn create_api_key_form(props: &CreateApiKeyFormProps) -> Html {
+    let name = use_state(String::new);
+    let expiry = use_state(String::new);
+    let models = use_state(String::new);
+    let spend_cap = use_state(String::new);
+    let observer = use_state(|| false);
+    let validation_error = use_state(|| Option::<String>::None);
+
+    let bind_input = |state: UseStateHandle<String>| {
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            state.set(input.value());
+        })
+    };
+
+    let on_submit = {
+        let name = name.clone();
+        let expiry = expiry.clone();
+        let models = models.clone();
+        let spend_cap = spend_cap.clone();
+        let observer = observer.clone();
+        let validation_error = validation_error.clone();
+        let on_submit = props.on_submit.clone();
+
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+
+            if name.trim().is_empty() {
+                validation_error.set(Some("Name is required".to_string()));
+                return;
+            }
+
+            let expires_at = if expiry.is_empty() {
+                None
+            } else {
+                match parse_expiry(&expiry) {
+                    Some(date) => Some(date),
+                    None => {
+                        validation_error.set(Some("Invalid expiry date".to_string()));
+                        return;
+                    }
+                }
+            };
+
+            let spend_cap = if spend_cap.trim().is_empty() {
+                None
+            } else {
+                match spend_cap.trim().parse::<f64>() {
+                    Ok(cap) if cap > 0.0 => Some(cap),
+                    _ => {
+                        validation_error
+                            .set(Some("Spend cap must be a positive number".to_string()));
+                        return;
+                    }
+                }
+            };
+
+            validation_error.set(None);
+            on_submit.emit(CreateApiKeyRequest {
+                name: name.trim().to_string(),
+                expires_at,
+                allowed_models: parse_models(&models),
+                spend_cap,
+                observer: *observer,
+            });
+        })
+    };
+
+    let on_observer = {
+        let observer = observer.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            observer.set(input.checked());
+        })
+    };
+
+    let on_cancel = {
+        let on_cancel = props.on_cancel.clone();
+        Callback::from(move |_| on_cancel.emit(()))
+    };
+
+    html! {
+        <form onsubmit={on_submit} class="bg-white dark:bg-gray-800 shadow rounded-lg p-6 space-y-4">
+            <h2 class="text-lg font-medium text-gray-900 dark:text-gray-100">{"Create API key"}</h2>
+
+            {if let Some(err) = (*validation_error).as_ref() {
+                html! { <p class="text-sm text-red-600 dark:text-red-400">{err}</p> }
+            } else {
+                html! {}
+            }}
+
+            <div>
+                <label for="api-key-name" class={LABEL_CLASS}>{"Name"}</label>
+                <input
+                    id="api-key-name"
+                    type="text"
+                    class={INPUT_CLASS}
+                    placeholder="e.g. laptop, ci"
+                    value={(*name).clone()}
+                    oninput={bind_input(name.clone())}
+                />
+            </div>
+
+            <div class="grid grid-cols-1 md:grid-cols-2 gap-4">
+                <div>
+                    <label for="api-key-expiry" class={LABEL_CLASS}>{"Expires (optional)"}</label>
+                    <input
+                        id="api-key-expiry"
+                        type="date"
+                        class={INPUT_CLASS}
+                        value={(*expiry).clone()}
+                        oninput={bind_input(expiry.clone())}
+                    />
+                </div>
+                <div>
+                    <label for="api-key-spend-cap" class={LABEL_CLASS}>{"Spend cap in USD (optional)"}</label>
+                    <input
+                        id="api-key-spend-cap"
+                        type="number"
+                        min="0"
+                        step="0.01"
+                        class={INPUT_CLASS}
+                        placeholder="Unlimited"
+                        value={(*spend_cap).clone()}
+                        oninput={bind_input(spend_cap.clone())}
+                    />
+                </div>
+            </div>
+
+            <div>
+                <label for="api-key-models" class={LABEL_CLASS}>{"Allowed models (optional)"}</label>
+                <input
+                    id="api-key-models"
+                    type="text"
+                    class={INPUT_CLASS}
+                    placeholder="Comma-separated; leave empty to allow all models"
+                    value={(*models).clone()}
+                    oninput={bind_input(models.clone())}
+                />
+            </div>
+
+            <div class="flex items-start gap-2">
+                <input
+                    id="api-key-observer"
+                    type="checkbox"
+                    class="mt-1 h-4 w-4 rounded border-gray-300 dark:border-gray-600 text-blue-600 focus:ring-blue-500"
+                    checked={*observer}
+                    onchange={on_observer}
+                />
+                <label for="api-key-observer" class="text-sm text-gray-700 dark:text-gray-300">
+                    {"Observer key: read status, usage and health only, for dashboards and monitoring"}
+                </label>
+            </div>
+
+            <div class="flex justify-end gap-2">
+                <button
+                    type="button"
+                    onclick={on_cancel}
+                    class="px-4 py-2 text-sm font-medium text-gray-700 dark:text-gray-300 bg-gray-100 dark:bg-gray-700 hover:bg-gray-200 dark:hover:bg-gray-600 rounded-md"
+                >
+                    {"Cancel"}
+                </button>
+                <button
+                    type="submit"
+                    disabled={props.is_submitting}
+                    class="px-4 py-2 text-sm font-medium text-white bg-blue-600 hover:bg-blue-700 disabled:opacity-50 rounded-md"
+                >
+                    {if props.is_submitting { "Creating..." } else { "Create key" }}
+                </button>
+            </div>
+        </form>
+    }
+}
@@ -0,0 +1,122 @@
+//! API key list view component
+
+use crate::components::user_management::shared::{EmptyState, UserListSkeleton};
+use crate::services::api_keys::ApiKeyInfo;
+use yew::prelude::*;
+
+const HEADER_CLASS: &str = "px-6 py-3 text-left text-xs font-medium text-gray-500 dark:text-gray-400 uppercase tracking-wider";
+const CELL_CLASS: &str = "px-6 py-4 whitespace-nowrap text-sm text-gray-500 dark:text-gray-400";
+
+#[derive(Properties, PartialEq)]
+pub struct ApiKeyListProps {
+    pub keys: Vec<ApiKeyInfo>,
+    pub is_loading: bool,
+    pub on_revoke: Callback<ApiKeyInfo>,
+}
+
+fn format_scope(key: &ApiKeyInfo) -> String {
+    if key.observer {
+        return "Observer (read-only)".to_string();
+    }
+    let models = if key.allowed_models.is_empty() {
+        "All models".to_string()
+    } else {
+        key.allowed_models.join(", ")
+    };
+    match key.spend_cap {
+        Some(cap) => format!("{models} · ${cap:.2} cap"),
+        None => models,
+    }
+}
+
+#[function_component(ApiKeyList)]
+pub fn api_key_list(props: &ApiKeyListProps) -> Html {
+    if props.is_loading {
+        return html! { <UserListSkeleton /> };
+    }
+
+    if props.keys.is_empty() {
+        return html! {
+            <EmptyState
+                title="No API keys"
+                description="Create a key to call the gateway from scripts and tools."
+                icon={html! {
+                    <svg fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                        <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2"
+                            d="M15 7a2 2 0 012 2m4 0a6 6 0 01-7.743 5.743L11 17H9v2H7v2H4a1 1 0 01-1-1v-2.586a1 1 0 01.293-.707l5.964-5.964A6 6 0 1121 9z" />
+                    </svg>
+                }}
+            />
+        };
+    }
+
+    html! {
+        <div class="bg-white dark:bg-gray-800 shadow overflow-hidden rounded-lg">
+            <table class="min-w-full divide-y divide-gray-200 dark:divide-gray-700">
+                <thead class="bg-gray-50 dark:bg-gray-900">
+                    <tr>
+                        <th scope="col" class={HEADER_CLASS}>{"Name"}</th>
+                        <th scope="col" class={HEADER_CLASS}>{"Scope"}</th>
+                        <th scope="col" class={HEADER_CLASS}>{"Created"}</th>
+                        <th scope="col" class={HEADER_CLASS}>{"Last used"}</th>
+                        <th scope="col" class={HEADER_CLASS}>{"Expires"}</th>
+                        <th scope="col" class="relative px-6 py-3">
+                            <span class="sr-only">{"Actions"}</span>
+                        </th>
+                    </tr>
+                </thead>
+                <tbody class="bg-white dark:bg-gray-800 divide-y divide-gray-200 dark:divide-gray-700">
+                    {props.keys.iter().map(|key| {
+                        let on_revoke = {
+                            let key = key.clone();
+                            let on_revoke = props.on_revoke.clone();
+                            Callback::from(move |_| on_revoke.emit(key.clone()))
+                        };
+
+                        html! {
+                            <tr key={key.id.clone()}>
+                                <td class="px-6 py-4 whitespace-nowrap">
+                                    <div class="text-sm font-medium text-gray-900 dark:text-gray-100">
+                                        {&key.name}
+                                        {if key.expired {
+                                            html! {
+                                                <span class="ml-2 inline-flex items-center px-2.5 py-0.5 rounded-full text-xs font-medium bg-red-100 text-red-800 dark:bg-red-900/30 dark:text-red-400">
+                                                    {"Expired"}
+                                                </span>
+                                            }
+                                        } else {
+                                            html! {}
+                                        }}
+                                    </div>
+                                    <div class="text-sm font-mono text-gray-500 dark:text-gray-400">
+                                        {format!("{}…", key.key_prefix)}
+                                    </div>
+                                </td>
+                                <td class={CELL_CLASS}>{format_scope(key)}</td>
+                                <td class={CELL_CLASS}>{key.created_at.format("%Y-%m-%d").to_string()}</td>
+                                <td class={CELL_CLASS}>
+                                    {key.last_used_at
+                                        .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                                        .unwrap_or_else(|| "Never".to_string())}
+                                </td>
+                                <td class={CELL_CLASS}>
+                                    {key.expires_at
+                                        .map(|t| t.format("%Y-%m-%d").to_string())
+                                        .unwrap_or_else(|| "Never".to_string())}
+                                </td>
+                                <td class="px-6 py-4 whitespace-nowrap text-right text-sm font-medium">
+                                    <button
+                                        onclick={on_revoke}
+                                        class="text-red-600 hover:text-red-900 dark:text-red-400 dark:hover:text-red-300"
+                                    >
+                                        {"Revoke"}
+                                    </button>
+                                </td>
+                            </tr>
+                        }
+                    }).collect::<Html>()}
+                </tbody>
+            </table>
+        </div>
+    }
+}
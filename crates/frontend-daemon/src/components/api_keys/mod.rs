@@ -0,0 +1,5 @@
+pub mod container;
+pub mod create_form;
+pub mod list;
+
+pub use container::ApiKeysContainer;
@@ -1,29 +1,106 @@
 use crate::services::ConfigApiService;
+use gate_frontend_common::i18n::use_i18n;
 use gloo::timers::callback::Timeout;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
 use yew::prelude::*;
 
 use super::{
     pages::{
-        AuthConfigPage, InferenceConfigPage, NetworkConfigPage, ProvidersConfigPage,
-        ServerConfigPage,
+        AdvancedConfigPage, AllSettingsConfigPage, AuthConfigPage, HistoryConfigPage,
+        InferenceConfigPage, NetworkConfigPage, ProvidersConfigPage, ServerConfigPage,
     },
-    sub_nav::{ConfigPage, SubNav},
+    sub_nav::{ConfigPage, SubNav, CONFIG_TABPANEL_ID},
     types::*,
 };
 
+#[derive(Properties, PartialEq)]
+pub struct ConfigEditorProps {
+    /// Page shown when the editor opens
+    #[prop_or(ConfigPage::Server)]
+    pub initial_page: ConfigPage,
+}
+
+/// Apply an edit made through a typed view to the document it came from.
+/// `before` and `after` are the typed view before and after the edit, so
+/// fields the view dropped are removed while fields it does not know about
+/// are kept. List items are matched by `name` where they have one.
+fn apply_edit(base: &mut Value, before: &Value, after: Value) {
+    match (base, after) {
+        (Value::Object(base), Value::Object(after)) => {
+            if let Some(before) = before.as_object() {
+                for key in before.keys().filter(|key| !after.contains_key(*key)) {
+                    base.remove(key);
+                }
+            }
+            for (key, value) in after {
+                let previous = before.get(&key).unwrap_or(&Value::Null);
+                apply_edit(base.entry(key).or_insert(Value::Null), previous, value);
+            }
+        }
+        (Value::Array(base), Value::Array(after)) => {
+            let named = |items: &[Value], item: &Value| {
+                let name = item.get("name")?;
+                items.iter().find(|i| i.get("name") == Some(name)).cloned()
+            };
+            let before = before.as_array().map(Vec::as_slice).unwrap_or_default();
+            let mut items = Vec::with_capacity(after.len());
+            for item in after {
+                let mut slot = named(base.as_slice(), &item).unwrap_or(Value::Null);
+                let previous = named(before, &item).unwrap_or(Value::Null);
+                apply_edit(&mut slot, &previous, item);
+                items.push(slot);
+            }
+            *base = items;
+        }
+        (base, after) => *base = after,
+    }
+}
+
+/// Setter for one top-level section of the configuration document
+fn section_setter<T: Serialize + DeserializeOwned + 'static>(
+    document: &UseStateHandle<Value>,
+    key: &'static str,
+) -> Callback<T> {
+    let document = document.clone();
+    Callback::from(move |section: T| {
+        let Ok(after) = serde_json::to_value(section) else {
+            return;
+        };
+        let before = document
+            .get(key)
+            .and_then(|value| serde_json::from_value::<T>(value.clone()).ok())
+            .and_then(|typed| serde_json::to_value(typed).ok())
+            .unwrap_or(Value::Null);
+        let mut new_document = (*document).clone();
+        match new_document.as_object_mut() {
+            Some(object) => apply_edit(object.entry(key).or_insert(Value::Null), &before, after),
+            None => new_document = serde_json::json!({ key: after }),
+        }
+        document.set(new_document);
+    })
+}
+
 #[function_component(ConfigEditor)]
-pub fn config_editor() -> Html {
+pub fn config_editor(props: &ConfigEditorProps) -> Html {
+    let i18n = use_i18n();
     let config_service = use_memo((), |_| ConfigApiService::new());
-    let config = use_state(GateConfig::default);
+    // Pending configuration; the hand-written pages edit a typed view of it,
+    // so settings they do not know about survive a save
+    let document = use_state(|| Value::Null);
+    let config: GateConfig = serde_json::from_value((*document).clone()).unwrap_or_default();
+    // Last configuration loaded from or saved to the daemon
+    let running = use_state(|| serde_json::Value::Null);
     let is_loading = use_state(|| false);
     let is_saving = use_state(|| false);
     let error_message = use_state(|| None::<String>);
     let success_message = use_state(|| None::<String>);
-    let active_page = use_state(|| ConfigPage::Server);
+    let active_page = use_state(|| props.initial_page);
 
     {
         let config_service = config_service.clone();
-        let config = config.clone();
+        let document = document.clone();
+        let running = running.clone();
         let is_loading = is_loading.clone();
         let error_message = error_message.clone();
 
@@ -31,10 +108,17 @@ pub fn config_editor() -> Html {
             is_loading.set(true);
             wasm_bindgen_futures::spawn_local(async move {
                 match config_service.get_config().await {
-                    Ok(config_json) => match serde_json::from_value::<GateConfig>(config_json) {
-                        Ok(loaded_config) => config.set(loaded_config),
-                        Err(e) => error_message.set(Some(format!("Failed to parse config: {e}"))),
-                    },
+                    Ok(config_json) => {
+                        match serde_json::from_value::<GateConfig>(config_json.clone()) {
+                            Ok(_) => {
+                                document.set(config_json.clone());
+                                running.set(config_json);
+                            }
+                            Err(e) => {
+                                error_message.set(Some(format!("Failed to parse config: {e}")))
+                            }
+                        }
+                    }
                     Err(e) => error_message.set(Some(format!("Failed to load config: {e}"))),
                 }
                 is_loading.set(false);
@@ -44,7 +128,8 @@ pub fn config_editor() -> Html {
 
     let on_save = {
         let config_service = config_service.clone();
-        let config = config.clone();
+        let document = document.clone();
+        let running = running.clone();
         let is_saving = is_saving.clone();
         let error_message = error_message.clone();
         let success_message = success_message.clone();
@@ -53,23 +138,18 @@ pub fn config_editor() -> Html {
             is_saving.set(true);
             error_message.set(None);
 
-            let config_json = match serde_json::to_value(&*config) {
-                Ok(json) => json,
-                Err(e) => {
-                    error_message.set(Some(format!("Failed to serialize config: {e}")));
-                    is_saving.set(false);
-                    return;
-                }
-            };
+            let config_json = (*document).clone();
 
             let config_service = config_service.clone();
+            let running = running.clone();
             let is_saving = is_saving.clone();
             let error_message = error_message.clone();
             let success_message = success_message.clone();
 
             wasm_bindgen_futures::spawn_local(async move {
                 match config_service.update_config(config_json).await {
-                    Ok(_) => {
+                    Ok(saved) => {
+                        running.set(saved);
                         success_message.set(Some("Configuration saved successfully!".to_string()));
                         let success_message = success_message.clone();
                         Timeout::new(3000, move || {
@@ -84,57 +164,33 @@ pub fn config_editor() -> Html {
         })
     };
 
-    let on_server_change = {
-        let config = config.clone();
-        Callback::from(move |new_server| {
-            let mut new_config = (*config).clone();
-            new_config.server = new_server;
-            config.set(new_config);
-        })
-    };
+    let on_server_change = section_setter::<ServerConfig>(&document, "server");
+    let on_auth_change = section_setter::<AuthConfig>(&document, "auth");
+    let on_providers_change = section_setter::<Vec<ProviderConfig>>(&document, "providers");
+    let on_tlsforward_change = section_setter::<TlsForwardConfig>(&document, "tlsforward");
+    let on_letsencrypt_change = section_setter::<LetsEncryptConfig>(&document, "letsencrypt");
+    let on_inference_change =
+        section_setter::<Option<LocalInferenceConfig>>(&document, "local_inference");
 
-    let on_auth_change = {
-        let config = config.clone();
-        Callback::from(move |new_auth| {
-            let mut new_config = (*config).clone();
-            new_config.auth = new_auth;
-            config.set(new_config);
-        })
+    let on_document_change = {
+        let document = document.clone();
+        Callback::from(move |new_document| document.set(new_document))
     };
 
-    let on_providers_change = {
-        let config = config.clone();
-        Callback::from(move |new_providers| {
-            let mut new_config = (*config).clone();
-            new_config.providers = new_providers;
-            config.set(new_config);
-        })
-    };
-
-    let on_tlsforward_change = {
-        let config = config.clone();
-        Callback::from(move |new_tlsforward| {
-            let mut new_config = (*config).clone();
-            new_config.tlsforward = new_tlsforward;
-            config.set(new_config);
-        })
-    };
-
-    let on_letsencrypt_change = {
-        let config = config.clone();
-        Callback::from(move |new_letsencrypt| {
-            let mut new_config = (*config).clone();
-            new_config.letsencrypt = new_letsencrypt;
-            config.set(new_config);
-        })
-    };
-
-    let on_inference_change = {
-        let config = config.clone();
-        Callback::from(move |new_inference| {
-            let mut new_config = (*config).clone();
-            new_config.local_inference = new_inference;
-            config.set(new_config);
+    // A rollback is applied by the daemon, so it replaces pending edits too
+    let on_rollback = {
+        let document = document.clone();
+        let running = running.clone();
+        let success_message = success_message.clone();
+        Callback::from(move |(revision, config): (u64, Value)| {
+            document.set(config.clone());
+            running.set(config);
+            success_message.set(Some(format!("Rolled back to revision {revision}.")));
+            let success_message = success_message.clone();
+            Timeout::new(3000, move || {
+                success_message.set(None);
+            })
+            .forget();
         })
     };
 
@@ -150,10 +206,10 @@ pub fn config_editor() -> Html {
             <div class="bg-white dark:bg-gray-800 rounded-lg shadow-lg">
                 <div class="border-b border-gray-200 dark:border-gray-700 px-6 py-4">
                     <h2 class="text-xl font-semibold text-gray-800 dark:text-gray-200">
-                        {"Configuration Editor"}
+                        {i18n.t("config.title")}
                     </h2>
                     <p class="text-sm text-gray-600 dark:text-gray-400 mt-1">
-                        {"Manage Gate configuration settings"}
+                        {i18n.t("config.subtitle")}
                     </p>
                 </div>
 
@@ -181,7 +237,12 @@ pub fn config_editor() -> Html {
                                 on_change={on_page_change}
                             />
 
-                            <div class="overflow-y-auto">
+                            <div
+                                class="overflow-y-auto"
+                                role="tabpanel"
+                                id={CONFIG_TABPANEL_ID}
+                                aria-labelledby={active_page.tab_id()}
+                            >
                                 {match *active_page {
                                     ConfigPage::Server => html! {
                                         <ServerConfigPage
@@ -215,6 +276,22 @@ pub fn config_editor() -> Html {
                                             on_change={on_inference_change}
                                         />
                                     },
+                                    ConfigPage::AllSettings => html! {
+                                        <AllSettingsConfigPage
+                                            config={(*document).clone()}
+                                            on_change={on_document_change.clone()}
+                                        />
+                                    },
+                                    ConfigPage::Advanced => html! {
+                                        <AdvancedConfigPage
+                                            config={(*document).clone()}
+                                            running={(*running).clone()}
+                                            on_change={on_document_change}
+                                        />
+                                    },
+                                    ConfigPage::History => html! {
+                                        <HistoryConfigPage on_rollback={on_rollback} />
+                                    },
                                 }}
                             </div>
 
@@ -225,9 +302,9 @@ pub fn config_editor() -> Html {
                                     disabled={*is_saving}
                                 >
                                     if *is_saving {
-                                        {"Saving..."}
+                                        {i18n.t("config.saving")}
                                     } else {
-                                        {"Save Configuration"}
+                                        {i18n.t("config.save")}
                                     }
                                 </button>
                             </div>
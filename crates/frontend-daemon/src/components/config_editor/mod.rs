@@ -4,10 +4,14 @@ mod inference;
 mod letsencrypt;
 pub mod pages;
 pub mod providers;
+mod relay_diagnostics;
+mod schema_form;
 mod server;
 mod shared;
 mod sub_nav;
+mod tls_status;
 mod tlsforward;
 pub mod types;
 
 pub use container::ConfigEditor;
+pub use sub_nav::ConfigPage;
@@ -0,0 +1,292 @@
+use super::super::types::GateConfig;
+use crate::services::config::{ConfigIssue, ConfigValidation, IssueSeverity};
+use crate::services::ConfigApiService;
+use crate::utils::diff::{diff_lines, DiffKind, DiffLine};
+use serde_json::Value;
+use std::collections::HashSet;
+use web_sys::HtmlTextAreaElement;
+use yew::prelude::*;
+
+/// Unchanged lines kept around each change in the diff preview
+const DIFF_CONTEXT: usize = 3;
+const EDITOR_TEXT: &str = "font-mono text-xs leading-5";
+
+#[derive(Properties, PartialEq)]
+pub struct AdvancedConfigPageProps {
+    /// Pending configuration in the editor
+    pub config: Value,
+    /// Configuration the daemon is currently running with
+    pub running: Value,
+    pub on_change: Callback<Value>,
+}
+
+fn pretty(value: &Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_default()
+}
+
+/// Re-format parseable JSON so the diff ignores whitespace-only edits
+fn normalized(text: &str) -> String {
+    serde_json::from_str::<Value>(text)
+        .map(|value| pretty(&value))
+        .unwrap_or_else(|_| text.to_string())
+}
+
+fn render_diff(lines: &[DiffLine]) -> Html {
+    if lines.iter().all(|line| line.kind == DiffKind::Same) {
+        return html! {
+            <p class="text-sm text-gray-500 dark:text-gray-400">
+                {"No changes from the running configuration."}
+            </p>
+        };
+    }
+
+    let near_change = |i: usize| {
+        let start = i.saturating_sub(DIFF_CONTEXT);
+        let end = (i + DIFF_CONTEXT + 1).min(lines.len());
+        lines[start..end]
+            .iter()
+            .any(|line| line.kind != DiffKind::Same)
+    };
+
+    let mut rows = Vec::new();
+    let mut skipped = 0;
+    for (i, line) in lines.iter().enumerate() {
+        if line.kind == DiffKind::Same && !near_change(i) {
+            skipped += 1;
+            continue;
+        }
+        if skipped > 0 {
+            rows.push(html! {
+                <div class="px-2 text-gray-400 dark:text-gray-500">
+                    {format!("… {skipped} unchanged lines")}
+                </div>
+            });
+            skipped = 0;
+        }
+        let (marker, class) = match line.kind {
+            DiffKind::Same => (" ", "text-gray-600 dark:text-gray-400"),
+            DiffKind::Added => (
+                "+",
+                "bg-green-50 dark:bg-green-900/20 text-green-800 dark:text-green-300",
+            ),
+            DiffKind::Removed => (
+                "-",
+                "bg-red-50 dark:bg-red-900/20 text-red-800 dark:text-red-300",
+            ),
+        };
+        rows.push(html! {
+            <div class={classes!("px-2", "whitespace-pre", class)}>
+                {format!("{marker} {}", line.text)}
+            </div>
+        });
+    }
+    if skipped > 0 {
+        rows.push(html! {
+            <div class="px-2 text-gray-400 dark:text-gray-500">
+                {format!("… {skipped} unchanged lines")}
+            </div>
+        });
+    }
+
+    html! {
+        <div class={classes!("border", "border-gray-200", "dark:border-gray-700", "rounded-md", "overflow-x-auto", "py-1", EDITOR_TEXT)}>
+            {rows}
+        </div>
+    }
+}
+
+fn render_issue(issue: &ConfigIssue) -> Html {
+    let (label, class) = match issue.severity {
+        IssueSeverity::Error => ("Error", "text-red-700 dark:text-red-300"),
+        IssueSeverity::Warning => ("Warning", "text-yellow-700 dark:text-yellow-300"),
+    };
+    let location = match (issue.line, issue.column, issue.path.as_deref()) {
+        (Some(line), Some(column), _) => format!("line {line}, column {column}"),
+        (Some(line), None, _) => format!("line {line}"),
+        (None, _, Some(path)) => path.to_string(),
+        (None, _, None) => String::new(),
+    };
+    html! {
+        <li class={classes!("text-sm", class)}>
+            <span class="font-medium">{label}</span>
+            if !location.is_empty() {
+                <span class="font-mono text-xs ml-2">{location}</span>
+            }
+            <span class="ml-2">{&issue.message}</span>
+        </li>
+    }
+}
+
+#[function_component(AdvancedConfigPage)]
+pub fn advanced_config_page(props: &AdvancedConfigPageProps) -> Html {
+    let config_service = use_memo((), |_| ConfigApiService::new());
+    let text = {
+        let config = props.config.clone();
+        use_state(move || pretty(&config))
+    };
+    let validation = use_state(|| None::<ConfigValidation>);
+    let is_validating = use_state(|| false);
+    let apply_error = use_state(|| None::<String>);
+    let applied = use_state(|| false);
+
+    let on_input = {
+        let text = text.clone();
+        let validation = validation.clone();
+        let applied = applied.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlTextAreaElement = e.target_unchecked_into();
+            text.set(input.value());
+            validation.set(None);
+            applied.set(false);
+        })
+    };
+
+    // Validate, then hand the parsed config to the editor when `apply` is set
+    let run_validation = {
+        let config_service = config_service.clone();
+        let text = text.clone();
+        let validation = validation.clone();
+        let is_validating = is_validating.clone();
+        let apply_error = apply_error.clone();
+        let applied = applied.clone();
+        let on_change = props.on_change.clone();
+
+        Callback::from(move |apply: bool| {
+            let config_service = config_service.clone();
+            let current = (*text).clone();
+            let validation = validation.clone();
+            let is_validating = is_validating.clone();
+            let apply_error = apply_error.clone();
+            let applied = applied.clone();
+            let on_change = on_change.clone();
+
+            is_validating.set(true);
+            apply_error.set(None);
+            wasm_bindgen_futures::spawn_local(async move {
+                match config_service.validate_config(&current).await {
+                    Ok(result) => {
+                        if apply && result.valid {
+                            let parsed =
+                                serde_json::from_str::<Value>(&current).and_then(|value| {
+                                    serde_json::from_value::<GateConfig>(value.clone())
+                                        .map(|_| value)
+                                });
+                            match parsed {
+                                Ok(config) => {
+                                    on_change.emit(config);
+                                    applied.set(true);
+                                }
+                                Err(e) => apply_error
+                                    .set(Some(format!("Editor cannot represent this config: {e}"))),
+                            }
+                        }
+                        validation.set(Some(result));
+                    }
+                    Err(e) => apply_error.set(Some(format!("Failed to validate config: {e}"))),
+                }
+                is_validating.set(false);
+            });
+        })
+    };
+
+    let on_reset = {
+        let text = text.clone();
+        let validation = validation.clone();
+        let applied = applied.clone();
+        let running = props.running.clone();
+        Callback::from(move |_: MouseEvent| {
+            text.set(pretty(&running));
+            validation.set(None);
+            applied.set(false);
+        })
+    };
+
+    let error_lines: HashSet<usize> = validation
+        .as_ref()
+        .map(|v| v.issues.iter().filter_map(|issue| issue.line).collect())
+        .unwrap_or_default();
+    let line_count = text.lines().count().max(1);
+    let diff = diff_lines(&pretty(&props.running), &normalized(&text));
+
+    html! {
+        <div class="p-6 space-y-6">
+            <div>
+                <h2 class="text-lg font-semibold text-gray-900 dark:text-gray-100">
+                    {"Advanced"}
+                </h2>
+                <p class="mt-1 text-sm text-gray-600 dark:text-gray-400">
+                    {"Edit the raw configuration JSON. Apply to load it into the editor, then save to persist."}
+                </p>
+            </div>
+
+            <div class="flex max-h-[32rem] overflow-auto border border-gray-300 dark:border-gray-600 rounded-md bg-white dark:bg-gray-900">
+                <div class={classes!("select-none", "text-right", "py-2", "bg-gray-50", "dark:bg-gray-800", "text-gray-400", EDITOR_TEXT)} aria-hidden="true">
+                    {for (1..=line_count).map(|n| {
+                        let has_error = error_lines.contains(&n);
+                        html! {
+                            <div class={classes!("px-2", has_error.then_some("bg-red-100 dark:bg-red-900/40 text-red-700 dark:text-red-300"))}>
+                                {if has_error { format!("● {n}") } else { n.to_string() }}
+                            </div>
+                        }
+                    })}
+                </div>
+                <textarea
+                    class={classes!("flex-1", "py-2", "px-3", "resize-none", "outline-none", "bg-transparent", "text-gray-900", "dark:text-gray-100", "whitespace-pre", "overflow-hidden", EDITOR_TEXT)}
+                    rows={(line_count + 1).to_string()}
+                    spellcheck="false"
+                    aria-label="Configuration JSON"
+                    value={(*text).clone()}
+                    oninput={on_input}
+                />
+            </div>
+
+            if let Some(result) = validation.as_ref() {
+                if result.issues.is_empty() {
+                    <p class="text-sm text-green-700 dark:text-green-300">{"Configuration is valid."}</p>
+                } else {
+                    <ul class="space-y-1">
+                        {for result.issues.iter().map(render_issue)}
+                    </ul>
+                }
+            }
+            if let Some(error) = apply_error.as_ref() {
+                <p class="text-sm text-red-700 dark:text-red-300">{error}</p>
+            }
+            if *applied {
+                <p class="text-sm text-blue-700 dark:text-blue-300">
+                    {"Applied to the editor. Review the changes below and save to persist."}
+                </p>
+            }
+
+            <div class="flex gap-3">
+                <button
+                    class="px-4 py-2 text-sm font-medium text-gray-700 dark:text-gray-300 bg-gray-100 dark:bg-gray-700 hover:bg-gray-200 dark:hover:bg-gray-600 rounded-md disabled:opacity-50"
+                    onclick={run_validation.reform(|_: MouseEvent| false)}
+                    disabled={*is_validating}
+                >
+                    {"Validate"}
+                </button>
+                <button
+                    class="px-4 py-2 text-sm font-medium text-white bg-blue-600 hover:bg-blue-700 rounded-md disabled:opacity-50"
+                    onclick={run_validation.reform(|_: MouseEvent| true)}
+                    disabled={*is_validating}
+                >
+                    {"Apply"}
+                </button>
+                <button
+                    class="px-4 py-2 text-sm font-medium text-gray-700 dark:text-gray-300 hover:underline"
+                    onclick={on_reset}
+                >
+                    {"Reset to running config"}
+                </button>
+            </div>
+
+            <div>
+                <h3 class="text-sm font-semibold text-gray-900 dark:text-gray-100 mb-2">
+                    {"Changes vs running configuration"}
+                </h3>
+                {render_diff(&diff)}
+            </div>
+        </div>
+    }
+}
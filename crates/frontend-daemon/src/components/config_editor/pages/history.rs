@@ -0,0 +1,244 @@
+use crate::services::config::{ConfigChange, ConfigDiff, ConfigRevision};
+use crate::services::ConfigApiService;
+use serde_json::Value;
+use yew::prelude::*;
+
+const CELL_CLASS: &str = "px-3 py-2 align-top font-mono text-xs";
+
+#[derive(Properties, PartialEq)]
+pub struct HistoryConfigPageProps {
+    /// Called with the revision and the configuration once it is running
+    pub on_rollback: Callback<(u64, Value)>,
+}
+
+fn render_value(value: Option<&Value>) -> String {
+    match value {
+        Some(Value::String(text)) => text.clone(),
+        Some(value) => value.to_string(),
+        None => "—".to_string(),
+    }
+}
+
+fn render_change(change: &ConfigChange) -> Html {
+    html! {
+        <tr key={change.path.clone()}>
+            <td class={classes!(CELL_CLASS, "text-gray-900", "dark:text-gray-100")}>{&change.path}</td>
+            <td class={classes!(CELL_CLASS, "text-red-800", "dark:text-red-300", "break-all")}>
+                {render_value(change.before.as_ref())}
+            </td>
+            <td class={classes!(CELL_CLASS, "text-green-800", "dark:text-green-300", "break-all")}>
+                {render_value(change.after.as_ref())}
+            </td>
+        </tr>
+    }
+}
+
+/// Applied configuration revisions, what each changed, and rollback
+#[function_component(HistoryConfigPage)]
+pub fn history_config_page(props: &HistoryConfigPageProps) -> Html {
+    let config_service = use_memo((), |_| ConfigApiService::new());
+    let revisions = use_state(Vec::<ConfigRevision>::new);
+    let selected = use_state(|| None::<u64>);
+    let diff = use_state(|| None::<ConfigDiff>);
+    let confirming = use_state(|| false);
+    let is_busy = use_state(|| false);
+    let error = use_state(|| None::<String>);
+
+    let reload = {
+        let config_service = config_service.clone();
+        let revisions = revisions.clone();
+        let error = error.clone();
+        Callback::from(move |_: ()| {
+            let config_service = config_service.clone();
+            let revisions = revisions.clone();
+            let error = error.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match config_service.list_history().await {
+                    Ok(list) => revisions.set(list),
+                    Err(e) => error.set(Some(format!("Failed to load config history: {e}"))),
+                }
+            });
+        })
+    };
+
+    {
+        let reload = reload.clone();
+        use_effect_with((), move |_| {
+            reload.emit(());
+            || ()
+        });
+    }
+
+    let on_select = {
+        let config_service = config_service.clone();
+        let selected = selected.clone();
+        let diff = diff.clone();
+        let confirming = confirming.clone();
+        let error = error.clone();
+        Callback::from(move |revision: u64| {
+            selected.set(Some(revision));
+            diff.set(None);
+            confirming.set(false);
+            let config_service = config_service.clone();
+            let diff = diff.clone();
+            let error = error.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match config_service.diff_revision(revision, None).await {
+                    Ok(loaded) => diff.set(Some(loaded)),
+                    Err(e) => error.set(Some(format!("Failed to load changes: {e}"))),
+                }
+            });
+        })
+    };
+
+    let on_rollback = {
+        let config_service = config_service.clone();
+        let selected = selected.clone();
+        let confirming = confirming.clone();
+        let is_busy = is_busy.clone();
+        let error = error.clone();
+        let reload = reload.clone();
+        let on_rollback = props.on_rollback.clone();
+        Callback::from(move |_: MouseEvent| {
+            let Some(revision) = *selected else {
+                return;
+            };
+            // The first click asks for confirmation
+            if !*confirming {
+                confirming.set(true);
+                return;
+            }
+            confirming.set(false);
+            is_busy.set(true);
+            error.set(None);
+            let config_service = config_service.clone();
+            let is_busy = is_busy.clone();
+            let error = error.clone();
+            let reload = reload.clone();
+            let on_rollback = on_rollback.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match config_service.rollback(revision).await {
+                    Ok(config) => {
+                        on_rollback.emit((revision, config));
+                        reload.emit(());
+                    }
+                    Err(e) => error.set(Some(format!("Failed to roll back: {e}"))),
+                }
+                is_busy.set(false);
+            });
+        })
+    };
+
+    let latest = revisions.first().map(|revision| revision.revision);
+
+    html! {
+        <div class="p-6 space-y-6">
+            <div>
+                <h2 class="text-lg font-semibold text-gray-900 dark:text-gray-100">
+                    {"History"}
+                </h2>
+                <p class="mt-1 text-sm text-gray-600 dark:text-gray-400">
+                    {"Every applied configuration, who applied it, and what it changed. Rolling back re-applies an earlier revision as a new one."}
+                </p>
+            </div>
+
+            if let Some(message) = error.as_ref() {
+                <p class="text-sm text-red-700 dark:text-red-300">{message}</p>
+            }
+
+            <div class="flex gap-6">
+                <ul class="w-64 shrink-0 space-y-1 max-h-[32rem] overflow-y-auto">
+                    {for revisions.iter().map(|revision| {
+                        let is_selected = *selected == Some(revision.revision);
+                        let onclick = {
+                            let number = revision.revision;
+                            on_select.reform(move |_: MouseEvent| number)
+                        };
+                        html! {
+                            <li key={revision.revision.to_string()}>
+                                <button
+                                    type="button"
+                                    class={classes!(
+                                        "w-full", "text-left", "px-3", "py-2", "rounded-md", "text-sm",
+                                        if is_selected {
+                                            "bg-blue-50 dark:bg-blue-900/30 text-blue-700 dark:text-blue-300"
+                                        } else {
+                                            "text-gray-700 dark:text-gray-300 hover:bg-gray-100 dark:hover:bg-gray-700"
+                                        }
+                                    )}
+                                    aria-pressed={is_selected.to_string()}
+                                    onclick={onclick}
+                                >
+                                    <div class="font-medium">
+                                        {format!("Revision {}", revision.revision)}
+                                        if latest == Some(revision.revision) {
+                                            <span class="ml-2 text-xs text-green-700 dark:text-green-300">{"running"}</span>
+                                        }
+                                    </div>
+                                    <div class="text-xs text-gray-500 dark:text-gray-400">
+                                        {format!("{} · {}", revision.author, revision.created_at)}
+                                    </div>
+                                    if let Some(restored) = revision.rollback_of {
+                                        <div class="text-xs text-gray-500 dark:text-gray-400">
+                                            {format!("Rollback to revision {restored}")}
+                                        </div>
+                                    }
+                                </button>
+                            </li>
+                        }
+                    })}
+                </ul>
+
+                <div class="flex-1 min-w-0 space-y-4">
+                    if let Some(loaded) = diff.as_ref() {
+                        if loaded.changes.is_empty() {
+                            <p class="text-sm text-gray-500 dark:text-gray-400">
+                                {match loaded.from {
+                                    Some(from) => format!("No changes from revision {from}."),
+                                    None => "The oldest kept revision has nothing to compare to.".to_string(),
+                                }}
+                            </p>
+                        } else {
+                            <div class="border border-gray-200 dark:border-gray-700 rounded-md overflow-x-auto">
+                                <table class="min-w-full divide-y divide-gray-200 dark:divide-gray-700">
+                                    <thead class="bg-gray-50 dark:bg-gray-900">
+                                        <tr>
+                                            <th scope="col" class="px-3 py-2 text-left text-xs font-medium text-gray-500 dark:text-gray-400">{"Setting"}</th>
+                                            <th scope="col" class="px-3 py-2 text-left text-xs font-medium text-gray-500 dark:text-gray-400">
+                                                {loaded.from.map(|from| format!("Revision {from}")).unwrap_or_default()}
+                                            </th>
+                                            <th scope="col" class="px-3 py-2 text-left text-xs font-medium text-gray-500 dark:text-gray-400">
+                                                {format!("Revision {}", loaded.to)}
+                                            </th>
+                                        </tr>
+                                    </thead>
+                                    <tbody class="divide-y divide-gray-200 dark:divide-gray-700">
+                                        {for loaded.changes.iter().map(render_change)}
+                                    </tbody>
+                                </table>
+                            </div>
+                        }
+                        if latest != Some(loaded.to) {
+                            <button
+                                type="button"
+                                class="px-4 py-2 text-sm font-medium text-white bg-red-600 hover:bg-red-700 rounded-md disabled:opacity-50"
+                                onclick={on_rollback}
+                                disabled={*is_busy}
+                            >
+                                if *confirming {
+                                    {format!("Confirm rollback to revision {}", loaded.to)}
+                                } else {
+                                    {format!("Roll back to revision {}", loaded.to)}
+                                }
+                            </button>
+                        }
+                    } else if selected.is_none() {
+                        <p class="text-sm text-gray-500 dark:text-gray-400">
+                            {"Select a revision to see what it changed."}
+                        </p>
+                    }
+                </div>
+            </div>
+        </div>
+    }
+}
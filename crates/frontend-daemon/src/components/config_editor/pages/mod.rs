@@ -1,11 +1,17 @@
+mod advanced;
 mod auth;
+mod history;
 mod inference;
 mod network;
 mod providers;
 mod server;
+mod settings;
 
+pub use advanced::AdvancedConfigPage;
 pub use auth::AuthConfigPage;
+pub use history::HistoryConfigPage;
 pub use inference::InferenceConfigPage;
 pub use network::NetworkConfigPage;
 pub use providers::ProvidersConfigPage;
 pub use server::ServerConfigPage;
+pub use settings::AllSettingsConfigPage;
@@ -1,5 +1,7 @@
 use super::super::{
     letsencrypt::LetsEncryptConfigSection,
+    relay_diagnostics::RelayDiagnosticsSection,
+    tls_status::TlsStatusSection,
     tlsforward::TlsForwardConfigSection,
     types::{LetsEncryptConfig, TlsForwardConfig},
 };
@@ -37,6 +39,20 @@ pub fn network_config_page(props: &NetworkConfigPageProps) -> Html {
                     />
                 </div>
 
+                <div>
+                    <h3 class="text-md font-medium text-gray-800 dark:text-gray-200 mb-3">
+                        {"Relay Diagnostics"}
+                    </h3>
+                    <RelayDiagnosticsSection />
+                </div>
+
+                <div>
+                    <h3 class="text-md font-medium text-gray-800 dark:text-gray-200 mb-3">
+                        {"Certificate Health"}
+                    </h3>
+                    <TlsStatusSection />
+                </div>
+
                 <div>
                     <h3 class="text-md font-medium text-gray-800 dark:text-gray-200 mb-3">
                         {"Let's Encrypt Certificates"}
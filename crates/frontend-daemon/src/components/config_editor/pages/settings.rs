@@ -0,0 +1,61 @@
+use super::super::schema_form::SchemaObject;
+use crate::services::ConfigApiService;
+use serde_json::Value;
+use std::rc::Rc;
+use yew::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct AllSettingsConfigPageProps {
+    /// Pending configuration document
+    pub config: Value,
+    pub on_change: Callback<Value>,
+}
+
+/// Every setting the daemon knows, with forms built from its schema
+#[function_component(AllSettingsConfigPage)]
+pub fn all_settings_config_page(props: &AllSettingsConfigPageProps) -> Html {
+    let schema = use_state(|| None::<Rc<Value>>);
+    let error = use_state(|| None::<String>);
+
+    {
+        let schema = schema.clone();
+        let error = error.clone();
+        use_effect_with((), move |_| {
+            wasm_bindgen_futures::spawn_local(async move {
+                match ConfigApiService::new().get_schema().await {
+                    Ok(loaded) => schema.set(Some(Rc::new(loaded))),
+                    Err(e) => error.set(Some(format!("Failed to load settings schema: {e}"))),
+                }
+            });
+            || ()
+        });
+    }
+
+    html! {
+        <div class="p-6">
+            <div class="mb-6">
+                <h2 class="text-lg font-semibold text-gray-900 dark:text-gray-100">
+                    {"All Settings"}
+                </h2>
+                <p class="mt-1 text-sm text-gray-600 dark:text-gray-400">
+                    {"Every setting the daemon supports, described by the daemon itself"}
+                </p>
+            </div>
+
+            if let Some(error) = (*error).as_ref() {
+                <p class="text-sm text-red-700 dark:text-red-300">{error}</p>
+            } else if let Some(schema) = (*schema).as_ref() {
+                <SchemaObject
+                    root={schema.clone()}
+                    schema={(**schema).clone()}
+                    value={props.config.clone()}
+                    on_change={props.on_change.clone()}
+                />
+            } else {
+                <div class="flex justify-center items-center h-32">
+                    <div class="animate-spin rounded-full h-8 w-8 border-b-2 border-blue-500"></div>
+                </div>
+            }
+        </div>
+    }
+}
@@ -3,9 +3,12 @@ use super::super::{
     types::ProviderConfig,
 };
 use super::provider_registry::ProviderMetadata;
+use gate_frontend_common::hooks::use_modal_focus;
 use web_sys::HtmlInputElement;
 use yew::prelude::*;
 
+const PANEL_TITLE_ID: &str = "provider-config-title";
+
 #[derive(Properties)]
 pub struct ProviderConfigPanelProps {
     pub provider: &'static ProviderMetadata,
@@ -136,14 +139,22 @@ pub fn provider_config_panel(props: &ProviderConfigPanelProps) -> Html {
         e.stop_propagation();
     });
 
+    let panel_ref = use_node_ref();
+    let on_keydown = use_modal_focus(panel_ref.clone(), true, props.on_close.clone());
+
     html! {
         <div
             class="fixed inset-0 bg-black bg-opacity-50 flex items-center justify-center p-4 z-50"
             onclick={on_backdrop_click}
+            onkeydown={on_keydown}
         >
             <div
+                ref={panel_ref}
                 class="bg-white dark:bg-gray-800 rounded-lg shadow-xl max-w-2xl w-full max-h-[90vh] overflow-y-auto"
                 onclick={on_panel_click}
+                role="dialog"
+                aria-modal="true"
+                aria-labelledby={PANEL_TITLE_ID}
             >
                 // Header
                 <div class="border-b border-gray-200 dark:border-gray-700 px-6 py-4">
@@ -154,7 +165,7 @@ pub fn provider_config_panel(props: &ProviderConfigPanelProps) -> Html {
                                 alt={props.provider.display_name}
                                 class="w-8 h-8 object-contain"
                             />
-                            <h2 class="text-xl font-semibold text-gray-800 dark:text-gray-200">
+                            <h2 id={PANEL_TITLE_ID} class="text-xl font-semibold text-gray-800 dark:text-gray-200">
                                 {format!("Configure {}", props.provider.display_name)}
                             </h2>
                         </div>
@@ -162,8 +173,9 @@ pub fn provider_config_panel(props: &ProviderConfigPanelProps) -> Html {
                             class="text-gray-400 hover:text-gray-600 dark:hover:text-gray-300"
                             onclick={on_close.clone()}
                             type="button"
+                            aria-label="Close"
                         >
-                            <svg class="w-6 h-6" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                            <svg class="w-6 h-6" fill="none" stroke="currentColor" viewBox="0 0 24 24" aria-hidden="true">
                                 <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M6 18L18 6M6 6l12 12"></path>
                             </svg>
                         </button>
@@ -19,7 +19,7 @@ pub static PROVIDER_REGISTRY: LazyLock<HashMap<&'static str, ProviderMetadata>>
             ProviderMetadata {
                 id: "openai",
                 display_name: "OpenAI",
-                icon_path: "/assets/providers/openai.svg",
+                icon_path: "assets/providers/openai.svg",
                 default_base_url: "https://api.openai.com",
                 requires_api_key: true,
                 supported_models: vec![
@@ -34,7 +34,7 @@ pub static PROVIDER_REGISTRY: LazyLock<HashMap<&'static str, ProviderMetadata>>
             ProviderMetadata {
                 id: "anthropic",
                 display_name: "Anthropic",
-                icon_path: "/assets/providers/anthropic.svg",
+                icon_path: "assets/providers/anthropic.svg",
                 default_base_url: "https://api.anthropic.com",
                 requires_api_key: true,
                 supported_models: vec![
@@ -49,7 +49,7 @@ pub static PROVIDER_REGISTRY: LazyLock<HashMap<&'static str, ProviderMetadata>>
             ProviderMetadata {
                 id: "groq",
                 display_name: "Groq",
-                icon_path: "/assets/providers/groq.svg",
+                icon_path: "assets/providers/groq.svg",
                 default_base_url: "https://api.groq.com/openai",
                 requires_api_key: true,
                 supported_models: vec!["llama2-70b-4096", "mixtral-8x7b-32768", "gemma-7b-it"],
@@ -59,7 +59,7 @@ pub static PROVIDER_REGISTRY: LazyLock<HashMap<&'static str, ProviderMetadata>>
             ProviderMetadata {
                 id: "mistral",
                 display_name: "Mistral AI",
-                icon_path: "/assets/providers/mistral.svg",
+                icon_path: "assets/providers/mistral.svg",
                 default_base_url: "https://api.mistral.ai",
                 requires_api_key: true,
                 supported_models: vec![
@@ -74,7 +74,7 @@ pub static PROVIDER_REGISTRY: LazyLock<HashMap<&'static str, ProviderMetadata>>
             ProviderMetadata {
                 id: "gemini",
                 display_name: "Google Gemini",
-                icon_path: "/assets/providers/gemini.svg",
+                icon_path: "assets/providers/gemini.svg",
                 default_base_url: "https://generativelanguage.googleapis.com",
                 requires_api_key: true,
                 supported_models: vec!["gemini-pro", "gemini-pro-vision"],
@@ -84,7 +84,7 @@ pub static PROVIDER_REGISTRY: LazyLock<HashMap<&'static str, ProviderMetadata>>
             ProviderMetadata {
                 id: "cohere",
                 display_name: "Cohere",
-                icon_path: "/assets/providers/cohere.svg",
+                icon_path: "assets/providers/cohere.svg",
                 default_base_url: "https://api.cohere.ai",
                 requires_api_key: true,
                 supported_models: vec![
@@ -99,7 +99,7 @@ pub static PROVIDER_REGISTRY: LazyLock<HashMap<&'static str, ProviderMetadata>>
             ProviderMetadata {
                 id: "perplexity",
                 display_name: "Perplexity",
-                icon_path: "/assets/providers/perplexity.svg",
+                icon_path: "assets/providers/perplexity.svg",
                 default_base_url: "https://api.perplexity.ai",
                 requires_api_key: true,
                 supported_models: vec![
@@ -113,7 +113,7 @@ pub static PROVIDER_REGISTRY: LazyLock<HashMap<&'static str, ProviderMetadata>>
             ProviderMetadata {
                 id: "custom",
                 display_name: "Custom Provider",
-                icon_path: "/assets/providers/custom.svg",
+                icon_path: "assets/providers/custom.svg",
                 default_base_url: "https://api.example.com",
                 requires_api_key: false,
                 supported_models: vec![],
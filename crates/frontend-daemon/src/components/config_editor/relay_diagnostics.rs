@@ -0,0 +1,211 @@
+//! Relay link diagnostics: round trip time to each TLS forward server,
+//! connect history and the forwarded connections that are open
+
+use crate::services::tlsforward::{
+    ForwardedConnection, LinkEvent, LinkEventKind, ServerDiagnostics, TlsForwardDiagnostics,
+    TlsForwardService, TlsForwardStatus,
+};
+use yew::prelude::*;
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+fn status_line(status: &TlsForwardStatus) -> String {
+    match status {
+        TlsForwardStatus::Disabled => "Disabled".to_string(),
+        TlsForwardStatus::Disconnected => "Disconnected".to_string(),
+        TlsForwardStatus::Connecting => "Connecting…".to_string(),
+        TlsForwardStatus::Connected { domain } => format!("Connected as {domain}"),
+        TlsForwardStatus::Error(e) => format!("Error: {e}"),
+    }
+}
+
+fn server_row(server: &ServerDiagnostics) -> Html {
+    let rtt = server
+        .rtt_ms
+        .map(|ms| format!("{ms:.0} ms"))
+        .unwrap_or_else(|| "not measured".to_string());
+    html! {
+        <li class="py-2">
+            <div class="flex items-center justify-between">
+                <span class="text-sm font-mono text-gray-900 dark:text-gray-100 truncate">
+                    {&server.server}
+                </span>
+                <span class="text-sm text-gray-600 dark:text-gray-400">
+                    {if server.connected { format!("{rtt} · in use") } else { rtt }}
+                </span>
+            </div>
+            {if let Some(error) = &server.error {
+                html! { <p class="text-xs text-red-600 dark:text-red-400">{error}</p> }
+            } else {
+                html! {}
+            }}
+        </li>
+    }
+}
+
+fn event_row(event: &LinkEvent) -> Html {
+    let (label, class) = match event.kind {
+        LinkEventKind::Connected => ("Connected", "text-green-700 dark:text-green-300"),
+        LinkEventKind::Disconnected => ("Disconnected", "text-yellow-700 dark:text-yellow-300"),
+        LinkEventKind::Failed => ("Failed", "text-red-700 dark:text-red-300"),
+    };
+    let detail = [event.server.as_deref(), event.detail.as_deref()]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" · ");
+    html! {
+        <li class="py-1 text-xs text-gray-600 dark:text-gray-400">
+            <span class="font-mono">{event.at.format("%Y-%m-%d %H:%M:%S UTC").to_string()}</span>
+            {" "}
+            <span class={classes!("font-medium", class)}>{label}</span>
+            {if detail.is_empty() { String::new() } else { format!(" {detail}") }}
+        </li>
+    }
+}
+
+fn connection_row(connection: &ForwardedConnection) -> Html {
+    html! {
+        <li class="py-1 text-xs text-gray-600 dark:text-gray-400">
+            <span class="font-medium text-gray-900 dark:text-gray-100">
+                {connection.hostname.clone().unwrap_or_else(|| "(handshaking)".to_string())}
+            </span>
+            {format!(
+                " from {} since {} · {} in, {} out",
+                connection.peer,
+                connection.started_at.format("%H:%M:%S UTC"),
+                format_bytes(connection.bytes_received),
+                format_bytes(connection.bytes_sent)
+            )}
+        </li>
+    }
+}
+
+fn list_or_empty<T>(items: &[T], empty: &str, row: fn(&T) -> Html) -> Html {
+    if items.is_empty() {
+        html! { <p class="text-sm text-gray-600 dark:text-gray-400">{empty.to_string()}</p> }
+    } else {
+        html! {
+            <ul class="divide-y divide-gray-200 dark:divide-gray-700">
+                {for items.iter().map(row)}
+            </ul>
+        }
+    }
+}
+
+#[function_component(RelayDiagnosticsSection)]
+pub fn relay_diagnostics_section() -> Html {
+    let service = use_memo((), |_| TlsForwardService::new());
+    let diagnostics = use_state(|| Option::<TlsForwardDiagnostics>::None);
+    let error = use_state(|| Option::<String>::None);
+    let probing = use_state(|| false);
+
+    {
+        let service = service.clone();
+        let diagnostics = diagnostics.clone();
+        let error = error.clone();
+        use_effect_with((), move |_| {
+            wasm_bindgen_futures::spawn_local(async move {
+                match service.get_diagnostics(false).await {
+                    Ok(current) => diagnostics.set(Some(current)),
+                    Err(e) => error.set(Some(format!("Failed to load relay diagnostics: {e}"))),
+                }
+            });
+        });
+    }
+
+    let on_probe = {
+        let service = service.clone();
+        let diagnostics = diagnostics.clone();
+        let error = error.clone();
+        let probing = probing.clone();
+        Callback::from(move |_| {
+            let service = service.clone();
+            let diagnostics = diagnostics.clone();
+            let error = error.clone();
+            let probing = probing.clone();
+            probing.set(true);
+            wasm_bindgen_futures::spawn_local(async move {
+                match service.get_diagnostics(true).await {
+                    Ok(current) => {
+                        diagnostics.set(Some(current));
+                        error.set(None);
+                    }
+                    Err(e) => error.set(Some(format!("Failed to probe relay servers: {e}"))),
+                }
+                probing.set(false);
+            });
+        })
+    };
+
+    html! {
+        <div class="space-y-4">
+            {if let Some(err) = (*error).as_ref() {
+                html! {
+                    <div class="p-3 bg-red-50 dark:bg-red-900/20 border border-red-200 dark:border-red-800 rounded-md">
+                        <p class="text-sm text-red-700 dark:text-red-300">{err}</p>
+                    </div>
+                }
+            } else {
+                html! {}
+            }}
+            {match (*diagnostics).as_ref() {
+                Some(current) if !current.enabled => html! {
+                    <p class="text-sm text-gray-600 dark:text-gray-400">
+                        {"TLS forwarding is not running"}
+                    </p>
+                },
+                Some(current) => html! {
+                    <>
+                        <div class="flex items-center justify-between">
+                            <div class="text-sm text-gray-700 dark:text-gray-300">
+                                <p>{status_line(&current.status)}</p>
+                                <p class="text-xs text-gray-600 dark:text-gray-400">
+                                    {format!(
+                                        "{} open connections · {} in, {} out since start",
+                                        current.traffic.active_connections,
+                                        format_bytes(current.traffic.bytes_received),
+                                        format_bytes(current.traffic.bytes_sent)
+                                    )}
+                                </p>
+                            </div>
+                            <button
+                                onclick={on_probe}
+                                disabled={*probing}
+                                class="px-3 py-1 text-sm font-medium text-blue-600 dark:text-blue-400 border border-blue-600 dark:border-blue-400 rounded-md hover:bg-blue-50 dark:hover:bg-blue-900/20 disabled:opacity-50"
+                            >
+                                {if *probing { "Probing…" } else { "Probe servers" }}
+                            </button>
+                        </div>
+                        <div>
+                            <h4 class="text-sm font-medium text-gray-800 dark:text-gray-200 mb-1">{"Servers"}</h4>
+                            {list_or_empty(&current.servers, "No servers contacted yet", server_row)}
+                        </div>
+                        <div>
+                            <h4 class="text-sm font-medium text-gray-800 dark:text-gray-200 mb-1">{"Open connections"}</h4>
+                            {list_or_empty(&current.connections, "No forwarded connections", connection_row)}
+                        </div>
+                        <div>
+                            <h4 class="text-sm font-medium text-gray-800 dark:text-gray-200 mb-1">{"Connection history"}</h4>
+                            {list_or_empty(&current.events, "No connection events yet", event_row)}
+                        </div>
+                    </>
+                },
+                None => html! {},
+            }}
+        </div>
+    }
+}
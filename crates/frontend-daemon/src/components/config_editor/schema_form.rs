@@ -0,0 +1,388 @@
+//! Forms generated from the daemon's settings JSON Schema
+//!
+//! Each field is rendered from its schema: switches for booleans, bounded
+//! number inputs, selects for enums, one entry per line for string lists and
+//! nested sections for objects. Shapes without a dedicated widget, such as
+//! lists of objects and maps, are edited as JSON. Input that does not match
+//! the schema is flagged next to the field and kept out of the config.
+
+use super::shared::{ConfigField, ConfigInput, ConfigSection, ConfigSelect, ConfigToggle};
+use serde_json::{Map, Value};
+use std::rc::Rc;
+use yew::prelude::*;
+
+const TEXTAREA_CLASS: &str = "w-full px-2.5 py-1.5 text-sm border border-gray-300 dark:border-gray-600 rounded-md shadow-sm
+                              focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-blue-500
+                              bg-white dark:bg-gray-800 text-gray-900 dark:text-gray-100 font-mono";
+
+/// Widget a field is edited with
+#[derive(Debug, Clone, PartialEq)]
+enum FieldKind {
+    Bool,
+    Integer,
+    Number,
+    Text,
+    Choice(Vec<String>),
+    TextList,
+    Object,
+    Json,
+}
+
+/// Follow `$ref`s into the root's `$defs` and unwrap optional values, which
+/// the schema describes as "T or null". Returns the schema and whether null
+/// is allowed.
+fn resolve<'a>(root: &'a Value, schema: &'a Value) -> (&'a Value, bool) {
+    if let Some(name) = schema
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|r| r.strip_prefix("#/$defs/"))
+    {
+        if let Some(target) = root.get("$defs").and_then(|defs| defs.get(name)) {
+            return resolve(root, target);
+        }
+    }
+    if let Some(options) = schema.get("anyOf").and_then(Value::as_array) {
+        let non_null: Vec<_> = options.iter().filter(|o| !is_null_type(o)).collect();
+        if non_null.len() == 1 && non_null.len() < options.len() {
+            return (resolve(root, non_null[0]).0, true);
+        }
+    }
+    let nullable = schema
+        .get("type")
+        .and_then(Value::as_array)
+        .is_some_and(|types| types.iter().any(|t| t == "null"));
+    (schema, nullable)
+}
+
+fn is_null_type(schema: &Value) -> bool {
+    schema.get("type").and_then(Value::as_str) == Some("null")
+}
+
+/// First non-null type of a schema
+fn primary_type(schema: &Value) -> Option<&str> {
+    match schema.get("type")? {
+        Value::String(t) => Some(t),
+        Value::Array(types) => types
+            .iter()
+            .filter_map(Value::as_str)
+            .find(|t| *t != "null"),
+        _ => None,
+    }
+}
+
+/// Allowed values of an enum, whether listed in `enum` or as `const`
+/// alternatives
+fn choices(schema: &Value) -> Option<Vec<String>> {
+    if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+        return Some(
+            values
+                .iter()
+                .filter_map(Value::as_str)
+                .map(String::from)
+                .collect(),
+        );
+    }
+    let options = schema
+        .get("oneOf")
+        .or_else(|| schema.get("anyOf"))?
+        .as_array()?;
+    let mut values = Vec::new();
+    for option in options {
+        if let Some(value) = option.get("const").and_then(Value::as_str) {
+            values.push(value.to_string());
+        } else {
+            values.extend(choices(option)?);
+        }
+    }
+    Some(values)
+}
+
+fn field_kind(root: &Value, schema: &Value) -> FieldKind {
+    if let Some(values) = choices(schema) {
+        return FieldKind::Choice(values);
+    }
+    match primary_type(schema) {
+        Some("boolean") => FieldKind::Bool,
+        Some("integer") => FieldKind::Integer,
+        Some("number") => FieldKind::Number,
+        Some("string") => FieldKind::Text,
+        Some("array") => {
+            let items = schema.get("items").map(|items| resolve(root, items).0);
+            if items.and_then(primary_type) == Some("string") {
+                FieldKind::TextList
+            } else {
+                FieldKind::Json
+            }
+        }
+        Some("object") if schema.get("properties").is_some() => FieldKind::Object,
+        _ => FieldKind::Json,
+    }
+}
+
+/// Field name as a label: `max_tokens` becomes "Max tokens"
+fn label(name: &str) -> String {
+    let text = name.replace('_', " ");
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => text,
+    }
+}
+
+/// Text shown in the input for a value
+fn display(kind: &FieldKind, value: &Value) -> String {
+    match (kind, value) {
+        (_, Value::Null) => String::new(),
+        (FieldKind::TextList, Value::Array(items)) => items
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join("\n"),
+        (FieldKind::Json, value) => serde_json::to_string_pretty(value).unwrap_or_default(),
+        (_, Value::String(text)) => text.clone(),
+        (_, value) => value.to_string(),
+    }
+}
+
+fn check_bounds(schema: &Value, number: f64) -> Result<(), String> {
+    if let Some(minimum) = schema.get("minimum").and_then(Value::as_f64) {
+        if number < minimum {
+            return Err(format!("Must be at least {minimum}"));
+        }
+    }
+    if let Some(maximum) = schema.get("maximum").and_then(Value::as_f64) {
+        if number > maximum {
+            return Err(format!("Must be at most {maximum}"));
+        }
+    }
+    Ok(())
+}
+
+/// Value for the text in an input, or why it does not match the schema
+fn parse(kind: &FieldKind, schema: &Value, nullable: bool, text: &str) -> Result<Value, String> {
+    let trimmed = text.trim();
+    if trimmed.is_empty() && nullable && !matches!(kind, FieldKind::TextList) {
+        return Ok(Value::Null);
+    }
+    match kind {
+        FieldKind::Integer => {
+            let number: i64 = trimmed
+                .parse()
+                .map_err(|_| "Must be a whole number".to_string())?;
+            check_bounds(schema, number as f64)?;
+            Ok(Value::from(number))
+        }
+        FieldKind::Number => {
+            let number: f64 = trimmed
+                .parse()
+                .map_err(|_| "Must be a number".to_string())?;
+            check_bounds(schema, number)?;
+            Ok(Value::from(number))
+        }
+        FieldKind::TextList => Ok(Value::Array(
+            text.lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(|line| Value::String(line.to_string()))
+                .collect(),
+        )),
+        FieldKind::Json => serde_json::from_str(text).map_err(|e| format!("Invalid JSON: {e}")),
+        FieldKind::Choice(values) if !values.iter().any(|v| v == text) => {
+            Err(format!("Must be one of {}", values.join(", ")))
+        }
+        _ => Ok(Value::String(text.to_string())),
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct SchemaFieldProps {
+    /// Whole settings schema, for resolving references
+    pub root: Rc<Value>,
+    pub schema: Value,
+    pub name: String,
+    pub value: Value,
+    pub on_change: Callback<Value>,
+}
+
+/// One setting, edited with the widget its schema calls for
+#[function_component(SchemaField)]
+pub fn schema_field(props: &SchemaFieldProps) -> Html {
+    let (schema, nullable) = resolve(&props.root, &props.schema);
+    let kind = field_kind(&props.root, schema);
+    let description = props
+        .schema
+        .get("description")
+        .or_else(|| schema.get("description"))
+        .and_then(Value::as_str)
+        .map(String::from);
+    let text = {
+        let initial = display(&kind, &props.value);
+        use_state(move || initial)
+    };
+    let error = use_state(|| None::<String>);
+
+    // Follow changes made elsewhere, such as the advanced editor
+    {
+        let text = text.clone();
+        let error = error.clone();
+        let kind = kind.clone();
+        let schema = schema.clone();
+        use_effect_with(props.value.clone(), move |value| {
+            if parse(&kind, &schema, nullable, &text).as_ref() != Ok(value) {
+                text.set(display(&kind, value));
+                error.set(None);
+            }
+            || ()
+        });
+    }
+
+    let on_text = {
+        let text = text.clone();
+        let error = error.clone();
+        let kind = kind.clone();
+        let schema = schema.clone();
+        let on_change = props.on_change.clone();
+        Callback::from(move |input: String| {
+            match parse(&kind, &schema, nullable, &input) {
+                Ok(value) => {
+                    error.set(None);
+                    on_change.emit(value);
+                }
+                Err(e) => error.set(Some(e)),
+            }
+            text.set(input);
+        })
+    };
+
+    let name = label(&props.name);
+    let write_only = schema.get("writeOnly") == Some(&Value::Bool(true))
+        || props.schema.get("writeOnly") == Some(&Value::Bool(true));
+
+    let field = match &kind {
+        FieldKind::Bool => {
+            let on_change = props.on_change.clone();
+            return html! {
+                <ConfigToggle
+                    label={name}
+                    checked={props.value.as_bool().unwrap_or(false)}
+                    on_change={Callback::from(move |checked: bool| on_change.emit(Value::Bool(checked)))}
+                    help_text={description}
+                />
+            };
+        }
+        FieldKind::Object => {
+            return html! {
+                <ConfigSection title={name}>
+                    <SchemaObject
+                        root={props.root.clone()}
+                        schema={schema.clone()}
+                        value={props.value.clone()}
+                        on_change={props.on_change.clone()}
+                    />
+                </ConfigSection>
+            };
+        }
+        FieldKind::Choice(values) => {
+            let mut options: Vec<(String, String)> =
+                values.iter().map(|v| (v.clone(), v.clone())).collect();
+            if nullable {
+                options.insert(0, (String::new(), "Not set".to_string()));
+            }
+            html! {
+                <ConfigSelect value={(*text).clone()} options={options} on_change={on_text} />
+            }
+        }
+        FieldKind::TextList | FieldKind::Json => {
+            let rows = text.lines().count().clamp(2, 12).to_string();
+            html! {
+                <textarea
+                    class={TEXTAREA_CLASS}
+                    rows={rows}
+                    spellcheck="false"
+                    value={(*text).clone()}
+                    oninput={on_text.reform(|e: InputEvent| {
+                        let input: web_sys::HtmlTextAreaElement = e.target_unchecked_into();
+                        input.value()
+                    })}
+                />
+            }
+        }
+        FieldKind::Integer | FieldKind::Number | FieldKind::Text => {
+            let input_type = match kind {
+                FieldKind::Text if write_only => "password",
+                FieldKind::Text => "text",
+                _ => "number",
+            };
+            let placeholder = schema.get("default").map(|d| display(&kind, d));
+            html! {
+                <ConfigInput
+                    value={(*text).clone()}
+                    on_change={on_text}
+                    input_type={input_type}
+                    placeholder={placeholder}
+                />
+            }
+        }
+    };
+
+    html! {
+        <ConfigField label={name} help_text={description}>
+            <>
+                {field}
+                if let Some(error) = (*error).as_ref() {
+                    <p class="mt-1 text-xs text-red-600 dark:text-red-400" role="alert">{error}</p>
+                }
+            </>
+        </ConfigField>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct SchemaObjectProps {
+    /// Whole settings schema, for resolving references
+    pub root: Rc<Value>,
+    /// Object schema, already resolved
+    pub schema: Value,
+    pub value: Value,
+    pub on_change: Callback<Value>,
+}
+
+/// Every property of an object schema, in schema order
+#[function_component(SchemaObject)]
+pub fn schema_object(props: &SchemaObjectProps) -> Html {
+    let Some(properties) = props.schema.get("properties").and_then(Value::as_object) else {
+        return html! {};
+    };
+
+    properties
+        .iter()
+        .map(|(name, schema)| {
+            let value = props
+                .value
+                .get(name)
+                .cloned()
+                .or_else(|| schema.get("default").cloned())
+                .unwrap_or(Value::Null);
+            let on_change = {
+                let name = name.clone();
+                let object = props.value.as_object().cloned().unwrap_or_default();
+                let on_change = props.on_change.clone();
+                Callback::from(move |field: Value| {
+                    let mut object: Map<String, Value> = object.clone();
+                    object.insert(name.clone(), field);
+                    on_change.emit(Value::Object(object));
+                })
+            };
+            html! {
+                <SchemaField
+                    key={name.clone()}
+                    root={props.root.clone()}
+                    schema={schema.clone()}
+                    name={name.clone()}
+                    value={value}
+                    on_change={on_change}
+                />
+            }
+        })
+        .collect()
+}
@@ -1,5 +1,12 @@
+use gate_chat_ui::utils::a11y::{elements_matching, roving_index, Orientation};
+use gate_frontend_common::i18n::use_i18n;
+use web_sys::Element;
 use yew::prelude::*;
 
+/// DOM id of the panel showing the active page
+pub const CONFIG_TABPANEL_ID: &str = "config-tabpanel";
+const TAB_SELECTOR: &str = "[role=\"tab\"]";
+
 #[derive(Clone, Copy, PartialEq)]
 pub enum ConfigPage {
     Server,
@@ -7,56 +14,96 @@ pub enum ConfigPage {
     Providers,
     Network,
     Inference,
+    AllSettings,
+    Advanced,
+    History,
 }
 
 impl ConfigPage {
-    pub fn label(&self) -> &'static str {
+    /// Message key for the page name
+    pub fn label_key(&self) -> &'static str {
+        match self {
+            ConfigPage::Server => "config.page.server",
+            ConfigPage::Authentication => "config.page.authentication",
+            ConfigPage::Providers => "config.page.providers",
+            ConfigPage::Network => "config.page.network",
+            ConfigPage::Inference => "config.page.inference",
+            ConfigPage::AllSettings => "config.page.all_settings",
+            ConfigPage::Advanced => "config.page.advanced",
+            ConfigPage::History => "config.page.history",
+        }
+    }
+
+    /// Message key for the page's tooltip
+    pub fn description_key(&self) -> &'static str {
         match self {
-            ConfigPage::Server => "Server",
-            ConfigPage::Authentication => "Authentication",
-            ConfigPage::Providers => "Providers",
-            ConfigPage::Network => "Network",
-            ConfigPage::Inference => "Inference",
+            ConfigPage::Server => "config.page.server.description",
+            ConfigPage::Authentication => "config.page.authentication.description",
+            ConfigPage::Providers => "config.page.providers.description",
+            ConfigPage::Network => "config.page.network.description",
+            ConfigPage::Inference => "config.page.inference.description",
+            ConfigPage::AllSettings => "config.page.all_settings.description",
+            ConfigPage::Advanced => "config.page.advanced.description",
+            ConfigPage::History => "config.page.history.description",
+        }
+    }
+
+    /// DOM id of the page's tab, referenced by the tab panel
+    pub fn tab_id(&self) -> &'static str {
+        match self {
+            ConfigPage::Server => "config-tab-server",
+            ConfigPage::Authentication => "config-tab-authentication",
+            ConfigPage::Providers => "config-tab-providers",
+            ConfigPage::Network => "config-tab-network",
+            ConfigPage::Inference => "config-tab-inference",
+            ConfigPage::AllSettings => "config-tab-all-settings",
+            ConfigPage::Advanced => "config-tab-advanced",
+            ConfigPage::History => "config-tab-history",
         }
     }
 
     pub fn icon(&self) -> Html {
         match self {
             ConfigPage::Server => html! {
-                <svg class="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                <svg class="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24" aria-hidden="true">
                     <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M5 12h14M5 12a2 2 0 01-2-2V6a2 2 0 012-2h14a2 2 0 012 2v4a2 2 0 01-2 2M5 12a2 2 0 00-2 2v4a2 2 0 002 2h14a2 2 0 002-2v-4a2 2 0 00-2-2m-2-4h.01M17 16h.01"></path>
                 </svg>
             },
             ConfigPage::Authentication => html! {
-                <svg class="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                <svg class="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24" aria-hidden="true">
                     <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M12 15v2m-6 4h12a2 2 0 002-2v-6a2 2 0 00-2-2H6a2 2 0 00-2 2v6a2 2 0 002 2zm10-10V7a4 4 0 00-8 0v4h8z"></path>
                 </svg>
             },
             ConfigPage::Providers => html! {
-                <svg class="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                <svg class="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24" aria-hidden="true">
                     <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M13 10V3L4 14h7v7l9-11h-7z"></path>
                 </svg>
             },
             ConfigPage::Network => html! {
-                <svg class="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                <svg class="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24" aria-hidden="true">
                     <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M21 12a9 9 0 01-9 9m9-9a9 9 0 00-9-9m9 9H3m9 9a9 9 0 01-9-9m9 9c1.657 0 3-4.03 3-9s-1.343-9-3-9m0 18c-1.657 0-3-4.03-3-9s1.343-9 3-9m-9 9a9 9 0 019-9"></path>
                 </svg>
             },
             ConfigPage::Inference => html! {
-                <svg class="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                <svg class="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24" aria-hidden="true">
                     <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M9.75 17L9 20l-1 1h8l-1-1-.75-3M3 13h18M5 17h14a2 2 0 002-2V5a2 2 0 00-2-2H5a2 2 0 00-2 2v10a2 2 0 002 2z"></path>
                 </svg>
             },
-        }
-    }
-
-    pub fn description(&self) -> &'static str {
-        match self {
-            ConfigPage::Server => "Configure server host, port, and metrics settings",
-            ConfigPage::Authentication => "Manage authentication, WebAuthn, and JWT settings",
-            ConfigPage::Providers => "Configure AI provider connections and API keys",
-            ConfigPage::Network => "Setup TLS forwarding and Let's Encrypt certificates",
-            ConfigPage::Inference => "Configure local inference settings and parameters",
+            ConfigPage::AllSettings => html! {
+                <svg class="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24" aria-hidden="true">
+                    <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M4 6h16M4 12h16M4 18h7"></path>
+                </svg>
+            },
+            ConfigPage::Advanced => html! {
+                <svg class="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24" aria-hidden="true">
+                    <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M10 20l4-16m4 4l4 4-4 4M6 16l-4-4 4-4"></path>
+                </svg>
+            },
+            ConfigPage::History => html! {
+                <svg class="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24" aria-hidden="true">
+                    <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M12 8v4l3 3m6-3a9 9 0 11-18 0 9 9 0 0118 0z"></path>
+                </svg>
+            },
         }
     }
 }
@@ -69,18 +116,49 @@ pub struct SubNavProps {
 
 #[function_component(SubNav)]
 pub fn sub_nav(props: &SubNavProps) -> Html {
+    let i18n = use_i18n();
     let pages = [
         ConfigPage::Server,
         ConfigPage::Authentication,
         ConfigPage::Providers,
         ConfigPage::Network,
         ConfigPage::Inference,
+        ConfigPage::AllSettings,
+        ConfigPage::Advanced,
+        ConfigPage::History,
     ];
+    let tablist_ref = use_node_ref();
+
+    // Arrow keys, Home and End select and focus the neighbouring tab
+    let on_keydown = {
+        let tablist_ref = tablist_ref.clone();
+        let on_change = props.on_change.clone();
+        let current = pages.iter().position(|page| *page == props.active_page);
+        Callback::from(move |e: KeyboardEvent| {
+            let Some(index) = roving_index(current, pages.len(), &e.key(), Orientation::Horizontal)
+            else {
+                return;
+            };
+            e.prevent_default();
+            on_change.emit(pages[index]);
+            if let Some(tablist) = tablist_ref.cast::<Element>() {
+                if let Some(tab) = elements_matching(&tablist, TAB_SELECTOR).get(index) {
+                    let _ = tab.focus();
+                }
+            }
+        })
+    };
 
     html! {
         <div class="border-b border-gray-200 dark:border-gray-700">
             // Tab-style navigation
-            <div class="flex gap-1 px-4">
+            <div
+                ref={tablist_ref}
+                class="flex gap-1 px-4"
+                role="tablist"
+                aria-label="Configuration sections"
+                onkeydown={on_keydown}
+            >
                 {pages.iter().map(|page| {
                     let is_active = *page == props.active_page;
                     let onclick = {
@@ -91,6 +169,12 @@ pub fn sub_nav(props: &SubNavProps) -> Html {
 
                     html! {
                         <button
+                            type="button"
+                            role="tab"
+                            id={page.tab_id()}
+                            aria-selected={is_active.to_string()}
+                            aria-controls={CONFIG_TABPANEL_ID}
+                            tabindex={if is_active { "0" } else { "-1" }}
                             class={classes!(
                                 "px-4", "py-3", "flex", "items-center", "gap-2",
                                 "text-sm", "font-medium", "border-b-2", "transition-colors",
@@ -102,10 +186,10 @@ pub fn sub_nav(props: &SubNavProps) -> Html {
                                 }
                             )}
                             onclick={onclick}
-                            title={page.description()}
+                            title={i18n.t(page.description_key())}
                         >
                             {page.icon()}
-                            <span>{page.label()}</span>
+                            <span>{i18n.t(page.label_key())}</span>
                         </button>
                     }
                 }).collect::<Html>()}
@@ -135,7 +219,7 @@ pub fn sub_nav(props: &SubNavProps) -> Html {
             //                 onclick={onclick}
             //             >
             //                 {page.icon()}
-            //                 <span>{page.label()}</span>
+            //                 <span>{i18n.t(page.label_key())}</span>
             //             </button>
             //         }
             //     }).collect::<Html>()}
@@ -0,0 +1,179 @@
+//! Health of the certificates being served, with a renew button per
+//! certificate
+
+use crate::services::tls::{CertificateHealth, CertificateStatus, TlsService, TlsStatus};
+use yew::prelude::*;
+
+fn health_badge(health: CertificateHealth) -> Html {
+    let (label, class) = match health {
+        CertificateHealth::Valid => (
+            "Valid",
+            "bg-green-100 text-green-800 dark:bg-green-900/30 dark:text-green-300",
+        ),
+        CertificateHealth::Expiring => (
+            "Expiring soon",
+            "bg-yellow-100 text-yellow-800 dark:bg-yellow-900/30 dark:text-yellow-300",
+        ),
+        CertificateHealth::Expired => (
+            "Expired",
+            "bg-red-100 text-red-800 dark:bg-red-900/30 dark:text-red-300",
+        ),
+        CertificateHealth::Missing => (
+            "Not issued",
+            "bg-gray-100 text-gray-800 dark:bg-gray-700 dark:text-gray-300",
+        ),
+        CertificateHealth::Invalid => (
+            "Invalid",
+            "bg-red-100 text-red-800 dark:bg-red-900/30 dark:text-red-300",
+        ),
+    };
+    html! {
+        <span class={classes!("px-2", "py-0.5", "text-xs", "font-medium", "rounded", class)}>
+            {label}
+        </span>
+    }
+}
+
+fn certificate_row(certificate: &CertificateStatus, on_renew: Callback<String>) -> Html {
+    let name = certificate.name.clone();
+    let onclick = Callback::from(move |_| on_renew.emit(name.clone()));
+    let mut details = Vec::new();
+    if !certificate.sans.is_empty() {
+        details.push(format!("Covers {}", certificate.sans.join(", ")));
+    }
+    if let Some(issuer) = &certificate.issuer {
+        details.push(format!("Issued by {issuer}"));
+    }
+    if let (Some(at), Some(days)) = (certificate.not_after, certificate.days_left) {
+        details.push(format!("Expires {} ({days} days)", at.format("%Y-%m-%d")));
+    }
+    if let Some(attempt) = &certificate.last_renewal {
+        details.push(format!(
+            "Last renewal {} at {}{}",
+            if attempt.succeeded {
+                "succeeded"
+            } else {
+                "failed"
+            },
+            attempt.at.format("%Y-%m-%d %H:%M UTC"),
+            attempt
+                .error
+                .as_deref()
+                .map(|e| format!(": {e}"))
+                .unwrap_or_default()
+        ));
+    }
+    html! {
+        <li class="py-3 flex items-start justify-between">
+            <div class="space-y-1">
+                <div class="flex items-center space-x-2">
+                    <span class="text-sm font-medium text-gray-900 dark:text-gray-100">
+                        {&certificate.name}
+                    </span>
+                    {health_badge(certificate.health)}
+                </div>
+                {for details.into_iter().map(|line| html! {
+                    <p class="text-xs text-gray-600 dark:text-gray-400">{line}</p>
+                })}
+                {if let Some(error) = &certificate.error {
+                    html! { <p class="text-xs text-red-600 dark:text-red-400">{error}</p> }
+                } else {
+                    html! {}
+                }}
+            </div>
+            <button
+                {onclick}
+                class="px-3 py-1 text-sm font-medium text-blue-600 dark:text-blue-400 border border-blue-600 dark:border-blue-400 rounded-md hover:bg-blue-50 dark:hover:bg-blue-900/20"
+            >
+                {"Renew now"}
+            </button>
+        </li>
+    }
+}
+
+#[function_component(TlsStatusSection)]
+pub fn tls_status_section() -> Html {
+    let service = use_memo((), |_| TlsService::new());
+    let status = use_state(|| Option::<TlsStatus>::None);
+    let error = use_state(|| Option::<String>::None);
+    let renewing = use_state(|| Option::<String>::None);
+
+    {
+        let service = service.clone();
+        let status = status.clone();
+        let error = error.clone();
+        use_effect_with((), move |_| {
+            wasm_bindgen_futures::spawn_local(async move {
+                match service.get_status().await {
+                    Ok(current) => status.set(Some(current)),
+                    Err(e) => error.set(Some(format!("Failed to load certificate status: {e}"))),
+                }
+            });
+        });
+    }
+
+    let on_renew = {
+        let service = service.clone();
+        let status = status.clone();
+        let error = error.clone();
+        let renewing = renewing.clone();
+        Callback::from(move |name: String| {
+            let service = service.clone();
+            let status = status.clone();
+            let error = error.clone();
+            let renewing = renewing.clone();
+            renewing.set(Some(name.clone()));
+            wasm_bindgen_futures::spawn_local(async move {
+                match service.renew(&name).await {
+                    Ok(updated) => {
+                        status.set(Some(updated));
+                        error.set(None);
+                    }
+                    Err(e) => {
+                        error.set(Some(format!("Failed to renew {name}: {e}")));
+                        if let Ok(current) = service.get_status().await {
+                            status.set(Some(current));
+                        }
+                    }
+                }
+                renewing.set(None);
+            });
+        })
+    };
+
+    html! {
+        <div class="space-y-3">
+            {if let Some(err) = (*error).as_ref() {
+                html! {
+                    <div class="p-3 bg-red-50 dark:bg-red-900/20 border border-red-200 dark:border-red-800 rounded-md">
+                        <p class="text-sm text-red-700 dark:text-red-300">{err}</p>
+                    </div>
+                }
+            } else {
+                html! {}
+            }}
+            {if let Some(name) = (*renewing).as_ref() {
+                html! {
+                    <p class="text-sm text-gray-600 dark:text-gray-400">
+                        {format!("Renewing {name}; this can take a few minutes…")}
+                    </p>
+                }
+            } else {
+                html! {}
+            }}
+            {match (*status).as_ref() {
+                Some(current) if current.certificates.is_empty() => html! {
+                    <p class="text-sm text-gray-600 dark:text-gray-400">
+                        {"No certificates configured"}
+                    </p>
+                },
+                Some(current) => html! {
+                    <ul class="divide-y divide-gray-200 dark:divide-gray-700">
+                        {for current.certificates.iter().map(|c| certificate_row(c, on_renew.clone()))}
+                    </ul>
+                },
+                None => html! {},
+            }}
+        </div>
+    }
+}
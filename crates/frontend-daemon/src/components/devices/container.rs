@@ -0,0 +1,226 @@
+//! Device management container component
+
+use super::list::{device_label, DeviceList};
+use crate::services::devices::{CreatePairingRequest, DeviceInfo, DeviceService, PairingCode};
+use gloo::timers::callback::Timeout;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+#[function_component(DevicesContainer)]
+pub fn devices_container() -> Html {
+    let device_service = use_memo((), |_| DeviceService::new());
+
+    let devices = use_state(Vec::<DeviceInfo>::new);
+    let pending = use_state(Vec::<PairingCode>::new);
+    let is_loading = use_state(|| true);
+    let is_pairing = use_state(|| false);
+    let device_name = use_state(String::new);
+    let error = use_state(|| Option::<String>::None);
+    let success = use_state(|| Option::<String>::None);
+
+    let reload_devices = {
+        let devices = devices.clone();
+        let pending = pending.clone();
+        let is_loading = is_loading.clone();
+        let error = error.clone();
+        let device_service = device_service.clone();
+
+        Callback::from(move |_: ()| {
+            let devices = devices.clone();
+            let pending = pending.clone();
+            let is_loading = is_loading.clone();
+            let error = error.clone();
+            let device_service = device_service.clone();
+
+            wasm_bindgen_futures::spawn_local(async move {
+                match device_service.list_devices().await {
+                    Ok(response) => {
+                        devices.set(response.devices);
+                        pending.set(response.pending);
+                        error.set(None);
+                    }
+                    Err(e) => {
+                        error.set(Some(format!("Failed to load devices: {e}")));
+                    }
+                }
+                is_loading.set(false);
+            });
+        })
+    };
+
+    // Load devices on mount
+    {
+        let reload_devices = reload_devices.clone();
+        use_effect_with((), move |_| {
+            reload_devices.emit(());
+        });
+    }
+
+    // Clear success message after timeout
+    {
+        let success = success.clone();
+        use_effect_with(success.clone(), move |msg| {
+            if msg.is_some() {
+                let success = success.clone();
+                Timeout::new(3000, move || {
+                    success.set(None);
+                })
+                .forget();
+            }
+        });
+    }
+
+    let on_name_input = {
+        let device_name = device_name.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            device_name.set(input.value());
+        })
+    };
+
+    let on_add_device = {
+        let device_service = device_service.clone();
+        let is_pairing = is_pairing.clone();
+        let device_name = device_name.clone();
+        let error = error.clone();
+        let reload = reload_devices.clone();
+
+        Callback::from(move |_| {
+            let device_service = device_service.clone();
+            let is_pairing = is_pairing.clone();
+            let device_name = device_name.clone();
+            let error = error.clone();
+            let reload = reload.clone();
+
+            let name = device_name.trim().to_string();
+            let request = CreatePairingRequest {
+                user_id: None,
+                device_name: (!name.is_empty()).then_some(name),
+            };
+
+            is_pairing.set(true);
+            wasm_bindgen_futures::spawn_local(async move {
+                match device_service.create_pairing_code(&request).await {
+                    Ok(_) => {
+                        device_name.set(String::new());
+                        error.set(None);
+                        reload.emit(());
+                    }
+                    Err(e) => {
+                        error.set(Some(format!("Failed to create pairing code: {e}")));
+                    }
+                }
+                is_pairing.set(false);
+            });
+        })
+    };
+
+    let on_revoke = {
+        let device_service = device_service.clone();
+        let error = error.clone();
+        let success = success.clone();
+        let reload = reload_devices.clone();
+
+        Callback::from(move |device: DeviceInfo| {
+            let label = device_label(&device);
+            if web_sys::window()
+                .and_then(|w| {
+                    w.confirm_with_message(&format!(
+                        "Revoke '{label}'? It will no longer be able to sign in."
+                    ))
+                    .ok()
+                })
+                .unwrap_or(false)
+            {
+                let device_service = device_service.clone();
+                let error = error.clone();
+                let success = success.clone();
+                let reload = reload.clone();
+
+                wasm_bindgen_futures::spawn_local(async move {
+                    match device_service.revoke_device(&device.credential_id).await {
+                        Ok(_) => {
+                            success.set(Some(format!("Device '{label}' revoked")));
+                            reload.emit(());
+                        }
+                        Err(e) => {
+                            error.set(Some(format!("Failed to revoke device: {e}")));
+                        }
+                    }
+                });
+            }
+        })
+    };
+
+    html! {
+        <div class="p-6 max-w-7xl mx-auto">
+            <div class="mb-6 flex items-start justify-between">
+                <div>
+                    <h1 class="text-2xl font-bold text-gray-900 dark:text-gray-100">
+                        {"Devices"}
+                    </h1>
+                    <p class="mt-1 text-sm text-gray-600 dark:text-gray-400">
+                        {"Passkeys that can sign in to your account. Pair another device with a one-time code."}
+                    </p>
+                </div>
+                <div class="flex items-center space-x-2">
+                    <input
+                        type="text"
+                        placeholder="Device name"
+                        class="px-3 py-2 text-sm border border-gray-300 dark:border-gray-600 rounded-md bg-white dark:bg-gray-800 text-gray-900 dark:text-gray-100"
+                        value={(*device_name).clone()}
+                        oninput={on_name_input}
+                    />
+                    <button
+                        onclick={on_add_device}
+                        disabled={*is_pairing}
+                        class="px-4 py-2 text-sm font-medium text-white bg-blue-600 hover:bg-blue-700 disabled:opacity-50 rounded-md"
+                    >
+                        {"Add device"}
+                    </button>
+                </div>
+            </div>
+
+            {if let Some(err) = (*error).as_ref() {
+                html! {
+                    <div class="mb-4 p-4 bg-red-50 dark:bg-red-900/20 border border-red-200 dark:border-red-800 rounded-md">
+                        <p class="text-red-700 dark:text-red-300">{err}</p>
+                    </div>
+                }
+            } else {
+                html! {}
+            }}
+
+            {if let Some(msg) = (*success).as_ref() {
+                html! {
+                    <div class="mb-4 p-4 bg-green-50 dark:bg-green-900/20 border border-green-200 dark:border-green-800 rounded-md">
+                        <p class="text-green-700 dark:text-green-300">{msg}</p>
+                    </div>
+                }
+            } else {
+                html! {}
+            }}
+
+            {for pending.iter().map(|code| html! {
+                <div key={code.code.clone()} class="mb-4 p-4 bg-blue-50 dark:bg-blue-900/20 border border-blue-200 dark:border-blue-800 rounded-md">
+                    <p class="text-sm text-blue-800 dark:text-blue-300">
+                        {format!(
+                            "Enter this code on {} to pair it. Expires at {}.",
+                            code.device_name.as_deref().unwrap_or("the new device"),
+                            code.expires_at.format("%H:%M"),
+                        )}
+                    </p>
+                    <p class="mt-2 font-mono text-2xl tracking-widest text-gray-900 dark:text-gray-100">
+                        {&code.code}
+                    </p>
+                </div>
+            })}
+
+            <DeviceList
+                devices={(*devices).clone()}
+                is_loading={*is_loading}
+                on_revoke={on_revoke}
+            />
+        </div>
+    }
+}
@@ -0,0 +1,101 @@
+//! Device list view component
+
+use crate::components::user_management::shared::{EmptyState, UserListSkeleton};
+use crate::services::devices::DeviceInfo;
+use yew::prelude::*;
+
+const HEADER_CLASS: &str = "px-6 py-3 text-left text-xs font-medium text-gray-500 dark:text-gray-400 uppercase tracking-wider";
+const CELL_CLASS: &str = "px-6 py-4 whitespace-nowrap text-sm text-gray-500 dark:text-gray-400";
+
+#[derive(Properties, PartialEq)]
+pub struct DeviceListProps {
+    pub devices: Vec<DeviceInfo>,
+    pub is_loading: bool,
+    pub on_revoke: Callback<DeviceInfo>,
+}
+
+pub fn device_label(device: &DeviceInfo) -> String {
+    device
+        .device_name
+        .clone()
+        .unwrap_or_else(|| "Unnamed device".to_string())
+}
+
+#[function_component(DeviceList)]
+pub fn device_list(props: &DeviceListProps) -> Html {
+    if props.is_loading {
+        return html! { <UserListSkeleton /> };
+    }
+
+    if props.devices.is_empty() {
+        return html! {
+            <EmptyState
+                title="No devices"
+                description="Passkeys you sign in with will appear here."
+                icon={html! {
+                    <svg fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                        <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2"
+                            d="M12 18h.01M8 21h8a2 2 0 002-2V5a2 2 0 00-2-2H8a2 2 0 00-2 2v14a2 2 0 002 2z" />
+                    </svg>
+                }}
+            />
+        };
+    }
+
+    // The last passkey cannot be revoked, or the user could not sign in
+    let can_revoke = props.devices.len() > 1;
+
+    html! {
+        <div class="bg-white dark:bg-gray-800 shadow overflow-hidden rounded-lg">
+            <table class="min-w-full divide-y divide-gray-200 dark:divide-gray-700">
+                <thead class="bg-gray-50 dark:bg-gray-900">
+                    <tr>
+                        <th scope="col" class={HEADER_CLASS}>{"Device"}</th>
+                        <th scope="col" class={HEADER_CLASS}>{"Added"}</th>
+                        <th scope="col" class={HEADER_CLASS}>{"Last used"}</th>
+                        <th scope="col" class="relative px-6 py-3">
+                            <span class="sr-only">{"Actions"}</span>
+                        </th>
+                    </tr>
+                </thead>
+                <tbody class="bg-white dark:bg-gray-800 divide-y divide-gray-200 dark:divide-gray-700">
+                    {props.devices.iter().map(|device| {
+                        let on_revoke = {
+                            let device = device.clone();
+                            let on_revoke = props.on_revoke.clone();
+                            Callback::from(move |_| on_revoke.emit(device.clone()))
+                        };
+
+                        html! {
+                            <tr key={device.credential_id.clone()}>
+                                <td class="px-6 py-4 whitespace-nowrap text-sm font-medium text-gray-900 dark:text-gray-100">
+                                    {device_label(device)}
+                                </td>
+                                <td class={CELL_CLASS}>{device.created_at.format("%Y-%m-%d").to_string()}</td>
+                                <td class={CELL_CLASS}>
+                                    {device.last_used_at
+                                        .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                                        .unwrap_or_else(|| "Never".to_string())}
+                                </td>
+                                <td class="px-6 py-4 whitespace-nowrap text-right text-sm font-medium">
+                                    {if can_revoke {
+                                        html! {
+                                            <button
+                                                onclick={on_revoke}
+                                                class="text-red-600 hover:text-red-900 dark:text-red-400 dark:hover:text-red-300"
+                                            >
+                                                {"Revoke"}
+                                            </button>
+                                        }
+                                    } else {
+                                        html! {}
+                                    }}
+                                </td>
+                            </tr>
+                        }
+                    }).collect::<Html>()}
+                </tbody>
+            </table>
+        </div>
+    }
+}
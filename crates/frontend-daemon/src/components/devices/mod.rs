@@ -0,0 +1,4 @@
+pub mod container;
+pub mod list;
+
+pub use container::DevicesContainer;
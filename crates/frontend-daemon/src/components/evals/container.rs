@@ -0,0 +1,205 @@
+//! Evals container component
+
+use super::results::EvalResultsView;
+use crate::components::user_management::shared::{EmptyState, UserListSkeleton};
+use crate::services::evals::{EvalRun, EvalService, EvalStatus};
+use gloo::timers::callback::Interval;
+use yew::prelude::*;
+
+const HEADER_CLASS: &str = "px-6 py-3 text-left text-xs font-medium text-gray-500 dark:text-gray-400 uppercase tracking-wider";
+const CELL_CLASS: &str = "px-6 py-4 whitespace-nowrap text-sm text-gray-500 dark:text-gray-400";
+
+/// How often runs are refreshed while the page is open
+const REFRESH_INTERVAL_MS: u32 = 3000;
+
+fn status_label(run: &EvalRun) -> String {
+    match run.status {
+        EvalStatus::Running => format!("Running ({} / {})", run.completed, run.total),
+        EvalStatus::Complete => "Complete".to_string(),
+        EvalStatus::Failed => "Failed".to_string(),
+    }
+}
+
+#[function_component(EvalsContainer)]
+pub fn evals_container() -> Html {
+    let service = use_memo((), |_| EvalService::new());
+
+    let runs = use_state(Vec::<EvalRun>::new);
+    let selected = use_state(|| Option::<EvalRun>::None);
+    let is_loading = use_state(|| true);
+    let error = use_state(|| Option::<String>::None);
+
+    let on_view = {
+        let service = service.clone();
+        let selected = selected.clone();
+        let error = error.clone();
+
+        Callback::from(move |id: String| {
+            let service = service.clone();
+            let selected = selected.clone();
+            let error = error.clone();
+
+            wasm_bindgen_futures::spawn_local(async move {
+                match service.get_run(&id).await {
+                    Ok(run) => selected.set(Some(run)),
+                    Err(e) => error.set(Some(format!("Failed to load results: {e}"))),
+                }
+            });
+        })
+    };
+
+    let reload = {
+        let runs = runs.clone();
+        let is_loading = is_loading.clone();
+        let error = error.clone();
+        let service = service.clone();
+
+        Callback::from(move |_: ()| {
+            let runs = runs.clone();
+            let is_loading = is_loading.clone();
+            let error = error.clone();
+            let service = service.clone();
+
+            wasm_bindgen_futures::spawn_local(async move {
+                match service.list_runs().await {
+                    Ok(list) => {
+                        runs.set(list);
+                        error.set(None);
+                    }
+                    Err(e) => {
+                        error.set(Some(format!("Failed to load eval runs: {e}")));
+                    }
+                }
+                is_loading.set(false);
+            });
+        })
+    };
+
+    // Load on mount and poll while the page is open
+    {
+        let reload = reload.clone();
+        use_effect_with((), move |_| {
+            reload.emit(());
+            let interval = Interval::new(REFRESH_INTERVAL_MS, move || reload.emit(()));
+            move || drop(interval)
+        });
+    }
+
+    // Follow the selected run while its results keep arriving
+    {
+        let on_view = on_view.clone();
+        let running = (*selected)
+            .as_ref()
+            .filter(|run| run.status == EvalStatus::Running)
+            .map(|run| run.id.clone());
+        use_effect_with(running, move |running| {
+            let interval = running
+                .clone()
+                .map(|id| Interval::new(REFRESH_INTERVAL_MS, move || on_view.emit(id.clone())));
+            move || drop(interval)
+        });
+    }
+
+    let list = if *is_loading {
+        html! { <UserListSkeleton /> }
+    } else if runs.is_empty() {
+        html! {
+            <EmptyState
+                title="No eval runs"
+                description="Upload a dataset to /api/admin/evals/datasets and start a run through /api/admin/evals/runs."
+                icon={html! {
+                    <svg fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                        <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2"
+                            d="M9 5H7a2 2 0 00-2 2v12a2 2 0 002 2h10a2 2 0 002-2V7a2 2 0 00-2-2h-2M9 5a2 2 0 002 2h2a2 2 0 002-2M9 5a2 2 0 012-2h2a2 2 0 012 2m-6 9l2 2 4-4" />
+                    </svg>
+                }}
+            />
+        }
+    } else {
+        html! {
+            <div class="mb-6 bg-white dark:bg-gray-800 shadow overflow-hidden rounded-lg">
+                <table class="min-w-full divide-y divide-gray-200 dark:divide-gray-700">
+                    <thead class="bg-gray-50 dark:bg-gray-900">
+                        <tr>
+                            <th scope="col" class={HEADER_CLASS}>{"Dataset"}</th>
+                            <th scope="col" class={HEADER_CLASS}>{"Models"}</th>
+                            <th scope="col" class={HEADER_CLASS}>{"Best pass rate"}</th>
+                            <th scope="col" class={HEADER_CLASS}>{"Status"}</th>
+                            <th scope="col" class="relative px-6 py-3">
+                                <span class="sr-only">{"Actions"}</span>
+                            </th>
+                        </tr>
+                    </thead>
+                    <tbody class="bg-white dark:bg-gray-800 divide-y divide-gray-200 dark:divide-gray-700">
+                        {runs.iter().map(|run| {
+                            let view = {
+                                let id = run.id.clone();
+                                let on_view = on_view.clone();
+                                Callback::from(move |_| on_view.emit(id.clone()))
+                            };
+                            let best = run
+                                .scores
+                                .iter()
+                                .filter(|score| score.items > 0)
+                                .max_by(|a, b| a.pass_rate.total_cmp(&b.pass_rate))
+                                .map(|score| format!("{:.0}% ({})", score.pass_rate * 100.0, score.model))
+                                .unwrap_or_else(|| "-".to_string());
+
+                            html! {
+                                <tr key={run.id.clone()}>
+                                    <td class="px-6 py-4 whitespace-nowrap text-sm font-medium text-gray-900 dark:text-gray-100">
+                                        {&run.dataset_name}
+                                    </td>
+                                    <td class={CELL_CLASS}>{run.models.join(", ")}</td>
+                                    <td class={CELL_CLASS}>{best}</td>
+                                    <td class={CELL_CLASS} title={run.error.clone().unwrap_or_default()}>
+                                        {status_label(run)}
+                                    </td>
+                                    <td class="px-6 py-4 whitespace-nowrap text-right text-sm font-medium">
+                                        <button
+                                            onclick={view}
+                                            class="text-blue-600 hover:text-blue-900 dark:text-blue-400 dark:hover:text-blue-300"
+                                        >
+                                            {"Results"}
+                                        </button>
+                                    </td>
+                                </tr>
+                            }
+                        }).collect::<Html>()}
+                    </tbody>
+                </table>
+            </div>
+        }
+    };
+
+    html! {
+        <div class="p-6 max-w-7xl mx-auto">
+            <div class="mb-6">
+                <h1 class="text-2xl font-bold text-gray-900 dark:text-gray-100">
+                    {"Evals"}
+                </h1>
+                <p class="mt-1 text-sm text-gray-600 dark:text-gray-400">
+                    {"Datasets of prompts run against models, each answer graded against what was expected."}
+                </p>
+            </div>
+
+            {if let Some(err) = (*error).as_ref() {
+                html! {
+                    <div class="mb-4 p-4 bg-red-50 dark:bg-red-900/20 border border-red-200 dark:border-red-800 rounded-md">
+                        <p class="text-red-700 dark:text-red-300">{err}</p>
+                    </div>
+                }
+            } else {
+                html! {}
+            }}
+
+            {list}
+
+            {if let Some(run) = (*selected).clone() {
+                html! { <EvalResultsView {run} /> }
+            } else {
+                html! {}
+            }}
+        </div>
+    }
+}
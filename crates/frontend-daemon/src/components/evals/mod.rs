@@ -0,0 +1,4 @@
+pub mod container;
+pub mod results;
+
+pub use container::EvalsContainer;
@@ -0,0 +1,104 @@
+//! Scores and per-item results of an eval run
+
+use crate::services::evals::{EvalResult, EvalRun};
+use yew::prelude::*;
+
+const HEADER_CLASS: &str = "px-6 py-3 text-left text-xs font-medium text-gray-500 dark:text-gray-400 uppercase tracking-wider";
+const CELL_CLASS: &str = "px-6 py-4 whitespace-nowrap text-sm text-gray-500 dark:text-gray-400";
+
+/// Longest answer shown in the table before it is cut short
+const ANSWER_PREVIEW_CHARS: usize = 120;
+
+#[derive(Properties, PartialEq)]
+pub struct EvalResultsViewProps {
+    pub run: EvalRun,
+}
+
+fn outcome(result: &EvalResult) -> Html {
+    let (label, class) = match (&result.error, result.passed) {
+        (Some(_), _) => ("Error", "text-yellow-700 dark:text-yellow-300"),
+        (None, true) => ("Pass", "text-green-700 dark:text-green-300"),
+        (None, false) => ("Fail", "text-red-700 dark:text-red-300"),
+    };
+    html! { <span class={class}>{label}</span> }
+}
+
+fn preview(result: &EvalResult) -> String {
+    let text = result
+        .error
+        .as_deref()
+        .or(result.answer.as_deref())
+        .unwrap_or_default();
+    if text.chars().count() > ANSWER_PREVIEW_CHARS {
+        let cut: String = text.chars().take(ANSWER_PREVIEW_CHARS).collect();
+        format!("{cut}…")
+    } else {
+        text.to_string()
+    }
+}
+
+#[function_component(EvalResultsView)]
+pub fn eval_results_view(props: &EvalResultsViewProps) -> Html {
+    let run = &props.run;
+
+    html! {
+        <div class="bg-white dark:bg-gray-800 shadow overflow-hidden rounded-lg">
+            <div class="px-6 py-4 border-b border-gray-200 dark:border-gray-700">
+                <h2 class="text-lg font-medium text-gray-900 dark:text-gray-100">{&run.dataset_name}</h2>
+                <p class="text-sm text-gray-500 dark:text-gray-400">
+                    {format!("{} of {} answers graded", run.completed, run.total)}
+                </p>
+            </div>
+            <table class="min-w-full divide-y divide-gray-200 dark:divide-gray-700">
+                <thead class="bg-gray-50 dark:bg-gray-900">
+                    <tr>
+                        <th scope="col" class={HEADER_CLASS}>{"Model"}</th>
+                        <th scope="col" class={HEADER_CLASS}>{"Passed"}</th>
+                        <th scope="col" class={HEADER_CLASS}>{"Mean score"}</th>
+                        <th scope="col" class={HEADER_CLASS}>{"Errors"}</th>
+                        <th scope="col" class={HEADER_CLASS}>{"Mean latency"}</th>
+                    </tr>
+                </thead>
+                <tbody class="bg-white dark:bg-gray-800 divide-y divide-gray-200 dark:divide-gray-700">
+                    {run.scores.iter().map(|score| html! {
+                        <tr key={score.model.clone()}>
+                            <td class="px-6 py-4 whitespace-nowrap text-sm font-medium text-gray-900 dark:text-gray-100">
+                                {&score.model}
+                            </td>
+                            <td class={CELL_CLASS}>
+                                {format!("{:.0}% ({} / {})", score.pass_rate * 100.0, score.passed, score.items)}
+                            </td>
+                            <td class={CELL_CLASS}>{format!("{:.2}", score.mean_score)}</td>
+                            <td class={CELL_CLASS}>{score.errors}</td>
+                            <td class={CELL_CLASS}>{format!("{:.0} ms", score.mean_latency_ms)}</td>
+                        </tr>
+                    }).collect::<Html>()}
+                </tbody>
+            </table>
+            <table class="min-w-full divide-y divide-gray-200 dark:divide-gray-700 border-t border-gray-200 dark:border-gray-700">
+                <thead class="bg-gray-50 dark:bg-gray-900">
+                    <tr>
+                        <th scope="col" class={HEADER_CLASS}>{"Item"}</th>
+                        <th scope="col" class={HEADER_CLASS}>{"Model"}</th>
+                        <th scope="col" class={HEADER_CLASS}>{"Result"}</th>
+                        <th scope="col" class={HEADER_CLASS}>{"Latency"}</th>
+                        <th scope="col" class={HEADER_CLASS}>{"Answer"}</th>
+                    </tr>
+                </thead>
+                <tbody class="bg-white dark:bg-gray-800 divide-y divide-gray-200 dark:divide-gray-700">
+                    {run.results.iter().map(|result| html! {
+                        <tr key={format!("{}-{}", result.item, result.model)}>
+                            <td class={CELL_CLASS}>
+                                {result.item_id.clone().unwrap_or_else(|| (result.item + 1).to_string())}
+                            </td>
+                            <td class={CELL_CLASS}>{&result.model}</td>
+                            <td class={CELL_CLASS}>{outcome(result)}</td>
+                            <td class={CELL_CLASS}>{format!("{} ms", result.latency_ms)}</td>
+                            <td class="px-6 py-4 text-sm text-gray-500 dark:text-gray-400">{preview(result)}</td>
+                        </tr>
+                    }).collect::<Html>()}
+                </tbody>
+            </table>
+        </div>
+    }
+}
@@ -0,0 +1,218 @@
+//! Experiments container component
+
+use super::results::ExperimentResultsView;
+use crate::components::user_management::shared::{EmptyState, UserListSkeleton};
+use crate::services::experiments::{
+    Experiment, ExperimentResults, ExperimentService, ExperimentStatus,
+};
+use yew::prelude::*;
+
+const HEADER_CLASS: &str = "px-6 py-3 text-left text-xs font-medium text-gray-500 dark:text-gray-400 uppercase tracking-wider";
+const CELL_CLASS: &str = "px-6 py-4 whitespace-nowrap text-sm text-gray-500 dark:text-gray-400";
+
+fn status_label(status: ExperimentStatus) -> &'static str {
+    match status {
+        ExperimentStatus::Draft => "Draft",
+        ExperimentStatus::Running => "Running",
+        ExperimentStatus::Stopped => "Stopped",
+    }
+}
+
+#[function_component(ExperimentsContainer)]
+pub fn experiments_container() -> Html {
+    let service = use_memo((), |_| ExperimentService::new());
+
+    let experiments = use_state(Vec::<Experiment>::new);
+    let selected = use_state(|| Option::<ExperimentResults>::None);
+    let is_loading = use_state(|| true);
+    let error = use_state(|| Option::<String>::None);
+
+    let reload = {
+        let experiments = experiments.clone();
+        let is_loading = is_loading.clone();
+        let error = error.clone();
+        let service = service.clone();
+
+        Callback::from(move |_: ()| {
+            let experiments = experiments.clone();
+            let is_loading = is_loading.clone();
+            let error = error.clone();
+            let service = service.clone();
+
+            wasm_bindgen_futures::spawn_local(async move {
+                match service.list_experiments().await {
+                    Ok(list) => {
+                        experiments.set(list);
+                        error.set(None);
+                    }
+                    Err(e) => {
+                        error.set(Some(format!("Failed to load experiments: {e}")));
+                    }
+                }
+                is_loading.set(false);
+            });
+        })
+    };
+
+    // Load experiments on mount
+    {
+        let reload = reload.clone();
+        use_effect_with((), move |_| {
+            reload.emit(());
+        });
+    }
+
+    let on_view = {
+        let service = service.clone();
+        let selected = selected.clone();
+        let error = error.clone();
+
+        Callback::from(move |id: String| {
+            let service = service.clone();
+            let selected = selected.clone();
+            let error = error.clone();
+
+            wasm_bindgen_futures::spawn_local(async move {
+                match service.get_results(&id).await {
+                    Ok(results) => selected.set(Some(results)),
+                    Err(e) => error.set(Some(format!("Failed to load results: {e}"))),
+                }
+            });
+        })
+    };
+
+    let on_toggle = {
+        let service = service.clone();
+        let error = error.clone();
+        let reload = reload.clone();
+
+        Callback::from(move |experiment: Experiment| {
+            let service = service.clone();
+            let error = error.clone();
+            let reload = reload.clone();
+            let status = if experiment.status == ExperimentStatus::Running {
+                ExperimentStatus::Stopped
+            } else {
+                ExperimentStatus::Running
+            };
+
+            wasm_bindgen_futures::spawn_local(async move {
+                match service.set_status(&experiment, status).await {
+                    Ok(_) => reload.emit(()),
+                    Err(e) => error.set(Some(format!("Failed to update experiment: {e}"))),
+                }
+            });
+        })
+    };
+
+    let list = if *is_loading {
+        html! { <UserListSkeleton /> }
+    } else if experiments.is_empty() {
+        html! {
+            <EmptyState
+                title="No experiments"
+                description="Experiments created through /api/admin/experiments will appear here."
+                icon={html! {
+                    <svg fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                        <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2"
+                            d="M9 19v-6a2 2 0 00-2-2H5a2 2 0 00-2 2v6a2 2 0 002 2h2a2 2 0 002-2zm0 0V9a2 2 0 012-2h2a2 2 0 012 2v10m-6 0a2 2 0 002 2h2a2 2 0 002-2m0 0V5a2 2 0 012-2h2a2 2 0 012 2v14a2 2 0 01-2 2h-2a2 2 0 01-2-2z" />
+                    </svg>
+                }}
+            />
+        }
+    } else {
+        html! {
+            <div class="mb-6 bg-white dark:bg-gray-800 shadow overflow-hidden rounded-lg">
+                <table class="min-w-full divide-y divide-gray-200 dark:divide-gray-700">
+                    <thead class="bg-gray-50 dark:bg-gray-900">
+                        <tr>
+                            <th scope="col" class={HEADER_CLASS}>{"Experiment"}</th>
+                            <th scope="col" class={HEADER_CLASS}>{"Model"}</th>
+                            <th scope="col" class={HEADER_CLASS}>{"Variants"}</th>
+                            <th scope="col" class={HEADER_CLASS}>{"Status"}</th>
+                            <th scope="col" class="relative px-6 py-3">
+                                <span class="sr-only">{"Actions"}</span>
+                            </th>
+                        </tr>
+                    </thead>
+                    <tbody class="bg-white dark:bg-gray-800 divide-y divide-gray-200 dark:divide-gray-700">
+                        {experiments.iter().map(|experiment| {
+                            let view = {
+                                let id = experiment.id.clone();
+                                let on_view = on_view.clone();
+                                Callback::from(move |_| on_view.emit(id.clone()))
+                            };
+                            let toggle = {
+                                let experiment = experiment.clone();
+                                let on_toggle = on_toggle.clone();
+                                Callback::from(move |_| on_toggle.emit(experiment.clone()))
+                            };
+                            let running = experiment.status == ExperimentStatus::Running;
+
+                            html! {
+                                <tr key={experiment.id.clone()}>
+                                    <td class="px-6 py-4 whitespace-nowrap text-sm font-medium text-gray-900 dark:text-gray-100">
+                                        {&experiment.name}
+                                    </td>
+                                    <td class={CELL_CLASS}>{&experiment.model}</td>
+                                    <td class={CELL_CLASS}>
+                                        {experiment.variants.iter()
+                                            .map(|v| format!("{} ({})", v.name, v.weight))
+                                            .collect::<Vec<_>>()
+                                            .join(", ")}
+                                    </td>
+                                    <td class={CELL_CLASS}>{status_label(experiment.status)}</td>
+                                    <td class="px-6 py-4 whitespace-nowrap text-right text-sm font-medium space-x-4">
+                                        <button
+                                            onclick={view}
+                                            class="text-blue-600 hover:text-blue-900 dark:text-blue-400 dark:hover:text-blue-300"
+                                        >
+                                            {"Results"}
+                                        </button>
+                                        <button
+                                            onclick={toggle}
+                                            class="text-gray-600 hover:text-gray-900 dark:text-gray-400 dark:hover:text-gray-200"
+                                        >
+                                            {if running { "Stop" } else { "Start" }}
+                                        </button>
+                                    </td>
+                                </tr>
+                            }
+                        }).collect::<Html>()}
+                    </tbody>
+                </table>
+            </div>
+        }
+    };
+
+    html! {
+        <div class="p-6 max-w-7xl mx-auto">
+            <div class="mb-6">
+                <h1 class="text-2xl font-bold text-gray-900 dark:text-gray-100">
+                    {"Experiments"}
+                </h1>
+                <p class="mt-1 text-sm text-gray-600 dark:text-gray-400">
+                    {"A/B tests between models, prompts and parameters. Each user is kept on one variant."}
+                </p>
+            </div>
+
+            {if let Some(err) = (*error).as_ref() {
+                html! {
+                    <div class="mb-4 p-4 bg-red-50 dark:bg-red-900/20 border border-red-200 dark:border-red-800 rounded-md">
+                        <p class="text-red-700 dark:text-red-300">{err}</p>
+                    </div>
+                }
+            } else {
+                html! {}
+            }}
+
+            {list}
+
+            {if let Some(results) = (*selected).clone() {
+                html! { <ExperimentResultsView {results} /> }
+            } else {
+                html! {}
+            }}
+        </div>
+    }
+}
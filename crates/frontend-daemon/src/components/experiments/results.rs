@@ -0,0 +1,103 @@
+//! Per-variant results of an experiment
+
+use crate::services::experiments::{ExperimentResults, VariantResults};
+use yew::prelude::*;
+
+const HEADER_CLASS: &str = "px-6 py-3 text-left text-xs font-medium text-gray-500 dark:text-gray-400 uppercase tracking-wider";
+const CELL_CLASS: &str = "px-6 py-4 whitespace-nowrap text-sm text-gray-500 dark:text-gray-400";
+
+#[derive(Properties, PartialEq)]
+pub struct ExperimentResultsViewProps {
+    pub results: ExperimentResults,
+}
+
+fn feedback(results: &VariantResults) -> String {
+    match results.positive_rate {
+        Some(rate) => format!(
+            "{:.0}% ({} / {})",
+            rate * 100.0,
+            results.positive_feedback,
+            results.positive_feedback + results.negative_feedback
+        ),
+        None => "No ratings".to_string(),
+    }
+}
+
+#[function_component(ExperimentResultsView)]
+pub fn experiment_results_view(props: &ExperimentResultsViewProps) -> Html {
+    let experiment = &props.results.experiment;
+    let shows = |metric: &str| {
+        experiment.metrics.is_empty() || experiment.metrics.iter().any(|m| m == metric)
+    };
+
+    html! {
+        <div class="bg-white dark:bg-gray-800 shadow overflow-hidden rounded-lg">
+            <div class="px-6 py-4 border-b border-gray-200 dark:border-gray-700">
+                <h2 class="text-lg font-medium text-gray-900 dark:text-gray-100">{&experiment.name}</h2>
+                <p class="text-sm text-gray-500 dark:text-gray-400">
+                    {format!("Requests for {}", experiment.model)}
+                </p>
+            </div>
+            <table class="min-w-full divide-y divide-gray-200 dark:divide-gray-700">
+                <thead class="bg-gray-50 dark:bg-gray-900">
+                    <tr>
+                        <th scope="col" class={HEADER_CLASS}>{"Variant"}</th>
+                        <th scope="col" class={HEADER_CLASS}>{"Requests"}</th>
+                        <th scope="col" class={HEADER_CLASS}>{"Errors"}</th>
+                        {if shows("latency") {
+                            html! {
+                                <>
+                                    <th scope="col" class={HEADER_CLASS}>{"Mean latency"}</th>
+                                    <th scope="col" class={HEADER_CLASS}>{"p95 latency"}</th>
+                                </>
+                            }
+                        } else {
+                            html! {}
+                        }}
+                        {if shows("cost") {
+                            html! { <th scope="col" class={HEADER_CLASS}>{"Mean cost"}</th> }
+                        } else {
+                            html! {}
+                        }}
+                        {if shows("feedback") {
+                            html! { <th scope="col" class={HEADER_CLASS}>{"Thumbs up"}</th> }
+                        } else {
+                            html! {}
+                        }}
+                    </tr>
+                </thead>
+                <tbody class="bg-white dark:bg-gray-800 divide-y divide-gray-200 dark:divide-gray-700">
+                    {props.results.variants.iter().map(|results| html! {
+                        <tr key={results.variant.clone()}>
+                            <td class="px-6 py-4 whitespace-nowrap text-sm font-medium text-gray-900 dark:text-gray-100">
+                                {&results.variant}
+                            </td>
+                            <td class={CELL_CLASS}>{results.requests}</td>
+                            <td class={CELL_CLASS}>{results.errors}</td>
+                            {if shows("latency") {
+                                html! {
+                                    <>
+                                        <td class={CELL_CLASS}>{format!("{:.0} ms", results.mean_latency_ms)}</td>
+                                        <td class={CELL_CLASS}>{format!("{} ms", results.p95_latency_ms)}</td>
+                                    </>
+                                }
+                            } else {
+                                html! {}
+                            }}
+                            {if shows("cost") {
+                                html! { <td class={CELL_CLASS}>{format!("${:.4}", results.mean_cost)}</td> }
+                            } else {
+                                html! {}
+                            }}
+                            {if shows("feedback") {
+                                html! { <td class={CELL_CLASS}>{feedback(results)}</td> }
+                            } else {
+                                html! {}
+                            }}
+                        </tr>
+                    }).collect::<Html>()}
+                </tbody>
+            </table>
+        </div>
+    }
+}
@@ -1,5 +1,17 @@
+mod api_keys;
 mod config_editor;
+mod devices;
+mod evals;
+mod experiments;
+mod providers;
+mod server_mode;
 pub mod user_management;
 
-pub use config_editor::ConfigEditor;
+pub use api_keys::ApiKeysContainer;
+pub use config_editor::{ConfigEditor, ConfigPage};
+pub use devices::DevicesContainer;
+pub use evals::EvalsContainer;
+pub use experiments::ExperimentsContainer;
+pub use providers::ProvidersContainer;
+pub use server_mode::ServerModeContainer;
 pub use user_management::UserManagementContainer;
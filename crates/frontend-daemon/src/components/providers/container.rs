@@ -0,0 +1,207 @@
+//! Provider status page container component
+
+use super::list::ProviderList;
+use crate::services::providers::{ProviderService, ProviderStatus};
+use gloo::timers::callback::{Interval, Timeout};
+use yew::prelude::*;
+
+/// How often the page re-reads provider health while open
+const REFRESH_INTERVAL_MS: u32 = 15_000;
+
+#[derive(Properties, PartialEq)]
+pub struct ProvidersContainerProps {
+    /// Called when "Edit" is clicked on a configured provider
+    pub on_edit: Callback<()>,
+}
+
+#[function_component(ProvidersContainer)]
+pub fn providers_container(props: &ProvidersContainerProps) -> Html {
+    let provider_service = use_memo((), |_| ProviderService::new());
+
+    let providers = use_state(Vec::<ProviderStatus>::new);
+    let is_loading = use_state(|| true);
+    let probing = use_state(|| Option::<String>::None);
+    let error = use_state(|| Option::<String>::None);
+    let success = use_state(|| Option::<String>::None);
+
+    let reload = {
+        let providers = providers.clone();
+        let is_loading = is_loading.clone();
+        let error = error.clone();
+        let provider_service = provider_service.clone();
+
+        Callback::from(move |_: ()| {
+            let providers = providers.clone();
+            let is_loading = is_loading.clone();
+            let error = error.clone();
+            let provider_service = provider_service.clone();
+
+            wasm_bindgen_futures::spawn_local(async move {
+                match provider_service.list().await {
+                    Ok(list) => {
+                        providers.set(list);
+                        error.set(None);
+                    }
+                    Err(e) => {
+                        error.set(Some(format!("Failed to load providers: {e}")));
+                    }
+                }
+                is_loading.set(false);
+            });
+        })
+    };
+
+    // Load on mount and poll while the page is open
+    {
+        let reload = reload.clone();
+        use_effect_with((), move |_| {
+            reload.emit(());
+            let interval = Interval::new(REFRESH_INTERVAL_MS, move || reload.emit(()));
+            move || drop(interval)
+        });
+    }
+
+    // Clear success message after timeout
+    {
+        let success = success.clone();
+        use_effect_with(success.clone(), move |msg| {
+            if msg.is_some() {
+                let success = success.clone();
+                Timeout::new(3000, move || {
+                    success.set(None);
+                })
+                .forget();
+            }
+        });
+    }
+
+    let on_probe = {
+        let provider_service = provider_service.clone();
+        let probing = probing.clone();
+        let error = error.clone();
+        let success = success.clone();
+        let reload = reload.clone();
+
+        Callback::from(move |id: String| {
+            let provider_service = provider_service.clone();
+            let probing = probing.clone();
+            let error = error.clone();
+            let success = success.clone();
+            let reload = reload.clone();
+
+            probing.set(Some(id.clone()));
+            wasm_bindgen_futures::spawn_local(async move {
+                match provider_service.probe(&id).await {
+                    Ok(status) => {
+                        let outcome = if status.healthy {
+                            "is healthy".to_string()
+                        } else {
+                            format!(
+                                "failed: {}",
+                                status.last_error.as_deref().unwrap_or("unknown error")
+                            )
+                        };
+                        success.set(Some(format!("Probe of {id} {outcome}")));
+                        reload.emit(());
+                    }
+                    Err(e) => {
+                        error.set(Some(format!("Failed to probe provider: {e}")));
+                    }
+                }
+                probing.set(None);
+            });
+        })
+    };
+
+    let on_toggle = {
+        let provider_service = provider_service.clone();
+        let error = error.clone();
+        let success = success.clone();
+        let reload = reload.clone();
+
+        Callback::from(move |provider: ProviderStatus| {
+            let enable = provider.disabled;
+            if !enable
+                && !web_sys::window()
+                    .and_then(|w| {
+                        w.confirm_with_message(&format!(
+                            "Disable {}? Requests will no longer be routed to it.",
+                            provider.id
+                        ))
+                        .ok()
+                    })
+                    .unwrap_or(false)
+            {
+                return;
+            }
+
+            let provider_service = provider_service.clone();
+            let error = error.clone();
+            let success = success.clone();
+            let reload = reload.clone();
+
+            wasm_bindgen_futures::spawn_local(async move {
+                match provider_service.set_enabled(&provider.id, enable).await {
+                    Ok(_) => {
+                        let verb = if enable { "enabled" } else { "disabled" };
+                        success.set(Some(format!("{} {verb}", provider.id)));
+                        reload.emit(());
+                    }
+                    Err(e) => {
+                        error.set(Some(format!("Failed to update provider: {e}")));
+                    }
+                }
+            });
+        })
+    };
+
+    html! {
+        <div class="p-6 max-w-7xl mx-auto">
+            <div class="mb-6 flex items-start justify-between">
+                <div>
+                    <h1 class="text-2xl font-bold text-gray-900 dark:text-gray-100">
+                        {"Providers"}
+                    </h1>
+                    <p class="mt-1 text-sm text-gray-600 dark:text-gray-400">
+                        {"Health of each upstream connection the gateway routes to"}
+                    </p>
+                </div>
+                <button
+                    onclick={reload.reform(|_: MouseEvent| ())}
+                    class="px-4 py-2 text-sm font-medium text-gray-700 dark:text-gray-300 bg-gray-100 dark:bg-gray-700 hover:bg-gray-200 dark:hover:bg-gray-600 rounded-md"
+                >
+                    {"Refresh"}
+                </button>
+            </div>
+
+            {if let Some(err) = (*error).as_ref() {
+                html! {
+                    <div class="mb-4 p-4 bg-red-50 dark:bg-red-900/20 border border-red-200 dark:border-red-800 rounded-md">
+                        <p class="text-red-700 dark:text-red-300">{err}</p>
+                    </div>
+                }
+            } else {
+                html! {}
+            }}
+
+            {if let Some(msg) = (*success).as_ref() {
+                html! {
+                    <div class="mb-4 p-4 bg-green-50 dark:bg-green-900/20 border border-green-200 dark:border-green-800 rounded-md">
+                        <p class="text-green-700 dark:text-green-300">{msg}</p>
+                    </div>
+                }
+            } else {
+                html! {}
+            }}
+
+            <ProviderList
+                providers={(*providers).clone()}
+                is_loading={*is_loading}
+                probing={(*probing).clone()}
+                on_probe={on_probe}
+                on_toggle={on_toggle}
+                on_edit={props.on_edit.clone()}
+            />
+        </div>
+    }
+}
@@ -0,0 +1,177 @@
+//! Provider status table
+
+use super::sparkline::Sparkline;
+use crate::components::user_management::shared::{EmptyState, UserListSkeleton};
+use crate::services::providers::{CircuitState, KeyStatus, ProviderStatus};
+use yew::prelude::*;
+
+const HEADER_CLASS: &str = "px-6 py-3 text-left text-xs font-medium text-gray-500 dark:text-gray-400 uppercase tracking-wider";
+const CELL_CLASS: &str = "px-6 py-4 whitespace-nowrap text-sm text-gray-500 dark:text-gray-400";
+const BADGE_CLASS: &str = "px-2 inline-flex text-xs leading-5 font-semibold rounded-full";
+const GREEN_BADGE: &str = "bg-green-100 text-green-800 dark:bg-green-900/30 dark:text-green-300";
+const YELLOW_BADGE: &str =
+    "bg-yellow-100 text-yellow-800 dark:bg-yellow-900/30 dark:text-yellow-300";
+const RED_BADGE: &str = "bg-red-100 text-red-800 dark:bg-red-900/30 dark:text-red-300";
+const GRAY_BADGE: &str = "bg-gray-100 text-gray-700 dark:bg-gray-700 dark:text-gray-300";
+
+#[derive(Properties, PartialEq)]
+pub struct ProviderListProps {
+    pub providers: Vec<ProviderStatus>,
+    pub is_loading: bool,
+    /// Id of the provider currently being probed
+    pub probing: Option<String>,
+    pub on_probe: Callback<String>,
+    pub on_toggle: Callback<ProviderStatus>,
+    /// Open the provider configuration; only offered for configured providers
+    pub on_edit: Callback<()>,
+}
+
+fn badge(label: &str, color: &'static str) -> Html {
+    html! { <span class={classes!(BADGE_CLASS, color)}>{label.to_string()}</span> }
+}
+
+fn status_badge(provider: &ProviderStatus) -> Html {
+    if provider.disabled {
+        badge("Disabled", GRAY_BADGE)
+    } else if provider.healthy {
+        badge("Healthy", GREEN_BADGE)
+    } else {
+        badge("Unhealthy", RED_BADGE)
+    }
+}
+
+fn circuit_badge(circuit: CircuitState) -> Html {
+    match circuit {
+        CircuitState::Closed => badge("Closed", GREEN_BADGE),
+        CircuitState::HalfOpen => badge("Half-open", YELLOW_BADGE),
+        CircuitState::Open => badge("Open", RED_BADGE),
+    }
+}
+
+fn key_badge(status: KeyStatus) -> Html {
+    match status {
+        KeyStatus::Configured => badge("Configured", GREEN_BADGE),
+        KeyStatus::Missing => badge("Missing", RED_BADGE),
+        KeyStatus::AwaitingCapture => badge("Awaiting capture", YELLOW_BADGE),
+        KeyStatus::NotRequired => badge("Not required", GRAY_BADGE),
+    }
+}
+
+#[function_component(ProviderList)]
+pub fn provider_list(props: &ProviderListProps) -> Html {
+    if props.is_loading {
+        return html! { <UserListSkeleton /> };
+    }
+
+    if props.providers.is_empty() {
+        return html! {
+            <EmptyState
+                title="No providers"
+                description="Add a provider in the configuration to start routing requests."
+                icon={html! {
+                    <svg fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                        <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2"
+                            d="M5 12h14M5 12a2 2 0 01-2-2V6a2 2 0 012-2h14a2 2 0 012 2v4a2 2 0 01-2 2M5 12a2 2 0 00-2 2v4a2 2 0 002 2h14a2 2 0 002-2v-4a2 2 0 00-2-2" />
+                    </svg>
+                }}
+            />
+        };
+    }
+
+    html! {
+        <div class="bg-white dark:bg-gray-800 shadow overflow-x-auto rounded-lg">
+            <table class="min-w-full divide-y divide-gray-200 dark:divide-gray-700">
+                <thead class="bg-gray-50 dark:bg-gray-900">
+                    <tr>
+                        <th scope="col" class={HEADER_CLASS}>{"Provider"}</th>
+                        <th scope="col" class={HEADER_CLASS}>{"Status"}</th>
+                        <th scope="col" class={HEADER_CLASS}>{"Latency"}</th>
+                        <th scope="col" class={HEADER_CLASS}>{"Error rate"}</th>
+                        <th scope="col" class={HEADER_CLASS}>{"Circuit"}</th>
+                        <th scope="col" class={HEADER_CLASS}>{"Key"}</th>
+                        <th scope="col" class="relative px-6 py-3">
+                            <span class="sr-only">{"Actions"}</span>
+                        </th>
+                    </tr>
+                </thead>
+                <tbody class="bg-white dark:bg-gray-800 divide-y divide-gray-200 dark:divide-gray-700">
+                    {for props.providers.iter().map(|provider| {
+                        let is_probing = props.probing.as_deref() == Some(provider.id.as_str());
+                        let on_probe = {
+                            let id = provider.id.clone();
+                            props.on_probe.reform(move |_: MouseEvent| id.clone())
+                        };
+                        let on_toggle = {
+                            let provider = provider.clone();
+                            props.on_toggle.reform(move |_: MouseEvent| provider.clone())
+                        };
+                        let on_edit = provider
+                            .config_name
+                            .is_some()
+                            .then(|| props.on_edit.reform(|_: MouseEvent| ()));
+                        let latency = provider
+                            .latency_ms
+                            .map(|ms| format!("{ms} ms"))
+                            .unwrap_or_else(|| "—".to_string());
+
+                        html! {
+                            <tr key={provider.id.clone()}>
+                                <td class="px-6 py-4 whitespace-nowrap">
+                                    <div class="text-sm font-medium text-gray-900 dark:text-gray-100 font-mono">
+                                        {&provider.id}
+                                    </div>
+                                    {if let Some(err) = provider.last_error.as_ref() {
+                                        html! {
+                                            <div class="text-xs text-red-600 dark:text-red-400 truncate max-w-xs" title={err.clone()}>
+                                                {err}
+                                            </div>
+                                        }
+                                    } else {
+                                        html! {}
+                                    }}
+                                </td>
+                                <td class={CELL_CLASS}>{status_badge(provider)}</td>
+                                <td class={CELL_CLASS}>
+                                    <div class="flex items-center gap-3">
+                                        <span class="w-16">{latency}</span>
+                                        <Sparkline samples={provider.history.clone()} />
+                                    </div>
+                                </td>
+                                <td class={CELL_CLASS}>{format!("{:.0}%", provider.error_rate * 100.0)}</td>
+                                <td class={CELL_CLASS}>{circuit_badge(provider.circuit)}</td>
+                                <td class={CELL_CLASS}>{key_badge(provider.key_status)}</td>
+                                <td class="px-6 py-4 whitespace-nowrap text-right text-sm font-medium space-x-3">
+                                    <button
+                                        onclick={on_probe}
+                                        disabled={is_probing}
+                                        class="text-blue-600 hover:text-blue-900 dark:text-blue-400 dark:hover:text-blue-300 disabled:opacity-50"
+                                    >
+                                        {if is_probing { "Probing..." } else { "Probe" }}
+                                    </button>
+                                    <button
+                                        onclick={on_toggle}
+                                        class="text-yellow-600 hover:text-yellow-900 dark:text-yellow-400 dark:hover:text-yellow-300"
+                                    >
+                                        {if provider.disabled { "Enable" } else { "Disable" }}
+                                    </button>
+                                    {if let Some(on_edit) = on_edit {
+                                        html! {
+                                            <button
+                                                onclick={on_edit}
+                                                class="text-gray-600 hover:text-gray-900 dark:text-gray-400 dark:hover:text-gray-200"
+                                            >
+                                                {"Edit"}
+                                            </button>
+                                        }
+                                    } else {
+                                        html! {}
+                                    }}
+                                </td>
+                            </tr>
+                        }
+                    })}
+                </tbody>
+            </table>
+        </div>
+    }
+}
@@ -0,0 +1,5 @@
+pub mod container;
+pub mod list;
+pub mod sparkline;
+
+pub use container::ProvidersContainer;
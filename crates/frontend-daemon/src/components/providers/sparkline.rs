@@ -0,0 +1,70 @@
+//! Inline latency sparkline
+
+use crate::services::providers::HealthSample;
+use yew::prelude::*;
+
+const WIDTH: f64 = 120.0;
+const HEIGHT: f64 = 24.0;
+
+#[derive(Properties, PartialEq)]
+pub struct SparklineProps {
+    pub samples: Vec<HealthSample>,
+}
+
+/// Latency over time as a polyline; unhealthy samples are marked in red
+#[function_component(Sparkline)]
+pub fn sparkline(props: &SparklineProps) -> Html {
+    let points: Vec<(usize, u64)> = props
+        .samples
+        .iter()
+        .enumerate()
+        .filter_map(|(i, s)| s.latency_ms.map(|ms| (i, ms)))
+        .collect();
+
+    if points.len() < 2 {
+        return html! {
+            <span class="text-xs text-gray-400 dark:text-gray-500">{"not enough data"}</span>
+        };
+    }
+
+    let max = points.iter().map(|(_, ms)| *ms).max().unwrap_or(1).max(1) as f64;
+    let step = WIDTH / (props.samples.len().max(2) - 1) as f64;
+    let coord = |i: usize, ms: u64| {
+        (
+            i as f64 * step,
+            HEIGHT - (ms as f64 / max) * (HEIGHT - 2.0) - 1.0,
+        )
+    };
+
+    let polyline = points
+        .iter()
+        .map(|&(i, ms)| {
+            let (x, y) = coord(i, ms);
+            format!("{x:.1},{y:.1}")
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let failures = points
+        .iter()
+        .filter(|(i, _)| !props.samples[*i].healthy)
+        .map(|&(i, ms)| {
+            let (x, y) = coord(i, ms);
+            html! { <circle cx={format!("{x:.1}")} cy={format!("{y:.1}")} r="2" class="fill-red-500" /> }
+        })
+        .collect::<Html>();
+
+    html! {
+        <svg
+            width={WIDTH.to_string()}
+            height={HEIGHT.to_string()}
+            viewBox={format!("0 0 {WIDTH} {HEIGHT}")}
+            class="text-blue-500"
+            role="img"
+            aria-label="Latency history"
+        >
+            <polyline points={polyline} fill="none" stroke="currentColor" stroke-width="1.5" />
+            {failures}
+        </svg>
+    }
+}
@@ -0,0 +1,153 @@
+//! Maintenance and read-only mode controls
+
+use crate::services::server_mode::{ServerMode, ServerModeService};
+use yew::prelude::*;
+
+const TOGGLE_CLASS: &str = "h-4 w-4 text-blue-600 border-gray-300 dark:border-gray-600 rounded";
+
+#[function_component(ServerModeContainer)]
+pub fn server_mode_container() -> Html {
+    let service = use_memo((), |_| ServerModeService::new());
+
+    let mode = use_state(|| Option::<ServerMode>::None);
+    let message = use_state(String::new);
+    let error = use_state(|| Option::<String>::None);
+
+    // Load modes on mount
+    {
+        let service = service.clone();
+        let mode = mode.clone();
+        let message = message.clone();
+        let error = error.clone();
+        use_effect_with((), move |_| {
+            wasm_bindgen_futures::spawn_local(async move {
+                match service.get_mode().await {
+                    Ok(current) => {
+                        message.set(current.message.clone().unwrap_or_default());
+                        mode.set(Some(current));
+                    }
+                    Err(e) => error.set(Some(format!("Failed to load server mode: {e}"))),
+                }
+            });
+        });
+    }
+
+    let apply = {
+        let service = service.clone();
+        let mode = mode.clone();
+        let message = message.clone();
+        let error = error.clone();
+
+        Callback::from(move |(maintenance, read_only): (bool, bool)| {
+            let service = service.clone();
+            let mode = mode.clone();
+            let text = (*message).clone();
+            let error = error.clone();
+
+            wasm_bindgen_futures::spawn_local(async move {
+                match service.set_mode(maintenance, read_only, &text).await {
+                    Ok(updated) => {
+                        mode.set(Some(updated));
+                        error.set(None);
+                    }
+                    Err(e) => error.set(Some(format!("Failed to update server mode: {e}"))),
+                }
+            });
+        })
+    };
+
+    let Some(current) = (*mode).clone() else {
+        return html! {};
+    };
+
+    let on_maintenance = {
+        let apply = apply.clone();
+        let read_only = current.read_only;
+        Callback::from(move |e: Event| {
+            let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+            apply.emit((input.checked(), read_only));
+        })
+    };
+    let on_read_only = {
+        let apply = apply.clone();
+        let maintenance = current.maintenance;
+        Callback::from(move |e: Event| {
+            let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+            apply.emit((maintenance, input.checked()));
+        })
+    };
+    let on_message = {
+        let message = message.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+            message.set(input.value());
+        })
+    };
+    let on_save_message = {
+        let apply = apply.clone();
+        let (maintenance, read_only) = (current.maintenance, current.read_only);
+        Callback::from(move |_| apply.emit((maintenance, read_only)))
+    };
+
+    html! {
+        <div class="p-6 max-w-7xl mx-auto">
+            <div class="bg-white dark:bg-gray-800 shadow rounded-lg p-6 space-y-4">
+                <div>
+                    <h2 class="text-lg font-semibold text-gray-900 dark:text-gray-100">
+                        {"Server mode"}
+                    </h2>
+                    <p class="mt-1 text-sm text-gray-600 dark:text-gray-400">
+                        {"Take inference offline or freeze admin changes during upgrades. Modes reset when the daemon restarts."}
+                    </p>
+                </div>
+
+                {if let Some(err) = (*error).as_ref() {
+                    html! {
+                        <div class="p-4 bg-red-50 dark:bg-red-900/20 border border-red-200 dark:border-red-800 rounded-md">
+                            <p class="text-red-700 dark:text-red-300">{err}</p>
+                        </div>
+                    }
+                } else {
+                    html! {}
+                }}
+
+                <label class="flex items-center space-x-3 text-sm text-gray-700 dark:text-gray-300">
+                    <input type="checkbox" class={TOGGLE_CLASS}
+                        checked={current.maintenance} onchange={on_maintenance} />
+                    <span>{"Maintenance mode: inference requests return 503"}</span>
+                </label>
+                <label class="flex items-center space-x-3 text-sm text-gray-700 dark:text-gray-300">
+                    <input type="checkbox" class={TOGGLE_CLASS}
+                        checked={current.read_only} onchange={on_read_only} />
+                    <span>{"Read-only mode: admin and configuration changes are disabled"}</span>
+                </label>
+
+                <div class="flex items-center space-x-3">
+                    <input
+                        type="text"
+                        class="flex-1 px-3 py-2 text-sm border border-gray-300 dark:border-gray-600 rounded-md bg-white dark:bg-gray-700 text-gray-900 dark:text-gray-100"
+                        placeholder={current.maintenance_message.clone()}
+                        value={(*message).clone()}
+                        oninput={on_message}
+                    />
+                    <button
+                        onclick={on_save_message}
+                        class="px-4 py-2 text-sm font-medium text-white bg-blue-600 hover:bg-blue-700 rounded-md"
+                    >
+                        {"Save message"}
+                    </button>
+                </div>
+
+                {if let (Some(by), Some(at)) = (&current.updated_by, current.updated_at) {
+                    html! {
+                        <p class="text-xs text-gray-500 dark:text-gray-400">
+                            {format!("Last changed by {by} at {}", at.format("%Y-%m-%d %H:%M UTC"))}
+                        </p>
+                    }
+                } else {
+                    html! {}
+                }}
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,3 @@
+pub mod container;
+
+pub use container::ServerModeContainer;
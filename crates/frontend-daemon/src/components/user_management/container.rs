@@ -7,6 +7,9 @@ use gate_frontend_common::auth::use_auth;
 use gloo::timers::callback::Timeout;
 use yew::prelude::*;
 
+const PAGE_SIZE: usize = 20;
+const SEARCH_DEBOUNCE_MS: u32 = 300;
+
 pub enum View {
     List,
     Detail(String),
@@ -18,6 +21,10 @@ pub fn user_management_container() -> Html {
     let user_service = use_memo((), |_| UserService::new());
 
     let users = use_state(Vec::<UserInfo>::new);
+    let total = use_state(|| 0usize);
+    let page = use_state(|| 1usize);
+    let search = use_state(String::new);
+    let reload_counter = use_state(|| 0u32);
     let view = use_state(|| View::List);
     let is_loading = use_state(|| true);
     let error = use_state(|| Option::<String>::None);
@@ -33,28 +40,35 @@ pub fn user_management_container() -> Html {
     // TODO: Check actual permissions from backend
     let can_manage_users = true;
 
-    // Load users effect
+    // Load users whenever the page, search term or reload counter changes
     {
         let users = users.clone();
+        let total = total.clone();
         let is_loading = is_loading.clone();
         let error = error.clone();
         let user_service = user_service.clone();
 
-        use_effect_with((), move |_| {
-            wasm_bindgen_futures::spawn_local(async move {
-                is_loading.set(true);
-                match user_service.list_users(1, 50, None).await {
-                    Ok(response) => {
-                        users.set(response.users);
-                        error.set(None);
-                    }
-                    Err(e) => {
-                        error.set(Some(format!("Failed to load users: {e}")));
+        use_effect_with(
+            (*page, (*search).clone(), *reload_counter),
+            move |(page, search, _)| {
+                let page = *page;
+                let search = (!search.is_empty()).then(|| search.clone());
+                wasm_bindgen_futures::spawn_local(async move {
+                    is_loading.set(true);
+                    match user_service.list_users(page, PAGE_SIZE, search).await {
+                        Ok(response) => {
+                            total.set(response.total);
+                            users.set(response.users);
+                            error.set(None);
+                        }
+                        Err(e) => {
+                            error.set(Some(format!("Failed to load users: {e}")));
+                        }
                     }
-                }
-                is_loading.set(false);
-            });
-        });
+                    is_loading.set(false);
+                });
+            },
+        );
     }
 
     // Clear success message after timeout
@@ -72,27 +86,8 @@ pub fn user_management_container() -> Html {
     }
 
     let reload_users = {
-        let users = users.clone();
-        let user_service = user_service.clone();
-        let error = error.clone();
-
-        Callback::from(move |_: ()| {
-            let users = users.clone();
-            let user_service = user_service.clone();
-            let error = error.clone();
-
-            wasm_bindgen_futures::spawn_local(async move {
-                match user_service.list_users(1, 50, None).await {
-                    Ok(response) => {
-                        users.set(response.users);
-                        error.set(None);
-                    }
-                    Err(e) => {
-                        error.set(Some(format!("Failed to reload users: {e}")));
-                    }
-                }
-            });
-        })
+        let reload_counter = reload_counter.clone();
+        Callback::from(move |_: ()| reload_counter.set(*reload_counter + 1))
     };
 
     let on_user_select = {
@@ -166,9 +161,25 @@ pub fn user_management_container() -> Html {
         })
     };
 
-    let on_search = Callback::from(move |_search: String| {
-        // TODO: Implement search
-    });
+    // Debounce search input so typing doesn't issue a request per keystroke
+    let search_timeout = use_mut_ref(|| Option::<Timeout>::None);
+    let on_search = {
+        let search = search.clone();
+        let page = page.clone();
+        Callback::from(move |term: String| {
+            let search = search.clone();
+            let page = page.clone();
+            *search_timeout.borrow_mut() = Some(Timeout::new(SEARCH_DEBOUNCE_MS, move || {
+                page.set(1);
+                search.set(term.trim().to_string());
+            }));
+        })
+    };
+
+    let on_page_change = {
+        let page = page.clone();
+        Callback::from(move |new_page: usize| page.set(new_page))
+    };
 
     html! {
         <div class="p-6 max-w-7xl mx-auto">
@@ -212,14 +223,22 @@ pub fn user_management_container() -> Html {
                         on_user_toggle={on_user_toggle}
                         on_search={on_search}
                         can_manage_users={can_manage_users}
+                        page={*page}
+                        page_size={PAGE_SIZE}
+                        total={*total}
+                        on_page_change={on_page_change}
                     />
                 },
                 View::Detail(user_id) => {
                     let view_clone = view.clone();
+                    let reload = reload_users.clone();
                     html! {
                         <UserDetail
                             user_id={user_id.clone()}
-                            on_back={Callback::from(move |_| view_clone.set(View::List))}
+                            on_back={Callback::from(move |_| {
+                                view_clone.set(View::List);
+                                reload.emit(());
+                            })}
                             can_manage_permissions={can_manage_users}
                         />
                     }
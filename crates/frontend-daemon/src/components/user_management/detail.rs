@@ -1,11 +1,23 @@
 //! User detail view with permission management
 
+use super::object_picker::ObjectPicker;
+use super::quota::QuotaEditor;
 use super::shared::{ActionButton, ActionButtonVariant, EmptyState, StatusBadge};
 use crate::services::user::{UserInfo, UserPermission, UserService};
+use gate_frontend_common::hooks::use_modal_focus;
 use gloo::timers::callback::Timeout;
 use yew::functional::use_memo;
 use yew::prelude::*;
 
+/// Built-in roles as (value, label); an empty value clears the role
+const ROLES: [(&str, &str); 5] = [
+    ("", "No role"),
+    ("observer", "Observer (read-only dashboards)"),
+    ("viewer", "Viewer"),
+    ("member", "Member"),
+    ("admin", "Admin"),
+];
+
 #[derive(Properties, PartialEq)]
 pub struct UserDetailProps {
     pub user_id: String,
@@ -110,6 +122,57 @@ pub fn user_detail(props: &UserDetailProps) -> Html {
         })
     };
 
+    let reload_permissions = {
+        let user_id = props.user_id.clone();
+        let user_service = user_service.clone();
+        let permissions = permissions.clone();
+
+        Callback::from(move |_: ()| {
+            let user_service = user_service.clone();
+            let permissions = permissions.clone();
+            let user_id = user_id.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Ok(perms) = user_service.get_user_permissions(&user_id).await {
+                    permissions.set(perms);
+                }
+            });
+        })
+    };
+
+    let on_role_change = {
+        let user = user.clone();
+        let user_service = user_service.clone();
+        let error = error.clone();
+        let success = success.clone();
+        let reload_permissions = reload_permissions.clone();
+        let user_id = props.user_id.clone();
+
+        Callback::from(move |e: Event| {
+            let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+            let role = select.value();
+            let user = user.clone();
+            let user_service = user_service.clone();
+            let error = error.clone();
+            let success = success.clone();
+            let reload_permissions = reload_permissions.clone();
+            let user_id = user_id.clone();
+
+            wasm_bindgen_futures::spawn_local(async move {
+                let role = (!role.is_empty()).then_some(role);
+                match user_service.set_user_role(&user_id, role.as_deref()).await {
+                    Ok(updated_user) => {
+                        user.set(Some(updated_user));
+                        success.set(Some("Role updated".to_string()));
+                        reload_permissions.emit(());
+                    }
+                    Err(e) => {
+                        error.set(Some(format!("Failed to update role: {e}")));
+                    }
+                }
+            });
+        })
+    };
+
     let on_revoke_permission = {
         let user_id = props.user_id.clone();
         let user_service = user_service.clone();
@@ -274,10 +337,56 @@ pub fn user_detail(props: &UserDetailProps) -> Html {
                             enabled={true}
                             variant={if user_info.enabled { ActionButtonVariant::Danger } else { ActionButtonVariant::Primary }}
                         />
+                        {if props.can_manage_permissions {
+                            let current_role = user_info.role.clone().unwrap_or_default();
+                            html! {
+                                <label class="flex items-center space-x-2 text-sm text-gray-700 dark:text-gray-300">
+                                    <span>{"Role"}</span>
+                                    <select
+                                        class="px-3 py-2 border border-gray-300 dark:border-gray-600 rounded-md
+                                               bg-white dark:bg-gray-700 text-gray-900 dark:text-gray-100"
+                                        onchange={on_role_change}
+                                    >
+                                        {ROLES.iter().map(|(value, label)| html! {
+                                            <option value={*value} selected={*value == current_role}>{*label}</option>
+                                        }).collect::<Html>()}
+                                    </select>
+                                </label>
+                            }
+                        } else {
+                            html! {}
+                        }}
                     </div>
                 </div>
             </div>
 
+            // Quota section
+            {if props.can_manage_permissions {
+                let success = success.clone();
+                let error = error.clone();
+                html! {
+                    <div class="bg-white dark:bg-gray-800 rounded-lg shadow">
+                        <div class="px-6 py-4 border-b border-gray-200 dark:border-gray-700">
+                            <h3 class="text-lg font-medium text-gray-900 dark:text-gray-100">
+                                {"Quota"}
+                            </h3>
+                            <p class="text-sm text-gray-500 dark:text-gray-400">
+                                {"Leave a field empty for no limit"}
+                            </p>
+                        </div>
+                        <div class="px-6 py-4">
+                            <QuotaEditor
+                                user_id={props.user_id.clone()}
+                                on_saved={Callback::from(move |_| success.set(Some("Quota saved".to_string())))}
+                                on_error={Callback::from(move |msg| error.set(Some(msg)))}
+                            />
+                        </div>
+                    </div>
+                }
+            } else {
+                html! {}
+            }}
+
             // Permissions section
             <div class="bg-white dark:bg-gray-800 rounded-lg shadow">
                 <div class="px-6 py-4 border-b border-gray-200 dark:border-gray-700">
@@ -325,7 +434,7 @@ pub fn user_detail(props: &UserDetailProps) -> Html {
                                              class="flex items-center justify-between p-3 bg-gray-50 dark:bg-gray-900 rounded-lg">
                                             <div>
                                                 <p class="font-medium text-gray-900 dark:text-gray-100">
-                                                    {&perm.action}
+                                                    {perm.action.trim_matches('"')}
                                                 </p>
                                                 <p class="text-sm text-gray-500 dark:text-gray-400">
                                                     {&perm.object}
@@ -359,9 +468,7 @@ pub fn user_detail(props: &UserDetailProps) -> Html {
             {if *show_add_permission {
                 let show_add_permission_close = show_add_permission.clone();
                 let show_add_permission_grant = show_add_permission.clone();
-                let permissions = permissions.clone();
-                let user_service = user_service.clone();
-                let user_id = props.user_id.clone();
+                let reload_permissions = reload_permissions.clone();
 
                 html! {
                     <PermissionGrantModal
@@ -369,15 +476,7 @@ pub fn user_detail(props: &UserDetailProps) -> Html {
                         on_close={Callback::from(move |_| show_add_permission_close.set(false))}
                         on_grant={Callback::from(move |_| {
                             show_add_permission_grant.set(false);
-                            // Reload permissions
-                            let user_service = user_service.clone();
-                            let permissions = permissions.clone();
-                            let user_id = user_id.clone();
-                            wasm_bindgen_futures::spawn_local(async move {
-                                if let Ok(perms) = user_service.get_user_permissions(&user_id).await {
-                                    permissions.set(perms);
-                                }
-                            });
+                            reload_permissions.emit(());
                         })}
                     />
                 }
@@ -388,6 +487,9 @@ pub fn user_detail(props: &UserDetailProps) -> Html {
     }
 }
 
+const GRANT_TITLE_ID: &str = "grant-permission-title";
+const GRANT_ACTION_ID: &str = "grant-permission-action";
+
 #[derive(Properties, PartialEq)]
 struct PermissionGrantModalProps {
     user_id: String,
@@ -398,7 +500,7 @@ struct PermissionGrantModalProps {
 #[function_component(PermissionGrantModal)]
 fn permission_grant_modal(props: &PermissionGrantModalProps) -> Html {
     let action = use_state(|| "Read".to_string());
-    let object = use_state(|| "local/System/*".to_string());
+    let object = use_state(|| "local/model/*".to_string());
 
     let available_actions = [
         "Read",
@@ -409,6 +511,8 @@ fn permission_grant_modal(props: &PermissionGrantModalProps) -> Html {
         "GrantPermission",
         "RevokePermission",
         "ViewPermissions",
+        "ViewQuota",
+        "UpdateQuota",
     ];
 
     let on_submit = {
@@ -446,18 +550,28 @@ fn permission_grant_modal(props: &PermissionGrantModalProps) -> Html {
         })
     };
 
+    let dialog_ref = use_node_ref();
+    let on_keydown = use_modal_focus(dialog_ref.clone(), true, props.on_close.clone());
+
     html! {
-        <div class="fixed inset-0 bg-black bg-opacity-50 flex items-center justify-center z-50">
-            <div class="bg-white dark:bg-gray-800 rounded-lg p-6 max-w-md w-full mx-4">
-                <h3 class="text-lg font-semibold mb-4">{"Grant Permission"}</h3>
+        <div class="fixed inset-0 bg-black bg-opacity-50 flex items-center justify-center z-50" onkeydown={on_keydown}>
+            <div
+                ref={dialog_ref}
+                class="bg-white dark:bg-gray-800 rounded-lg p-6 max-w-md w-full mx-4"
+                role="dialog"
+                aria-modal="true"
+                aria-labelledby={GRANT_TITLE_ID}
+            >
+                <h3 id={GRANT_TITLE_ID} class="text-lg font-semibold mb-4">{"Grant Permission"}</h3>
 
                 <form onsubmit={on_submit}>
                     <div class="space-y-4">
                         <div>
-                            <label class="block text-sm font-medium text-gray-700 dark:text-gray-300 mb-1">
+                            <label for={GRANT_ACTION_ID} class="block text-sm font-medium text-gray-700 dark:text-gray-300 mb-1">
                                 {"Action"}
                             </label>
                             <select
+                                id={GRANT_ACTION_ID}
                                 class="w-full px-3 py-2 border border-gray-300 dark:border-gray-600 rounded-md
                                        bg-white dark:bg-gray-700 text-gray-900 dark:text-gray-100"
                                 value={(*action).clone()}
@@ -477,22 +591,13 @@ fn permission_grant_modal(props: &PermissionGrantModalProps) -> Html {
 
                         <div>
                             <label class="block text-sm font-medium text-gray-700 dark:text-gray-300 mb-1">
-                                {"Object (namespace/kind/id)"}
+                                {"Object"}
                             </label>
-                            <input
-                                type="text"
-                                class="w-full px-3 py-2 border border-gray-300 dark:border-gray-600 rounded-md
-                                       bg-white dark:bg-gray-700 text-gray-900 dark:text-gray-100"
-                                value={(*object).clone()}
-                                oninput={{
-                                    let object = object.clone();
-                                    Callback::from(move |e: InputEvent| {
-                                        let input: web_sys::HtmlInputElement = e.target_unchecked_into();
-                                        object.set(input.value());
-                                    })
-                                }}
-                                placeholder="e.g., local/System/*, system/User/user123"
-                            />
+                            <ObjectPicker on_change={{
+                                let object = object.clone();
+                                Callback::from(move |value: String| object.set(value))
+                            }} />
+                            <p class="mt-1 text-xs text-gray-500 dark:text-gray-400">{(*object).clone()}</p>
                         </div>
                     </div>
 
@@ -15,6 +15,10 @@ pub struct UserListProps {
     pub on_user_toggle: Callback<(String, bool)>,
     pub on_search: Callback<String>,
     pub can_manage_users: bool,
+    pub page: usize,
+    pub page_size: usize,
+    pub total: usize,
+    pub on_page_change: Callback<usize>,
 }
 
 #[function_component(UserList)]
@@ -32,12 +36,10 @@ pub fn user_list(props: &UserListProps) -> Html {
         })
     };
 
-    if props.is_loading {
-        return html! { <UserListSkeleton /> };
-    }
-
-    if props.users.is_empty() {
-        return html! {
+    let content = if props.is_loading {
+        html! { <UserListSkeleton /> }
+    } else if props.users.is_empty() {
+        html! {
             <EmptyState
                 title="No users found"
                 description={if (*search_value).is_empty() {
@@ -52,8 +54,10 @@ pub fn user_list(props: &UserListProps) -> Html {
                     </svg>
                 }}
             />
-        };
-    }
+        }
+    } else {
+        render_table(props)
+    };
 
     html! {
         <div class="space-y-4">
@@ -77,6 +81,29 @@ pub fn user_list(props: &UserListProps) -> Html {
                 />
             </div>
 
+            {content}
+        </div>
+    }
+}
+
+fn render_table(props: &UserListProps) -> Html {
+    let total_pages = props.total.div_ceil(props.page_size.max(1)).max(1);
+    let first = props.page.saturating_sub(1) * props.page_size + 1;
+    let last = (first + props.users.len()).saturating_sub(1);
+
+    let on_prev = {
+        let on_page_change = props.on_page_change.clone();
+        let page = props.page;
+        Callback::from(move |_| on_page_change.emit(page.saturating_sub(1).max(1)))
+    };
+    let on_next = {
+        let on_page_change = props.on_page_change.clone();
+        let page = props.page;
+        Callback::from(move |_| on_page_change.emit(page + 1))
+    };
+
+    html! {
+        <>
             // User list
             <div class="bg-white dark:bg-gray-800 shadow overflow-hidden rounded-lg">
                 <table class="min-w-full divide-y divide-gray-200 dark:divide-gray-700">
@@ -88,6 +115,9 @@ pub fn user_list(props: &UserListProps) -> Html {
                             <th scope="col" class="px-6 py-3 text-left text-xs font-medium text-gray-500 dark:text-gray-400 uppercase tracking-wider">
                                 {"Status"}
                             </th>
+                            <th scope="col" class="px-6 py-3 text-left text-xs font-medium text-gray-500 dark:text-gray-400 uppercase tracking-wider">
+                                {"Role"}
+                            </th>
                             <th scope="col" class="px-6 py-3 text-left text-xs font-medium text-gray-500 dark:text-gray-400 uppercase tracking-wider">
                                 {"Created"}
                             </th>
@@ -150,6 +180,9 @@ pub fn user_list(props: &UserListProps) -> Html {
                                     <td class="px-6 py-4 whitespace-nowrap">
                                         <StatusBadge enabled={user.enabled} />
                                     </td>
+                                    <td class="px-6 py-4 whitespace-nowrap text-sm text-gray-700 dark:text-gray-300 capitalize">
+                                        {user.role.as_deref().unwrap_or("—")}
+                                    </td>
                                     <td class="px-6 py-4 whitespace-nowrap text-sm text-gray-500 dark:text-gray-400">
                                         {user.created_at.format("%Y-%m-%d").to_string()}
                                     </td>
@@ -191,6 +224,28 @@ pub fn user_list(props: &UserListProps) -> Html {
                     </tbody>
                 </table>
             </div>
-        </div>
+
+            // Pagination
+            <div class="flex items-center justify-between text-sm text-gray-600 dark:text-gray-400">
+                <span>{format!("Showing {first}–{last} of {}", props.total)}</span>
+                <div class="flex items-center space-x-2">
+                    <button
+                        onclick={on_prev}
+                        disabled={props.page <= 1}
+                        class="px-3 py-1 rounded border border-gray-300 dark:border-gray-600 disabled:opacity-50"
+                    >
+                        {"Previous"}
+                    </button>
+                    <span>{format!("Page {} of {total_pages}", props.page)}</span>
+                    <button
+                        onclick={on_next}
+                        disabled={props.page >= total_pages}
+                        class="px-3 py-1 rounded border border-gray-300 dark:border-gray-600 disabled:opacity-50"
+                    >
+                        {"Next"}
+                    </button>
+                </div>
+            </div>
+        </>
     }
 }
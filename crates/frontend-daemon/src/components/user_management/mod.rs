@@ -1,6 +1,8 @@
 pub mod container;
 pub mod detail;
 pub mod list;
+pub mod object_picker;
+pub mod quota;
 pub mod shared;
 
 pub use container::UserManagementContainer;
@@ -0,0 +1,124 @@
+//! Picker for permission target objects (namespace/kind/id)
+
+use crate::services::user::UserService;
+use gate_frontend_common::services::InferenceService;
+use yew::prelude::*;
+
+const WILDCARD: &str = "*";
+const NAMESPACES: [&str; 2] = ["local", "system"];
+const KINDS: [&str; 8] = [
+    "model", "provider", "user", "users", "config", "billing", "system", "quota",
+];
+
+#[derive(Properties, PartialEq)]
+pub struct ObjectPickerProps {
+    /// Emits the object in `namespace/kind/id` form whenever the selection changes
+    pub on_change: Callback<String>,
+}
+
+#[function_component(ObjectPicker)]
+pub fn object_picker(props: &ObjectPickerProps) -> Html {
+    let namespace = use_state(|| NAMESPACES[0].to_string());
+    let kind = use_state(|| KINDS[0].to_string());
+    let object_id = use_state(|| WILDCARD.to_string());
+    let suggestions = use_state(Vec::<String>::new);
+
+    // Offer known ids for the selected kind
+    {
+        let suggestions = suggestions.clone();
+        use_effect_with((*kind).clone(), move |kind| {
+            let kind = kind.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                let ids = match kind.as_str() {
+                    "model" => InferenceService::get_models()
+                        .await
+                        .map(|models| models.into_iter().map(|m| m.id).collect())
+                        .unwrap_or_default(),
+                    "user" | "quota" => UserService::new()
+                        .list_users(1, 100, None)
+                        .await
+                        .map(|r| r.users.into_iter().map(|u| u.id).collect())
+                        .unwrap_or_default(),
+                    _ => Vec::new(),
+                };
+                suggestions.set(ids);
+            });
+        });
+    }
+
+    // Report the combined value to the parent
+    {
+        let on_change = props.on_change.clone();
+        use_effect_with(
+            ((*namespace).clone(), (*kind).clone(), (*object_id).clone()),
+            move |(namespace, kind, object_id)| {
+                let id = if object_id.trim().is_empty() {
+                    WILDCARD
+                } else {
+                    object_id.trim()
+                };
+                on_change.emit(format!("{namespace}/{kind}/{id}"));
+            },
+        );
+    }
+
+    let select_class = "w-full px-3 py-2 border border-gray-300 dark:border-gray-600 rounded-md \
+                        bg-white dark:bg-gray-700 text-gray-900 dark:text-gray-100";
+
+    html! {
+        <div class="grid grid-cols-3 gap-2">
+            <select
+                class={select_class}
+                aria-label="Namespace"
+                onchange={{
+                    let namespace = namespace.clone();
+                    Callback::from(move |e: Event| {
+                        let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+                        namespace.set(select.value());
+                    })
+                }}
+            >
+                {NAMESPACES.iter().map(|ns| html! {
+                    <option value={*ns} selected={*ns == namespace.as_str()}>{*ns}</option>
+                }).collect::<Html>()}
+            </select>
+            <select
+                class={select_class}
+                aria-label="Kind"
+                onchange={{
+                    let kind = kind.clone();
+                    let object_id = object_id.clone();
+                    Callback::from(move |e: Event| {
+                        let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+                        kind.set(select.value());
+                        object_id.set(WILDCARD.to_string());
+                    })
+                }}
+            >
+                {KINDS.iter().map(|k| html! {
+                    <option value={*k} selected={*k == kind.as_str()}>{*k}</option>
+                }).collect::<Html>()}
+            </select>
+            <input
+                type="text"
+                list="object-picker-ids"
+                aria-label="Object id"
+                class={select_class}
+                value={(*object_id).clone()}
+                oninput={{
+                    let object_id = object_id.clone();
+                    Callback::from(move |e: InputEvent| {
+                        let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+                        object_id.set(input.value());
+                    })
+                }}
+            />
+            <datalist id="object-picker-ids">
+                <option value={WILDCARD} />
+                {suggestions.iter().map(|id| html! {
+                    <option value={id.clone()} />
+                }).collect::<Html>()}
+            </datalist>
+        </div>
+    }
+}
@@ -0,0 +1,143 @@
+//! Quota editor for a single user
+
+use crate::services::user::{UserQuota, UserService};
+use yew::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct QuotaEditorProps {
+    pub user_id: String,
+    pub on_saved: Callback<()>,
+    pub on_error: Callback<String>,
+}
+
+/// Parse an optional numeric field; an empty input means unlimited
+fn parse_limit<T: std::str::FromStr>(value: &str, label: &str) -> Result<Option<T>, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    value
+        .parse()
+        .map(Some)
+        .map_err(|_| format!("{label} must be a non-negative number"))
+}
+
+fn format_limit<T: ToString>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+#[function_component(QuotaEditor)]
+pub fn quota_editor(props: &QuotaEditorProps) -> Html {
+    let spend = use_state(String::new);
+    let requests = use_state(String::new);
+    let tokens = use_state(String::new);
+    let is_saving = use_state(|| false);
+
+    {
+        let user_id = props.user_id.clone();
+        let spend = spend.clone();
+        let requests = requests.clone();
+        let tokens = tokens.clone();
+        let on_error = props.on_error.clone();
+
+        use_effect_with(user_id.clone(), move |_| {
+            wasm_bindgen_futures::spawn_local(async move {
+                match UserService::new().get_user_quota(&user_id).await {
+                    Ok(quota) => {
+                        spend.set(format_limit(quota.monthly_spend_limit));
+                        requests.set(format_limit(quota.requests_per_minute));
+                        tokens.set(format_limit(quota.tokens_per_day));
+                    }
+                    Err(e) => on_error.emit(format!("Failed to load quota: {e}")),
+                }
+            });
+        });
+    }
+
+    let on_submit = {
+        let user_id = props.user_id.clone();
+        let spend = spend.clone();
+        let requests = requests.clone();
+        let tokens = tokens.clone();
+        let is_saving = is_saving.clone();
+        let on_saved = props.on_saved.clone();
+        let on_error = props.on_error.clone();
+
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+
+            let quota = (|| {
+                Ok::<_, String>(UserQuota {
+                    monthly_spend_limit: parse_limit(&spend, "Monthly spend limit")?,
+                    requests_per_minute: parse_limit(&requests, "Requests per minute")?,
+                    tokens_per_day: parse_limit(&tokens, "Tokens per day")?,
+                })
+            })();
+            let quota = match quota {
+                Ok(quota) => quota,
+                Err(msg) => {
+                    on_error.emit(msg);
+                    return;
+                }
+            };
+
+            let user_id = user_id.clone();
+            let is_saving = is_saving.clone();
+            let on_saved = on_saved.clone();
+            let on_error = on_error.clone();
+            is_saving.set(true);
+
+            wasm_bindgen_futures::spawn_local(async move {
+                match UserService::new().update_user_quota(&user_id, &quota).await {
+                    Ok(_) => on_saved.emit(()),
+                    Err(e) => on_error.emit(format!("Failed to save quota: {e}")),
+                }
+                is_saving.set(false);
+            });
+        })
+    };
+
+    let field = |label: &'static str, placeholder: &'static str, value: UseStateHandle<String>| {
+        let oninput = {
+            let value = value.clone();
+            Callback::from(move |e: InputEvent| {
+                let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+                value.set(input.value());
+            })
+        };
+        html! {
+            <label class="block">
+                <span class="block text-sm text-gray-500 dark:text-gray-400 mb-1">{label}</span>
+                <input
+                    type="number"
+                    min="0"
+                    step="any"
+                    class="w-full px-3 py-2 border border-gray-300 dark:border-gray-600 rounded-md
+                           bg-white dark:bg-gray-700 text-gray-900 dark:text-gray-100"
+                    placeholder={placeholder}
+                    value={(*value).clone()}
+                    {oninput}
+                />
+            </label>
+        }
+    };
+
+    html! {
+        <form onsubmit={on_submit} class="space-y-4">
+            <div class="grid grid-cols-1 md:grid-cols-3 gap-4">
+                {field("Monthly spend limit (USD)", "Unlimited", spend)}
+                {field("Requests per minute", "Unlimited", requests)}
+                {field("Tokens per day", "Unlimited", tokens)}
+            </div>
+            <div class="flex justify-end">
+                <button
+                    type="submit"
+                    disabled={*is_saving}
+                    class="px-4 py-2 bg-blue-600 text-white text-sm rounded-md hover:bg-blue-700 disabled:opacity-50"
+                >
+                    {if *is_saving { "Saving..." } else { "Save Quota" }}
+                </button>
+            </div>
+        </form>
+    }
+}
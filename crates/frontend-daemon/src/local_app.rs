@@ -1,19 +1,30 @@
-use crate::components::{ConfigEditor, UserManagementContainer};
+use crate::components::{
+    ApiKeysContainer, ConfigEditor, ConfigPage, DevicesContainer, EvalsContainer,
+    ExperimentsContainer, ProvidersContainer, ServerModeContainer, UserManagementContainer,
+};
 use crate::local_auth::LocalAuth;
+use crate::services::node::{pin_node, IdentityCheck, NodeIdentityService};
+use crate::services::status::{SafeMode, StatusService};
+use crate::services::version::{VersionInfo, VersionService};
+use gate_chat_ui::utils::a11y::{elements_matching, move_roving_focus, Orientation};
 use gate_frontend_common::{
     auth::{use_auth, use_is_authenticated, AuthAction, AuthProvider},
-    components::{LiveChat, ThemeToggle},
-    theme::ThemeProvider,
+    components::{AppearanceMenu, LanguageSelect, LiveChat, MotionToggle},
+    i18n::{use_i18n, I18nProvider},
+    theme::{use_theme_sync, ThemeProvider},
 };
+use web_sys::Element;
 use yew::prelude::*;
 
 #[function_component(LocalApp)]
 pub fn local_app() -> Html {
     html! {
         <ThemeProvider>
-            <AuthProvider>
-                <LocalAppContent />
-            </AuthProvider>
+            <I18nProvider>
+                <AuthProvider>
+                    <LocalAppContent />
+                </AuthProvider>
+            </I18nProvider>
         </ThemeProvider>
     }
 }
@@ -22,23 +33,74 @@ pub fn local_app() -> Html {
 enum Tab {
     Chat,
     Config,
+    ApiKeys,
+    Providers,
     Users,
+    Experiments,
+    Evals,
 }
 
+impl Tab {
+    /// DOM id of the tab button, referenced by the tab panel
+    fn id(&self) -> &'static str {
+        match self {
+            Tab::Chat => "app-tab-chat",
+            Tab::Config => "app-tab-config",
+            Tab::ApiKeys => "app-tab-api-keys",
+            Tab::Providers => "app-tab-providers",
+            Tab::Users => "app-tab-users",
+            Tab::Experiments => "app-tab-experiments",
+            Tab::Evals => "app-tab-evals",
+        }
+    }
+}
+
+const TABPANEL_ID: &str = "app-tabpanel";
+const TAB_SELECTOR: &str = "[role=\"tab\"]";
+
 #[function_component(LocalAppContent)]
 fn local_app_content() -> Html {
     let auth = use_auth();
+    let i18n = use_i18n();
     let is_authenticated = use_is_authenticated();
     let active_tab = use_state(|| Tab::Chat);
+    let config_page = use_state(|| ConfigPage::Server);
     let is_admin = use_state(|| false);
+    let safe_mode = use_state(|| None::<SafeMode>);
+    let identity = use_state(|| None::<IdentityCheck>);
+    let stale_frontend = use_state(|| None::<VersionInfo>);
+    use_theme_sync(is_authenticated);
 
     let on_tab_change = {
         let active_tab = active_tab.clone();
+        let config_page = config_page.clone();
         Callback::from(move |tab: Tab| {
+            config_page.set(ConfigPage::Server);
             active_tab.set(tab);
         })
     };
 
+    let on_edit_provider = {
+        let active_tab = active_tab.clone();
+        let config_page = config_page.clone();
+        Callback::from(move |_: ()| {
+            config_page.set(ConfigPage::Providers);
+            active_tab.set(Tab::Config);
+        })
+    };
+
+    // Arrow keys move between tabs; Enter or Space opens the focused one
+    let tablist_ref = use_node_ref();
+    let on_tablist_keydown = {
+        let tablist_ref = tablist_ref.clone();
+        Callback::from(move |e: KeyboardEvent| {
+            if let Some(tablist) = tablist_ref.cast::<Element>() {
+                let tabs = elements_matching(&tablist, TAB_SELECTOR);
+                move_roving_focus(&tabs, &e, Orientation::Horizontal);
+            }
+        })
+    };
+
     let on_logout = {
         let auth = auth.clone();
         Callback::from(move |_| {
@@ -58,15 +120,89 @@ fn local_app_content() -> Html {
         });
     }
 
+    // Surface a safe-mode start so the configuration can be fixed here
+    {
+        let safe_mode = safe_mode.clone();
+        use_effect_with(is_authenticated, move |authenticated| {
+            if *authenticated {
+                wasm_bindgen_futures::spawn_local(async move {
+                    match StatusService::new().get_status().await {
+                        Ok(status) => safe_mode.set(status.safe_mode),
+                        Err(e) => {
+                            web_sys::console::error_1(
+                                &format!("Failed to load daemon status: {e}").into(),
+                            );
+                        }
+                    }
+                });
+            }
+            || ()
+        });
+    }
+
+    // Check the daemon attests to the node this browser pinned
+    {
+        let identity = identity.clone();
+        use_effect_with(is_authenticated, move |authenticated| {
+            if *authenticated {
+                wasm_bindgen_futures::spawn_local(async move {
+                    match NodeIdentityService::new().check().await {
+                        Ok(check) => identity.set(Some(check)),
+                        Err(e) => {
+                            web_sys::console::error_1(
+                                &format!("Failed to load node identity: {e}").into(),
+                            );
+                        }
+                    }
+                });
+            }
+            || ()
+        });
+    }
+
+    // Warn when the served UI is older than the daemon, as a partial upgrade
+    // leaves it
+    {
+        let stale_frontend = stale_frontend.clone();
+        use_effect_with(is_authenticated, move |authenticated| {
+            if *authenticated {
+                wasm_bindgen_futures::spawn_local(async move {
+                    match VersionService::new().get_version().await {
+                        Ok(daemon) if VersionInfo::frontend().is_older_than(&daemon) => {
+                            stale_frontend.set(Some(daemon));
+                        }
+                        Ok(_) => stale_frontend.set(None),
+                        Err(e) => {
+                            web_sys::console::error_1(
+                                &format!("Failed to load daemon version: {e}").into(),
+                            );
+                        }
+                    }
+                });
+            }
+            || ()
+        });
+    }
+
+    let on_trust_node = {
+        let identity = identity.clone();
+        Callback::from(move |_: MouseEvent| {
+            if let Some(IdentityCheck::Changed { current, .. }) = &*identity {
+                pin_node(current);
+                identity.set(Some(IdentityCheck::Trusted(current.clone())));
+            }
+        })
+    };
+
     // Show loading state while auth is being restored from sessionStorage
     if auth.is_loading {
         html! {
             <div class="min-h-screen bg-gray-950 flex items-center justify-center">
                 <div class="text-center">
                     <div class="inline-flex items-center justify-center w-20 h-20 rounded-full mb-4 animate-pulse">
-                        <img src="/assets/hellas-token-white.svg" alt="Hellas" class="w-16 h-16" />
+                        <img src="assets/hellas-token-white.svg" alt="Hellas" class="w-16 h-16" />
                     </div>
-                    <p class="text-white text-lg">{"Loading..."}</p>
+                    <p class="text-white text-lg">{i18n.t("common.loading")}</p>
                 </div>
             </div>
         }
@@ -77,26 +213,28 @@ fn local_app_content() -> Html {
                 <div class="bg-gray-50 dark:bg-gray-900 border-b border-gray-200 dark:border-gray-800">
                     <div class="p-4 flex justify-between items-center">
                         <div class="flex items-center gap-3">
-                            <img src="/assets/Hellas_Logotype_Black.svg" class="h-6 block dark:hidden" alt="hellas" />
-                            <img src="/assets/Hellas_Logotype_White.svg" class="h-6 hidden dark:block" alt="hellas" />
-                            <span class="text-sm text-gray-500 dark:text-gray-400">{"Local Daemon"}</span>
+                            <img src="assets/Hellas_Logotype_Black.svg" class="h-6 block dark:hidden" alt="hellas" />
+                            <img src="assets/Hellas_Logotype_White.svg" class="h-6 hidden dark:block" alt="hellas" />
+                            <span class="text-sm text-gray-500 dark:text-gray-400">{i18n.t("app.local_daemon")}</span>
                         </div>
                         <div class="flex items-center gap-3">
-                            <ThemeToggle />
+                            <LanguageSelect />
+                            <MotionToggle />
+                            <AppearanceMenu />
                             <button
                                 onclick={on_logout}
                                 class="px-4 py-2 text-sm font-medium text-gray-700 dark:text-gray-300 bg-gray-100 dark:bg-gray-700 hover:bg-gray-200 dark:hover:bg-gray-600 rounded-lg transition-colors flex items-center gap-2"
                             >
-                                <svg class="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                                <svg class="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24" aria-hidden="true">
                                     <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M17 16l4-4m0 0l-4-4m4 4H7m6 4v1a3 3 0 01-3 3H6a3 3 0 01-3-3V7a3 3 0 013-3h4a3 3 0 013 3v1"></path>
                                 </svg>
-                                {"Logout"}
+                                {i18n.t("common.logout")}
                             </button>
                         </div>
                     </div>
 
                     // Tab navigation
-                    <div class="flex">
+                    <div class="flex" role="tablist" aria-label="Sections" ref={tablist_ref} onkeydown={on_tablist_keydown}>
                         <button
                             class={format!("px-6 py-3 text-sm font-medium transition-colors {}",
                                 if *active_tab == Tab::Chat {
@@ -106,12 +244,18 @@ fn local_app_content() -> Html {
                                 }
                             )}
                             onclick={on_tab_change.reform(|_| Tab::Chat)}
+                            type="button"
+                            role="tab"
+                            id={Tab::Chat.id()}
+                            aria-selected={(*active_tab == Tab::Chat).to_string()}
+                            aria-controls={TABPANEL_ID}
+                            tabindex={if *active_tab == Tab::Chat { "0" } else { "-1" }}
                         >
                             <div class="flex items-center gap-2">
-                                <svg class="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                                <svg class="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24" aria-hidden="true">
                                     <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M8 12h.01M12 12h.01M16 12h.01M21 12c0 4.418-4.03 8-9 8a9.863 9.863 0 01-4.255-.949L3 20l1.395-3.72C3.512 15.042 3 13.574 3 12c0-4.418 4.03-8 9-8s9 3.582 9 8z"></path>
                                 </svg>
-                                {"Chat"}
+                                {i18n.t("app.tab.chat")}
                             </div>
                         </button>
                         <button
@@ -123,17 +267,70 @@ fn local_app_content() -> Html {
                                 }
                             )}
                             onclick={on_tab_change.reform(|_| Tab::Config)}
+                            type="button"
+                            role="tab"
+                            id={Tab::Config.id()}
+                            aria-selected={(*active_tab == Tab::Config).to_string()}
+                            aria-controls={TABPANEL_ID}
+                            tabindex={if *active_tab == Tab::Config { "0" } else { "-1" }}
                         >
                             <div class="flex items-center gap-2">
-                                <svg class="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                                <svg class="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24" aria-hidden="true">
                                     <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M10.325 4.317c.426-1.756 2.924-1.756 3.35 0a1.724 1.724 0 002.573 1.066c1.543-.94 3.31.826 2.37 2.37a1.724 1.724 0 001.065 2.572c1.756.426 1.756 2.924 0 3.35a1.724 1.724 0 00-1.066 2.573c.94 1.543-.826 3.31-2.37 2.37a1.724 1.724 0 00-2.572 1.065c-.426 1.756-2.924 1.756-3.35 0a1.724 1.724 0 00-2.573-1.066c-1.543.94-3.31-.826-2.37-2.37a1.724 1.724 0 00-1.065-2.572c-1.756-.426-1.756-2.924 0-3.35a1.724 1.724 0 001.066-2.573c-.94-1.543.826-3.31 2.37-2.37.996.608 2.296.07 2.572-1.065z"></path>
                                     <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M15 12a3 3 0 11-6 0 3 3 0 016 0z"></path>
                                 </svg>
-                                {"Config"}
+                                {i18n.t("app.tab.config")}
+                            </div>
+                        </button>
+                        <button
+                            class={format!("px-6 py-3 text-sm font-medium transition-colors {}",
+                                if *active_tab == Tab::ApiKeys {
+                                    "text-blue-600 dark:text-blue-400 border-b-2 border-blue-600 dark:border-blue-400"
+                                } else {
+                                    "text-gray-600 dark:text-gray-400 hover:text-gray-900 dark:hover:text-gray-100"
+                                }
+                            )}
+                            onclick={on_tab_change.reform(|_| Tab::ApiKeys)}
+                            type="button"
+                            role="tab"
+                            id={Tab::ApiKeys.id()}
+                            aria-selected={(*active_tab == Tab::ApiKeys).to_string()}
+                            aria-controls={TABPANEL_ID}
+                            tabindex={if *active_tab == Tab::ApiKeys { "0" } else { "-1" }}
+                        >
+                            <div class="flex items-center gap-2">
+                                <svg class="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24" aria-hidden="true">
+                                    <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M15 7a2 2 0 012 2m4 0a6 6 0 01-7.743 5.743L11 17H9v2H7v2H4a1 1 0 01-1-1v-2.586a1 1 0 01.293-.707l5.964-5.964A6 6 0 1121 9z"></path>
+                                </svg>
+                                {i18n.t("app.tab.api_keys")}
                             </div>
                         </button>
                         {if *is_admin {
                             html! {
+                                <>
+                                <button
+                                    class={format!("px-6 py-3 text-sm font-medium transition-colors {}",
+                                        if *active_tab == Tab::Providers {
+                                            "text-blue-600 dark:text-blue-400 border-b-2 border-blue-600 dark:border-blue-400"
+                                        } else {
+                                            "text-gray-600 dark:text-gray-400 hover:text-gray-900 dark:hover:text-gray-100"
+                                        }
+                                    )}
+                                    onclick={on_tab_change.reform(|_| Tab::Providers)}
+                                    type="button"
+                                    role="tab"
+                                    id={Tab::Providers.id()}
+                                    aria-selected={(*active_tab == Tab::Providers).to_string()}
+                                    aria-controls={TABPANEL_ID}
+                                    tabindex={if *active_tab == Tab::Providers { "0" } else { "-1" }}
+                                >
+                                    <div class="flex items-center gap-2">
+                                        <svg class="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24" aria-hidden="true">
+                                            <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M5 12h14M5 12a2 2 0 01-2-2V6a2 2 0 012-2h14a2 2 0 012 2v4a2 2 0 01-2 2M5 12a2 2 0 00-2 2v4a2 2 0 002 2h14a2 2 0 002-2v-4a2 2 0 00-2-2m-2-4h.01M17 16h.01"></path>
+                                        </svg>
+                                        {i18n.t("app.tab.providers")}
+                                    </div>
+                                </button>
                                 <button
                                     class={format!("px-6 py-3 text-sm font-medium transition-colors {}",
                                         if *active_tab == Tab::Users {
@@ -143,14 +340,67 @@ fn local_app_content() -> Html {
                                         }
                                     )}
                                     onclick={on_tab_change.reform(|_| Tab::Users)}
+                                    type="button"
+                                    role="tab"
+                                    id={Tab::Users.id()}
+                                    aria-selected={(*active_tab == Tab::Users).to_string()}
+                                    aria-controls={TABPANEL_ID}
+                                    tabindex={if *active_tab == Tab::Users { "0" } else { "-1" }}
                                 >
                                     <div class="flex items-center gap-2">
-                                        <svg class="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                                        <svg class="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24" aria-hidden="true">
                                             <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M12 4.354a4 4 0 110 5.292M15 21H3v-1a6 6 0 0112 0v1zm0 0h6v-1a6 6 0 00-9-5.197M13 7a4 4 0 11-8 0 4 4 0 018 0z"></path>
                                         </svg>
-                                        {"Users"}
+                                        {i18n.t("app.tab.users")}
                                     </div>
                                 </button>
+                                <button
+                                    class={format!("px-6 py-3 text-sm font-medium transition-colors {}",
+                                        if *active_tab == Tab::Experiments {
+                                            "text-blue-600 dark:text-blue-400 border-b-2 border-blue-600 dark:border-blue-400"
+                                        } else {
+                                            "text-gray-600 dark:text-gray-400 hover:text-gray-900 dark:hover:text-gray-100"
+                                        }
+                                    )}
+                                    onclick={on_tab_change.reform(|_| Tab::Experiments)}
+                                    type="button"
+                                    role="tab"
+                                    id={Tab::Experiments.id()}
+                                    aria-selected={(*active_tab == Tab::Experiments).to_string()}
+                                    aria-controls={TABPANEL_ID}
+                                    tabindex={if *active_tab == Tab::Experiments { "0" } else { "-1" }}
+                                >
+                                    <div class="flex items-center gap-2">
+                                        <svg class="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24" aria-hidden="true">
+                                            <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M9 19v-6a2 2 0 00-2-2H5a2 2 0 00-2 2v6a2 2 0 002 2h2a2 2 0 002-2zm0 0V9a2 2 0 012-2h2a2 2 0 012 2v10m-6 0a2 2 0 002 2h2a2 2 0 002-2m0 0V5a2 2 0 012-2h2a2 2 0 012 2v14a2 2 0 01-2 2h-2a2 2 0 01-2-2z"></path>
+                                        </svg>
+                                        {i18n.t("app.tab.experiments")}
+                                    </div>
+                                </button>
+                                <button
+                                    class={format!("px-6 py-3 text-sm font-medium transition-colors {}",
+                                        if *active_tab == Tab::Evals {
+                                            "text-blue-600 dark:text-blue-400 border-b-2 border-blue-600 dark:border-blue-400"
+                                        } else {
+                                            "text-gray-600 dark:text-gray-400 hover:text-gray-900 dark:hover:text-gray-100"
+                                        }
+                                    )}
+                                    onclick={on_tab_change.reform(|_| Tab::Evals)}
+                                    type="button"
+                                    role="tab"
+                                    id={Tab::Evals.id()}
+                                    aria-selected={(*active_tab == Tab::Evals).to_string()}
+                                    aria-controls={TABPANEL_ID}
+                                    tabindex={if *active_tab == Tab::Evals { "0" } else { "-1" }}
+                                >
+                                    <div class="flex items-center gap-2">
+                                        <svg class="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24" aria-hidden="true">
+                                            <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M9 5H7a2 2 0 00-2 2v12a2 2 0 002 2h10a2 2 0 002-2V7a2 2 0 00-2-2h-2M9 5a2 2 0 002 2h2a2 2 0 002-2M9 5a2 2 0 012-2h2a2 2 0 012 2m-6 9l2 2 4-4"></path>
+                                        </svg>
+                                        {i18n.t("app.tab.evals")}
+                                    </div>
+                                </button>
+                                </>
                             }
                         } else {
                             html! {}
@@ -158,12 +408,83 @@ fn local_app_content() -> Html {
                     </div>
                 </div>
 
+                {if let Some(safe_mode) = &*safe_mode {
+                    html! {
+                        <div class="px-4 py-3 bg-yellow-50 dark:bg-yellow-900/30 border-b border-yellow-200 dark:border-yellow-800" role="alert">
+                            <p class="text-sm font-medium text-yellow-800 dark:text-yellow-200">{i18n.t("app.safe_mode.title")}</p>
+                            <p class="text-sm text-yellow-700 dark:text-yellow-300">
+                                {i18n.t_with("app.safe_mode.body", &[("reason", safe_mode.reason.as_str())])}
+                            </p>
+                        </div>
+                    }
+                } else {
+                    html! {}
+                }}
+
+                {if let Some(daemon) = &*stale_frontend {
+                    html! {
+                        <div class="px-4 py-3 bg-yellow-50 dark:bg-yellow-900/30 border-b border-yellow-200 dark:border-yellow-800" role="alert">
+                            <p class="text-sm font-medium text-yellow-800 dark:text-yellow-200">{i18n.t("app.stale_frontend.title")}</p>
+                            <p class="text-sm text-yellow-700 dark:text-yellow-300">
+                                {i18n.t_with(
+                                    "app.stale_frontend.body",
+                                    &[
+                                        ("frontend", VersionInfo::frontend().label().as_str()),
+                                        ("daemon", daemon.label().as_str()),
+                                    ],
+                                )}
+                            </p>
+                        </div>
+                    }
+                } else {
+                    html! {}
+                }}
+
+                {match &*identity {
+                    Some(IdentityCheck::Changed { pinned, current }) => html! {
+                        <div class="px-4 py-3 bg-red-50 dark:bg-red-900/30 border-b border-red-200 dark:border-red-800 flex items-start justify-between gap-4" role="alert">
+                            <div>
+                                <p class="text-sm font-medium text-red-800 dark:text-red-200">{i18n.t("app.identity.changed.title")}</p>
+                                <p class="text-sm text-red-700 dark:text-red-300 break-all">
+                                    {i18n.t_with(
+                                        "app.identity.changed.body",
+                                        &[("pinned", pinned.as_str()), ("current", current.as_str())],
+                                    )}
+                                </p>
+                            </div>
+                            <button
+                                type="button"
+                                onclick={on_trust_node}
+                                class="shrink-0 px-3 py-1.5 text-sm font-medium text-red-800 dark:text-red-200 border border-red-300 dark:border-red-700 rounded-md hover:bg-red-100 dark:hover:bg-red-900/50"
+                            >
+                                {i18n.t("app.identity.trust")}
+                            </button>
+                        </div>
+                    },
+                    Some(IdentityCheck::Invalid(reason)) => html! {
+                        <div class="px-4 py-3 bg-red-50 dark:bg-red-900/30 border-b border-red-200 dark:border-red-800" role="alert">
+                            <p class="text-sm font-medium text-red-800 dark:text-red-200">{i18n.t("app.identity.invalid.title")}</p>
+                            <p class="text-sm text-red-700 dark:text-red-300">{reason}</p>
+                        </div>
+                    },
+                    _ => html! {},
+                }}
+
                 // Tab content
-                <div class="flex-1 overflow-y-auto">
+                <div class="flex-1 overflow-y-auto" role="tabpanel" id={TABPANEL_ID} aria-labelledby={active_tab.id()}>
                     {match *active_tab {
                         Tab::Chat => html! { <LiveChat /> },
-                        Tab::Config => html! { <ConfigEditor /> },
+                        Tab::Config => html! {
+                            <>
+                                {if *is_admin { html! { <ServerModeContainer /> } } else { html! {} }}
+                                <ConfigEditor initial_page={*config_page} />
+                            </>
+                        },
+                        Tab::ApiKeys => html! { <><ApiKeysContainer /><DevicesContainer /></> },
+                        Tab::Providers => html! { <ProvidersContainer on_edit={on_edit_provider} /> },
                         Tab::Users => html! { <UserManagementContainer /> },
+                        Tab::Experiments => html! { <ExperimentsContainer /> },
+                        Tab::Evals => html! { <EvalsContainer /> },
                     }}
                 </div>
             </div>
@@ -175,9 +496,9 @@ fn local_app_content() -> Html {
                 <div class="max-w-md w-full">
                     <div class="text-center mb-8">
                         <div class="inline-flex items-center justify-center w-auto h-auto mb-4">
-                            <img src="/assets/Hellas_Logotype_White.svg" alt="hellas" class="w-40" />
+                            <img src="assets/Hellas_Logotype_White.svg" alt="hellas" class="w-40" />
                         </div>
-                        <p class="text-gray-300">{"Secure AI Gateway"}</p>
+                        <p class="text-gray-300">{i18n.t("app.tagline")}</p>
                     </div>
                     <div class="bg-white/10 backdrop-blur-md rounded-2xl shadow-2xl p-8 border border-white/20">
                         <LocalAuth />
@@ -23,9 +23,11 @@ fn switch(route: Route) -> Html {
         Route::Home => html! { <LocalApp /> },
         Route::Bootstrap { token } => html! {
             <gate_frontend_common::theme::ThemeProvider>
-                <gate_frontend_common::auth::AuthProvider>
-                    <OnboardingAuth bootstrap_token={token} />
-                </gate_frontend_common::auth::AuthProvider>
+                <gate_frontend_common::i18n::I18nProvider>
+                    <gate_frontend_common::auth::AuthProvider>
+                        <OnboardingAuth bootstrap_token={token} />
+                    </gate_frontend_common::auth::AuthProvider>
+                </gate_frontend_common::i18n::I18nProvider>
             </gate_frontend_common::theme::ThemeProvider>
         },
     }
@@ -34,6 +36,7 @@ fn switch(route: Route) -> Html {
 #[function_component(App)]
 fn app() -> Html {
     html! {
+        // Routes are matched below the page's <base href>
         <BrowserRouter>
             <Switch<Route> render={switch} />
         </BrowserRouter>
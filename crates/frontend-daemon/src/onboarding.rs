@@ -1,10 +1,16 @@
+use crate::services::onboarding::{DetectedCredential, ImportResult, OnboardingService};
 use gate_frontend_common::{
     auth::use_auth,
-    components::Spinner as LoadingSpinner,
+    components::{LanguageSelect, Spinner as LoadingSpinner},
     hooks::{use_webauthn, WebAuthnState},
+    i18n::{use_i18n, I18nContext},
 };
+use std::collections::BTreeSet;
 use yew::prelude::*;
 
+const NAME_INPUT_ID: &str = "onboarding-name";
+const NAME_HINT_ID: &str = "onboarding-name-hint";
+
 #[derive(Properties, PartialEq)]
 pub struct OnboardingAuthProps {
     pub bootstrap_token: String,
@@ -14,21 +20,10 @@ pub struct OnboardingAuthProps {
 pub fn onboarding_auth(props: &OnboardingAuthProps) -> Html {
     let webauthn = use_webauthn();
     let auth = use_auth();
+    let i18n = use_i18n();
     let name = use_state(String::new);
     let bootstrap_token = props.bootstrap_token.clone();
 
-    // Redirect to home if user is authenticated
-    use_effect_with(auth.auth_state.clone(), {
-        move |auth_state| {
-            if auth_state.is_some() {
-                // User is authenticated, redirect to home
-                let window = web_sys::window().unwrap();
-                let location = window.location();
-                location.set_href("/").ok();
-            }
-        }
-    });
-
     // Handle input changes
     let on_name_input = {
         let name = name.clone();
@@ -57,37 +52,43 @@ pub fn onboarding_auth(props: &OnboardingAuthProps) -> Html {
     html! {
         <div class="min-h-screen flex items-center justify-center bg-gray-950">
             <div class="max-w-md w-full p-8">
+                <div class="flex justify-end mb-4">
+                    <LanguageSelect />
+                </div>
                 <div class="backdrop-blur-lg bg-white/10 rounded-2xl shadow-2xl p-8 border border-white/20">
                     <div class="text-center mb-8">
-                        <img src="/assets/Hellas_Logotype_White.svg" alt="hellas" class="w-40 mx-auto mb-4" />
-                        <p class="text-white/70">{"Let's set up your first admin account"}</p>
+                        <img src="assets/Hellas_Logotype_White.svg" alt="hellas" class="w-40 mx-auto mb-4" />
+                        <p class="text-white/70">{i18n.t("onboarding.subtitle")}</p>
                     </div>
 
-                    {match webauthn.state() {
+                    {if auth.auth_state.is_some() {
+                        // Account created; offer to import keys before going home
+                        html! { <ImportKeysStep /> }
+                    } else { match webauthn.state() {
                         WebAuthnState::Processing => html! {
-                            <div class="text-center">
-                                <LoadingSpinner text={Some("Creating your account...".to_string())} />
+                            <div class="text-center" role="status" aria-live="polite">
+                                <LoadingSpinner text={Some(i18n.t("onboarding.creating_account"))} />
                                 <p class="text-sm text-white/70 mt-4">
-                                    {"Use your device's biometrics or security key"}
+                                    {i18n.t("onboarding.use_authenticator")}
                                 </p>
                             </div>
                         },
                         WebAuthnState::Error(error) => html! {
                             <div class="space-y-4">
-                                <div class="bg-red-500/20 border border-red-500/30 rounded-lg p-4 text-center">
+                                <div class="bg-red-500/20 border border-red-500/30 rounded-lg p-4 text-center" role="alert">
                                     <p class="text-red-200 text-sm">{error}</p>
                                 </div>
-                                {registration_form(&name, &on_name_input, &on_register)}
+                                {registration_form(&i18n, &name, &on_name_input, &on_register)}
                             </div>
                         },
                         WebAuthnState::Idle => html! {
-                            {registration_form(&name, &on_name_input, &on_register)}
+                            {registration_form(&i18n, &name, &on_name_input, &on_register)}
                         }
-                    }}
+                    }}}
 
                     <div class="mt-6 text-center">
                         <p class="text-white/50 text-xs">
-                            {"This is a one-time setup. Your credentials will be securely stored."}
+                            {i18n.t("onboarding.one_time_setup")}
                         </p>
                     </div>
                 </div>
@@ -96,7 +97,194 @@ pub fn onboarding_auth(props: &OnboardingAuthProps) -> Html {
     }
 }
 
+fn go_home() {
+    if let Some(window) = web_sys::window() {
+        let home = format!("{}/", gate_frontend_common::client::base_path());
+        window.location().set_href(&home).ok();
+    }
+}
+
+/// Offer to import provider keys found on this machine.
+///
+/// Sources are only listed here; nothing is read until the user ticks a
+/// source and confirms the import.
+#[function_component(ImportKeysStep)]
+fn import_keys_step() -> Html {
+    let i18n = use_i18n();
+    let service = use_memo((), |_| OnboardingService::new());
+    let sources = use_state(|| None::<Vec<DetectedCredential>>);
+    let selected = use_state(BTreeSet::<String>::new);
+    let is_importing = use_state(|| false);
+    let result = use_state(|| None::<ImportResult>);
+    let error = use_state(|| None::<String>);
+    let heading_ref = use_node_ref();
+
+    // Announce the new step by moving focus to its heading
+    {
+        let heading_ref = heading_ref.clone();
+        use_effect_with((), move |_| {
+            if let Some(heading) = heading_ref.cast::<web_sys::HtmlElement>() {
+                let _ = heading.focus();
+            }
+        });
+    }
+
+    {
+        let i18n = i18n.clone();
+        let service = service.clone();
+        let sources = sources.clone();
+        let error = error.clone();
+        use_effect_with((), move |_| {
+            wasm_bindgen_futures::spawn_local(async move {
+                match service.detect_credentials().await {
+                    Ok(found) => sources.set(Some(found)),
+                    Err(e) => {
+                        error.set(Some(i18n.t_with(
+                            "onboarding.import.detect_failed",
+                            &[("error", &e.to_string())],
+                        )));
+                        sources.set(Some(Vec::new()));
+                    }
+                }
+            });
+        });
+    }
+
+    let on_toggle = {
+        let selected = selected.clone();
+        Callback::from(move |source: String| {
+            let mut next = (*selected).clone();
+            if !next.remove(&source) {
+                next.insert(source);
+            }
+            selected.set(next);
+        })
+    };
+
+    let on_import = {
+        let i18n = i18n.clone();
+        let service = service.clone();
+        let selected = selected.clone();
+        let is_importing = is_importing.clone();
+        let result = result.clone();
+        let error = error.clone();
+        Callback::from(move |_: MouseEvent| {
+            let i18n = i18n.clone();
+            let service = service.clone();
+            let chosen: Vec<String> = selected.iter().cloned().collect();
+            let is_importing = is_importing.clone();
+            let result = result.clone();
+            let error = error.clone();
+
+            is_importing.set(true);
+            wasm_bindgen_futures::spawn_local(async move {
+                match service.import_credentials(&chosen).await {
+                    Ok(outcome) => result.set(Some(outcome)),
+                    Err(e) => error.set(Some(
+                        i18n.t_with("onboarding.import.failed", &[("error", &e.to_string())]),
+                    )),
+                }
+                is_importing.set(false);
+            });
+        })
+    };
+
+    let on_continue = Callback::from(|_: MouseEvent| go_home());
+
+    let body = match (&*sources, &*result) {
+        (None, _) => html! {
+            <div role="status">
+                <LoadingSpinner text={Some(i18n.t("onboarding.import.detecting"))} />
+            </div>
+        },
+        (Some(_), Some(outcome)) => html! {
+            <div class="space-y-3" role="status">
+                {for outcome.imported.iter().map(|item| html! {
+                    <p class="text-green-200 text-sm">
+                        {i18n.t_with("onboarding.import.added", &[("name", &item.provider_name)])}
+                    </p>
+                })}
+                {for outcome.skipped.iter().map(|item| html! {
+                    <p class="text-yellow-200 text-sm">
+                        {i18n.t_with("onboarding.import.skipped", &[("source", &item.source), ("reason", &item.reason)])}
+                    </p>
+                })}
+            </div>
+        },
+        (Some(found), None) if found.is_empty() => html! {
+            <p class="text-white/70 text-sm text-center">
+                {i18n.t("onboarding.import.none_found")}
+            </p>
+        },
+        (Some(found), None) => html! {
+            <div class="space-y-3">
+                <p class="text-white/70 text-sm">
+                    {i18n.t("onboarding.import.found")}
+                </p>
+                {for found.iter().map(|credential| {
+                    let source = credential.source.clone();
+                    let onchange = on_toggle.reform(move |_: Event| source.clone());
+                    html! {
+                        <label class="flex items-start gap-3 p-3 bg-white/5 border border-white/10 rounded-lg cursor-pointer focus-within:ring-2 focus-within:ring-blue-400">
+                            <input
+                                type="checkbox"
+                                class="mt-1"
+                                checked={selected.contains(&credential.source)}
+                                {onchange}
+                            />
+                            <div>
+                                <p class="text-white text-sm font-medium">{&credential.label}</p>
+                                <p class="text-white/50 text-xs font-mono break-all">{&credential.location}</p>
+                            </div>
+                        </label>
+                    }
+                })}
+            </div>
+        },
+    };
+
+    let can_import = result.is_none() && !selected.is_empty() && !*is_importing;
+
+    html! {
+        <div class="space-y-4">
+            <div class="text-center">
+                <h2
+                    ref={heading_ref}
+                    tabindex="-1"
+                    class="text-white text-lg font-medium focus:outline-none"
+                >
+                    {i18n.t("onboarding.import.title")}
+                </h2>
+            </div>
+            if let Some(err) = (*error).as_ref() {
+                <div class="bg-red-500/20 border border-red-500/30 rounded-lg p-3" role="alert">
+                    <p class="text-red-200 text-sm">{err}</p>
+                </div>
+            }
+            {body}
+            <div class="flex gap-3">
+                if result.is_none() && sources.as_ref().is_some_and(|found| !found.is_empty()) {
+                    <button
+                        class="flex-1 px-4 py-3 bg-blue-600 hover:bg-blue-700 text-white rounded-lg font-medium transition-colors disabled:opacity-50 disabled:cursor-not-allowed"
+                        onclick={on_import}
+                        disabled={!can_import}
+                    >
+                        {if *is_importing { i18n.t("onboarding.import.importing") } else { i18n.t("onboarding.import.import_selected") }}
+                    </button>
+                }
+                <button
+                    class="flex-1 px-4 py-3 bg-white/10 hover:bg-white/20 text-white rounded-lg font-medium transition-colors"
+                    onclick={on_continue}
+                >
+                    {if result.is_some() { i18n.t("common.continue") } else { i18n.t("common.skip") }}
+                </button>
+            </div>
+        </div>
+    }
+}
+
 fn registration_form(
+    i18n: &I18nContext,
     name: &UseStateHandle<String>,
     on_name_input: &Callback<InputEvent>,
     on_register: &Callback<MouseEvent>,
@@ -124,19 +312,22 @@ fn registration_form(
     html! {
         <div class="space-y-4">
             <div>
-                <label class="block text-white/80 text-sm font-medium mb-2">
-                    {"Your Name"}
+                <label for={NAME_INPUT_ID} class="block text-white/80 text-sm font-medium mb-2">
+                    {i18n.t("onboarding.name_label")}
                 </label>
                 <input
+                    id={NAME_INPUT_ID}
                     type="text"
+                    autofocus=true
+                    aria-describedby={NAME_HINT_ID}
                     class="w-full px-4 py-3 bg-white/10 border border-white/20 rounded-lg text-white placeholder-white/50 focus:outline-none focus:border-blue-400 focus:bg-white/20 transition-all"
-                    placeholder="Enter your name"
+                    placeholder={i18n.t("onboarding.name_placeholder")}
                     value={(**name).clone()}
                     oninput={on_name_input}
                     onkeydown={handle_keydown}
                 />
-                <p class="text-white/50 text-xs mt-2">
-                    {"This will be displayed when you log in"}
+                <p id={NAME_HINT_ID} class="text-white/50 text-xs mt-2">
+                    {i18n.t("onboarding.name_hint")}
                 </p>
             </div>
 
@@ -145,7 +336,7 @@ fn registration_form(
                 onclick={on_register}
                 disabled={(**name).is_empty()}
             >
-                {"Create Admin Account"}
+                {i18n.t("onboarding.create_account")}
             </button>
         </div>
     }
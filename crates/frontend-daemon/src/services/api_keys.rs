@@ -0,0 +1,94 @@
+//! API key management service
+
+use gate_frontend_common::client::{create_authenticated_client, ClientError};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ApiKeyInfo {
+    pub id: String,
+    pub name: String,
+    pub key_prefix: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub last_used_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub allowed_models: Vec<String>,
+    pub spend_cap: Option<f64>,
+    #[serde(default)]
+    pub observer: bool,
+    pub expired: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKeyListResponse {
+    pub keys: Vec<ApiKeyInfo>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct CreateApiKeyRequest {
+    pub name: String,
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub allowed_models: Vec<String>,
+    pub spend_cap: Option<f64>,
+    pub observer: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateApiKeyResponse {
+    pub key: String,
+    pub info: ApiKeyInfo,
+}
+
+#[derive(Clone)]
+pub struct ApiKeyService;
+
+impl ApiKeyService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// List the current user's API keys
+    pub async fn list_keys(&self) -> Result<Vec<ApiKeyInfo>, ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        let response: ApiKeyListResponse = client
+            .execute(client.request(Method::GET, "/api/keys")?)
+            .await?;
+
+        Ok(response.keys)
+    }
+
+    /// Create a new API key; the raw key is only returned here
+    pub async fn create_key(
+        &self,
+        request: &CreateApiKeyRequest,
+    ) -> Result<CreateApiKeyResponse, ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        let response: CreateApiKeyResponse = client
+            .execute(client.request(Method::POST, "/api/keys")?.json(request))
+            .await?;
+
+        Ok(response)
+    }
+
+    /// Revoke an API key
+    pub async fn revoke_key(&self, key_id: &str) -> Result<(), ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        let _: serde_json::Value = client
+            .execute(client.request(Method::DELETE, &format!("/api/keys/{key_id}"))?)
+            .await?;
+
+        Ok(())
+    }
+}
+
+impl Default for ApiKeyService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
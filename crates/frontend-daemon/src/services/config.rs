@@ -5,6 +5,60 @@ use reqwest::Method;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+/// Severity of a configuration validation issue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IssueSeverity {
+    Error,
+    Warning,
+}
+
+/// Problem reported by server-side config validation
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ConfigIssue {
+    pub severity: IssueSeverity,
+    pub path: Option<String>,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+/// Result of validating a raw configuration document
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ConfigValidation {
+    pub valid: bool,
+    pub issues: Vec<ConfigIssue>,
+}
+
+/// An applied configuration revision
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ConfigRevision {
+    pub revision: u64,
+    pub author: String,
+    pub created_at: String,
+    /// Revision this one restored, when it was a rollback
+    #[serde(default)]
+    pub rollback_of: Option<u64>,
+}
+
+/// A setting changed between two revisions
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ConfigChange {
+    pub path: String,
+    #[serde(default)]
+    pub before: Option<Value>,
+    #[serde(default)]
+    pub after: Option<Value>,
+}
+
+/// Settings a revision changed
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ConfigDiff {
+    pub from: Option<u64>,
+    pub to: u64,
+    pub changes: Vec<ConfigChange>,
+}
+
 /// Configuration API service
 #[derive(Clone)]
 pub struct ConfigApiService;
@@ -65,4 +119,83 @@ impl ConfigApiService {
 
         Ok(response.config)
     }
+
+    /// JSON Schema of the configuration, for building editor forms
+    pub async fn get_schema(&self) -> Result<Value, ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        client
+            .execute(client.request(Method::GET, "/api/config/schema")?)
+            .await
+    }
+
+    /// Validate a raw configuration document against the daemon's schema
+    pub async fn validate_config(&self, text: &str) -> Result<ConfigValidation, ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        #[derive(Serialize)]
+        struct ValidateRequest<'a> {
+            text: &'a str,
+        }
+
+        client
+            .execute(
+                client
+                    .request(Method::POST, "/api/config/validate")?
+                    .json(&ValidateRequest { text }),
+            )
+            .await
+    }
+
+    /// Applied configuration revisions, newest first
+    pub async fn list_history(&self) -> Result<Vec<ConfigRevision>, ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        #[derive(Deserialize)]
+        struct HistoryResponse {
+            revisions: Vec<ConfigRevision>,
+        }
+
+        let response: HistoryResponse = client
+            .execute(client.request(Method::GET, "/api/config/history")?)
+            .await?;
+
+        Ok(response.revisions)
+    }
+
+    /// Settings a revision changed, compared to `against` or to the revision
+    /// before it
+    pub async fn diff_revision(
+        &self,
+        revision: u64,
+        against: Option<u64>,
+    ) -> Result<ConfigDiff, ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        let path = match against {
+            Some(against) => format!("/api/config/history/{revision}/diff?against={against}"),
+            None => format!("/api/config/history/{revision}/diff"),
+        };
+        client.execute(client.request(Method::GET, &path)?).await
+    }
+
+    /// Re-apply a revision, returning the configuration now running
+    pub async fn rollback(&self, revision: u64) -> Result<Value, ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        #[derive(Deserialize)]
+        struct ConfigResponse {
+            config: Value,
+        }
+
+        let path = format!("/api/config/history/{revision}/rollback");
+        let response: ConfigResponse = client.execute(client.request(Method::POST, &path)?).await?;
+
+        Ok(response.config)
+    }
 }
@@ -0,0 +1,87 @@
+//! Device pairing and management service
+
+use gate_frontend_common::client::{create_authenticated_client, ClientError};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DeviceInfo {
+    pub credential_id: String,
+    pub device_name: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub last_used_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PairingCode {
+    pub code: String,
+    pub user_id: String,
+    pub device_name: Option<String>,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceListResponse {
+    pub devices: Vec<DeviceInfo>,
+    pub pending: Vec<PairingCode>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CreatePairingRequest {
+    pub user_id: Option<String>,
+    pub device_name: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct DeviceService;
+
+impl DeviceService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// List the current user's devices and outstanding pairing codes
+    pub async fn list_devices(&self) -> Result<DeviceListResponse, ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        client
+            .execute(client.request(Method::GET, "/api/devices")?)
+            .await
+    }
+
+    /// Create a pairing code for adding a device
+    pub async fn create_pairing_code(
+        &self,
+        request: &CreatePairingRequest,
+    ) -> Result<PairingCode, ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        client
+            .execute(
+                client
+                    .request(Method::POST, "/api/devices/pairing")?
+                    .json(request),
+            )
+            .await
+    }
+
+    /// Revoke a device's passkey
+    pub async fn revoke_device(&self, credential_id: &str) -> Result<(), ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        let _: serde_json::Value = client
+            .execute(client.request(Method::DELETE, &format!("/api/devices/{credential_id}"))?)
+            .await?;
+
+        Ok(())
+    }
+}
+
+impl Default for DeviceService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,99 @@
+//! Evals service
+
+use gate_frontend_common::client::{create_authenticated_client, ClientError};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EvalStatus {
+    Running,
+    Complete,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EvalResult {
+    pub item: usize,
+    #[serde(default)]
+    pub item_id: Option<String>,
+    pub model: String,
+    #[serde(default)]
+    pub answer: Option<String>,
+    #[serde(default)]
+    pub error: Option<String>,
+    pub passed: bool,
+    pub score: f64,
+    pub latency_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ModelScore {
+    pub model: String,
+    pub items: usize,
+    pub passed: usize,
+    pub errors: usize,
+    pub pass_rate: f64,
+    pub mean_score: f64,
+    pub mean_latency_ms: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EvalRun {
+    pub id: String,
+    pub dataset_id: String,
+    pub dataset_name: String,
+    pub models: Vec<String>,
+    pub concurrency: usize,
+    pub started_by: String,
+    pub status: EvalStatus,
+    pub total: usize,
+    pub completed: usize,
+    pub scores: Vec<ModelScore>,
+    #[serde(default)]
+    pub results: Vec<EvalResult>,
+    #[serde(default)]
+    pub error: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalRunListResponse {
+    pub runs: Vec<EvalRun>,
+}
+
+#[derive(Clone)]
+pub struct EvalService;
+
+impl EvalService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// List runs without their per-item results, newest first
+    pub async fn list_runs(&self) -> Result<Vec<EvalRun>, ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        let response: EvalRunListResponse = client
+            .execute(client.request(Method::GET, "/api/admin/evals/runs")?)
+            .await?;
+        Ok(response.runs)
+    }
+
+    /// A run with its per-item results
+    pub async fn get_run(&self, id: &str) -> Result<EvalRun, ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        client
+            .execute(client.request(Method::GET, &format!("/api/admin/evals/runs/{id}"))?)
+            .await
+    }
+}
+
+impl Default for EvalService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,131 @@
+//! Experiments service
+
+use gate_frontend_common::client::{create_authenticated_client, ClientError};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExperimentStatus {
+    Draft,
+    Running,
+    Stopped,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExperimentVariant {
+    pub name: String,
+    pub weight: u32,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub prompt_id: Option<String>,
+    #[serde(default)]
+    pub prompt_version: Option<u32>,
+    #[serde(default)]
+    pub parameters: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Experiment {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub model: String,
+    pub status: ExperimentStatus,
+    pub variants: Vec<ExperimentVariant>,
+    #[serde(default)]
+    pub metrics: Vec<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VariantResults {
+    pub variant: String,
+    pub requests: u64,
+    pub errors: u64,
+    pub mean_latency_ms: f64,
+    pub p95_latency_ms: u64,
+    pub total_cost: f64,
+    pub mean_cost: f64,
+    pub positive_feedback: u64,
+    pub negative_feedback: u64,
+    pub positive_rate: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExperimentListResponse {
+    pub experiments: Vec<Experiment>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExperimentResults {
+    pub experiment: Experiment,
+    pub variants: Vec<VariantResults>,
+}
+
+#[derive(Clone)]
+pub struct ExperimentService;
+
+impl ExperimentService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// List experiments, newest first
+    pub async fn list_experiments(&self) -> Result<Vec<Experiment>, ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        let response: ExperimentListResponse = client
+            .execute(client.request(Method::GET, "/api/admin/experiments")?)
+            .await?;
+        Ok(response.experiments)
+    }
+
+    /// Outcomes of an experiment aggregated per variant
+    pub async fn get_results(&self, id: &str) -> Result<ExperimentResults, ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        client
+            .execute(client.request(Method::GET, &format!("/api/admin/experiments/{id}/results"))?)
+            .await
+    }
+
+    /// Start or stop an experiment, keeping the rest of its definition
+    pub async fn set_status(
+        &self,
+        experiment: &Experiment,
+        status: ExperimentStatus,
+    ) -> Result<Experiment, ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        let body = serde_json::json!({
+            "name": experiment.name,
+            "description": experiment.description,
+            "model": experiment.model,
+            "status": status,
+            "variants": experiment.variants,
+            "metrics": experiment.metrics,
+        });
+        client
+            .execute(
+                client
+                    .request(
+                        Method::PUT,
+                        &format!("/api/admin/experiments/{}", experiment.id),
+                    )?
+                    .json(&body),
+            )
+            .await
+    }
+}
+
+impl Default for ExperimentService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
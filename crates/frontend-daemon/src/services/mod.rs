@@ -1,4 +1,16 @@
+pub mod api_keys;
 pub mod config;
+pub mod devices;
+pub mod evals;
+pub mod experiments;
+pub mod node;
+pub mod onboarding;
+pub mod providers;
+pub mod server_mode;
+pub mod status;
+pub mod tls;
+pub mod tlsforward;
 pub mod user;
+pub mod version;
 
 pub use config::ConfigApiService;
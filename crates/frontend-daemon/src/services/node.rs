@@ -0,0 +1,151 @@
+//! Node identity service
+//!
+//! Fetches the daemon's signed attestation over a fresh nonce, checks the
+//! signature, the nonce and when it was issued, and compares the node id
+//! with the one pinned the first time this browser saw the daemon, so a
+//! daemon whose key changed is noticed.
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use ed25519_dalek::{Signature, VerifyingKey};
+use gate_frontend_common::client::{create_authenticated_client, ClientError};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Local storage key of the pinned node id
+const PINNED_NODE_KEY: &str = "gate.node_id";
+
+/// Minutes from this browser's clock an attestation may have been issued
+const MAX_ATTESTATION_AGE_MINUTES: i64 = 5;
+
+/// The daemon's attestation. The document is kept as JSON, since the
+/// signature covers it as compact JSON with its keys sorted.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Attestation {
+    pub document: Value,
+    pub signature: String,
+}
+
+/// How the daemon's identity compares with what this browser knows
+#[derive(Debug, Clone, PartialEq)]
+pub enum IdentityCheck {
+    /// The node id matches the pinned one
+    Trusted(String),
+    /// No node id was pinned; this one now is
+    Pinned(String),
+    /// The node id differs from the pinned one
+    Changed { pinned: String, current: String },
+    /// The attestation does not verify
+    Invalid(String),
+}
+
+#[derive(Clone)]
+pub struct NodeIdentityService;
+
+impl NodeIdentityService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// The daemon's attestation, signed over `nonce`
+    pub async fn get_attestation(&self, nonce: &str) -> Result<Attestation, ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        client
+            .execute(
+                client
+                    .request(Method::GET, "/api/node/identity")?
+                    .query(&[("nonce", nonce)]),
+            )
+            .await
+    }
+
+    /// Verify the daemon's attestation and compare it with the pinned node
+    pub async fn check(&self) -> Result<IdentityCheck, ClientError> {
+        let mut bytes = [0u8; 16];
+        getrandom::fill(&mut bytes)
+            .map_err(|e| ClientError::Configuration(format!("No randomness for a nonce: {e}")))?;
+        let nonce = hex::encode(bytes);
+        let attestation = self.get_attestation(&nonce).await?;
+        let current = match verify(&attestation).and_then(|node_id| {
+            check_fresh(&attestation, &nonce, chrono::Utc::now()).map(|()| node_id)
+        }) {
+            Ok(node_id) => node_id,
+            Err(reason) => return Ok(IdentityCheck::Invalid(reason)),
+        };
+        Ok(match pinned_node() {
+            Some(pinned) if pinned.eq_ignore_ascii_case(&current) => {
+                IdentityCheck::Trusted(current)
+            }
+            Some(pinned) => IdentityCheck::Changed { pinned, current },
+            None => {
+                pin_node(&current);
+                IdentityCheck::Pinned(current)
+            }
+        })
+    }
+}
+
+impl Default for NodeIdentityService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Check the attestation's signature, returning the node id it names
+pub fn verify(attestation: &Attestation) -> Result<String, String> {
+    let node_id = attestation.document["node_id"]
+        .as_str()
+        .ok_or("The attestation names no node")?;
+    let key_bytes: [u8; 32] = hex::decode(node_id)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or("The node id is not a key")?;
+    let key = VerifyingKey::from_bytes(&key_bytes).map_err(|e| e.to_string())?;
+    let signature_bytes: [u8; 64] = STANDARD
+        .decode(&attestation.signature)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or("The signature is malformed")?;
+    // Objects in a `Value` serialize with their keys sorted
+    let message = serde_json::to_vec(&attestation.document).map_err(|e| e.to_string())?;
+    key.verify_strict(&message, &Signature::from_bytes(&signature_bytes))
+        .map_err(|_| "The signature does not match".to_string())?;
+    Ok(node_id.to_string())
+}
+
+/// Check the attestation signs `nonce` and was issued close to `now`
+pub fn check_fresh(
+    attestation: &Attestation,
+    nonce: &str,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<(), String> {
+    if attestation.document["nonce"].as_str() != Some(nonce) {
+        return Err("The attestation does not sign the nonce sent".to_string());
+    }
+    let issued_at = attestation.document["issued_at"]
+        .as_str()
+        .and_then(|issued| chrono::DateTime::parse_from_rfc3339(issued).ok())
+        .ok_or("The attestation has no issue time")?;
+    if (now - issued_at.to_utc()).num_minutes().abs() > MAX_ATTESTATION_AGE_MINUTES {
+        return Err(format!("The attestation was issued at {issued_at}"));
+    }
+    Ok(())
+}
+
+fn storage() -> Option<web_sys::Storage> {
+    web_sys::window().and_then(|window| window.local_storage().ok().flatten())
+}
+
+/// Node id this browser trusts for the daemon
+pub fn pinned_node() -> Option<String> {
+    storage().and_then(|storage| storage.get_item(PINNED_NODE_KEY).ok().flatten())
+}
+
+/// Trust `node_id` from now on
+pub fn pin_node(node_id: &str) {
+    if let Some(storage) = storage() {
+        let _ = storage.set_item(PINNED_NODE_KEY, node_id);
+    }
+}
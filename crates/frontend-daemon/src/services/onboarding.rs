@@ -0,0 +1,87 @@
+//! Onboarding service for importing provider keys from existing tools
+
+use gate_frontend_common::client::{create_authenticated_client, ClientError};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+
+/// A credential source the daemon found; the key itself is never sent
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct DetectedCredential {
+    /// Source identifier to send back when importing
+    pub source: String,
+    pub label: String,
+    pub provider: String,
+    pub location: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ImportedProvider {
+    pub source: String,
+    pub provider_name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SkippedSource {
+    pub source: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ImportResult {
+    pub imported: Vec<ImportedProvider>,
+    pub skipped: Vec<SkippedSource>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DetectedCredentialsResponse {
+    sources: Vec<DetectedCredential>,
+}
+
+#[derive(Debug, Serialize)]
+struct ImportCredentialsRequest<'a> {
+    sources: &'a [String],
+}
+
+#[derive(Clone)]
+pub struct OnboardingService;
+
+impl OnboardingService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// List credential sources present on the daemon's machine
+    pub async fn detect_credentials(&self) -> Result<Vec<DetectedCredential>, ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        let response: DetectedCredentialsResponse = client
+            .execute(client.request(Method::GET, "/api/onboarding/credentials")?)
+            .await?;
+
+        Ok(response.sources)
+    }
+
+    /// Import keys from the sources the user agreed to
+    pub async fn import_credentials(
+        &self,
+        sources: &[String],
+    ) -> Result<ImportResult, ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        client
+            .execute(
+                client
+                    .request(Method::POST, "/api/onboarding/credentials/import")?
+                    .json(&ImportCredentialsRequest { sources }),
+            )
+            .await
+    }
+}
+
+impl Default for OnboardingService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
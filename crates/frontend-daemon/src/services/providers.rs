@@ -0,0 +1,121 @@
+//! Provider health and control service
+
+use gate_frontend_common::client::{create_authenticated_client, ClientError};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+
+const PROVIDERS_PATH: &str = "/api/admin/providers";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    Closed,
+    HalfOpen,
+    Open,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyStatus {
+    Configured,
+    Missing,
+    AwaitingCapture,
+    NotRequired,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct HealthSample {
+    pub at: chrono::DateTime<chrono::Utc>,
+    pub healthy: bool,
+    pub latency_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ProviderStatus {
+    pub id: String,
+    pub config_name: Option<String>,
+    pub healthy: bool,
+    pub latency_ms: Option<u64>,
+    pub error_rate: f32,
+    pub last_error: Option<String>,
+    pub last_check: chrono::DateTime<chrono::Utc>,
+    pub circuit: CircuitState,
+    pub key_status: KeyStatus,
+    pub disabled: bool,
+    pub history: Vec<HealthSample>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ProviderListResponse {
+    providers: Vec<ProviderStatus>,
+}
+
+#[derive(Debug, Serialize)]
+struct ProbeProviderRequest<'a> {
+    id: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct SetProviderEnabledRequest<'a> {
+    id: &'a str,
+    enabled: bool,
+}
+
+#[derive(Clone)]
+pub struct ProviderService;
+
+impl ProviderService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// List registered providers with their health
+    pub async fn list(&self) -> Result<Vec<ProviderStatus>, ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        let response: ProviderListResponse = client
+            .execute(client.request(Method::GET, PROVIDERS_PATH)?)
+            .await?;
+
+        Ok(response.providers)
+    }
+
+    /// Actively check a provider
+    pub async fn probe(&self, id: &str) -> Result<ProviderStatus, ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        client
+            .execute(
+                client
+                    .request(Method::POST, &format!("{PROVIDERS_PATH}/probe"))?
+                    .json(&ProbeProviderRequest { id }),
+            )
+            .await
+    }
+
+    /// Enable or disable routing to a provider
+    pub async fn set_enabled(
+        &self,
+        id: &str,
+        enabled: bool,
+    ) -> Result<ProviderStatus, ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        client
+            .execute(
+                client
+                    .request(Method::PUT, &format!("{PROVIDERS_PATH}/enabled"))?
+                    .json(&SetProviderEnabledRequest { id, enabled }),
+            )
+            .await
+    }
+}
+
+impl Default for ProviderService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,63 @@
+//! Maintenance and read-only mode service
+
+use gate_frontend_common::client::{create_authenticated_client, ClientError};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ServerMode {
+    pub maintenance: bool,
+    pub read_only: bool,
+    #[serde(default)]
+    pub message: Option<String>,
+    pub maintenance_message: String,
+    #[serde(default)]
+    pub updated_by: Option<String>,
+    #[serde(default)]
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Clone)]
+pub struct ServerModeService;
+
+impl ServerModeService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Modes in effect
+    pub async fn get_mode(&self) -> Result<ServerMode, ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        client
+            .execute(client.request(Method::GET, "/api/admin/mode")?)
+            .await
+    }
+
+    /// Turn modes on or off; an empty message restores the configured one
+    pub async fn set_mode(
+        &self,
+        maintenance: bool,
+        read_only: bool,
+        message: &str,
+    ) -> Result<ServerMode, ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        let body = serde_json::json!({
+            "maintenance": maintenance,
+            "read_only": read_only,
+            "message": message,
+        });
+        client
+            .execute(client.request(Method::PUT, "/api/admin/mode")?.json(&body))
+            .await
+    }
+}
+
+impl Default for ServerModeService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
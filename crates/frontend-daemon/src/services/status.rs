@@ -0,0 +1,43 @@
+//! Daemon status service
+
+use gate_frontend_common::client::{create_authenticated_client, ClientError};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+
+/// Why the daemon started in safe mode
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SafeMode {
+    pub reason: String,
+    pub since: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DaemonStatus {
+    #[serde(default)]
+    pub safe_mode: Option<SafeMode>,
+}
+
+#[derive(Clone)]
+pub struct StatusService;
+
+impl StatusService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Whether the daemon is running normally
+    pub async fn get_status(&self) -> Result<DaemonStatus, ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        client
+            .execute(client.request(Method::GET, "/api/status")?)
+            .await
+    }
+}
+
+impl Default for StatusService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
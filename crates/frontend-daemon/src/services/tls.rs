@@ -0,0 +1,87 @@
+//! TLS certificate status service
+
+use gate_frontend_common::client::{create_authenticated_client, ClientError};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CertificateHealth {
+    Valid,
+    Expiring,
+    Expired,
+    Missing,
+    Invalid,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RenewalAttempt {
+    pub at: chrono::DateTime<chrono::Utc>,
+    pub succeeded: bool,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CertificateStatus {
+    pub name: String,
+    pub health: CertificateHealth,
+    #[serde(default)]
+    pub issuer: Option<String>,
+    #[serde(default)]
+    pub sans: Vec<String>,
+    #[serde(default)]
+    pub not_after: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub days_left: Option<i64>,
+    #[serde(default)]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub last_renewal: Option<RenewalAttempt>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TlsStatus {
+    pub source: String,
+    pub certificates: Vec<CertificateStatus>,
+}
+
+#[derive(Clone)]
+pub struct TlsService;
+
+impl TlsService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Certificates served, with their expiry and last renewal
+    pub async fn get_status(&self) -> Result<TlsStatus, ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        client
+            .execute(client.request(Method::GET, "/api/admin/tls")?)
+            .await
+    }
+
+    /// Renew the certificate for `domain` now, or reload user-supplied ones
+    pub async fn renew(&self, domain: &str) -> Result<TlsStatus, ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        let body = serde_json::json!({ "domain": domain });
+        client
+            .execute(
+                client
+                    .request(Method::POST, "/api/admin/tls/renew")?
+                    .json(&body),
+            )
+            .await
+    }
+}
+
+impl Default for TlsService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
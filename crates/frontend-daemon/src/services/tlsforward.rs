@@ -0,0 +1,100 @@
+//! Relay link diagnostics service
+
+use gate_frontend_common::client::{create_authenticated_client, ClientError};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum TlsForwardStatus {
+    Disabled,
+    Disconnected,
+    Connecting,
+    Connected { domain: String },
+    Error(String),
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkEventKind {
+    Connected,
+    Disconnected,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LinkEvent {
+    pub at: chrono::DateTime<chrono::Utc>,
+    pub kind: LinkEventKind,
+    #[serde(default)]
+    pub server: Option<String>,
+    #[serde(default)]
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ServerDiagnostics {
+    pub server: String,
+    pub connected: bool,
+    #[serde(default)]
+    pub rtt_ms: Option<f64>,
+    #[serde(default)]
+    pub checked_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ForwardedConnection {
+    pub id: u64,
+    pub peer: String,
+    #[serde(default)]
+    pub hostname: Option<String>,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub bytes_received: u64,
+    pub bytes_sent: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct TrafficSnapshot {
+    pub bytes_received: u64,
+    pub bytes_sent: u64,
+    pub active_connections: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TlsForwardDiagnostics {
+    pub enabled: bool,
+    pub status: TlsForwardStatus,
+    #[serde(default)]
+    pub servers: Vec<ServerDiagnostics>,
+    #[serde(default)]
+    pub events: Vec<LinkEvent>,
+    #[serde(default)]
+    pub connections: Vec<ForwardedConnection>,
+    #[serde(default)]
+    pub traffic: TrafficSnapshot,
+}
+
+#[derive(Clone)]
+pub struct TlsForwardService;
+
+impl TlsForwardService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Relay link diagnostics, pinging every server first when `probe` is set
+    pub async fn get_diagnostics(&self, probe: bool) -> Result<TlsForwardDiagnostics, ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        let path = format!("/api/admin/tlsforward/diagnostics?probe={probe}");
+        client.execute(client.request(Method::GET, &path)?).await
+    }
+}
+
+impl Default for TlsForwardService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -12,6 +12,8 @@ pub struct UserInfo {
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
     pub disabled_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub role: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,6 +46,19 @@ pub struct UserPermissionsResponse {
     pub permissions: Vec<UserPermission>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateUserRoleResponse {
+    pub user: UserInfo,
+}
+
+/// Per-user usage limits; `None` means unlimited
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct UserQuota {
+    pub monthly_spend_limit: Option<f64>,
+    pub requests_per_minute: Option<u32>,
+    pub tokens_per_day: Option<u64>,
+}
+
 #[derive(Clone)]
 pub struct UserService;
 
@@ -121,7 +136,6 @@ impl UserService {
         let client = create_authenticated_client()?
             .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
 
-        // For DELETE with no response body, we expect empty JSON
         let _: serde_json::Value = client
             .execute(client.request(Method::DELETE, &format!("/api/admin/users/{user_id}"))?)
             .await?;
@@ -207,6 +221,54 @@ impl UserService {
 
         Ok(())
     }
+
+    /// Assign a built-in role, or clear it with `None`
+    pub async fn set_user_role(
+        &self,
+        user_id: &str,
+        role: Option<&str>,
+    ) -> Result<UserInfo, ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        let response: UpdateUserRoleResponse = client
+            .execute(
+                client
+                    .request(Method::PUT, &format!("/api/admin/users/{user_id}/role"))?
+                    .json(&serde_json::json!({ "role": role })),
+            )
+            .await?;
+
+        Ok(response.user)
+    }
+
+    /// Get a user's quota
+    pub async fn get_user_quota(&self, user_id: &str) -> Result<UserQuota, ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        client
+            .execute(client.request(Method::GET, &format!("/api/admin/users/{user_id}/quota"))?)
+            .await
+    }
+
+    /// Replace a user's quota
+    pub async fn update_user_quota(
+        &self,
+        user_id: &str,
+        quota: &UserQuota,
+    ) -> Result<UserQuota, ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        client
+            .execute(
+                client
+                    .request(Method::PUT, &format!("/api/admin/users/{user_id}/quota"))?
+                    .json(quota),
+            )
+            .await
+    }
 }
 
 impl Default for UserService {
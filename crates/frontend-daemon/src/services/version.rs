@@ -0,0 +1,79 @@
+//! Daemon version service
+//!
+//! Compares the daemon's build with the one this UI was built from, so a
+//! UI left behind by a partial upgrade is noticed.
+
+use chrono::{DateTime, Utc};
+use gate_frontend_common::client::{create_authenticated_client, ClientError};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+
+/// Commit recorded when the git hash is not known
+const UNKNOWN_HASH: &str = "unknown";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VersionInfo {
+    pub version: String,
+    pub git_hash: String,
+    pub build_date: DateTime<Utc>,
+    #[serde(default)]
+    pub frontend_hash: Option<String>,
+}
+
+impl VersionInfo {
+    /// The build of this UI
+    pub fn frontend() -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            git_hash: env!("GATE_GIT_HASH").to_string(),
+            build_date: env!("GATE_BUILD_TIMESTAMP")
+                .parse()
+                .ok()
+                .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+                .unwrap_or_default(),
+            frontend_hash: None,
+        }
+    }
+
+    /// `version (hash)`, for display
+    pub fn label(&self) -> String {
+        format!("{} ({})", self.version, self.git_hash)
+    }
+
+    /// Whether this build is older than, and not the same as, `daemon`.
+    /// Builds are told apart by commit when both know theirs, by version
+    /// otherwise.
+    pub fn is_older_than(&self, daemon: &VersionInfo) -> bool {
+        let different = if self.git_hash != UNKNOWN_HASH && daemon.git_hash != UNKNOWN_HASH {
+            self.git_hash != daemon.git_hash
+        } else {
+            self.version != daemon.version
+        };
+        different && self.build_date < daemon.build_date
+    }
+}
+
+#[derive(Clone)]
+pub struct VersionService;
+
+impl VersionService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// What the daemon was built from
+    pub async fn get_version(&self) -> Result<VersionInfo, ClientError> {
+        let client = create_authenticated_client()?
+            .ok_or_else(|| ClientError::Configuration("Not authenticated".into()))?;
+
+        client
+            .execute(client.request(Method::GET, "/api/version")?)
+            .await
+    }
+}
+
+impl Default for VersionService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
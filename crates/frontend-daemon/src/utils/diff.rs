@@ -0,0 +1,57 @@
+//! Line diff for previewing configuration changes
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffKind {
+    Same,
+    Added,
+    Removed,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffLine {
+    pub kind: DiffKind,
+    pub text: String,
+}
+
+/// Diff two texts line by line using a longest-common-subsequence table.
+///
+/// Quadratic in the number of lines, which is fine for config documents.
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // lcs[i][j] = LCS length of old[i..] and new[j..]
+    let mut lcs = vec![vec![0u32; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let line = |kind, text: &str| DiffLine {
+        kind,
+        text: text.to_string(),
+    };
+    let mut result = Vec::with_capacity(old.len().max(new.len()));
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            result.push(line(DiffKind::Same, old[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            result.push(line(DiffKind::Removed, old[i]));
+            i += 1;
+        } else {
+            result.push(line(DiffKind::Added, new[j]));
+            j += 1;
+        }
+    }
+    result.extend(old[i..].iter().map(|text| line(DiffKind::Removed, text)));
+    result.extend(new[j..].iter().map(|text| line(DiffKind::Added, text)));
+    result
+}
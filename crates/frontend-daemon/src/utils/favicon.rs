@@ -6,9 +6,9 @@ pub fn update_favicon(is_dark_mode: bool) {
         if let Some(document) = window.document() {
             // Use white icon in dark mode, black icon in light mode for better contrast
             let favicon_path = if is_dark_mode {
-                "/assets/hellas-token-white.svg"
+                "assets/hellas-token-white.svg"
             } else {
-                "/assets/hellas-token-black.svg"
+                "assets/hellas-token-black.svg"
             };
 
             // Remove existing favicon links
@@ -1,3 +1,4 @@
+pub mod diff;
 pub mod favicon;
 
 use wasm_bindgen::prelude::*;
@@ -1,6 +1,6 @@
 use crate::tauri_api::{
     configure_tlsforward, enable_tlsforward, get_daemon_runtime_config, get_daemon_status,
-    start_daemon, DaemonRuntimeConfig, Settings, TlsForwardState,
+    start_daemon, DaemonRuntimeConfig, Settings, TlsForwardState, TransportKind,
 };
 use gloo_timers::callback::Interval;
 use wasm_bindgen::JsCast;
@@ -475,15 +475,17 @@ impl Component for DaemonStatusComponent {
                                     }}
 
                                     {match &config.tlsforward_state {
-                                        Some(TlsForwardState::Connected { server_address: _, assigned_domain }) => {
+                                        Some(TlsForwardState::Connected { domain: assigned_domain, transport }) => {
                                             html! {
                                                 <>
                                                 <div class="flex items-center justify-between">
-                                                    <span class={classes!("text-xs", "uppercase", "tracking-wider", "font-medium", "text-gray-500")}>{"TLS Forward Domain"}</span>
+                                                    <span class={classes!("text-xs", "uppercase", "tracking-wider", "font-medium", "text-gray-500")}>{transport.address_label()}</span>
                                                     <div class="flex items-center gap-2">
                                                         {if let Some(daemon_cfg) = &self.daemon_config {
-                                                            if daemon_cfg.letsencrypt.email.is_some() {
-                                                                // Email configured - show HTTPS URL
+                                                            if daemon_cfg.letsencrypt.email.is_some()
+                                                                || *transport == TransportKind::Cloudflare
+                                                            {
+                                                                // Email configured, or Cloudflare terminating TLS - show HTTPS URL
                                                                 html! {
                                                                     <a
                                                                         href={format!("https://{assigned_domain}")}
@@ -78,12 +78,36 @@ pub enum TlsForwardState {
     Disconnected,
     Connecting,
     Connected {
-        server_address: String,
-        assigned_domain: String,
+        domain: String,
+        #[serde(default)]
+        transport: TransportKind,
     },
     Error(String),
 }
 
+/// Transport the node is exposed through
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TransportKind {
+    #[default]
+    Relay,
+    Ssh,
+    Cloudflare,
+    Command,
+}
+
+impl TransportKind {
+    /// Label for the public address the node is reached at
+    pub fn address_label(self) -> &'static str {
+        match self {
+            TransportKind::Relay => "TLS Forward Domain",
+            TransportKind::Ssh => "SSH Tunnel Address",
+            TransportKind::Cloudflare => "Cloudflare Tunnel Hostname",
+            TransportKind::Command => "Tunnel Address",
+        }
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
 pub struct DaemonRuntimeConfig {
     pub listen_address: String,
@@ -1,5 +1,7 @@
+use gate_daemon::bootstrap::BootstrapPairing;
+use gate_daemon::services::DoctorService;
 use gate_daemon::types::DaemonRuntimeConfigResponse;
-use gate_daemon::{Daemon, DaemonStatus, Settings, StateDir};
+use gate_daemon::{Daemon, DaemonStatus, DoctorReport, Settings, StateDir};
 use tauri::path::BaseDirectory;
 use tauri::{AppHandle, Manager, State};
 
@@ -207,3 +209,46 @@ pub async fn get_bootstrap_token(daemon: State<'_, Daemon>) -> Result<Option<Str
         .get_token()
         .await)
 }
+
+/// Pairing payload for the GUI to render as a QR code
+#[tauri::command]
+pub async fn get_bootstrap_pairing(
+    daemon: State<'_, Daemon>,
+) -> Result<Option<BootstrapPairing>, String> {
+    daemon
+        .bootstrap_pairing()
+        .await
+        .map_err(|e| format!("Failed to get bootstrap pairing: {e}"))
+}
+
+/// Replace the bootstrap token, e.g. after it expired
+#[tauri::command]
+pub async fn regenerate_bootstrap_token(daemon: State<'_, Daemon>) -> Result<String, String> {
+    daemon
+        .get_bootstrap_manager()
+        .await
+        .map_err(|e| format!("Failed to get bootstrap manager: {e}"))?
+        .regenerate_token()
+        .await
+        .map_err(|e| format!("Failed to regenerate bootstrap token: {e}"))
+}
+
+#[tauri::command]
+pub async fn run_doctor(daemon: State<'_, Daemon>) -> Result<DoctorReport, String> {
+    let settings = daemon
+        .get_settings()
+        .await
+        .map_err(|e| format!("Failed to get config: {e}"))?;
+    let status = daemon
+        .status()
+        .await
+        .map_err(|e| format!("Failed to get status: {e}"))?;
+    let state_dir = daemon
+        .state_dir()
+        .map_err(|e| format!("Failed to get state directory: {e}"))?;
+
+    Ok(DoctorService::new(settings, state_dir)
+        .with_status(status)
+        .run()
+        .await)
+}
@@ -52,6 +52,9 @@ fn main() {
             commands::disable_tlsforward,
             commands::get_bootstrap_url,
             commands::get_bootstrap_token,
+            commands::get_bootstrap_pairing,
+            commands::regenerate_bootstrap_token,
+            commands::run_doctor,
         ])
         .on_window_event(|window, event| {
             if let tauri::WindowEvent::CloseRequested { .. } = event {
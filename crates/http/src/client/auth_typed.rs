@@ -82,4 +82,7 @@ pub struct UserResponse {
     pub name: Option<String>,
     pub created_at: String,
     pub updated_at: String,
+    /// Per-user preferences, when the server provides them
+    #[serde(default)]
+    pub preferences: serde_json::Value,
 }
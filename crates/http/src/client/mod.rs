@@ -114,11 +114,11 @@ impl GateClientBuilder {
         // Parse base_url into Url
         let mut base_url = Url::parse(&base_url)
             .map_err(|e| ClientError::Configuration(format!("invalid base_url: {e}")))?;
-        // Normalize path to avoid trailing slash issues
-        if base_url.path() != "/" && base_url.path().ends_with('/') {
-            base_url = base_url
-                .join(".")
-                .map_err(|e| ClientError::Configuration(format!("invalid base_url path: {e}")))?;
+        // Joining a request path replaces the base's last segment unless it
+        // ends in a slash, which would drop a base path such as `/gate`
+        if !base_url.path().ends_with('/') {
+            let path = format!("{}/", base_url.path());
+            base_url.set_path(&path);
         }
 
         let mut client_builder = ClientBuilder::new();
@@ -33,10 +33,11 @@ impl PublicGateClient {
     ) -> Result<Self, ClientError> {
         let mut base_url = Url::parse(&base_url.into())
             .map_err(|e| ClientError::Configuration(format!("invalid base_url: {e}")))?;
-        if base_url.path() != "/" && base_url.path().ends_with('/') {
-            base_url = base_url
-                .join(".")
-                .map_err(|e| ClientError::Configuration(format!("invalid base_url path: {e}")))?;
+        // Joining a request path replaces the base's last segment unless it
+        // ends in a slash, which would drop a base path such as `/gate`
+        if !base_url.path().ends_with('/') {
+            let path = format!("{}/", base_url.path());
+            base_url.set_path(&path);
         }
 
         #[cfg(not(target_arch = "wasm32"))]
@@ -116,10 +117,11 @@ impl AuthenticatedGateClient {
     ) -> Result<Self, ClientError> {
         let mut base_url = Url::parse(&base_url.into())
             .map_err(|e| ClientError::Configuration(format!("invalid base_url: {e}")))?;
-        if base_url.path() != "/" && base_url.path().ends_with('/') {
-            base_url = base_url
-                .join(".")
-                .map_err(|e| ClientError::Configuration(format!("invalid base_url path: {e}")))?;
+        // Joining a request path replaces the base's last segment unless it
+        // ends in a slash, which would drop a base path such as `/gate`
+        if !base_url.path().ends_with('/') {
+            let path = format!("{}/", base_url.path());
+            base_url.set_path(&path);
         }
         let api_key = api_key.into();
 
@@ -164,7 +166,8 @@ impl AuthenticatedGateClient {
             .header(header::AUTHORIZATION, format!("Bearer {}", self.api_key)))
     }
 
-    /// Execute a request and handle common errors
+    /// Execute a request and handle common errors. A success without a body,
+    /// such as a 204, reads as JSON `null`.
     pub async fn execute<T: serde::de::DeserializeOwned>(
         &self,
         request: reqwest::RequestBuilder,
@@ -173,7 +176,12 @@ impl AuthenticatedGateClient {
         let status = response.status();
 
         if status.is_success() {
-            Ok(response.json().await?)
+            let body = response.bytes().await?;
+            if body.is_empty() {
+                Ok(serde_json::from_value(serde_json::Value::Null)?)
+            } else {
+                Ok(serde_json::from_slice(&body)?)
+            }
         } else {
             let message = response.text().await.unwrap_or_else(|_| status.to_string());
             Err(ClientError::from_status(status, message))
@@ -6,6 +6,8 @@ use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
 };
+#[cfg(feature = "server")]
+use gate_core::{ErrorClass, UpstreamError};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -69,6 +71,7 @@ pub struct ErrorResponse {
 #[cfg(feature = "server")]
 impl IntoResponse for HttpError {
     fn into_response(self) -> Response {
+        let mut details = None;
         let (status, error_type) = match &self {
             HttpError::AuthenticationFailed(_) => {
                 (StatusCode::UNAUTHORIZED, "authentication_failed")
@@ -91,7 +94,14 @@ impl IntoResponse for HttpError {
             HttpError::Core(core_err) => {
                 use gate_core::Error;
                 match core_err {
-                    Error::Rejected(status, _) => (*status, "upstream_error"),
+                    Error::Upstream(upstream) => {
+                        details = Some(upstream_details(upstream));
+                        (upstream.client_status(), upstream.class.as_str())
+                    }
+                    Error::Rejected(status, message) => {
+                        let class = ErrorClass::classify(*status, message);
+                        (class.status(*status), class.as_str())
+                    }
                     Error::Unauthorized => (StatusCode::FORBIDDEN, "unauthorized"),
                     Error::ApiKeyNotFound | Error::InvalidApiKey => {
                         (StatusCode::UNAUTHORIZED, "invalid_api_key")
@@ -100,7 +110,14 @@ impl IntoResponse for HttpError {
                         (StatusCode::NOT_FOUND, "not_found")
                     }
                     Error::QuotaExceeded(_) => (StatusCode::TOO_MANY_REQUESTS, "quota_exceeded"),
+                    Error::ConcurrencyLimit { limit, active } => {
+                        details = Some(serde_json::json!({ "limit": limit, "active": active }));
+                        (StatusCode::TOO_MANY_REQUESTS, "concurrency_limit_exceeded")
+                    }
                     Error::InvalidRequest(_) => (StatusCode::BAD_REQUEST, "invalid_request"),
+                    Error::StructuredOutput(_) => {
+                        (StatusCode::BAD_GATEWAY, "invalid_structured_output")
+                    }
                     Error::ServiceUnavailable(_) => {
                         (StatusCode::SERVICE_UNAVAILABLE, "service_unavailable")
                     }
@@ -112,12 +129,27 @@ impl IntoResponse for HttpError {
         let body = ErrorResponse {
             error: error_type.to_string(),
             message: self.to_string(),
-            details: None,
+            details,
         };
 
         (status, Json(body)).into_response()
     }
 }
 
+/// Envelope details of a provider error; the provider's own body is kept
+/// under `upstream` for clients that understand it
+#[cfg(feature = "server")]
+fn upstream_details(error: &UpstreamError) -> serde_json::Value {
+    let upstream = serde_json::from_str(&error.body)
+        .unwrap_or_else(|_| serde_json::Value::String(error.body.clone()));
+    serde_json::json!({
+        "provider": error.provider,
+        "class": error.class,
+        "retryable": error.class.is_retryable(),
+        "upstream_status": error.status.as_u16(),
+        "upstream": upstream,
+    })
+}
+
 /// Result type alias using HttpError
 pub type Result<T> = std::result::Result<T, HttpError>;
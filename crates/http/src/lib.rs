@@ -29,6 +29,8 @@ pub mod sinks;
 pub mod state;
 #[cfg(feature = "server")]
 pub mod streaming;
+#[cfg(feature = "server")]
+pub mod tools;
 
 #[cfg(feature = "client")]
 pub mod client;
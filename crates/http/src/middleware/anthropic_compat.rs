@@ -0,0 +1,307 @@
+//! Anthropic API compatibility mode
+//!
+//! Lets Anthropic clients such as Claude Code use the gateway as if it were
+//! the Anthropic API. On the Anthropic surface (`/v1/messages`,
+//! `/v1/messages/count_tokens` and `/v1/models`) errors use Anthropic's
+//! envelope, model listings use Anthropic's page shape, every response
+//! carries a `request-id`, and streams carry only Anthropic events.
+//!
+//! Requests that came in on a configured Anthropic prefix are on the
+//! Anthropic surface whether or not compatibility mode is on; requests on an
+//! OpenAI prefix never are.
+
+use crate::routes::ApiSurface;
+use axum::{
+    body::{Body, to_bytes},
+    extract::Request,
+    http::{HeaderName, HeaderValue, StatusCode, header::CONTENT_TYPE},
+    middleware::Next,
+    response::{IntoResponse, Json, Response},
+};
+use futures::StreamExt;
+use gate_core::router::{ResponseChunk, ResponseStream};
+use serde_json::{Value as JsonValue, json};
+
+const REQUEST_ID: HeaderName = HeaderName::from_static("request-id");
+
+/// Largest error or model listing body that is rewritten
+const MAX_REWRITE_BYTES: usize = 1024 * 1024;
+
+/// Marker in request extensions telling handlers to answer in Anthropic's
+/// exact wire format
+#[derive(Debug, Clone, Copy)]
+pub struct AnthropicCompat;
+
+/// Whether `path` is part of the Anthropic API surface
+pub fn is_anthropic_path(path: &str) -> bool {
+    path == "/v1/messages" || path == "/v1/messages/count_tokens" || path == "/v1/models"
+}
+
+/// Anthropic error type for an HTTP status
+pub fn error_type(status: StatusCode) -> &'static str {
+    match status.as_u16() {
+        400 | 422 => "invalid_request_error",
+        401 => "authentication_error",
+        402 => "billing_error",
+        403 => "permission_error",
+        404 => "not_found_error",
+        413 => "request_too_large",
+        429 => "rate_limit_error",
+        503 | 529 => "overloaded_error",
+        504 => "timeout_error",
+        _ => "api_error",
+    }
+}
+
+/// Anthropic error envelope
+pub fn error_body(error_type: &str, message: &str) -> JsonValue {
+    json!({
+        "type": "error",
+        "error": {"type": error_type, "message": message}
+    })
+}
+
+/// Turn a gateway error body into an Anthropic error envelope. Errors that
+/// already came from Anthropic upstream are passed through unchanged.
+pub fn to_anthropic_error(status: StatusCode, body: &[u8]) -> JsonValue {
+    let parsed: Option<JsonValue> = serde_json::from_slice(body).ok();
+    if let Some(value) = &parsed
+        && value.get("type").and_then(|t| t.as_str()) == Some("error")
+    {
+        return value.clone();
+    }
+
+    let message = parsed
+        .as_ref()
+        .and_then(|v| v.get("message"))
+        .and_then(|m| m.as_str())
+        .map(String::from)
+        .unwrap_or_else(|| String::from_utf8_lossy(body).into_owned());
+
+    // Provider errors carry the provider's body in their details
+    if let Some(upstream) = parsed.as_ref().map(|v| &v["details"]["upstream"])
+        && upstream.get("type").and_then(|t| t.as_str()) == Some("error")
+    {
+        return upstream.clone();
+    }
+
+    error_body(error_type(status), &message)
+}
+
+/// Turn an OpenAI-style model list into an Anthropic models page
+pub fn to_anthropic_models(list: &JsonValue) -> JsonValue {
+    let models: Vec<JsonValue> = list
+        .get("data")
+        .and_then(|d| d.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|m| {
+            let id = m.get("id")?.as_str()?;
+            let created_at = m
+                .get("created")
+                .and_then(|c| c.as_i64())
+                .and_then(|c| chrono::DateTime::from_timestamp(c, 0))
+                .unwrap_or_default();
+            Some(json!({
+                "type": "model",
+                "id": id,
+                "display_name": id,
+                "created_at": created_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            }))
+        })
+        .collect();
+
+    let first_id = models.first().map(|m| m["id"].clone());
+    let last_id = models.last().map(|m| m["id"].clone());
+    json!({
+        "data": models,
+        "has_more": false,
+        "first_id": first_id,
+        "last_id": last_id,
+    })
+}
+
+/// Reduce a response stream to what the Anthropic API would send: gateway
+/// usage, metadata and completion events are dropped, and errors become
+/// Anthropic `error` events.
+pub fn anthropic_events(stream: ResponseStream) -> ResponseStream {
+    let stream = stream.filter_map(|item| {
+        let mapped = match item {
+            Ok(
+                chunk @ (ResponseChunk::Headers(_)
+                | ResponseChunk::Content(_)
+                | ResponseChunk::Raw { .. }),
+            ) => Some(Ok(chunk)),
+            Ok(ResponseChunk::Stop {
+                error: Some(error), ..
+            }) => Some(Ok(ResponseChunk::Content(stream_error(&error)))),
+            Ok(_) => None,
+            Err(e) => Some(Ok(ResponseChunk::Content(error_body(
+                "api_error",
+                &e.to_string(),
+            )))),
+        };
+        std::future::ready(mapped)
+    });
+    Box::pin(stream)
+}
+
+/// Error event for an error reported mid-stream. Upstream Anthropic errors
+/// arrive as their serialized `error` object.
+fn stream_error(error: &str) -> JsonValue {
+    match serde_json::from_str::<JsonValue>(error) {
+        Ok(inner) if inner.get("type").is_some() && inner.get("message").is_some() => {
+            json!({"type": "error", "error": inner})
+        }
+        _ => error_body("api_error", error),
+    }
+}
+
+/// Middleware applying Anthropic wire conventions to the Anthropic surface
+pub async fn anthropic_compat_middleware(request: Request, next: Next) -> Response {
+    let surface = request.extensions().get::<ApiSurface>().copied();
+    if surface == Some(ApiSurface::OpenAI) || !is_anthropic_path(request.uri().path()) {
+        return next.run(request).await;
+    }
+    apply_compat(request, next).await
+}
+
+/// Middleware applying Anthropic wire conventions to requests on an
+/// Anthropic prefix only, for when compatibility mode is off
+pub async fn anthropic_prefix_middleware(request: Request, next: Next) -> Response {
+    let surface = request.extensions().get::<ApiSurface>().copied();
+    if surface != Some(ApiSurface::Anthropic) || !is_anthropic_path(request.uri().path()) {
+        return next.run(request).await;
+    }
+    apply_compat(request, next).await
+}
+
+async fn apply_compat(mut request: Request, next: Next) -> Response {
+    let path = request.uri().path().to_string();
+    request.extensions_mut().insert(AnthropicCompat);
+
+    let response = next.run(request).await;
+    let status = response.status();
+    let is_json = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+
+    let mut response = if status == StatusCode::NOT_MODIFIED {
+        // The client already has the page, rewritten from the same list
+        response
+    } else if !status.is_success() {
+        let (parts, body) = response.into_parts();
+        let bytes = to_bytes(body, MAX_REWRITE_BYTES).await.unwrap_or_default();
+        let mut rewritten = (status, Json(to_anthropic_error(status, &bytes))).into_response();
+        carry_headers(&parts.headers, rewritten.headers_mut());
+        rewritten
+    } else if path == "/v1/models" && is_json {
+        let (parts, body) = response.into_parts();
+        match to_bytes(body, MAX_REWRITE_BYTES)
+            .await
+            .ok()
+            .and_then(|b| serde_json::from_slice::<JsonValue>(&b).ok())
+        {
+            Some(list) => {
+                let mut rewritten = Json(to_anthropic_models(&list)).into_response();
+                carry_headers(&parts.headers, rewritten.headers_mut());
+                rewritten
+            }
+            None => Response::from_parts(parts, Body::empty()),
+        }
+    } else {
+        response
+    };
+
+    if !response.headers().contains_key(REQUEST_ID)
+        && let Ok(value) = HeaderValue::from_str(&format!("req_{}", uuid::Uuid::new_v4().simple()))
+    {
+        response.headers_mut().insert(REQUEST_ID, value);
+    }
+    response
+}
+
+/// Keep upstream headers such as `request-id` on a rewritten response
+fn carry_headers(from: &axum::http::HeaderMap, to: &mut axum::http::HeaderMap) {
+    for (name, value) in from {
+        if name != CONTENT_TYPE && name != axum::http::header::CONTENT_LENGTH {
+            to.insert(name.clone(), value.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gate_core::router::types::StopReason;
+
+    #[test]
+    fn test_gateway_errors_use_anthropic_envelope() {
+        let body = br#"{"error":"not_found","message":"Resource not found: model x"}"#;
+        assert_eq!(
+            to_anthropic_error(StatusCode::NOT_FOUND, body),
+            error_body("not_found_error", "Resource not found: model x")
+        );
+    }
+
+    #[test]
+    fn test_upstream_anthropic_errors_pass_through() {
+        let upstream =
+            r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#;
+        let body = json!({
+            "error": "overloaded",
+            "message": "Upstream error: anthropic overloaded: Overloaded",
+            "details": {"upstream": serde_json::from_str::<JsonValue>(upstream).unwrap()},
+        });
+        let converted = to_anthropic_error(
+            StatusCode::from_u16(529).unwrap(),
+            body.to_string().as_bytes(),
+        );
+        assert_eq!(
+            converted,
+            serde_json::from_str::<JsonValue>(upstream).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_models_page_shape() {
+        let list = json!({"object": "list", "data": [
+            {"id": "claude-sonnet-4", "object": "model", "owned_by": "system", "created": 0},
+            {"id": "claude-opus-4", "object": "model", "owned_by": "system", "created": 0}
+        ]});
+        let page = to_anthropic_models(&list);
+        assert_eq!(page["data"][0]["type"], "model");
+        assert_eq!(page["data"][0]["created_at"], "1970-01-01T00:00:00Z");
+        assert_eq!(page["first_id"], "claude-sonnet-4");
+        assert_eq!(page["last_id"], "claude-opus-4");
+        assert_eq!(page["has_more"], false);
+    }
+
+    #[tokio::test]
+    async fn test_stream_drops_gateway_events_and_maps_errors() {
+        let chunks = vec![
+            Ok(ResponseChunk::Content(json!({"type": "message_start"}))),
+            Ok(ResponseChunk::Usage {
+                prompt_tokens: 1,
+                completion_tokens: 1,
+            }),
+            Ok(ResponseChunk::Stop {
+                reason: StopReason::Error,
+                error: Some(r#"{"type":"overloaded_error","message":"Overloaded"}"#.into()),
+                cost: None,
+            }),
+        ];
+        let events: Vec<_> = anthropic_events(Box::pin(futures::stream::iter(chunks)))
+            .collect()
+            .await;
+        assert_eq!(events.len(), 2);
+        match &events[1] {
+            Ok(ResponseChunk::Content(json)) => {
+                assert_eq!(json, &error_body("overloaded_error", "Overloaded"));
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+    }
+}
@@ -2,6 +2,8 @@ use crate::error::HttpError;
 use crate::services::identity::HttpIdentity;
 use async_trait::async_trait;
 use axum::{extract::Request, http::request::Parts, middleware::Next, response::Response};
+use gate_core::router::ResponseMetadata;
+use std::time::Instant;
 
 /// Trait for authentication providers
 #[async_trait]
@@ -33,7 +35,12 @@ where
 
     let (mut parts, body) = req.into_parts();
 
-    match app_state.authenticate(&parts).await {
+    let started = Instant::now();
+    let authenticated = app_state.authenticate(&parts).await;
+    if let Some(metadata) = parts.extensions.get::<ResponseMetadata>() {
+        metadata.publish(|info| info.timings.auth = Some(started.elapsed()));
+    }
+    match authenticated {
         Ok(identity) => {
             parts.extensions.insert(identity);
             let req = Request::from_parts(parts, body);
@@ -4,11 +4,14 @@
 //! supporting both W3C TraceContext headers and legacy x-correlation-id.
 
 use axum::{
+    body::Body,
     extract::Request,
-    http::{HeaderMap, HeaderName, HeaderValue},
+    http::{HeaderMap, HeaderName, HeaderValue, header::CONTENT_TYPE},
     middleware::Next,
-    response::IntoResponse,
+    response::{IntoResponse, Response},
 };
+use bytes::Bytes;
+use futures::StreamExt;
 use gate_core::tracing::{
     prelude::*,
     trace_context::{extract_trace_context, inject_trace_context},
@@ -41,7 +44,24 @@ pub fn extract_correlation_id(headers: &HeaderMap) -> CorrelationId {
         .unwrap_or_default()
 }
 
-/// Middleware to handle correlation IDs
+/// Start an event stream with a comment naming the correlation id, for
+/// clients that only ever see the stream. Comments are ignored by SSE
+/// parsers.
+fn with_correlation_comment(response: Response, correlation_id: &CorrelationId) -> Response {
+    let is_event_stream = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/event-stream"));
+    if !is_event_stream {
+        return response;
+    }
+    let comment = Bytes::from(format!(": correlation-id {correlation_id}\n\n"));
+    let (parts, body) = response.into_parts();
+    let body = futures::stream::once(async move { Ok(comment) }).chain(body.into_data_stream());
+    Response::from_parts(parts, Body::from_stream(body))
+}
+
 /// Middleware to handle correlation IDs
 #[allow(clippy::manual_async_fn)]
 pub fn correlation_id_middleware(
@@ -82,7 +102,8 @@ pub fn correlation_id_middleware(
             // Process request with the span attached
             // Note: When compiling with all features, we skip the context guard to avoid Send issues
             // The span itself still provides the trace context for OpenTelemetry
-            let mut response = next.run(request).instrument(span).await;
+            let mut response =
+                with_correlation_comment(next.run(request).instrument(span).await, &correlation_id);
 
             // Add both W3C and legacy headers to response
             let headers = response.headers_mut();
@@ -119,7 +140,8 @@ pub fn correlation_id_middleware(
             );
 
             // Process request with the span attached
-            let mut response = next.run(request).instrument(span).await;
+            let mut response =
+                with_correlation_comment(next.run(request).instrument(span).await, &correlation_id);
 
             // Add both W3C and legacy headers to response
             let headers = response.headers_mut();
@@ -156,7 +178,8 @@ pub fn correlation_id_middleware(
             );
 
             // Process request with the span attached
-            let mut response = next.run(request).instrument(span).await;
+            let mut response =
+                with_correlation_comment(next.run(request).instrument(span).await, &correlation_id);
 
             // Add both W3C and legacy headers to response
             let headers = response.headers_mut();
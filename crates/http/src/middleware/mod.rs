@@ -1,17 +1,23 @@
 //! Middleware components for HTTP request processing
 
+pub mod anthropic_compat;
 pub mod auth;
 pub mod correlation;
 pub mod metrics;
+pub mod timing;
 pub mod trace;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod webauthn;
 
+pub use anthropic_compat::{
+    AnthropicCompat, anthropic_compat_middleware, anthropic_prefix_middleware,
+};
 pub use auth::{AuthProvider, auth_middleware};
 pub use correlation::{
     CORRELATION_ID_HEADER, CorrelationIdExt, correlation_id_middleware, extract_correlation_id,
 };
 pub use metrics::metrics_middleware;
+pub use timing::timing_middleware;
 pub use trace::with_request_tracing;
 #[cfg(not(target_arch = "wasm32"))]
 pub use webauthn::{WebAuthnConfig, WebAuthnState};
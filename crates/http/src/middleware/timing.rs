@@ -0,0 +1,37 @@
+//! Per-request timings
+//!
+//! Gives each request the [`ResponseMetadata`] its stages publish their
+//! timings to; handlers build the request context with it. When enabled,
+//! the timings known once the response starts are sent back in a
+//! `Server-Timing` header.
+
+use axum::{
+    extract::{Request, State},
+    http::HeaderValue,
+    middleware::Next,
+    response::Response,
+};
+use gate_core::router::ResponseMetadata;
+use gate_core::router::timings::SERVER_TIMING_HEADER;
+use std::time::Instant;
+
+/// Middleware giving each request its response metadata, and adding a
+/// `Server-Timing` header to the response if `server_timing` is set
+pub async fn timing_middleware(
+    State(server_timing): State<bool>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let started = Instant::now();
+    let metadata = ResponseMetadata::default();
+    request.extensions_mut().insert(metadata.clone());
+
+    let mut response = next.run(request).await;
+    if server_timing {
+        let timings = metadata.get().timings.server_timing(started.elapsed());
+        if let Ok(value) = HeaderValue::from_str(&timings) {
+            response.headers_mut().insert(SERVER_TIMING_HEADER, value);
+        }
+    }
+    response
+}
@@ -0,0 +1,951 @@
+//! OpenAI Assistants API emulation
+//!
+//! Assistants, threads, messages and runs are kept in Gate's state backend
+//! and runs are executed through chat completions, so apps written against
+//! the Assistants API work with any routed backend. Runs execute while the
+//! create request is open: the returned run is already completed, failed or
+//! waiting on tool outputs. Only function tools are supported, and runs
+//! cannot be streamed.
+//!
+//! Not part of the default router; servers opt in by merging [`router`].
+
+mod run;
+
+use crate::{auth::extract_identity, error::HttpError, state::AppState};
+use axum::{
+    Router,
+    extract::{Json, Path, Query, State},
+    http::{HeaderMap, HeaderName},
+    routing::{get, post},
+};
+use gate_core::router::{routing::Router as CoreRouter, sink::RequestContext};
+use gate_core::tracing::prelude::*;
+use gate_core::{AssistantObject, StateBackend};
+use serde::Deserialize;
+use serde_json::{Value as JsonValue, json};
+use std::sync::Arc;
+
+const X_TRACE_ID: HeaderName = HeaderName::from_static("x-trace-id");
+
+const ASSISTANT: &str = "assistant";
+const THREAD: &str = "thread";
+const MESSAGE: &str = "thread.message";
+const RUN: &str = "thread.run";
+
+/// Default and largest page size for list endpoints
+const DEFAULT_LIMIT: usize = 20;
+const MAX_LIMIT: usize = 100;
+
+/// Assistant fields a client may set
+const ASSISTANT_FIELDS: &[&str] = &[
+    "model",
+    "name",
+    "description",
+    "instructions",
+    "tools",
+    "metadata",
+    "temperature",
+    "top_p",
+    "response_format",
+];
+
+/// Pagination parameters shared by list endpoints
+#[derive(Debug, Default, Deserialize)]
+pub struct ListQuery {
+    pub limit: Option<usize>,
+    /// `asc` or `desc` by creation time; defaults to `desc`
+    pub order: Option<String>,
+    pub after: Option<String>,
+    pub before: Option<String>,
+}
+
+fn new_id(prefix: &str) -> String {
+    format!("{prefix}_{}", uuid::Uuid::new_v4().simple())
+}
+
+fn now() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+/// The caller's Assistants objects
+struct Store<'a> {
+    backend: &'a dyn StateBackend,
+    owner_id: &'a str,
+}
+
+impl Store<'_> {
+    /// Load an object of the given type, hiding other callers' objects
+    async fn get(&self, object: &str, id: &str) -> Result<AssistantObject, HttpError> {
+        match self.backend.get_assistant_object(id).await? {
+            Some(found) if found.object == object && found.owner_id == self.owner_id => Ok(found),
+            _ => Err(HttpError::NotFound(format!(
+                "No {object} found with id '{id}'"
+            ))),
+        }
+    }
+
+    /// Load a message or run, checking it belongs to `thread_id`
+    async fn get_in_thread(
+        &self,
+        object: &str,
+        thread_id: &str,
+        id: &str,
+    ) -> Result<AssistantObject, HttpError> {
+        let found = self.get(object, id).await?;
+        if found.thread_id.as_deref() != Some(thread_id) {
+            return Err(HttpError::NotFound(format!(
+                "No {object} found with id '{id}'"
+            )));
+        }
+        Ok(found)
+    }
+
+    async fn list(
+        &self,
+        object: &str,
+        thread_id: Option<&str>,
+    ) -> Result<Vec<AssistantObject>, HttpError> {
+        Ok(self
+            .backend
+            .list_assistant_objects(self.owner_id, object, thread_id)
+            .await?)
+    }
+
+    async fn save(&self, object: &AssistantObject) -> Result<(), HttpError> {
+        Ok(self.backend.save_assistant_object(object).await?)
+    }
+
+    async fn create(
+        &self,
+        object: &str,
+        thread_id: Option<&str>,
+        data: JsonValue,
+    ) -> Result<AssistantObject, HttpError> {
+        let created = AssistantObject {
+            id: data["id"].as_str().unwrap_or_default().to_string(),
+            object: object.to_string(),
+            owner_id: self.owner_id.to_string(),
+            thread_id: thread_id.map(String::from),
+            data,
+            state: json!({}),
+            created_at: chrono::Utc::now(),
+        };
+        self.save(&created).await?;
+        Ok(created)
+    }
+
+    async fn delete(&self, id: &str) -> Result<(), HttpError> {
+        Ok(self.backend.delete_assistant_object(id).await?)
+    }
+}
+
+fn request_context(headers: &HeaderMap, correlation_id: CorrelationId) -> RequestContext {
+    RequestContext {
+        identity: extract_identity(headers),
+        correlation_id,
+        headers: headers.clone(),
+        query: None,
+        trace_id: headers
+            .get(X_TRACE_ID)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from),
+        metadata: Default::default(),
+        response: Default::default(),
+        extensions: Default::default(),
+    }
+}
+
+fn core_router<T>(app_state: &AppState<T>) -> Result<Arc<CoreRouter>, HttpError> {
+    app_state
+        .router
+        .clone()
+        .ok_or_else(|| HttpError::InternalServerError("Router not configured".to_string()))
+}
+
+/// Page of objects in the Assistants list shape
+fn list_page(objects: Vec<AssistantObject>, query: &ListQuery) -> JsonValue {
+    let mut data: Vec<JsonValue> = objects.into_iter().map(|o| o.data).collect();
+    if query.order.as_deref() != Some("asc") {
+        data.reverse();
+    }
+    let position = |id: &Option<String>| {
+        id.as_deref()
+            .and_then(|id| data.iter().position(|o| o["id"] == id))
+    };
+    let start = position(&query.after).map_or(0, |i| i + 1);
+    let end = position(&query.before).unwrap_or(data.len()).max(start);
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    let page: Vec<JsonValue> = data[start..end].iter().take(limit).cloned().collect();
+    json!({
+        "object": "list",
+        "first_id": page.first().map(|o| o["id"].clone()),
+        "last_id": page.last().map(|o| o["id"].clone()),
+        "has_more": end - start > page.len(),
+        "data": page,
+    })
+}
+
+fn deleted(id: &str, object: &str) -> JsonValue {
+    json!({"id": id, "object": format!("{object}.deleted"), "deleted": true})
+}
+
+/// Copy the allowed fields present in `request` onto `target`
+fn apply_fields(target: &mut JsonValue, request: &JsonValue, fields: &[&str]) {
+    for field in fields {
+        if let Some(value) = request.get(*field) {
+            target[*field] = value.clone();
+        }
+    }
+}
+
+/// Reject tool types Gate cannot run
+fn validate_tools(tools: Option<&JsonValue>) -> Result<(), HttpError> {
+    let Some(tools) = tools else {
+        return Ok(());
+    };
+    let tools = tools
+        .as_array()
+        .ok_or_else(|| HttpError::BadRequest("tools must be an array".to_string()))?;
+    for tool in tools {
+        if tool["type"] != "function" {
+            return Err(HttpError::BadRequest(format!(
+                "Unsupported tool type {}; only function tools are supported",
+                tool["type"]
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Message content in the Assistants shape, from a string or content parts
+fn message_content(content: &JsonValue) -> Result<JsonValue, HttpError> {
+    let text_part =
+        |text: &JsonValue| json!({"type": "text", "text": {"value": text, "annotations": []}});
+    match content {
+        JsonValue::String(_) => Ok(json!([text_part(content)])),
+        JsonValue::Array(parts) => parts
+            .iter()
+            .map(|part| match part["type"].as_str() {
+                Some("text") if part["text"].is_string() => Ok(text_part(&part["text"])),
+                Some("image_url") => {
+                    Ok(json!({"type": "image_url", "image_url": part["image_url"]}))
+                }
+                _ => Err(HttpError::BadRequest(format!(
+                    "Unsupported message content part {}",
+                    part["type"]
+                ))),
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(JsonValue::Array),
+        _ => Err(HttpError::BadRequest(
+            "content must be a string or an array of content parts".to_string(),
+        )),
+    }
+}
+
+/// Add a client-supplied message to a thread
+async fn add_message(
+    store: &Store<'_>,
+    thread_id: &str,
+    request: &JsonValue,
+) -> Result<AssistantObject, HttpError> {
+    let role = request["role"].as_str().unwrap_or("user");
+    if role != "user" && role != "assistant" {
+        return Err(HttpError::BadRequest(format!(
+            "Unsupported message role {role}"
+        )));
+    }
+    let data = json!({
+        "id": new_id("msg"),
+        "object": MESSAGE,
+        "created_at": now(),
+        "thread_id": thread_id,
+        "status": "completed",
+        "role": role,
+        "content": message_content(&request["content"])?,
+        "assistant_id": null,
+        "run_id": null,
+        "attachments": request.get("attachments").cloned().unwrap_or_else(|| json!([])),
+        "metadata": request.get("metadata").cloned().unwrap_or_else(|| json!({}))
+    });
+    store.create(MESSAGE, Some(thread_id), data).await
+}
+
+async fn new_thread(store: &Store<'_>, request: &JsonValue) -> Result<AssistantObject, HttpError> {
+    let id = new_id("thread");
+    let data = json!({
+        "id": id,
+        "object": THREAD,
+        "created_at": now(),
+        "metadata": request.get("metadata").cloned().unwrap_or_else(|| json!({})),
+        "tool_resources": {}
+    });
+    let thread = store.create(THREAD, None, data).await?;
+    for message in request["messages"].as_array().into_iter().flatten() {
+        add_message(store, &id, message).await?;
+    }
+    Ok(thread)
+}
+
+/// Create a run on a thread and execute it
+async fn start_run(
+    router: &CoreRouter,
+    store: &Store<'_>,
+    ctx: &RequestContext,
+    thread_id: &str,
+    request: &JsonValue,
+) -> Result<JsonValue, HttpError> {
+    if request["stream"].as_bool() == Some(true) {
+        return Err(HttpError::BadRequest(
+            "Streaming runs are not supported".to_string(),
+        ));
+    }
+    let assistant_id = request["assistant_id"]
+        .as_str()
+        .ok_or_else(|| HttpError::BadRequest("assistant_id is required".to_string()))?;
+    let assistant = store.get(ASSISTANT, assistant_id).await?.data;
+    validate_tools(request.get("tools"))?;
+
+    let mut instructions = request
+        .get("instructions")
+        .unwrap_or(&assistant["instructions"])
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    if let Some(additional) = request["additional_instructions"].as_str() {
+        if !instructions.is_empty() {
+            instructions.push_str("\n\n");
+        }
+        instructions.push_str(additional);
+    }
+    for message in request["additional_messages"]
+        .as_array()
+        .into_iter()
+        .flatten()
+    {
+        add_message(store, thread_id, message).await?;
+    }
+
+    let pick = |key: &str| {
+        request
+            .get(key)
+            .filter(|v| !v.is_null())
+            .unwrap_or(&assistant[key])
+            .clone()
+    };
+    let data = json!({
+        "id": new_id("run"),
+        "object": RUN,
+        "created_at": now(),
+        "assistant_id": assistant_id,
+        "thread_id": thread_id,
+        "status": "queued",
+        "required_action": null,
+        "last_error": null,
+        "expires_at": null,
+        "started_at": null,
+        "cancelled_at": null,
+        "failed_at": null,
+        "completed_at": null,
+        "incomplete_details": null,
+        "model": pick("model"),
+        "instructions": instructions,
+        "tools": pick("tools"),
+        "metadata": request.get("metadata").cloned().unwrap_or_else(|| json!({})),
+        "usage": null,
+        "temperature": pick("temperature"),
+        "top_p": pick("top_p")
+    });
+    let mut created = store.create(RUN, Some(thread_id), data).await?;
+    run::execute(router, store, ctx, &mut created).await?;
+    Ok(created.data)
+}
+
+/// Create an assistant
+#[instrument(name = "create_assistant", skip_all)]
+pub async fn create_assistant<T>(
+    State(app_state): State<AppState<T>>,
+    headers: HeaderMap,
+    Json(request): Json<JsonValue>,
+) -> Result<Json<JsonValue>, HttpError>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let identity = extract_identity(&headers);
+    let store = Store {
+        backend: app_state.state_backend.as_ref(),
+        owner_id: &identity.id,
+    };
+    if !request["model"].is_string() {
+        return Err(HttpError::BadRequest("model is required".to_string()));
+    }
+    validate_tools(request.get("tools"))?;
+
+    let mut data = json!({
+        "id": new_id("asst"),
+        "object": ASSISTANT,
+        "created_at": now(),
+        "name": null,
+        "description": null,
+        "instructions": null,
+        "tools": [],
+        "metadata": {},
+        "temperature": null,
+        "top_p": null,
+        "response_format": "auto"
+    });
+    apply_fields(&mut data, &request, ASSISTANT_FIELDS);
+    Ok(Json(store.create(ASSISTANT, None, data).await?.data))
+}
+
+/// List the caller's assistants
+#[instrument(name = "list_assistants", skip_all)]
+pub async fn list_assistants<T>(
+    State(app_state): State<AppState<T>>,
+    headers: HeaderMap,
+    Query(query): Query<ListQuery>,
+) -> Result<Json<JsonValue>, HttpError>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let identity = extract_identity(&headers);
+    let store = Store {
+        backend: app_state.state_backend.as_ref(),
+        owner_id: &identity.id,
+    };
+    Ok(Json(list_page(store.list(ASSISTANT, None).await?, &query)))
+}
+
+#[instrument(name = "get_assistant", skip(app_state, headers))]
+pub async fn get_assistant<T>(
+    State(app_state): State<AppState<T>>,
+    headers: HeaderMap,
+    Path(assistant_id): Path<String>,
+) -> Result<Json<JsonValue>, HttpError>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let identity = extract_identity(&headers);
+    let store = Store {
+        backend: app_state.state_backend.as_ref(),
+        owner_id: &identity.id,
+    };
+    Ok(Json(store.get(ASSISTANT, &assistant_id).await?.data))
+}
+
+#[instrument(name = "modify_assistant", skip(app_state, headers, request))]
+pub async fn modify_assistant<T>(
+    State(app_state): State<AppState<T>>,
+    headers: HeaderMap,
+    Path(assistant_id): Path<String>,
+    Json(request): Json<JsonValue>,
+) -> Result<Json<JsonValue>, HttpError>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let identity = extract_identity(&headers);
+    let store = Store {
+        backend: app_state.state_backend.as_ref(),
+        owner_id: &identity.id,
+    };
+    validate_tools(request.get("tools"))?;
+    let mut assistant = store.get(ASSISTANT, &assistant_id).await?;
+    apply_fields(&mut assistant.data, &request, ASSISTANT_FIELDS);
+    store.save(&assistant).await?;
+    Ok(Json(assistant.data))
+}
+
+#[instrument(name = "delete_assistant", skip(app_state, headers))]
+pub async fn delete_assistant<T>(
+    State(app_state): State<AppState<T>>,
+    headers: HeaderMap,
+    Path(assistant_id): Path<String>,
+) -> Result<Json<JsonValue>, HttpError>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let identity = extract_identity(&headers);
+    let store = Store {
+        backend: app_state.state_backend.as_ref(),
+        owner_id: &identity.id,
+    };
+    store.get(ASSISTANT, &assistant_id).await?;
+    store.delete(&assistant_id).await?;
+    Ok(Json(deleted(&assistant_id, ASSISTANT)))
+}
+
+/// Create a thread, optionally with initial messages
+#[instrument(name = "create_thread", skip_all)]
+pub async fn create_thread<T>(
+    State(app_state): State<AppState<T>>,
+    headers: HeaderMap,
+    request: Option<Json<JsonValue>>,
+) -> Result<Json<JsonValue>, HttpError>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let identity = extract_identity(&headers);
+    let store = Store {
+        backend: app_state.state_backend.as_ref(),
+        owner_id: &identity.id,
+    };
+    let request = request.map(|Json(r)| r).unwrap_or_else(|| json!({}));
+    Ok(Json(new_thread(&store, &request).await?.data))
+}
+
+#[instrument(name = "get_thread", skip(app_state, headers))]
+pub async fn get_thread<T>(
+    State(app_state): State<AppState<T>>,
+    headers: HeaderMap,
+    Path(thread_id): Path<String>,
+) -> Result<Json<JsonValue>, HttpError>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let identity = extract_identity(&headers);
+    let store = Store {
+        backend: app_state.state_backend.as_ref(),
+        owner_id: &identity.id,
+    };
+    Ok(Json(store.get(THREAD, &thread_id).await?.data))
+}
+
+#[instrument(name = "modify_thread", skip(app_state, headers, request))]
+pub async fn modify_thread<T>(
+    State(app_state): State<AppState<T>>,
+    headers: HeaderMap,
+    Path(thread_id): Path<String>,
+    Json(request): Json<JsonValue>,
+) -> Result<Json<JsonValue>, HttpError>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let identity = extract_identity(&headers);
+    let store = Store {
+        backend: app_state.state_backend.as_ref(),
+        owner_id: &identity.id,
+    };
+    let mut thread = store.get(THREAD, &thread_id).await?;
+    apply_fields(&mut thread.data, &request, &["metadata"]);
+    store.save(&thread).await?;
+    Ok(Json(thread.data))
+}
+
+/// Delete a thread with its messages and runs
+#[instrument(name = "delete_thread", skip(app_state, headers))]
+pub async fn delete_thread<T>(
+    State(app_state): State<AppState<T>>,
+    headers: HeaderMap,
+    Path(thread_id): Path<String>,
+) -> Result<Json<JsonValue>, HttpError>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let identity = extract_identity(&headers);
+    let store = Store {
+        backend: app_state.state_backend.as_ref(),
+        owner_id: &identity.id,
+    };
+    store.get(THREAD, &thread_id).await?;
+    for object in [MESSAGE, RUN] {
+        for child in store.list(object, Some(&thread_id)).await? {
+            store.delete(&child.id).await?;
+        }
+    }
+    store.delete(&thread_id).await?;
+    Ok(Json(deleted(&thread_id, THREAD)))
+}
+
+#[instrument(name = "create_message", skip(app_state, headers, request))]
+pub async fn create_message<T>(
+    State(app_state): State<AppState<T>>,
+    headers: HeaderMap,
+    Path(thread_id): Path<String>,
+    Json(request): Json<JsonValue>,
+) -> Result<Json<JsonValue>, HttpError>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let identity = extract_identity(&headers);
+    let store = Store {
+        backend: app_state.state_backend.as_ref(),
+        owner_id: &identity.id,
+    };
+    store.get(THREAD, &thread_id).await?;
+    Ok(Json(add_message(&store, &thread_id, &request).await?.data))
+}
+
+#[instrument(name = "list_messages", skip(app_state, headers, query))]
+pub async fn list_messages<T>(
+    State(app_state): State<AppState<T>>,
+    headers: HeaderMap,
+    Path(thread_id): Path<String>,
+    Query(query): Query<ListQuery>,
+) -> Result<Json<JsonValue>, HttpError>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let identity = extract_identity(&headers);
+    let store = Store {
+        backend: app_state.state_backend.as_ref(),
+        owner_id: &identity.id,
+    };
+    store.get(THREAD, &thread_id).await?;
+    let messages = store.list(MESSAGE, Some(&thread_id)).await?;
+    Ok(Json(list_page(messages, &query)))
+}
+
+#[instrument(name = "get_message", skip(app_state, headers))]
+pub async fn get_message<T>(
+    State(app_state): State<AppState<T>>,
+    headers: HeaderMap,
+    Path((thread_id, message_id)): Path<(String, String)>,
+) -> Result<Json<JsonValue>, HttpError>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let identity = extract_identity(&headers);
+    let store = Store {
+        backend: app_state.state_backend.as_ref(),
+        owner_id: &identity.id,
+    };
+    let message = store
+        .get_in_thread(MESSAGE, &thread_id, &message_id)
+        .await?;
+    Ok(Json(message.data))
+}
+
+#[instrument(name = "modify_message", skip(app_state, headers, request))]
+pub async fn modify_message<T>(
+    State(app_state): State<AppState<T>>,
+    headers: HeaderMap,
+    Path((thread_id, message_id)): Path<(String, String)>,
+    Json(request): Json<JsonValue>,
+) -> Result<Json<JsonValue>, HttpError>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let identity = extract_identity(&headers);
+    let store = Store {
+        backend: app_state.state_backend.as_ref(),
+        owner_id: &identity.id,
+    };
+    let mut message = store
+        .get_in_thread(MESSAGE, &thread_id, &message_id)
+        .await?;
+    apply_fields(&mut message.data, &request, &["metadata"]);
+    store.save(&message).await?;
+    Ok(Json(message.data))
+}
+
+/// Create a run on a thread
+#[instrument(name = "create_run", skip(app_state, headers, correlation_id, request))]
+pub async fn create_run<T>(
+    State(app_state): State<AppState<T>>,
+    headers: HeaderMap,
+    axum::Extension(correlation_id): axum::Extension<CorrelationId>,
+    Path(thread_id): Path<String>,
+    Json(request): Json<JsonValue>,
+) -> Result<Json<JsonValue>, HttpError>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let router = core_router(&app_state)?;
+    let ctx = request_context(&headers, correlation_id);
+    let store = Store {
+        backend: app_state.state_backend.as_ref(),
+        owner_id: &ctx.identity.id,
+    };
+    store.get(THREAD, &thread_id).await?;
+    Ok(Json(
+        start_run(&router, &store, &ctx, &thread_id, &request).await?,
+    ))
+}
+
+/// Create a thread and run it in one request
+#[instrument(name = "create_thread_and_run", skip_all)]
+pub async fn create_thread_and_run<T>(
+    State(app_state): State<AppState<T>>,
+    headers: HeaderMap,
+    axum::Extension(correlation_id): axum::Extension<CorrelationId>,
+    Json(request): Json<JsonValue>,
+) -> Result<Json<JsonValue>, HttpError>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let router = core_router(&app_state)?;
+    let ctx = request_context(&headers, correlation_id);
+    let store = Store {
+        backend: app_state.state_backend.as_ref(),
+        owner_id: &ctx.identity.id,
+    };
+    let thread_request = request.get("thread").cloned().unwrap_or_else(|| json!({}));
+    let thread = new_thread(&store, &thread_request).await?;
+    Ok(Json(
+        start_run(&router, &store, &ctx, &thread.id, &request).await?,
+    ))
+}
+
+#[instrument(name = "list_runs", skip(app_state, headers, query))]
+pub async fn list_runs<T>(
+    State(app_state): State<AppState<T>>,
+    headers: HeaderMap,
+    Path(thread_id): Path<String>,
+    Query(query): Query<ListQuery>,
+) -> Result<Json<JsonValue>, HttpError>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let identity = extract_identity(&headers);
+    let store = Store {
+        backend: app_state.state_backend.as_ref(),
+        owner_id: &identity.id,
+    };
+    store.get(THREAD, &thread_id).await?;
+    let runs = store.list(RUN, Some(&thread_id)).await?;
+    Ok(Json(list_page(runs, &query)))
+}
+
+#[instrument(name = "get_run", skip(app_state, headers))]
+pub async fn get_run<T>(
+    State(app_state): State<AppState<T>>,
+    headers: HeaderMap,
+    Path((thread_id, run_id)): Path<(String, String)>,
+) -> Result<Json<JsonValue>, HttpError>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let identity = extract_identity(&headers);
+    let store = Store {
+        backend: app_state.state_backend.as_ref(),
+        owner_id: &identity.id,
+    };
+    Ok(Json(
+        store.get_in_thread(RUN, &thread_id, &run_id).await?.data,
+    ))
+}
+
+#[instrument(name = "modify_run", skip(app_state, headers, request))]
+pub async fn modify_run<T>(
+    State(app_state): State<AppState<T>>,
+    headers: HeaderMap,
+    Path((thread_id, run_id)): Path<(String, String)>,
+    Json(request): Json<JsonValue>,
+) -> Result<Json<JsonValue>, HttpError>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let identity = extract_identity(&headers);
+    let store = Store {
+        backend: app_state.state_backend.as_ref(),
+        owner_id: &identity.id,
+    };
+    let mut run = store.get_in_thread(RUN, &thread_id, &run_id).await?;
+    apply_fields(&mut run.data, &request, &["metadata"]);
+    store.save(&run).await?;
+    Ok(Json(run.data))
+}
+
+/// Continue a run waiting on function tool results
+#[instrument(
+    name = "submit_tool_outputs",
+    skip(app_state, headers, correlation_id, request)
+)]
+pub async fn submit_tool_outputs<T>(
+    State(app_state): State<AppState<T>>,
+    headers: HeaderMap,
+    axum::Extension(correlation_id): axum::Extension<CorrelationId>,
+    Path((thread_id, run_id)): Path<(String, String)>,
+    Json(request): Json<JsonValue>,
+) -> Result<Json<JsonValue>, HttpError>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    if request["stream"].as_bool() == Some(true) {
+        return Err(HttpError::BadRequest(
+            "Streaming runs are not supported".to_string(),
+        ));
+    }
+    let router = core_router(&app_state)?;
+    let ctx = request_context(&headers, correlation_id);
+    let store = Store {
+        backend: app_state.state_backend.as_ref(),
+        owner_id: &ctx.identity.id,
+    };
+    let mut run = store.get_in_thread(RUN, &thread_id, &run_id).await?;
+    let outputs = request["tool_outputs"]
+        .as_array()
+        .ok_or_else(|| HttpError::BadRequest("tool_outputs is required".to_string()))?;
+    run::accept_tool_outputs(&mut run, outputs)?;
+    run::execute(&router, &store, &ctx, &mut run).await?;
+    Ok(Json(run.data))
+}
+
+#[instrument(name = "cancel_run", skip(app_state, headers))]
+pub async fn cancel_run<T>(
+    State(app_state): State<AppState<T>>,
+    headers: HeaderMap,
+    Path((thread_id, run_id)): Path<(String, String)>,
+) -> Result<Json<JsonValue>, HttpError>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let identity = extract_identity(&headers);
+    let store = Store {
+        backend: app_state.state_backend.as_ref(),
+        owner_id: &identity.id,
+    };
+    let mut run = store.get_in_thread(RUN, &thread_id, &run_id).await?;
+    let status = run.data["status"].as_str().unwrap_or_default();
+    if !run::is_active(status) {
+        return Err(HttpError::BadRequest(format!(
+            "Cannot cancel run with status '{status}'"
+        )));
+    }
+    run.data["status"] = json!("cancelled");
+    run.data["cancelled_at"] = json!(now());
+    run.data["required_action"] = JsonValue::Null;
+    store.save(&run).await?;
+    Ok(Json(run.data))
+}
+
+/// Create the Assistants API router
+pub fn router<T>() -> Router<AppState<T>>
+where
+    T: Send + Sync + Clone + 'static,
+{
+    Router::new()
+        .route(
+            "/v1/assistants",
+            post(create_assistant).get(list_assistants),
+        )
+        .route(
+            "/v1/assistants/{assistant_id}",
+            get(get_assistant)
+                .post(modify_assistant)
+                .delete(delete_assistant),
+        )
+        .route("/v1/threads", post(create_thread))
+        .route("/v1/threads/runs", post(create_thread_and_run))
+        .route(
+            "/v1/threads/{thread_id}",
+            get(get_thread).post(modify_thread).delete(delete_thread),
+        )
+        .route(
+            "/v1/threads/{thread_id}/messages",
+            post(create_message).get(list_messages),
+        )
+        .route(
+            "/v1/threads/{thread_id}/messages/{message_id}",
+            get(get_message).post(modify_message),
+        )
+        .route(
+            "/v1/threads/{thread_id}/runs",
+            post(create_run).get(list_runs),
+        )
+        .route(
+            "/v1/threads/{thread_id}/runs/{run_id}",
+            get(get_run).post(modify_run),
+        )
+        .route(
+            "/v1/threads/{thread_id}/runs/{run_id}/submit_tool_outputs",
+            post(submit_tool_outputs),
+        )
+        .route(
+            "/v1/threads/{thread_id}/runs/{run_id}/cancel",
+            post(cancel_run),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gate_core::tests::state::InMemoryBackend;
+
+    fn object(id: &str) -> AssistantObject {
+        AssistantObject {
+            id: id.to_string(),
+            object: ASSISTANT.to_string(),
+            owner_id: "user".to_string(),
+            thread_id: None,
+            data: json!({"id": id}),
+            state: json!({}),
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_list_page_orders_and_paginates() {
+        let objects: Vec<_> = ["a", "b", "c", "d"].into_iter().map(object).collect();
+
+        let page = list_page(objects.clone(), &ListQuery::default());
+        assert_eq!(page["first_id"], "d");
+        assert_eq!(page["has_more"], false);
+
+        let query = ListQuery {
+            limit: Some(2),
+            order: Some("asc".to_string()),
+            after: Some("a".to_string()),
+            before: None,
+        };
+        let page = list_page(objects, &query);
+        let ids: Vec<_> = page["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|o| o["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(ids, vec!["b", "c"]);
+        assert_eq!(page["has_more"], true);
+    }
+
+    #[test]
+    fn test_message_content_normalizes_strings_and_parts() {
+        let content = message_content(&json!("Hello")).unwrap();
+        assert_eq!(content[0]["text"]["value"], "Hello");
+
+        let content = message_content(&json!([{"type": "text", "text": "Hi"}])).unwrap();
+        assert_eq!(content[0]["type"], "text");
+        assert!(message_content(&json!([{"type": "image_file"}])).is_err());
+    }
+
+    #[test]
+    fn test_only_function_tools_are_accepted() {
+        assert!(
+            validate_tools(Some(
+                &json!([{"type": "function", "function": {"name": "f"}}])
+            ))
+            .is_ok()
+        );
+        assert!(validate_tools(Some(&json!([{"type": "code_interpreter"}]))).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_objects_are_scoped_to_their_owner() {
+        let backend = InMemoryBackend::default();
+        let owner = Store {
+            backend: &backend,
+            owner_id: "owner",
+        };
+        let thread = new_thread(
+            &owner,
+            &json!({"messages": [{"role": "user", "content": "Hi"}]}),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            owner.list(MESSAGE, Some(&thread.id)).await.unwrap().len(),
+            1
+        );
+
+        let other = Store {
+            backend: &backend,
+            owner_id: "other",
+        };
+        assert!(matches!(
+            other.get(THREAD, &thread.id).await,
+            Err(HttpError::NotFound(_))
+        ));
+    }
+}
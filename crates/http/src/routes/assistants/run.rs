@@ -0,0 +1,347 @@
+//! Run execution
+//!
+//! A run replays its thread through chat completions. When the model calls
+//! function tools the run stops in `requires_action`; the outputs the client
+//! submits are appended to the run's transcript and the model is called
+//! again, until it answers with a message.
+
+use super::{MESSAGE, Store, message_content, new_id, now};
+use crate::{error::HttpError, routes::responses::final_response};
+use gate_core::AssistantObject;
+use gate_core::router::{
+    routing::Router as CoreRouter, service::route_and_execute_json_with_protocol,
+    sink::RequestContext, types::Protocol,
+};
+use gate_core::tracing::prelude::*;
+use serde_json::{Value as JsonValue, json};
+
+const TRANSCRIPT: &str = "transcript";
+
+/// Statuses a run can still move on from
+pub(super) fn is_active(status: &str) -> bool {
+    matches!(status, "queued" | "in_progress" | "requires_action")
+}
+
+/// Call the model for `run` and record the outcome on it: a reply message,
+/// a tool call request or a failure
+pub(super) async fn execute(
+    router: &CoreRouter,
+    store: &Store<'_>,
+    ctx: &RequestContext,
+    run: &mut AssistantObject,
+) -> Result<(), HttpError> {
+    run.data["status"] = json!("in_progress");
+    if run.data["started_at"].is_null() {
+        run.data["started_at"] = json!(now());
+    }
+
+    let thread_id = run.thread_id.clone().unwrap_or_default();
+    let messages = store.list(MESSAGE, Some(&thread_id)).await?;
+    let request = chat_request(&run.data, &messages, transcript(&run.state));
+
+    let result = match route_and_execute_json_with_protocol(
+        router,
+        ctx,
+        Protocol::OpenAIChat,
+        request,
+    )
+    .await
+    {
+        Ok(stream) => final_response(stream).await.map(|(_, body)| body),
+        Err(e) => Err(e.into()),
+    };
+    let response = match result {
+        Ok(response) => response,
+        Err(e) => {
+            warn!("Run {} failed: {e}", run.id);
+            run.data["status"] = json!("failed");
+            run.data["failed_at"] = json!(now());
+            run.data["last_error"] = json!({"code": "server_error", "message": e.to_string()});
+            return store.save(run).await;
+        }
+    };
+
+    add_usage(&mut run.data, response.get("usage"));
+    let message = response
+        .pointer("/choices/0/message")
+        .cloned()
+        .unwrap_or_default();
+    let tool_calls: Vec<JsonValue> = message
+        .get("tool_calls")
+        .and_then(|t| t.as_array())
+        .cloned()
+        .unwrap_or_default();
+
+    if tool_calls.is_empty() {
+        let text = message
+            .get("content")
+            .and_then(|c| c.as_str())
+            .unwrap_or_default();
+        let reply = json!({
+            "id": new_id("msg"),
+            "object": MESSAGE,
+            "created_at": now(),
+            "thread_id": thread_id,
+            "status": "completed",
+            "role": "assistant",
+            "content": message_content(&json!(text))?,
+            "assistant_id": run.data["assistant_id"],
+            "run_id": run.id,
+            "attachments": [],
+            "metadata": {}
+        });
+        store.create(MESSAGE, Some(&thread_id), reply).await?;
+        run.data["status"] = json!("completed");
+        run.data["completed_at"] = json!(now());
+        run.data["required_action"] = JsonValue::Null;
+    } else {
+        let requested: Vec<JsonValue> = tool_calls
+            .iter()
+            .map(|call| {
+                json!({
+                    "id": call["id"],
+                    "type": "function",
+                    "function": {
+                        "name": call["function"]["name"],
+                        "arguments": call["function"]["arguments"]
+                    }
+                })
+            })
+            .collect();
+        push_transcript(
+            &mut run.state,
+            json!({"role": "assistant", "content": message.get("content"), "tool_calls": tool_calls}),
+        );
+        run.data["status"] = json!("requires_action");
+        run.data["required_action"] = json!({
+            "type": "submit_tool_outputs",
+            "submit_tool_outputs": {"tool_calls": requested}
+        });
+    }
+    store.save(run).await
+}
+
+/// Record the client's tool outputs on a run waiting for them
+pub(super) fn accept_tool_outputs(
+    run: &mut AssistantObject,
+    outputs: &[JsonValue],
+) -> Result<(), HttpError> {
+    if run.data["status"] != "requires_action" {
+        return Err(HttpError::BadRequest(format!(
+            "Run {} is not waiting for tool outputs",
+            run.id
+        )));
+    }
+    let pending: Vec<String> = run
+        .data
+        .pointer("/required_action/submit_tool_outputs/tool_calls")
+        .and_then(|c| c.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|call| call["id"].as_str().map(String::from))
+        .collect();
+
+    let mut messages = Vec::with_capacity(pending.len());
+    for id in &pending {
+        let output = outputs
+            .iter()
+            .find(|o| o["tool_call_id"].as_str() == Some(id.as_str()))
+            .ok_or_else(|| HttpError::BadRequest(format!("Missing output for tool call {id}")))?;
+        let content = match &output["output"] {
+            JsonValue::String(text) => text.clone(),
+            JsonValue::Null => String::new(),
+            other => other.to_string(),
+        };
+        messages.push(json!({"role": "tool", "tool_call_id": id, "content": content}));
+    }
+    for message in messages {
+        push_transcript(&mut run.state, message);
+    }
+    run.data["required_action"] = JsonValue::Null;
+    run.data["status"] = json!("queued");
+    Ok(())
+}
+
+/// Chat completions request replaying the thread, followed by this run's
+/// tool call round trips
+fn chat_request(
+    run: &JsonValue,
+    messages: &[AssistantObject],
+    transcript: &[JsonValue],
+) -> JsonValue {
+    let mut chat = Vec::new();
+    if let Some(instructions) = run["instructions"].as_str()
+        && !instructions.is_empty()
+    {
+        chat.push(json!({"role": "system", "content": instructions}));
+    }
+    chat.extend(messages.iter().map(|m| chat_message(&m.data)));
+    chat.extend(transcript.iter().cloned());
+
+    let mut request = json!({
+        "model": run["model"],
+        "messages": chat,
+        "stream": false
+    });
+    let tools: Vec<JsonValue> = run["tools"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|t| t["type"] == "function")
+        .cloned()
+        .collect();
+    if !tools.is_empty() {
+        request["tools"] = json!(tools);
+    }
+    for key in ["temperature", "top_p"] {
+        if !run[key].is_null() {
+            request[key] = run[key].clone();
+        }
+    }
+    request
+}
+
+/// A thread message as a chat message
+fn chat_message(message: &JsonValue) -> JsonValue {
+    let parts: Vec<JsonValue> = message["content"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|part| match part["type"].as_str()? {
+            "text" => Some(json!({"type": "text", "text": part["text"]["value"]})),
+            "image_url" => Some(json!({"type": "image_url", "image_url": part["image_url"]})),
+            _ => None,
+        })
+        .collect();
+
+    // Plain strings for text-only messages, which every backend accepts
+    let content = if parts.iter().all(|p| p["type"] == "text") {
+        let texts: Vec<&str> = parts.iter().filter_map(|p| p["text"].as_str()).collect();
+        json!(texts.join("\n"))
+    } else {
+        json!(parts)
+    };
+    json!({"role": message["role"], "content": content})
+}
+
+fn transcript(state: &JsonValue) -> &[JsonValue] {
+    state[TRANSCRIPT]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or(&[])
+}
+
+fn push_transcript(state: &mut JsonValue, message: JsonValue) {
+    if !state.is_object() {
+        *state = json!({});
+    }
+    match state[TRANSCRIPT].as_array_mut() {
+        Some(messages) => messages.push(message),
+        None => state[TRANSCRIPT] = json!([message]),
+    }
+}
+
+/// Add a chat completion's token usage to the run's running total
+fn add_usage(run: &mut JsonValue, usage: Option<&JsonValue>) {
+    let Some(usage) = usage else {
+        return;
+    };
+    let total =
+        |key: &str| run["usage"][key].as_u64().unwrap_or(0) + usage[key].as_u64().unwrap_or(0);
+    let prompt_tokens = total("prompt_tokens");
+    let completion_tokens = total("completion_tokens");
+    run["usage"] = json!({
+        "prompt_tokens": prompt_tokens,
+        "completion_tokens": completion_tokens,
+        "total_tokens": prompt_tokens + completion_tokens
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn run(status: &str) -> AssistantObject {
+        AssistantObject {
+            id: "run_1".to_string(),
+            object: "thread.run".to_string(),
+            owner_id: "user".to_string(),
+            thread_id: Some("thread_1".to_string()),
+            data: json!({
+                "id": "run_1",
+                "status": status,
+                "model": "gpt-4o",
+                "instructions": "Be brief",
+                "tools": [{"type": "function", "function": {"name": "lookup"}}],
+                "required_action": {
+                    "type": "submit_tool_outputs",
+                    "submit_tool_outputs": {"tool_calls": [
+                        {"id": "call_1", "type": "function", "function": {"name": "lookup", "arguments": "{}"}}
+                    ]}
+                }
+            }),
+            state: json!({"transcript": [
+                {"role": "assistant", "content": null, "tool_calls": [{"id": "call_1"}]}
+            ]}),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_tool_outputs_continue_the_transcript() {
+        let mut run = run("requires_action");
+        let missing = accept_tool_outputs(&mut run, &[]);
+        assert!(matches!(missing, Err(HttpError::BadRequest(_))));
+
+        accept_tool_outputs(
+            &mut run,
+            &[json!({"tool_call_id": "call_1", "output": "42"})],
+        )
+        .unwrap();
+        assert_eq!(run.data["status"], "queued");
+        assert!(run.data["required_action"].is_null());
+
+        let message = AssistantObject {
+            data: json!({"role": "user", "content": [
+                {"type": "text", "text": {"value": "What is it?", "annotations": []}}
+            ]}),
+            ..run.clone()
+        };
+        let request = chat_request(&run.data, &[message], transcript(&run.state));
+        let roles: Vec<_> = request["messages"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m["role"].as_str().unwrap())
+            .collect();
+        assert_eq!(roles, vec!["system", "user", "assistant", "tool"]);
+        assert_eq!(request["messages"][1]["content"], "What is it?");
+        assert_eq!(request["messages"][3]["content"], "42");
+        assert_eq!(request["tools"][0]["function"]["name"], "lookup");
+    }
+
+    #[test]
+    fn test_tool_outputs_rejected_unless_requested() {
+        let mut run = run("completed");
+        let result = accept_tool_outputs(
+            &mut run,
+            &[json!({"tool_call_id": "call_1", "output": "42"})],
+        );
+        assert!(matches!(result, Err(HttpError::BadRequest(_))));
+    }
+
+    #[test]
+    fn test_usage_accumulates_across_calls() {
+        let mut data = json!({});
+        add_usage(
+            &mut data,
+            Some(&json!({"prompt_tokens": 10, "completion_tokens": 2})),
+        );
+        add_usage(
+            &mut data,
+            Some(&json!({"prompt_tokens": 15, "completion_tokens": 3})),
+        );
+        assert_eq!(data["usage"]["total_tokens"], 30);
+    }
+}
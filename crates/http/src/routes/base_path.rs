@@ -0,0 +1,56 @@
+//! Serving under a public base path
+//!
+//! Behind a reverse proxy that forwards `https://example.com/gate/` to the
+//! gateway unchanged, every request path starts with `/gate`. The whole
+//! application is nested under the base path, so routes, middleware and
+//! path-based policies keep matching the paths they were written for.
+
+use axum::Router;
+
+/// Why a base path cannot be used, if it cannot. Empty serves at the root.
+pub fn base_path_error(base_path: &str) -> Option<&'static str> {
+    if base_path.is_empty() {
+        None
+    } else if !base_path.starts_with('/') || base_path.ends_with('/') {
+        Some("Base path must start with '/' and not end with one")
+    } else if base_path.contains(['?', '#', '{', '}']) {
+        Some("Base path must be a plain path")
+    } else {
+        None
+    }
+}
+
+/// Serve `router` under `base_path` instead of at the root
+pub fn with_base_path(router: Router, base_path: &str) -> Router {
+    if base_path.is_empty() {
+        return router;
+    }
+    Router::new().nest_service(base_path, router)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::StatusCode, routing::get};
+    use tower::ServiceExt;
+
+    async fn status(app: &Router, path: &str) -> StatusCode {
+        app.clone()
+            .oneshot(axum::http::Request::get(path).body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn test_routes_move_under_the_base_path() {
+        let router = Router::new().route("/api/status", get(|| async { "ok" }));
+        let app = with_base_path(router, "/gate");
+
+        assert_eq!(status(&app, "/gate/api/status").await, StatusCode::OK);
+        assert_eq!(status(&app, "/api/status").await, StatusCode::NOT_FOUND);
+
+        assert!(base_path_error("/gate").is_none());
+        assert!(base_path_error("gate/").is_some());
+    }
+}
@@ -0,0 +1,238 @@
+//! Legacy OpenAI completions API
+//!
+//! Requests go to sinks that accept completions natively. When none serve the
+//! model, the prompt is sent to a chat sink as a single user message and the
+//! replies converted back, streamed or not. On that path `echo` is emulated
+//! by prepending the prompt, and `suffix` is rejected since chat models cannot
+//! fill in the middle.
+
+use crate::{
+    auth::extract_identity,
+    error::HttpError,
+    sinks::response_converter::{response_stream_to_axum, response_stream_to_json},
+    state::AppState,
+    types::OpenAICompletionRequest,
+};
+use axum::{
+    Router,
+    extract::{Json, State},
+    http::{HeaderMap, HeaderName},
+    response::Response,
+    routing::post,
+};
+use futures::StreamExt;
+use gate_core::router::{
+    ResponseChunk, ResponseStream,
+    extensions::ClientIp,
+    protocols::{convert_request, convert_response},
+    routing::Router as CoreRouter,
+    service::{
+        descriptor_from_json_with_protocol, one_shot_stream, route_and_execute_json_with_protocol,
+    },
+    sink::RequestContext,
+    types::Protocol,
+};
+use gate_core::tracing::prelude::*;
+use serde_json::{Value as JsonValue, json};
+use std::collections::HashSet;
+
+const X_TRACE_ID: HeaderName = HeaderName::from_static("x-trace-id");
+
+/// Handle OpenAI completions (legacy) requests
+#[instrument(
+    name = "openai_completions",
+    skip(app_state, headers, client_ip, response_metadata),
+    fields(
+        model = %request.model,
+        stream = %request.stream
+    )
+)]
+pub async fn completions_handler<T>(
+    State(app_state): State<AppState<T>>,
+    uri: axum::http::Uri,
+    headers: HeaderMap,
+    axum::Extension(correlation_id): axum::Extension<CorrelationId>,
+    client_ip: Option<axum::Extension<ClientIp>>,
+    response_metadata: Option<axum::Extension<ResponseMetadata>>,
+    Json(request): Json<OpenAICompletionRequest>,
+) -> Result<Response, HttpError>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let router = app_state
+        .router
+        .ok_or_else(|| HttpError::InternalServerError("Router not configured".to_string()))?;
+
+    let mut ctx = RequestContext {
+        identity: extract_identity(&headers),
+        correlation_id,
+        headers: headers.clone(),
+        query: uri.query().map(|s| s.to_string()),
+        trace_id: headers
+            .get(X_TRACE_ID)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from),
+        metadata: Default::default(),
+        response: response_metadata.map(|m| m.0).unwrap_or_default(),
+        extensions: Default::default(),
+    };
+    if let Some(axum::Extension(client_ip)) = client_ip {
+        ctx.extensions.insert(client_ip);
+    }
+
+    let mut request_json = serde_json::to_value(&request)
+        .map_err(|e| HttpError::InternalServerError(format!("Failed to serialize request: {e}")))?;
+
+    router
+        .rewrite(&mut ctx, Protocol::OpenAICompletions, &mut request_json)
+        .await?;
+    let desc = descriptor_from_json_with_protocol(&request_json, Protocol::OpenAICompletions)?;
+    let stream = match router.route(&ctx, &desc).await {
+        Ok(plan) => {
+            router
+                .execute(
+                    plan,
+                    one_shot_stream(Protocol::OpenAICompletions, request_json),
+                )
+                .await?
+        }
+        Err(gate_core::Error::NoSinksAvailable) => {
+            complete_via_chat(router.as_ref(), &ctx, request_json).await?
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    if request.stream {
+        response_stream_to_axum(stream).await
+    } else {
+        response_stream_to_json(stream).await
+    }
+}
+
+/// Prompt text as sent to the chat model
+fn prompt_text(request: &JsonValue) -> String {
+    match request.get("prompt") {
+        Some(JsonValue::String(prompt)) => prompt.clone(),
+        Some(JsonValue::Array(parts)) => parts
+            .iter()
+            .filter_map(|p| p.as_str())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+/// Prepend `prefix` to the text of every choice not yet in `echoed`, by
+/// index, and add them to it
+fn prepend_text(completion: &mut JsonValue, prefix: &str, echoed: &mut HashSet<u64>) {
+    for (position, choice) in completion
+        .get_mut("choices")
+        .and_then(|c| c.as_array_mut())
+        .into_iter()
+        .flatten()
+        .enumerate()
+    {
+        let index = choice
+            .get("index")
+            .and_then(|i| i.as_u64())
+            .unwrap_or(position as u64);
+        if !echoed.insert(index) {
+            continue;
+        }
+        let text = choice
+            .get("text")
+            .and_then(|t| t.as_str())
+            .unwrap_or_default();
+        choice["text"] = json!(format!("{prefix}{text}"));
+    }
+}
+
+/// Serve a completions request from a chat sink, converting each reply chunk
+async fn complete_via_chat(
+    router: &CoreRouter,
+    ctx: &RequestContext,
+    request_json: JsonValue,
+) -> Result<ResponseStream, HttpError> {
+    if request_json.get("suffix").is_some_and(|s| !s.is_null()) {
+        return Err(HttpError::BadRequest(
+            "suffix is not supported for models served through chat completions".to_string(),
+        ));
+    }
+    let echo = request_json
+        .get("echo")
+        .and_then(|e| e.as_bool())
+        .unwrap_or(false);
+    let echo_prefix = echo.then(|| prompt_text(&request_json));
+    let mut echoed = HashSet::new();
+
+    let (chat_request, warnings) = ctx.response.time_conversion(|| {
+        convert_request(
+            Protocol::OpenAICompletions,
+            Protocol::OpenAIChat,
+            &request_json,
+        )
+    })?;
+    for warning in warnings {
+        debug!("Completions to chat conversion: {warning}");
+    }
+
+    let stream =
+        route_and_execute_json_with_protocol(router, ctx, Protocol::OpenAIChat, chat_request)
+            .await?;
+    let metadata = ctx.response.clone();
+    let converted = stream.map(move |item| match item.map(ResponseChunk::parsed) {
+        Ok(ResponseChunk::Content(json)) => {
+            let (mut completion, _) = metadata.time_conversion(|| {
+                convert_response(Protocol::OpenAIChat, Protocol::OpenAICompletions, &json)
+            })?;
+            // Echo the prompt once per choice, ahead of its first text
+            if let Some(prefix) = &echo_prefix {
+                prepend_text(&mut completion, prefix, &mut echoed);
+            }
+            Ok(ResponseChunk::Content(completion))
+        }
+        other => other,
+    });
+    Ok(Box::pin(converted))
+}
+
+/// Create the completions router
+pub fn router<T>() -> Router<AppState<T>>
+where
+    T: Send + Sync + Clone + 'static,
+{
+    Router::new().route("/v1/completions", post(completions_handler))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompt_text_joins_prompt_list() {
+        assert_eq!(prompt_text(&json!({"prompt": "Once"})), "Once");
+        assert_eq!(prompt_text(&json!({"prompt": ["a", "b"]})), "a\nb");
+        assert_eq!(prompt_text(&json!({})), "");
+    }
+
+    #[test]
+    fn test_prepend_text_echoes_into_choices() {
+        let mut echoed = HashSet::new();
+        let mut completion = json!({"choices": [{"text": " upon a time"}]});
+        prepend_text(&mut completion, "Once", &mut echoed);
+        assert_eq!(completion["choices"][0]["text"], "Once upon a time");
+
+        // With n > 1, each choice gets the prompt with its first chunk
+        let mut echoed = HashSet::new();
+        let mut first = json!({"choices": [{"index": 0, "text": " upon"}]});
+        prepend_text(&mut first, "Once", &mut echoed);
+        let mut second = json!({"choices": [
+            {"index": 1, "text": " more"},
+            {"index": 0, "text": " a time"}
+        ]});
+        prepend_text(&mut second, "Once", &mut echoed);
+        assert_eq!(first["choices"][0]["text"], "Once upon");
+        assert_eq!(second["choices"][0]["text"], "Once more");
+        assert_eq!(second["choices"][1]["text"], " a time");
+    }
+}
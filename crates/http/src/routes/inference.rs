@@ -3,8 +3,11 @@
 use crate::{
     auth::extract_identity,
     error::HttpError,
+    middleware::anthropic_compat::{AnthropicCompat, anthropic_events},
+    services::multiplex,
     sinks::response_converter::{response_stream_to_axum, response_stream_to_json},
     state::AppState,
+    tools::{TOOLS_HEADER, agent::Agent},
     types::*,
 };
 use http::header::HeaderName;
@@ -13,18 +16,27 @@ use axum::{
     Router,
     extract::{Json, State},
     http::HeaderMap,
-    response::Response,
+    response::{IntoResponse, Response},
     routing::post,
 };
 use gate_core::router::{
-    service::route_and_execute_json_with_protocol, sink::RequestContext, types::Protocol,
+    Router as CoreRouter,
+    digest::request_hash,
+    extensions::ClientIp,
+    service::{count_tokens, route_and_execute_json_with_protocol},
+    sink::{RequestContext, ResponseStream},
+    types::Protocol,
 };
 use gate_core::tracing::prelude::*;
+use serde_json::Value as JsonValue;
+
+/// Most models one comparison may run
+const MAX_COMPARE_MODELS: usize = 8;
 
 /// Handle Anthropic messages requests
 #[instrument(
     name = "anthropic_messages",
-    skip(app_state, headers),
+    skip(app_state, headers, client_ip, response_metadata),
     fields(
         model = %request.model,
         request_id = tracing::field::Empty
@@ -35,6 +47,9 @@ pub async fn messages_handler<T>(
     uri: axum::http::Uri,
     headers: HeaderMap,
     axum::Extension(correlation_id): axum::Extension<CorrelationId>,
+    client_ip: Option<axum::Extension<ClientIp>>,
+    response_metadata: Option<axum::Extension<ResponseMetadata>>,
+    compat: Option<axum::Extension<AnthropicCompat>>,
     Json(request): Json<AnthropicMessagesRequest>,
 ) -> Result<Response, HttpError>
 where
@@ -44,7 +59,7 @@ where
         .router
         .ok_or_else(|| HttpError::InternalServerError("Router not configured".to_string()))?;
 
-    let ctx = RequestContext {
+    let mut ctx = RequestContext {
         identity: extract_identity(&headers),
         correlation_id,
         headers: headers.clone(),
@@ -54,30 +69,84 @@ where
             .and_then(|v| v.to_str().ok())
             .map(String::from),
         metadata: Default::default(),
+        response: response_metadata.map(|m| m.0).unwrap_or_default(),
+        extensions: Default::default(),
     };
+    if let Some(axum::Extension(client_ip)) = client_ip {
+        ctx.extensions.insert(client_ip);
+    }
 
     let request_json = serde_json::to_value(&request)
         .map_err(|e| HttpError::InternalServerError(format!("Failed to serialize request: {e}")))?;
 
-    let stream = route_and_execute_json_with_protocol(
+    let stream = execute(
+        &app_state,
         router.as_ref(),
         &ctx,
         Protocol::Anthropic,
         request_json,
+        request.stream,
     )
     .await?;
 
     if request.stream {
+        let stream = if compat.is_some() {
+            anthropic_events(stream)
+        } else {
+            stream
+        };
         response_stream_to_axum(stream).await
     } else {
         response_stream_to_json(stream).await
     }
 }
 
+/// Count input tokens for an Anthropic messages request.
+///
+/// Forwarded to an Anthropic provider when one serves the model, otherwise
+/// answered with a local estimate so clients never see a 404.
+#[instrument(name = "anthropic_count_tokens", skip_all)]
+pub async fn count_tokens_handler<T>(
+    State(app_state): State<AppState<T>>,
+    uri: axum::http::Uri,
+    headers: HeaderMap,
+    axum::Extension(correlation_id): axum::Extension<CorrelationId>,
+    client_ip: Option<axum::Extension<ClientIp>>,
+    response_metadata: Option<axum::Extension<ResponseMetadata>>,
+    Json(request): Json<serde_json::Value>,
+) -> Result<Json<serde_json::Value>, HttpError>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let router = app_state
+        .router
+        .ok_or_else(|| HttpError::InternalServerError("Router not configured".to_string()))?;
+
+    let mut ctx = RequestContext {
+        identity: extract_identity(&headers),
+        correlation_id,
+        headers: headers.clone(),
+        query: uri.query().map(|s| s.to_string()),
+        trace_id: headers
+            .get(X_TRACE_ID)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from),
+        metadata: Default::default(),
+        response: response_metadata.map(|m| m.0).unwrap_or_default(),
+        extensions: Default::default(),
+    };
+    if let Some(axum::Extension(client_ip)) = client_ip {
+        ctx.extensions.insert(client_ip);
+    }
+
+    let input_tokens = count_tokens(router.as_ref(), &ctx, request).await;
+    Ok(Json(serde_json::json!({ "input_tokens": input_tokens })))
+}
+
 /// Handle OpenAI chat completions requests
 #[instrument(
     name = "openai_chat_completions",
-    skip(app_state, headers),
+    skip(app_state, headers, client_ip, response_metadata),
     fields(
         model = %request.model,
         stream = %request.stream
@@ -88,6 +157,8 @@ pub async fn chat_completions_handler<T>(
     uri: axum::http::Uri,
     headers: HeaderMap,
     axum::Extension(correlation_id): axum::Extension<CorrelationId>,
+    client_ip: Option<axum::Extension<ClientIp>>,
+    response_metadata: Option<axum::Extension<ResponseMetadata>>,
     Json(request): Json<OpenAIChatCompletionRequest>,
 ) -> Result<Response, HttpError>
 where
@@ -97,7 +168,7 @@ where
         .router
         .ok_or_else(|| HttpError::InternalServerError("Router not configured".to_string()))?;
 
-    let ctx = RequestContext {
+    let mut ctx = RequestContext {
         identity: extract_identity(&headers),
         correlation_id,
         headers: headers.clone(),
@@ -107,16 +178,42 @@ where
             .and_then(|v| v.to_str().ok())
             .map(String::from),
         metadata: Default::default(),
+        response: response_metadata.map(|m| m.0).unwrap_or_default(),
+        extensions: Default::default(),
     };
+    if let Some(axum::Extension(client_ip)) = client_ip {
+        ctx.extensions.insert(client_ip);
+    }
 
     let request_json = serde_json::to_value(&request)
         .map_err(|e| HttpError::InternalServerError(format!("Failed to serialize request: {e}")))?;
 
-    let stream = route_and_execute_json_with_protocol(
+    // Agentic mode: Gate runs the tools the caller selected
+    if let Some(selection) = headers.get(TOOLS_HEADER).and_then(|v| v.to_str().ok()) {
+        let registry = app_state.tools.clone().ok_or_else(|| {
+            HttpError::BadRequest("Server-side tools are not enabled".to_string())
+        })?;
+        let tools = registry.select(&ctx.identity.id, selection);
+        if tools.is_empty() {
+            return Err(HttpError::BadRequest(format!(
+                "No permitted tools match {TOOLS_HEADER}: {selection}"
+            )));
+        }
+        let agent = Agent::new(router, registry, ctx, request_json, tools);
+        return if request.stream {
+            response_stream_to_axum(agent.into_stream()).await
+        } else {
+            Ok(Json(agent.run().await?).into_response())
+        };
+    }
+
+    let stream = execute(
+        &app_state,
         router.as_ref(),
         &ctx,
         Protocol::OpenAIChat,
         request_json,
+        request.stream,
     )
     .await?;
 
@@ -127,21 +224,23 @@ where
     }
 }
 
-/// Handle OpenAI responses requests
+/// Run one chat completion against several models at once, streaming their
+/// chunks as one SSE stream. Each event is tagged with the index of its
+/// model in `models` as `channel`, and the model's own events are wrapped as
+/// `chunk`; a model that fails ends its channel with an `error`.
 #[instrument(
-    name = "openai_responses",
-    skip(app_state, headers),
-    fields(
-        model = %request.model,
-        stream = %request.stream
-    )
+    name = "openai_chat_compare",
+    skip(app_state, headers, client_ip, response_metadata, request),
+    fields(models = request.models.len())
 )]
-pub async fn responses_handler<T>(
+pub async fn chat_compare_handler<T>(
     State(app_state): State<AppState<T>>,
     uri: axum::http::Uri,
     headers: HeaderMap,
     axum::Extension(correlation_id): axum::Extension<CorrelationId>,
-    Json(request): Json<OpenAICompletionRequest>,
+    client_ip: Option<axum::Extension<ClientIp>>,
+    response_metadata: Option<axum::Extension<ResponseMetadata>>,
+    Json(request): Json<OpenAIChatCompareRequest>,
 ) -> Result<Response, HttpError>
 where
     T: Clone + Send + Sync + 'static,
@@ -149,8 +248,13 @@ where
     let router = app_state
         .router
         .ok_or_else(|| HttpError::InternalServerError("Router not configured".to_string()))?;
+    if request.models.is_empty() || request.models.len() > MAX_COMPARE_MODELS {
+        return Err(HttpError::BadRequest(format!(
+            "models must list between 1 and {MAX_COMPARE_MODELS} models"
+        )));
+    }
 
-    let ctx = RequestContext {
+    let mut ctx = RequestContext {
         identity: extract_identity(&headers),
         correlation_id,
         headers: headers.clone(),
@@ -160,53 +264,50 @@ where
             .and_then(|v| v.to_str().ok())
             .map(String::from),
         metadata: Default::default(),
+        response: response_metadata.map(|m| m.0).unwrap_or_default(),
+        extensions: Default::default(),
     };
-
-    let request_json = serde_json::to_value(&request)
-        .map_err(|e| HttpError::InternalServerError(format!("Failed to serialize request: {e}")))?;
-
-    let stream = route_and_execute_json_with_protocol(
-        router.as_ref(),
-        &ctx,
-        Protocol::OpenAIResponses,
-        request_json,
-    )
-    .await?;
-
-    if request.stream {
-        response_stream_to_axum(stream).await
-    } else {
-        response_stream_to_json(stream).await
+    if let Some(axum::Extension(client_ip)) = client_ip {
+        ctx.extensions.insert(client_ip);
     }
-}
 
-/// Create inference router
-pub fn router<T>() -> Router<AppState<T>>
-where
-    T: Send + Sync + Clone + 'static,
-{
-    Router::new()
-        .route("/v1/chat/completions", post(chat_completions_handler))
-        .route("/v1/responses", post(responses_handler))
-        .route("/v1/completions", post(completions_handler))
-        .route("/v1/messages", post(messages_handler))
+    let channels = request
+        .models
+        .into_iter()
+        .map(|model| {
+            let mut request_json = request.extra.clone();
+            request_json["model"] = JsonValue::String(model.clone());
+            request_json["stream"] = JsonValue::Bool(true);
+            let (router, ctx) = (router.clone(), ctx.clone());
+            let response = async move {
+                route_and_execute_json_with_protocol(
+                    router.as_ref(),
+                    &ctx,
+                    Protocol::OpenAIChat,
+                    request_json,
+                )
+                .await
+            };
+            (model, response)
+        })
+        .collect();
+    response_stream_to_axum(multiplex(channels)).await
 }
 
-/// Handle OpenAI completions (legacy) requests
+/// Handle OpenAI embeddings requests
 #[instrument(
-    name = "openai_completions",
-    skip(app_state, headers),
-    fields(
-        model = %request.model,
-        stream = %request.stream
-    )
+    name = "openai_embeddings",
+    skip(app_state, headers, client_ip, response_metadata, request),
+    fields(model = %request.model)
 )]
-pub async fn completions_handler<T>(
+pub async fn embeddings_handler<T>(
     State(app_state): State<AppState<T>>,
     uri: axum::http::Uri,
     headers: HeaderMap,
     axum::Extension(correlation_id): axum::Extension<CorrelationId>,
-    Json(request): Json<OpenAICompletionRequest>,
+    client_ip: Option<axum::Extension<ClientIp>>,
+    response_metadata: Option<axum::Extension<ResponseMetadata>>,
+    Json(request): Json<OpenAIEmbeddingsRequest>,
 ) -> Result<Response, HttpError>
 where
     T: Clone + Send + Sync + 'static,
@@ -215,7 +316,7 @@ where
         .router
         .ok_or_else(|| HttpError::InternalServerError("Router not configured".to_string()))?;
 
-    let ctx = RequestContext {
+    let mut ctx = RequestContext {
         identity: extract_identity(&headers),
         correlation_id,
         headers: headers.clone(),
@@ -225,22 +326,61 @@ where
             .and_then(|v| v.to_str().ok())
             .map(String::from),
         metadata: Default::default(),
+        response: response_metadata.map(|m| m.0).unwrap_or_default(),
+        extensions: Default::default(),
     };
+    if let Some(axum::Extension(client_ip)) = client_ip {
+        ctx.extensions.insert(client_ip);
+    }
 
     let request_json = serde_json::to_value(&request)
         .map_err(|e| HttpError::InternalServerError(format!("Failed to serialize request: {e}")))?;
 
-    let stream = route_and_execute_json_with_protocol(
+    let stream = execute(
+        &app_state,
         router.as_ref(),
         &ctx,
-        Protocol::OpenAICompletions,
+        Protocol::OpenAIEmbeddings,
         request_json,
+        false,
     )
     .await?;
+    response_stream_to_json(stream).await
+}
 
-    if request.stream {
-        response_stream_to_axum(stream).await
-    } else {
-        response_stream_to_json(stream).await
+/// Route and execute a request. Identical non-streaming requests share one
+/// execution when coalescing is on.
+async fn execute<T>(
+    app_state: &AppState<T>,
+    router: &CoreRouter,
+    ctx: &RequestContext,
+    protocol: Protocol,
+    request: JsonValue,
+    stream: bool,
+) -> gate_core::Result<ResponseStream> {
+    match app_state.coalescer.as_ref().filter(|_| !stream) {
+        Some(coalescer) => {
+            let key = request_hash(ctx, protocol, &request);
+            coalescer
+                .execute(
+                    key,
+                    route_and_execute_json_with_protocol(router, ctx, protocol, request),
+                )
+                .await
+        }
+        None => route_and_execute_json_with_protocol(router, ctx, protocol, request).await,
     }
 }
+
+/// Create inference router
+pub fn router<T>() -> Router<AppState<T>>
+where
+    T: Send + Sync + Clone + 'static,
+{
+    Router::new()
+        .route("/v1/chat/completions", post(chat_completions_handler))
+        .route("/v1/chat/completions:compare", post(chat_compare_handler))
+        .route("/v1/embeddings", post(embeddings_handler))
+        .route("/v1/messages", post(messages_handler))
+        .route("/v1/messages/count_tokens", post(count_tokens_handler))
+}
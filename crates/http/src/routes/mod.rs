@@ -1,19 +1,29 @@
 //! API route definitions
 
+pub mod assistants;
+pub mod base_path;
+pub mod completions;
 pub mod health;
 pub mod inference;
 pub mod models;
 pub mod observability;
+pub mod prefixes;
+pub mod responses;
 
 use axum::Router;
 
+pub use base_path::with_base_path;
+pub use prefixes::{ApiSurface, RoutePrefixes, with_route_prefixes};
+
 pub fn router<T>() -> Router<crate::AppState<T>>
 where
     T: Send + Sync + Clone + 'static,
 {
     Router::new()
+        .merge(completions::router())
         .merge(health::router())
         .merge(inference::router())
         .merge(models::router())
         .merge(observability::router())
+        .merge(responses::router())
 }
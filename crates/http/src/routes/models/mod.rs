@@ -2,32 +2,46 @@
 
 use crate::{
     error::HttpError,
+    services::{cached_json, response_cache::MODELS_KEY},
     state::AppState,
     types::{ModelInfo, ModelsListResponse},
 };
-use axum::{
-    Router,
-    extract::State,
-    response::{IntoResponse, Json, Response},
-    routing::get,
-};
+use axum::{Router, extract::State, http::HeaderMap, response::Response, routing::get};
 use gate_core::router::prelude::Sink;
+use std::sync::LazyLock;
 use tracing::{info, instrument};
 
+/// When the listing was first served, given as the creation time of every
+/// model so the listing, and its ETag, stay the same between requests
+static LISTED_AT: LazyLock<i64> = LazyLock::new(|| chrono::Utc::now().timestamp());
+
 /// Handle models list requests
 #[instrument(
     name = "list_models",
-    skip(app_state),
+    skip(app_state, headers),
     fields(
         model_count = tracing::field::Empty
     )
 )]
-pub async fn models_handler<T>(State(app_state): State<AppState<T>>) -> Result<Response, HttpError>
+pub async fn models_handler<T>(
+    State(app_state): State<AppState<T>>,
+    headers: HeaderMap,
+) -> Result<Response, HttpError>
 where
     T: Clone + Send + Sync + 'static,
 {
     info!("Received models list request");
 
+    cached_json(
+        app_state.response_cache.as_deref(),
+        MODELS_KEY,
+        &headers,
+        || list_models(&app_state),
+    )
+    .await
+}
+
+async fn list_models<T>(app_state: &AppState<T>) -> Result<ModelsListResponse, HttpError> {
     let mut models = Vec::new();
 
     // Get models from router if available
@@ -40,7 +54,7 @@ where
                         id,
                         object: "model".to_string(),
                         owned_by: "system".to_string(),
-                        created: chrono::Utc::now().timestamp(),
+                        created: *LISTED_AT,
                         context_length: desc.capabilities.max_context_length,
                     });
                 }
@@ -58,12 +72,10 @@ where
 
     tracing::Span::current().record("model_count", models.len());
 
-    let response = ModelsListResponse {
+    Ok(ModelsListResponse {
         object: "list".to_string(),
         data: models,
-    };
-
-    Ok(Json(response).into_response())
+    })
 }
 
 /// Create models router
@@ -0,0 +1,184 @@
+//! Vendor-shaped API surfaces under path prefixes
+//!
+//! Clients are configured with a base URL and append their vendor's paths to
+//! it: Anthropic SDKs add `/v1/messages`, while OpenAI SDKs add
+//! `/chat/completions` to a base that already ends in the version. With
+//! `anthropic = ["/anthropic"]` and `openai = ["/openai/v1"]` one gateway
+//! serves both, at `https://gate.example/anthropic` and
+//! `https://gate.example/openai/v1`.
+//!
+//! Prefixed requests are mapped onto the canonical `/v1` routes before
+//! routing, so authentication, server modes and every other path-based
+//! policy apply to them unchanged. Handlers still see the path the client
+//! asked for in [`OriginalUri`], and the surface a request came in on as an
+//! [`ApiSurface`] extension.
+
+use axum::{
+    Router,
+    extract::{OriginalUri, Request},
+    http::{Uri, uri::PathAndQuery},
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+/// API surface a prefixed request came in on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiSurface {
+    Anthropic,
+    OpenAI,
+}
+
+/// Base paths each protocol family is served under, besides the root
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct RoutePrefixes {
+    /// Base paths for Anthropic clients: `/anthropic` serves
+    /// `/anthropic/v1/messages`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub anthropic: Vec<String>,
+    /// Base paths for OpenAI clients, including the version: `/openai/v1`
+    /// serves `/openai/v1/chat/completions`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub openai: Vec<String>,
+}
+
+impl RoutePrefixes {
+    pub fn is_empty(&self) -> bool {
+        self.anthropic.is_empty() && self.openai.is_empty()
+    }
+
+    /// The surface and canonical path a prefixed path maps to
+    pub fn resolve(&self, path: &str) -> Option<(ApiSurface, String)> {
+        let anthropic = self
+            .anthropic
+            .iter()
+            .filter_map(|prefix| strip(path, prefix))
+            .find(|rest| rest.starts_with("/v1/"))
+            .map(|rest| (ApiSurface::Anthropic, rest.to_string()));
+        anthropic.or_else(|| {
+            self.openai
+                .iter()
+                .find_map(|prefix| strip(path, prefix))
+                .map(|rest| (ApiSurface::OpenAI, format!("/v1{rest}")))
+        })
+    }
+}
+
+fn strip<'a>(path: &'a str, prefix: &str) -> Option<&'a str> {
+    path.strip_prefix(prefix)
+        .filter(|rest| rest.len() > 1 && rest.starts_with('/'))
+}
+
+/// Why a base path cannot be used as a prefix, if it cannot
+pub fn prefix_error(prefix: &str) -> Option<&'static str> {
+    let overlaps = |root: &str| prefix == root || prefix.starts_with(&format!("{root}/"));
+    if !prefix.starts_with('/') || prefix.ends_with('/') {
+        Some("Prefixes must start with '/' and not end with one")
+    } else if ["/v1", "/api", "/auth", "/health", "/metrics"]
+        .into_iter()
+        .any(overlaps)
+    {
+        Some("Prefix overlaps the gateway's own routes")
+    } else {
+        None
+    }
+}
+
+/// Serve `router` under the configured prefixes as well as at the root
+pub fn with_route_prefixes(router: Router, prefixes: RoutePrefixes) -> Router {
+    if prefixes.is_empty() {
+        return router;
+    }
+    let prefixes = Arc::new(prefixes);
+    let mapped = router.map_request(move |mut request: Request| {
+        if let Some((surface, path)) = prefixes.resolve(request.uri().path())
+            && let Some(uri) = with_path(request.uri(), &path)
+        {
+            let original = std::mem::replace(request.uri_mut(), uri);
+            request.extensions_mut().insert(OriginalUri(original));
+            request.extensions_mut().insert(surface);
+        }
+        request
+    });
+    Router::new().fallback_service(mapped)
+}
+
+/// `uri` with its path replaced, keeping the query
+fn with_path(uri: &Uri, path: &str) -> Option<Uri> {
+    let path_and_query = match uri.query() {
+        Some(query) => format!("{path}?{query}"),
+        None => path.to_string(),
+    };
+    let mut parts = uri.clone().into_parts();
+    parts.path_and_query = Some(PathAndQuery::try_from(path_and_query).ok()?);
+    Uri::from_parts(parts).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::StatusCode, routing::post};
+
+    fn prefixes() -> RoutePrefixes {
+        RoutePrefixes {
+            anthropic: vec!["/anthropic".to_string()],
+            openai: vec!["/openai/v1".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_prefixed_paths_map_to_canonical_routes() {
+        let prefixes = prefixes();
+        assert_eq!(
+            prefixes.resolve("/anthropic/v1/messages"),
+            Some((ApiSurface::Anthropic, "/v1/messages".to_string()))
+        );
+        assert_eq!(
+            prefixes.resolve("/openai/v1/chat/completions"),
+            Some((ApiSurface::OpenAI, "/v1/chat/completions".to_string()))
+        );
+        assert_eq!(prefixes.resolve("/v1/messages"), None);
+        assert_eq!(prefixes.resolve("/anthropicx/v1/messages"), None);
+        assert_eq!(prefixes.resolve("/anthropic/"), None);
+
+        assert!(prefix_error("/openai/v1").is_none());
+        assert!(prefix_error("/v1").is_some());
+        assert!(prefix_error("/api/anthropic").is_some());
+        assert!(prefix_error("anthropic/").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_prefixed_requests_reach_the_same_handler() {
+        let router = Router::new().route(
+            "/v1/chat/completions",
+            post(|OriginalUri(uri): OriginalUri| async move { uri.to_string() }),
+        );
+        let app = with_route_prefixes(router, prefixes());
+
+        let response = app
+            .clone()
+            .oneshot(
+                axum::http::Request::post("/openai/v1/chat/completions?x=1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), 1024)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"/openai/v1/chat/completions?x=1");
+
+        let response = app
+            .oneshot(
+                axum::http::Request::post("/v1/chat/completions")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
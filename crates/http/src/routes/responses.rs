@@ -0,0 +1,382 @@
+//! OpenAI Responses API
+//!
+//! Requests go to sinks that speak Responses natively. When none serve the
+//! model, the request is converted to chat completions and the result converted
+//! back; Gate then keeps the conversation itself so `previous_response_id`
+//! still works.
+
+use crate::{
+    auth::extract_identity, error::HttpError, sinks::response_converter::response_stream_to_axum,
+    state::AppState, types::OpenAICompletionRequest,
+};
+use axum::{
+    Router,
+    extract::{Json, State},
+    http::{HeaderMap, HeaderName},
+    response::{IntoResponse, Response},
+    routing::post,
+};
+use futures::StreamExt;
+use gate_core::router::{
+    ResponseChunk, ResponseStream,
+    extensions::ClientIp,
+    protocols::{convert_request, convert_response},
+    routing::Router as CoreRouter,
+    service::{
+        descriptor_from_json_with_protocol, one_shot_stream, route_and_execute_json_with_protocol,
+    },
+    sink::RequestContext,
+    types::{Protocol, StopReason},
+};
+use gate_core::tracing::prelude::*;
+use gate_core::{StateBackend, StoredResponse};
+use serde_json::{Value as JsonValue, json};
+use std::collections::HashMap;
+
+const X_TRACE_ID: HeaderName = HeaderName::from_static("x-trace-id");
+const PREVIOUS_RESPONSE_ID: &str = "previous_response_id";
+const RESPONSE_COMPLETED: &str = "response.completed";
+
+/// Handle OpenAI responses requests
+#[instrument(
+    name = "openai_responses",
+    skip(app_state, headers, client_ip, response_metadata),
+    fields(
+        model = %request.model,
+        stream = %request.stream
+    )
+)]
+pub async fn responses_handler<T>(
+    State(app_state): State<AppState<T>>,
+    uri: axum::http::Uri,
+    headers: HeaderMap,
+    axum::Extension(correlation_id): axum::Extension<CorrelationId>,
+    client_ip: Option<axum::Extension<ClientIp>>,
+    response_metadata: Option<axum::Extension<ResponseMetadata>>,
+    Json(request): Json<OpenAICompletionRequest>,
+) -> Result<Response, HttpError>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let router = app_state
+        .router
+        .ok_or_else(|| HttpError::InternalServerError("Router not configured".to_string()))?;
+
+    let mut ctx = RequestContext {
+        identity: extract_identity(&headers),
+        correlation_id,
+        headers: headers.clone(),
+        query: uri.query().map(|s| s.to_string()),
+        trace_id: headers
+            .get(X_TRACE_ID)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from),
+        metadata: Default::default(),
+        response: response_metadata.map(|m| m.0).unwrap_or_default(),
+        extensions: Default::default(),
+    };
+    if let Some(axum::Extension(client_ip)) = client_ip {
+        ctx.extensions.insert(client_ip);
+    }
+
+    let mut request_json = serde_json::to_value(&request)
+        .map_err(|e| HttpError::InternalServerError(format!("Failed to serialize request: {e}")))?;
+
+    // Continuing from a response Gate stored: replay the conversation instead
+    let previous_id = request_json
+        .get(PREVIOUS_RESPONSE_ID)
+        .and_then(|v| v.as_str())
+        .map(String::from);
+    if let Some(id) = &previous_id
+        && let Some(previous) =
+            load_stored_response(app_state.state_backend.as_ref(), id, &ctx).await?
+    {
+        let input = continued_input(&previous.input, request_json.get("input"));
+        if let Some(obj) = request_json.as_object_mut() {
+            obj.remove(PREVIOUS_RESPONSE_ID);
+            obj.insert("input".to_string(), input);
+        }
+    }
+
+    router
+        .rewrite(&mut ctx, Protocol::OpenAIResponses, &mut request_json)
+        .await?;
+    let desc = descriptor_from_json_with_protocol(&request_json, Protocol::OpenAIResponses)?;
+    match router.route(&ctx, &desc).await {
+        Ok(plan) => {
+            let stream = router
+                .execute(
+                    plan,
+                    one_shot_stream(Protocol::OpenAIResponses, request_json),
+                )
+                .await?;
+            if request.stream {
+                response_stream_to_axum(stream).await
+            } else {
+                let (response_headers, body) = final_response(stream).await?;
+                let mut resp = Json(body).into_response();
+                let headers = resp.headers_mut();
+                for (k, v) in response_headers {
+                    if let (Ok(name), Ok(value)) = (HeaderName::try_from(k), v.parse()) {
+                        headers.insert(name, value);
+                    }
+                }
+                Ok(resp)
+            }
+        }
+        Err(gate_core::Error::NoSinksAvailable) => {
+            // A provider-side id cannot be continued through chat completions
+            if request_json.get(PREVIOUS_RESPONSE_ID).is_some() {
+                return Err(HttpError::NotFound(format!(
+                    "Response {} not found",
+                    previous_id.unwrap_or_default()
+                )));
+            }
+            let response = respond_via_chat(
+                router.as_ref(),
+                app_state.state_backend.as_ref(),
+                &ctx,
+                request_json,
+                previous_id,
+            )
+            .await?;
+            if request.stream {
+                let events = response_events(&response)
+                    .into_iter()
+                    .map(|event| Ok(ResponseChunk::Content(event)))
+                    .chain(std::iter::once(Ok(ResponseChunk::Stop {
+                        reason: StopReason::Complete,
+                        error: None,
+                        cost: None,
+                    })));
+                let stream: ResponseStream = Box::pin(futures::stream::iter(events));
+                response_stream_to_axum(stream).await
+            } else {
+                Ok(Json(response).into_response())
+            }
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// A response Gate stored for this caller, if `id` is one
+async fn load_stored_response(
+    backend: &dyn StateBackend,
+    id: &str,
+    ctx: &RequestContext,
+) -> Result<Option<StoredResponse>, HttpError> {
+    match backend.get_stored_response(id).await {
+        Ok(Some(stored)) if stored.owner_id != ctx.identity.id => {
+            Err(HttpError::NotFound(format!("Response {id} not found")))
+        }
+        Ok(stored) => Ok(stored),
+        Err(e) => {
+            // Not stored by Gate; the upstream may still know the id
+            debug!("Stored response lookup failed for {id}: {e}");
+            Ok(None)
+        }
+    }
+}
+
+/// Input items as a list, wrapping a bare string as a user message
+fn input_items(input: Option<&JsonValue>) -> Vec<JsonValue> {
+    match input {
+        Some(JsonValue::String(text)) => vec![json!({"role": "user", "content": text})],
+        Some(JsonValue::Array(items)) => items.clone(),
+        _ => Vec::new(),
+    }
+}
+
+/// Stored conversation followed by the new request's input
+fn continued_input(history: &JsonValue, input: Option<&JsonValue>) -> JsonValue {
+    let mut items = input_items(Some(history));
+    items.extend(input_items(input));
+    JsonValue::Array(items)
+}
+
+/// Serve a Responses request from a chat-only sink and keep its state
+async fn respond_via_chat(
+    router: &CoreRouter,
+    backend: &dyn StateBackend,
+    ctx: &RequestContext,
+    request_json: JsonValue,
+    previous_id: Option<String>,
+) -> Result<JsonValue, HttpError> {
+    let (mut chat_request, warnings) = ctx.response.time_conversion(|| {
+        convert_request(
+            Protocol::OpenAIResponses,
+            Protocol::OpenAIChat,
+            &request_json,
+        )
+    })?;
+    for warning in warnings {
+        debug!("Responses to chat conversion: {warning}");
+    }
+    chat_request["stream"] = json!(false);
+
+    let stream =
+        route_and_execute_json_with_protocol(router, ctx, Protocol::OpenAIChat, chat_request)
+            .await?;
+    let (_, chat_response) = final_response(stream).await?;
+    let (mut response, _) = ctx.response.time_conversion(|| {
+        convert_response(
+            Protocol::OpenAIChat,
+            Protocol::OpenAIResponses,
+            &chat_response,
+        )
+    })?;
+    response[PREVIOUS_RESPONSE_ID] = json!(previous_id);
+
+    let store = request_json
+        .get("store")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+    if store {
+        let mut input = input_items(request_json.get("input"));
+        if let Some(output) = response.get("output").and_then(|o| o.as_array()) {
+            input.extend(output.iter().cloned());
+        }
+        let stored = StoredResponse {
+            id: response["id"].as_str().unwrap_or_default().to_string(),
+            owner_id: ctx.identity.id.clone(),
+            input: JsonValue::Array(input),
+            response: response.clone(),
+            created_at: chrono::Utc::now(),
+        };
+        if let Err(e) = backend.save_stored_response(&stored).await {
+            warn!("Failed to store response {}: {e}", stored.id);
+        }
+    }
+
+    Ok(response)
+}
+
+/// Collect a sink's output into one JSON body. For streamed Responses the
+/// body is the response carried by `response.completed`.
+pub(crate) async fn final_response(
+    mut stream: ResponseStream,
+) -> Result<(HashMap<String, String>, JsonValue), HttpError> {
+    let mut headers = HashMap::new();
+    let mut last_json = None;
+    while let Some(item) = stream.next().await {
+        match item.map(ResponseChunk::parsed) {
+            Ok(ResponseChunk::Headers(h)) if headers.is_empty() => headers = h,
+            Ok(ResponseChunk::Content(json)) => {
+                if json.get("type").and_then(|t| t.as_str()) == Some(RESPONSE_COMPLETED)
+                    && let Some(response) = json.get("response")
+                {
+                    return Ok((headers, response.clone()));
+                }
+                last_json = Some(json);
+            }
+            Ok(ResponseChunk::Stop {
+                error: Some(err), ..
+            }) if last_json.is_none() => return Err(HttpError::ServiceUnavailable(err)),
+            Ok(_) => {}
+            Err(e) => return Err(HttpError::Core(e)),
+        }
+    }
+    last_json
+        .map(|json| (headers, json))
+        .ok_or_else(|| HttpError::ServiceUnavailable("No content received from sink".to_string()))
+}
+
+/// Streaming events replaying a complete response, for clients that asked to
+/// stream a request served through chat completions
+fn response_events(response: &JsonValue) -> Vec<JsonValue> {
+    let mut in_progress = response.clone();
+    in_progress["status"] = json!("in_progress");
+    in_progress["output"] = json!([]);
+    let mut events = vec![json!({"type": "response.created", "response": in_progress})];
+
+    let output = response.get("output").and_then(|o| o.as_array());
+    for (output_index, item) in output.into_iter().flatten().enumerate() {
+        events.push(json!({
+            "type": "response.output_item.added",
+            "output_index": output_index,
+            "item": item
+        }));
+        let parts = item.get("content").and_then(|c| c.as_array());
+        for (content_index, part) in parts.into_iter().flatten().enumerate() {
+            let Some(text) = part.get("text") else {
+                continue;
+            };
+            let position = json!({
+                "item_id": item.get("id"),
+                "output_index": output_index,
+                "content_index": content_index
+            });
+            let mut delta = position.clone();
+            delta["type"] = json!("response.output_text.delta");
+            delta["delta"] = text.clone();
+            let mut done = position;
+            done["type"] = json!("response.output_text.done");
+            done["text"] = text.clone();
+            events.extend([delta, done]);
+        }
+        events.push(json!({
+            "type": "response.output_item.done",
+            "output_index": output_index,
+            "item": item
+        }));
+    }
+
+    events.push(json!({"type": RESPONSE_COMPLETED, "response": response}));
+    events
+}
+
+/// Create the Responses API router
+pub fn router<T>() -> Router<AppState<T>>
+where
+    T: Send + Sync + Clone + 'static,
+{
+    Router::new().route("/v1/responses", post(responses_handler))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_continued_input_appends_to_history() {
+        let history = json!([
+            {"role": "user", "content": "Hi"},
+            {"type": "message", "role": "assistant", "content": [{"type": "output_text", "text": "Hello"}]}
+        ]);
+        let input = continued_input(&history, Some(&json!("How are you?")));
+        let items = input.as_array().unwrap();
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[2], json!({"role": "user", "content": "How are you?"}));
+    }
+
+    #[test]
+    fn test_response_events_replay_output() {
+        let response = json!({
+            "id": "resp_1",
+            "object": "response",
+            "status": "completed",
+            "output": [{
+                "type": "message",
+                "id": "msg_1",
+                "role": "assistant",
+                "content": [{"type": "output_text", "text": "Hello"}]
+            }]
+        });
+        let events = response_events(&response);
+        let types: Vec<_> = events.iter().map(|e| e["type"].as_str().unwrap()).collect();
+        assert_eq!(
+            types,
+            [
+                "response.created",
+                "response.output_item.added",
+                "response.output_text.delta",
+                "response.output_text.done",
+                "response.output_item.done",
+                RESPONSE_COMPLETED,
+            ]
+        );
+        assert_eq!(events[0]["response"]["status"], "in_progress");
+        assert_eq!(events[2]["delta"], "Hello");
+        assert_eq!(events[5]["response"], response);
+    }
+}
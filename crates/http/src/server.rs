@@ -5,9 +5,15 @@ use axum::extract::Request;
 use hyper::body::Incoming;
 use hyper_util::rt::{TokioExecutor, TokioIo};
 use hyper_util::server;
+use std::net::SocketAddr;
 use tokio::io::{AsyncRead, AsyncWrite};
 use tower::Service;
 
+/// Address of the client a forwarded request came from, as reported by the
+/// relay; present only on requests that arrived through it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ForwardedClient(pub SocketAddr);
+
 /// HTTP server that can handle streams from various sources (TCP, P2P, etc.)
 #[derive(Clone, Debug)]
 pub struct HttpServer {
@@ -23,6 +29,13 @@ impl HttpServer {
     /// Handle a stream
     #[tracing::instrument(name = "http.handle_stream", skip_all)]
     pub async fn handle_stream<S>(&self, stream: S) -> anyhow::Result<()>
+    where
+        S: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+    {
+        self.serve(stream, None).await
+    }
+
+    async fn serve<S>(&self, stream: S, client: Option<ForwardedClient>) -> anyhow::Result<()>
     where
         S: AsyncRead + AsyncWrite + Send + Unpin + 'static,
     {
@@ -35,7 +48,10 @@ impl HttpServer {
         let io = TokioIo::new(stream);
 
         // Router<()> implements Service<Request<Incoming>> directly
-        let hyper_service = hyper::service::service_fn(move |request: Request<Incoming>| {
+        let hyper_service = hyper::service::service_fn(move |mut request: Request<Incoming>| {
+            if let Some(client) = client {
+                request.extensions_mut().insert(client);
+            }
             router.clone().call(request)
         });
 
@@ -63,7 +79,8 @@ impl HttpServer {
         skip_all,
         fields(
             p2p.node_id = %node_id,
-            p2p.relay_domain = _relay_domain.as_deref().unwrap_or("direct")
+            p2p.relay_domain = _relay_domain.as_deref().unwrap_or("direct"),
+            p2p.client = ?client
         )
     )]
     pub async fn handle_p2p_stream<S>(
@@ -71,11 +88,12 @@ impl HttpServer {
         stream: S,
         node_id: impl std::fmt::Display,
         _relay_domain: Option<String>,
+        client: Option<SocketAddr>,
     ) -> anyhow::Result<()>
     where
         S: AsyncRead + AsyncWrite + Send + Unpin + 'static,
     {
         info!("Handling P2P stream from node: {}", node_id);
-        self.handle_stream(stream).await
+        self.serve(stream, client.map(ForwardedClient)).await
     }
 }
@@ -0,0 +1,186 @@
+//! Duplicate suppression for non-streaming inference requests
+//!
+//! Clients that time out and retry, sometimes many times over, send the
+//! same request while the first is still running, and each copy is billed.
+//! Requests are hashed in canonical form with the identity that sent them
+//! ([`request_hash`](gate_core::router::digest::request_hash)); identical
+//! requests within the window share one upstream execution and replay its
+//! result. If the request that is running is cancelled, a waiting duplicate
+//! runs in its place. Failures are not shared: duplicates of a failed
+//! request run on their own. Results are held against the shared
+//! [`BufferBudget`] for as long as they are kept for replay.
+
+use futures::StreamExt;
+use gate_core::router::buffers::{BufferBudget, ResponseBuffer, json_size};
+use gate_core::router::sink::{ResponseStream, with_headers};
+use gate_core::router::types::ResponseChunk;
+use gate_core::{Error, Result};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::OnceCell;
+use tokio::time::Instant;
+
+/// Response header set on results replayed from an identical request
+pub const COALESCED_HEADER: &str = "x-gate-coalesced";
+
+/// A completed response and the buffer budget it holds
+struct Completed {
+    chunks: Vec<ResponseChunk>,
+    _buffer: ResponseBuffer<'static>,
+}
+
+/// A completed response, or `None` if it failed
+type SharedResult = OnceCell<Option<Arc<Completed>>>;
+
+/// Coalesces identical requests made within a window
+pub struct RequestCoalescer {
+    window: Duration,
+    entries: Mutex<HashMap<String, (Instant, Arc<SharedResult>)>>,
+}
+
+impl RequestCoalescer {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Run `execute` for the request hashed as `key`, unless an identical
+    /// request within the window already has or will have the result
+    pub async fn execute<F>(&self, key: String, execute: F) -> Result<ResponseStream>
+    where
+        F: Future<Output = Result<ResponseStream>>,
+    {
+        let shared = self.entry(key);
+        let mut execute = Some(execute);
+        let mut failure = None;
+        let (pending, failed) = (&mut execute, &mut failure);
+        let result = shared
+            .get_or_init(|| async move {
+                let execute = pending.take().expect("initialized once");
+                match collect(execute).await {
+                    Ok(completed) => Some(Arc::new(completed)),
+                    Err(e) => {
+                        *failed = Some(e);
+                        None
+                    }
+                }
+            })
+            .await
+            .clone();
+
+        match (result, execute) {
+            // This request ran and failed
+            (None, None) => Err(failure.unwrap_or_else(|| {
+                Error::Internal("Coalesced request failed without an error".to_string())
+            })),
+            // The request this one waited on failed, so it runs on its own
+            (None, Some(execute)) => execute.await,
+            (Some(completed), execute) => {
+                let chunks = completed.chunks.to_vec();
+                let stream: ResponseStream =
+                    Box::pin(futures::stream::iter(chunks.into_iter().map(Ok)));
+                if execute.is_none() {
+                    return Ok(stream);
+                }
+                let headers = HashMap::from([(COALESCED_HEADER.to_string(), "true".to_string())]);
+                Ok(with_headers(stream, headers).await)
+            }
+        }
+    }
+
+    /// Forget completed results, releasing the memory they hold. Requests
+    /// still running are kept for the duplicates waiting on them.
+    pub fn clear_completed(&self) {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|_, (_, shared)| shared.get().is_none());
+    }
+
+    /// The shared result for `key`, forgetting failures, results older than
+    /// the window and cancelled requests no one waits on
+    fn entry(&self, key: String) -> Arc<SharedResult> {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|_, (started, shared)| match shared.get() {
+            None => Arc::strong_count(shared) > 1,
+            Some(None) => false,
+            Some(Some(_)) => now.duration_since(*started) < self.window,
+        });
+        entries
+            .entry(key)
+            .or_insert_with(|| (now, Arc::new(OnceCell::new())))
+            .1
+            .clone()
+    }
+}
+
+/// Run a request to completion, failing if its stream does or it outgrows
+/// the buffer budget
+async fn collect<F>(execute: F) -> Result<Completed>
+where
+    F: Future<Output = Result<ResponseStream>>,
+{
+    let mut buffer = BufferBudget::shared().buffer()?;
+    let mut stream = execute.await?;
+    let mut chunks = Vec::new();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        match &chunk {
+            ResponseChunk::Content(json) => buffer.reserve(json_size(json))?,
+            ResponseChunk::Raw { data, .. } => buffer.reserve(data.len())?,
+            _ => {}
+        }
+        chunks.push(chunk);
+    }
+    Ok(Completed {
+        chunks,
+        _buffer: buffer,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_identical_requests_share_one_execution() {
+        let coalescer = Arc::new(RequestCoalescer::new(Duration::from_millis(200)));
+        let executions = Arc::new(AtomicUsize::new(0));
+        let run = |coalescer: Arc<RequestCoalescer>, executions: Arc<AtomicUsize>| async move {
+            let stream = coalescer
+                .execute("key".to_string(), async move {
+                    executions.fetch_add(1, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    let chunks = vec![Ok(ResponseChunk::Content(json!({"id": "r1"})))];
+                    Ok(Box::pin(futures::stream::iter(chunks)) as ResponseStream)
+                })
+                .await
+                .unwrap();
+            stream.collect::<Vec<_>>().await
+        };
+
+        let (first, second) = tokio::join!(
+            run(coalescer.clone(), executions.clone()),
+            run(coalescer.clone(), executions.clone())
+        );
+        assert_eq!(executions.load(Ordering::SeqCst), 1);
+        assert_eq!(first.len(), 1);
+        // The duplicate is told its result was shared
+        assert!(matches!(
+            &second[0],
+            Ok(ResponseChunk::Headers(headers)) if headers.contains_key(COALESCED_HEADER)
+        ));
+
+        // Past the window the request runs again
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        run(coalescer.clone(), executions.clone()).await;
+        assert_eq!(executions.load(Ordering::SeqCst), 2);
+    }
+}
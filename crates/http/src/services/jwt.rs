@@ -6,6 +6,9 @@ use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation, deco
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
+/// Reason given for rejecting a token past its expiry
+pub const EXPIRED_TOKEN_MESSAGE: &str = "Token has expired";
+
 /// JWT Claims structure
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
@@ -19,6 +22,9 @@ pub struct Claims {
     pub iat: i64,
     /// Issuer
     pub iss: String,
+    /// Restricts what the token may be used for, e.g. `observer`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
 }
 
 /// JWT service configuration
@@ -65,6 +71,16 @@ impl JwtService {
 
     /// Generate a JWT token for a user
     pub fn generate_token(&self, user_id: &str, name: Option<&str>) -> Result<String, HttpError> {
+        self.generate_scoped_token(user_id, name, None)
+    }
+
+    /// Generate a JWT token for a user, restricted to `scope` if given
+    pub fn generate_scoped_token(
+        &self,
+        user_id: &str,
+        name: Option<&str>,
+        scope: Option<&str>,
+    ) -> Result<String, HttpError> {
         let now = Utc::now();
         let expiration = now + self.config.expiration;
 
@@ -74,6 +90,7 @@ impl JwtService {
             exp: expiration.timestamp(),
             iat: now.timestamp(),
             iss: self.config.issuer.clone(),
+            scope: scope.map(|s| s.to_string()),
         };
 
         let header = Header::new(Algorithm::HS256);
@@ -90,7 +107,7 @@ impl JwtService {
             .map(|token_data| token_data.claims)
             .map_err(|e| match e.kind() {
                 jsonwebtoken::errors::ErrorKind::ExpiredSignature => {
-                    HttpError::AuthenticationFailed("Token has expired".to_string())
+                    HttpError::AuthenticationFailed(EXPIRED_TOKEN_MESSAGE.to_string())
                 }
                 jsonwebtoken::errors::ErrorKind::InvalidToken => {
                     HttpError::AuthenticationFailed("Invalid token".to_string())
@@ -135,6 +152,13 @@ mod tests {
         let claims = service.validate_token(&token).unwrap();
         assert_eq!(claims.sub, user_id);
         assert_eq!(claims.name.as_deref(), name);
+        assert!(claims.scope.is_none());
+
+        let token = service
+            .generate_scoped_token(user_id, name, Some("observer"))
+            .unwrap();
+        let claims = service.validate_token(&token).unwrap();
+        assert_eq!(claims.scope.as_deref(), Some("observer"));
     }
 
     #[test]
@@ -152,6 +176,7 @@ mod tests {
             exp: expired_time.timestamp(),
             iat: expired_time.timestamp(),
             iss: service.config.issuer.clone(),
+            scope: None,
         };
 
         let header = Header::new(Algorithm::HS256);
@@ -1,11 +1,17 @@
 //! Service layer for business logic
 
+pub mod coalescer;
 pub mod identity;
+pub mod multiplex;
+pub mod response_cache;
 
 #[cfg(not(target_arch = "wasm32"))]
 pub mod jwt;
 
+pub use coalescer::{COALESCED_HEADER, RequestCoalescer};
 pub use identity::{HttpContext, HttpIdentity};
+pub use multiplex::multiplex;
+pub use response_cache::{CachedResponse, ResponseCache, cached_json};
 
 #[cfg(not(target_arch = "wasm32"))]
 pub use jwt::{Claims, JwtConfig, JwtService};
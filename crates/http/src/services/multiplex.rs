@@ -0,0 +1,136 @@
+//! Fan-in of several response streams into one
+//!
+//! Comparing models means running one prompt against each of them at once
+//! and watching the answers arrive side by side. Each model's response is a
+//! channel; the multiplexed stream interleaves chunks from all channels as
+//! they arrive, wrapping each in an envelope naming its channel and model.
+//! Headers are dropped, since one response can only carry one set. A channel
+//! that fails ends with an error envelope without stopping the others, and
+//! the multiplexed stream stops once every channel has.
+
+use futures::stream::{self, StreamExt};
+use gate_core::Result;
+use gate_core::router::sink::ResponseStream;
+use gate_core::router::types::{ResponseChunk, StopReason};
+use serde_json::{Value as JsonValue, json};
+use std::future::Future;
+
+/// Envelope for a chunk from channel `index`, serving `model`
+fn envelope(index: usize, model: &str, chunk: Result<ResponseChunk>) -> Option<JsonValue> {
+    let mut tagged = json!({"channel": index, "model": model});
+    let fields = match chunk.map(ResponseChunk::parsed) {
+        // Raw chunks were parsed into content above
+        Ok(ResponseChunk::Headers(_) | ResponseChunk::Raw { .. }) => return None,
+        Ok(ResponseChunk::Content(content)) => json!({"chunk": content}),
+        Ok(ResponseChunk::Usage {
+            prompt_tokens,
+            completion_tokens,
+        }) => json!({"usage": {
+            "prompt_tokens": prompt_tokens,
+            "completion_tokens": completion_tokens
+        }}),
+        Ok(ResponseChunk::Metadata(metadata)) => json!({"metadata": metadata}),
+        Ok(ResponseChunk::Stop {
+            reason,
+            error,
+            cost,
+        }) => json!({
+            "done": true,
+            "reason": format!("{reason:?}"),
+            "error": error,
+            "cost": cost
+        }),
+        Err(e) => json!({"done": true, "reason": "Error", "error": e.to_string()}),
+    };
+    if let (Some(tagged), Some(fields)) = (tagged.as_object_mut(), fields.as_object()) {
+        tagged.extend(fields.iter().map(|(k, v)| (k.clone(), v.clone())));
+    }
+    Some(tagged)
+}
+
+/// Run every channel at once and interleave their chunks as they arrive.
+/// Channels are `(model, response)` pairs, the response started only when
+/// the multiplexed stream is first polled.
+pub fn multiplex<F>(channels: Vec<(String, F)>) -> ResponseStream
+where
+    F: Future<Output = Result<ResponseStream>> + Send + 'static,
+{
+    let channels = channels
+        .into_iter()
+        .enumerate()
+        .map(|(index, (model, response))| {
+            stream::once(response)
+                .flat_map(|response| match response {
+                    Ok(stream) => stream,
+                    Err(e) => Box::pin(stream::once(async move { Err(e) })) as ResponseStream,
+                })
+                .filter_map(move |chunk| {
+                    let tagged = envelope(index, &model, chunk);
+                    async move { tagged.map(|json| Ok(ResponseChunk::Content(json))) }
+                })
+                .boxed()
+        });
+    let done = stream::once(async {
+        Ok(ResponseChunk::Stop {
+            reason: StopReason::Complete,
+            error: None,
+            cost: None,
+        })
+    });
+    Box::pin(stream::select_all(channels).chain(done))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gate_core::Error;
+    use std::pin::Pin;
+
+    type Pending = Pin<Box<dyn Future<Output = Result<ResponseStream>> + Send>>;
+
+    fn response(chunks: Vec<ResponseChunk>) -> Result<ResponseStream> {
+        Ok(Box::pin(stream::iter(chunks.into_iter().map(Ok))))
+    }
+
+    #[tokio::test]
+    async fn test_channels_are_tagged_and_failures_stay_in_their_channel() {
+        let stop = || ResponseChunk::Stop {
+            reason: StopReason::Complete,
+            error: None,
+            cost: None,
+        };
+        let channels = vec![
+            (
+                "small".to_string(),
+                Box::pin(async move {
+                    response(vec![
+                        ResponseChunk::Headers(Default::default()),
+                        ResponseChunk::Content(json!({"text": "hi"})),
+                        stop(),
+                    ])
+                }) as Pending,
+            ),
+            (
+                "broken".to_string(),
+                Box::pin(async move { Err(Error::Internal("no route".to_string())) }) as Pending,
+            ),
+        ];
+        let chunks: Vec<_> = multiplex(channels)
+            .map(|chunk| match chunk.unwrap() {
+                ResponseChunk::Content(json) => json,
+                ResponseChunk::Stop { .. } => json!("end"),
+                other => panic!("unexpected chunk {other:?}"),
+            })
+            .collect()
+            .await;
+
+        assert_eq!(chunks.len(), 4);
+        let small: Vec<_> = chunks.iter().filter(|c| c["model"] == "small").collect();
+        assert_eq!(small[0]["chunk"]["text"], "hi");
+        assert_eq!(small[1]["done"], true);
+        let broken = chunks.iter().find(|c| c["channel"] == 1).unwrap();
+        assert_eq!(broken["model"], "broken");
+        assert!(broken["error"].as_str().unwrap().contains("no route"));
+        assert_eq!(chunks[3], "end");
+    }
+}
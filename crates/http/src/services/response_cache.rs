@@ -0,0 +1,260 @@
+//! Short-lived cache of metadata responses
+//!
+//! IDE plugins poll `/v1/models`, and dashboards poll status and provider
+//! health, every few seconds. Each such response is built at most once per
+//! time-to-live and carries an ETag; a client that sends it back in
+//! `If-None-Match` gets `304 Not Modified` without a body. Entries are
+//! dropped as soon as the sink registry changes, so a provider that is added,
+//! removed or changes health shows up on the next request.
+
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use bytes::Bytes;
+use gate_core::router::registry::{RegistryEvent, SinkRegistry};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::Instant;
+use tracing::warn;
+
+/// Cache key of the `/v1/models` listing
+pub const MODELS_KEY: &str = "models";
+
+/// Prefix of the keys of responses that report sink health, dropped on
+/// every health event
+pub const HEALTH_KEY_PREFIX: &str = "health:";
+
+/// A response body with its entity tag
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedResponse {
+    pub etag: String,
+    pub body: Bytes,
+}
+
+impl CachedResponse {
+    /// Serialize `value` as JSON and tag it with a hash of the body
+    pub fn json<T: Serialize>(value: &T) -> Self {
+        let body = serde_json::to_vec(value).expect("Responses should always serialize");
+        let hash = Sha256::digest(&body);
+        Self {
+            etag: format!("\"{}\"", hex_prefix(&hash)),
+            body: Bytes::from(body),
+        }
+    }
+
+    /// Whether an `If-None-Match` header names this response
+    fn matches(&self, headers: &HeaderMap) -> bool {
+        headers
+            .get_all(header::IF_NONE_MATCH)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(|tag| tag.trim())
+            .any(|tag| tag == "*" || tag.trim_start_matches("W/") == self.etag)
+    }
+
+    /// `304 Not Modified` if the client already has this response, else the
+    /// response itself
+    pub fn respond(&self, headers: &HeaderMap) -> Response {
+        let etag = HeaderValue::from_str(&self.etag).expect("ETags are hex");
+        let cache_control = HeaderValue::from_static("private, no-cache");
+        if self.matches(headers) {
+            return (
+                StatusCode::NOT_MODIFIED,
+                [(header::ETAG, etag), (header::CACHE_CONTROL, cache_control)],
+            )
+                .into_response();
+        }
+        (
+            [
+                (header::ETAG, etag),
+                (header::CACHE_CONTROL, cache_control),
+                (
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_static("application/json"),
+                ),
+            ],
+            self.body.clone(),
+        )
+            .into_response()
+    }
+}
+
+fn hex_prefix(hash: &[u8]) -> String {
+    hash.iter().take(16).map(|b| format!("{b:02x}")).collect()
+}
+
+/// Responses by key, each kept for the time-to-live
+pub struct ResponseCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, (Instant, Arc<CachedResponse>)>>,
+}
+
+impl ResponseCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The response cached under `key`, or the one `build` makes, which is
+    /// cached if it succeeds
+    pub async fn get_or_build<T, E, F, Fut>(
+        &self,
+        key: &str,
+        build: F,
+    ) -> Result<Arc<CachedResponse>, E>
+    where
+        T: Serialize,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        if let Some((stored, response)) = self.entries.lock().expect("poisoned").get(key)
+            && stored.elapsed() < self.ttl
+        {
+            return Ok(response.clone());
+        }
+        let response = Arc::new(CachedResponse::json(&build().await?));
+        self.entries
+            .lock()
+            .expect("poisoned")
+            .insert(key.to_string(), (Instant::now(), response.clone()));
+        Ok(response)
+    }
+
+    /// Drop the response cached under `key`
+    pub fn invalidate(&self, key: &str) {
+        self.entries.lock().expect("poisoned").remove(key);
+    }
+
+    /// Drop every cached response whose key starts with `prefix`
+    pub fn invalidate_prefix(&self, prefix: &str) {
+        self.entries
+            .lock()
+            .expect("poisoned")
+            .retain(|key, _| !key.starts_with(prefix));
+    }
+
+    /// Drop every cached response
+    pub fn clear(&self) {
+        self.entries.lock().expect("poisoned").clear();
+    }
+
+    /// Drop cached responses as `registry` changes: everything when a sink
+    /// is registered or removed, health reports on each health event.
+    /// Subscribes immediately; the returned future runs until the registry
+    /// is dropped and is meant to be spawned.
+    pub fn follow(
+        self: &Arc<Self>,
+        registry: &SinkRegistry,
+    ) -> impl Future<Output = ()> + Send + 'static {
+        let mut events = registry.subscribe();
+        let cache = self.clone();
+        async move {
+            loop {
+                match events.recv().await {
+                    Ok(RegistryEvent::Registered(_) | RegistryEvent::Removed(_)) => cache.clear(),
+                    Ok(RegistryEvent::Health { .. }) => cache.invalidate_prefix(HEALTH_KEY_PREFIX),
+                    Err(RecvError::Lagged(missed)) => {
+                        warn!("Response cache missed {missed} registry events, clearing");
+                        cache.clear();
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        }
+    }
+}
+
+/// Respond with the JSON `build` makes, through `cache` when there is one,
+/// honouring `If-None-Match` either way
+pub async fn cached_json<T, E, F, Fut>(
+    cache: Option<&ResponseCache>,
+    key: &str,
+    headers: &HeaderMap,
+    build: F,
+) -> Result<Response, E>
+where
+    T: Serialize,
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let response = match cache {
+        Some(cache) => cache.get_or_build(key, build).await?,
+        None => Arc::new(CachedResponse::json(&build().await?)),
+    };
+    Ok(response.respond(headers))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sinks::openai::{OpenAIConfig, create_sink};
+    use gate_core::router::types::SinkHealth;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_cache_serves_etags_and_drops_on_registry_changes() {
+        let cache = Arc::new(ResponseCache::new(Duration::from_secs(60)));
+        let builds = AtomicUsize::new(0);
+        let build = || async {
+            builds.fetch_add(1, Ordering::SeqCst);
+            Ok::<_, ()>(serde_json::json!({"data": ["a"]}))
+        };
+
+        let first = cache.get_or_build(MODELS_KEY, build).await.unwrap();
+        let second = cache.get_or_build(MODELS_KEY, build).await.unwrap();
+        assert_eq!(first, second);
+        assert_eq!(builds.load(Ordering::SeqCst), 1);
+
+        let mut headers = HeaderMap::new();
+        assert_eq!(first.respond(&headers).status(), StatusCode::OK);
+        headers.insert(
+            header::IF_NONE_MATCH,
+            HeaderValue::from_str(&format!("\"other\", W/{}", first.etag)).unwrap(),
+        );
+        assert_eq!(first.respond(&headers).status(), StatusCode::NOT_MODIFIED);
+
+        let registry = SinkRegistry::new();
+        let follow = tokio::spawn(cache.follow(&registry));
+        cache.get_or_build("health:providers", build).await.unwrap();
+        registry.publish_health(
+            "sink",
+            SinkHealth {
+                healthy: false,
+                latency_ms: None,
+                error_rate: 1.0,
+                last_error: None,
+                last_check: chrono::Utc::now(),
+            },
+        );
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(cache.entries.lock().unwrap().contains_key(MODELS_KEY));
+        assert!(
+            !cache
+                .entries
+                .lock()
+                .unwrap()
+                .contains_key("health:providers")
+        );
+
+        let sink = create_sink(OpenAIConfig {
+            api_key: None,
+            base_url: None,
+            models: None,
+            timeout_seconds: None,
+            sink_id: None,
+        })
+        .unwrap();
+        registry.register("sink".to_string(), Arc::new(sink)).await;
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(cache.entries.lock().unwrap().is_empty());
+        drop(registry);
+        follow.await.unwrap();
+    }
+}
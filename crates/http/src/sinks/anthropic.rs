@@ -2,9 +2,8 @@
 
 use super::http_sink::{HttpSink, HttpSinkConfig, Provider};
 use crate::sinks::DEFAULT_SINK_TIMEOUT_SECS;
-use chrono::{DateTime, Utc};
 use gate_core::Result;
-use gate_core::router::types::{CostStructure, Protocol, SinkCapabilities};
+use gate_core::router::types::{CostStructure, ModelLimits, Protocol, SinkCapabilities};
 use http::header::{AUTHORIZATION, USER_AGENT};
 use http::{HeaderName, HeaderValue};
 use rust_decimal::Decimal;
@@ -61,7 +60,7 @@ pub async fn create_sink(config: AnthropicConfig) -> Result<HttpSink> {
         provider: Provider::Anthropic,
         base_url,
         api_key: config.api_key,
-        models,
+        models: models.iter().map(|(id, _)| id.clone()).collect(),
         timeout,
         max_retries: 3,
         accepted_protocols: vec![Protocol::Anthropic],
@@ -69,6 +68,7 @@ pub async fn create_sink(config: AnthropicConfig) -> Result<HttpSink> {
             supports_streaming: true,
             supports_batching: false,
             supports_tools: true,
+            supports_structured_output: true,
             max_context_length: Some(200000),
             modalities: vec!["text".to_string(), "image".to_string()],
         },
@@ -80,7 +80,7 @@ pub async fn create_sink(config: AnthropicConfig) -> Result<HttpSink> {
         }),
     };
 
-    HttpSink::new(sink_config)
+    Ok(HttpSink::new(sink_config)?.with_model_limits(models))
 }
 
 /// Create a fallback Anthropic sink with no API key and default base URL.
@@ -104,12 +104,24 @@ struct AnthropicModelsResponse {
 #[derive(Deserialize)]
 struct AnthropicModelItem {
     id: String,
-    _display_name: String,
-    _created_at: DateTime<Utc>,
+    /// Context window, where the API reports it
+    max_input_tokens: Option<u32>,
+    /// Output limit, where the API reports it
+    max_tokens: Option<u32>,
 }
 
-/// Fetch list of models from Anthropic
-pub async fn fetch_models(base_url: &str, api_key: &str) -> Result<Vec<String>> {
+impl AnthropicModelItem {
+    fn into_limits(self) -> (String, ModelLimits) {
+        let limits = ModelLimits {
+            context_window: self.max_input_tokens,
+            max_output_tokens: self.max_tokens,
+        };
+        (self.id, limits)
+    }
+}
+
+/// Fetch list of models from Anthropic, with the token limits it reports
+pub async fn fetch_models(base_url: &str, api_key: &str) -> Result<Vec<(String, ModelLimits)>> {
     // Build URL safely: base_url + /v1/models
     let mut url = Url::parse(base_url)
         .map_err(|e| gate_core::Error::Internal(format!("Invalid base_url: {e}")))?;
@@ -146,5 +158,51 @@ pub async fn fetch_models(base_url: &str, api_key: &str) -> Result<Vec<String>>
         gate_core::Error::Internal(format!("Failed to parse Anthropic models: {e}"))
     })?;
 
-    Ok(payload.data.into_iter().map(|m| m.id).collect())
+    Ok(payload
+        .data
+        .into_iter()
+        .map(AnthropicModelItem::into_limits)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_model_list_reports_limits() {
+        let payload: AnthropicModelsResponse = serde_json::from_value(serde_json::json!({
+            "data": [
+                {
+                    "type": "model",
+                    "id": "claude-sonnet-4-20250514",
+                    "display_name": "Claude Sonnet 4",
+                    "created_at": "2025-05-22T00:00:00Z",
+                    "max_input_tokens": 200000,
+                    "max_tokens": 64000
+                },
+                {
+                    "type": "model",
+                    "id": "claude-3-haiku-20240307",
+                    "display_name": "Claude Haiku 3",
+                    "created_at": "2024-03-07T00:00:00Z"
+                }
+            ],
+            "has_more": false
+        }))
+        .unwrap();
+        let models: Vec<_> = payload
+            .data
+            .into_iter()
+            .map(AnthropicModelItem::into_limits)
+            .collect();
+        assert_eq!(
+            models[0].1,
+            ModelLimits {
+                context_window: Some(200_000),
+                max_output_tokens: Some(64_000),
+            }
+        );
+        assert_eq!(models[1].1, ModelLimits::default());
+    }
 }
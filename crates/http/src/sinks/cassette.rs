@@ -0,0 +1,302 @@
+//! Record and replay of upstream HTTP interactions
+//!
+//! A [`Cassette`] attached to an [`HttpSink`](super::HttpSink) either records
+//! every request the sink sends along with the provider's response, or
+//! answers requests from a previous recording without touching the network.
+//! Credentials are scrubbed before anything is written, so cassettes can be
+//! committed and replayed in tests of connectors and protocol conversions.
+
+use gate_core::{Error, Result};
+use reqwest::{Client, Request, Response};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use url::Url;
+
+/// Placeholder written in place of secrets
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Headers whose values are never written to a cassette
+const SENSITIVE_HEADERS: &[&str] = &[
+    "authorization",
+    "x-api-key",
+    "cookie",
+    "set-cookie",
+    "openai-organization",
+    "openai-project",
+    "anthropic-organization-id",
+];
+
+/// Query parameters and body fields holding secrets
+const SENSITIVE_FIELDS: &[&str] = &["key", "api_key", "access_token", "refresh_token"];
+
+/// Response headers describing the wire encoding, which no longer applies to
+/// the decoded body that is recorded
+const TRANSPORT_HEADERS: &[&str] = &["content-encoding", "content-length", "transfer-encoding"];
+
+/// Whether a cassette records live traffic or replays it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CassetteMode {
+    Record,
+    Replay,
+}
+
+/// One recorded request and the response it received
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub method: String,
+    pub url: String,
+    #[serde(default)]
+    pub request_headers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub request_body: JsonValue,
+    pub status: u16,
+    #[serde(default)]
+    pub response_headers: BTreeMap<String, String>,
+    pub response_body: String,
+}
+
+impl Interaction {
+    fn matches(&self, method: &str, url: &str, body: &JsonValue) -> bool {
+        self.method == method && self.url == url && &self.request_body == body
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+/// A file of recorded interactions
+#[derive(Debug)]
+pub struct Cassette {
+    path: PathBuf,
+    mode: CassetteMode,
+    interactions: Mutex<Vec<Interaction>>,
+}
+
+impl Cassette {
+    /// Start a new recording, replacing any existing cassette at `path`
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            mode: CassetteMode::Record,
+            interactions: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Load a recording to replay
+    pub fn replay(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let contents = std::fs::read_to_string(&path).map_err(|e| {
+            Error::Internal(format!("Failed to read cassette {}: {e}", path.display()))
+        })?;
+        let file: CassetteFile = serde_json::from_str(&contents)
+            .map_err(|e| Error::Internal(format!("Invalid cassette {}: {e}", path.display())))?;
+        Ok(Self {
+            path,
+            mode: CassetteMode::Replay,
+            interactions: Mutex::new(file.interactions),
+        })
+    }
+
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Send `request` through the cassette
+    pub async fn send(&self, client: &Client, request: Request) -> Result<Response> {
+        match self.mode {
+            CassetteMode::Record => self.send_and_record(client, request).await,
+            CassetteMode::Replay => self.replay_request(&request),
+        }
+    }
+
+    async fn send_and_record(&self, client: &Client, request: Request) -> Result<Response> {
+        let method = request.method().to_string();
+        let url = scrub_url(request.url());
+        let request_headers = scrub_headers(request.headers(), &[]);
+        let request_body = request_body(&request);
+
+        let response = client
+            .execute(request)
+            .await
+            .map_err(|e| Error::ServiceUnavailable(format!("Failed to send request: {e}")))?;
+        let status = response.status().as_u16();
+        let response_headers = scrub_headers(response.headers(), TRANSPORT_HEADERS);
+        let response_body = response
+            .text()
+            .await
+            .map_err(|e| Error::Internal(format!("Failed to read response: {e}")))?;
+
+        let interaction = Interaction {
+            method,
+            url,
+            request_headers,
+            request_body,
+            status,
+            response_headers,
+            response_body,
+        };
+        let response = to_response(&interaction)?;
+        let contents = {
+            let mut interactions = self.lock()?;
+            interactions.push(interaction);
+            serde_json::to_string_pretty(&CassetteFile {
+                interactions: interactions.clone(),
+            })
+            .map_err(|e| Error::Internal(format!("Failed to serialize cassette: {e}")))?
+        };
+        if let Some(dir) = self.path.parent() {
+            tokio::fs::create_dir_all(dir).await.ok();
+        }
+        tokio::fs::write(&self.path, contents).await.map_err(|e| {
+            Error::Internal(format!(
+                "Failed to write cassette {}: {e}",
+                self.path.display()
+            ))
+        })?;
+        Ok(response)
+    }
+
+    /// Answer with the first unused interaction recorded for the same request
+    fn replay_request(&self, request: &Request) -> Result<Response> {
+        let method = request.method().to_string();
+        let url = scrub_url(request.url());
+        let body = request_body(request);
+
+        let mut interactions = self.lock()?;
+        let index = interactions
+            .iter()
+            .position(|i| i.matches(&method, &url, &body))
+            .ok_or_else(|| {
+                Error::Internal(format!(
+                    "No recorded interaction for {method} {url} in {}",
+                    self.path.display()
+                ))
+            })?;
+        to_response(&interactions.remove(index))
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, Vec<Interaction>>> {
+        self.interactions
+            .lock()
+            .map_err(|_| Error::Internal("Cassette lock poisoned".to_string()))
+    }
+}
+
+fn scrub_url(url: &Url) -> String {
+    let mut url = url.clone();
+    if url.query().is_some() {
+        let pairs: Vec<(String, String)> = url
+            .query_pairs()
+            .map(|(k, v)| {
+                let value = if SENSITIVE_FIELDS.contains(&k.as_ref()) {
+                    REDACTED.to_string()
+                } else {
+                    v.into_owned()
+                };
+                (k.into_owned(), value)
+            })
+            .collect();
+        url.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+    url.to_string()
+}
+
+fn scrub_headers(headers: &http::HeaderMap, skip: &[&str]) -> BTreeMap<String, String> {
+    headers
+        .iter()
+        .filter(|(name, _)| !skip.contains(&name.as_str()))
+        .filter_map(|(name, value)| {
+            let value = if SENSITIVE_HEADERS.contains(&name.as_str()) {
+                REDACTED
+            } else {
+                value.to_str().ok()?
+            };
+            Some((name.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// Replace secret fields anywhere in a JSON body
+fn scrub_json(value: &mut JsonValue) {
+    match value {
+        JsonValue::Object(obj) => {
+            for (key, item) in obj.iter_mut() {
+                if SENSITIVE_FIELDS.contains(&key.as_str()) && item.is_string() {
+                    *item = JsonValue::String(REDACTED.to_string());
+                } else {
+                    scrub_json(item);
+                }
+            }
+        }
+        JsonValue::Array(items) => items.iter_mut().for_each(scrub_json),
+        _ => {}
+    }
+}
+
+fn request_body(request: &Request) -> JsonValue {
+    let Some(bytes) = request.body().and_then(|b| b.as_bytes()) else {
+        return JsonValue::Null;
+    };
+    let mut body = serde_json::from_slice(bytes)
+        .unwrap_or_else(|_| JsonValue::String(String::from_utf8_lossy(bytes).into_owned()));
+    scrub_json(&mut body);
+    body
+}
+
+fn to_response(interaction: &Interaction) -> Result<Response> {
+    let mut builder = http::Response::builder().status(interaction.status);
+    for (name, value) in &interaction.response_headers {
+        builder = builder.header(name, value);
+    }
+    let response = builder
+        .body(interaction.response_body.clone())
+        .map_err(|e| Error::Internal(format!("Invalid recorded response: {e}")))?;
+    Ok(Response::from(response))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_scrub_url_redacts_secret_query_params() {
+        let url = Url::parse("https://example.com/v1/models?key=secret&limit=5").unwrap();
+        assert_eq!(
+            scrub_url(&url),
+            "https://example.com/v1/models?key=%5BREDACTED%5D&limit=5"
+        );
+    }
+
+    #[test]
+    fn test_scrub_json_redacts_nested_secrets() {
+        let mut body =
+            json!({"model": "m", "auth": {"api_key": "sk-1"}, "items": [{"access_token": "t"}]});
+        scrub_json(&mut body);
+        assert_eq!(body["auth"]["api_key"], REDACTED);
+        assert_eq!(body["items"][0]["access_token"], REDACTED);
+        assert_eq!(body["model"], "m");
+    }
+
+    #[test]
+    fn test_scrub_headers_redacts_credentials() {
+        let mut headers = http::HeaderMap::new();
+        headers.insert("authorization", "Bearer sk-1".parse().unwrap());
+        headers.insert("content-type", "application/json".parse().unwrap());
+        headers.insert("content-encoding", "gzip".parse().unwrap());
+        let scrubbed = scrub_headers(&headers, TRANSPORT_HEADERS);
+        assert_eq!(scrubbed["authorization"], REDACTED);
+        assert_eq!(scrubbed["content-type"], "application/json");
+        assert!(!scrubbed.contains_key("content-encoding"));
+    }
+}
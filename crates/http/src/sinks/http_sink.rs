@@ -5,22 +5,28 @@ use crate::sinks::anthropic::{
     CLAUDE_CODE_USER_AGENT, X_API_KEY, X_APP, X_APP_VALUE,
 };
 
+use super::params::{PARAMETER_WARNINGS_KEY, map_params, rules_for};
+use super::signing::RequestSigner;
 use super::sse_parser::parse_sse;
+use crate::middleware::correlation::TRACEPARENT_HEADER;
 use async_trait::async_trait;
 use futures::StreamExt;
+use gate_core::router::ResponseMetadata;
+use gate_core::router::buffers::BufferBudget;
 use gate_core::router::sink::{RequestContext, ResponseStream, Sink, SinkDescription};
 use gate_core::router::types::{
-    CostStructure, ModelList, Protocol, RequestStream, ResponseChunk, SinkCapabilities, SinkHealth,
-    StopReason,
+    CostStructure, ModelLimits, ModelList, Protocol, RequestStream, ResponseChunk,
+    SinkCapabilities, SinkHealth, StopReason,
 };
-use gate_core::{Error, Result};
+use gate_core::{Error, ErrorClass, Result, UpstreamError};
 use http::header::{AUTHORIZATION, USER_AGENT};
 use http::{HeaderName, HeaderValue, StatusCode};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
+use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use url::{Url, form_urlencoded};
 
@@ -60,11 +66,33 @@ pub struct HttpSinkConfig {
     pub cost_structure: Option<CostStructure>,
 }
 
+/// Endpoint used for active health checks; cheap and supported by both providers
+const HEALTH_CHECK_ENDPOINT: &str = "v1/models";
+
+/// Anthropic token counting endpoint
+const COUNT_TOKENS_ENDPOINT: &str = "v1/messages/count_tokens";
+
+/// Messages fields accepted by the token counting endpoint; it rejects the rest
+const COUNT_TOKENS_FIELDS: &[&str] = &[
+    "model",
+    "messages",
+    "system",
+    "tools",
+    "tool_choice",
+    "thinking",
+    "mcp_servers",
+];
+
 /// HTTP-based sink for external LLM providers
 pub struct HttpSink {
     config: HttpSinkConfig,
     client: Client,
     health: Arc<RwLock<SinkHealth>>,
+    signer: Option<Arc<dyn RequestSigner>>,
+    /// Token limits by model, as the provider's model list reports them
+    model_limits: HashMap<String, ModelLimits>,
+    #[cfg(feature = "cassettes")]
+    cassette: Option<Arc<super::cassette::Cassette>>,
 }
 
 impl HttpSink {
@@ -87,9 +115,35 @@ impl HttpSink {
             config,
             client,
             health,
+            signer: None,
+            model_limits: HashMap::new(),
+            #[cfg(feature = "cassettes")]
+            cassette: None,
         })
     }
 
+    /// Sign every request sent upstream with `signer`
+    pub fn with_signer(mut self, signer: Arc<dyn RequestSigner>) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    /// Report `limits` for the models they name
+    pub fn with_model_limits(
+        mut self,
+        limits: impl IntoIterator<Item = (String, ModelLimits)>,
+    ) -> Self {
+        self.model_limits.extend(limits);
+        self
+    }
+
+    /// Record upstream traffic to, or replay it from, `cassette`
+    #[cfg(feature = "cassettes")]
+    pub fn with_cassette(mut self, cassette: Arc<super::cassette::Cassette>) -> Self {
+        self.cassette = Some(cassette);
+        self
+    }
+
     /// Get the appropriate authorization header for the provider
     fn auth_header(&self) -> Option<(HeaderName, HeaderValue)> {
         self.config
@@ -183,6 +237,7 @@ impl HttpSink {
             (Protocol::OpenAICompletions, Provider::OpenAI) => Ok("/v1/completions"),
             (Protocol::OpenAIResponses, Provider::OpenAI) => Ok("/v1/responses"),
             (Protocol::OpenAIResponses, Provider::OpenAICodex) => Ok("/responses"),
+            (Protocol::OpenAIEmbeddings, Provider::OpenAI) => Ok("/v1/embeddings"),
             _ => Err(Error::InvalidRoutingConfig(format!(
                 "Provider {} doesn't support protocol {:?}",
                 self.config.provider, protocol
@@ -216,14 +271,44 @@ impl HttpSink {
         ctx: &RequestContext,
         mut request_stream: RequestStream,
     ) -> Result<ResponseStream> {
-        let request = self.get_first_request(&mut request_stream).await?;
+        let mut request = self.get_first_request(&mut request_stream).await?;
         let protocol = request_stream.protocol();
         let url = self.build_url(ctx, protocol)?;
 
+        let warnings = map_params(&mut request, rules_for(&self.config.provider));
+        for warning in &warnings {
+            debug!("{}: {warning}", self.config.id);
+        }
+
         let req = self.prepare_http_request(url, &request, ctx);
-        let response = self.send_http_request(req).await?;
-        let response = self.validate_response_status(response).await?;
-        self.process_response(response, protocol).await
+        let started = Instant::now();
+        let response = match self.send_http_request(req).await {
+            Ok(response) => self.validate_response_status(response).await,
+            Err(e) => Err(e),
+        };
+        self.record_outcome(started, response.as_ref().err()).await;
+        let stream = self
+            .process_response(response?, protocol, &ctx.response)
+            .await?;
+        if warnings.is_empty() {
+            return Ok(stream);
+        }
+        Ok(with_parameter_warnings(stream, warnings).await)
+    }
+
+    /// Update passive health from a request outcome. Errors caused by the
+    /// caller (bad requests, unknown models) say nothing about the provider.
+    async fn record_outcome(&self, started: Instant, error: Option<&Error>) {
+        let provider_fault = match error {
+            None => None,
+            Some(e) if e.class().is_some_and(ErrorClass::is_provider_fault) => Some(e.to_string()),
+            Some(_) => return,
+        };
+        let latency_ms = started.elapsed().as_millis() as u64;
+        self.health
+            .write()
+            .await
+            .record(Some(latency_ms), provider_fault);
     }
 
     /// Get and validate the first request from the stream
@@ -263,13 +348,18 @@ impl HttpSink {
                 || name == CONTENT_TYPE
                 || name == AUTHORIZATION
                 || name == X_API_KEY
+                || name == TRACEPARENT_HEADER
             {
                 continue;
             }
             req = req.header(name, value);
         }
 
-        req
+        // The upstream call is a child of this request in the same trace
+        req.header(
+            TRACEPARENT_HEADER,
+            ctx.correlation_id.child().to_traceparent(),
+        )
     }
 
     /// Send the HTTP request and handle network errors
@@ -277,7 +367,22 @@ impl HttpSink {
         &self,
         request: reqwest::RequestBuilder,
     ) -> Result<reqwest::Response> {
-        request.send().await.map_err(|e| {
+        let mut request = request.build().map_err(|e| {
+            Error::Internal(format!(
+                "Failed to build request to {}: {e}",
+                self.config.provider
+            ))
+        })?;
+        if let Some(signer) = &self.signer {
+            signer.sign(&mut request);
+        }
+
+        #[cfg(feature = "cassettes")]
+        if let Some(cassette) = &self.cassette {
+            return cassette.send(&self.client, request).await;
+        }
+
+        self.client.execute(request).await.map_err(|e| {
             Error::ServiceUnavailable(format!(
                 "Failed to send request to {}: {}",
                 self.config.provider, e
@@ -297,15 +402,21 @@ impl HttpSink {
 
         let code =
             StatusCode::from_u16(status.as_u16()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .map(Duration::from_secs);
 
         let error_body = response
             .text()
             .await
             .unwrap_or_else(|_| "Unable to read error".to_string());
 
-        Err(Error::Rejected(
-            code,
-            format!("{} upstream error: {}", self.config.provider, error_body),
+        Err(Error::Upstream(
+            UpstreamError::new(self.config.provider.to_string(), code, error_body)
+                .with_retry_after(retry_after),
         ))
     }
 
@@ -314,15 +425,20 @@ impl HttpSink {
         &self,
         response: reqwest::Response,
         protocol: Protocol,
+        metadata: &ResponseMetadata,
     ) -> Result<ResponseStream> {
         let headers = self.extract_response_headers(&response);
+        metadata.publish(|info| {
+            info.observe_headers(headers.iter().map(|(k, v)| (k.as_str(), v.as_str())))
+        });
         let is_streaming = self.is_streaming_response(&response, protocol);
 
         if is_streaming {
-            self.process_streaming_response(response, protocol, headers)
+            self.process_streaming_response(response, protocol, headers, metadata.clone())
                 .await
         } else {
-            self.process_non_streaming_response(response, headers).await
+            self.process_non_streaming_response(response, headers, metadata)
+                .await
         }
     }
 
@@ -342,8 +458,10 @@ impl HttpSink {
 
     /// Check if response is a streaming response
     fn is_streaming_response(&self, response: &reqwest::Response, protocol: Protocol) -> bool {
-        // OpenAI Responses are always treated as streaming
-        if protocol == Protocol::OpenAIResponses {
+        // The Codex backend only streams Responses, whatever the request asked for
+        if protocol == Protocol::OpenAIResponses
+            && matches!(self.config.provider, Provider::OpenAICodex)
+        {
             return true;
         }
 
@@ -363,8 +481,9 @@ impl HttpSink {
         response: reqwest::Response,
         protocol: Protocol,
         headers: std::collections::HashMap<String, String>,
+        metadata: ResponseMetadata,
     ) -> Result<ResponseStream> {
-        let sse_stream = self.parse_sse_stream(response, protocol).await?;
+        let sse_stream = self.parse_sse_stream(response, protocol, metadata).await?;
 
         // Prepend headers chunk to the stream
         let stream = futures::stream::once(async move { Ok(ResponseChunk::Headers(headers)) })
@@ -373,16 +492,24 @@ impl HttpSink {
         Ok(Box::pin(stream))
     }
 
-    /// Process a non-streaming response
+    /// Process a non-streaming response, read whole within the buffer budget
     async fn process_non_streaming_response(
         &self,
-        response: reqwest::Response,
+        mut response: reqwest::Response,
         headers: std::collections::HashMap<String, String>,
+        metadata: &ResponseMetadata,
     ) -> Result<ResponseStream> {
-        let text = response
-            .text()
+        let mut buffer = BufferBudget::shared().buffer()?;
+        let mut body = Vec::new();
+        while let Some(chunk) = response
+            .chunk()
             .await
-            .map_err(|e| Error::Internal(format!("Failed to read response: {e}")))?;
+            .map_err(|e| Error::Internal(format!("Failed to read response: {e}")))?
+        {
+            buffer.reserve(chunk.len())?;
+            body.extend_from_slice(&chunk);
+        }
+        let text = String::from_utf8_lossy(&body).into_owned();
 
         debug!("Non-streaming response: {}", text);
 
@@ -392,12 +519,21 @@ impl HttpSink {
             // Fallback: return raw text as content rather than failing
             JsonValue::String(text)
         };
+        let mut filtered = false;
+        metadata.publish(|info| {
+            info.observe_body(&content);
+            filtered = info.observe_stop(&content);
+        });
 
         let chunks = vec![
             Ok(ResponseChunk::Headers(headers)),
             Ok(ResponseChunk::Content(content)),
             Ok(ResponseChunk::Stop {
-                reason: StopReason::Complete,
+                reason: if filtered {
+                    StopReason::ContentFilter
+                } else {
+                    StopReason::Complete
+                },
                 error: None,
                 cost: None,
             }),
@@ -411,39 +547,58 @@ impl HttpSink {
         &self,
         response: reqwest::Response,
         _protocol: Protocol,
+        metadata: ResponseMetadata,
     ) -> Result<ResponseStream> {
         let stream = response.bytes_stream();
         let sse_stream = parse_sse(stream);
 
         let provider = self.config.provider.clone();
+        // Frames are parsed for the model only until one names it, and for
+        // a stop reason only when they mention a content filter
+        let mut model_seen = false;
+        let mut filtered = false;
         let stream = sse_stream.map(move |result| {
             match result {
                 Ok(event) => {
                     // Handle special [DONE] message
                     if event.data == "[DONE]" {
                         return Ok(ResponseChunk::Stop {
-                            reason: StopReason::Complete,
+                            reason: if filtered {
+                                StopReason::ContentFilter
+                            } else {
+                                StopReason::Complete
+                            },
                             error: None,
                             cost: None,
                         });
                     }
 
-                    // Try to parse as JSON
-                    if let Ok(json) = serde_json::from_str::<JsonValue>(&event.data) {
-                        // Check if this is an error response
-                        if let Some(error) = json.get("error") {
-                            return Ok(ResponseChunk::Stop {
-                                reason: StopReason::Error,
-                                error: Some(error.to_string()),
-                                cost: None,
-                            });
-                        }
-
-                        return Ok(ResponseChunk::Content(json));
+                    if !model_seen && let Ok(frame) = serde_json::from_str::<JsonValue>(&event.data)
+                    {
+                        metadata.publish(|info| model_seen = info.observe_body(&frame));
+                    }
+                    if !filtered
+                        && (event.data.contains("content_filter") || event.data.contains("refusal"))
+                        && let Ok(frame) = serde_json::from_str::<JsonValue>(&event.data)
+                    {
+                        metadata.publish(|info| filtered = info.observe_stop(&frame));
                     }
 
-                    // If not JSON, return as string
-                    Ok(ResponseChunk::Content(JsonValue::String(event.data)))
+                    // Only the error field is materialized; other fields are
+                    // skipped, and the frame itself is forwarded as is
+                    match serde_json::from_str::<ErrorProbe>(&event.data) {
+                        Ok(ErrorProbe { error: Some(error) }) => Ok(ResponseChunk::Stop {
+                            reason: StopReason::Error,
+                            error: Some(error.to_string()),
+                            cost: None,
+                        }),
+                        Ok(_) => Ok(ResponseChunk::Raw {
+                            event: event.event,
+                            data: bytes::Bytes::from(event.data),
+                        }),
+                        // If not JSON, return as string
+                        Err(_) => Ok(ResponseChunk::Content(JsonValue::String(event.data))),
+                    }
                 }
                 Err(e) => {
                     error!("SSE parse error from {}: {}", provider, e);
@@ -470,6 +625,34 @@ impl HttpSink {
     }
 }
 
+/// The part of an SSE frame checked before it is forwarded
+#[derive(Deserialize)]
+struct ErrorProbe {
+    #[serde(default)]
+    error: Option<JsonValue>,
+}
+
+/// Report parameter mapping warnings as metadata right after the headers chunk
+async fn with_parameter_warnings(
+    mut stream: ResponseStream,
+    warnings: Vec<String>,
+) -> ResponseStream {
+    let head = stream.next().await;
+    let metadata = ResponseChunk::Metadata(
+        [(
+            PARAMETER_WARNINGS_KEY.to_string(),
+            serde_json::json!(warnings),
+        )]
+        .into_iter()
+        .collect(),
+    );
+    Box::pin(
+        futures::stream::iter(head)
+            .chain(futures::stream::once(async move { Ok(metadata) }))
+            .chain(stream),
+    )
+}
+
 #[async_trait]
 impl Sink for HttpSink {
     async fn describe(&self) -> SinkDescription {
@@ -487,10 +670,70 @@ impl Sink for HttpSink {
     }
 
     async fn probe(&self) -> SinkHealth {
-        // In a real implementation, we might ping the provider's API
+        // Passive health, updated from real request outcomes and checks
         self.health.read().await.clone()
     }
 
+    async fn check(&self) -> SinkHealth {
+        // Without a configured key there is nothing to authenticate the check with
+        let Some((header_name, header_value)) = self.auth_header() else {
+            return self.probe().await;
+        };
+        let url = match Url::parse(&self.config.base_url)
+            .and_then(|base| base.join(HEALTH_CHECK_ENDPOINT))
+        {
+            Ok(url) => url,
+            Err(e) => {
+                let mut health = self.health.write().await;
+                health.record(None, Some(format!("Invalid base_url: {e}")));
+                return health.clone();
+            }
+        };
+
+        let mut request = self.client.get(url).header(header_name, header_value);
+        for (name, value) in self.provider_headers() {
+            request = request.header(name, value);
+        }
+
+        let started = Instant::now();
+        let response = match self.send_http_request(request).await {
+            Ok(response) => self.validate_response_status(response).await,
+            Err(e) => Err(e),
+        };
+        self.record_outcome(started, response.as_ref().err()).await;
+        self.probe().await
+    }
+
+    fn model_limits(&self, model: &str) -> Option<ModelLimits> {
+        self.model_limits.get(model).copied()
+    }
+
+    async fn count_tokens(&self, ctx: &RequestContext, request: &JsonValue) -> Result<Option<u64>> {
+        if !matches!(self.config.provider, Provider::Anthropic) {
+            return Ok(None);
+        }
+
+        let url = Url::parse(&self.config.base_url)
+            .and_then(|base| base.join(COUNT_TOKENS_ENDPOINT))
+            .map_err(|e| Error::Internal(format!("Invalid base_url: {e}")))?;
+        let body: serde_json::Map<String, JsonValue> = request
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter(|(key, _)| COUNT_TOKENS_FIELDS.contains(&key.as_str()))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+
+        let req = self.prepare_http_request(url, &JsonValue::Object(body), ctx);
+        let response = self.send_http_request(req).await?;
+        let response = self.validate_response_status(response).await?;
+        let body: JsonValue = response
+            .json()
+            .await
+            .map_err(|e| Error::Internal(format!("Invalid count_tokens response: {e}")))?;
+        Ok(body.get("input_tokens").and_then(JsonValue::as_u64))
+    }
+
     async fn execute(
         &self,
         ctx: &RequestContext,
@@ -548,6 +791,8 @@ mod tests {
             query: None,
             trace_id: None,
             metadata: Default::default(),
+            response: Default::default(),
+            extensions: Default::default(),
         }
     }
 
@@ -566,6 +811,7 @@ mod tests {
                 supports_streaming: true,
                 supports_batching: false,
                 supports_tools: true,
+                supports_structured_output: false,
                 max_context_length: None,
                 modalities: vec!["text".to_string()],
             },
@@ -608,6 +854,7 @@ mod tests {
                 supports_streaming: true,
                 supports_batching: false,
                 supports_tools: true,
+                supports_structured_output: false,
                 max_context_length: None,
                 modalities: vec!["text".to_string()],
             },
@@ -624,4 +871,43 @@ mod tests {
             "https://api.anthropic.com/v1/messages?beta=true&foo=bar"
         );
     }
+    #[tokio::test]
+    async fn test_upstream_requests_continue_the_trace() {
+        let sink = HttpSink::new(HttpSinkConfig {
+            id: "provider://anthropic".into(),
+            provider: Provider::Anthropic,
+            base_url: "https://api.anthropic.com".into(),
+            api_key: None,
+            models: vec![],
+            timeout: std::time::Duration::from_secs(5),
+            max_retries: 0,
+            accepted_protocols: vec![Protocol::Anthropic],
+            capabilities: SinkCapabilities {
+                supports_streaming: true,
+                supports_batching: false,
+                supports_tools: true,
+                supports_structured_output: false,
+                max_context_length: None,
+                modalities: vec!["text".to_string()],
+            },
+            cost_structure: None,
+        })
+        .expect("create sink");
+
+        let client_parent = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        let mut ctx = make_ctx(vec![(TRACEPARENT_HEADER, client_parent)]);
+        ctx.correlation_id = CorrelationId::from_string(client_parent);
+
+        let url = sink.build_url(&ctx, Protocol::Anthropic).expect("url");
+        let request = sink
+            .prepare_http_request(url, &serde_json::json!({}), &ctx)
+            .build()
+            .expect("request");
+        let sent = request.headers().get_all(TRACEPARENT_HEADER);
+        assert_eq!(sent.iter().count(), 1);
+        let sent =
+            CorrelationId::from_traceparent(sent.iter().next().unwrap().to_str().unwrap()).unwrap();
+        assert_eq!(sent.trace_id(), ctx.correlation_id.trace_id());
+        assert_ne!(sent.span_id(), ctx.correlation_id.span_id());
+    }
 }
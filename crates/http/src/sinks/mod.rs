@@ -1,9 +1,13 @@
 //! HTTP-based sink implementations for external providers
 
 pub mod anthropic;
+#[cfg(feature = "cassettes")]
+pub mod cassette;
 pub mod http_sink;
 pub mod openai;
+pub mod params;
 pub mod response_converter;
+pub mod signing;
 pub mod sse_parser;
 
 pub use http_sink::HttpSink;
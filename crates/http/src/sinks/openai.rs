@@ -48,11 +48,13 @@ pub fn create_sink(config: OpenAIConfig) -> Result<HttpSink> {
             Protocol::OpenAIMessages,
             Protocol::OpenAICompletions,
             Protocol::OpenAIResponses,
+            Protocol::OpenAIEmbeddings,
         ],
         capabilities: SinkCapabilities {
             supports_streaming: true,
             supports_batching: false,
             supports_tools: true,
+            supports_structured_output: true,
             max_context_length: Some(128000), // GPT-4 Turbo supports up to 128k
             modalities: vec!["text".to_string(), "image".to_string()], // GPT-4V supports vision
         },
@@ -101,6 +103,7 @@ pub fn create_codex_fallback_sink() -> Result<HttpSink> {
             supports_streaming: true,
             supports_batching: false,
             supports_tools: true,
+            supports_structured_output: true,
             max_context_length: None,
             modalities: vec!["text".to_string()],
         },
@@ -0,0 +1,197 @@
+//! Per-provider request parameter mapping
+//!
+//! Providers disagree on which sampling parameters they accept and on their
+//! ranges. Each provider has a table of rules applied to the request body
+//! before it is sent; every change made is reported as a warning.
+
+use super::http_sink::Provider;
+use serde_json::{Map, Value as JsonValue, json};
+
+/// Metadata key the warnings are reported under
+pub const PARAMETER_WARNINGS_KEY: &str = "parameter_warnings";
+
+/// A single mapping rule
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParamRule {
+    /// Limit a numeric parameter to `[min, max]`
+    Clamp {
+        name: &'static str,
+        min: f64,
+        max: f64,
+    },
+    /// Move a parameter to the provider's name for it, unless already set
+    Rename {
+        from: &'static str,
+        to: &'static str,
+    },
+    /// Remove a parameter the provider rejects
+    Drop { name: &'static str },
+    /// Turn a single value into the one-element array the provider expects
+    Wrap { name: &'static str },
+}
+
+const ANTHROPIC_RULES: &[ParamRule] = &[
+    ParamRule::Clamp {
+        name: "temperature",
+        min: 0.0,
+        max: 1.0,
+    },
+    ParamRule::Clamp {
+        name: "top_p",
+        min: 0.0,
+        max: 1.0,
+    },
+    ParamRule::Rename {
+        from: "stop",
+        to: "stop_sequences",
+    },
+    ParamRule::Wrap {
+        name: "stop_sequences",
+    },
+    ParamRule::Drop {
+        name: "frequency_penalty",
+    },
+    ParamRule::Drop {
+        name: "presence_penalty",
+    },
+    ParamRule::Drop { name: "seed" },
+    ParamRule::Drop { name: "logit_bias" },
+    ParamRule::Drop { name: "n" },
+];
+
+const OPENAI_RULES: &[ParamRule] = &[
+    ParamRule::Clamp {
+        name: "temperature",
+        min: 0.0,
+        max: 2.0,
+    },
+    ParamRule::Clamp {
+        name: "top_p",
+        min: 0.0,
+        max: 1.0,
+    },
+    ParamRule::Clamp {
+        name: "frequency_penalty",
+        min: -2.0,
+        max: 2.0,
+    },
+    ParamRule::Clamp {
+        name: "presence_penalty",
+        min: -2.0,
+        max: 2.0,
+    },
+    ParamRule::Rename {
+        from: "stop_sequences",
+        to: "stop",
+    },
+    ParamRule::Drop { name: "top_k" },
+];
+
+/// Rules for a provider; custom endpoints are passed through untouched
+pub fn rules_for(provider: &Provider) -> &'static [ParamRule] {
+    match provider {
+        Provider::Anthropic => ANTHROPIC_RULES,
+        Provider::OpenAI | Provider::OpenAICodex => OPENAI_RULES,
+        Provider::Custom => &[],
+    }
+}
+
+/// Apply `rules` to a request body, returning a warning for each change
+pub fn map_params(request: &mut JsonValue, rules: &[ParamRule]) -> Vec<String> {
+    let Some(body) = request.as_object_mut() else {
+        return Vec::new();
+    };
+    rules
+        .iter()
+        .filter_map(|rule| apply_rule(body, rule))
+        .collect()
+}
+
+fn apply_rule(body: &mut Map<String, JsonValue>, rule: &ParamRule) -> Option<String> {
+    match *rule {
+        ParamRule::Clamp { name, min, max } => {
+            let value = body.get(name)?.as_f64()?;
+            let clamped = value.clamp(min, max);
+            if clamped == value {
+                return None;
+            }
+            body.insert(name.to_string(), json!(clamped));
+            Some(format!("{name} {value} clamped to {clamped}"))
+        }
+        ParamRule::Rename { from, to } => {
+            let value = body.remove(from)?;
+            if body.contains_key(to) {
+                return Some(format!("{from} dropped in favour of {to}"));
+            }
+            body.insert(to.to_string(), value);
+            Some(format!("{from} renamed to {to}"))
+        }
+        ParamRule::Drop { name } => {
+            body.remove(name)?;
+            Some(format!("{name} is not supported and was dropped"))
+        }
+        ParamRule::Wrap { name } => {
+            let value = body
+                .get_mut(name)
+                .filter(|v| !v.is_array() && !v.is_null())?;
+            *value = json!([value.take()]);
+            Some(format!("{name} wrapped in an array"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_anthropic_rules() {
+        let mut request = json!({
+            "model": "claude",
+            "temperature": 1.5,
+            "top_p": 0.9,
+            "stop": ["END"],
+            "presence_penalty": 0.5
+        });
+        let warnings = map_params(&mut request, rules_for(&Provider::Anthropic));
+        assert_eq!(request["temperature"], 1.0);
+        assert_eq!(request["top_p"], 0.9);
+        assert_eq!(request["stop_sequences"], json!(["END"]));
+        assert!(request.get("stop").is_none());
+        assert!(request.get("presence_penalty").is_none());
+        assert_eq!(warnings.len(), 3);
+
+        // A single stop string becomes a list
+        let mut request = json!({"model": "claude", "stop": "END"});
+        let warnings = map_params(&mut request, rules_for(&Provider::Anthropic));
+        assert_eq!(request["stop_sequences"], json!(["END"]));
+        assert_eq!(warnings.len(), 2);
+    }
+
+    #[test]
+    fn test_openai_rules() {
+        let mut request = json!({
+            "model": "gpt-4",
+            "temperature": 1.5,
+            "frequency_penalty": -3,
+            "top_k": 40,
+            "stop_sequences": ["a"],
+            "stop": ["b"]
+        });
+        let warnings = map_params(&mut request, rules_for(&Provider::OpenAI));
+        assert_eq!(request["temperature"], 1.5);
+        assert_eq!(request["frequency_penalty"], -2.0);
+        assert!(request.get("top_k").is_none());
+        assert_eq!(request["stop"], json!(["b"]));
+        assert!(request.get("stop_sequences").is_none());
+        assert_eq!(warnings.len(), 3);
+    }
+
+    #[test]
+    fn test_custom_provider_untouched() {
+        let mut request = json!({"temperature": 5, "top_k": 40});
+        let original = request.clone();
+        assert!(map_params(&mut request, rules_for(&Provider::Custom)).is_empty());
+        assert_eq!(request, original);
+    }
+}
@@ -5,7 +5,7 @@ use axum::response::Json;
 use axum::response::{IntoResponse, Response, Sse, sse::Event};
 use futures::stream::{StreamExt, iter};
 use gate_core::router::types::ActualCost;
-use gate_core::router::{ResponseChunk, ResponseStream};
+use gate_core::router::{ResponseChunk, ResponseStream, parse_content};
 use http::header::HeaderName;
 use serde::Serialize;
 use serde::ser::{SerializeMap, Serializer};
@@ -61,6 +61,13 @@ pub async fn response_stream_to_axum(stream: ResponseStream) -> Result<Response,
                         };
                         Ok(ev.data(body))
                     }
+                    ResponseChunk::Raw { event, data } => {
+                        let mut ev = Event::default();
+                        if let Some(event_name) = event {
+                            ev = ev.event(event_name);
+                        }
+                        Ok(ev.data(String::from_utf8_lossy(&data)))
+                    }
                     ResponseChunk::Stop {
                         reason,
                         error,
@@ -117,7 +124,7 @@ pub async fn response_stream_to_axum(stream: ResponseStream) -> Result<Response,
 
 /// Convert a ResponseStream representing a non-streaming response to a JSON HTTP response
 pub async fn response_stream_to_json(stream: ResponseStream) -> Result<Response, HttpError> {
-    let mut stream = stream;
+    let mut stream = parse_content(stream);
     let head = stream.next().await;
     let mut response_headers: Option<HashMap<String, String>> = None;
     let mut last_json: Option<serde_json::Value> = None;
@@ -286,4 +293,42 @@ mod tests {
         assert!(s.contains("event: message_start"));
         assert!(s.contains("data: {\"type\":\"message_start\""));
     }
+
+    #[tokio::test]
+    async fn test_raw_chunks_are_forwarded_verbatim() {
+        // Key order and spacing survive because the frame is never re-serialized
+        let frame = r#"{"model":"m", "type":"content_block_delta","index":0}"#;
+        let chunks = vec![
+            Ok(ResponseChunk::Raw {
+                event: Some("content_block_delta".to_string()),
+                data: bytes::Bytes::from_static(frame.as_bytes()),
+            }),
+            Ok(ResponseChunk::Stop {
+                reason: gate_core::router::types::StopReason::Complete,
+                error: None,
+                cost: None,
+            }),
+        ];
+        let resp = response_stream_to_axum(Box::pin(stream::iter(chunks)))
+            .await
+            .expect("sse resp");
+        let body_bytes = resp.into_body().collect().await.unwrap().to_bytes();
+        let s = String::from_utf8_lossy(&body_bytes);
+        assert!(s.contains("event: content_block_delta"));
+        assert!(s.contains(&format!("data: {frame}")));
+    }
+
+    #[tokio::test]
+    async fn test_response_stream_to_json_parses_raw_chunks() {
+        let chunks = vec![Ok(ResponseChunk::Raw {
+            event: None,
+            data: bytes::Bytes::from_static(br#"{"ok":true}"#),
+        })];
+        let resp = response_stream_to_json(Box::pin(stream::iter(chunks)))
+            .await
+            .expect("json resp");
+        let body_bytes = resp.into_body().collect().await.unwrap().to_bytes();
+        let body: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+        assert_eq!(body, serde_json::json!({"ok": true}));
+    }
 }
@@ -0,0 +1,8 @@
+//! Signing of outgoing sink requests
+
+/// Signs requests just before they are sent, e.g. to let an upstream
+/// authenticate the node that made them
+pub trait RequestSigner: Send + Sync {
+    /// Add signature headers to a fully built request
+    fn sign(&self, request: &mut reqwest::Request);
+}
@@ -1,5 +1,7 @@
 //! Application state management
 
+use crate::services::{RequestCoalescer, ResponseCache};
+use crate::tools::ToolRegistry;
 use gate_core::StateBackend;
 use gate_core::router::prelude::Router;
 use std::sync::Arc;
@@ -15,6 +17,12 @@ pub struct AppState<T = ()> {
     pub state_backend: Arc<dyn StateBackend>,
     /// Router for all routing decisions
     pub router: Option<Arc<Router>>,
+    /// Tools the gateway runs for agentic chat completions
+    pub tools: Option<Arc<ToolRegistry>>,
+    /// Shares one execution among identical non-streaming requests
+    pub coalescer: Option<Arc<RequestCoalescer>>,
+    /// Short-lived cache of metadata responses such as the model list
+    pub response_cache: Option<Arc<ResponseCache>>,
     /// Custom state data
     pub data: Arc<T>,
 }
@@ -25,6 +33,9 @@ impl<T> AppState<T> {
         Self {
             state_backend,
             router: None,
+            tools: None,
+            coalescer: None,
+            response_cache: None,
             data: Arc::new(data),
         }
     }
@@ -34,4 +45,22 @@ impl<T> AppState<T> {
         self.router = Some(router);
         self
     }
+
+    /// Set the server-side tools
+    pub fn with_tools(mut self, tools: Arc<ToolRegistry>) -> Self {
+        self.tools = Some(tools);
+        self
+    }
+
+    /// Coalesce identical non-streaming requests
+    pub fn with_coalescer(mut self, coalescer: Arc<RequestCoalescer>) -> Self {
+        self.coalescer = Some(coalescer);
+        self
+    }
+
+    /// Cache metadata responses
+    pub fn with_response_cache(mut self, cache: Arc<ResponseCache>) -> Self {
+        self.response_cache = Some(cache);
+        self
+    }
 }
@@ -15,6 +15,7 @@ pub fn map_to_sse_event(chunk: ResponseChunk, protocol: Protocol) -> Event {
 fn chunk_to_payload(chunk: ResponseChunk, protocol: Protocol) -> JsonValue {
     match chunk {
         ResponseChunk::Content(v) => v,
+        raw @ ResponseChunk::Raw { .. } => chunk_to_payload(raw.parsed(), protocol),
         ResponseChunk::Usage {
             prompt_tokens,
             completion_tokens,
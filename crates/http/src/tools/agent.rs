@@ -0,0 +1,335 @@
+//! The tool loop behind agentic chat completions
+//!
+//! Each model call is made without streaming. Calls to server-side tools are
+//! run and their results appended before generating again; a completion
+//! without such calls, or one that also calls tools the client defined, is
+//! the final answer. Streaming clients receive `gate.tool_call` and
+//! `gate.tool_result` events as the loop progresses, then the answer as
+//! ordinary completion chunks.
+
+use super::{ToolConfig, ToolRegistry};
+use crate::{error::HttpError, routes::responses::final_response};
+use futures::{StreamExt, stream};
+use gate_core::router::{
+    ResponseChunk, ResponseStream,
+    routing::Router as CoreRouter,
+    service::route_and_execute_json_with_protocol,
+    sink::RequestContext,
+    types::{Protocol, StopReason},
+};
+use gate_core::tracing::prelude::*;
+use serde_json::{Value as JsonValue, json};
+use std::sync::Arc;
+use std::time::Instant;
+
+pub const TOOL_CALL_EVENT: &str = "gate.tool_call";
+pub const TOOL_RESULT_EVENT: &str = "gate.tool_result";
+
+/// Progress of the loop
+#[derive(Debug, Clone)]
+pub enum Step {
+    /// The model called server-side tools; one event per call
+    ToolCalls(Vec<JsonValue>),
+    /// Those calls finished; one event per result
+    ToolResults(Vec<JsonValue>),
+    /// The final chat completion
+    Final(JsonValue),
+}
+
+/// A chat completions request being answered with server-side tools
+pub struct Agent {
+    router: Arc<CoreRouter>,
+    registry: Arc<ToolRegistry>,
+    ctx: RequestContext,
+    tools: Vec<ToolConfig>,
+    request: JsonValue,
+    iterations: usize,
+    prompt_tokens: u64,
+    completion_tokens: u64,
+    /// Tool calls the model made that are still to run
+    pending: Vec<JsonValue>,
+    /// Every call made so far, with its result
+    calls: Vec<JsonValue>,
+    done: bool,
+}
+
+impl Agent {
+    pub fn new(
+        router: Arc<CoreRouter>,
+        registry: Arc<ToolRegistry>,
+        ctx: RequestContext,
+        mut request: JsonValue,
+        tools: Vec<ToolConfig>,
+    ) -> Self {
+        request["stream"] = json!(false);
+        if let Some(obj) = request.as_object_mut() {
+            obj.remove("stream_options");
+        }
+        let mut definitions = request["tools"].as_array().cloned().unwrap_or_default();
+        definitions.extend(tools.iter().map(ToolConfig::definition));
+        request["tools"] = json!(definitions);
+
+        Self {
+            router,
+            registry,
+            ctx,
+            tools,
+            request,
+            iterations: 0,
+            prompt_tokens: 0,
+            completion_tokens: 0,
+            pending: Vec::new(),
+            calls: Vec::new(),
+            done: false,
+        }
+    }
+
+    fn server_tool(&self, name: &str) -> Option<&ToolConfig> {
+        self.tools.iter().find(|t| t.name == name)
+    }
+
+    /// Advance the loop by one step; `None` once the final completion has
+    /// been returned
+    pub async fn step(&mut self) -> Result<Option<Step>, HttpError> {
+        if self.done {
+            return Ok(None);
+        }
+        if !self.pending.is_empty() {
+            return Ok(Some(Step::ToolResults(self.run_pending().await)));
+        }
+
+        self.iterations += 1;
+        if self.iterations >= self.registry.max_iterations() {
+            // Out of rounds: the model has to answer with what it has
+            self.request["tool_choice"] = json!("none");
+        }
+        let stream = route_and_execute_json_with_protocol(
+            &self.router,
+            &self.ctx,
+            Protocol::OpenAIChat,
+            self.request.clone(),
+        )
+        .await?;
+        let (_, mut completion) = final_response(stream).await?;
+        self.prompt_tokens += completion["usage"]["prompt_tokens"].as_u64().unwrap_or(0);
+        self.completion_tokens += completion["usage"]["completion_tokens"]
+            .as_u64()
+            .unwrap_or(0);
+
+        let message = completion
+            .pointer("/choices/0/message")
+            .cloned()
+            .unwrap_or_default();
+        let tool_calls = message["tool_calls"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+        let all_server_side = tool_calls.iter().all(|call| {
+            call["function"]["name"]
+                .as_str()
+                .is_some_and(|name| self.server_tool(name).is_some())
+        });
+
+        if tool_calls.is_empty() || !all_server_side {
+            self.done = true;
+            completion["usage"] = json!({
+                "prompt_tokens": self.prompt_tokens,
+                "completion_tokens": self.completion_tokens,
+                "total_tokens": self.prompt_tokens + self.completion_tokens
+            });
+            return Ok(Some(Step::Final(completion)));
+        }
+
+        if let Some(messages) = self.request["messages"].as_array_mut() {
+            messages.push(message);
+        }
+        let events = tool_calls
+            .iter()
+            .map(|call| {
+                json!({
+                    "type": TOOL_CALL_EVENT,
+                    "id": call["id"],
+                    "name": call["function"]["name"],
+                    "arguments": call["function"]["arguments"]
+                })
+            })
+            .collect();
+        self.pending = tool_calls;
+        Ok(Some(Step::ToolCalls(events)))
+    }
+
+    async fn run_pending(&mut self) -> Vec<JsonValue> {
+        let mut events = Vec::new();
+        for call in std::mem::take(&mut self.pending) {
+            let name = call["function"]["name"].as_str().unwrap_or_default();
+            let arguments = call["function"]["arguments"].as_str().unwrap_or_default();
+            let Some(tool) = self.server_tool(name).cloned() else {
+                continue;
+            };
+
+            let started = Instant::now();
+            let result = self.registry.call(&tool, arguments).await;
+            let duration_ms = started.elapsed().as_millis() as u64;
+            if result.is_error {
+                debug!("Tool {name} failed: {}", result.content);
+            }
+
+            if let Some(messages) = self.request["messages"].as_array_mut() {
+                messages.push(json!({
+                    "role": "tool",
+                    "tool_call_id": call["id"],
+                    "content": result.content
+                }));
+            }
+            self.calls.push(json!({
+                "id": call["id"],
+                "name": name,
+                "arguments": arguments,
+                "content": result.content,
+                "is_error": result.is_error
+            }));
+            events.push(json!({
+                "type": TOOL_RESULT_EVENT,
+                "id": call["id"],
+                "name": name,
+                "content": result.content,
+                "is_error": result.is_error,
+                "duration_ms": duration_ms
+            }));
+        }
+        events
+    }
+
+    /// Run to completion, returning the final completion with the tool
+    /// calls Gate made listed under `gate_tool_calls`
+    pub async fn run(mut self) -> Result<JsonValue, HttpError> {
+        while let Some(step) = self.step().await? {
+            if let Step::Final(mut completion) = step {
+                completion["gate_tool_calls"] = json!(self.calls);
+                return Ok(completion);
+            }
+        }
+        Err(HttpError::InternalServerError(
+            "Tool loop ended without a completion".to_string(),
+        ))
+    }
+
+    /// Run as a stream of tool events followed by the answer's chunks
+    pub fn into_stream(self) -> ResponseStream {
+        let steps = stream::unfold(Some(self), |agent| async move {
+            let mut agent = agent?;
+            match agent.step().await {
+                Ok(Some(step)) => {
+                    let chunks = match step {
+                        Step::ToolCalls(events) | Step::ToolResults(events) => events
+                            .into_iter()
+                            .map(|e| Ok(ResponseChunk::Content(e)))
+                            .collect(),
+                        Step::Final(completion) => final_chunks(&completion),
+                    };
+                    Some((chunks, Some(agent)))
+                }
+                Ok(None) => None,
+                Err(e) => {
+                    warn!("Tool loop failed: {e}");
+                    let stop = ResponseChunk::Stop {
+                        reason: StopReason::Error,
+                        error: Some(e.to_string()),
+                        cost: None,
+                    };
+                    Some((vec![Ok(stop)], None))
+                }
+            }
+        });
+        Box::pin(steps.flat_map(stream::iter))
+    }
+}
+
+/// A complete chat completion replayed as streaming chunks
+fn final_chunks(completion: &JsonValue) -> Vec<gate_core::Result<ResponseChunk>> {
+    let mut chunks = completion_chunks(completion)
+        .into_iter()
+        .map(|c| Ok(ResponseChunk::Content(c)))
+        .collect::<Vec<_>>();
+    chunks.push(Ok(ResponseChunk::Usage {
+        prompt_tokens: completion["usage"]["prompt_tokens"].as_u64().unwrap_or(0) as u32,
+        completion_tokens: completion["usage"]["completion_tokens"]
+            .as_u64()
+            .unwrap_or(0) as u32,
+    }));
+    chunks.push(Ok(ResponseChunk::Stop {
+        reason: StopReason::Complete,
+        error: None,
+        cost: None,
+    }));
+    chunks
+}
+
+fn completion_chunks(completion: &JsonValue) -> Vec<JsonValue> {
+    let chunk = |choices: JsonValue| {
+        json!({
+            "id": completion["id"],
+            "object": "chat.completion.chunk",
+            "created": completion["created"],
+            "model": completion["model"],
+            "choices": choices
+        })
+    };
+
+    let mut chunks = Vec::new();
+    let choices = completion["choices"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default();
+    for choice in &choices {
+        let message = &choice["message"];
+        let mut delta = json!({"role": "assistant", "content": message["content"]});
+        if let Some(calls) = message["tool_calls"].as_array() {
+            let calls: Vec<JsonValue> = calls
+                .iter()
+                .enumerate()
+                .map(|(index, call)| {
+                    let mut call = call.clone();
+                    call["index"] = json!(index);
+                    call
+                })
+                .collect();
+            delta["tool_calls"] = json!(calls);
+        }
+        chunks.push(chunk(json!([{
+            "index": choice["index"],
+            "delta": delta,
+            "finish_reason": null
+        }])));
+        chunks.push(chunk(json!([{
+            "index": choice["index"],
+            "delta": {},
+            "finish_reason": choice["finish_reason"]
+        }])));
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_completion_replayed_as_chunks() {
+        let completion = json!({
+            "id": "chatcmpl-1",
+            "created": 1,
+            "model": "gpt-4o",
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": "Hello"},
+                "finish_reason": "stop"
+            }]
+        });
+        let chunks = completion_chunks(&completion);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0]["object"], "chat.completion.chunk");
+        assert_eq!(chunks[0]["choices"][0]["delta"]["content"], "Hello");
+        assert_eq!(chunks[1]["choices"][0]["finish_reason"], "stop");
+    }
+}
@@ -0,0 +1,361 @@
+//! Tool executors: HTTP endpoints, MCP servers and built-ins
+
+use super::{ToolConfig, ToolKind, ToolResult};
+use reqwest::header::{CONTENT_TYPE, LOCATION};
+use reqwest::redirect::Policy;
+use reqwest::{Client, RequestBuilder, Response};
+use serde_json::{Value as JsonValue, json};
+use std::net::{IpAddr, SocketAddr};
+use url::{Host, Url};
+
+const MCP_SESSION_ID: &str = "mcp-session-id";
+const MCP_PROTOCOL_VERSION: &str = "2025-03-26";
+
+/// Redirects `web_fetch` follows, each checked like the first URL
+const MAX_REDIRECTS: usize = 5;
+
+pub(super) async fn execute(
+    client: &Client,
+    tool: &ToolConfig,
+    arguments: &JsonValue,
+) -> ToolResult {
+    let result = match &tool.kind {
+        ToolKind::Http {
+            url,
+            api_key,
+            headers,
+        } => {
+            let mut request = with_key(client.post(url), api_key.as_deref()).json(arguments);
+            for (name, value) in headers {
+                request = request.header(name, value);
+            }
+            http_call(request).await
+        }
+        ToolKind::Mcp {
+            url,
+            tool: remote,
+            api_key,
+        } => {
+            let name = remote.as_deref().unwrap_or(&tool.name);
+            mcp_call(client, url, api_key.as_deref(), name, arguments).await
+        }
+        ToolKind::WebFetch {
+            allowed_hosts,
+            max_bytes,
+        } => web_fetch(allowed_hosts, *max_bytes, arguments).await,
+    };
+    result.unwrap_or_else(ToolResult::error)
+}
+
+fn with_key(request: RequestBuilder, api_key: Option<&str>) -> RequestBuilder {
+    match api_key {
+        Some(key) => request.bearer_auth(key),
+        None => request,
+    }
+}
+
+async fn http_call(request: RequestBuilder) -> Result<ToolResult, String> {
+    let response = request.send().await.map_err(|e| e.to_string())?;
+    let status = response.status();
+    let body = response.text().await.map_err(|e| e.to_string())?;
+    if status.is_success() {
+        Ok(ToolResult::ok(body))
+    } else {
+        Ok(ToolResult::error(format!("HTTP {status}: {body}")))
+    }
+}
+
+/// Initialize an MCP session and call one tool on it
+async fn mcp_call(
+    client: &Client,
+    url: &str,
+    api_key: Option<&str>,
+    name: &str,
+    arguments: &JsonValue,
+) -> Result<ToolResult, String> {
+    let initialize = json!({
+        "jsonrpc": "2.0",
+        "id": 0,
+        "method": "initialize",
+        "params": {
+            "protocolVersion": MCP_PROTOCOL_VERSION,
+            "capabilities": {},
+            "clientInfo": {"name": "gate", "version": env!("CARGO_PKG_VERSION")}
+        }
+    });
+    let (session, _) = mcp_request(client, url, api_key, None, &initialize).await?;
+
+    let initialized = json!({"jsonrpc": "2.0", "method": "notifications/initialized"});
+    mcp_request(client, url, api_key, session.as_deref(), &initialized).await?;
+
+    let call = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "tools/call",
+        "params": {"name": name, "arguments": arguments}
+    });
+    let (_, reply) = mcp_request(client, url, api_key, session.as_deref(), &call).await?;
+    let reply = reply.ok_or_else(|| "MCP server sent no reply".to_string())?;
+    if let Some(error) = reply.get("error") {
+        return Ok(ToolResult::error(
+            error["message"].as_str().unwrap_or("MCP error").to_string(),
+        ));
+    }
+    Ok(mcp_result(&reply["result"]))
+}
+
+/// Send one JSON-RPC message, returning the session id the server assigned
+/// and its reply, if the message expected one
+async fn mcp_request(
+    client: &Client,
+    url: &str,
+    api_key: Option<&str>,
+    session: Option<&str>,
+    message: &JsonValue,
+) -> Result<(Option<String>, Option<JsonValue>), String> {
+    let mut request = with_key(client.post(url), api_key)
+        .header("accept", "application/json, text/event-stream")
+        .json(message);
+    if let Some(session) = session {
+        request = request.header(MCP_SESSION_ID, session);
+    }
+    let response = request.send().await.map_err(|e| e.to_string())?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("MCP server returned {status}: {body}"));
+    }
+
+    let session = response
+        .headers()
+        .get(MCP_SESSION_ID)
+        .and_then(|v| v.to_str().ok())
+        .map(String::from)
+        .or_else(|| session.map(String::from));
+    let is_sse = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/event-stream"));
+    let body = response.text().await.map_err(|e| e.to_string())?;
+
+    let Some(id) = message.get("id") else {
+        return Ok((session, None));
+    };
+    let reply = if is_sse {
+        body.lines()
+            .filter_map(|line| line.strip_prefix("data:"))
+            .filter_map(|data| serde_json::from_str::<JsonValue>(data.trim()).ok())
+            .find(|event| event.get("id") == Some(id))
+    } else {
+        serde_json::from_str(&body).ok()
+    };
+    Ok((session, reply))
+}
+
+/// Text of an MCP `tools/call` result
+fn mcp_result(result: &JsonValue) -> ToolResult {
+    let text: Vec<String> = result["content"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|part| match part["text"].as_str() {
+            Some(text) => text.to_string(),
+            None => part.to_string(),
+        })
+        .collect();
+    let content = text.join("\n");
+    if result["isError"].as_bool() == Some(true) {
+        ToolResult::error(content)
+    } else {
+        ToolResult::ok(content)
+    }
+}
+
+/// Fetch `arguments.url` for the model. Only allowed hosts that resolve to
+/// public addresses are fetched, each connection is pinned to the address
+/// that was checked, redirects are checked the same way hop by hop, and no
+/// more than `max_bytes` of the body is read.
+async fn web_fetch(
+    allowed_hosts: &[String],
+    max_bytes: usize,
+    arguments: &JsonValue,
+) -> Result<ToolResult, String> {
+    let url = arguments["url"]
+        .as_str()
+        .ok_or_else(|| "url is required".to_string())?;
+    let mut url = Url::parse(url).map_err(|e| format!("Invalid url: {e}"))?;
+
+    for _ in 0..=MAX_REDIRECTS {
+        let (host, addr) = public_target(&url, allowed_hosts).await?;
+        let client = Client::builder()
+            .redirect(Policy::none())
+            .resolve(&host, addr)
+            .build()
+            .map_err(|e| e.to_string())?;
+        let response = client
+            .get(url.clone())
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        if !response.status().is_redirection() {
+            return read_capped(response, max_bytes).await;
+        }
+        let location = response
+            .headers()
+            .get(LOCATION)
+            .and_then(|v| v.to_str().ok())
+            .ok_or_else(|| "Redirect without a location".to_string())?;
+        url = url
+            .join(location)
+            .map_err(|e| format!("Invalid redirect: {e}"))?;
+    }
+    Err(format!("More than {MAX_REDIRECTS} redirects"))
+}
+
+/// Host of `url` and the address to connect to, if the host is allowed and
+/// every address it resolves to is public
+async fn public_target(
+    url: &Url,
+    allowed_hosts: &[String],
+) -> Result<(String, SocketAddr), String> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!("Unsupported scheme {}", url.scheme()));
+    }
+    let host = url
+        .host_str()
+        .ok_or_else(|| "url has no host".to_string())?;
+    if !allowed_hosts
+        .iter()
+        .any(|allowed| allowed == "*" || allowed.eq_ignore_ascii_case(host))
+    {
+        return Err(format!("Fetching from {host} is not allowed"));
+    }
+    let lookup = match url.host() {
+        Some(Host::Ipv6(ip)) => ip.to_string(),
+        _ => host.to_string(),
+    };
+    let port = url.port_or_known_default().unwrap_or(80);
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((lookup.as_str(), port))
+        .await
+        .map_err(|e| format!("Cannot resolve {host}: {e}"))?
+        .collect();
+    if let Some(private) = addrs.iter().find(|addr| !is_public(addr.ip())) {
+        return Err(format!(
+            "Fetching from {host} is not allowed: {} is not a public address",
+            private.ip()
+        ));
+    }
+    let addr = addrs
+        .first()
+        .copied()
+        .ok_or_else(|| format!("Cannot resolve {host}"))?;
+    Ok((host.to_string(), addr))
+}
+
+/// Whether `ip` is reachable on the public internet, rather than on the
+/// gateway's own host or network
+fn is_public(ip: IpAddr) -> bool {
+    match ip.to_canonical() {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                || a == 0
+                // Carrier-grade NAT, 100.64.0.0/10
+                || (a == 100 && b & 0xc0 == 64))
+        }
+        IpAddr::V6(ip) => {
+            let first = ip.segments()[0];
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                // Unique local, fc00::/7, and link-local, fe80::/10
+                || first & 0xfe00 == 0xfc00
+                || first & 0xffc0 == 0xfe80)
+        }
+    }
+}
+
+/// Read at most `max_bytes` of `response` as text
+async fn read_capped(mut response: Response, max_bytes: usize) -> Result<ToolResult, String> {
+    let status = response.status();
+    let mut body = Vec::new();
+    while body.len() < max_bytes {
+        match response.chunk().await.map_err(|e| e.to_string())? {
+            Some(chunk) => body.extend_from_slice(&chunk),
+            None => break,
+        }
+    }
+    body.truncate(max_bytes);
+    // Drop a character the cap cut in half
+    if let Err(e) = std::str::from_utf8(&body)
+        && e.error_len().is_none()
+    {
+        body.truncate(e.valid_up_to());
+    }
+    let text = String::from_utf8_lossy(&body).into_owned();
+    if status.is_success() {
+        Ok(ToolResult::ok(text))
+    } else {
+        Ok(ToolResult::error(format!("HTTP {status}: {text}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mcp_result_text_and_errors() {
+        let result =
+            json!({"content": [{"type": "text", "text": "a"}, {"type": "text", "text": "b"}]});
+        assert_eq!(mcp_result(&result), ToolResult::ok("a\nb"));
+
+        let failed = json!({"content": [{"type": "text", "text": "boom"}], "isError": true});
+        assert_eq!(mcp_result(&failed), ToolResult::error("boom"));
+    }
+
+    #[tokio::test]
+    async fn test_web_fetch_enforces_allowed_hosts() {
+        let fetch = |allowed: &[&str], url: &str| {
+            let allowed: Vec<String> = allowed.iter().map(|h| h.to_string()).collect();
+            let arguments = json!({ "url": url });
+            async move { web_fetch(&allowed, 100, &arguments).await }
+        };
+        let denied = fetch(&["example.com"], "http://169.254.169.254/latest").await;
+        assert!(denied.unwrap_err().contains("not allowed"));
+
+        // Nothing is allowed until hosts are listed
+        let denied = fetch(&[], "http://example.com/").await;
+        assert!(denied.unwrap_err().contains("not allowed"));
+
+        for url in [
+            "http://169.254.169.254/latest",
+            "http://127.0.0.1:8080/",
+            "http://[::1]/",
+            "http://[::ffff:10.0.0.1]/",
+            "http://localhost/",
+        ] {
+            let denied = fetch(&["*"], url).await;
+            assert!(denied.unwrap_err().contains("not allowed"), "{url}");
+        }
+
+        let scheme = fetch(&["*"], "file:///etc/passwd").await;
+        assert!(scheme.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_web_fetch_caps_the_body() {
+        let response = http::Response::builder()
+            .body("héllo wörld".repeat(100))
+            .unwrap();
+        let result = read_capped(Response::from(response), 2).await.unwrap();
+        assert_eq!(result, ToolResult::ok("h"));
+    }
+}
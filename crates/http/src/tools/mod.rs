@@ -0,0 +1,280 @@
+//! Server-side tool execution
+//!
+//! Tools registered here are offered to the model when a chat completions
+//! request opts in with the `x-gate-tools` header. When the model calls one,
+//! Gate runs it, appends the result to the conversation and generates again,
+//! streaming each call and result to the client as it happens.
+
+pub mod agent;
+mod executors;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value as JsonValue, json};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Request header opting into server-side tools: `*` for every tool the
+/// caller may use, or a comma-separated list of tool names
+pub const TOOLS_HEADER: &str = "x-gate-tools";
+
+fn default_timeout_secs() -> u64 {
+    30
+}
+
+fn default_max_iterations() -> usize {
+    8
+}
+
+fn default_max_bytes() -> usize {
+    100_000
+}
+
+/// Server-side tools configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ToolsConfig {
+    /// Model calls allowed per request before tools are withheld and the
+    /// model must answer
+    #[serde(default = "default_max_iterations")]
+    pub max_iterations: usize,
+    #[serde(default)]
+    pub tools: Vec<ToolConfig>,
+}
+
+impl Default for ToolsConfig {
+    fn default() -> Self {
+        Self {
+            max_iterations: default_max_iterations(),
+            tools: Vec::new(),
+        }
+    }
+}
+
+/// A tool the gateway can run on the model's behalf
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ToolConfig {
+    /// Function name the model calls
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// JSON schema of the arguments; built-in tools supply their own
+    #[serde(default)]
+    pub parameters: Option<JsonValue>,
+    #[serde(flatten)]
+    pub kind: ToolKind,
+    /// Seconds a call may take before it is abandoned
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Identities allowed to use the tool; empty allows every caller
+    #[serde(default)]
+    pub allowed_users: Vec<String>,
+}
+
+/// How a tool is executed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ToolKind {
+    /// POST the arguments as JSON to `url`; the response body is the result
+    Http {
+        url: String,
+        /// Sent as a bearer token
+        #[serde(default)]
+        api_key: Option<String>,
+        #[serde(default)]
+        headers: HashMap<String, String>,
+    },
+    /// Call a tool on an MCP server over streamable HTTP
+    Mcp {
+        url: String,
+        /// Tool name on the server; defaults to the tool's own name
+        #[serde(default)]
+        tool: Option<String>,
+        /// Sent as a bearer token
+        #[serde(default)]
+        api_key: Option<String>,
+    },
+    /// Built-in: fetch a URL and return its body as text. Hosts resolving
+    /// to private, loopback or link-local addresses are never fetched.
+    WebFetch {
+        /// Hosts that may be fetched; `*` allows any public host, and empty
+        /// allows none
+        #[serde(default)]
+        allowed_hosts: Vec<String>,
+        /// Longest body returned to the model
+        #[serde(default = "default_max_bytes")]
+        max_bytes: usize,
+    },
+}
+
+impl ToolConfig {
+    pub fn is_allowed(&self, identity_id: &str) -> bool {
+        self.allowed_users.is_empty() || self.allowed_users.iter().any(|u| u == identity_id)
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs)
+    }
+
+    /// Chat completions function definition offered to the model
+    pub fn definition(&self) -> JsonValue {
+        let parameters = self.parameters.clone().unwrap_or_else(|| match self.kind {
+            ToolKind::WebFetch { .. } => json!({
+                "type": "object",
+                "properties": {"url": {"type": "string", "description": "URL to fetch"}},
+                "required": ["url"]
+            }),
+            _ => json!({"type": "object", "properties": {}}),
+        });
+        let description = self.description.clone().unwrap_or_else(|| match self.kind {
+            ToolKind::WebFetch { .. } => "Fetch a web page and return its contents".to_string(),
+            _ => String::new(),
+        });
+        json!({
+            "type": "function",
+            "function": {
+                "name": self.name,
+                "description": description,
+                "parameters": parameters
+            }
+        })
+    }
+}
+
+/// Outcome of one tool call
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolResult {
+    /// Text returned to the model
+    pub content: String,
+    pub is_error: bool,
+}
+
+impl ToolResult {
+    pub fn ok(content: impl Into<String>) -> Self {
+        Self {
+            content: content.into(),
+            is_error: false,
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            content: message.into(),
+            is_error: true,
+        }
+    }
+}
+
+/// Registered server-side tools
+#[derive(Debug, Clone)]
+pub struct ToolRegistry {
+    config: ToolsConfig,
+    client: reqwest::Client,
+}
+
+impl ToolRegistry {
+    pub fn new(config: ToolsConfig) -> Self {
+        Self {
+            config,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.config.tools.is_empty()
+    }
+
+    pub fn max_iterations(&self) -> usize {
+        self.config.max_iterations.max(1)
+    }
+
+    /// Tools a caller asked for with the [`TOOLS_HEADER`] value `selection`
+    /// and is allowed to use
+    pub fn select(&self, identity_id: &str, selection: &str) -> Vec<ToolConfig> {
+        let names: Vec<&str> = selection
+            .split(',')
+            .map(str::trim)
+            .filter(|n| !n.is_empty())
+            .collect();
+        self.config
+            .tools
+            .iter()
+            .filter(|t| names.contains(&"*") || names.contains(&t.name.as_str()))
+            .filter(|t| t.is_allowed(identity_id))
+            .cloned()
+            .collect()
+    }
+
+    /// Run `tool` with the model's JSON-encoded `arguments`. Failures are
+    /// reported to the model as error results rather than ending the request.
+    pub async fn call(&self, tool: &ToolConfig, arguments: &str) -> ToolResult {
+        let arguments: JsonValue = match serde_json::from_str(if arguments.trim().is_empty() {
+            "{}"
+        } else {
+            arguments
+        }) {
+            Ok(arguments) => arguments,
+            Err(e) => return ToolResult::error(format!("Invalid arguments: {e}")),
+        };
+
+        let call = executors::execute(&self.client, tool, &arguments);
+        match tokio::time::timeout(tool.timeout(), call).await {
+            Ok(result) => result,
+            Err(_) => ToolResult::error(format!(
+                "Tool {} timed out after {}s",
+                tool.name, tool.timeout_secs
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> ToolRegistry {
+        let config: ToolsConfig = serde_json::from_value(json!({
+            "tools": [
+                {"name": "fetch", "type": "web_fetch"},
+                {"name": "search", "type": "http", "url": "http://localhost/search",
+                 "allowed_users": ["alice"]}
+            ]
+        }))
+        .unwrap();
+        ToolRegistry::new(config)
+    }
+
+    #[test]
+    fn test_selection_respects_permissions() {
+        let registry = registry();
+        let names = |tools: Vec<ToolConfig>| tools.into_iter().map(|t| t.name).collect::<Vec<_>>();
+        assert_eq!(
+            names(registry.select("alice", "*")),
+            vec!["fetch", "search"]
+        );
+        assert_eq!(names(registry.select("bob", "*")), vec!["fetch"]);
+        assert_eq!(names(registry.select("alice", "search")), vec!["search"]);
+        assert!(registry.select("alice", "unknown").is_empty());
+    }
+
+    #[test]
+    fn test_builtin_definition_has_schema() {
+        let registry = registry();
+        let fetch = &registry.select("bob", "fetch")[0];
+        let definition = fetch.definition();
+        assert_eq!(definition["function"]["name"], "fetch");
+        assert_eq!(
+            definition["function"]["parameters"]["required"],
+            json!(["url"])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invalid_arguments_become_error_results() {
+        let registry = registry();
+        let fetch = registry.select("bob", "fetch").remove(0);
+        let result = registry.call(&fetch, "{not json").await;
+        assert!(result.is_error);
+    }
+}
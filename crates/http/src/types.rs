@@ -23,6 +23,14 @@ pub struct OpenAIChatCompletionRequest {
     pub extra: JsonValue,
 }
 
+/// Chat completion request run against several models at once
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAIChatCompareRequest {
+    pub models: Vec<String>,
+    #[serde(flatten)]
+    pub extra: JsonValue,
+}
+
 /// OpenAI Completion request (legacy)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OpenAICompletionRequest {
@@ -33,6 +41,16 @@ pub struct OpenAICompletionRequest {
     pub extra: Option<JsonValue>,
 }
 
+/// OpenAI embeddings request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAIEmbeddingsRequest {
+    pub model: String,
+    /// A string, an array of strings or an array of token arrays
+    pub input: JsonValue,
+    #[serde(flatten)]
+    pub extra: Option<JsonValue>,
+}
+
 /// Registration start request
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RegisterStartRequest {
@@ -0,0 +1,136 @@
+//! Conformance of the Anthropic compatibility mode against captured traffic
+//!
+//! Replays recorded Anthropic responses through the gateway's response path
+//! and checks that a client sees exactly what the Anthropic API sent.
+
+use axum::{Router, body::Body, http::Request, http::StatusCode, routing::post};
+use bytes::Bytes;
+use futures::{StreamExt, stream};
+use gate_core::router::types::StopReason;
+use gate_core::router::{ResponseChunk, ResponseStream};
+use gate_http::error::HttpError;
+use gate_http::middleware::anthropic_compat::{anthropic_compat_middleware, anthropic_events};
+use gate_http::sinks::response_converter::{response_stream_to_axum, response_stream_to_json};
+use gate_http::sinks::sse_parser::parse_sse;
+use http_body_util::BodyExt;
+use serde_json::Value as JsonValue;
+use tower::ServiceExt;
+
+/// (event, data) pairs of an SSE body
+async fn sse_events(body: &str) -> Vec<(Option<String>, String)> {
+    let bytes = Bytes::from(body.to_string());
+    let parser = parse_sse(stream::iter(vec![Ok::<_, reqwest::Error>(bytes)]));
+    parser
+        .map(|event| {
+            let event = event.expect("valid sse");
+            (event.event, event.data)
+        })
+        .collect()
+        .await
+}
+
+/// What an Anthropic sink yields for a captured stream, plus the gateway's
+/// own usage and completion events
+fn replayed(events: &[(Option<String>, String)]) -> ResponseStream {
+    let mut chunks = vec![Ok(ResponseChunk::Headers(Default::default()))];
+    chunks.extend(events.iter().map(|(event, data)| {
+        Ok(ResponseChunk::Raw {
+            event: event.clone(),
+            data: Bytes::from(data.clone()),
+        })
+    }));
+    chunks.push(Ok(ResponseChunk::Usage {
+        prompt_tokens: 1,
+        completion_tokens: 1,
+    }));
+    chunks.push(Ok(ResponseChunk::Stop {
+        reason: StopReason::Complete,
+        error: None,
+        cost: None,
+    }));
+    Box::pin(stream::iter(chunks))
+}
+
+async fn body_string(response: axum::response::Response) -> String {
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8(bytes.to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn test_streams_match_captured_traffic() {
+    let mut checked = 0;
+    for name in gate_fixtures::list_cassettes("anthropic") {
+        let Some(conversation) = gate_fixtures::get_conversation("anthropic", name) else {
+            continue;
+        };
+        if !conversation.is_streaming() {
+            continue;
+        }
+        for index in 0..conversation.interactions.len() {
+            let captured = sse_events(conversation.raw_response_body(index).unwrap()).await;
+            let response = response_stream_to_axum(anthropic_events(replayed(&captured)))
+                .await
+                .expect("sse response");
+            let served = sse_events(&body_string(response).await).await;
+            assert_eq!(served, captured, "{name} interaction {index}");
+            checked += 1;
+        }
+    }
+    assert!(checked > 0, "no streaming anthropic cassettes found");
+}
+
+#[tokio::test]
+async fn test_messages_match_captured_traffic() {
+    let mut checked = 0;
+    for name in gate_fixtures::list_cassettes("anthropic") {
+        let Some(conversation) = gate_fixtures::get_conversation("anthropic", name) else {
+            continue;
+        };
+        if conversation.is_streaming() {
+            continue;
+        }
+        for index in 0..conversation.interactions.len() {
+            let Some(captured) = conversation.response_body(index) else {
+                continue;
+            };
+            let chunks = vec![Ok(ResponseChunk::Raw {
+                event: None,
+                data: Bytes::from(captured.to_string()),
+            })];
+            let response = response_stream_to_json(Box::pin(stream::iter(chunks)))
+                .await
+                .expect("json response");
+            let served: JsonValue = serde_json::from_str(&body_string(response).await).unwrap();
+            assert_eq!(served, captured, "{name} interaction {index}");
+            checked += 1;
+        }
+    }
+    assert!(checked > 0, "no anthropic message cassettes found");
+}
+
+#[tokio::test]
+async fn test_errors_use_anthropic_envelope_and_request_id() {
+    let app = Router::new()
+        .route(
+            "/v1/messages",
+            post(|| async { Err::<(), _>(HttpError::RateLimitExceeded) }),
+        )
+        .layer(axum::middleware::from_fn(anthropic_compat_middleware));
+
+    let response = app
+        .oneshot(Request::post("/v1/messages").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(
+        response
+            .headers()
+            .get("request-id")
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("req_"))
+    );
+    let body: JsonValue = serde_json::from_str(&body_string(response).await).unwrap();
+    assert_eq!(body["type"], "error");
+    assert_eq!(body["error"]["type"], "rate_limit_error");
+}
@@ -0,0 +1,101 @@
+//! Record and replay of provider traffic through an HTTP sink
+
+#![cfg(feature = "cassettes")]
+
+use futures::StreamExt;
+use gate_core::router::sink::{RequestContext, RouterIdentityContext, Sink};
+use gate_core::router::types::{Protocol, ResponseChunk, SinkCapabilities};
+use gate_core::tracing::CorrelationId;
+use gate_http::sinks::cassette::Cassette;
+use gate_http::sinks::http_sink::{HttpSink, HttpSinkConfig, Provider};
+use serde_json::{Value as JsonValue, json};
+use std::sync::Arc;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn sink(base_url: &str, cassette: Cassette) -> HttpSink {
+    HttpSink::new(HttpSinkConfig {
+        id: "provider://openai/test".into(),
+        provider: Provider::OpenAI,
+        base_url: base_url.into(),
+        api_key: Some("sk-live-secret".into()),
+        models: vec!["gpt-4o".into()],
+        timeout: std::time::Duration::from_secs(5),
+        max_retries: 0,
+        accepted_protocols: vec![Protocol::OpenAIChat],
+        capabilities: SinkCapabilities {
+            supports_streaming: true,
+            supports_batching: false,
+            supports_tools: true,
+            supports_structured_output: true,
+            max_context_length: None,
+            modalities: vec!["text".to_string()],
+        },
+        cost_structure: None,
+    })
+    .expect("create sink")
+    .with_cassette(Arc::new(cassette))
+}
+
+fn ctx() -> RequestContext {
+    RequestContext {
+        identity: gate_core::access::SubjectIdentity::new(
+            "test",
+            "test",
+            RouterIdentityContext::default(),
+        ),
+        correlation_id: CorrelationId::new(),
+        headers: http::HeaderMap::new(),
+        query: None,
+        trace_id: None,
+        metadata: Default::default(),
+        response: Default::default(),
+        extensions: Default::default(),
+    }
+}
+
+async fn content(sink: &HttpSink) -> JsonValue {
+    let request = json!({"model": "gpt-4o", "messages": [{"role": "user", "content": "Hi"}]});
+    let stream = gate_core::router::service::one_shot_stream(Protocol::OpenAIChat, request);
+    let mut response = sink.execute(&ctx(), stream).await.expect("execute");
+    while let Some(chunk) = response.next().await {
+        if let Ok(ResponseChunk::Content(json)) = chunk {
+            return json;
+        }
+    }
+    panic!("no content in response");
+}
+
+#[tokio::test]
+async fn test_record_then_replay_without_network() {
+    let path = std::env::temp_dir().join(format!("gate-cassette-{}.json", std::process::id()));
+    let body = json!({
+        "id": "chatcmpl-1",
+        "object": "chat.completion",
+        "model": "gpt-4o",
+        "choices": [{"index": 0, "message": {"role": "assistant", "content": "Hello"}, "finish_reason": "stop"}]
+    });
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&body))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let base_url = server.uri();
+
+    let recorded = content(&sink(&base_url, Cassette::record(&path))).await;
+    assert_eq!(recorded, body);
+
+    let cassette = std::fs::read_to_string(&path).unwrap();
+    assert!(!cassette.contains("sk-live-secret"));
+
+    // The server is gone; only the cassette can answer
+    drop(server);
+    let replay = Cassette::replay(&path).unwrap();
+    let replayed = content(&sink(&base_url, replay)).await;
+    assert_eq!(replayed, body);
+
+    std::fs::remove_file(&path).ok();
+}
@@ -20,6 +20,23 @@ async fn test_client_builder() {
     assert_eq!(client.base_url().as_str(), "http://localhost:8080/");
 }
 
+#[tokio::test]
+async fn test_client_keeps_base_path() {
+    let client = GateClient::builder()
+        .base_url("http://localhost:8080/gate")
+        .build()
+        .unwrap();
+    let request = client
+        .request(reqwest::Method::GET, "/api/status")
+        .unwrap()
+        .build()
+        .unwrap();
+    assert_eq!(
+        request.url().as_str(),
+        "http://localhost:8080/gate/api/status"
+    );
+}
+
 #[tokio::test]
 async fn test_client_builder_requires_base_url() {
     let result = GateClient::builder().build();
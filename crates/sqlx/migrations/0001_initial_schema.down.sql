@@ -0,0 +1,11 @@
+-- Revert initial schema
+DROP INDEX IF EXISTS idx_api_keys_org_id;
+DROP INDEX IF EXISTS idx_usage_records_timestamp;
+DROP INDEX IF EXISTS idx_usage_records_org_id;
+DROP TABLE IF EXISTS usage_records;
+DROP TABLE IF EXISTS models;
+DROP TABLE IF EXISTS providers;
+DROP TABLE IF EXISTS api_keys;
+DROP TABLE IF EXISTS grants;
+DROP TABLE IF EXISTS organizations;
+DROP TABLE IF EXISTS users;
@@ -0,0 +1,4 @@
+-- Revert WebAuthn schema
+DROP INDEX IF EXISTS idx_users_name;
+DROP INDEX IF EXISTS idx_webauthn_credentials_user_id;
+DROP TABLE IF EXISTS webauthn_credentials;
@@ -0,0 +1,4 @@
+-- Revert permissions schema
+DROP INDEX IF EXISTS idx_permissions_object;
+DROP INDEX IF EXISTS idx_permissions_subject;
+DROP TABLE IF EXISTS permissions;
@@ -0,0 +1,3 @@
+-- Remove disabled_at field from users table
+DROP INDEX IF EXISTS idx_users_disabled_at;
+ALTER TABLE users DROP COLUMN disabled_at;
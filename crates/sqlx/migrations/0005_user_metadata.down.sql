@@ -0,0 +1,2 @@
+-- Remove metadata field from users table
+ALTER TABLE users DROP COLUMN metadata;
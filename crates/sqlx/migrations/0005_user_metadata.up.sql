@@ -0,0 +1,2 @@
+-- Add free-form metadata (JSON object) to users, e.g. role and quota settings
+ALTER TABLE users ADD COLUMN metadata TEXT;
@@ -0,0 +1,3 @@
+-- Revert conversations
+DROP INDEX IF EXISTS idx_conversations_user_updated;
+DROP TABLE IF EXISTS conversations;
@@ -0,0 +1,12 @@
+-- Saved chat sessions, owned by a user
+CREATE TABLE IF NOT EXISTS conversations (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    title TEXT NOT NULL,
+    model TEXT,
+    messages TEXT NOT NULL,    -- JSON array
+    created_at TEXT NOT NULL,  -- ISO8601 format
+    updated_at TEXT NOT NULL   -- ISO8601 format
+);
+
+CREATE INDEX IF NOT EXISTS idx_conversations_user_updated ON conversations(user_id, updated_at);
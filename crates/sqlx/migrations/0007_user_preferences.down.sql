@@ -0,0 +1,2 @@
+-- Revert user preferences
+DROP TABLE IF EXISTS user_preferences;
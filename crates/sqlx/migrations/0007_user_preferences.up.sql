@@ -0,0 +1,6 @@
+-- Per-user UI and client preferences
+CREATE TABLE IF NOT EXISTS user_preferences (
+    user_id TEXT PRIMARY KEY,
+    preferences TEXT NOT NULL, -- JSON object
+    updated_at TEXT NOT NULL   -- ISO8601 format
+);
@@ -0,0 +1,2 @@
+-- Revert stored responses
+DROP TABLE IF EXISTS stored_responses;
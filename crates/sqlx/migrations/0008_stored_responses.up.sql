@@ -0,0 +1,8 @@
+-- Responses API state for upstreams without native previous_response_id support
+CREATE TABLE IF NOT EXISTS stored_responses (
+    id TEXT PRIMARY KEY,
+    owner_id TEXT NOT NULL,
+    input TEXT NOT NULL,      -- JSON array of input items
+    response TEXT NOT NULL,   -- JSON response object
+    created_at TEXT NOT NULL  -- ISO8601 format
+);
@@ -0,0 +1,4 @@
+-- Revert Assistants API emulation storage
+DROP INDEX IF EXISTS idx_assistant_objects_thread;
+DROP INDEX IF EXISTS idx_assistant_objects_owner;
+DROP TABLE IF EXISTS assistant_objects;
@@ -0,0 +1,13 @@
+-- Assistants API emulation: assistants, threads, messages and runs
+CREATE TABLE IF NOT EXISTS assistant_objects (
+    id TEXT PRIMARY KEY,
+    object TEXT NOT NULL,     -- assistant, thread, thread.message or thread.run
+    owner_id TEXT NOT NULL,
+    thread_id TEXT,
+    data TEXT NOT NULL,       -- JSON object returned to clients
+    state TEXT NOT NULL,      -- JSON bookkeeping
+    created_at TEXT NOT NULL  -- ISO8601 format
+);
+
+CREATE INDEX IF NOT EXISTS idx_assistant_objects_owner ON assistant_objects(owner_id, object);
+CREATE INDEX IF NOT EXISTS idx_assistant_objects_thread ON assistant_objects(thread_id);
@@ -0,0 +1,4 @@
+-- Revert prompt library storage
+DROP INDEX IF EXISTS idx_prompt_renders_prompt;
+DROP TABLE IF EXISTS prompt_renders;
+DROP TABLE IF EXISTS prompt_templates;
@@ -0,0 +1,24 @@
+-- Prompt library: versioned templates and a record of requests rendered from them
+CREATE TABLE IF NOT EXISTS prompt_templates (
+    name TEXT NOT NULL,
+    version INTEGER NOT NULL,
+    description TEXT,
+    messages TEXT NOT NULL,    -- JSON array of {role, content}
+    variables TEXT NOT NULL,   -- JSON array of {name, default}
+    parameters TEXT NOT NULL,  -- JSON object of default request parameters
+    created_by TEXT NOT NULL,
+    created_at TEXT NOT NULL,  -- ISO8601 format
+    PRIMARY KEY (name, version)
+);
+
+CREATE TABLE IF NOT EXISTS prompt_renders (
+    id TEXT PRIMARY KEY,
+    prompt_name TEXT NOT NULL,
+    version INTEGER NOT NULL,
+    user_id TEXT NOT NULL,
+    request_id TEXT NOT NULL,
+    model TEXT NOT NULL,
+    created_at TEXT NOT NULL   -- ISO8601 format
+);
+
+CREATE INDEX IF NOT EXISTS idx_prompt_renders_prompt ON prompt_renders(prompt_name, created_at);
@@ -0,0 +1,5 @@
+-- Revert experiment storage
+DROP INDEX IF EXISTS idx_experiment_outcomes_request;
+DROP INDEX IF EXISTS idx_experiment_outcomes_experiment;
+DROP TABLE IF EXISTS experiment_outcomes;
+DROP TABLE IF EXISTS experiments;
@@ -0,0 +1,31 @@
+-- Experiments: A/B tests between variants and the outcome of each request
+CREATE TABLE IF NOT EXISTS experiments (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    description TEXT,
+    model TEXT NOT NULL,
+    status TEXT NOT NULL,      -- draft, running or stopped
+    variants TEXT NOT NULL,    -- JSON array of variants
+    metrics TEXT NOT NULL,     -- JSON array of metric names
+    created_by TEXT NOT NULL,
+    created_at TEXT NOT NULL,  -- ISO8601 format
+    updated_at TEXT NOT NULL   -- ISO8601 format
+);
+
+CREATE TABLE IF NOT EXISTS experiment_outcomes (
+    id TEXT PRIMARY KEY,
+    experiment_id TEXT NOT NULL,
+    variant TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    request_id TEXT NOT NULL,
+    latency_ms INTEGER NOT NULL,
+    input_tokens INTEGER NOT NULL,
+    output_tokens INTEGER NOT NULL,
+    cost REAL NOT NULL,
+    error INTEGER NOT NULL,
+    feedback INTEGER,
+    created_at TEXT NOT NULL   -- ISO8601 format
+);
+
+CREATE INDEX IF NOT EXISTS idx_experiment_outcomes_experiment ON experiment_outcomes(experiment_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_experiment_outcomes_request ON experiment_outcomes(request_id);
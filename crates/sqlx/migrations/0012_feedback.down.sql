@@ -0,0 +1,5 @@
+-- Revert feedback storage
+DROP INDEX IF EXISTS idx_usage_records_request_id;
+DROP INDEX IF EXISTS idx_feedback_updated_at;
+DROP INDEX IF EXISTS idx_feedback_request_user;
+DROP TABLE IF EXISTS feedback;
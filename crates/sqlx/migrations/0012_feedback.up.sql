@@ -0,0 +1,17 @@
+-- Feedback: user ratings of responses, joined to usage records by request_id
+CREATE TABLE IF NOT EXISTS feedback (
+    id TEXT PRIMARY KEY,
+    request_id TEXT NOT NULL,  -- correlation id of the rated request
+    user_id TEXT NOT NULL,
+    thumbs_up INTEGER,
+    rating INTEGER,            -- 1 to 5
+    comment TEXT,
+    model_id TEXT,
+    provider_id TEXT,
+    created_at TEXT NOT NULL,  -- ISO8601 format
+    updated_at TEXT NOT NULL   -- ISO8601 format
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_feedback_request_user ON feedback(request_id, user_id);
+CREATE INDEX IF NOT EXISTS idx_feedback_updated_at ON feedback(updated_at);
+CREATE INDEX IF NOT EXISTS idx_usage_records_request_id ON usage_records(request_id);
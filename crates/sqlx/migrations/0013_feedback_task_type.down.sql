@@ -0,0 +1,2 @@
+-- Revert feedback task type
+ALTER TABLE feedback DROP COLUMN task_type;
@@ -0,0 +1,2 @@
+-- Task type of the rated request, for quality-aware routing
+ALTER TABLE feedback ADD COLUMN task_type TEXT;
@@ -0,0 +1,4 @@
+-- Revert permission decision storage
+DROP INDEX IF EXISTS idx_permission_decisions_created_at;
+DROP INDEX IF EXISTS idx_permission_decisions_subject;
+DROP TABLE IF EXISTS permission_decisions;
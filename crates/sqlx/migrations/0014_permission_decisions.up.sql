@@ -0,0 +1,14 @@
+-- Permission decisions: sampled outcomes of permission checks for auditing
+CREATE TABLE IF NOT EXISTS permission_decisions (
+    id TEXT PRIMARY KEY,
+    subject_id TEXT NOT NULL,
+    action TEXT NOT NULL,
+    object TEXT NOT NULL,       -- namespace/kind/id
+    allowed INTEGER NOT NULL,
+    policy TEXT NOT NULL,       -- owner, grant, no_grant or error
+    reason TEXT,
+    created_at TEXT NOT NULL    -- ISO8601 format
+);
+
+CREATE INDEX IF NOT EXISTS idx_permission_decisions_subject ON permission_decisions(subject_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_permission_decisions_created_at ON permission_decisions(created_at);
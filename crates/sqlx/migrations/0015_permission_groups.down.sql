@@ -0,0 +1,3 @@
+-- Revert permission groups
+DROP INDEX IF EXISTS idx_group_members_subject;
+DROP TABLE IF EXISTS group_members;
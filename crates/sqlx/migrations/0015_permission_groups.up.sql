@@ -0,0 +1,9 @@
+-- Permission groups: members inherit the grants stored for `group:<name>`
+CREATE TABLE IF NOT EXISTS group_members (
+    group_name TEXT NOT NULL,
+    subject_id TEXT NOT NULL,
+    added_at TEXT NOT NULL,     -- ISO8601 format
+    PRIMARY KEY (group_name, subject_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_group_members_subject ON group_members(subject_id);
@@ -0,0 +1,2 @@
+-- Revert stream slots
+DROP TABLE IF EXISTS stream_slots;
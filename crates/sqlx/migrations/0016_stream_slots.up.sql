@@ -0,0 +1,7 @@
+-- Stream slots held by API keys; a slot past its expiry is free
+CREATE TABLE IF NOT EXISTS stream_slots (
+    slot_key TEXT NOT NULL,
+    slot_id TEXT NOT NULL,
+    expires_at TEXT NOT NULL,   -- ISO8601 format
+    PRIMARY KEY (slot_key, slot_id)
+);
@@ -0,0 +1,3 @@
+-- Revert routing decisions
+DROP INDEX IF EXISTS idx_routing_decisions_user;
+DROP TABLE IF EXISTS routing_decisions;
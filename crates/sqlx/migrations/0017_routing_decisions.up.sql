@@ -0,0 +1,14 @@
+-- Audit trail of how requests for the automatic model were routed
+CREATE TABLE IF NOT EXISTS routing_decisions (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    request_id TEXT NOT NULL,
+    requested_model TEXT NOT NULL,
+    model TEXT NOT NULL,
+    tier TEXT NOT NULL,
+    rationale TEXT NOT NULL,
+    overridden INTEGER NOT NULL,
+    created_at TEXT NOT NULL   -- ISO8601 format
+);
+
+CREATE INDEX IF NOT EXISTS idx_routing_decisions_user ON routing_decisions(user_id, created_at);
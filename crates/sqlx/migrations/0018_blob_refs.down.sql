@@ -0,0 +1,3 @@
+-- Revert blob reference counts
+DROP INDEX IF EXISTS idx_blob_refs_refcount;
+DROP TABLE IF EXISTS blob_refs;
@@ -0,0 +1,9 @@
+-- Reference counts of the deduplicated body strings kept in the blob store
+CREATE TABLE IF NOT EXISTS blob_refs (
+    hash TEXT PRIMARY KEY,     -- SHA-256 of the contents, hex
+    refcount INTEGER NOT NULL,
+    size INTEGER NOT NULL,     -- compressed bytes on disk
+    created_at TEXT NOT NULL   -- ISO8601 format
+);
+
+CREATE INDEX IF NOT EXISTS idx_blob_refs_refcount ON blob_refs(refcount);
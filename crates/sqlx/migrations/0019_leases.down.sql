@@ -0,0 +1,2 @@
+-- Revert leases
+DROP TABLE IF EXISTS leases;
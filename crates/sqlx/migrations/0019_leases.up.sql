@@ -0,0 +1,7 @@
+-- Leases electing the daemon that runs work only one replica should; a
+-- lease past its expiry is free
+CREATE TABLE IF NOT EXISTS leases (
+    name TEXT PRIMARY KEY,
+    holder TEXT NOT NULL,
+    expires_at TEXT NOT NULL    -- ISO8601 format
+);
@@ -0,0 +1,198 @@
+//! Content-addressed store for large body strings
+//!
+//! System prompts, pasted documents and tool output repeat across stored
+//! conversations and responses. Each string in a body over the size
+//! threshold is kept once, zstd compressed, in the blob store under the
+//! SHA-256 of its contents, and the body holds a `{"$blob": "<hash>"}`
+//! reference in its place. The backend counts references per blob in `blob_refs` as rows
+//! are written and deleted; blobs no row refers to are removed by
+//! [`StateBackend::collect_blobs`](gate_core::StateBackend::collect_blobs).
+
+use chrono::{DateTime, Utc};
+use gate_core::{BlobStore, Result};
+use serde_json::Value as JsonValue;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io;
+use std::sync::Arc;
+
+/// Key of a blob reference object
+const BLOB_KEY: &str = "$blob";
+
+/// Prefix of the keys blobs are stored under
+const KEY_PREFIX: &str = "blobs/";
+
+/// zstd level blobs are compressed at
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// Compressed blobs named by content hash
+#[derive(Clone)]
+pub struct ContentStore {
+    store: Arc<dyn BlobStore>,
+    min_bytes: Option<usize>,
+}
+
+impl ContentStore {
+    /// Keep strings of at least `min_bytes` in `store`. With `None` new
+    /// bodies are stored whole, while references already written still
+    /// resolve.
+    pub fn new(store: Arc<dyn BlobStore>, min_bytes: Option<usize>) -> Self {
+        Self { store, min_bytes }
+    }
+
+    fn is_valid_hash(hash: &str) -> bool {
+        hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit())
+    }
+
+    fn key(hash: &str) -> String {
+        format!("{KEY_PREFIX}{}/{hash}", &hash[..2])
+    }
+
+    /// Replace the long strings in `value` with references, returning each
+    /// string moved out by hash
+    pub(crate) fn externalize(&self, value: &mut JsonValue) -> Vec<(String, String)> {
+        let Some(min_bytes) = self.min_bytes else {
+            return Vec::new();
+        };
+        let mut moved = Vec::new();
+        externalize(value, min_bytes, &mut moved);
+        moved
+    }
+
+    /// Write a blob unless it is already stored, returning its stored size
+    pub(crate) async fn write(&self, hash: &str, content: &[u8]) -> Result<u64> {
+        let key = Self::key(hash);
+        if let Some(info) = self.store.head(&key).await? {
+            return Ok(info.size);
+        }
+        let compressed = zstd::encode_all(content, COMPRESSION_LEVEL)?;
+        let size = compressed.len() as u64;
+        self.store.put(&key, compressed).await?;
+        Ok(size)
+    }
+
+    async fn read(&self, hash: &str) -> Result<String> {
+        let compressed = self
+            .store
+            .get(&Self::key(hash))
+            .await?
+            .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))?;
+        let content = zstd::decode_all(compressed.as_slice())?;
+        Ok(
+            String::from_utf8(content)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+        )
+    }
+
+    /// Put the strings referenced in `value` back in place. References to
+    /// missing blobs are left as they are.
+    pub(crate) async fn hydrate(&self, value: &mut JsonValue) {
+        let hashes = references(value);
+        if hashes.is_empty() {
+            return;
+        }
+        let mut contents = HashMap::new();
+        for hash in hashes {
+            if contents.contains_key(&hash) {
+                continue;
+            }
+            match self.read(&hash).await {
+                Ok(content) => {
+                    contents.insert(hash, content);
+                }
+                Err(e) => tracing::warn!("Failed to read blob {hash}: {e}"),
+            }
+        }
+        hydrate(value, &contents);
+    }
+
+    /// Stored blobs by hash, with their size and when they were last written
+    pub(crate) async fn list(&self) -> Result<Vec<(String, u64, DateTime<Utc>)>> {
+        Ok(self
+            .store
+            .list(KEY_PREFIX)
+            .await?
+            .into_iter()
+            .filter_map(|info| {
+                let hash = info.key.rsplit('/').next()?;
+                (Self::is_valid_hash(hash) && info.key == Self::key(hash))
+                    .then(|| (hash.to_string(), info.size, info.modified))
+            })
+            .collect())
+    }
+
+    pub(crate) async fn remove(&self, hash: &str) -> Result<()> {
+        self.store.delete(&Self::key(hash)).await
+    }
+}
+
+fn externalize(value: &mut JsonValue, min_bytes: usize, moved: &mut Vec<(String, String)>) {
+    match value {
+        JsonValue::String(content) if content.len() >= min_bytes => {
+            let hash = hex::encode(Sha256::digest(content.as_bytes()));
+            let content = std::mem::take(content);
+            *value = JsonValue::Object(serde_json::Map::from_iter([(
+                BLOB_KEY.to_string(),
+                JsonValue::String(hash.clone()),
+            )]));
+            moved.push((hash, content));
+        }
+        JsonValue::Array(items) => {
+            for item in items {
+                externalize(item, min_bytes, moved);
+            }
+        }
+        JsonValue::Object(fields) => {
+            for field in fields.values_mut() {
+                externalize(field, min_bytes, moved);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Hash a blob reference names, if `value` is one
+fn reference(value: &JsonValue) -> Option<&str> {
+    let fields = value.as_object()?;
+    let hash = fields.get(BLOB_KEY)?.as_str()?;
+    (fields.len() == 1 && ContentStore::is_valid_hash(hash)).then_some(hash)
+}
+
+/// Hashes of the blobs `value` refers to, once per reference
+pub(crate) fn references(value: &JsonValue) -> Vec<String> {
+    fn collect(value: &JsonValue, hashes: &mut Vec<String>) {
+        if let Some(hash) = reference(value) {
+            hashes.push(hash.to_string());
+            return;
+        }
+        match value {
+            JsonValue::Array(items) => items.iter().for_each(|item| collect(item, hashes)),
+            JsonValue::Object(fields) => fields.values().for_each(|field| collect(field, hashes)),
+            _ => {}
+        }
+    }
+    let mut hashes = Vec::new();
+    collect(value, &mut hashes);
+    hashes
+}
+
+/// References in a stored body, which may not be JSON at all
+pub(crate) fn stored_references(body: &str) -> Vec<String> {
+    serde_json::from_str(body)
+        .map(|value| references(&value))
+        .unwrap_or_default()
+}
+
+fn hydrate(value: &mut JsonValue, contents: &HashMap<String, String>) {
+    if let Some(content) = reference(value).and_then(|hash| contents.get(hash)) {
+        *value = JsonValue::String(content.clone());
+        return;
+    }
+    match value {
+        JsonValue::Array(items) => items.iter_mut().for_each(|item| hydrate(item, contents)),
+        JsonValue::Object(fields) => fields
+            .values_mut()
+            .for_each(|field| hydrate(field, contents)),
+        _ => {}
+    }
+}
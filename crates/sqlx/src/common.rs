@@ -2,8 +2,10 @@
 
 use chrono::{DateTime, Utc};
 use gate_core::{
-    ApiKey, Error, Model, ModelType, Organization, Provider, ProviderType, Result, UsageRecord,
-    User,
+    ApiKey, AssistantObject, Conversation, Error, Experiment, ExperimentOutcome, ExperimentStatus,
+    Feedback, Model, ModelType, Organization, PermissionDecision, PromptRender, PromptTemplate,
+    Provider, ProviderType, Result, RoutingDecision, StoredResponse, UsageRecord, User,
+    UserPreferences,
 };
 use sqlx::FromRow;
 use std::collections::HashMap;
@@ -28,6 +30,7 @@ pub struct UserRow {
     pub created_at: String,          // ISO8601 format
     pub updated_at: String,          // ISO8601 format
     pub disabled_at: Option<String>, // ISO8601 format
+    pub metadata: Option<String>,    // JSON string
 }
 
 #[derive(FromRow)]
@@ -40,6 +43,137 @@ pub struct ApiKeyRow {
     pub last_used_at: Option<String>, // ISO8601 format
 }
 
+#[derive(FromRow)]
+pub struct ConversationRow {
+    pub id: String,
+    pub user_id: String,
+    pub title: String,
+    pub model: Option<String>,
+    pub messages: String,   // JSON string
+    pub created_at: String, // ISO8601 format
+    pub updated_at: String, // ISO8601 format
+}
+
+#[derive(FromRow)]
+pub struct UserPreferencesRow {
+    pub user_id: String,
+    pub preferences: String, // JSON string
+    pub updated_at: String,  // ISO8601 format
+}
+
+#[derive(FromRow)]
+pub struct StoredResponseRow {
+    pub id: String,
+    pub owner_id: String,
+    pub input: String,      // JSON string
+    pub response: String,   // JSON string
+    pub created_at: String, // ISO8601 format
+}
+
+#[derive(FromRow)]
+pub struct AssistantObjectRow {
+    pub id: String,
+    pub object: String,
+    pub owner_id: String,
+    pub thread_id: Option<String>,
+    pub data: String,       // JSON string
+    pub state: String,      // JSON string
+    pub created_at: String, // ISO8601 format
+}
+
+#[derive(FromRow)]
+pub struct PromptTemplateRow {
+    pub name: String,
+    pub version: i64,
+    pub description: Option<String>,
+    pub messages: String,   // JSON string
+    pub variables: String,  // JSON string
+    pub parameters: String, // JSON string
+    pub created_by: String,
+    pub created_at: String, // ISO8601 format
+}
+
+#[derive(FromRow)]
+pub struct PromptRenderRow {
+    pub id: String,
+    pub prompt_name: String,
+    pub version: i64,
+    pub user_id: String,
+    pub request_id: String,
+    pub model: String,
+    pub created_at: String, // ISO8601 format
+}
+
+#[derive(FromRow)]
+pub struct ExperimentRow {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub model: String,
+    pub status: String,
+    pub variants: String, // JSON string
+    pub metrics: String,  // JSON string
+    pub created_by: String,
+    pub created_at: String, // ISO8601 format
+    pub updated_at: String, // ISO8601 format
+}
+
+#[derive(FromRow)]
+pub struct ExperimentOutcomeRow {
+    pub id: String,
+    pub experiment_id: String,
+    pub variant: String,
+    pub user_id: String,
+    pub request_id: String,
+    pub latency_ms: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cost: f64,
+    pub error: bool,
+    pub feedback: Option<i64>,
+    pub created_at: String, // ISO8601 format
+}
+
+#[derive(FromRow)]
+pub struct FeedbackRow {
+    pub id: String,
+    pub request_id: String,
+    pub user_id: String,
+    pub thumbs_up: Option<bool>,
+    pub rating: Option<i64>,
+    pub comment: Option<String>,
+    pub model_id: Option<String>,
+    pub provider_id: Option<String>,
+    pub task_type: Option<String>,
+    pub created_at: String, // ISO8601 format
+    pub updated_at: String, // ISO8601 format
+}
+
+#[derive(FromRow)]
+pub struct PermissionDecisionRow {
+    pub id: String,
+    pub subject_id: String,
+    pub action: String,
+    pub object: String,
+    pub allowed: bool,
+    pub policy: String,
+    pub reason: Option<String>,
+    pub created_at: String, // ISO8601 format
+}
+
+#[derive(FromRow)]
+pub struct RoutingDecisionRow {
+    pub id: String,
+    pub user_id: String,
+    pub request_id: String,
+    pub requested_model: String,
+    pub model: String,
+    pub tier: String,
+    pub rationale: String,
+    pub overridden: bool,
+    pub created_at: String, // ISO8601 format
+}
+
 #[derive(FromRow)]
 pub struct UsageRecordRow {
     pub id: String,
@@ -89,7 +223,10 @@ pub struct OrganizationRow {
 // Conversion implementations
 impl From<UserRow> for User {
     fn from(row: UserRow) -> Self {
-        let mut metadata = HashMap::new();
+        let mut metadata: HashMap<String, String> = row
+            .metadata
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
         if let Some(email) = row.email {
             metadata.insert("email".to_string(), email);
         }
@@ -105,6 +242,174 @@ impl From<UserRow> for User {
     }
 }
 
+impl From<ConversationRow> for Conversation {
+    fn from(row: ConversationRow) -> Self {
+        Conversation {
+            id: row.id,
+            user_id: row.user_id,
+            title: row.title,
+            model: row.model,
+            messages: serde_json::from_str(&row.messages)
+                .unwrap_or_else(|_| serde_json::Value::Array(Vec::new())),
+            created_at: string_to_datetime(&row.created_at).unwrap_or_else(|_| Utc::now()),
+            updated_at: string_to_datetime(&row.updated_at).unwrap_or_else(|_| Utc::now()),
+        }
+    }
+}
+
+impl From<UserPreferencesRow> for UserPreferences {
+    fn from(row: UserPreferencesRow) -> Self {
+        UserPreferences {
+            user_id: row.user_id,
+            preferences: serde_json::from_str(&row.preferences)
+                .unwrap_or_else(|_| serde_json::Value::Object(Default::default())),
+            updated_at: string_to_datetime(&row.updated_at).unwrap_or_else(|_| Utc::now()),
+        }
+    }
+}
+
+impl From<StoredResponseRow> for StoredResponse {
+    fn from(row: StoredResponseRow) -> Self {
+        StoredResponse {
+            id: row.id,
+            owner_id: row.owner_id,
+            input: serde_json::from_str(&row.input)
+                .unwrap_or_else(|_| serde_json::Value::Array(Vec::new())),
+            response: serde_json::from_str(&row.response).unwrap_or_default(),
+            created_at: string_to_datetime(&row.created_at).unwrap_or_else(|_| Utc::now()),
+        }
+    }
+}
+
+impl From<AssistantObjectRow> for AssistantObject {
+    fn from(row: AssistantObjectRow) -> Self {
+        AssistantObject {
+            id: row.id,
+            object: row.object,
+            owner_id: row.owner_id,
+            thread_id: row.thread_id,
+            data: serde_json::from_str(&row.data).unwrap_or_default(),
+            state: serde_json::from_str(&row.state).unwrap_or_default(),
+            created_at: string_to_datetime(&row.created_at).unwrap_or_else(|_| Utc::now()),
+        }
+    }
+}
+
+impl From<PromptTemplateRow> for PromptTemplate {
+    fn from(row: PromptTemplateRow) -> Self {
+        PromptTemplate {
+            name: row.name,
+            version: row.version as u32,
+            description: row.description,
+            messages: serde_json::from_str(&row.messages).unwrap_or_default(),
+            variables: serde_json::from_str(&row.variables).unwrap_or_default(),
+            parameters: serde_json::from_str(&row.parameters).unwrap_or_default(),
+            created_by: row.created_by,
+            created_at: string_to_datetime(&row.created_at).unwrap_or_else(|_| Utc::now()),
+        }
+    }
+}
+
+impl From<PromptRenderRow> for PromptRender {
+    fn from(row: PromptRenderRow) -> Self {
+        PromptRender {
+            id: row.id,
+            prompt_name: row.prompt_name,
+            version: row.version as u32,
+            user_id: row.user_id,
+            request_id: row.request_id,
+            model: row.model,
+            created_at: string_to_datetime(&row.created_at).unwrap_or_else(|_| Utc::now()),
+        }
+    }
+}
+
+impl From<PermissionDecisionRow> for PermissionDecision {
+    fn from(row: PermissionDecisionRow) -> Self {
+        PermissionDecision {
+            id: row.id,
+            subject_id: row.subject_id,
+            action: row.action,
+            object: row.object,
+            allowed: row.allowed,
+            policy: row.policy,
+            reason: row.reason,
+            created_at: string_to_datetime(&row.created_at).unwrap_or_else(|_| Utc::now()),
+        }
+    }
+}
+
+impl From<RoutingDecisionRow> for RoutingDecision {
+    fn from(row: RoutingDecisionRow) -> Self {
+        RoutingDecision {
+            id: row.id,
+            user_id: row.user_id,
+            request_id: row.request_id,
+            requested_model: row.requested_model,
+            model: row.model,
+            tier: row.tier,
+            rationale: row.rationale,
+            overridden: row.overridden,
+            created_at: string_to_datetime(&row.created_at).unwrap_or_else(|_| Utc::now()),
+        }
+    }
+}
+
+impl From<ExperimentRow> for Experiment {
+    fn from(row: ExperimentRow) -> Self {
+        Experiment {
+            id: row.id,
+            name: row.name,
+            description: row.description,
+            model: row.model,
+            status: serde_json::from_value(serde_json::Value::String(row.status))
+                .unwrap_or(ExperimentStatus::Stopped),
+            variants: serde_json::from_str(&row.variants).unwrap_or_default(),
+            metrics: serde_json::from_str(&row.metrics).unwrap_or_default(),
+            created_by: row.created_by,
+            created_at: string_to_datetime(&row.created_at).unwrap_or_else(|_| Utc::now()),
+            updated_at: string_to_datetime(&row.updated_at).unwrap_or_else(|_| Utc::now()),
+        }
+    }
+}
+
+impl From<ExperimentOutcomeRow> for ExperimentOutcome {
+    fn from(row: ExperimentOutcomeRow) -> Self {
+        ExperimentOutcome {
+            id: row.id,
+            experiment_id: row.experiment_id,
+            variant: row.variant,
+            user_id: row.user_id,
+            request_id: row.request_id,
+            latency_ms: row.latency_ms as u64,
+            input_tokens: row.input_tokens as u64,
+            output_tokens: row.output_tokens as u64,
+            cost: row.cost,
+            error: row.error,
+            feedback: row.feedback.map(|f| f as i32),
+            created_at: string_to_datetime(&row.created_at).unwrap_or_else(|_| Utc::now()),
+        }
+    }
+}
+
+impl From<FeedbackRow> for Feedback {
+    fn from(row: FeedbackRow) -> Self {
+        Feedback {
+            id: row.id,
+            request_id: row.request_id,
+            user_id: row.user_id,
+            thumbs_up: row.thumbs_up,
+            rating: row.rating.map(|r| r as u8),
+            comment: row.comment,
+            model_id: row.model_id,
+            provider_id: row.provider_id,
+            task_type: row.task_type,
+            created_at: string_to_datetime(&row.created_at).unwrap_or_else(|_| Utc::now()),
+            updated_at: string_to_datetime(&row.updated_at).unwrap_or_else(|_| Utc::now()),
+        }
+    }
+}
+
 impl From<ApiKeyRow> for ApiKey {
     fn from(row: ApiKeyRow) -> Self {
         ApiKey {
@@ -1,5 +1,6 @@
 extern crate tracing;
 
+mod blobs;
 mod common;
 mod webauthn;
 
@@ -9,6 +10,8 @@ mod sqlite;
 #[cfg(feature = "sqlite")]
 pub use webauthn::{SqlxWebAuthnBackend, StoredCredential};
 
+pub use blobs::ContentStore;
+
 #[cfg(feature = "sqlite")]
 pub use sqlite::SqliteStateBackend;
 
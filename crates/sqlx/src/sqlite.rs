@@ -1,21 +1,46 @@
+use crate::blobs::{ContentStore, stored_references};
 use crate::common::{
-    ApiKeyRow, ModelRow, OrganizationRow, ProviderRow, UsageRecordRow, UserRow, datetime_to_string,
-    string_to_datetime,
+    ApiKeyRow, AssistantObjectRow, ConversationRow, ExperimentOutcomeRow, ExperimentRow,
+    FeedbackRow, ModelRow, OrganizationRow, PermissionDecisionRow, PromptRenderRow,
+    PromptTemplateRow, ProviderRow, RoutingDecisionRow, StoredResponseRow, UsageRecordRow,
+    UserPreferencesRow, UserRow, datetime_to_string, string_to_datetime,
 };
 use async_trait::async_trait;
 use gate_core::{
-    ApiKey, Error, Model, Organization, Provider, Result, StateBackend, TimeRange, UsageRecord,
-    User,
+    ApiKey, AssistantObject, BlobCollection, Conversation, DataClass, Error, Experiment,
+    ExperimentOutcome, Feedback, Model, Organization, PermissionDecision, PermissionDecisionFilter,
+    PromptRender, PromptTemplate, Provider, Result, RoutingDecision, StateBackend, StoredResponse,
+    TimeRange, UsageRecord, UsageSubject, UsageTotals, User, UserDataDeletion, UserPreferences,
     access::{Action, ObjectIdentity},
+    state::{MigrationInfo, SchemaMigrator},
 };
-use sqlx::{Pool, Sqlite};
+use serde_json::Value as JsonValue;
+use sqlx::migrate::{Migrate, MigrationType, Migrator};
+use sqlx::{Pool, QueryBuilder, Sqlite, Transaction};
+use std::collections::HashSet;
+
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// Tables whose body columns may refer to blobs
+const CONVERSATION_BODIES: (&str, &[&str]) = ("conversations", &["messages"]);
+const STORED_RESPONSE_BODIES: (&str, &[&str]) = ("stored_responses", &["input", "response"]);
 
 pub struct SqliteStateBackend {
     pool: Pool<Sqlite>,
+    content_store: Option<ContentStore>,
 }
 
 impl SqliteStateBackend {
+    /// Connect and apply any pending migrations
     pub async fn new(database_url: &str) -> Result<Self> {
+        let backend = Self::connect(database_url).await?;
+        backend.migrate_up().await?;
+        Ok(backend)
+    }
+
+    /// Connect without touching the schema, so its migration status can be
+    /// checked or a backup taken first
+    pub async fn connect(database_url: &str) -> Result<Self> {
         use sqlx::sqlite::SqliteConnectOptions;
         use std::str::FromStr;
 
@@ -27,162 +52,1271 @@ impl SqliteStateBackend {
             .await
             .map_err(|e| Error::StateError(format!("Failed to connect to database: {e}")))?;
 
-        sqlx::migrate!("./migrations")
-            .run(&pool)
+        Ok(Self::from_pool(pool))
+    }
+
+    pub fn from_pool(pool: Pool<Sqlite>) -> Self {
+        Self {
+            pool,
+            content_store: None,
+        }
+    }
+
+    pub fn pool(&self) -> &Pool<Sqlite> {
+        &self.pool
+    }
+
+    /// Keep the long strings of conversation and stored response bodies in
+    /// `store`, once each
+    pub fn with_content_store(mut self, store: ContentStore) -> Self {
+        self.content_store = Some(store);
+        self
+    }
+
+    /// Serialize a body for its column, moving long strings to the content
+    /// store. Returns the JSON and the blobs it refers to, with their size.
+    async fn store_body(
+        &self,
+        body: &JsonValue,
+        what: &str,
+    ) -> Result<(String, Vec<(String, u64)>)> {
+        let serialize = |body: &JsonValue| {
+            serde_json::to_string(body)
+                .map_err(|e| Error::StateError(format!("Failed to serialize {what}: {e}")))
+        };
+        let Some(store) = &self.content_store else {
+            return Ok((serialize(body)?, Vec::new()));
+        };
+        let mut body = body.clone();
+        let mut blobs = Vec::new();
+        for (hash, content) in store.externalize(&mut body) {
+            let size = store
+                .write(&hash, content.as_bytes())
+                .await
+                .map_err(|e| Error::StateError(format!("Failed to store {what} blob: {e}")))?;
+            blobs.push((hash, size));
+        }
+        Ok((serialize(&body)?, blobs))
+    }
+
+    /// Put the strings a body refers to back in place
+    async fn load_body(&self, body: &mut JsonValue) {
+        if let Some(store) = &self.content_store {
+            store.hydrate(body).await;
+        }
+    }
+}
+
+/// Query selecting the bodies of `table` rows matching `condition` that
+/// refer to blobs, one body per row
+fn body_query((table, columns): (&str, &[&str]), condition: &str) -> String {
+    columns
+        .iter()
+        .map(|column| {
+            format!(
+                r#"SELECT {column} FROM {table} WHERE {condition} AND {column} LIKE '%"$blob"%'"#
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" UNION ALL ")
+}
+
+/// Count new references to `added` blobs and drop references to `removed`
+async fn update_blob_refs(
+    tx: &mut Transaction<'_, Sqlite>,
+    added: &[(String, u64)],
+    removed: &[String],
+) -> Result<()> {
+    let now = datetime_to_string(chrono::Utc::now());
+    for (hash, size) in added {
+        sqlx::query(
+            r#"
+            INSERT INTO blob_refs (hash, refcount, size, created_at) VALUES (?1, 1, ?2, ?3)
+            ON CONFLICT(hash) DO UPDATE SET refcount = refcount + 1
+            "#,
+        )
+        .bind(hash)
+        .bind(*size as i64)
+        .bind(&now)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to count blob reference: {e}")))?;
+    }
+    for hash in removed {
+        sqlx::query("UPDATE blob_refs SET refcount = refcount - 1 WHERE hash = ?1")
+            .bind(hash)
+            .execute(&mut **tx)
             .await
-            .map_err(|e| Error::StateError(format!("Failed to run migrations: {e}")))?;
+            .map_err(|e| Error::StateError(format!("Failed to drop blob reference: {e}")))?;
+    }
+    Ok(())
+}
+
+/// Drop the blob references of the bodies `query` selects, ahead of
+/// deleting or replacing their rows
+async fn release_blob_refs(
+    tx: &mut Transaction<'_, Sqlite>,
+    query: &str,
+    bind: &str,
+) -> Result<()> {
+    let bodies: Vec<String> = sqlx::query_scalar(query)
+        .bind(bind)
+        .fetch_all(&mut **tx)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to read blob references: {e}")))?;
+    let removed: Vec<String> = bodies
+        .iter()
+        .flat_map(|body| stored_references(body))
+        .collect();
+    update_blob_refs(tx, &[], &removed).await
+}
+
+/// Serialize user metadata, leaving out the email which has its own column
+fn user_metadata_json(user: &User) -> Result<String> {
+    let metadata: std::collections::HashMap<&String, &String> = user
+        .metadata
+        .iter()
+        .filter(|(key, _)| key.as_str() != "email")
+        .collect();
+    serde_json::to_string(&metadata)
+        .map_err(|e| Error::StateError(format!("Failed to serialize user metadata: {e}")))
+}
+
+#[async_trait]
+impl StateBackend for SqliteStateBackend {
+    // User management
+    async fn get_user(&self, user_id: &str) -> Result<Option<User>> {
+        let row = sqlx::query_as::<_, UserRow>(
+            "SELECT id, email, name, created_at, updated_at, disabled_at, metadata FROM users WHERE id = ?1",
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to get user: {e}")))?;
+
+        Ok(row.map(User::from))
+    }
+
+    async fn get_user_by_id(&self, user_id: &str) -> Result<Option<User>> {
+        self.get_user(user_id).await
+    }
+
+    async fn create_user(&self, user: &User) -> Result<()> {
+        let email = user.metadata.get("email").map(|s| s.as_str());
+        let metadata = user_metadata_json(user)?;
+        let created_at = datetime_to_string(user.created_at);
+        let updated_at = datetime_to_string(user.updated_at);
+
+        sqlx::query(
+            "INSERT INTO users (id, email, name, created_at, updated_at, disabled_at, metadata) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        )
+        .bind(&user.id)
+        .bind(email)
+        .bind(&user.name)
+        .bind(&created_at)
+        .bind(&updated_at)
+        .bind(user.disabled_at.map(datetime_to_string))
+        .bind(&metadata)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to create user: {e}")))?;
+
+        Ok(())
+    }
+
+    async fn update_user(&self, user: &User) -> Result<()> {
+        let email = user.metadata.get("email").map(|s| s.as_str());
+        let metadata = user_metadata_json(user)?;
+        let updated_at = datetime_to_string(user.updated_at);
+
+        sqlx::query("UPDATE users SET email = ?2, name = ?3, updated_at = ?4, disabled_at = ?5, metadata = ?6 WHERE id = ?1")
+            .bind(&user.id)
+            .bind(email)
+            .bind(&user.name)
+            .bind(&updated_at)
+            .bind(user.disabled_at.map(datetime_to_string))
+            .bind(&metadata)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| Error::StateError(format!("Failed to update user: {e}")))?;
+
+        Ok(())
+    }
+
+    async fn delete_user(&self, user_id: &str) -> Result<()> {
+        sqlx::query("DELETE FROM users WHERE id = ?1")
+            .bind(user_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| Error::StateError(format!("Failed to delete user: {e}")))?;
+
+        Ok(())
+    }
+
+    async fn list_users(&self) -> Result<Vec<User>> {
+        let rows = sqlx::query_as::<_, UserRow>(
+            "SELECT id, email, name, created_at, updated_at, disabled_at, metadata FROM users ORDER BY created_at DESC",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to list users: {e}")))?;
+
+        Ok(rows.into_iter().map(User::from).collect())
+    }
+
+    // API key management
+    async fn get_api_key(&self, key_hash: &str) -> Result<Option<ApiKey>> {
+        let row = sqlx::query_as::<_, ApiKeyRow>(
+            "SELECT key_hash, name, org_id, config, created_at, last_used_at FROM api_keys WHERE key_hash = ?1",
+        )
+        .bind(key_hash)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to get API key: {e}")))?;
+
+        Ok(row.map(ApiKey::from))
+    }
+
+    async fn create_api_key(&self, key: &ApiKey, _raw_key: &str) -> Result<()> {
+        let config = key
+            .config
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| Error::StateError(format!("Failed to serialize config: {e}")))?;
+        let created_at = datetime_to_string(key.created_at);
+        let last_used_at = key.last_used_at.map(datetime_to_string);
+
+        sqlx::query(
+            "INSERT INTO api_keys (key_hash, name, org_id, config, created_at, last_used_at) 
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )
+        .bind(&key.key_hash)
+        .bind(&key.name)
+        .bind(&key.org_id)
+        .bind(config)
+        .bind(&created_at)
+        .bind(last_used_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to create API key: {e}")))?;
+
+        Ok(())
+    }
+
+    async fn list_api_keys(&self, org_id: &str) -> Result<Vec<ApiKey>> {
+        let rows = sqlx::query_as::<_, ApiKeyRow>(
+            "SELECT key_hash, name, org_id, config, created_at, last_used_at 
+             FROM api_keys WHERE org_id = ?1 ORDER BY created_at DESC",
+        )
+        .bind(org_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to list API keys: {e}")))?;
+
+        Ok(rows.into_iter().map(ApiKey::from).collect())
+    }
+
+    async fn delete_api_key(&self, key_hash: &str) -> Result<()> {
+        sqlx::query("DELETE FROM api_keys WHERE key_hash = ?1")
+            .bind(key_hash)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| Error::StateError(format!("Failed to delete API key: {e}")))?;
+
+        Ok(())
+    }
+
+    async fn touch_api_key(
+        &self,
+        key_hash: &str,
+        used_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<()> {
+        sqlx::query("UPDATE api_keys SET last_used_at = ?2 WHERE key_hash = ?1")
+            .bind(key_hash)
+            .bind(datetime_to_string(used_at))
+            .execute(&self.pool)
+            .await
+            .map_err(|e| Error::StateError(format!("Failed to update API key: {e}")))?;
+
+        Ok(())
+    }
+
+    async fn acquire_stream_slot(
+        &self,
+        key: &str,
+        slot_id: &str,
+        limit: u32,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<bool> {
+        let now = datetime_to_string(chrono::Utc::now());
+        sqlx::query("DELETE FROM stream_slots WHERE slot_key = ?1 AND expires_at <= ?2")
+            .bind(key)
+            .bind(&now)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| Error::StateError(format!("Failed to expire stream slots: {e}")))?;
+
+        // Counting and inserting in one statement keeps concurrent
+        // requests from both taking the last slot
+        let result = sqlx::query(
+            r#"
+            INSERT INTO stream_slots (slot_key, slot_id, expires_at)
+            SELECT ?1, ?2, ?3
+            WHERE (SELECT COUNT(*) FROM stream_slots WHERE slot_key = ?1 AND expires_at > ?4) < ?5
+            "#,
+        )
+        .bind(key)
+        .bind(slot_id)
+        .bind(datetime_to_string(expires_at))
+        .bind(&now)
+        .bind(limit as i64)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to acquire stream slot: {e}")))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn renew_stream_slot(
+        &self,
+        key: &str,
+        slot_id: &str,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE stream_slots SET expires_at = ?3 WHERE slot_key = ?1 AND slot_id = ?2 AND expires_at > ?4",
+        )
+        .bind(key)
+        .bind(slot_id)
+        .bind(datetime_to_string(expires_at))
+        .bind(datetime_to_string(chrono::Utc::now()))
+        .execute(&self.pool)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to renew stream slot: {e}")))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn release_stream_slot(&self, key: &str, slot_id: &str) -> Result<()> {
+        sqlx::query("DELETE FROM stream_slots WHERE slot_key = ?1 AND slot_id = ?2")
+            .bind(key)
+            .bind(slot_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| Error::StateError(format!("Failed to release stream slot: {e}")))?;
+
+        Ok(())
+    }
+
+    async fn count_stream_slots(&self, key: &str) -> Result<u32> {
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM stream_slots WHERE slot_key = ?1 AND expires_at > ?2",
+        )
+        .bind(key)
+        .bind(datetime_to_string(chrono::Utc::now()))
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to count stream slots: {e}")))?;
+
+        Ok(count as u32)
+    }
+
+    async fn acquire_lease(
+        &self,
+        name: &str,
+        holder: &str,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<bool> {
+        // Taken over only from its holder or once expired, in one statement
+        // so two replicas cannot both win
+        let result = sqlx::query(
+            r#"
+            INSERT INTO leases (name, holder, expires_at) VALUES (?1, ?2, ?3)
+            ON CONFLICT(name) DO UPDATE SET holder = excluded.holder, expires_at = excluded.expires_at
+            WHERE leases.holder = excluded.holder OR leases.expires_at <= ?4
+            "#,
+        )
+        .bind(name)
+        .bind(holder)
+        .bind(datetime_to_string(expires_at))
+        .bind(datetime_to_string(chrono::Utc::now()))
+        .execute(&self.pool)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to acquire lease: {e}")))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn release_lease(&self, name: &str, holder: &str) -> Result<()> {
+        sqlx::query("DELETE FROM leases WHERE name = ?1 AND holder = ?2")
+            .bind(name)
+            .bind(holder)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| Error::StateError(format!("Failed to release lease: {e}")))?;
 
-        Ok(Self { pool })
+        Ok(())
+    }
+
+    // Conversations
+    async fn save_conversation(&self, conversation: &Conversation) -> Result<()> {
+        let (messages, blobs) = self.store_body(&conversation.messages, "messages").await?;
+
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| Error::StateError(format!("Failed to start transaction: {e}")))?;
+        release_blob_refs(
+            &mut tx,
+            &body_query(CONVERSATION_BODIES, "id = ?1"),
+            &conversation.id,
+        )
+        .await?;
+        update_blob_refs(&mut tx, &blobs, &[]).await?;
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO conversations (id, user_id, title, model, messages, created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            "#,
+        )
+        .bind(&conversation.id)
+        .bind(&conversation.user_id)
+        .bind(&conversation.title)
+        .bind(&conversation.model)
+        .bind(&messages)
+        .bind(datetime_to_string(conversation.created_at))
+        .bind(datetime_to_string(conversation.updated_at))
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to save conversation: {e}")))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| Error::StateError(format!("Failed to save conversation: {e}")))
+    }
+
+    async fn get_conversation(&self, id: &str) -> Result<Option<Conversation>> {
+        let row = sqlx::query_as::<_, ConversationRow>(
+            "SELECT id, user_id, title, model, messages, created_at, updated_at FROM conversations WHERE id = ?1",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to get conversation: {e}")))?;
+
+        let mut conversation = row.map(Conversation::from);
+        if let Some(conversation) = &mut conversation {
+            self.load_body(&mut conversation.messages).await;
+        }
+        Ok(conversation)
     }
 
-    pub fn from_pool(pool: Pool<Sqlite>) -> Self {
-        Self { pool }
+    async fn list_conversations(&self, user_id: &str) -> Result<Vec<Conversation>> {
+        let rows = sqlx::query_as::<_, ConversationRow>(
+            "SELECT id, user_id, title, model, messages, created_at, updated_at FROM conversations WHERE user_id = ?1 ORDER BY updated_at DESC",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to list conversations: {e}")))?;
+
+        let mut conversations: Vec<Conversation> =
+            rows.into_iter().map(Conversation::from).collect();
+        for conversation in &mut conversations {
+            self.load_body(&mut conversation.messages).await;
+        }
+        Ok(conversations)
+    }
+
+    async fn delete_conversation(&self, id: &str) -> Result<()> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| Error::StateError(format!("Failed to start transaction: {e}")))?;
+        release_blob_refs(&mut tx, &body_query(CONVERSATION_BODIES, "id = ?1"), id).await?;
+        sqlx::query("DELETE FROM conversations WHERE id = ?1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| Error::StateError(format!("Failed to delete conversation: {e}")))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| Error::StateError(format!("Failed to delete conversation: {e}")))
+    }
+
+    // Preferences
+    async fn get_user_preferences(&self, user_id: &str) -> Result<Option<UserPreferences>> {
+        let row = sqlx::query_as::<_, UserPreferencesRow>(
+            "SELECT user_id, preferences, updated_at FROM user_preferences WHERE user_id = ?1",
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to get user preferences: {e}")))?;
+
+        Ok(row.map(UserPreferences::from))
+    }
+
+    async fn save_user_preferences(&self, preferences: &UserPreferences) -> Result<()> {
+        let json = serde_json::to_string(&preferences.preferences)
+            .map_err(|e| Error::StateError(format!("Failed to serialize preferences: {e}")))?;
+
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO user_preferences (user_id, preferences, updated_at)
+            VALUES (?1, ?2, ?3)
+            "#,
+        )
+        .bind(&preferences.user_id)
+        .bind(&json)
+        .bind(datetime_to_string(preferences.updated_at))
+        .execute(&self.pool)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to save user preferences: {e}")))?;
+
+        Ok(())
+    }
+
+    // Responses API state
+    async fn get_stored_response(&self, id: &str) -> Result<Option<StoredResponse>> {
+        let row = sqlx::query_as::<_, StoredResponseRow>(
+            "SELECT id, owner_id, input, response, created_at FROM stored_responses WHERE id = ?1",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to get stored response: {e}")))?;
+
+        let mut response = row.map(StoredResponse::from);
+        if let Some(response) = &mut response {
+            self.load_body(&mut response.input).await;
+            self.load_body(&mut response.response).await;
+        }
+        Ok(response)
+    }
+
+    async fn save_stored_response(&self, response: &StoredResponse) -> Result<()> {
+        let (input, mut blobs) = self.store_body(&response.input, "input").await?;
+        let (body, response_blobs) = self.store_body(&response.response, "response").await?;
+        blobs.extend(response_blobs);
+
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| Error::StateError(format!("Failed to start transaction: {e}")))?;
+        release_blob_refs(
+            &mut tx,
+            &body_query(STORED_RESPONSE_BODIES, "id = ?1"),
+            &response.id,
+        )
+        .await?;
+        update_blob_refs(&mut tx, &blobs, &[]).await?;
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO stored_responses (id, owner_id, input, response, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            "#,
+        )
+        .bind(&response.id)
+        .bind(&response.owner_id)
+        .bind(&input)
+        .bind(&body)
+        .bind(datetime_to_string(response.created_at))
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to save stored response: {e}")))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| Error::StateError(format!("Failed to save stored response: {e}")))
+    }
+
+    // Assistants API emulation
+    async fn get_assistant_object(&self, id: &str) -> Result<Option<AssistantObject>> {
+        let row = sqlx::query_as::<_, AssistantObjectRow>(
+            r#"
+            SELECT id, object, owner_id, thread_id, data, state, created_at
+            FROM assistant_objects WHERE id = ?1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to get assistant object: {e}")))?;
+
+        Ok(row.map(AssistantObject::from))
+    }
+
+    async fn save_assistant_object(&self, object: &AssistantObject) -> Result<()> {
+        let data = serde_json::to_string(&object.data)
+            .map_err(|e| Error::StateError(format!("Failed to serialize data: {e}")))?;
+        let state = serde_json::to_string(&object.state)
+            .map_err(|e| Error::StateError(format!("Failed to serialize state: {e}")))?;
+
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO assistant_objects
+                (id, object, owner_id, thread_id, data, state, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            "#,
+        )
+        .bind(&object.id)
+        .bind(&object.object)
+        .bind(&object.owner_id)
+        .bind(&object.thread_id)
+        .bind(&data)
+        .bind(&state)
+        .bind(datetime_to_string(object.created_at))
+        .execute(&self.pool)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to save assistant object: {e}")))?;
+
+        Ok(())
+    }
+
+    async fn list_assistant_objects(
+        &self,
+        owner_id: &str,
+        object: &str,
+        thread_id: Option<&str>,
+    ) -> Result<Vec<AssistantObject>> {
+        let rows = sqlx::query_as::<_, AssistantObjectRow>(
+            r#"
+            SELECT id, object, owner_id, thread_id, data, state, created_at
+            FROM assistant_objects
+            WHERE owner_id = ?1 AND object = ?2 AND (?3 IS NULL OR thread_id = ?3)
+            ORDER BY created_at ASC, rowid ASC
+            "#,
+        )
+        .bind(owner_id)
+        .bind(object)
+        .bind(thread_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to list assistant objects: {e}")))?;
+
+        Ok(rows.into_iter().map(AssistantObject::from).collect())
+    }
+
+    async fn delete_assistant_object(&self, id: &str) -> Result<()> {
+        sqlx::query("DELETE FROM assistant_objects WHERE id = ?1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| Error::StateError(format!("Failed to delete assistant object: {e}")))?;
+
+        Ok(())
+    }
+
+    async fn get_prompt(&self, name: &str, version: Option<u32>) -> Result<Option<PromptTemplate>> {
+        let row = sqlx::query_as::<_, PromptTemplateRow>(
+            r#"
+            SELECT name, version, description, messages, variables, parameters, created_by, created_at
+            FROM prompt_templates
+            WHERE name = ?1 AND (?2 IS NULL OR version = ?2)
+            ORDER BY version DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(name)
+        .bind(version.map(i64::from))
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to get prompt: {e}")))?;
+
+        Ok(row.map(PromptTemplate::from))
+    }
+
+    async fn save_prompt(&self, prompt: &PromptTemplate) -> Result<()> {
+        let messages = serde_json::to_string(&prompt.messages)
+            .map_err(|e| Error::StateError(format!("Failed to serialize messages: {e}")))?;
+        let variables = serde_json::to_string(&prompt.variables)
+            .map_err(|e| Error::StateError(format!("Failed to serialize variables: {e}")))?;
+        let parameters = serde_json::to_string(&prompt.parameters)
+            .map_err(|e| Error::StateError(format!("Failed to serialize parameters: {e}")))?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO prompt_templates
+                (name, version, description, messages, variables, parameters, created_by, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            "#,
+        )
+        .bind(&prompt.name)
+        .bind(i64::from(prompt.version))
+        .bind(&prompt.description)
+        .bind(&messages)
+        .bind(&variables)
+        .bind(&parameters)
+        .bind(&prompt.created_by)
+        .bind(datetime_to_string(prompt.created_at))
+        .execute(&self.pool)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to save prompt: {e}")))?;
+
+        Ok(())
+    }
+
+    async fn list_prompts(&self) -> Result<Vec<PromptTemplate>> {
+        let rows = sqlx::query_as::<_, PromptTemplateRow>(
+            r#"
+            SELECT name, version, description, messages, variables, parameters, created_by, created_at
+            FROM prompt_templates p
+            WHERE version = (SELECT MAX(version) FROM prompt_templates WHERE name = p.name)
+            ORDER BY name ASC
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to list prompts: {e}")))?;
+
+        Ok(rows.into_iter().map(PromptTemplate::from).collect())
+    }
+
+    async fn list_prompt_versions(&self, name: &str) -> Result<Vec<PromptTemplate>> {
+        let rows = sqlx::query_as::<_, PromptTemplateRow>(
+            r#"
+            SELECT name, version, description, messages, variables, parameters, created_by, created_at
+            FROM prompt_templates WHERE name = ?1
+            ORDER BY version ASC
+            "#,
+        )
+        .bind(name)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to list prompt versions: {e}")))?;
+
+        Ok(rows.into_iter().map(PromptTemplate::from).collect())
+    }
+
+    async fn delete_prompt(&self, name: &str) -> Result<()> {
+        sqlx::query("DELETE FROM prompt_templates WHERE name = ?1")
+            .bind(name)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| Error::StateError(format!("Failed to delete prompt: {e}")))?;
+
+        Ok(())
+    }
+
+    async fn record_prompt_render(&self, render: &PromptRender) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO prompt_renders
+                (id, prompt_name, version, user_id, request_id, model, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            "#,
+        )
+        .bind(&render.id)
+        .bind(&render.prompt_name)
+        .bind(i64::from(render.version))
+        .bind(&render.user_id)
+        .bind(&render.request_id)
+        .bind(&render.model)
+        .bind(datetime_to_string(render.created_at))
+        .execute(&self.pool)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to record prompt render: {e}")))?;
+
+        Ok(())
+    }
+
+    async fn list_prompt_renders(&self, name: &str, limit: usize) -> Result<Vec<PromptRender>> {
+        let rows = sqlx::query_as::<_, PromptRenderRow>(
+            r#"
+            SELECT id, prompt_name, version, user_id, request_id, model, created_at
+            FROM prompt_renders WHERE prompt_name = ?1
+            ORDER BY created_at DESC, rowid DESC
+            LIMIT ?2
+            "#,
+        )
+        .bind(name)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to list prompt renders: {e}")))?;
+
+        Ok(rows.into_iter().map(PromptRender::from).collect())
+    }
+
+    async fn get_experiment(&self, id: &str) -> Result<Option<Experiment>> {
+        let row = sqlx::query_as::<_, ExperimentRow>(
+            r#"
+            SELECT id, name, description, model, status, variants, metrics,
+                   created_by, created_at, updated_at
+            FROM experiments WHERE id = ?1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to get experiment: {e}")))?;
+
+        Ok(row.map(Experiment::from))
+    }
+
+    async fn save_experiment(&self, experiment: &Experiment) -> Result<()> {
+        let status = serde_json::to_value(experiment.status)
+            .map_err(|e| Error::StateError(format!("Failed to serialize status: {e}")))?;
+        let variants = serde_json::to_string(&experiment.variants)
+            .map_err(|e| Error::StateError(format!("Failed to serialize variants: {e}")))?;
+        let metrics = serde_json::to_string(&experiment.metrics)
+            .map_err(|e| Error::StateError(format!("Failed to serialize metrics: {e}")))?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO experiments
+                (id, name, description, model, status, variants, metrics,
+                 created_by, created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+            ON CONFLICT(id) DO UPDATE SET
+                name = excluded.name,
+                description = excluded.description,
+                model = excluded.model,
+                status = excluded.status,
+                variants = excluded.variants,
+                metrics = excluded.metrics,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&experiment.id)
+        .bind(&experiment.name)
+        .bind(&experiment.description)
+        .bind(&experiment.model)
+        .bind(status.as_str().unwrap_or_default())
+        .bind(&variants)
+        .bind(&metrics)
+        .bind(&experiment.created_by)
+        .bind(datetime_to_string(experiment.created_at))
+        .bind(datetime_to_string(experiment.updated_at))
+        .execute(&self.pool)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to save experiment: {e}")))?;
+
+        Ok(())
+    }
+
+    async fn list_experiments(&self) -> Result<Vec<Experiment>> {
+        let rows = sqlx::query_as::<_, ExperimentRow>(
+            r#"
+            SELECT id, name, description, model, status, variants, metrics,
+                   created_by, created_at, updated_at
+            FROM experiments
+            ORDER BY created_at DESC
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to list experiments: {e}")))?;
+
+        Ok(rows.into_iter().map(Experiment::from).collect())
+    }
+
+    async fn delete_experiment(&self, id: &str) -> Result<()> {
+        sqlx::query("DELETE FROM experiment_outcomes WHERE experiment_id = ?1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| Error::StateError(format!("Failed to delete experiment outcomes: {e}")))?;
+        sqlx::query("DELETE FROM experiments WHERE id = ?1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| Error::StateError(format!("Failed to delete experiment: {e}")))?;
+
+        Ok(())
+    }
+
+    async fn record_experiment_outcome(&self, outcome: &ExperimentOutcome) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO experiment_outcomes
+                (id, experiment_id, variant, user_id, request_id, latency_ms,
+                 input_tokens, output_tokens, cost, error, feedback, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+            "#,
+        )
+        .bind(&outcome.id)
+        .bind(&outcome.experiment_id)
+        .bind(&outcome.variant)
+        .bind(&outcome.user_id)
+        .bind(&outcome.request_id)
+        .bind(outcome.latency_ms as i64)
+        .bind(outcome.input_tokens as i64)
+        .bind(outcome.output_tokens as i64)
+        .bind(outcome.cost)
+        .bind(outcome.error)
+        .bind(outcome.feedback.map(i64::from))
+        .bind(datetime_to_string(outcome.created_at))
+        .execute(&self.pool)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to record experiment outcome: {e}")))?;
+
+        Ok(())
+    }
+
+    async fn list_experiment_outcomes(
+        &self,
+        experiment_id: &str,
+    ) -> Result<Vec<ExperimentOutcome>> {
+        let rows = sqlx::query_as::<_, ExperimentOutcomeRow>(
+            r#"
+            SELECT id, experiment_id, variant, user_id, request_id, latency_ms,
+                   input_tokens, output_tokens, cost, error, feedback, created_at
+            FROM experiment_outcomes WHERE experiment_id = ?1
+            ORDER BY created_at ASC, rowid ASC
+            "#,
+        )
+        .bind(experiment_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to list experiment outcomes: {e}")))?;
+
+        Ok(rows.into_iter().map(ExperimentOutcome::from).collect())
     }
 
-    pub fn pool(&self) -> &Pool<Sqlite> {
-        &self.pool
+    async fn record_experiment_feedback(&self, request_id: &str, feedback: i32) -> Result<bool> {
+        let result =
+            sqlx::query("UPDATE experiment_outcomes SET feedback = ?1 WHERE request_id = ?2")
+                .bind(i64::from(feedback))
+                .bind(request_id)
+                .execute(&self.pool)
+                .await
+                .map_err(|e| {
+                    Error::StateError(format!("Failed to record experiment feedback: {e}"))
+                })?;
+
+        Ok(result.rows_affected() > 0)
     }
-}
 
-#[async_trait]
-impl StateBackend for SqliteStateBackend {
-    // User management
-    async fn get_user(&self, user_id: &str) -> Result<Option<User>> {
-        let row = sqlx::query_as::<_, UserRow>(
-            "SELECT id, email, name, created_at, updated_at, disabled_at FROM users WHERE id = ?1",
+    async fn get_feedback(&self, request_id: &str, user_id: &str) -> Result<Option<Feedback>> {
+        let row = sqlx::query_as::<_, FeedbackRow>(
+            r#"
+            SELECT id, request_id, user_id, thumbs_up, rating, comment, model_id,
+                   provider_id, task_type, created_at, updated_at
+            FROM feedback WHERE request_id = ?1 AND user_id = ?2
+            "#,
         )
+        .bind(request_id)
         .bind(user_id)
         .fetch_optional(&self.pool)
         .await
-        .map_err(|e| Error::StateError(format!("Failed to get user: {e}")))?;
-
-        Ok(row.map(User::from))
-    }
+        .map_err(|e| Error::StateError(format!("Failed to get feedback: {e}")))?;
 
-    async fn get_user_by_id(&self, user_id: &str) -> Result<Option<User>> {
-        self.get_user(user_id).await
+        Ok(row.map(Feedback::from))
     }
 
-    async fn create_user(&self, user: &User) -> Result<()> {
-        let email = user.metadata.get("email").map(|s| s.as_str());
-        let created_at = datetime_to_string(user.created_at);
-        let updated_at = datetime_to_string(user.updated_at);
-
+    async fn save_feedback(&self, feedback: &Feedback) -> Result<()> {
         sqlx::query(
-            "INSERT INTO users (id, email, name, created_at, updated_at, disabled_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            r#"
+            INSERT INTO feedback
+                (id, request_id, user_id, thumbs_up, rating, comment, model_id,
+                 provider_id, task_type, created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+            ON CONFLICT(id) DO UPDATE SET
+                thumbs_up = excluded.thumbs_up,
+                rating = excluded.rating,
+                comment = excluded.comment,
+                model_id = excluded.model_id,
+                provider_id = excluded.provider_id,
+                task_type = excluded.task_type,
+                updated_at = excluded.updated_at
+            "#,
         )
-        .bind(&user.id)
-        .bind(email)
-        .bind(&user.name)
-        .bind(&created_at)
-        .bind(&updated_at)
-        .bind(user.disabled_at.map(datetime_to_string))
+        .bind(&feedback.id)
+        .bind(&feedback.request_id)
+        .bind(&feedback.user_id)
+        .bind(feedback.thumbs_up)
+        .bind(feedback.rating.map(i64::from))
+        .bind(&feedback.comment)
+        .bind(&feedback.model_id)
+        .bind(&feedback.provider_id)
+        .bind(&feedback.task_type)
+        .bind(datetime_to_string(feedback.created_at))
+        .bind(datetime_to_string(feedback.updated_at))
         .execute(&self.pool)
         .await
-        .map_err(|e| Error::StateError(format!("Failed to create user: {e}")))?;
-
-        Ok(())
-    }
-
-    async fn update_user(&self, user: &User) -> Result<()> {
-        let email = user.metadata.get("email").map(|s| s.as_str());
-        let updated_at = datetime_to_string(user.updated_at);
-
-        sqlx::query("UPDATE users SET email = ?2, name = ?3, updated_at = ?4, disabled_at = ?5 WHERE id = ?1")
-            .bind(&user.id)
-            .bind(email)
-            .bind(&user.name)
-            .bind(&updated_at)
-            .bind(user.disabled_at.map(datetime_to_string))
-            .execute(&self.pool)
-            .await
-            .map_err(|e| Error::StateError(format!("Failed to update user: {e}")))?;
+        .map_err(|e| Error::StateError(format!("Failed to save feedback: {e}")))?;
 
         Ok(())
     }
 
-    async fn delete_user(&self, user_id: &str) -> Result<()> {
-        sqlx::query("DELETE FROM users WHERE id = ?1")
-            .bind(user_id)
-            .execute(&self.pool)
-            .await
-            .map_err(|e| Error::StateError(format!("Failed to delete user: {e}")))?;
+    async fn list_feedback(
+        &self,
+        since: Option<chrono::DateTime<chrono::Utc>>,
+        limit: usize,
+    ) -> Result<Vec<Feedback>> {
+        let rows = sqlx::query_as::<_, FeedbackRow>(
+            r#"
+            SELECT id, request_id, user_id, thumbs_up, rating, comment, model_id,
+                   provider_id, task_type, created_at, updated_at
+            FROM feedback WHERE ?1 IS NULL OR updated_at >= ?1
+            ORDER BY updated_at DESC, rowid DESC
+            LIMIT ?2
+            "#,
+        )
+        .bind(since.map(datetime_to_string))
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to list feedback: {e}")))?;
 
-        Ok(())
+        Ok(rows.into_iter().map(Feedback::from).collect())
     }
 
-    async fn list_users(&self) -> Result<Vec<User>> {
-        let rows = sqlx::query_as::<_, UserRow>(
-            "SELECT id, email, name, created_at, updated_at, disabled_at FROM users ORDER BY created_at DESC",
+    async fn record_permission_decision(&self, decision: &PermissionDecision) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO permission_decisions
+                (id, subject_id, action, object, allowed, policy, reason, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            "#,
         )
-        .fetch_all(&self.pool)
+        .bind(&decision.id)
+        .bind(&decision.subject_id)
+        .bind(&decision.action)
+        .bind(&decision.object)
+        .bind(decision.allowed)
+        .bind(&decision.policy)
+        .bind(&decision.reason)
+        .bind(datetime_to_string(decision.created_at))
+        .execute(&self.pool)
         .await
-        .map_err(|e| Error::StateError(format!("Failed to list users: {e}")))?;
+        .map_err(|e| Error::StateError(format!("Failed to record permission decision: {e}")))?;
 
-        Ok(rows.into_iter().map(User::from).collect())
+        Ok(())
     }
 
-    // API key management
-    async fn get_api_key(&self, key_hash: &str) -> Result<Option<ApiKey>> {
-        let row = sqlx::query_as::<_, ApiKeyRow>(
-            "SELECT key_hash, name, org_id, config, created_at, last_used_at FROM api_keys WHERE key_hash = ?1",
+    async fn list_permission_decisions(
+        &self,
+        filter: &PermissionDecisionFilter,
+        limit: usize,
+    ) -> Result<Vec<PermissionDecision>> {
+        let rows = sqlx::query_as::<_, PermissionDecisionRow>(
+            r#"
+            SELECT id, subject_id, action, object, allowed, policy, reason, created_at
+            FROM permission_decisions
+            WHERE (?1 IS NULL OR subject_id = ?1)
+              AND (?2 IS NULL OR action = ?2)
+              AND (?3 IS NULL OR allowed = ?3)
+              AND (?4 IS NULL OR created_at >= ?4)
+            ORDER BY created_at DESC, rowid DESC
+            LIMIT ?5
+            "#,
         )
-        .bind(key_hash)
-        .fetch_optional(&self.pool)
+        .bind(&filter.subject_id)
+        .bind(&filter.action)
+        .bind(filter.allowed)
+        .bind(filter.since.map(datetime_to_string))
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
         .await
-        .map_err(|e| Error::StateError(format!("Failed to get API key: {e}")))?;
+        .map_err(|e| Error::StateError(format!("Failed to list permission decisions: {e}")))?;
 
-        Ok(row.map(ApiKey::from))
+        Ok(rows.into_iter().map(PermissionDecision::from).collect())
     }
 
-    async fn create_api_key(&self, key: &ApiKey, _raw_key: &str) -> Result<()> {
-        let config = key
-            .config
-            .as_ref()
-            .map(serde_json::to_string)
-            .transpose()
-            .map_err(|e| Error::StateError(format!("Failed to serialize config: {e}")))?;
-        let created_at = datetime_to_string(key.created_at);
-        let last_used_at = key.last_used_at.map(datetime_to_string);
-
+    async fn record_routing_decision(&self, decision: &RoutingDecision) -> Result<()> {
         sqlx::query(
-            "INSERT INTO api_keys (key_hash, name, org_id, config, created_at, last_used_at) 
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            r#"
+            INSERT INTO routing_decisions
+                (id, user_id, request_id, requested_model, model, tier, rationale, overridden,
+                 created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+            "#,
         )
-        .bind(&key.key_hash)
-        .bind(&key.name)
-        .bind(&key.org_id)
-        .bind(config)
-        .bind(&created_at)
-        .bind(last_used_at)
+        .bind(&decision.id)
+        .bind(&decision.user_id)
+        .bind(&decision.request_id)
+        .bind(&decision.requested_model)
+        .bind(&decision.model)
+        .bind(&decision.tier)
+        .bind(&decision.rationale)
+        .bind(decision.overridden)
+        .bind(datetime_to_string(decision.created_at))
         .execute(&self.pool)
         .await
-        .map_err(|e| Error::StateError(format!("Failed to create API key: {e}")))?;
+        .map_err(|e| Error::StateError(format!("Failed to record routing decision: {e}")))?;
 
         Ok(())
     }
 
-    async fn list_api_keys(&self, org_id: &str) -> Result<Vec<ApiKey>> {
-        let rows = sqlx::query_as::<_, ApiKeyRow>(
-            "SELECT key_hash, name, org_id, config, created_at, last_used_at 
-             FROM api_keys WHERE org_id = ?1 ORDER BY created_at DESC",
+    async fn list_routing_decisions(
+        &self,
+        user_id: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<RoutingDecision>> {
+        let rows = sqlx::query_as::<_, RoutingDecisionRow>(
+            r#"
+            SELECT id, user_id, request_id, requested_model, model, tier, rationale, overridden,
+                   created_at
+            FROM routing_decisions
+            WHERE (?1 IS NULL OR user_id = ?1)
+            ORDER BY created_at DESC, rowid DESC
+            LIMIT ?2
+            "#,
         )
-        .bind(org_id)
+        .bind(user_id)
+        .bind(limit as i64)
         .fetch_all(&self.pool)
         .await
-        .map_err(|e| Error::StateError(format!("Failed to list API keys: {e}")))?;
+        .map_err(|e| Error::StateError(format!("Failed to list routing decisions: {e}")))?;
 
-        Ok(rows.into_iter().map(ApiKey::from).collect())
+        Ok(rows.into_iter().map(RoutingDecision::from).collect())
     }
 
-    async fn delete_api_key(&self, key_hash: &str) -> Result<()> {
-        sqlx::query("DELETE FROM api_keys WHERE key_hash = ?1")
-            .bind(key_hash)
+    async fn purge_data(
+        &self,
+        class: DataClass,
+        before: chrono::DateTime<chrono::Utc>,
+    ) -> Result<u64> {
+        let statements: &[&str] = match class {
+            DataClass::UsageRecords => &["DELETE FROM usage_records WHERE timestamp < ?1"],
+            DataClass::AuditLog => &[
+                "DELETE FROM prompt_renders WHERE created_at < ?1",
+                "DELETE FROM experiment_outcomes WHERE created_at < ?1",
+                "DELETE FROM permission_decisions WHERE created_at < ?1",
+                "DELETE FROM routing_decisions WHERE created_at < ?1",
+            ],
+            DataClass::CapturedBodies => &["DELETE FROM stored_responses WHERE created_at < ?1"],
+            DataClass::Conversations => &["DELETE FROM conversations WHERE updated_at < ?1"],
+        };
+
+        let bodies = match class {
+            DataClass::CapturedBodies => {
+                Some(body_query(STORED_RESPONSE_BODIES, "created_at < ?1"))
+            }
+            DataClass::Conversations => Some(body_query(CONVERSATION_BODIES, "updated_at < ?1")),
+            DataClass::UsageRecords | DataClass::AuditLog => None,
+        };
+
+        let before = datetime_to_string(before);
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| Error::StateError(format!("Failed to start transaction: {e}")))?;
+        if let Some(bodies) = bodies {
+            release_blob_refs(&mut tx, &bodies, &before).await?;
+        }
+        let mut purged = 0;
+        for statement in statements {
+            purged += sqlx::query(statement)
+                .bind(&before)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| Error::StateError(format!("Failed to purge {class:?}: {e}")))?
+                .rows_affected();
+        }
+        tx.commit()
+            .await
+            .map_err(|e| Error::StateError(format!("Failed to purge {class:?}: {e}")))?;
+        Ok(purged)
+    }
+
+    async fn collect_blobs(&self, before: chrono::DateTime<chrono::Utc>) -> Result<BlobCollection> {
+        let mut collection = BlobCollection::default();
+        let Some(store) = &self.content_store else {
+            return Ok(collection);
+        };
+
+        sqlx::query("DELETE FROM blob_refs WHERE refcount <= 0")
             .execute(&self.pool)
             .await
-            .map_err(|e| Error::StateError(format!("Failed to delete API key: {e}")))?;
+            .map_err(|e| Error::StateError(format!("Failed to collect blobs: {e}")))?;
+        let referenced: HashSet<String> = sqlx::query_scalar("SELECT hash FROM blob_refs")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| Error::StateError(format!("Failed to collect blobs: {e}")))?
+            .into_iter()
+            .collect();
 
-        Ok(())
+        // Blobs written since `before` may belong to a row being saved
+        let blobs = store
+            .list()
+            .await
+            .map_err(|e| Error::StateError(format!("Failed to list blobs: {e}")))?;
+        for (hash, size, modified) in blobs {
+            if referenced.contains(&hash) || modified >= before {
+                continue;
+            }
+            store
+                .remove(&hash)
+                .await
+                .map_err(|e| Error::StateError(format!("Failed to remove blob {hash}: {e}")))?;
+            collection.blobs += 1;
+            collection.bytes += size;
+        }
+        Ok(collection)
+    }
+
+    async fn delete_user_data(&self, user_id: &str) -> Result<UserDataDeletion> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| Error::StateError(format!("Failed to start transaction: {e}")))?;
+
+        for bodies in [
+            body_query(CONVERSATION_BODIES, "user_id = ?1"),
+            body_query(STORED_RESPONSE_BODIES, "owner_id = ?1"),
+        ] {
+            release_blob_refs(&mut tx, &bodies, user_id).await?;
+        }
+        let statements = [
+            "DELETE FROM conversations WHERE user_id = ?1",
+            "DELETE FROM feedback WHERE user_id = ?1",
+            "DELETE FROM usage_records WHERE user_id = ?1",
+            "DELETE FROM prompt_renders WHERE user_id = ?1",
+            "DELETE FROM experiment_outcomes WHERE user_id = ?1",
+            "DELETE FROM permission_decisions WHERE subject_id = ?1",
+            "DELETE FROM routing_decisions WHERE user_id = ?1",
+            "DELETE FROM stored_responses WHERE owner_id = ?1",
+            "DELETE FROM assistant_objects WHERE owner_id = ?1",
+            "DELETE FROM user_preferences WHERE user_id = ?1",
+        ];
+        let mut counts = [0u64; 10];
+        for (count, statement) in counts.iter_mut().zip(statements) {
+            *count = sqlx::query(statement)
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| Error::StateError(format!("Failed to delete user data: {e}")))?
+                .rows_affected();
+        }
+        let [
+            conversations,
+            feedback,
+            usage_records,
+            prompt_renders,
+            experiment_outcomes,
+            permission_decisions,
+            routing_decisions,
+            captured_bodies,
+            assistant_objects,
+            preferences,
+        ] = counts;
+
+        tx.commit()
+            .await
+            .map_err(|e| Error::StateError(format!("Failed to delete user data: {e}")))?;
+        Ok(UserDataDeletion {
+            conversations,
+            feedback,
+            usage_records,
+            audit_entries: prompt_renders
+                + experiment_outcomes
+                + permission_decisions
+                + routing_decisions,
+            captured_bodies,
+            assistant_objects,
+            preferences,
+        })
     }
 
     // Usage tracking
@@ -237,6 +1371,66 @@ impl StateBackend for SqliteStateBackend {
         Ok(rows.into_iter().map(UsageRecord::from).collect())
     }
 
+    async fn list_usage(&self, range: &TimeRange) -> Result<Vec<UsageRecord>> {
+        let start = datetime_to_string(range.start);
+        let end = datetime_to_string(range.end);
+
+        let rows = sqlx::query_as::<_, UsageRecordRow>(
+            "SELECT id, org_id, user_id, api_key_hash, request_id, provider_id, model_id, 
+             input_tokens, output_tokens, total_tokens, cost, timestamp, metadata 
+             FROM usage_records WHERE timestamp >= ?1 AND timestamp <= ?2 
+             ORDER BY timestamp DESC",
+        )
+        .bind(&start)
+        .bind(&end)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to list usage: {e}")))?;
+
+        Ok(rows.into_iter().map(UsageRecord::from).collect())
+    }
+
+    async fn usage_totals(
+        &self,
+        subject: &UsageSubject,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<UsageTotals> {
+        let (column, value) = match subject {
+            UsageSubject::ApiKey(key_hash) => ("api_key_hash", key_hash),
+            UsageSubject::User(user_id) => ("user_id", user_id),
+        };
+        let (requests, tokens, cost): (i64, i64, f64) = sqlx::query_as(&format!(
+            "SELECT COUNT(*), COALESCE(SUM(total_tokens), 0), COALESCE(SUM(cost), 0.0)
+             FROM usage_records WHERE {column} = ?1 AND timestamp >= ?2"
+        ))
+        .bind(value)
+        .bind(datetime_to_string(since))
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to total usage: {e}")))?;
+
+        Ok(UsageTotals {
+            requests: requests as u64,
+            tokens: tokens as u64,
+            cost,
+        })
+    }
+
+    async fn get_usage_by_request(&self, request_id: &str) -> Result<Option<UsageRecord>> {
+        let row = sqlx::query_as::<_, UsageRecordRow>(
+            "SELECT id, org_id, user_id, api_key_hash, request_id, provider_id, model_id, 
+             input_tokens, output_tokens, total_tokens, cost, timestamp, metadata 
+             FROM usage_records WHERE request_id = ?1 
+             ORDER BY timestamp DESC LIMIT 1",
+        )
+        .bind(request_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to get usage: {e}")))?;
+
+        Ok(row.map(UsageRecord::from))
+    }
+
     // Provider management
     async fn get_provider(&self, id: &str) -> Result<Option<Provider>> {
         let row = sqlx::query_as::<_, ProviderRow>(
@@ -435,6 +1629,158 @@ impl StateBackend for SqliteStateBackend {
 
         Ok(permissions)
     }
+
+    async fn has_any_permission(
+        &self,
+        subject_ids: &[String],
+        action: &Action,
+        objects: &[ObjectIdentity],
+    ) -> Result<bool> {
+        if subject_ids.is_empty() || objects.is_empty() {
+            return Ok(false);
+        }
+        let action_str = serde_json::to_string(action)
+            .map_err(|e| Error::StateError(format!("Failed to serialize action: {e}")))?;
+
+        let mut query =
+            QueryBuilder::<Sqlite>::new("SELECT COUNT(*) FROM permissions WHERE action = ");
+        query.push_bind(action_str).push(" AND subject_id IN (");
+        let mut subject_list = query.separated(", ");
+        for subject_id in subject_ids {
+            subject_list.push_bind(subject_id);
+        }
+        query.push(") AND object IN (");
+        let mut object_list = query.separated(", ");
+        for object in objects {
+            object_list.push_bind(object.to_string());
+        }
+        query.push(")");
+
+        let result: (i64,) = query
+            .build_query_as()
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| Error::StateError(format!("Failed to check permission: {e}")))?;
+
+        Ok(result.0 > 0)
+    }
+
+    async fn add_group_member(&self, group: &str, subject_id: &str) -> Result<()> {
+        sqlx::query(
+            "INSERT OR IGNORE INTO group_members (group_name, subject_id, added_at) VALUES (?1, ?2, ?3)",
+        )
+        .bind(group)
+        .bind(subject_id)
+        .bind(datetime_to_string(chrono::Utc::now()))
+        .execute(&self.pool)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to add group member: {e}")))?;
+
+        Ok(())
+    }
+
+    async fn remove_group_member(&self, group: &str, subject_id: &str) -> Result<bool> {
+        let result =
+            sqlx::query("DELETE FROM group_members WHERE group_name = ?1 AND subject_id = ?2")
+                .bind(group)
+                .bind(subject_id)
+                .execute(&self.pool)
+                .await
+                .map_err(|e| Error::StateError(format!("Failed to remove group member: {e}")))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn list_groups(&self) -> Result<Vec<String>> {
+        sqlx::query_scalar("SELECT DISTINCT group_name FROM group_members ORDER BY group_name")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| Error::StateError(format!("Failed to list groups: {e}")))
+    }
+
+    async fn list_group_members(&self, group: &str) -> Result<Vec<String>> {
+        sqlx::query_scalar(
+            "SELECT subject_id FROM group_members WHERE group_name = ?1 ORDER BY subject_id",
+        )
+        .bind(group)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to list group members: {e}")))
+    }
+
+    async fn list_subject_groups(&self, subject_id: &str) -> Result<Vec<String>> {
+        sqlx::query_scalar(
+            "SELECT group_name FROM group_members WHERE subject_id = ?1 ORDER BY group_name",
+        )
+        .bind(subject_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| Error::StateError(format!("Failed to list subject groups: {e}")))
+    }
+}
+
+#[async_trait]
+impl SchemaMigrator for SqliteStateBackend {
+    fn known(&self) -> Vec<MigrationInfo> {
+        MIGRATOR
+            .iter()
+            .filter(|m| m.migration_type.is_up_migration())
+            .map(|m| MigrationInfo {
+                version: m.version,
+                description: m.description.to_string(),
+                reversible: m.migration_type == MigrationType::ReversibleUp,
+            })
+            .collect()
+    }
+
+    async fn applied(&self) -> Result<Vec<i64>> {
+        let mut conn = self
+            .pool
+            .acquire()
+            .await
+            .map_err(|e| Error::StateError(format!("Failed to acquire connection: {e}")))?;
+        conn.ensure_migrations_table()
+            .await
+            .map_err(|e| Error::StateError(format!("Failed to create migrations table: {e}")))?;
+        let applied = conn
+            .list_applied_migrations()
+            .await
+            .map_err(|e| Error::StateError(format!("Failed to list migrations: {e}")))?;
+        Ok(applied.into_iter().map(|m| m.version).collect())
+    }
+
+    async fn migrate_up(&self) -> Result<Vec<i64>> {
+        let pending = self.status().await?.pending;
+        MIGRATOR
+            .run(&self.pool)
+            .await
+            .map_err(|e| Error::StateError(format!("Failed to run migrations: {e}")))?;
+        Ok(pending.into_iter().map(|m| m.version).collect())
+    }
+
+    async fn migrate_down(&self, target: i64) -> Result<Vec<i64>> {
+        let mut reverted: Vec<i64> = self
+            .applied()
+            .await?
+            .into_iter()
+            .filter(|v| *v > target)
+            .collect();
+        reverted.sort_unstable_by(|a, b| b.cmp(a));
+        MIGRATOR
+            .undo(&self.pool, target)
+            .await
+            .map_err(|e| Error::StateError(format!("Failed to revert migrations: {e}")))?;
+        Ok(reverted)
+    }
+
+    async fn backup(&self, destination: &str) -> Result<()> {
+        sqlx::query("VACUUM INTO ?1")
+            .bind(destination)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| Error::StateError(format!("Failed to back up database: {e}")))?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -452,4 +1798,117 @@ mod tests {
         let suite = StateBackendTestSuite::new(backend);
         suite.run_all_tests().await.expect("All tests should pass");
     }
+
+    #[tokio::test]
+    async fn test_grants_are_inherited_through_groups_and_parents() {
+        use gate_core::access::{group_subject, has_inherited_permission};
+
+        let backend = setup_sqlite_backend().await;
+        let model = ObjectIdentity::provider_model("openai", "gpt-4o");
+        let check = |subject: &'static str| {
+            let backend = &backend;
+            let model = &model;
+            async move {
+                has_inherited_permission(backend, subject, &Action::Execute, model)
+                    .await
+                    .unwrap()
+            }
+        };
+        assert!(!check("alice").await);
+
+        backend
+            .grant_permission(
+                &group_subject("engineering"),
+                &Action::Execute,
+                &ObjectIdentity::local_provider("openai"),
+            )
+            .await
+            .unwrap();
+        backend
+            .add_group_member("engineering", "alice")
+            .await
+            .unwrap();
+        assert!(check("alice").await);
+        assert!(!check("bob").await);
+
+        backend
+            .remove_group_member("engineering", "alice")
+            .await
+            .unwrap();
+        assert!(!check("alice").await);
+    }
+
+    #[tokio::test]
+    async fn test_bodies_share_blobs_until_collected() {
+        use gate_core::tests::blob::InMemoryBlobStore;
+
+        let backend = setup_sqlite_backend()
+            .await
+            .with_content_store(ContentStore::new(
+                std::sync::Arc::new(InMemoryBlobStore::new()),
+                Some(64),
+            ));
+        let system = "You are a careful assistant. ".repeat(20);
+        let conversation = |id: &str, question: &str| Conversation {
+            id: id.to_string(),
+            user_id: "alice".to_string(),
+            title: id.to_string(),
+            model: None,
+            messages: serde_json::json!([
+                {"role": "system", "content": system},
+                {"role": "user", "content": question},
+            ]),
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        };
+        let first = conversation("c1", "Hi");
+        backend.save_conversation(&first).await.unwrap();
+        backend
+            .save_conversation(&conversation("c2", "Hello"))
+            .await
+            .unwrap();
+        // Saving again replaces the row's references rather than adding to them
+        backend.save_conversation(&first).await.unwrap();
+
+        let refcount = || async {
+            sqlx::query_scalar::<_, i64>("SELECT refcount FROM blob_refs")
+                .fetch_all(backend.pool())
+                .await
+                .unwrap()
+        };
+        assert_eq!(refcount().await, [2]);
+        let stored = backend.get_conversation("c1").await.unwrap().unwrap();
+        assert_eq!(stored.messages, first.messages);
+
+        let later = chrono::Utc::now() + chrono::Duration::hours(1);
+        backend.delete_conversation("c1").await.unwrap();
+        assert_eq!(backend.collect_blobs(later).await.unwrap().blobs, 0);
+
+        backend.delete_user_data("alice").await.unwrap();
+        assert_eq!(refcount().await, [0]);
+        // Recently written blobs are spared
+        let now = chrono::Utc::now() - chrono::Duration::hours(1);
+        assert_eq!(backend.collect_blobs(now).await.unwrap().blobs, 0);
+        assert_eq!(backend.collect_blobs(later).await.unwrap().blobs, 1);
+        assert!(refcount().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_migrations_round_trip() {
+        let backend = SqliteStateBackend::connect(":memory:").await.unwrap();
+        let status = backend.status().await.unwrap();
+        assert!(status.is_fresh());
+        assert!(status.pending.iter().all(|m| m.reversible));
+
+        let applied = backend.migrate_up().await.unwrap();
+        assert_eq!(applied.len(), status.pending.len());
+        assert!(backend.status().await.unwrap().is_up_to_date());
+
+        let reverted = backend.migrate_down(3).await.unwrap();
+        assert_eq!(reverted.first(), Some(&status.latest));
+        assert_eq!(backend.status().await.unwrap().current, 3);
+
+        backend.migrate_up().await.unwrap();
+        assert!(backend.status().await.unwrap().is_up_to_date());
+    }
 }
@@ -0,0 +1,466 @@
+//! Byte accounting and bandwidth caps for forwarded connections
+//!
+//! Every forwarded stream is wrapped in a [`MeteredStream`] that counts the
+//! bytes crossing it, attributes them to the hostname the client asked for,
+//! and holds reads or writes back when a per-connection or aggregate cap is
+//! exceeded. Caps apply to each direction separately, so a limit on sent
+//! bytes protects a home uplink without slowing uploads to the node. Open
+//! connections are listed for diagnostics.
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use gate_core::tracing::metrics::counter;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::task::{Context, Poll, ready};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::time::Sleep;
+
+/// Hostname traffic is attributed to before, or without, a TLS handshake
+const UNKNOWN_HOST: &str = "unknown";
+
+/// Bandwidth caps in bytes per second, each direction; `None` is unlimited
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BandwidthLimits {
+    /// Cap on each forwarded connection
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub per_connection: Option<u64>,
+    /// Cap on all forwarded connections together
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aggregate: Option<u64>,
+}
+
+/// Token bucket allowing one second of burst
+struct RateLimiter {
+    rate: f64,
+    /// Tokens available, negative while in debt, and when last refilled
+    state: Mutex<(f64, Instant)>,
+}
+
+impl RateLimiter {
+    fn new(bytes_per_second: u64) -> Self {
+        let rate = bytes_per_second.max(1) as f64;
+        Self {
+            rate,
+            state: Mutex::new((rate, Instant::now())),
+        }
+    }
+
+    /// Take `bytes` from the bucket, returning how long traffic must pause
+    /// to pay back any debt
+    fn charge(&self, bytes: usize) -> Duration {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        let refill = now.duration_since(state.1).as_secs_f64() * self.rate;
+        state.0 = (state.0 + refill).min(self.rate) - bytes as f64;
+        state.1 = now;
+        if state.0 >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-state.0 / self.rate)
+        }
+    }
+}
+
+/// A limiter for each direction
+struct DirectionalLimit {
+    received: RateLimiter,
+    sent: RateLimiter,
+}
+
+impl DirectionalLimit {
+    fn new(bytes_per_second: Option<u64>) -> Option<Self> {
+        bytes_per_second.map(|rate| Self {
+            received: RateLimiter::new(rate),
+            sent: RateLimiter::new(rate),
+        })
+    }
+
+    fn charge(&self, direction: Direction, bytes: usize) -> Duration {
+        match direction {
+            Direction::Received => self.received.charge(bytes),
+            Direction::Sent => self.sent.charge(bytes),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Direction {
+    Received,
+    Sent,
+}
+
+#[derive(Default)]
+struct Counters {
+    bytes_received: AtomicU64,
+    bytes_sent: AtomicU64,
+    connections: AtomicU64,
+    active_connections: AtomicU64,
+}
+
+impl Counters {
+    fn add(&self, direction: Direction, bytes: u64) {
+        match direction {
+            Direction::Received => self.bytes_received.fetch_add(bytes, Ordering::Relaxed),
+            Direction::Sent => self.bytes_sent.fetch_add(bytes, Ordering::Relaxed),
+        };
+    }
+}
+
+/// Traffic forwarded for one hostname
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HostTraffic {
+    pub hostname: String,
+    pub bytes_received: u64,
+    pub bytes_sent: u64,
+    pub connections: u64,
+    pub active_connections: u64,
+}
+
+/// Forwarded traffic since start, in total and per hostname
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrafficSnapshot {
+    pub bytes_received: u64,
+    pub bytes_sent: u64,
+    pub active_connections: u64,
+    pub limits: BandwidthLimits,
+    pub hosts: Vec<HostTraffic>,
+}
+
+/// A forwarded connection that is open
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForwardedConnection {
+    pub id: u64,
+    /// Client address when the relay reports it, otherwise the node the
+    /// connection was forwarded by
+    pub peer: String,
+    /// Server name the client asked for, once the handshake is done
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub bytes_received: u64,
+    pub bytes_sent: u64,
+}
+
+struct ConnectionEntry {
+    id: u64,
+    peer: String,
+    started_at: DateTime<Utc>,
+    hostname: OnceLock<String>,
+    counters: Counters,
+}
+
+/// Counts forwarded bytes and enforces bandwidth caps. Clones share their
+/// counters and aggregate cap.
+#[derive(Clone)]
+pub struct TrafficMeter {
+    limits: BandwidthLimits,
+    aggregate: Option<Arc<DirectionalLimit>>,
+    totals: Arc<Counters>,
+    hosts: Arc<DashMap<String, Arc<Counters>>>,
+    open: Arc<DashMap<u64, Arc<ConnectionEntry>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl Default for TrafficMeter {
+    fn default() -> Self {
+        Self::new(BandwidthLimits::default())
+    }
+}
+
+impl TrafficMeter {
+    pub fn new(limits: BandwidthLimits) -> Self {
+        Self {
+            limits,
+            aggregate: DirectionalLimit::new(limits.aggregate).map(Arc::new),
+            totals: Arc::new(Counters::default()),
+            hosts: Arc::new(DashMap::new()),
+            open: Arc::new(DashMap::new()),
+            next_id: Arc::new(AtomicU64::new(1)),
+        }
+    }
+
+    pub fn limits(&self) -> BandwidthLimits {
+        self.limits
+    }
+
+    /// Count and cap traffic over `stream`, a connection newly forwarded
+    /// by `peer`
+    pub fn meter<S>(&self, stream: S, peer: impl std::fmt::Display) -> MeteredStream<S> {
+        self.totals
+            .active_connections
+            .fetch_add(1, Ordering::Relaxed);
+        let entry = Arc::new(ConnectionEntry {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            peer: peer.to_string(),
+            started_at: Utc::now(),
+            hostname: OnceLock::new(),
+            counters: Counters::default(),
+        });
+        self.open.insert(entry.id, entry.clone());
+        MeteredStream {
+            inner: stream,
+            meter: self.clone(),
+            limit: DirectionalLimit::new(self.limits.per_connection),
+            host: OnceLock::new(),
+            entry,
+            read_delay: None,
+            write_delay: None,
+        }
+    }
+
+    /// Connections open now, oldest first
+    pub fn connections(&self) -> Vec<ForwardedConnection> {
+        let mut connections: Vec<ForwardedConnection> = self
+            .open
+            .iter()
+            .map(|entry| ForwardedConnection {
+                id: entry.id,
+                peer: entry.peer.clone(),
+                hostname: entry.hostname.get().cloned(),
+                started_at: entry.started_at,
+                bytes_received: entry.counters.bytes_received.load(Ordering::Relaxed),
+                bytes_sent: entry.counters.bytes_sent.load(Ordering::Relaxed),
+            })
+            .collect();
+        connections.sort_by_key(|c| (c.started_at, c.id));
+        connections
+    }
+
+    fn host(&self, hostname: &str) -> Arc<Counters> {
+        self.hosts
+            .entry(hostname.to_string())
+            .or_default()
+            .value()
+            .clone()
+    }
+
+    pub fn snapshot(&self) -> TrafficSnapshot {
+        let mut hosts: Vec<HostTraffic> = self
+            .hosts
+            .iter()
+            .map(|entry| HostTraffic {
+                hostname: entry.key().clone(),
+                bytes_received: entry.bytes_received.load(Ordering::Relaxed),
+                bytes_sent: entry.bytes_sent.load(Ordering::Relaxed),
+                connections: entry.connections.load(Ordering::Relaxed),
+                active_connections: entry.active_connections.load(Ordering::Relaxed),
+            })
+            .collect();
+        hosts.sort_by(|a, b| a.hostname.cmp(&b.hostname));
+        TrafficSnapshot {
+            bytes_received: self.totals.bytes_received.load(Ordering::Relaxed),
+            bytes_sent: self.totals.bytes_sent.load(Ordering::Relaxed),
+            active_connections: self.totals.active_connections.load(Ordering::Relaxed),
+            limits: self.limits,
+            hosts,
+        }
+    }
+}
+
+/// Stream counting its traffic and holding it back when over a cap
+pub struct MeteredStream<S> {
+    inner: S,
+    meter: TrafficMeter,
+    limit: Option<DirectionalLimit>,
+    host: OnceLock<Arc<Counters>>,
+    entry: Arc<ConnectionEntry>,
+    read_delay: Option<Pin<Box<Sleep>>>,
+    write_delay: Option<Pin<Box<Sleep>>>,
+}
+
+impl<S> MeteredStream<S> {
+    /// Attribute this connection's traffic, including the handshake, to
+    /// `hostname`. Only the first call has an effect.
+    pub fn set_host(&self, hostname: &str) {
+        let counters = self.meter.host(hostname);
+        if self.host.set(counters.clone()).is_ok() {
+            let (received, sent) = self.bytes();
+            counters.connections.fetch_add(1, Ordering::Relaxed);
+            counters.active_connections.fetch_add(1, Ordering::Relaxed);
+            counters.add(Direction::Received, received);
+            counters.add(Direction::Sent, sent);
+            self.entry.hostname.set(hostname.to_string()).ok();
+        }
+    }
+
+    /// Bytes received and sent over this connection
+    pub fn bytes(&self) -> (u64, u64) {
+        let counters = &self.entry.counters;
+        (
+            counters.bytes_received.load(Ordering::Relaxed),
+            counters.bytes_sent.load(Ordering::Relaxed),
+        )
+    }
+
+    /// Count `bytes` and return the pause owed to the caps, if any
+    fn record(&mut self, direction: Direction, bytes: usize) -> Option<Pin<Box<Sleep>>> {
+        let n = bytes as u64;
+        match direction {
+            Direction::Received => counter("relay_bytes_received").add(n),
+            Direction::Sent => counter("relay_bytes_sent").add(n),
+        }
+        self.entry.counters.add(direction, n);
+        self.meter.totals.add(direction, n);
+        if let Some(host) = self.host.get() {
+            host.add(direction, n);
+        }
+
+        let connection = self
+            .limit
+            .as_ref()
+            .map_or(Duration::ZERO, |limit| limit.charge(direction, bytes));
+        let aggregate = self
+            .meter
+            .aggregate
+            .as_ref()
+            .map_or(Duration::ZERO, |limit| limit.charge(direction, bytes));
+        let delay = connection.max(aggregate);
+        if delay.is_zero() {
+            return None;
+        }
+        counter("relay_bandwidth_throttled").increment();
+        Some(Box::pin(tokio::time::sleep(delay)))
+    }
+}
+
+impl<S> Drop for MeteredStream<S> {
+    fn drop(&mut self) {
+        self.meter.open.remove(&self.entry.id);
+        self.meter
+            .totals
+            .active_connections
+            .fetch_sub(1, Ordering::Relaxed);
+        let (received, sent) = self.bytes();
+        match self.host.get() {
+            Some(host) => {
+                host.active_connections.fetch_sub(1, Ordering::Relaxed);
+            }
+            None => {
+                let counters = self.meter.host(UNKNOWN_HOST);
+                counters.connections.fetch_add(1, Ordering::Relaxed);
+                counters.add(Direction::Received, received);
+                counters.add(Direction::Sent, sent);
+            }
+        }
+        debug!(
+            "Forwarded connection {} closed: {} bytes received, {} bytes sent",
+            self.entry.id, received, sent
+        );
+    }
+}
+
+/// Wait out a pending pause before more traffic
+fn poll_delay(delay: &mut Option<Pin<Box<Sleep>>>, cx: &mut Context<'_>) -> Poll<()> {
+    if let Some(sleep) = delay {
+        ready!(sleep.as_mut().poll(cx));
+        *delay = None;
+    }
+    Poll::Ready(())
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for MeteredStream<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(poll_delay(&mut this.read_delay, cx));
+        let before = buf.filled().len();
+        ready!(Pin::new(&mut this.inner).poll_read(cx, buf))?;
+        let read = buf.filled().len() - before;
+        if read > 0 {
+            this.read_delay = this.record(Direction::Received, read);
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for MeteredStream<S> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        ready!(poll_delay(&mut this.write_delay, cx));
+        let written = ready!(Pin::new(&mut this.inner).poll_write(cx, buf))?;
+        if written > 0 {
+            this.write_delay = this.record(Direction::Sent, written);
+        }
+        Poll::Ready(Ok(written))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn test_counts_traffic_per_host_and_caps_rate() {
+        let meter = TrafficMeter::new(BandwidthLimits {
+            per_connection: Some(1_000),
+            aggregate: None,
+        });
+        let (client, server) = tokio::io::duplex(64);
+        let mut client = client;
+        let mut metered = meter.meter(server, "relay-node");
+
+        client.write_all(b"hello").await.unwrap();
+        let mut buf = [0u8; 5];
+        metered.read_exact(&mut buf).await.unwrap();
+        metered.set_host("gate.example.com");
+        metered.write_all(b"hi").await.unwrap();
+
+        let snapshot = meter.snapshot();
+        assert_eq!(snapshot.bytes_received, 5);
+        assert_eq!(snapshot.bytes_sent, 2);
+        assert_eq!(snapshot.active_connections, 1);
+        assert_eq!(
+            snapshot.hosts,
+            vec![HostTraffic {
+                hostname: "gate.example.com".to_string(),
+                bytes_received: 5,
+                bytes_sent: 2,
+                connections: 1,
+                active_connections: 1,
+            }]
+        );
+
+        let connections = meter.connections();
+        assert_eq!(connections.len(), 1);
+        assert_eq!(connections[0].peer, "relay-node");
+        assert_eq!(connections[0].hostname.as_deref(), Some("gate.example.com"));
+        assert_eq!(
+            (connections[0].bytes_received, connections[0].bytes_sent),
+            (5, 2)
+        );
+
+        drop(metered);
+        assert!(meter.connections().is_empty());
+        let snapshot = meter.snapshot();
+        assert_eq!(snapshot.active_connections, 0);
+        assert_eq!(snapshot.hosts[0].active_connections, 0);
+
+        // A second of burst passes freely; the next half second is owed
+        let limiter = RateLimiter::new(1_000);
+        assert_eq!(limiter.charge(1_000), Duration::ZERO);
+        let owed = limiter.charge(500);
+        assert!(owed > Duration::from_millis(450) && owed <= Duration::from_millis(500));
+    }
+}
@@ -1,5 +1,5 @@
 //! Certificate management for Let's Encrypt integration
-use crate::common::ChallengeStatus;
+use super::dns::{DnsProvider, challenge_name};
 use anyhow::{Context, Result};
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -8,7 +8,7 @@ use tokio::sync::RwLock;
 
 use instant_acme::{
     Account, AccountCredentials, ChallengeType, Identifier, LetsEncrypt, NewAccount, NewOrder,
-    OrderStatus,
+    Order, OrderStatus,
 };
 
 /// Certificate manager for handling Let's Encrypt certificates
@@ -96,14 +96,30 @@ impl CertificateManager {
         Ok(account)
     }
 
-    /// Request a certificate for a domain
+    /// Request a certificate for a domain, answering the challenge through
+    /// the TLS forward server
     pub async fn request_certificate(&self, domain: &str, email: &str) -> Result<()> {
         let tls_forward_client = self
             .tls_forward_client
             .as_ref()
             .context("TLS forward client not configured")?;
+        self.request_certificate_with(domain, email, tls_forward_client)
+            .await
+    }
 
-        info!("Requesting certificate for domain: {}", domain);
+    /// Request a certificate for a domain, publishing the DNS-01 challenge
+    /// record through `dns`. Wildcard domains are supported.
+    pub async fn request_certificate_with(
+        &self,
+        domain: &str,
+        email: &str,
+        dns: &dyn DnsProvider,
+    ) -> Result<()> {
+        info!(
+            "Requesting certificate for domain: {} (DNS through {})",
+            domain,
+            dns.name()
+        );
 
         // Get or create account
         let account = self.get_or_create_account(email).await?;
@@ -131,118 +147,30 @@ impl CertificateManager {
         info!("Creating DNS challenge for domain: {}", domain);
         info!("DNS challenge value: {}", dns_value);
 
-        // Create DNS challenge through TLS forward server
-        let challenge_response = tls_forward_client
-            .create_challenge(domain.to_string(), "_acme-challenge".to_string(), dns_value)
+        let record = dns
+            .create_txt(&challenge_name(domain), &dns_value)
             .await
-            .context("Failed to create challenge with TLS forward server")?;
-
-        info!("Challenge response: {:?}", challenge_response);
+            .with_context(|| format!("Failed to publish challenge through {}", dns.name()))?;
 
-        // Validate that we got a valid challenge ID
-        if challenge_response.id.is_empty() {
-            return Err(anyhow::anyhow!(
-                "Received empty challenge ID from TLS forward server"
-            ));
+        let delay = dns.propagation_delay();
+        if !delay.is_zero() {
+            info!("Waiting {:?} for DNS propagation", delay);
+            tokio::time::sleep(delay).await;
         }
 
-        // Check if the challenge was created successfully
-        if let ChallengeStatus::Failed { error } = &challenge_response.status {
-            return Err(anyhow::anyhow!("Challenge creation failed: {}", error));
-        }
-
-        // Wait for DNS propagation
-        info!(
-            "Waiting for DNS propagation for challenge ID: {}",
-            challenge_response.id
-        );
-        tls_forward_client
-            .wait_for_dns_propagation(
-                &challenge_response.id,
-                Duration::from_secs(300), // 5 minute timeout
-                Duration::from_secs(5),   // 5 second check interval
-            )
-            .await
-            .inspect_err(|_e| {
-                // Try to clean up the challenge on error
-                let challenge_id = challenge_response.id.clone();
-                let tls_forward_client = tls_forward_client.clone();
-                tokio::spawn(async move {
-                    if let Err(cleanup_err) =
-                        tls_forward_client.delete_challenge(&challenge_id).await
-                    {
-                        warn!(
-                            "Failed to clean up challenge {} after error: {}",
-                            challenge_id, cleanup_err
-                        );
-                    }
-                });
-            })?;
-
         // Notify ACME server that challenge is ready
         info!("Notifying ACME server that challenge is ready");
-        challenge.set_ready().await?;
-
-        // Wait for challenge validation
-        let mut attempts = 0;
-        let max_attempts = 30;
-
-        loop {
-            tokio::time::sleep(std::time::Duration::from_secs(2)).await;
-
-            order.refresh().await?;
-            match order.state().status {
-                OrderStatus::Ready => {
-                    info!("Order is ready for finalization");
-                    break;
-                }
-                OrderStatus::Invalid => {
-                    error!("Order validation failed. Order state: {:?}", order.state());
-                    // Try to get more details about the failure
-                    let mut authz_iter = order.authorizations();
-                    let mut idx = 0;
-                    while let Some(Ok(authz)) = authz_iter.next().await {
-                        error!("Authorization {}: status={:?}", idx, authz.status);
-                        for chall in &authz.challenges {
-                            error!(
-                                "  Challenge type={:?} status={:?} error={:?}",
-                                chall.r#type, chall.status, chall.error
-                            );
-                        }
-                        idx += 1;
-                    }
-                    // Clean up DNS record
-                    tls_forward_client
-                        .delete_challenge(&challenge_response.id)
-                        .await
-                        .ok();
-                    return Err(anyhow::anyhow!("Order validation failed"));
-                }
-                OrderStatus::Valid => {
-                    info!("Order already valid");
-                    break;
-                }
-                _ => {
-                    debug!("Order status: {:?}", order.state().status);
-                }
-            }
-
-            attempts += 1;
-            if attempts >= max_attempts {
-                // Clean up DNS record
-                tls_forward_client
-                    .delete_challenge(&challenge_response.id)
-                    .await
-                    .ok();
-                return Err(anyhow::anyhow!("Challenge validation timeout"));
-            }
-        }
+        let validated = match challenge.set_ready().await {
+            Ok(()) => wait_for_validation(&mut order).await,
+            Err(e) => Err(e.into()),
+        };
 
-        // Clean up DNS record
+        // Clean up DNS record whether or not validation succeeded
         info!("Cleaning up DNS challenge");
-        tls_forward_client
-            .delete_challenge(&challenge_response.id)
-            .await?;
+        if let Err(e) = dns.delete_txt(&record).await {
+            warn!("Failed to clean up challenge record {}: {}", record.name, e);
+        }
+        validated?;
 
         // Finalize order - instant-acme generates the key for us
         let private_key_pem = order.finalize().await?;
@@ -256,7 +184,7 @@ impl CertificateManager {
         };
 
         // Save certificate and key
-        let cert_dir = self.cert_path.join(domain);
+        let cert_dir = self.cert_path.join(certificate_dir_name(domain));
         tokio::fs::create_dir_all(&cert_dir).await?;
 
         let cert_path = cert_dir.join("fullchain.pem");
@@ -274,13 +202,16 @@ impl CertificateManager {
 
     /// Check if a certificate exists for a domain
     pub async fn has_certificate(&self, domain: &str) -> bool {
-        let cert_path = self.cert_path.join(domain).join("fullchain.pem");
+        let cert_path = self
+            .cert_path
+            .join(certificate_dir_name(domain))
+            .join("fullchain.pem");
         cert_path.exists()
     }
 
     /// Get certificate paths for a domain
     pub fn get_certificate_paths(&self, domain: &str) -> Option<CertificatePaths> {
-        let cert_dir = self.cert_path.join(domain);
+        let cert_dir = self.cert_path.join(certificate_dir_name(domain));
         let cert_path = cert_dir.join("fullchain.pem");
         let key_path = cert_dir.join("key.pem");
 
@@ -366,6 +297,63 @@ impl CertificateManager {
     }
 }
 
+/// Wait for the CA to validate an order's challenges
+async fn wait_for_validation(order: &mut Order) -> Result<()> {
+    let mut attempts = 0;
+    let max_attempts = 30;
+
+    loop {
+        tokio::time::sleep(Duration::from_secs(2)).await;
+
+        order.refresh().await?;
+        match order.state().status {
+            OrderStatus::Ready => {
+                info!("Order is ready for finalization");
+                return Ok(());
+            }
+            OrderStatus::Invalid => {
+                error!("Order validation failed. Order state: {:?}", order.state());
+                // Try to get more details about the failure
+                let mut authz_iter = order.authorizations();
+                let mut idx = 0;
+                while let Some(Ok(authz)) = authz_iter.next().await {
+                    error!("Authorization {}: status={:?}", idx, authz.status);
+                    for chall in &authz.challenges {
+                        error!(
+                            "  Challenge type={:?} status={:?} error={:?}",
+                            chall.r#type, chall.status, chall.error
+                        );
+                    }
+                    idx += 1;
+                }
+                return Err(anyhow::anyhow!("Order validation failed"));
+            }
+            OrderStatus::Valid => {
+                info!("Order already valid");
+                return Ok(());
+            }
+            _ => {
+                debug!("Order status: {:?}", order.state().status);
+            }
+        }
+
+        attempts += 1;
+        if attempts >= max_attempts {
+            return Err(anyhow::anyhow!("Challenge validation timeout"));
+        }
+    }
+}
+
+/// Directory a domain's certificate is stored in under the certificate
+/// path; `*` is not a portable file name, so `*.example.com` is kept as
+/// `_wildcard.example.com`
+pub fn certificate_dir_name(domain: &str) -> String {
+    match domain.strip_prefix("*.") {
+        Some(base) => format!("_wildcard.{base}"),
+        None => domain.to_string(),
+    }
+}
+
 /// Paths to certificate files
 #[derive(Debug, Clone)]
 pub struct CertificatePaths {
@@ -407,5 +395,12 @@ mod tests {
         assert!(paths.cert.exists());
         assert!(paths.key.exists());
         assert!(paths.chain.is_none());
+
+        // Wildcards are stored under a portable name
+        assert_eq!(
+            certificate_dir_name("*.example.com"),
+            "_wildcard.example.com"
+        );
+        assert!(manager.get_certificate_paths("*.example.com").is_none());
     }
 }
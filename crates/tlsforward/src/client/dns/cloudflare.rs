@@ -0,0 +1,122 @@
+//! Cloudflare DNS through its v4 API
+
+use super::{DnsProvider, TxtRecord};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::json;
+use std::time::Duration;
+
+const API_BASE: &str = "https://api.cloudflare.com/client/v4";
+
+/// TTL of challenge records; the lowest Cloudflare allows
+const RECORD_TTL: u32 = 60;
+
+#[derive(Deserialize)]
+struct ApiResponse<T> {
+    success: bool,
+    #[serde(default)]
+    errors: Vec<ApiError>,
+    result: Option<T>,
+}
+
+#[derive(Deserialize)]
+struct ApiError {
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct CreatedRecord {
+    id: String,
+}
+
+impl<T> ApiResponse<T> {
+    fn into_result(self) -> Result<Option<T>> {
+        if self.success {
+            return Ok(self.result);
+        }
+        let messages: Vec<String> = self.errors.into_iter().map(|e| e.message).collect();
+        anyhow::bail!("Cloudflare API error: {}", messages.join("; "))
+    }
+}
+
+/// Writes challenge records to a Cloudflare zone with an API token
+/// allowed to edit its DNS
+pub struct CloudflareDns {
+    client: reqwest::Client,
+    api_token: String,
+    zone_id: String,
+    propagation_delay: Duration,
+}
+
+impl CloudflareDns {
+    pub fn new(api_token: String, zone_id: String, propagation_delay: Duration) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            api_token,
+            zone_id,
+            propagation_delay,
+        }
+    }
+
+    fn records_url(&self) -> String {
+        format!("{API_BASE}/zones/{}/dns_records", self.zone_id)
+    }
+}
+
+#[async_trait]
+impl DnsProvider for CloudflareDns {
+    fn name(&self) -> &'static str {
+        "cloudflare"
+    }
+
+    async fn create_txt(&self, name: &str, value: &str) -> Result<TxtRecord> {
+        let response: ApiResponse<CreatedRecord> = self
+            .client
+            .post(self.records_url())
+            .bearer_auth(&self.api_token)
+            .json(&json!({
+                "type": "TXT",
+                "name": name,
+                "content": value,
+                "ttl": RECORD_TTL,
+            }))
+            .send()
+            .await
+            .context("Failed to reach Cloudflare")?
+            .json()
+            .await
+            .context("Unexpected response from Cloudflare")?;
+        let created = response
+            .into_result()?
+            .context("Cloudflare returned no record")?;
+        Ok(TxtRecord {
+            name: name.to_string(),
+            value: value.to_string(),
+            id: Some(created.id),
+        })
+    }
+
+    async fn delete_txt(&self, record: &TxtRecord) -> Result<()> {
+        let id = record
+            .id
+            .as_deref()
+            .context("Cloudflare record has no id")?;
+        let response: ApiResponse<serde_json::Value> = self
+            .client
+            .delete(format!("{}/{id}", self.records_url()))
+            .bearer_auth(&self.api_token)
+            .send()
+            .await
+            .context("Failed to reach Cloudflare")?
+            .json()
+            .await
+            .context("Unexpected response from Cloudflare")?;
+        response.into_result()?;
+        Ok(())
+    }
+
+    fn propagation_delay(&self) -> Duration {
+        self.propagation_delay
+    }
+}
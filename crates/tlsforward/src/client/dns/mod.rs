@@ -0,0 +1,144 @@
+//! DNS providers for ACME DNS-01 challenges
+//!
+//! A DNS-01 challenge is answered by publishing a TXT record at
+//! `_acme-challenge.<domain>`. The relay publishes it for domains under its
+//! own zone; for custom domains the record is written directly to the
+//! domain's DNS through one of the providers here, which works behind NAT
+//! without the relay and is the only way to obtain wildcard certificates.
+
+mod cloudflare;
+mod rfc2136;
+mod route53;
+
+pub use cloudflare::CloudflareDns;
+pub use rfc2136::{Rfc2136Dns, TsigKey};
+pub use route53::{Route53Credentials, Route53Dns};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use std::time::Duration;
+
+/// Label the challenge record is published under
+pub const ACME_CHALLENGE_LABEL: &str = "_acme-challenge";
+
+/// How long the relay is given to see its challenge record resolve
+const RELAY_PROPAGATION_TIMEOUT: Duration = Duration::from_secs(300);
+const RELAY_PROPAGATION_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Name of the challenge record for `domain`; a wildcard is validated at
+/// the domain it covers
+pub fn challenge_name(domain: &str) -> String {
+    let domain = domain.strip_prefix("*.").unwrap_or(domain);
+    format!("{ACME_CHALLENGE_LABEL}.{}", domain.trim_end_matches('.'))
+}
+
+/// A published TXT record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxtRecord {
+    /// Fully qualified name, without a trailing dot
+    pub name: String,
+    pub value: String,
+    /// Provider's id for the record, where it assigns one
+    pub id: Option<String>,
+}
+
+/// Publishes and removes challenge TXT records
+#[async_trait]
+pub trait DnsProvider: Send + Sync {
+    /// Provider name, for logs
+    fn name(&self) -> &'static str;
+
+    /// Publish a TXT record
+    async fn create_txt(&self, name: &str, value: &str) -> Result<TxtRecord>;
+
+    /// Remove a record published by [`DnsProvider::create_txt`]
+    async fn delete_txt(&self, record: &TxtRecord) -> Result<()>;
+
+    /// Wait after publishing before the CA is asked to look
+    fn propagation_delay(&self) -> Duration {
+        Duration::ZERO
+    }
+}
+
+/// The relay answers challenges for domains in its zone, and waits until
+/// the record resolves before returning it
+#[async_trait]
+impl DnsProvider for super::TlsForwardClient {
+    fn name(&self) -> &'static str {
+        "relay"
+    }
+
+    async fn create_txt(&self, name: &str, value: &str) -> Result<TxtRecord> {
+        let domain = name
+            .strip_prefix(ACME_CHALLENGE_LABEL)
+            .and_then(|rest| rest.strip_prefix('.'))
+            .with_context(|| format!("{name} is not a challenge record"))?;
+        let response = self
+            .create_challenge(
+                domain.to_string(),
+                ACME_CHALLENGE_LABEL.to_string(),
+                value.to_string(),
+            )
+            .await
+            .context("Failed to create challenge with TLS forward server")?;
+        if response.id.is_empty() {
+            anyhow::bail!("Received empty challenge ID from TLS forward server");
+        }
+        if let crate::common::ChallengeStatus::Failed { error } = &response.status {
+            anyhow::bail!("Challenge creation failed: {}", error);
+        }
+
+        info!(
+            "Waiting for DNS propagation for challenge ID: {}",
+            response.id
+        );
+        if let Err(e) = self
+            .wait_for_dns_propagation(
+                &response.id,
+                RELAY_PROPAGATION_TIMEOUT,
+                RELAY_PROPAGATION_INTERVAL,
+            )
+            .await
+        {
+            if let Err(cleanup_err) = self.delete_challenge(&response.id).await {
+                warn!(
+                    "Failed to clean up challenge {} after error: {}",
+                    response.id, cleanup_err
+                );
+            }
+            return Err(e.into());
+        }
+        Ok(TxtRecord {
+            name: name.to_string(),
+            value: value.to_string(),
+            id: Some(response.id),
+        })
+    }
+
+    async fn delete_txt(&self, record: &TxtRecord) -> Result<()> {
+        let id = record.id.as_deref().context("Relay challenge has no id")?;
+        self.delete_challenge(id).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_challenge_name_covers_wildcards() {
+        assert_eq!(
+            challenge_name("gate.example.com"),
+            "_acme-challenge.gate.example.com"
+        );
+        assert_eq!(
+            challenge_name("*.example.com"),
+            "_acme-challenge.example.com"
+        );
+        assert_eq!(
+            challenge_name("example.com."),
+            "_acme-challenge.example.com"
+        );
+    }
+}
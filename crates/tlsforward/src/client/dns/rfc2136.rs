@@ -0,0 +1,260 @@
+//! Dynamic DNS updates (RFC 2136) authenticated with TSIG (RFC 8945)
+//!
+//! Updates are sent over UDP to the zone's primary server and signed with
+//! HMAC-SHA256, which BIND, Knot and PowerDNS all accept.
+
+use super::{DnsProvider, TxtRecord};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::net::UdpSocket;
+
+const OPCODE_UPDATE: u16 = 5 << 11;
+const TYPE_SOA: u16 = 6;
+const TYPE_TXT: u16 = 16;
+const TYPE_TSIG: u16 = 250;
+const CLASS_IN: u16 = 1;
+const CLASS_NONE: u16 = 254;
+const CLASS_ANY: u16 = 255;
+const TSIG_ALGORITHM: &str = "hmac-sha256.";
+const TSIG_FUDGE: u16 = 300;
+const RECORD_TTL: u32 = 60;
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Shared secret the server knows the key by
+#[derive(Clone)]
+pub struct TsigKey {
+    pub name: String,
+    pub secret: Vec<u8>,
+}
+
+impl std::fmt::Debug for TsigKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TsigKey")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Writes challenge records to a zone through its primary server
+pub struct Rfc2136Dns {
+    server: String,
+    zone: String,
+    key: TsigKey,
+    propagation_delay: Duration,
+}
+
+/// Append `name` in wire format
+fn push_name(buf: &mut Vec<u8>, name: &str) -> Result<()> {
+    for label in name
+        .trim_end_matches('.')
+        .split('.')
+        .filter(|l| !l.is_empty())
+    {
+        anyhow::ensure!(label.len() <= 63, "DNS label too long: {label}");
+        buf.push(label.len() as u8);
+        buf.extend_from_slice(label.as_bytes());
+    }
+    buf.push(0);
+    Ok(())
+}
+
+/// TXT data as character strings of at most 255 bytes
+fn txt_rdata(value: &str) -> Vec<u8> {
+    let mut rdata = Vec::new();
+    for chunk in value.as_bytes().chunks(255) {
+        rdata.push(chunk.len() as u8);
+        rdata.extend_from_slice(chunk);
+    }
+    rdata
+}
+
+fn rcode_name(rcode: u16) -> &'static str {
+    match rcode {
+        1 => "FORMERR",
+        2 => "SERVFAIL",
+        3 => "NXDOMAIN",
+        4 => "NOTIMP",
+        5 => "REFUSED",
+        8 => "NXRRSET",
+        9 => "NOTAUTH",
+        10 => "NOTZONE",
+        _ => "error",
+    }
+}
+
+impl Rfc2136Dns {
+    /// Updates for `zone` sent to `server` (`host:port`)
+    pub fn new(server: String, zone: String, key: TsigKey, propagation_delay: Duration) -> Self {
+        Self {
+            server,
+            zone,
+            key,
+            propagation_delay,
+        }
+    }
+
+    /// An unsigned UPDATE message adding (or, with `delete`, removing) one
+    /// TXT record
+    fn update_message(&self, id: u16, name: &str, value: &str, delete: bool) -> Result<Vec<u8>> {
+        let mut msg = Vec::with_capacity(512);
+        msg.extend_from_slice(&id.to_be_bytes());
+        msg.extend_from_slice(&OPCODE_UPDATE.to_be_bytes());
+        // ZOCOUNT, PRCOUNT, UPCOUNT, ADCOUNT
+        for count in [1u16, 0, 1, 0] {
+            msg.extend_from_slice(&count.to_be_bytes());
+        }
+
+        push_name(&mut msg, &self.zone)?;
+        msg.extend_from_slice(&TYPE_SOA.to_be_bytes());
+        msg.extend_from_slice(&CLASS_IN.to_be_bytes());
+
+        let (class, ttl) = if delete {
+            (CLASS_NONE, 0)
+        } else {
+            (CLASS_IN, RECORD_TTL)
+        };
+        let rdata = txt_rdata(value);
+        push_name(&mut msg, name)?;
+        msg.extend_from_slice(&TYPE_TXT.to_be_bytes());
+        msg.extend_from_slice(&class.to_be_bytes());
+        msg.extend_from_slice(&ttl.to_be_bytes());
+        msg.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+        msg.extend_from_slice(&rdata);
+        Ok(msg)
+    }
+
+    /// Append a TSIG record signing `msg`, signed at `time` (Unix seconds)
+    fn sign(&self, msg: &mut Vec<u8>, time: u64) -> Result<()> {
+        let id = [msg[0], msg[1]];
+        let key_name = self.key.name.to_ascii_lowercase();
+        let time = &time.to_be_bytes()[2..];
+
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.key.secret).context("Invalid TSIG secret")?;
+        mac.update(msg);
+        let mut variables = Vec::new();
+        push_name(&mut variables, &key_name)?;
+        variables.extend_from_slice(&CLASS_ANY.to_be_bytes());
+        variables.extend_from_slice(&0u32.to_be_bytes());
+        push_name(&mut variables, TSIG_ALGORITHM)?;
+        variables.extend_from_slice(time);
+        variables.extend_from_slice(&TSIG_FUDGE.to_be_bytes());
+        // Error and other length
+        variables.extend_from_slice(&[0, 0, 0, 0]);
+        mac.update(&variables);
+        let digest = mac.finalize().into_bytes();
+
+        let mut rdata = Vec::new();
+        push_name(&mut rdata, TSIG_ALGORITHM)?;
+        rdata.extend_from_slice(time);
+        rdata.extend_from_slice(&TSIG_FUDGE.to_be_bytes());
+        rdata.extend_from_slice(&(digest.len() as u16).to_be_bytes());
+        rdata.extend_from_slice(&digest);
+        rdata.extend_from_slice(&id);
+        rdata.extend_from_slice(&[0, 0, 0, 0]);
+
+        push_name(msg, &key_name)?;
+        msg.extend_from_slice(&TYPE_TSIG.to_be_bytes());
+        msg.extend_from_slice(&CLASS_ANY.to_be_bytes());
+        msg.extend_from_slice(&0u32.to_be_bytes());
+        msg.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+        msg.extend_from_slice(&rdata);
+        // ADCOUNT now includes the TSIG record
+        msg[10..12].copy_from_slice(&1u16.to_be_bytes());
+        Ok(())
+    }
+
+    async fn update(&self, name: &str, value: &str, delete: bool) -> Result<()> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?;
+        let id = now.subsec_nanos() as u16;
+        let mut msg = self.update_message(id, name, value, delete)?;
+        self.sign(&mut msg, now.as_secs())?;
+
+        let socket = UdpSocket::bind("0.0.0.0:0").await?;
+        socket
+            .connect(&self.server)
+            .await
+            .with_context(|| format!("Failed to reach DNS server {}", self.server))?;
+        socket.send(&msg).await?;
+
+        let mut reply = [0u8; 512];
+        let len = tokio::time::timeout(RESPONSE_TIMEOUT, socket.recv(&mut reply))
+            .await
+            .with_context(|| format!("DNS server {} did not answer", self.server))??;
+        anyhow::ensure!(
+            len >= 12 && reply[..2] == id.to_be_bytes(),
+            "Unexpected reply from DNS server {}",
+            self.server
+        );
+        let rcode = u16::from_be_bytes([reply[2], reply[3]]) & 0x000f;
+        if rcode != 0 {
+            anyhow::bail!(
+                "DNS server {} refused the update: {} ({rcode})",
+                self.server,
+                rcode_name(rcode)
+            );
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl DnsProvider for Rfc2136Dns {
+    fn name(&self) -> &'static str {
+        "rfc2136"
+    }
+
+    async fn create_txt(&self, name: &str, value: &str) -> Result<TxtRecord> {
+        self.update(name, value, false).await?;
+        Ok(TxtRecord {
+            name: name.to_string(),
+            value: value.to_string(),
+            id: None,
+        })
+    }
+
+    async fn delete_txt(&self, record: &TxtRecord) -> Result<()> {
+        self.update(&record.name, &record.value, true).await
+    }
+
+    fn propagation_delay(&self) -> Duration {
+        self.propagation_delay
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signed_update_layout() {
+        let dns = Rfc2136Dns::new(
+            "127.0.0.1:53".to_string(),
+            "example.com".to_string(),
+            TsigKey {
+                name: "Acme".to_string(),
+                secret: b"secret".to_vec(),
+            },
+            Duration::ZERO,
+        );
+        let mut msg = dns
+            .update_message(0x1234, "_acme-challenge.example.com", "token", false)
+            .unwrap();
+        let unsigned = msg.len();
+        dns.sign(&mut msg, 1_700_000_000).unwrap();
+
+        assert_eq!(&msg[..4], &[0x12, 0x34, 0x28, 0x00]);
+        // One zone, one update, one TSIG record
+        assert_eq!(&msg[4..12], &[0, 1, 0, 0, 0, 1, 0, 1]);
+        assert_eq!(&msg[12..25], b"\x07example\x03com\x00");
+        assert!(msg[unsigned..].starts_with(b"\x04acme\x00\x00\xfa\x00\xff"));
+
+        let mut name = Vec::new();
+        push_name(&mut name, "a.b.").unwrap();
+        assert_eq!(name, b"\x01a\x01b\x00");
+        assert_eq!(txt_rdata(&"x".repeat(300))[..1], [255]);
+    }
+}
@@ -0,0 +1,193 @@
+//! Amazon Route 53 through its REST API, signed with Signature Version 4
+
+use super::{DnsProvider, TxtRecord};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+
+const HOST: &str = "route53.amazonaws.com";
+const API_VERSION: &str = "2013-04-01";
+/// Route 53 is global and signed for this region
+const REGION: &str = "us-east-1";
+const SERVICE: &str = "route53";
+const CONTENT_TYPE: &str = "application/xml";
+const RECORD_TTL: u32 = 60;
+
+/// AWS credentials allowed `route53:ChangeResourceRecordSets` on the zone
+#[derive(Clone)]
+pub struct Route53Credentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    /// Set for temporary credentials
+    pub session_token: Option<String>,
+}
+
+/// Writes challenge records to a Route 53 hosted zone
+pub struct Route53Dns {
+    client: reqwest::Client,
+    credentials: Route53Credentials,
+    hosted_zone_id: String,
+    propagation_delay: Duration,
+}
+
+fn hmac(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Key requests made on `date` (`YYYYMMDD`) are signed with
+fn signing_key(secret: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let key = hmac(format!("AWS4{secret}").as_bytes(), date);
+    let key = hmac(&key, region);
+    let key = hmac(&key, service);
+    hmac(&key, "aws4_request")
+}
+
+/// Minimal XML escaping for values placed in element text
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+impl Route53Dns {
+    pub fn new(
+        credentials: Route53Credentials,
+        hosted_zone_id: &str,
+        propagation_delay: Duration,
+    ) -> Self {
+        // Accept ids as the console shows them and as the API returns them
+        let hosted_zone_id = hosted_zone_id
+            .trim_start_matches("/hostedzone/")
+            .to_string();
+        Self {
+            client: reqwest::Client::new(),
+            credentials,
+            hosted_zone_id,
+            propagation_delay,
+        }
+    }
+
+    /// `Authorization` header for a POST of `body` to `path` at `now`
+    fn authorization(&self, path: &str, body: &str, now: DateTime<Utc>) -> (String, String) {
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+
+        let mut headers = vec![
+            ("content-type", CONTENT_TYPE.to_string()),
+            ("host", HOST.to_string()),
+            ("x-amz-date", amz_date.clone()),
+        ];
+        if let Some(token) = &self.credentials.session_token {
+            headers.push(("x-amz-security-token", token.clone()));
+        }
+        let canonical_headers: String = headers
+            .iter()
+            .map(|(name, value)| format!("{name}:{value}\n"))
+            .collect();
+        let signed_headers = headers
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join(";");
+        let canonical_request = format!(
+            "POST\n{path}\n\n{canonical_headers}\n{signed_headers}\n{}",
+            hex::encode(Sha256::digest(body.as_bytes()))
+        );
+
+        let scope = format!("{date}/{REGION}/{SERVICE}/aws4_request");
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+        let key = signing_key(&self.credentials.secret_access_key, &date, REGION, SERVICE);
+        let signature = hex::encode(hmac(&key, &string_to_sign));
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+            self.credentials.access_key_id
+        );
+        (authorization, amz_date)
+    }
+
+    /// Apply one change to the record set `name`
+    async fn change(&self, action: &str, name: &str, value: &str) -> Result<()> {
+        let path = format!("/{API_VERSION}/hostedzone/{}/rrset/", self.hosted_zone_id);
+        let body = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<ChangeResourceRecordSetsRequest xmlns="https://route53.amazonaws.com/doc/{API_VERSION}/"><ChangeBatch><Changes><Change><Action>{action}</Action><ResourceRecordSet><Name>{}.</Name><Type>TXT</Type><TTL>{RECORD_TTL}</TTL><ResourceRecords><ResourceRecord><Value>"{}"</Value></ResourceRecord></ResourceRecords></ResourceRecordSet></Change></Changes></ChangeBatch></ChangeResourceRecordSetsRequest>"#,
+            escape_xml(name),
+            escape_xml(value)
+        );
+        let (authorization, amz_date) = self.authorization(&path, &body, Utc::now());
+
+        let mut request = self
+            .client
+            .post(format!("https://{HOST}{path}"))
+            .header("content-type", CONTENT_TYPE)
+            .header("x-amz-date", amz_date)
+            .header("authorization", authorization);
+        if let Some(token) = &self.credentials.session_token {
+            request = request.header("x-amz-security-token", token);
+        }
+        let response = request
+            .body(body)
+            .send()
+            .await
+            .context("Failed to reach Route 53")?;
+        let status = response.status();
+        if !status.is_success() {
+            let detail = response.text().await.unwrap_or_default();
+            anyhow::bail!("Route 53 refused the change ({status}): {detail}");
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl DnsProvider for Route53Dns {
+    fn name(&self) -> &'static str {
+        "route53"
+    }
+
+    async fn create_txt(&self, name: &str, value: &str) -> Result<TxtRecord> {
+        self.change("UPSERT", name, value).await?;
+        Ok(TxtRecord {
+            name: name.to_string(),
+            value: value.to_string(),
+            id: None,
+        })
+    }
+
+    async fn delete_txt(&self, record: &TxtRecord) -> Result<()> {
+        self.change("DELETE", &record.name, &record.value).await
+    }
+
+    fn propagation_delay(&self) -> Duration {
+        self.propagation_delay
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signing_key_matches_aws_example() {
+        // From the AWS Signature Version 4 documentation
+        let key = signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20150830",
+            "us-east-1",
+            "iam",
+        );
+        assert_eq!(
+            hex::encode(key),
+            "c4afb1cc5771d871763a393e44b703571b55cc28424d1a5e86da6ed3c154a4b9"
+        );
+    }
+}
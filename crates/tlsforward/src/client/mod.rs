@@ -1,9 +1,12 @@
 //! TLS forward client functionality for daemons
 
+pub mod bandwidth;
 mod certificate_manager;
+pub mod dns;
 mod tls_forward_client;
 mod tls_forward_handler;
 
-pub use certificate_manager::CertificateManager;
+pub use bandwidth::{BandwidthLimits, ForwardedConnection, TrafficMeter, TrafficSnapshot};
+pub use certificate_manager::{CertificateManager, certificate_dir_name};
 pub use tls_forward_client::TlsForwardClient;
 pub use tls_forward_handler::{TlsAcceptorProvider, TlsForwardHandler};
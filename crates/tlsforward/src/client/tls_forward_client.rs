@@ -36,7 +36,9 @@ impl TlsForwardClient {
     pub async fn register(&self) -> Result<(String, TlsForwardInfo)> {
         info!("Registering with TLS forward server at {}", self.forwarder);
 
-        let request = RegistrationRequest {};
+        let request = RegistrationRequest {
+            client_address: true,
+        };
         let response: RegistrationResponse = self
             .request(Method::POST, "/register", Some(&request))
             .await?;
@@ -1,4 +1,6 @@
 //! TLS forwarding handler for receiving traffic from relay
+use crate::client::bandwidth::TrafficMeter;
+use crate::common::proxy_header::{self, Prefixed};
 use gate_core::tracing::metrics::{counter, gauge, histogram};
 use gate_http::server::HttpServer;
 use gate_p2p::stream::CombinedStream;
@@ -36,6 +38,7 @@ pub struct TlsForwardHandler<P> {
     connection_timeout: Duration,
     connections_semaphore: Arc<Semaphore>,
     max_connections: usize,
+    traffic: TrafficMeter,
 }
 
 impl<P: TlsAcceptorProvider> TlsForwardHandler<P> {
@@ -52,9 +55,16 @@ impl<P: TlsAcceptorProvider> TlsForwardHandler<P> {
             connection_timeout: Duration::from_secs(connection_timeout_secs),
             connections_semaphore: Arc::new(Semaphore::new(max_connections)),
             max_connections,
+            traffic: TrafficMeter::default(),
         }
     }
 
+    /// Count forwarded traffic, and cap its bandwidth, with `traffic`
+    pub fn with_traffic_meter(mut self, traffic: TrafficMeter) -> Self {
+        self.traffic = traffic;
+        self
+    }
+
     /// Handle a forwarded TLS connection
     #[instrument(
         name = "p2p.tls_connection",
@@ -103,8 +113,21 @@ impl<P: TlsAcceptorProvider> TlsForwardHandler<P> {
             active_connections, self.max_connections
         );
 
-        // Combine streams
-        let stream = CombinedStream::new(recv, send);
+        // Read the client's address if the relay sent it
+        let mut stream = CombinedStream::new(recv, send);
+        let (client, rest) = timeout(
+            self.connection_timeout,
+            proxy_header::read_client_address(&mut stream),
+        )
+        .await
+        .map_err(|_| anyhow::anyhow!("Client address read timeout"))??;
+
+        // Count and cap the connection's traffic
+        let stream = Prefixed::new(rest, stream);
+        let stream = match client {
+            Some(client) => self.traffic.meter(stream, client),
+            None => self.traffic.meter(stream, node_id),
+        };
 
         // Get current TLS acceptor
         let tls_acceptor = self.tls_acceptor_provider.get_acceptor().await;
@@ -116,13 +139,15 @@ impl<P: TlsAcceptorProvider> TlsForwardHandler<P> {
             .map_err(|e| anyhow::anyhow!("TLS handshake failed: {}", e))?;
 
         debug!("TLS handshake completed successfully");
+        let (metered, tls_connection) = tls_stream.get_ref();
+        metered.set_host(tls_connection.server_name().unwrap_or("unknown"));
 
         // Forward to HTTP server with P2P info with a timeout to prevent hanging
         let http_timeout = Duration::from_secs(300); // 5 minutes for HTTP handling
         let result = timeout(
             http_timeout,
             self.http_server
-                .handle_p2p_stream(tls_stream, node_id, None),
+                .handle_p2p_stream(tls_stream, node_id, None, client),
         )
         .await
         .map_err(|_| {
@@ -1,5 +1,6 @@
 //! Common types and utilities shared between client and server
 pub mod error;
+pub mod proxy_header;
 pub mod types;
 
 #[cfg(test)]
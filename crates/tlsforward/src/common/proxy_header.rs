@@ -0,0 +1,160 @@
+//! Client address header for forwarded connections
+//!
+//! The node only sees the relay it is forwarded by. A relay asked to at
+//! registration writes a PROXY protocol v1 line - `PROXY TCP4 <client>
+//! <relay> <client port> <relay port>\r\n` - before the client's TLS bytes.
+//! A TLS record never starts with `P`, so nodes can read the header when
+//! present and pass the stream through untouched when not.
+
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, ReadBuf};
+
+/// Longest line the v1 format allows
+const MAX_HEADER_LEN: usize = 107;
+
+/// Header line announcing a connection from `client` to `local`
+pub fn encode(client: SocketAddr, local: SocketAddr) -> String {
+    let (client_ip, local_ip) = (client.ip().to_canonical(), local.ip().to_canonical());
+    let (family, client_ip, local_ip) = match (client_ip, local_ip) {
+        (IpAddr::V4(c), IpAddr::V4(l)) => ("TCP4", c.to_string(), l.to_string()),
+        (c, l) => ("TCP6", to_v6(c).to_string(), to_v6(l).to_string()),
+    };
+    format!(
+        "PROXY {family} {client_ip} {local_ip} {} {}\r\n",
+        client.port(),
+        local.port()
+    )
+}
+
+fn to_v6(ip: IpAddr) -> std::net::Ipv6Addr {
+    match ip {
+        IpAddr::V4(ip) => ip.to_ipv6_mapped(),
+        IpAddr::V6(ip) => ip,
+    }
+}
+
+/// Client address in a header line; `None` for `PROXY UNKNOWN` or a
+/// malformed line
+pub fn parse(line: &str) -> Option<SocketAddr> {
+    let mut parts = line.trim_end_matches("\r\n").split(' ');
+    if parts.next()? != "PROXY" || !matches!(parts.next()?, "TCP4" | "TCP6") {
+        return None;
+    }
+    let ip: IpAddr = parts.next()?.parse().ok()?;
+    let _local = parts.next()?;
+    let port: u16 = parts.next()?.parse().ok()?;
+    Some(SocketAddr::new(ip.to_canonical(), port))
+}
+
+/// Read a header off the start of `stream` if there is one, returning the
+/// client address and any bytes read past it
+pub async fn read_client_address<R: AsyncRead + Unpin>(
+    stream: &mut R,
+) -> io::Result<(Option<SocketAddr>, Vec<u8>)> {
+    let first = stream.read_u8().await?;
+    if first != b'P' {
+        return Ok((None, vec![first]));
+    }
+    let mut line = vec![first];
+    while !line.ends_with(b"\r\n") {
+        if line.len() == MAX_HEADER_LEN {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Client address header too long",
+            ));
+        }
+        line.push(stream.read_u8().await?);
+    }
+    let line = String::from_utf8(line)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid client address header"))?;
+    Ok((parse(&line), Vec::new()))
+}
+
+/// Stream that yields `prefix` before reading from the inner stream
+pub struct Prefixed<S> {
+    prefix: Vec<u8>,
+    offset: usize,
+    inner: S,
+}
+
+impl<S> Prefixed<S> {
+    pub fn new(prefix: Vec<u8>, inner: S) -> Self {
+        Self {
+            prefix,
+            offset: 0,
+            inner,
+        }
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for Prefixed<S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        if self.offset < self.prefix.len() {
+            let remaining = &self.prefix[self.offset..];
+            let n = remaining.len().min(buf.remaining());
+            buf.put_slice(&remaining[..n]);
+            self.offset += n;
+            return Poll::Ready(Ok(()));
+        }
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for Prefixed<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_header_round_trip() {
+        let client: SocketAddr = "203.0.113.7:51234".parse().unwrap();
+        let local: SocketAddr = "[::ffff:198.51.100.1]:443".parse().unwrap();
+        let header = encode(client, local);
+        assert_eq!(header, "PROXY TCP4 203.0.113.7 198.51.100.1 51234 443\r\n");
+
+        let mut stream = [header.as_bytes(), b"\x16\x03\x01"].concat();
+        let mut reader = stream.as_slice();
+        let (address, rest) = read_client_address(&mut reader).await.unwrap();
+        assert_eq!(address, Some(client));
+        assert!(rest.is_empty());
+        assert_eq!(reader, b"\x16\x03\x01");
+
+        // Without a header the first byte is handed back
+        stream = b"\x16\x03\x01".to_vec();
+        let mut reader = stream.as_slice();
+        let (address, rest) = read_client_address(&mut reader).await.unwrap();
+        assert_eq!(address, None);
+        let mut prefixed = Prefixed::new(rest, reader);
+        let mut read = Vec::new();
+        prefixed.read_to_end(&mut read).await.unwrap();
+        assert_eq!(read, b"\x16\x03\x01");
+
+        let v6: SocketAddr = "[2001:db8::1]:4000".parse().unwrap();
+        assert_eq!(parse(&encode(v6, local)), Some(v6));
+        assert_eq!(parse("PROXY UNKNOWN\r\n"), None);
+    }
+}
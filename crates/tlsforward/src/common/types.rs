@@ -42,8 +42,13 @@ pub struct TlsForwardInfo {
 }
 
 /// Registration request from a gate server
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RegistrationRequest {}
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RegistrationRequest {
+    /// Prefix each forwarded connection with the client's address, see
+    /// [`crate::common::proxy_header`]
+    #[serde(default)]
+    pub client_address: bool,
+}
 
 /// Registration response from TLS forward server
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -2,6 +2,7 @@
 
 use crate::TLS_FORWARD_ALPN;
 use crate::common::error::Result;
+use crate::common::proxy_header;
 use crate::server::config::ProxyTimeouts;
 use crate::server::registry::ProxyRegistry;
 use crate::server::sni::extract_sni;
@@ -13,6 +14,7 @@ use gate_p2p::stream::{CombinedStream, StreamUtils};
 use iroh::Endpoint;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
 use tokio::net::{TcpListener, TcpStream};
 use tokio::time::timeout;
 
@@ -175,6 +177,13 @@ impl HttpsProxy {
                 })??;
         let mut p2p_stream = CombinedStream::new(recv_stream, send_stream);
 
+        if entry.client_address {
+            let local_addr = stream.local_addr()?;
+            p2p_stream
+                .write_all(proxy_header::encode(peer_addr, local_addr).as_bytes())
+                .await?;
+        }
+
         info!("Proxying {} -> {} ({})", peer_addr, hostname, entry.node_id);
 
         // Forward traffic bidirectionally with idle timeout
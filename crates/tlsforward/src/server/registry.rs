@@ -21,6 +21,9 @@ pub struct RegistryEntry {
     pub last_ping: Instant,
     /// Latest measured latency in milliseconds
     pub latency_ms: Option<u64>,
+    /// Whether the node reads a client address header on forwarded
+    /// connections
+    pub client_address: bool,
 }
 
 /// Registry for mapping short domain hashes to node information
@@ -44,7 +47,7 @@ impl ProxyRegistry {
     }
 
     /// Register a node with its address
-    pub async fn register(&self, node_id: NodeId) -> Result<String> {
+    pub async fn register(&self, node_id: NodeId, client_address: bool) -> Result<String> {
         let short_hash = node_id.fmt_short();
         let now = Instant::now();
         let entry = RegistryEntry {
@@ -53,6 +56,7 @@ impl ProxyRegistry {
             connected_at: now,
             last_ping: now,
             latency_ms: None,
+            client_address,
         };
 
         let mut entries = self.entries.write().await;
@@ -199,12 +199,16 @@ async fn delete_challenge(
 async fn handle_register(
     State(state): State<ApiState>,
     Extension(node_id): Extension<NodeId>,
-    _: Json<RegistrationRequest>,
+    Json(request): Json<RegistrationRequest>,
 ) -> impl IntoResponse {
     // Generate domain
     let domain = format!("{}.{}", node_id.fmt_short(), state.domain_suffix);
 
-    match state.registry.register(node_id).await {
+    match state
+        .registry
+        .register(node_id, request.client_address)
+        .await
+    {
         Ok(_) => {
             info!("Registered node {} with domain {}", node_id, domain);
             debug!(